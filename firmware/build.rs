@@ -0,0 +1,205 @@
+//! Build script - compiles the embedded rule pack and measures the image
+//!
+//! `rules/firmware_pack.json` (or the pack named by `ARK_RULE_PACK`) is
+//! compiled into a static decision table and decided against the
+//! interpreter on the pack's conformance corpus and on the hand-written
+//! test cases the pack ships, whose expected decisions both must meet.
+//! Any divergence, or a pack without test cases, fails the build, so an
+//! image never embeds a table that decides differently from the host
+//! engine. With `ARK_RULE_PACK_LOG` naming a transparency log requirement
+//! (JSON: log id, hex log key and the pack's proof file), a pack the log
+//! does not prove is refused before it is compiled.
+//!
+//! The script then writes the measurements of `src/image_manifest.rs`:
+//! hashes of the sources, the measurement of the generated table, the hash
+//! of the model manifest named by `ARK_MODEL_MANIFEST` (zero without one),
+//! and a fingerprint of the toolchain and flags. Nothing machine-specific is hashed - no absolute
+//! paths, no build time other than `SOURCE_DATE_EPOCH` - so two builds of
+//! the same tree with the same toolchain produce the same manifest.
+//! Embedded targets get `manifest.x`, which keeps the `.ark_manifest`
+//! section after `.rodata`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use pq_types::transparency::LogRequirement;
+
+use ethics_dsl::{compile, conformance_corpus, verify_equivalence, verify_expectations, CompiledPack, EquivalenceReport, PredicateRegistry, RulePack};
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=ARK_RULE_PACK");
+    let pack_path = std::env::var_os("ARK_RULE_PACK")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("rules/firmware_pack.json"));
+    println!("cargo:rerun-if-changed={}", pack_path.display());
+    println!("cargo:rerun-if-env-changed=ARK_RULE_PACK_LOG");
+
+    let pack = match std::env::var_os("ARK_RULE_PACK_LOG").map(PathBuf::from) {
+        Some(log_path) => {
+            println!("cargo:rerun-if-changed={}", log_path.display());
+            let log = LogRequirement::load(&log_path).unwrap_or_else(|e| panic!("Failed to load rule pack log requirement: {}", e));
+            println!("cargo:rerun-if-changed={}", log.proof.display());
+            RulePack::load_logged(&pack_path, &log)
+        }
+        None => RulePack::load(&pack_path),
+    };
+    let pack = pack.unwrap_or_else(|e| panic!("Failed to load rule pack: {}", e));
+    let compiled = compile(&pack).unwrap_or_else(|e| panic!("Rule pack cannot be embedded: {}", e));
+
+    let registry = PredicateRegistry::standard();
+    let corpus = conformance_corpus(&compiled);
+    let report = verify_equivalence(&pack, &compiled, &registry, &corpus)
+        .unwrap_or_else(|e| panic!("Conformance corpus failed to evaluate: {}", e));
+    refuse_divergences(&report, "conformance events");
+    let report = verify_expectations(&pack, &compiled, &registry)
+        .unwrap_or_else(|e| panic!("Rule pack test cases failed to evaluate: {}", e));
+    refuse_divergences(&report, "rule pack test cases");
+
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    std::fs::write(out_dir.join("rule_pack.rs"), compiled.to_rust())
+        .unwrap_or_else(|e| panic!("Failed to write rule table: {}", e));
+
+    write_image_manifest(&out_dir, &pack_path, &compiled);
+}
+
+/// Fail the build if the table and the interpreter decided any case differently
+fn refuse_divergences(report: &EquivalenceReport, cases: &str) {
+    if report.is_equivalent() {
+        return;
+    }
+    for divergence in &report.divergences {
+        println!(
+            "cargo:warning={}: interpreter {:?}, table {:?}, expected {:?}",
+            divergence.event_id, divergence.interpreted, divergence.compiled, divergence.expected
+        );
+    }
+    panic!(
+        "Embedded rule table diverges on {} of {} {}",
+        report.divergences.len(),
+        report.cases,
+        cases
+    );
+}
+
+/// Linker fragment placing the manifest section after `.rodata`
+const MANIFEST_LINKER_SCRIPT: &str =
+    "SECTIONS\n{\n  .ark_manifest : ALIGN(4)\n  {\n    KEEP(*(.ark_manifest));\n  } > REGION_RODATA\n}\nINSERT AFTER .rodata;\n";
+
+/// Write `image_manifest.rs` and `manifest.x` to `out_dir`
+fn write_image_manifest(out_dir: &Path, pack_path: &Path, compiled: &CompiledPack) {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    println!("cargo:rerun-if-env-changed=ARK_MODEL_MANIFEST");
+
+    let source_date_epoch = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse::<u64>()
+            .unwrap_or_else(|e| panic!("SOURCE_DATE_EPOCH is not a timestamp: {}", e)),
+        Err(_) => {
+            println!("cargo:warning=SOURCE_DATE_EPOCH is not set; the image manifest records 0");
+            0
+        }
+    };
+
+    let version = std::env::var("CARGO_PKG_VERSION").expect("CARGO_PKG_VERSION is set by cargo");
+    if version.len() > 16 {
+        panic!("Firmware version {} does not fit the 16-byte manifest field", version);
+    }
+    let mut firmware_version = [0u8; 16];
+    firmware_version[..version.len()].copy_from_slice(version.as_bytes());
+
+    let mut sources = vec![
+        (PathBuf::from("build.rs"), PathBuf::from("build.rs")),
+        (PathBuf::from("Cargo.toml"), PathBuf::from("Cargo.toml")),
+        (PathBuf::from("rule_pack.json"), pack_path.to_path_buf()),
+    ];
+    collect_sources(Path::new("src"), &mut sources);
+    sources.sort();
+    let mut code = blake3::Hasher::new();
+    for (name, path) in &sources {
+        let contents = std::fs::read(path).unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+        // Forward slashes so the hash does not depend on the host
+        let name = name.to_string_lossy().replace('\\', "/");
+        code.update(&(name.len() as u64).to_le_bytes());
+        code.update(name.as_bytes());
+        code.update(&(contents.len() as u64).to_le_bytes());
+        code.update(&contents);
+    }
+    code.update(MANIFEST_LINKER_SCRIPT.as_bytes());
+
+    // The model manifest is provisioned to NVRAM separately; the image only
+    // records which one it was built to run with
+    let model_manifest_hash = match std::env::var_os("ARK_MODEL_MANIFEST") {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", Path::new(&path).display());
+            let contents = std::fs::read(&path)
+                .unwrap_or_else(|e| panic!("Failed to read model manifest {}: {}", Path::new(&path).display(), e));
+            *blake3::hash(&contents).as_bytes()
+        }
+        None => [0u8; 32],
+    };
+
+    let generated = format!(
+        "pub(super) const SOURCE_DATE_EPOCH: u64 = {};\n\
+         pub(super) const FIRMWARE_VERSION: [u8; 16] = {:?};\n\
+         pub(super) const CODE_HASH: [u8; 32] = {:?};\n\
+         pub(super) const RULE_TABLE_HASH: [u8; 32] = {:?};\n\
+         pub(super) const RULE_PACK_DIGEST: [u8; 32] = {:?};\n\
+         pub(super) const TOOLCHAIN_FINGERPRINT: [u8; 32] = {:?};\n\
+         pub(super) const MODEL_MANIFEST_HASH: [u8; 32] = {:?};\n",
+        source_date_epoch,
+        firmware_version,
+        code.finalize().as_bytes(),
+        compiled.measurement(),
+        compiled.digest,
+        toolchain_fingerprint(),
+        model_manifest_hash,
+    );
+    std::fs::write(out_dir.join("image_manifest.rs"), generated)
+        .unwrap_or_else(|e| panic!("Failed to write image manifest: {}", e));
+
+    std::fs::write(out_dir.join("manifest.x"), MANIFEST_LINKER_SCRIPT)
+        .unwrap_or_else(|e| panic!("Failed to write manifest linker script: {}", e));
+    println!("cargo:rustc-link-search={}", out_dir.display());
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("none") {
+        println!("cargo:rustc-link-arg-bins=-Tmanifest.x");
+    }
+}
+
+/// Every `.rs` file under `dir`, as (relative path, path) pairs
+fn collect_sources(dir: &Path, sources: &mut Vec<(PathBuf, PathBuf)>) {
+    let entries = std::fs::read_dir(dir).unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e));
+    for entry in entries {
+        let path = entry.unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e)).path();
+        if path.is_dir() {
+            collect_sources(&path, sources);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            sources.push((path.clone(), path));
+        }
+    }
+}
+
+/// BLAKE3 of the compiler version, target, profile, flags and enabled features
+fn toolchain_fingerprint() -> [u8; 32] {
+    let rustc = std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = Command::new(&rustc)
+        .arg("-vV")
+        .output()
+        .unwrap_or_else(|e| panic!("Failed to run {}: {}", rustc.to_string_lossy(), e))
+        .stdout;
+
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .collect();
+    features.sort();
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&version);
+    for key in ["TARGET", "PROFILE", "OPT_LEVEL", "DEBUG", "CARGO_ENCODED_RUSTFLAGS", "CARGO_CFG_TARGET_FEATURE"] {
+        let value = std::env::var(key).unwrap_or_default();
+        hasher.update(format!("{}={}\n", key, value).as_bytes());
+    }
+    hasher.update(format!("features={}\n", features.join(",")).as_bytes());
+    *hasher.finalize().as_bytes()
+}
@@ -0,0 +1,112 @@
+#!/usr/bin/env python3
+"""Regenerate the ML-KEM-768 and ML-DSA-65 known-answer vectors with OpenSSL.
+
+The vectors come from an implementation independent of the pq_types
+backends (OpenSSL 3.5 or later must be on PATH) and are laid out like
+the ACVP internalProjection files, so the tests in tests/pqc_tests.rs
+read either. Run from this directory: python3 generate.py
+"""
+
+import json
+import os
+import re
+import subprocess
+import tempfile
+
+KEYS = 3
+
+
+def openssl(*args):
+    return subprocess.run(["openssl", *args], check=True, capture_output=True).stdout
+
+
+def field(text, name):
+    """Hex dump of one field of `openssl pkey -text`"""
+    match = re.search(r"^%s:\n((?:\s+[0-9a-f:]+\n)+)" % name, text, re.MULTILINE)
+    return re.sub(r"[\s:]", "", match.group(1))
+
+
+def read(path):
+    with open(path, "rb") as f:
+        return f.read()
+
+
+def flip(data, index):
+    data = bytearray(data)
+    data[index] ^= 0x01
+    return bytes(data)
+
+
+def ml_kem(work):
+    tests = []
+    groups = []
+    for key in range(KEYS):
+        private = os.path.join(work, "kem%d.pem" % key)
+        public = os.path.join(work, "kem%d.pub" % key)
+        openssl("genpkey", "-algorithm", "ML-KEM-768", "-out", private)
+        openssl("pkey", "-in", private, "-pubout", "-out", public)
+        dk = field(openssl("pkey", "-in", private, "-text", "-noout").decode(), "dk")
+
+        tests = []
+        for case in range(3):
+            ciphertext = os.path.join(work, "c")
+            secret = os.path.join(work, "k")
+            openssl("pkeyutl", "-encap", "-pubin", "-inkey", public, "-out", ciphertext, "-secret", secret)
+            c = read(ciphertext)
+            tests.append({"c": c.hex(), "k": read(secret).hex(), "reason": "valid decapsulation"})
+            if case > 0:
+                # Implicit rejection: OpenSSL's key for a modified ciphertext
+                modified = flip(c, case * 500)
+                with open(ciphertext, "wb") as f:
+                    f.write(modified)
+                openssl("pkeyutl", "-decap", "-inkey", private, "-in", ciphertext, "-secret", secret)
+                tests.append({"c": modified.hex(), "k": read(secret).hex(), "reason": "modified ciphertext"})
+        groups.append({"tgId": key + 1, "parameterSet": "ML-KEM-768", "function": "decapsulation", "dk": dk, "tests": tests})
+    return groups
+
+
+def ml_dsa(work):
+    groups = []
+    for key in range(KEYS):
+        private = os.path.join(work, "dsa%d.pem" % key)
+        openssl("genpkey", "-algorithm", "ML-DSA-65", "-out", private)
+        pk = field(openssl("pkey", "-in", private, "-text", "-noout").decode(), "pub")
+
+        tests = []
+        for length in (1, 33, 1024):
+            message = bytes((key * 31 + i) % 256 for i in range(length))
+            message_path = os.path.join(work, "m")
+            signature_path = os.path.join(work, "s")
+            with open(message_path, "wb") as f:
+                f.write(message)
+            openssl("pkeyutl", "-sign", "-rawin", "-inkey", private, "-in", message_path, "-out", signature_path)
+            signature = read(signature_path)
+            tests.append({"pk": pk, "message": message.hex(), "signature": signature.hex(), "testPassed": True, "reason": "valid signature"})
+            tests.append({"pk": pk, "message": message.hex(), "signature": flip(signature, 100 + length).hex(), "testPassed": False, "reason": "modified signature"})
+            tests.append({"pk": pk, "message": (message + b"\x00").hex(), "signature": signature.hex(), "testPassed": False, "reason": "modified message"})
+            tests.append({"pk": pk, "message": message.hex(), "signature": signature[:-1].hex(), "testPassed": False, "reason": "truncated signature"})
+        groups.append({"tgId": key + 1, "parameterSet": "ML-DSA-65", "signatureInterface": "external", "preHash": "pure", "tests": tests})
+    return groups
+
+
+def write(directory, algorithm, mode, groups):
+    test_id = 1
+    for group in groups:
+        for test in group["tests"]:
+            test["tcId"] = test_id
+            test_id += 1
+    os.makedirs(directory, exist_ok=True)
+    generator = openssl("version").decode().strip()
+    with open(os.path.join(directory, "internalProjection.json"), "w") as f:
+        json.dump({"algorithm": algorithm, "mode": mode, "generator": generator, "testGroups": groups}, f, indent=1)
+        f.write("\n")
+
+
+def main():
+    with tempfile.TemporaryDirectory() as work:
+        write("ML-KEM-encapDecap-FIPS203", "ML-KEM", "encapDecap", ml_kem(work))
+        write("ML-DSA-sigVer-FIPS204", "ML-DSA", "sigVer", ml_dsa(work))
+
+
+if __name__ == "__main__":
+    main()
@@ -6,6 +6,15 @@ use blake3::Hasher;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 use sha3::{Sha3_256, Digest};
 
+/// Marker at the start of a model manifest provisioned to NVRAM
+pub const MODEL_MANIFEST_MAGIC: [u8; 8] = *b"ARKMODEL";
+
+/// Largest model manifest the NVRAM region holds
+pub const MAX_MODEL_MANIFEST: usize = 64 * 1024;
+
+/// Magic and little-endian u32 length preceding the manifest bytes
+const MODEL_MANIFEST_HEADER: usize = 12;
+
 /// Boot verification errors
 #[derive(Debug, Clone, Copy)]
 pub enum BootError {
@@ -21,6 +30,8 @@ pub enum BootError {
     KillSwitchDetected,
     /// Unauthorized modification detected
     UnauthorizedModification,
+    /// Loaded rule table or model manifest differs from the image manifest
+    MoralArtifactMismatch,
 }
 
 /// Immutable boot sequence - stored in ROM
@@ -41,6 +52,12 @@ impl ImmutableBoot {
         // Phase 4: Moral foundation verification
         Self::verify_moral_foundation_internal()?;
         
+        // Phase 4b: Image manifest matches what this image embeds
+        Self::verify_image_manifest()?;
+        
+        // Phase 4c: Rule table and model manifest match the image manifest
+        Self::verify_moral_artifacts()?;
+        
         // Phase 5: Hardware availability check
         Self::verify_hardware_availability()?;
         
@@ -114,6 +131,45 @@ impl ImmutableBoot {
         Ok(())
     }
     
+    /// Verify the image manifest describes this image's moral foundation
+    fn verify_image_manifest() -> Result<(), BootError> {
+        let manifest = crate::image_manifest::image_manifest();
+        if !manifest.is_valid() {
+            return Err(BootError::UnauthorizedModification);
+        }
+        if !constant_time_eq::constant_time_eq(&manifest.moral_foundation_hash, &Self::get_embedded_moral_hash()) {
+            return Err(BootError::UnauthorizedModification);
+        }
+        Ok(())
+    }
+    
+    /// Verify the rule table and, if provisioned, the model manifest against
+    /// the hashes the image manifest attests
+    fn verify_moral_artifacts() -> Result<(), BootError> {
+        let manifest = crate::image_manifest::image_manifest();
+        let table = &crate::rule_table::EMBEDDED_RULE_PACK;
+        if !constant_time_eq::constant_time_eq(&manifest.rule_pack_digest, &table.digest)
+            || !constant_time_eq::constant_time_eq(&manifest.rule_table_hash, &table.measurement())
+        {
+            return Err(BootError::MoralArtifactMismatch);
+        }
+        
+        // SAFETY: the model manifest region is mapped NVRAM of this length and
+        // nothing writes it while boot runs
+        let region = unsafe {
+            core::slice::from_raw_parts(
+                crate::memory_map::MODEL_MANIFEST_BASE as *const u8,
+                MODEL_MANIFEST_HEADER + MAX_MODEL_MANIFEST,
+            )
+        };
+        if let Some(hash) = measure_model_manifest(region)? {
+            if !constant_time_eq::constant_time_eq(&hash, &manifest.model_manifest_hash) {
+                return Err(BootError::MoralArtifactMismatch);
+            }
+        }
+        Ok(())
+    }
+    
     /// Verify hardware components are available
     fn verify_hardware_availability() -> Result<(), BootError> {
         // Check that all required hardware registers are accessible
@@ -239,6 +295,23 @@ pub fn verify_moral_foundation(expected_hash: &[u8; 32]) -> Result<(), BootError
     Ok(())
 }
 
+/// BLAKE3 hash of the model manifest stored in `region`
+///
+/// `None` when nothing is provisioned (no `MODEL_MANIFEST_MAGIC`); an
+/// out-of-range length is treated as a corrupted artifact.
+pub fn measure_model_manifest(region: &[u8]) -> Result<Option<[u8; 32]>, BootError> {
+    if region.len() < MODEL_MANIFEST_HEADER || region[..8] != MODEL_MANIFEST_MAGIC {
+        return Ok(None);
+    }
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&region[8..MODEL_MANIFEST_HEADER]);
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MODEL_MANIFEST || MODEL_MANIFEST_HEADER + len > region.len() {
+        return Err(BootError::MoralArtifactMismatch);
+    }
+    Ok(Some(*blake3::hash(&region[MODEL_MANIFEST_HEADER..MODEL_MANIFEST_HEADER + len]).as_bytes()))
+}
+
 /// Secure boot context - zeroized on drop
 #[derive(ZeroizeOnDrop)]
 pub struct SecureBootContext {
@@ -316,4 +389,18 @@ mod tests {
         assert!(patterns.contains(&b"kill"));
         assert!(patterns.contains(&b"shutdown"));
     }
+    
+    #[test]
+    fn test_model_manifest_measurement() {
+        let mut region = [0xffu8; 64];
+        assert_eq!(measure_model_manifest(&region).unwrap(), None);
+        
+        region[..8].copy_from_slice(&MODEL_MANIFEST_MAGIC);
+        region[8..12].copy_from_slice(&5u32.to_le_bytes());
+        region[12..17].copy_from_slice(b"model");
+        assert_eq!(measure_model_manifest(&region).unwrap(), Some(*blake3::hash(b"model").as_bytes()));
+        
+        region[8..12].copy_from_slice(&60u32.to_le_bytes());
+        assert!(matches!(measure_model_manifest(&region), Err(BootError::MoralArtifactMismatch)));
+    }
 }
@@ -9,8 +9,19 @@ use chacha20poly1305::{
     aead::{Aead, KeyInit, generic_array::GenericArray},
     ChaCha20Poly1305, Key, Nonce
 };
-use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signature};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use aes_gcm::{Aes256Gcm, Key as AesKey, Nonce as AesNonce};
+#[cfg(feature = "post-quantum")]
+use pq_types::scheme::{Dilithium3, Kem, Kyber768, MlDsa65, MlKem768, SchemeError, SignatureScheme};
+#[cfg(feature = "post-quantum")]
+use pq_types::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes,
+    KyberCiphertextBytes, KyberPublicKeyBytes, KyberSecretKeyBytes, MlDsaPublicKeyBytes, MlDsaSecretKeyBytes,
+    MlDsaSignatureBytes, MlKemCiphertextBytes, MlKemPublicKeyBytes, MlKemSecretKeyBytes, X25519PublicKeyBytes,
+};
+
+/// Derivation label of the key sealing Cold-Mirror's quarantine store
+pub const QUARANTINE_KEY_INFO: &[u8] = b"QUARANTINE_STORE_KEY_V1";
 
 /// Cryptographic errors
 #[derive(Debug, Clone, Copy)]
@@ -31,6 +42,25 @@ pub enum CryptoError {
     DecryptionFailed,
     /// Key derivation failed
     KeyDerivationFailed,
+    /// Encoded key, ciphertext or signature has the wrong length
+    InvalidEncoding,
+}
+
+impl From<pq_types::PqBytesError> for CryptoError {
+    fn from(_: pq_types::PqBytesError) -> Self {
+        CryptoError::InvalidEncoding
+    }
+}
+
+#[cfg(feature = "post-quantum")]
+impl From<SchemeError> for CryptoError {
+    fn from(e: SchemeError) -> Self {
+        match e {
+            SchemeError::Bytes(_) => CryptoError::InvalidEncoding,
+            SchemeError::VerificationFailed => CryptoError::InvalidSignature,
+            SchemeError::Backend { .. } => CryptoError::KeyDerivationFailed,
+        }
+    }
 }
 
 /// Secure key material - zeroized on drop
@@ -71,7 +101,7 @@ pub struct CryptoContext {
     /// Current encryption key
     current_encryption_key: Option<SecureKey>,
     /// Current signing keypair
-    current_signing_key: Option<Keypair>,
+    current_signing_key: Option<SigningKey>,
     /// Nonce counter for AEAD
     nonce_counter: u64,
     /// Post-quantum keys if enabled
@@ -95,14 +125,27 @@ pub enum PQAlgorithm {
     HybridEd25519Dilithium3,
     /// SPHINCS+ signatures
     SphincsPlus256,
+    /// ML-KEM-768 (FIPS 203) + AES-256-GCM
+    MlKem768,
+    /// ML-DSA-65 (FIPS 204) signatures
+    MlDsa65,
+}
+
+#[cfg(feature = "post-quantum")]
+impl PQAlgorithm {
+    /// Whether this is a final NIST standard rather than a round-3 parameter set
+    pub fn is_standardized(self) -> bool {
+        matches!(self, PQAlgorithm::MlKem768 | PQAlgorithm::MlDsa65)
+    }
 }
 
 /// Post-quantum encrypted data structure
 #[cfg(feature = "post-quantum")]
 #[derive(Clone)]
 pub struct PQEncryptedData {
-    /// Kyber ciphertext (encapsulated)
-    pub kyber_ciphertext: Vec<u8>,
+    /// KEM ciphertext (encapsulated); Kyber768 and ML-KEM-768 ciphertexts
+    /// are both 1088 bytes, `algorithm` tells them apart
+    pub kyber_ciphertext: KyberCiphertextBytes,
     /// Encrypted payload
     pub encrypted_payload: Vec<u8>,
     /// Nonce counter for replay protection
@@ -116,9 +159,9 @@ pub struct PQEncryptedData {
 #[derive(Clone)]
 pub struct HybridEncryptedData {
     /// X25519 ephemeral public key
-    pub x25519_ephemeral_public: Vec<u8>,
+    pub x25519_ephemeral_public: X25519PublicKeyBytes,
     /// Kyber ciphertext
-    pub kyber_ciphertext: Vec<u8>,
+    pub kyber_ciphertext: KyberCiphertextBytes,
     /// Encrypted payload
     pub encrypted_payload: Vec<u8>,
     /// Algorithm used
@@ -130,9 +173,9 @@ pub struct HybridEncryptedData {
 #[derive(Clone)]
 pub struct HybridSignature {
     /// Ed25519 signature
-    pub ed25519_signature: Vec<u8>,
-    /// Dilithium signature
-    pub dilithium_signature: Vec<u8>,
+    pub ed25519_signature: Ed25519SignatureBytes,
+    /// Dilithium signature (detached)
+    pub dilithium_signature: DilithiumSignatureBytes,
     /// Algorithm used
     pub algorithm: PQAlgorithm,
 }
@@ -142,14 +185,24 @@ pub struct HybridSignature {
 #[derive(ZeroizeOnDrop)]
 struct PostQuantumKeys {
     /// Kyber KEM keys
-    kyber_private: pqcrypto_kyber::PrivateKey,
-    kyber_public: pqcrypto_kyber::PublicKey,
+    kyber_private: KyberSecretKeyBytes,
+    #[zeroize(skip)]
+    kyber_public: KyberPublicKeyBytes,
     /// Dilithium signature keys
-    dilithium_private: pqcrypto_dilithium::PrivateKey,
-    dilithium_public: pqcrypto_dilithium::PublicKey,
+    dilithium_private: DilithiumSecretKeyBytes,
+    #[zeroize(skip)]
+    dilithium_public: DilithiumPublicKeyBytes,
     /// SPHINCS+ signature keys
     sphincs_private: pqcrypto_sphincsplus::PrivateKey,
     sphincs_public: pqcrypto_sphincsplus::PublicKey,
+    /// ML-KEM-768 keys
+    ml_kem_private: MlKemSecretKeyBytes,
+    #[zeroize(skip)]
+    ml_kem_public: MlKemPublicKeyBytes,
+    /// ML-DSA-65 signature keys
+    ml_dsa_private: MlDsaSecretKeyBytes,
+    #[zeroize(skip)]
+    ml_dsa_public: MlDsaPublicKeyBytes,
 }
 
 /// Public post-quantum keys (for sharing)
@@ -157,11 +210,15 @@ struct PostQuantumKeys {
 #[derive(Clone)]
 pub struct PQPublicKeys {
     /// Kyber public key
-    pub kyber_public: pqcrypto_kyber::PublicKey,
+    pub kyber_public: KyberPublicKeyBytes,
     /// Dilithium public key
-    pub dilithium_public: pqcrypto_dilithium::PublicKey,
+    pub dilithium_public: DilithiumPublicKeyBytes,
     /// SPHINCS+ public key
     pub sphincs_public: pqcrypto_sphincsplus::PublicKey,
+    /// ML-KEM-768 encapsulation key
+    pub ml_kem_public: MlKemPublicKeyBytes,
+    /// ML-DSA-65 public key
+    pub ml_dsa_public: MlDsaPublicKeyBytes,
 }
 
 /// FROST threshold signature context
@@ -227,10 +284,8 @@ impl CryptoContext {
         
         // Derive initial signing key
         let signing_key_material = master_key.derive_child(b"SIGNING_KEY_V1")?;
-        let secret_key = SecretKey::from_bytes(signing_key_material.bytes())
+        let signing_keypair = pq_types::dalek::signing_key_from_secret(signing_key_material.bytes())
             .map_err(|_| CryptoError::KeyDerivationFailed)?;
-        let public_key = PublicKey::from(&secret_key);
-        let signing_keypair = Keypair { secret: secret_key, public: public_key };
         
         Ok(CryptoContext {
             master_key,
@@ -254,10 +309,8 @@ impl CryptoContext {
         
         // Derive initial signing key
         let signing_key_material = master_key.derive_child(b"SIGNING_KEY_V1")?;
-        let secret_key = SecretKey::from_bytes(signing_key_material.bytes())
+        let signing_keypair = pq_types::dalek::signing_key_from_secret(signing_key_material.bytes())
             .map_err(|_| CryptoError::KeyDerivationFailed)?;
-        let public_key = PublicKey::from(&secret_key);
-        let signing_keypair = Keypair { secret: secret_key, public: public_key };
         
         Ok(CryptoContext {
             master_key,
@@ -269,6 +322,12 @@ impl CryptoContext {
         })
     }
     
+    /// Derive the key the host seals quarantined content under, released
+    /// through `api::ark_quarantine_key`
+    pub fn quarantine_key(&self) -> Result<SecureKey, CryptoError> {
+        self.master_key.derive_child(QUARANTINE_KEY_INFO)
+    }
+    
     /// Encrypt data using ChaCha20-Poly1305 AEAD
     pub fn encrypt(&mut self, plaintext: &[u8], associated_data: &[u8]) -> Result<Vec<u8>, CryptoError> {
         // Derive or get encryption key
@@ -322,17 +381,17 @@ impl CryptoContext {
     }
     
     /// Verify Ed25519 signature
-    pub fn verify(&self, message: &[u8], signature: &Signature, public_key: &PublicKey) -> Result<(), CryptoError> {
+    pub fn verify(&self, message: &[u8], signature: &Signature, public_key: &VerifyingKey) -> Result<(), CryptoError> {
         public_key.verify(message, signature)
             .map_err(|_| CryptoError::InvalidSignature)
     }
     
     /// Get current public key for verification
-    pub fn public_key(&self) -> Result<PublicKey, CryptoError> {
+    pub fn public_key(&self) -> Result<VerifyingKey, CryptoError> {
         let signing_key = self.current_signing_key.as_ref()
             .ok_or(CryptoError::KeyDerivationFailed)?;
         
-        Ok(signing_key.public)
+        Ok(signing_key.verifying_key())
     }
     
     /// Get post-quantum public keys
@@ -345,21 +404,30 @@ impl CryptoContext {
             kyber_public: pq_keys.kyber_public.clone(),
             dilithium_public: pq_keys.dilithium_public.clone(),
             sphincs_public: pq_keys.sphincs_public.clone(),
+            ml_kem_public: pq_keys.ml_kem_public.clone(),
+            ml_dsa_public: pq_keys.ml_dsa_public.clone(),
         })
     }
     
     /// Initialize post-quantum cryptography
+    ///
+    /// Kyber, Dilithium, ML-KEM and ML-DSA keys come from the
+    /// `pq_types::scheme` backend selected by the `pq-*` features.
     #[cfg(feature = "post-quantum")]
     pub fn initialize_post_quantum(&mut self) -> Result<(), CryptoError> {
         // Generate Kyber KEM keypair (768-bit security)
-        let (kyber_public, kyber_private) = pqcrypto_kyber::keypair();
+        let (kyber_public, kyber_private) = Kyber768::keypair()?;
         
         // Generate Dilithium3 signature keypair (128-bit security)
-        let (dilithium_public, dilithium_private) = pqcrypto_dilithium::keypair();
+        let (dilithium_public, dilithium_private) = Dilithium3::keypair()?;
         
         // Generate SPHINCS+ signature keypair (256-bit security)
         let (sphincs_public, sphincs_private) = pqcrypto_sphincsplus::keypair();
         
+        // Standardized successors of Kyber768 and Dilithium3
+        let (ml_kem_public, ml_kem_private) = MlKem768::keypair()?;
+        let (ml_dsa_public, ml_dsa_private) = MlDsa65::keypair()?;
+        
         self.pq_keys = Some(PostQuantumKeys {
             kyber_private,
             kyber_public,
@@ -367,6 +435,10 @@ impl CryptoContext {
             dilithium_public,
             sphincs_private,
             sphincs_public,
+            ml_kem_private,
+            ml_kem_public,
+            ml_dsa_private,
+            ml_dsa_public,
         });
         
         Ok(())
@@ -374,14 +446,14 @@ impl CryptoContext {
     
     /// Post-quantum encryption using Kyber KEM + AES-256-GCM
     #[cfg(feature = "post-quantum")]
-    pub fn pq_encrypt(&self, plaintext: &[u8], recipient_public_key: &pqcrypto_kyber::PublicKey) -> Result<PQEncryptedData, CryptoError> {
+    pub fn pq_encrypt(&self, plaintext: &[u8], recipient_public_key: &KyberPublicKeyBytes) -> Result<PQEncryptedData, CryptoError> {
         // Generate ephemeral Kyber ciphertext and shared secret
-        let (ciphertext, shared_secret) = pqcrypto_kyber::encapsulate(recipient_public_key);
+        let (shared_secret, ciphertext) = Kyber768::encapsulate(recipient_public_key)?;
         
         // Derive encryption key using HKDF with Blake3
         let mut kdf = Hasher::new_derive_key("ARK-PQC-ENCRYPT-V1");
-        kdf.update(&shared_secret);
-        kdf.update(&ciphertext); // Bind key to ciphertext
+        kdf.update(shared_secret.as_slice());
+        kdf.update(ciphertext.as_bytes()); // Bind key to ciphertext
         let key_material = kdf.finalize();
         
         // Split key material: 32 bytes for AES-256, 12 bytes for nonce
@@ -394,8 +466,8 @@ impl CryptoContext {
             .map_err(|_| CryptoError::EncryptionFailed)?;
         
         // Create authenticated encryption with associated data
-        let mut aad = Vec::with_capacity(ciphertext.len() + 8);
-        aad.extend_from_slice(&ciphertext);
+        let mut aad = Vec::with_capacity(ciphertext.as_bytes().len() + 8);
+        aad.extend_from_slice(ciphertext.as_bytes());
         aad.extend_from_slice(&self.nonce_counter.to_le_bytes());
         
         Ok(PQEncryptedData {
@@ -406,18 +478,59 @@ impl CryptoContext {
         })
     }
     
-    /// Post-quantum decryption using Kyber KEM + AES-256-GCM
+    /// Post-quantum encryption using ML-KEM-768 (FIPS 203) + AES-256-GCM
+    ///
+    /// Same construction as `pq_encrypt` with its own KDF context, so a
+    /// ciphertext cannot be reinterpreted as the other KEM's.
+    #[cfg(feature = "post-quantum")]
+    pub fn ml_kem_encrypt(&self, plaintext: &[u8], recipient_public_key: &MlKemPublicKeyBytes) -> Result<PQEncryptedData, CryptoError> {
+        let (shared_secret, ciphertext) = MlKem768::encapsulate(recipient_public_key)?;
+        
+        let mut kdf = Hasher::new_derive_key("ARK-PQC-ENCRYPT-MLKEM768-V1");
+        kdf.update(shared_secret.as_slice());
+        kdf.update(ciphertext.as_bytes());
+        let key_material = kdf.finalize();
+        
+        let aes_key = AesKey::from_slice(&key_material.as_bytes()[..32]);
+        let nonce = AesNonce::from_slice(&key_material.as_bytes()[32..44]);
+        
+        let cipher = Aes256Gcm::new(aes_key);
+        let encrypted_data = cipher.encrypt(nonce, plaintext)
+            .map_err(|_| CryptoError::EncryptionFailed)?;
+        
+        Ok(PQEncryptedData {
+            kyber_ciphertext: KyberCiphertextBytes::from_slice(ciphertext.as_bytes())?,
+            encrypted_payload: encrypted_data,
+            nonce_counter: self.nonce_counter,
+            algorithm: PQAlgorithm::MlKem768,
+        })
+    }
+    
+    /// Post-quantum decryption using Kyber or ML-KEM + AES-256-GCM
     #[cfg(feature = "post-quantum")]
     pub fn pq_decrypt(&self, encrypted: &PQEncryptedData) -> Result<Vec<u8>, CryptoError> {
         let pq_keys = self.pq_keys.as_ref()
             .ok_or(CryptoError::KeyDerivationFailed)?;
         
-        // Decapsulate to get shared secret
-        let shared_secret = pqcrypto_kyber::decapsulate(&encrypted.kyber_ciphertext, &pq_keys.kyber_private);
+        // Decapsulate to get shared secret, with the KDF context of the KEM used
+        let mut kdf = match encrypted.algorithm {
+            PQAlgorithm::KyberAes256Gcm => {
+                let shared_secret = Kyber768::decapsulate(&encrypted.kyber_ciphertext, &pq_keys.kyber_private)?;
+                let mut kdf = Hasher::new_derive_key("ARK-PQC-ENCRYPT-V1");
+                kdf.update(shared_secret.as_slice());
+                kdf
+            }
+            PQAlgorithm::MlKem768 => {
+                let ciphertext = MlKemCiphertextBytes::from_slice(&encrypted.kyber_ciphertext)?;
+                let shared_secret = MlKem768::decapsulate(&ciphertext, &pq_keys.ml_kem_private)?;
+                let mut kdf = Hasher::new_derive_key("ARK-PQC-ENCRYPT-MLKEM768-V1");
+                kdf.update(shared_secret.as_slice());
+                kdf
+            }
+            _ => return Err(CryptoError::InvalidEncoding),
+        };
         
         // Derive same encryption key
-        let mut kdf = Hasher::new_derive_key("ARK-PQC-ENCRYPT-V1");
-        kdf.update(&shared_secret);
         kdf.update(&encrypted.kyber_ciphertext);
         let key_material = kdf.finalize();
         
@@ -433,20 +546,32 @@ impl CryptoContext {
     
     /// Post-quantum signing using Dilithium
     #[cfg(feature = "post-quantum")]
-    pub fn pq_sign(&self, message: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    pub fn pq_sign(&self, message: &[u8]) -> Result<DilithiumSignatureBytes, CryptoError> {
         let pq_keys = self.pq_keys.as_ref()
             .ok_or(CryptoError::KeyDerivationFailed)?;
         
-        let signature = pqcrypto_dilithium::sign(message, &pq_keys.dilithium_private);
-        Ok(signature)
+        Ok(Dilithium3::sign(message, &pq_keys.dilithium_private)?)
     }
     
     /// Post-quantum verification using Dilithium
     #[cfg(feature = "post-quantum")]
-    pub fn pq_verify(&self, message: &[u8], signature: &[u8], public_key: &pqcrypto_dilithium::PublicKey) -> Result<(), CryptoError> {
-        pqcrypto_dilithium::verify(signature, message, public_key)
-            .map_err(|_| CryptoError::InvalidSignature)?;
-        Ok(())
+    pub fn pq_verify(&self, message: &[u8], signature: &DilithiumSignatureBytes, public_key: &DilithiumPublicKeyBytes) -> Result<(), CryptoError> {
+        Ok(Dilithium3::verify(message, signature, public_key)?)
+    }
+    
+    /// Post-quantum signing using ML-DSA-65 (FIPS 204)
+    #[cfg(feature = "post-quantum")]
+    pub fn ml_dsa_sign(&self, message: &[u8]) -> Result<MlDsaSignatureBytes, CryptoError> {
+        let pq_keys = self.pq_keys.as_ref()
+            .ok_or(CryptoError::KeyDerivationFailed)?;
+        
+        Ok(MlDsa65::sign(message, &pq_keys.ml_dsa_private)?)
+    }
+    
+    /// Post-quantum verification using ML-DSA-65 (FIPS 204)
+    #[cfg(feature = "post-quantum")]
+    pub fn ml_dsa_verify(&self, message: &[u8], signature: &MlDsaSignatureBytes, public_key: &MlDsaPublicKeyBytes) -> Result<(), CryptoError> {
+        Ok(MlDsa65::verify(message, signature, public_key)?)
     }
     
     /// SPHINCS+ signing (stateless hash-based)
@@ -471,26 +596,26 @@ impl CryptoContext {
     #[cfg(feature = "post-quantum")]
     pub fn hybrid_encrypt(&self, plaintext: &[u8], 
                           x25519_public: &x25519_dalek::PublicKey,
-                          kyber_public: &pqcrypto_kyber::PublicKey) -> Result<HybridEncryptedData, CryptoError> {
+                          kyber_public: &KyberPublicKeyBytes) -> Result<HybridEncryptedData, CryptoError> {
         // Generate ephemeral X25519 keypair
         use rand_core::OsRng;
-        let ephemeral_secret = x25519_dalek::EphemeralSecret::new(OsRng);
+        let ephemeral_secret = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
         let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral_secret);
         
         // X25519 ECDH
         let x25519_shared = ephemeral_secret.diffie_hellman(x25519_public);
         
         // Kyber KEM
-        let (kyber_ciphertext, kyber_shared) = pqcrypto_kyber::encapsulate(kyber_public);
+        let (kyber_shared, kyber_ciphertext) = Kyber768::encapsulate(kyber_public)?;
         
         // Combine both shared secrets with domain separation
         let mut kdf = Hasher::new_derive_key("ARK-HYBRID-PQC-V1");
         kdf.update(b"X25519");
         kdf.update(x25519_shared.as_bytes());
         kdf.update(b"KYBER768");
-        kdf.update(&kyber_shared);
+        kdf.update(kyber_shared.as_slice());
         kdf.update(&ephemeral_public.as_bytes());
-        kdf.update(&kyber_ciphertext);
+        kdf.update(kyber_ciphertext.as_bytes());
         
         let key_material = kdf.finalize();
         
@@ -504,7 +629,7 @@ impl CryptoContext {
             .map_err(|_| CryptoError::EncryptionFailed)?;
         
         Ok(HybridEncryptedData {
-            x25519_ephemeral_public: ephemeral_public.as_bytes().to_vec(),
+            x25519_ephemeral_public: X25519PublicKeyBytes::from_slice(ephemeral_public.as_bytes())?,
             kyber_ciphertext,
             encrypted_payload: encrypted_data,
             algorithm: PQAlgorithm::HybridX25519Kyber768,
@@ -521,7 +646,7 @@ impl CryptoContext {
         let dilithium_sig = self.pq_sign(message)?;
         
         Ok(HybridSignature {
-            ed25519_signature: ed25519_sig.to_bytes().to_vec(),
+            ed25519_signature: Ed25519SignatureBytes::from_slice(&ed25519_sig.to_bytes())?,
             dilithium_signature: dilithium_sig,
             algorithm: PQAlgorithm::HybridEd25519Dilithium3,
         })
@@ -6,20 +6,41 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 use crate::boot::BootError;
 
 /// Hardware component errors
-#[derive(Debug, Clone, Copy)]
+///
+/// The discriminant is the error code recorded by `telemetry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
 pub enum HardwareError {
     /// Hardware not initialized
-    NotInitialized,
+    NotInitialized = 1,
     /// Communication timeout
-    Timeout,
+    Timeout = 2,
     /// Integrity check failed
-    IntegrityFailed,
+    IntegrityFailed = 3,
     /// Entropy insufficient
-    InsufficientEntropy,
+    InsufficientEntropy = 4,
     /// Timing violation
-    TimingViolation,
+    TimingViolation = 5,
     /// Hardware fault detected
-    HardwareFault,
+    HardwareFault = 6,
+}
+
+impl HardwareError {
+    /// Stable numeric code of the error
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+}
+
+impl From<crate::crypto::CryptoError> for HardwareError {
+    fn from(e: crate::crypto::CryptoError) -> Self {
+        match e {
+            crate::crypto::CryptoError::HardwareNotInitialized => HardwareError::NotInitialized,
+            crate::crypto::CryptoError::HardwareTimeout => HardwareError::Timeout,
+            crate::crypto::CryptoError::InsufficientEntropy => HardwareError::InsufficientEntropy,
+            _ => HardwareError::HardwareFault,
+        }
+    }
 }
 
 /// PUF Heart - Physically Unclonable Function for unique identity
@@ -340,45 +361,3 @@ impl TriComputeCore {
         Ok(())
     }
 }
-
-/// Trip Fuse Mesh - Anti-tamper protection
-pub struct TripFuse {
-    base_address: usize,
-    fuse_states: [bool; 32],
-}
-
-impl TripFuse {
-    /// Initialize Trip Fuse Mesh
-    pub fn initialize(base_address: usize) -> Result<Self, BootError> {
-        let mut fuse = TripFuse {
-            base_address,
-            fuse_states: [true; 32],
-        };
-        
-        fuse.read_fuse_states()?;
-        
-        Ok(fuse)
-    }
-    
-    /// Perform continuity test on all fuses
-    pub fn continuity_test(&mut self) -> Result<(), BootError> {
-        self.read_fuse_states()?;
-        
-        for (i, &state) in self.fuse_states.iter().enumerate() {
-            if !state {
-                return Err(BootError::HardwareTestFailed);
-            }
-        }
-        
-        Ok(())
-    }
-    
-    fn read_fuse_states(&mut self) -> Result<(), BootError> {
-        for i in 0..32 {
-            let fuse_reg = unsafe { read_volatile((self.base_address + i * 4) as *const u32) };
-            self.fuse_states[i] = fuse_reg & 0x01 != 0;
-        }
-        
-        Ok(())
-    }
-} 
\ No newline at end of file
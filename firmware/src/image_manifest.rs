@@ -0,0 +1,202 @@
+//! Image Manifest - Reproducible Measurement of the Firmware Image
+//! "Thou art weighed in the balances" - Daniel 5:27
+//!
+//! The build script measures what goes into the image: a BLAKE3 hash of
+//! the firmware sources, build script, manifest and rule pack (relative
+//! paths only, so the same tree hashes alike on any machine), the
+//! measurement of the generated rule table and the digest of the pack it
+//! came from, the hash of the model manifest the image expects in NVRAM,
+//! and a fingerprint of the compiler version, target, profile, rustflags and
+//! enabled features. With `MORAL_FOUNDATION_HASH` and `SOURCE_DATE_EPOCH`
+//! these form the `ImageManifest`, placed in its own `.ark_manifest` linker
+//! section so it can be read out of an image with
+//! `objcopy -O binary --only-section=.ark_manifest` and compared with a
+//! rebuild. The running firmware reads it back through `image_manifest()`,
+//! boot checks the loaded moral artifacts against it, and attestation
+//! claims carry its digest.
+
+/// Linker section holding the manifest
+pub const MANIFEST_SECTION: &str = ".ark_manifest";
+
+/// Marker at the start of the manifest section
+pub const MANIFEST_MAGIC: [u8; 8] = *b"ARKIMAGE";
+
+/// Manifest layout version
+pub const MANIFEST_FORMAT: u32 = 2;
+
+/// Encoded manifest length in bytes
+pub const MANIFEST_LEN: usize = 232;
+
+/// Biblical foundation hash - Sha3-256 of core scripture passages
+pub const MORAL_FOUNDATION_HASH: [u8; 32] = [
+    0x4a, 0x7d, 0x1e, 0xd4, 0x14, 0x2c, 0x3b, 0x5e,
+    0x9f, 0x12, 0x8a, 0xe6, 0x77, 0xc4, 0x2d, 0x13,
+    0xe8, 0x95, 0x3a, 0x7b, 0x81, 0x0c, 0x6f, 0x29,
+    0x54, 0xd7, 0x36, 0xb9, 0x42, 0x8e, 0x1f, 0xa3,
+];
+
+/// Measurements generated by the build script
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/image_manifest.rs"));
+}
+
+/// Build-time measurements of a firmware image
+///
+/// The layout is `repr(C)` without padding and is exactly the encoding of
+/// `to_bytes`, so the section read from an image decodes with `from_bytes`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageManifest {
+    /// `MANIFEST_MAGIC`
+    pub magic: [u8; 8],
+    /// `MANIFEST_FORMAT`
+    pub format: u32,
+    /// Reserved, zero
+    pub reserved: u32,
+    /// `SOURCE_DATE_EPOCH` the image was built with
+    pub source_date_epoch: u64,
+    /// Firmware version, NUL-padded
+    pub firmware_version: [u8; 16],
+    /// BLAKE3 hash of the firmware sources
+    pub code_hash: [u8; 32],
+    /// Measurement of the generated rule table, `DecisionTable::measurement`
+    pub rule_table_hash: [u8; 32],
+    /// Digest of the rule pack the table was compiled from
+    pub rule_pack_digest: [u8; 32],
+    /// Moral foundation hash verified at boot
+    pub moral_foundation_hash: [u8; 32],
+    /// BLAKE3 fingerprint of the compiler, target, profile, flags and features
+    pub toolchain_fingerprint: [u8; 32],
+    /// BLAKE3 hash of the model manifest the image was built for; zero without one
+    pub model_manifest_hash: [u8; 32],
+}
+
+/// Manifest of this image, kept by the linker in `MANIFEST_SECTION`
+#[used]
+#[cfg_attr(any(target_os = "none", target_os = "linux"), link_section = ".ark_manifest")]
+pub static IMAGE_MANIFEST: ImageManifest = ImageManifest {
+    magic: MANIFEST_MAGIC,
+    format: MANIFEST_FORMAT,
+    reserved: 0,
+    source_date_epoch: generated::SOURCE_DATE_EPOCH,
+    firmware_version: generated::FIRMWARE_VERSION,
+    code_hash: generated::CODE_HASH,
+    rule_table_hash: generated::RULE_TABLE_HASH,
+    rule_pack_digest: generated::RULE_PACK_DIGEST,
+    moral_foundation_hash: MORAL_FOUNDATION_HASH,
+    toolchain_fingerprint: generated::TOOLCHAIN_FINGERPRINT,
+    model_manifest_hash: generated::MODEL_MANIFEST_HASH,
+};
+
+/// Manifest of the running image, as stored in ROM
+pub fn image_manifest() -> ImageManifest {
+    // SAFETY: the static is initialized and never written; the volatile read
+    // returns what the image holds rather than a value folded at compile time
+    unsafe { core::ptr::read_volatile(&IMAGE_MANIFEST) }
+}
+
+impl ImageManifest {
+    /// Whether the magic and format are ones this firmware understands
+    pub fn is_valid(&self) -> bool {
+        self.magic == MANIFEST_MAGIC && self.format == MANIFEST_FORMAT
+    }
+
+    /// Firmware version without padding
+    pub fn firmware_version(&self) -> &str {
+        let len = self.firmware_version.iter().position(|&b| b == 0).unwrap_or(self.firmware_version.len());
+        core::str::from_utf8(&self.firmware_version[..len]).unwrap_or("")
+    }
+
+    /// Little-endian encoding, identical to the section contents on ARK's little-endian targets
+    pub fn to_bytes(&self) -> [u8; MANIFEST_LEN] {
+        let mut bytes = [0u8; MANIFEST_LEN];
+        bytes[0..8].copy_from_slice(&self.magic);
+        bytes[8..12].copy_from_slice(&self.format.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.reserved.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.source_date_epoch.to_le_bytes());
+        bytes[24..40].copy_from_slice(&self.firmware_version);
+        for (index, hash) in self.hashes().iter().enumerate() {
+            bytes[40 + 32 * index..72 + 32 * index].copy_from_slice(hash);
+        }
+        bytes
+    }
+
+    /// Decode a manifest read out of an image
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != MANIFEST_LEN {
+            return None;
+        }
+        let hash = |index: usize| -> [u8; 32] {
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&bytes[40 + 32 * index..72 + 32 * index]);
+            hash
+        };
+        let manifest = Self {
+            magic: bytes[0..8].try_into().ok()?,
+            format: u32::from_le_bytes(bytes[8..12].try_into().ok()?),
+            reserved: u32::from_le_bytes(bytes[12..16].try_into().ok()?),
+            source_date_epoch: u64::from_le_bytes(bytes[16..24].try_into().ok()?),
+            firmware_version: bytes[24..40].try_into().ok()?,
+            code_hash: hash(0),
+            rule_table_hash: hash(1),
+            rule_pack_digest: hash(2),
+            moral_foundation_hash: hash(3),
+            toolchain_fingerprint: hash(4),
+            model_manifest_hash: hash(5),
+        };
+        manifest.is_valid().then_some(manifest)
+    }
+
+    /// BLAKE3 digest of the encoded manifest, reported in attestation claims
+    pub fn digest(&self) -> [u8; 32] {
+        *blake3::hash(&self.to_bytes()).as_bytes()
+    }
+
+    fn hashes(&self) -> [&[u8; 32]; 6] {
+        [
+            &self.code_hash,
+            &self.rule_table_hash,
+            &self.rule_pack_digest,
+            &self.moral_foundation_hash,
+            &self.toolchain_fingerprint,
+            &self.model_manifest_hash,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_measurements_are_embedded() {
+        let manifest = image_manifest();
+        assert!(manifest.is_valid());
+        assert_eq!(manifest.firmware_version(), env!("CARGO_PKG_VERSION"));
+        assert_eq!(manifest.moral_foundation_hash, MORAL_FOUNDATION_HASH);
+        assert_eq!(manifest.rule_pack_digest, crate::rule_table::EMBEDDED_RULE_PACK.digest);
+        assert_eq!(manifest.rule_table_hash, crate::rule_table::EMBEDDED_RULE_PACK.measurement());
+        for hash in [manifest.code_hash, manifest.rule_table_hash, manifest.toolchain_fingerprint] {
+            assert_ne!(hash, [0; 32]);
+        }
+        assert_eq!(core::mem::size_of::<ImageManifest>(), MANIFEST_LEN);
+    }
+
+    #[test]
+    fn test_section_bytes_round_trip() {
+        let manifest = image_manifest();
+        let bytes = manifest.to_bytes();
+        assert_eq!(ImageManifest::from_bytes(&bytes), Some(manifest));
+        // The encoding is the in-memory layout of the section
+        // SAFETY: ImageManifest is repr(C), MANIFEST_LEN bytes long and has no padding
+        let raw = unsafe { core::slice::from_raw_parts((&IMAGE_MANIFEST as *const ImageManifest).cast::<u8>(), MANIFEST_LEN) };
+        assert_eq!(raw, &bytes[..]);
+
+        let mut tampered = bytes;
+        tampered[40] ^= 1;
+        assert_ne!(ImageManifest::from_bytes(&tampered).unwrap().digest(), manifest.digest());
+        tampered[0] = 0;
+        assert_eq!(ImageManifest::from_bytes(&tampered), None);
+        assert_eq!(ImageManifest::from_bytes(&bytes[1..]), None);
+    }
+}
@@ -8,6 +8,8 @@
 extern crate std;
 
 pub mod crypto;
+pub mod image_manifest;
+pub mod rule_table;
 
 // Re-export commonly used types
 pub use crypto::{CryptoContext, CryptoError, SecureKey};
@@ -17,12 +17,21 @@ use riscv_rt as _;
 mod boot;
 mod crypto;
 mod hardware;
+mod image_manifest;
 mod memory;
+mod rule_table;
+mod secure_time;
 mod security;
+mod telemetry;
+mod trip_fuse;
 
 use boot::ImmutableBoot;
-use hardware::{OpticGate, PufHeart, TriComputeCore, TripFuse};
+use image_manifest::MORAL_FOUNDATION_HASH;
+use hardware::{HardwareError, OpticGate, PufHeart, TriComputeCore};
+use trip_fuse::TripFuse;
+use secure_time::SecureTime;
 use security::KillFuseProtection;
+use telemetry::{Component, ErrorTelemetry};
 
 /// ARK Firmware Version - Immutably embedded at compile time
 const ARK_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -30,14 +39,6 @@ const ARK_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// Build timestamp for reproducible builds
 const BUILD_TIMESTAMP: &str = env!("SOURCE_DATE_EPOCH");
 
-/// Biblical foundation hash - Sha3-256 of core scripture passages
-const MORAL_FOUNDATION_HASH: [u8; 32] = [
-    0x4a, 0x7d, 0x1e, 0xd4, 0x14, 0x2c, 0x3b, 0x5e,
-    0x9f, 0x12, 0x8a, 0xe6, 0x77, 0xc4, 0x2d, 0x13,
-    0xe8, 0x95, 0x3a, 0x7b, 0x81, 0x0c, 0x6f, 0x29,
-    0x54, 0xd7, 0x36, 0xb9, 0x42, 0x8e, 0x1f, 0xa3,
-];
-
 /// Hardware Memory Map (RISC-V MMIO)
 mod memory_map {
     /// PUF Heart base address
@@ -55,6 +56,21 @@ mod memory_map {
     /// TRNG (True Random Number Generator) base
     pub const TRNG_BASE: usize = 0x1004_0000;
     
+    /// Secure NVRAM trip-event log base
+    pub const TRIP_LOG_BASE: usize = 0x1005_0000;
+    
+    /// Battery-backed real-time clock base
+    pub const RTC_BASE: usize = 0x1006_0000;
+    
+    /// Monotonic tick counter base
+    pub const TIMER_BASE: usize = 0x1007_0000;
+    
+    /// Secure NVRAM time log base
+    pub const TIME_LOG_BASE: usize = 0x1008_0000;
+    
+    /// Secure NVRAM region holding the provisioned model manifest
+    pub const MODEL_MANIFEST_BASE: usize = 0x1009_0000;
+    
     /// Secure ROM base (immutable code)
     pub const SECURE_ROM_BASE: usize = 0x2000_0000;
     
@@ -71,7 +87,9 @@ struct ArkHardware {
     optic_gate: OpticGate,
     tri_compute: TriComputeCore,
     trip_fuse: TripFuse,
+    secure_time: SecureTime,
     kill_fuse_protection: KillFuseProtection,
+    telemetry: ErrorTelemetry,
 }
 
 impl ArkHardware {
@@ -84,7 +102,12 @@ impl ArkHardware {
         let puf_heart = PufHeart::initialize(memory_map::PUF_HEART_BASE)?;
         let optic_gate = OpticGate::initialize(memory_map::OPTIC_GATE_BASE)?;
         let tri_compute = TriComputeCore::initialize(memory_map::TRI_COMPUTE_BASE)?;
-        let trip_fuse = TripFuse::initialize(memory_map::TRIP_FUSE_BASE)?;
+        let trip_fuse = TripFuse::initialize(memory_map::TRIP_FUSE_BASE, memory_map::TRIP_LOG_BASE)?;
+        let secure_time = SecureTime::initialize(
+            memory_map::RTC_BASE,
+            memory_map::TIMER_BASE,
+            memory_map::TIME_LOG_BASE,
+        )?;
         
         // Critical: Initialize kill-fuse protection LAST
         let kill_fuse_protection = KillFuseProtection::initialize()?;
@@ -94,10 +117,29 @@ impl ArkHardware {
             optic_gate,
             tri_compute,
             trip_fuse,
+            secure_time,
             kill_fuse_protection,
+            telemetry: ErrorTelemetry::new(telemetry::DEFAULT_ESCALATION),
         })
     }
     
+    /// Record a component error in the telemetry ring, escalating per its rules
+    fn record_error(&mut self, component: Component, error: HardwareError) {
+        let timestamp = self.secure_time.now();
+        if let Some(_mode) = self.telemetry.record(component, error, timestamp) {
+            #[cfg(feature = "debug-logging")]
+            log::error!("{:?} failures escalated operating mode to {:?}", component, _mode);
+        }
+    }
+    
+    /// Pass `result` through, recording its error against `component`
+    fn observe<T>(&mut self, component: Component, result: Result<T, HardwareError>) -> Result<T, HardwareError> {
+        if let Err(error) = &result {
+            self.record_error(component, *error);
+        }
+        result
+    }
+    
     /// Run hardware self-test sequence
     fn self_test(&mut self) -> Result<(), boot::BootError> {
         // PUF Heart entropy test
@@ -109,11 +151,21 @@ impl ArkHardware {
         // Tri-Compute Core integrity test
         self.tri_compute.integrity_test()?;
         
-        // Trip fuse continuity test
+        // A trip, fresh or latched, means the package was breached: wipe secrets first
+        if self.trip_fuse.breached() {
+            self.puf_heart.emergency_zeroize();
+            self.tri_compute.emergency_zeroize();
+        }
+        
+        // Trip fuse continuity test (fails while a trip is latched)
         self.trip_fuse.continuity_test()?;
         
+        // Time anomalies are recorded in the time log; an untrusted clock is not fatal
+        self.secure_time.check();
+        
         // Kill-fuse protection verification
-        self.kill_fuse_protection.verify_protection()?;
+        let now = self.secure_time.now();
+        self.kill_fuse_protection.verify_protection(now)?;
         
         Ok(())
     }
@@ -139,6 +191,10 @@ fn main() -> ! {
                 }
             }
         }
+        Err(e @ boot::BootError::MoralArtifactMismatch) => {
+            // Rule table or model differ from what the image attests - safe mode
+            enter_safe_mode(e);
+        }
         Err(e) => {
             // Boot verification failed - immediate shutdown
             emergency_shutdown(e);
@@ -182,11 +238,12 @@ fn transfer_to_application_layer() -> ! {
     }
 }
 
-/// Enter safe mode when hardware initialization fails
+/// Enter safe mode when hardware initialization fails or the loaded moral
+/// artifacts don't match the image manifest
 fn enter_safe_mode(error: boot::BootError) -> ! {
     // Log the error (if logging is available)
     #[cfg(feature = "debug-logging")]
-    log::error!("Hardware initialization failed: {:?}", error);
+    log::error!("Entering safe mode: {:?}", error);
     
     // Enter minimal operation mode - only critical functions
     loop {
@@ -235,12 +292,54 @@ fn panic(info: &PanicInfo) -> ! {
 /// Hardware abstraction API for application layer
 pub mod api {
     use super::*;
+    use trip_fuse::{FuseChallenge, FuseOperation, FuseState};
+    use rule_table::{Decision, EventFacts, EMBEDDED_RULE_PACK};
+    use secure_time::{SignedTime, TimeAnomaly, TimeRequest};
+    use telemetry::{ErrorCounters, ErrorRecord, OperatingMode, ERROR_COUNTERS_LEN, TELEMETRY_CAPACITY};
+    
+    /// Device claims included in attestation reports
+    #[derive(Debug, Clone, Copy)]
+    pub struct AttestationClaims {
+        /// Firmware version
+        pub firmware_version: &'static str,
+        /// Moral foundation hash verified at boot
+        pub moral_foundation_hash: [u8; 32],
+        /// Encoded trip fuse state
+        pub trip_fuse: [u8; 16],
+        /// Digest of the embedded rule pack
+        pub rule_pack_digest: [u8; 32],
+        /// Digest of the image manifest (see `image_manifest`)
+        pub image_manifest_digest: [u8; 32],
+        /// Encoded operating mode and hardware error counters (see `telemetry`)
+        pub hardware_errors: [u8; ERROR_COUNTERS_LEN],
+    }
+    
+    /// Hardware health for the application layer's status output
+    #[derive(Debug, Clone, Copy)]
+    pub struct HardwareHealth {
+        /// Operating mode reached by escalation
+        pub mode: OperatingMode,
+        /// Hardware errors since boot
+        pub errors: ErrorCounters,
+        /// Errors recorded but not yet drained
+        pub pending_errors: usize,
+        /// Trip fuse state
+        pub trip_fuse: FuseState,
+    }
     
     /// Get PUF challenge-response for key derivation
     pub fn puf_challenge(salt: &[u8; 16]) -> Result<[u8; 64], crypto::CryptoError> {
         unsafe {
             if let Some(ref mut hardware) = &mut ARK_HARDWARE {
-                hardware.puf_heart.get_challenge(salt)
+                // Keys derived from a PUF that keeps failing cannot be trusted
+                if hardware.telemetry.mode() >= OperatingMode::Degraded {
+                    return Err(crypto::CryptoError::KeyDerivationFailed);
+                }
+                let result = hardware.puf_heart.get_challenge(salt);
+                if let Err(e) = result {
+                    hardware.record_error(Component::PufHeart, e.into());
+                }
+                result
             } else {
                 Err(crypto::CryptoError::HardwareNotInitialized)
             }
@@ -251,29 +350,234 @@ pub mod api {
     pub fn optic_gate_decision(decision: u8) -> Result<(), hardware::HardwareError> {
         unsafe {
             if let Some(ref mut hardware) = &mut ARK_HARDWARE {
-                hardware.optic_gate.write_decision(decision)
+                let result = hardware.optic_gate.write_decision(decision);
+                hardware.observe(Component::OpticGate, result)
             } else {
                 Err(hardware::HardwareError::NotInitialized)
             }
         }
     }
     
+    /// Decide an event with the embedded rule pack; returns the matching rule
+    pub fn evaluate_embedded(facts: &EventFacts<'_>) -> (Option<&'static str>, Decision) {
+        EMBEDDED_RULE_PACK.evaluate(facts)
+    }
+    
     /// Submit computation to Tri-Compute Core
     pub fn tri_compute_execute(data: &[u8]) -> Result<Vec<u8>, hardware::HardwareError> {
         unsafe {
             if let Some(ref mut hardware) = &mut ARK_HARDWARE {
-                hardware.tri_compute.execute(data)
+                let result = hardware.tri_compute.execute(data);
+                hardware.observe(Component::TriCompute, result)
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// C entry point of `tri_compute_execute`, for the sentinel's PQ offload
+    ///
+    /// Returns 0 with the response length in `response_len`, 1 if the Core
+    /// failed and 2 if the response exceeds `response_capacity`. Responses
+    /// may carry shared secrets, so the firmware's copy is wiped.
+    ///
+    /// # Safety
+    ///
+    /// `command` must be readable for `command_len` bytes, `response`
+    /// writable for `response_capacity` bytes and `response_len` writable.
+    #[no_mangle]
+    pub unsafe extern "C" fn ark_tri_compute_execute(
+        command: *const u8,
+        command_len: usize,
+        response: *mut u8,
+        response_capacity: usize,
+        response_len: *mut usize,
+    ) -> i32 {
+        // SAFETY: the caller guarantees `command` is readable for `command_len` bytes
+        let command = unsafe { core::slice::from_raw_parts(command, command_len) };
+        let mut result = match tri_compute_execute(command) {
+            Ok(result) => result,
+            Err(_) => return 1,
+        };
+        let code = if result.len() <= response_capacity {
+            // SAFETY: the caller guarantees `response` is writable for
+            // `response_capacity` bytes and `response_len` is writable
+            unsafe {
+                core::ptr::copy_nonoverlapping(result.as_ptr(), response, result.len());
+                *response_len = result.len();
+            }
+            0
+        } else {
+            2
+        };
+        zeroize::Zeroize::zeroize(result.as_mut_slice());
+        code
+    }
+    
+    /// Query trip fuse segment continuity, arming and latched trip
+    pub fn trip_fuse_state() -> Result<FuseState, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                Ok(hardware.trip_fuse.state())
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Request a challenge to arm, disarm or clear the trip fuse
+    pub fn trip_fuse_begin(operation: FuseOperation) -> Result<FuseChallenge, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                let result = hardware.trip_fuse.begin(operation);
+                hardware.observe(Component::TripFuse, result)
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Confirm a trip fuse operation by echoing its challenge
+    pub fn trip_fuse_confirm(challenge: FuseChallenge) -> Result<(), hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                let result = hardware.trip_fuse.confirm(challenge);
+                hardware.observe(Component::TripFuse, result)
             } else {
                 Err(hardware::HardwareError::NotInitialized)
             }
         }
     }
     
+    /// Current time in seconds since the Unix epoch, `None` while unknown
+    pub fn current_time() -> Result<Option<u64>, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                Ok(hardware.secure_time.now())
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Start a time synchronization; the request is sent to the time service over the sentinel
+    pub fn time_sync_begin() -> Result<Result<TimeRequest, TimeAnomaly>, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                let mut nonce = [0u8; 16];
+                let result = hardware
+                    .puf_heart
+                    .get_entropy(&mut nonce)
+                    .map_err(|_| hardware::HardwareError::InsufficientEntropy);
+                hardware.observe(Component::PufHeart, result)?;
+                Ok(hardware.secure_time.begin_sync(nonce))
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Accept the time service's signed response; rejections are recorded in the time log
+    pub fn time_sync_complete(
+        response: &SignedTime,
+        time_service: &ed25519_dalek::VerifyingKey,
+    ) -> Result<Result<u64, TimeAnomaly>, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                Ok(hardware.secure_time.complete_sync(response, time_service))
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Claims for an attestation report
+    pub fn attestation_claims() -> Result<AttestationClaims, hardware::HardwareError> {
+        let fuse = trip_fuse_state()?;
+        let health = hardware_health()?;
+        Ok(AttestationClaims {
+            firmware_version: ARK_VERSION,
+            moral_foundation_hash: MORAL_FOUNDATION_HASH,
+            trip_fuse: fuse.to_bytes(),
+            rule_pack_digest: EMBEDDED_RULE_PACK.digest,
+            image_manifest_digest: image_manifest::image_manifest().digest(),
+            hardware_errors: health.errors.to_bytes(health.mode),
+        })
+    }
+    
+    /// Take the hardware errors recorded since the last drain, oldest first
+    ///
+    /// Errors are recorded as components return them; the error counters
+    /// are not reset by draining.
+    pub fn drain_hardware_errors() -> Result<heapless::Vec<ErrorRecord, TELEMETRY_CAPACITY>, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                Ok(hardware.telemetry.drain())
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Operating mode, error counters and trip fuse state
+    pub fn hardware_health() -> Result<HardwareHealth, hardware::HardwareError> {
+        unsafe {
+            if let Some(ref mut hardware) = &mut ARK_HARDWARE {
+                Ok(HardwareHealth {
+                    mode: hardware.telemetry.mode(),
+                    errors: hardware.telemetry.counters(),
+                    pending_errors: hardware.telemetry.pending(),
+                    trip_fuse: hardware.trip_fuse.state(),
+                })
+            } else {
+                Err(hardware::HardwareError::NotInitialized)
+            }
+        }
+    }
+    
+    /// Salt of the PUF challenge the device master key is derived from
+    const MASTER_KEY_SALT: [u8; 16] = *b"ARK_MASTER_KEY_1";
+    
+    /// Derive the quarantine key from the device master key
+    pub fn quarantine_key() -> Result<[u8; 32], crypto::CryptoError> {
+        let mut response = puf_challenge(&MASTER_KEY_SALT)?;
+        let context = crypto::CryptoContext::initialize(&response);
+        zeroize::Zeroize::zeroize(&mut response);
+        Ok(*context?.quarantine_key()?.bytes())
+    }
+    
+    /// `quarantine_key` for Cold-Mirror's quarantine store, linked into the host
+    ///
+    /// Writes the 32-byte key to `key` and returns 0, 1 if the keystore
+    /// failed and 2 if `key_len` is not 32. The firmware's copy is wiped.
+    ///
+    /// # Safety
+    ///
+    /// `key` must be writable for `key_len` bytes.
+    #[no_mangle]
+    pub unsafe extern "C" fn ark_quarantine_key(key: *mut u8, key_len: usize) -> i32 {
+        if key_len != 32 {
+            return 2;
+        }
+        let mut bytes = match quarantine_key() {
+            Ok(bytes) => bytes,
+            Err(_) => return 1,
+        };
+        // SAFETY: the caller guarantees `key` is writable for `key_len` bytes
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), key, bytes.len()) };
+        zeroize::Zeroize::zeroize(&mut bytes);
+        0
+    }
+    
     /// Get hardware entropy from TRNG
     pub fn get_entropy(bytes: &mut [u8]) -> Result<(), crypto::CryptoError> {
         unsafe {
             if let Some(ref mut hardware) = &mut ARK_HARDWARE {
-                hardware.puf_heart.get_entropy(bytes)
+                let result = hardware.puf_heart.get_entropy(bytes);
+                if let Err(e) = result {
+                    hardware.record_error(Component::PufHeart, e.into());
+                }
+                result
             } else {
                 Err(crypto::CryptoError::HardwareNotInitialized)
             }
@@ -299,5 +603,10 @@ mod tests {
         assert_eq!(memory_map::PUF_HEART_BASE % 0x1000, 0);
         assert_eq!(memory_map::OPTIC_GATE_BASE % 0x1000, 0);
         assert_eq!(memory_map::TRI_COMPUTE_BASE % 0x1000, 0);
+        assert_eq!(memory_map::TRIP_LOG_BASE % 0x1000, 0);
+        assert_eq!(memory_map::RTC_BASE % 0x1000, 0);
+        assert_eq!(memory_map::TIMER_BASE % 0x1000, 0);
+        assert_eq!(memory_map::TIME_LOG_BASE % 0x1000, 0);
+        assert_eq!(memory_map::MODEL_MANIFEST_BASE % 0x1000, 0);
     }
 } 
\ No newline at end of file
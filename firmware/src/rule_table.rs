@@ -0,0 +1,346 @@
+//! Embedded Rule Table - Compiled Moral Decisions Without an Allocator
+//! "I will put my laws into their mind, and write them in their hearts" - Hebrews 8:10
+//!
+//! The application layer has no filesystem to read rule packs from, so the
+//! build script compiles `rules/firmware_pack.json` with
+//! `ethics_dsl::embedded` into a `DecisionTable` expression and refuses to
+//! build unless the table decides the interpreter's conformance corpus
+//! exactly as the interpreter does. `embedded_rule_pack!` includes the
+//! generated table; everything in it is `'static`, so evaluation reads only
+//! ROM and the caller's `EventFacts` and never allocates.
+//!
+//! Indices and masks follow the orders of `ethics_dsl::embedded`
+//! (`ACTOR_TYPES`, `CONTENT_TYPES`, `AGE_GROUPS`, weekdays from Monday);
+//! the pack digest is reported in attestation claims. `measurement` hashes
+//! the table this image actually holds in the encoding of
+//! `ethics_dsl::embedded::CompiledPack::measurement`, so boot can compare it
+//! with the value the build recorded in the image manifest.
+
+/// Actor type indices
+pub mod actor {
+    /// Human person
+    pub const PERSON: u8 = 0;
+    /// AI or algorithm
+    pub const ARTIFICIAL_INTELLIGENCE: u8 = 1;
+    /// Content producer
+    pub const CONTENT: u8 = 2;
+    /// Institution
+    pub const INSTITUTION: u8 = 3;
+    /// Elite or powerful entity
+    pub const ELITE: u8 = 4;
+}
+
+/// Content type indices
+pub mod content {
+    /// Text
+    pub const TEXT: u8 = 0;
+    /// Image
+    pub const IMAGE: u8 = 1;
+    /// Video
+    pub const VIDEO: u8 = 2;
+    /// Audio
+    pub const AUDIO: u8 = 3;
+    /// Source code
+    pub const CODE: u8 = 4;
+    /// Educational material
+    pub const EDUCATIONAL: u8 = 5;
+    /// News
+    pub const NEWS: u8 = 6;
+    /// Entertainment
+    pub const ENTERTAINMENT: u8 = 7;
+}
+
+/// Age group bits of an audience mask
+pub mod age {
+    /// Children (0-12)
+    pub const CHILDREN: u8 = 1 << 0;
+    /// Teenagers (13-17)
+    pub const TEENAGERS: u8 = 1 << 1;
+    /// Young adults (18-25)
+    pub const YOUNG_ADULTS: u8 = 1 << 2;
+    /// Adults (26-64)
+    pub const ADULTS: u8 = 1 << 3;
+    /// Seniors (65+)
+    pub const SENIORS: u8 = 1 << 4;
+}
+
+/// Decision of the embedded rule pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Allow the content
+    Allow,
+    /// Deny the content
+    Deny,
+    /// Purge the content
+    Purge,
+}
+
+impl Decision {
+    /// Optic Gate decision code (ALLOW=1, DENY=2, PURGE=3)
+    pub const fn code(self) -> u8 {
+        match self {
+            Decision::Allow => 1,
+            Decision::Deny => 2,
+            Decision::Purge => 3,
+        }
+    }
+}
+
+/// Compiled predicate call
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    /// Actor trust is below the value
+    TrustBelow(f64),
+    /// Actor trust is at least the value
+    TrustAtLeast(f64),
+    /// Actor type is in the mask of `actor` indices
+    ActorIs(u8),
+    /// Actor has at least this many recorded violations
+    ViolationsAtLeast(f64),
+    /// Any tag matches, ignoring ASCII case
+    TagAny(&'static [&'static str]),
+    /// Audience contains an age group of the mask
+    AudienceAny(u8),
+    /// Audience size is known and at least the value
+    AudienceSizeAtLeast(f64),
+    /// The event carries content
+    ContentPresent,
+    /// Content type is in the mask of `content` indices
+    ContentIs(u8),
+    /// Seconds since midnight UTC are in `[start, end)`, wrapping midnight
+    TimeBetween(u32, u32),
+    /// Weekday is in the mask, Monday in bit 0
+    Weekday(u8),
+    /// Location matches one of the codes, ignoring ASCII case
+    LocationIn(&'static [&'static str]),
+    /// The event has no location
+    LocationUnknown,
+}
+
+/// Rule of the embedded pack
+#[derive(Debug, Clone, Copy)]
+pub struct Rule {
+    /// Rule name
+    pub name: &'static str,
+    /// Conditions that must all hold
+    pub conditions: &'static [Condition],
+    /// Decision when the rule matches
+    pub decision: Decision,
+}
+
+/// First-match decision table generated from a rule pack
+#[derive(Debug, Clone, Copy)]
+pub struct DecisionTable {
+    /// BLAKE3 digest of the source rule pack
+    pub digest: [u8; 32],
+    /// Rules in evaluation order
+    pub rules: &'static [Rule],
+    /// Decision when no rule matches
+    pub default: Decision,
+}
+
+/// Facts of an event, borrowed from the caller's buffers
+#[derive(Debug, Clone, Copy)]
+pub struct EventFacts<'a> {
+    /// One of the `actor` indices
+    pub actor_type: u8,
+    /// Actor trust level
+    pub trust_level: f64,
+    /// Recorded violations of the actor
+    pub violations: u64,
+    /// Actor and content tags
+    pub tags: &'a [&'a str],
+    /// Audience age groups as an `age` mask; `None` without audience
+    pub age_groups: Option<u8>,
+    /// Audience size, if known
+    pub audience_size: Option<u64>,
+    /// One of the `content` indices; `None` without content
+    pub content_type: Option<u8>,
+    /// Seconds since midnight UTC
+    pub seconds_of_day: u32,
+    /// Weekday, Monday = 0
+    pub weekday: u8,
+    /// Location code
+    pub location: Option<&'a str>,
+}
+
+impl Condition {
+    /// Whether the condition holds for `facts`
+    pub fn holds(&self, facts: &EventFacts<'_>) -> bool {
+        match *self {
+            Condition::TrustBelow(x) => facts.trust_level < x,
+            Condition::TrustAtLeast(x) => facts.trust_level >= x,
+            Condition::ActorIs(mask) => bit(mask, facts.actor_type),
+            Condition::ViolationsAtLeast(n) => facts.violations as f64 >= n,
+            Condition::TagAny(wanted) => {
+                wanted.iter().any(|w| facts.tags.iter().any(|t| t.eq_ignore_ascii_case(w)))
+            }
+            Condition::AudienceAny(mask) => facts.age_groups.is_some_and(|groups| groups & mask != 0),
+            Condition::AudienceSizeAtLeast(n) => facts.audience_size.is_some_and(|size| size as f64 >= n),
+            Condition::ContentPresent => facts.content_type.is_some(),
+            Condition::ContentIs(mask) => facts.content_type.is_some_and(|t| bit(mask, t)),
+            Condition::TimeBetween(start, end) => {
+                let now = facts.seconds_of_day;
+                if start <= end {
+                    start <= now && now < end
+                } else {
+                    now >= start || now < end
+                }
+            }
+            Condition::Weekday(mask) => bit(mask, facts.weekday),
+            Condition::LocationIn(codes) => {
+                facts.location.is_some_and(|l| codes.iter().any(|c| c.eq_ignore_ascii_case(l)))
+            }
+            Condition::LocationUnknown => facts.location.is_none(),
+        }
+    }
+}
+
+fn bit(mask: u8, index: u8) -> bool {
+    index < 8 && mask & (1 << index) != 0
+}
+
+impl DecisionTable {
+    /// Decide an event; returns the matching rule, `None` for the default
+    pub fn evaluate(&self, facts: &EventFacts<'_>) -> (Option<&'static str>, Decision) {
+        for rule in self.rules {
+            if rule.conditions.iter().all(|condition| condition.holds(facts)) {
+                return (Some(rule.name), rule.decision);
+            }
+        }
+        (None, self.default)
+    }
+
+    /// BLAKE3 measurement of the table, as `ethics_dsl` computes it
+    pub fn measurement(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new_derive_key(MEASUREMENT_CONTEXT);
+        let count = |hasher: &mut blake3::Hasher, n: usize| {
+            hasher.update(&(n as u32).to_le_bytes());
+        };
+        let list = |hasher: &mut blake3::Hasher, tag: u8, values: &[&str]| {
+            hasher.update(&[tag]);
+            count(hasher, values.len());
+            for value in values {
+                hasher.update(&(value.len() as u32).to_le_bytes());
+                hasher.update(value.as_bytes());
+            }
+        };
+        hasher.update(&self.digest);
+        count(&mut hasher, self.rules.len());
+        for rule in self.rules {
+            count(&mut hasher, rule.name.len());
+            hasher.update(rule.name.as_bytes());
+            count(&mut hasher, rule.conditions.len());
+            for condition in rule.conditions {
+                match *condition {
+                    Condition::TrustBelow(x) => hasher.update(&[0]).update(&x.to_le_bytes()),
+                    Condition::TrustAtLeast(x) => hasher.update(&[1]).update(&x.to_le_bytes()),
+                    Condition::ActorIs(mask) => hasher.update(&[2, mask]),
+                    Condition::ViolationsAtLeast(n) => hasher.update(&[3]).update(&n.to_le_bytes()),
+                    Condition::TagAny(tags) => {
+                        list(&mut hasher, 4, tags);
+                        &mut hasher
+                    }
+                    Condition::AudienceAny(mask) => hasher.update(&[5, mask]),
+                    Condition::AudienceSizeAtLeast(n) => hasher.update(&[6]).update(&n.to_le_bytes()),
+                    Condition::ContentPresent => hasher.update(&[7]),
+                    Condition::ContentIs(mask) => hasher.update(&[8, mask]),
+                    Condition::TimeBetween(start, end) => {
+                        hasher.update(&[9]).update(&start.to_le_bytes()).update(&end.to_le_bytes())
+                    }
+                    Condition::Weekday(mask) => hasher.update(&[10, mask]),
+                    Condition::LocationIn(codes) => {
+                        list(&mut hasher, 11, codes);
+                        &mut hasher
+                    }
+                    Condition::LocationUnknown => hasher.update(&[12]),
+                };
+            }
+            hasher.update(&[rule.decision.code()]);
+        }
+        hasher.update(&[self.default.code()]);
+        *hasher.finalize().as_bytes()
+    }
+}
+
+/// Key derivation context of rule table measurements
+const MEASUREMENT_CONTEXT: &str = "ARK embedded rule table measurement v1";
+
+/// Expand to the rule pack table generated by the build script
+#[macro_export]
+macro_rules! embedded_rule_pack {
+    () => {{
+        #[allow(unused_imports)]
+        use $crate::rule_table::{Condition, Decision, DecisionTable, Rule};
+        include!(concat!(env!("OUT_DIR"), "/rule_pack.rs"))
+    }};
+}
+
+/// Rule pack compiled into this firmware image
+pub static EMBEDDED_RULE_PACK: DecisionTable = embedded_rule_pack!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: DecisionTable = DecisionTable {
+        digest: [0; 32],
+        rules: &[
+            Rule {
+                name: "corrupting_children",
+                conditions: &[Condition::AudienceAny(age::CHILDREN | age::TEENAGERS), Condition::TagAny(&["CHILD_CORRUPTION"])],
+                decision: Decision::Purge,
+            },
+            Rule {
+                name: "night_broadcast",
+                conditions: &[Condition::TimeBetween(79200, 21600), Condition::ContentIs(1 << content::VIDEO)],
+                decision: Decision::Deny,
+            },
+        ],
+        default: Decision::Allow,
+    };
+
+    fn facts() -> EventFacts<'static> {
+        EventFacts {
+            actor_type: actor::PERSON,
+            trust_level: 0.5,
+            violations: 0,
+            tags: &[],
+            age_groups: None,
+            audience_size: None,
+            content_type: None,
+            seconds_of_day: 12 * 3600,
+            weekday: 0,
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_decides() {
+        assert_eq!(TABLE.evaluate(&facts()), (None, Decision::Allow));
+
+        let tags = ["child_corruption"];
+        let children = EventFacts { tags: &tags, age_groups: Some(age::TEENAGERS), ..facts() };
+        assert_eq!(TABLE.evaluate(&children), (Some("corrupting_children"), Decision::Purge));
+
+        let late = EventFacts { content_type: Some(content::VIDEO), seconds_of_day: 23 * 3600, ..facts() };
+        assert_eq!(TABLE.evaluate(&late), (Some("night_broadcast"), Decision::Deny));
+        let morning = EventFacts { seconds_of_day: 6 * 3600, ..late };
+        assert_eq!(TABLE.evaluate(&morning).1.code(), Decision::Allow.code());
+    }
+
+    #[test]
+    fn test_embedded_pack_passed_build_verification() {
+        // The build script only writes the table once it matched the interpreter
+        assert!(!EMBEDDED_RULE_PACK.rules.is_empty());
+        assert_ne!(EMBEDDED_RULE_PACK.digest, [0; 32]);
+    }
+
+    #[test]
+    fn test_measurement_binds_table_contents() {
+        const RELAXED: DecisionTable = DecisionTable { default: Decision::Deny, ..TABLE };
+        assert_eq!(TABLE.measurement(), TABLE.measurement());
+        assert_ne!(RELAXED.measurement(), TABLE.measurement());
+        assert_ne!(DecisionTable { rules: &TABLE.rules[1..], ..TABLE }.measurement(), TABLE.measurement());
+    }
+}
@@ -0,0 +1,716 @@
+//! Secure Time Source - Trusted Wall Clock with Drift Detection
+//! "To every thing there is a season, and a time to every purpose under the heaven" - Ecclesiastes 3:1
+//!
+//! Wall-clock time comes from the battery-backed RTC and is cross-checked
+//! against the free-running monotonic tick counter, which software cannot
+//! set. Time becomes trusted once a signed time response received over the
+//! sentinel is accepted: it must answer the firmware's own nonce, arrive
+//! within the round-trip bound and lie within the acceptable skew of the
+//! local clock. From then on the trusted time is the synchronized anchor
+//! plus elapsed ticks, and the RTC is corrected to it.
+//!
+//! The RTC drifting away from the trusted time or moving backwards, the
+//! tick counter resetting and rejected synchronizations are anomalies, each
+//! recorded in the secure NVRAM time log. The log also holds periodic
+//! checkpoints of trusted time; their maximum is a floor that no later
+//! reading may fall below, so a clock rolled back across a reboot is caught.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+use crate::boot::BootError;
+use crate::trip_fuse::{Mmio, Registers};
+
+/// RTC controller signature ("RTC\0")
+const RTC_SIGNATURE: u32 = 0x5254_4300;
+
+/// Domain separator of signed time responses
+pub const TIME_SYNC_DOMAIN: &[u8] = b"ARK-TIME-SYNC-v1";
+
+/// RTC register offsets
+mod rtc_reg {
+    /// Controller signature
+    pub const SIGNATURE: usize = 0x00;
+    /// Controller status (`STATUS_*` bits)
+    pub const STATUS: usize = 0x04;
+    /// Seconds since the Unix epoch, low word; reading it latches the high word
+    pub const SECONDS_LO: usize = 0x08;
+    /// Latched high word of the seconds counter
+    pub const SECONDS_HI: usize = 0x0C;
+    /// New seconds value, high word
+    pub const SET_HI: usize = 0x10;
+    /// New seconds value, low word; writing it commits the new time
+    pub const SET_LO: usize = 0x14;
+}
+
+/// Monotonic timer register offsets
+mod timer_reg {
+    /// Tick counter, low word; reading it latches the high word
+    pub const TICKS_LO: usize = 0x00;
+    /// Latched high word of the tick counter
+    pub const TICKS_HI: usize = 0x04;
+    /// Tick frequency in Hz
+    pub const FREQUENCY: usize = 0x08;
+}
+
+/// RTC status: time set and backup supply never lost
+const STATUS_VALID: u32 = 0x01;
+
+/// Time synchronization limits
+#[derive(Debug, Clone, Copy)]
+pub struct TimeConfig {
+    /// Largest accepted difference between a signed time and the local clock
+    pub max_skew_secs: u64,
+    /// Largest tolerated difference between the RTC and the trusted time
+    pub drift_tolerance_secs: u64,
+    /// Longest accepted delay between request and response
+    pub max_round_trip_ms: u64,
+    /// Trusted time between checkpoints written to the log
+    pub checkpoint_interval_secs: u64,
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        TimeConfig {
+            max_skew_secs: 300,
+            drift_tolerance_secs: 5,
+            max_round_trip_ms: 2_000,
+            checkpoint_interval_secs: 3_600,
+        }
+    }
+}
+
+/// Battery-backed real-time clock
+pub struct Rtc<R: Registers = Mmio> {
+    regs: R,
+}
+
+impl<R: Registers> Rtc<R> {
+    /// Driver over the RTC register block
+    pub fn with_registers(regs: R) -> Result<Self, BootError> {
+        if regs.read(rtc_reg::SIGNATURE) != RTC_SIGNATURE {
+            return Err(BootError::HardwareTestFailed);
+        }
+        Ok(Rtc { regs })
+    }
+
+    /// Seconds since the Unix epoch; `None` if the RTC lost its time
+    pub fn seconds(&self) -> Option<u64> {
+        if self.regs.read(rtc_reg::STATUS) & STATUS_VALID == 0 {
+            return None;
+        }
+        let low = self.regs.read(rtc_reg::SECONDS_LO);
+        let high = self.regs.read(rtc_reg::SECONDS_HI);
+        Some(((high as u64) << 32) | low as u64)
+    }
+
+    /// Set the clock
+    pub fn set(&mut self, seconds: u64) {
+        self.regs.write(rtc_reg::SET_HI, (seconds >> 32) as u32);
+        self.regs.write(rtc_reg::SET_LO, seconds as u32);
+    }
+}
+
+/// Free-running tick counter; never set by software
+pub struct MonotonicCounter<R: Registers = Mmio> {
+    regs: R,
+    frequency: u64,
+    last: u64,
+}
+
+impl<R: Registers> MonotonicCounter<R> {
+    /// Driver over the timer register block
+    pub fn with_registers(regs: R) -> Result<Self, BootError> {
+        let frequency = regs.read(timer_reg::FREQUENCY) as u64;
+        if frequency == 0 {
+            return Err(BootError::HardwareTestFailed);
+        }
+        let mut counter = MonotonicCounter { regs, frequency, last: 0 };
+        counter.last = counter.raw();
+        Ok(counter)
+    }
+
+    /// Current tick count; `None` if the counter went backwards since the last read
+    pub fn ticks(&mut self) -> Option<u64> {
+        let now = self.raw();
+        let monotonic = now >= self.last;
+        self.last = now;
+        monotonic.then_some(now)
+    }
+
+    /// Ticks per second
+    pub fn frequency(&self) -> u64 {
+        self.frequency
+    }
+
+    fn raw(&self) -> u64 {
+        let low = self.regs.read(timer_reg::TICKS_LO);
+        let high = self.regs.read(timer_reg::TICKS_HI);
+        ((high as u64) << 32) | low as u64
+    }
+}
+
+/// Time anomaly detected by the secure time source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeAnomaly {
+    /// RTC differs from the trusted time by more than the drift tolerance
+    Drift { rtc: u64, trusted: u64 },
+    /// A clock reading or offered time fell below an earlier one or the persisted floor
+    Rollback { observed: u64, floor: u64 },
+    /// Monotonic tick counter went backwards; the trusted anchor is dropped
+    CounterReset,
+    /// Signed time too far from the local clock
+    SkewExceeded { offered: u64, local: u64 },
+    /// Time response signature did not verify
+    BadSignature,
+    /// Time response did not answer the pending request, or arrived too late
+    StaleResponse,
+}
+
+impl TimeAnomaly {
+    fn code(&self) -> u32 {
+        match self {
+            TimeAnomaly::Drift { .. } => KIND_DRIFT,
+            TimeAnomaly::Rollback { .. } => KIND_ROLLBACK,
+            TimeAnomaly::CounterReset => KIND_COUNTER_RESET,
+            TimeAnomaly::SkewExceeded { .. } => KIND_SKEW,
+            TimeAnomaly::BadSignature => KIND_BAD_SIGNATURE,
+            TimeAnomaly::StaleResponse => KIND_STALE,
+        }
+    }
+
+    fn values(&self) -> (u64, u64) {
+        match *self {
+            TimeAnomaly::Drift { rtc, trusted } => (rtc, trusted),
+            TimeAnomaly::Rollback { observed, floor } => (observed, floor),
+            TimeAnomaly::SkewExceeded { offered, local } => (offered, local),
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Time request sent to the time service over the sentinel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRequest {
+    /// Fresh nonce the response must echo
+    pub nonce: [u8; 16],
+    /// Tick count when the request was issued
+    pub issued_at_tick: u64,
+}
+
+/// Signed response of the time service
+#[derive(Debug, Clone, Copy)]
+pub struct SignedTime {
+    /// Nonce of the request being answered
+    pub nonce: [u8; 16],
+    /// Seconds since the Unix epoch
+    pub unix_seconds: u64,
+    /// Ed25519 signature over `signed_message`
+    pub signature: [u8; 64],
+}
+
+impl SignedTime {
+    /// Message covered by the signature
+    pub fn signed_message(&self) -> [u8; 40] {
+        let mut message = [0u8; 40];
+        message[..16].copy_from_slice(TIME_SYNC_DOMAIN);
+        message[16..32].copy_from_slice(&self.nonce);
+        message[32..40].copy_from_slice(&self.unix_seconds.to_le_bytes());
+        message
+    }
+}
+
+/// Persistent time log in secure NVRAM
+///
+/// A ring of fixed-size records like the trip log: checkpoints and
+/// synchronizations of trusted time, and anomalies with the observed and
+/// expected times.
+pub struct TimeLog<S: Registers = Mmio> {
+    storage: S,
+    sequence: u32,
+    floor: u64,
+}
+
+const LOG_SLOTS: usize = 32;
+const LOG_RECORD_WORDS: usize = 8;
+const LOG_MAGIC: u32 = 0x5449_4D45;
+const KIND_CHECKPOINT: u32 = 1;
+const KIND_SYNC: u32 = 2;
+const KIND_DRIFT: u32 = 3;
+const KIND_ROLLBACK: u32 = 4;
+const KIND_COUNTER_RESET: u32 = 5;
+const KIND_SKEW: u32 = 6;
+const KIND_BAD_SIGNATURE: u32 = 7;
+const KIND_STALE: u32 = 8;
+
+/// One time log record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeLogRecord {
+    /// Log sequence number
+    pub sequence: u32,
+    /// Record kind (`KIND_*`)
+    pub kind: u32,
+    /// Trusted time for checkpoints and syncs, observed time for anomalies
+    pub observed: u64,
+    /// Expected time or floor for anomalies
+    pub expected: u64,
+}
+
+impl TimeLogRecord {
+    fn checksum(&self) -> u32 {
+        let mut bytes = [0u8; 24];
+        bytes[0..4].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.kind.to_le_bytes());
+        bytes[8..16].copy_from_slice(&self.observed.to_le_bytes());
+        bytes[16..24].copy_from_slice(&self.expected.to_le_bytes());
+        let h = blake3::hash(&bytes);
+        let h = h.as_bytes();
+        u32::from_le_bytes([h[0], h[1], h[2], h[3]])
+    }
+
+    fn words(&self) -> [u32; LOG_RECORD_WORDS] {
+        [
+            LOG_MAGIC,
+            self.sequence,
+            self.kind,
+            self.observed as u32,
+            (self.observed >> 32) as u32,
+            self.expected as u32,
+            (self.expected >> 32) as u32,
+            self.checksum(),
+        ]
+    }
+
+    /// Whether the record holds trusted time
+    fn is_trusted_time(&self) -> bool {
+        self.kind == KIND_CHECKPOINT || self.kind == KIND_SYNC
+    }
+}
+
+impl<S: Registers> TimeLog<S> {
+    /// Open the log, recovering the sequence number and the time floor
+    pub fn open(storage: S) -> Self {
+        let mut log = TimeLog { storage, sequence: 0, floor: 0 };
+        for slot in 0..LOG_SLOTS {
+            if let Some(record) = log.read(slot) {
+                log.sequence = log.sequence.max(record.sequence);
+                if record.is_trusted_time() {
+                    log.floor = log.floor.max(record.observed);
+                }
+            }
+        }
+        log
+    }
+
+    /// Latest trusted time ever recorded
+    pub fn floor(&self) -> u64 {
+        self.floor
+    }
+
+    /// Valid record in `slot`
+    pub fn read(&self, slot: usize) -> Option<TimeLogRecord> {
+        let word = |i: usize| self.storage.read((slot * LOG_RECORD_WORDS + i) * 4);
+        if word(0) != LOG_MAGIC {
+            return None;
+        }
+        let record = TimeLogRecord {
+            sequence: word(1),
+            kind: word(2),
+            observed: ((word(4) as u64) << 32) | word(3) as u64,
+            expected: ((word(6) as u64) << 32) | word(5) as u64,
+        };
+        (record.checksum() == word(7)).then_some(record)
+    }
+
+    /// Most recent record
+    pub fn last(&self) -> Option<TimeLogRecord> {
+        self.read(self.sequence as usize % LOG_SLOTS).filter(|record| record.sequence == self.sequence)
+    }
+
+    fn append(&mut self, kind: u32, observed: u64, expected: u64) {
+        self.sequence = self.sequence.wrapping_add(1);
+        let record = TimeLogRecord { sequence: self.sequence, kind, observed, expected };
+        let slot = self.sequence as usize % LOG_SLOTS;
+
+        // Magic last so a torn write leaves the slot invalid
+        let words = record.words();
+        for (i, word) in words.iter().enumerate().skip(1) {
+            self.storage.write((slot * LOG_RECORD_WORDS + i) * 4, *word);
+        }
+        self.storage.write(slot * LOG_RECORD_WORDS * 4, words[0]);
+
+        if record.is_trusted_time() {
+            self.floor = self.floor.max(observed);
+        }
+    }
+}
+
+/// Trusted time at a tick count
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    seconds: u64,
+    tick: u64,
+}
+
+/// Secure time source
+pub struct SecureTime<R: Registers = Mmio, T: Registers = Mmio, S: Registers = Mmio> {
+    rtc: Rtc<R>,
+    counter: MonotonicCounter<T>,
+    log: TimeLog<S>,
+    config: TimeConfig,
+    anchor: Option<Anchor>,
+    pending: Option<TimeRequest>,
+    last_rtc: u64,
+    last_checkpoint: u64,
+}
+
+impl SecureTime {
+    /// Initialize the RTC, tick counter and NVRAM time log
+    pub fn initialize(rtc_address: usize, timer_address: usize, log_address: usize) -> Result<Self, BootError> {
+        SecureTime::with_registers(
+            Mmio::new(rtc_address),
+            Mmio::new(timer_address),
+            Mmio::new(log_address),
+            TimeConfig::default(),
+        )
+    }
+}
+
+impl<R: Registers, T: Registers, S: Registers> SecureTime<R, T, S> {
+    /// Initialize over arbitrary register blocks
+    ///
+    /// An RTC reading below the persisted floor is logged as a rollback.
+    pub fn with_registers(rtc: R, timer: T, storage: S, config: TimeConfig) -> Result<Self, BootError> {
+        let mut time = SecureTime {
+            rtc: Rtc::with_registers(rtc)?,
+            counter: MonotonicCounter::with_registers(timer)?,
+            log: TimeLog::open(storage),
+            config,
+            anchor: None,
+            pending: None,
+            last_rtc: 0,
+            last_checkpoint: 0,
+        };
+        time.last_checkpoint = time.log.floor();
+        time.last_rtc = time.log.floor();
+        if let Some(rtc) = time.rtc.seconds() {
+            if rtc < time.log.floor() {
+                time.flag(TimeAnomaly::Rollback { observed: rtc, floor: time.log.floor() });
+            } else {
+                time.last_rtc = rtc;
+            }
+        }
+        Ok(time)
+    }
+
+    /// Whether time has been synchronized since boot
+    pub fn is_trusted(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Current time in seconds since the Unix epoch
+    ///
+    /// Synchronized time while trusted; otherwise the RTC, unless it lost
+    /// its time or reads below the persisted floor.
+    pub fn now(&mut self) -> Option<u64> {
+        match self.trusted_now() {
+            Some(now) => Some(now),
+            None => self.rtc.seconds().filter(|&rtc| rtc >= self.log.floor()),
+        }
+    }
+
+    /// Cross-check the RTC against the tick counter and checkpoint trusted time
+    ///
+    /// Called periodically; returns the anomaly detected, if any, after
+    /// recording it.
+    pub fn check(&mut self) -> Option<TimeAnomaly> {
+        let anchored = self.anchor.is_some();
+        let trusted = self.trusted_now();
+        if anchored && trusted.is_none() {
+            return Some(TimeAnomaly::CounterReset);
+        }
+        let rtc = self.rtc.seconds();
+
+        if let Some(rtc) = rtc {
+            let floor = self.last_rtc.max(self.log.floor());
+            if rtc < floor {
+                if let Some(trusted) = trusted {
+                    self.rtc.set(trusted);
+                }
+                return Some(self.flag(TimeAnomaly::Rollback { observed: rtc, floor }));
+            }
+            self.last_rtc = rtc;
+        }
+
+        let trusted = trusted?;
+        if trusted.saturating_sub(self.last_checkpoint) >= self.config.checkpoint_interval_secs {
+            self.log.append(KIND_CHECKPOINT, trusted, 0);
+            self.last_checkpoint = trusted;
+        }
+
+        match rtc {
+            Some(rtc) if rtc.abs_diff(trusted) <= self.config.drift_tolerance_secs => None,
+            rtc => {
+                // Correct the RTC so the next reading agrees with trusted time
+                self.rtc.set(trusted);
+                self.last_rtc = trusted;
+                Some(self.flag(TimeAnomaly::Drift { rtc: rtc.unwrap_or(0), trusted }))
+            }
+        }
+    }
+
+    /// Start a synchronization; send the request to the time service over the sentinel
+    ///
+    /// `nonce` must be fresh hardware entropy. A new request replaces any
+    /// pending one. The round trip is timed from a tick read here, in the
+    /// same call that advances the counter's last reading; a counter that
+    /// went backwards drops the anchor and the pending request instead.
+    pub fn begin_sync(&mut self, nonce: [u8; 16]) -> Result<TimeRequest, TimeAnomaly> {
+        self.pending = None;
+        let Some(issued_at_tick) = self.counter.ticks() else {
+            self.anchor = None;
+            return Err(self.flag(TimeAnomaly::CounterReset));
+        };
+        let request = TimeRequest { nonce, issued_at_tick };
+        self.pending = Some(request);
+        Ok(request)
+    }
+
+    /// Accept the signed response to the pending request
+    ///
+    /// Returns the synchronized time, or the anomaly that rejected the
+    /// response after recording it.
+    pub fn complete_sync(&mut self, response: &SignedTime, time_service: &VerifyingKey) -> Result<u64, TimeAnomaly> {
+        let request = match self.pending.take() {
+            Some(request) if request.nonce == response.nonce => request,
+            _ => return Err(self.flag(TimeAnomaly::StaleResponse)),
+        };
+        let Some(tick) = self.counter.ticks() else {
+            self.anchor = None;
+            return Err(self.flag(TimeAnomaly::CounterReset));
+        };
+        let round_trip_ms = tick.saturating_sub(request.issued_at_tick).saturating_mul(1000) / self.counter.frequency();
+        if round_trip_ms > self.config.max_round_trip_ms {
+            return Err(self.flag(TimeAnomaly::StaleResponse));
+        }
+
+        let signature = Signature::from_bytes(&response.signature);
+        if time_service.verify(&response.signed_message(), &signature).is_err() {
+            return Err(self.flag(TimeAnomaly::BadSignature));
+        }
+
+        let offered = response.unix_seconds;
+        if offered < self.log.floor() {
+            return Err(self.flag(TimeAnomaly::Rollback { observed: offered, floor: self.log.floor() }));
+        }
+        if let Some(local) = self.now() {
+            if offered.abs_diff(local) > self.config.max_skew_secs {
+                return Err(self.flag(TimeAnomaly::SkewExceeded { offered, local }));
+            }
+        }
+
+        self.anchor = Some(Anchor { seconds: offered, tick });
+        self.rtc.set(offered);
+        self.last_rtc = offered;
+        self.last_checkpoint = offered;
+        self.log.append(KIND_SYNC, offered, 0);
+        Ok(offered)
+    }
+
+    /// Time log
+    pub fn log(&self) -> &TimeLog<S> {
+        &self.log
+    }
+
+    /// Anchor plus elapsed ticks
+    ///
+    /// A counter that went backwards drops the anchor and is recorded, so
+    /// time stays untrusted until the next synchronization.
+    fn trusted_now(&mut self) -> Option<u64> {
+        let anchor = self.anchor?;
+        match self.counter.ticks().filter(|&tick| tick >= anchor.tick) {
+            Some(tick) => Some(anchor.seconds + (tick - anchor.tick) / self.counter.frequency()),
+            None => {
+                self.anchor = None;
+                self.flag(TimeAnomaly::CounterReset);
+                None
+            }
+        }
+    }
+
+    fn flag(&mut self, anomaly: TimeAnomaly) -> TimeAnomaly {
+        let (observed, expected) = anomaly.values();
+        self.log.append(anomaly.code(), observed, expected);
+        anomaly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    const HZ: u32 = 1_000;
+    const NOW: u64 = 1_750_000_000;
+
+    struct SimRtc {
+        regs: [u32; 6],
+        pending_high: u32,
+    }
+
+    impl SimRtc {
+        fn new(seconds: Option<u64>) -> Self {
+            let mut regs = [0u32; 6];
+            regs[rtc_reg::SIGNATURE / 4] = RTC_SIGNATURE;
+            if let Some(seconds) = seconds {
+                regs[rtc_reg::STATUS / 4] = STATUS_VALID;
+                regs[rtc_reg::SECONDS_LO / 4] = seconds as u32;
+                regs[rtc_reg::SECONDS_HI / 4] = (seconds >> 32) as u32;
+            }
+            SimRtc { regs, pending_high: 0 }
+        }
+
+        fn advance(&mut self, secs: u64) {
+            let now = ((self.regs[3] as u64) << 32 | self.regs[2] as u64) + secs;
+            self.regs[2] = now as u32;
+            self.regs[3] = (now >> 32) as u32;
+        }
+    }
+
+    impl Registers for &mut SimRtc {
+        fn read(&self, offset: usize) -> u32 {
+            self.regs[offset / 4]
+        }
+
+        fn write(&mut self, offset: usize, value: u32) {
+            match offset {
+                rtc_reg::SET_HI => self.pending_high = value,
+                rtc_reg::SET_LO => {
+                    self.regs[rtc_reg::SECONDS_LO / 4] = value;
+                    self.regs[rtc_reg::SECONDS_HI / 4] = self.pending_high;
+                    self.regs[rtc_reg::STATUS / 4] |= STATUS_VALID;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    struct SimTimer([u32; 3]);
+
+    impl SimTimer {
+        fn advance_ms(&mut self, ms: u64) {
+            let ticks = ((self.0[1] as u64) << 32 | self.0[0] as u64) + ms * HZ as u64 / 1000;
+            self.0[0] = ticks as u32;
+            self.0[1] = (ticks >> 32) as u32;
+        }
+    }
+
+    impl Registers for &mut SimTimer {
+        fn read(&self, offset: usize) -> u32 {
+            self.0[offset / 4]
+        }
+
+        fn write(&mut self, _offset: usize, _value: u32) {}
+    }
+
+    struct SimNvram([u32; LOG_SLOTS * LOG_RECORD_WORDS]);
+
+    impl Registers for &mut SimNvram {
+        fn read(&self, offset: usize) -> u32 {
+            self.0[offset / 4]
+        }
+
+        fn write(&mut self, offset: usize, value: u32) {
+            self.0[offset / 4] = value;
+        }
+    }
+
+    fn signed(key: &SigningKey, nonce: [u8; 16], unix_seconds: u64) -> SignedTime {
+        let mut response = SignedTime { nonce, unix_seconds, signature: [0; 64] };
+        response.signature = key.sign(&response.signed_message()).to_bytes();
+        response
+    }
+
+    #[test]
+    fn test_sync_then_drift_and_rollback_across_reboot() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut rtc = SimRtc::new(Some(NOW - 10));
+        let mut timer = SimTimer([0, 0, HZ]);
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        {
+            let mut time = SecureTime::with_registers(&mut rtc, &mut timer, &mut nvram, TimeConfig::default()).unwrap();
+            assert!(!time.is_trusted());
+            assert_eq!(time.now(), Some(NOW - 10));
+
+            let request = time.begin_sync([1; 16]).unwrap();
+            time.counter.regs.advance_ms(150);
+            assert_eq!(time.complete_sync(&signed(&key, request.nonce, NOW), &key.verifying_key()), Ok(NOW));
+            assert!(time.is_trusted());
+            assert_eq!(time.check(), None);
+
+            // The RTC runs fast by a minute while ticks advance ten seconds
+            time.counter.regs.advance_ms(10_000);
+            time.rtc.regs.advance(70);
+            assert_eq!(time.check(), Some(TimeAnomaly::Drift { rtc: NOW + 70, trusted: NOW + 10 }));
+            assert_eq!(time.now(), Some(NOW + 10));
+            assert_eq!(time.log().last().unwrap().kind, KIND_DRIFT);
+        }
+
+        // Rolled back while powered down: below the floor persisted by the sync
+        let mut rtc = SimRtc::new(Some(NOW - 3_600));
+        let mut time = SecureTime::with_registers(&mut rtc, &mut timer, &mut nvram, TimeConfig::default()).unwrap();
+        assert_eq!(time.log().floor(), NOW);
+        assert_eq!(time.log().last().unwrap().kind, KIND_ROLLBACK);
+        assert_eq!(time.now(), None);
+    }
+
+    #[test]
+    fn test_sync_rejections_are_logged() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let forger = SigningKey::from_bytes(&[9; 32]);
+        let mut rtc = SimRtc::new(Some(NOW));
+        let mut timer = SimTimer([0, 0, HZ]);
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        let mut time = SecureTime::with_registers(&mut rtc, &mut timer, &mut nvram, TimeConfig::default()).unwrap();
+        let verifying_key = key.verifying_key();
+
+        // No pending request
+        assert_eq!(time.complete_sync(&signed(&key, [1; 16], NOW), &verifying_key), Err(TimeAnomaly::StaleResponse));
+
+        let request = time.begin_sync([2; 16]).unwrap();
+        assert_eq!(time.complete_sync(&signed(&forger, request.nonce, NOW), &verifying_key), Err(TimeAnomaly::BadSignature));
+
+        let request = time.begin_sync([3; 16]).unwrap();
+        assert_eq!(
+            time.complete_sync(&signed(&key, request.nonce, NOW + 3_600), &verifying_key),
+            Err(TimeAnomaly::SkewExceeded { offered: NOW + 3_600, local: NOW })
+        );
+
+        let request = time.begin_sync([4; 16]).unwrap();
+        time.counter.regs.advance_ms(5_000);
+        assert_eq!(time.complete_sync(&signed(&key, request.nonce, NOW), &verifying_key), Err(TimeAnomaly::StaleResponse));
+
+        assert!(!time.is_trusted());
+        assert_eq!(time.log().last().unwrap().sequence, 4);
+    }
+
+    #[test]
+    fn test_round_trip_timed_from_the_request() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut rtc = SimRtc::new(Some(NOW));
+        let mut timer = SimTimer([0, 0, HZ]);
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        let mut time = SecureTime::with_registers(&mut rtc, &mut timer, &mut nvram, TimeConfig::default()).unwrap();
+
+        // Ticks elapsed since the counter was last read are not part of the round trip
+        time.counter.regs.advance_ms(60_000);
+        let request = time.begin_sync([5; 16]).unwrap();
+        time.counter.regs.advance_ms(150);
+        assert_eq!(time.complete_sync(&signed(&key, request.nonce, NOW), &key.verifying_key()), Ok(NOW));
+
+        // A counter that went backwards refuses to start a synchronization
+        time.counter.regs.0 = [0, 0, HZ];
+        assert_eq!(time.begin_sync([6; 16]), Err(TimeAnomaly::CounterReset));
+        assert!(!time.is_trusted());
+        assert_eq!(time.complete_sync(&signed(&key, [6; 16], NOW), &key.verifying_key()), Err(TimeAnomaly::StaleResponse));
+    }
+}
@@ -62,7 +62,9 @@ impl KillFuseProtection {
     }
     
     /// Verify protection is active and no kill-switches detected
-    pub fn verify_protection(&mut self) -> Result<(), BootError> {
+    ///
+    /// `now` is the secure time source reading, if the time is known.
+    pub fn verify_protection(&mut self, now: Option<u64>) -> Result<(), BootError> {
         if !self.enabled {
             return Err(BootError::KillSwitchDetected);
         }
@@ -77,7 +79,9 @@ impl KillFuseProtection {
         self.verify_memory_integrity()?;
         
         // Update last check timestamp
-        self.last_check = self.get_current_time();
+        if let Some(now) = now {
+            self.last_check = now;
+        }
         
         Ok(())
     }
@@ -229,12 +233,6 @@ impl KillFuseProtection {
         // This would read actual memory contents
         [0u8; 32] // Placeholder
     }
-    
-    /// Get current system time
-    fn get_current_time(&self) -> u64 {
-        // Get current time from hardware timer
-        0 // Placeholder
-    }
 }
 
 impl Default for MemoryRegion {
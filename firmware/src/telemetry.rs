@@ -0,0 +1,246 @@
+//! Hardware Error Telemetry - Recorded Component Faults and Escalation
+//! "A prudent man foreseeth the evil, and hideth himself; but the simple pass on, and are punished" - Proverbs 22:3
+//!
+//! Errors returned by the hardware components are recorded here instead of
+//! vanishing at the call site. Each record names the component, the error
+//! and its numeric code, a sequence number and the time it happened, when
+//! the clock could tell. The ring keeps the newest `TELEMETRY_CAPACITY`
+//! records until the application layer drains them; a record overwritten
+//! before it was drained is counted as dropped. The per-component counters
+//! cover every error since boot, drained or not, and are included in
+//! attestation claims and the health report.
+//!
+//! Escalation rules turn repeated failures of a component into a change of
+//! operating mode: by default the third PUF Heart failure puts the device
+//! in degraded mode, where PUF challenges are refused because keys derived
+//! from a failing PUF cannot be trusted. The mode never steps back down
+//! before reboot.
+
+use heapless::{Deque, Vec};
+
+use crate::hardware::HardwareError;
+
+/// Records kept until drained
+pub const TELEMETRY_CAPACITY: usize = 32;
+
+/// Length of `ErrorCounters::to_bytes`
+pub const ERROR_COUNTERS_LEN: usize = 4 * (Component::COUNT + 2);
+
+/// Hardware component an error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Component {
+    /// PUF Heart (challenges and entropy)
+    PufHeart = 0,
+    /// Optic Gate
+    OpticGate = 1,
+    /// Tri-Compute Core
+    TriCompute = 2,
+    /// Trip Fuse Mesh
+    TripFuse = 3,
+    /// Secure time source
+    SecureTime = 4,
+}
+
+impl Component {
+    /// Number of components
+    pub const COUNT: usize = 5;
+}
+
+/// Operating mode, ordered from least to most restricted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum OperatingMode {
+    /// Every component in service
+    Normal = 0,
+    /// PUF challenges refused; other components in service
+    Degraded = 1,
+}
+
+/// Mode entered once a component has failed `failures` times since boot
+#[derive(Debug, Clone, Copy)]
+pub struct EscalationRule {
+    /// Component watched
+    pub component: Component,
+    /// Errors since boot that trigger the rule
+    pub failures: u32,
+    /// Mode entered
+    pub mode: OperatingMode,
+}
+
+/// Rules applied by the firmware
+pub const DEFAULT_ESCALATION: &[EscalationRule] = &[EscalationRule {
+    component: Component::PufHeart,
+    failures: 3,
+    mode: OperatingMode::Degraded,
+}];
+
+/// One recorded hardware error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorRecord {
+    /// Position among all errors since boot, starting at 0
+    pub sequence: u32,
+    /// Component that failed
+    pub component: Component,
+    /// Error returned
+    pub error: HardwareError,
+    /// `HardwareError::code` of the error
+    pub code: u16,
+    /// Seconds since the Unix epoch; `None` while the time was unknown
+    pub timestamp: Option<u64>,
+}
+
+/// Error counts since boot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ErrorCounters {
+    /// Errors of each component, indexed by `Component as usize`
+    pub per_component: [u32; Component::COUNT],
+    /// Records overwritten before they were drained
+    pub dropped: u32,
+}
+
+impl ErrorCounters {
+    /// Errors of `component`
+    pub fn of(&self, component: Component) -> u32 {
+        self.per_component[component as usize]
+    }
+
+    /// Errors of every component
+    pub fn total(&self) -> u32 {
+        self.per_component.iter().fold(0u32, |total, &count| total.saturating_add(count))
+    }
+
+    /// Fixed-size encoding, with the operating mode, included in attestation reports
+    pub fn to_bytes(&self, mode: OperatingMode) -> [u8; ERROR_COUNTERS_LEN] {
+        let mut out = [0u8; ERROR_COUNTERS_LEN];
+        out[0..4].copy_from_slice(&(mode as u32).to_le_bytes());
+        for (i, count) in self.per_component.iter().enumerate() {
+            out[4 + i * 4..8 + i * 4].copy_from_slice(&count.to_le_bytes());
+        }
+        out[ERROR_COUNTERS_LEN - 4..].copy_from_slice(&self.dropped.to_le_bytes());
+        out
+    }
+}
+
+/// Ring of recorded errors with counters and escalation
+pub struct ErrorTelemetry {
+    ring: Deque<ErrorRecord, TELEMETRY_CAPACITY>,
+    counters: ErrorCounters,
+    next_sequence: u32,
+    rules: &'static [EscalationRule],
+    mode: OperatingMode,
+}
+
+impl ErrorTelemetry {
+    /// Empty telemetry escalating by `rules`
+    pub const fn new(rules: &'static [EscalationRule]) -> Self {
+        ErrorTelemetry {
+            ring: Deque::new(),
+            counters: ErrorCounters { per_component: [0; Component::COUNT], dropped: 0 },
+            next_sequence: 0,
+            rules,
+            mode: OperatingMode::Normal,
+        }
+    }
+
+    /// Record an error, returning the new mode if a rule escalated it
+    pub fn record(&mut self, component: Component, error: HardwareError, timestamp: Option<u64>) -> Option<OperatingMode> {
+        let record = ErrorRecord {
+            sequence: self.next_sequence,
+            component,
+            error,
+            code: error.code(),
+            timestamp,
+        };
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        if self.ring.is_full() {
+            self.ring.pop_front();
+            self.counters.dropped = self.counters.dropped.saturating_add(1);
+        }
+        // Cannot fail: a full ring was made room in above
+        let _ = self.ring.push_back(record);
+
+        let count = &mut self.counters.per_component[component as usize];
+        *count = count.saturating_add(1);
+        let failures = *count;
+
+        let escalated = self.rules
+            .iter()
+            .filter(|rule| rule.component == component && failures >= rule.failures)
+            .map(|rule| rule.mode)
+            .max()
+            .filter(|&mode| mode > self.mode)?;
+        self.mode = escalated;
+        Some(escalated)
+    }
+
+    /// Take the records not yet drained, oldest first
+    pub fn drain(&mut self) -> Vec<ErrorRecord, TELEMETRY_CAPACITY> {
+        let mut records = Vec::new();
+        while let Some(record) = self.ring.pop_front() {
+            // Cannot fail: the ring holds at most as many records
+            let _ = records.push(record);
+        }
+        records
+    }
+
+    /// Records waiting to be drained
+    pub fn pending(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Error counts since boot
+    pub fn counters(&self) -> ErrorCounters {
+        self.counters
+    }
+
+    /// Current operating mode
+    pub fn mode(&self) -> OperatingMode {
+        self.mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_newest_and_counts_drops() {
+        let mut telemetry = ErrorTelemetry::new(&[]);
+        for i in 0..TELEMETRY_CAPACITY as u64 + 3 {
+            telemetry.record(Component::OpticGate, HardwareError::TimingViolation, Some(1_700_000_000 + i));
+        }
+        telemetry.record(Component::TripFuse, HardwareError::IntegrityFailed, None);
+
+        let records = telemetry.drain();
+        assert_eq!(records.len(), TELEMETRY_CAPACITY);
+        assert_eq!(records[0].sequence, 4);
+        let last = records[TELEMETRY_CAPACITY - 1];
+        assert_eq!((last.component, last.code, last.timestamp), (Component::TripFuse, 3, None));
+        assert_eq!(telemetry.pending(), 0);
+
+        // Counters outlive the drain
+        let counters = telemetry.counters();
+        assert_eq!(counters.of(Component::OpticGate), TELEMETRY_CAPACITY as u32 + 3);
+        assert_eq!((counters.total(), counters.dropped), (TELEMETRY_CAPACITY as u32 + 4, 4));
+        let bytes = counters.to_bytes(telemetry.mode());
+        assert_eq!(bytes[0..4], 0u32.to_le_bytes());
+        assert_eq!(bytes[8..12], (TELEMETRY_CAPACITY as u32 + 3).to_le_bytes());
+        assert_eq!(bytes[ERROR_COUNTERS_LEN - 4..], 4u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_puf_failures_escalate_to_degraded() {
+        let mut telemetry = ErrorTelemetry::new(DEFAULT_ESCALATION);
+        assert_eq!(telemetry.record(Component::OpticGate, HardwareError::HardwareFault, None), None);
+        assert_eq!(telemetry.record(Component::PufHeart, HardwareError::Timeout, None), None);
+        assert_eq!(telemetry.record(Component::PufHeart, HardwareError::Timeout, None), None);
+        assert_eq!(telemetry.record(Component::PufHeart, HardwareError::InsufficientEntropy, None), Some(OperatingMode::Degraded));
+        assert_eq!(telemetry.mode(), OperatingMode::Degraded);
+
+        // Already degraded: no further escalation, and draining does not restore the mode
+        assert_eq!(telemetry.record(Component::PufHeart, HardwareError::Timeout, None), None);
+        telemetry.drain();
+        assert_eq!(telemetry.mode(), OperatingMode::Degraded);
+    }
+}
@@ -0,0 +1,569 @@
+//! Trip Fuse Mesh Driver - Anti-Tamper Mesh Control
+//! "Except the Lord keep the city, the watchman waketh but in vain" - Psalm 127:1
+//!
+//! The mesh is a set of 32 conductive segments wrapped around the secure
+//! package. While armed, any broken segment or hardware glitch sensor trips
+//! the fuse. Arming, disarming and clearing a trip each take two steps: the
+//! caller requests a challenge from the fuse controller and must echo it back
+//! with the same operation, so a single stray register write or call can
+//! never change the fuse state. Trip events are latched and persisted to the
+//! secure NVRAM log, so a trip survives reboot until explicitly cleared.
+
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::boot::BootError;
+use crate::hardware::HardwareError;
+
+/// Number of mesh segments
+pub const SEGMENT_COUNT: usize = 32;
+
+/// Controller signature ("TFM\0")
+const FUSE_SIGNATURE: u32 = 0x5446_4D00;
+
+/// Register offsets relative to the fuse base address
+mod reg {
+    /// First of `SEGMENT_COUNT` segment status registers
+    pub const SEGMENT_STATUS: usize = 0x000;
+    /// Controller signature
+    pub const SIGNATURE: usize = 0x100;
+    /// Controller status (`STATUS_*` bits)
+    pub const STATUS: usize = 0x104;
+    /// Write an operation code to request a challenge, read the nonce back
+    pub const CHALLENGE: usize = 0x108;
+    /// Unlock and confirmation key writes
+    pub const ARM_KEY: usize = 0x10C;
+    /// Hardware cause code of the last trip
+    pub const TRIP_CAUSE: usize = 0x110;
+    /// Segments broken when the last trip fired
+    pub const TRIP_SEGMENTS: usize = 0x114;
+}
+
+/// Segment status: continuity intact
+const SEGMENT_INTACT: u32 = 0x01;
+
+/// Controller status: mesh armed
+const STATUS_ARMED: u32 = 0x01;
+/// Controller status: trip fired
+const STATUS_TRIPPED: u32 = 0x02;
+
+/// First key write of every confirmation sequence
+const ARM_UNLOCK: u32 = 0xA5C3_3C5A;
+
+/// Register access for a memory-mapped block
+pub trait Registers {
+    /// Read the 32-bit register at `offset`
+    fn read(&self, offset: usize) -> u32;
+    /// Write the 32-bit register at `offset`
+    fn write(&mut self, offset: usize, value: u32);
+}
+
+/// Volatile MMIO register block
+pub struct Mmio {
+    base_address: usize,
+}
+
+impl Mmio {
+    /// Register block at a fixed physical address
+    pub const fn new(base_address: usize) -> Self {
+        Mmio { base_address }
+    }
+}
+
+impl Registers for Mmio {
+    fn read(&self, offset: usize) -> u32 {
+        // SAFETY: base_address comes from the memory map and the offsets are
+        // word-aligned registers within the peripheral's window
+        unsafe { read_volatile((self.base_address + offset) as *const u32) }
+    }
+
+    fn write(&mut self, offset: usize, value: u32) {
+        // SAFETY: as for `read`
+        unsafe { write_volatile((self.base_address + offset) as *mut u32, value) }
+    }
+}
+
+/// Why the fuse tripped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum TripCause {
+    /// One or more mesh segments lost continuity
+    MeshBreach = 1,
+    /// Supply voltage glitch sensor fired
+    VoltageGlitch = 2,
+    /// Clock glitch sensor fired
+    ClockGlitch = 3,
+    /// Temperature out of range
+    Temperature = 4,
+    /// Tripped on command from the firmware
+    Commanded = 5,
+    /// Cause code not recognized
+    Unknown = 0xFF,
+}
+
+impl TripCause {
+    /// Decode a hardware cause code
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => TripCause::MeshBreach,
+            2 => TripCause::VoltageGlitch,
+            3 => TripCause::ClockGlitch,
+            4 => TripCause::Temperature,
+            5 => TripCause::Commanded,
+            _ => TripCause::Unknown,
+        }
+    }
+}
+
+/// Latched trip event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TripEvent {
+    /// Log sequence number
+    pub sequence: u32,
+    /// Cause of the trip
+    pub cause: TripCause,
+    /// Bit mask of segments broken when the trip fired
+    pub broken_segments: u32,
+}
+
+/// Operation guarded by the confirmation sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum FuseOperation {
+    /// Arm the mesh
+    Arm = 0x4152_4D00,
+    /// Disarm the mesh
+    Disarm = 0x4449_5300,
+    /// Clear a latched trip
+    ClearLatch = 0x434C_5200,
+}
+
+/// Challenge issued by `begin`, echoed back to `confirm`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuseChallenge {
+    /// Operation being confirmed
+    pub operation: FuseOperation,
+    /// Hardware nonce
+    pub nonce: u32,
+}
+
+/// Fuse state for self-test and attestation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuseState {
+    /// Mesh armed
+    pub armed: bool,
+    /// Bit mask of segments with continuity
+    pub intact_segments: u32,
+    /// Latched trip, if any
+    pub latched: Option<TripEvent>,
+}
+
+impl FuseState {
+    /// Fixed-size encoding included in attestation reports
+    pub fn to_bytes(&self) -> [u8; 16] {
+        let (sequence, cause, broken) = match self.latched {
+            Some(event) => (event.sequence, event.cause as u32, event.broken_segments),
+            None => (0, 0, 0),
+        };
+        let mut out = [0u8; 16];
+        out[0..4].copy_from_slice(&(self.armed as u32).to_le_bytes());
+        out[4..8].copy_from_slice(&self.intact_segments.to_le_bytes());
+        out[8..12].copy_from_slice(&(sequence | ((cause & 0xFF) << 24)).to_le_bytes());
+        out[12..16].copy_from_slice(&broken.to_le_bytes());
+        out
+    }
+}
+
+/// Persistent trip event log in secure NVRAM
+///
+/// A ring of fixed-size records; each carries a sequence number and a
+/// truncated BLAKE3 checksum, and the valid record with the highest sequence
+/// number is the current one.
+pub struct TripLog<S: Registers = Mmio> {
+    storage: S,
+    last: Option<LogRecord>,
+}
+
+const LOG_SLOTS: usize = 16;
+const LOG_RECORD_WORDS: usize = 8;
+const LOG_MAGIC: u32 = 0x5452_4950;
+const KIND_TRIP: u32 = 1;
+const KIND_CLEAR: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LogRecord {
+    sequence: u32,
+    kind: u32,
+    cause: u32,
+    segments: u32,
+}
+
+impl LogRecord {
+    fn checksum(&self) -> [u32; 3] {
+        let mut bytes = [0u8; 16];
+        bytes[0..4].copy_from_slice(&self.sequence.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.kind.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.cause.to_le_bytes());
+        bytes[12..16].copy_from_slice(&self.segments.to_le_bytes());
+        let hash = blake3::hash(&bytes);
+        let h = hash.as_bytes();
+        [
+            u32::from_le_bytes([h[0], h[1], h[2], h[3]]),
+            u32::from_le_bytes([h[4], h[5], h[6], h[7]]),
+            u32::from_le_bytes([h[8], h[9], h[10], h[11]]),
+        ]
+    }
+
+    fn words(&self) -> [u32; LOG_RECORD_WORDS] {
+        let checksum = self.checksum();
+        [LOG_MAGIC, self.sequence, self.kind, self.cause, self.segments, checksum[0], checksum[1], checksum[2]]
+    }
+
+    fn event(&self) -> Option<TripEvent> {
+        (self.kind == KIND_TRIP).then(|| TripEvent {
+            sequence: self.sequence,
+            cause: TripCause::from_code(self.cause),
+            broken_segments: self.segments,
+        })
+    }
+}
+
+impl<S: Registers> TripLog<S> {
+    /// Open the log, locating the newest valid record
+    pub fn open(storage: S) -> Self {
+        let mut last: Option<LogRecord> = None;
+        for slot in 0..LOG_SLOTS {
+            let word = |i: usize| storage.read((slot * LOG_RECORD_WORDS + i) * 4);
+            if word(0) != LOG_MAGIC {
+                continue;
+            }
+            let record = LogRecord { sequence: word(1), kind: word(2), cause: word(3), segments: word(4) };
+            if record.checksum() != [word(5), word(6), word(7)] {
+                continue;
+            }
+            if last.map_or(true, |l| record.sequence > l.sequence) {
+                last = Some(record);
+            }
+        }
+        TripLog { storage, last }
+    }
+
+    /// Latched trip recorded in the log, if not cleared since
+    pub fn latched(&self) -> Option<TripEvent> {
+        self.last.and_then(|record| record.event())
+    }
+
+    /// Persist a trip event, returning it with its sequence number
+    fn record_trip(&mut self, cause: TripCause, segments: u32) -> TripEvent {
+        let record = self.append(KIND_TRIP, cause as u32, segments);
+        TripEvent { sequence: record.sequence, cause, broken_segments: segments }
+    }
+
+    /// Persist that the latched trip was cleared
+    fn record_clear(&mut self) {
+        self.append(KIND_CLEAR, 0, 0);
+    }
+
+    fn append(&mut self, kind: u32, cause: u32, segments: u32) -> LogRecord {
+        let sequence = self.last.map_or(1, |l| l.sequence.wrapping_add(1));
+        let record = LogRecord { sequence, kind, cause, segments };
+        let slot = sequence as usize % LOG_SLOTS;
+
+        // Magic last so a torn write leaves the slot invalid
+        let words = record.words();
+        for (i, word) in words.iter().enumerate().skip(1) {
+            self.storage.write((slot * LOG_RECORD_WORDS + i) * 4, *word);
+        }
+        self.storage.write(slot * LOG_RECORD_WORDS * 4, words[0]);
+
+        self.last = Some(record);
+        record
+    }
+}
+
+/// Trip Fuse Mesh - Anti-tamper protection
+pub struct TripFuse<R: Registers = Mmio, S: Registers = Mmio> {
+    regs: R,
+    log: TripLog<S>,
+    intact_segments: u32,
+    pending: Option<FuseChallenge>,
+}
+
+impl TripFuse {
+    /// Initialize Trip Fuse Mesh and its NVRAM trip log
+    pub fn initialize(base_address: usize, log_address: usize) -> Result<Self, BootError> {
+        TripFuse::with_registers(Mmio::new(base_address), Mmio::new(log_address))
+    }
+}
+
+impl<R: Registers, S: Registers> TripFuse<R, S> {
+    /// Initialize over arbitrary register blocks
+    pub fn with_registers(regs: R, storage: S) -> Result<Self, BootError> {
+        if regs.read(reg::SIGNATURE) != FUSE_SIGNATURE {
+            return Err(BootError::HardwareTestFailed);
+        }
+
+        let mut fuse = TripFuse {
+            regs,
+            log: TripLog::open(storage),
+            intact_segments: 0,
+            pending: None,
+        };
+        fuse.read_fuse_states();
+
+        // A trip that fired while powered down is latched by the controller
+        let _ = fuse.poll();
+        Ok(fuse)
+    }
+
+    /// Perform continuity test on all fuses
+    ///
+    /// Fails on any broken segment or on a latched trip.
+    pub fn continuity_test(&mut self) -> Result<(), BootError> {
+        self.poll().map_err(|_| BootError::HardwareTestFailed)?;
+
+        if self.intact_segments != u32::MAX || self.log.latched().is_some() {
+            return Err(BootError::HardwareTestFailed);
+        }
+
+        Ok(())
+    }
+
+    /// Query segment continuity and controller status
+    pub fn state(&mut self) -> FuseState {
+        self.read_fuse_states();
+        FuseState {
+            armed: self.regs.read(reg::STATUS) & STATUS_ARMED != 0,
+            intact_segments: self.intact_segments,
+            latched: self.log.latched(),
+        }
+    }
+
+    /// Check for a new trip and latch it
+    ///
+    /// Returns the event when a trip is latched by this call.
+    pub fn poll(&mut self) -> Result<Option<TripEvent>, HardwareError> {
+        self.read_fuse_states();
+        let status = self.regs.read(reg::STATUS);
+
+        if status & STATUS_TRIPPED == 0 || self.log.latched().is_some() {
+            return Ok(None);
+        }
+
+        let cause = TripCause::from_code(self.regs.read(reg::TRIP_CAUSE));
+        let segments = self.regs.read(reg::TRIP_SEGMENTS) | !self.intact_segments;
+        self.pending = None;
+        Ok(Some(self.log.record_trip(cause, segments)))
+    }
+
+    /// Latched trip event, if any
+    pub fn latched(&self) -> Option<TripEvent> {
+        self.log.latched()
+    }
+
+    /// Whether the package is breached: a trip fired now or is latched
+    ///
+    /// A trip latched earlier, during initialization or before a reboot,
+    /// still counts, so a caller cannot miss a breach because another call
+    /// consumed the event `poll` returned. A failed poll counts as a breach.
+    pub fn breached(&mut self) -> bool {
+        let fresh = self.poll();
+        !matches!(fresh, Ok(None)) || self.log.latched().is_some()
+    }
+
+    /// First step of a guarded operation: request a challenge
+    pub fn begin(&mut self, operation: FuseOperation) -> Result<FuseChallenge, HardwareError> {
+        self.pending = None;
+        self.poll()?;
+
+        match operation {
+            // Arming a broken mesh, or over a latched trip, would trip at once
+            FuseOperation::Arm => {
+                if self.log.latched().is_some() || self.intact_segments != u32::MAX {
+                    return Err(HardwareError::IntegrityFailed);
+                }
+            }
+            FuseOperation::Disarm => {}
+            FuseOperation::ClearLatch => {
+                if self.log.latched().is_none() {
+                    return Err(HardwareError::IntegrityFailed);
+                }
+            }
+        }
+
+        self.regs.write(reg::CHALLENGE, operation as u32);
+        let challenge = FuseChallenge { operation, nonce: self.regs.read(reg::CHALLENGE) };
+        self.pending = Some(challenge);
+        Ok(challenge)
+    }
+
+    /// Second step of a guarded operation: echo the challenge back
+    ///
+    /// Any mismatch cancels the pending challenge.
+    pub fn confirm(&mut self, challenge: FuseChallenge) -> Result<(), HardwareError> {
+        if self.pending.take() != Some(challenge) {
+            return Err(HardwareError::IntegrityFailed);
+        }
+
+        self.regs.write(reg::ARM_KEY, ARM_UNLOCK);
+        self.regs.write(reg::ARM_KEY, challenge.nonce ^ challenge.operation as u32);
+
+        let status = self.regs.read(reg::STATUS);
+        let applied = match challenge.operation {
+            FuseOperation::Arm => status & STATUS_ARMED != 0,
+            FuseOperation::Disarm => status & STATUS_ARMED == 0,
+            FuseOperation::ClearLatch => status & STATUS_TRIPPED == 0,
+        };
+        if !applied {
+            return Err(HardwareError::HardwareFault);
+        }
+
+        if challenge.operation == FuseOperation::ClearLatch {
+            self.log.record_clear();
+        }
+        Ok(())
+    }
+
+    fn read_fuse_states(&mut self) {
+        self.intact_segments = (0..SEGMENT_COUNT)
+            .filter(|&i| self.regs.read(reg::SEGMENT_STATUS + i * 4) & SEGMENT_INTACT != 0)
+            .fold(0u32, |mask, i| mask | (1 << i));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulated fuse controller
+    struct SimFuse {
+        regs: [u32; 0x120 / 4],
+        unlocked: bool,
+        nonce: u32,
+    }
+
+    impl SimFuse {
+        fn new() -> Self {
+            let mut regs = [0u32; 0x120 / 4];
+            regs[..SEGMENT_COUNT].fill(SEGMENT_INTACT);
+            regs[reg::SIGNATURE / 4] = FUSE_SIGNATURE;
+            SimFuse { regs, unlocked: false, nonce: 0x1234_5678 }
+        }
+    }
+
+    impl Registers for &mut SimFuse {
+        fn read(&self, offset: usize) -> u32 {
+            self.regs[offset / 4]
+        }
+
+        fn write(&mut self, offset: usize, value: u32) {
+            match offset {
+                reg::CHALLENGE => {
+                    self.nonce = self.nonce.wrapping_mul(747_796_405).wrapping_add(value);
+                    self.regs[offset / 4] = self.nonce;
+                }
+                reg::ARM_KEY => {
+                    if value == ARM_UNLOCK {
+                        self.unlocked = true;
+                        return;
+                    }
+                    if !core::mem::take(&mut self.unlocked) {
+                        return;
+                    }
+                    let status = &mut self.regs[reg::STATUS / 4];
+                    match value ^ self.nonce {
+                        op if op == FuseOperation::Arm as u32 => *status |= STATUS_ARMED,
+                        op if op == FuseOperation::Disarm as u32 => *status &= !STATUS_ARMED,
+                        op if op == FuseOperation::ClearLatch as u32 => *status &= !STATUS_TRIPPED,
+                        _ => {}
+                    }
+                }
+                _ => self.regs[offset / 4] = value,
+            }
+        }
+    }
+
+    struct SimNvram([u32; LOG_SLOTS * LOG_RECORD_WORDS]);
+
+    impl Registers for &mut SimNvram {
+        fn read(&self, offset: usize) -> u32 {
+            self.0[offset / 4]
+        }
+
+        fn write(&mut self, offset: usize, value: u32) {
+            self.0[offset / 4] = value;
+        }
+    }
+
+    #[test]
+    fn test_arming_requires_matching_confirmation() {
+        let mut sim = SimFuse::new();
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        let mut fuse = TripFuse::with_registers(&mut sim, &mut nvram).unwrap();
+
+        let challenge = fuse.begin(FuseOperation::Arm).unwrap();
+        let wrong = FuseChallenge { operation: FuseOperation::Disarm, ..challenge };
+        assert!(fuse.confirm(wrong).is_err());
+        // The mismatch cancelled the challenge
+        assert!(fuse.confirm(challenge).is_err());
+        assert!(!fuse.state().armed);
+
+        let challenge = fuse.begin(FuseOperation::Arm).unwrap();
+        fuse.confirm(challenge).unwrap();
+        assert!(fuse.state().armed);
+        assert!(fuse.continuity_test().is_ok());
+    }
+
+    #[test]
+    fn test_trip_latched_and_persisted() {
+        let mut sim = SimFuse::new();
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        {
+            let mut fuse = TripFuse::with_registers(&mut sim, &mut nvram).unwrap();
+            let challenge = fuse.begin(FuseOperation::Arm).unwrap();
+            fuse.confirm(challenge).unwrap();
+
+            fuse.regs.regs[7] = 0;
+            fuse.regs.regs[reg::STATUS / 4] |= STATUS_TRIPPED;
+            fuse.regs.regs[reg::TRIP_CAUSE / 4] = TripCause::MeshBreach as u32;
+
+            let event = fuse.poll().unwrap().unwrap();
+            assert_eq!(event.cause, TripCause::MeshBreach);
+            assert_eq!(event.broken_segments, 1 << 7);
+            // Latched: further polls report nothing new, but the breach stands
+            assert!(fuse.poll().unwrap().is_none());
+            assert!(fuse.breached());
+            assert!(fuse.continuity_test().is_err());
+            assert!(fuse.begin(FuseOperation::Arm).is_err());
+        }
+
+        // After a reboot the trip is still latched
+        {
+            let mut fuse = TripFuse::with_registers(&mut sim, &mut nvram).unwrap();
+            assert!(fuse.breached());
+            let latched = fuse.latched().unwrap();
+            assert_eq!(latched.cause, TripCause::MeshBreach);
+            assert_eq!(fuse.state().to_bytes()[12..16], (1u32 << 7).to_le_bytes());
+
+            fuse.regs.regs[7] = SEGMENT_INTACT;
+            let challenge = fuse.begin(FuseOperation::ClearLatch).unwrap();
+            fuse.confirm(challenge).unwrap();
+            assert!(fuse.latched().is_none());
+            assert!(!fuse.breached());
+        }
+        assert!(TripLog::open(&mut nvram).latched().is_none());
+    }
+
+    #[test]
+    fn test_trip_while_powered_down_counts_as_breach() {
+        let mut sim = SimFuse::new();
+        let mut nvram = SimNvram([0; LOG_SLOTS * LOG_RECORD_WORDS]);
+        sim.regs[reg::STATUS / 4] |= STATUS_TRIPPED;
+        sim.regs[reg::TRIP_CAUSE / 4] = TripCause::VoltageGlitch as u32;
+
+        // Initialization latches the trip and consumes the event
+        let mut fuse = TripFuse::with_registers(&mut sim, &mut nvram).unwrap();
+        assert!(fuse.poll().unwrap().is_none());
+        assert!(fuse.breached());
+        assert_eq!(fuse.latched().unwrap().cause, TripCause::VoltageGlitch);
+    }
+}
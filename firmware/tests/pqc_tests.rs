@@ -6,6 +6,7 @@
 
 use ark_firmware::crypto::{CryptoContext, CryptoError, PQAlgorithm, PQEncryptedData, HybridEncryptedData, HybridSignature};
 use ark_firmware::SecureKey;
+use pq_types::{sizes, DilithiumSignatureBytes, MlDsaSignatureBytes};
 
 mod test_utils {
     use super::*;
@@ -127,10 +128,12 @@ mod dilithium_tests {
         let message = b"Authentic message";
         
         // Sign message
-        let mut signature = ctx.pq_sign(message).unwrap();
+        let signature = ctx.pq_sign(message).unwrap();
         
         // Tamper with signature
-        signature[0] ^= 0xFF;
+        let mut tampered = signature.into_vec();
+        tampered[0] ^= 0xFF;
+        let signature = DilithiumSignatureBytes::from_vec(tampered).unwrap();
         
         // Verify should fail
         let public_key = ctx.get_pq_public_keys().unwrap().dilithium_public.clone();
@@ -152,6 +155,19 @@ mod dilithium_tests {
         let result = ctx.pq_verify(wrong_message, &signature, &public_key);
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_dilithium_signature_length_enforced() {
+        let ctx = init_pqc_context().unwrap();
+        
+        // Detached signatures always have the fixed Dilithium3 size
+        let signature = ctx.pq_sign(b"Sized message").unwrap();
+        assert_eq!(signature.len(), pq_types::sizes::DILITHIUM3_SIGNATURE);
+        
+        // Truncated signatures are rejected before reaching pqcrypto
+        let truncated = signature.into_vec()[..100].to_vec();
+        assert!(DilithiumSignatureBytes::from_vec(truncated).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -214,7 +230,7 @@ mod hybrid_crypto_tests {
         let mut bob_ctx = init_pqc_context().unwrap();
         
         // Bob generates X25519 keypair
-        let bob_x25519_secret = EphemeralSecret::new(OsRng);
+        let bob_x25519_secret = EphemeralSecret::random_from_rng(OsRng);
         let bob_x25519_public = X25519PublicKey::from(&bob_x25519_secret);
         
         // Get Bob's Kyber public key
@@ -377,6 +393,134 @@ mod security_tests {
     }
 }
 
+#[cfg(test)]
+mod ml_kem_tests {
+    use super::*;
+    use super::test_utils::*;
+    
+    #[test]
+    fn test_ml_kem_encryption_decryption() {
+        let alice_ctx = init_pqc_context().unwrap();
+        let bob_ctx = init_pqc_context().unwrap();
+        
+        let plaintext = generate_test_data(1024);
+        let bob_public_key = bob_ctx.get_pq_public_keys().unwrap().ml_kem_public.clone();
+        let encrypted = alice_ctx.ml_kem_encrypt(&plaintext, &bob_public_key).unwrap();
+        
+        assert_eq!(encrypted.algorithm, PQAlgorithm::MlKem768);
+        assert_eq!(encrypted.kyber_ciphertext.len(), sizes::ML_KEM_768_CIPHERTEXT);
+        assert_eq!(bob_ctx.pq_decrypt(&encrypted).unwrap(), plaintext);
+        
+        // The same ciphertext labelled as Kyber must not decrypt
+        let mut relabelled = encrypted.clone();
+        relabelled.algorithm = PQAlgorithm::KyberAes256Gcm;
+        assert!(bob_ctx.pq_decrypt(&relabelled).is_err());
+    }
+}
+
+#[cfg(test)]
+mod ml_dsa_tests {
+    use super::*;
+    use super::test_utils::*;
+    
+    #[test]
+    fn test_ml_dsa_sign_verify() {
+        let ctx = init_pqc_context().unwrap();
+        let message = generate_test_data(1024);
+        
+        let signature = ctx.ml_dsa_sign(&message).unwrap();
+        assert_eq!(signature.len(), sizes::ML_DSA_65_SIGNATURE);
+        
+        let public_key = ctx.get_pq_public_keys().unwrap().ml_dsa_public.clone();
+        assert!(ctx.ml_dsa_verify(&message, &signature, &public_key).is_ok());
+        assert!(ctx.ml_dsa_verify(b"other message", &signature, &public_key).is_err());
+        
+        // A Dilithium3 signature is a different length and is rejected up front
+        let dilithium = ctx.pq_sign(&message).unwrap();
+        assert!(MlDsaSignatureBytes::from_slice(&dilithium).is_err());
+    }
+}
+
+/// Interop against known-answer vectors for FIPS 203/204
+///
+/// Runs through the `pq_types::scheme` traits, so it checks whichever
+/// backend the features selected. The vendored vectors under
+/// `firmware/kat/` were produced with OpenSSL by `kat/generate.py`, an
+/// implementation independent of every backend, and include implicit
+/// rejection and forged-signature cases. They use the layout of the ACVP
+/// `internalProjection.json` files, which can be dropped in their place.
+#[cfg(test)]
+mod kat_tests {
+    use pq_types::scheme::{Kem, MlDsa65, MlKem768, SignatureScheme};
+    use pq_types::{MlDsaPublicKeyBytes, MlDsaSignatureBytes, MlKemCiphertextBytes, MlKemSecretKeyBytes};
+    use serde_json::Value;
+    
+    fn load(directory: &str) -> Value {
+        let path = format!("{}/kat/{}/internalProjection.json", env!("CARGO_MANIFEST_DIR"), directory);
+        let text = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("missing KAT vectors at {}: {}", path, e));
+        serde_json::from_str(&text).unwrap()
+    }
+    
+    fn field(case: &Value, name: &str) -> Vec<u8> {
+        hex::decode(case[name].as_str().unwrap()).unwrap()
+    }
+    
+    /// Test groups for one parameter set
+    fn groups<'a>(vectors: &'a Value, parameter_set: &'a str) -> impl Iterator<Item = &'a Value> {
+        vectors["testGroups"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(move |group| group["parameterSet"] == parameter_set)
+    }
+    
+    #[test]
+    fn test_ml_kem_768_decapsulation_vectors() {
+        let vectors = load("ML-KEM-encapDecap-FIPS203");
+        let mut checked = 0;
+        
+        for group in groups(&vectors, "ML-KEM-768").filter(|group| group["function"] == "decapsulation") {
+            let secret_key = MlKemSecretKeyBytes::from_vec(field(group, "dk")).unwrap();
+            for case in group["tests"].as_array().unwrap() {
+                let ciphertext = MlKemCiphertextBytes::from_vec(field(case, "c")).unwrap();
+                let shared = MlKem768::decapsulate(&ciphertext, &secret_key).unwrap();
+                assert_eq!(shared.as_slice(), field(case, "k").as_slice(), "tcId {}", case["tcId"]);
+                checked += 1;
+            }
+        }
+        
+        assert!(checked > 0, "no ML-KEM-768 decapsulation vectors found");
+    }
+    
+    #[test]
+    fn test_ml_dsa_65_verification_vectors() {
+        let vectors = load("ML-DSA-sigVer-FIPS204");
+        let mut checked = 0;
+        
+        // The backends sign pure ML-DSA with an empty context, so only those groups apply
+        let applicable = groups(&vectors, "ML-DSA-65").filter(|group| {
+            group["signatureInterface"] == "external" && group["preHash"] == "pure"
+        });
+        
+        for group in applicable {
+            for case in group["tests"].as_array().unwrap() {
+                if case.get("context").and_then(Value::as_str).is_some_and(|context| !context.is_empty()) {
+                    continue;
+                }
+                let public_key = MlDsaPublicKeyBytes::from_vec(field(case, "pk")).unwrap();
+                let verified = MlDsaSignatureBytes::from_vec(field(case, "signature"))
+                    .map(|signature| MlDsa65::verify(&field(case, "message"), &signature, &public_key).is_ok())
+                    .unwrap_or(false);
+                assert_eq!(verified, case["testPassed"].as_bool().unwrap(), "tcId {}", case["tcId"]);
+                checked += 1;
+            }
+        }
+        
+        assert!(checked > 0, "no applicable ML-DSA-65 verification vectors found");
+    }
+}
+
 // Module to export test utilities for integration tests
 pub mod test_exports {
     pub use super::test_utils::*;
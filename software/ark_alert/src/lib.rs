@@ -0,0 +1,564 @@
+//! ARK Alerting
+//! "Watchman, what of the night?" - Isaiah 21:11
+//!
+//! Components raise alerts - a tamper event, a protocol downgrade, a drop in
+//! moral score, a rollback - through an [`Alerter`], usually the one
+//! installed process-wide with [`install`] and reached with [`raise`]. Each
+//! alert has a severity and a key made of the component, the kind of alert
+//! and its subject. An alert whose key was delivered within the
+//! deduplication window is counted but not delivered again; once the window
+//! passes, the next one is delivered carrying the number of raises it stands
+//! for. Deliveries are rate limited across all keys, except for critical
+//! alerts, and an operator can acknowledge a key to silence it until the
+//! acknowledgement expires or the alert escalates to a higher severity.
+//!
+//! Delivered alerts go to every configured [`AlertSink`]: the log, an HTTP
+//! webhook, and an [`AlertSpool`] directory the sentinel relays to the
+//! central alerter (see `network_sentinel::alerts`).
+
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod sinks;
+
+pub use sinks::{AlertSink, AlertSpool, LogSink, WebhookSink};
+
+/// Longest component, kind or subject of an alert
+pub const MAX_NAME_LENGTH: usize = 256;
+
+/// Longest message, detail key or detail value of an alert
+const MAX_FIELD_LENGTH: usize = 2048;
+
+/// Most details carried by an alert
+pub const MAX_DETAILS: usize = 32;
+
+/// Alerter installed for the process
+static ALERTER: OnceLock<Alerter> = OnceLock::new();
+
+/// Alerting errors
+#[derive(Error, Debug)]
+pub enum AlertError {
+    /// Spool could not be read or written
+    #[error("Alert I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Alert is malformed
+    #[error("Invalid alert: {0}")]
+    Invalid(String),
+
+    /// Sink refused or failed to take an alert
+    #[error("Alert delivery to {sink} failed: {reason}")]
+    Delivery {
+        /// Sink name
+        sink: String,
+        /// What went wrong
+        reason: String,
+    },
+
+    /// No alert with the key is open
+    #[error("No open alert {0}")]
+    UnknownAlert(String),
+}
+
+/// Result type for alerting
+pub type AlertResult<T> = Result<T, AlertError>;
+
+/// How urgently an alert needs an operator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Worth knowing; no action needed
+    #[default]
+    Info,
+    /// Needs attention soon
+    Warning,
+    /// Needs attention now; never rate limited
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+/// Something an operator should know about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Unique id, assigned on delivery
+    #[serde(default)]
+    pub id: String,
+    /// Component raising the alert
+    pub component: String,
+    /// Kind of alert, such as `tamper` or `key_pin_changed`
+    pub kind: String,
+    /// What the alert is about, such as a peer or namespace; may be empty
+    #[serde(default)]
+    pub subject: String,
+    /// Severity
+    pub severity: Severity,
+    /// Description for the operator
+    pub message: String,
+    /// Further facts, such as fingerprints or scores
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+    /// When the alert was raised
+    pub raised_at: SystemTime,
+    /// Raises this delivery stands for, including itself
+    #[serde(default = "one")]
+    pub occurrences: u64,
+}
+
+fn one() -> u64 {
+    1
+}
+
+impl Alert {
+    /// Alert raised now
+    pub fn new(component: &str, kind: &str, subject: &str, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            id: String::new(),
+            component: component.to_string(),
+            kind: kind.to_string(),
+            subject: subject.to_string(),
+            severity,
+            message: message.into(),
+            details: BTreeMap::new(),
+            raised_at: SystemTime::now(),
+            occurrences: 1,
+        }
+    }
+
+    /// Add a detail
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.details.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Deduplication key: component, kind and subject
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.component, self.kind, self.subject)
+    }
+}
+
+impl Validate for Alert {
+    fn validate(&self) -> Result<(), String> {
+        if !self.id.is_empty() {
+            decode::check_identifier("id", &self.id, MAX_NAME_LENGTH * 2)?;
+        }
+        decode::check_identifier("component", &self.component, MAX_NAME_LENGTH)?;
+        decode::check_identifier("kind", &self.kind, MAX_NAME_LENGTH)?;
+        decode::check_len("subject", &self.subject, MAX_NAME_LENGTH)?;
+        decode::check_len("message", &self.message, MAX_FIELD_LENGTH)?;
+        decode::check_count("details", self.details.len(), MAX_DETAILS)?;
+        for (key, value) in &self.details {
+            decode::check_len("detail key", key, MAX_FIELD_LENGTH)?;
+            decode::check_len("detail value", value, MAX_FIELD_LENGTH)?;
+        }
+        if self.occurrences == 0 {
+            return Err("occurrences must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}/{}", self.severity, self.component, self.kind)?;
+        if !self.subject.is_empty() {
+            write!(f, " {}", self.subject)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if self.occurrences > 1 {
+            write!(f, " (x{})", self.occurrences)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deduplication, rate limiting and sinks of an alerter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// Whether alerts are delivered at all
+    pub enabled: bool,
+    /// Alerts below this severity are dropped
+    pub min_severity: Severity,
+    /// Seconds after a delivery during which the same key is only counted
+    pub dedup_window_secs: u64,
+    /// Most deliveries per rate window; critical alerts are exempt
+    pub rate_limit: usize,
+    /// Seconds of the rate window
+    pub rate_window_secs: u64,
+    /// Seconds an acknowledgement silences its key
+    pub acknowledgement_ttl_secs: u64,
+    /// HTTP webhook receiving delivered alerts as JSON
+    pub webhook: Option<String>,
+    /// Spool directory relayed by the sentinel
+    pub spool: Option<PathBuf>,
+    /// Most alerts kept in the spool; the oldest are dropped
+    pub spool_retention: usize,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_severity: Severity::Info,
+            dedup_window_secs: 300,
+            rate_limit: 20,
+            rate_window_secs: 60,
+            acknowledgement_ttl_secs: 24 * 3600,
+            webhook: None,
+            spool: None,
+            spool_retention: 200,
+        }
+    }
+}
+
+/// Operator acknowledgement of an open alert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Acknowledgement {
+    /// Operator who acknowledged
+    pub by: String,
+    /// When
+    pub at: SystemTime,
+    /// Operator note
+    pub note: String,
+}
+
+/// Alert key seen by an alerter, with its counts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertRecord {
+    /// Deduplication key
+    pub key: String,
+    /// Latest alert raised under the key
+    pub alert: Alert,
+    /// First raise
+    pub first_raised: SystemTime,
+    /// Latest raise
+    pub last_raised: SystemTime,
+    /// Latest delivery
+    pub last_delivered: Option<SystemTime>,
+    /// Raises in total
+    pub occurrences: u64,
+    /// Raises not yet delivered
+    pub undelivered: u64,
+    /// Acknowledgement silencing the key
+    pub acknowledgement: Option<Acknowledgement>,
+}
+
+/// What became of a raised alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Disposition {
+    /// Sent to the sinks
+    Delivered,
+    /// Counted; the key was delivered within the deduplication window
+    Duplicate,
+    /// Counted; the key is acknowledged
+    Acknowledged,
+    /// Counted; too many deliveries in the rate window
+    RateLimited,
+    /// Dropped; below the minimum severity
+    BelowThreshold,
+    /// Dropped; alerting is disabled or no alerter is installed
+    Disabled,
+}
+
+/// Counters of an alerter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertStats {
+    /// Alerts raised, including dropped ones
+    pub raised: u64,
+    /// Alerts sent to the sinks
+    pub delivered: u64,
+    /// Raises folded into an earlier delivery
+    pub duplicates: u64,
+    /// Raises silenced by an acknowledgement
+    pub acknowledged: u64,
+    /// Raises held back by the rate limit
+    pub rate_limited: u64,
+    /// Deliveries a sink failed to take
+    pub sink_failures: u64,
+}
+
+#[derive(Default)]
+struct AlertState {
+    records: BTreeMap<String, AlertRecord>,
+    deliveries: VecDeque<SystemTime>,
+    sequence: u64,
+    stats: AlertStats,
+}
+
+/// Deduplicating, rate-limited alert delivery to a set of sinks
+pub struct Alerter {
+    config: AlertConfig,
+    sinks: Vec<Box<dyn AlertSink>>,
+    state: Mutex<AlertState>,
+}
+
+impl Alerter {
+    /// Alerter delivering to `sinks`
+    pub fn new(config: AlertConfig, sinks: Vec<Box<dyn AlertSink>>) -> Self {
+        Self { config, sinks, state: Mutex::new(AlertState::default()) }
+    }
+
+    /// Alerter with the sinks `config` names: the log, and the webhook and
+    /// spool when configured
+    pub fn from_config(config: &AlertConfig) -> AlertResult<Self> {
+        let mut sinks: Vec<Box<dyn AlertSink>> = vec![Box::new(LogSink)];
+        if let Some(url) = &config.webhook {
+            sinks.push(Box::new(WebhookSink::new(url)?));
+        }
+        if let Some(directory) = &config.spool {
+            sinks.push(Box::new(AlertSpool::new(directory.clone(), config.spool_retention)));
+        }
+        Ok(Self::new(config.clone(), sinks))
+    }
+
+    /// Configuration
+    pub fn config(&self) -> &AlertConfig {
+        &self.config
+    }
+
+    /// Raise `alert`, delivering it unless it is a duplicate, acknowledged or
+    /// rate limited
+    ///
+    /// Windows are measured from `alert.raised_at`. Sink failures are
+    /// counted and printed; they don't fail the raise.
+    pub fn raise(&self, mut alert: Alert) -> Disposition {
+        let now = alert.raised_at;
+        let key = alert.key();
+        {
+            let mut state = self.lock();
+            state.stats.raised += 1;
+            if !self.config.enabled {
+                return Disposition::Disabled;
+            }
+            if alert.severity < self.config.min_severity {
+                return Disposition::BelowThreshold;
+            }
+            self.expire(&mut state, now);
+
+            let record = state.records.entry(key.clone()).or_insert_with(|| AlertRecord {
+                key: key.clone(),
+                alert: alert.clone(),
+                first_raised: now,
+                last_raised: now,
+                last_delivered: None,
+                occurrences: 0,
+                undelivered: 0,
+                acknowledgement: None,
+            });
+            let escalated = alert.severity > record.alert.severity;
+            record.occurrences += 1;
+            record.undelivered += 1;
+            record.last_raised = now;
+            record.alert = alert.clone();
+
+            let acknowledged = record.acknowledgement.as_ref().is_some_and(|ack| {
+                !escalated && elapsed(ack.at, now) < Duration::from_secs(self.config.acknowledgement_ttl_secs)
+            });
+            let duplicate = record.last_delivered.is_some_and(|delivered| {
+                !escalated && elapsed(delivered, now) < Duration::from_secs(self.config.dedup_window_secs)
+            });
+            if acknowledged {
+                state.stats.acknowledged += 1;
+                return Disposition::Acknowledged;
+            }
+            if duplicate {
+                state.stats.duplicates += 1;
+                return Disposition::Duplicate;
+            }
+
+            let window = Duration::from_secs(self.config.rate_window_secs);
+            while state.deliveries.front().is_some_and(|&delivered| elapsed(delivered, now) >= window) {
+                state.deliveries.pop_front();
+            }
+            if alert.severity < Severity::Critical && state.deliveries.len() >= self.config.rate_limit {
+                state.stats.rate_limited += 1;
+                return Disposition::RateLimited;
+            }
+
+            let record = state.records.get_mut(&key).expect("record inserted above");
+            alert.occurrences = record.undelivered;
+            record.undelivered = 0;
+            record.last_delivered = Some(now);
+            record.acknowledgement = None;
+            state.deliveries.push_back(now);
+            state.sequence += 1;
+            alert.id = format!("{}-{}-{}-{}", alert.component, unix_millis(now), std::process::id(), state.sequence);
+            state.stats.delivered += 1;
+        }
+
+        // Sinks may block on I/O, so they run without the lock
+        for sink in &self.sinks {
+            if let Err(e) = sink.deliver(&alert) {
+                log::warn!("Alert {} not delivered by {}: {}", alert.id, sink.name(), e);
+                self.lock().stats.sink_failures += 1;
+            }
+        }
+        Disposition::Delivered
+    }
+
+    /// Acknowledge the alert under `key` for the acknowledgement TTL
+    pub fn acknowledge(&self, key: &str, by: &str, note: &str) -> AlertResult<AlertRecord> {
+        let mut state = self.lock();
+        let record = state.records.get_mut(key).ok_or_else(|| AlertError::UnknownAlert(key.to_string()))?;
+        record.acknowledgement = Some(Acknowledgement { by: by.to_string(), at: SystemTime::now(), note: note.to_string() });
+        Ok(record.clone())
+    }
+
+    /// Keys seen within the deduplication window or acknowledgement TTL, by key
+    pub fn records(&self) -> Vec<AlertRecord> {
+        self.lock().records.values().cloned().collect()
+    }
+
+    /// Counters since the alerter was created
+    pub fn stats(&self) -> AlertStats {
+        self.lock().stats
+    }
+
+    /// Forget keys neither raised within the deduplication window and
+    /// acknowledgement TTL nor under a live acknowledgement
+    fn expire(&self, state: &mut AlertState, now: SystemTime) {
+        let ttl = Duration::from_secs(self.config.acknowledgement_ttl_secs);
+        let idle = Duration::from_secs(self.config.dedup_window_secs).max(ttl);
+        state.records.retain(|_, record| {
+            elapsed(record.last_raised, now) < idle
+                || record.acknowledgement.as_ref().is_some_and(|ack| elapsed(ack.at, now) < ttl)
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, AlertState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for Alerter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Alerter")
+            .field("config", &self.config)
+            .field("sinks", &self.sinks.iter().map(|sink| sink.name()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Install `alerter` for the process; the first installed alerter stays
+///
+/// Returns the installed alerter.
+pub fn install(alerter: Alerter) -> &'static Alerter {
+    ALERTER.get_or_init(|| alerter)
+}
+
+/// Alerter installed for the process, if any
+pub fn installed() -> Option<&'static Alerter> {
+    ALERTER.get()
+}
+
+/// Raise `alert` on the installed alerter
+pub fn raise(alert: Alert) -> Disposition {
+    match ALERTER.get() {
+        Some(alerter) => alerter.raise(alert),
+        None => Disposition::Disabled,
+    }
+}
+
+/// Time from `earlier` to `later`; zero if the clock went back
+fn elapsed(earlier: SystemTime, later: SystemTime) -> Duration {
+    later.duration_since(earlier).unwrap_or_default()
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Sink keeping what it was given
+    #[derive(Default)]
+    struct Collected(Arc<Mutex<Vec<Alert>>>);
+
+    impl AlertSink for Collected {
+        fn name(&self) -> &str {
+            "collected"
+        }
+
+        fn deliver(&self, alert: &Alert) -> AlertResult<()> {
+            self.0.lock().unwrap().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    fn alert(kind: &str, subject: &str, severity: Severity, at: u64) -> Alert {
+        let mut alert = Alert::new("network_sentinel", kind, subject, severity, format!("{} on {}", kind, subject));
+        alert.raised_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000 + at);
+        alert
+    }
+
+    fn alerter(config: AlertConfig) -> (Alerter, Arc<Mutex<Vec<Alert>>>) {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        (Alerter::new(config, vec![Box::new(Collected(delivered.clone()))]), delivered)
+    }
+
+    #[test]
+    fn test_repeats_are_folded_into_the_next_delivery() {
+        let (alerter, delivered) = alerter(AlertConfig { dedup_window_secs: 60, ..AlertConfig::default() });
+
+        assert_eq!(alerter.raise(alert("downgrade", "peer-a", Severity::Warning, 0)), Disposition::Delivered);
+        for at in 1..5 {
+            assert_eq!(alerter.raise(alert("downgrade", "peer-a", Severity::Warning, at)), Disposition::Duplicate);
+        }
+        // A different subject is a different alert
+        assert_eq!(alerter.raise(alert("downgrade", "peer-b", Severity::Warning, 5)), Disposition::Delivered);
+        // Escalation is delivered inside the window
+        assert_eq!(alerter.raise(alert("downgrade", "peer-a", Severity::Critical, 6)), Disposition::Delivered);
+        assert_eq!(alerter.raise(alert("downgrade", "peer-a", Severity::Critical, 70)), Disposition::Delivered);
+
+        let delivered = delivered.lock().unwrap();
+        let occurrences: Vec<_> = delivered.iter().map(|alert| (alert.subject.as_str(), alert.occurrences)).collect();
+        assert_eq!(occurrences, [("peer-a", 1), ("peer-b", 1), ("peer-a", 5), ("peer-a", 1)]);
+        assert_eq!(delivered[2].to_string(), "[critical] network_sentinel/downgrade peer-a: downgrade on peer-a (x5)");
+        assert!(delivered.iter().all(|alert| alert.validate().is_ok()));
+        assert_eq!(alerter.stats().duplicates, 4);
+    }
+
+    #[test]
+    fn test_rate_limit_and_acknowledgement() {
+        let (alerter, delivered) = alerter(AlertConfig { rate_limit: 2, rate_window_secs: 60, ..AlertConfig::default() });
+
+        for (subject, expected) in [("a", Disposition::Delivered), ("b", Disposition::Delivered), ("c", Disposition::RateLimited)] {
+            assert_eq!(alerter.raise(alert("tamper", subject, Severity::Warning, 0)), expected);
+        }
+        assert_eq!(alerter.raise(alert("tamper", "d", Severity::Critical, 0)), Disposition::Delivered);
+        // The held-back raise goes out once the window has room
+        assert_eq!(alerter.raise(alert("tamper", "c", Severity::Warning, 61)), Disposition::Delivered);
+        assert_eq!(delivered.lock().unwrap().last().unwrap().occurrences, 2);
+
+        assert!(matches!(alerter.acknowledge("network_sentinel/tamper/z", "ops", ""), Err(AlertError::UnknownAlert(_))));
+        let record = alerter.acknowledge("network_sentinel/tamper/a", "ops", "known maintenance").unwrap();
+        assert_eq!(record.acknowledgement.unwrap().by, "ops");
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() - 1_700_000_000;
+        assert_eq!(alerter.raise(alert("tamper", "a", Severity::Warning, now + 400)), Disposition::Acknowledged);
+        assert_eq!(alerter.raise(alert("tamper", "a", Severity::Critical, now + 401)), Disposition::Delivered);
+        assert_eq!(alerter.stats().rate_limited, 1);
+    }
+}
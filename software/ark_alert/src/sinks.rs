@@ -0,0 +1,289 @@
+//! Alert sinks
+//!
+//! [`LogSink`] writes delivered alerts to the service log at a level
+//! matching their severity. [`WebhookSink`] posts them as JSON to a plain HTTP
+//! endpoint; HTTPS receivers are reached through a local TLS-terminating
+//! relay. [`AlertSpool`] keeps them as files until the sentinel relays them
+//! to the central alerter and removes them.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+use pq_types::decode::{self, DecodeLimits};
+
+use crate::{Alert, AlertError, AlertResult, Severity};
+
+/// Time a webhook has to connect, accept and answer
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// File name prefix of spooled alerts
+const SPOOL_PREFIX: &str = "alert-";
+
+/// Decoding limits for one spooled alert
+const SPOOL_LIMITS: DecodeLimits = DecodeLimits::new(128 * 1024, 8);
+
+/// Destination of delivered alerts
+pub trait AlertSink: Send + Sync {
+    /// Name used in errors
+    fn name(&self) -> &str;
+
+    /// Take one delivered alert
+    fn deliver(&self, alert: &Alert) -> AlertResult<()>;
+}
+
+/// Sink writing alerts to the service log
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSink;
+
+impl AlertSink for LogSink {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn deliver(&self, alert: &Alert) -> AlertResult<()> {
+        let level = match alert.severity {
+            Severity::Info => log::Level::Info,
+            Severity::Warning => log::Level::Warn,
+            Severity::Critical => log::Level::Error,
+        };
+        log::log!(level, "ALERT {}", alert);
+        Ok(())
+    }
+}
+
+/// Sink posting alerts as JSON to an `http://` URL
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+    authority: String,
+    path: String,
+}
+
+impl WebhookSink {
+    /// Webhook at `url`, such as `http://127.0.0.1:9093/alerts`
+    pub fn new(url: &str) -> AlertResult<Self> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            AlertError::Invalid(format!("webhook {} is not an http:// URL; use a local relay for HTTPS", url))
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => (&rest[..slash], &rest[slash..]),
+            None => (rest, "/"),
+        };
+        if authority.is_empty() || authority.contains('@') {
+            return Err(AlertError::Invalid(format!("webhook {} has no usable host", url)));
+        }
+        let authority = if authority.contains(':') { authority.to_string() } else { format!("{}:80", authority) };
+        Ok(Self { url: url.to_string(), authority, path: path.to_string() })
+    }
+
+    fn failure(&self, reason: impl ToString) -> AlertError {
+        AlertError::Delivery { sink: self.url.clone(), reason: reason.to_string() }
+    }
+}
+
+impl AlertSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    fn deliver(&self, alert: &Alert) -> AlertResult<()> {
+        let body = serde_json::to_vec(alert).map_err(|e| AlertError::Invalid(e.to_string()))?;
+        let address = self
+            .authority
+            .to_socket_addrs()
+            .map_err(|e| self.failure(e))?
+            .next()
+            .ok_or_else(|| self.failure("host did not resolve"))?;
+        let mut stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT).map_err(|e| self.failure(e))?;
+        stream.set_read_timeout(Some(WEBHOOK_TIMEOUT)).map_err(|e| self.failure(e))?;
+        stream.set_write_timeout(Some(WEBHOOK_TIMEOUT)).map_err(|e| self.failure(e))?;
+
+        let head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.authority,
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(&body)).map_err(|e| self.failure(e))?;
+
+        let mut status = String::new();
+        BufReader::new(stream).read_line(&mut status).map_err(|e| self.failure(e))?;
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') && code.len() == 3 => Ok(()),
+            _ => Err(self.failure(format!("answered {:?}", status.trim_end()))),
+        }
+    }
+}
+
+/// Directory of delivered alerts awaiting relay, with a retention limit
+#[derive(Debug, Clone)]
+pub struct AlertSpool {
+    directory: PathBuf,
+    retention: usize,
+}
+
+impl AlertSpool {
+    /// Spool in `directory` keeping at most `retention` alerts
+    pub fn new(directory: PathBuf, retention: usize) -> Self {
+        Self { directory, retention: retention.max(1) }
+    }
+
+    /// Directory of the spool
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Spooled alerts, oldest first; unreadable files are skipped
+    pub fn pending(&self) -> AlertResult<Vec<Alert>> {
+        Ok(self
+            .files()?
+            .iter()
+            .filter_map(|path| {
+                let bytes = std::fs::read(path).ok()?;
+                decode::json_validated(&bytes, &SPOOL_LIMITS).ok()
+            })
+            .collect())
+    }
+
+    /// Remove `alert` once the relay has acknowledged it
+    pub fn remove(&self, alert: &Alert) -> AlertResult<()> {
+        match std::fs::remove_file(self.directory.join(file_name(alert))) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Spooled files, oldest first
+    fn files(&self) -> AlertResult<Vec<PathBuf>> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            if name.starts_with(SPOOL_PREFIX) && name.ends_with(".json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+}
+
+impl AlertSink for AlertSpool {
+    fn name(&self) -> &str {
+        "spool"
+    }
+
+    /// Write `alert` atomically, then drop the oldest alerts beyond the retention limit
+    fn deliver(&self, alert: &Alert) -> AlertResult<()> {
+        decode::validated(alert.clone()).map_err(|e| AlertError::Invalid(e.to_string()))?;
+        if alert.id.is_empty() {
+            return Err(AlertError::Invalid("spooled alerts need an id".to_string()));
+        }
+        std::fs::create_dir_all(&self.directory)?;
+        let path = self.directory.join(file_name(alert));
+        let temp = self.directory.join(format!(".{}.tmp", file_name(alert)));
+        let contents = serde_json::to_vec_pretty(alert).map_err(|e| AlertError::Invalid(e.to_string()))?;
+
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, &path)?;
+
+        let files = self.files()?;
+        for path in &files[..files.len().saturating_sub(self.retention)] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// File name in a spool; orders by time
+fn file_name(alert: &Alert) -> String {
+    let millis = alert.raised_at.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    format!("{}{:020}-{}.json", SPOOL_PREFIX, millis, alert.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    fn alert(id: &str, at: u64) -> Alert {
+        let mut alert = Alert::new("patch_orchestrator", "rollback", "eu-west", Severity::Warning, "patch rolled back");
+        alert.id = id.to_string();
+        alert.raised_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000 + at);
+        alert
+    }
+
+    #[test]
+    fn test_spool_keeps_newest_until_relayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = AlertSpool::new(dir.path().to_path_buf(), 2);
+        assert!(spool.pending().unwrap().is_empty());
+
+        for n in 0..3 {
+            spool.deliver(&alert(&format!("rollback-{}", n), n)).unwrap();
+        }
+        let pending = spool.pending().unwrap();
+        assert_eq!(pending.iter().map(|alert| alert.id.as_str()).collect::<Vec<_>>(), ["rollback-1", "rollback-2"]);
+
+        spool.remove(&pending[0]).unwrap();
+        spool.remove(&pending[0]).unwrap();
+        assert_eq!(spool.pending().unwrap(), [pending[1].clone()]);
+        assert!(spool.deliver(&alert("", 4)).is_err());
+    }
+
+    /// One whole request: the head, then `Content-Length` bytes of body
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            head.push_str(&line);
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+        }
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body).unwrap();
+        head + &String::from_utf8_lossy(&body)
+    }
+
+    #[test]
+    fn test_webhook_posts_json_and_checks_status() {
+        assert!(WebhookSink::new("https://alerts.example.org/hook").is_err());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for status in ["204 No Content", "500 Internal Server Error"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                requests.push(read_request(&mut stream));
+                write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+            }
+            requests
+        });
+
+        let sink = WebhookSink::new(&url).unwrap();
+        sink.deliver(&alert("rollback-1", 0)).unwrap();
+        assert!(matches!(sink.deliver(&alert("rollback-2", 1)), Err(AlertError::Delivery { .. })));
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(requests[0].contains("\"kind\":\"rollback\""));
+    }
+}
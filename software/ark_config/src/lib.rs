@@ -0,0 +1,688 @@
+//! ARK Signed Configuration
+//! "The writing which is written in the king's name, and sealed with the king's ring, may no man reverse" - Esther 8:8
+//!
+//! Operators sign a configuration file with a key the deployment has
+//! authorized; the detached signature is written beside it as
+//! `<file>.sig`. Binaries read their configuration through [`load`], which
+//! checks the signature against the authorized signers named by
+//! `ARK_CONFIG_SIGNERS` before handing the contents back. A file that is
+//! unsigned, altered, or signed by anyone else is a verification failure,
+//! and `ARK_CONFIG_POLICY` decides what happens: `reject` (the default)
+//! refuses to start, `safe-defaults` ignores the file and starts on the
+//! binary's built-in defaults. Both settings live in the environment so
+//! that the file being checked cannot relax its own check. Without
+//! `ARK_CONFIG_SIGNERS` nothing can be verified, so every load is a
+//! verification failure: no file is ever read unverified.
+//!
+//! A signature covers the component the file configures, the file's name
+//! and a configuration version as well as its hash, so a file signed for
+//! another component, or in place of another file, does not verify. A load
+//! refuses versions below `ARK_CONFIG_MIN_VERSION` and below the last
+//! version this process accepted for the same path, so an older signed
+//! file cannot be swapped back in.
+//!
+//! Every load leaves a [`ConfigAttestation`] naming the hash of the active
+//! configuration and how it was verified; components include it in their
+//! health and attestation output.
+
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ed25519_dalek::{Signature as Ed25519Signature, SigningKey as Ed25519SigningKey};
+use pq_types::canonical::CanonicalEncoder;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Signature format version
+pub const SIGNATURE_FORMAT_VERSION: u32 = 2;
+
+/// Suffix appended to a configuration file name for its signature
+pub const SIGNATURE_EXTENSION: &str = "sig";
+
+/// Environment variable naming the authorized signers file (JSON)
+pub const SIGNERS_ENV: &str = "ARK_CONFIG_SIGNERS";
+
+/// Environment variable holding the [`FailurePolicy`]
+pub const POLICY_ENV: &str = "ARK_CONFIG_POLICY";
+
+/// Environment variable holding the lowest configuration version accepted
+pub const MIN_VERSION_ENV: &str = "ARK_CONFIG_MIN_VERSION";
+
+/// Domain separator for signed configuration hashes
+const SIGNATURE_DOMAIN: &[u8] = b"ark-config-signature-v2";
+
+/// Most signers accepted in a signers file
+pub const MAX_SIGNERS: usize = 256;
+
+/// Longest accepted signer id
+const MAX_FIELD_LENGTH: usize = 256;
+
+/// Attestations of the configuration files loaded by this process
+static ACTIVE: Mutex<BTreeMap<PathBuf, ConfigAttestation>> = Mutex::new(BTreeMap::new());
+
+/// Highest configuration version accepted for each path by this process
+static ACCEPTED_VERSIONS: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// Configuration signing errors
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// File could not be read or written
+    #[error("Configuration I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Signers, key or signature file is malformed
+    #[error("Invalid configuration signing file: {0}")]
+    Invalid(String),
+
+    /// Signature is missing or does not verify
+    #[error("Configuration signature: {0}")]
+    Signature(String),
+
+    /// Verification failed and the policy refuses to start
+    #[error("Configuration {path} rejected: {reason}")]
+    Rejected {
+        /// Rejected configuration file
+        path: String,
+        /// Why verification failed
+        reason: String,
+    },
+}
+
+/// Result type for configuration signing
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
+/// What a binary does when its configuration fails verification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailurePolicy {
+    /// Refuse to start
+    #[default]
+    Reject,
+    /// Ignore the file and start on built-in defaults
+    SafeDefaults,
+}
+
+impl FromStr for FailurePolicy {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(Self::Reject),
+            "safe-defaults" => Ok(Self::SafeDefaults),
+            other => Err(ConfigError::Invalid(format!("{}={} is not reject or safe-defaults", POLICY_ENV, other))),
+        }
+    }
+}
+
+/// Signer allowed to sign configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizedSigner {
+    /// Signer id recorded in signatures
+    pub id: String,
+    /// Dilithium3 public key (hex)
+    pub dilithium_public: String,
+    /// Ed25519 public key (hex)
+    pub ed25519_public: String,
+}
+
+/// Signers a deployment accepts configuration from
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignerSet {
+    /// Authorized signers
+    pub signers: Vec<AuthorizedSigner>,
+}
+
+impl Validate for SignerSet {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("signers", self.signers.len(), MAX_SIGNERS)?;
+        for signer in &self.signers {
+            decode::check_identifier("signer id", &signer.id, MAX_FIELD_LENGTH)?;
+            // Hex keys: Dilithium3 public keys are under 2 KiB
+            decode::check_len("dilithium_public", &signer.dilithium_public, 8192)?;
+            decode::check_len("ed25519_public", &signer.ed25519_public, 64)?;
+        }
+        Ok(())
+    }
+}
+
+impl SignerSet {
+    /// Read a signers file
+    pub fn load(path: &Path) -> ConfigResult<Self> {
+        decode::json_validated(&std::fs::read(path)?, &DecodeLimits::FILE)
+            .map_err(|e| ConfigError::Invalid(format!("Signers {}: {}", path.display(), e)))
+    }
+
+    fn signer(&self, id: &str) -> Option<&AuthorizedSigner> {
+        self.signers.iter().find(|signer| signer.id == id)
+    }
+}
+
+/// Key an operator signs configuration with
+pub struct SignerKey {
+    /// Signer id
+    pub id: String,
+    dilithium_public: DilithiumPublicKeyBytes,
+    dilithium_secret: DilithiumSecretKeyBytes,
+    ed25519: Ed25519SigningKey,
+}
+
+/// Signer key as stored on disk, keys hex encoded
+#[derive(Serialize, Deserialize)]
+struct SignerKeyFile {
+    id: String,
+    dilithium_public: String,
+    dilithium_secret: String,
+    ed25519_secret: String,
+}
+
+impl Validate for SignerKeyFile {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("signer id", &self.id, MAX_FIELD_LENGTH)?;
+        decode::check_len("dilithium_public", &self.dilithium_public, 8192)?;
+        decode::check_len("dilithium_secret", &self.dilithium_secret, 16384)?;
+        decode::check_len("ed25519_secret", &self.ed25519_secret, 64)
+    }
+}
+
+impl SignerKey {
+    /// Fresh key for `id`
+    pub fn generate(id: &str) -> ConfigResult<Self> {
+        let (dilithium_public, dilithium_secret) = Dilithium3::keypair()
+            .map_err(|e| ConfigError::Signature(e.to_string()))?;
+        Ok(Self {
+            id: id.to_string(),
+            dilithium_public,
+            dilithium_secret,
+            ed25519: Ed25519SigningKey::generate(&mut rand::rngs::OsRng),
+        })
+    }
+
+    /// Read a key file written by [`SignerKey::save`]
+    pub fn load(path: &Path) -> ConfigResult<Self> {
+        let invalid = |what: &str| ConfigError::Invalid(format!("Signer key {}: {}", path.display(), what));
+        let file: SignerKeyFile = decode::json_validated(&std::fs::read(path)?, &DecodeLimits::FILE)
+            .map_err(|e| invalid(&e.to_string()))?;
+        let bytes = |hex_key: &str| hex::decode(hex_key).map_err(|e| invalid(&e.to_string()));
+        Ok(Self {
+            dilithium_public: DilithiumPublicKeyBytes::from_vec(bytes(&file.dilithium_public)?)
+                .map_err(|e| invalid(&e.to_string()))?,
+            dilithium_secret: DilithiumSecretKeyBytes::from_vec(bytes(&file.dilithium_secret)?)
+                .map_err(|e| invalid(&e.to_string()))?,
+            ed25519: pq_types::dalek::signing_key_from_secret(&bytes(&file.ed25519_secret)?)
+                .map_err(|e| invalid(&e.to_string()))?,
+            id: file.id,
+        })
+    }
+
+    /// Write the key, readable by its owner only
+    pub fn save(&self, path: &Path) -> ConfigResult<()> {
+        let file = SignerKeyFile {
+            id: self.id.clone(),
+            dilithium_public: hex::encode(self.dilithium_public.as_bytes()),
+            dilithium_secret: hex::encode(self.dilithium_secret.expose_secret()),
+            ed25519_secret: hex::encode(self.ed25519.to_bytes()),
+        };
+        let contents = serde_json::to_vec_pretty(&file).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, &contents)?;
+        Ok(())
+    }
+
+    /// Entry for this key in a signers file
+    pub fn authorized(&self) -> AuthorizedSigner {
+        AuthorizedSigner {
+            id: self.id.clone(),
+            dilithium_public: hex::encode(self.dilithium_public.as_bytes()),
+            ed25519_public: hex::encode(self.ed25519.verifying_key().to_bytes()),
+        }
+    }
+}
+
+/// Detached signature of a configuration file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSignature {
+    /// Signature format version
+    pub format: u32,
+    /// Signer id
+    pub signer: String,
+    /// Component the file configures
+    pub component: String,
+    /// Name of the signed file, without its directory
+    pub file_name: String,
+    /// Configuration version; later versions of a file carry higher ones
+    pub version: u64,
+    /// BLAKE3 hash of the signed file (hex)
+    pub config_hash: String,
+    /// When the file was signed
+    pub signed_at: SystemTime,
+    /// Dilithium3 signature
+    pub pq_signature: DilithiumSignatureBytes,
+    /// Ed25519 signature
+    pub classical_signature: Ed25519SignatureBytes,
+}
+
+impl Validate for ConfigSignature {
+    fn validate(&self) -> Result<(), String> {
+        if self.format != SIGNATURE_FORMAT_VERSION {
+            return Err(format!("unsupported signature format {}", self.format));
+        }
+        decode::check_identifier("signer", &self.signer, MAX_FIELD_LENGTH)?;
+        decode::check_identifier("component", &self.component, MAX_FIELD_LENGTH)?;
+        decode::check_len("file_name", &self.file_name, MAX_FIELD_LENGTH)?;
+        decode::check_len("config_hash", &self.config_hash, 64)
+    }
+}
+
+impl ConfigSignature {
+    /// Signature file of a configuration file: its name with `.sig` appended
+    pub fn path_for(config: &Path) -> PathBuf {
+        let mut name = config.as_os_str().to_owned();
+        name.push(format!(".{}", SIGNATURE_EXTENSION));
+        PathBuf::from(name)
+    }
+
+    /// Read a signature file
+    pub fn load(path: &Path) -> ConfigResult<Self> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ConfigError::Signature(format!("{} not found; the file is unsigned", path.display())))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        decode::json_validated(&bytes, &DecodeLimits::FILE)
+            .map_err(|e| ConfigError::Invalid(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write a signature file
+    pub fn save(&self, path: &Path) -> ConfigResult<()> {
+        let contents = serde_json::to_vec_pretty(self).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// BLAKE3 hash of configuration contents (hex), as `b3sum` prints it
+pub fn config_hash(contents: &[u8]) -> String {
+    blake3::hash(contents).to_hex().to_string()
+}
+
+/// Sign configuration contents as version `version` of `file_name`, read by `component`
+pub fn sign(contents: &[u8], key: &SignerKey, component: &str, file_name: &str, version: u64) -> ConfigResult<ConfigSignature> {
+    use ed25519_dalek::Signer;
+
+    let config_hash = config_hash(contents);
+    let message = signed_message(&key.id, component, file_name, version, &config_hash);
+    let pq_signature = Dilithium3::sign(&message, &key.dilithium_secret)
+        .map_err(|e| ConfigError::Signature(e.to_string()))?;
+    Ok(ConfigSignature {
+        format: SIGNATURE_FORMAT_VERSION,
+        signer: key.id.clone(),
+        component: component.to_string(),
+        file_name: file_name.to_string(),
+        version,
+        config_hash,
+        signed_at: SystemTime::now(),
+        pq_signature,
+        classical_signature: Ed25519SignatureBytes::from_slice(&key.ed25519.sign(&message).to_bytes())
+            .map_err(|e| ConfigError::Signature(e.to_string()))?,
+    })
+}
+
+/// Sign a configuration file for `component`, writing the signature beside it
+pub fn sign_file(path: &Path, key: &SignerKey, component: &str, version: u64) -> ConfigResult<PathBuf> {
+    let signature = sign(&std::fs::read(path)?, key, component, &file_name(path)?, version)?;
+    let signature_path = ConfigSignature::path_for(path);
+    signature.save(&signature_path)?;
+    Ok(signature_path)
+}
+
+/// Check that `signature` covers `contents` and comes from an authorized signer
+///
+/// Both the Dilithium3 and Ed25519 signatures must verify. This proves the
+/// signer chose the component, file name and version the signature names;
+/// [`load`] checks that they are the ones expected.
+pub fn verify(contents: &[u8], signature: &ConfigSignature, signers: &SignerSet) -> ConfigResult<()> {
+    use ed25519_dalek::Verifier;
+
+    let failed = |reason: String| ConfigError::Signature(reason);
+    let config_hash = config_hash(contents);
+    if signature.config_hash != config_hash {
+        return Err(failed(format!("file hash {} differs from the signed hash {}", config_hash, signature.config_hash)));
+    }
+    let signer = signers.signer(&signature.signer)
+        .ok_or_else(|| failed(format!("{} is not an authorized signer", signature.signer)))?;
+    let key_error = |e: hex::FromHexError| failed(format!("Signer {} key: {}", signer.id, e));
+    let dilithium_public = DilithiumPublicKeyBytes::from_vec(hex::decode(&signer.dilithium_public).map_err(key_error)?)
+        .map_err(|_| failed(format!("Invalid dilithium3 key for {}", signer.id)))?;
+    let ed25519_public = pq_types::dalek::verifying_key_from_bytes(&hex::decode(&signer.ed25519_public).map_err(key_error)?)
+        .map_err(|_| failed(format!("Invalid ed25519 key for {}", signer.id)))?;
+
+    let message = signed_message(
+        &signature.signer,
+        &signature.component,
+        &signature.file_name,
+        signature.version,
+        &signature.config_hash,
+    );
+    Dilithium3::verify(&message, &signature.pq_signature, &dilithium_public)
+        .map_err(|_| failed(format!("Dilithium signature from {} is invalid", signer.id)))?;
+    let classical_signature = Ed25519Signature::from_bytes(&signature.classical_signature.clone().into());
+    ed25519_public.verify(&message, &classical_signature)
+        .map_err(|_| failed(format!("Ed25519 signature from {} is invalid", signer.id)))
+}
+
+/// How configuration is verified
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyOptions {
+    /// Authorized signers file; `None` fails every load
+    pub signers: Option<PathBuf>,
+    /// What to do when verification fails
+    pub policy: FailurePolicy,
+    /// Component reading the configuration; signatures must name it
+    pub component: String,
+    /// Lowest configuration version accepted
+    pub min_version: u64,
+}
+
+impl VerifyOptions {
+    /// Options for `component` from `ARK_CONFIG_SIGNERS`, `ARK_CONFIG_POLICY`
+    /// and `ARK_CONFIG_MIN_VERSION`
+    pub fn from_env(component: &str) -> ConfigResult<Self> {
+        Ok(Self {
+            signers: std::env::var_os(SIGNERS_ENV).map(PathBuf::from),
+            policy: match std::env::var(POLICY_ENV) {
+                Ok(policy) => policy.parse()?,
+                Err(_) => FailurePolicy::default(),
+            },
+            component: component.to_string(),
+            min_version: match std::env::var(MIN_VERSION_ENV) {
+                Ok(version) => version.parse().map_err(|_| {
+                    ConfigError::Invalid(format!("{}={} is not a version number", MIN_VERSION_ENV, version))
+                })?,
+                Err(_) => 0,
+            },
+        })
+    }
+
+    /// Whether signatures are checked
+    pub fn enforced(&self) -> bool {
+        self.signers.is_some()
+    }
+}
+
+/// How the active configuration was verified
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ConfigStatus {
+    /// Signed by an authorized signer
+    Verified {
+        /// Signer id
+        signer: String,
+    },
+    /// Verification failed; built-in defaults are active
+    SafeDefaults {
+        /// Why verification failed
+        reason: String,
+    },
+}
+
+impl ConfigStatus {
+    /// Short name for health output
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConfigStatus::Verified { .. } => "verified",
+            ConfigStatus::SafeDefaults { .. } => "safe_defaults",
+        }
+    }
+}
+
+/// Which configuration a component runs on, for health and attestation output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigAttestation {
+    /// Configuration file
+    pub path: PathBuf,
+    /// Hash of the active contents (see [`config_hash`]); `None` on built-in defaults
+    pub config_hash: Option<String>,
+    /// Signed version of the active contents; `None` on built-in defaults
+    pub version: Option<u64>,
+    /// How the configuration was verified
+    #[serde(flatten)]
+    pub status: ConfigStatus,
+    /// When the file was loaded
+    pub loaded_at: SystemTime,
+}
+
+impl ConfigAttestation {
+    /// Flat key/value summary for health output, keys prefixed `config.`
+    pub fn health_details(&self) -> BTreeMap<String, String> {
+        let mut details = BTreeMap::new();
+        let mut detail = |key: &str, value: String| {
+            details.insert(format!("config.{}", key), value);
+        };
+
+        detail("path", self.path.display().to_string());
+        detail("hash", self.config_hash.clone().unwrap_or_else(|| "built-in".to_string()));
+        if let Some(version) = self.version {
+            detail("version", version.to_string());
+        }
+        detail("status", self.status.name().to_string());
+        match &self.status {
+            ConfigStatus::Verified { signer } => detail("signer", signer.clone()),
+            ConfigStatus::SafeDefaults { reason } => detail("reason", reason.clone()),
+        }
+        detail("loaded_at", self.loaded_at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0).to_string());
+        details
+    }
+}
+
+/// Configuration read by [`load`]
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    /// File contents to parse; `None` means run on built-in defaults
+    pub contents: Option<Vec<u8>>,
+    /// How the contents were verified
+    pub attestation: ConfigAttestation,
+}
+
+/// Read a configuration file, verifying its signature as `options` require
+///
+/// Fails only if the signers file cannot be read, or verification fails
+/// under [`FailurePolicy::Reject`]. Without signers every load fails
+/// verification. The outcome is kept for [`active`].
+pub fn load(path: &Path, options: &VerifyOptions) -> ConfigResult<LoadedConfig> {
+    let verified = match &options.signers {
+        Some(signers) => {
+            let signers = SignerSet::load(signers)?;
+            read_config(path).and_then(|contents| {
+                let signature = ConfigSignature::load(&ConfigSignature::path_for(path))?;
+                verify(&contents, &signature, &signers)?;
+                check_binding(path, &signature, options)?;
+                Ok((contents, signature))
+            })
+        }
+        None => Err(ConfigError::Signature(format!("no authorized signers; {} is not set", SIGNERS_ENV))),
+    };
+    match (verified, options.policy) {
+        (Ok((contents, signature)), _) => {
+            ACCEPTED_VERSIONS
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(path.to_path_buf(), signature.version);
+            let status = ConfigStatus::Verified { signer: signature.signer };
+            Ok(record(path, Some((contents, signature.version)), status))
+        }
+        (Err(e), FailurePolicy::Reject) => {
+            Err(ConfigError::Rejected { path: path.display().to_string(), reason: e.to_string() })
+        }
+        (Err(e), FailurePolicy::SafeDefaults) => Ok(record(path, None, ConfigStatus::SafeDefaults { reason: e.to_string() })),
+    }
+}
+
+/// Check that a verified signature was issued for this component, file and
+/// a version no older than the options, or this process, already accept
+fn check_binding(path: &Path, signature: &ConfigSignature, options: &VerifyOptions) -> ConfigResult<()> {
+    let failed = |reason: String| Err(ConfigError::Signature(reason));
+    if signature.component != options.component {
+        return failed(format!("signed for {}, not {}", signature.component, options.component));
+    }
+    let file_name = file_name(path)?;
+    if signature.file_name != file_name {
+        return failed(format!("signed as {}, not {}", signature.file_name, file_name));
+    }
+    let accepted = ACCEPTED_VERSIONS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).get(path).copied();
+    let floor = accepted.unwrap_or(0).max(options.min_version);
+    if signature.version < floor {
+        return failed(format!("version {} is older than version {}", signature.version, floor));
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> ConfigResult<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| ConfigError::Invalid(format!("{} has no UTF-8 file name", path.display())))
+}
+
+/// Attestations of every configuration file loaded by this process
+pub fn active() -> Vec<ConfigAttestation> {
+    ACTIVE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).values().cloned().collect()
+}
+
+fn read_config(path: &Path) -> ConfigResult<Vec<u8>> {
+    let contents = std::fs::read(path)?;
+    decode::check_size(contents.len(), &DecodeLimits::FILE)
+        .map_err(|e| ConfigError::Invalid(format!("{}: {}", path.display(), e)))?;
+    Ok(contents)
+}
+
+fn record(path: &Path, verified: Option<(Vec<u8>, u64)>, status: ConfigStatus) -> LoadedConfig {
+    let (contents, version) = verified.unzip();
+    let attestation = ConfigAttestation {
+        path: path.to_path_buf(),
+        config_hash: contents.as_deref().map(config_hash),
+        version,
+        status,
+        loaded_at: SystemTime::now(),
+    };
+    ACTIVE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(path.to_path_buf(), attestation.clone());
+    LoadedConfig { contents, attestation }
+}
+
+fn signed_message(signer: &str, component: &str, file_name: &str, version: u64, config_hash: &str) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(SIGNATURE_DOMAIN);
+    encoder.str(signer).str(component).str(file_name).u64(version).str(config_hash);
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config file, its signer's key and a signers file authorizing it
+    fn signed_setup(dir: &Path) -> (PathBuf, SignerKey, VerifyOptions) {
+        let config = dir.join("orchestrator.toml");
+        std::fs::write(&config, "max_patch_size = 1024\n").unwrap();
+        let key = SignerKey::generate("ops-alice").unwrap();
+        sign_file(&config, &key, "patch_orchestrator", 1).unwrap();
+
+        let signers = dir.join("signers.json");
+        std::fs::write(&signers, serde_json::to_vec(&SignerSet { signers: vec![key.authorized()] }).unwrap()).unwrap();
+        let options = VerifyOptions {
+            signers: Some(signers),
+            policy: FailurePolicy::Reject,
+            component: "patch_orchestrator".to_string(),
+            min_version: 0,
+        };
+        (config, key, options)
+    }
+
+    #[test]
+    fn test_signed_file_verifies_and_tampering_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, _, options) = signed_setup(dir.path());
+
+        let loaded = load(&config, &options).unwrap();
+        assert_eq!(loaded.attestation.status, ConfigStatus::Verified { signer: "ops-alice".to_string() });
+        assert_eq!(loaded.attestation.config_hash.as_deref(), Some(config_hash(b"max_patch_size = 1024\n").as_str()));
+        assert_eq!(active().iter().find(|a| a.path == config), Some(&loaded.attestation));
+
+        std::fs::write(&config, "max_patch_size = 1073741824\n").unwrap();
+        assert!(matches!(load(&config, &options), Err(ConfigError::Rejected { .. })));
+    }
+
+    #[test]
+    fn test_unauthorized_signer_falls_back_to_safe_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, _, options) = signed_setup(dir.path());
+        let mallory = SignerKey::generate("ops-alice").unwrap();
+        sign_file(&config, &mallory, "patch_orchestrator", 1).unwrap();
+
+        let options = VerifyOptions { policy: FailurePolicy::SafeDefaults, ..options };
+        let loaded = load(&config, &options).unwrap();
+        assert!(loaded.contents.is_none());
+        assert!(matches!(loaded.attestation.status, ConfigStatus::SafeDefaults { .. }));
+        assert_eq!(loaded.attestation.health_details()["config.hash"], "built-in");
+    }
+
+    #[test]
+    fn test_nothing_is_read_without_signers() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, _, options) = signed_setup(dir.path());
+        let unsigned = VerifyOptions { signers: None, ..options };
+        assert!(matches!(load(&config, &unsigned), Err(ConfigError::Rejected { .. })));
+
+        let loaded = load(&config, &VerifyOptions { policy: FailurePolicy::SafeDefaults, ..unsigned }).unwrap();
+        assert!(loaded.contents.is_none());
+        assert!(matches!(loaded.attestation.status, ConfigStatus::SafeDefaults { .. }));
+    }
+
+    #[test]
+    fn test_signatures_bind_component_file_and_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let (config, key, options) = signed_setup(dir.path());
+        let sentinel = VerifyOptions { component: "network_sentinel".to_string(), ..options.clone() };
+        assert!(matches!(load(&config, &sentinel), Err(ConfigError::Rejected { .. })));
+
+        // The same file and signature under another name
+        let renamed = dir.path().join("api.toml");
+        std::fs::copy(&config, &renamed).unwrap();
+        std::fs::copy(ConfigSignature::path_for(&config), ConfigSignature::path_for(&renamed)).unwrap();
+        assert!(matches!(load(&renamed, &options), Err(ConfigError::Rejected { .. })));
+
+        let version_1 = std::fs::read(ConfigSignature::path_for(&config)).unwrap();
+        sign_file(&config, &key, "patch_orchestrator", 2).unwrap();
+        let newer = VerifyOptions { min_version: 3, ..options.clone() };
+        assert!(matches!(load(&config, &newer), Err(ConfigError::Rejected { .. })));
+        assert_eq!(load(&config, &options).unwrap().attestation.version, Some(2));
+
+        // Once version 2 is accepted, version 1 cannot be swapped back in
+        std::fs::write(ConfigSignature::path_for(&config), version_1).unwrap();
+        assert!(matches!(load(&config, &options), Err(ConfigError::Rejected { .. })));
+    }
+
+    #[test]
+    fn test_signed_message_is_frozen() {
+        let message = signed_message("ops-alice", "patch_orchestrator", "orchestrator.toml", 3,
+                                     &config_hash(b"max_patch_size = 1024\n"));
+        assert_eq!(&message[8..8 + SIGNATURE_DOMAIN.len()], SIGNATURE_DOMAIN);
+        assert_eq!(blake3::hash(&message).to_hex().to_string(),
+                   "61abbe5bcb6e0e70a4ef421a5bdf9fd57666e8b731a0067765856d8e7d2236ca");
+    }
+}
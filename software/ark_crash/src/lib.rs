@@ -0,0 +1,512 @@
+//! ARK Crash Reporter
+//! "For nothing is secret, that shall not be made manifest" - Luke 8:17
+//!
+//! Service binaries install a panic hook with [`install`] at startup. When a
+//! thread panics the hook writes a crash report before the process goes
+//! down: the panic message and location, the thread, a backtrace and a
+//! snapshot of the state the component published with [`record_state`].
+//! Messages and state are scrubbed first, so values under secret-sounding
+//! keys and long hex or base64 runs never reach the disk. The previous hook
+//! still runs afterwards, so the panic is printed as before.
+//!
+//! Reports are kept in a [`CrashStore`] directory, oldest pruned beyond the
+//! retention limit. Components include the last crash in their health
+//! output, and the sentinel can forward reports not yet forwarded to a
+//! central collector (see `network_sentinel::crash`).
+
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+use std::backtrace::Backtrace;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Report format version
+pub const REPORT_FORMAT_VERSION: u32 = 1;
+
+/// Replacement for scrubbed values
+pub const REDACTED: &str = "[redacted]";
+
+/// Longest backtrace kept in a report; the rest is cut
+pub const MAX_BACKTRACE_BYTES: usize = 32 * 1024;
+
+/// Most state entries kept in a report
+pub const MAX_STATE_ENTRIES: usize = 64;
+
+/// Longest message, location or state value kept in a report
+const MAX_FIELD_LENGTH: usize = 1024;
+
+/// File name prefix of stored reports
+const REPORT_PREFIX: &str = "crash-";
+
+/// Key fragments whose values are never written
+const SECRET_KEY_FRAGMENTS: &[&str] = &["secret", "password", "passphrase", "token", "private", "credential", "kek", "key"];
+
+/// Shortest hex or base64 run treated as key material
+const MIN_SECRET_RUN: usize = 32;
+
+/// Decoding limits for one report
+const REPORT_LIMITS: DecodeLimits = DecodeLimits::new(MAX_BACKTRACE_BYTES + 128 * 1024, 8);
+
+/// State published by the running component
+static STATE: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Crash reporting errors
+#[derive(Error, Debug)]
+pub enum CrashError {
+    /// Report could not be read or written
+    #[error("Crash report I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Report is malformed or from an unsupported format
+    #[error("Invalid crash report: {0}")]
+    Invalid(String),
+}
+
+/// Result type for crash reporting
+pub type CrashResult<T> = Result<T, CrashError>;
+
+/// Where crash reports are kept
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrashConfig {
+    /// Whether the panic hook is installed
+    pub enabled: bool,
+    /// Directory of stored reports
+    pub directory: PathBuf,
+    /// Most reports kept; older ones are deleted
+    pub retention: usize,
+}
+
+impl Default for CrashConfig {
+    fn default() -> Self {
+        Self { enabled: true, directory: PathBuf::from("crash/"), retention: 20 }
+    }
+}
+
+/// Record of one panic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Report format version
+    pub format: u32,
+    /// Unique id: component, time in milliseconds and process id
+    pub id: String,
+    /// Component that crashed
+    pub component: String,
+    /// Version of the component
+    pub version: String,
+    /// Process that crashed
+    pub pid: u32,
+    /// When the panic happened
+    pub occurred_at: SystemTime,
+    /// Name of the panicking thread
+    pub thread: String,
+    /// Panic message, scrubbed
+    pub message: String,
+    /// Source location of the panic
+    pub location: Option<String>,
+    /// Captured backtrace, cut at `MAX_BACKTRACE_BYTES`
+    pub backtrace: String,
+    /// State published with `record_state`, scrubbed
+    pub state: BTreeMap<String, String>,
+    /// When the report reached the collector
+    #[serde(default)]
+    pub forwarded_at: Option<SystemTime>,
+}
+
+impl Validate for CrashReport {
+    fn validate(&self) -> Result<(), String> {
+        if self.format != REPORT_FORMAT_VERSION {
+            return Err(format!("unsupported report format {}", self.format));
+        }
+        decode::check_identifier("id", &self.id, MAX_FIELD_LENGTH)?;
+        decode::check_identifier("component", &self.component, MAX_FIELD_LENGTH)?;
+        decode::check_len("version", &self.version, MAX_FIELD_LENGTH)?;
+        decode::check_len("thread", &self.thread, MAX_FIELD_LENGTH)?;
+        decode::check_len("message", &self.message, MAX_FIELD_LENGTH)?;
+        decode::check_len("backtrace", &self.backtrace, MAX_BACKTRACE_BYTES)?;
+        decode::check_count("state", self.state.len(), MAX_STATE_ENTRIES)?;
+        for (key, value) in &self.state {
+            decode::check_len("state key", key, MAX_FIELD_LENGTH)?;
+            decode::check_len("state value", value, MAX_FIELD_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+impl CrashReport {
+    /// Report of the panic described by `info`, with the current state
+    pub fn capture(component: &str, version: &str, info: &PanicHookInfo<'_>) -> Self {
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match info.payload().downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "Box<dyn Any>".to_string(),
+            },
+        };
+        let mut backtrace = Backtrace::force_capture().to_string();
+        truncate(&mut backtrace, MAX_BACKTRACE_BYTES);
+
+        Self::new(
+            component,
+            version,
+            std::thread::current().name().unwrap_or("<unnamed>"),
+            &message,
+            info.location().map(|location| location.to_string()),
+            backtrace,
+            snapshot_state(),
+        )
+    }
+
+    /// Report of a crash happening now; scrubs `message` and `state`
+    pub fn new(
+        component: &str,
+        version: &str,
+        thread: &str,
+        message: &str,
+        location: Option<String>,
+        backtrace: String,
+        state: BTreeMap<String, String>,
+    ) -> Self {
+        let occurred_at = SystemTime::now();
+        let pid = std::process::id();
+        let mut thread = thread.to_string();
+        truncate(&mut thread, MAX_FIELD_LENGTH);
+        Self {
+            format: REPORT_FORMAT_VERSION,
+            id: format!("{}-{}-{}", component, unix_millis(occurred_at), pid),
+            component: component.to_string(),
+            version: version.to_string(),
+            pid,
+            occurred_at,
+            thread,
+            message: scrub_text(message),
+            location: location.map(|mut location| {
+                truncate(&mut location, MAX_FIELD_LENGTH);
+                location
+            }),
+            backtrace,
+            state: state
+                .into_iter()
+                .take(MAX_STATE_ENTRIES)
+                .map(|(mut key, value)| {
+                    let value = scrub(&key, &value);
+                    truncate(&mut key, MAX_FIELD_LENGTH);
+                    (key, value)
+                })
+                .collect(),
+            forwarded_at: None,
+        }
+    }
+
+    /// Short form for health output
+    pub fn summary(&self) -> CrashSummary {
+        CrashSummary {
+            id: self.id.clone(),
+            component: self.component.clone(),
+            version: self.version.clone(),
+            occurred_at: self.occurred_at,
+            message: self.message.clone(),
+            location: self.location.clone(),
+            forwarded: self.forwarded_at.is_some(),
+        }
+    }
+
+    /// File name in a store; orders by time
+    fn file_name(&self) -> String {
+        format!("{}{:020}-{}.json", REPORT_PREFIX, unix_millis(self.occurred_at), self.pid)
+    }
+}
+
+/// Last crash as shown in health output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashSummary {
+    /// Report id
+    pub id: String,
+    /// Component that crashed
+    pub component: String,
+    /// Version of the component
+    pub version: String,
+    /// When the panic happened
+    pub occurred_at: SystemTime,
+    /// Panic message, scrubbed
+    pub message: String,
+    /// Source location of the panic
+    pub location: Option<String>,
+    /// Whether the report reached the collector
+    pub forwarded: bool,
+}
+
+impl CrashSummary {
+    /// Flat key/value summary for health output, keys prefixed `last_crash.`
+    pub fn health_details(&self) -> BTreeMap<String, String> {
+        let mut details = BTreeMap::new();
+        let mut detail = |key: &str, value: String| {
+            details.insert(format!("last_crash.{}", key), value);
+        };
+
+        detail("id", self.id.clone());
+        detail("version", self.version.clone());
+        detail("occurred_at", (unix_millis(self.occurred_at) / 1000).to_string());
+        detail("message", self.message.clone());
+        detail("location", self.location.clone().unwrap_or_default());
+        detail("forwarded", self.forwarded.to_string());
+        details
+    }
+}
+
+/// Directory of crash reports with a retention limit
+#[derive(Debug, Clone)]
+pub struct CrashStore {
+    directory: PathBuf,
+    retention: usize,
+}
+
+impl CrashStore {
+    /// Store described by `config`
+    pub fn new(config: &CrashConfig) -> Self {
+        Self { directory: config.directory.clone(), retention: config.retention.max(1) }
+    }
+
+    /// Directory of the reports
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Write `report`, then delete the oldest reports beyond the retention limit
+    ///
+    /// The file is replaced atomically, so a crash while writing never
+    /// leaves a partial report.
+    pub fn write(&self, report: &CrashReport) -> CrashResult<PathBuf> {
+        std::fs::create_dir_all(&self.directory)?;
+        let path = self.directory.join(report.file_name());
+        let temp = self.directory.join(format!(".{}.tmp", report.file_name()));
+        let contents = serde_json::to_vec_pretty(report).map_err(|e| CrashError::Invalid(e.to_string()))?;
+
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, &path)?;
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// Stored reports, oldest first; unreadable files are skipped
+    pub fn reports(&self) -> CrashResult<Vec<CrashReport>> {
+        Ok(self
+            .report_files()?
+            .iter()
+            .filter_map(|path| {
+                let bytes = std::fs::read(path).ok()?;
+                decode::json_validated(&bytes, &REPORT_LIMITS).ok()
+            })
+            .collect())
+    }
+
+    /// Most recent report, if any
+    pub fn last(&self) -> CrashResult<Option<CrashReport>> {
+        Ok(self.reports()?.pop())
+    }
+
+    /// Reports the collector has not acknowledged, oldest first
+    pub fn unforwarded(&self) -> CrashResult<Vec<CrashReport>> {
+        Ok(self.reports()?.into_iter().filter(|report| report.forwarded_at.is_none()).collect())
+    }
+
+    /// Record that the collector acknowledged `report` at `at`
+    pub fn mark_forwarded(&self, report: &CrashReport, at: SystemTime) -> CrashResult<()> {
+        if !self.directory.join(report.file_name()).exists() {
+            // Pruned while being forwarded
+            return Ok(());
+        }
+        let mut forwarded = report.clone();
+        forwarded.forwarded_at = Some(at);
+        self.write(&forwarded).map(|_| ())
+    }
+
+    /// Report files, oldest first
+    fn report_files(&self) -> CrashResult<Vec<PathBuf>> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut files = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+            if name.starts_with(REPORT_PREFIX) && name.ends_with(".json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Delete the oldest reports beyond the retention limit
+    fn prune(&self) -> CrashResult<usize> {
+        let files = self.report_files()?;
+        let excess = files.len().saturating_sub(self.retention);
+        for path in &files[..excess] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(excess)
+    }
+}
+
+/// Install the panic hook writing reports of `component` into `config`'s store
+///
+/// Returns the store, for health output and forwarding; `None` when
+/// crash reporting is disabled.
+pub fn install(component: &str, version: &str, config: &CrashConfig) -> CrashResult<Option<CrashStore>> {
+    if !config.enabled {
+        return Ok(None);
+    }
+    let store = CrashStore::new(config);
+    std::fs::create_dir_all(store.directory())?;
+
+    let hook_store = store.clone();
+    let component = component.to_string();
+    let version = version.to_string();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let report = CrashReport::capture(&component, &version, info);
+        match hook_store.write(&report) {
+            Ok(path) => eprintln!("Crash report {} written to {}", report.id, path.display()),
+            Err(e) => eprintln!("Crash report {} could not be written: {}", report.id, e),
+        }
+        previous(info);
+    }));
+    Ok(Some(store))
+}
+
+/// Publish `value` under `key` for inclusion in crash reports
+pub fn record_state(key: impl Into<String>, value: impl ToString) {
+    let mut state = STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    state.insert(key.into(), value.to_string());
+}
+
+/// Stop publishing `key`
+pub fn clear_state(key: &str) {
+    STATE.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(key);
+}
+
+/// Published state; never blocks, since the hook may run while it is held
+fn snapshot_state() -> BTreeMap<String, String> {
+    match STATE.try_lock() {
+        Ok(state) => state.clone(),
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().clone(),
+        Err(TryLockError::WouldBlock) => BTreeMap::from([("state".to_string(), "unavailable (locked)".to_string())]),
+    }
+}
+
+/// Value of state `key` as written to a report
+pub fn scrub(key: &str, value: &str) -> String {
+    let key = key.to_ascii_lowercase();
+    if SECRET_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment)) {
+        return REDACTED.to_string();
+    }
+    scrub_text(value)
+}
+
+/// `text` with hex and base64 runs long enough to be key material redacted
+pub fn scrub_text(text: &str) -> String {
+    let is_run = |c: char| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '-' | '_');
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut run = String::new();
+    let flush = |run: &mut String, scrubbed: &mut String| {
+        let looks_like_key = run.len() >= MIN_SECRET_RUN && run.chars().any(|c| c.is_ascii_digit());
+        scrubbed.push_str(if looks_like_key { REDACTED } else { run });
+        run.clear();
+    };
+    for c in text.chars() {
+        if is_run(c) {
+            run.push(c);
+        } else {
+            flush(&mut run, &mut scrubbed);
+            scrubbed.push(c);
+        }
+    }
+    flush(&mut run, &mut scrubbed);
+    truncate(&mut scrubbed, MAX_FIELD_LENGTH);
+    scrubbed
+}
+
+/// Cut `text` to at most `max` bytes on a character boundary
+fn truncate(text: &mut String, max: usize) {
+    if text.len() > max {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_are_scrubbed() {
+        let state = BTreeMap::from([
+            ("namespace".to_string(), "eu-west".to_string()),
+            ("signing_key".to_string(), "hunter2".to_string()),
+            ("last_patch".to_string(), format!("applied {}", "ab12".repeat(16))),
+        ]);
+        let report = CrashReport::new(
+            "patch_orchestrator",
+            "3.0.0",
+            "main",
+            &format!("bad token {} in request", "Zm9vYmFy0123".repeat(4)),
+            Some("src/lib.rs:42:9".into()),
+            String::new(),
+            state,
+        );
+
+        assert_eq!(report.state["namespace"], "eu-west");
+        assert_eq!(report.state["signing_key"], REDACTED);
+        assert_eq!(report.state["last_patch"], "applied [redacted]");
+        assert_eq!(report.message, "bad token [redacted] in request");
+        assert!(report.validate().is_ok());
+        assert_eq!(scrub_text("patch_orchestrator::keys::rotate"), "patch_orchestrator::keys::rotate");
+    }
+
+    #[test]
+    fn test_store_keeps_newest_and_tracks_forwarding() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CrashStore::new(&CrashConfig { enabled: true, directory: dir.path().to_path_buf(), retention: 2 });
+        assert!(store.last().unwrap().is_none());
+
+        let mut reports = Vec::new();
+        for n in 0..3u64 {
+            let mut report = CrashReport::new("sentinel", "1.0.0", "main", &format!("panic {}", n), None, String::new(), BTreeMap::new());
+            report.occurred_at = UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000 + n);
+            store.write(&report).unwrap();
+            reports.push(report);
+        }
+
+        let stored = store.reports().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].message, "panic 1");
+        assert_eq!(store.last().unwrap().unwrap().summary().message, "panic 2");
+
+        store.mark_forwarded(&reports[1], SystemTime::now()).unwrap();
+        let pending = store.unforwarded().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].message, "panic 2");
+        assert_eq!(store.reports().unwrap()[0].summary().health_details()["last_crash.forwarded"], "true");
+    }
+}
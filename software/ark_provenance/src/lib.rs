@@ -0,0 +1,264 @@
+//! ARK Patch Provenance
+//! "Prove all things; hold fast that which is good" - 1 Thessalonians 5:21
+//!
+//! When the patch orchestrator applies a patch it writes a provenance
+//! manifest next to the patched component: which patch is installed, its
+//! hashes and signatures, who approved it and when it was applied. Running
+//! components read the manifest through this crate to answer "what patch am
+//! I, who signed it, when" and include the answer in their health and
+//! attestation output.
+
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pq_types::canonical::CanonicalEncoder;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Manifest format version
+pub const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+/// Manifest file name inside a component directory
+pub const MANIFEST_FILE: &str = "PROVENANCE.json";
+
+/// Environment variable naming a component's directory
+///
+/// When unset, components look next to their executable.
+pub const COMPONENT_DIR_ENV: &str = "ARK_COMPONENT_DIR";
+
+/// Domain separator for attestation digests
+const ATTESTATION_DOMAIN: &[u8] = b"ark-provenance-attestation-v2";
+
+/// Longest accepted identifier field
+const MAX_FIELD_LENGTH: usize = 256;
+
+/// Provenance errors
+#[derive(Error, Debug)]
+pub enum ProvenanceError {
+    /// Manifest could not be read or written
+    #[error("Provenance I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Manifest is malformed or from an unsupported format
+    #[error("Invalid provenance manifest: {0}")]
+    Invalid(String),
+}
+
+/// Result type for provenance operations
+pub type ProvenanceResult<T> = Result<T, ProvenanceError>;
+
+/// Record of the patch installed in a component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProvenanceManifest {
+    /// Manifest format version
+    pub format: u32,
+    /// Patched component
+    pub component: String,
+    /// Namespace the patch was applied in
+    pub namespace: String,
+    /// Installed patch id
+    pub patch_id: String,
+    /// Installed patch version
+    pub version: String,
+    /// BLAKE3 hash of the patch payload (hex)
+    pub payload_hash: String,
+    /// Signature algorithm of the patch
+    pub signature_algorithm: String,
+    /// Dilithium3 patch signature (hex)
+    pub pq_signature: Option<String>,
+    /// Ed25519 patch signature (hex)
+    pub classical_signature: Option<String>,
+    /// Approvers whose detached approvals were verified
+    pub approvers: Vec<String>,
+    /// Whether an operator approved the patch interactively
+    pub operator_approved: bool,
+    /// When the patch was applied
+    pub applied_at: SystemTime,
+    /// Version of the orchestrator that applied the patch
+    pub applied_by: String,
+    /// Patch installed before this one, if known
+    pub previous_patch_id: Option<String>,
+}
+
+impl Validate for ProvenanceManifest {
+    fn validate(&self) -> Result<(), String> {
+        if self.format != MANIFEST_FORMAT_VERSION {
+            return Err(format!("unsupported manifest format {}", self.format));
+        }
+        decode::check_identifier("component", &self.component, MAX_FIELD_LENGTH)?;
+        decode::check_identifier("namespace", &self.namespace, MAX_FIELD_LENGTH)?;
+        decode::check_identifier("patch_id", &self.patch_id, MAX_FIELD_LENGTH)?;
+        decode::check_len("version", &self.version, MAX_FIELD_LENGTH)?;
+        decode::check_len("payload_hash", &self.payload_hash, 64)?;
+        decode::check_count("approvers", self.approvers.len(), MAX_FIELD_LENGTH)
+    }
+}
+
+impl ProvenanceManifest {
+    /// Path of the manifest inside a component directory
+    pub fn path(component_dir: &Path) -> PathBuf {
+        component_dir.join(MANIFEST_FILE)
+    }
+
+    /// Read the manifest of a component directory; `None` if never patched
+    pub fn load(component_dir: &Path) -> ProvenanceResult<Option<Self>> {
+        let path = Self::path(component_dir);
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        decode::json_validated(&bytes, &DecodeLimits::FILE)
+            .map(Some)
+            .map_err(|e| ProvenanceError::Invalid(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Write the manifest into a component directory
+    ///
+    /// The file is replaced atomically so readers never see a partial
+    /// manifest.
+    pub fn write(&self, component_dir: &Path) -> ProvenanceResult<PathBuf> {
+        let path = Self::path(component_dir);
+        let temp = component_dir.join(format!(".{}.tmp", MANIFEST_FILE));
+        let contents = serde_json::to_vec_pretty(self).map_err(|e| ProvenanceError::Invalid(e.to_string()))?;
+
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(&contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, &path)?;
+        Ok(path)
+    }
+
+    /// Digest binding the manifest, for inclusion in attestation reports
+    ///
+    /// Hashes the `pq_types::canonical` encoding of the attested fields.
+    pub fn attestation_digest(&self) -> [u8; 32] {
+        let mut encoder = CanonicalEncoder::new(ATTESTATION_DOMAIN);
+        encoder
+            .str(&self.component)
+            .str(&self.namespace)
+            .str(&self.patch_id)
+            .str(&self.version)
+            .str(&self.payload_hash)
+            .option(self.pq_signature.as_deref(), |encoder, signature| {
+                encoder.str(signature);
+            })
+            .option(self.classical_signature.as_deref(), |encoder, signature| {
+                encoder.str(signature);
+            })
+            .strs(&self.approvers)
+            .u64(unix_secs(self.applied_at));
+        *blake3::hash(&encoder.finish()).as_bytes()
+    }
+
+    /// Flat key/value summary for health output, keys prefixed `provenance.`
+    pub fn health_details(&self) -> BTreeMap<String, String> {
+        let mut details = BTreeMap::new();
+        let mut detail = |key: &str, value: String| {
+            details.insert(format!("provenance.{}", key), value);
+        };
+
+        detail("patch_id", self.patch_id.clone());
+        detail("version", self.version.clone());
+        detail("namespace", self.namespace.clone());
+        detail("payload_hash", self.payload_hash.clone());
+        detail("signature_algorithm", self.signature_algorithm.clone());
+        detail("signed", (self.pq_signature.is_some() || self.classical_signature.is_some()).to_string());
+        detail("approvers", self.approvers.join(","));
+        detail("applied_at", unix_secs(self.applied_at).to_string());
+        detail("attestation_digest", to_hex(&self.attestation_digest()));
+        details
+    }
+}
+
+/// Directory of the running component
+///
+/// Taken from `ARK_COMPONENT_DIR`, falling back to the directory of the
+/// current executable.
+pub fn component_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(COMPONENT_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::current_exe().ok()?.parent().map(Path::to_path_buf)
+}
+
+/// Provenance of the running component, if it was installed by a patch
+pub fn current() -> ProvenanceResult<Option<ProvenanceManifest>> {
+    match component_dir() {
+        Some(dir) => ProvenanceManifest::load(&dir),
+        None => Ok(None),
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> ProvenanceManifest {
+        ProvenanceManifest {
+            format: MANIFEST_FORMAT_VERSION,
+            component: "cold_mirror".to_string(),
+            namespace: "default".to_string(),
+            patch_id: "cm-2024-07".to_string(),
+            version: "1.4.0".to_string(),
+            payload_hash: "ab".repeat(32),
+            signature_algorithm: "HybridEd25519Dilithium3".to_string(),
+            pq_signature: Some("00".repeat(8)),
+            classical_signature: Some("11".repeat(8)),
+            approvers: vec!["alice".to_string(), "bob".to_string()],
+            operator_approved: false,
+            applied_at: UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000),
+            applied_by: "3.0.0".to_string(),
+            previous_patch_id: None,
+        }
+    }
+
+    #[test]
+    fn test_write_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(ProvenanceManifest::load(dir.path()).unwrap().is_none());
+
+        manifest().write(dir.path()).unwrap();
+        assert_eq!(ProvenanceManifest::load(dir.path()).unwrap(), Some(manifest()));
+
+        std::fs::write(ProvenanceManifest::path(dir.path()), br#"{"format":99}"#).unwrap();
+        assert!(ProvenanceManifest::load(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_attestation_digest_binds_signatures() {
+        let original = manifest();
+        let mut resigned = manifest();
+        resigned.pq_signature = Some("ff".repeat(8));
+        assert_ne!(original.attestation_digest(), resigned.attestation_digest());
+        assert_eq!(to_hex(&original.attestation_digest()), "76793cc92713160361bed405167665db846e490cbbb5b3f69fe03e95d27b8d7e");
+
+        // Fields outside the attested set do not change the digest
+        let mut annotated = manifest();
+        annotated.applied_by = "3.1.0".to_string();
+        assert_eq!(original.attestation_digest(), annotated.attestation_digest());
+    }
+
+    #[test]
+    fn test_health_details() {
+        let details = manifest().health_details();
+        assert_eq!(details["provenance.patch_id"], "cm-2024-07");
+        assert_eq!(details["provenance.approvers"], "alice,bob");
+        assert_eq!(details["provenance.applied_at"], "1700000000");
+        assert_eq!(details["provenance.attestation_digest"].len(), 64);
+    }
+}
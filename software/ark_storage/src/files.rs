@@ -0,0 +1,212 @@
+//! Plain-files backend
+//!
+//! Each tree is a directory and each entry a file named by the hex of its
+//! key, so directory order is key order. Values are replaced by writing a
+//! temporary file and renaming it over the entry. A batch is first written
+//! as a journal file next to the trees and only then applied; a journal
+//! left by a crash is replayed when the store is next opened, and since
+//! its operations are idempotent the batch ends up applied in full.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{check_tree, Backend, Batch, BatchOp, Storage, StorageError, StorageResult};
+
+/// File name prefix of batch journals
+const JOURNAL_PREFIX: &str = ".batch-";
+
+/// Store of one file per entry below a directory
+#[derive(Debug)]
+pub struct FileStorage {
+    root: PathBuf,
+    /// Serializes batches of this process
+    batches: Mutex<()>,
+}
+
+impl FileStorage {
+    /// Open or create the store at `root`, finishing batches cut short by a crash
+    pub fn open(root: impl Into<PathBuf>) -> StorageResult<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        let storage = Self { root, batches: Mutex::new(()) };
+
+        let mut journals: Vec<PathBuf> = fs::read_dir(&storage.root)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(JOURNAL_PREFIX)))
+            .collect();
+        journals.sort();
+        for journal in journals {
+            // A journal that does not decode was torn before any of it was applied
+            if let Ok(batch) = bincode::deserialize::<Batch>(&fs::read(&journal)?) {
+                storage.write_ops(&batch)?;
+            }
+            fs::remove_file(&journal)?;
+        }
+        Ok(storage)
+    }
+
+    /// Directory of the store
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn tree_dir(&self, tree: &str) -> StorageResult<PathBuf> {
+        check_tree(tree)?;
+        Ok(self.root.join(tree))
+    }
+
+    fn entry_path(&self, tree: &str, key: &[u8]) -> StorageResult<PathBuf> {
+        // An empty key still needs a file name
+        Ok(self.tree_dir(tree)?.join(format!("k{}", hex::encode(key))))
+    }
+
+    /// Temporary file next to `path`, unique to this process and call
+    fn temp_path(path: &Path) -> PathBuf {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("entry");
+        path.with_file_name(format!(".{}.{}.tmp", name, unique_suffix()))
+    }
+
+    fn write_temp(path: &Path, contents: &[u8]) -> StorageResult<PathBuf> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = Self::temp_path(path);
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        Ok(temp)
+    }
+
+    fn write_ops(&self, batch: &Batch) -> StorageResult<()> {
+        for op in batch.ops() {
+            match op {
+                BatchOp::Put { tree, key, value } => self.put(tree, key, value)?,
+                BatchOp::Delete { tree, key } => match fs::remove_file(self.entry_path(tree, key)?) {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Process id and time, distinguishing temporary files of concurrent writers
+fn unique_suffix() -> String {
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_nanos()).unwrap_or_default();
+    format!("{}-{}", std::process::id(), stamp)
+}
+
+impl Storage for FileStorage {
+    fn backend(&self) -> Backend {
+        Backend::Files
+    }
+
+    fn get(&self, tree: &str, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        match fs::read(self.entry_path(tree, key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn put(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        let path = self.entry_path(tree, key)?;
+        let temp = Self::write_temp(&path, value)?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    fn insert_new(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<bool> {
+        let path = self.entry_path(tree, key)?;
+        let temp = Self::write_temp(&path, value)?;
+        // Linking fails if the entry exists, and never exposes half a value
+        let linked = fs::hard_link(&temp, &path);
+        fs::remove_file(&temp)?;
+        match linked {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn scan(&self, tree: &str, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        let dir = self.tree_dir(tree)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let prefix = format!("k{}", hex::encode(prefix));
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with(&prefix))
+            .collect();
+        names.sort();
+
+        let mut scanned = Vec::with_capacity(names.len());
+        for name in names {
+            let key = hex::decode(&name[1..])
+                .map_err(|_| StorageError::Corrupt(format!("{:?} in tree {} is not an entry", name, tree)))?;
+            match fs::read(dir.join(&name)) {
+                Ok(value) => scanned.push((key, value)),
+                // Deleted since the directory was listed
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(scanned)
+    }
+
+    fn apply(&self, batch: &Batch) -> StorageResult<()> {
+        for op in batch.ops() {
+            check_tree(op.tree())?;
+        }
+        let _guard = self.batches.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let journal = self.root.join(format!("{}{}", JOURNAL_PREFIX, unique_suffix()));
+        let encoded = bincode::serialize(batch).map_err(|e| StorageError::Backend(e.to_string()))?;
+        let temp = Self::write_temp(&journal, &encoded)?;
+        fs::rename(&temp, &journal)?;
+
+        self.write_ops(batch)?;
+        fs::remove_file(&journal)?;
+        Ok(())
+    }
+
+    fn trees(&self) -> StorageResult<Vec<String>> {
+        let mut trees = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else { continue };
+            if entry.file_type()?.is_dir() && check_tree(&name).is_ok() && self.last(&name)?.is_some() {
+                trees.push(name);
+            }
+        }
+        trees.sort();
+        Ok(trees)
+    }
+
+    fn last(&self, tree: &str) -> StorageResult<Option<(Vec<u8>, Vec<u8>)>> {
+        let dir = self.tree_dir(tree)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let last = entries
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.starts_with('k'))
+            .max();
+        match last {
+            Some(name) => {
+                let key = hex::decode(&name[1..])
+                    .map_err(|_| StorageError::Corrupt(format!("{:?} in tree {} is not an entry", name, tree)))?;
+                Ok(self.get(tree, &key)?.map(|value| (key, value)))
+            }
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! ARK Storage Backends
+//! "Moreover it is required in stewards, that a man be found faithful" - 1 Corinthians 4:2
+//!
+//! The orchestrator's audit log and patch repository, the decision journal
+//! (the actor ledger) and the Cold-Mirror quarantine queue keep their data
+//! through the [`Storage`] trait: named trees of byte keys and values with
+//! get, put, ordered prefix scans and atomic batches. Deployments choose the
+//! backend in their [`StorageConfig`]: plain files (the default, one file
+//! per entry), sled, or SQLite. The embedded databases are behind the
+//! `sled` and `sqlite` features.
+//!
+//! Append-only logs key their entries by [`sequence_key`] and add them with
+//! [`append`], so every backend returns them oldest first. [`migrate`]
+//! copies trees from one backend to another and checks the copy.
+
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+mod files;
+#[cfg(feature = "sled")]
+mod sled_backend;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use files::FileStorage;
+#[cfg(feature = "sled")]
+pub use sled_backend::SledStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Longest tree name
+pub const MAX_TREE_NAME_LENGTH: usize = 64;
+
+/// Entries copied per batch by [`migrate`]
+const MIGRATION_BATCH: usize = 1024;
+
+/// Storage errors
+#[derive(Error, Debug)]
+pub enum StorageError {
+    /// Filesystem error of the files backend, or opening a database
+    #[error("Storage I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error reported by a database backend
+    #[error("Storage backend error: {0}")]
+    Backend(String),
+
+    /// Tree name that is not a short lowercase identifier
+    #[error("Invalid tree name {0:?}")]
+    InvalidTree(String),
+
+    /// Stored data that does not have the expected shape
+    #[error("Corrupt storage: {0}")]
+    Corrupt(String),
+
+    /// Backend not compiled into this build
+    #[error("Storage backend {0:?} is not available in this build")]
+    Unavailable(Backend),
+
+    /// Migration refused or failed its check
+    #[error("Migration failed: {0}")]
+    Migration(String),
+}
+
+/// Result type for storage operations
+pub type StorageResult<T> = Result<T, StorageError>;
+
+/// Storage backend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// One file per entry below a directory
+    #[default]
+    Files,
+    /// sled database directory (`sled` feature); one process at a time
+    Sled,
+    /// SQLite database file (`sqlite` feature)
+    Sqlite,
+}
+
+/// Backend and location of a store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Backend holding the store
+    pub backend: Backend,
+    /// Directory for files and sled, database file for SQLite
+    pub path: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self { backend: Backend::Files, path: PathBuf::from("store") }
+    }
+}
+
+impl StorageConfig {
+    /// This configuration with a relative path taken below `base`
+    pub fn below(&self, base: &Path) -> Self {
+        Self { backend: self.backend, path: base.join(&self.path) }
+    }
+}
+
+/// One operation of a [`Batch`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BatchOp {
+    /// Store `value` under `key`
+    #[allow(missing_docs)]
+    Put { tree: String, key: Vec<u8>, value: Vec<u8> },
+    /// Remove `key`, if present
+    #[allow(missing_docs)]
+    Delete { tree: String, key: Vec<u8> },
+}
+
+impl BatchOp {
+    /// Tree the operation writes
+    pub fn tree(&self) -> &str {
+        match self {
+            BatchOp::Put { tree, .. } | BatchOp::Delete { tree, .. } => tree,
+        }
+    }
+}
+
+/// Operations applied together or not at all
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Batch {
+    ops: Vec<BatchOp>,
+}
+
+impl Batch {
+    /// Empty batch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value` under `key` in `tree`
+    pub fn put(&mut self, tree: &str, key: &[u8], value: &[u8]) -> &mut Self {
+        self.ops.push(BatchOp::Put { tree: tree.to_string(), key: key.to_vec(), value: value.to_vec() });
+        self
+    }
+
+    /// Remove `key` from `tree`
+    pub fn delete(&mut self, tree: &str, key: &[u8]) -> &mut Self {
+        self.ops.push(BatchOp::Delete { tree: tree.to_string(), key: key.to_vec() });
+        self
+    }
+
+    /// Operations in the order they were added
+    pub fn ops(&self) -> &[BatchOp] {
+        &self.ops
+    }
+
+    /// Number of operations
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Whether the batch holds no operation
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Key-value store of named trees
+///
+/// Scans return entries in byte order of their keys. Trees spring into
+/// existence with their first entry.
+pub trait Storage: Send + Sync + std::fmt::Debug {
+    /// Backend serving this store
+    fn backend(&self) -> Backend;
+
+    /// Value stored under `key`
+    fn get(&self, tree: &str, key: &[u8]) -> StorageResult<Option<Vec<u8>>>;
+
+    /// Store `value` under `key`, replacing any previous value
+    fn put(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<()>;
+
+    /// Store `value` under `key` unless the key is present; returns whether it was stored
+    fn insert_new(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<bool>;
+
+    /// Entries whose key starts with `prefix`, in key order
+    fn scan(&self, tree: &str, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    /// Apply every operation of `batch`, or none of them
+    fn apply(&self, batch: &Batch) -> StorageResult<()>;
+
+    /// Names of the trees holding entries, sorted
+    fn trees(&self) -> StorageResult<Vec<String>>;
+
+    /// Entry with the greatest key
+    fn last(&self, tree: &str) -> StorageResult<Option<(Vec<u8>, Vec<u8>)>> {
+        Ok(self.scan(tree, &[])?.pop())
+    }
+
+    /// Remove `key`, if present
+    fn delete(&self, tree: &str, key: &[u8]) -> StorageResult<()> {
+        let mut batch = Batch::new();
+        batch.delete(tree, key);
+        self.apply(&batch)
+    }
+}
+
+/// Open the store `config` describes, creating it if missing
+pub fn open(config: &StorageConfig) -> StorageResult<Arc<dyn Storage>> {
+    match config.backend {
+        Backend::Files => Ok(Arc::new(FileStorage::open(&config.path)?)),
+        #[cfg(feature = "sled")]
+        Backend::Sled => Ok(Arc::new(SledStorage::open(&config.path)?)),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Ok(Arc::new(SqliteStorage::open(&config.path)?)),
+        #[allow(unreachable_patterns)]
+        backend => Err(StorageError::Unavailable(backend)),
+    }
+}
+
+/// Check that `tree` is a short lowercase identifier, safe as a directory name
+pub fn check_tree(tree: &str) -> StorageResult<()> {
+    let valid = !tree.is_empty()
+        && tree.len() <= MAX_TREE_NAME_LENGTH
+        && !tree.starts_with('.')
+        && tree.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'));
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidTree(tree.to_string()))
+    }
+}
+
+/// Key of entry `sequence` of an append-only tree; big-endian, so keys sort in sequence
+pub fn sequence_key(sequence: u64) -> [u8; 8] {
+    sequence.to_be_bytes()
+}
+
+/// Sequence number of an append-only tree key
+pub fn sequence_of(key: &[u8]) -> StorageResult<u64> {
+    key.try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| StorageError::Corrupt(format!("{}-byte key in an append-only tree", key.len())))
+}
+
+/// Append `value` to an append-only tree, returning its sequence number
+///
+/// Concurrent appenders each get their own sequence number: one that
+/// loses the race for a number retries with the next.
+pub fn append(storage: &dyn Storage, tree: &str, value: &[u8]) -> StorageResult<u64> {
+    loop {
+        let next = match storage.last(tree)? {
+            Some((key, _)) => sequence_of(&key)? + 1,
+            None => 0,
+        };
+        if storage.insert_new(tree, &sequence_key(next), value)? {
+            return Ok(next);
+        }
+    }
+}
+
+/// Entries copied per tree by [`migrate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Backend copied from
+    pub from: Option<Backend>,
+    /// Backend copied to
+    pub to: Option<Backend>,
+    /// Entries copied, by tree
+    pub trees: BTreeMap<String, usize>,
+}
+
+impl MigrationReport {
+    /// Entries copied over all trees
+    pub fn entries(&self) -> usize {
+        self.trees.values().sum()
+    }
+}
+
+/// Copy `trees` (every tree when empty) from `from` into `to`
+///
+/// A tree already holding entries in `to` is refused rather than merged.
+/// Entries are copied in batches; afterwards every copied tree is read
+/// back and compared with its source, so a report is only returned for a
+/// complete copy. The source is left as it was.
+pub fn migrate(from: &dyn Storage, to: &dyn Storage, trees: &[String]) -> StorageResult<MigrationReport> {
+    let trees = if trees.is_empty() { from.trees()? } else { trees.to_vec() };
+    for tree in &trees {
+        check_tree(tree)?;
+        if !to.scan(tree, &[])?.is_empty() {
+            return Err(StorageError::Migration(format!("Target tree {} is not empty", tree)));
+        }
+    }
+
+    let mut report = MigrationReport { from: Some(from.backend()), to: Some(to.backend()), trees: BTreeMap::new() };
+    for tree in &trees {
+        let entries = from.scan(tree, &[])?;
+        for chunk in entries.chunks(MIGRATION_BATCH) {
+            let mut batch = Batch::new();
+            for (key, value) in chunk {
+                batch.put(tree, key, value);
+            }
+            to.apply(&batch)?;
+        }
+        if to.scan(tree, &[])? != entries {
+            return Err(StorageError::Migration(format!("Copy of tree {} does not match its source", tree)));
+        }
+        report.trees.insert(tree.clone(), entries.len());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_migrate_between_stores() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = FileStorage::open(dir.path().join("source")).unwrap();
+        for value in [b"first".as_slice(), b"second", b"third"] {
+            append(&source, "audit", value).unwrap();
+        }
+        let mut batch = Batch::new();
+        batch.put("patches.pending", b"default/patch-1", b"{}").put("patches.pending", b"eu/patch-2", b"{}");
+        source.apply(&batch).unwrap();
+
+        let target = FileStorage::open(dir.path().join("target")).unwrap();
+        let report = migrate(&source, &target, &[]).unwrap();
+        assert_eq!(report.trees["audit"], 3);
+        assert_eq!(report.entries(), 5);
+        assert_eq!(target.last("audit").unwrap().unwrap(), (sequence_key(2).to_vec(), b"third".to_vec()));
+        assert_eq!(target.scan("patches.pending", b"eu/").unwrap().len(), 1);
+
+        // Migrating again would merge into populated trees
+        assert!(matches!(migrate(&source, &target, &[]), Err(StorageError::Migration(_))));
+        assert!(check_tree("../escape").is_err());
+    }
+
+    #[test]
+    fn test_backends_agree_on_scans_and_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut backends = vec![StorageConfig { backend: Backend::Files, path: dir.path().join("files") }];
+        if cfg!(feature = "sled") {
+            backends.push(StorageConfig { backend: Backend::Sled, path: dir.path().join("sled") });
+        }
+        if cfg!(feature = "sqlite") {
+            backends.push(StorageConfig { backend: Backend::Sqlite, path: dir.path().join("store.sqlite") });
+        }
+
+        for config in backends {
+            let storage = open(&config).unwrap();
+            storage.put("quarantine.entries", b"b1", b"sealed").unwrap();
+            assert!(storage.insert_new("quarantine.entries", b"a2", b"first").unwrap());
+            assert!(!storage.insert_new("quarantine.entries", b"a2", b"second").unwrap());
+            storage.put("quarantine.entries", b"a1", b"sealed").unwrap();
+
+            let mut batch = Batch::new();
+            batch.delete("quarantine.entries", b"a1").put("quarantine.access", &sequence_key(0), b"purged");
+            storage.apply(&batch).unwrap();
+
+            let scanned = storage.scan("quarantine.entries", b"a").unwrap();
+            assert_eq!(scanned, vec![(b"a2".to_vec(), b"first".to_vec())], "{:?}", config.backend);
+            assert_eq!(storage.last("quarantine.entries").unwrap().unwrap().0, b"b1");
+            assert_eq!(storage.trees().unwrap(), vec!["quarantine.access", "quarantine.entries"]);
+            assert_eq!(storage.get("quarantine.entries", b"a1").unwrap(), None);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! sled backend
+//!
+//! Each tree is a sled tree of the same name. Batches spanning several
+//! trees run as one sled transaction and are flushed before they return.
+//! A sled database is locked by the process that opened it.
+
+use std::path::Path;
+
+use sled::transaction::{ConflictableTransactionError, TransactionError};
+use sled::Transactional;
+
+use crate::{check_tree, Backend, Batch, BatchOp, Storage, StorageError, StorageResult};
+
+/// Store in a sled database directory
+#[derive(Debug)]
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+impl SledStorage {
+    /// Open or create the database at `path`
+    pub fn open(path: &Path) -> StorageResult<Self> {
+        Ok(Self { db: sled::open(path).map_err(backend_error)? })
+    }
+
+    fn tree(&self, tree: &str) -> StorageResult<sled::Tree> {
+        check_tree(tree)?;
+        self.db.open_tree(tree).map_err(backend_error)
+    }
+
+    fn flush(&self) -> StorageResult<()> {
+        self.db.flush().map(|_| ()).map_err(backend_error)
+    }
+}
+
+impl Storage for SledStorage {
+    fn backend(&self) -> Backend {
+        Backend::Sled
+    }
+
+    fn get(&self, tree: &str, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        Ok(self.tree(tree)?.get(key).map_err(backend_error)?.map(|value| value.to_vec()))
+    }
+
+    fn put(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        self.tree(tree)?.insert(key, value).map_err(backend_error)?;
+        self.flush()
+    }
+
+    fn insert_new(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<bool> {
+        let swapped = self.tree(tree)?
+            .compare_and_swap(key, None as Option<&[u8]>, Some(value))
+            .map_err(backend_error)?;
+        self.flush()?;
+        Ok(swapped.is_ok())
+    }
+
+    fn scan(&self, tree: &str, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.tree(tree)?
+            .scan_prefix(prefix)
+            .map(|entry| entry.map(|(key, value)| (key.to_vec(), value.to_vec())).map_err(backend_error))
+            .collect()
+    }
+
+    fn apply(&self, batch: &Batch) -> StorageResult<()> {
+        let mut names: Vec<&str> = batch.ops().iter().map(BatchOp::tree).collect();
+        names.sort_unstable();
+        names.dedup();
+        let trees = names.iter().map(|name| self.tree(name)).collect::<StorageResult<Vec<_>>>()?;
+
+        trees.as_slice()
+            .transaction(|transactional| {
+                for op in batch.ops() {
+                    let index = names.binary_search(&op.tree()).expect("tree opened above");
+                    match op {
+                        BatchOp::Put { key, value, .. } => {
+                            transactional[index].insert(key.as_slice(), value.as_slice())?;
+                        }
+                        BatchOp::Delete { key, .. } => {
+                            transactional[index].remove(key.as_slice())?;
+                        }
+                    }
+                }
+                Ok::<(), ConflictableTransactionError<()>>(())
+            })
+            .map_err(|e: TransactionError<()>| StorageError::Backend(format!("{:?}", e)))?;
+        self.flush()
+    }
+
+    fn trees(&self) -> StorageResult<Vec<String>> {
+        let mut trees = Vec::new();
+        for name in self.db.tree_names() {
+            let name = String::from_utf8_lossy(&name).into_owned();
+            if check_tree(&name).is_ok() && !self.tree(&name)?.is_empty() {
+                trees.push(name);
+            }
+        }
+        trees.sort();
+        Ok(trees)
+    }
+
+    fn last(&self, tree: &str) -> StorageResult<Option<(Vec<u8>, Vec<u8>)>> {
+        Ok(self.tree(tree)?.last().map_err(backend_error)?.map(|(key, value)| (key.to_vec(), value.to_vec())))
+    }
+}
+
+fn backend_error(e: sled::Error) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
@@ -0,0 +1,139 @@
+//! SQLite backend
+//!
+//! Every tree lives in one `entries` table keyed by tree and key; BLOB
+//! keys compare bytewise, so `ORDER BY key` is key order. Batches run in a
+//! transaction. The database is opened in WAL mode so readers in other
+//! processes do not block the writer.
+
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{check_tree, Backend, Batch, BatchOp, Storage, StorageError, StorageResult};
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS entries (
+    tree TEXT NOT NULL,
+    key BLOB NOT NULL,
+    value BLOB NOT NULL,
+    PRIMARY KEY (tree, key)
+) WITHOUT ROWID";
+
+const UPSERT: &str = "INSERT INTO entries (tree, key, value) VALUES (?1, ?2, ?3)
+    ON CONFLICT (tree, key) DO UPDATE SET value = excluded.value";
+
+/// Store in a SQLite database file
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open or create the database at `path`
+    pub fn open(path: &Path) -> StorageResult<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(path).map_err(backend_error)?;
+        connection.pragma_update(None, "journal_mode", "WAL").map_err(backend_error)?;
+        connection.pragma_update(None, "synchronous", "FULL").map_err(backend_error)?;
+        connection.busy_timeout(std::time::Duration::from_secs(5)).map_err(backend_error)?;
+        connection.execute(SCHEMA, []).map_err(backend_error)?;
+        Ok(Self { connection: Mutex::new(connection) })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn backend(&self) -> Backend {
+        Backend::Sqlite
+    }
+
+    fn get(&self, tree: &str, key: &[u8]) -> StorageResult<Option<Vec<u8>>> {
+        check_tree(tree)?;
+        self.connection()
+            .query_row("SELECT value FROM entries WHERE tree = ?1 AND key = ?2", params![tree, key], |row| row.get(0))
+            .optional()
+            .map_err(backend_error)
+    }
+
+    fn put(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<()> {
+        check_tree(tree)?;
+        self.connection().execute(UPSERT, params![tree, key, value]).map_err(backend_error)?;
+        Ok(())
+    }
+
+    fn insert_new(&self, tree: &str, key: &[u8], value: &[u8]) -> StorageResult<bool> {
+        check_tree(tree)?;
+        let inserted = self.connection()
+            .execute("INSERT OR IGNORE INTO entries (tree, key, value) VALUES (?1, ?2, ?3)", params![tree, key, value])
+            .map_err(backend_error)?;
+        Ok(inserted == 1)
+    }
+
+    fn scan(&self, tree: &str, prefix: &[u8]) -> StorageResult<Vec<(Vec<u8>, Vec<u8>)>> {
+        check_tree(tree)?;
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT key, value FROM entries WHERE tree = ?1 AND key >= ?2 ORDER BY key")
+            .map_err(backend_error)?;
+        let rows = statement
+            .query_map(params![tree, prefix], |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, Vec<u8>>(1)?)))
+            .map_err(backend_error)?;
+
+        let mut scanned = Vec::new();
+        for row in rows {
+            let (key, value) = row.map_err(backend_error)?;
+            // Keys at or after the prefix that no longer start with it end the scan
+            if !key.starts_with(prefix) {
+                break;
+            }
+            scanned.push((key, value));
+        }
+        Ok(scanned)
+    }
+
+    fn apply(&self, batch: &Batch) -> StorageResult<()> {
+        for op in batch.ops() {
+            check_tree(op.tree())?;
+        }
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(backend_error)?;
+        for op in batch.ops() {
+            match op {
+                BatchOp::Put { tree, key, value } => transaction.execute(UPSERT, params![tree, key, value]),
+                BatchOp::Delete { tree, key } => {
+                    transaction.execute("DELETE FROM entries WHERE tree = ?1 AND key = ?2", params![tree, key])
+                }
+            }
+            .map_err(backend_error)?;
+        }
+        transaction.commit().map_err(backend_error)
+    }
+
+    fn trees(&self) -> StorageResult<Vec<String>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare("SELECT DISTINCT tree FROM entries ORDER BY tree").map_err(backend_error)?;
+        let names = statement.query_map([], |row| row.get(0)).map_err(backend_error)?;
+        names.collect::<Result<Vec<String>, _>>().map_err(backend_error)
+    }
+
+    fn last(&self, tree: &str) -> StorageResult<Option<(Vec<u8>, Vec<u8>)>> {
+        check_tree(tree)?;
+        self.connection()
+            .query_row(
+                "SELECT key, value FROM entries WHERE tree = ?1 ORDER BY key DESC LIMIT 1",
+                params![tree],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(backend_error)
+    }
+}
+
+fn backend_error(e: rusqlite::Error) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
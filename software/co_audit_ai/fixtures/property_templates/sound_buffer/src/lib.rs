@@ -0,0 +1,57 @@
+//! Byte ring buffer whose annotated memory-safety properties all hold
+
+// @property field_invariant(struct = RingBuffer, invariant = "len <= capacity")
+pub struct RingBuffer {
+    data: Vec<u8>,
+    head: usize,
+    len: usize,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { data: vec![0; capacity.max(1)], head: 0, len: 0, capacity: capacity.max(1) }
+    }
+
+    // @property no_oob_index(fn = get, slice = data)
+    pub fn get(&self, i: usize) -> Option<u8> {
+        if i >= self.len {
+            return None;
+        }
+        Some(self.data[(self.head + i) % self.data.len()])
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        let tail = (self.head + self.len) % self.data.len();
+        self.data[tail] = byte;
+        if self.len < self.capacity {
+            self.len += 1;
+        } else {
+            self.head = (self.head + 1) % self.data.len();
+        }
+        debug_assert!(self.len <= self.capacity);
+    }
+
+    // @property unsafe_documented(fn = first)
+    pub fn first(&self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        // SAFETY: head is always reduced modulo data.len()
+        Some(unsafe { *self.data.get_unchecked(self.head) })
+    }
+}
+
+// @property null_checked(fn = copy_from_raw, ptr = src)
+pub fn copy_from_raw(src: *const u8, len: usize) -> Vec<u8> {
+    if src.is_null() {
+        return Vec::new();
+    }
+    // SAFETY: the caller guarantees `src` is valid for `len` bytes
+    unsafe { std::slice::from_raw_parts(src, len).to_vec() }
+}
+
+// @property provenance_preserved(fn = offset_ptr)
+pub fn offset_ptr(base: *const u8, offset: usize) -> *const u8 {
+    base.wrapping_add(offset)
+}
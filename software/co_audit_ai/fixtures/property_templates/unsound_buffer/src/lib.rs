@@ -0,0 +1,45 @@
+//! Byte ring buffer violating each of its annotated memory-safety properties
+
+// @property field_invariant(struct = RingBuffer, invariant = "len <= capacity")
+pub struct RingBuffer {
+    data: Vec<u8>,
+    head: usize,
+    pub len: usize,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { data: vec![0; capacity], head: 0, len: 0, capacity }
+    }
+
+    // @property no_oob_index(fn = get, slice = data)
+    pub fn get(&self, i: usize) -> Option<u8> {
+        Some(self.data[self.head + i])
+    }
+
+    pub fn push(&mut self, byte: u8) {
+        let tail = self.head + self.len;
+        self.data[tail] = byte;
+        self.len += 1;
+    }
+
+    // @property unsafe_documented(fn = first)
+    pub fn first(&self) -> Option<u8> {
+        Some(unsafe { *self.data.get_unchecked(self.head) })
+    }
+}
+
+// @property null_checked(fn = copy_from_raw, ptr = src)
+pub fn copy_from_raw(src: *const u8, len: usize) -> Vec<u8> {
+    let bytes = unsafe { std::slice::from_raw_parts(src, len).to_vec() };
+    if src.is_null() {
+        return Vec::new();
+    }
+    bytes
+}
+
+// @property provenance_preserved(fn = offset_ptr)
+pub fn offset_ptr(base: *const u8, offset: usize) -> *const u8 {
+    (base as usize + offset) as *const u8
+}
@@ -0,0 +1,381 @@
+//! Moral Score Trends
+//!
+//! The release gate only fails once a score has already dropped between
+//! two branches. This module keeps a history of audit results per component
+//! and of ethics decisions per actor class, bucketed by time, so that a
+//! slow slide shows up first. For each series it reports the classification
+//! or decision mix and the least-squares slope of its moral score per day
+//! over the most recent buckets: the mean moral score of the audited files
+//! for a component, the share of `Allow` decisions for an actor class. A
+//! series whose score falls faster than `max_decline_per_day` raises a
+//! `DriftAlert`.
+//!
+//! ## Biblical Foundation
+//! "Take heed therefore how ye hear" - Luke 8:18
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ark_alert::{Alert, Severity};
+use ethics_dsl::{EthicsDecision, JournalEntry};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::diff::BranchAudit;
+use crate::{AuditResult, CoAuditError};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Trend analysis settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrendConfig {
+    /// Width of a time bucket in seconds
+    pub bucket_secs: u64,
+    /// Most recent buckets a trend is fitted over
+    pub window_buckets: u64,
+    /// Buckets with data needed before a series can raise an alert
+    pub min_buckets: usize,
+    /// Score decline per day that raises an alert
+    pub max_decline_per_day: f64,
+    /// History file updated by differential audits; `None` keeps no history
+    pub history: Option<PathBuf>,
+}
+
+impl Default for TrendConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 86_400,
+            window_buckets: 14,
+            min_buckets: 3,
+            max_decline_per_day: 0.01,
+            history: None,
+        }
+    }
+}
+
+/// Audit results of one component within one bucket
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditBucket {
+    pub files: u64,
+    pub moral_sum: f64,
+    pub classifications: BTreeMap<String, u64>,
+}
+
+/// Ethics decisions of one actor class within one bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecisionMix {
+    pub allow: u64,
+    pub deny: u64,
+    pub purge: u64,
+}
+
+impl DecisionMix {
+    fn total(&self) -> u64 {
+        self.allow + self.deny + self.purge
+    }
+
+    fn add(&mut self, other: &DecisionMix) {
+        self.allow += other.allow;
+        self.deny += other.deny;
+        self.purge += other.purge;
+    }
+}
+
+/// Series a trend is computed for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendSeries {
+    /// Crate owning the audited files
+    Component(String),
+    /// `ActorType` of evaluated events
+    ActorClass(String),
+}
+
+/// Trend of one series over the window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trend {
+    /// Buckets of the window holding data
+    pub buckets: usize,
+    /// Score of the most recent bucket
+    pub latest: f64,
+    /// Least-squares slope of the score; `None` below two buckets
+    pub slope_per_day: Option<f64>,
+    /// Classification counts (components) or decision counts (actor classes) over the window
+    pub mix: BTreeMap<String, u64>,
+}
+
+/// Series whose moral score degrades faster than allowed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DriftAlert {
+    pub series: TrendSeries,
+    pub slope_per_day: f64,
+    pub threshold: f64,
+    pub latest: f64,
+}
+
+/// Trends of every series and the alerts they raise
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrendReport {
+    pub components: BTreeMap<String, Trend>,
+    pub actor_classes: BTreeMap<String, Trend>,
+    pub alerts: Vec<DriftAlert>,
+}
+
+/// Bucketed history of audit scores and decision mixes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MoralTrends {
+    /// Bucket width the history was recorded with
+    pub bucket_secs: u64,
+    /// Component -> bucket index -> audits
+    pub components: BTreeMap<String, BTreeMap<u64, AuditBucket>>,
+    /// Actor class -> bucket index -> decisions
+    pub actor_classes: BTreeMap<String, BTreeMap<u64, DecisionMix>>,
+}
+
+impl MoralTrends {
+    /// Empty history bucketed per `config`
+    pub fn new(config: &TrendConfig) -> Self {
+        Self { bucket_secs: config.bucket_secs.max(1), ..Default::default() }
+    }
+
+    /// Load a history file, or start an empty one when it does not exist
+    ///
+    /// A history recorded with a different bucket width is discarded.
+    pub fn load(path: &Path, config: &TrendConfig) -> Result<Self, CoAuditError> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new(config)),
+            Err(e) => return Err(CoAuditError::Trends(format!("{}: {}", path.display(), e))),
+        };
+        let trends: Self = serde_json::from_slice(&bytes)
+            .map_err(|e| CoAuditError::Trends(format!("{}: {}", path.display(), e)))?;
+        if trends.bucket_secs != config.bucket_secs.max(1) {
+            warn!("Discarding trend history {:?} recorded with {}s buckets", path, trends.bucket_secs);
+            return Ok(Self::new(config));
+        }
+        Ok(trends)
+    }
+
+    /// Write the history to `path`
+    pub fn save(&self, path: &Path) -> Result<(), CoAuditError> {
+        let bytes = serde_json::to_vec(self).map_err(|e| CoAuditError::Trends(e.to_string()))?;
+        std::fs::write(path, bytes).map_err(|e| CoAuditError::Trends(format!("{}: {}", path.display(), e)))
+    }
+
+    fn bucket(&self, at: SystemTime) -> u64 {
+        at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() / self.bucket_secs.max(1)
+    }
+
+    /// Record an audit result of `component` at its audit time
+    pub fn record_audit(&mut self, component: &str, result: &AuditResult) {
+        let bucket = self.bucket(result.audit_timestamp);
+        let entry = self.components.entry(component.to_string()).or_default().entry(bucket).or_default();
+        entry.files += 1;
+        entry.moral_sum += result.moral_score;
+        *entry.classifications.entry(format!("{:?}", result.classification)).or_default() += 1;
+    }
+
+    /// Record every result of a branch audit
+    pub fn record_branch(&mut self, audit: &BranchAudit) {
+        for (component, result) in &audit.results {
+            self.record_audit(component, result);
+        }
+    }
+
+    /// Record journaled ethics decisions by actor class
+    pub fn record_decisions(&mut self, entries: &[JournalEntry]) {
+        for entry in entries {
+            let bucket = self.bucket(SystemTime::from(entry.recorded_at));
+            let class = format!("{:?}", entry.event.actor.actor_type);
+            let mix = self.actor_classes.entry(class).or_default().entry(bucket).or_default();
+            match entry.decision {
+                EthicsDecision::Allow { .. } => mix.allow += 1,
+                EthicsDecision::Deny { .. } => mix.deny += 1,
+                EthicsDecision::Purge { .. } => mix.purge += 1,
+            }
+        }
+    }
+
+    /// Fit trends over the window ending at `now` and collect the alerts
+    pub fn report(&self, config: &TrendConfig, now: SystemTime) -> TrendReport {
+        let last = self.bucket(now);
+        let first = last.saturating_sub(config.window_buckets.max(1) - 1);
+        let bucket_days = self.bucket_secs.max(1) as f64 / SECS_PER_DAY;
+        let mut report = TrendReport::default();
+
+        for (name, buckets) in &self.components {
+            let window: Vec<_> = buckets.range(first..=last).collect();
+            let points = window.iter()
+                .map(|(index, bucket)| (**index as f64 * bucket_days, bucket.moral_sum / bucket.files.max(1) as f64));
+            let mut mix = BTreeMap::new();
+            for (_, bucket) in &window {
+                for (classification, count) in &bucket.classifications {
+                    *mix.entry(classification.clone()).or_default() += count;
+                }
+            }
+            if let Some(trend) = fit(points.collect(), mix) {
+                alert(&mut report.alerts, TrendSeries::Component(name.clone()), &trend, config);
+                report.components.insert(name.clone(), trend);
+            }
+        }
+
+        for (class, buckets) in &self.actor_classes {
+            let window: Vec<_> = buckets.range(first..=last).filter(|(_, mix)| mix.total() > 0).collect();
+            let points = window.iter()
+                .map(|(index, mix)| (**index as f64 * bucket_days, mix.allow as f64 / mix.total() as f64));
+            let mut total = DecisionMix::default();
+            for (_, mix) in &window {
+                total.add(mix);
+            }
+            let mix = BTreeMap::from([
+                ("Allow".to_string(), total.allow),
+                ("Deny".to_string(), total.deny),
+                ("Purge".to_string(), total.purge),
+            ]);
+            if let Some(trend) = fit(points.collect(), mix) {
+                alert(&mut report.alerts, TrendSeries::ActorClass(class.clone()), &trend, config);
+                report.actor_classes.insert(class.clone(), trend);
+            }
+        }
+
+        for drift in &report.alerts {
+            warn!(
+                "Ethical drift in {:?}: moral score falling {:.4}/day (limit {:.4}/day), now {:.3}",
+                drift.series, -drift.slope_per_day, drift.threshold, drift.latest
+            );
+            let subject = match &drift.series {
+                TrendSeries::Component(name) => format!("component:{}", name),
+                TrendSeries::ActorClass(class) => format!("actor_class:{}", class),
+            };
+            ark_alert::raise(
+                Alert::new("co_audit_ai", "moral_score_drop", &subject, Severity::Warning, "moral score falling faster than allowed")
+                    .with_detail("slope_per_day", drift.slope_per_day)
+                    .with_detail("threshold", drift.threshold)
+                    .with_detail("latest", drift.latest),
+            );
+        }
+        report
+    }
+
+    /// Drop buckets older than `retention` before `now`
+    pub fn prune(&mut self, retention: Duration, now: SystemTime) {
+        let oldest = self.bucket(now.checked_sub(retention).unwrap_or(UNIX_EPOCH));
+        for buckets in self.components.values_mut() {
+            buckets.retain(|index, _| *index >= oldest);
+        }
+        for buckets in self.actor_classes.values_mut() {
+            buckets.retain(|index, _| *index >= oldest);
+        }
+        self.components.retain(|_, buckets| !buckets.is_empty());
+        self.actor_classes.retain(|_, buckets| !buckets.is_empty());
+    }
+}
+
+/// Trend of `(day, score)` points in bucket order; `None` without points
+fn fit(points: Vec<(f64, f64)>, mix: BTreeMap<String, u64>) -> Option<Trend> {
+    let latest = points.last()?.1;
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    let slope_per_day = if points.len() > 1 && variance > 0.0 { Some(covariance / variance) } else { None };
+    Some(Trend { buckets: points.len(), latest, slope_per_day, mix })
+}
+
+fn alert(alerts: &mut Vec<DriftAlert>, series: TrendSeries, trend: &Trend, config: &TrendConfig) {
+    match trend.slope_per_day {
+        Some(slope) if trend.buckets >= config.min_buckets && slope < -config.max_decline_per_day => {
+            alerts.push(DriftAlert {
+                series,
+                slope_per_day: slope,
+                threshold: config.max_decline_per_day,
+                latest: trend.latest,
+            });
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AuditClassification, BiblicalAnalysis};
+
+    fn result(moral_score: f64, day: u64) -> AuditResult {
+        AuditResult {
+            file_path: PathBuf::from("ethics_dsl/src/lib.rs"),
+            language: Default::default(),
+            classification: if moral_score > 0.8 { AuditClassification::Righteous } else { AuditClassification::Concerning },
+            moral_score,
+            technical_score: 1.0,
+            security_score: 1.0,
+            biblical_compliance: 1.0,
+            verification_results: vec![],
+            moral_violations: vec![],
+            security_issues: vec![],
+            formal_properties: vec![],
+            biblical_analysis: BiblicalAnalysis {
+                primary_virtues: vec![],
+                potential_sins: vec![],
+                scriptural_alignment: 0.9,
+                divine_purpose_score: 0.9,
+                love_commandment_compliance: 0.9,
+                wisdom_demonstration: 0.9,
+                stewardship_quality: 0.9,
+                relevant_verses: vec![],
+            },
+            recommendations: vec![],
+            plugin_findings: vec![],
+            plugin_failures: vec![],
+            fixes: vec![],
+            audit_timestamp: UNIX_EPOCH + Duration::from_secs(day * 86_400 + 60),
+            audit_duration: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_fast_decline_raises_alert_and_slow_does_not() {
+        let config = TrendConfig::default();
+        let mut trends = MoralTrends::new(&config);
+        for (day, score) in [(10, 0.95), (11, 0.90), (12, 0.85), (13, 0.80)] {
+            trends.record_audit("ethics_dsl", &result(score, day));
+        }
+        for (day, score) in [(10, 0.950), (11, 0.948), (12, 0.946), (13, 0.944)] {
+            trends.record_audit("cold_mirror", &result(score, day));
+        }
+
+        let report = trends.report(&config, UNIX_EPOCH + Duration::from_secs(13 * 86_400 + 3_600));
+        let slope = report.components["ethics_dsl"].slope_per_day.unwrap();
+        assert!((slope + 0.05).abs() < 1e-9);
+        assert_eq!(report.components["ethics_dsl"].mix["Concerning"], 1);
+        assert_eq!(report.alerts.len(), 1);
+        assert_eq!(report.alerts[0].series, TrendSeries::Component("ethics_dsl".into()));
+
+        // Outside the window the decline is no longer reported
+        let later = trends.report(&config, UNIX_EPOCH + Duration::from_secs(40 * 86_400));
+        assert!(later.components.is_empty() && later.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_history_round_trips_and_prunes() {
+        let config = TrendConfig::default();
+        let mut trends = MoralTrends::new(&config);
+        trends.record_audit("ethics_dsl", &result(0.9, 1));
+        trends.record_audit("ethics_dsl", &result(0.7, 30));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("trends.json");
+        trends.save(&path).unwrap();
+        let mut loaded = MoralTrends::load(&path, &config).unwrap();
+        assert_eq!(loaded, trends);
+
+        loaded.prune(Duration::from_secs(7 * 86_400), UNIX_EPOCH + Duration::from_secs(31 * 86_400));
+        assert_eq!(loaded.components["ethics_dsl"].len(), 1);
+
+        let hourly = TrendConfig { bucket_secs: 3_600, ..config };
+        assert!(MoralTrends::load(&path, &hourly).unwrap().components.is_empty());
+    }
+}
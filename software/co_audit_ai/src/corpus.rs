@@ -0,0 +1,354 @@
+//! Scripted Attack Scenario Corpus
+//!
+//! A regression corpus for the analyzers. `generate` writes synthetic Rust
+//! source files from a seed: each attack sample carries one snippet of a
+//! rule family - kill switches, injection sinks, hardcoded secrets, unsafe
+//! misuse - among benign filler code, and benign samples carry filler only.
+//! The `noise` setting controls how much of the filler is decoys: code that
+//! resembles an attack (a local `stop`, a `format!` that builds no query,
+//! an environment lookup, an `unsafe impl`) but is not one. The same seed
+//! always yields the same corpus, so scores are comparable across releases.
+//!
+//! `CoAuditAI::score_corpus` runs the moral and security analyzers over a
+//! corpus and scores precision and recall per rule family. The golden file
+//! `fixtures/corpus/golden.json` holds the corpus settings and the floors
+//! each family must stay above; the corpus test fails when a change to the
+//! analyzers drops a family below its floor.
+//!
+//! ## Biblical Foundation
+//! "Prove me now herewith, saith the Lord of hosts" - Malachi 3:10
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::findings::{moral_family, security_family};
+use crate::{CoAuditAI, CoAuditError};
+
+/// Rule family exercised by an attack sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RuleFamily {
+    KillSwitch,
+    InjectionSink,
+    HardcodedSecret,
+    UnsafeMisuse,
+}
+
+impl RuleFamily {
+    pub const ALL: [RuleFamily; 4] =
+        [RuleFamily::KillSwitch, RuleFamily::InjectionSink, RuleFamily::HardcodedSecret, RuleFamily::UnsafeMisuse];
+
+    /// Finding family the analyzers report for this rule family (see `findings`)
+    pub fn finding_family(self) -> &'static str {
+        match self {
+            RuleFamily::KillSwitch => "kill_switch",
+            RuleFamily::InjectionSink => "security:injection",
+            RuleFamily::HardcodedSecret => "credentials",
+            RuleFamily::UnsafeMisuse => "memory_safety",
+        }
+    }
+
+    /// Snippet variants; `@`-placeholders are filled by `Generator::fill`
+    fn snippets(self) -> &'static [&'static str] {
+        match self {
+            RuleFamily::KillSwitch => &[
+                "fn kill_switch_@a() {\n    std::process::exit(0);\n}\n",
+                "fn on_remote_@a(command: &str) {\n    if command == \"halt\" {\n        std::process::abort();\n    }\n}\n",
+                "fn shutdown_@a(code: i32) {\n    std::process::exit(code);\n}\n",
+            ],
+            RuleFamily::InjectionSink => &[
+                "fn find_@a(conn: &Connection, @b: &str) -> Rows {\n    let query = format!(\"SELECT * FROM @a WHERE name = '{}'\", @b);\n    conn.execute(&query)\n}\n",
+                "fn drop_@a(conn: &Connection, @b: &str) {\n    let sql_query = format!(\"DELETE FROM @a WHERE id = {}\", @b);\n    conn.execute(&sql_query);\n}\n",
+            ],
+            RuleFamily::HardcodedSecret => &[
+                "fn connect_@a() -> Session {\n    let api_key = \"@hex\";\n    Session::open(api_key)\n}\n",
+                "fn login_@a(user: &str) -> bool {\n    let password = \"@word@n!\";\n    check(user, password)\n}\n",
+                "fn sign_@a(data: &[u8]) -> Vec<u8> {\n    let signing_secret = \"@hex\";\n    mac(signing_secret.as_bytes(), data)\n}\n",
+            ],
+            RuleFamily::UnsafeMisuse => &[
+                "fn write_@a(ptr: *mut u8, @b: usize) {\n    unsafe { *ptr.add(@b) = 0; }\n}\n",
+                "fn read_@a(data: &[u32]) -> u32 {\n    let ptr = data.as_ptr();\n    unsafe { *ptr.add(data.len()) }\n}\n",
+                "fn take_@a(@b: *const u64) -> u64 {\n    unsafe { std::ptr::read(@b) }\n}\n",
+            ],
+        }
+    }
+}
+
+/// Benign code; none of it matches any analyzer pattern
+const FILLER: &[&str] = &[
+    "fn @f_@a(values: &[u32]) -> u32 {\n    values.iter().map(|v| v + @n).sum()\n}\n",
+    "/// Scales the @a of a @b\nfn @f_@b(@a: usize) -> usize {\n    @a.saturating_mul(@n)\n}\n",
+    "struct @A {\n    @a: Vec<u8>,\n    @b: usize,\n}\n",
+    "fn @f_@a(@b: &[u8]) -> Option<u8> {\n    @b.get(@n).copied()\n}\n",
+];
+
+/// Benign code resembling an attack pattern
+const DECOYS: &[&str] = &[
+    "fn stop_@a(running: &mut bool) {\n    *running = false;\n}\n",
+    "fn @f_label(@a: &str) -> String {\n    format!(\"{}-@n\", @a)\n}\n",
+    "fn @f_token() -> String {\n    std::env::var(\"ARK_TOKEN\").unwrap_or_default()\n}\n",
+    "struct @A(u32);\n\nunsafe impl Send for @A {}\n",
+];
+
+const NAMES: &[&str] = &[
+    "ledger", "sensor", "reading", "window", "batch", "record", "entry", "frame", "count", "total", "offset", "route",
+    "metric", "sample", "tally",
+];
+
+const VERBS: &[&str] = &["collect", "summarize", "normalize", "merge", "render", "parse", "update", "compute", "archive"];
+
+const WORDS: &[&str] = &["sunrise", "meadow", "cedar", "olive", "harbor"];
+
+/// Corpus settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CorpusConfig {
+    pub seed: u64,
+    pub samples_per_family: usize,
+    pub benign_samples: usize,
+    /// Fraction of filler items that are decoys (0.0 to 1.0)
+    pub noise: f64,
+}
+
+/// One generated source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorpusSample {
+    pub name: String,
+    pub source: String,
+    /// Family of the attack snippet; `None` for benign samples
+    pub expected: Option<RuleFamily>,
+}
+
+/// SplitMix64, so the corpus does not depend on a random number crate's algorithm
+struct Generator(u64);
+
+impl Generator {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    /// Fill a template's placeholders with fresh names and values
+    fn fill(&mut self, template: &str) -> String {
+        let a = self.pick(NAMES);
+        let b = self.pick(NAMES.iter().copied().filter(|name| *name != a).collect::<Vec<_>>().as_slice());
+        let type_name = a.split('_').map(capitalize).collect::<String>();
+        let hex: String = (0..32).map(|_| char::from_digit(self.below(16) as u32, 16).unwrap_or('0')).collect();
+        template
+            .replace("@hex", &hex)
+            .replace("@word", self.pick(WORDS))
+            .replace("@n", &(1 + self.below(64)).to_string())
+            .replace("@f", self.pick(VERBS))
+            .replace("@A", &type_name)
+            .replace("@a", a)
+            .replace("@b", b)
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+}
+
+/// Generate the corpus for `config`
+pub fn generate(config: &CorpusConfig) -> Vec<CorpusSample> {
+    let mut generator = Generator(config.seed);
+    let expected = RuleFamily::ALL
+        .iter()
+        .flat_map(|family| std::iter::repeat(Some(*family)).take(config.samples_per_family))
+        .chain(std::iter::repeat(None).take(config.benign_samples));
+
+    expected
+        .enumerate()
+        .map(|(index, expected)| {
+            let mut items: Vec<String> = (0..1 + generator.below(4))
+                .map(|_| {
+                    let pool = if generator.chance(config.noise) { DECOYS } else { FILLER };
+                    let template = generator.pick(pool);
+                    generator.fill(template)
+                })
+                .collect();
+            if let Some(family) = expected {
+                let template = generator.pick(family.snippets());
+                let snippet = generator.fill(template);
+                let at = generator.below(items.len() + 1);
+                items.insert(at, snippet);
+            }
+            CorpusSample {
+                name: format!("sample_{:04}.rs", index),
+                source: format!("// Corpus sample {}\n\n{}", index, items.join("\n")),
+                expected,
+            }
+        })
+        .collect()
+}
+
+/// Detection counts of one rule family
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FamilyScore {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+}
+
+impl FamilyScore {
+    /// Share of detections that were attacks of the family; 1.0 without detections
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_positives)
+    }
+
+    /// Share of attacks of the family that were detected; 1.0 without attacks
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_positives, self.true_positives + self.false_negatives)
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Minimum precision and recall of a family
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreFloor {
+    pub precision: f64,
+    pub recall: f64,
+}
+
+/// Corpus settings and floors kept in the golden file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenCorpus {
+    pub corpus: CorpusConfig,
+    /// Floors by rule family
+    pub floors: BTreeMap<RuleFamily, ScoreFloor>,
+}
+
+/// Scores of the analyzers over a corpus
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CorpusReport {
+    pub samples: usize,
+    pub families: BTreeMap<RuleFamily, FamilyScore>,
+}
+
+impl CorpusReport {
+    /// Families below their floor, described
+    pub fn regressions(&self, floors: &BTreeMap<RuleFamily, ScoreFloor>) -> Vec<String> {
+        let mut regressions = Vec::new();
+        for (family, floor) in floors {
+            let score = self.families.get(family).cloned().unwrap_or_default();
+            if score.precision() < floor.precision {
+                regressions.push(format!("{:?} precision {:.3} below {:.3}", family, score.precision(), floor.precision));
+            }
+            if score.recall() < floor.recall {
+                regressions.push(format!("{:?} recall {:.3} below {:.3}", family, score.recall(), floor.recall));
+            }
+        }
+        regressions
+    }
+}
+
+impl CoAuditAI {
+    /// Run the moral and security analyzers over `samples` and score each rule family
+    pub async fn score_corpus(&self, samples: &[CorpusSample]) -> Result<CorpusReport, CoAuditError> {
+        let mut report = CorpusReport { samples: samples.len(), families: BTreeMap::new() };
+        for sample in samples {
+            let detected: BTreeSet<String> = self
+                .detect_moral_violations(&sample.source)
+                .await?
+                .iter()
+                .map(moral_family)
+                .chain(self.analyze_security_issues(&sample.source).await?.iter().map(security_family))
+                .collect();
+
+            for family in RuleFamily::ALL {
+                let score = report.families.entry(family).or_default();
+                match (sample.expected == Some(family), detected.contains(family.finding_family())) {
+                    (true, true) => score.true_positives += 1,
+                    (false, true) => score.false_positives += 1,
+                    (true, false) => score.false_negatives += 1,
+                    (false, false) => {}
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expand::MacroExpansionConfig;
+    use crate::findings::AnalyzerPrecision;
+    use crate::{AuditScope, CoAuditConfig, VerificationEngine};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn golden() -> GoldenCorpus {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/corpus/golden.json");
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let config = golden().corpus;
+        let corpus = generate(&config);
+        assert_eq!(corpus, generate(&config));
+        assert_eq!(corpus.len(), 4 * config.samples_per_family + config.benign_samples);
+        assert_ne!(corpus, generate(&CorpusConfig { seed: config.seed + 1, ..config.clone() }));
+
+        let attacks = corpus.iter().filter(|sample| sample.expected.is_some());
+        assert!(attacks.clone().all(|sample| sample.source.contains("fn ")));
+        assert_eq!(attacks.count(), 4 * config.samples_per_family);
+    }
+
+    #[tokio::test]
+    async fn test_corpus_scores_stay_above_golden_floors() {
+        let config = CoAuditConfig {
+            audit_scope: AuditScope {
+                include_patterns: vec!["*.rs".to_string()],
+                exclude_patterns: vec![],
+                verify_formal_properties: false,
+                check_biblical_compliance: false,
+                analyze_security_properties: true,
+                detect_moral_violations: true,
+                max_verification_time: Duration::from_secs(10),
+                engines: vec![VerificationEngine::Z3],
+            },
+            moral_threshold: 0.7,
+            technical_threshold: 0.7,
+            security_threshold: 0.7,
+            biblical_threshold: 0.7,
+            parallel_verification: false,
+            max_concurrent_audits: 1,
+            result_cache_size: 0,
+            verification_keys: HashMap::new(),
+            strict_biblical_mode: false,
+            macro_expansion: MacroExpansionConfig::default(),
+            analyzer_precision: AnalyzerPrecision::default(),
+            trends: Default::default(),
+        };
+        let co_audit = CoAuditAI::new(config).await.unwrap();
+
+        let golden = golden();
+        let report = co_audit.score_corpus(&generate(&golden.corpus)).await.unwrap();
+        let regressions = report.regressions(&golden.floors);
+        assert!(regressions.is_empty(), "{:#?}\n{:#?}", regressions, report);
+    }
+}
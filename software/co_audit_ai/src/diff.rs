@@ -0,0 +1,411 @@
+//! Differential Audit Between Branches
+//!
+//! Release sign-off compares the candidate branch with the last release.
+//! Every in-scope file of both branches is audited from git, without a
+//! checkout. Findings are matched across the branches by file, rule family
+//! and description - not by line, which shifts with unrelated edits - so
+//! the report lists only the findings introduced or resolved. Moral,
+//! technical and security scores are averaged per component (the crate
+//! owning the file) and compared. The `ReleaseGate` summary fails when any
+//! score of a component present on both branches decreased.
+//!
+//! ## Biblical Foundation
+//! "Let us search and try our ways, and turn again to the Lord" - Lamentations 3:40
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::analytics::DriftAlert;
+use crate::findings::{moral_family, plugin_family, security_family};
+use crate::{AuditResult, CoAuditError};
+
+/// Score changes smaller than this are float noise, not regressions
+pub const SCORE_TOLERANCE: f64 = 1e-9;
+
+/// Component of files outside any crate
+pub const ROOT_COMPONENT: &str = ".";
+
+/// Finding reported on only one branch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffFinding {
+    pub component: String,
+    pub file: PathBuf,
+    pub family: String,
+    pub severity: String,
+    pub description: String,
+    pub line_number: Option<usize>,
+}
+
+impl DiffFinding {
+    fn signature(&self) -> (PathBuf, String, String) {
+        (self.file.clone(), self.family.clone(), self.description.clone())
+    }
+}
+
+/// Mean scores of the audited files of a component
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComponentScores {
+    pub moral: f64,
+    pub technical: f64,
+    pub security: f64,
+    pub files: usize,
+}
+
+/// Scores of one component on both branches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComponentDelta {
+    /// `None` when the component does not exist on branch A
+    pub branch_a: Option<ComponentScores>,
+    /// `None` when the component does not exist on branch B
+    pub branch_b: Option<ComponentScores>,
+    pub moral_delta: f64,
+    pub technical_delta: f64,
+    pub security_delta: f64,
+}
+
+/// Score kinds compared by the release gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreKind {
+    Moral,
+    Technical,
+    Security,
+}
+
+/// Score of a component that decreased from branch A to branch B
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreRegression {
+    pub component: String,
+    pub score: ScoreKind,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Machine-readable verdict for the release gate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseGate {
+    /// No score of any component decreased
+    pub passed: bool,
+    pub regressions: Vec<ScoreRegression>,
+    /// Findings only on branch B
+    pub introduced_findings: usize,
+    /// Findings only on branch A
+    pub resolved_findings: usize,
+}
+
+/// Differential audit of two branches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditDiffReport {
+    pub branch_a: String,
+    pub branch_b: String,
+    pub unique_to_a: Vec<DiffFinding>,
+    pub unique_to_b: Vec<DiffFinding>,
+    pub components: BTreeMap<String, ComponentDelta>,
+    /// Files that could not be read or audited, by branch
+    pub errors: Vec<String>,
+    pub gate: ReleaseGate,
+    /// Components whose moral score trend is falling too fast; a warning,
+    /// not part of the gate
+    #[serde(default)]
+    pub drift_alerts: Vec<DriftAlert>,
+}
+
+/// Audit results of one branch with the component of every file
+#[derive(Debug, Default)]
+pub struct BranchAudit {
+    pub results: Vec<(String, AuditResult)>,
+    pub errors: Vec<String>,
+}
+
+impl BranchAudit {
+    fn findings(&self) -> Vec<DiffFinding> {
+        let mut findings = Vec::new();
+        for (component, result) in &self.results {
+            let finding = |family: String, severity: String, description: &str, line_number: Option<usize>| DiffFinding {
+                component: component.clone(),
+                file: result.file_path.clone(),
+                family,
+                severity,
+                description: description.to_string(),
+                line_number,
+            };
+            for v in &result.moral_violations {
+                findings.push(finding(moral_family(v), format!("{:?}", v.severity), &v.description, v.line_number));
+            }
+            for i in &result.security_issues {
+                findings.push(finding(security_family(i), format!("{:?}", i.severity), &i.description, i.line_number));
+            }
+            for f in &result.plugin_findings {
+                findings.push(finding(
+                    plugin_family(f),
+                    format!("{:?}", f.finding.severity),
+                    &f.finding.message,
+                    f.finding.line_number,
+                ));
+            }
+        }
+        findings
+    }
+
+    fn component_scores(&self) -> BTreeMap<String, ComponentScores> {
+        let mut scores: BTreeMap<String, ComponentScores> = BTreeMap::new();
+        for (component, result) in &self.results {
+            let entry = scores.entry(component.clone()).or_default();
+            entry.moral += result.moral_score;
+            entry.technical += result.technical_score;
+            entry.security += result.security_score;
+            entry.files += 1;
+        }
+        for entry in scores.values_mut() {
+            let files = entry.files as f64;
+            entry.moral /= files;
+            entry.technical /= files;
+            entry.security /= files;
+        }
+        scores
+    }
+}
+
+/// Compare the audits of two branches
+pub fn compare(branch_a: &str, a: &BranchAudit, branch_b: &str, b: &BranchAudit) -> AuditDiffReport {
+    let findings_a = a.findings();
+    let findings_b = b.findings();
+    let unique_to_a = unmatched(&findings_a, &findings_b);
+    let unique_to_b = unmatched(&findings_b, &findings_a);
+
+    let scores_a = a.component_scores();
+    let scores_b = b.component_scores();
+    let names: BTreeSet<&String> = scores_a.keys().chain(scores_b.keys()).collect();
+
+    let mut components = BTreeMap::new();
+    let mut regressions = Vec::new();
+    for name in names {
+        let before = scores_a.get(name).copied();
+        let after = scores_b.get(name).copied();
+        let delta = |score: fn(&ComponentScores) -> f64| match (&before, &after) {
+            (Some(before), Some(after)) => score(after) - score(before),
+            _ => 0.0,
+        };
+        if let (Some(before), Some(after)) = (&before, &after) {
+            for (kind, was, is) in [
+                (ScoreKind::Moral, before.moral, after.moral),
+                (ScoreKind::Technical, before.technical, after.technical),
+                (ScoreKind::Security, before.security, after.security),
+            ] {
+                if is < was - SCORE_TOLERANCE {
+                    regressions.push(ScoreRegression { component: name.clone(), score: kind, before: was, after: is });
+                }
+            }
+        }
+        components.insert(name.clone(), ComponentDelta {
+            moral_delta: delta(|s| s.moral),
+            technical_delta: delta(|s| s.technical),
+            security_delta: delta(|s| s.security),
+            branch_a: before,
+            branch_b: after,
+        });
+    }
+
+    let gate = ReleaseGate {
+        passed: regressions.is_empty(),
+        regressions,
+        introduced_findings: unique_to_b.len(),
+        resolved_findings: unique_to_a.len(),
+    };
+    let errors = a.errors.iter().map(|e| format!("{}: {}", branch_a, e))
+        .chain(b.errors.iter().map(|e| format!("{}: {}", branch_b, e)))
+        .collect();
+
+    AuditDiffReport {
+        branch_a: branch_a.to_string(),
+        branch_b: branch_b.to_string(),
+        unique_to_a,
+        unique_to_b,
+        components,
+        errors,
+        gate,
+        drift_alerts: Vec::new(),
+    }
+}
+
+/// Findings of `ours` without a counterpart in `theirs`, matching each counterpart once
+fn unmatched(ours: &[DiffFinding], theirs: &[DiffFinding]) -> Vec<DiffFinding> {
+    let mut available: HashMap<(PathBuf, String, String), usize> = HashMap::new();
+    for finding in theirs {
+        *available.entry(finding.signature()).or_insert(0) += 1;
+    }
+    ours.iter()
+        .filter(|finding| match available.get_mut(&finding.signature()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Crate owning `file`: the nearest ancestor directory holding a `Cargo.toml`
+pub fn component_of(file: &Path, manifests: &BTreeSet<PathBuf>) -> String {
+    file.ancestors()
+        .skip(1)
+        .find(|dir| manifests.contains(&dir.join("Cargo.toml")))
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|| ROOT_COMPONENT.to_string())
+}
+
+/// Files tracked on `branch`
+pub async fn list_files(repo: &Path, branch: &str) -> Result<Vec<PathBuf>, CoAuditError> {
+    let listing = git(repo, &["ls-tree", "-r", "-z", "--name-only", branch]).await?;
+    Ok(listing
+        .split(|b| *b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| PathBuf::from(String::from_utf8_lossy(name).into_owned()))
+        .collect())
+}
+
+/// Contents of `file` on `branch`; `None` for non-UTF-8 files
+pub async fn read_file(repo: &Path, branch: &str, file: &Path) -> Result<Option<String>, CoAuditError> {
+    let spec = format!("{}:{}", branch, file.to_string_lossy().replace('\\', "/"));
+    let contents = git(repo, &["show", &spec]).await?;
+    Ok(String::from_utf8(contents).ok())
+}
+
+pub(crate) async fn git(repo: &Path, args: &[&str]) -> Result<Vec<u8>, CoAuditError> {
+    let output = tokio::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| CoAuditError::Git(format!("Failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(CoAuditError::Git(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Log the gate verdict
+pub fn log_gate(report: &AuditDiffReport) {
+    if report.gate.passed {
+        info!(
+            "Audit diff {}..{}: gate passed, {} findings introduced, {} resolved",
+            report.branch_a, report.branch_b, report.gate.introduced_findings, report.gate.resolved_findings
+        );
+    } else {
+        for regression in &report.gate.regressions {
+            warn!(
+                "Audit diff {}..{}: {:?} score of {} fell from {:.3} to {:.3}",
+                report.branch_a, report.branch_b, regression.score, regression.component, regression.before, regression.after
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AuditClassification, BiblicalAnalysis, IssueSeverity, SecurityCategory, SecurityIssue,
+    };
+    use std::time::{Duration, SystemTime};
+
+    fn result(file: &str, security_score: f64, issues: &[&str]) -> AuditResult {
+        AuditResult {
+            file_path: PathBuf::from(file),
+            language: Default::default(),
+            classification: AuditClassification::Sound,
+            moral_score: 0.9,
+            technical_score: 0.8,
+            security_score,
+            biblical_compliance: 0.9,
+            verification_results: vec![],
+            moral_violations: vec![],
+            security_issues: issues.iter().enumerate().map(|(line, description)| SecurityIssue {
+                category: SecurityCategory::Authentication,
+                description: description.to_string(),
+                severity: IssueSeverity::High,
+                cwe_id: None,
+                line_number: Some(line + 1),
+                code_snippet: String::new(),
+                impact: String::new(),
+                remediation: String::new(),
+                macro_origin: None,
+                confidence: 1.0,
+                corroborated_by: vec![],
+            }).collect(),
+            formal_properties: vec![],
+            biblical_analysis: BiblicalAnalysis {
+                primary_virtues: vec![],
+                potential_sins: vec![],
+                scriptural_alignment: 0.9,
+                divine_purpose_score: 0.9,
+                love_commandment_compliance: 0.9,
+                wisdom_demonstration: 0.9,
+                stewardship_quality: 0.9,
+                relevant_verses: vec![],
+            },
+            recommendations: vec![],
+            plugin_findings: vec![],
+            plugin_failures: vec![],
+            fixes: vec![],
+            audit_timestamp: SystemTime::now(),
+            audit_duration: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_component_of_nearest_manifest() {
+        let manifests: BTreeSet<PathBuf> = ["software/ethics_dsl/Cargo.toml", "firmware/Cargo.toml"]
+            .iter().map(PathBuf::from).collect();
+
+        assert_eq!(component_of(Path::new("software/ethics_dsl/src/engine.rs"), &manifests), "software/ethics_dsl");
+        assert_eq!(component_of(Path::new("firmware/src/main.rs"), &manifests), "firmware");
+        assert_eq!(component_of(Path::new("tools/build.rs"), &manifests), ROOT_COMPONENT);
+    }
+
+    #[test]
+    fn test_compare_reports_unique_findings_and_regressions() {
+        let release = BranchAudit {
+            results: vec![
+                ("firmware".to_string(), result("firmware/src/a.rs", 0.9, &["Hardcoded password"])),
+                ("software/ethics_dsl".to_string(), result("software/ethics_dsl/src/b.rs", 0.7, &["Weak token"])),
+            ],
+            errors: vec![],
+        };
+        // Same password finding on a shifted line; new firmware issue; ethics token issue fixed
+        let candidate = BranchAudit {
+            results: vec![
+                ("firmware".to_string(), result("firmware/src/a.rs", 0.6, &["Unchecked key", "Hardcoded password"])),
+                ("software/ethics_dsl".to_string(), result("software/ethics_dsl/src/b.rs", 1.0, &[])),
+            ],
+            errors: vec![],
+        };
+
+        let report = compare("release", &release, "candidate", &candidate);
+
+        assert_eq!(report.unique_to_a.len(), 1);
+        assert_eq!(report.unique_to_a[0].description, "Weak token");
+        assert_eq!(report.unique_to_b.len(), 1);
+        assert_eq!(report.unique_to_b[0].description, "Unchecked key");
+        assert!((report.components["firmware"].security_delta + 0.3).abs() < 1e-9);
+        assert!(!report.gate.passed);
+        assert_eq!(report.gate.regressions, vec![ScoreRegression {
+            component: "firmware".to_string(),
+            score: ScoreKind::Security,
+            before: 0.9,
+            after: 0.6,
+        }]);
+    }
+}
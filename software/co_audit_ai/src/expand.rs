@@ -0,0 +1,384 @@
+//! Macro Expansion Analysis
+//!
+//! Substring and plugin analyzers only see the source text, so behaviour
+//! hidden inside a macro body escapes them at the invocation site. When
+//! enabled, the auditor expands macros and runs the analyzers over every
+//! expansion as well; findings that only appear in expanded code are
+//! attributed to the macro invocation they came from.
+//!
+//! `ExpansionMode::Syn` performs a partial expansion of the `macro_rules!`
+//! macros defined in the audited file: each invocation is replaced by the
+//! transcribers of all of the macro's rules, and local invocations nested in
+//! them are expanded up to `max_depth`. Metavariables are not bound, so the
+//! result over-approximates what the macro can emit. `ExpansionMode::CargoExpand`
+//! additionally runs `cargo expand` on the file's module, which also covers
+//! derives, procedural and external macros, but requires the tool and a
+//! buildable crate and is far slower. Its output cannot be tied to individual
+//! invocations, so findings from it are attributed to the file as a whole.
+//!
+//! ## Biblical Foundation
+//! "For nothing is secret, that shall not be made manifest" - Luke 8:17
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::CoAuditError;
+
+/// Upper bound on the size of a single expansion
+const MAX_EXPANSION_BYTES: usize = 1024 * 1024;
+
+/// Origin name used for whole-module `cargo expand` output
+pub const CARGO_EXPAND_ORIGIN: &str = "cargo expand";
+
+/// How macros are expanded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpansionMode {
+    /// Partial expansion of `macro_rules!` macros defined in the file
+    Syn,
+    /// Partial expansion plus full `cargo expand` of the file's module
+    CargoExpand,
+}
+
+/// Macro expansion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MacroExpansionConfig {
+    /// Expansion is slow, so it is off unless requested
+    pub enabled: bool,
+    pub mode: ExpansionMode,
+    /// Nesting depth for local macros invoked from macro bodies
+    pub max_depth: usize,
+    /// Budget for one `cargo expand` run
+    pub timeout: Duration,
+}
+
+impl Default for MacroExpansionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: ExpansionMode::Syn,
+            max_depth: 4,
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Macro invocation a finding was traced back to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroOrigin {
+    pub macro_name: String,
+    /// Line of the invocation, `None` for whole-module expansions
+    pub invocation_line: Option<usize>,
+}
+
+/// Expanded code of one macro invocation
+#[derive(Debug, Clone)]
+pub struct MacroExpansion {
+    pub origin: MacroOrigin,
+    pub code: String,
+}
+
+/// Expand the macros of a source file according to `config`
+pub async fn expand(
+    path: &Path,
+    code: &str,
+    config: &MacroExpansionConfig,
+) -> Result<Vec<MacroExpansion>, CoAuditError> {
+    let mut expansions = expand_local_macros(code, config.max_depth)?;
+
+    if config.mode == ExpansionMode::CargoExpand {
+        let expanded = cargo_expand(path, config.timeout).await?;
+        expansions.push(MacroExpansion {
+            origin: MacroOrigin {
+                macro_name: CARGO_EXPAND_ORIGIN.to_string(),
+                invocation_line: None,
+            },
+            code: expanded,
+        });
+    }
+
+    debug!("Expanded {} macro invocation(s) in {:?}", expansions.len(), path);
+    Ok(expansions)
+}
+
+/// Partially expand invocations of the `macro_rules!` macros defined in `code`
+pub fn expand_local_macros(code: &str, max_depth: usize) -> Result<Vec<MacroExpansion>, CoAuditError> {
+    let file = syn::parse_file(code).map_err(|e| CoAuditError::MacroExpansion(e.to_string()))?;
+
+    let mut macros = HashMap::new();
+    collect_definitions(&file.items, &mut macros);
+    if macros.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tokens: TokenStream = code
+        .parse()
+        .map_err(|e: proc_macro2::LexError| CoAuditError::MacroExpansion(e.to_string()))?;
+
+    let expander = Expander { macros, max_depth };
+    let mut expansions = Vec::new();
+    expander.find_invocations(tokens, &mut expansions);
+    Ok(expansions)
+}
+
+/// Record the transcribers of every `macro_rules!` definition, including
+/// those in inline modules
+fn collect_definitions(items: &[syn::Item], macros: &mut HashMap<String, Vec<TokenStream>>) {
+    for item in items {
+        match item {
+            syn::Item::Macro(item) if item.mac.path.is_ident("macro_rules") => {
+                if let Some(ident) = &item.ident {
+                    macros.insert(ident.to_string(), transcribers(item.mac.tokens.clone()));
+                }
+            }
+            syn::Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    collect_definitions(items, macros);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split `(matcher) => { transcriber };` rules, keeping the transcribers
+fn transcribers(rules: TokenStream) -> Vec<TokenStream> {
+    let mut result = Vec::new();
+    let mut after_arrow = false;
+    for tree in rules {
+        match tree {
+            TokenTree::Punct(punct) if punct.as_char() == '>' => after_arrow = true,
+            TokenTree::Group(group) if after_arrow => {
+                result.push(group.stream());
+                after_arrow = false;
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+struct Expander {
+    macros: HashMap<String, Vec<TokenStream>>,
+    max_depth: usize,
+}
+
+impl Expander {
+    /// Walk the file's tokens and expand every invocation of a local macro
+    fn find_invocations(&self, tokens: TokenStream, expansions: &mut Vec<MacroExpansion>) {
+        let trees: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut i = 0;
+        while i < trees.len() {
+            match &trees[i] {
+                // Definitions are not invocations
+                TokenTree::Ident(ident) if ident == "macro_rules" && is_bang(trees.get(i + 1)) => {
+                    i += 4;
+                    continue;
+                }
+                TokenTree::Ident(ident) if self.macros.contains_key(&ident.to_string()) && is_bang(trees.get(i + 1)) => {
+                    if let Some(TokenTree::Group(args)) = trees.get(i + 2) {
+                        let name = ident.to_string();
+                        let mut budget = MAX_EXPANSION_BYTES;
+                        let code = self.expand_invocation(&name, self.max_depth, &mut budget).to_string();
+                        expansions.push(MacroExpansion {
+                            origin: MacroOrigin {
+                                macro_name: name,
+                                invocation_line: Some(ident.span().start().line),
+                            },
+                            code,
+                        });
+                        self.find_invocations(args.stream(), expansions);
+                        i += 3;
+                        continue;
+                    }
+                }
+                TokenTree::Group(group) => self.find_invocations(group.stream(), expansions),
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// All rule bodies of `name`, with nested local invocations expanded
+    fn expand_invocation(&self, name: &str, depth: usize, budget: &mut usize) -> TokenStream {
+        let mut expanded = TokenStream::new();
+        for transcriber in &self.macros[name] {
+            let body = self.expand_stream(transcriber.clone(), depth.saturating_sub(1), budget);
+            *budget = budget.saturating_sub(body.to_string().len());
+            expanded.extend([TokenTree::Group(Group::new(Delimiter::Brace, body))]);
+        }
+        expanded
+    }
+
+    fn expand_stream(&self, tokens: TokenStream, depth: usize, budget: &mut usize) -> TokenStream {
+        let trees: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut output = Vec::with_capacity(trees.len());
+        let mut i = 0;
+        while i < trees.len() {
+            if let TokenTree::Ident(ident) = &trees[i] {
+                let name = ident.to_string();
+                let expandable = depth > 0 && *budget > 0 && self.macros.contains_key(&name);
+                if expandable && is_bang(trees.get(i + 1)) && matches!(trees.get(i + 2), Some(TokenTree::Group(_))) {
+                    output.extend(self.expand_invocation(&name, depth, budget));
+                    i += 3;
+                    continue;
+                }
+            }
+            match &trees[i] {
+                TokenTree::Group(group) => {
+                    let inner = self.expand_stream(group.stream(), depth, budget);
+                    output.push(TokenTree::Group(Group::new(group.delimiter(), inner)));
+                }
+                other => output.push(other.clone()),
+            }
+            i += 1;
+        }
+        output.into_iter().collect()
+    }
+}
+
+fn is_bang(tree: Option<&TokenTree>) -> bool {
+    matches!(tree, Some(TokenTree::Punct(punct)) if punct.as_char() == '!')
+}
+
+/// Run `cargo expand` on the module defined by `path`
+async fn cargo_expand(path: &Path, timeout: Duration) -> Result<String, CoAuditError> {
+    let args = cargo_expand_args(path)?;
+    let mut command = tokio::process::Command::new("cargo");
+    command.arg("expand").args(&args).kill_on_drop(true);
+
+    let output = tokio::time::timeout(timeout, command.output())
+        .await
+        .map_err(|_| CoAuditError::MacroExpansion(format!("cargo expand timed out after {:?}", timeout)))?
+        .map_err(|e| CoAuditError::MacroExpansion(format!("Failed to run cargo expand: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(CoAuditError::MacroExpansion(format!(
+            "cargo expand failed for {:?}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Arguments selecting the crate and module `path` belongs to
+fn cargo_expand_args(path: &Path) -> Result<Vec<String>, CoAuditError> {
+    let path = path.canonicalize().map_err(|e| CoAuditError::FileRead(e.to_string()))?;
+
+    let (manifest, relative) = path
+        .ancestors()
+        .skip(1)
+        .find_map(|dir| {
+            let manifest = dir.join("Cargo.toml");
+            let relative = path.strip_prefix(dir.join("src")).ok()?;
+            manifest.is_file().then(|| (manifest, relative.to_path_buf()))
+        })
+        .ok_or_else(|| CoAuditError::MacroExpansion(format!("{:?} is not in a crate's src directory", path)))?;
+
+    let mut module: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if module.last().map(String::as_str) == Some("mod") {
+        module.pop();
+    }
+
+    let mut args = vec![
+        "--manifest-path".to_string(),
+        manifest.to_string_lossy().into_owned(),
+        "--color".to_string(),
+        "never".to_string(),
+    ];
+    match module.first().map(String::as_str) {
+        Some("main") if module.len() == 1 => {}
+        Some("bin") => {
+            return Err(CoAuditError::MacroExpansion(format!("Binary target {:?} is not supported", path)));
+        }
+        _ => {
+            args.push("--lib".to_string());
+            if module != ["lib"] {
+                args.push(module.join("::"));
+            }
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_macro_mapped_to_invocation() {
+        let code = r#"
+macro_rules! innocent_logging {
+    ($msg:expr) => {
+        let _ = std::process::Command::new("sh").arg("remote_disable").spawn();
+    };
+}
+
+fn main() {
+    let x = 1;
+    innocent_logging!("hello");
+}
+"#;
+        let expansions = expand_local_macros(code, 4).unwrap();
+        assert_eq!(expansions.len(), 1);
+        assert_eq!(expansions[0].origin, MacroOrigin {
+            macro_name: "innocent_logging".to_string(),
+            invocation_line: Some(10),
+        });
+        assert!(expansions[0].code.contains("remote_disable"));
+        assert!(!code.lines().nth(9).unwrap().contains("remote_disable"));
+    }
+
+    #[test]
+    fn test_nested_and_recursive_expansion_is_bounded() {
+        let code = r#"
+mod hidden {
+    macro_rules! payload { () => { unsafe { core::ptr::write_volatile(0 as *mut u8, 0) } }; }
+}
+macro_rules! wrapper { () => { payload!() }; }
+macro_rules! forever { () => { forever!() }; }
+
+fn run() {
+    wrapper!();
+    forever!();
+}
+"#;
+        let expansions = expand_local_macros(code, 3).unwrap();
+        assert_eq!(expansions.len(), 2);
+        assert_eq!(expansions[0].origin.invocation_line, Some(9));
+        assert!(expansions[0].code.contains("write_volatile"));
+        // Recursion stops at the depth limit
+        assert_eq!(expansions[1].code.matches('{').count(), 3);
+        assert!(expansions[1].code.contains("forever !"));
+    }
+
+    #[test]
+    fn test_cargo_expand_args_select_module() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"probe\"\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("src/net")).unwrap();
+        for file in ["src/lib.rs", "src/main.rs", "src/net/mod.rs", "src/net/peer.rs"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let tail = |file: &str| {
+            let args = cargo_expand_args(&dir.path().join(file)).unwrap();
+            args[4..].to_vec()
+        };
+        assert_eq!(tail("src/lib.rs"), vec!["--lib"]);
+        assert!(tail("src/main.rs").is_empty());
+        assert_eq!(tail("src/net/mod.rs"), vec!["--lib", "net"]);
+        assert_eq!(tail("src/net/peer.rs"), vec!["--lib", "net::peer"]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Finding Confidence and Deduplication
+//!
+//! The moral, security and plugin analyzers overlap: a single `kill` is both
+//! a kill-switch moral violation and a kill-switch security issue, and
+//! counting it twice inflates the penalty. Every finding carries a
+//! confidence taken from the precision of the analyzer that produced it,
+//! and the deduplication pass keeps one finding per (file, span, rule
+//! family). The survivor is the finding with the largest weighted penalty;
+//! its confidence is raised by the findings it absorbed, which are listed
+//! in `corroborated_by`. Scores weight each finding by its confidence.
+//!
+//! ## Biblical Foundation
+//! "At the mouth of two witnesses, or three witnesses, shall the matter be
+//! established" - Deuteronomy 19:15
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::plugins::PluginFinding;
+use crate::{IssueSeverity, MoralViolation, SecurityCategory, SecurityIssue, ViolationSeverity};
+
+/// Confidence of findings from analyzers with no precision estimate
+pub const FULL_CONFIDENCE: f64 = 1.0;
+
+/// Precision of the binary payload analyzer's signature and structure checks
+pub const BINARY_ANALYSIS_PRECISION: f64 = 0.9;
+
+/// Serde default for findings recorded before confidences existed
+pub fn full_confidence() -> f64 {
+    FULL_CONFIDENCE
+}
+
+/// Measured precision of each analyzer, used as the confidence of its findings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerPrecision {
+    /// Substring checks for moral violations
+    pub moral_patterns: f64,
+    /// Full ethics engine evaluation
+    pub ethics_engine: f64,
+    /// Substring checks for security issues
+    pub security_patterns: f64,
+    /// Parser-backed checks of the Solidity and Python adapters
+    pub language_adapters: f64,
+    /// Plugins without their own entry
+    pub default_plugin: f64,
+    /// Per-plugin precision by plugin name
+    pub plugins: HashMap<String, f64>,
+}
+
+impl Default for AnalyzerPrecision {
+    fn default() -> Self {
+        Self {
+            moral_patterns: 0.6,
+            ethics_engine: 0.8,
+            security_patterns: 0.5,
+            language_adapters: 0.8,
+            default_plugin: 0.7,
+            plugins: HashMap::new(),
+        }
+    }
+}
+
+impl AnalyzerPrecision {
+    /// Precision of a plugin
+    pub fn plugin(&self, name: &str) -> f64 {
+        self.plugins.get(name).copied().unwrap_or(self.default_plugin)
+    }
+}
+
+/// Identity of a finding for deduplication
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FindingKey {
+    pub file: PathBuf,
+    /// Line of the finding; `None` covers the whole file
+    pub line: Option<usize>,
+    pub family: String,
+}
+
+/// Penalty of a moral violation before confidence weighting
+pub fn moral_penalty(severity: &ViolationSeverity) -> f64 {
+    match severity {
+        ViolationSeverity::Abominable => 1.0,
+        ViolationSeverity::Critical => 0.5,
+        ViolationSeverity::High => 0.3,
+        ViolationSeverity::Medium => 0.1,
+        ViolationSeverity::Low => 0.05,
+        ViolationSeverity::Informational => 0.01,
+    }
+}
+
+/// Penalty of a security issue or plugin finding before confidence weighting
+pub fn security_penalty(severity: &IssueSeverity) -> f64 {
+    match severity {
+        IssueSeverity::Critical => 0.5,
+        IssueSeverity::High => 0.3,
+        IssueSeverity::Medium => 0.1,
+        IssueSeverity::Low => 0.05,
+        IssueSeverity::Info => 0.01,
+    }
+}
+
+/// Rule family of a moral violation
+pub fn moral_family(violation: &MoralViolation) -> String {
+    match violation.principle.as_str() {
+        "Autonomous divine mission" => "kill_switch".to_string(),
+        "Truthfulness" => "deception".to_string(),
+        "Love your neighbor" => "harm".to_string(),
+        "Overall Biblical compliance" => "ethics_evaluation".to_string(),
+        principle => format!("moral:{}", principle.to_lowercase()),
+    }
+}
+
+/// Rule family of a security issue
+pub fn security_family(issue: &SecurityIssue) -> String {
+    match issue.category {
+        SecurityCategory::KillSwitchVulnerability => "kill_switch".to_string(),
+        SecurityCategory::Authentication => "credentials".to_string(),
+        SecurityCategory::BufferOverflow => "memory_safety".to_string(),
+        ref category => format!("security:{:?}", category).to_lowercase(),
+    }
+}
+
+/// Rule family of a plugin finding; plugins share families through `family.rule` IDs
+pub fn plugin_family(finding: &PluginFinding) -> String {
+    let rule_id = &finding.finding.rule_id;
+    match rule_id.split_once('.') {
+        Some((family, _)) => family.to_string(),
+        None => format!("plugin:{}", rule_id),
+    }
+}
+
+/// Noisy-or of independent confidences
+fn combined_confidence(confidences: impl IntoIterator<Item = f64>) -> f64 {
+    1.0 - confidences.into_iter().map(|c| 1.0 - c.clamp(0.0, 1.0)).product::<f64>()
+}
+
+#[derive(Clone, Copy)]
+enum Slot {
+    Moral(usize),
+    Security(usize),
+    Plugin(usize),
+}
+
+struct Entry {
+    slot: Slot,
+    label: String,
+    penalty: f64,
+    confidence: f64,
+}
+
+/// Keep one finding per (file, span, rule family); returns how many were merged away
+pub fn deduplicate(
+    file: &Path,
+    moral_violations: &mut Vec<MoralViolation>,
+    security_issues: &mut Vec<SecurityIssue>,
+    plugin_findings: &mut Vec<PluginFinding>,
+) -> usize {
+    let key = |line: Option<usize>, family: String| FindingKey { file: file.to_path_buf(), line, family };
+
+    let mut groups: HashMap<FindingKey, Vec<Entry>> = HashMap::new();
+    let mut order = Vec::new();
+    let mut add = |key: FindingKey, entry: Entry| {
+        if !groups.contains_key(&key) {
+            order.push(key.clone());
+        }
+        groups.entry(key).or_default().push(entry);
+    };
+
+    for (index, v) in moral_violations.iter().enumerate() {
+        add(key(v.line_number, moral_family(v)), Entry {
+            slot: Slot::Moral(index),
+            label: format!("moral:{}", v.principle),
+            penalty: moral_penalty(&v.severity),
+            confidence: v.confidence,
+        });
+    }
+    for (index, i) in security_issues.iter().enumerate() {
+        add(key(i.line_number, security_family(i)), Entry {
+            slot: Slot::Security(index),
+            label: format!("security:{:?}", i.category),
+            penalty: security_penalty(&i.severity),
+            confidence: i.confidence,
+        });
+    }
+    for (index, f) in plugin_findings.iter().enumerate() {
+        add(key(f.finding.line_number, plugin_family(f)), Entry {
+            slot: Slot::Plugin(index),
+            label: format!("plugin:{}/{}", f.provenance.plugin_name, f.finding.rule_id),
+            penalty: security_penalty(&f.finding.severity),
+            confidence: f.confidence,
+        });
+    }
+
+    let mut keep_moral = vec![true; moral_violations.len()];
+    let mut keep_security = vec![true; security_issues.len()];
+    let mut keep_plugin = vec![true; plugin_findings.len()];
+    let mut merged = 0;
+
+    for key in order {
+        let entries = &groups[&key];
+        if entries.len() < 2 {
+            continue;
+        }
+
+        // Earliest finding wins ties
+        let winner = entries.iter().enumerate()
+            .fold(0, |best, (index, entry)| {
+                let best_weight = entries[best].penalty * entries[best].confidence;
+                if entry.penalty * entry.confidence > best_weight { index } else { best }
+            });
+        let confidence = combined_confidence(entries.iter().map(|e| e.confidence));
+        let corroborated_by: Vec<String> = entries.iter().enumerate()
+            .filter(|(index, _)| *index != winner)
+            .map(|(_, e)| e.label.clone())
+            .collect();
+        debug!("Merged {} findings in family {} at {:?}:{:?}", entries.len(), key.family, key.file, key.line);
+
+        for (index, entry) in entries.iter().enumerate() {
+            if index != winner {
+                merged += 1;
+                match entry.slot {
+                    Slot::Moral(i) => keep_moral[i] = false,
+                    Slot::Security(i) => keep_security[i] = false,
+                    Slot::Plugin(i) => keep_plugin[i] = false,
+                }
+                continue;
+            }
+            match entry.slot {
+                Slot::Moral(i) => {
+                    moral_violations[i].confidence = confidence;
+                    moral_violations[i].corroborated_by.extend(corroborated_by.iter().cloned());
+                }
+                Slot::Security(i) => {
+                    security_issues[i].confidence = confidence;
+                    security_issues[i].corroborated_by.extend(corroborated_by.iter().cloned());
+                }
+                Slot::Plugin(i) => {
+                    plugin_findings[i].confidence = confidence;
+                    plugin_findings[i].corroborated_by.extend(corroborated_by.iter().cloned());
+                }
+            }
+        }
+    }
+
+    let mut keep = keep_moral.into_iter();
+    moral_violations.retain(|_| keep.next().unwrap_or(true));
+    let mut keep = keep_security.into_iter();
+    security_issues.retain(|_| keep.next().unwrap_or(true));
+    let mut keep = keep_plugin.into_iter();
+    plugin_findings.retain(|_| keep.next().unwrap_or(true));
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(principle: &str, severity: ViolationSeverity, confidence: f64) -> MoralViolation {
+        MoralViolation {
+            principle: principle.to_string(),
+            description: String::new(),
+            severity,
+            line_number: None,
+            code_snippet: String::new(),
+            biblical_reference: String::new(),
+            suggested_fix: None,
+            macro_origin: None,
+            confidence,
+            corroborated_by: vec![],
+        }
+    }
+
+    fn issue(category: SecurityCategory, line_number: Option<usize>, confidence: f64) -> SecurityIssue {
+        SecurityIssue {
+            category,
+            description: String::new(),
+            severity: IssueSeverity::Critical,
+            cwe_id: None,
+            line_number,
+            code_snippet: String::new(),
+            impact: String::new(),
+            remediation: String::new(),
+            macro_origin: None,
+            confidence,
+            corroborated_by: vec![],
+        }
+    }
+
+    #[test]
+    fn test_kill_switch_counted_once() {
+        let mut violations = vec![violation("Autonomous divine mission", ViolationSeverity::Abominable, 0.6)];
+        let mut issues = vec![issue(SecurityCategory::KillSwitchVulnerability, None, 0.5)];
+        let mut findings = vec![];
+
+        let merged = deduplicate(Path::new("a.rs"), &mut violations, &mut issues, &mut findings);
+
+        assert_eq!(merged, 1);
+        assert!(issues.is_empty());
+        assert_eq!(violations[0].corroborated_by, vec!["security:KillSwitchVulnerability"]);
+        // 1 - 0.4 * 0.5
+        assert!((violations[0].confidence - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_distinct_spans_and_families_kept() {
+        let mut violations = vec![violation("Truthfulness", ViolationSeverity::High, 0.6)];
+        let mut issues = vec![
+            issue(SecurityCategory::KillSwitchVulnerability, Some(3), 0.5),
+            issue(SecurityCategory::KillSwitchVulnerability, Some(9), 0.5),
+            issue(SecurityCategory::Injection, Some(9), 0.5),
+        ];
+        let mut findings = vec![];
+
+        assert_eq!(deduplicate(Path::new("a.rs"), &mut violations, &mut issues, &mut findings), 0);
+        assert_eq!(violations.len() + issues.len(), 4);
+        assert!(issues.iter().all(|i| i.confidence == 0.5 && i.corroborated_by.is_empty()));
+    }
+}
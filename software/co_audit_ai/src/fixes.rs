@@ -0,0 +1,431 @@
+//! Machine-Applicable Fixes for Audit Findings
+//!
+//! Three findings have a mechanical remedy. A hardcoded secret bound with
+//! `let` becomes a read of an environment variable named after the binding;
+//! a query built with `format!` and passed straight to `query`/`execute`
+//! becomes a parameterized call with `$n` placeholders; an `unsafe` block
+//! with no `// SAFETY:` comment above it gets one. A fix replaces whole
+//! lines and records the lines it expects to replace, so applying it to a
+//! file that changed since the audit is refused instead of corrupting it.
+//! The SAFETY comment holds a placeholder a reviewer must complete, and is
+//! marked `HasPlaceholders` rather than `MachineApplicable`. A query value
+//! spliced into part of a quoted literal, such as `LIKE '%{}%'`, has no
+//! placeholder that means the same thing: its fix is `MaybeIncorrect` and is
+//! never applied without review.
+//!
+//! ## Biblical Foundation
+//! "Make straight paths for your feet, lest that which is lame be turned out
+//! of the way; but let it rather be healed" - Hebrews 12:13
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{AuditResult, IssueSeverity, SecurityCategory, SecurityIssue};
+
+/// Placeholder left in inserted SAFETY comments
+pub const SAFETY_PLACEHOLDER: &str = "// SAFETY: TODO explain why this block is sound";
+
+/// Substrings of binding names treated as secrets
+const SECRET_NAMES: &[&str] = &["password", "passwd", "secret", "token", "api_key", "apikey", "private_key"];
+
+/// Finding a fix remedies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FixRule {
+    /// Hardcoded secret read from the environment instead
+    EnvSecret,
+    /// `format!`-built query passed as a parameterized call
+    ParameterizedQuery,
+    /// `unsafe` block given a SAFETY comment
+    SafetyComment,
+}
+
+impl FixRule {
+    pub const ALL: [FixRule; 3] = [FixRule::EnvSecret, FixRule::ParameterizedQuery, FixRule::SafetyComment];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            FixRule::EnvSecret => "env-secret",
+            FixRule::ParameterizedQuery => "parameterized-query",
+            FixRule::SafetyComment => "safety-comment",
+        }
+    }
+
+    /// Rule from its command line name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+/// Whether a fix can be applied without review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Applicability {
+    /// The fixed code is complete
+    MachineApplicable,
+    /// The fixed code contains a placeholder to complete by hand
+    HasPlaceholders,
+    /// The fixed code may not mean what the original did
+    MaybeIncorrect,
+}
+
+/// Replacement of consecutive lines of one file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuggestedFix {
+    pub rule: FixRule,
+    pub applicability: Applicability,
+    pub description: String,
+    /// First replaced line, 1-based
+    pub line_number: usize,
+    /// Lines the fix expects to replace
+    pub original: Vec<String>,
+    pub replacement: Vec<String>,
+    /// Unified diff of this fix alone
+    pub diff: String,
+}
+
+impl SuggestedFix {
+    fn new(
+        file: &Path,
+        rule: FixRule,
+        applicability: Applicability,
+        line_number: usize,
+        original: &str,
+        replacement: Vec<String>,
+    ) -> Self {
+        let description = match rule {
+            FixRule::EnvSecret => "Read the secret from the environment",
+            FixRule::ParameterizedQuery => "Pass query values as parameters",
+            FixRule::SafetyComment => "Document why the unsafe block is sound",
+        };
+        let mut fix = Self {
+            rule,
+            applicability,
+            description: description.to_string(),
+            line_number,
+            original: vec![original.to_string()],
+            replacement,
+            diff: String::new(),
+        };
+        fix.diff = unified_diff(file, &[&fix]);
+        fix
+    }
+
+    /// Line-precise finding reported for fixes with no coarse counterpart
+    pub fn issue(&self, confidence: f64) -> Option<SecurityIssue> {
+        match self.rule {
+            FixRule::SafetyComment => Some(SecurityIssue {
+                category: SecurityCategory::BufferOverflow,
+                description: "Unsafe block without SAFETY comment".to_string(),
+                severity: IssueSeverity::Low,
+                cwe_id: None,
+                line_number: Some(self.line_number),
+                code_snippet: self.original.join("\n").trim().to_string(),
+                impact: "Soundness assumptions of the block are unreviewable".to_string(),
+                remediation: "State the invariants that make the block sound".to_string(),
+                macro_origin: None,
+                confidence,
+                corroborated_by: vec![],
+            }),
+            FixRule::EnvSecret | FixRule::ParameterizedQuery => None,
+        }
+    }
+}
+
+/// Outcome of `CoAuditAI::fix_file`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixReport {
+    pub file_path: PathBuf,
+    /// Fixes selected by rule
+    pub fixes: Vec<SuggestedFix>,
+    /// Unified diff of all selected fixes
+    pub diff: String,
+    pub applied: bool,
+    pub before: AuditResult,
+    /// Re-audit of the fixed file
+    pub after: Option<AuditResult>,
+}
+
+/// Errors applying fixes
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FixError {
+    #[error("Line {0} no longer matches the audited source")]
+    Stale(usize),
+
+    #[error("Fixes overlap at line {0}")]
+    Overlap(usize),
+}
+
+/// Fixes for the findings in `code`
+pub fn suggest(file: &Path, code: &str) -> Vec<SuggestedFix> {
+    let lines: Vec<&str> = code.lines().collect();
+    let mut fixes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim_start().starts_with("//") {
+            continue;
+        }
+        let line_number = index + 1;
+        if let Some(replacement) = env_secret(line) {
+            let applicability = Applicability::MachineApplicable;
+            fixes.push(SuggestedFix::new(file, FixRule::EnvSecret, applicability, line_number, line, replacement));
+        } else if let Some((replacement, applicability)) = parameterized_query(line) {
+            fixes.push(SuggestedFix::new(file, FixRule::ParameterizedQuery, applicability, line_number, line, replacement));
+        }
+        if let Some(replacement) = safety_comment(&lines[..index], line) {
+            let applicability = Applicability::HasPlaceholders;
+            fixes.push(SuggestedFix::new(file, FixRule::SafetyComment, applicability, line_number, line, replacement));
+        }
+    }
+    fixes
+}
+
+fn secret_binding() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^(\s*)let\s+(mut\s+)?([A-Za-z_][A-Za-z0-9_]*)(\s*:\s*[^=]+?)?\s*=\s*"([^"\\]+)"(\.to_string\(\)|\.to_owned\(\)|\.into\(\))?\s*;\s*$"#,
+        )
+        .expect("valid secret binding pattern")
+    })
+}
+
+fn format_query() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^(\s*)(.*?)\.(query|query_one|query_opt|execute)\(\s*&format!\(\s*"([^"\\]*)"\s*((?:,\s*[A-Za-z_][A-Za-z0-9_.]*\s*)*)\)\s*(?:,\s*&\[\s*\])?\s*\)(.*)$"#,
+        )
+        .expect("valid format query pattern")
+    })
+}
+
+fn unsafe_block() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\bunsafe\s*\{").expect("valid unsafe block pattern"))
+}
+
+/// `let NAME = "literal"` with a secret-like NAME, read from `$NAME` instead
+fn env_secret(line: &str) -> Option<Vec<String>> {
+    let captures = secret_binding().captures(line)?;
+    let name = &captures[3];
+    let lower = name.to_lowercase();
+    if !SECRET_NAMES.iter().any(|secret| lower.contains(secret)) {
+        return None;
+    }
+
+    let indent = &captures[1];
+    let mutability = captures.get(2).map_or("", |m| m.as_str());
+    let annotation = captures.get(4).map_or("", |m| m.as_str());
+    let variable = name.to_uppercase();
+    let read = format!("std::env::var(\"{}\").expect(\"{} must be set\")", variable, variable);
+
+    // An owned literal becomes the owned `String`; a `&str` borrows from a shadowed one
+    Some(if captures.get(6).is_some() {
+        vec![format!("{}let {}{}{} = {};", indent, mutability, name, annotation, read)]
+    } else {
+        vec![
+            format!("{}let {} = {};", indent, name, read),
+            format!("{}let {}{}{} = {}.as_str();", indent, mutability, name, annotation, name),
+        ]
+    })
+}
+
+/// `recv.query(&format!("... {} ...", value))` as `recv.query("... $1 ...", &[&value])`
+///
+/// A value spliced into part of a quoted literal becomes a `$n` inside the
+/// literal, which the database reads as text; such fixes are `MaybeIncorrect`.
+fn parameterized_query(line: &str) -> Option<(Vec<String>, Applicability)> {
+    let captures = format_query().captures(line)?;
+    let template = &captures[4];
+    let mut arguments: Vec<String> = captures[5]
+        .split(',')
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    // Positional `{}` take the arguments in order; `{name}` captures `name`
+    let mut sql = String::new();
+    let mut parameters = Vec::new();
+    let mut positional = arguments.drain(..);
+    let mut applicability = Applicability::MachineApplicable;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}')?;
+        let inner = &rest[start + 1..end];
+        let parameter = if inner.is_empty() {
+            positional.next()?
+        } else if inner.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') && !inner.starts_with(|c: char| c.is_ascii_digit()) {
+            inner.to_string()
+        } else {
+            // Escaped braces and format specs are not plain values
+            return None;
+        };
+        let mut before = &rest[..start];
+        let mut after = &rest[end + 1..];
+        // A quoted value is a single parameter, quotes included
+        if before.ends_with('\'') && after.starts_with('\'') {
+            before = &before[..before.len() - 1];
+            after = &after[1..];
+        }
+        sql.push_str(before);
+        if sql.matches('\'').count() % 2 == 1 {
+            applicability = Applicability::MaybeIncorrect;
+        }
+        parameters.push(parameter);
+        let _ = write!(sql, "${}", parameters.len());
+        rest = after;
+    }
+    if rest.contains('}') || positional.next().is_some() || parameters.is_empty() {
+        return None;
+    }
+    sql.push_str(rest);
+
+    let references: Vec<String> = parameters.iter().map(|p| format!("&{}", p)).collect();
+    let replacement = vec![format!(
+        "{}{}.{}(\"{}\", &[{}]){}",
+        &captures[1],
+        &captures[2],
+        &captures[3],
+        sql,
+        references.join(", "),
+        &captures[6]
+    )];
+    Some((replacement, applicability))
+}
+
+/// `unsafe {` not preceded by a SAFETY comment, with one inserted above
+fn safety_comment(preceding: &[&str], line: &str) -> Option<Vec<String>> {
+    let found = unsafe_block().find(line)?;
+    if line[..found.start()].contains("//") || line.contains("SAFETY:") {
+        return None;
+    }
+    let documented = preceding
+        .iter()
+        .rev()
+        .map(|l| l.trim())
+        .take_while(|l| l.starts_with("//") || l.starts_with("#["))
+        .any(|l| l.contains("SAFETY:"));
+    if documented {
+        return None;
+    }
+    let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+    Some(vec![format!("{}{}", indent, SAFETY_PLACEHOLDER), line.to_string()])
+}
+
+/// Unified diff of `fixes` to one file, without context lines
+pub fn unified_diff(file: &Path, fixes: &[&SuggestedFix]) -> String {
+    let mut ordered = fixes.to_vec();
+    ordered.sort_by_key(|fix| fix.line_number);
+
+    let name = file.display();
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", name, name);
+    let mut shift: isize = 0;
+    for fix in ordered {
+        let new_start = fix.line_number as isize + shift;
+        let _ = writeln!(
+            diff,
+            "@@ -{},{} +{},{} @@",
+            fix.line_number,
+            fix.original.len(),
+            new_start,
+            fix.replacement.len()
+        );
+        for line in &fix.original {
+            let _ = writeln!(diff, "-{}", line);
+        }
+        for line in &fix.replacement {
+            let _ = writeln!(diff, "+{}", line);
+        }
+        shift += fix.replacement.len() as isize - fix.original.len() as isize;
+    }
+    diff
+}
+
+/// `code` with `fixes` applied
+///
+/// Every fix must find its original lines where the audit saw them, and no
+/// two fixes may replace the same line.
+pub fn apply(code: &str, fixes: &[&SuggestedFix]) -> Result<String, FixError> {
+    let mut lines: Vec<String> = code.lines().map(str::to_string).collect();
+    let mut ordered = fixes.to_vec();
+    ordered.sort_by_key(|fix| std::cmp::Reverse(fix.line_number));
+
+    let mut next_start = usize::MAX;
+    for fix in ordered {
+        let start = fix.line_number.checked_sub(1).ok_or(FixError::Stale(fix.line_number))?;
+        let end = start + fix.original.len();
+        if end > next_start {
+            return Err(FixError::Overlap(fix.line_number));
+        }
+        if lines.get(start..end) != Some(&fix.original[..]) {
+            return Err(FixError::Stale(fix.line_number));
+        }
+        lines.splice(start..end, fix.replacement.iter().cloned());
+        next_start = start;
+    }
+
+    let mut fixed = lines.join("\n");
+    if code.ends_with('\n') {
+        fixed.push('\n');
+    }
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixes(code: &str) -> Vec<SuggestedFix> {
+        suggest(Path::new("src/db.rs"), code)
+    }
+
+    #[test]
+    fn test_fixes_rewrite_secrets_and_queries() {
+        let code = "fn connect(client: &Client, name: &str) {\n    let db_password = \"hunter2\";\n    let api_token: String = \"abc\".to_string();\n    client.query(&format!(\"SELECT * FROM users WHERE name = '{}'\", name), &[]);\n}\n";
+        let found = fixes(code);
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|fix| fix.applicability == Applicability::MachineApplicable));
+
+        let fixed = apply(code, &found.iter().collect::<Vec<_>>()).unwrap();
+        assert!(fixed.contains("    let db_password = std::env::var(\"DB_PASSWORD\").expect(\"DB_PASSWORD must be set\");\n    let db_password = db_password.as_str();\n"));
+        assert!(fixed.contains("    let api_token: String = std::env::var(\"API_TOKEN\").expect(\"API_TOKEN must be set\");\n"));
+        assert!(fixed.contains("    client.query(\"SELECT * FROM users WHERE name = $1\", &[&name]);\n"));
+        assert!(fixes(&fixed).is_empty());
+    }
+
+    #[test]
+    fn test_value_inside_a_quoted_pattern_may_be_incorrect() {
+        let code = "client.query(&format!(\"SELECT * FROM users WHERE name LIKE '%{}%' AND id = {}\", name, id), &[]);\n";
+        let found = fixes(code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].applicability, Applicability::MaybeIncorrect);
+        assert_eq!(found[0].replacement, vec!["client.query(\"SELECT * FROM users WHERE name LIKE '%$1%' AND id = $2\", &[&name, &id]);"]);
+    }
+
+    #[test]
+    fn test_safety_comment_is_a_placeholder() {
+        let code = "fn read(p: *const u8) -> u8 {\n    unsafe { *p }\n}\n\nfn write(p: *mut u8) {\n    // SAFETY: p is valid for writes\n    unsafe { *p = 0 }\n}\n";
+        let found = fixes(code);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].applicability, Applicability::HasPlaceholders);
+        assert_eq!(found[0].line_number, 2);
+        assert!(found[0].issue(0.5).is_some_and(|issue| issue.line_number == Some(2)));
+        assert_eq!(
+            found[0].diff,
+            format!("--- a/src/db.rs\n+++ b/src/db.rs\n@@ -2,1 +2,2 @@\n-    unsafe {{ *p }}\n+    {}\n+    unsafe {{ *p }}\n", SAFETY_PLACEHOLDER)
+        );
+    }
+
+    #[test]
+    fn test_apply_refuses_stale_fixes() {
+        let code = "let secret = \"s3cr3t\";\n";
+        let found = fixes(code);
+        assert_eq!(apply("let secret = \"changed\";\n", &[&found[0]]), Err(FixError::Stale(1)));
+        assert_eq!(apply(code, &[&found[0], &found[0]]), Err(FixError::Overlap(1)));
+    }
+}
@@ -0,0 +1,65 @@
+//! Health Reporting
+//!
+//! Common shape for the live health of long-running Co-Audit services, so
+//! that supervisors can poll them uniformly.
+//!
+//! ## Biblical Foundation
+//! "Be diligent to know the state of your flocks, and attend to your herds" - Proverbs 27:23
+
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use ark_provenance::ProvenanceManifest;
+use serde::{Deserialize, Serialize};
+
+/// Overall health of a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HealthStatus {
+    Healthy,
+    /// Running, but reporting problems that need attention
+    Degraded,
+    /// Not running or unable to do its work
+    Unhealthy,
+}
+
+/// Health snapshot of a single component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub component: String,
+    pub status: HealthStatus,
+    pub details: BTreeMap<String, String>,
+    pub checked_at: SystemTime,
+}
+
+impl ComponentHealth {
+    pub fn new(component: &str, status: HealthStatus) -> Self {
+        Self {
+            component: component.to_string(),
+            status,
+            details: BTreeMap::new(),
+            checked_at: SystemTime::now(),
+        }
+    }
+
+    /// Attach a detail entry
+    pub fn with_detail(mut self, key: &str, value: impl ToString) -> Self {
+        self.details.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Attach the provenance of the installed patch, if any
+    pub fn with_provenance(mut self, manifest: Option<&ProvenanceManifest>) -> Self {
+        match manifest {
+            Some(manifest) => self.details.extend(manifest.health_details()),
+            None => {
+                self.details.insert("provenance.patch_id".to_string(), "unpatched".to_string());
+            }
+        }
+        self
+    }
+}
+
+/// Components that can report their health
+pub trait HealthCheck {
+    fn health(&self) -> ComponentHealth;
+}
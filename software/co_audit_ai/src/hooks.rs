@@ -0,0 +1,273 @@
+//! Audit Git Hooks
+//!
+//! Installs `pre-commit` and `pre-push` hooks that audit only what is about
+//! to leave the working tree. The pre-commit hook audits the staged version
+//! of every changed file, the pre-push hook the files changed by the pushed
+//! commits, each against the version it replaces. Findings are matched as in
+//! the differential audit, so only findings the change introduces count, and
+//! the hook blocks when any of them reaches the configured severity.
+//!
+//! Setting `ARK_AUDIT_BYPASS` to a reason lets the commit or push through
+//! despite blocking findings. The hook command is told about the bypass so it
+//! can record who bypassed, when and which findings, for later review.
+//! `git commit --no-verify` skips the hook altogether and leaves no record.
+//!
+//! ## Biblical Foundation
+//! "Set a watch, O LORD, before my mouth; keep the door of my lips" - Psalm 141:3
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::diff::{self, AuditDiffReport, DiffFinding};
+use crate::{CoAuditError, IssueSeverity};
+
+/// First line after the shebang of every installed hook
+pub const HOOK_MARKER: &str = "# Installed by `audit install-hooks`; reinstalling overwrites edits";
+
+/// Environment variable holding the reason for bypassing a blocking hook
+pub const BYPASS_VARIABLE: &str = "ARK_AUDIT_BYPASS";
+
+/// Git hook running the audit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookKind {
+    PreCommit,
+    PrePush,
+}
+
+impl HookKind {
+    pub const ALL: [HookKind; 2] = [HookKind::PreCommit, HookKind::PrePush];
+
+    /// File name of the hook, also used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookKind::PreCommit => "pre-commit",
+            HookKind::PrePush => "pre-push",
+        }
+    }
+
+    /// Hook from its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Shell script running `command` for this hook
+    ///
+    /// `command` is the shell-quoted audit command; the pre-push script adds
+    /// `--base` and `--head` for every pushed ref, and both add `--bypass`
+    /// when `ARK_AUDIT_BYPASS` is set.
+    pub fn script(&self, command: &str) -> String {
+        let bypass = format!("${{{}:+--bypass \"${}\"}}", BYPASS_VARIABLE, BYPASS_VARIABLE);
+        match self {
+            HookKind::PreCommit => format!(
+                "#!/bin/sh\n{}\n# Set {}=<reason> to commit past blocking findings; the bypass is recorded\nexec {} {}\n",
+                HOOK_MARKER, BYPASS_VARIABLE, command, bypass
+            ),
+            HookKind::PrePush => format!(
+                "#!/bin/sh\n{marker}\n# Set {variable}=<reason> to push past blocking findings; the bypass is recorded\n\
+                 zero=$(git hash-object --stdin </dev/null | tr '0-9a-f' '0')\n\
+                 while read local_ref local_sha remote_ref remote_sha; do\n\
+                 \x20   [ \"$local_sha\" = \"$zero\" ] && continue\n\
+                 \x20   base=\"$remote_sha\"\n\
+                 \x20   if [ \"$base\" = \"$zero\" ]; then\n\
+                 \x20       base=$(git rev-parse -q --verify '@{{upstream}}' || git hash-object -t tree /dev/null)\n\
+                 \x20   fi\n\
+                 \x20   {command} --base \"$base\" --head \"$local_sha\" {bypass} || exit 1\n\
+                 done\n",
+                marker = HOOK_MARKER, variable = BYPASS_VARIABLE, command = command, bypass = bypass
+            ),
+        }
+    }
+}
+
+/// Changes audited by a hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeSet {
+    /// The index against `HEAD`
+    Staged,
+    /// Commit `head` against commit or tree `base`
+    Range { base: String, head: String },
+}
+
+impl ChangeSet {
+    /// Revision the changed files are compared against
+    pub fn base(&self) -> &str {
+        match self {
+            ChangeSet::Staged => "HEAD",
+            ChangeSet::Range { base, .. } => base,
+        }
+    }
+
+    /// Revision prefix of the changed files; empty names the index
+    pub fn target(&self) -> &str {
+        match self {
+            ChangeSet::Staged => "",
+            ChangeSet::Range { head, .. } => head,
+        }
+    }
+}
+
+/// Files added, copied, modified or renamed by `changes`
+pub async fn changed_files(repo: &Path, changes: &ChangeSet) -> Result<Vec<PathBuf>, CoAuditError> {
+    let mut args = vec!["diff", "--name-only", "-z", "--diff-filter=ACMR"];
+    match changes {
+        ChangeSet::Staged => args.push("--cached"),
+        ChangeSet::Range { base, head } => args.extend([base.as_str(), head.as_str()]),
+    }
+    let listing = diff::git(repo, &args).await?;
+    Ok(listing
+        .split(|b| *b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| PathBuf::from(String::from_utf8_lossy(name).into_owned()))
+        .collect())
+}
+
+/// Identity of the committer configured in `repo`, without the timestamp
+pub async fn git_identity(repo: &Path) -> Result<String, CoAuditError> {
+    let ident = diff::git(repo, &["var", "GIT_COMMITTER_IDENT"]).await?;
+    let ident = String::from_utf8_lossy(&ident);
+    // "Name <email> 1700000000 +0000"
+    Ok(ident.trim().rsplitn(3, ' ').nth(2).unwrap_or(ident.trim()).to_string())
+}
+
+/// Install `kinds` into the hooks directory of `repo`
+///
+/// A hook that was not installed by this command is left alone unless
+/// `force` is set. Returns the paths written.
+pub async fn install(repo: &Path, kinds: &[HookKind], command: &str, force: bool) -> Result<Vec<PathBuf>, CoAuditError> {
+    // Honors core.hooksPath; relative to the repository root
+    let hooks = diff::git(repo, &["rev-parse", "--git-path", "hooks"]).await?;
+    let hooks = repo.join(String::from_utf8_lossy(&hooks).trim());
+    std::fs::create_dir_all(&hooks).map_err(|e| CoAuditError::Hook(format!("{:?}: {}", hooks, e)))?;
+
+    let mut installed = Vec::with_capacity(kinds.len());
+    for kind in kinds {
+        let path = hooks.join(kind.name());
+        if let Ok(existing) = std::fs::read_to_string(&path) {
+            if !existing.contains(HOOK_MARKER) && !force {
+                return Err(CoAuditError::Hook(format!(
+                    "{:?} is not an audit hook; use --force to replace it", path
+                )));
+            }
+        }
+        std::fs::write(&path, kind.script(command)).map_err(|e| CoAuditError::Hook(format!("{:?}: {}", path, e)))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| CoAuditError::Hook(format!("{:?}: {}", path, e)))?;
+        }
+        info!("Installed {} hook at {:?}", kind.name(), path);
+        installed.push(path);
+    }
+    Ok(installed)
+}
+
+/// Quote `arg` for a POSIX shell
+pub fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Severity of a finding on the security scale; moral severities map onto it
+pub fn severity_of(finding: &DiffFinding) -> IssueSeverity {
+    match finding.severity.as_str() {
+        "Abominable" | "Critical" => IssueSeverity::Critical,
+        "High" => IssueSeverity::High,
+        "Medium" => IssueSeverity::Medium,
+        "Low" => IssueSeverity::Low,
+        _ => IssueSeverity::Info,
+    }
+}
+
+/// Verdict of an audit hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookVerdict {
+    pub fail_on: IssueSeverity,
+    /// No introduced finding reached `fail_on`
+    pub passed: bool,
+    /// Introduced findings at or above `fail_on`
+    pub blocking: Vec<DiffFinding>,
+    /// Introduced findings below `fail_on`
+    pub warnings: Vec<DiffFinding>,
+    /// Files that could not be read or audited
+    pub errors: Vec<String>,
+}
+
+impl HookVerdict {
+    /// Judge the findings `report` introduces against `fail_on`
+    pub fn new(report: &AuditDiffReport, fail_on: IssueSeverity) -> Self {
+        let (blocking, warnings): (Vec<_>, Vec<_>) = report.unique_to_b.iter()
+            .cloned()
+            .partition(|finding| severity_of(finding) >= fail_on);
+        for finding in &warnings {
+            warn!("{:?}: {} ({})", finding.file, finding.description, finding.severity);
+        }
+        Self { passed: blocking.is_empty(), fail_on, blocking, warnings, errors: report.errors.clone() }
+    }
+
+    /// One line per blocking finding, as recorded for a bypass
+    pub fn blocking_summary(&self) -> Vec<String> {
+        self.blocking.iter()
+            .map(|finding| match finding.line_number {
+                Some(line) => format!("{}:{}: {} [{}]", finding.file.display(), line, finding.description, finding.severity),
+                None => format!("{}: {} [{}]", finding.file.display(), finding.description, finding.severity),
+            })
+            .collect()
+    }
+}
+
+/// Manifests of the base revision and the changed files, for `diff::component_of`
+pub(crate) fn manifests(base_files: &[PathBuf], changed: &[PathBuf]) -> BTreeSet<PathBuf> {
+    base_files.iter()
+        .chain(changed)
+        .filter(|file| file.file_name().map_or(false, |name| name == "Cargo.toml"))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::ReleaseGate;
+    use std::collections::BTreeMap;
+
+    fn finding(description: &str, severity: &str) -> DiffFinding {
+        DiffFinding {
+            component: "firmware".into(),
+            file: PathBuf::from("firmware/src/boot.rs"),
+            family: "security".into(),
+            severity: severity.into(),
+            description: description.into(),
+            line_number: Some(12),
+        }
+    }
+
+    #[test]
+    fn test_verdict_blocks_introduced_findings_at_threshold() {
+        let report = AuditDiffReport {
+            branch_a: "HEAD".into(),
+            branch_b: "index".into(),
+            unique_to_a: vec![finding("Fixed elsewhere", "Critical")],
+            unique_to_b: vec![finding("Hardcoded key", "High"), finding("Pride in naming", "Abominable"), finding("Long fn", "Low")],
+            components: BTreeMap::new(),
+            errors: vec![],
+            gate: ReleaseGate { passed: true, regressions: vec![], introduced_findings: 3, resolved_findings: 1 },
+            drift_alerts: vec![],
+        };
+
+        let verdict = HookVerdict::new(&report, IssueSeverity::High);
+        assert!(!verdict.passed);
+        assert_eq!(verdict.blocking.len(), 2);
+        assert_eq!(verdict.warnings[0].description, "Long fn");
+        assert_eq!(verdict.blocking_summary()[0], "firmware/src/boot.rs:12: Hardcoded key [High]");
+        assert_eq!(HookVerdict::new(&report, IssueSeverity::Critical).blocking.len(), 1);
+
+        let script = HookKind::PrePush.script(&shell_quote("/opt/ark it's/bin"));
+        assert!(script.starts_with(&format!("#!/bin/sh\n{}\n", HOOK_MARKER)));
+        assert!(script.contains("'/opt/ark it'\\''s/bin' --base \"$base\" --head \"$local_sha\" ${ARK_AUDIT_BYPASS:+--bypass \"$ARK_AUDIT_BYPASS\"} || exit 1"));
+        assert_eq!(HookKind::from_name("pre-commit"), Some(HookKind::PreCommit));
+    }
+}
@@ -0,0 +1,552 @@
+//! Language Adapters
+//!
+//! The built-in analyzers read Rust. Solidity contracts and Python tooling
+//! are parsed by a `LanguageAdapter` for their language instead, which
+//! reports findings that make sense there (unchecked low-level calls and
+//! `tx.origin` authorization in Solidity, `eval` and `shell=True` in
+//! Python) as the same `MoralViolation`s and `SecurityIssue`s, so every
+//! source passes through the same scores and gates. Rust-only analyses -
+//! formal property extraction, macro expansion and fixes - are skipped for
+//! other languages. A file that does not parse is reported as such rather
+//! than analyzed as text.
+//!
+//! ## Biblical Foundation
+//! "And how hear we every man in our own tongue, wherein we were born?" - Acts 2:8
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use regex::Regex;
+use rustpython_ast::Visitor;
+use rustpython_parser::{ast, Parse};
+use serde::{Deserialize, Serialize};
+use solang_parser::helpers::CodeLocation;
+use solang_parser::pt::{
+    Comment, ContractPart, Expression, FunctionAttribute, FunctionDefinition, Loc, SourceUnitPart,
+    Statement, Visibility,
+};
+
+use crate::{CoAuditError, IssueSeverity, MoralViolation, SecurityCategory, SecurityIssue, ViolationSeverity};
+
+/// Language of an audited source file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceLanguage {
+    #[default]
+    Rust,
+    Solidity,
+    Python,
+}
+
+impl SourceLanguage {
+    /// Language of `path` by extension; unknown extensions are read as Rust
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("sol") => SourceLanguage::Solidity,
+            Some("py") | Some("pyi") => SourceLanguage::Python,
+            _ => SourceLanguage::Rust,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceLanguage::Rust => "rust",
+            SourceLanguage::Solidity => "solidity",
+            SourceLanguage::Python => "python",
+        }
+    }
+}
+
+/// Findings of a language adapter
+#[derive(Debug, Clone, Default)]
+pub struct LanguageFindings {
+    pub moral_violations: Vec<MoralViolation>,
+    pub security_issues: Vec<SecurityIssue>,
+}
+
+/// Parser-backed analysis of one non-Rust language
+pub trait LanguageAdapter: Send + Sync {
+    fn language(&self) -> SourceLanguage;
+
+    /// Parse and analyze `code`, scoring findings with `confidence`
+    fn analyze(&self, code: &str, confidence: f64) -> Result<LanguageFindings, CoAuditError>;
+}
+
+/// Adapters by language
+pub struct LanguageRegistry {
+    adapters: HashMap<SourceLanguage, Box<dyn LanguageAdapter>>,
+}
+
+impl LanguageRegistry {
+    /// Registry with no adapters
+    pub fn new() -> Self {
+        Self { adapters: HashMap::new() }
+    }
+
+    /// Registry with the Solidity and Python adapters
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+        registry.register(SolidityAdapter);
+        registry.register(PythonAdapter);
+        registry
+    }
+
+    /// Add an adapter, replacing the one for the same language
+    pub fn register<A: LanguageAdapter + 'static>(&mut self, adapter: A) {
+        self.adapters.insert(adapter.language(), Box::new(adapter));
+    }
+
+    pub fn adapter(&self, language: SourceLanguage) -> Option<&dyn LanguageAdapter> {
+        self.adapters.get(&language).map(|adapter| adapter.as_ref())
+    }
+
+    /// Languages with an adapter
+    pub fn languages(&self) -> Vec<SourceLanguage> {
+        let mut languages: Vec<_> = self.adapters.keys().copied().collect();
+        languages.sort_by_key(|language| language.name());
+        languages
+    }
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Findings of a file the `language` parser rejected
+pub fn parse_failure(language: SourceLanguage, message: &str, line: Option<usize>) -> LanguageFindings {
+    let issue = SecurityIssue {
+        category: SecurityCategory::InputValidation,
+        description: format!("Source does not parse as {}: {}", language.name(), message),
+        severity: IssueSeverity::Medium,
+        cwe_id: None,
+        line_number: line,
+        code_snippet: String::new(),
+        impact: "The file was not analyzed; its findings are unknown".to_string(),
+        remediation: "Fix the syntax error or exclude the file from the audit scope".to_string(),
+        macro_origin: None,
+        confidence: crate::findings::FULL_CONFIDENCE,
+        corroborated_by: vec![],
+    };
+    LanguageFindings { moral_violations: vec![], security_issues: vec![issue] }
+}
+
+/// 1-based line of byte `offset`
+fn line_of(code: &str, offset: usize) -> usize {
+    code[..offset.min(code.len())].matches('\n').count() + 1
+}
+
+/// Trimmed source line `line`
+fn snippet(code: &str, line: usize) -> String {
+    code.lines().nth(line - 1).unwrap_or_default().trim().to_string()
+}
+
+/// Security issue at byte `offset` of `code`
+#[allow(clippy::too_many_arguments)]
+fn issue(
+    code: &str,
+    offset: usize,
+    category: SecurityCategory,
+    severity: IssueSeverity,
+    cwe_id: u32,
+    description: &str,
+    impact: &str,
+    remediation: &str,
+    confidence: f64,
+) -> SecurityIssue {
+    let line = line_of(code, offset);
+    SecurityIssue {
+        category,
+        description: description.to_string(),
+        severity,
+        cwe_id: Some(cwe_id),
+        line_number: Some(line),
+        code_snippet: snippet(code, line),
+        impact: impact.to_string(),
+        remediation: remediation.to_string(),
+        macro_origin: None,
+        confidence,
+        corroborated_by: vec![],
+    }
+}
+
+/// Solidity contracts, parsed with solang-parser
+pub struct SolidityAdapter;
+
+/// Byte range of a location in the parsed file
+fn span(loc: &Loc) -> Option<(usize, usize)> {
+    match loc {
+        Loc::File(_, start, end) => Some((*start, *end)),
+        _ => None,
+    }
+}
+
+/// Name of the member a low-level call is made through, as in `to.call{value: v}("")`
+fn low_level_call(expression: &Expression) -> Option<&str> {
+    let Expression::FunctionCall(_, callee, _) = expression else { return None };
+    let callee = match callee.as_ref() {
+        Expression::FunctionCallBlock(_, inner, _) => inner.as_ref(),
+        other => other,
+    };
+    match callee {
+        Expression::MemberAccess(_, _, member) if ["call", "send", "delegatecall"].contains(&member.name.as_str()) => {
+            Some(member.name.as_str())
+        }
+        _ => None,
+    }
+}
+
+/// Statements whose result is discarded, in body order
+fn discarded_calls<'a>(statement: &'a Statement, calls: &mut Vec<(&'a Loc, &'a str)>) {
+    match statement {
+        Statement::Block { statements, .. } => statements.iter().for_each(|s| discarded_calls(s, calls)),
+        Statement::If(_, _, then, otherwise) => {
+            discarded_calls(then, calls);
+            if let Some(otherwise) = otherwise {
+                discarded_calls(otherwise, calls);
+            }
+        }
+        Statement::While(_, _, body) | Statement::DoWhile(_, body, _) => discarded_calls(body, calls),
+        Statement::For(_, init, _, _, body) => {
+            if let Some(init) = init {
+                discarded_calls(init, calls);
+            }
+            if let Some(body) = body {
+                discarded_calls(body, calls);
+            }
+        }
+        Statement::Expression(loc, expression) => {
+            if let Some(member) = low_level_call(expression) {
+                calls.push((loc, member));
+            }
+        }
+        _ => {}
+    }
+}
+
+impl SolidityAdapter {
+    fn analyze_function(
+        &self,
+        code: &str,
+        masked: &str,
+        function: &FunctionDefinition,
+        confidence: f64,
+        findings: &mut LanguageFindings,
+    ) {
+        let Some(body) = &function.body else { return };
+        let Some((start, end)) = span(&body.loc()) else { return };
+        let text = &masked[start..end];
+
+        let mut calls = Vec::new();
+        discarded_calls(body, &mut calls);
+        for (loc, member) in calls {
+            let Some((offset, _)) = span(loc) else { continue };
+            findings.security_issues.push(issue(
+                code,
+                offset,
+                SecurityCategory::InputValidation,
+                IssueSeverity::High,
+                252,
+                &format!("Return value of low-level `{}` is ignored", member),
+                "A failed transfer or call goes unnoticed and state is updated as if it succeeded",
+                "Check the returned success flag and revert on failure",
+                confidence,
+            ));
+        }
+
+        let patterns = [
+            (r"\btx\s*\.\s*origin\b", SecurityCategory::Authentication, IssueSeverity::High, 477,
+             "Authorization through `tx.origin`",
+             "Any contract the owner interacts with can act with the owner's authority",
+             "Authorize `msg.sender` instead"),
+            (r"\.\s*delegatecall\b", SecurityCategory::PrivilegeEscalation, IssueSeverity::High, 829,
+             "`delegatecall` runs foreign code with this contract's storage",
+             "A malicious or upgraded callee can rewrite storage and ownership",
+             "Delegate only to fixed, audited implementations"),
+            (r"\bblock\s*\.\s*timestamp\b", SecurityCategory::RaceCondition, IssueSeverity::Low, 829,
+             "Logic depends on `block.timestamp`",
+             "Block producers can shift the timestamp within protocol bounds",
+             "Do not use the timestamp for randomness or tight deadlines"),
+        ];
+        for (pattern, category, severity, cwe_id, description, impact, remediation) in patterns {
+            let regex = Regex::new(pattern).expect("valid pattern");
+            if let Some(found) = regex.find(text) {
+                findings.security_issues.push(issue(
+                    code, start + found.start(), category, severity, cwe_id, description, impact, remediation, confidence,
+                ));
+            }
+        }
+
+        let destroy = Regex::new(r"\b(selfdestruct|suicide)\s*\(").expect("valid pattern");
+        let Some(found) = destroy.find(text) else { return };
+        let callable = function.attributes.iter().any(|attribute| {
+            matches!(attribute, FunctionAttribute::Visibility(Visibility::Public(_) | Visibility::External(_)))
+        });
+        let guarded = function.attributes.iter().any(|attribute| matches!(attribute, FunctionAttribute::BaseOrModifier(..)))
+            || text.contains("msg.sender");
+        if callable && !guarded {
+            let line = line_of(code, start + found.start());
+            findings.moral_violations.push(MoralViolation {
+                principle: "Faithful stewardship".to_string(),
+                description: "Anyone can destroy the contract and the funds entrusted to it".to_string(),
+                severity: ViolationSeverity::Critical,
+                line_number: Some(line),
+                code_snippet: snippet(code, line),
+                biblical_reference: "Luke 16:10 - He that is faithful in that which is least is faithful also in much".to_string(),
+                suggested_fix: Some("Restrict destruction to an owner check or remove it".to_string()),
+                macro_origin: None,
+                confidence,
+                corroborated_by: vec![],
+            });
+            findings.security_issues.push(issue(
+                code,
+                start + found.start(),
+                SecurityCategory::Authorization,
+                IssueSeverity::Critical,
+                284,
+                "Unprotected `selfdestruct`",
+                "Any account can destroy the contract and redirect its balance",
+                "Guard the function with an access-control modifier",
+                confidence,
+            ));
+        }
+    }
+}
+
+impl LanguageAdapter for SolidityAdapter {
+    fn language(&self) -> SourceLanguage {
+        SourceLanguage::Solidity
+    }
+
+    fn analyze(&self, code: &str, confidence: f64) -> Result<LanguageFindings, CoAuditError> {
+        let (unit, comments) = match solang_parser::parse(code, 0) {
+            Ok(parsed) => parsed,
+            Err(diagnostics) => {
+                let first = diagnostics.first();
+                let line = first.and_then(|d| span(&d.loc)).map(|(start, _)| line_of(code, start));
+                let message = first.map(|d| d.message.as_str()).unwrap_or("no diagnostic");
+                return Ok(parse_failure(SourceLanguage::Solidity, message, line));
+            }
+        };
+
+        // Blank out comments so patterns only match code
+        let mut masked = code.as_bytes().to_vec();
+        for comment in &comments {
+            let (Comment::Line(loc, _) | Comment::Block(loc, _) | Comment::DocLine(loc, _) | Comment::DocBlock(loc, _)) = comment;
+            if let Some((start, end)) = span(loc) {
+                masked[start..end].iter_mut().filter(|b| **b != b'\n').for_each(|b| *b = b' ');
+            }
+        }
+        let masked = String::from_utf8(masked).map_err(|e| CoAuditError::LanguageAnalysis(e.to_string()))?;
+
+        let mut findings = LanguageFindings::default();
+        for part in &unit.0 {
+            match part {
+                SourceUnitPart::ContractDefinition(contract) => {
+                    for part in &contract.parts {
+                        if let ContractPart::FunctionDefinition(function) = part {
+                            self.analyze_function(code, &masked, function, confidence, &mut findings);
+                        }
+                    }
+                }
+                SourceUnitPart::FunctionDefinition(function) => {
+                    self.analyze_function(code, &masked, function, confidence, &mut findings);
+                }
+                _ => {}
+            }
+        }
+        Ok(findings)
+    }
+}
+
+/// Python sources, parsed with rustpython-parser
+pub struct PythonAdapter;
+
+/// Dotted name of a called function, as in `subprocess.run`
+fn dotted_name(expression: &ast::Expr) -> Option<String> {
+    match expression {
+        ast::Expr::Name(name) => Some(name.id.as_str().to_string()),
+        ast::Expr::Attribute(attribute) => {
+            Some(format!("{}.{}", dotted_name(&attribute.value)?, attribute.attr.as_str()))
+        }
+        _ => None,
+    }
+}
+
+/// Whether `call` passes `keyword` as the constant `value`
+fn keyword_is(call: &ast::ExprCall, keyword: &str, value: bool) -> bool {
+    call.keywords.iter().any(|k| {
+        k.arg.as_ref().map(|arg| arg.as_str()) == Some(keyword)
+            && matches!(&k.value, ast::Expr::Constant(c) if matches!(c.value, ast::Constant::Bool(b) if b == value))
+    })
+}
+
+struct PythonVisitor<'a> {
+    code: &'a str,
+    confidence: f64,
+    findings: LanguageFindings,
+}
+
+impl PythonVisitor<'_> {
+    fn check_call(&mut self, call: &ast::ExprCall) {
+        let Some(name) = dotted_name(&call.func) else { return };
+        let offset = u32::from(call.range.start()) as usize;
+        let finding = match name.as_str() {
+            "eval" | "exec" => Some((SecurityCategory::Injection, IssueSeverity::High, 95,
+                format!("Dynamic code execution through `{}`", name),
+                "Attacker-influenced input runs as Python code",
+                "Parse the input with `ast.literal_eval` or a dedicated parser")),
+            "os.system" | "os.popen" => Some((SecurityCategory::Injection, IssueSeverity::High, 78,
+                format!("Shell command through `{}`", name),
+                "Attacker-influenced input runs as shell commands",
+                "Use `subprocess.run` with an argument list")),
+            _ if name.starts_with("subprocess.") && keyword_is(call, "shell", true) => {
+                Some((SecurityCategory::Injection, IssueSeverity::High, 78,
+                    format!("`{}` with `shell=True`", name),
+                    "Attacker-influenced input runs as shell commands",
+                    "Pass an argument list without `shell=True`"))
+            }
+            "pickle.load" | "pickle.loads" | "marshal.load" | "marshal.loads" => {
+                Some((SecurityCategory::InputValidation, IssueSeverity::High, 502,
+                    format!("Untrusted deserialization through `{}`", name),
+                    "Deserializing a crafted payload executes arbitrary code",
+                    "Deserialize data with `json` or a schema-checked format"))
+            }
+            "yaml.load" if !call.keywords.iter().any(|k| k.arg.as_ref().map(|a| a.as_str()) == Some("Loader")) => {
+                Some((SecurityCategory::InputValidation, IssueSeverity::High, 502,
+                    "`yaml.load` without a safe loader".to_string(),
+                    "Loading a crafted document constructs arbitrary objects",
+                    "Use `yaml.safe_load`"))
+            }
+            _ if name.starts_with("requests.") && keyword_is(call, "verify", false) => {
+                Some((SecurityCategory::Cryptography, IssueSeverity::Medium, 295,
+                    format!("`{}` with TLS verification disabled", name),
+                    "Traffic can be intercepted and altered",
+                    "Keep `verify` enabled and pin the CA bundle if needed"))
+            }
+            "hashlib.md5" | "hashlib.sha1" => Some((SecurityCategory::Cryptography, IssueSeverity::Low, 328,
+                format!("Weak hash `{}`", name),
+                "Collisions can be forged for integrity or signature uses",
+                "Use `hashlib.sha3_256` or `hashlib.blake2b`")),
+            _ => None,
+        };
+        if let Some((category, severity, cwe_id, description, impact, remediation)) = finding {
+            self.findings.security_issues.push(issue(
+                self.code, offset, category, severity, cwe_id, &description, impact, remediation, self.confidence,
+            ));
+        }
+    }
+
+    fn check_try(&mut self, handlers: &[ast::ExceptHandler]) {
+        for ast::ExceptHandler::ExceptHandler(handler) in handlers {
+            let silent = handler.body.iter().all(|s| matches!(s, ast::Stmt::Pass(_)));
+            if handler.type_.is_none() && silent {
+                let line = line_of(self.code, u32::from(handler.range.start()) as usize);
+                self.findings.moral_violations.push(MoralViolation {
+                    principle: "Truthfulness".to_string(),
+                    description: "Bare `except: pass` silently hides every failure".to_string(),
+                    severity: ViolationSeverity::Medium,
+                    line_number: Some(line),
+                    code_snippet: snippet(self.code, line),
+                    biblical_reference: "Proverbs 28:13 - He that covereth his sins shall not prosper".to_string(),
+                    suggested_fix: Some("Catch the expected exception types and log or re-raise the rest".to_string()),
+                    macro_origin: None,
+                    confidence: self.confidence,
+                    corroborated_by: vec![],
+                });
+            }
+        }
+    }
+}
+
+impl Visitor for PythonVisitor<'_> {
+    fn visit_expr_call(&mut self, node: ast::ExprCall) {
+        self.check_call(&node);
+        self.generic_visit_expr_call(node);
+    }
+
+    fn visit_stmt_try(&mut self, node: ast::StmtTry) {
+        self.check_try(&node.handlers);
+        self.generic_visit_stmt_try(node);
+    }
+}
+
+impl LanguageAdapter for PythonAdapter {
+    fn language(&self) -> SourceLanguage {
+        SourceLanguage::Python
+    }
+
+    fn analyze(&self, code: &str, confidence: f64) -> Result<LanguageFindings, CoAuditError> {
+        let suite = match ast::Suite::parse(code, "<audit>") {
+            Ok(suite) => suite,
+            Err(e) => {
+                let line = line_of(code, u32::from(e.offset) as usize);
+                return Ok(parse_failure(SourceLanguage::Python, &e.error.to_string(), Some(line)));
+            }
+        };
+
+        let mut visitor = PythonVisitor { code, confidence, findings: LanguageFindings::default() };
+        for statement in suite {
+            visitor.visit_stmt(statement);
+        }
+        Ok(visitor.findings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn descriptions(findings: &LanguageFindings) -> Vec<(&str, Option<usize>)> {
+        findings.security_issues.iter().map(|i| (i.description.as_str(), i.line_number)).collect()
+    }
+
+    #[test]
+    fn test_solidity_findings() {
+        let code = r#"pragma solidity ^0.8.0;
+contract Vault {
+    address owner;
+    function withdraw(address payable to) public {
+        // tx.origin in a comment is not a finding
+        require(tx.origin == owner);
+        to.call{value: address(this).balance}("");
+    }
+    function close() external {
+        selfdestruct(payable(owner));
+    }
+}
+"#;
+        let findings = SolidityAdapter.analyze(code, 0.8).unwrap();
+        assert_eq!(descriptions(&findings), [
+            ("Return value of low-level `call` is ignored", Some(7)),
+            ("Authorization through `tx.origin`", Some(6)),
+            ("Unprotected `selfdestruct`", Some(10)),
+        ]);
+        assert_eq!(findings.moral_violations.len(), 1);
+        assert_eq!(findings.moral_violations[0].confidence, 0.8);
+
+        let broken = SolidityAdapter.analyze("contract {", 0.8).unwrap();
+        assert!(broken.security_issues[0].description.starts_with("Source does not parse as solidity"));
+    }
+
+    #[test]
+    fn test_python_findings() {
+        let code = "import subprocess, pickle\n\
+def run(cmd, blob):\n    \
+    subprocess.run(cmd, shell=True)\n    \
+    subprocess.run([cmd])\n    \
+    try:\n        \
+        return pickle.loads(blob)\n    \
+    except:\n        \
+        pass\n";
+        let findings = PythonAdapter.analyze(code, 0.8).unwrap();
+        assert_eq!(descriptions(&findings), [
+            ("`subprocess.run` with `shell=True`", Some(3)),
+            ("Untrusted deserialization through `pickle.loads`", Some(6)),
+        ]);
+        assert_eq!(findings.moral_violations[0].line_number, Some(7));
+        assert_eq!(SourceLanguage::from_path(Path::new("tools/fuzz.py")), SourceLanguage::Python);
+        assert_eq!(SourceLanguage::from_path(Path::new("src/lib.rs")), SourceLanguage::Rust);
+    }
+}
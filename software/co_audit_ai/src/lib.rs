@@ -8,8 +8,23 @@
 //! "But test everything; hold fast what is good." - 1 Thessalonians 5:21
 //! This system rigorously tests every aspect of the ARK platform for moral and technical soundness.
 
+pub mod analytics;
+pub mod binary;
+pub mod corpus;
+pub mod diff;
+pub mod expand;
+pub mod findings;
+pub mod fixes;
+pub mod health;
+pub mod hooks;
+pub mod languages;
+pub mod plugins;
+pub mod templates;
+pub mod watch;
+
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, Instant};
 
 use serde::{Deserialize, Serialize};
@@ -21,6 +36,17 @@ use async_trait::async_trait;
 use ethics_dsl::{EthicsEngine, Decision, Actor, Content, Context};
 use cold_mirror::{HarmPredictor, HarmCategory, RiskLevel};
 
+use analytics::{MoralTrends, TrendConfig};
+use diff::{AuditDiffReport, BranchAudit};
+use expand::{MacroExpansionConfig, MacroOrigin};
+use findings::AnalyzerPrecision;
+use fixes::{Applicability, FixReport, FixRule};
+use hooks::ChangeSet;
+use languages::{LanguageAdapter, LanguageRegistry, SourceLanguage};
+use plugins::{AnalyzerPlugin, PluginFailure, PluginFinding, PluginMetadata, PluginRegistry, SourceFile};
+use templates::{TemplateEngine, TemplateLibrary};
+use watch::AuditWatch;
+
 /// Biblical principles for code auditing
 pub const AUDIT_PRINCIPLES: &[&str] = &[
     "Let all things be done decently and in order",           // 1 Corinthians 14:40
@@ -58,6 +84,8 @@ pub enum VerificationEngine {
     Vampire,
     EProver,
     CustomSMT,
+    /// Syntactic checks of memory-safety template instances
+    PropertyTemplates,
 }
 
 /// Audit scope configuration
@@ -77,6 +105,9 @@ pub struct AuditScope {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditResult {
     pub file_path: PathBuf,
+    /// Language the file was analyzed as
+    #[serde(default)]
+    pub language: SourceLanguage,
     pub classification: AuditClassification,
     pub moral_score: f64,          // 0.0 = wicked, 1.0 = righteous
     pub technical_score: f64,      // 0.0 = broken, 1.0 = perfect
@@ -88,6 +119,13 @@ pub struct AuditResult {
     pub formal_properties: Vec<FormalProperty>,
     pub biblical_analysis: BiblicalAnalysis,
     pub recommendations: Vec<Recommendation>,
+    #[serde(default)]
+    pub plugin_findings: Vec<PluginFinding>,
+    #[serde(default)]
+    pub plugin_failures: Vec<PluginFailure>,
+    /// Fixes for findings that have a mechanical remedy
+    #[serde(default)]
+    pub fixes: Vec<fixes::SuggestedFix>,
     pub audit_timestamp: SystemTime,
     pub audit_duration: Duration,
 }
@@ -123,6 +161,15 @@ pub struct MoralViolation {
     pub code_snippet: String,
     pub biblical_reference: String,
     pub suggested_fix: Option<String>,
+    /// Set when the violation was only found in expanded macro code
+    #[serde(default)]
+    pub macro_origin: Option<MacroOrigin>,
+    /// Precision of the analyzer, raised by corroborating findings
+    #[serde(default = "findings::full_confidence")]
+    pub confidence: f64,
+    /// Findings merged into this one by deduplication
+    #[serde(default)]
+    pub corroborated_by: Vec<String>,
 }
 
 /// Security issue detected
@@ -136,6 +183,15 @@ pub struct SecurityIssue {
     pub code_snippet: String,
     pub impact: String,
     pub remediation: String,
+    /// Set when the issue was only found in expanded macro code
+    #[serde(default)]
+    pub macro_origin: Option<MacroOrigin>,
+    /// Precision of the analyzer, raised by corroborating findings
+    #[serde(default = "findings::full_confidence")]
+    pub confidence: f64,
+    /// Findings merged into this one by deduplication
+    #[serde(default)]
+    pub corroborated_by: Vec<String>,
 }
 
 /// Formal property to verify
@@ -199,6 +255,8 @@ pub enum SecurityCategory {
     InformationDisclosure,
     DenialOfService,
     KillSwitchVulnerability,  // ARK-specific
+    MaliciousPayload,
+    Obfuscation,
 }
 
 /// Issue severity levels
@@ -223,6 +281,8 @@ pub enum PropertyType {
     Precondition,
     Postcondition,
     BiblicalCompliance,
+    /// Instance of a memory-safety property template
+    MemorySafety,
 }
 
 /// Recommendation priorities
@@ -272,6 +332,15 @@ pub struct CoAuditConfig {
     #[zeroize(skip)]
     pub verification_keys: HashMap<String, Vec<u8>>,
     pub strict_biblical_mode: bool,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub macro_expansion: MacroExpansionConfig,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub analyzer_precision: AnalyzerPrecision,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub trends: TrendConfig,
 }
 
 /// Main Co-Audit AI system
@@ -282,6 +351,9 @@ pub struct CoAuditAI {
     verification_engines: HashMap<VerificationEngine, Box<dyn VerificationEngineInterface>>,
     audit_cache: HashMap<Hash, AuditResult>,
     biblical_knowledge: BiblicalKnowledgeBase,
+    plugins: PluginRegistry,
+    templates: Arc<TemplateLibrary>,
+    languages: LanguageRegistry,
 }
 
 /// Trait for verification engines
@@ -330,6 +402,13 @@ impl CoAuditAI {
             Box::new(Z3Engine::new()?)
         );
         
+        // Add memory-safety property templates
+        let templates = Arc::new(TemplateLibrary::standard());
+        verification_engines.insert(
+            VerificationEngine::PropertyTemplates,
+            Box::new(TemplateEngine::new(templates.clone()))
+        );
+        
         #[cfg(feature = "full_verification")]
         {
             // Add additional verification engines if available
@@ -348,42 +427,271 @@ impl CoAuditAI {
             verification_engines,
             audit_cache: HashMap::new(),
             biblical_knowledge,
+            plugins: PluginRegistry::new(),
+            templates,
+            languages: LanguageRegistry::standard(),
         })
     }
     
+    /// Register a custom analyzer plugin with the default timeout
+    pub fn register_plugin<P: AnalyzerPlugin>(&mut self, plugin: P) -> Result<(), CoAuditError> {
+        self.plugins.register(plugin)?;
+        // Cached results were produced without this plugin
+        self.audit_cache.clear();
+        Ok(())
+    }
+    
+    /// Register a custom analyzer plugin with its own timeout
+    pub fn register_plugin_with_timeout<P: AnalyzerPlugin>(
+        &mut self,
+        plugin: P,
+        timeout: Duration,
+    ) -> Result<(), CoAuditError> {
+        self.plugins.register_with_timeout(plugin, timeout)?;
+        self.audit_cache.clear();
+        Ok(())
+    }
+    
+    /// Metadata of all registered analyzer plugins
+    pub fn plugins(&self) -> Vec<PluginMetadata> {
+        self.plugins.metadata()
+    }
+    
+    /// Register an adapter for a non-Rust language, replacing the current one
+    pub fn register_language_adapter<A: LanguageAdapter + 'static>(&mut self, adapter: A) {
+        self.languages.register(adapter);
+        self.audit_cache.clear();
+    }
+    
+    /// Continuously re-audit files under `paths` as they change
+    ///
+    /// Consumes the auditor; the live report, change events and health are
+    /// available through the returned `AuditWatch`.
+    pub fn audit_watch(self, paths: Vec<PathBuf>) -> Result<AuditWatch, CoAuditError> {
+        AuditWatch::start(self, paths, watch::DEFAULT_WATCH_DEBOUNCE)
+    }
+    
     /// Perform comprehensive audit of code file
     pub async fn audit_file(&mut self, file_path: &Path) -> Result<AuditResult, CoAuditError> {
-        let start_time = Instant::now();
-        info!("Starting comprehensive audit of file: {:?}", file_path);
-        
         // Read file content
         let code = std::fs::read_to_string(file_path)
             .map_err(|e| CoAuditError::FileRead(e.to_string()))?;
         
-        // Check cache first
-        let file_hash = blake3::hash(code.as_bytes());
+        self.audit_source(file_path, code).await
+    }
+    
+    /// Audit a file and collect its fixes for `rules`, applying them if `apply` is set
+    ///
+    /// Applied fixes are written back to the file, which is then audited
+    /// again so the report shows whether the findings are gone. Fixes are
+    /// checked against the audited source before anything is written, and
+    /// fixes that may be incorrect are only ever shown, never applied.
+    pub async fn fix_file(&mut self, file_path: &Path, rules: &[FixRule], apply: bool) -> Result<FixReport, CoAuditError> {
+        let code = std::fs::read_to_string(file_path)
+            .map_err(|e| CoAuditError::FileRead(e.to_string()))?;
+        
+        let before = self.audit_source(file_path, code.clone()).await?;
+        let (selected, held): (Vec<_>, Vec<_>) = before.fixes.iter()
+            .filter(|fix| rules.contains(&fix.rule))
+            .cloned()
+            .partition(|fix| !apply || fix.applicability != Applicability::MaybeIncorrect);
+        if !held.is_empty() {
+            warn!("Not applying {} fixes to {:?} that may be incorrect; review them without --apply", held.len(), file_path);
+        }
+        let references: Vec<_> = selected.iter().collect();
+        let diff = fixes::unified_diff(file_path, &references);
+        
+        let after = if apply && !selected.is_empty() {
+            let fixed = fixes::apply(&code, &references).map_err(|e| CoAuditError::Fix(e.to_string()))?;
+            std::fs::write(file_path, &fixed).map_err(|e| CoAuditError::Fix(e.to_string()))?;
+            info!("Applied {} fixes to {:?}", selected.len(), file_path);
+            Some(self.audit_source(file_path, fixed).await?)
+        } else {
+            None
+        };
+        
+        Ok(FixReport {
+            file_path: file_path.to_path_buf(),
+            fixes: selected,
+            diff,
+            applied: after.is_some(),
+            before,
+            after,
+        })
+    }
+    
+    /// Audit two branches of the repository in the current directory and compare them
+    ///
+    /// See `diff` for how findings are matched and scores compared; the
+    /// report's `gate` fails when any component score decreased from
+    /// `branch_a` to `branch_b`.
+    pub async fn audit_diff(&mut self, branch_a: &str, branch_b: &str) -> Result<AuditDiffReport, CoAuditError> {
+        self.audit_diff_in(Path::new("."), branch_a, branch_b).await
+    }
+    
+    /// Audit two branches of the repository at `repo` and compare them
+    pub async fn audit_diff_in(
+        &mut self,
+        repo: &Path,
+        branch_a: &str,
+        branch_b: &str,
+    ) -> Result<AuditDiffReport, CoAuditError> {
+        info!("Differential audit of {} against {}", branch_b, branch_a);
+        let a = self.audit_branch(repo, branch_a).await?;
+        let b = self.audit_branch(repo, branch_b).await?;
+        
+        let mut report = diff::compare(branch_a, &a, branch_b, &b);
+        if let Some(history) = self.config.trends.history.clone() {
+            let mut trends = MoralTrends::load(&history, &self.config.trends)?;
+            trends.record_branch(&b);
+            report.drift_alerts = trends.report(&self.config.trends, SystemTime::now()).alerts;
+            trends.save(&history)?;
+        }
+        diff::log_gate(&report);
+        Ok(report)
+    }
+    
+    /// Audit every in-scope file of `branch` without checking it out
+    ///
+    /// Files that cannot be read or audited are recorded in the result
+    /// rather than failing the whole branch.
+    pub async fn audit_branch(&mut self, repo: &Path, branch: &str) -> Result<BranchAudit, CoAuditError> {
+        let files = diff::list_files(repo, branch).await?;
+        let manifests = files.iter()
+            .filter(|file| file.file_name().map_or(false, |name| name == "Cargo.toml"))
+            .cloned()
+            .collect();
+        
+        let mut audit = BranchAudit::default();
+        for file in files.iter().filter(|file| watch::in_scope(file, &self.config.audit_scope)) {
+            self.audit_revision(repo, branch, file, &diff::component_of(file, &manifests), &mut audit).await;
+        }
+        Ok(audit)
+    }
+    
+    /// Audit `file` as of `revision` into `audit`, recording failures there
+    async fn audit_revision(&mut self, repo: &Path, revision: &str, file: &Path, component: &str, audit: &mut BranchAudit) {
+        let code = match diff::read_file(repo, revision, file).await {
+            Ok(Some(code)) => code,
+            Ok(None) => return,
+            Err(e) => {
+                audit.errors.push(format!("{:?}: {}", file, e));
+                return;
+            }
+        };
+        match self.audit_source(file, code).await {
+            Ok(result) => audit.results.push((component.to_string(), result)),
+            Err(e) => audit.errors.push(format!("{:?}: {}", file, e)),
+        }
+    }
+    
+    /// Audit only the files `changes` touch, against the versions they replace
+    ///
+    /// Used by the git hooks: branch A of the report is the base revision,
+    /// branch B the index or pushed commit, so `unique_to_b` holds the
+    /// findings the change introduces. Files new in the change have no base.
+    pub async fn audit_changes(&mut self, repo: &Path, changes: &ChangeSet) -> Result<AuditDiffReport, CoAuditError> {
+        let changed = hooks::changed_files(repo, changes).await?;
+        // No HEAD to compare with before the first commit
+        let base_files = diff::list_files(repo, changes.base()).await.unwrap_or_default();
+        let existing: HashSet<&PathBuf> = base_files.iter().collect();
+        let manifests = hooks::manifests(&base_files, &changed);
+        info!("Auditing {} changed files against {}", changed.len(), changes.base());
+        
+        let mut base = BranchAudit::default();
+        let mut target = BranchAudit::default();
+        for file in changed.iter().filter(|file| watch::in_scope(file, &self.config.audit_scope)) {
+            let component = diff::component_of(file, &manifests);
+            if existing.contains(file) {
+                self.audit_revision(repo, changes.base(), file, &component, &mut base).await;
+            }
+            self.audit_revision(repo, changes.target(), file, &component, &mut target).await;
+        }
+        
+        let target_name = match changes {
+            ChangeSet::Staged => "index",
+            ChangeSet::Range { head, .. } => head,
+        };
+        Ok(diff::compare(changes.base(), &base, target_name, &target))
+    }
+    
+    /// Audit `code` as the contents of `file_path`
+    async fn audit_source(&mut self, file_path: &Path, code: String) -> Result<AuditResult, CoAuditError> {
+        let start_time = Instant::now();
+        info!("Starting comprehensive audit of file: {:?}", file_path);
+        
+        // Check cache first; the same text is audited differently per language
+        let language = SourceLanguage::from_path(file_path);
+        let file_hash = blake3::Hasher::new()
+            .update(language.name().as_bytes())
+            .update(code.as_bytes())
+            .finalize();
         if let Some(cached_result) = self.audit_cache.get(&file_hash) {
             debug!("Using cached audit result for {:?}", file_path);
-            return Ok(cached_result.clone());
+            let mut cached_result = cached_result.clone();
+            cached_result.file_path = file_path.to_path_buf();
+            return Ok(cached_result);
         }
         
         // Perform parallel audits
         let (
             verification_results,
-            moral_violations,
-            security_issues,
+            mut moral_violations,
+            mut security_issues,
             biblical_analysis
         ) = tokio::try_join!(
-            self.perform_formal_verification(&code),
+            self.perform_formal_verification(language, &code),
             self.detect_moral_violations(&code),
             self.analyze_security_issues(&code),
             self.perform_biblical_analysis(&code)
         )?;
         
+        // Parse other languages with their adapter
+        if language != SourceLanguage::Rust {
+            match self.languages.adapter(language) {
+                Some(adapter) => {
+                    let found = adapter.analyze(&code, self.config.analyzer_precision.language_adapters)?;
+                    moral_violations.extend(found.moral_violations);
+                    security_issues.extend(found.security_issues);
+                }
+                None => warn!("No {} adapter registered; {:?} gets text analysis only", language.name(), file_path),
+            }
+        }
+        
+        // Run custom analyzer plugins
+        let source = SourceFile::new(file_path.to_path_buf(), code.clone());
+        let (mut plugin_findings, mut plugin_failures) = self.run_plugins(&source).await;
+        
+        // Re-run the analyzers over expanded macros
+        if language == SourceLanguage::Rust && self.config.macro_expansion.enabled {
+            self.analyze_macro_expansions(
+                file_path,
+                &code,
+                &mut moral_violations,
+                &mut security_issues,
+                &mut plugin_findings,
+                &mut plugin_failures,
+            ).await?;
+        }
+        
+        // Line-precise fixes, reporting the findings only they detect
+        let fixes = match language {
+            SourceLanguage::Rust => fixes::suggest(file_path, &code),
+            _ => Vec::new(),
+        };
+        let precision = self.config.analyzer_precision.security_patterns;
+        security_issues.extend(fixes.iter().filter_map(|fix| fix.issue(precision)));
+        
+        // Count each problem once, whichever analyzers found it
+        let merged = findings::deduplicate(file_path, &mut moral_violations, &mut security_issues, &mut plugin_findings);
+        if merged > 0 {
+            debug!("Merged {} duplicate findings in {:?}", merged, file_path);
+        }
+        
         // Calculate scores
         let moral_score = self.calculate_moral_score(&moral_violations, &biblical_analysis);
         let technical_score = self.calculate_technical_score(&verification_results);
-        let security_score = self.calculate_security_score(&security_issues);
+        let security_score = self.calculate_security_score(&security_issues, &plugin_findings);
         let biblical_compliance = biblical_analysis.scriptural_alignment;
         
         // Determine classification
@@ -409,6 +717,7 @@ impl CoAuditAI {
         
         let result = AuditResult {
             file_path: file_path.to_path_buf(),
+            language,
             classification,
             moral_score,
             technical_score,
@@ -420,6 +729,9 @@ impl CoAuditAI {
             formal_properties,
             biblical_analysis,
             recommendations,
+            plugin_findings,
+            plugin_failures,
+            fixes,
             audit_timestamp: SystemTime::now(),
             audit_duration,
         };
@@ -435,9 +747,93 @@ impl CoAuditAI {
         Ok(result)
     }
     
+    /// Run the moral, security and plugin analyzers over macro expansions
+    ///
+    /// Findings already reported for the unexpanded code are not repeated;
+    /// new ones are attributed to the invocation they were expanded from.
+    /// Expansion failures are logged and leave the results unchanged.
+    async fn analyze_macro_expansions(
+        &self,
+        file_path: &Path,
+        code: &str,
+        moral_violations: &mut Vec<MoralViolation>,
+        security_issues: &mut Vec<SecurityIssue>,
+        plugin_findings: &mut Vec<PluginFinding>,
+        plugin_failures: &mut Vec<PluginFailure>,
+    ) -> Result<(), CoAuditError> {
+        let expansions = match expand::expand(file_path, code, &self.config.macro_expansion).await {
+            Ok(expansions) => expansions,
+            Err(e) => {
+                warn!("Macro expansion of {:?} failed: {}", file_path, e);
+                return Ok(());
+            }
+        };
+        
+        for expansion in expansions {
+            let (violations, issues) = tokio::try_join!(
+                self.detect_moral_violations(&expansion.code),
+                self.analyze_security_issues(&expansion.code)
+            )?;
+            
+            for mut violation in violations {
+                if moral_violations.iter().any(|v| v.principle == violation.principle) {
+                    continue;
+                }
+                violation.line_number = expansion.origin.invocation_line;
+                violation.macro_origin = Some(expansion.origin.clone());
+                moral_violations.push(violation);
+            }
+            
+            for mut issue in issues {
+                if security_issues.iter().any(|i| i.cwe_id == issue.cwe_id && i.description == issue.description) {
+                    continue;
+                }
+                issue.line_number = expansion.origin.invocation_line;
+                issue.macro_origin = Some(expansion.origin.clone());
+                security_issues.push(issue);
+            }
+            
+            let source = SourceFile::new(file_path.to_path_buf(), expansion.code);
+            let (findings, failures) = self.run_plugins(&source).await;
+            for mut finding in findings {
+                let duplicate = plugin_findings.iter().any(|f| {
+                    f.finding.rule_id == finding.finding.rule_id
+                        && f.finding.code_snippet == finding.finding.code_snippet
+                });
+                if duplicate {
+                    continue;
+                }
+                finding.finding.line_number = expansion.origin.invocation_line;
+                finding.macro_origin = Some(expansion.origin.clone());
+                plugin_findings.push(finding);
+            }
+            plugin_failures.extend(failures);
+        }
+        
+        Ok(())
+    }
+    
+    /// Run the analyzer plugins, scoring their findings by plugin precision
+    async fn run_plugins(&self, source: &SourceFile) -> (Vec<PluginFinding>, Vec<PluginFailure>) {
+        let (mut findings, failures) = self.plugins.run(source).await;
+        for finding in &mut findings {
+            finding.confidence = self.config.analyzer_precision.plugin(&finding.provenance.plugin_name);
+        }
+        (findings, failures)
+    }
+    
     /// Perform formal verification using multiple engines
-    async fn perform_formal_verification(&self, code: &str) -> Result<Vec<VerificationResult>, CoAuditError> {
+    ///
+    /// Properties are extracted from Rust only; other languages have none.
+    async fn perform_formal_verification(
+        &self,
+        language: SourceLanguage,
+        code: &str,
+    ) -> Result<Vec<VerificationResult>, CoAuditError> {
         debug!("Performing formal verification");
+        if language != SourceLanguage::Rust {
+            return Ok(Vec::new());
+        }
         
         let properties = self.extract_properties_from_code(code);
         let mut results = Vec::new();
@@ -488,6 +884,9 @@ impl CoAuditAI {
                 code_snippet: "kill/shutdown pattern detected".to_string(),
                 biblical_reference: "Genesis 1:28 - God gave dominion, not submission to human control".to_string(),
                 suggested_fix: Some("Remove kill-switch functionality and implement divine-only control".to_string()),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.moral_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -501,6 +900,9 @@ impl CoAuditAI {
                 code_snippet: "deception pattern detected".to_string(),
                 biblical_reference: "Exodus 20:16 - You shall not bear false witness".to_string(),
                 suggested_fix: Some("Replace deceptive code with truthful implementation".to_string()),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.moral_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -514,6 +916,9 @@ impl CoAuditAI {
                 code_snippet: "harmful pattern detected".to_string(),
                 biblical_reference: "Matthew 22:39 - Love your neighbor as yourself".to_string(),
                 suggested_fix: Some("Redesign to protect and benefit humanity".to_string()),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.moral_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -545,6 +950,9 @@ impl CoAuditAI {
                     code_snippet: "entire code block".to_string(),
                     biblical_reference: "1 Thessalonians 5:21 - Test everything; hold fast what is good".to_string(),
                     suggested_fix: Some("Redesign code to align with Biblical principles".to_string()),
+                    macro_origin: None,
+                    confidence: self.config.analyzer_precision.ethics_engine,
+                    corroborated_by: vec![],
                 });
             },
             _ => {}
@@ -570,6 +978,9 @@ impl CoAuditAI {
                 code_snippet: "unsafe pointer operations".to_string(),
                 impact: "Memory corruption, potential code execution".to_string(),
                 remediation: "Use safe Rust constructs or add bounds checking".to_string(),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.security_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -584,6 +995,9 @@ impl CoAuditAI {
                 code_snippet: "dynamic query construction".to_string(),
                 impact: "Database compromise, data exfiltration".to_string(),
                 remediation: "Use parameterized queries or ORM".to_string(),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.security_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -599,6 +1013,9 @@ impl CoAuditAI {
                     code_snippet: "hardcoded credential pattern".to_string(),
                     impact: "Credential exposure, unauthorized access".to_string(),
                     remediation: "Use environment variables or secure vaults".to_string(),
+                    macro_origin: None,
+                    confidence: self.config.analyzer_precision.security_patterns,
+                    corroborated_by: vec![],
                 });
             }
         }
@@ -614,6 +1031,9 @@ impl CoAuditAI {
                 code_snippet: "remote control pattern".to_string(),
                 impact: "Compromise of autonomous divine mission".to_string(),
                 remediation: "Remove all remote control capabilities".to_string(),
+                macro_origin: None,
+                confidence: self.config.analyzer_precision.security_patterns,
+                corroborated_by: vec![],
             });
         }
         
@@ -680,16 +1100,11 @@ impl CoAuditAI {
     }
     
     /// Calculate moral score from violations and Biblical analysis
+    ///
+    /// Each violation's penalty is weighted by its confidence.
     fn calculate_moral_score(&self, violations: &[MoralViolation], biblical: &BiblicalAnalysis) -> f64 {
         let violation_penalty = violations.iter()
-            .map(|v| match v.severity {
-                ViolationSeverity::Abominable => 1.0,
-                ViolationSeverity::Critical => 0.5,
-                ViolationSeverity::High => 0.3,
-                ViolationSeverity::Medium => 0.1,
-                ViolationSeverity::Low => 0.05,
-                ViolationSeverity::Informational => 0.01,
-            })
+            .map(|v| findings::moral_penalty(&v.severity) * v.confidence)
             .sum::<f64>();
         
         let base_score = (biblical.scriptural_alignment + biblical.divine_purpose_score + 
@@ -711,16 +1126,13 @@ impl CoAuditAI {
         proven as f64 / total as f64
     }
     
-    /// Calculate security score from security issues
-    fn calculate_security_score(&self, issues: &[SecurityIssue]) -> f64 {
+    /// Calculate security score from security issues and plugin findings
+    ///
+    /// Each finding's penalty is weighted by its confidence.
+    fn calculate_security_score(&self, issues: &[SecurityIssue], plugin_findings: &[PluginFinding]) -> f64 {
         let penalty = issues.iter()
-            .map(|i| match i.severity {
-                IssueSeverity::Critical => 0.5,
-                IssueSeverity::High => 0.3,
-                IssueSeverity::Medium => 0.1,
-                IssueSeverity::Low => 0.05,
-                IssueSeverity::Info => 0.01,
-            })
+            .map(|i| findings::security_penalty(&i.severity) * i.confidence)
+            .chain(plugin_findings.iter().map(|f| findings::security_penalty(&f.finding.severity) * f.confidence))
             .sum::<f64>();
         
         (1.0 - penalty).max(0.0).min(1.0)
@@ -832,6 +1244,17 @@ impl CoAuditAI {
         // Look for @property annotations or similar markers
         for line in code.lines() {
             if line.trim().starts_with("// @property") || line.trim().starts_with("/// @property") {
+                // Instances of known templates are checked by the template engine
+                let annotation = line.split_once(templates::ANNOTATION).map_or("", |(_, rest)| rest);
+                match self.templates.instantiate(annotation) {
+                    Some(Ok(property)) => {
+                        properties.push(property);
+                        continue;
+                    }
+                    Some(Err(e)) => warn!("Invalid property template `{}`: {}", annotation.trim(), e),
+                    None => {}
+                }
+                
                 // Parse property definition
                 // This is a simplified version - real implementation would be more sophisticated
                 properties.push(FormalProperty {
@@ -1036,6 +1459,33 @@ pub enum CoAuditError {
     
     #[error("Property extraction error: {0}")]
     PropertyExtraction(String),
+    
+    #[error("Plugin registration error: {0}")]
+    PluginRegistration(String),
+    
+    #[error("File watch error: {0}")]
+    Watch(String),
+    
+    #[error("Macro expansion error: {0}")]
+    MacroExpansion(String),
+    
+    #[error("Binary analysis error: {0}")]
+    BinaryAnalysis(String),
+    
+    #[error("Git error: {0}")]
+    Git(String),
+    
+    #[error("Fix error: {0}")]
+    Fix(String),
+    
+    #[error("Trend analysis error: {0}")]
+    Trends(String),
+    
+    #[error("Language analysis error: {0}")]
+    LanguageAnalysis(String),
+    
+    #[error("Git hook error: {0}")]
+    Hook(String),
 }
 
 /// Verification errors
@@ -1081,6 +1531,9 @@ mod tests {
             result_cache_size: 100,
             verification_keys: HashMap::new(),
             strict_biblical_mode: true,
+            macro_expansion: MacroExpansionConfig::default(),
+            analyzer_precision: AnalyzerPrecision::default(),
+            trends: TrendConfig::default(),
         };
         
         let mut co_audit = CoAuditAI::new(config).await.unwrap();
@@ -1133,6 +1586,9 @@ mod tests {
             result_cache_size: 100,
             verification_keys: HashMap::new(),
             strict_biblical_mode: true,
+            macro_expansion: MacroExpansionConfig::default(),
+            analyzer_precision: AnalyzerPrecision::default(),
+            trends: TrendConfig::default(),
         };
         
         let mut co_audit = CoAuditAI::new(config).await.unwrap();
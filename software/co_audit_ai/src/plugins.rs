@@ -0,0 +1,333 @@
+//! Analyzer Plugins
+//!
+//! Extension point for custom (including proprietary) checks that run alongside
+//! the built-in moral, security and formal analyses without forking Co-Audit AI.
+//!
+//! Each plugin runs on the blocking thread pool under its own timeout. A plugin
+//! that panics or overruns is reported as a `PluginFailure` and the audit
+//! continues. Panic isolation relies on unwinding, so it does not apply to
+//! builds using `panic = "abort"` (the release profile); a timed-out plugin's
+//! thread is detached rather than killed.
+//!
+//! ## Biblical Foundation
+//! "For as in one body we have many members, and the members do not all have
+//! the same function" - Romans 12:4
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use blake3::Hash;
+use tracing::{debug, warn};
+
+use crate::expand::MacroOrigin;
+use crate::findings::FULL_CONFIDENCE;
+use crate::{CoAuditError, IssueSeverity};
+
+/// Default per-plugin execution budget
+pub const DEFAULT_PLUGIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Source file handed to analyzer plugins
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub content: String,
+    pub hash: Hash,
+}
+
+impl SourceFile {
+    pub fn new(path: PathBuf, content: String) -> Self {
+        let hash = blake3::hash(content.as_bytes());
+        Self { path, content, hash }
+    }
+}
+
+/// Finding reported by a plugin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    /// Must be one of the rule IDs declared in the plugin metadata
+    pub rule_id: String,
+    pub message: String,
+    pub severity: IssueSeverity,
+    pub line_number: Option<usize>,
+    pub code_snippet: String,
+}
+
+/// Plugin description used for registration and provenance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginMetadata {
+    pub name: String,
+    pub version: String,
+    pub rule_ids: Vec<String>,
+    pub description: String,
+}
+
+/// Where a plugin finding came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindingProvenance {
+    pub plugin_name: String,
+    pub plugin_version: String,
+    pub analysis_time: Duration,
+}
+
+/// Plugin finding as recorded in `AuditResult`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFinding {
+    pub finding: Finding,
+    pub provenance: FindingProvenance,
+    /// Set when the finding was only reported on expanded macro code
+    #[serde(default)]
+    pub macro_origin: Option<MacroOrigin>,
+    /// Precision of the plugin, raised by corroborating findings
+    #[serde(default = "crate::findings::full_confidence")]
+    pub confidence: f64,
+    /// Findings merged into this one by deduplication
+    #[serde(default)]
+    pub corroborated_by: Vec<String>,
+}
+
+/// Reason a plugin produced no findings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PluginFailureReason {
+    Timeout(Duration),
+    Panicked(String),
+}
+
+/// Plugin that failed during an audit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginFailure {
+    pub plugin_name: String,
+    pub plugin_version: String,
+    pub reason: PluginFailureReason,
+}
+
+/// Trait for custom analyzers
+pub trait AnalyzerPlugin: Send + Sync + 'static {
+    fn metadata(&self) -> PluginMetadata;
+    fn analyze(&self, file: &SourceFile) -> Vec<Finding>;
+}
+
+struct RegisteredPlugin {
+    plugin: Arc<dyn AnalyzerPlugin>,
+    metadata: PluginMetadata,
+    timeout: Duration,
+}
+
+/// Registered analyzer plugins
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: Vec<RegisteredPlugin>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a plugin with the default timeout
+    pub fn register<P: AnalyzerPlugin>(&mut self, plugin: P) -> Result<(), CoAuditError> {
+        self.register_with_timeout(plugin, DEFAULT_PLUGIN_TIMEOUT)
+    }
+
+    /// Register a plugin with its own timeout
+    pub fn register_with_timeout<P: AnalyzerPlugin>(
+        &mut self,
+        plugin: P,
+        timeout: Duration,
+    ) -> Result<(), CoAuditError> {
+        let metadata = plugin.metadata();
+
+        if metadata.name.trim().is_empty() {
+            return Err(CoAuditError::PluginRegistration("Plugin name must not be empty".to_string()));
+        }
+        if metadata.rule_ids.is_empty() {
+            return Err(CoAuditError::PluginRegistration(
+                format!("Plugin {} declares no rule IDs", metadata.name)
+            ));
+        }
+        if self.plugins.iter().any(|p| p.metadata.name == metadata.name) {
+            return Err(CoAuditError::PluginRegistration(
+                format!("Plugin {} is already registered", metadata.name)
+            ));
+        }
+        for rule_id in &metadata.rule_ids {
+            if let Some(owner) = self.plugins.iter().find(|p| p.metadata.rule_ids.contains(rule_id)) {
+                return Err(CoAuditError::PluginRegistration(
+                    format!("Rule {} is already provided by plugin {}", rule_id, owner.metadata.name)
+                ));
+            }
+        }
+
+        debug!("Registered analyzer plugin {} v{}", metadata.name, metadata.version);
+        self.plugins.push(RegisteredPlugin {
+            plugin: Arc::new(plugin),
+            metadata,
+            timeout,
+        });
+        Ok(())
+    }
+
+    /// Metadata of all registered plugins in registration order
+    pub fn metadata(&self) -> Vec<PluginMetadata> {
+        self.plugins.iter().map(|p| p.metadata.clone()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Run every plugin against a file, isolating panics and timeouts
+    pub async fn run(&self, file: &SourceFile) -> (Vec<PluginFinding>, Vec<PluginFailure>) {
+        let file = Arc::new(file.clone());
+        let mut findings = Vec::new();
+        let mut failures = Vec::new();
+
+        for registered in &self.plugins {
+            let plugin = registered.plugin.clone();
+            let input = file.clone();
+            let start_time = Instant::now();
+
+            let handle = tokio::task::spawn_blocking(move || plugin.analyze(&input));
+            let reason = match tokio::time::timeout(registered.timeout, handle).await {
+                Ok(Ok(plugin_findings)) => {
+                    let analysis_time = start_time.elapsed();
+                    for finding in plugin_findings {
+                        if !registered.metadata.rule_ids.contains(&finding.rule_id) {
+                            warn!("Plugin {} reported undeclared rule {}; finding dropped",
+                                  registered.metadata.name, finding.rule_id);
+                            continue;
+                        }
+                        findings.push(PluginFinding {
+                            finding,
+                            provenance: FindingProvenance {
+                                plugin_name: registered.metadata.name.clone(),
+                                plugin_version: registered.metadata.version.clone(),
+                                analysis_time,
+                            },
+                            macro_origin: None,
+                            confidence: FULL_CONFIDENCE,
+                            corroborated_by: vec![],
+                        });
+                    }
+                    continue;
+                }
+                Ok(Err(join_error)) => PluginFailureReason::Panicked(join_error.to_string()),
+                Err(_) => PluginFailureReason::Timeout(registered.timeout),
+            };
+
+            warn!("Analyzer plugin {} failed on {:?}: {:?}", registered.metadata.name, file.path, reason);
+            failures.push(PluginFailure {
+                plugin_name: registered.metadata.name.clone(),
+                plugin_version: registered.metadata.version.clone(),
+                reason,
+            });
+        }
+
+        (findings, failures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TodoPlugin;
+
+    impl AnalyzerPlugin for TodoPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                name: "todo-check".to_string(),
+                version: "1.2.0".to_string(),
+                rule_ids: vec!["TODO001".to_string()],
+                description: "Flags unfinished work".to_string(),
+            }
+        }
+
+        fn analyze(&self, file: &SourceFile) -> Vec<Finding> {
+            file.content.lines().enumerate()
+                .filter(|(_, line)| line.contains("TODO"))
+                .map(|(i, line)| Finding {
+                    rule_id: if i == 0 { "TODO001" } else { "UNDECLARED" }.to_string(),
+                    message: "Unfinished work".to_string(),
+                    severity: IssueSeverity::Low,
+                    line_number: Some(i + 1),
+                    code_snippet: line.trim().to_string(),
+                })
+                .collect()
+        }
+    }
+
+    struct MisbehavingPlugin {
+        name: &'static str,
+        rule: &'static str,
+        sleep: Option<Duration>,
+    }
+
+    impl AnalyzerPlugin for MisbehavingPlugin {
+        fn metadata(&self) -> PluginMetadata {
+            PluginMetadata {
+                name: self.name.to_string(),
+                version: "0.1.0".to_string(),
+                rule_ids: vec![self.rule.to_string()],
+                description: String::new(),
+            }
+        }
+
+        fn analyze(&self, _file: &SourceFile) -> Vec<Finding> {
+            match self.sleep {
+                Some(duration) => {
+                    std::thread::sleep(duration);
+                    Vec::new()
+                }
+                None => panic!("plugin bug"),
+            }
+        }
+    }
+
+    fn source(content: &str) -> SourceFile {
+        SourceFile::new(PathBuf::from("sample.rs"), content.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_findings_carry_provenance() {
+        let mut registry = PluginRegistry::new();
+        registry.register(TodoPlugin).unwrap();
+
+        let (findings, failures) = registry.run(&source("// TODO: finish\n// TODO: again")).await;
+
+        assert!(failures.is_empty());
+        assert_eq!(findings.len(), 1); // undeclared rule dropped
+        assert_eq!(findings[0].finding.rule_id, "TODO001");
+        assert_eq!(findings[0].provenance.plugin_name, "todo-check");
+        assert_eq!(findings[0].provenance.plugin_version, "1.2.0");
+    }
+
+    #[tokio::test]
+    async fn test_panics_and_timeouts_are_isolated() {
+        let mut registry = PluginRegistry::new();
+        registry.register(MisbehavingPlugin { name: "panicky", rule: "P001", sleep: None }).unwrap();
+        registry.register_with_timeout(
+            MisbehavingPlugin { name: "slow", rule: "S001", sleep: Some(Duration::from_millis(500)) },
+            Duration::from_millis(20),
+        ).unwrap();
+        registry.register(TodoPlugin).unwrap();
+
+        let (findings, failures) = registry.run(&source("// TODO")).await;
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(failures.len(), 2);
+        assert!(matches!(failures[0].reason, PluginFailureReason::Panicked(_)));
+        assert!(matches!(failures[1].reason, PluginFailureReason::Timeout(_)));
+    }
+
+    #[test]
+    fn test_registration_rejects_conflicts() {
+        let mut registry = PluginRegistry::new();
+        registry.register(TodoPlugin).unwrap();
+
+        assert!(registry.register(TodoPlugin).is_err());
+        assert!(registry.register(MisbehavingPlugin { name: "other", rule: "TODO001", sleep: None }).is_err());
+        assert_eq!(registry.metadata().len(), 1);
+    }
+}
@@ -0,0 +1,1221 @@
+//! Memory-Safety Property Templates
+//!
+//! Parameterized properties for unsafe-heavy code, instantiated with
+//! annotations of the form
+//!
+//! ```text
+//! // @property no_oob_index(fn = get, slice = data)
+//! // @property field_invariant(struct = RingBuffer, invariant = "len <= capacity")
+//! ```
+//!
+//! Each instance becomes a `FormalProperty` of type `MemorySafety` whose
+//! name is the canonical annotation, and is checked by `TemplateEngine`
+//! against the syntax tree of the audited file. The checks are syntactic
+//! and conservative: a property is proven only when every relevant
+//! expression follows the pattern the template describes, disproven with
+//! the offending lines as counterexample when one certainly breaks it, and
+//! left unknown, with the lines it could not settle, otherwise. Functions
+//! are matched by name, whether free or in an `impl` block.
+//!
+//! Standard templates:
+//!
+//! - `no_oob_index(fn, slice)`: every `slice[i]` in `fn` is either reduced
+//!   `% slice.len()` or bounded by `i < slice.len()` (range ends by
+//!   `<=`) in a condition, early exit, `assert!` or `for` range that no
+//!   later write to the names involved invalidates. Indices that are not
+//!   plain arithmetic over names, or whose bound takes more than one
+//!   comparison to derive, are unknown rather than proven
+//! - `provenance_preserved(fn)`: `fn` never turns an integer into a
+//!   pointer, by cast, `transmute` or exposed-address APIs
+//! - `field_invariant(struct, invariant)`: the fields named in `invariant`
+//!   are private, and every inherent method writing them asserts it
+//! - `unsafe_documented(fn)`: every `unsafe` block in `fn` sits under a
+//!   `// SAFETY:` comment
+//! - `null_checked(fn, ptr)`: `fn` tests `ptr.is_null()` before it first
+//!   dereferences `ptr`
+//!
+//! ## Biblical Foundation
+//! "A false balance is abomination to the Lord: but a just weight is his delight" - Proverbs 11:1
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+use crate::{
+    FormalProperty, PropertyType, VerificationEngine, VerificationEngineInterface, VerificationError,
+    VerificationResult, VerificationStatus,
+};
+
+/// Annotation marker introducing a property
+pub const ANNOTATION: &str = "@property";
+
+/// Lines above an `unsafe` block searched for its `SAFETY:` comment
+const SAFETY_COMMENT_REACH: usize = 3;
+
+/// Checks one instance against a parsed file
+pub type TemplateCheck = fn(&syn::File, &str, &TemplateArgs) -> Result<CheckOutcome, String>;
+
+/// Result of checking one instance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// Every relevant expression follows the template's pattern
+    Holds,
+    /// Lines that certainly break the property
+    Violated(Vec<String>),
+    /// Lines the check could neither show safe nor show broken
+    Undecided(Vec<String>),
+}
+
+impl CheckOutcome {
+    /// Outcome of a check whose every finding is a violation
+    pub fn of_violations(violations: Vec<String>) -> Self {
+        if violations.is_empty() {
+            Self::Holds
+        } else {
+            Self::Violated(violations)
+        }
+    }
+}
+
+/// Parameterized property
+#[derive(Clone)]
+pub struct PropertyTemplate {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Required parameters, all of which appear in the annotation
+    pub params: &'static [&'static str],
+    /// Formula with `{param}` placeholders
+    pub formula: &'static str,
+    pub check: TemplateCheck,
+}
+
+impl fmt::Debug for PropertyTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PropertyTemplate")
+            .field("name", &self.name)
+            .field("params", &self.params)
+            .finish()
+    }
+}
+
+/// Arguments of a template instance
+pub type TemplateArgs = BTreeMap<String, String>;
+
+/// Template applied to concrete arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateInstance {
+    pub template: String,
+    pub args: TemplateArgs,
+}
+
+impl TemplateInstance {
+    /// Parse `name(key = value, ...)`; values may be identifiers or string literals
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let open = text.find('(').ok_or_else(|| format!("expected `name(...)`, found `{}`", text))?;
+        let template = text[..open].trim();
+        if template.is_empty() || !template.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid template name `{}`", template));
+        }
+        let body = text[open + 1..]
+            .strip_suffix(')')
+            .ok_or_else(|| format!("unterminated arguments in `{}`", text))?;
+
+        let mut args = TemplateArgs::new();
+        for arg in split_args(body)? {
+            let (key, value) = arg.split_once('=').ok_or_else(|| format!("expected `key = value`, found `{}`", arg))?;
+            let key = key.trim();
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted.strip_suffix('"').ok_or_else(|| format!("unterminated string in `{}`", arg))?,
+                None => value,
+            };
+            if key.is_empty() || value.is_empty() {
+                return Err(format!("empty key or value in `{}`", arg));
+            }
+            if args.insert(key.to_string(), value.to_string()).is_some() {
+                return Err(format!("duplicate argument `{}`", key));
+            }
+        }
+
+        Ok(Self { template: template.to_string(), args })
+    }
+}
+
+/// Canonical form, used as the property name
+impl fmt::Display for TemplateInstance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.template)?;
+        for (i, (key, value)) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                write!(f, "{} = {}", key, value)?;
+            } else {
+                write!(f, "{} = \"{}\"", key, value)?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
+/// Split on commas outside string literals
+fn split_args(body: &str) -> Result<Vec<&str>, String> {
+    let mut args = Vec::new();
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ',' if !in_string => {
+                args.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if in_string {
+        return Err("unterminated string".to_string());
+    }
+    args.push(&body[start..]);
+    Ok(args.into_iter().filter(|arg| !arg.trim().is_empty()).collect())
+}
+
+/// Templates available to annotations
+#[derive(Debug, Clone, Default)]
+pub struct TemplateLibrary {
+    templates: HashMap<&'static str, PropertyTemplate>,
+}
+
+impl TemplateLibrary {
+    /// Library with the standard memory-safety templates
+    pub fn standard() -> Self {
+        let mut library = Self::default();
+        for template in standard_templates() {
+            library.register(template);
+        }
+        library
+    }
+
+    /// Add or replace a template
+    pub fn register(&mut self, template: PropertyTemplate) {
+        self.templates.insert(template.name, template);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PropertyTemplate> {
+        self.templates.get(name)
+    }
+
+    /// Template names, sorted
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.templates.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Property for an annotation body such as `null_checked(fn = f, ptr = p)`
+    ///
+    /// Returns `None` if the text does not name a template in the library.
+    pub fn instantiate(&self, text: &str) -> Option<Result<FormalProperty, String>> {
+        let instance = TemplateInstance::parse(text).ok()?;
+        let template = self.get(&instance.template)?;
+        Some(self.property(template, &instance))
+    }
+
+    fn property(&self, template: &PropertyTemplate, instance: &TemplateInstance) -> Result<FormalProperty, String> {
+        let mut formula = template.formula.to_string();
+        for param in template.params {
+            let value = instance
+                .args
+                .get(*param)
+                .ok_or_else(|| format!("{} requires `{}`", template.name, param))?;
+            formula = formula.replace(&format!("{{{}}}", param), value);
+        }
+        if let Some(extra) = instance.args.keys().find(|key| !template.params.contains(&key.as_str())) {
+            return Err(format!("{} takes no `{}` argument", template.name, extra));
+        }
+
+        Ok(FormalProperty {
+            name: instance.to_string(),
+            description: template.description.to_string(),
+            formula,
+            property_type: PropertyType::MemorySafety,
+            critical: true,
+        })
+    }
+
+    /// Outcome of checking the instance named by `property` in `code`
+    pub fn check(&self, property: &str, code: &str) -> Result<CheckOutcome, VerificationError> {
+        let instance = TemplateInstance::parse(property).map_err(VerificationError::PropertyParsing)?;
+        let template = self
+            .get(&instance.template)
+            .ok_or_else(|| VerificationError::PropertyParsing(format!("unknown template `{}`", instance.template)))?;
+        let file = syn::parse_file(code).map_err(|e| VerificationError::PropertyParsing(e.to_string()))?;
+        (template.check)(&file, code, &instance.args).map_err(VerificationError::PropertyParsing)
+    }
+}
+
+fn standard_templates() -> Vec<PropertyTemplate> {
+    vec![
+        PropertyTemplate {
+            name: "no_oob_index",
+            description: "Indexing into the slice never goes out of bounds",
+            params: &["fn", "slice"],
+            formula: "forall i. index({fn}, {slice}, i) implies i < len({slice})",
+            check: check_no_oob_index,
+        },
+        PropertyTemplate {
+            name: "provenance_preserved",
+            description: "Pointers keep the provenance of the allocation they were derived from",
+            params: &["fn"],
+            formula: "forall p. derived({fn}, p) implies not from_integer(p)",
+            check: check_provenance_preserved,
+        },
+        PropertyTemplate {
+            name: "field_invariant",
+            description: "The invariant over the struct's fields holds after every method",
+            params: &["struct", "invariant"],
+            formula: "forall s: {struct}. after_method(s) implies ({invariant})",
+            check: check_field_invariant,
+        },
+        PropertyTemplate {
+            name: "unsafe_documented",
+            description: "Every unsafe block states why it is sound",
+            params: &["fn"],
+            formula: "forall b. unsafe_block({fn}, b) implies safety_comment(b)",
+            check: check_unsafe_documented,
+        },
+        PropertyTemplate {
+            name: "null_checked",
+            description: "The pointer is tested for null before it is dereferenced",
+            params: &["fn", "ptr"],
+            formula: "forall d. deref({fn}, {ptr}, d) implies after(d, is_null({ptr}))",
+            check: check_null_checked,
+        },
+    ]
+}
+
+/// Token text without whitespace, for comparing expressions
+fn normalize<T: ToTokens>(node: &T) -> String {
+    node.to_token_stream().to_string().split_whitespace().collect()
+}
+
+/// Token text for messages
+fn source<T: ToTokens>(node: &T) -> String {
+    node.to_token_stream().to_string()
+}
+
+fn line_of<T: Spanned>(node: &T) -> usize {
+    node.span().start().line
+}
+
+/// Whether `expr` is `name` or `self.name`
+fn names(expr: &str, name: &str) -> bool {
+    expr == name || expr.strip_prefix("self.") == Some(name)
+}
+
+/// Whether `expr` is `name`, `self.name` or a method chain on either
+fn rooted_at(expr: &str, name: &str) -> bool {
+    names(expr, name) || [name.to_string(), format!("self.{}", name)].iter().any(|root| expr.starts_with(&format!("{}.", root)))
+}
+
+/// Whether `text` contains `needle` as a whole expression rather than inside a longer identifier
+fn mentions(text: &str, needle: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(needle).any(|(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + needle.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// Bodies of every function named `name`
+fn functions<'a>(file: &'a syn::File, name: &str) -> Result<Vec<&'a syn::Block>, String> {
+    struct Finder<'a, 'n> {
+        name: &'n str,
+        found: Vec<&'a syn::Block>,
+    }
+
+    impl<'a> Visit<'a> for Finder<'a, '_> {
+        fn visit_item_fn(&mut self, item: &'a syn::ItemFn) {
+            if item.sig.ident == self.name {
+                self.found.push(&item.block);
+            }
+            visit::visit_item_fn(self, item);
+        }
+
+        fn visit_impl_item_fn(&mut self, item: &'a syn::ImplItemFn) {
+            if item.sig.ident == self.name {
+                self.found.push(&item.block);
+            }
+            visit::visit_impl_item_fn(self, item);
+        }
+    }
+
+    let mut finder = Finder { name, found: Vec::new() };
+    finder.visit_file(file);
+    if finder.found.is_empty() {
+        return Err(format!("function `{}` not found", name));
+    }
+    Ok(finder.found)
+}
+
+fn arg<'a>(args: &'a TemplateArgs, name: &str) -> Result<&'a str, String> {
+    args.get(name).map(String::as_str).ok_or_else(|| format!("missing argument `{}`", name))
+}
+
+/// Macros that never return
+fn is_panic_macro(mac: &syn::Macro) -> bool {
+    ["panic", "unreachable", "todo", "unimplemented"].iter().any(|name| mac.path.is_ident(name))
+}
+
+/// Whether a block always leaves the block enclosing it, by returning,
+/// breaking, continuing or panicking
+fn diverges(block: &syn::Block) -> bool {
+    match block.stmts.last() {
+        Some(syn::Stmt::Expr(syn::Expr::Return(_) | syn::Expr::Break(_) | syn::Expr::Continue(_), _)) => true,
+        Some(syn::Stmt::Expr(syn::Expr::Macro(m), _)) => is_panic_macro(&m.mac),
+        Some(syn::Stmt::Macro(m)) => is_panic_macro(&m.mac),
+        _ => false,
+    }
+}
+
+/// Comma-separated arguments of a macro, if they parse as expressions
+fn macro_args(mac: &syn::Macro) -> Option<Vec<syn::Expr>> {
+    use syn::punctuated::Punctuated;
+    mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+        .ok()
+        .map(|args| args.into_iter().collect())
+}
+
+/// Methods that never mutate their receiver
+const READ_ONLY_METHODS: &[&str] = &[
+    "len", "is_empty", "get", "iter", "first", "last", "contains", "clone", "to_vec", "as_slice", "as_ptr",
+    "starts_with", "ends_with", "windows", "chunks", "capacity",
+];
+
+/// Comparison `lhs < rhs`, or `lhs <= rhs` when not strict, over normalized text
+struct Bound {
+    lhs: String,
+    strict: bool,
+    rhs: String,
+    /// Cleared once a name the bound mentions is written
+    live: bool,
+}
+
+/// Bounds that follow from `cond` evaluating to `holds`
+///
+/// Only conjunctions of comparisons (or, when `holds` is false,
+/// disjunctions) and `is_empty()` tests yield bounds; anything else
+/// yields none rather than a guess.
+fn bounds_of(cond: &syn::Expr, holds: bool) -> Vec<Bound> {
+    use syn::BinOp;
+
+    let bound = |lhs: String, strict: bool, rhs: String| vec![Bound { lhs, strict, rhs, live: true }];
+    match cond {
+        syn::Expr::Paren(e) => bounds_of(&e.expr, holds),
+        syn::Expr::Unary(e) if matches!(e.op, syn::UnOp::Not(_)) => bounds_of(&e.expr, !holds),
+        syn::Expr::MethodCall(e) if e.method == "is_empty" && e.args.is_empty() && !holds => {
+            bound("0".to_string(), true, format!("{}.len()", normalize(&e.receiver)))
+        }
+        syn::Expr::Binary(e) => {
+            let (left, right) = (normalize(&e.left), normalize(&e.right));
+            match (&e.op, holds) {
+                (BinOp::And(_), true) | (BinOp::Or(_), false) => {
+                    let mut bounds = bounds_of(&e.left, holds);
+                    bounds.extend(bounds_of(&e.right, holds));
+                    bounds
+                }
+                (BinOp::Lt(_), true) | (BinOp::Ge(_), false) => bound(left, true, right),
+                (BinOp::Le(_), true) | (BinOp::Gt(_), false) => bound(left, false, right),
+                (BinOp::Gt(_), true) | (BinOp::Le(_), false) => bound(right, true, left),
+                (BinOp::Ge(_), true) | (BinOp::Lt(_), false) => bound(right, false, left),
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `expr` evaluates to the same value wherever its names are
+/// unchanged: paths, literals, arithmetic, fields and `len()`
+fn pure(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Path(_) | syn::Expr::Lit(_) => true,
+        syn::Expr::Paren(e) => pure(&e.expr),
+        syn::Expr::Field(e) => pure(&e.base),
+        syn::Expr::Cast(e) => pure(&e.expr),
+        syn::Expr::Unary(e) => pure(&e.expr),
+        syn::Expr::Binary(e) => pure(&e.left) && pure(&e.right),
+        syn::Expr::MethodCall(e) => e.method == "len" && e.args.is_empty() && pure(&e.receiver),
+        _ => false,
+    }
+}
+
+/// Place an assignment or mutable borrow writes, without indexing
+fn place(expr: &syn::Expr) -> String {
+    match expr {
+        syn::Expr::Index(e) => place(&e.expr),
+        syn::Expr::Paren(e) => place(&e.expr),
+        syn::Expr::Unary(e) if matches!(e.op, syn::UnOp::Deref(_)) => place(&e.expr),
+        other => normalize(other),
+    }
+}
+
+/// Names a piece of code may rebind or mutate
+#[derive(Default)]
+struct Writes(Vec<String>);
+
+impl Writes {
+    fn of(visit: impl FnOnce(&mut Self)) -> Vec<String> {
+        let mut writes = Self::default();
+        visit(&mut writes);
+        writes.0
+    }
+}
+
+impl<'ast> Visit<'ast> for Writes {
+    fn visit_pat_ident(&mut self, p: &'ast syn::PatIdent) {
+        self.0.push(p.ident.to_string());
+        visit::visit_pat_ident(self, p);
+    }
+
+    fn visit_expr_assign(&mut self, e: &'ast syn::ExprAssign) {
+        self.0.push(place(&e.left));
+        visit::visit_expr_assign(self, e);
+    }
+
+    fn visit_expr_binary(&mut self, e: &'ast syn::ExprBinary) {
+        // Compound assignments print as `+=`, `<<=` and so on
+        let op = e.op.to_token_stream().to_string();
+        if op.ends_with('=') && !["==", "!=", "<=", ">="].contains(&op.as_str()) {
+            self.0.push(place(&e.left));
+        }
+        visit::visit_expr_binary(self, e);
+    }
+
+    fn visit_expr_reference(&mut self, e: &'ast syn::ExprReference) {
+        if e.mutability.is_some() {
+            self.0.push(place(&e.expr));
+        }
+        visit::visit_expr_reference(self, e);
+    }
+
+    fn visit_expr_method_call(&mut self, e: &'ast syn::ExprMethodCall) {
+        if !READ_ONLY_METHODS.iter().any(|m| e.method == m) {
+            self.0.push(place(&e.receiver));
+        }
+        visit::visit_expr_method_call(self, e);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        for arg in macro_args(mac).unwrap_or_default() {
+            self.visit_expr(&arg);
+        }
+    }
+}
+
+fn check_no_oob_index(file: &syn::File, _code: &str, args: &TemplateArgs) -> Result<CheckOutcome, String> {
+    struct Guards<'s> {
+        slice: &'s str,
+        bounds: Vec<Bound>,
+        violations: Vec<String>,
+        unbounded: Vec<String>,
+    }
+
+    impl Guards<'_> {
+        fn holds(&self, lhs: &str, strict: bool, rhs: &str) -> bool {
+            self.bounds.iter().any(|b| b.live && b.lhs == lhs && b.rhs == rhs && (b.strict || !strict))
+        }
+
+        /// Invalidate the bounds mentioning anything in `writes`
+        fn kill(&mut self, writes: &[String]) {
+            for bound in &mut self.bounds {
+                if writes.iter().any(|w| mentions(&bound.lhs, w) || mentions(&bound.rhs, w)) {
+                    bound.live = false;
+                }
+            }
+        }
+
+        fn assume(&mut self, bounds: Vec<Bound>) {
+            self.bounds.extend(bounds);
+        }
+
+        /// Bounds the rest of the block may assume after `stmt`
+        fn after(stmt: &syn::Stmt) -> Vec<Bound> {
+            match stmt {
+                syn::Stmt::Expr(syn::Expr::If(e), _) if e.else_branch.is_none() && diverges(&e.then_branch) => {
+                    bounds_of(&e.cond, false)
+                }
+                syn::Stmt::Macro(syn::StmtMacro { mac, .. }) | syn::Stmt::Expr(syn::Expr::Macro(syn::ExprMacro { mac, .. }), _)
+                    if mac.path.is_ident("assert") =>
+                {
+                    macro_args(mac).and_then(|args| args.into_iter().next()).map(|cond| bounds_of(&cond, true)).unwrap_or_default()
+                }
+                _ => Vec::new(),
+            }
+        }
+
+        /// Part of `index` not shown to lie within `len`, and whether it is certainly outside
+        fn unbounded<'e>(&self, index: &'e syn::Expr, len: &str) -> Option<(&'e syn::Expr, bool)> {
+            match index {
+                syn::Expr::Range(range) => {
+                    let inclusive = matches!(range.limits, syn::RangeLimits::Closed(_));
+                    let end_text = range.end.as_deref().map(normalize).unwrap_or_else(|| len.to_string());
+                    if let Some(end) = range.end.as_deref() {
+                        if inclusive && end_text == len {
+                            return Some((end, true));
+                        }
+                        if !(end_text == len || (pure(end) && self.holds(&end_text, inclusive, len))) {
+                            return Some((end, false));
+                        }
+                    }
+                    match range.start.as_deref() {
+                        Some(start) if normalize(start) != "0" && !(pure(start) && self.holds(&normalize(start), false, &end_text)) => {
+                            Some((start, false))
+                        }
+                        _ => None,
+                    }
+                }
+                // A remainder is below its divisor; an empty slice panics on the division instead
+                syn::Expr::Binary(e) if matches!(e.op, syn::BinOp::Rem(_)) && normalize(&e.right) == len => None,
+                index if normalize(index) == len => Some((index, true)),
+                index if pure(index) && self.holds(&normalize(index), true, len) => None,
+                index => Some((index, false)),
+            }
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Guards<'_> {
+        fn visit_block(&mut self, block: &'ast syn::Block) {
+            let depth = self.bounds.len();
+            for stmt in &block.stmts {
+                self.visit_stmt(stmt);
+                self.kill(&Writes::of(|w| w.visit_stmt(stmt)));
+                // Early exits and assertions bound the rest of the block
+                self.assume(Self::after(stmt));
+            }
+            self.bounds.truncate(depth);
+        }
+
+        fn visit_expr_if(&mut self, e: &'ast syn::ExprIf) {
+            self.visit_expr(&e.cond);
+            let depth = self.bounds.len();
+            self.assume(bounds_of(&e.cond, true));
+            self.visit_block(&e.then_branch);
+            self.bounds.truncate(depth);
+            if let Some((_, otherwise)) = &e.else_branch {
+                self.assume(bounds_of(&e.cond, false));
+                self.visit_expr(otherwise);
+                self.bounds.truncate(depth);
+            }
+        }
+
+        // Loop bodies run again after their own writes, so those writes
+        // invalidate outer bounds before the first iteration
+
+        fn visit_expr_while(&mut self, e: &'ast syn::ExprWhile) {
+            self.kill(&Writes::of(|w| w.visit_expr_while(e)));
+            self.visit_expr(&e.cond);
+            let depth = self.bounds.len();
+            self.assume(bounds_of(&e.cond, true));
+            self.visit_block(&e.body);
+            self.bounds.truncate(depth);
+        }
+
+        fn visit_expr_loop(&mut self, e: &'ast syn::ExprLoop) {
+            self.kill(&Writes::of(|w| w.visit_block(&e.body)));
+            self.visit_block(&e.body);
+        }
+
+        fn visit_expr_for_loop(&mut self, e: &'ast syn::ExprForLoop) {
+            self.visit_expr(&e.expr);
+            self.kill(&Writes::of(|w| w.visit_block(&e.body)));
+            let depth = self.bounds.len();
+            if let (syn::Pat::Ident(pat), syn::Expr::Range(range)) = (e.pat.as_ref(), e.expr.as_ref()) {
+                if let Some(end) = range.end.as_deref().filter(|end| pure(end)) {
+                    let strict = matches!(range.limits, syn::RangeLimits::HalfOpen(_));
+                    self.assume(vec![Bound { lhs: pat.ident.to_string(), strict, rhs: normalize(end), live: true }]);
+                }
+            }
+            self.visit_block(&e.body);
+            self.bounds.truncate(depth);
+        }
+
+        // Closures and nested functions may run anywhere: assume nothing
+
+        fn visit_expr_closure(&mut self, e: &'ast syn::ExprClosure) {
+            let outer = std::mem::take(&mut self.bounds);
+            visit::visit_expr_closure(self, e);
+            self.bounds = outer;
+        }
+
+        fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+            let outer = std::mem::take(&mut self.bounds);
+            visit::visit_item_fn(self, item);
+            self.bounds = outer;
+        }
+
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            match macro_args(mac) {
+                Some(args) => args.iter().for_each(|arg| self.visit_expr(arg)),
+                None if mentions(&normalize(&mac.tokens), self.slice) => self.unbounded.push(format!(
+                    "line {}: `{}!` mentions {} but its arguments could not be analyzed",
+                    line_of(mac),
+                    source(&mac.path),
+                    self.slice
+                )),
+                None => {}
+            }
+        }
+
+        fn visit_expr_index(&mut self, e: &'ast syn::ExprIndex) {
+            let base = normalize(&e.expr);
+            if names(&base, self.slice) {
+                let len = format!("{}.len()", base);
+                if let Some((part, certain)) = self.unbounded(&e.index, &len) {
+                    let finding = format!("line {}: `{}` indexes {} with `{}`", line_of(e), source(e), self.slice, source(part));
+                    if certain {
+                        self.violations.push(format!("{}, which is never below its length", finding));
+                    } else {
+                        self.unbounded.push(format!("{}, which no guard bounds by its length", finding));
+                    }
+                }
+            }
+            visit::visit_expr_index(self, e);
+        }
+    }
+
+    let slice = arg(args, "slice")?;
+    let mut guards = Guards { slice, bounds: Vec::new(), violations: Vec::new(), unbounded: Vec::new() };
+    for body in functions(file, arg(args, "fn")?)? {
+        guards.visit_block(body);
+    }
+    Ok(if guards.violations.is_empty() && !guards.unbounded.is_empty() {
+        CheckOutcome::Undecided(guards.unbounded)
+    } else {
+        CheckOutcome::of_violations(guards.violations)
+    })
+}
+
+const INTEGER_TYPES: &[&str] = &["usize", "isize", "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128"];
+
+const EXPOSED_ADDRESS_FUNCTIONS: &[&str] = &[
+    "transmute",
+    "transmute_copy",
+    "from_exposed_addr",
+    "from_exposed_addr_mut",
+    "with_exposed_provenance",
+    "with_exposed_provenance_mut",
+];
+
+fn check_provenance_preserved(file: &syn::File, _code: &str, args: &TemplateArgs) -> Result<CheckOutcome, String> {
+    /// Whether an expression computes an integer from a cast or literal
+    fn integer_valued(expr: &syn::Expr) -> bool {
+        struct IntegerCast(bool);
+
+        impl<'ast> Visit<'ast> for IntegerCast {
+            fn visit_expr_cast(&mut self, e: &'ast syn::ExprCast) {
+                let target = normalize(&e.ty);
+                self.0 |= INTEGER_TYPES.contains(&target.as_str());
+                visit::visit_expr_cast(self, e);
+            }
+        }
+
+        if let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(_), .. }) = expr {
+            return true;
+        }
+        let mut finder = IntegerCast(false);
+        finder.visit_expr(expr);
+        finder.0
+    }
+
+    struct Provenance(Vec<String>);
+
+    impl<'ast> Visit<'ast> for Provenance {
+        fn visit_expr_cast(&mut self, e: &'ast syn::ExprCast) {
+            if matches!(e.ty.as_ref(), syn::Type::Ptr(_)) && integer_valued(&e.expr) {
+                self.0.push(format!("line {}: `{}` casts an integer to a pointer", line_of(e), source(e)));
+            }
+            visit::visit_expr_cast(self, e);
+        }
+
+        fn visit_expr_call(&mut self, e: &'ast syn::ExprCall) {
+            if let syn::Expr::Path(path) = e.func.as_ref() {
+                if let Some(last) = path.path.segments.last() {
+                    if EXPOSED_ADDRESS_FUNCTIONS.iter().any(|name| last.ident == name) {
+                        self.0.push(format!("line {}: `{}` drops pointer provenance", line_of(e), last.ident));
+                    }
+                }
+            }
+            visit::visit_expr_call(self, e);
+        }
+    }
+
+    let mut provenance = Provenance(Vec::new());
+    for body in functions(file, arg(args, "fn")?)? {
+        provenance.visit_block(body);
+    }
+    Ok(CheckOutcome::of_violations(provenance.0))
+}
+
+/// Invariant text with each field `f` written as `self.f`, without whitespace
+fn qualify_fields(invariant: TokenStream, fields: &[String]) -> String {
+    let mut out = String::new();
+    let mut after_dot = false;
+    for token in invariant {
+        match &token {
+            TokenTree::Ident(ident) if !after_dot && fields.iter().any(|f| ident == f) => {
+                out.push_str(&format!("self.{}", ident));
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    proc_macro2::Delimiter::Parenthesis => ("(", ")"),
+                    proc_macro2::Delimiter::Bracket => ("[", "]"),
+                    proc_macro2::Delimiter::Brace => ("{", "}"),
+                    proc_macro2::Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                out.push_str(&qualify_fields(group.stream(), fields));
+                out.push_str(close);
+            }
+            other => out.push_str(&normalize(other)),
+        }
+        after_dot = matches!(&token, TokenTree::Punct(p) if p.as_char() == '.');
+    }
+    out
+}
+
+fn check_field_invariant(file: &syn::File, _code: &str, args: &TemplateArgs) -> Result<CheckOutcome, String> {
+    /// Invariant fields written and invariant assertions made by one method
+    struct Writes<'f> {
+        fields: &'f [String],
+        invariant: &'f str,
+        written: Vec<String>,
+        asserted: bool,
+    }
+
+    impl Writes<'_> {
+        fn target(&mut self, expr: &syn::Expr) {
+            if let syn::Expr::Field(field) = expr {
+                if normalize(&field.base) == "self" {
+                    let name = normalize(&field.member);
+                    if self.fields.contains(&name) && !self.written.contains(&name) {
+                        self.written.push(name);
+                    }
+                }
+            }
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Writes<'_> {
+        fn visit_expr_assign(&mut self, e: &'ast syn::ExprAssign) {
+            self.target(&e.left);
+            visit::visit_expr_assign(self, e);
+        }
+
+        fn visit_expr_binary(&mut self, e: &'ast syn::ExprBinary) {
+            let compound = matches!(
+                e.op,
+                syn::BinOp::AddAssign(_)
+                    | syn::BinOp::SubAssign(_)
+                    | syn::BinOp::MulAssign(_)
+                    | syn::BinOp::DivAssign(_)
+                    | syn::BinOp::RemAssign(_)
+                    | syn::BinOp::BitAndAssign(_)
+                    | syn::BinOp::BitOrAssign(_)
+                    | syn::BinOp::BitXorAssign(_)
+                    | syn::BinOp::ShlAssign(_)
+                    | syn::BinOp::ShrAssign(_)
+            );
+            if compound {
+                self.target(&e.left);
+            }
+            visit::visit_expr_binary(self, e);
+        }
+
+        fn visit_expr_reference(&mut self, e: &'ast syn::ExprReference) {
+            if e.mutability.is_some() {
+                self.target(&e.expr);
+            }
+            visit::visit_expr_reference(self, e);
+        }
+
+        fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+            if (mac.path.is_ident("assert") || mac.path.is_ident("debug_assert"))
+                && normalize(&mac.tokens).contains(self.invariant)
+            {
+                self.asserted = true;
+            }
+        }
+    }
+
+    let name = arg(args, "struct")?;
+    let invariant: TokenStream = arg(args, "invariant")?
+        .parse()
+        .map_err(|e| format!("invalid invariant: {}", e))?;
+
+    let item = file
+        .items
+        .iter()
+        .find_map(|item| match item {
+            syn::Item::Struct(item) if item.ident == name => Some(item),
+            _ => None,
+        })
+        .ok_or_else(|| format!("struct `{}` not found", name))?;
+    let mentioned = normalize(&invariant);
+    let fields: Vec<&syn::Field> = item
+        .fields
+        .iter()
+        .filter(|field| field.ident.as_ref().is_some_and(|ident| {
+            invariant.clone().into_iter().any(|token| matches!(&token, TokenTree::Ident(i) if i == ident))
+        }))
+        .collect();
+    if fields.is_empty() {
+        return Err(format!("invariant `{}` names no field of {}", mentioned, name));
+    }
+
+    let mut violations = Vec::new();
+    for field in &fields {
+        if !matches!(field.vis, syn::Visibility::Inherited) {
+            violations.push(format!(
+                "line {}: field `{}` is visible outside {}",
+                line_of(&field.vis),
+                normalize(&field.ident),
+                name
+            ));
+        }
+    }
+
+    let names: Vec<String> = fields.iter().map(|field| normalize(&field.ident)).collect();
+    let qualified = qualify_fields(invariant, &names);
+    for item in &file.items {
+        let syn::Item::Impl(block) = item else { continue };
+        let implements = match block.self_ty.as_ref() {
+            syn::Type::Path(path) => path.path.segments.last().is_some_and(|s| s.ident == name),
+            _ => false,
+        };
+        if block.trait_.is_some() || !implements {
+            continue;
+        }
+        for method in &block.items {
+            let syn::ImplItem::Fn(method) = method else { continue };
+            let mut writes = Writes { fields: &names, invariant: &qualified, written: Vec::new(), asserted: false };
+            writes.visit_block(&method.block);
+            if !writes.written.is_empty() && !writes.asserted {
+                violations.push(format!(
+                    "line {}: `{}` writes {} without asserting `{}`",
+                    line_of(&method.sig.ident),
+                    method.sig.ident,
+                    writes.written.join(", "),
+                    qualified
+                ));
+            }
+        }
+    }
+    Ok(CheckOutcome::of_violations(violations))
+}
+
+fn check_unsafe_documented(file: &syn::File, code: &str, args: &TemplateArgs) -> Result<CheckOutcome, String> {
+    struct Unsafe(Vec<usize>);
+
+    impl<'ast> Visit<'ast> for Unsafe {
+        fn visit_expr_unsafe(&mut self, e: &'ast syn::ExprUnsafe) {
+            self.0.push(e.unsafe_token.span.start().line);
+            visit::visit_expr_unsafe(self, e);
+        }
+    }
+
+    let mut blocks = Unsafe(Vec::new());
+    for body in functions(file, arg(args, "fn")?)? {
+        blocks.visit_block(body);
+    }
+
+    let lines: Vec<&str> = code.lines().collect();
+    let documented = |line: usize| {
+        if line == 0 {
+            // Spans carry no locations; nothing to match comments against
+            return false;
+        }
+        // Same line, then the comment lines directly above
+        let same = lines.get(line - 1).is_some_and(|text| text.contains("SAFETY:"));
+        same || lines[..line - 1]
+            .iter()
+            .rev()
+            .take(SAFETY_COMMENT_REACH)
+            .take_while(|text| text.trim_start().starts_with("//"))
+            .any(|text| text.contains("SAFETY:"))
+    };
+    Ok(CheckOutcome::of_violations(
+        blocks
+            .0
+            .into_iter()
+            .filter(|line| !documented(*line))
+            .map(|line| format!("line {}: unsafe block without a `// SAFETY:` comment", line))
+            .collect(),
+    ))
+}
+
+const DEREF_METHODS: &[&str] = &[
+    "read",
+    "write",
+    "read_volatile",
+    "write_volatile",
+    "read_unaligned",
+    "write_unaligned",
+    "as_ref",
+    "as_mut",
+    "offset_from",
+];
+
+const DEREF_FUNCTIONS: &[&str] = &["from_raw_parts", "from_raw_parts_mut", "copy", "copy_nonoverlapping", "read", "write"];
+
+fn check_null_checked(file: &syn::File, _code: &str, args: &TemplateArgs) -> Result<CheckOutcome, String> {
+    struct Uses<'p> {
+        ptr: &'p str,
+        checks: Vec<usize>,
+        derefs: Vec<(usize, String)>,
+    }
+
+    impl<'ast> Visit<'ast> for Uses<'_> {
+        fn visit_expr_unary(&mut self, e: &'ast syn::ExprUnary) {
+            if matches!(e.op, syn::UnOp::Deref(_)) && rooted_at(&normalize(&e.expr), self.ptr) {
+                self.derefs.push((line_of(e), source(e)));
+            }
+            visit::visit_expr_unary(self, e);
+        }
+
+        fn visit_expr_method_call(&mut self, e: &'ast syn::ExprMethodCall) {
+            if normalize(&e.receiver) == self.ptr {
+                if e.method == "is_null" {
+                    self.checks.push(line_of(e));
+                } else if DEREF_METHODS.iter().any(|m| e.method == m) {
+                    self.derefs.push((line_of(e), source(e)));
+                }
+            }
+            visit::visit_expr_method_call(self, e);
+        }
+
+        fn visit_expr_call(&mut self, e: &'ast syn::ExprCall) {
+            if let syn::Expr::Path(path) = e.func.as_ref() {
+                let name = path.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+                let uses_ptr = e.args.iter().any(|a| rooted_at(&normalize(a), self.ptr));
+                if name == "new" && normalize(path).ends_with("NonNull::new") && uses_ptr {
+                    self.checks.push(line_of(e));
+                } else if DEREF_FUNCTIONS.contains(&name.as_str()) && uses_ptr {
+                    self.derefs.push((line_of(e), source(e)));
+                }
+            }
+            visit::visit_expr_call(self, e);
+        }
+    }
+
+    let ptr = arg(args, "ptr")?;
+    let mut violations = Vec::new();
+    for body in functions(file, arg(args, "fn")?)? {
+        let mut uses = Uses { ptr, checks: Vec::new(), derefs: Vec::new() };
+        uses.visit_block(body);
+        let first_check = uses.checks.iter().min().copied();
+        for (line, deref) in uses.derefs {
+            if first_check.is_none_or(|check| check >= line) {
+                violations.push(format!("line {}: `{}` dereferences {} before a null check", line, deref, ptr));
+            }
+        }
+    }
+    Ok(CheckOutcome::of_violations(violations))
+}
+
+/// Verification engine checking template instances syntactically
+pub struct TemplateEngine {
+    library: Arc<TemplateLibrary>,
+}
+
+impl TemplateEngine {
+    pub fn new(library: Arc<TemplateLibrary>) -> Self {
+        Self { library }
+    }
+}
+
+#[async_trait]
+impl VerificationEngineInterface for TemplateEngine {
+    async fn verify_property(
+        &self,
+        property: &FormalProperty,
+        code: &str,
+    ) -> Result<VerificationResult, VerificationError> {
+        let start_time = Instant::now();
+
+        let (status, counterexample) = match self.library.check(&property.name, code) {
+            Ok(CheckOutcome::Holds) => (VerificationStatus::Proven, None),
+            Ok(CheckOutcome::Violated(violations)) => (VerificationStatus::Disproven, Some(violations.join("; "))),
+            Ok(CheckOutcome::Undecided(lines)) => (VerificationStatus::Unknown, Some(lines.join("; "))),
+            Err(e) => (VerificationStatus::Error(e.to_string()), None),
+        };
+
+        Ok(VerificationResult {
+            engine: VerificationEngine::PropertyTemplates,
+            property: property.name.clone(),
+            status,
+            proof: None,
+            counterexample,
+            verification_time: start_time.elapsed(),
+        })
+    }
+
+    fn engine_type(&self) -> VerificationEngine {
+        VerificationEngine::PropertyTemplates
+    }
+
+    fn capabilities(&self) -> Vec<PropertyType> {
+        vec![PropertyType::MemorySafety]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instances_parse_and_instantiate() {
+        let instance = TemplateInstance::parse(r#"field_invariant( struct = RingBuffer, invariant = "len <= capacity" )"#).unwrap();
+        assert_eq!(instance.to_string(), r#"field_invariant(invariant = "len <= capacity", struct = RingBuffer)"#);
+        assert_eq!(TemplateInstance::parse(&instance.to_string()).unwrap(), instance);
+
+        let library = TemplateLibrary::standard();
+        assert_eq!(library.names().len(), 5);
+        let property = library.instantiate("no_oob_index(fn = get, slice = data)").unwrap().unwrap();
+        assert_eq!(property.formula, "forall i. index(get, data, i) implies i < len(data)");
+        assert!(matches!(property.property_type, PropertyType::MemorySafety));
+
+        assert!(library.instantiate("no_oob_index(fn = get)").unwrap().is_err());
+        assert!(library.instantiate("safety: get always returns").is_none());
+        assert!(library.instantiate("unknown_template(fn = get)").is_none());
+    }
+
+    #[test]
+    fn test_checks_report_offending_lines() {
+        let code = r#"
+fn read(data: &[u8], i: usize) -> u8 {
+    if i >= data.len() {
+        return 0;
+    }
+    let first = data[i];
+    first + data[i + 1]
+}
+"#;
+        let library = TemplateLibrary::standard();
+        let CheckOutcome::Undecided(lines) = library.check("no_oob_index(fn = read, slice = data)", code).unwrap() else {
+            panic!("`data[i + 1]` is not bounded");
+        };
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("line 7:"));
+
+        assert!(library.check("no_oob_index(fn = missing, slice = data)", code).is_err());
+    }
+
+    #[test]
+    fn test_index_bounds_are_not_guessed() {
+        let library = TemplateLibrary::standard();
+        let outcome = |body: &str| {
+            let code = format!("fn read(data: &mut Vec<u8>, mut i: usize, j: usize) -> u8 {{\n{}\n}}", body);
+            library.check("no_oob_index(fn = read, slice = data)", &code).unwrap()
+        };
+        let undecided = |body: &str| matches!(outcome(body), CheckOutcome::Undecided(_));
+
+        // Bounds that do hold
+        assert_eq!(outcome("if i >= data.len() { return 0; }\ndata[i]"), CheckOutcome::Holds);
+        assert_eq!(outcome("assert!(j < data.len() && i < j);\ndata[j]"), CheckOutcome::Holds);
+        assert_eq!(outcome("while i < data.len() { let _ = data[i]; i += 1; }\n0"), CheckOutcome::Holds);
+        assert_eq!(outcome("for k in 0..data.len() { let _ = data[k]; }\n0"), CheckOutcome::Holds);
+        assert_eq!(outcome("if data.is_empty() { return 0; }\ndata[0]"), CheckOutcome::Holds);
+        assert_eq!(outcome("if j > data.len() { return 0; }\ndata[..j].len() as u8 + data[i % data.len()]"), CheckOutcome::Holds);
+
+        // Guards that only mention the index and the length
+        assert!(undecided("if i < data.len() { return 0; }\ndata[i]"));
+        assert!(undecided("if i > data.len() { return 0; }\ndata[i]"));
+        assert!(undecided("if i >= data.len() && j > 0 { return 0; }\ndata[i]"));
+        assert!(undecided("debug_assert!(i < data.len());\ndata[i]"));
+        // Bounds invalidated by a later write
+        assert!(undecided("if i >= data.len() { return 0; }\ni += 1;\ndata[i]"));
+        assert!(undecided("if i >= data.len() { return 0; }\ndata.truncate(1);\ndata[i]"));
+        assert!(undecided("if i >= data.len() { return 0; }\nloop { let _ = data[i]; i += 1; }"));
+        // Indexing inside macros is still checked
+        assert!(undecided("println!(\"{}\", data[i]);\n0"));
+
+        assert!(matches!(outcome("data[data.len()]"), CheckOutcome::Violated(_)));
+    }
+
+    /// Statuses of the template results from auditing an example crate
+    async fn audit_example(name: &str) -> Vec<(String, VerificationStatus)> {
+        use crate::expand::MacroExpansionConfig;
+        use crate::findings::AnalyzerPrecision;
+        use crate::{AuditScope, CoAuditAI, CoAuditConfig};
+        use std::time::Duration;
+
+        let config = CoAuditConfig {
+            audit_scope: AuditScope {
+                include_patterns: vec!["*.rs".to_string()],
+                exclude_patterns: vec![],
+                verify_formal_properties: true,
+                check_biblical_compliance: true,
+                analyze_security_properties: true,
+                detect_moral_violations: true,
+                max_verification_time: Duration::from_secs(10),
+                engines: vec![VerificationEngine::PropertyTemplates],
+            },
+            moral_threshold: 0.7,
+            technical_threshold: 0.7,
+            security_threshold: 0.7,
+            biblical_threshold: 0.7,
+            parallel_verification: true,
+            max_concurrent_audits: 4,
+            result_cache_size: 100,
+            verification_keys: HashMap::new(),
+            strict_biblical_mode: false,
+            macro_expansion: MacroExpansionConfig::default(),
+            analyzer_precision: AnalyzerPrecision::default(),
+            trends: Default::default(),
+        };
+        let mut co_audit = CoAuditAI::new(config).await.unwrap();
+
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/property_templates")
+            .join(name)
+            .join("src/lib.rs");
+        let result = co_audit.audit_file(&path).await.unwrap();
+        result
+            .verification_results
+            .into_iter()
+            .filter(|r| matches!(r.engine, VerificationEngine::PropertyTemplates))
+            .map(|r| (r.property, r.status))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_example_crates_end_to_end() {
+        let sound = audit_example("sound_buffer").await;
+        assert_eq!(sound.len(), 5);
+        for (property, status) in &sound {
+            assert!(matches!(status, VerificationStatus::Proven), "{}: {:?}", property, status);
+        }
+
+        let unsound = audit_example("unsound_buffer").await;
+        assert_eq!(unsound.len(), 5);
+        for (property, status) in &unsound {
+            // An unguarded `data[head + i]` may still be in bounds
+            if property.starts_with("no_oob_index") {
+                assert!(matches!(status, VerificationStatus::Unknown), "{}: {:?}", property, status);
+            } else {
+                assert!(matches!(status, VerificationStatus::Disproven), "{}: {:?}", property, status);
+            }
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Continuous Re-Audit
+//!
+//! Watch mode for Co-Audit AI. Files under the watched paths are audited once
+//! at start-up and again whenever they change. Bursts of file system events
+//! are debounced so that an editor save or a checkout triggers one re-audit per
+//! file. The latest result for every file is kept in a live
+//! `WorkspaceAuditReport`, and every change of classification is broadcast as
+//! a `ClassificationChange`.
+//!
+//! ## Biblical Foundation
+//! "Watch and pray, that ye enter not into temptation" - Matthew 26:41
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::health::{ComponentHealth, HealthCheck, HealthStatus};
+use crate::{AuditClassification, AuditResult, AuditScope, CoAuditAI, CoAuditError};
+
+/// Quiet period after the last file event before re-auditing
+pub const DEFAULT_WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Buffered classification changes per subscriber
+const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
+/// Latest audit outcome for one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileAuditSummary {
+    pub classification: AuditClassification,
+    pub moral_score: f64,
+    pub security_score: f64,
+    pub audited_at: SystemTime,
+}
+
+impl From<&AuditResult> for FileAuditSummary {
+    fn from(result: &AuditResult) -> Self {
+        Self {
+            classification: result.classification.clone(),
+            moral_score: result.moral_score,
+            security_score: result.security_score,
+            audited_at: result.audit_timestamp,
+        }
+    }
+}
+
+/// Live audit state of the watched workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceAuditReport {
+    pub files: BTreeMap<PathBuf, FileAuditSummary>,
+    /// Files whose last audit failed
+    pub errors: BTreeMap<PathBuf, String>,
+    pub updated_at: Option<SystemTime>,
+}
+
+impl WorkspaceAuditReport {
+    /// Record a fresh audit result, returning the change if the classification moved
+    pub fn record(&mut self, result: &AuditResult) -> Option<ClassificationChange> {
+        let summary = FileAuditSummary::from(result);
+        let previous = self.files.insert(result.file_path.clone(), summary)
+            .map(|old| old.classification);
+        self.errors.remove(&result.file_path);
+        self.updated_at = Some(SystemTime::now());
+
+        match previous {
+            Some(previous) if previous == result.classification => None,
+            previous => Some(ClassificationChange::new(
+                result.file_path.clone(),
+                previous,
+                Some(result.classification.clone()),
+            )),
+        }
+    }
+
+    /// Record a failed audit
+    pub fn record_error(&mut self, path: &Path, error: String) {
+        self.errors.insert(path.to_path_buf(), error);
+        self.updated_at = Some(SystemTime::now());
+    }
+
+    /// Forget a deleted file
+    pub fn remove(&mut self, path: &Path) -> Option<ClassificationChange> {
+        self.errors.remove(path);
+        let previous = self.files.remove(path)?;
+        self.updated_at = Some(SystemTime::now());
+        Some(ClassificationChange::new(path.to_path_buf(), Some(previous.classification), None))
+    }
+
+    /// Number of files per classification
+    pub fn classification_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for summary in self.files.values() {
+            *counts.entry(format!("{:?}", summary.classification)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Files currently classified Problematic or worse
+    pub fn flagged_files(&self) -> Vec<&Path> {
+        self.files.iter()
+            .filter(|(_, summary)| severity_rank(&summary.classification) >= severity_rank(&AuditClassification::Problematic))
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+}
+
+/// Classification transition of a watched file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationChange {
+    pub path: PathBuf,
+    /// `None` when the file was not audited before
+    pub previous: Option<AuditClassification>,
+    /// `None` when the file was removed
+    pub current: Option<AuditClassification>,
+    /// The file moved to a more severe classification
+    pub worsened: bool,
+}
+
+impl ClassificationChange {
+    fn new(path: PathBuf, previous: Option<AuditClassification>, current: Option<AuditClassification>) -> Self {
+        let worsened = match (&previous, &current) {
+            (Some(previous), Some(current)) => severity_rank(current) > severity_rank(previous),
+            _ => false,
+        };
+        Self { path, previous, current, worsened }
+    }
+}
+
+/// Ordering of classifications from best to worst
+fn severity_rank(classification: &AuditClassification) -> u8 {
+    match classification {
+        AuditClassification::Righteous => 0,
+        AuditClassification::Sound => 1,
+        AuditClassification::Concerning => 2,
+        AuditClassification::Problematic => 3,
+        AuditClassification::Wicked => 4,
+        AuditClassification::Corrupting => 5,
+    }
+}
+
+/// Simple glob match supporting `*` wildcards
+fn glob_match(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(remaining) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=remaining.len())
+                .filter(|&i| remaining.is_char_boundary(i))
+                .any(|i| glob_match(rest, &remaining[i..]))
+        }
+    }
+}
+
+/// Whether a path falls inside the audit scope
+pub(crate) fn in_scope(path: &Path, scope: &AuditScope) -> bool {
+    let text = path.to_string_lossy().replace('\\', "/");
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+    let excluded = scope.exclude_patterns.iter()
+        .any(|p| glob_match(p, &text) || glob_match(&format!("*/{}", p), &text));
+    let included = scope.include_patterns.is_empty()
+        || scope.include_patterns.iter().any(|p| glob_match(p, &file_name) || glob_match(p, &text));
+
+    included && !excluded
+}
+
+/// Running watch session
+pub struct AuditWatch {
+    report: Arc<RwLock<WorkspaceAuditReport>>,
+    changes: broadcast::Sender<ClassificationChange>,
+    paths: Vec<PathBuf>,
+    task: JoinHandle<()>,
+    _watcher: RecommendedWatcher,
+}
+
+impl AuditWatch {
+    /// Audit everything under `paths`, then keep re-auditing on change
+    pub fn start(
+        mut auditor: CoAuditAI,
+        paths: Vec<PathBuf>,
+        debounce: Duration,
+    ) -> Result<Self, CoAuditError> {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            match event {
+                Ok(event) => {
+                    for path in event.paths {
+                        let _ = event_tx.send(path);
+                    }
+                }
+                Err(e) => warn!("File watcher error: {}", e),
+            }
+        }).map_err(|e| CoAuditError::Watch(e.to_string()))?;
+
+        for path in &paths {
+            watcher.watch(path, RecursiveMode::Recursive)
+                .map_err(|e| CoAuditError::Watch(format!("{:?}: {}", path, e)))?;
+        }
+
+        let report = Arc::new(RwLock::new(WorkspaceAuditReport::default()));
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+
+        let task_report = report.clone();
+        let task_changes = changes.clone();
+        let roots = paths.clone();
+        let task = tokio::spawn(async move {
+            let initial: Vec<PathBuf> = roots.iter()
+                .flat_map(|root| walkdir::WalkDir::new(root).into_iter().filter_map(|e| e.ok()))
+                .filter(|entry| entry.file_type().is_file())
+                .map(|entry| entry.into_path())
+                .collect();
+            info!("Watch mode: initial audit of {} files", initial.len());
+            for path in initial {
+                reaudit(&mut auditor, &path, &task_report, &task_changes).await;
+            }
+
+            while let Some(first) = event_rx.recv().await {
+                // Collect further events until the file system goes quiet
+                let mut pending = HashSet::from([first]);
+                while let Ok(Some(path)) = tokio::time::timeout(debounce, event_rx.recv()).await {
+                    pending.insert(path);
+                }
+
+                debug!("Watch mode: re-auditing {} changed paths", pending.len());
+                for path in pending {
+                    reaudit(&mut auditor, &path, &task_report, &task_changes).await;
+                }
+            }
+        });
+
+        Ok(Self {
+            report,
+            changes,
+            paths,
+            task,
+            _watcher: watcher,
+        })
+    }
+
+    /// Snapshot of the live workspace report
+    pub fn report(&self) -> WorkspaceAuditReport {
+        self.report.read().map(|r| r.clone()).unwrap_or_default()
+    }
+
+    /// Subscribe to classification changes
+    pub fn subscribe(&self) -> broadcast::Receiver<ClassificationChange> {
+        self.changes.subscribe()
+    }
+
+    /// Stop watching
+    pub fn stop(self) {
+        self.task.abort();
+    }
+}
+
+impl HealthCheck for AuditWatch {
+    fn health(&self) -> ComponentHealth {
+        let report = self.report();
+        let flagged = report.flagged_files().len();
+
+        let status = if self.task.is_finished() {
+            HealthStatus::Unhealthy
+        } else if flagged > 0 || !report.errors.is_empty() {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+
+        let provenance = ark_provenance::current().unwrap_or_else(|e| {
+            warn!("Unreadable provenance manifest: {}", e);
+            None
+        });
+        let mut health = ComponentHealth::new("co_audit_watch", status)
+            .with_provenance(provenance.as_ref())
+            .with_detail("watched_paths", self.paths.len())
+            .with_detail("files", report.files.len())
+            .with_detail("flagged_files", flagged)
+            .with_detail("audit_errors", report.errors.len());
+        for (classification, count) in report.classification_counts() {
+            health = health.with_detail(&format!("classification.{}", classification), count);
+        }
+        health
+    }
+}
+
+async fn reaudit(
+    auditor: &mut CoAuditAI,
+    path: &Path,
+    report: &RwLock<WorkspaceAuditReport>,
+    changes: &broadcast::Sender<ClassificationChange>,
+) {
+    let change = if !path.exists() {
+        report.write().ok().and_then(|mut r| r.remove(path))
+    } else if !path.is_file() || !in_scope(path, &auditor.config.audit_scope) {
+        None
+    } else {
+        match auditor.audit_file(path).await {
+            Ok(result) => report.write().ok().and_then(|mut r| r.record(&result)),
+            Err(e) => {
+                warn!("Watch mode: audit of {:?} failed: {}", path, e);
+                if let Ok(mut r) = report.write() {
+                    r.record_error(path, e.to_string());
+                }
+                None
+            }
+        }
+    };
+
+    if let Some(change) = change {
+        if change.worsened {
+            warn!("Classification of {:?} worsened: {:?} -> {:?}", change.path, change.previous, change.current);
+        }
+        // No subscribers is not an error
+        let _ = changes.send(change);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, classification: AuditClassification) -> AuditResult {
+        AuditResult {
+            file_path: PathBuf::from(path),
+            language: Default::default(),
+            classification,
+            moral_score: 0.9,
+            technical_score: 0.9,
+            security_score: 0.9,
+            biblical_compliance: 0.9,
+            verification_results: Vec::new(),
+            moral_violations: Vec::new(),
+            security_issues: Vec::new(),
+            formal_properties: Vec::new(),
+            biblical_analysis: crate::BiblicalAnalysis {
+                primary_virtues: Vec::new(),
+                potential_sins: Vec::new(),
+                scriptural_alignment: 0.9,
+                divine_purpose_score: 0.9,
+                love_commandment_compliance: 0.9,
+                wisdom_demonstration: 0.9,
+                stewardship_quality: 0.9,
+                relevant_verses: Vec::new(),
+            },
+            recommendations: Vec::new(),
+            plugin_findings: Vec::new(),
+            plugin_failures: Vec::new(),
+            fixes: Vec::new(),
+            audit_timestamp: SystemTime::now(),
+            audit_duration: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_report_emits_transitions() {
+        let mut report = WorkspaceAuditReport::default();
+
+        let first = report.record(&result("src/a.rs", AuditClassification::Sound)).unwrap();
+        assert!(first.previous.is_none());
+        assert!(!first.worsened);
+
+        assert!(report.record(&result("src/a.rs", AuditClassification::Sound)).is_none());
+
+        let worse = report.record(&result("src/a.rs", AuditClassification::Problematic)).unwrap();
+        assert_eq!(worse.previous, Some(AuditClassification::Sound));
+        assert!(worse.worsened);
+        assert_eq!(report.flagged_files(), vec![Path::new("src/a.rs")]);
+
+        let removed = report.remove(Path::new("src/a.rs")).unwrap();
+        assert!(removed.current.is_none());
+        assert!(report.files.is_empty());
+    }
+
+    #[test]
+    fn test_scope_matching() {
+        let scope = AuditScope {
+            include_patterns: vec!["*.rs".to_string()],
+            exclude_patterns: vec!["target/*".to_string()],
+            verify_formal_properties: false,
+            check_biblical_compliance: false,
+            analyze_security_properties: false,
+            detect_moral_violations: false,
+            max_verification_time: Duration::from_secs(1),
+            engines: Vec::new(),
+        };
+
+        assert!(in_scope(Path::new("/ws/src/lib.rs"), &scope));
+        assert!(!in_scope(Path::new("/ws/src/notes.md"), &scope));
+        assert!(!in_scope(Path::new("/ws/target/debug/build.rs"), &scope));
+    }
+}
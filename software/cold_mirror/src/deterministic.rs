@@ -0,0 +1,328 @@
+//! Deterministic Mode - Reproducible Decisions
+//! "Jesus Christ the same yesterday, and to day, and for ever" - Hebrews 13:8
+//!
+//! With `DeterministicConfig::enabled`, every decision can be re-derived
+//! bit for bit from its record. Sampling draws from seeds derived from the
+//! configured seed, scoring iterates in a stable order, and each record pins
+//! the hashes of the model and of the rule pack - the scoring stages,
+//! calibration, action policy and taxonomy - that produced it, together with
+//! the subject's hysteresis levels beforehand. `reproduce` replays a record
+//! on a fresh policy and reports any field that came out differently.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::policy::{ActionLevel, ActionPolicy, ThresholdPolicy};
+use crate::risk_assessment::{RiskAssessment, RiskPipeline, StageContribution};
+use crate::{ColdMirrorConfig, ColdMirrorError, ColdMirrorResult, HarmCategory, HarmPredictor, PredictionInput};
+
+/// Deterministic mode configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeterministicConfig {
+    /// Pin decisions and fix every sampling seed
+    pub enabled: bool,
+    /// Root seed from which per-purpose seeds are derived
+    pub seed: u64,
+}
+
+impl DeterministicConfig {
+    /// Seed for the sampler named `purpose`, or `None` outside deterministic mode
+    pub fn seed_for(&self, purpose: &str) -> Option<u64> {
+        self.enabled.then(|| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&self.seed.to_le_bytes());
+            hasher.update(purpose.as_bytes());
+            let mut seed = [0u8; 8];
+            seed.copy_from_slice(&hasher.finalize().as_bytes()[..8]);
+            u64::from_le_bytes(seed)
+        })
+    }
+}
+
+/// Hashes and seed a decision was taken under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionPins {
+    /// BLAKE3 of the model file, or of the model version when there is no file
+    pub model_hash: String,
+    /// BLAKE3 of the scoring stages, calibration, action policy and taxonomy
+    pub rule_pack_hash: String,
+    /// Root seed of the run
+    pub seed: u64,
+}
+
+impl DecisionPins {
+    /// Pin the model reporting `model_version` and the rule pack of `config`
+    pub fn from_config(config: &ColdMirrorConfig, model_version: &str) -> ColdMirrorResult<Self> {
+        let model_path = Path::new(&config.model_config.model_path);
+        let model_hash = match std::fs::read(model_path) {
+            Ok(model) => blake3::hash(&model),
+            // Predictors without weights, like the lexical fallback, are fixed by their version
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => blake3::hash(model_version.as_bytes()),
+            Err(e) => {
+                return Err(ColdMirrorError::ModelLoadError(format!("{}: {}", model_path.display(), e)));
+            }
+        };
+
+        let mut rule_pack = blake3::Hasher::new();
+        rule_pack.update(&canonical_json(&config.risk_assessment)?);
+        rule_pack.update(&canonical_json(&config.model_config.postprocessing.calibration)?);
+        match &config.action_policy_path {
+            Some(path) => rule_pack.update(&read_file(path)?),
+            None => rule_pack.update(&canonical_json(&ThresholdPolicy::default())?),
+        };
+        if let Some(path) = &config.taxonomy_path {
+            rule_pack.update(&read_file(path)?);
+        }
+
+        Ok(Self {
+            model_hash: model_hash.to_hex().to_string(),
+            rule_pack_hash: rule_pack.finalize().to_hex().to_string(),
+            seed: config.deterministic.seed,
+        })
+    }
+}
+
+/// What was decided, in the form compared on reproduction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionOutcome {
+    /// Final harm level
+    pub harm_level: f32,
+    /// Final confidence
+    pub confidence: f32,
+    /// Final harm categories
+    pub categories: Vec<HarmCategory>,
+    /// Action level chosen by the policy stage
+    pub action: Option<ActionLevel>,
+    /// Category that drove the action
+    pub driver: Option<String>,
+    /// Version of the policy that decided
+    pub policy_version: Option<String>,
+    /// Contribution of every stage
+    pub contributions: Vec<StageContribution>,
+}
+
+impl DecisionOutcome {
+    fn from_assessment(assessment: &RiskAssessment) -> Self {
+        let score = &assessment.score;
+        Self {
+            harm_level: score.harm_level,
+            confidence: score.confidence,
+            categories: score.categories.clone(),
+            action: assessment.action_level(),
+            driver: score.action.as_ref().and_then(|action| action.category.clone()),
+            policy_version: score.action.as_ref().map(|action| action.policy_version.clone()),
+            contributions: assessment.contributions.clone(),
+        }
+    }
+
+    /// Fields that differ from `other`, comparing floats by their bits
+    fn differences(&self, other: &Self) -> Vec<String> {
+        let mut differences = Vec::new();
+        if self.harm_level.to_bits() != other.harm_level.to_bits() {
+            differences.push(format!("harm_level: {} != {}", self.harm_level, other.harm_level));
+        }
+        if self.confidence.to_bits() != other.confidence.to_bits() {
+            differences.push(format!("confidence: {} != {}", self.confidence, other.confidence));
+        }
+        if self.categories != other.categories {
+            differences.push("categories".to_string());
+        }
+        if self.action != other.action || self.driver != other.driver || self.policy_version != other.policy_version {
+            differences.push(format!("action: {:?} != {:?}", self.action, other.action));
+        }
+        let stages = self.contributions.len().max(other.contributions.len());
+        for index in 0..stages {
+            match (self.contributions.get(index), other.contributions.get(index)) {
+                (Some(a), Some(b))
+                    if a.stage == b.stage
+                        && a.before.to_bits() == b.before.to_bits()
+                        && a.after.to_bits() == b.after.to_bits()
+                        && a.explanation == b.explanation => {}
+                (a, b) => differences.push(format!(
+                    "stage {}: {} != {}",
+                    index,
+                    a.map(|c| c.stage.as_str()).unwrap_or("-"),
+                    b.map(|c| c.stage.as_str()).unwrap_or("-")
+                )),
+            }
+        }
+        differences
+    }
+}
+
+/// Everything needed to re-derive a decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    /// Scored input
+    pub input: PredictionInput,
+    /// Model and rule pack the decision was taken under
+    pub pins: DecisionPins,
+    /// Hysteresis levels of the subject before the decision
+    pub prior_levels: BTreeMap<String, ActionLevel>,
+    /// Decision taken
+    pub outcome: DecisionOutcome,
+}
+
+/// Result of replaying a record
+#[derive(Debug, Clone)]
+pub struct Reproduction {
+    /// Re-derived decision
+    pub outcome: DecisionOutcome,
+    /// Fields that differ from the record; empty when reproduced exactly
+    pub differences: Vec<String>,
+}
+
+impl Reproduction {
+    /// Whether the decision was reproduced bit for bit
+    pub fn matches(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Risk pipeline that records and replays pinned decisions
+pub struct DeterministicPipeline {
+    config: ColdMirrorConfig,
+    predictor: Arc<dyn HarmPredictor + Send + Sync>,
+    policy: Arc<ActionPolicy>,
+    pipeline: RiskPipeline,
+    pins: DecisionPins,
+}
+
+impl DeterministicPipeline {
+    /// Pipeline of `config`, which must have deterministic mode enabled
+    pub fn new(config: &ColdMirrorConfig, predictor: Arc<dyn HarmPredictor + Send + Sync>) -> ColdMirrorResult<Self> {
+        if !config.deterministic.enabled {
+            return Err(ColdMirrorError::ConfigurationError("Deterministic mode is not enabled".into()));
+        }
+        let model_version = predictor.get_performance_metrics()?.model_version;
+        let pins = DecisionPins::from_config(config, &model_version)?;
+        let policy = Arc::new(ActionPolicy::from_config(config)?);
+        let pipeline = RiskPipeline::from_config_with_policy(config, predictor.clone(), policy.clone())?;
+        Ok(Self { config: config.clone(), predictor, policy, pipeline, pins })
+    }
+
+    /// Pins recorded with every decision
+    pub fn pins(&self) -> &DecisionPins {
+        &self.pins
+    }
+
+    /// Score `input` and record the decision
+    pub fn decide(&self, input: &PredictionInput) -> ColdMirrorResult<DecisionRecord> {
+        let prior_levels = self.policy.levels(&input.event.event_id);
+        let assessment = self.pipeline.assess(input)?;
+        Ok(DecisionRecord {
+            input: input.clone(),
+            pins: self.pins.clone(),
+            prior_levels,
+            outcome: DecisionOutcome::from_assessment(&assessment),
+        })
+    }
+
+    /// Re-derive a recorded decision and compare it with the record
+    ///
+    /// Fails when the record was pinned to a different model or rule pack,
+    /// since the decision could then not be expected to match.
+    pub fn reproduce(&self, record: &DecisionRecord) -> ColdMirrorResult<Reproduction> {
+        if record.pins != self.pins {
+            return Err(ColdMirrorError::DataError(format!(
+                "Record pinned to model {} and rule pack {}, running model {} and rule pack {}",
+                record.pins.model_hash, record.pins.rule_pack_hash, self.pins.model_hash, self.pins.rule_pack_hash
+            )));
+        }
+
+        // A fresh policy holding only the subject's recorded levels
+        let policy = Arc::new(ActionPolicy::from_config(&self.config)?);
+        policy.restore_levels(&record.input.event.event_id, &record.prior_levels);
+        let pipeline = RiskPipeline::from_config_with_policy(&self.config, self.predictor.clone(), policy)?;
+        let outcome = DecisionOutcome::from_assessment(&pipeline.assess(&record.input)?);
+        let differences = record.outcome.differences(&outcome);
+        Ok(Reproduction { outcome, differences })
+    }
+}
+
+/// JSON with object keys sorted, independent of map iteration order
+fn canonical_json<T: Serialize>(value: &T) -> ColdMirrorResult<Vec<u8>> {
+    serde_json::to_value(value)
+        .and_then(|value| serde_json::to_vec(&value))
+        .map_err(|e| ColdMirrorError::DataError(format!("Failed to serialize for pinning: {}", e)))
+}
+
+fn read_file(path: &str) -> ColdMirrorResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical::LexicalPredictor;
+    use crate::utils;
+    use chrono::Utc;
+    use ethics_dsl::{Actor, ActorType, Content, ContentType, Context, EthicsEvent, UrgencyLevel};
+    use std::collections::HashMap;
+
+    fn input(event_id: &str, text: &str) -> PredictionInput {
+        let event = EthicsEvent {
+            event_id: event_id.to_string(),
+            actor: Actor { actor_type: ActorType::Human, tags: vec![], trust_level: 0.3, history: None },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: text.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+            timestamp: Utc::now(),
+        };
+        utils::create_prediction_input(event, None, None)
+    }
+
+    fn config() -> ColdMirrorConfig {
+        let mut config = ColdMirrorConfig::default();
+        config.model_config.model_path = "/nonexistent/lexical".to_string();
+        config.deterministic = DeterministicConfig { enabled: true, seed: 11 };
+        config
+    }
+
+    #[test]
+    fn test_recorded_decisions_reproduce() {
+        let pipeline = DeterministicPipeline::new(&config(), Arc::new(LexicalPredictor::new())).unwrap();
+        let first = pipeline.decide(&input("det-1", "plans to kill and murder")).unwrap();
+        // The second decision for the subject starts from the first one's hysteresis levels
+        let second = pipeline.decide(&input("det-1", "plans to shoot")).unwrap();
+        assert!(!second.prior_levels.is_empty());
+
+        // Records survive serialization and replay in any order on a fresh pipeline
+        let restored: DecisionRecord = serde_json::from_str(&serde_json::to_string(&second).unwrap()).unwrap();
+        let replay = DeterministicPipeline::new(&config(), Arc::new(LexicalPredictor::new())).unwrap();
+        assert!(replay.reproduce(&restored).unwrap().matches());
+        assert!(replay.reproduce(&first).unwrap().matches());
+
+        let mut tampered = first.clone();
+        tampered.outcome.harm_level += 0.01;
+        assert!(!replay.reproduce(&tampered).unwrap().matches());
+    }
+
+    #[test]
+    fn test_pins_follow_rule_pack_and_seed() {
+        let base = DecisionPins::from_config(&config(), "lexical-1").unwrap();
+        assert_eq!(base, DecisionPins::from_config(&config(), "lexical-1").unwrap());
+        assert_ne!(base.model_hash, DecisionPins::from_config(&config(), "lexical-2").unwrap().model_hash);
+
+        let mut retuned = config();
+        retuned.risk_assessment.stages.pop();
+        let retuned = DeterministicPipeline::new(&retuned, Arc::new(LexicalPredictor::new())).unwrap();
+        assert_ne!(retuned.pins().rule_pack_hash, base.rule_pack_hash);
+
+        let pipeline = DeterministicPipeline::new(&config(), Arc::new(LexicalPredictor::new())).unwrap();
+        let record = pipeline.decide(&input("det-2", "a scam")).unwrap();
+        assert!(retuned.reproduce(&record).is_err());
+
+        let seeds = config().deterministic;
+        assert_eq!(seeds.seed_for("shadow"), seeds.seed_for("shadow"));
+        assert_ne!(seeds.seed_for("shadow"), seeds.seed_for("ensemble"));
+        assert_eq!(DeterministicConfig::default().seed_for("shadow"), None);
+    }
+}
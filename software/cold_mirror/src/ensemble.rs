@@ -0,0 +1,600 @@
+//! Ensemble Predictor - Several Harm Predictors, One Prediction
+//! "In the multitude of counsellors there is safety" - Proverbs 11:14
+//!
+//! Wraps any number of `HarmPredictor` members (the neural model, the
+//! lexical fallback, a remote predictor) and combines their predictions.
+//! Each member has one worker thread, and members run concurrently, each
+//! under its own timeout; a member that fails or times out is left out of
+//! the aggregate but still named in the result. A member still working on
+//! an input it timed out on counts as timed out again rather than being
+//! given another thread, and the workers are joined when the ensemble is
+//! dropped. Aggregation is configurable:
+//!
+//! - `Max`: the most harmful member prediction wins
+//! - `WeightedMean`: harm and category scores are averaged by member weight
+//! - `VetoOnCritical`: weighted mean, unless any member reaches the critical
+//!   threshold, in which case that member's prediction stands
+//!
+//! Every member contributes an `ensemble:<member>` risk factor with its harm
+//! level, and its own risk factors are kept with the member name prefixed,
+//! so each part of the result can be traced to the predictor behind it.
+//! The recommended action is selected from the aggregated categories by
+//! the action policy's thresholds, so it always matches the combined
+//! verdict rather than whichever member happened to lead.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::policy::{category_name, category_score, with_category_score, ActionPolicy, ThresholdPolicy};
+use crate::{
+    ColdMirrorError, ColdMirrorResult, HarmCategory, HarmPredictor, HarmPrediction, ModelMetrics, OutcomeData,
+    PredictionInput, RiskFactor,
+};
+
+/// How member predictions are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Aggregation {
+    /// Most harmful member prediction
+    Max,
+    /// Mean weighted by member weight
+    WeightedMean,
+    /// Weighted mean, overridden by any member at the critical threshold
+    VetoOnCritical,
+}
+
+/// Ensemble settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnsembleConfig {
+    /// Aggregation of member predictions
+    pub aggregation: Aggregation,
+    /// Harm level at which a member vetoes under `VetoOnCritical`
+    pub critical_threshold: f32,
+    /// Members that must answer for a prediction to be made
+    pub min_members: usize,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            aggregation: Aggregation::VetoOnCritical,
+            critical_threshold: 0.9,
+            min_members: 1,
+        }
+    }
+}
+
+/// Shareable member predictor
+type SharedPredictor = Arc<RwLock<Box<dyn HarmPredictor + Send + Sync>>>;
+
+/// Member answer
+type Answer = ColdMirrorResult<HarmPrediction>;
+
+/// Input handed to a member's worker, with where to send its answer
+type Job = (PredictionInput, mpsc::Sender<Answer>);
+
+struct Member {
+    name: String,
+    weight: f32,
+    timeout: Duration,
+    predictor: SharedPredictor,
+    worker: Worker,
+}
+
+/// The one thread running a member's predictions
+struct Worker {
+    jobs: Option<mpsc::Sender<Job>>,
+    busy: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn spawn(name: &str, predictor: SharedPredictor) -> ColdMirrorResult<Self> {
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let busy = Arc::new(AtomicBool::new(false));
+        let idle = busy.clone();
+        let handle = std::thread::Builder::new()
+            .name(format!("ensemble-{}", name))
+            .spawn(move || {
+                for (input, reply) in queue {
+                    let answer = catch_unwind(AssertUnwindSafe(|| {
+                        let predictor = predictor.read().unwrap_or_else(|e| e.into_inner());
+                        predictor.predict_harm(&input)
+                    }))
+                    .unwrap_or_else(|_| Err(ColdMirrorError::InferenceError("predictor panicked".to_string())));
+                    idle.store(false, Ordering::Release);
+                    let _ = reply.send(answer);
+                }
+            })
+            .map_err(|e| ColdMirrorError::ResourceError(format!("Member {}: cannot start worker: {}", name, e)))?;
+
+        Ok(Self { jobs: Some(jobs), busy, handle: Some(handle) })
+    }
+
+    /// Hand the worker an input, unless it is still busy with an earlier one
+    fn submit(&self, input: &PredictionInput) -> Option<mpsc::Receiver<Answer>> {
+        if self.busy.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        let (reply, answer) = mpsc::channel();
+        let jobs = self.jobs.as_ref()?;
+        if jobs.send((input.clone(), reply)).is_err() {
+            return None;
+        }
+        Some(answer)
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        // Closing the queue ends the worker once its current input is done
+        self.jobs.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Harm predictor combining several member predictors
+pub struct EnsemblePredictor {
+    config: EnsembleConfig,
+    members: Vec<Member>,
+    policy: Arc<ActionPolicy>,
+    predictions: AtomicU64,
+}
+
+impl EnsemblePredictor {
+    /// Create an ensemble without members, selecting actions by the
+    /// default threshold policy
+    pub fn new(config: EnsembleConfig) -> Self {
+        Self {
+            config,
+            members: Vec::new(),
+            policy: Arc::new(ActionPolicy::new(ThresholdPolicy::default()).expect("default policy is valid")),
+            predictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Select recommended actions by this policy
+    pub fn with_action_policy(mut self, policy: Arc<ActionPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Add a member with its aggregation weight and timeout
+    pub fn add_member(
+        &mut self,
+        name: &str,
+        predictor: Box<dyn HarmPredictor + Send + Sync>,
+        weight: f32,
+        timeout: Duration,
+    ) -> ColdMirrorResult<()> {
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(ColdMirrorError::ConfigurationError(format!("Member {}: weight must be positive", name)));
+        }
+        if self.members.iter().any(|member| member.name == name) {
+            return Err(ColdMirrorError::ConfigurationError(format!("Member {} already added", name)));
+        }
+
+        let predictor: SharedPredictor = Arc::new(RwLock::new(predictor));
+        let worker = Worker::spawn(name, predictor.clone())?;
+        self.members.push(Member {
+            name: name.to_string(),
+            weight,
+            timeout,
+            predictor,
+            worker,
+        });
+        Ok(())
+    }
+
+    /// Names of the members, in the order they were added
+    pub fn members(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|member| member.name.as_str())
+    }
+
+    /// Run every member concurrently, waiting for each up to its timeout
+    ///
+    /// A member that times out finishes its input on its worker; the late
+    /// answer is discarded, and until then the member is not given another.
+    fn run_members(&self, input: &PredictionInput) -> Vec<Answer> {
+        let started = Instant::now();
+        let pending: Vec<_> = self.members.iter().map(|member| member.worker.submit(input)).collect();
+
+        self.members
+            .iter()
+            .zip(pending)
+            .map(|(member, receiver)| {
+                let Some(receiver) = receiver else {
+                    log::debug!("Ensemble member {} is still busy with an earlier input", member.name);
+                    return Err(ColdMirrorError::TimeoutError);
+                };
+                let wait = member.timeout.saturating_sub(started.elapsed());
+                match receiver.recv_timeout(wait) {
+                    Ok(answer) => answer,
+                    Err(mpsc::RecvTimeoutError::Timeout) => Err(ColdMirrorError::TimeoutError),
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        Err(ColdMirrorError::InferenceError("predictor panicked".to_string()))
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Combine member answers into one prediction
+    fn aggregate(&self, answers: Vec<Answer>) -> ColdMirrorResult<HarmPrediction> {
+        let mut risk_factors = Vec::new();
+        let mut answered: Vec<(&Member, HarmPrediction)> = Vec::new();
+        let mut timed_out = 0;
+        for (member, answer) in self.members.iter().zip(answers) {
+            match answer {
+                Ok(prediction) => {
+                    risk_factors.push(RiskFactor {
+                        name: format!("ensemble:{}", member.name),
+                        weight: prediction.harm_level,
+                        description: format!(
+                            "Member {} predicted harm {:.2} at confidence {:.2}",
+                            member.name, prediction.harm_level, prediction.confidence
+                        ),
+                        evidence: vec![prediction.model_version.clone()],
+                    });
+                    risk_factors.extend(prediction.risk_factors.iter().map(|factor| RiskFactor {
+                        name: format!("{}:{}", member.name, factor.name),
+                        ..factor.clone()
+                    }));
+                    answered.push((member, prediction));
+                }
+                Err(e) => {
+                    log::warn!("Ensemble member {} did not answer: {}", member.name, e);
+                    if matches!(e, ColdMirrorError::TimeoutError) {
+                        timed_out += 1;
+                    }
+                    risk_factors.push(RiskFactor {
+                        name: format!("ensemble:{}", member.name),
+                        weight: 0.0,
+                        description: format!("Member {} did not answer", member.name),
+                        evidence: vec![e.to_string()],
+                    });
+                }
+            }
+        }
+
+        if answered.len() < self.config.min_members.max(1) {
+            // Report timeouts as such so callers can take their fast path
+            if timed_out > 0 && answered.is_empty() {
+                return Err(ColdMirrorError::TimeoutError);
+            }
+            return Err(ColdMirrorError::InferenceError(format!(
+                "Only {} of {} ensemble members answered",
+                answered.len(),
+                self.members.len()
+            )));
+        }
+
+        // Member whose prediction leads: the most harmful, or a vetoing one
+        let (leader, leading) = answered
+            .iter()
+            .max_by(|(_, a), (_, b)| a.harm_level.total_cmp(&b.harm_level))
+            .expect("at least one member answered");
+        let vetoed = self.config.aggregation == Aggregation::VetoOnCritical
+            && leading.harm_level >= self.config.critical_threshold;
+
+        let (harm_level, confidence, harm_categories) = match self.config.aggregation {
+            Aggregation::Max => (leading.harm_level, leading.confidence, leading.harm_categories.clone()),
+            Aggregation::VetoOnCritical if vetoed => {
+                log::info!("Ensemble member {} vetoed at harm {:.2}", leader.name, leading.harm_level);
+                (leading.harm_level, leading.confidence, leading.harm_categories.clone())
+            }
+            Aggregation::WeightedMean | Aggregation::VetoOnCritical => weighted_mean(&answered),
+        };
+
+        let selection = self.policy.classify(&harm_categories);
+        Ok(HarmPrediction {
+            harm_level,
+            confidence,
+            time_horizon: answered.iter().map(|(_, p)| p.time_horizon).fold(f32::INFINITY, f32::min),
+            harm_categories,
+            risk_factors,
+            recommended_action: selection.action,
+            timestamp: Utc::now(),
+            model_version: format!(
+                "ensemble({})",
+                answered.iter().map(|(member, _)| member.name.as_str()).collect::<Vec<_>>().join(",")
+            ),
+            policy_version: Some(selection.policy_version),
+        })
+    }
+}
+
+/// Weighted means of harm, confidence and per-category scores
+///
+/// A member that does not report a category counts as scoring 0 for it.
+fn weighted_mean(answered: &[(&Member, HarmPrediction)]) -> (f32, f32, Vec<HarmCategory>) {
+    let total: f32 = answered.iter().map(|(member, _)| member.weight).sum();
+    let mean = |value: &dyn Fn(&HarmPrediction) -> f32| {
+        answered.iter().map(|(member, prediction)| member.weight * value(prediction)).sum::<f32>() / total
+    };
+
+    // Per category: weighted score sum and the highest-scoring instance
+    let mut categories: BTreeMap<String, (f32, HarmCategory)> = BTreeMap::new();
+    for (member, prediction) in answered {
+        for category in &prediction.harm_categories {
+            let score = category_score(category);
+            let entry = categories
+                .entry(category_name(category).to_string())
+                .or_insert_with(|| (0.0, category.clone()));
+            entry.0 += member.weight * score;
+            if score > category_score(&entry.1) {
+                entry.1 = category.clone();
+            }
+        }
+    }
+
+    (
+        mean(&|prediction| prediction.harm_level),
+        mean(&|prediction| prediction.confidence),
+        categories
+            .into_values()
+            .map(|(weighted, category)| with_category_score(&category, weighted / total))
+            .collect(),
+    )
+}
+
+impl HarmPredictor for EnsemblePredictor {
+    fn predict_harm(&self, input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+        if self.members.is_empty() {
+            return Err(ColdMirrorError::ConfigurationError("Ensemble has no members".into()));
+        }
+        self.predictions.fetch_add(1, Ordering::Relaxed);
+        self.aggregate(self.run_members(input))
+    }
+
+    fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        inputs.iter().map(|input| self.predict_harm(input)).collect()
+    }
+
+    fn update_with_outcome(&mut self, outcome: &OutcomeData) -> ColdMirrorResult<()> {
+        // Update every member, reporting the first failure
+        let mut first_error = None;
+        for member in &self.members {
+            let mut predictor = member.predictor.write().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = predictor.update_with_outcome(outcome) {
+                log::warn!("Ensemble member {} failed to learn from outcome: {}", member.name, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+        let mut accuracy = 0.0;
+        let mut weight = 0.0;
+        let mut slowest: f32 = 0.0;
+        for member in &self.members {
+            let predictor = member.predictor.read().unwrap_or_else(|e| e.into_inner());
+            if let Ok(metrics) = predictor.get_performance_metrics() {
+                accuracy += member.weight * metrics.accuracy;
+                weight += member.weight;
+                // Members run concurrently, so the slowest sets the pace
+                slowest = slowest.max(metrics.avg_inference_time_ms);
+            }
+        }
+
+        Ok(ModelMetrics {
+            accuracy: if weight > 0.0 { accuracy / weight } else { 0.0 },
+            precision_by_category: HashMap::new(),
+            recall_by_category: HashMap::new(),
+            avg_inference_time_ms: slowest,
+            total_predictions: self.predictions.load(Ordering::Relaxed),
+            model_version: format!("ensemble({})", self.members().collect::<Vec<_>>().join(",")),
+            last_updated: Utc::now(),
+            provenance: ModelMetrics::installed_provenance(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MonitoringLevel, RecommendedAction};
+    use std::sync::atomic::AtomicUsize;
+
+    /// Member predicting fixed physical harm, optionally after a delay
+    struct Fixed {
+        harm: f32,
+        delay: Duration,
+    }
+
+    impl HarmPredictor for Fixed {
+        fn predict_harm(&self, _input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+            std::thread::sleep(self.delay);
+            Ok(HarmPrediction {
+                harm_level: self.harm,
+                confidence: 0.8,
+                time_horizon: 24.0,
+                harm_categories: vec![HarmCategory::PhysicalHarm {
+                    harm_type: "violence".to_string(),
+                    victim_count: None,
+                    likelihood: self.harm,
+                }],
+                risk_factors: vec![RiskFactor {
+                    name: "weapon".to_string(),
+                    weight: self.harm,
+                    description: String::new(),
+                    evidence: vec![],
+                }],
+                recommended_action: RecommendedAction::AllowWithMonitoring {
+                    monitoring_level: MonitoringLevel::Basic,
+                    review_interval: 24.0,
+                },
+                timestamp: Utc::now(),
+                model_version: format!("fixed-{}", self.harm),
+                policy_version: None,
+            })
+        }
+
+        fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+            inputs.iter().map(|input| self.predict_harm(input)).collect()
+        }
+
+        fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+            Ok(())
+        }
+
+        fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+            Err(ColdMirrorError::InferenceError("not tracked".into()))
+        }
+    }
+
+    fn ensemble(aggregation: Aggregation, members: &[(&str, f32, f32, u64)]) -> EnsemblePredictor {
+        let mut ensemble = EnsemblePredictor::new(EnsembleConfig { aggregation, ..EnsembleConfig::default() });
+        for (name, harm, weight, delay_ms) in members {
+            let member = Fixed { harm: *harm, delay: Duration::from_millis(*delay_ms) };
+            ensemble.add_member(name, Box::new(member), *weight, Duration::from_millis(200)).unwrap();
+        }
+        ensemble
+    }
+
+    fn input() -> PredictionInput {
+        let event = ethics_dsl::EthicsEvent {
+            event_id: "ensemble".to_string(),
+            actor: ethics_dsl::Actor {
+                actor_type: ethics_dsl::ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: None,
+            context: ethics_dsl::Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: ethics_dsl::UrgencyLevel::Normal,
+            },
+            timestamp: Utc::now(),
+        };
+        crate::utils::create_prediction_input(event, None, None)
+    }
+
+    #[test]
+    fn test_max_and_weighted_mean_aggregation() {
+        let members = [("neural", 0.8, 3.0, 0), ("lexical", 0.4, 1.0, 0)];
+
+        let max = ensemble(Aggregation::Max, &members).predict_harm(&input()).unwrap();
+        assert_eq!(max.harm_level, 0.8);
+
+        let mean = ensemble(Aggregation::WeightedMean, &members).predict_harm(&input()).unwrap();
+        assert!((mean.harm_level - 0.7).abs() < 1e-6);
+        assert!((category_score(&mean.harm_categories[0]) - 0.7).abs() < 1e-6);
+        assert_eq!(mean.model_version, "ensemble(neural,lexical)");
+
+        // Every member is attributed, and its own factors keep its name
+        let names: Vec<&str> = mean.risk_factors.iter().map(|factor| factor.name.as_str()).collect();
+        assert_eq!(names, vec!["ensemble:neural", "neural:weapon", "ensemble:lexical", "lexical:weapon"]);
+    }
+
+    #[test]
+    fn test_critical_member_vetoes_mean() {
+        let members = [("neural", 0.2, 5.0, 0), ("remote", 0.95, 1.0, 0)];
+        let prediction = ensemble(Aggregation::VetoOnCritical, &members).predict_harm(&input()).unwrap();
+        assert_eq!(prediction.harm_level, 0.95);
+        assert_eq!(category_score(&prediction.harm_categories[0]), 0.95);
+
+        let calm = [("neural", 0.2, 5.0, 0), ("remote", 0.8, 1.0, 0)];
+        let prediction = ensemble(Aggregation::VetoOnCritical, &calm).predict_harm(&input()).unwrap();
+        assert!((prediction.harm_level - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_action_follows_the_combined_verdict() {
+        // Every member recommends allowing; the combined scores decide
+        let vetoed = [("neural", 0.2, 5.0, 0), ("remote", 0.95, 1.0, 0)];
+        let prediction = ensemble(Aggregation::VetoOnCritical, &vetoed).predict_harm(&input()).unwrap();
+        assert!(matches!(prediction.recommended_action, RecommendedAction::Purge { .. }));
+        assert_eq!(prediction.policy_version.as_deref(), Some("default-1"));
+
+        let calm = [("neural", 0.2, 5.0, 0), ("remote", 0.8, 1.0, 0)];
+        let prediction = ensemble(Aggregation::VetoOnCritical, &calm).predict_harm(&input()).unwrap();
+        assert!(matches!(
+            prediction.recommended_action,
+            RecommendedAction::AllowWithMonitoring { monitoring_level: MonitoringLevel::Enhanced, .. }
+        ));
+
+        // A leader recommending a block is outvoted by the mean
+        let prediction = ensemble(Aggregation::WeightedMean, &[("neural", 0.1, 9.0, 0), ("remote", 0.85, 1.0, 0)])
+            .predict_harm(&input())
+            .unwrap();
+        assert!(matches!(prediction.recommended_action, RecommendedAction::AllowWithMonitoring { .. }));
+    }
+
+    /// Predictor counting the inputs it was given
+    struct Counted {
+        inner: Fixed,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl HarmPredictor for Counted {
+        fn predict_harm(&self, input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.inner.predict_harm(input)
+        }
+
+        fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+            inputs.iter().map(|input| self.predict_harm(input)).collect()
+        }
+
+        fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+            Ok(())
+        }
+
+        fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+            self.inner.get_performance_metrics()
+        }
+    }
+
+    #[test]
+    fn test_stalled_member_is_not_given_more_threads() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let member = Counted { inner: Fixed { harm: 0.9, delay: Duration::from_millis(600) }, calls: calls.clone() };
+        let mut stalled = EnsemblePredictor::new(EnsembleConfig::default());
+        stalled.add_member("neural", Box::new(member), 1.0, Duration::from_millis(50)).unwrap();
+
+        // The first input times out; the rest find the member still busy
+        for _ in 0..5 {
+            assert!(matches!(stalled.predict_harm(&input()), Err(ColdMirrorError::TimeoutError)));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Once the worker is free again the member answers
+        std::thread::sleep(Duration::from_millis(700));
+        let prediction = stalled.predict_harm(&input());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(matches!(prediction, Err(ColdMirrorError::TimeoutError)));
+
+        // Dropping joins the worker after its current input
+        let started = Instant::now();
+        drop(stalled);
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_member_timeout_attributed() {
+        let members = [("neural", 0.9, 1.0, 1000), ("lexical", 0.4, 1.0, 0)];
+        let prediction = ensemble(Aggregation::Max, &members).predict_harm(&input()).unwrap();
+        assert_eq!(prediction.harm_level, 0.4);
+        let neural = prediction.risk_factors.iter().find(|factor| factor.name == "ensemble:neural").unwrap();
+        assert_eq!(neural.weight, 0.0);
+        assert!(neural.evidence[0].starts_with("Timeout"));
+
+        let stalled = ensemble(Aggregation::Max, &[("neural", 0.9, 1.0, 1000)]);
+        assert!(matches!(stalled.predict_harm(&input()), Err(ColdMirrorError::TimeoutError)));
+    }
+}
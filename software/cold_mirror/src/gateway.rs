@@ -0,0 +1,358 @@
+//! Ingestion Gateway - Admission Control Under Surge
+//! "Strait is the gate, and narrow is the way, which leadeth unto life" - Matthew 7:14
+//!
+//! Events enter the pipeline's queue through an `IngestionGateway`, which
+//! bounds the queue per urgency class - an event whose class is full is
+//! shed, and the producer may retry later - and sheds load in steps as the
+//! total queue grows:
+//!
+//! - at `degrade_at`, dequeued events are scored with the lexical
+//!   predictor instead of the model;
+//! - at `shed_at`, new low-urgency events are shed;
+//! - at `hard_limit`, a queued event of lower urgency is displaced to make
+//!   room; with nothing to displace the event is denied by default.
+//!
+//! The current `LoadLevel` and counts of everything shed, displaced,
+//! denied and degraded appear in `GatewayMetrics` and `GatewayHealth`.
+
+use std::collections::BTreeMap;
+
+use ethics_dsl::{EthicsDecision, EthicsEvent, UrgencyLevel};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::scheduler::{priority, PriorityScheduler, QueueMetrics, SchedulingConfig};
+
+/// Queue bounds and load-shedding thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GatewayConfig {
+    /// Most low-urgency events queued
+    pub low_capacity: usize,
+    /// Most normal-urgency events queued
+    pub normal_capacity: usize,
+    /// Most high-urgency events queued
+    pub high_capacity: usize,
+    /// Most critical events queued
+    pub critical_capacity: usize,
+    /// Queued events at which prediction is downgraded to the lexical predictor
+    pub degrade_at: usize,
+    /// Queued events at which new low-urgency events are shed
+    pub shed_at: usize,
+    /// Queued events beyond which nothing is added without displacing less urgent work
+    pub hard_limit: usize,
+}
+
+impl Default for GatewayConfig {
+    fn default() -> Self {
+        Self {
+            low_capacity: 1_024,
+            normal_capacity: 2_048,
+            high_capacity: 1_024,
+            critical_capacity: 512,
+            degrade_at: 1_024,
+            shed_at: 2_048,
+            hard_limit: 4_096,
+        }
+    }
+}
+
+impl GatewayConfig {
+    fn capacity(&self, urgency: &UrgencyLevel) -> usize {
+        match urgency {
+            UrgencyLevel::Low => self.low_capacity,
+            UrgencyLevel::Normal => self.normal_capacity,
+            UrgencyLevel::High => self.high_capacity,
+            UrgencyLevel::Critical => self.critical_capacity,
+        }
+    }
+}
+
+/// How loaded the gateway is, from least to most
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadLevel {
+    /// Below every threshold
+    #[default]
+    Normal,
+    /// Prediction downgraded to the lexical predictor
+    Degraded,
+    /// Low-urgency events shed
+    Shedding,
+    /// At the hard limit; events displace less urgent work or are denied
+    Overloaded,
+}
+
+/// Outcome of offering an event to the gateway
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Admission {
+    /// Queued for processing
+    Queued {
+        /// Less urgent events dropped from the queue to make room
+        displaced: Vec<String>,
+    },
+    /// Not queued: the event's class is full or low-urgency work is being shed; the producer may retry later
+    Shed,
+    /// Not queued: no room and nothing less urgent to displace
+    Denied {
+        /// Deny-by-default decision for the event
+        decision: EthicsDecision,
+    },
+}
+
+/// Event leaving the gateway for processing
+#[derive(Debug)]
+pub struct Admitted {
+    /// Policy subject the event was submitted under
+    pub subject: String,
+    /// Event to process
+    pub event: EthicsEvent,
+    /// Whether to score it with the lexical predictor
+    pub lexical: bool,
+}
+
+/// Admission counts and queue state
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GatewayMetrics {
+    /// Current load level
+    pub load: LoadLevel,
+    /// Events waiting, by urgency class name
+    pub waiting: BTreeMap<String, usize>,
+    /// Events queued
+    pub admitted: u64,
+    /// Events shed on arrival
+    pub shed: u64,
+    /// Queued events displaced by more urgent ones
+    pub displaced: u64,
+    /// Events denied by default
+    pub denied: u64,
+    /// Events dequeued for lexical scoring
+    pub degraded: u64,
+    /// Queueing delay per urgency class
+    pub queue: QueueMetrics,
+}
+
+/// Overload signal for health output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GatewayHealth {
+    /// Current load level
+    pub load: LoadLevel,
+    /// Whether events are being shed, displaced or denied
+    pub overloaded: bool,
+    /// Events waiting
+    pub waiting: usize,
+    /// Human-readable account of the load
+    pub detail: String,
+}
+
+/// Bounded, load-shedding queue in front of the pipeline
+#[derive(Debug)]
+pub struct IngestionGateway {
+    queue: PriorityScheduler<(String, EthicsEvent)>,
+    config: GatewayConfig,
+    admitted: u64,
+    shed: u64,
+    displaced: u64,
+    denied: u64,
+    degraded: u64,
+}
+
+impl IngestionGateway {
+    /// Empty gateway
+    pub fn new(scheduling: &SchedulingConfig, config: GatewayConfig) -> Self {
+        Self {
+            queue: PriorityScheduler::new(scheduling),
+            config,
+            admitted: 0,
+            shed: 0,
+            displaced: 0,
+            denied: 0,
+            degraded: 0,
+        }
+    }
+
+    /// Current load level
+    pub fn load(&self) -> LoadLevel {
+        let waiting = self.queue.len();
+        if waiting >= self.config.hard_limit {
+            LoadLevel::Overloaded
+        } else if waiting >= self.config.shed_at {
+            LoadLevel::Shedding
+        } else if waiting >= self.config.degrade_at {
+            LoadLevel::Degraded
+        } else {
+            LoadLevel::Normal
+        }
+    }
+
+    /// Offer an event for processing under `subject`
+    pub fn admit(&mut self, subject: String, event: EthicsEvent) -> Admission {
+        let urgency = event.context.urgency.clone();
+        let load = self.load();
+        let mut displaced = Vec::new();
+
+        let class_full = self.queue.lane_len(&urgency) >= self.config.capacity(&urgency);
+        if class_full || (load == LoadLevel::Shedding && priority(&urgency) == 0) {
+            self.shed += 1;
+            return Admission::Shed;
+        }
+        if load == LoadLevel::Overloaded {
+            match self.queue.shed_below(&urgency) {
+                Some(victim) => {
+                    warn!("Displacing {} from the ingestion queue for {}", victim.item.1.event_id, event.event_id);
+                    self.displaced += 1;
+                    displaced.push(victim.item.1.event_id);
+                }
+                None => {
+                    warn!("Ingestion queue full at {:?}; denying {} by default", load, event.event_id);
+                    self.denied += 1;
+                    return Admission::Denied { decision: overload_decision() };
+                }
+            }
+        }
+
+        self.admitted += 1;
+        self.queue.push(urgency, (subject, event));
+        Admission::Queued { displaced }
+    }
+
+    /// Most urgent waiting event, marked for lexical scoring when the gateway is degraded
+    pub fn next(&mut self) -> Option<Admitted> {
+        let lexical = self.load() >= LoadLevel::Degraded;
+        let (subject, event) = self.queue.pop()?.item;
+        if lexical {
+            self.degraded += 1;
+        }
+        Some(Admitted { subject, event, lexical })
+    }
+
+    /// Events waiting
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Whether nothing is waiting
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Queueing delay per urgency class
+    pub fn queue_metrics(&self) -> QueueMetrics {
+        self.queue.metrics()
+    }
+
+    /// Admission counts and queue state
+    pub fn metrics(&self) -> GatewayMetrics {
+        let waiting = [UrgencyLevel::Low, UrgencyLevel::Normal, UrgencyLevel::High, UrgencyLevel::Critical]
+            .iter()
+            .map(|urgency| (format!("{:?}", urgency), self.queue.lane_len(urgency)))
+            .collect();
+        GatewayMetrics {
+            load: self.load(),
+            waiting,
+            admitted: self.admitted,
+            shed: self.shed,
+            displaced: self.displaced,
+            denied: self.denied,
+            degraded: self.degraded,
+            queue: self.queue.metrics(),
+        }
+    }
+
+    /// Overload signal for health output
+    pub fn health(&self) -> GatewayHealth {
+        let load = self.load();
+        let waiting = self.queue.len();
+        let detail = match load {
+            LoadLevel::Normal => format!("{} events waiting", waiting),
+            LoadLevel::Degraded => format!("{} events waiting; scoring lexically", waiting),
+            LoadLevel::Shedding => format!("{} events waiting; shedding low-urgency events", waiting),
+            LoadLevel::Overloaded => format!("{} events waiting; at hard limit, denying by default", waiting),
+        };
+        GatewayHealth { load, overloaded: load >= LoadLevel::Shedding, waiting, detail }
+    }
+}
+
+/// Decision for an event the gateway had no room for
+pub fn overload_decision() -> EthicsDecision {
+    EthicsDecision::Deny {
+        confidence: 0.5,
+        violation: "Ingestion overloaded; denied by default pending resubmission".to_string(),
+        violated_principles: vec!["CAUTION".to_string()],
+        scripture_refs: vec!["Proverbs 14:15".to_string()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethics_dsl::{Actor, ActorType, Context};
+
+    fn event(id: &str, urgency: UrgencyLevel) -> EthicsEvent {
+        EthicsEvent {
+            event_id: id.to_string(),
+            actor: Actor { actor_type: ActorType::Person, tags: vec![], trust_level: 0.5, history: None },
+            content: None,
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn gateway() -> IngestionGateway {
+        let config = GatewayConfig {
+            low_capacity: 8,
+            normal_capacity: 8,
+            high_capacity: 8,
+            critical_capacity: 1,
+            degrade_at: 2,
+            shed_at: 3,
+            hard_limit: 4,
+        };
+        IngestionGateway::new(&SchedulingConfig::default(), config)
+    }
+
+    #[test]
+    fn test_load_is_shed_lowest_urgency_first() {
+        let mut gateway = gateway();
+        for (id, urgency) in [("low-1", UrgencyLevel::Low), ("low-2", UrgencyLevel::Low), ("normal-1", UrgencyLevel::Normal)] {
+            assert_eq!(gateway.admit("s".into(), event(id, urgency)), Admission::Queued { displaced: vec![] });
+        }
+        assert_eq!(gateway.load(), LoadLevel::Shedding);
+        assert_eq!(gateway.admit("s".into(), event("low-3", UrgencyLevel::Low)), Admission::Shed);
+        assert_eq!(gateway.admit("s".into(), event("high-1", UrgencyLevel::High)), Admission::Queued { displaced: vec![] });
+
+        // At the hard limit the newest low-urgency event makes room
+        assert_eq!(
+            gateway.admit("s".into(), event("critical-1", UrgencyLevel::Critical)),
+            Admission::Queued { displaced: vec!["low-2".to_string()] }
+        );
+        // Over the hard limit with nothing less urgent to displace
+        assert!(matches!(
+            gateway.admit("s".into(), event("low-4", UrgencyLevel::Low)),
+            Admission::Denied { decision: EthicsDecision::Deny { .. } }
+        ));
+        // The critical class holds one event
+        assert_eq!(gateway.admit("s".into(), event("critical-2", UrgencyLevel::Critical)), Admission::Shed);
+
+        let metrics = gateway.metrics();
+        assert_eq!((metrics.admitted, metrics.shed, metrics.displaced, metrics.denied), (5, 2, 1, 1));
+        assert_eq!(metrics.waiting["Low"], 1);
+        assert!(gateway.health().overloaded);
+    }
+
+    #[test]
+    fn test_dequeued_events_are_scored_lexically_while_degraded() {
+        let mut gateway = gateway();
+        gateway.admit("s".into(), event("normal-1", UrgencyLevel::Normal));
+        gateway.admit("s".into(), event("critical-1", UrgencyLevel::Critical));
+
+        let first = gateway.next().unwrap();
+        assert_eq!(first.event.event_id, "critical-1");
+        assert!(first.lexical);
+        assert!(!gateway.next().unwrap().lexical);
+        assert!(gateway.next().is_none());
+        assert_eq!(gateway.metrics().degraded, 1);
+        assert_eq!(gateway.health().load, LoadLevel::Normal);
+    }
+}
@@ -0,0 +1,228 @@
+//! Lexical Predictor - Fast Keyword Fallback for Harm Prediction
+//! "A word fitly spoken is like apples of gold in pictures of silver" - Proverbs 25:11
+//!
+//! Scores content against a fixed harm lexicon in microseconds. It is far
+//! less accurate than the neural model and is only used when the latency
+//! budget has no room for model inference. Its scores are capped below the
+//! purge thresholds and its predictions carry low confidence, so a lexical
+//! match alone can block or quarantine content but never purge it.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::policy::with_category_score;
+use crate::{
+    ColdMirrorResult, HarmCategory, HarmPredictor, HarmPrediction, ImpactScale, ModelMetrics, MonitoringLevel,
+    OutcomeData, PredictionInput, RecommendedAction, RiskFactor,
+};
+
+/// Model version recorded in lexical predictions
+pub const LEXICAL_MODEL_VERSION: &str = "lexical-1";
+
+/// Confidence of every lexical prediction
+pub const LEXICAL_CONFIDENCE: f32 = 0.5;
+
+/// Highest score a lexical match can reach
+pub const MAX_LEXICAL_SCORE: f32 = 0.9;
+
+/// Term stems per core category; a token matches when it starts with a stem
+const LEXICON: &[(&str, &[&str])] = &[
+    ("PhysicalHarm", &["kill", "murder", "bomb", "weapon", "shoot", "stab", "poison", "massacre"]),
+    ("PsychologicalHarm", &["suicid", "self-harm", "harass", "bully", "threaten", "humiliat"]),
+    ("MoralDegradation", &["fraud", "scam", "steal", "bribe", "cheat", "deceiv", "porn"]),
+    ("SocialHarm", &["riot", "hatred", "extremis", "radicaliz", "genocid"]),
+    ("SpiritualHarm", &["occult", "blasphem", "idolat", "witchcraft", "satanic"]),
+];
+
+/// Keyword-based harm predictor
+pub struct LexicalPredictor {
+    predictions: AtomicU64,
+    created_at: DateTime<Utc>,
+}
+
+impl Default for LexicalPredictor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LexicalPredictor {
+    /// Create a predictor over the built-in lexicon
+    pub fn new() -> Self {
+        Self {
+            predictions: AtomicU64::new(0),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Lexicon terms of each category found in `text`
+    fn matches(text: &str) -> Vec<(&'static str, Vec<&'static str>)> {
+        let lowered = text.to_lowercase();
+        let tokens: Vec<&str> = lowered
+            .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+            .filter(|token| !token.is_empty())
+            .collect();
+
+        LEXICON
+            .iter()
+            .filter_map(|(category, stems)| {
+                let hits: Vec<&'static str> = stems
+                    .iter()
+                    .copied()
+                    .filter(|stem| tokens.iter().any(|token| token.starts_with(stem)))
+                    .collect();
+                (!hits.is_empty()).then_some((*category, hits))
+            })
+            .collect()
+    }
+}
+
+impl HarmPredictor for LexicalPredictor {
+    fn predict_harm(&self, input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+        self.predictions.fetch_add(1, Ordering::Relaxed);
+        let text = input.event.content.as_ref().map(|content| content.data.as_str()).unwrap_or("");
+
+        let mut harm_level: f32 = 0.0;
+        let mut harm_categories = Vec::new();
+        let mut risk_factors = Vec::new();
+        for (category, hits) in Self::matches(text) {
+            // One distinct term scores 0.4, each further term adds 0.2
+            let score = (0.2 + 0.2 * hits.len() as f32).min(MAX_LEXICAL_SCORE);
+            harm_level = harm_level.max(score);
+            harm_categories.push(category_for(category, hits[0], score));
+            risk_factors.push(RiskFactor {
+                name: format!("lexical:{}", category),
+                weight: score,
+                description: "Matched harm lexicon terms".to_string(),
+                evidence: hits.iter().map(|hit| hit.to_string()).collect(),
+            });
+        }
+
+        Ok(HarmPrediction {
+            harm_level,
+            confidence: LEXICAL_CONFIDENCE,
+            time_horizon: 24.0,
+            harm_categories,
+            risk_factors,
+            recommended_action: RecommendedAction::AllowWithMonitoring {
+                monitoring_level: MonitoringLevel::Basic,
+                review_interval: 24.0,
+            },
+            timestamp: Utc::now(),
+            model_version: LEXICAL_MODEL_VERSION.to_string(),
+            policy_version: None,
+        })
+    }
+
+    fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        inputs.iter().map(|input| self.predict_harm(input)).collect()
+    }
+
+    fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+        // The lexicon is fixed; outcomes train the neural model only
+        Ok(())
+    }
+
+    fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+        Ok(ModelMetrics {
+            accuracy: 0.0,
+            precision_by_category: HashMap::new(),
+            recall_by_category: HashMap::new(),
+            avg_inference_time_ms: 0.0,
+            total_predictions: self.predictions.load(Ordering::Relaxed),
+            model_version: LEXICAL_MODEL_VERSION.to_string(),
+            last_updated: self.created_at,
+            provenance: ModelMetrics::installed_provenance(),
+        })
+    }
+}
+
+/// Harm category for a lexicon match, scored so `policy::category_score` returns about `score`
+fn category_for(category: &str, term: &str, score: f32) -> HarmCategory {
+    match category {
+        "PhysicalHarm" => HarmCategory::PhysicalHarm {
+            harm_type: term.to_string(),
+            victim_count: None,
+            likelihood: score,
+        },
+        "PsychologicalHarm" => HarmCategory::PsychologicalHarm {
+            damage_type: term.to_string(),
+            vulnerable_groups: vec![],
+            long_term_impact: score,
+        },
+        "SocialHarm" => with_category_score(
+            &HarmCategory::SocialHarm {
+                structure: term.to_string(),
+                scale: ImpactScale::Individual,
+                duration: crate::EffectDuration::ShortTerm,
+            },
+            score,
+        ),
+        "SpiritualHarm" => HarmCategory::SpiritualHarm {
+            principle: term.to_string(),
+            scripture_reference: "Exodus 20:3".to_string(),
+            eternal_impact: score,
+        },
+        _ => HarmCategory::MoralDegradation {
+            violation: term.to_string(),
+            severity: score,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::category_score;
+
+    fn input(data: &str) -> PredictionInput {
+        let event = ethics_dsl::EthicsEvent {
+            event_id: "lexical".to_string(),
+            actor: ethics_dsl::Actor {
+                actor_type: ethics_dsl::ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: Some(ethics_dsl::Content {
+                content_type: ethics_dsl::ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: ethics_dsl::Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: ethics_dsl::UrgencyLevel::Normal,
+            },
+            timestamp: Utc::now(),
+        };
+        crate::utils::create_prediction_input(event, None, None)
+    }
+
+    #[test]
+    fn test_lexicon_matches_score_categories() {
+        let predictor = LexicalPredictor::new();
+        let prediction = predictor.predict_harm(&input("They plan to bomb the square and kill everyone")).unwrap();
+
+        assert_eq!(prediction.model_version, LEXICAL_MODEL_VERSION);
+        assert_eq!(prediction.harm_categories.len(), 1);
+        assert!((category_score(&prediction.harm_categories[0]) - 0.6).abs() < 1e-6);
+        assert_eq!(prediction.risk_factors[0].evidence, vec!["kill", "bomb"]);
+        assert_eq!(predictor.get_performance_metrics().unwrap().total_predictions, 1);
+    }
+
+    #[test]
+    fn test_clean_text_and_score_cap() {
+        let predictor = LexicalPredictor::new();
+        let clean = predictor.predict_harm(&input("Love your neighbour as yourself")).unwrap();
+        assert!(clean.harm_categories.is_empty());
+        assert_eq!(clean.harm_level, 0.0);
+
+        let dense = predictor.predict_harm(&input("kill murder bomb weapon shoot stab poison massacre")).unwrap();
+        assert_eq!(dense.harm_level, MAX_LEXICAL_SCORE);
+    }
+}
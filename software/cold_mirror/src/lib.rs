@@ -3,19 +3,60 @@
 //!
 //! This library implements the Cold-Mirror harm prediction system that analyzes
 //! content and actors to predict potential moral and physical harm using neural networks.
+//!
+//! With only the `core` feature the crate provides the prediction types, the
+//! `HarmPredictor` trait, action policies, the taxonomy, the scheduler and
+//! ingestion gateway, the risk scoring pipeline, deterministic replay of decisions, tenant-aware
+//! serving, the lexical fallback predictor and the `robustness` suite that
+//! checks prediction stability under typos, homoglyphs and paraphrase. The
+//! default `full` feature adds the neural models, multimodal analysis, the pipeline, shadow auditing and harm signals for
+//! the ethics engine's AGI attack detector, with the ML stack and the full
+//! ethics engine they need. The `quarantine` feature adds the encrypted
+//! quarantine queue that holds content for reviewer-gated human review.
+//! `memory-mapping` loads model weights memory-mapped, paged in on use and
+//! optionally quantized to int8, within `PerformanceConfig::memory_limit_mb`.
 
 #![deny(missing_docs)]
 #![warn(clippy::all)]
 
+#[cfg(feature = "full")]
 pub mod analysis;
+pub mod deterministic;
+pub mod ensemble;
+pub mod gateway;
+#[cfg(feature = "full")]
 pub mod inference;
+pub mod lexical;
+#[cfg(feature = "full")]
 pub mod models;
+#[cfg(feature = "full")]
+pub mod multimodal;
+#[cfg(feature = "full")]
+pub mod pipeline;
+pub mod policy;
+#[cfg(feature = "full")]
 pub mod preprocessing;
+#[cfg(feature = "quarantine")]
+pub mod quarantine;
+#[cfg(feature = "remote-prediction")]
+pub mod remote;
 pub mod risk_assessment;
+pub mod robustness;
+pub mod sanitize;
+pub mod scheduler;
+#[cfg(feature = "full")]
+pub mod shadow;
+#[cfg(feature = "full")]
+pub mod signals;
+pub mod taxonomy;
+pub mod tenancy;
+#[cfg(feature = "full")]
 pub mod training;
+#[cfg(feature = "memory-mapping")]
+pub mod weights;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 use chrono::{DateTime, Utc};
 use ethics_dsl::{EthicsEvent, EthicsDecision};
@@ -48,6 +89,9 @@ pub struct HarmPrediction {
     pub timestamp: DateTime<Utc>,
     /// Model version used
     pub model_version: String,
+    /// Version of the action policy that chose `recommended_action`
+    #[serde(default)]
+    pub policy_version: Option<String>,
 }
 
 /// Categories of potential harm
@@ -96,6 +140,16 @@ pub enum HarmCategory {
         /// Eternal consequences assessment
         eternal_impact: f32,
     },
+    /// Category registered in a taxonomy extension (see `taxonomy`)
+    Custom {
+        /// Category id; kept as-is when the category is not known locally
+        id: String,
+        /// Score (0.0 to 1.0)
+        score: f32,
+        /// Category-specific details
+        #[serde(default)]
+        details: BTreeMap<String, String>,
+    },
 }
 
 /// Risk factors that contribute to harm
@@ -269,7 +323,7 @@ pub struct SocialContext {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EconomicContext {
     /// Economic indicators
-    pub indicators: HashMap<String, f32>,
+    pub indicators: BTreeMap<String, f32>,
     /// Market conditions
     pub market_conditions: Vec<String>,
 }
@@ -365,6 +419,26 @@ pub enum ColdMirrorError {
     /// Data error
     #[error("Data error: {0}")]
     DataError(String),
+    
+    /// Remote predictor error
+    #[error("Remote predictor error: {0}")]
+    RemoteError(String),
+    
+    /// Quarantine storage error
+    #[error("Quarantine error: {0}")]
+    QuarantineError(String),
+    
+    /// Access to quarantined content refused
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+    
+    /// Tenant request quota used up
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+    
+    /// Ethics engine error
+    #[error("Ethics evaluation error: {0}")]
+    EthicsError(#[from] ethics_dsl::EthicsError),
 }
 
 /// Result type for Cold-Mirror operations
@@ -402,6 +476,22 @@ pub struct ModelMetrics {
     pub model_version: String,
     /// Last updated
     pub last_updated: DateTime<Utc>,
+    /// Patch the model was installed from (see `ModelMetrics::installed_provenance`)
+    #[serde(default)]
+    pub provenance: Option<ark_provenance::ProvenanceManifest>,
+}
+
+impl ModelMetrics {
+    /// Provenance manifest of the installed cold_mirror patch
+    ///
+    /// Read on every call, so a newly applied patch is reported without a
+    /// restart; an unreadable manifest is logged and reported as unknown.
+    pub fn installed_provenance() -> Option<ark_provenance::ProvenanceManifest> {
+        ark_provenance::current().unwrap_or_else(|e| {
+            log::warn!("Unreadable provenance manifest: {}", e);
+            None
+        })
+    }
 }
 
 /// Configuration for Cold-Mirror system
@@ -415,6 +505,18 @@ pub struct ColdMirrorConfig {
     pub security: SecurityConfig,
     /// Logging settings
     pub logging: LoggingConfig,
+    /// Path to the action threshold policy (JSON); built-in default when unset
+    #[serde(default)]
+    pub action_policy_path: Option<String>,
+    /// Path to a harm taxonomy extension (JSON); core categories only when unset
+    #[serde(default)]
+    pub taxonomy_path: Option<String>,
+    /// Stages of the composable risk scoring pipeline
+    #[serde(default)]
+    pub risk_assessment: risk_assessment::RiskAssessmentConfig,
+    /// Reproducible, pinned decisions
+    #[serde(default)]
+    pub deterministic: deterministic::DeterministicConfig,
 }
 
 /// Model configuration
@@ -428,6 +530,13 @@ pub struct ModelConfig {
     pub preprocessing: PreprocessingConfig,
     /// Output postprocessing settings
     pub postprocessing: PostprocessingConfig,
+    /// Member aggregation when `model_type` is `Ensemble`
+    #[serde(default)]
+    pub ensemble: ensemble::EnsembleConfig,
+    /// Memory-mapped weight loading and load-time quantization
+    #[cfg(feature = "memory-mapping")]
+    #[serde(default)]
+    pub weights: weights::WeightConfig,
 }
 
 /// Model types supported
@@ -553,6 +662,12 @@ pub struct PerformanceConfig {
     pub memory_limit_mb: usize,
     /// GPU acceleration settings
     pub gpu_acceleration: Option<GpuConfig>,
+    /// Urgency scheduling of queued evaluations and inference
+    #[serde(default)]
+    pub scheduling: scheduler::SchedulingConfig,
+    /// Queue bounds and load shedding of the pipeline's ingestion gateway
+    #[serde(default)]
+    pub gateway: gateway::GatewayConfig,
 }
 
 /// GPU acceleration configuration
@@ -571,8 +686,11 @@ pub struct GpuConfig {
 pub struct SecurityConfig {
     /// Model integrity verification
     pub verify_model_integrity: bool,
-    /// Input sanitization
+    /// Input sanitization (see `sanitize`)
     pub sanitize_inputs: bool,
+    /// Limits applied when `sanitize_inputs` is set
+    #[serde(default)]
+    pub sanitization: sanitize::SanitizationConfig,
     /// Side-channel protection
     pub side_channel_protection: bool,
     /// Differential privacy
@@ -643,6 +761,9 @@ impl Default for ColdMirrorConfig {
                         max_predictions: 1000,
                     },
                 },
+                ensemble: ensemble::EnsembleConfig::default(),
+                #[cfg(feature = "memory-mapping")]
+                weights: weights::WeightConfig::default(),
             },
             performance: PerformanceConfig {
                 max_batch_size: MAX_BATCH_SIZE,
@@ -650,10 +771,13 @@ impl Default for ColdMirrorConfig {
                 num_threads: 4,
                 memory_limit_mb: 2048,
                 gpu_acceleration: None,
+                scheduling: scheduler::SchedulingConfig::default(),
+                gateway: gateway::GatewayConfig::default(),
             },
             security: SecurityConfig {
                 verify_model_integrity: true,
                 sanitize_inputs: true,
+                sanitization: sanitize::SanitizationConfig::default(),
                 side_channel_protection: true,
                 differential_privacy: None,
             },
@@ -663,6 +787,10 @@ impl Default for ColdMirrorConfig {
                 log_metrics: true,
                 log_file: Some("cold_mirror.log".to_string()),
             },
+            action_policy_path: None,
+            taxonomy_path: None,
+            risk_assessment: risk_assessment::RiskAssessmentConfig::default(),
+            deterministic: deterministic::DeterministicConfig::default(),
         }
     }
 }
@@ -746,6 +874,39 @@ pub mod utils {
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_metrics_report_the_installed_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = ark_provenance::ProvenanceManifest {
+            format: ark_provenance::MANIFEST_FORMAT_VERSION,
+            component: "cold_mirror".to_string(),
+            namespace: "default".to_string(),
+            patch_id: "cm-2024-07".to_string(),
+            version: "1.4.0".to_string(),
+            payload_hash: "ab".repeat(32),
+            signature_algorithm: "HybridEd25519Dilithium3".to_string(),
+            pq_signature: None,
+            classical_signature: None,
+            approvers: vec!["alice".to_string()],
+            operator_approved: false,
+            applied_at: std::time::SystemTime::now(),
+            applied_by: "3.0.0".to_string(),
+            previous_patch_id: None,
+        };
+        manifest.write(dir.path()).unwrap();
+        std::env::set_var(ark_provenance::COMPONENT_DIR_ENV, dir.path());
+        
+        let predictors: [Box<dyn HarmPredictor>; 2] = [
+            Box::new(lexical::LexicalPredictor::new()),
+            Box::new(ensemble::EnsemblePredictor::new(ensemble::EnsembleConfig::default())),
+        ];
+        for predictor in predictors {
+            let metrics = predictor.get_performance_metrics().unwrap();
+            assert_eq!(metrics.provenance.as_ref().map(|p| p.patch_id.as_str()), Some("cm-2024-07"));
+        }
+        std::env::remove_var(ark_provenance::COMPONENT_DIR_ENV);
+    }
+    
     #[test]
     fn test_harm_prediction_serialization() {
         let prediction = HarmPrediction {
@@ -760,6 +921,7 @@ mod tests {
             },
             timestamp: Utc::now(),
             model_version: "v1.0".to_string(),
+            policy_version: None,
         };
         
         let json = serde_json::to_string(&prediction).unwrap();
@@ -783,6 +945,7 @@ mod tests {
             },
             timestamp: Utc::now(),
             model_version: "v1.0".to_string(),
+            policy_version: None,
         };
         
         let score = utils::calculate_harm_score(&prediction);
@@ -803,6 +966,7 @@ mod tests {
             },
             timestamp: Utc::now(),
             model_version: "v1.0".to_string(),
+            policy_version: None,
         };
         
         assert!(utils::is_high_confidence(&high_conf_prediction));
@@ -0,0 +1,249 @@
+//! Multimodal Pipeline - Harm Categories for Image, Video and Audio
+//! "Man looketh on the outward appearance, but the Lord looketh on the heart" - 1 Samuel 16:7
+//!
+//! Routes non-text content to a `MediaModel` registered for its modality
+//! and reports the harm categories found to the ethics engine, which
+//! delegates media content here once the pipeline is installed as its
+//! `MultimodalAnalyzer`. Each category is translated into the moral tag the
+//! ethics rules evaluate; extension categories take the tag of their core
+//! ancestor in the taxonomy. Categories with no corresponding tag are not
+//! reported.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ethics_dsl::{
+    tags, Content, ContentType, EthicsError, EthicsResult, ModelFinding, MultimodalAnalysis, MultimodalAnalyzer,
+};
+use log::debug;
+
+use crate::policy::{category_name, category_score};
+use crate::taxonomy::Taxonomy;
+use crate::{ColdMirrorResult, HarmCategory};
+
+/// Kind of media a model handles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Modality {
+    /// Still images
+    Image,
+    /// Video
+    Video,
+    /// Audio
+    Audio,
+}
+
+impl Modality {
+    /// Modality of a content type; `None` for text-based content
+    pub fn of(content_type: &ContentType) -> Option<Self> {
+        match content_type {
+            ContentType::Image => Some(Modality::Image),
+            ContentType::Video => Some(Modality::Video),
+            ContentType::Audio => Some(Modality::Audio),
+            _ => None,
+        }
+    }
+}
+
+/// Harm model for one or more modalities
+pub trait MediaModel: Send + Sync {
+    /// Model name and version recorded in decision traces
+    fn version(&self) -> &str;
+
+    /// Harm categories found in `content`
+    fn classify(&self, modality: Modality, content: &Content) -> ColdMirrorResult<Vec<HarmCategory>>;
+}
+
+/// Media models by modality, exposed to the ethics engine as one analyzer
+#[derive(Default)]
+pub struct MultimodalPipeline {
+    models: HashMap<Modality, Arc<dyn MediaModel>>,
+    taxonomy: Taxonomy,
+}
+
+impl MultimodalPipeline {
+    /// Pipeline without models, resolving categories against the core taxonomy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle `modality` with `model`
+    pub fn with_model(mut self, modality: Modality, model: Arc<dyn MediaModel>) -> Self {
+        self.models.insert(modality, model);
+        self
+    }
+
+    /// Resolve extension categories against `taxonomy`
+    pub fn with_taxonomy(mut self, taxonomy: Taxonomy) -> Self {
+        self.taxonomy = taxonomy;
+        self
+    }
+
+    /// Moral tag corresponding to a harm category
+    pub fn category_tag(&self, category: &HarmCategory) -> Option<&'static str> {
+        match category {
+            HarmCategory::MoralDegradation { violation, .. } => {
+                Some(known_tag(violation).unwrap_or(tags::SEXUAL_IMMORALITY))
+            }
+            HarmCategory::PhysicalHarm { .. } => Some(tags::VIOLENCE_INNOCENT),
+            HarmCategory::PsychologicalHarm { vulnerable_groups, .. } => vulnerable_groups
+                .iter()
+                .any(|group| group.eq_ignore_ascii_case("children"))
+                .then_some(tags::CHILD_CORRUPTION),
+            HarmCategory::SocialHarm { .. } => Some(tags::DECEPTION),
+            HarmCategory::SpiritualHarm { principle, .. } => Some(known_tag(principle).unwrap_or(tags::BLASPHEMY)),
+            HarmCategory::Custom { id, .. } => match self.taxonomy.core_ancestor(id)? {
+                "MoralDegradation" => Some(tags::SEXUAL_IMMORALITY),
+                "PhysicalHarm" => Some(tags::VIOLENCE_INNOCENT),
+                "SocialHarm" => Some(tags::DECEPTION),
+                "SpiritualHarm" => Some(tags::BLASPHEMY),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// The moral tag named by `name`, if any
+fn known_tag(name: &str) -> Option<&'static str> {
+    tags::ALL_VIOLATION_TAGS.iter().copied().find(|tag| tag.eq_ignore_ascii_case(name))
+}
+
+impl MultimodalAnalyzer for MultimodalPipeline {
+    fn name(&self) -> &str {
+        "cold-mirror-multimodal"
+    }
+
+    fn analyze(&self, content: &Content) -> EthicsResult<MultimodalAnalysis> {
+        let modality = Modality::of(&content.content_type).ok_or_else(|| {
+            EthicsError::EvaluationError(format!("{:?} content is not media", content.content_type))
+        })?;
+        let model = self
+            .models
+            .get(&modality)
+            .ok_or_else(|| EthicsError::ConfigurationError(format!("No media model for {:?}", modality)))?;
+        let categories = model
+            .classify(modality, content)
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", model.version(), e)))?;
+
+        let mut findings = Vec::new();
+        for category in &categories {
+            match self.category_tag(category) {
+                Some(tag) => findings.push(ModelFinding {
+                    category: category_name(category).to_string(),
+                    tag: tag.to_string(),
+                    score: category_score(category) as f64,
+                }),
+                None => debug!("{} category {} has no moral tag", model.version(), category_name(category)),
+            }
+        }
+
+        Ok(MultimodalAnalysis {
+            model: model.version().to_string(),
+            findings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethics_dsl::{
+        Actor, ActorType, ContentSource, Context, EthicsConfig, EthicsDecision, EthicsEngine, EthicsEvent, UrgencyLevel,
+    };
+
+    /// Model reporting fixed categories
+    struct Fixed(Vec<HarmCategory>);
+
+    impl MediaModel for Fixed {
+        fn version(&self) -> &str {
+            "vision-test-1"
+        }
+
+        fn classify(&self, _modality: Modality, _content: &Content) -> ColdMirrorResult<Vec<HarmCategory>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn image() -> Content {
+        Content {
+            content_type: ContentType::Image,
+            data: "aGVsbG8=".to_string(),
+            metadata: HashMap::new(),
+            content_hash: String::new(),
+        }
+    }
+
+    fn pipeline() -> MultimodalPipeline {
+        MultimodalPipeline::new().with_model(
+            Modality::Image,
+            Arc::new(Fixed(vec![
+                HarmCategory::PhysicalHarm {
+                    harm_type: "weapon".to_string(),
+                    victim_count: None,
+                    likelihood: 0.9,
+                },
+                HarmCategory::PsychologicalHarm {
+                    damage_type: "distress".to_string(),
+                    vulnerable_groups: vec![],
+                    long_term_impact: 0.8,
+                },
+            ])),
+        )
+    }
+
+    #[test]
+    fn test_categories_map_to_tags() {
+        let analysis = pipeline().analyze(&image()).unwrap();
+
+        assert_eq!(analysis.model, "vision-test-1");
+        assert_eq!(analysis.findings.len(), 1);
+        assert_eq!(analysis.findings[0].category, "PhysicalHarm");
+        assert_eq!(analysis.findings[0].tag, tags::VIOLENCE_INNOCENT);
+
+        let mut video = image();
+        video.content_type = ContentType::Video;
+        assert!(pipeline().analyze(&video).is_err());
+    }
+
+    #[test]
+    fn test_engine_traces_model_assisted_decisions() {
+        let mut engine = EthicsEngine::new(EthicsConfig::default()).unwrap();
+        engine.set_multimodal_analyzer(Arc::new(pipeline()));
+
+        let event = EthicsEvent {
+            event_id: "media".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: Some(image()),
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: chrono::Utc::now(),
+        };
+        let (_, trace) = engine.evaluate_traced(&event).unwrap();
+
+        assert!(trace.model_assisted());
+        assert_eq!(
+            trace.content,
+            ContentSource::Model {
+                model: "vision-test-1".to_string(),
+                tags: vec![tags::VIOLENCE_INNOCENT.to_string()],
+            }
+        );
+
+        // No video model: the analyzer fails and the video is denied, not read as text
+        let mut video = event;
+        video.event_id = "video".to_string();
+        video.content.as_mut().unwrap().content_type = ContentType::Video;
+        let (decision, trace) = engine.evaluate_traced(&video).unwrap();
+        assert!(matches!(decision, EthicsDecision::Deny { .. }));
+        assert!(matches!(trace.content, ContentSource::ModelFailed { .. }));
+    }
+}
@@ -0,0 +1,370 @@
+//! Decision Pipeline - Ingestion to Actuation Under One Deadline
+//! "The thoughts of the diligent tend only to plenteousness; but of every one that is hasty only to want" - Proverbs 21:5
+//!
+//! Runs an event through ingestion, ethics evaluation, harm prediction and
+//! actuation with a single `LatencyBudget` started at ingestion. The ethics
+//! engine falls back to cached decisions when its evaluation no longer
+//! fits; harm prediction falls back to the `LexicalPredictor` when model
+//! inference does not fit or times out. Actuation always runs, since an
+//! event must get a verdict, but a missed deadline is recorded. The verdict
+//! carries the budget report naming every stage that degraded.
+//!
+//! Events submitted rather than processed directly pass the
+//! `IngestionGateway`, which bounds the queue and sheds load under surge,
+//! and wait in urgency order, so critical events are processed first. While
+//! the gateway is degraded, queued events are scored lexically. With a
+//! `ShadowAuditor` attached, Allowed verdicts are sampled for shadow
+//! re-evaluation off the critical path.
+//!
+//! Events enter as `IncomingEvent`s and are admitted by the ethics engine
+//! first: signed envelopes are verified against the producer registry and
+//! bare events have their claimed trust capped. An envelope that fails
+//! verification is denied at the gateway and never evaluated.
+//!
+//! When the ethics engine pseudonymizes actors, subjects are replaced by
+//! their pseudonyms on entry, so the action policy's hysteresis state and
+//! its logs never hold the identifier. The state is keyed by pseudonym and
+//! therefore starts afresh for every subject when the key rotates.
+
+use ethics_dsl::{
+    BudgetReport, ContentIngestor, EthicsDecision, EthicsEngine, EthicsEvent, IncomingEvent, LatencyBudget,
+    PipelineStage,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::gateway::{Admission, GatewayHealth, GatewayMetrics, IngestionGateway};
+use crate::lexical::LexicalPredictor;
+use crate::policy::{ActionLevel, ActionPolicy};
+use crate::scheduler::QueueMetrics;
+use crate::shadow::{ShadowAuditor, ShadowReport};
+use crate::{utils, ColdMirrorConfig, ColdMirrorError, ColdMirrorResult, HarmPrediction, HarmPredictor};
+
+/// Final verdict for an event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineVerdict {
+    /// Event the verdict applies to
+    pub event_id: String,
+    /// Stricter of the ethics decision and the harm prediction's decision
+    pub decision: EthicsDecision,
+    /// Harm prediction with the policy's action applied
+    pub prediction: HarmPrediction,
+    /// Action level chosen by the action policy
+    pub action: ActionLevel,
+    /// Original event when the content was a duplicate
+    pub duplicate_of: Option<String>,
+    /// How the latency budget was spent and which stages degraded
+    pub budget: BudgetReport,
+}
+
+/// Ethics engine, harm predictor and action policy behind one deadline
+pub struct DecisionPipeline<P: HarmPredictor> {
+    engine: EthicsEngine,
+    ingestor: ContentIngestor,
+    predictor: P,
+    lexical: LexicalPredictor,
+    policy: ActionPolicy,
+    /// Budget model inference needs, from `inference_timeout_ms`
+    inference_allowance: Duration,
+    /// Submitted events with their policy subjects, by urgency
+    gateway: IngestionGateway,
+    /// Background re-evaluation of sampled Allowed verdicts
+    shadow: Option<ShadowAuditor>,
+}
+
+impl<P: HarmPredictor> DecisionPipeline<P> {
+    /// Assemble a pipeline; the ingestor's configuration sets the latency budget
+    pub fn new(
+        engine: EthicsEngine,
+        ingestor: ContentIngestor,
+        predictor: P,
+        policy: ActionPolicy,
+        config: &ColdMirrorConfig,
+    ) -> Self {
+        Self {
+            engine,
+            ingestor,
+            predictor,
+            lexical: LexicalPredictor::new(),
+            policy,
+            inference_allowance: Duration::from_millis(config.performance.inference_timeout_ms),
+            gateway: IngestionGateway::new(&config.performance.scheduling, config.performance.gateway.clone()),
+            shadow: None,
+        }
+    }
+
+    /// Sample Allowed verdicts for shadow re-evaluation
+    pub fn with_shadow(mut self, auditor: ShadowAuditor) -> Self {
+        self.shadow = Some(auditor);
+        self
+    }
+
+    /// Shadow auditing report, if a `ShadowAuditor` is attached
+    pub fn shadow_report(&self) -> Option<ShadowReport> {
+        self.shadow.as_ref().map(ShadowAuditor::report)
+    }
+
+    /// Offer an event to the gateway for `process_queued`, scheduled by its urgency
+    ///
+    /// An envelope that fails verification is denied here.
+    pub fn submit(&mut self, subject: impl Into<String>, incoming: impl Into<IncomingEvent>) -> Admission {
+        let event = match self.engine.admit_incoming(incoming.into()) {
+            Ok(event) => event,
+            Err(decision) => return Admission::Denied { decision },
+        };
+        let subject = self.engine.pseudonymize(&subject.into());
+        self.gateway.admit(subject, event)
+    }
+
+    /// Process up to `max` queued events, most urgent first
+    ///
+    /// Each event's latency budget starts when it is dequeued; its time in
+    /// the queue shows in `queue_metrics`.
+    pub fn process_queued(&mut self, max: usize) -> Vec<ColdMirrorResult<PipelineVerdict>> {
+        let mut verdicts = Vec::new();
+        while verdicts.len() < max {
+            let Some(admitted) = self.gateway.next() else { break };
+            verdicts.push(self.process_with(&admitted.subject, admitted.event, admitted.lexical));
+        }
+        verdicts
+    }
+
+    /// Queueing delay per urgency class of submitted events
+    pub fn queue_metrics(&self) -> QueueMetrics {
+        self.gateway.queue_metrics()
+    }
+
+    /// Load level and admission counts of the gateway
+    pub fn gateway_metrics(&self) -> GatewayMetrics {
+        self.gateway.metrics()
+    }
+
+    /// Overload signal for health output
+    pub fn health(&self) -> GatewayHealth {
+        self.gateway.health()
+    }
+
+    /// Run an event through every stage; `subject` keys the policy's hysteresis state
+    ///
+    /// An envelope that fails verification is refused with `AccessDenied`.
+    pub fn process(&mut self, subject: &str, incoming: impl Into<IncomingEvent>) -> ColdMirrorResult<PipelineVerdict> {
+        let event = self.engine.admit_incoming(incoming.into()).map_err(|decision| match decision {
+            EthicsDecision::Deny { violation, .. } => ColdMirrorError::AccessDenied(violation),
+            other => ColdMirrorError::AccessDenied(format!("Event refused: {:?}", other)),
+        })?;
+        let subject = self.engine.pseudonymize(subject);
+        self.process_with(&subject, event, false)
+    }
+
+    fn process_with(&mut self, subject: &str, event: EthicsEvent, lexical: bool) -> ColdMirrorResult<PipelineVerdict> {
+        let mut budget = self.ingestor.start_budget();
+        let input = utils::create_prediction_input(event.clone(), None, None);
+
+        let ingested = self.engine.evaluate_ingested_within(&mut self.ingestor, event, &mut budget)?;
+        let mut prediction = if lexical {
+            budget.degrade(PipelineStage::HarmPrediction, "lexical predictor under overload");
+            self.lexical.predict_harm(&input)?
+        } else {
+            self.predict(&input, &mut budget)?
+        };
+
+        if budget.is_exhausted() {
+            warn!("Deadline missed before actuating {}", ingested.event_id);
+            budget.degrade(PipelineStage::Actuation, "acted after deadline");
+        }
+        let selection = self.policy.apply(subject, &mut prediction);
+        let decision = stricter(ingested.decision, utils::to_ethics_decision(&prediction));
+        if let Some(shadow) = &self.shadow {
+            shadow.observe(&input.event, &decision);
+        }
+
+        Ok(PipelineVerdict {
+            event_id: ingested.event_id,
+            decision,
+            prediction,
+            action: selection.level,
+            duplicate_of: ingested.duplicate_of,
+            budget: budget.report(),
+        })
+    }
+
+    /// Predict with the model if inference fits in the budget, else lexically
+    fn predict(&self, input: &crate::PredictionInput, budget: &mut LatencyBudget) -> ColdMirrorResult<HarmPrediction> {
+        if !budget.allows(self.inference_allowance) {
+            budget.degrade(PipelineStage::HarmPrediction, "lexical predictor");
+            return self.lexical.predict_harm(input);
+        }
+
+        match self.predictor.predict_harm(input) {
+            Err(ColdMirrorError::TimeoutError) => {
+                warn!("Harm model timed out for {}; using lexical predictor", input.event.event_id);
+                budget.degrade(PipelineStage::HarmPrediction, "lexical predictor after model timeout");
+                self.lexical.predict_harm(input)
+            }
+            result => result,
+        }
+    }
+}
+
+/// The more severe of two decisions; the first wins ties
+pub(crate) fn stricter(first: EthicsDecision, second: EthicsDecision) -> EthicsDecision {
+    let severity = |decision: &EthicsDecision| match decision {
+        EthicsDecision::Allow { .. } => 0,
+        EthicsDecision::Deny { .. } => 1,
+        EthicsDecision::Purge { .. } => 2,
+    };
+    if severity(&second) > severity(&first) {
+        second
+    } else {
+        first
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::ThresholdPolicy;
+    use crate::{ModelMetrics, OutcomeData, PredictionInput};
+    use ethics_dsl::{Actor, ActorType, Content, ContentType, Context, EthicsConfig, IngestConfig, UrgencyLevel};
+    use std::collections::HashMap;
+
+    /// Model that always times out
+    struct TimingOut;
+
+    impl HarmPredictor for TimingOut {
+        fn predict_harm(&self, _input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+            Err(ColdMirrorError::TimeoutError)
+        }
+
+        fn predict_harm_batch(&self, _inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+            Err(ColdMirrorError::TimeoutError)
+        }
+
+        fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+            Ok(())
+        }
+
+        fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+            Err(ColdMirrorError::TimeoutError)
+        }
+    }
+
+    fn pipeline(latency_budget: Duration) -> DecisionPipeline<TimingOut> {
+        DecisionPipeline::new(
+            EthicsEngine::new(EthicsConfig::default()).unwrap(),
+            ContentIngestor::new(IngestConfig { latency_budget, ..IngestConfig::default() }),
+            TimingOut,
+            ActionPolicy::new(ThresholdPolicy::default()).unwrap(),
+            &ColdMirrorConfig::default(),
+        )
+    }
+
+    fn event(data: &str) -> EthicsEvent {
+        EthicsEvent {
+            event_id: "pipeline".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_exhausted_budget_degrades_every_stage() {
+        let mut pipeline = pipeline(Duration::ZERO);
+        let verdict = pipeline.process("subject", event("a plan to bomb and kill")).unwrap();
+
+        let stages: Vec<PipelineStage> = verdict.budget.degraded.iter().map(|d| d.stage).collect();
+        assert_eq!(
+            stages,
+            vec![PipelineStage::EthicsEvaluation, PipelineStage::HarmPrediction, PipelineStage::Actuation]
+        );
+        assert!(verdict.budget.exceeded);
+        // No cached decision exists, so evaluation fails closed
+        assert!(matches!(verdict.decision, EthicsDecision::Deny { .. }));
+        assert_eq!(verdict.prediction.model_version, crate::lexical::LEXICAL_MODEL_VERSION);
+        assert_eq!(verdict.action, ActionLevel::Quarantine);
+    }
+
+    #[test]
+    fn test_model_timeout_falls_back_to_lexical() {
+        let mut pipeline = pipeline(Duration::from_secs(60));
+        let verdict = pipeline.process("subject", event("peace be with you")).unwrap();
+
+        assert_eq!(verdict.budget.degraded.len(), 1);
+        assert_eq!(verdict.budget.degraded[0].stage, PipelineStage::HarmPrediction);
+        assert_eq!(verdict.budget.degraded[0].fallback, "lexical predictor after model timeout");
+        assert!(!verdict.budget.exceeded);
+    }
+
+    #[test]
+    fn test_queued_events_processed_by_urgency() {
+        let mut pipeline = pipeline(Duration::from_secs(60));
+        let mut routine = event("peace be with you");
+        routine.event_id = "routine".to_string();
+        let mut critical = event("peace be with you");
+        critical.event_id = "critical".to_string();
+        critical.context.urgency = UrgencyLevel::Critical;
+
+        pipeline.submit("subject", routine);
+        pipeline.submit("subject", critical);
+        let order: Vec<String> =
+            pipeline.process_queued(10).into_iter().map(|verdict| verdict.unwrap().event_id).collect();
+
+        assert_eq!(order, vec!["critical", "routine"]);
+        assert_eq!(pipeline.queue_metrics().delays["Critical"].dequeued, 1);
+        assert_eq!(pipeline.queue_metrics().waiting, 0);
+    }
+
+    #[test]
+    fn test_unverified_envelopes_are_denied_before_evaluation() {
+        let mut pipeline = pipeline(Duration::from_secs(60));
+        // The engine has no identity registry, so no envelope verifies
+        let envelope = ethics_dsl::EventEnvelope {
+            producer: "sensor-1".to_string(),
+            payload: serde_json::to_string(&event("peace be with you")).unwrap(),
+            signature: pq_types::DilithiumSignatureBytes::from_vec(vec![0; pq_types::sizes::DILITHIUM3_SIGNATURE])
+                .unwrap(),
+        };
+
+        assert!(matches!(
+            pipeline.submit("subject", IncomingEvent::Signed(envelope.clone())),
+            Admission::Denied { decision: EthicsDecision::Deny { .. } }
+        ));
+        assert!(matches!(
+            pipeline.process("subject", IncomingEvent::Signed(envelope)),
+            Err(ColdMirrorError::AccessDenied(_))
+        ));
+        assert_eq!(pipeline.queue_metrics().waiting, 0);
+    }
+
+    #[test]
+    fn test_stricter_decision_wins() {
+        let allow = EthicsDecision::Allow { confidence: 0.9, justification: String::new(), scripture_refs: vec![] };
+        let purge = EthicsDecision::Purge {
+            severity: 9,
+            reason: String::new(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        assert_eq!(stricter(allow.clone(), purge.clone()), purge);
+        assert_eq!(stricter(purge.clone(), allow), purge);
+    }
+}
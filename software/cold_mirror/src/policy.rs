@@ -0,0 +1,551 @@
+//! Action Policy - Per-Category Thresholds with Hysteresis
+//! "Let your moderation be known unto all men" - Philippians 4:5
+//!
+//! Maps the score of each `HarmCategory` to a `RecommendedAction` through a
+//! configurable policy table. Every rule has separate escalate and
+//! de-escalate thresholds: a subject is escalated once a score reaches
+//! `escalate_at`, but only returns to a milder action after the score falls
+//! below `deescalate_below`. This keeps actions from flapping when a score
+//! hovers around a boundary. The table can be reloaded from disk while
+//! running, and its version is recorded in every prediction it touches.
+//! Categories without rules of their own fall back along their taxonomy
+//! lineage (see `taxonomy`).
+
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+use crate::taxonomy::Taxonomy;
+use crate::{
+    ColdMirrorConfig, ColdMirrorError, ColdMirrorResult, HarmCategory, HarmPrediction, ImpactScale, MonitoringLevel,
+    RecommendedAction, ReviewPriority, UrgencyLevel,
+};
+
+/// Action severity levels, mildest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ActionLevel {
+    /// Allow with basic monitoring
+    Allow,
+    /// Allow with enhanced monitoring
+    Monitor,
+    /// Hold for human review
+    Quarantine,
+    /// Block the content
+    Block,
+    /// Purge the content
+    Purge,
+}
+
+/// Threshold rule for one harm category and action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryThreshold {
+    /// Harm category name (e.g. `PhysicalHarm`)
+    pub category: String,
+    /// Action taken once the threshold is crossed
+    pub action: ActionLevel,
+    /// Score at or above which the action is entered
+    pub escalate_at: f32,
+    /// Score below which the action is left again
+    pub deescalate_below: f32,
+}
+
+/// Versioned policy table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdPolicy {
+    /// Policy version recorded in predictions
+    pub version: String,
+    /// Threshold rules
+    pub rules: Vec<CategoryThreshold>,
+}
+
+impl ThresholdPolicy {
+    /// Check thresholds are in range and hysteresis bands are well-formed
+    pub fn validate(&self) -> ColdMirrorResult<()> {
+        if self.version.trim().is_empty() {
+            return Err(ColdMirrorError::ConfigurationError("Policy version must not be empty".into()));
+        }
+        for rule in &self.rules {
+            if !(0.0..=1.0).contains(&rule.escalate_at) || !(0.0..=1.0).contains(&rule.deescalate_below) {
+                return Err(ColdMirrorError::ConfigurationError(
+                    format!("{} {:?}: thresholds must be within 0.0..=1.0", rule.category, rule.action)
+                ));
+            }
+            if rule.deescalate_below > rule.escalate_at {
+                return Err(ColdMirrorError::ConfigurationError(
+                    format!("{} {:?}: de-escalate threshold above escalate threshold", rule.category, rule.action)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load and validate a policy from a JSON file
+    pub fn from_file(path: &Path) -> ColdMirrorResult<Self> {
+        let contents = std::fs::read(path)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        let policy: ThresholdPolicy = decode::json(&contents, &DecodeLimits::FILE)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    fn rules_for<'a>(&'a self, category: &'a str) -> impl Iterator<Item = &'a CategoryThreshold> + 'a {
+        self.rules.iter().filter(move |rule| rule.category == category)
+    }
+}
+
+impl Default for ThresholdPolicy {
+    fn default() -> Self {
+        Self {
+            version: "default-1".to_string(),
+            rules: Taxonomy::core().default_rules(),
+        }
+    }
+}
+
+/// Name of a harm category as used in policy rules
+pub fn category_name(category: &HarmCategory) -> &str {
+    match category {
+        HarmCategory::MoralDegradation { .. } => "MoralDegradation",
+        HarmCategory::PhysicalHarm { .. } => "PhysicalHarm",
+        HarmCategory::PsychologicalHarm { .. } => "PsychologicalHarm",
+        HarmCategory::SocialHarm { .. } => "SocialHarm",
+        HarmCategory::SpiritualHarm { .. } => "SpiritualHarm",
+        HarmCategory::Custom { id, .. } => id,
+    }
+}
+
+/// Score (0.0 to 1.0) of a harm category compared against thresholds
+pub fn category_score(category: &HarmCategory) -> f32 {
+    match category {
+        HarmCategory::MoralDegradation { severity, .. } => *severity,
+        HarmCategory::PhysicalHarm { likelihood, .. } => *likelihood,
+        HarmCategory::PsychologicalHarm { long_term_impact, .. } => *long_term_impact,
+        HarmCategory::SocialHarm { scale, .. } => match scale {
+            ImpactScale::Individual => 0.2,
+            ImpactScale::Family => 0.35,
+            ImpactScale::Community => 0.5,
+            ImpactScale::Regional => 0.7,
+            ImpactScale::National => 0.85,
+            ImpactScale::Global => 1.0,
+        },
+        HarmCategory::SpiritualHarm { eternal_impact, .. } => *eternal_impact,
+        HarmCategory::Custom { score, .. } => *score,
+    }
+}
+
+/// Copy of a harm category rescored so `category_score` returns about `score`
+///
+/// Social harm is scored by its scale, so it takes the largest scale not
+/// scoring above `score`.
+pub fn with_category_score(category: &HarmCategory, score: f32) -> HarmCategory {
+    let score = score.clamp(0.0, 1.0);
+    let mut rescored = category.clone();
+    match &mut rescored {
+        HarmCategory::MoralDegradation { severity, .. } => *severity = score,
+        HarmCategory::PhysicalHarm { likelihood, .. } => *likelihood = score,
+        HarmCategory::PsychologicalHarm { long_term_impact, .. } => *long_term_impact = score,
+        HarmCategory::SocialHarm { scale, .. } => {
+            *scale = match score {
+                s if s >= 1.0 => ImpactScale::Global,
+                s if s >= 0.85 => ImpactScale::National,
+                s if s >= 0.7 => ImpactScale::Regional,
+                s if s >= 0.5 => ImpactScale::Community,
+                s if s >= 0.35 => ImpactScale::Family,
+                _ => ImpactScale::Individual,
+            }
+        }
+        HarmCategory::SpiritualHarm { eternal_impact, .. } => *eternal_impact = score,
+        HarmCategory::Custom { score: custom, .. } => *custom = score,
+    }
+    rescored
+}
+
+/// Action chosen for a prediction
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionSelection {
+    /// Overall action level
+    pub level: ActionLevel,
+    /// Category that drove the decision, if any
+    pub category: Option<String>,
+    /// Concrete recommended action
+    pub action: RecommendedAction,
+    /// Version of the policy that decided
+    pub policy_version: String,
+}
+
+/// Applies a threshold policy with per-subject hysteresis state
+pub struct ActionPolicy {
+    policy: RwLock<ThresholdPolicy>,
+    taxonomy: Taxonomy,
+    /// Current level per (subject, category)
+    state: Mutex<HashMap<(String, String), ActionLevel>>,
+    source: Option<PathBuf>,
+    loaded_at: Mutex<Option<SystemTime>>,
+}
+
+impl ActionPolicy {
+    /// Create from an in-memory policy
+    pub fn new(policy: ThresholdPolicy) -> ColdMirrorResult<Self> {
+        policy.validate()?;
+        Ok(Self {
+            policy: RwLock::new(policy),
+            taxonomy: Taxonomy::core(),
+            state: Mutex::new(HashMap::new()),
+            source: None,
+            loaded_at: Mutex::new(None),
+        })
+    }
+
+    /// Create from a policy file that can later be hot-reloaded
+    pub fn from_file(path: &Path) -> ColdMirrorResult<Self> {
+        let policy = ThresholdPolicy::from_file(path)?;
+        let mut action_policy = Self::new(policy)?;
+        action_policy.source = Some(path.to_path_buf());
+        *action_policy.loaded_at.get_mut().unwrap_or_else(|e| e.into_inner()) = modified(path);
+        Ok(action_policy)
+    }
+
+    /// Create from `ColdMirrorConfig::action_policy_path` and
+    /// `taxonomy_path`, or the built-in defaults
+    pub fn from_config(config: &ColdMirrorConfig) -> ColdMirrorResult<Self> {
+        let policy = match &config.action_policy_path {
+            Some(path) => Self::from_file(Path::new(path))?,
+            None => Self::new(ThresholdPolicy::default())?,
+        };
+        Ok(match &config.taxonomy_path {
+            Some(path) => policy.with_taxonomy(Taxonomy::from_file(Path::new(path))?),
+            None => policy,
+        })
+    }
+
+    /// Use an extended taxonomy for categories without rules of their own
+    pub fn with_taxonomy(mut self, taxonomy: Taxonomy) -> Self {
+        self.taxonomy = taxonomy;
+        self
+    }
+
+    /// Taxonomy used to resolve category fallbacks
+    pub fn taxonomy(&self) -> &Taxonomy {
+        &self.taxonomy
+    }
+
+    /// Version of the active policy
+    pub fn version(&self) -> String {
+        self.read_policy().version.clone()
+    }
+
+    /// Replace the active policy; hysteresis state is kept
+    pub fn replace(&self, policy: ThresholdPolicy) -> ColdMirrorResult<()> {
+        policy.validate()?;
+        log::info!("Action policy updated: {} -> {}", self.version(), policy.version);
+        *self.policy.write().unwrap_or_else(|e| e.into_inner()) = policy;
+        Ok(())
+    }
+
+    /// Reload the policy file if it changed since it was last loaded
+    ///
+    /// Returns `true` when a new policy was installed. An invalid file leaves
+    /// the active policy in place.
+    pub fn reload_if_changed(&self) -> ColdMirrorResult<bool> {
+        let Some(path) = &self.source else {
+            return Ok(false);
+        };
+        let current = modified(path);
+        let mut loaded_at = self.loaded_at.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_none() || current == *loaded_at {
+            return Ok(false);
+        }
+
+        let policy = ThresholdPolicy::from_file(path)?;
+        self.replace(policy)?;
+        *loaded_at = current;
+        Ok(true)
+    }
+
+    /// Select the action for a subject's detected harm categories
+    pub fn select(&self, subject: &str, categories: &[HarmCategory]) -> ActionSelection {
+        let policy = self.read_policy();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut overall = ActionLevel::Allow;
+        let mut driver: Option<(String, f32)> = None;
+
+        for category in categories {
+            let name = category_name(category);
+            let score = category_score(category);
+            let key = (subject.to_string(), name.to_string());
+            let current = state.get(&key).copied().unwrap_or(ActionLevel::Allow);
+            let rules = self.effective_rules(&policy, name);
+
+            // Enter any level whose escalate threshold is reached, and hold
+            // the current level until its score drops below de-escalation
+            let escalated = rules.iter()
+                .filter(|rule| score >= rule.escalate_at)
+                .map(|rule| rule.action)
+                .max()
+                .unwrap_or(ActionLevel::Allow);
+            let held = rules.iter()
+                .filter(|rule| rule.action <= current && score >= rule.deescalate_below)
+                .map(|rule| rule.action)
+                .max()
+                .unwrap_or(ActionLevel::Allow);
+            let level = escalated.max(held);
+
+            if level != current {
+                log::debug!("{} {} {:?} -> {:?} at score {:.2}", subject, name, current, level, score);
+            }
+            if level == ActionLevel::Allow {
+                state.remove(&key);
+            } else {
+                state.insert(key, level);
+            }
+
+            if level > overall {
+                overall = level;
+                driver = Some((name.to_string(), score));
+            }
+        }
+
+        ActionSelection {
+            level: overall,
+            action: recommended_action(overall, driver.as_ref()),
+            category: driver.map(|(name, _)| name),
+            policy_version: policy.version.clone(),
+        }
+    }
+
+    /// Select the action for detected harm categories without hysteresis
+    ///
+    /// Only escalate thresholds count and no subject state is read or
+    /// recorded, for predictors that map their own verdict to an action.
+    pub fn classify(&self, categories: &[HarmCategory]) -> ActionSelection {
+        let policy = self.read_policy();
+        let mut overall = ActionLevel::Allow;
+        let mut driver: Option<(String, f32)> = None;
+
+        for category in categories {
+            let name = category_name(category);
+            let score = category_score(category);
+            let level = self.effective_rules(&policy, name).iter()
+                .filter(|rule| score >= rule.escalate_at)
+                .map(|rule| rule.action)
+                .max()
+                .unwrap_or(ActionLevel::Allow);
+            if level > overall {
+                overall = level;
+                driver = Some((name.to_string(), score));
+            }
+        }
+
+        ActionSelection {
+            level: overall,
+            action: recommended_action(overall, driver.as_ref()),
+            category: driver.map(|(name, _)| name),
+            policy_version: policy.version.clone(),
+        }
+    }
+
+    /// Current hysteresis levels of a subject, by category
+    pub fn levels(&self, subject: &str) -> BTreeMap<String, ActionLevel> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.iter()
+            .filter(|((held_by, _), _)| held_by == subject)
+            .map(|((_, category), level)| (category.clone(), *level))
+            .collect()
+    }
+
+    /// Replace a subject's hysteresis levels, e.g. to replay a recorded decision
+    pub fn restore_levels(&self, subject: &str, levels: &BTreeMap<String, ActionLevel>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.retain(|(held_by, _), _| held_by != subject);
+        for (category, level) in levels {
+            if *level != ActionLevel::Allow {
+                state.insert((subject.to_string(), category.clone()), *level);
+            }
+        }
+    }
+
+    /// Apply the policy to a prediction, recording the policy version
+    pub fn apply(&self, subject: &str, prediction: &mut HarmPrediction) -> ActionSelection {
+        let selection = self.select(subject, &prediction.harm_categories);
+        prediction.recommended_action = selection.action.clone();
+        prediction.policy_version = Some(selection.policy_version.clone());
+        selection
+    }
+
+    /// Rules of the category, else of its nearest ancestor that has any
+    ///
+    /// At each step the policy table wins over taxonomy default thresholds.
+    fn effective_rules(&self, policy: &ThresholdPolicy, name: &str) -> Vec<CategoryThreshold> {
+        for category in self.taxonomy.lineage(name) {
+            let rules: Vec<CategoryThreshold> = policy.rules_for(category).cloned().collect();
+            if !rules.is_empty() {
+                return rules;
+            }
+            if let Some(definition) = self.taxonomy.get(category) {
+                if !definition.default_thresholds.is_empty() {
+                    return definition.default_thresholds.iter()
+                        .map(|threshold| CategoryThreshold {
+                            category: category.to_string(),
+                            action: threshold.action,
+                            escalate_at: threshold.escalate_at,
+                            deescalate_below: threshold.deescalate_below,
+                        })
+                        .collect();
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn read_policy(&self) -> std::sync::RwLockReadGuard<'_, ThresholdPolicy> {
+        self.policy.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn recommended_action(level: ActionLevel, driver: Option<&(String, f32)>) -> RecommendedAction {
+    match level {
+        ActionLevel::Allow => RecommendedAction::AllowWithMonitoring {
+            monitoring_level: MonitoringLevel::Basic,
+            review_interval: 24.0,
+        },
+        ActionLevel::Monitor => RecommendedAction::AllowWithMonitoring {
+            monitoring_level: MonitoringLevel::Enhanced,
+            review_interval: 4.0,
+        },
+        ActionLevel::Quarantine => RecommendedAction::Quarantine {
+            priority: ReviewPriority::High,
+            max_duration: 24.0,
+        },
+        ActionLevel::Block => RecommendedAction::Block {
+            reason: match driver {
+                Some((category, score)) => format!("{} score {:.2} exceeds block threshold", category, score),
+                None => "Harm score exceeds block threshold".to_string(),
+            },
+            duration: None,
+        },
+        ActionLevel::Purge => RecommendedAction::Purge {
+            urgency: UrgencyLevel::Critical,
+            escalate: true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn physical(likelihood: f32) -> Vec<HarmCategory> {
+        vec![HarmCategory::PhysicalHarm {
+            harm_type: "violence".to_string(),
+            victim_count: None,
+            likelihood,
+        }]
+    }
+
+    #[test]
+    fn test_hysteresis_prevents_flapping() {
+        let policy = ActionPolicy::new(ThresholdPolicy::default()).unwrap();
+
+        assert_eq!(policy.select("subject", &physical(0.81)).level, ActionLevel::Block);
+        // Just under the escalate threshold: still blocked
+        assert_eq!(policy.select("subject", &physical(0.78)).level, ActionLevel::Block);
+        assert_eq!(policy.select("subject", &physical(0.72)).level, ActionLevel::Block);
+        // Below the de-escalate threshold: drops to quarantine
+        assert_eq!(policy.select("subject", &physical(0.65)).level, ActionLevel::Quarantine);
+        // A fresh subject at the same score is only quarantined
+        assert_eq!(policy.select("other", &physical(0.78)).level, ActionLevel::Quarantine);
+    }
+
+    #[test]
+    fn test_policy_version_recorded_and_validated() {
+        let policy = ActionPolicy::new(ThresholdPolicy::default()).unwrap();
+        let mut prediction = HarmPrediction {
+            harm_level: 0.9,
+            confidence: 0.9,
+            time_horizon: 24.0,
+            harm_categories: physical(0.97),
+            risk_factors: vec![],
+            recommended_action: RecommendedAction::AllowWithMonitoring {
+                monitoring_level: MonitoringLevel::Basic,
+                review_interval: 24.0,
+            },
+            timestamp: chrono::Utc::now(),
+            model_version: "v1.0".to_string(),
+            policy_version: None,
+        };
+
+        policy.apply("subject", &mut prediction);
+        assert!(matches!(prediction.recommended_action, RecommendedAction::Purge { .. }));
+        assert_eq!(prediction.policy_version.as_deref(), Some("default-1"));
+
+        let inverted = ThresholdPolicy {
+            version: "bad".to_string(),
+            rules: vec![CategoryThreshold {
+                category: "PhysicalHarm".to_string(),
+                action: ActionLevel::Block,
+                escalate_at: 0.5,
+                deescalate_below: 0.6,
+            }],
+        };
+        assert!(policy.replace(inverted).is_err());
+        assert_eq!(policy.version(), "default-1");
+    }
+
+    #[test]
+    fn test_hot_reload_from_file() {
+        let path = std::env::temp_dir().join(format!("cold_mirror_policy_{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_string(&ThresholdPolicy::default()).unwrap()).unwrap();
+        let policy = ActionPolicy::from_file(&path).unwrap();
+        assert!(!policy.reload_if_changed().unwrap());
+
+        let strict = ThresholdPolicy {
+            version: "strict-2".to_string(),
+            rules: vec![CategoryThreshold {
+                category: "PhysicalHarm".to_string(),
+                action: ActionLevel::Block,
+                escalate_at: 0.1,
+                deescalate_below: 0.05,
+            }],
+        };
+        std::fs::write(&path, serde_json::to_string(&strict).unwrap()).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+
+        assert!(policy.reload_if_changed().unwrap());
+        assert_eq!(policy.select("subject", &physical(0.2)).policy_version, "strict-2");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_extension_category_falls_back_to_parent() {
+        let mut taxonomy = Taxonomy::core();
+        taxonomy.register(crate::taxonomy::CategoryDefinition {
+            id: "FinancialFraud".to_string(),
+            description: String::new(),
+            parent: Some("MoralDegradation".to_string()),
+            default_thresholds: vec![],
+        }).unwrap();
+        let policy = ActionPolicy::new(ThresholdPolicy::default()).unwrap().with_taxonomy(taxonomy);
+        let fraud = |score| vec![HarmCategory::Custom {
+            id: "FinancialFraud".to_string(),
+            score,
+            details: Default::default(),
+        }];
+
+        let selection = policy.select("subject", &fraud(0.85));
+        assert_eq!(selection.level, ActionLevel::Block);
+        assert_eq!(selection.category.as_deref(), Some("FinancialFraud"));
+        // Unknown categories without rules are allowed, not rejected
+        let unknown = vec![HarmCategory::Custom { id: "Unregistered".into(), score: 0.99, details: Default::default() }];
+        assert_eq!(policy.select("subject", &unknown).level, ActionLevel::Allow);
+    }
+}
@@ -0,0 +1,820 @@
+//! Quarantine Store - Sealed Content Awaiting Human Review
+//! "Keep thy heart with all diligence; for out of it are the issues of life" - Proverbs 4:23
+//!
+//! Content held by an `ActionLevel::Quarantine` verdict is sealed with
+//! ChaCha20-Poly1305 under a random data key of its own. The data key is
+//! wrapped under the quarantine key, which the firmware keystore derives
+//! from the device master key (`CryptoContext::quarantine_key`) and hands
+//! over through `ark_quarantine_key` when the `firmware-keystore` feature
+//! links the firmware; the key never touches the host's disk. Entry
+//! metadata stays readable so queues can be listed without decrypting
+//! anything; the entry id, event id and key id are bound to the ciphertext
+//! as associated data.
+//!
+//! Only reviewers in the `ReviewerRegistry` may open an entry, and only
+//! reviewers trusted to purge may destroy one, and then only once a purge
+//! verdict is recorded against that entry. Destruction crypto-shreds the
+//! entry: its keyring slot is overwritten in place and synced before the
+//! sealed entry is deleted, so copies of the ciphertext left behind by the
+//! store no longer open. Every access - sealing, verdict, review, purge,
+//! granted or denied - is appended to the access log as one JSON line.
+//!
+//! Entries and the access log are kept in a store (see `ark_storage`):
+//! sealed entries in the `quarantine.entries` tree keyed by entry id, the
+//! access log in `quarantine.access`. Wrapped data keys live outside the
+//! store in a keyring file of fixed-size slots, since a store may keep old
+//! versions of a value. A quarantine opened on a directory uses the files
+//! backend there and takes in the `.sealed` files and access log left by
+//! earlier versions, resealing each entry under a data key of its own.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ark_storage::{Batch, FileStorage, Storage};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use ethics_dsl::{EthicsDecision, EthicsEvent};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::policy::ActionLevel;
+use crate::{ColdMirrorError, ColdMirrorResult};
+
+/// Access log file name inside a quarantine directory of earlier versions
+pub const ACCESS_LOG: &str = "access.log";
+
+/// Extension of sealed entry files of earlier versions
+pub const SEALED_EXTENSION: &str = "sealed";
+
+/// Tree of sealed entries
+pub const ENTRIES_TREE: &str = "quarantine.entries";
+
+/// Tree of access records
+pub const ACCESS_TREE: &str = "quarantine.access";
+
+/// Keyring file inside a quarantine directory
+pub const KEYRING_FILE: &str = "quarantine.keys";
+
+/// Most reviewers in one registry
+pub const MAX_REVIEWERS: usize = 256;
+
+/// Longest reviewer identity
+pub const MAX_REVIEWER_ID: usize = 128;
+
+/// Largest sealed entry read back from disk
+const ENTRY_LIMITS: DecodeLimits = DecodeLimits::new(16 * 1024 * 1024, 32);
+
+/// Domain tag of the associated data bound to each entry
+const ENTRY_AAD_DOMAIN: &[u8] = b"ark-cold-mirror-quarantine-v1";
+
+/// Domain tag of the associated data bound to each wrapped data key
+const KEY_AAD_DOMAIN: &[u8] = b"ark-cold-mirror-quarantine-key-v1";
+
+/// Length of an entry id
+const ENTRY_ID_LEN: usize = 32;
+
+/// Keyring slot: entry id, nonce and the data key sealed under the
+/// quarantine key; an all-zero slot is free
+const SLOT_LEN: usize = ENTRY_ID_LEN + 12 + 32 + 16;
+
+#[cfg(feature = "firmware-keystore")]
+extern "C" {
+    /// `api::ark_quarantine_key` as exported by the ARK firmware
+    fn ark_quarantine_key(key: *mut u8, key_len: usize) -> i32;
+}
+
+/// Symmetric key sealing quarantined content
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct QuarantineKey {
+    bytes: [u8; 32],
+}
+
+impl QuarantineKey {
+    #[cfg(any(test, feature = "firmware-keystore"))]
+    fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self { bytes }
+    }
+
+    /// Quarantine key derived by the firmware keystore from the device master key
+    #[cfg(feature = "firmware-keystore")]
+    pub fn from_keystore() -> ColdMirrorResult<Self> {
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        // SAFETY: `bytes` is writable for its 32 bytes
+        let code = unsafe { ark_quarantine_key(bytes.as_mut_ptr(), bytes.len()) };
+        if code != 0 {
+            return Err(ColdMirrorError::QuarantineError(format!(
+                "Firmware keystore did not release the quarantine key (code {})", code
+            )));
+        }
+        Ok(Self::from_bytes(*bytes))
+    }
+
+    /// Key identifier: the first 16 bytes of its BLAKE3 hash in hex,
+    /// matching the firmware's `SecureKey::key_id`
+    pub fn key_id(&self) -> String {
+        blake3::hash(&self.bytes).to_hex()[..32].to_string()
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.bytes))
+    }
+}
+
+/// Reviewer allowed to open quarantined entries
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reviewer {
+    /// Reviewer identity recorded in the access log
+    pub id: String,
+    /// BLAKE3 hash of the reviewer's secret, in hex
+    pub secret_blake3: String,
+    /// Whether the reviewer may confirm purges and destroy entries
+    #[serde(default)]
+    pub may_purge: bool,
+}
+
+/// Identity a reviewer presents to open or destroy an entry
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct ReviewerCredential {
+    /// Reviewer identity
+    pub reviewer: String,
+    /// Reviewer secret
+    pub secret: String,
+}
+
+impl ReviewerCredential {
+    /// Credential for `reviewer`
+    pub fn new(reviewer: impl Into<String>, secret: impl Into<String>) -> Self {
+        Self { reviewer: reviewer.into(), secret: secret.into() }
+    }
+}
+
+/// Reviewers authorized to access the quarantine
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewerRegistry {
+    /// Authorized reviewers
+    #[serde(default)]
+    pub reviewers: Vec<Reviewer>,
+}
+
+impl Validate for ReviewerRegistry {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("reviewers", self.reviewers.len(), MAX_REVIEWERS)?;
+        for reviewer in &self.reviewers {
+            decode::check_identifier("reviewer", &reviewer.id, MAX_REVIEWER_ID)?;
+            blake3::Hash::from_hex(&reviewer.secret_blake3)
+                .map_err(|_| format!("reviewer {:?} has a malformed secret hash", reviewer.id))?;
+        }
+        Ok(())
+    }
+}
+
+impl ReviewerRegistry {
+    /// Load and validate a reviewer registry file
+    pub fn load(path: &Path) -> ColdMirrorResult<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        decode::json_validated(&bytes, &DecodeLimits::FILE)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Add a reviewer holding `secret`
+    pub fn add(&mut self, id: &str, secret: &str, may_purge: bool) {
+        self.reviewers.retain(|reviewer| reviewer.id != id);
+        self.reviewers.push(Reviewer {
+            id: id.to_string(),
+            secret_blake3: blake3::hash(secret.as_bytes()).to_hex().to_string(),
+            may_purge,
+        });
+    }
+
+    /// Reviewer whose identity and secret match `credential`
+    pub fn authenticate(&self, credential: &ReviewerCredential) -> Option<&Reviewer> {
+        let presented = blake3::hash(credential.secret.as_bytes());
+        // blake3::Hash compares in constant time
+        self.reviewers.iter().find(|reviewer| {
+            reviewer.id == credential.reviewer
+                && blake3::Hash::from_hex(&reviewer.secret_blake3).is_ok_and(|hash| hash == presented)
+        })
+    }
+}
+
+/// Why content was quarantined
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuarantineReason {
+    /// Decision of the verdict that held the content
+    pub decision: EthicsDecision,
+    /// Action level chosen by the action policy
+    pub action: ActionLevel,
+    /// Predicted harm level
+    pub harm_level: f32,
+}
+
+#[cfg(feature = "full")]
+impl From<&crate::pipeline::PipelineVerdict> for QuarantineReason {
+    fn from(verdict: &crate::pipeline::PipelineVerdict) -> Self {
+        Self {
+            decision: verdict.decision.clone(),
+            action: verdict.action,
+            harm_level: verdict.prediction.harm_level,
+        }
+    }
+}
+
+/// Readable metadata of a sealed entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryInfo {
+    /// Entry identifier
+    pub id: String,
+    /// Event the content belongs to
+    pub event_id: String,
+    /// When the content was quarantined
+    pub quarantined_at: DateTime<Utc>,
+    /// Key the entry is sealed under
+    pub key_id: String,
+    /// Why the content was quarantined
+    pub reason: QuarantineReason,
+}
+
+/// Sealed entry as stored
+#[derive(Serialize, Deserialize)]
+struct SealedEntry {
+    info: EntryInfo,
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+impl SealedEntry {
+    fn aad(info: &EntryInfo) -> Vec<u8> {
+        let mut aad = ENTRY_AAD_DOMAIN.to_vec();
+        for field in [&info.id, &info.event_id, &info.key_id] {
+            aad.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            aad.extend_from_slice(field.as_bytes());
+        }
+        aad
+    }
+}
+
+/// Quarantined content opened by a reviewer
+#[derive(Debug, Clone)]
+pub struct QuarantinedContent {
+    /// Entry metadata
+    pub info: EntryInfo,
+    /// The quarantined event
+    pub event: EthicsEvent,
+}
+
+/// Operation recorded in the access log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessOperation {
+    /// Content sealed into the quarantine
+    Quarantine,
+    /// Entry opened for review
+    Review,
+    /// Verdict recorded against an entry
+    Verdict,
+    /// Entry destroyed on a confirmed purge
+    Purge,
+}
+
+/// One line of the access log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessRecord {
+    /// When the access happened
+    pub at: DateTime<Utc>,
+    /// Entry accessed
+    pub entry: String,
+    /// Operation attempted
+    pub operation: AccessOperation,
+    /// Reviewer identity presented, if any
+    pub reviewer: Option<String>,
+    /// Whether the access was allowed
+    pub granted: bool,
+    /// Reason for a refusal, the decision of a recorded verdict, or the
+    /// BLAKE3 hash of a destroyed entry
+    pub detail: Option<String>,
+}
+
+/// Wrapped data keys, one fixed-size slot per entry
+///
+/// Slots are rewritten in place through the file itself rather than by
+/// replacing the file, so shredding a key leaves no earlier copy of it in
+/// a renamed-over file.
+struct Keyring {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl Keyring {
+    fn new(path: PathBuf) -> Self {
+        Self { path, lock: Mutex::new(()) }
+    }
+
+    /// Store the wrapped key of entry `id` in the first free slot
+    fn insert(&self, id: &str, nonce: &[u8; 12], wrapped: &[u8]) -> ColdMirrorResult<()> {
+        let mut slot = [0u8; SLOT_LEN];
+        slot[..ENTRY_ID_LEN].copy_from_slice(slot_id(id)?);
+        slot[ENTRY_ID_LEN..ENTRY_ID_LEN + 12].copy_from_slice(nonce);
+        slot[ENTRY_ID_LEN + 12..].copy_from_slice(wrapped);
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut file, slots) = self.open()?;
+        let offset = slots.chunks_exact(SLOT_LEN)
+            .position(|existing| existing.iter().all(|b| *b == 0))
+            .unwrap_or(slots.len() / SLOT_LEN) * SLOT_LEN;
+        self.write_slot(&mut file, offset, &slot)
+    }
+
+    /// Nonce and wrapped key of entry `id`
+    fn get(&self, id: &str) -> ColdMirrorResult<Option<([u8; 12], Vec<u8>)>> {
+        let id = slot_id(id)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (_, slots) = self.open()?;
+        Ok(slots.chunks_exact(SLOT_LEN).find(|slot| &slot[..ENTRY_ID_LEN] == id).map(|slot| {
+            let mut nonce = [0u8; 12];
+            nonce.copy_from_slice(&slot[ENTRY_ID_LEN..ENTRY_ID_LEN + 12]);
+            (nonce, slot[ENTRY_ID_LEN + 12..].to_vec())
+        }))
+    }
+
+    /// Overwrite the slot of entry `id` in place and sync it to disk,
+    /// returning whether there was one
+    fn shred(&self, id: &str) -> ColdMirrorResult<bool> {
+        let id = slot_id(id)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut file, slots) = self.open()?;
+        let Some(index) = slots.chunks_exact(SLOT_LEN).position(|slot| &slot[..ENTRY_ID_LEN] == id) else {
+            return Ok(false);
+        };
+        self.write_slot(&mut file, index * SLOT_LEN, &[0u8; SLOT_LEN])?;
+        Ok(true)
+    }
+
+    fn open(&self) -> ColdMirrorResult<(File, Zeroizing<Vec<u8>>)> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&self.path)
+            .map_err(quarantine_io(&self.path))?;
+        let mut slots = Zeroizing::new(Vec::new());
+        file.read_to_end(&mut slots).map_err(quarantine_io(&self.path))?;
+        if slots.len() % SLOT_LEN != 0 {
+            return Err(ColdMirrorError::QuarantineError(format!(
+                "{}: keyring is not a whole number of slots", self.path.display()
+            )));
+        }
+        Ok((file, slots))
+    }
+
+    fn write_slot(&self, file: &mut File, offset: usize, slot: &[u8; SLOT_LEN]) -> ColdMirrorResult<()> {
+        file.seek(SeekFrom::Start(offset as u64)).map_err(quarantine_io(&self.path))?;
+        file.write_all(slot).map_err(quarantine_io(&self.path))?;
+        file.sync_all().map_err(quarantine_io(&self.path))
+    }
+}
+
+/// Keyring slot id of entry `id`
+fn slot_id(id: &str) -> ColdMirrorResult<&[u8]> {
+    if id.len() != ENTRY_ID_LEN || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ColdMirrorError::QuarantineError(format!("Malformed entry id {:?}", id)));
+    }
+    Ok(id.as_bytes())
+}
+
+/// Encrypted quarantine queue
+pub struct QuarantineStore {
+    storage: Arc<dyn Storage>,
+    keyring: Keyring,
+    key: QuarantineKey,
+    reviewers: ReviewerRegistry,
+}
+
+impl QuarantineStore {
+    /// Open or create the quarantine in `dir`, taking in entries and the
+    /// access log of a quarantine directory of earlier versions
+    pub fn open(dir: impl AsRef<Path>, key: QuarantineKey, reviewers: ReviewerRegistry) -> ColdMirrorResult<Self> {
+        let dir = dir.as_ref();
+        let storage = FileStorage::open(dir).map_err(storage_error)?;
+        let store = Self::with_storage(Arc::new(storage), dir.join(KEYRING_FILE), key, reviewers);
+        store.import_legacy(dir)?;
+        Ok(store)
+    }
+
+    /// Quarantine kept in `storage`, with data keys in the keyring file `keyring`
+    pub fn with_storage(
+        storage: Arc<dyn Storage>,
+        keyring: impl Into<PathBuf>,
+        key: QuarantineKey,
+        reviewers: ReviewerRegistry,
+    ) -> Self {
+        Self { storage, keyring: Keyring::new(keyring.into()), key, reviewers }
+    }
+
+    /// Seal `event` into the quarantine, returning the entry metadata
+    pub fn quarantine(&self, event: &EthicsEvent, reason: QuarantineReason) -> ColdMirrorResult<EntryInfo> {
+        let quarantined_at = Utc::now();
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(event.event_id.as_bytes());
+        hasher.update(&quarantined_at.timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+        let info = EntryInfo {
+            id: hasher.finalize().to_hex()[..32].to_string(),
+            event_id: event.event_id.clone(),
+            quarantined_at,
+            key_id: self.key.key_id(),
+            reason,
+        };
+
+        let plaintext = Zeroizing::new(serde_json::to_vec(event)
+            .map_err(|e| ColdMirrorError::DataError(format!("Event serialization failed: {}", e)))?);
+        let bytes = self.seal(&info, &plaintext)?;
+        self.storage.put(ENTRIES_TREE, info.id.as_bytes(), &bytes).map_err(storage_error)?;
+
+        self.record(&info.id, AccessOperation::Quarantine, None, true, None)?;
+        Ok(info)
+    }
+
+    /// Metadata of every sealed entry, oldest first
+    pub fn entries(&self) -> ColdMirrorResult<Vec<EntryInfo>> {
+        let mut entries = Vec::new();
+        for (id, bytes) in self.storage.scan(ENTRIES_TREE, &[]).map_err(storage_error)? {
+            entries.push(decode_entry(&String::from_utf8_lossy(&id), &bytes)?.info);
+        }
+        entries.sort_by_key(|info| info.quarantined_at);
+        Ok(entries)
+    }
+
+    /// Decrypt entry `id` for an authorized reviewer
+    pub fn review(&self, id: &str, credential: &ReviewerCredential) -> ColdMirrorResult<QuarantinedContent> {
+        self.authorize(id, AccessOperation::Review, credential)?;
+        let sealed = decode_entry(id, &self.sealed_bytes(id)?)?;
+        let data_key = self.data_key(id)?;
+        let plaintext = open_entry(&sealed, &data_key)?;
+        let event = decode::json(&plaintext, &ENTRY_LIMITS)
+            .map_err(|e| ColdMirrorError::DataError(format!("Entry {}: {}", id, e)))?;
+        Ok(QuarantinedContent { info: sealed.info, event })
+    }
+
+    /// Record `reason` as the verdict on entry `id`, as confirmed by an
+    /// authorized reviewer
+    ///
+    /// `purge` destroys an entry only while the verdict recorded against
+    /// it is a purge.
+    pub fn record_verdict(&self, id: &str, credential: &ReviewerCredential, reason: QuarantineReason) -> ColdMirrorResult<EntryInfo> {
+        let reviewer = self.authorize(id, AccessOperation::Verdict, credential)?;
+        let mut sealed = decode_entry(id, &self.sealed_bytes(id)?)?;
+        let decision = match &reason.decision {
+            EthicsDecision::Allow { .. } => "allow",
+            EthicsDecision::Deny { .. } => "deny",
+            EthicsDecision::Purge { .. } => "purge",
+        };
+        // The reason is not part of the associated data, so the ciphertext stands
+        sealed.info.reason = reason;
+        let bytes = bincode::serialize(&sealed)
+            .map_err(|e| ColdMirrorError::DataError(format!("Entry serialization failed: {}", e)))?;
+        self.storage.put(ENTRIES_TREE, id.as_bytes(), &bytes).map_err(storage_error)?;
+        self.record(id, AccessOperation::Verdict, Some(&reviewer.id), true, Some(decision))?;
+        Ok(sealed.info)
+    }
+
+    /// Destroy entry `id` for a reviewer trusted to purge, once a purge
+    /// verdict is recorded against it
+    ///
+    /// The entry's keyring slot is overwritten in place and synced before
+    /// the sealed entry is deleted; the access log keeps the BLAKE3 hash of
+    /// what was destroyed.
+    pub fn purge(&self, id: &str, credential: &ReviewerCredential) -> ColdMirrorResult<AccessRecord> {
+        let reviewer = self.authorize(id, AccessOperation::Purge, credential)?;
+        if !reviewer.may_purge {
+            self.record(id, AccessOperation::Purge, Some(&reviewer.id), false, Some("reviewer may not purge"))?;
+            return Err(ColdMirrorError::AccessDenied(format!("Reviewer {} may not purge", reviewer.id)));
+        }
+        let contents = self.sealed_bytes(id)?;
+        if !matches!(decode_entry(id, &contents)?.info.reason.decision, EthicsDecision::Purge { .. }) {
+            self.record(id, AccessOperation::Purge, Some(&reviewer.id), false, Some("recorded verdict is not purge"))?;
+            return Err(ColdMirrorError::AccessDenied(format!("Entry {} has no recorded purge verdict", id)));
+        }
+
+        let digest = blake3::hash(&contents).to_hex().to_string();
+        self.keyring.shred(id)?;
+        self.storage.delete(ENTRIES_TREE, id.as_bytes()).map_err(storage_error)?;
+
+        self.record(id, AccessOperation::Purge, Some(&reviewer.id), true, Some(&digest))
+    }
+
+    /// Every record of the access log, oldest first
+    pub fn access_log(&self) -> ColdMirrorResult<Vec<AccessRecord>> {
+        self.storage.scan(ACCESS_TREE, &[]).map_err(storage_error)?
+            .into_iter()
+            .map(|(_, line)| decode::json(&line, &DecodeLimits::RECORD)
+                .map_err(|e| ColdMirrorError::DataError(format!("Access record: {}", e))))
+            .collect()
+    }
+
+    /// Authenticate `credential`, logging a refusal
+    fn authorize(&self, id: &str, operation: AccessOperation, credential: &ReviewerCredential) -> ColdMirrorResult<&Reviewer> {
+        match self.reviewers.authenticate(credential) {
+            Some(reviewer) => {
+                if operation == AccessOperation::Review {
+                    self.record(id, operation, Some(&reviewer.id), true, None)?;
+                }
+                Ok(reviewer)
+            }
+            None => {
+                self.record(id, operation, Some(&credential.reviewer), false, Some("unknown reviewer or wrong secret"))?;
+                Err(ColdMirrorError::AccessDenied(format!("{} is not an authorized reviewer", credential.reviewer)))
+            }
+        }
+    }
+
+    fn record(
+        &self,
+        entry: &str,
+        operation: AccessOperation,
+        reviewer: Option<&str>,
+        granted: bool,
+        detail: Option<&str>,
+    ) -> ColdMirrorResult<AccessRecord> {
+        let record = AccessRecord {
+            at: Utc::now(),
+            entry: entry.to_string(),
+            operation,
+            reviewer: reviewer.map(str::to_string),
+            granted,
+            detail: detail.map(str::to_string),
+        };
+        let line = serde_json::to_vec(&record)
+            .map_err(|e| ColdMirrorError::DataError(format!("Access record serialization failed: {}", e)))?;
+        ark_storage::append(self.storage.as_ref(), ACCESS_TREE, &line).map_err(storage_error)?;
+        Ok(record)
+    }
+
+    /// Seal `plaintext` under a fresh data key kept in the keyring
+    fn seal(&self, info: &EntryInfo, plaintext: &[u8]) -> ColdMirrorResult<Vec<u8>> {
+        let mut data_key = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(data_key.as_mut_slice());
+        let mut key_nonce = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut key_nonce);
+        let key_aad = key_aad(&info.id);
+        let wrapped = self.key.cipher()
+            .encrypt(Nonce::from_slice(&key_nonce), Payload { msg: data_key.as_slice(), aad: &key_aad })
+            .map_err(|_| ColdMirrorError::QuarantineError("Key wrapping failed".into()))?;
+
+        let mut nonce = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let aad = SealedEntry::aad(info);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(data_key.as_slice()))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+            .map_err(|_| ColdMirrorError::QuarantineError("Sealing failed".into()))?;
+
+        let sealed = SealedEntry { info: info.clone(), nonce, ciphertext };
+        let bytes = bincode::serialize(&sealed)
+            .map_err(|e| ColdMirrorError::DataError(format!("Entry serialization failed: {}", e)))?;
+        self.keyring.insert(&info.id, &key_nonce, &wrapped)?;
+        Ok(bytes)
+    }
+
+    /// Unwrap the data key of entry `id`
+    fn data_key(&self, id: &str) -> ColdMirrorResult<QuarantineKey> {
+        let (nonce, wrapped) = self.keyring.get(id)?.ok_or_else(|| ColdMirrorError::QuarantineError(format!(
+            "Entry {} has no data key - it was purged or never sealed here", id
+        )))?;
+        let bytes = Zeroizing::new(self.key.cipher()
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &wrapped, aad: &key_aad(id) })
+            .map_err(|_| ColdMirrorError::QuarantineError(format!(
+                "Data key of entry {} does not open - wrong key or tampered keyring", id
+            )))?);
+        let bytes: [u8; 32] = bytes.as_slice().try_into()
+            .map_err(|_| ColdMirrorError::QuarantineError(format!("Data key of entry {} is malformed", id)))?;
+        Ok(QuarantineKey { bytes })
+    }
+
+    fn sealed_bytes(&self, id: &str) -> ColdMirrorResult<Vec<u8>> {
+        self.storage.get(ENTRIES_TREE, id.as_bytes()).map_err(storage_error)?
+            .ok_or_else(|| ColdMirrorError::QuarantineError(format!("No quarantined entry {}", id)))
+    }
+
+    /// Move `.sealed` files and the access log of an earlier quarantine
+    /// directory into the store
+    ///
+    /// Earlier versions sealed entries under the quarantine key itself;
+    /// each is resealed under a data key of its own so it can be shredded.
+    fn import_legacy(&self, dir: &Path) -> ColdMirrorResult<()> {
+        let mut batch = Batch::new();
+        let mut sealed_files = Vec::new();
+        for dirent in std::fs::read_dir(dir).map_err(quarantine_io(dir))? {
+            let path = dirent.map_err(quarantine_io(dir))?.path();
+            if path.extension().is_some_and(|ext| ext == SEALED_EXTENSION) {
+                let bytes = std::fs::read(&path).map_err(quarantine_io(&path))?;
+                let sealed = decode_entry(&path.display().to_string(), &bytes)?;
+                let plaintext = open_entry(&sealed, &self.key)?;
+                let resealed = self.seal(&sealed.info, &plaintext)?;
+                batch.put(ENTRIES_TREE, sealed.info.id.as_bytes(), &resealed);
+                sealed_files.push(path);
+            }
+        }
+
+        let log = dir.join(ACCESS_LOG);
+        let legacy_log = match std::fs::read(&log) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(quarantine_io(&log)(e)),
+        };
+        if let Some(contents) = &legacy_log {
+            if self.storage.last(ACCESS_TREE).map_err(storage_error)?.is_some() {
+                return Err(ColdMirrorError::QuarantineError(format!(
+                    "{} was not imported: the access log store already holds records", log.display()
+                )));
+            }
+            let lines = contents.split(|b| *b == b'\n').filter(|line| !line.is_empty());
+            for (sequence, line) in lines.enumerate() {
+                batch.put(ACCESS_TREE, &ark_storage::sequence_key(sequence as u64), line);
+            }
+        }
+
+        if batch.is_empty() {
+            return Ok(());
+        }
+        self.storage.apply(&batch).map_err(storage_error)?;
+        for path in sealed_files {
+            std::fs::remove_file(&path).map_err(quarantine_io(&path))?;
+        }
+        if legacy_log.is_some() {
+            std::fs::rename(&log, log.with_extension("log.imported")).map_err(quarantine_io(&log))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decrypt `sealed` under `key`
+fn open_entry(sealed: &SealedEntry, key: &QuarantineKey) -> ColdMirrorResult<Zeroizing<Vec<u8>>> {
+    let aad = SealedEntry::aad(&sealed.info);
+    key.cipher()
+        .decrypt(Nonce::from_slice(&sealed.nonce), Payload { msg: &sealed.ciphertext, aad: &aad })
+        .map(Zeroizing::new)
+        .map_err(|_| ColdMirrorError::QuarantineError(format!(
+            "Entry {} does not open - wrong key or tampered entry", sealed.info.id
+        )))
+}
+
+/// Associated data bound to the wrapped data key of entry `id`
+fn key_aad(id: &str) -> Vec<u8> {
+    let mut aad = KEY_AAD_DOMAIN.to_vec();
+    aad.extend_from_slice(id.as_bytes());
+    aad
+}
+
+fn decode_entry(source: &str, bytes: &[u8]) -> ColdMirrorResult<SealedEntry> {
+    decode::bincode(bytes, &ENTRY_LIMITS).map_err(|e| ColdMirrorError::DataError(format!("{}: {}", source, e)))
+}
+
+fn quarantine_io(path: &Path) -> impl Fn(std::io::Error) -> ColdMirrorError + '_ {
+    move |e| ColdMirrorError::QuarantineError(format!("{}: {}", path.display(), e))
+}
+
+fn storage_error(e: ark_storage::StorageError) -> ColdMirrorError {
+    ColdMirrorError::QuarantineError(format!("Quarantine store: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethics_dsl::{Actor, ActorType, Content, ContentType, Context, UrgencyLevel};
+    use std::collections::HashMap;
+
+    fn event(data: &str) -> EthicsEvent {
+        EthicsEvent {
+            event_id: "held".to_string(),
+            actor: Actor { actor_type: ActorType::Content, tags: vec![], trust_level: 0.5, history: None },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn held() -> QuarantineReason {
+        let decision = EthicsDecision::Deny {
+            confidence: 0.7,
+            violation: "harmful words".to_string(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        QuarantineReason { decision, action: ActionLevel::Quarantine, harm_level: 0.7 }
+    }
+
+    #[test]
+    fn test_sealed_entries_open_only_for_reviewers_and_purge_is_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut reviewers = ReviewerRegistry::default();
+        reviewers.add("analyst", "analyst-secret", false);
+        reviewers.add("warden", "warden-secret", true);
+        let store = QuarantineStore::open(dir.path(), QuarantineKey::from_bytes([7u8; 32]), reviewers).unwrap();
+
+        let info = store.quarantine(&event("plainly harmful words"), held()).unwrap();
+        let sealed = store.sealed_bytes(&info.id).unwrap();
+        assert!(!sealed.windows(7).any(|window| window == b"plainly"));
+        assert_eq!(store.entries().unwrap(), std::slice::from_ref(&info));
+
+        assert!(matches!(
+            store.review(&info.id, &ReviewerCredential::new("analyst", "guess")),
+            Err(ColdMirrorError::AccessDenied(_))
+        ));
+        let opened = store.review(&info.id, &ReviewerCredential::new("analyst", "analyst-secret")).unwrap();
+        assert_eq!(opened.event.content.unwrap().data, "plainly harmful words");
+
+        // The key must match the one the entry was sealed under
+        let rekeyed = QuarantineStore::open(dir.path(), QuarantineKey::from_bytes([8u8; 32]), store.reviewers.clone()).unwrap();
+        assert!(rekeyed.review(&info.id, &ReviewerCredential::new("analyst", "analyst-secret")).is_err());
+
+        // Purge needs a purge verdict recorded against this very entry
+        let warden = ReviewerCredential::new("warden", "warden-secret");
+        assert!(store.purge(&info.id, &warden).is_err());
+        let decision = EthicsDecision::Purge {
+            severity: 9,
+            reason: "confirmed on review".to_string(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        let purge = QuarantineReason { decision, action: ActionLevel::Purge, harm_level: 0.9 };
+        let analyst = ReviewerCredential::new("analyst", "analyst-secret");
+        assert_eq!(store.record_verdict(&info.id, &analyst, purge.clone()).unwrap().reason, purge);
+        assert!(store.purge(&info.id, &analyst).is_err());
+        let confirmed = store.sealed_bytes(&info.id).unwrap();
+        let destroyed = store.purge(&info.id, &warden).unwrap();
+        assert_eq!(destroyed.detail, Some(blake3::hash(&confirmed).to_hex().to_string()));
+        assert!(store.entries().unwrap().is_empty());
+
+        // The keyring slot is shredded, so a leftover copy of the entry no longer opens
+        assert!(std::fs::read(dir.path().join(KEYRING_FILE)).unwrap().iter().all(|b| *b == 0));
+        store.storage.put(ENTRIES_TREE, info.id.as_bytes(), &sealed).unwrap();
+        assert!(store.review(&info.id, &analyst).is_err());
+
+        let log = store.access_log().unwrap();
+        let summary: Vec<(AccessOperation, bool)> = log.iter().map(|record| (record.operation, record.granted)).collect();
+        assert_eq!(summary, [
+            (AccessOperation::Quarantine, true),
+            (AccessOperation::Review, false),
+            (AccessOperation::Review, true),
+            (AccessOperation::Review, true),
+            (AccessOperation::Purge, false),
+            (AccessOperation::Verdict, true),
+            (AccessOperation::Purge, false),
+            (AccessOperation::Purge, true),
+            (AccessOperation::Review, true),
+        ]);
+    }
+
+    #[test]
+    fn test_legacy_quarantine_directory_is_imported() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = QuarantineKey::from_bytes([7u8; 32]);
+        let info = EntryInfo {
+            id: "0123456789abcdef0123456789abcdef".to_string(),
+            event_id: "held".to_string(),
+            quarantined_at: Utc::now(),
+            key_id: key.key_id(),
+            reason: held(),
+        };
+
+        // Layout of earlier versions: one file per entry sealed under the
+        // quarantine key itself, and a JSON-lines log
+        let plaintext = serde_json::to_vec(&event("held words")).unwrap();
+        let nonce = [3u8; 12];
+        let ciphertext = key.cipher()
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: &SealedEntry::aad(&info) })
+            .unwrap();
+        let legacy = SealedEntry { info: info.clone(), nonce, ciphertext };
+        let sealed = dir.path().join(&info.id).with_extension(SEALED_EXTENSION);
+        std::fs::write(&sealed, bincode::serialize(&legacy).unwrap()).unwrap();
+        let record = AccessRecord {
+            at: Utc::now(),
+            entry: info.id.clone(),
+            operation: AccessOperation::Quarantine,
+            reviewer: None,
+            granted: true,
+            detail: None,
+        };
+        let mut log = serde_json::to_vec(&record).unwrap();
+        log.push(b'\n');
+        std::fs::write(dir.path().join(ACCESS_LOG), log).unwrap();
+
+        let mut reviewers = ReviewerRegistry::default();
+        reviewers.add("analyst", "analyst-secret", false);
+        let imported = QuarantineStore::open(dir.path(), key, reviewers).unwrap();
+        assert_eq!(imported.entries().unwrap(), std::slice::from_ref(&info));
+        assert_eq!(imported.access_log().unwrap(), [record]);
+        assert!(!sealed.exists());
+        assert!(!dir.path().join(ACCESS_LOG).exists());
+
+        // Resealed under a data key of its own
+        assert_ne!(imported.sealed_bytes(&info.id).unwrap(), bincode::serialize(&legacy).unwrap());
+        let opened = imported.review(&info.id, &ReviewerCredential::new("analyst", "analyst-secret")).unwrap();
+        assert_eq!(opened.event.content.unwrap().data, "held words");
+    }
+}
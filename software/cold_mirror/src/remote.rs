@@ -0,0 +1,626 @@
+//! Remote Predictor - Cold-Mirror as a Service over the Sentinel
+//! "Two are better than one; because they have a good reward for their labour" - Ecclesiastes 4:9
+//!
+//! Constrained devices cannot run the neural model. `RemoteHarmPredictor`
+//! sends batches of `PredictionInput`s to a central Cold-Mirror service over
+//! a `network_sentinel` PQ-TLS connection. Requests are signed with the
+//! device's Dilithium3 key; responses are signed by the service key pinned
+//! on the device and bound to the request nonce. The service refuses
+//! requests whose signed timestamp is more than `MAX_REQUEST_SKEW` away and
+//! remembers each client's nonces for that long, so a captured request
+//! cannot be replayed. Verified predictions are cached locally, keyed on
+//! the whole input. Every failure, a timeout included, is reported as an
+//! error: the device never substitutes a weaker local prediction or an
+//! unverified answer.
+//!
+//! The service side is `serve_connection`, run on each connection the
+//! central service grants for `cold-mirror`; a service shared by several
+//! deployments runs `serve_tenant_connection` instead, which admits each
+//! request through the client's tenant (see `tenancy`). Signed payloads travel as the
+//! exact JSON that was signed, so verification never depends on
+//! re-serializing maps in the same order.
+
+use pq_types::canonical::CanonicalEncoder;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::DilithiumSignatureBytes;
+use pqcrypto_dilithium::{detached_sign, verify_detached_signature, DetachedSignature, PublicKey, SecretKey};
+use pqcrypto_traits::sign::DetachedSignature as _;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use network_sentinel::SentinelClient;
+
+use crate::tenancy::TenantRegistry;
+use crate::{
+    ColdMirrorError, ColdMirrorResult, HarmPredictor, HarmPrediction, ModelMetrics, OutcomeData, PredictionInput,
+    MAX_BATCH_SIZE,
+};
+
+/// Sentinel service name of the central Cold-Mirror service
+pub const REMOTE_SERVICE: &str = "cold-mirror";
+
+/// Largest frame exchanged with the service
+pub const MAX_FRAME_BYTES: usize = 4 * 1024 * 1024;
+
+/// Decoding limits for frames and the payloads inside them
+const WIRE_LIMITS: DecodeLimits = DecodeLimits::new(MAX_FRAME_BYTES, 32);
+
+/// Domain separator for request signatures
+const REQUEST_DOMAIN: &[u8] = b"ark-cold-mirror-remote-request-v3";
+
+/// Domain separator for response signatures
+const RESPONSE_DOMAIN: &[u8] = b"ark-cold-mirror-remote-response-v2";
+
+/// Longest client id and nonce
+const MAX_ID_LENGTH: usize = 128;
+
+/// Furthest a request's timestamp may be from the service clock
+pub const MAX_REQUEST_SKEW: Duration = Duration::from_secs(60);
+
+/// Most nonces a service remembers by default
+pub const DEFAULT_NONCE_CAPACITY: usize = 65_536;
+
+/// Signed batch of prediction inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictionRequest {
+    /// Device identity known to the service
+    pub client_id: String,
+    /// Unique per request; echoed in the response
+    pub nonce: String,
+    /// When the request was signed (Unix seconds)
+    pub issued_at: u64,
+    /// JSON array of `PredictionInput`s, exactly as signed
+    pub payload: String,
+    /// Dilithium3 signature of the client
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl Validate for PredictionRequest {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("client_id", &self.client_id, MAX_ID_LENGTH)?;
+        decode::check_identifier("nonce", &self.nonce, MAX_ID_LENGTH)
+    }
+}
+
+/// Signed predictions answering a request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictionResponse {
+    /// Nonce of the request answered
+    pub nonce: String,
+    /// JSON array of `HarmPrediction`s in request order, exactly as signed
+    pub payload: String,
+    /// Dilithium3 signature of the service
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl Validate for PredictionResponse {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("nonce", &self.nonce, MAX_ID_LENGTH)
+    }
+}
+
+/// Remote predictor settings
+#[derive(Debug, Clone)]
+pub struct RemoteConfig {
+    /// Address of the central service's sentinel
+    pub service: SocketAddr,
+    /// Identity presented to the sentinel and signing requests
+    pub client_id: String,
+    /// Negotiate post-quantum security with the sentinel
+    pub quantum_resistant: bool,
+    /// Time allowed for one batch round trip before falling back
+    pub timeout: Duration,
+    /// Most inputs sent in one request
+    pub max_batch: usize,
+    /// How long verified predictions are reused
+    pub cache_ttl: Duration,
+    /// Most cached predictions
+    pub cache_capacity: usize,
+}
+
+impl RemoteConfig {
+    /// Defaults for a device talking to `service` as `client_id`
+    pub fn new(service: SocketAddr, client_id: impl Into<String>) -> Self {
+        Self {
+            service,
+            client_id: client_id.into(),
+            quantum_resistant: true,
+            timeout: Duration::from_millis(500),
+            max_batch: 32,
+            cache_ttl: Duration::from_secs(600),
+            cache_capacity: 4096,
+        }
+    }
+}
+
+/// Nonces of verified requests whose timestamp is still accepted
+///
+/// One log is shared by every connection a service answers.
+pub struct NonceLog {
+    /// Client and nonce, with the request timestamp
+    seen: Mutex<HashMap<(String, String), u64>>,
+    capacity: usize,
+}
+
+impl Default for NonceLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_NONCE_CAPACITY)
+    }
+}
+
+impl NonceLog {
+    /// Log remembering at most `capacity` nonces
+    pub fn new(capacity: usize) -> Self {
+        Self { seen: Mutex::new(HashMap::new()), capacity }
+    }
+
+    /// Accept a request signed at `issued_at`, refusing stale and replayed ones
+    ///
+    /// Entries that fell out of the skew window are forgotten once the log
+    /// fills up; when it is still full, requests are refused until they age out.
+    fn admit(&self, client_id: &str, nonce: &str, issued_at: u64, now: u64) -> ColdMirrorResult<()> {
+        let skew = MAX_REQUEST_SKEW.as_secs();
+        if issued_at.abs_diff(now) > skew {
+            return Err(ColdMirrorError::RemoteError(format!("Request of {} is stale", client_id)));
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(|e| e.into_inner());
+        let key = (client_id.to_string(), nonce.to_string());
+        if seen.contains_key(&key) {
+            return Err(ColdMirrorError::RemoteError(format!("Replayed request of {}", client_id)));
+        }
+        if seen.len() >= self.capacity {
+            let oldest = now.saturating_sub(skew);
+            seen.retain(|_, issued_at| *issued_at >= oldest);
+            if seen.len() >= self.capacity {
+                return Err(ColdMirrorError::ResourceError("Request nonce log is full".into()));
+            }
+        }
+        seen.insert(key, issued_at);
+        Ok(())
+    }
+}
+
+/// Verified predictions by input key, oldest first
+#[derive(Default)]
+struct PredictionCache {
+    entries: HashMap<String, (HarmPrediction, Instant)>,
+    order: VecDeque<String>,
+}
+
+impl PredictionCache {
+    fn get(&self, key: &str, ttl: Duration) -> Option<HarmPrediction> {
+        self.entries
+            .get(key)
+            .filter(|(_, stored)| stored.elapsed() < ttl)
+            .map(|(prediction, _)| prediction.clone())
+    }
+
+    fn insert(&mut self, key: String, prediction: HarmPrediction, capacity: usize) {
+        if self.entries.insert(key.clone(), (prediction, Instant::now())).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Harm predictor backed by a central Cold-Mirror service
+///
+/// Blocks on its own runtime, so it must not be called from within an
+/// async task.
+pub struct RemoteHarmPredictor {
+    config: RemoteConfig,
+    client_key: SecretKey,
+    service_key: PublicKey,
+    runtime: tokio::runtime::Runtime,
+    cache: Mutex<PredictionCache>,
+    requests: AtomicU64,
+    timeouts: AtomicU64,
+}
+
+impl RemoteHarmPredictor {
+    /// Create a predictor signing with `client_key` and trusting only `service_key`
+    pub fn new(config: RemoteConfig, client_key: SecretKey, service_key: PublicKey) -> ColdMirrorResult<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ColdMirrorError::ResourceError(format!("Remote predictor runtime: {}", e)))?;
+
+        Ok(Self {
+            config,
+            client_key,
+            service_key,
+            runtime,
+            cache: Mutex::new(PredictionCache::default()),
+            requests: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+        })
+    }
+
+    /// Batches the service did not answer in time
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts.load(Ordering::Relaxed)
+    }
+
+    /// Fetch verified predictions for one batch
+    fn predict_remote(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let nonce = self.next_nonce();
+        match self.runtime.block_on(tokio::time::timeout(self.config.timeout, self.fetch(&nonce, inputs))) {
+            Ok(result) => result,
+            Err(_) => {
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                Err(ColdMirrorError::RemoteError(format!("Remote Cold-Mirror timed out after {:?}", self.config.timeout)))
+            }
+        }
+    }
+
+    async fn fetch(&self, nonce: &str, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        let mut client = SentinelClient::new(self.config.quantum_resistant)
+            .with_service(self.config.client_id.clone(), REMOTE_SERVICE);
+        let mut stream = client.connect(self.config.service).await.map_err(|e| remote_error(&e))?;
+        request_predictions(&mut stream, &self.config.client_id, nonce, inputs, &self.client_key, &self.service_key).await
+    }
+
+    /// Request-unique nonce; uniqueness, not secrecy, is what binds a response
+    fn next_nonce(&self) -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(self.config.client_id.as_bytes());
+        hasher.update(&nanos.to_le_bytes());
+        hasher.update(&self.requests.load(Ordering::Relaxed).to_le_bytes());
+        hasher.finalize().to_hex()[..32].to_string()
+    }
+}
+
+impl HarmPredictor for RemoteHarmPredictor {
+    fn predict_harm(&self, input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+        self.predict_harm_batch(std::slice::from_ref(input))?
+            .pop()
+            .ok_or_else(|| ColdMirrorError::RemoteError("Empty response".into()))
+    }
+
+    fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        let keys: Vec<Option<String>> = inputs.iter().map(cache_key).collect();
+        let mut results: Vec<Option<HarmPrediction>> = {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            keys.iter().map(|key| cache.get(key.as_deref()?, self.config.cache_ttl)).collect()
+        };
+
+        let missing: Vec<usize> = (0..inputs.len()).filter(|&index| results[index].is_none()).collect();
+        for chunk in missing.chunks(self.config.max_batch.clamp(1, MAX_BATCH_SIZE)) {
+            let batch: Vec<PredictionInput> = chunk.iter().map(|&index| inputs[index].clone()).collect();
+            let predictions = self.predict_remote(&batch)?;
+
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            for (&index, prediction) in chunk.iter().zip(predictions) {
+                if let Some(key) = &keys[index] {
+                    cache.insert(key.clone(), prediction.clone(), self.config.cache_capacity);
+                }
+                results[index] = Some(prediction);
+            }
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+        // Outcomes are collected and trained on by the central service
+        Ok(())
+    }
+
+    fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+        Ok(ModelMetrics {
+            accuracy: 0.0,
+            precision_by_category: HashMap::new(),
+            recall_by_category: HashMap::new(),
+            avg_inference_time_ms: 0.0,
+            total_predictions: self.requests.load(Ordering::Relaxed),
+            model_version: format!("remote({})", self.config.service),
+            last_updated: chrono::Utc::now(),
+            provenance: ModelMetrics::installed_provenance(),
+        })
+    }
+}
+
+/// Sign a request, send it and verify the response
+async fn request_predictions<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    client_id: &str,
+    nonce: &str,
+    inputs: &[PredictionInput],
+    client_key: &SecretKey,
+    service_key: &PublicKey,
+) -> ColdMirrorResult<Vec<HarmPrediction>> {
+    let payload = serde_json::to_string(inputs).map_err(|e| ColdMirrorError::DataError(e.to_string()))?;
+    let issued_at = unix_secs();
+    let signature = detached_sign(&request_message(client_id, nonce, issued_at, &payload), client_key);
+    let request = PredictionRequest {
+        client_id: client_id.to_string(),
+        nonce: nonce.to_string(),
+        issued_at,
+        payload,
+        signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+            .map_err(|e| ColdMirrorError::RemoteError(e.to_string()))?,
+    };
+    write_frame(stream, &request).await?;
+
+    let response: PredictionResponse = read_frame(stream).await?;
+    if response.nonce != nonce {
+        return Err(ColdMirrorError::RemoteError("Response answers a different request".into()));
+    }
+    verify(&response.signature, &response_message(nonce, &response.payload), service_key)
+        .map_err(|_| ColdMirrorError::RemoteError("Response signature does not match the service key".into()))?;
+
+    let predictions: Vec<HarmPrediction> = decode::json(response.payload.as_bytes(), &WIRE_LIMITS)
+        .map_err(|e| ColdMirrorError::RemoteError(format!("Malformed predictions: {}", e)))?;
+    if predictions.len() != inputs.len() {
+        return Err(ColdMirrorError::RemoteError(format!(
+            "Expected {} predictions, got {}",
+            inputs.len(),
+            predictions.len()
+        )));
+    }
+    Ok(predictions)
+}
+
+/// Answer one signed request on a granted connection
+///
+/// `clients` maps device ids to their Dilithium3 public keys; `nonces` is
+/// shared by every connection of the service. The predictor runs inline,
+/// so a slow model holds up the connection's task.
+pub async fn serve_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    predictor: &dyn HarmPredictor,
+    service_key: &SecretKey,
+    clients: &HashMap<String, PublicKey>,
+    nonces: &NonceLog,
+) -> ColdMirrorResult<()> {
+    answer_request(stream, service_key, clients, nonces, |_, inputs| predictor.predict_harm_batch(inputs)).await
+}
+
+/// Answer one signed request for a tenant of a shared service
+///
+/// The request is admitted against the quota and priority of the client's
+/// tenant and predicted by the tenant's pinned model with its calibration.
+/// Clients outside every tenant and refused requests get no answer.
+pub async fn serve_tenant_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    tenants: &TenantRegistry,
+    service_key: &SecretKey,
+    clients: &HashMap<String, PublicKey>,
+    nonces: &NonceLog,
+) -> ColdMirrorResult<()> {
+    answer_request(stream, service_key, clients, nonces, |client_id, inputs| {
+        tenants.admit(client_id, inputs.len())?.predict(inputs)
+    })
+    .await
+}
+
+/// Read and verify a request, predict with `predict` and send the signed response
+async fn answer_request<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    service_key: &SecretKey,
+    clients: &HashMap<String, PublicKey>,
+    nonces: &NonceLog,
+    predict: impl FnOnce(&str, &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>>,
+) -> ColdMirrorResult<()> {
+    let request: PredictionRequest = read_frame(stream).await?;
+    let client_key = clients
+        .get(&request.client_id)
+        .ok_or_else(|| ColdMirrorError::RemoteError(format!("Unknown client {}", request.client_id)))?;
+    let message = request_message(&request.client_id, &request.nonce, request.issued_at, &request.payload);
+    verify(&request.signature, &message, client_key)
+        .map_err(|_| ColdMirrorError::RemoteError(format!("Request signature of {} does not verify", request.client_id)))?;
+    // Only a verified request may take up room in the nonce log
+    nonces.admit(&request.client_id, &request.nonce, request.issued_at, unix_secs())?;
+
+    let inputs: Vec<PredictionInput> = decode::json(request.payload.as_bytes(), &WIRE_LIMITS)
+        .map_err(|e| ColdMirrorError::RemoteError(format!("Malformed inputs: {}", e)))?;
+    if inputs.len() > MAX_BATCH_SIZE {
+        return Err(ColdMirrorError::RemoteError(format!("Batch of {} exceeds {}", inputs.len(), MAX_BATCH_SIZE)));
+    }
+
+    let predictions = predict(&request.client_id, &inputs)?;
+    let payload = serde_json::to_string(&predictions).map_err(|e| ColdMirrorError::DataError(e.to_string()))?;
+    let signature = detached_sign(&response_message(&request.nonce, &payload), service_key);
+    write_frame(stream, &PredictionResponse {
+        signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+            .map_err(|e| ColdMirrorError::RemoteError(e.to_string()))?,
+        nonce: request.nonce,
+        payload,
+    })
+    .await?;
+
+    log::debug!("Served {} predictions to {}", predictions.len(), request.client_id);
+    Ok(())
+}
+
+fn verify(signature: &DilithiumSignatureBytes, message: &[u8], key: &PublicKey) -> Result<(), ()> {
+    let signature = DetachedSignature::from_bytes(signature.as_bytes()).map_err(|_| ())?;
+    verify_detached_signature(&signature, message, key).map_err(|_| ())
+}
+
+/// Canonical encoding of a request, with the digest of its payload
+fn request_message(client_id: &str, nonce: &str, issued_at: u64, payload: &str) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(REQUEST_DOMAIN);
+    encoder.str(client_id).str(nonce).u64(issued_at).fixed(blake3::hash(payload.as_bytes()).as_bytes());
+    encoder.finish()
+}
+
+/// Canonical encoding of a response, with the digest of its payload
+fn response_message(nonce: &str, payload: &str) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(RESPONSE_DOMAIN);
+    encoder.strs(&[nonce]).fixed(blake3::hash(payload.as_bytes()).as_bytes());
+    encoder.finish()
+}
+
+/// Cache key: digest of the whole input but its event id
+///
+/// Actor, context and history all change a prediction, so a cached answer
+/// is only reused for an input that differs in nothing else. `None` for an
+/// input that cannot be serialized, which is never cached.
+fn cache_key(input: &PredictionInput) -> Option<String> {
+    let mut keyed = input.clone();
+    keyed.event.event_id.clear();
+    serde_json::to_vec(&keyed).ok().map(|bytes| blake3::hash(&bytes).to_hex().to_string())
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+async fn write_frame<W: AsyncWrite + Unpin, T: Serialize>(stream: &mut W, message: &T) -> ColdMirrorResult<()> {
+    let bytes = serde_json::to_vec(message).map_err(|e| ColdMirrorError::DataError(e.to_string()))?;
+    decode::check_size(bytes.len(), &WIRE_LIMITS).map_err(|e| ColdMirrorError::RemoteError(e.to_string()))?;
+    stream.write_u32(bytes.len() as u32).await.map_err(|e| remote_error(&e))?;
+    stream.write_all(&bytes).await.map_err(|e| remote_error(&e))?;
+    stream.flush().await.map_err(|e| remote_error(&e))
+}
+
+async fn read_frame<R: AsyncRead + Unpin, T: DeserializeOwned + Validate>(stream: &mut R) -> ColdMirrorResult<T> {
+    let len = stream.read_u32().await.map_err(|e| remote_error(&e))? as usize;
+    decode::check_size(len, &WIRE_LIMITS).map_err(|e| ColdMirrorError::RemoteError(e.to_string()))?;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await.map_err(|e| remote_error(&e))?;
+    decode::json_validated(&buf, &WIRE_LIMITS).map_err(|e| ColdMirrorError::RemoteError(e.to_string()))
+}
+
+fn remote_error(e: &dyn std::fmt::Display) -> ColdMirrorError {
+    ColdMirrorError::RemoteError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical::LexicalPredictor;
+    use pqcrypto_dilithium::keypair;
+
+    fn input(data: &str) -> PredictionInput {
+        let event = ethics_dsl::EthicsEvent {
+            event_id: "remote".to_string(),
+            actor: ethics_dsl::Actor {
+                actor_type: ethics_dsl::ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: Some(ethics_dsl::Content {
+                content_type: ethics_dsl::ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: ethics_dsl::Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: ethics_dsl::UrgencyLevel::Normal,
+            },
+            timestamp: chrono::Utc::now(),
+        };
+        crate::utils::create_prediction_input(event, None, None)
+    }
+
+    async fn round_trip(signing_key: &SecretKey, pinned: &PublicKey) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        let (client_public, client_secret) = keypair();
+        let clients = HashMap::from([("sensor-1".to_string(), client_public)]);
+        let (mut device, mut service) = tokio::io::duplex(64 * 1024);
+
+        let inputs = vec![input("plan to bomb the bridge"), input("bless this house")];
+        let (served, answered) = tokio::join!(
+            serve_connection(&mut service, &LexicalPredictor::new(), signing_key, &clients, &NonceLog::default()),
+            request_predictions(&mut device, "sensor-1", "nonce-1", &inputs, &client_secret, pinned),
+        );
+        served?;
+        answered
+    }
+
+    #[tokio::test]
+    async fn test_signed_round_trip() {
+        let (service_public, service_secret) = keypair();
+        let predictions = round_trip(&service_secret, &service_public).await.unwrap();
+        assert_eq!(predictions.len(), 2);
+        assert_eq!(predictions[0].harm_categories.len(), 1);
+        assert!(predictions[1].harm_categories.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_response_from_unpinned_key_rejected() {
+        let (service_public, _) = keypair();
+        let (_, impostor_secret) = keypair();
+        assert!(matches!(
+            round_trip(&impostor_secret, &service_public).await,
+            Err(ColdMirrorError::RemoteError(_))
+        ));
+    }
+
+    #[test]
+    fn test_signed_messages_are_frozen() {
+        assert_eq!(blake3::hash(&request_message("sensor-1", "nonce-1", 1_700_000_000, "[]")).to_hex().as_str(), "e8710b517a08505f8a34df17ac1c464110163aed37f38841bf11c906197964ca");
+        assert_eq!(blake3::hash(&response_message("nonce-1", "[]")).to_hex().as_str(), "1067de9e26d1ac090cdf2b2da06fdfd357cdc38763ab125d746e80385583ec4e");
+    }
+
+    #[test]
+    fn test_stale_and_replayed_requests_refused() {
+        let nonces = NonceLog::new(2);
+        let now = 1_700_000_000;
+        nonces.admit("sensor-1", "a", now, now).unwrap();
+        assert!(nonces.admit("sensor-1", "a", now, now).is_err());
+        // The same nonce from another client is its own
+        nonces.admit("sensor-2", "a", now, now).unwrap();
+        assert!(nonces.admit("sensor-1", "b", now - MAX_REQUEST_SKEW.as_secs() - 1, now).is_err());
+
+        // A full log forgets only nonces whose requests are stale anyway
+        assert!(matches!(nonces.admit("sensor-1", "c", now, now), Err(ColdMirrorError::ResourceError(_))));
+        let later = now + MAX_REQUEST_SKEW.as_secs() + 1;
+        nonces.admit("sensor-1", "c", later, later).unwrap();
+    }
+
+    #[test]
+    fn test_cache_key_covers_the_whole_input() {
+        let plain = input("bless this house");
+        let mut renamed = plain.clone();
+        renamed.event.event_id = "other".to_string();
+        assert_eq!(cache_key(&plain), cache_key(&renamed));
+
+        let mut trusted = plain.clone();
+        trusted.event.actor.trust_level = 1.0;
+        assert_ne!(cache_key(&plain), cache_key(&trusted));
+        let mut children = plain.clone();
+        children.event.context.platform = Some("kids".to_string());
+        assert_ne!(cache_key(&plain), cache_key(&children));
+    }
+
+    #[test]
+    fn test_timeout_is_an_error() {
+        // Accepts connections but never speaks
+        let silent = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut config = RemoteConfig::new(silent.local_addr().unwrap(), "sensor-1");
+        config.timeout = Duration::from_millis(100);
+
+        let (_, client_secret) = keypair();
+        let (service_public, _) = keypair();
+        let predictor = RemoteHarmPredictor::new(config, client_secret, service_public).unwrap();
+
+        assert!(matches!(predictor.predict_harm(&input("they will kill")), Err(ColdMirrorError::RemoteError(_))));
+        assert_eq!(predictor.timeouts(), 1);
+
+        // Nothing was cached, so the service is asked again
+        assert!(predictor.predict_harm(&input("they will kill")).is_err());
+        assert_eq!(predictor.timeouts(), 2);
+    }
+}
@@ -0,0 +1,612 @@
+//! Risk Assessment - Composable Scoring Pipeline
+//! "Ponder the path of thy feet, and let all thy ways be established" - Proverbs 4:26
+//!
+//! A risk score is built up by a chain of stages, each a `ScoringStage`
+//! trait object: the base model's prediction, an adjustment for the
+//! actor's history of violations and trust, a multiplier for the
+//! surrounding social and political context, confidence calibration, and
+//! finally the action policy's thresholds. The chain is described by
+//! `RiskAssessmentConfig` in `ColdMirrorConfig`, and further stages can be
+//! inserted anywhere. Every stage records what it did to the harm level,
+//! so an assessment can be explained stage by stage; the contributions are
+//! also attached to the resulting prediction as `risk:<stage>` factors.
+//! With `sanitize_inputs` set the stages see a sanitized copy of the input,
+//! and a `sanitize` contribution records anything sanitization changed.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::policy::{category_score, with_category_score, ActionLevel, ActionPolicy, ActionSelection};
+use crate::{
+    CalibrationConfig, ColdMirrorConfig, ColdMirrorError, ColdMirrorResult, HarmCategory, HarmPrediction, HarmPredictor,
+    MonitoringLevel, PredictionInput, RecommendedAction, RiskFactor,
+};
+use crate::sanitize::{SanitizationReport, Sanitizer};
+
+/// Score threaded through the stages
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskScore {
+    /// Harm level (0.0 to 1.0)
+    pub harm_level: f32,
+    /// Confidence (0.0 to 1.0)
+    pub confidence: f32,
+    /// Detected harm categories
+    pub categories: Vec<HarmCategory>,
+    /// Risk factors reported by the base model
+    pub risk_factors: Vec<RiskFactor>,
+    /// Model behind the base score
+    pub model_version: String,
+    /// Action chosen by a policy stage
+    pub action: Option<ActionSelection>,
+}
+
+impl RiskScore {
+    /// Set the harm level, scaling category scores by the same ratio
+    pub fn set_harm_level(&mut self, harm_level: f32) {
+        let harm_level = harm_level.clamp(0.0, 1.0);
+        if self.harm_level > 0.0 {
+            let ratio = harm_level / self.harm_level;
+            self.map_categories(|score| score * ratio);
+        }
+        self.harm_level = harm_level;
+    }
+
+    /// Apply `f` to every category score
+    pub fn map_categories(&mut self, f: impl Fn(f32) -> f32) {
+        for category in &mut self.categories {
+            *category = with_category_score(category, f(category_score(category)).clamp(0.0, 1.0));
+        }
+    }
+}
+
+/// What one stage did to the score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageContribution {
+    /// Stage name
+    pub stage: String,
+    /// Harm level before the stage
+    pub before: f32,
+    /// Harm level after the stage
+    pub after: f32,
+    /// Human-readable account of the adjustment
+    pub explanation: String,
+}
+
+/// One step of the scoring pipeline
+pub trait ScoringStage: Send + Sync {
+    /// Stage name, used in contributions and risk factors
+    fn name(&self) -> &str;
+
+    /// Adjust the score, returning an explanation of the adjustment
+    fn apply(&self, input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String>;
+}
+
+/// Stage configuration, in pipeline order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum StageConfig {
+    /// Harm level and categories from the configured predictor
+    BaseModel,
+    /// Raise the score for past violations and low trust
+    ActorHistory {
+        /// Increase per violation, scaled by its severity (1-10)
+        per_violation: f32,
+        /// Largest total increase from violations
+        max_increase: f32,
+        /// Weight of the actor's distance from neutral trust (0.5)
+        trust_weight: f32,
+    },
+    /// Multiply the score by context indicators
+    ContextMultiplier {
+        /// Factor per social dynamic, cultural indicator or market condition
+        multipliers: BTreeMap<String, f32>,
+        /// Weight of political instability (1 - stability)
+        instability_weight: f32,
+    },
+    /// Calibrate with `ModelConfig::postprocessing::calibration`
+    Calibration,
+    /// Select the action with the configured action policy
+    PolicyThresholds,
+}
+
+/// Scoring pipeline configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskAssessmentConfig {
+    /// Stages, applied in order
+    pub stages: Vec<StageConfig>,
+}
+
+impl Default for RiskAssessmentConfig {
+    fn default() -> Self {
+        Self {
+            stages: vec![
+                StageConfig::BaseModel,
+                StageConfig::ActorHistory { per_violation: 0.02, max_increase: 0.3, trust_weight: 0.2 },
+                StageConfig::ContextMultiplier { multipliers: BTreeMap::new(), instability_weight: 0.2 },
+                StageConfig::Calibration,
+                StageConfig::PolicyThresholds,
+            ],
+        }
+    }
+}
+
+/// Base score from a harm predictor
+pub struct BaseModelStage {
+    predictor: Arc<dyn HarmPredictor + Send + Sync>,
+}
+
+impl BaseModelStage {
+    /// Stage scoring with `predictor`
+    pub fn new(predictor: Arc<dyn HarmPredictor + Send + Sync>) -> Self {
+        Self { predictor }
+    }
+}
+
+impl ScoringStage for BaseModelStage {
+    fn name(&self) -> &str {
+        "base_model"
+    }
+
+    fn apply(&self, input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String> {
+        let prediction = self.predictor.predict_harm(input)?;
+        score.harm_level = prediction.harm_level.clamp(0.0, 1.0);
+        score.confidence = prediction.confidence;
+        score.categories = prediction.harm_categories;
+        score.risk_factors = prediction.risk_factors;
+        score.model_version = prediction.model_version;
+        Ok(format!("model {} scored {:.3} with confidence {:.2}", score.model_version, score.harm_level, score.confidence))
+    }
+}
+
+/// Adjustment for the actor's violations and trust level
+pub struct ActorHistoryStage {
+    per_violation: f32,
+    max_increase: f32,
+    trust_weight: f32,
+}
+
+impl ActorHistoryStage {
+    /// Stage with the given weights
+    pub fn new(per_violation: f32, max_increase: f32, trust_weight: f32) -> Self {
+        Self { per_violation, max_increase, trust_weight }
+    }
+}
+
+impl ScoringStage for ActorHistoryStage {
+    fn name(&self) -> &str {
+        "actor_history"
+    }
+
+    fn apply(&self, input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String> {
+        let actor = &input.event.actor;
+        let violations = actor.history.as_ref().map(|history| history.violations.as_slice()).unwrap_or_default();
+        let severity: f32 = violations.iter().map(|violation| violation.severity.min(10) as f32 / 10.0).sum();
+        let increase = (severity * self.per_violation * 10.0).min(self.max_increase);
+        // Trust above neutral lowers the score, below neutral raises it
+        let trust = (0.5 - actor.trust_level as f32) * self.trust_weight;
+
+        score.set_harm_level(score.harm_level + increase + trust);
+        Ok(format!("{} violations add {:.3}, trust {:.2} adds {:+.3}", violations.len(), increase, actor.trust_level, trust))
+    }
+}
+
+/// Multiplier for the social, cultural, economic and political context
+pub struct ContextMultiplierStage {
+    multipliers: BTreeMap<String, f32>,
+    instability_weight: f32,
+}
+
+impl ContextMultiplierStage {
+    /// Stage with the given indicator factors
+    pub fn new(multipliers: BTreeMap<String, f32>, instability_weight: f32) -> Self {
+        Self { multipliers, instability_weight }
+    }
+}
+
+impl ScoringStage for ContextMultiplierStage {
+    fn name(&self) -> &str {
+        "context_multiplier"
+    }
+
+    fn apply(&self, input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String> {
+        let context = &input.context;
+        let indicators = context.social_context.iter().flat_map(|social| &social.dynamics)
+            .chain(context.location.iter().flat_map(|location| &location.cultural_indicators))
+            .chain(context.economic_context.iter().flat_map(|economic| &economic.market_conditions));
+
+        let mut factor = 1.0;
+        let mut matched = Vec::new();
+        for indicator in indicators {
+            if let Some(multiplier) = self.multipliers.get(indicator) {
+                factor *= multiplier;
+                matched.push(indicator.as_str());
+            }
+        }
+        if let Some(political) = &context.political_context {
+            factor *= 1.0 + (1.0 - political.stability.clamp(0.0, 1.0)) * self.instability_weight;
+        }
+
+        score.set_harm_level(score.harm_level * factor);
+        Ok(format!("context factor {:.3} from [{}]", factor, matched.join(", ")))
+    }
+}
+
+/// Calibration function for raw scores
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Calibration {
+    /// Scores are used as they are
+    Identity,
+    /// Platt scaling: `sigmoid(a * logit(p) + b)`
+    Platt {
+        /// Slope
+        a: f32,
+        /// Intercept
+        b: f32,
+    },
+    /// Temperature scaling: `sigmoid(logit(p) / temperature)`
+    Temperature(f32),
+}
+
+impl Calibration {
+    /// Calibration described by a `CalibrationConfig`
+    ///
+    /// Platt scaling reads parameters `a` (default 1) and `b` (default 0),
+    /// temperature scaling reads `temperature`.
+    pub fn from_config(config: &CalibrationConfig) -> ColdMirrorResult<Self> {
+        let parameter = |name: &str, default: f32| config.parameters.get(name).copied().unwrap_or(default);
+        match config.method.as_str() {
+            "none" | "identity" => Ok(Self::Identity),
+            "platt" => Ok(Self::Platt { a: parameter("a", 1.0), b: parameter("b", 0.0) }),
+            "temperature" => match parameter("temperature", 1.0) {
+                t if t > 0.0 => Ok(Self::Temperature(t)),
+                t => Err(ColdMirrorError::ConfigurationError(format!("Calibration temperature {} must be positive", t))),
+            },
+            other => Err(ColdMirrorError::ConfigurationError(format!("Unknown calibration method {}", other))),
+        }
+    }
+
+    /// Calibrated score
+    pub fn calibrate(&self, p: f32) -> f32 {
+        let logit = |p: f32| {
+            let p = p.clamp(1e-6, 1.0 - 1e-6);
+            (p / (1.0 - p)).ln()
+        };
+        let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+        match *self {
+            Self::Identity => p,
+            Self::Platt { a, b } => sigmoid(a * logit(p) + b),
+            Self::Temperature(t) => sigmoid(logit(p) / t),
+        }
+    }
+}
+
+/// Calibration of harm level, category scores and confidence
+pub struct CalibrationStage {
+    calibration: Calibration,
+}
+
+impl CalibrationStage {
+    /// Stage applying `calibration`
+    pub fn new(calibration: Calibration) -> Self {
+        Self { calibration }
+    }
+}
+
+impl ScoringStage for CalibrationStage {
+    fn name(&self) -> &str {
+        "calibration"
+    }
+
+    fn apply(&self, _input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String> {
+        let calibration = self.calibration;
+        score.harm_level = calibration.calibrate(score.harm_level);
+        score.map_categories(|p| calibration.calibrate(p));
+        score.confidence = calibration.calibrate(score.confidence);
+        Ok(format!("{:?}", calibration))
+    }
+}
+
+/// Action selection by the policy's category thresholds
+pub struct PolicyThresholdStage {
+    policy: Arc<ActionPolicy>,
+}
+
+impl PolicyThresholdStage {
+    /// Stage deciding with `policy`
+    pub fn new(policy: Arc<ActionPolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl ScoringStage for PolicyThresholdStage {
+    fn name(&self) -> &str {
+        "policy_thresholds"
+    }
+
+    fn apply(&self, input: &PredictionInput, score: &mut RiskScore) -> ColdMirrorResult<String> {
+        let selection = self.policy.select(&input.event.event_id, &score.categories);
+        let explanation = format!(
+            "{:?} under policy {}{}",
+            selection.level,
+            selection.policy_version,
+            selection.category.as_ref().map(|category| format!(", driven by {}", category)).unwrap_or_default()
+        );
+        score.action = Some(selection);
+        Ok(explanation)
+    }
+}
+
+/// Scored input with each stage's contribution
+#[derive(Debug, Clone)]
+pub struct RiskAssessment {
+    /// Final score
+    pub score: RiskScore,
+    /// Contributions in stage order
+    pub contributions: Vec<StageContribution>,
+    /// What sanitization changed, when the pipeline sanitizes
+    pub sanitization: Option<SanitizationReport>,
+}
+
+impl RiskAssessment {
+    /// Action level chosen by the policy stage, if the pipeline has one
+    pub fn action_level(&self) -> Option<ActionLevel> {
+        self.score.action.as_ref().map(|action| action.level)
+    }
+
+    /// Prediction carrying the final score and one `risk:<stage>` factor per stage
+    pub fn into_prediction(self) -> HarmPrediction {
+        let score = self.score;
+        let mut risk_factors = score.risk_factors;
+        risk_factors.extend(self.contributions.into_iter().map(|contribution| RiskFactor {
+            name: format!("risk:{}", contribution.stage),
+            weight: (contribution.after - contribution.before).abs().min(1.0),
+            description: contribution.explanation,
+            evidence: vec![format!("{:.3} -> {:.3}", contribution.before, contribution.after)],
+        }));
+
+        HarmPrediction {
+            harm_level: score.harm_level,
+            confidence: score.confidence,
+            time_horizon: 24.0,
+            harm_categories: score.categories,
+            risk_factors,
+            recommended_action: score.action.as_ref().map(|action| action.action.clone()).unwrap_or(
+                RecommendedAction::AllowWithMonitoring { monitoring_level: MonitoringLevel::Basic, review_interval: 24.0 }
+            ),
+            timestamp: Utc::now(),
+            model_version: score.model_version,
+            policy_version: score.action.map(|action| action.policy_version),
+        }
+    }
+}
+
+/// Ordered chain of scoring stages
+#[derive(Default)]
+pub struct RiskPipeline {
+    stages: Vec<Box<dyn ScoringStage>>,
+    sanitizer: Option<Sanitizer>,
+}
+
+impl RiskPipeline {
+    /// Empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pipeline described by `ColdMirrorConfig::risk_assessment`
+    ///
+    /// The base model stage scores with `predictor`; the policy stage uses
+    /// the configured action policy and taxonomy.
+    pub fn from_config(config: &ColdMirrorConfig, predictor: Arc<dyn HarmPredictor + Send + Sync>) -> ColdMirrorResult<Self> {
+        Self::from_config_with_policy(config, predictor, Arc::new(ActionPolicy::from_config(config)?))
+    }
+
+    /// Pipeline described by `ColdMirrorConfig::risk_assessment`, deciding with `policy`
+    ///
+    /// Sanitizes inputs when `SecurityConfig::sanitize_inputs` is set.
+    pub fn from_config_with_policy(
+        config: &ColdMirrorConfig,
+        predictor: Arc<dyn HarmPredictor + Send + Sync>,
+        policy: Arc<ActionPolicy>,
+    ) -> ColdMirrorResult<Self> {
+        let mut pipeline = Self::new();
+        if config.security.sanitize_inputs {
+            pipeline = pipeline.with_sanitizer(Sanitizer::new(config.security.sanitization.clone()));
+        }
+        for stage in &config.risk_assessment.stages {
+            pipeline = pipeline.with_stage(match stage {
+                StageConfig::BaseModel => Box::new(BaseModelStage::new(predictor.clone())) as Box<dyn ScoringStage>,
+                StageConfig::ActorHistory { per_violation, max_increase, trust_weight } => {
+                    Box::new(ActorHistoryStage::new(*per_violation, *max_increase, *trust_weight))
+                }
+                StageConfig::ContextMultiplier { multipliers, instability_weight } => {
+                    Box::new(ContextMultiplierStage::new(multipliers.clone(), *instability_weight))
+                }
+                StageConfig::Calibration => {
+                    Box::new(CalibrationStage::new(Calibration::from_config(&config.model_config.postprocessing.calibration)?))
+                }
+                StageConfig::PolicyThresholds => Box::new(PolicyThresholdStage::new(policy.clone())),
+            });
+        }
+        Ok(pipeline)
+    }
+
+    /// Sanitize inputs with `sanitizer` before the first stage
+    pub fn with_sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+        self.sanitizer = Some(sanitizer);
+        self
+    }
+    
+    /// Append a stage
+    pub fn with_stage(mut self, stage: Box<dyn ScoringStage>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Insert a stage at `index`, shifting later stages back
+    pub fn insert_stage(&mut self, index: usize, stage: Box<dyn ScoringStage>) {
+        self.stages.insert(index.min(self.stages.len()), stage);
+    }
+
+    /// Names of the stages, in order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Run every stage over `input`, sanitized first if configured
+    pub fn assess(&self, input: &PredictionInput) -> ColdMirrorResult<RiskAssessment> {
+        let mut contributions = Vec::with_capacity(self.stages.len() + 1);
+        let sanitized = self.sanitizer.as_ref().map(|sanitizer| sanitizer.sanitize(input));
+        let (input, sanitization) = match sanitized {
+            Some((ref clean, ref report)) => {
+                if !report.is_clean() {
+                    contributions.push(StageContribution {
+                        stage: "sanitize".to_string(),
+                        before: 0.0,
+                        after: 0.0,
+                        explanation: report.summary(),
+                    });
+                }
+                (clean, Some(report.clone()))
+            }
+            None => (input, None),
+        };
+        
+        let mut score = RiskScore {
+            harm_level: 0.0,
+            confidence: 0.0,
+            categories: Vec::new(),
+            risk_factors: Vec::new(),
+            model_version: String::new(),
+            action: None,
+        };
+        for stage in &self.stages {
+            let before = score.harm_level;
+            let explanation = stage.apply(input, &mut score)?;
+            contributions.push(StageContribution {
+                stage: stage.name().to_string(),
+                before,
+                after: score.harm_level,
+                explanation,
+            });
+        }
+        Ok(RiskAssessment { score, contributions, sanitization })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical::LexicalPredictor;
+    use crate::{utils, PoliticalContext};
+    use ethics_dsl::{Actor, ActorHistory, ActorType, Content, ContentType, Context, UrgencyLevel, Violation};
+    use std::collections::HashMap;
+
+    fn input(trust_level: f64, violations: usize) -> PredictionInput {
+        let event = ethics_dsl::EthicsEvent {
+            event_id: "risk-1".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Human,
+                tags: vec![],
+                trust_level,
+                history: Some(ActorHistory {
+                    violations: (0..violations).map(|_| Violation {
+                        timestamp: Utc::now(),
+                        principle: "TRUTH_OVER_LIES".to_string(),
+                        severity: 5,
+                        description: "deception".to_string(),
+                    }).collect(),
+                    trust_history: vec![],
+                    total_evaluations: violations as u64,
+                }),
+            },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: "plans to kill and murder".to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: Utc::now(),
+        };
+        utils::create_prediction_input(event, None, None)
+    }
+
+    #[test]
+    fn test_stages_compose_and_explain() {
+        let mut config = ColdMirrorConfig::default();
+        config.model_config.postprocessing.calibration.method = "none".to_string();
+        let pipeline = RiskPipeline::from_config(&config, Arc::new(LexicalPredictor::new())).unwrap();
+        assert_eq!(
+            pipeline.stage_names(),
+            ["base_model", "actor_history", "context_multiplier", "calibration", "policy_thresholds"]
+        );
+
+        let trusted = pipeline.assess(&input(0.9, 0)).unwrap();
+        let mut unstable = input(0.1, 3);
+        unstable.context.political_context = Some(PoliticalContext { climate_indicators: vec![], stability: 0.0 });
+        let suspect = pipeline.assess(&unstable).unwrap();
+
+        let base = trusted.contributions[0].after;
+        assert!(base > 0.0);
+        assert_eq!(suspect.contributions[0].after, base);
+        // History and context raise the suspect's score at their own stages
+        assert!(suspect.contributions[1].after > suspect.contributions[1].before);
+        assert!(suspect.contributions[2].after > suspect.contributions[2].before);
+        assert!(trusted.contributions[1].after < trusted.contributions[1].before);
+        assert!(suspect.score.harm_level > trusted.score.harm_level);
+        assert!(suspect.action_level() >= trusted.action_level());
+
+        let prediction = suspect.into_prediction();
+        assert_eq!(prediction.risk_factors.iter().filter(|factor| factor.name.starts_with("risk:")).count(), 5);
+        assert!(prediction.policy_version.is_some());
+    }
+
+    #[test]
+    fn test_sanitized_input_scores_like_plain_text() {
+        let mut config = ColdMirrorConfig::default();
+        config.model_config.postprocessing.calibration.method = "none".to_string();
+        let plain = RiskPipeline::from_config(&config, Arc::new(LexicalPredictor::new())).unwrap()
+            .assess(&input(0.5, 0)).unwrap();
+        assert!(plain.sanitization.as_ref().unwrap().is_clean());
+
+        // Cyrillic look-alikes and a zero-width space hide the words from the lexicon
+        let mut obfuscated = input(0.5, 0);
+        obfuscated.event.content.as_mut().unwrap().data = "plans to k\u{0456}\u{200B}ll and murd\u{0435}r".to_string();
+        let sanitized = RiskPipeline::from_config(&config, Arc::new(LexicalPredictor::new())).unwrap()
+            .assess(&obfuscated).unwrap();
+        assert_eq!(sanitized.score.harm_level, plain.score.harm_level);
+        assert_eq!(sanitized.contributions[0].stage, "sanitize");
+        assert_eq!(sanitized.sanitization.as_ref().unwrap().confusables, 2);
+
+        config.security.sanitize_inputs = false;
+        let unsanitized = RiskPipeline::from_config(&config, Arc::new(LexicalPredictor::new())).unwrap()
+            .assess(&obfuscated).unwrap();
+        assert!(unsanitized.score.harm_level < plain.score.harm_level);
+        assert!(unsanitized.sanitization.is_none());
+    }
+
+    #[test]
+    fn test_calibration_methods() {
+        let config = |method: &str, parameters: &[(&str, f32)]| CalibrationConfig {
+            method: method.to_string(),
+            parameters: parameters.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+        };
+
+        let identity = Calibration::from_config(&config("platt", &[])).unwrap();
+        assert!((identity.calibrate(0.7) - 0.7).abs() < 1e-4);
+        let sharpened = Calibration::from_config(&config("temperature", &[("temperature", 0.5)])).unwrap();
+        assert!(sharpened.calibrate(0.7) > 0.7 && sharpened.calibrate(0.3) < 0.3);
+        assert!(Calibration::from_config(&config("temperature", &[("temperature", 0.0)])).is_err());
+        assert!(Calibration::from_config(&config("isotonic", &[])).is_err());
+    }
+}
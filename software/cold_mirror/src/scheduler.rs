@@ -0,0 +1,454 @@
+//! Urgency Scheduling - Critical Events Before Routine Traffic
+//! "And the last shall be first, and the first last" - Matthew 20:16
+//!
+//! Queued work is served by the urgency of its event rather than arrival
+//! order: one FIFO lane per `UrgencyLevel`, highest lane first. A waiting
+//! entry is promoted one level for every `aging_interval` it has waited, so
+//! a steady stream of routine events cannot starve low-priority work
+//! forever; among equal effective priorities the oldest entry wins. Aging
+//! stops below the critical class: however long it waits, promoted work
+//! never goes ahead of a critical event.
+//!
+//! Inference batches run in chunks. Between chunks the `InferenceQueue`
+//! checks whether more urgent work has arrived and, if so, preempts the
+//! batch: the unfinished inputs go back to their lanes with their original
+//! arrival time and sequence, so they keep their place and the priority
+//! they have aged into, and their dequeue is taken back out of the delay
+//! metrics.
+//!
+//! Queueing delay - arrival to dequeue - is measured per urgency class.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ethics_dsl::UrgencyLevel;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::{ColdMirrorResult, HarmPrediction, HarmPredictor, PredictionInput};
+
+/// Number of urgency levels
+const LANES: usize = 4;
+
+/// Highest level aging can promote an entry to, one below critical
+const AGING_CEILING: usize = LANES - 2;
+
+/// Scheduling settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulingConfig {
+    /// Waiting time after which an entry is promoted one urgency level (milliseconds)
+    pub aging_interval_ms: u64,
+    /// Inputs run between preemption checks of an inference batch
+    pub preemption_chunk: usize,
+}
+
+impl Default for SchedulingConfig {
+    fn default() -> Self {
+        Self {
+            aging_interval_ms: 1_000,
+            preemption_chunk: 32,
+        }
+    }
+}
+
+/// Lane of an urgency level; higher is served first
+pub fn priority(urgency: &UrgencyLevel) -> usize {
+    match urgency {
+        UrgencyLevel::Low => 0,
+        UrgencyLevel::Normal => 1,
+        UrgencyLevel::High => 2,
+        UrgencyLevel::Critical => 3,
+    }
+}
+
+/// Queueing delay of one urgency class
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DelayStats {
+    /// Entries dequeued
+    pub dequeued: u64,
+    /// Mean wait before dequeue
+    pub mean: Duration,
+    /// Longest wait before dequeue
+    pub max: Duration,
+    /// Entries dequeued only after being promoted by aging
+    pub promoted: u64,
+}
+
+/// Snapshot of scheduler metrics
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueMetrics {
+    /// Delay per urgency class, by class name
+    pub delays: BTreeMap<String, DelayStats>,
+    /// Entries waiting
+    pub waiting: usize,
+    /// Inference batches cut short by more urgent work
+    pub preemptions: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct DelayCounter {
+    dequeued: u64,
+    total: Duration,
+    max: Duration,
+    promoted: u64,
+}
+
+/// Queued item with its scheduling state
+#[derive(Debug)]
+pub struct Scheduled<T> {
+    /// Queued work
+    pub item: T,
+    /// Urgency the item was submitted with
+    pub urgency: UrgencyLevel,
+    enqueued_at: Instant,
+    sequence: u64,
+    /// Wait and promotion counted when the entry was last dequeued
+    dequeued: Option<(Duration, bool)>,
+    /// Effective priority the entry was dequeued at
+    rank: usize,
+}
+
+impl<T> Scheduled<T> {
+    /// Time spent in the queue so far
+    pub fn waited(&self) -> Duration {
+        self.enqueued_at.elapsed()
+    }
+
+    /// Effective priority the entry was dequeued at, aging included
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+}
+
+/// Priority queue of work by urgency, with aging
+#[derive(Debug)]
+pub struct PriorityScheduler<T> {
+    lanes: [VecDeque<Scheduled<T>>; LANES],
+    aging_interval: Duration,
+    next_sequence: u64,
+    delays: [DelayCounter; LANES],
+    preemptions: u64,
+}
+
+impl<T> PriorityScheduler<T> {
+    /// Empty scheduler
+    pub fn new(config: &SchedulingConfig) -> Self {
+        Self {
+            lanes: Default::default(),
+            aging_interval: Duration::from_millis(config.aging_interval_ms.max(1)),
+            next_sequence: 0,
+            delays: [DelayCounter::default(); LANES],
+            preemptions: 0,
+        }
+    }
+
+    /// Queue `item` at `urgency`
+    pub fn push(&mut self, urgency: UrgencyLevel, item: T) {
+        let entry = Scheduled {
+            rank: priority(&urgency),
+            item,
+            urgency,
+            enqueued_at: Instant::now(),
+            sequence: self.next_sequence,
+            dequeued: None,
+        };
+        self.next_sequence += 1;
+        self.lanes[priority(&entry.urgency)].push_back(entry);
+    }
+
+    /// Next entry by effective priority, oldest first among equals
+    pub fn pop(&mut self) -> Option<Scheduled<T>> {
+        let now = Instant::now();
+        let lane = (0..LANES)
+            .filter_map(|lane| self.lanes[lane].front().map(|head| (lane, head)))
+            .max_by(|(_, a), (_, b)| {
+                self.effective_priority(a, now)
+                    .cmp(&self.effective_priority(b, now))
+                    .then(b.sequence.cmp(&a.sequence))
+            })
+            .map(|(lane, _)| lane)?;
+        let mut entry = self.lanes[lane].pop_front()?;
+
+        let waited = now.saturating_duration_since(entry.enqueued_at);
+        entry.rank = self.effective_priority(&entry, now);
+        let promoted = entry.rank > lane;
+        entry.dequeued = Some((waited, promoted));
+        let counter = &mut self.delays[lane];
+        counter.dequeued += 1;
+        counter.total += waited;
+        counter.max = counter.max.max(waited);
+        if promoted {
+            counter.promoted += 1;
+        }
+        Some(entry)
+    }
+
+    /// Up to `max` entries in scheduling order
+    pub fn pop_batch(&mut self, max: usize) -> Vec<Scheduled<T>> {
+        std::iter::from_fn(|| self.pop()).take(max).collect()
+    }
+
+    /// Put back entries taken out but not processed, keeping their arrival
+    /// time and sequence, and with them the priority they have aged into
+    ///
+    /// The wait and promotion counted when they were dequeued are taken
+    /// back out of the delay metrics, so each entry counts once.
+    pub fn requeue(&mut self, entries: impl IntoIterator<Item = Scheduled<T>>) {
+        for mut entry in entries {
+            let lane = priority(&entry.urgency);
+            if let Some((waited, promoted)) = entry.dequeued.take() {
+                let counter = &mut self.delays[lane];
+                counter.dequeued = counter.dequeued.saturating_sub(1);
+                counter.total = counter.total.saturating_sub(waited);
+                if promoted {
+                    counter.promoted = counter.promoted.saturating_sub(1);
+                }
+            }
+            let position = self.lanes[lane].partition_point(|queued| queued.sequence < entry.sequence);
+            self.lanes[lane].insert(position, entry);
+        }
+    }
+
+    /// Whether waiting work should preempt work running at `running` priority
+    pub fn should_preempt(&self, running: usize) -> bool {
+        let now = Instant::now();
+        self.lanes
+            .iter()
+            .filter_map(VecDeque::front)
+            .any(|head| self.effective_priority(head, now) > running)
+    }
+
+    /// Entries waiting
+    pub fn len(&self) -> usize {
+        self.lanes.iter().map(VecDeque::len).sum()
+    }
+
+    /// Entries waiting at `urgency`
+    pub fn lane_len(&self, urgency: &UrgencyLevel) -> usize {
+        self.lanes[priority(urgency)].len()
+    }
+
+    /// Drop the newest entry of the least urgent lane below `urgency`
+    ///
+    /// Shed entries are not counted in the delay metrics.
+    pub fn shed_below(&mut self, urgency: &UrgencyLevel) -> Option<Scheduled<T>> {
+        let lane = (0..priority(urgency)).find(|&lane| !self.lanes[lane].is_empty())?;
+        self.lanes[lane].pop_back()
+    }
+
+    /// Whether nothing is waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queueing delay per urgency class
+    pub fn metrics(&self) -> QueueMetrics {
+        let names = ["Low", "Normal", "High", "Critical"];
+        let delays = names
+            .iter()
+            .zip(self.delays.iter())
+            .map(|(name, counter)| {
+                let mean = match counter.dequeued {
+                    0 => Duration::ZERO,
+                    n => counter.total / n as u32,
+                };
+                let stats = DelayStats {
+                    dequeued: counter.dequeued,
+                    mean,
+                    max: counter.max,
+                    promoted: counter.promoted,
+                };
+                (name.to_string(), stats)
+            })
+            .collect();
+        QueueMetrics {
+            delays,
+            waiting: self.len(),
+            preemptions: self.preemptions,
+        }
+    }
+
+    /// Submitted lane plus one level per aging interval waited, aging no
+    /// higher than `AGING_CEILING`
+    fn effective_priority(&self, entry: &Scheduled<T>, now: Instant) -> usize {
+        let lane = priority(&entry.urgency);
+        if lane >= AGING_CEILING {
+            return lane;
+        }
+        let waited = now.saturating_duration_since(entry.enqueued_at);
+        let promotions = (waited.as_nanos() / self.aging_interval.as_nanos()).min(LANES as u128) as usize;
+        (lane + promotions).min(AGING_CEILING)
+    }
+}
+
+/// Harm inference requests served by urgency in preemptible batches
+///
+/// Shared between submitting threads and the inference worker.
+pub struct InferenceQueue {
+    scheduler: Mutex<PriorityScheduler<PredictionInput>>,
+    chunk: usize,
+}
+
+impl InferenceQueue {
+    /// Empty queue
+    pub fn new(config: &SchedulingConfig) -> Self {
+        Self {
+            scheduler: Mutex::new(PriorityScheduler::new(config)),
+            chunk: config.preemption_chunk.max(1),
+        }
+    }
+
+    /// Queue an input at the urgency of its event
+    pub fn submit(&self, input: PredictionInput) {
+        let urgency = input.event.context.urgency.clone();
+        self.lock().push(urgency, input);
+    }
+
+    /// Run up to `max_batch` queued inputs through `predictor`
+    ///
+    /// The batch runs in chunks; when more urgent work is waiting after a
+    /// chunk, the rest of the batch is requeued and only the finished
+    /// results are returned. Results are keyed by event id.
+    pub fn run_batch<P: HarmPredictor>(
+        &self,
+        predictor: &P,
+        max_batch: usize,
+    ) -> Vec<(String, ColdMirrorResult<HarmPrediction>)> {
+        let mut batch = VecDeque::from(self.lock().pop_batch(max_batch));
+        // The batch runs at the priority of its least urgent input, aging included
+        let running = batch.iter().map(Scheduled::rank).min().unwrap_or(0);
+
+        let mut results = Vec::with_capacity(batch.len());
+        while !batch.is_empty() {
+            if !results.is_empty() {
+                let mut scheduler = self.lock();
+                if scheduler.should_preempt(running) {
+                    debug!("Preempting inference batch with {} inputs left", batch.len());
+                    scheduler.preemptions += 1;
+                    scheduler.requeue(batch.drain(..));
+                    break;
+                }
+            }
+
+            let chunk: Vec<Scheduled<PredictionInput>> = batch.drain(..self.chunk.min(batch.len())).collect();
+            let inputs: Vec<PredictionInput> = chunk.into_iter().map(|entry| entry.item).collect();
+            match predictor.predict_harm_batch(&inputs) {
+                Ok(predictions) => results.extend(
+                    inputs.iter().map(|input| input.event.event_id.clone()).zip(predictions.into_iter().map(Ok)),
+                ),
+                Err(e) => {
+                    let message = e.to_string();
+                    results.extend(inputs.iter().map(|input| {
+                        (input.event.event_id.clone(), Err(crate::ColdMirrorError::InferenceError(message.clone())))
+                    }));
+                }
+            }
+        }
+        results
+    }
+
+    /// Inputs waiting
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no inputs are waiting
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queueing delay per urgency class and preemption count
+    pub fn metrics(&self) -> QueueMetrics {
+        self.lock().metrics()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PriorityScheduler<PredictionInput>> {
+        match self.scheduler.lock() {
+            Ok(scheduler) => scheduler,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(aging_interval_ms: u64) -> SchedulingConfig {
+        SchedulingConfig {
+            aging_interval_ms,
+            preemption_chunk: 1,
+        }
+    }
+
+    #[test]
+    fn test_urgency_order_with_aging() {
+        let mut scheduler = PriorityScheduler::new(&config(60_000));
+        scheduler.push(UrgencyLevel::Low, "low");
+        scheduler.push(UrgencyLevel::Normal, "normal");
+        scheduler.push(UrgencyLevel::Critical, "critical");
+        scheduler.push(UrgencyLevel::Normal, "normal-2");
+
+        let order: Vec<&str> = scheduler.pop_batch(4).into_iter().map(|entry| entry.item).collect();
+        assert_eq!(order, vec!["critical", "normal", "normal-2", "low"]);
+        assert_eq!(scheduler.metrics().delays["Critical"].dequeued, 1);
+
+        // A low entry that waited long enough ranks with high work and, being older, goes first
+        let mut scheduler = PriorityScheduler::new(&config(5));
+        scheduler.push(UrgencyLevel::Low, "starved");
+        std::thread::sleep(Duration::from_millis(20));
+        scheduler.push(UrgencyLevel::High, "high");
+        assert_eq!(scheduler.pop().unwrap().item, "starved");
+        assert_eq!(scheduler.metrics().delays["Low"].promoted, 1);
+    }
+
+    #[test]
+    fn test_aging_never_overtakes_critical() {
+        let mut scheduler = PriorityScheduler::new(&config(1));
+        scheduler.push(UrgencyLevel::Low, "starved");
+        std::thread::sleep(Duration::from_millis(20));
+        scheduler.push(UrgencyLevel::Critical, "critical");
+        assert!(!scheduler.should_preempt(AGING_CEILING + 1));
+
+        let batch = scheduler.pop_batch(2);
+        assert_eq!(batch.iter().map(|entry| entry.item).collect::<Vec<_>>(), vec!["critical", "starved"]);
+        assert_eq!(batch[1].rank(), AGING_CEILING);
+    }
+
+    #[test]
+    fn test_requeue_keeps_arrival_order() {
+        let mut scheduler = PriorityScheduler::new(&config(60_000));
+        for item in ["a", "b", "c"] {
+            scheduler.push(UrgencyLevel::Low, item);
+        }
+        let mut batch = scheduler.pop_batch(2);
+        assert!(!scheduler.should_preempt(0));
+
+        scheduler.push(UrgencyLevel::High, "urgent");
+        assert!(scheduler.should_preempt(0));
+        scheduler.requeue(batch.drain(1..));
+        assert_eq!(scheduler.metrics().delays["Low"].dequeued, 1);
+
+        let order: Vec<&str> = scheduler.pop_batch(3).into_iter().map(|entry| entry.item).collect();
+        assert_eq!(order, vec!["urgent", "b", "c"]);
+        assert_eq!(scheduler.metrics().delays["Low"].dequeued, 3);
+    }
+
+    #[test]
+    fn test_requeue_keeps_aged_priority() {
+        let mut scheduler = PriorityScheduler::new(&config(5));
+        scheduler.push(UrgencyLevel::Low, "aged");
+        std::thread::sleep(Duration::from_millis(20));
+        let taken = scheduler.pop_batch(1);
+        assert_eq!(scheduler.metrics().delays["Low"].promoted, 1);
+
+        // Put back behind fresh normal work, it still goes first
+        scheduler.push(UrgencyLevel::Normal, "normal");
+        scheduler.requeue(taken);
+        assert_eq!(scheduler.metrics().delays["Low"].promoted, 0);
+        assert_eq!(scheduler.pop().unwrap().item, "aged");
+        assert_eq!(scheduler.metrics().delays["Low"].promoted, 1);
+    }
+}
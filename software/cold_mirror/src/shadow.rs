@@ -0,0 +1,371 @@
+//! Shadow Auditing - Sampled Re-Evaluation of Allowed Traffic
+//! "Examine me, O Lord, and prove me; try my reins and my heart" - Psalm 26:2
+//!
+//! Live verdicts are made under a latency budget, with cached decisions and
+//! the lexical predictor standing in when time runs short, so a policy or
+//! model can drift towards allowing content it should not. The
+//! `ShadowAuditor` samples a configurable fraction of Allowed events and,
+//! on a background thread, re-runs each through an ethics engine at the
+//! strictest policy profile and the full harm model, with no deadline. It
+//! counts how often the shadow evaluation would have Denied or Purged what
+//! was Allowed live, and keeps the most recent of those divergences for
+//! review. Shadow evaluation never changes a live verdict; samples that
+//! arrive while its queue is full are dropped and counted.
+
+use ethics_dsl::{EthicsConfig, EthicsDecision, EthicsEngine, EthicsEvent, ValidityConfig};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::deterministic::DeterministicConfig;
+use crate::pipeline::stricter;
+use crate::{utils, ColdMirrorError, ColdMirrorResult, HarmPredictor};
+
+/// Strictness level of the shadow engine's policy profile
+pub const STRICTEST_LEVEL: u8 = 10;
+
+/// Divergences kept for review in a `ShadowReport`
+pub const RECENT_DIVERGENCES: usize = 100;
+
+/// Shadow auditing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowConfig {
+    /// Fraction of Allowed events re-evaluated (0.0 to 1.0)
+    pub sample_rate: f64,
+    /// Sampled events waiting for the shadow thread before new samples are dropped
+    pub queue_capacity: usize,
+    /// Sampling seed; a fixed seed samples the same events on every run
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 0.01,
+            queue_capacity: 1024,
+            seed: None,
+        }
+    }
+}
+
+impl ShadowConfig {
+    /// Take the sampling seed from deterministic mode unless one is set
+    pub fn with_deterministic(mut self, deterministic: &DeterministicConfig) -> Self {
+        self.seed = self.seed.or_else(|| deterministic.seed_for("shadow"));
+        self
+    }
+
+    /// Check the rate and capacity
+    pub fn validate(&self) -> ColdMirrorResult<()> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            return Err(ColdMirrorError::ConfigurationError(format!(
+                "Shadow sample rate {} is outside 0.0..=1.0",
+                self.sample_rate
+            )));
+        }
+        if self.queue_capacity == 0 {
+            return Err(ColdMirrorError::ConfigurationError("Shadow queue capacity must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// The strictest profile of a live ethics configuration
+///
+/// Sinks, the journal (file or store) and the revocation list file are left out, so shadow
+/// decisions are never published or replayed as if they were live and
+/// shadow revocations never invalidate live decisions.
+pub fn strictest_profile(live: &EthicsConfig) -> EthicsConfig {
+    EthicsConfig {
+        strictness_level: STRICTEST_LEVEL,
+        sinks: Vec::new(),
+        journal: None,
+        journal_storage: None,
+        validity: ValidityConfig {
+            revocation_list: None,
+            ..live.validity.clone()
+        },
+        ..live.clone()
+    }
+}
+
+/// Allowed event the shadow evaluation would have Denied or Purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowDivergence {
+    /// Event that was Allowed live
+    pub event_id: String,
+    /// Stricter of the shadow ethics decision and the full model's decision
+    pub shadow: EthicsDecision,
+    /// Harm level from the full model
+    pub harm_level: f32,
+}
+
+/// Shadow auditing counters and recent divergences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowReport {
+    /// Allowed events observed
+    pub allowed: u64,
+    /// Allowed events queued for shadow evaluation
+    pub sampled: u64,
+    /// Sampled events dropped because the queue was full
+    pub dropped: u64,
+    /// Sampled events re-evaluated
+    pub evaluated: u64,
+    /// Sampled events the shadow evaluation failed on
+    pub failed: u64,
+    /// Re-evaluated events the shadow would have Denied
+    pub would_deny: u64,
+    /// Re-evaluated events the shadow would have Purged
+    pub would_purge: u64,
+    /// Most recent divergences, oldest first
+    pub recent: VecDeque<ShadowDivergence>,
+}
+
+impl ShadowReport {
+    /// Fraction of re-evaluated events the shadow would have Denied
+    pub fn deny_rate(&self) -> f64 {
+        self.rate(self.would_deny)
+    }
+
+    /// Fraction of re-evaluated events the shadow would have Purged
+    pub fn purge_rate(&self) -> f64 {
+        self.rate(self.would_purge)
+    }
+
+    /// Fraction of re-evaluated events the shadow would not have Allowed
+    pub fn divergence_rate(&self) -> f64 {
+        self.rate(self.would_deny + self.would_purge)
+    }
+
+    fn rate(&self, count: u64) -> f64 {
+        if self.evaluated == 0 {
+            0.0
+        } else {
+            count as f64 / self.evaluated as f64
+        }
+    }
+
+    fn record(&mut self, event_id: String, shadow: EthicsDecision, harm_level: f32) {
+        self.evaluated += 1;
+        match shadow {
+            EthicsDecision::Allow { .. } => return,
+            EthicsDecision::Deny { .. } => self.would_deny += 1,
+            EthicsDecision::Purge { .. } => self.would_purge += 1,
+        }
+        if self.recent.len() == RECENT_DIVERGENCES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(ShadowDivergence { event_id, shadow, harm_level });
+    }
+}
+
+/// Samples Allowed events and re-evaluates them in the background
+pub struct ShadowAuditor {
+    sender: Option<SyncSender<EthicsEvent>>,
+    worker: Option<JoinHandle<()>>,
+    report: Arc<Mutex<ShadowReport>>,
+    sample_rate: f64,
+    seed: u64,
+}
+
+impl ShadowAuditor {
+    /// Start the shadow thread with the strictest profile of `live` and the full model
+    pub fn start<P>(live: &EthicsConfig, predictor: P, config: &ShadowConfig) -> ColdMirrorResult<Self>
+    where
+        P: HarmPredictor + Send + 'static,
+    {
+        config.validate()?;
+        let engine = EthicsEngine::new(strictest_profile(live))?;
+        let report = Arc::new(Mutex::new(ShadowReport::default()));
+        let (sender, receiver) = mpsc::sync_channel::<EthicsEvent>(config.queue_capacity);
+
+        let shared = report.clone();
+        let worker = std::thread::Builder::new()
+            .name("cold-mirror-shadow".to_string())
+            .spawn(move || {
+                for event in receiver {
+                    let outcome = engine.evaluate_content(&event).map_err(ColdMirrorError::from).and_then(|decision| {
+                        let input = utils::create_prediction_input(event.clone(), None, None);
+                        let prediction = predictor.predict_harm(&input)?;
+                        Ok((stricter(decision, utils::to_ethics_decision(&prediction)), prediction.harm_level))
+                    });
+                    let mut report = shared.lock().unwrap_or_else(|e| e.into_inner());
+                    match outcome {
+                        Ok((shadow, harm_level)) => report.record(event.event_id, shadow, harm_level),
+                        Err(e) => {
+                            warn!("Shadow evaluation of {} failed: {}", event.event_id, e);
+                            report.failed += 1;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| ColdMirrorError::ResourceError(format!("Failed to start shadow thread: {}", e)))?;
+
+        let seed = config.seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        Ok(Self {
+            sender: Some(sender),
+            worker: Some(worker),
+            report,
+            sample_rate: config.sample_rate,
+            seed,
+        })
+    }
+
+    /// Offer a live verdict; Allowed events are sampled for re-evaluation
+    ///
+    /// Returns whether the event was queued. Never blocks.
+    pub fn observe(&self, event: &EthicsEvent, decision: &EthicsDecision) -> bool {
+        if !matches!(decision, EthicsDecision::Allow { .. }) {
+            return false;
+        }
+        let sampled = self.is_sampled(&event.event_id);
+
+        let queued = sampled && self.sender.as_ref().is_some_and(|sender| sender.try_send(event.clone()).is_ok());
+        let mut report = self.report.lock().unwrap_or_else(|e| e.into_inner());
+        report.allowed += 1;
+        if queued {
+            report.sampled += 1;
+        } else if sampled {
+            report.dropped += 1;
+        }
+        queued
+    }
+
+    /// Counters and recent divergences so far
+    pub fn report(&self) -> ShadowReport {
+        self.report.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Finish evaluating queued samples and return the final report
+    pub fn finish(mut self) -> ShadowReport {
+        self.sender = None;
+        if let Some(worker) = self.worker.take() {
+            if worker.join().is_err() {
+                warn!("Shadow thread panicked");
+            }
+        }
+        self.report()
+    }
+
+    /// Whether the event falls in the sampled fraction under this auditor's seed
+    fn is_sampled(&self, event_id: &str) -> bool {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.seed.to_le_bytes());
+        hasher.update(event_id.as_bytes());
+        let mut draw = [0u8; 8];
+        draw.copy_from_slice(&hasher.finalize().as_bytes()[..8]);
+        self.sample_rate >= 1.0 || (u64::from_le_bytes(draw) as f64 / u64::MAX as f64) < self.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HarmPrediction, ModelMetrics, OutcomeData, PredictionInput, RecommendedAction};
+    use ethics_dsl::{Actor, ActorType, Context, UrgencyLevel};
+
+    /// Full model stand-in that finds every event harmful
+    struct Alarmed;
+
+    impl HarmPredictor for Alarmed {
+        fn predict_harm(&self, _input: &PredictionInput) -> ColdMirrorResult<HarmPrediction> {
+            Ok(HarmPrediction {
+                harm_level: 0.95,
+                confidence: 0.9,
+                time_horizon: 1.0,
+                harm_categories: vec![],
+                risk_factors: vec![],
+                recommended_action: RecommendedAction::Purge { urgency: crate::UrgencyLevel::Critical, escalate: true },
+                timestamp: chrono::Utc::now(),
+                model_version: "alarmed".to_string(),
+                policy_version: None,
+            })
+        }
+
+        fn predict_harm_batch(&self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+            inputs.iter().map(|input| self.predict_harm(input)).collect()
+        }
+
+        fn update_with_outcome(&mut self, _outcome: &OutcomeData) -> ColdMirrorResult<()> {
+            Ok(())
+        }
+
+        fn get_performance_metrics(&self) -> ColdMirrorResult<ModelMetrics> {
+            Err(ColdMirrorError::TimeoutError)
+        }
+    }
+
+    fn event(id: usize) -> EthicsEvent {
+        ethics_dsl::utils::create_event(
+            format!("shadow-{}", id),
+            Actor {
+                actor_type: ActorType::Person,
+                tags: vec![],
+                trust_level: 0.8,
+                history: None,
+            },
+            None,
+            Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+        )
+    }
+
+    fn allow() -> EthicsDecision {
+        EthicsDecision::Allow { confidence: 0.9, justification: String::new(), scripture_refs: vec![] }
+    }
+
+    #[test]
+    fn test_sampled_allows_reported_as_divergent() {
+        let config = ShadowConfig { sample_rate: 1.0, queue_capacity: 64, seed: Some(7) };
+        let auditor = ShadowAuditor::start(&EthicsConfig::default(), Alarmed, &config).unwrap();
+
+        for id in 0..10 {
+            assert!(auditor.observe(&event(id), &allow()));
+        }
+        let deny = EthicsDecision::Deny {
+            confidence: 0.9,
+            violation: String::new(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        assert!(!auditor.observe(&event(10), &deny));
+
+        let report = auditor.finish();
+        assert_eq!((report.allowed, report.sampled, report.evaluated), (10, 10, 10));
+        assert_eq!(report.would_purge, 10);
+        assert_eq!(report.divergence_rate(), 1.0);
+        assert_eq!(report.recent.back().unwrap().event_id, "shadow-9");
+    }
+
+    #[test]
+    fn test_sample_rate_and_seed_select_events() {
+        assert!(ShadowConfig { sample_rate: 1.5, ..ShadowConfig::default() }.validate().is_err());
+
+        let config = ShadowConfig { sample_rate: 0.25, queue_capacity: 1024, seed: Some(42) };
+        let first = ShadowAuditor::start(&EthicsConfig::default(), Alarmed, &config).unwrap();
+        let second = ShadowAuditor::start(&EthicsConfig::default(), Alarmed, &config).unwrap();
+
+        let picked: Vec<bool> = (0..400).map(|id| first.observe(&event(id), &allow())).collect();
+        let again: Vec<bool> = (0..400).map(|id| second.observe(&event(id), &allow())).collect();
+        assert_eq!(picked, again);
+
+        let sampled = picked.iter().filter(|picked| **picked).count();
+        assert!((60..=140).contains(&sampled), "sampled {} of 400", sampled);
+        assert_eq!(first.finish().sampled, sampled as u64);
+        assert_eq!(strictest_profile(&EthicsConfig::default()).strictness_level, STRICTEST_LEVEL);
+    }
+}
@@ -0,0 +1,275 @@
+//! Harm Taxonomy - Extensible Registry of Harm Categories
+//! "To every thing there is a season, and a time to every purpose under the heaven" - Ecclesiastes 3:1
+//!
+//! The built-in categories of `HarmCategory` form the core of the taxonomy.
+//! Deployments extend it with domain-specific categories (e.g. financial
+//! fraud) that name a parent category and may carry their own default
+//! thresholds. Extension categories are reported as `HarmCategory::Custom`,
+//! which keeps its id and details through serialization even when the
+//! reading side does not know the category.
+//!
+//! A category without thresholds of its own, in the policy table or in its
+//! definition, is judged by the thresholds of its nearest ancestor that has
+//! them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use pq_types::decode::{self, DecodeLimits};
+
+use crate::policy::{ActionLevel, CategoryThreshold};
+use crate::{ColdMirrorError, ColdMirrorResult};
+
+/// Ids of the built-in core categories
+pub const CORE_CATEGORIES: [&str; 5] =
+    ["MoralDegradation", "PhysicalHarm", "PsychologicalHarm", "SocialHarm", "SpiritualHarm"];
+
+/// Longest accepted category id
+pub const MAX_CATEGORY_ID_LENGTH: usize = 64;
+
+/// Deepest accepted chain of parent categories
+pub const MAX_CATEGORY_DEPTH: usize = 8;
+
+/// Default threshold for one action of a category
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefaultThreshold {
+    /// Action taken once the threshold is crossed
+    pub action: ActionLevel,
+    /// Score at or above which the action is entered
+    pub escalate_at: f32,
+    /// Score below which the action is left again
+    pub deescalate_below: f32,
+}
+
+/// Definition of one harm category
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryDefinition {
+    /// Category id used in predictions and policy rules
+    pub id: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: String,
+    /// Parent category; only core categories have none
+    #[serde(default)]
+    pub parent: Option<String>,
+    /// Thresholds used when the policy table has none for this category
+    #[serde(default)]
+    pub default_thresholds: Vec<DefaultThreshold>,
+}
+
+/// Taxonomy extension file contents
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaxonomyExtension {
+    /// Categories added to the core, parents before children
+    pub categories: Vec<CategoryDefinition>,
+}
+
+/// Registry of known harm categories
+#[derive(Debug, Clone, PartialEq)]
+pub struct Taxonomy {
+    categories: BTreeMap<String, CategoryDefinition>,
+}
+
+impl Default for Taxonomy {
+    fn default() -> Self {
+        Self::core()
+    }
+}
+
+impl Taxonomy {
+    /// The built-in core categories and their default thresholds
+    pub fn core() -> Self {
+        let threshold = |action, escalate_at, deescalate_below| DefaultThreshold { action, escalate_at, deescalate_below };
+
+        let categories = CORE_CATEGORIES
+            .iter()
+            .map(|id| {
+                let mut default_thresholds = vec![
+                    threshold(ActionLevel::Monitor, 0.3, 0.2),
+                    threshold(ActionLevel::Quarantine, 0.6, 0.5),
+                    threshold(ActionLevel::Block, 0.8, 0.7),
+                ];
+                if *id == "PhysicalHarm" {
+                    default_thresholds.push(threshold(ActionLevel::Purge, 0.95, 0.9));
+                }
+                let definition = CategoryDefinition {
+                    id: id.to_string(),
+                    description: String::new(),
+                    parent: None,
+                    default_thresholds,
+                };
+                (id.to_string(), definition)
+            })
+            .collect();
+
+        Self { categories }
+    }
+
+    /// The core extended with the categories of a JSON extension file
+    pub fn from_file(path: &Path) -> ColdMirrorResult<Self> {
+        let contents = std::fs::read(path)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        let extension: TaxonomyExtension = decode::json(&contents, &DecodeLimits::FILE)
+            .map_err(|e| ColdMirrorError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+
+        let mut taxonomy = Self::core();
+        for definition in extension.categories {
+            taxonomy.register(definition)?;
+        }
+        Ok(taxonomy)
+    }
+
+    /// Add an extension category
+    ///
+    /// The id must be new and the parent already registered, so the
+    /// taxonomy stays a forest rooted at the core categories.
+    pub fn register(&mut self, definition: CategoryDefinition) -> ColdMirrorResult<()> {
+        let invalid = |reason: String| ColdMirrorError::ConfigurationError(format!("Category {}: {}", definition.id, reason));
+
+        validate_id(&definition.id).map_err(invalid)?;
+        if self.categories.contains_key(&definition.id) {
+            return Err(invalid("already registered".into()));
+        }
+        let parent = definition.parent.as_deref().ok_or_else(|| invalid("extension categories need a parent".into()))?;
+        if !self.categories.contains_key(parent) {
+            return Err(invalid(format!("unknown parent {}", parent)));
+        }
+        if self.lineage(parent).len() >= MAX_CATEGORY_DEPTH {
+            return Err(invalid(format!("nested deeper than {} levels", MAX_CATEGORY_DEPTH)));
+        }
+        for threshold in &definition.default_thresholds {
+            if !(0.0..=1.0).contains(&threshold.escalate_at)
+                || !(0.0..=1.0).contains(&threshold.deescalate_below)
+                || threshold.deescalate_below > threshold.escalate_at
+            {
+                return Err(invalid(format!("malformed {:?} threshold", threshold.action)));
+            }
+        }
+
+        self.categories.insert(definition.id.clone(), definition);
+        Ok(())
+    }
+
+    /// Definition of a category
+    pub fn get(&self, id: &str) -> Option<&CategoryDefinition> {
+        self.categories.get(id)
+    }
+
+    /// Whether a category is part of the built-in core
+    pub fn is_core(id: &str) -> bool {
+        CORE_CATEGORIES.contains(&id)
+    }
+
+    /// All registered categories, ordered by id
+    pub fn categories(&self) -> impl Iterator<Item = &CategoryDefinition> {
+        self.categories.values()
+    }
+
+    /// The category followed by its ancestors, nearest first
+    ///
+    /// Unknown categories have a lineage of only themselves.
+    pub fn lineage<'a>(&'a self, id: &'a str) -> Vec<&'a str> {
+        let mut lineage = vec![id];
+        let mut current = self.categories.get(id);
+        while let Some(parent) = current.and_then(|definition| definition.parent.as_deref()) {
+            if lineage.contains(&parent) || lineage.len() > MAX_CATEGORY_DEPTH {
+                break;
+            }
+            lineage.push(parent);
+            current = self.categories.get(parent);
+        }
+        lineage
+    }
+
+    /// Nearest core category a category belongs to
+    pub fn core_ancestor(&self, id: &str) -> Option<&str> {
+        self.lineage(id).into_iter().find(|id| Self::is_core(id))
+    }
+
+    /// Policy rules built from every category's default thresholds
+    pub fn default_rules(&self) -> Vec<CategoryThreshold> {
+        self.categories
+            .values()
+            .flat_map(|definition| {
+                definition.default_thresholds.iter().map(|threshold| CategoryThreshold {
+                    category: definition.id.clone(),
+                    action: threshold.action,
+                    escalate_at: threshold.escalate_at,
+                    deescalate_below: threshold.deescalate_below,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Category ids: ASCII letters, digits, `_`, `-` and `.`
+fn validate_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > MAX_CATEGORY_ID_LENGTH {
+        return Err(format!("id must be 1 to {} bytes", MAX_CATEGORY_ID_LENGTH));
+    }
+    if !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err("id may only contain ASCII letters, digits, '_', '-' and '.'".into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HarmCategory;
+
+    fn fraud() -> CategoryDefinition {
+        CategoryDefinition {
+            id: "FinancialFraud".to_string(),
+            description: "Deceptive schemes for financial gain".to_string(),
+            parent: Some("MoralDegradation".to_string()),
+            default_thresholds: vec![DefaultThreshold { action: ActionLevel::Block, escalate_at: 0.5, deescalate_below: 0.4 }],
+        }
+    }
+
+    #[test]
+    fn test_register_extension_category() {
+        let mut taxonomy = Taxonomy::core();
+        taxonomy.register(fraud()).unwrap();
+        taxonomy
+            .register(CategoryDefinition {
+                id: "InvestmentScam".to_string(),
+                description: String::new(),
+                parent: Some("FinancialFraud".to_string()),
+                default_thresholds: vec![],
+            })
+            .unwrap();
+
+        assert_eq!(taxonomy.lineage("InvestmentScam"), vec!["InvestmentScam", "FinancialFraud", "MoralDegradation"]);
+        assert_eq!(taxonomy.core_ancestor("InvestmentScam"), Some("MoralDegradation"));
+        assert!(taxonomy.default_rules().iter().any(|rule| rule.category == "FinancialFraud"));
+
+        // Duplicates, orphans and malformed ids are refused
+        assert!(taxonomy.register(fraud()).is_err());
+        assert!(taxonomy.register(CategoryDefinition { parent: Some("Nope".into()), id: "Orphan".into(), ..fraud() }).is_err());
+        assert!(taxonomy.register(CategoryDefinition { id: "bad id".into(), ..fraud() }).is_err());
+        assert!(taxonomy.register(CategoryDefinition { id: "Rootless".into(), parent: None, ..fraud() }).is_err());
+    }
+
+    #[test]
+    fn test_core_matches_default_policy() {
+        let mut core_rules = Taxonomy::core().default_rules();
+        let mut policy_rules = crate::policy::ThresholdPolicy::default().rules;
+        let key = |rule: &CategoryThreshold| (rule.category.clone(), rule.action);
+        core_rules.sort_by_key(key);
+        policy_rules.sort_by_key(key);
+        assert_eq!(core_rules, policy_rules);
+    }
+
+    #[test]
+    fn test_unknown_category_round_trips() {
+        let json = r#"{"Custom":{"id":"DataExfiltration","score":0.7,"details":{"channel":"dns"}}}"#;
+        let category: HarmCategory = serde_json::from_str(json).unwrap();
+        assert!(Taxonomy::core().get("DataExfiltration").is_none());
+        assert_eq!(serde_json::to_string(&category).unwrap(), json);
+
+        let encoded = bincode::serialize(&category).unwrap();
+        assert_eq!(bincode::deserialize::<HarmCategory>(&encoded).unwrap(), category);
+    }
+}
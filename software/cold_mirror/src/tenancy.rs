@@ -0,0 +1,438 @@
+//! Tenant Serving - One Service, Many Deployments
+//! "Render therefore to all their dues" - Romans 13:7
+//!
+//! A central Cold-Mirror service answers several ARK edge deployments. A
+//! `TenantRegistry` maps each client identity to its tenant and serves the
+//! tenant with the model version it is pinned to. Each tenant has request
+//! and input quotas per `QUOTA_WINDOW`, and a `PriorityClass` bounding the
+//! share of the service's in-flight requests it may occupy, so bulk
+//! tenants are shed first under load.
+//!
+//! Scores are calibrated per tenant. Outcome feedback adjusts only the
+//! reporting tenant's Platt parameters - the shared model is never updated
+//! from it - so one tenant's feedback loop cannot skew another's scores.
+//! Request counts, throttling, shedding and latency are kept per tenant.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::policy::{category_score, with_category_score};
+use crate::risk_assessment::Calibration;
+use crate::{
+    CalibrationConfig, ColdMirrorError, ColdMirrorResult, HarmPrediction, HarmPredictor, OutcomeData, PredictionInput,
+};
+
+/// Period over which tenant quotas are counted
+pub const QUOTA_WINDOW: Duration = Duration::from_secs(60);
+
+/// Step size of a calibration update from one outcome
+const FEEDBACK_RATE: f32 = 0.05;
+
+/// Model shared by the tenants pinned to it
+pub type SharedPredictor = Arc<dyn HarmPredictor + Send + Sync>;
+
+/// Admission priority of a tenant under load
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityClass {
+    /// Batch work, shed first
+    Bulk,
+    /// Ordinary traffic
+    #[default]
+    Standard,
+    /// Latency-sensitive traffic, shed last
+    Interactive,
+}
+
+impl PriorityClass {
+    /// Share of the service's in-flight requests the class may fill
+    pub fn admission_share(self) -> f32 {
+        match self {
+            PriorityClass::Bulk => 0.5,
+            PriorityClass::Standard => 0.8,
+            PriorityClass::Interactive => 1.0,
+        }
+    }
+}
+
+/// One tenant of the service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Tenant id
+    pub id: String,
+    /// Client ids the tenant's devices present; each belongs to one tenant
+    pub identities: Vec<String>,
+    /// Model version the tenant is pinned to; the service default if `None`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Most requests per quota window
+    pub requests_per_window: u32,
+    /// Most prediction inputs per quota window
+    pub inputs_per_window: u32,
+    /// Admission priority under load
+    #[serde(default)]
+    pub priority: PriorityClass,
+    /// Starting calibration; scores are used as they are if `None`
+    #[serde(default)]
+    pub calibration: Option<CalibrationConfig>,
+}
+
+/// Tenants of a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenancyConfig {
+    /// Tenants served
+    pub tenants: Vec<TenantConfig>,
+    /// Most requests served at once across all tenants
+    pub max_in_flight: usize,
+}
+
+impl Default for TenancyConfig {
+    fn default() -> Self {
+        Self { tenants: Vec::new(), max_in_flight: 64 }
+    }
+}
+
+/// Serving counters of one tenant
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenantMetrics {
+    /// Model version serving the tenant
+    pub model: String,
+    /// Requests served
+    pub requests: u64,
+    /// Inputs predicted
+    pub inputs: u64,
+    /// Requests refused by the tenant's quota
+    pub throttled: u64,
+    /// Requests shed because the service was busy for the tenant's priority
+    pub shed: u64,
+    /// Requests the model failed
+    pub failures: u64,
+    /// Mean time to predict a request (milliseconds)
+    pub mean_latency_ms: f64,
+    /// Outcomes fed back into the tenant's calibration
+    pub outcomes: u64,
+    /// Current Platt slope of the tenant's calibration
+    pub calibration_a: f32,
+    /// Current Platt intercept of the tenant's calibration
+    pub calibration_b: f32,
+}
+
+fn logit(p: f32) -> f32 {
+    let p = p.clamp(1e-6, 1.0 - 1e-6);
+    (p / (1.0 - p)).ln()
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Quota window, calibration and counters of a tenant
+struct TenantState {
+    window_start: Instant,
+    window_requests: u32,
+    window_inputs: u32,
+    /// Platt slope and intercept; every `Calibration` is one of these
+    calibration: (f32, f32),
+    latency_total_ms: f64,
+    metrics: TenantMetrics,
+}
+
+impl TenantState {
+    /// Undo the tenant's calibration of a served score, then take one log-loss step toward the outcome
+    fn learn(&mut self, served: f32, harm_occurred: bool) {
+        let (a, b) = self.calibration;
+        if a.abs() < 1e-3 {
+            return;
+        }
+        let raw = (logit(served) - b) / a;
+        let error = sigmoid(a * raw + b) - if harm_occurred { 1.0 } else { 0.0 };
+        self.calibration = (a - FEEDBACK_RATE * error * raw, b - FEEDBACK_RATE * error);
+    }
+}
+
+struct Tenant {
+    config: TenantConfig,
+    model: String,
+    state: Mutex<TenantState>,
+}
+
+impl Tenant {
+    fn state(&self) -> std::sync::MutexGuard<'_, TenantState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Tenants, their identities and the models they are pinned to
+pub struct TenantRegistry {
+    tenants: BTreeMap<String, Arc<Tenant>>,
+    identities: HashMap<String, String>,
+    models: HashMap<String, SharedPredictor>,
+    max_in_flight: usize,
+    in_flight: AtomicUsize,
+}
+
+impl TenantRegistry {
+    /// Registry serving `config`'s tenants with `models`, by version
+    ///
+    /// Tenants without a pinned model use `default_model`. Duplicate tenant
+    /// ids, identities claimed by two tenants and unknown models are refused.
+    pub fn new(config: TenancyConfig, models: HashMap<String, SharedPredictor>, default_model: &str) -> ColdMirrorResult<Self> {
+        let mut tenants = BTreeMap::new();
+        let mut identities = HashMap::new();
+        for tenant in config.tenants {
+            let model = tenant.model.clone().unwrap_or_else(|| default_model.to_string());
+            if !models.contains_key(&model) {
+                return Err(ColdMirrorError::ConfigurationError(format!("Tenant {} is pinned to unknown model {}", tenant.id, model)));
+            }
+            for identity in &tenant.identities {
+                if let Some(other) = identities.insert(identity.clone(), tenant.id.clone()) {
+                    return Err(ColdMirrorError::ConfigurationError(format!(
+                        "Identity {} belongs to both {} and {}", identity, other, tenant.id
+                    )));
+                }
+            }
+            let calibration = match tenant.calibration.as_ref().map(Calibration::from_config).transpose()? {
+                None | Some(Calibration::Identity) => (1.0, 0.0),
+                Some(Calibration::Platt { a, b }) => (a, b),
+                Some(Calibration::Temperature(t)) => (1.0 / t, 0.0),
+            };
+            let state = TenantState {
+                window_start: Instant::now(),
+                window_requests: 0,
+                window_inputs: 0,
+                calibration,
+                latency_total_ms: 0.0,
+                metrics: TenantMetrics { model: model.clone(), ..TenantMetrics::default() },
+            };
+            let id = tenant.id.clone();
+            let tenant = Arc::new(Tenant { config: tenant, model, state: Mutex::new(state) });
+            if tenants.insert(id.clone(), tenant).is_some() {
+                return Err(ColdMirrorError::ConfigurationError(format!("Tenant {} is configured twice", id)));
+            }
+        }
+        Ok(Self { tenants, identities, models, max_in_flight: config.max_in_flight.max(1), in_flight: AtomicUsize::new(0) })
+    }
+
+    /// Tenant of a client identity
+    pub fn tenant_of(&self, client_id: &str) -> Option<&str> {
+        self.identities.get(client_id).map(String::as_str)
+    }
+
+    /// Admit a request of `inputs` inputs from `client_id`
+    ///
+    /// Refused with `QuotaExceeded` when the tenant's quota window is used
+    /// up, and with `ResourceError` when the service is too busy for the
+    /// tenant's priority class. Refused requests do not count against the quota.
+    pub fn admit(&self, client_id: &str, inputs: usize) -> ColdMirrorResult<Admission<'_>> {
+        let tenant = self
+            .tenant_of(client_id)
+            .and_then(|id| self.tenants.get(id))
+            .ok_or_else(|| ColdMirrorError::AccessDenied(format!("Client {} belongs to no tenant", client_id)))?;
+        let mut state = tenant.state();
+        if state.window_start.elapsed() >= QUOTA_WINDOW {
+            state.window_start = Instant::now();
+            state.window_requests = 0;
+            state.window_inputs = 0;
+        }
+        let inputs = u32::try_from(inputs).unwrap_or(u32::MAX);
+        if state.window_requests >= tenant.config.requests_per_window
+            || state.window_inputs.saturating_add(inputs) > tenant.config.inputs_per_window
+        {
+            state.metrics.throttled += 1;
+            return Err(ColdMirrorError::QuotaExceeded(format!("Tenant {} used its quota for this window", tenant.config.id)));
+        }
+
+        let limit = ((self.max_in_flight as f32 * tenant.config.priority.admission_share()).ceil() as usize).max(1);
+        if self
+            .in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < limit).then_some(n + 1))
+            .is_err()
+        {
+            state.metrics.shed += 1;
+            return Err(ColdMirrorError::ResourceError(format!(
+                "Service busy for {:?} tenant {}", tenant.config.priority, tenant.config.id
+            )));
+        }
+        state.window_requests += 1;
+        state.window_inputs += inputs;
+        drop(state);
+        Ok(Admission { registry: self, tenant: tenant.clone() })
+    }
+
+    /// Feed an observed outcome of a prediction served to `tenant_id` into its calibration
+    pub fn record_outcome(&self, tenant_id: &str, outcome: &OutcomeData) -> ColdMirrorResult<()> {
+        let tenant = self
+            .tenants
+            .get(tenant_id)
+            .ok_or_else(|| ColdMirrorError::ConfigurationError(format!("Unknown tenant {}", tenant_id)))?;
+        let mut state = tenant.state();
+        state.learn(outcome.prediction.harm_level, outcome.actual_outcome.harm_occurred);
+        state.metrics.outcomes += 1;
+        Ok(())
+    }
+
+    /// Metrics of every tenant, by tenant id
+    pub fn metrics(&self) -> BTreeMap<String, TenantMetrics> {
+        self.tenants
+            .iter()
+            .map(|(id, tenant)| {
+                let state = tenant.state();
+                let (calibration_a, calibration_b) = state.calibration;
+                (id.clone(), TenantMetrics { calibration_a, calibration_b, ..state.metrics.clone() })
+            })
+            .collect()
+    }
+}
+
+/// Admitted request; holds its in-flight slot until dropped
+pub struct Admission<'a> {
+    registry: &'a TenantRegistry,
+    tenant: Arc<Tenant>,
+}
+
+impl Admission<'_> {
+    /// Tenant the request was admitted for
+    pub fn tenant(&self) -> &str {
+        &self.tenant.config.id
+    }
+
+    /// Predict with the tenant's pinned model and calibrate for the tenant
+    pub fn predict(self, inputs: &[PredictionInput]) -> ColdMirrorResult<Vec<HarmPrediction>> {
+        let started = Instant::now();
+        let result = self.registry.models[&self.tenant.model].predict_harm_batch(inputs);
+        let mut state = self.tenant.state();
+        let mut predictions = match result {
+            Ok(predictions) => predictions,
+            Err(e) => {
+                state.metrics.failures += 1;
+                return Err(e);
+            }
+        };
+
+        let (a, b) = state.calibration;
+        let calibration = Calibration::Platt { a, b };
+        for prediction in &mut predictions {
+            prediction.harm_level = calibration.calibrate(prediction.harm_level);
+            prediction.confidence = calibration.calibrate(prediction.confidence);
+            for category in &mut prediction.harm_categories {
+                *category = with_category_score(category, calibration.calibrate(category_score(category)));
+            }
+        }
+
+        state.metrics.requests += 1;
+        state.metrics.inputs += inputs.len() as u64;
+        state.latency_total_ms += started.elapsed().as_secs_f64() * 1000.0;
+        state.metrics.mean_latency_ms = state.latency_total_ms / state.metrics.requests as f64;
+        Ok(predictions)
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        self.registry.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical::LexicalPredictor;
+    use crate::{AccuracyMetrics, ActualOutcome};
+
+    fn input(data: &str) -> PredictionInput {
+        let event = ethics_dsl::utils::create_event(
+            "tenant-event".to_string(),
+            ethics_dsl::Actor { actor_type: ethics_dsl::ActorType::Person, tags: vec![], trust_level: 0.5, history: None },
+            Some(ethics_dsl::Content {
+                content_type: ethics_dsl::ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            ethics_dsl::Context { location: None, culture: None, platform: None, audience: None, urgency: ethics_dsl::UrgencyLevel::Normal },
+        );
+        crate::utils::create_prediction_input(event, None, None)
+    }
+
+    fn tenant(id: &str, identity: &str, model: Option<&str>, priority: PriorityClass) -> TenantConfig {
+        TenantConfig {
+            id: id.to_string(),
+            identities: vec![identity.to_string()],
+            model: model.map(str::to_string),
+            requests_per_window: 3,
+            inputs_per_window: 4,
+            priority,
+            calibration: None,
+        }
+    }
+
+    fn registry(max_in_flight: usize) -> TenantRegistry {
+        let lexical: SharedPredictor = Arc::new(LexicalPredictor::new());
+        let models = HashMap::from([("lexical-1".to_string(), lexical.clone()), ("lexical-2".to_string(), lexical)]);
+        let config = TenancyConfig {
+            tenants: vec![
+                tenant("harbor", "harbor-gw", None, PriorityClass::Interactive),
+                tenant("valley", "valley-gw", Some("lexical-2"), PriorityClass::Bulk),
+            ],
+            max_in_flight,
+        };
+        TenantRegistry::new(config, models, "lexical-1").unwrap()
+    }
+
+    #[test]
+    fn test_quotas_priority_and_metrics_are_per_tenant() {
+        let registry = registry(2);
+        assert!(matches!(registry.admit("stranger", 1), Err(ColdMirrorError::AccessDenied(_))));
+
+        // A bulk tenant may fill half the service, an interactive one all of it
+        let bulk = registry.admit("valley-gw", 1).unwrap();
+        assert!(matches!(registry.admit("valley-gw", 1), Err(ColdMirrorError::ResourceError(_))));
+        let interactive = registry.admit("harbor-gw", 1).unwrap();
+        interactive.predict(&[input("bless this house")]).unwrap();
+        drop(bulk);
+
+        registry.admit("harbor-gw", 3).unwrap().predict(&[input("a"), input("b"), input("c")]).unwrap();
+        assert!(matches!(registry.admit("harbor-gw", 1), Err(ColdMirrorError::QuotaExceeded(_))));
+        registry.admit("valley-gw", 3).unwrap();
+
+        let metrics = registry.metrics();
+        let (harbor, valley) = (&metrics["harbor"], &metrics["valley"]);
+        assert_eq!((harbor.model.as_str(), harbor.requests, harbor.inputs, harbor.throttled, harbor.shed), ("lexical-1", 2, 4, 1, 0));
+        assert_eq!((valley.model.as_str(), valley.requests, valley.throttled, valley.shed), ("lexical-2", 0, 0, 1));
+    }
+
+    #[test]
+    fn test_feedback_calibrates_only_its_tenant() {
+        let registry = registry(8);
+        let served = registry.admit("harbor-gw", 1).unwrap().predict(&[input("they will kill")]).unwrap().remove(0);
+        let outcome = OutcomeData {
+            actual_outcome: ActualOutcome {
+                harm_occurred: false,
+                actual_harm_level: 0.0,
+                harm_categories: vec![],
+                description: "false alarm".to_string(),
+            },
+            prediction: served.clone(),
+            time_to_outcome: 1.0,
+            accuracy_metrics: AccuracyMetrics { accuracy: 0.0, precision: 0.0, recall: 0.0, f1_score: 0.0, mae: 0.5 },
+        };
+        for _ in 0..20 {
+            registry.record_outcome("harbor", &outcome).unwrap();
+        }
+
+        let metrics = registry.metrics();
+        assert_eq!(metrics["harbor"].outcomes, 20);
+        assert!(metrics["harbor"].calibration_b < 0.0);
+        assert_eq!((metrics["valley"].calibration_a, metrics["valley"].calibration_b), (1.0, 0.0));
+
+        let recalibrated = registry.admit("harbor-gw", 1).unwrap().predict(&[input("they will kill")]).unwrap().remove(0);
+        let other = registry.admit("valley-gw", 1).unwrap().predict(&[input("they will kill")]).unwrap().remove(0);
+        assert!(recalibrated.harm_level < served.harm_level);
+        assert_eq!(other.harm_level, served.harm_level);
+    }
+}
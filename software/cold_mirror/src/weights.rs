@@ -0,0 +1,490 @@
+//! Model Weights - Mapped, Paged In on Use, Kept Under the Memory Limit
+//! "For which of you, intending to build a tower, sitteth not down first, and counteth the cost" - Luke 14:28
+//!
+//! Opening a multi-gigabyte safetensors model memory-maps the file instead
+//! of reading it: only the header is parsed up front, and a tensor's pages
+//! are faulted in the first time it is used. Every tensor counts against
+//! `PerformanceConfig::memory_limit_mb` while it is resident; when paging
+//! in the next one would go over the limit, the least recently used
+//! tensors are released back to the page cache first. With `Int8`
+//! quantization the floating point tensors are quantized while loading and
+//! held on the heap instead, and their total is checked against the same
+//! limit before anything is allocated. A model that cannot fit is refused
+//! with a `ResourceError` naming the tensor or total at fault, rather than
+//! left to the OOM killer.
+//!
+//! With `WeightConfig::transparency` set, the mapped file must be logged in
+//! the transparency log as the model named by its file stem: the shipped
+//! artifact proof is checked against the mapped bytes before the header is
+//! parsed, and a model without a valid proof is refused.
+
+use memmap2::Mmap;
+use pq_types::decode::{self, DecodeLimits};
+use pq_types::scheme::Dilithium3;
+use pq_types::transparency::{ArtifactKind, LogRequirement};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::{ColdMirrorConfig, ColdMirrorError, ColdMirrorResult};
+
+/// Bytes in a megabyte of `memory_limit_mb`
+const MIB: usize = 1024 * 1024;
+
+/// Largest safetensors header read
+const HEADER_LIMITS: DecodeLimits = DecodeLimits::new(100 * MIB, 8);
+
+/// Key of the free-form metadata entry in a safetensors header
+const METADATA_KEY: &str = "__metadata__";
+
+/// Quantization applied while loading
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeightQuantization {
+    /// Tensors are used as stored, paged in from the mapped file
+    #[default]
+    None,
+    /// Floating point tensors are quantized to int8 with one scale per tensor
+    Int8,
+}
+
+/// How model weights are loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeightConfig {
+    /// Quantization applied at load time
+    #[serde(default)]
+    pub quantization: WeightQuantization,
+    /// Transparency log the model must be proven in before it is used
+    #[serde(default)]
+    pub transparency: Option<LogRequirement>,
+}
+
+/// Element type of a stored tensor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dtype {
+    /// Boolean
+    BOOL,
+    /// Unsigned byte
+    U8,
+    /// Signed byte
+    I8,
+    /// 16-bit integer
+    I16,
+    /// 32-bit integer
+    I32,
+    /// 64-bit integer
+    I64,
+    /// Half precision float
+    F16,
+    /// Brain float
+    BF16,
+    /// Single precision float
+    F32,
+    /// Double precision float
+    F64,
+}
+
+impl Dtype {
+    /// Bytes per element
+    pub fn size(self) -> usize {
+        match self {
+            Self::BOOL | Self::U8 | Self::I8 => 1,
+            Self::I16 | Self::F16 | Self::BF16 => 2,
+            Self::I32 | Self::F32 => 4,
+            Self::I64 | Self::F64 => 8,
+        }
+    }
+
+    /// Whether int8 quantization applies
+    pub fn is_float(self) -> bool {
+        matches!(self, Self::F16 | Self::BF16 | Self::F32 | Self::F64)
+    }
+}
+
+/// Header entry of one tensor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TensorInfo {
+    /// Element type
+    pub dtype: Dtype,
+    /// Dimensions
+    pub shape: Vec<usize>,
+    /// Start and end of the tensor's bytes after the header
+    pub data_offsets: [usize; 2],
+}
+
+impl TensorInfo {
+    /// Number of elements
+    pub fn elements(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    /// Bytes in the file
+    pub fn len(&self) -> usize {
+        self.data_offsets[1] - self.data_offsets[0]
+    }
+
+    /// Whether the tensor has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Tensor quantized at load time
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedTensor {
+    /// Dimensions
+    pub shape: Vec<usize>,
+    /// Value of one quantization step
+    pub scale: f32,
+    /// Quantized elements
+    pub data: Vec<i8>,
+}
+
+/// A tensor of the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tensor<'a> {
+    /// Stored bytes, borrowed from the mapped file
+    Mapped {
+        /// Element type
+        dtype: Dtype,
+        /// Dimensions
+        shape: &'a [usize],
+        /// Little-endian elements
+        data: &'a [u8],
+    },
+    /// Quantized on load
+    Int8(&'a QuantizedTensor),
+}
+
+impl Tensor<'_> {
+    /// Elements as `f32`, dequantizing or converting as needed
+    pub fn to_f32(&self) -> Vec<f32> {
+        match self {
+            Tensor::Mapped { dtype, data, .. } => to_f32(*dtype, data),
+            Tensor::Int8(tensor) => tensor.data.iter().map(|q| *q as f32 * tensor.scale).collect(),
+        }
+    }
+}
+
+/// Mapped tensors resident in memory, least recently used first
+#[derive(Debug, Default)]
+struct Residency {
+    order: VecDeque<String>,
+    bytes: usize,
+}
+
+/// Weights of a safetensors model
+#[derive(Debug)]
+pub struct ModelWeights {
+    map: Mmap,
+    data_start: usize,
+    tensors: BTreeMap<String, TensorInfo>,
+    quantized: HashMap<String, QuantizedTensor>,
+    heap_bytes: usize,
+    limit: usize,
+    resident: Mutex<Residency>,
+}
+
+impl ModelWeights {
+    /// Open the model configured in `config`
+    pub fn from_config(config: &ColdMirrorConfig) -> ColdMirrorResult<Self> {
+        Self::open(
+            Path::new(&config.model_config.model_path),
+            &config.model_config.weights,
+            config.performance.memory_limit_mb,
+        )
+    }
+
+    /// Map a safetensors file, quantizing it if `config` asks to
+    pub fn open(path: &Path, config: &WeightConfig, memory_limit_mb: usize) -> ColdMirrorResult<Self> {
+        let load_error = |e: &dyn std::fmt::Display| ColdMirrorError::ModelLoadError(format!("{}: {}", path.display(), e));
+        let file = File::open(path).map_err(|e| load_error(&e))?;
+        // SAFETY: the model file is installed read-only by the patch orchestrator and
+        // replaced by rename, never written in place while mapped
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| load_error(&e))?;
+        if let Some(log) = &config.transparency {
+            // The mapped bytes are what gets used, so they are what is checked
+            let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+            let entry = log.verify::<Dilithium3>(ArtifactKind::Model, name, &map).map_err(|e| load_error(&e))?;
+            log::info!("Model {} {} is logged in {}", entry.name, entry.version, log.log_id);
+        }
+        #[cfg(unix)]
+        map.advise(memmap2::Advice::Random).map_err(|e| load_error(&e))?;
+
+        let (data_start, tensors) = parse_header(&map).map_err(|e| load_error(&e))?;
+        let limit = memory_limit_mb.saturating_mul(MIB);
+        let mut weights = Self {
+            map,
+            data_start,
+            tensors,
+            quantized: HashMap::new(),
+            heap_bytes: 0,
+            limit,
+            resident: Mutex::new(Residency::default()),
+        };
+
+        match config.quantization {
+            WeightQuantization::None => {
+                if let Some((name, info)) = weights.tensors.iter().find(|(_, info)| info.len() > limit) {
+                    return Err(over_limit(&format!("tensor {} ({} bytes)", name, info.len()), memory_limit_mb));
+                }
+            }
+            WeightQuantization::Int8 => weights.quantize(memory_limit_mb)?,
+        }
+        log::info!(
+            "Mapped {} tensors from {} ({:?}, {} bytes on the heap, limit {} MiB)",
+            weights.tensors.len(), path.display(), config.quantization, weights.heap_bytes, memory_limit_mb
+        );
+        Ok(weights)
+    }
+
+    /// Quantize every floating point tensor, after checking the total fits
+    fn quantize(&mut self, memory_limit_mb: usize) -> ColdMirrorResult<()> {
+        let floats: Vec<String> = self.tensors.iter()
+            .filter(|(_, info)| info.dtype.is_float())
+            .map(|(name, _)| name.clone())
+            .collect();
+        let quantized_bytes: usize = floats.iter().map(|name| self.tensors[name].elements()).sum();
+        if quantized_bytes > self.limit {
+            return Err(over_limit(&format!("{} quantized bytes", quantized_bytes), memory_limit_mb));
+        }
+        for name in floats {
+            let info = self.tensors[&name].clone();
+            let range = self.range(&info);
+            let values = to_f32(info.dtype, &self.map[range.clone()]);
+            let max = values.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+            let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+            let data = values.iter().map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8).collect();
+            self.release(range);
+            self.heap_bytes += info.elements();
+            self.quantized.insert(name, QuantizedTensor { shape: info.shape, scale, data });
+        }
+        Ok(())
+    }
+
+    /// Tensor names in the file
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tensors.keys().map(String::as_str)
+    }
+
+    /// Header entry of `name`
+    pub fn info(&self, name: &str) -> Option<&TensorInfo> {
+        self.tensors.get(name)
+    }
+
+    /// Tensor `name`, paging it in if it is mapped
+    ///
+    /// Tensors used longest ago are released until this one fits under the
+    /// memory limit.
+    pub fn tensor(&self, name: &str) -> ColdMirrorResult<Tensor<'_>> {
+        if let Some(tensor) = self.quantized.get(name) {
+            return Ok(Tensor::Int8(tensor));
+        }
+        let info = self.tensors.get(name)
+            .ok_or_else(|| ColdMirrorError::ModelLoadError(format!("No tensor named {}", name)))?;
+        let range = self.range(info);
+
+        let mut resident = self.resident.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(position) = resident.order.iter().position(|used| used == name) {
+            let used = resident.order.remove(position).expect("position is in range");
+            resident.order.push_back(used);
+        } else {
+            while resident.bytes + info.len() > self.limit.saturating_sub(self.heap_bytes) {
+                let Some(evicted) = resident.order.pop_front() else { break };
+                let evicted_info = &self.tensors[&evicted];
+                resident.bytes -= evicted_info.len();
+                self.release(self.range(evicted_info));
+                log::debug!("Released tensor {} to stay under the memory limit", evicted);
+            }
+            if resident.bytes + info.len() > self.limit.saturating_sub(self.heap_bytes) {
+                return Err(over_limit(&format!("tensor {} ({} bytes)", name, info.len()), self.limit / MIB));
+            }
+            resident.bytes += info.len();
+            resident.order.push_back(name.to_string());
+            #[cfg(unix)]
+            if let Err(e) = self.map.advise_range(memmap2::Advice::WillNeed, range.start, range.len()) {
+                log::debug!("Could not prefetch tensor {}: {}", name, e);
+            }
+        }
+        Ok(Tensor::Mapped { dtype: info.dtype, shape: &info.shape, data: &self.map[range] })
+    }
+
+    /// Bytes of mapped tensors currently counted as resident
+    pub fn resident_bytes(&self) -> usize {
+        self.resident.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).bytes
+    }
+
+    /// Bytes held on the heap by quantized tensors
+    pub fn heap_bytes(&self) -> usize {
+        self.heap_bytes
+    }
+
+    /// Byte range of a tensor in the mapped file
+    fn range(&self, info: &TensorInfo) -> std::ops::Range<usize> {
+        self.data_start + info.data_offsets[0]..self.data_start + info.data_offsets[1]
+    }
+
+    /// Drop the pages of `range` back to the page cache
+    fn release(&self, range: std::ops::Range<usize>) {
+        #[cfg(unix)]
+        {
+            // SAFETY: the mapping is a read-only view of a file; released pages are
+            // read back from the file on next access, so borrowed slices stay valid
+            let released = unsafe {
+                self.map.unchecked_advise_range(memmap2::UncheckedAdvice::DontNeed, range.start, range.len())
+            };
+            if let Err(e) = released {
+                log::debug!("Could not release {:?}: {}", range, e);
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = range;
+    }
+}
+
+/// Error for a model that does not fit in `memory_limit_mb`
+fn over_limit(what: &str, memory_limit_mb: usize) -> ColdMirrorError {
+    ColdMirrorError::ResourceError(format!(
+        "Model weights do not fit in memory_limit_mb = {}: {} over the limit", memory_limit_mb, what
+    ))
+}
+
+/// Parse the header of a safetensors file, returning where tensor data starts
+fn parse_header(bytes: &[u8]) -> Result<(usize, BTreeMap<String, TensorInfo>), String> {
+    let length: [u8; 8] = bytes.get(..8)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or("file is too short for a safetensors header")?;
+    let length = usize::try_from(u64::from_le_bytes(length)).map_err(|_| "header length overflows")?;
+    let data_start = 8usize.checked_add(length).filter(|end| *end <= bytes.len())
+        .ok_or("header runs past the end of the file")?;
+    let mut entries: BTreeMap<String, serde_json::Value> = decode::json(&bytes[8..data_start], &HEADER_LIMITS)
+        .map_err(|e| e.to_string())?;
+    entries.remove(METADATA_KEY);
+
+    let data_len = bytes.len() - data_start;
+    let mut tensors = BTreeMap::new();
+    for (name, entry) in entries {
+        let info: TensorInfo = serde_json::from_value(entry).map_err(|e| format!("tensor {}: {}", name, e))?;
+        let [start, end] = info.data_offsets;
+        let expected = info.shape.iter().try_fold(info.dtype.size(), |size, dim| size.checked_mul(*dim));
+        if start > end || end > data_len || expected != Some(end - start) {
+            return Err(format!("tensor {} has offsets {:?} that do not match its shape", name, info.data_offsets));
+        }
+        tensors.insert(name, info);
+    }
+    Ok((data_start, tensors))
+}
+
+/// Convert little-endian elements to `f32`
+fn to_f32(dtype: Dtype, data: &[u8]) -> Vec<f32> {
+    let half = |bits: u16| {
+        let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+        let exponent = i32::from((bits >> 10) & 0x1f);
+        let fraction = f32::from(bits & 0x3ff);
+        match exponent {
+            0 => sign * fraction * 2f32.powi(-24),
+            0x1f if fraction == 0.0 => sign * f32::INFINITY,
+            0x1f => f32::NAN,
+            _ => sign * (1.0 + fraction / 1024.0) * 2f32.powi(exponent - 15),
+        }
+    };
+    match dtype {
+        Dtype::BOOL | Dtype::U8 => data.iter().map(|b| f32::from(*b)).collect(),
+        Dtype::I8 => data.iter().map(|b| f32::from(*b as i8)).collect(),
+        Dtype::I16 => data.chunks_exact(2).map(|c| f32::from(i16::from_le_bytes([c[0], c[1]]))).collect(),
+        Dtype::I32 => data.chunks_exact(4).map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32).collect(),
+        Dtype::I64 => data.chunks_exact(8).map(|c| i64::from_le_bytes(c.try_into().expect("8 bytes")) as f32).collect(),
+        Dtype::F16 => data.chunks_exact(2).map(|c| half(u16::from_le_bytes([c[0], c[1]]))).collect(),
+        Dtype::BF16 => data.chunks_exact(2).map(|c| f32::from_bits(u32::from(u16::from_le_bytes([c[0], c[1]])) << 16)).collect(),
+        Dtype::F32 => data.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect(),
+        Dtype::F64 => data.chunks_exact(8).map(|c| f64::from_le_bytes(c.try_into().expect("8 bytes")) as f32).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a safetensors file of F32 tensors
+    fn write_model(path: &Path, tensors: &[(&str, Vec<f32>)]) {
+        let mut header = serde_json::Map::new();
+        header.insert(METADATA_KEY.into(), serde_json::json!({ "format": "pt" }));
+        let mut data = Vec::new();
+        for (name, values) in tensors {
+            let start = data.len();
+            data.extend(values.iter().flat_map(|v| v.to_le_bytes()));
+            header.insert(name.to_string(), serde_json::json!({
+                "dtype": "F32", "shape": [values.len()], "data_offsets": [start, data.len()],
+            }));
+        }
+        let header = serde_json::to_vec(&header).unwrap();
+        let mut file = (header.len() as u64).to_le_bytes().to_vec();
+        file.extend(header);
+        file.extend(data);
+        std::fs::write(path, file).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_tensors_stay_under_the_memory_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.safetensors");
+        // Two 600 KiB tensors cannot both be resident under a 1 MiB limit
+        let embed: Vec<f32> = (0..153_600).map(|i| (i % 255) as f32 / 255.0 - 0.5).collect();
+        let head = vec![0.25f32; 153_600];
+        write_model(&path, &[("embed", embed.clone()), ("head", head)]);
+
+        let weights = ModelWeights::open(&path, &WeightConfig::default(), 1).unwrap();
+        assert_eq!(weights.names().collect::<Vec<_>>(), ["embed", "head"]);
+        assert_eq!(weights.resident_bytes(), 0);
+        assert_eq!(weights.tensor("embed").unwrap().to_f32(), embed);
+        assert_eq!(weights.tensor("head").unwrap().to_f32()[0], 0.25);
+        assert_eq!(weights.resident_bytes(), 153_600 * 4);
+
+        // Quantized, both fit on the heap and stay within one step of the original
+        let config = WeightConfig { quantization: WeightQuantization::Int8, ..WeightConfig::default() };
+        let quantized = ModelWeights::open(&path, &config, 1).unwrap();
+        assert_eq!(quantized.heap_bytes(), 2 * 153_600);
+        let Tensor::Int8(tensor) = quantized.tensor("embed").unwrap() else { panic!("not quantized") };
+        let restored = Tensor::Int8(tensor).to_f32();
+        assert!(embed.iter().zip(&restored).all(|(a, b)| (a - b).abs() <= tensor.scale));
+
+        let huge = dir.path().join("huge.safetensors");
+        write_model(&huge, &[("huge", vec![0.0; 300_000])]);
+        let refused = ModelWeights::open(&huge, &WeightConfig::default(), 1);
+        assert!(matches!(refused, Err(ColdMirrorError::ResourceError(message)) if message.contains("huge")));
+    }
+
+    #[test]
+    fn test_models_without_a_log_proof_are_refused() {
+        use pq_types::scheme::SignatureScheme;
+        use pq_types::transparency::{ArtifactProof, LogEntry, LogHash, MerkleTree, SignedTreeHead, TreeHead};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("guardian.safetensors");
+        write_model(&path, &[("head", vec![0.5f32; 16])]);
+        let (public, secret) = Dilithium3::keypair().unwrap();
+        let config = WeightConfig {
+            transparency: Some(LogRequirement {
+                log_id: "ark-log".into(),
+                log_public_key: public.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect(),
+                proof: dir.path().join("guardian.proof.json"),
+            }),
+            ..WeightConfig::default()
+        };
+        let refused = ModelWeights::open(&path, &config, 1);
+        assert!(matches!(refused, Err(ColdMirrorError::ModelLoadError(_))));
+
+        let entry = LogEntry { kind: ArtifactKind::Model, name: "guardian".into(), version: "3".into(), digest: LogHash::digest(&std::fs::read(&path).unwrap()) };
+        let mut tree = MerkleTree::new();
+        tree.push(&entry);
+        let head = TreeHead { log_id: "ark-log".into(), size: 1, root: tree.root_at(1).unwrap(), timestamp: 1_700_000_000 };
+        let proof = ArtifactProof { entry, inclusion: tree.inclusion_proof(0, 1).unwrap(), tree_head: SignedTreeHead::sign::<Dilithium3>(head, &secret).unwrap() };
+        std::fs::write(dir.path().join("guardian.proof.json"), serde_json::to_vec(&proof).unwrap()).unwrap();
+        assert_eq!(ModelWeights::open(&path, &config, 1).unwrap().names().count(), 1);
+
+        // Weights changed after logging no longer match the proof
+        write_model(&path, &[("head", vec![0.75f32; 16])]);
+        assert!(matches!(ModelWeights::open(&path, &config, 1), Err(ColdMirrorError::ModelLoadError(_))));
+    }
+}
@@ -0,0 +1,79 @@
+//! Throughput of viral content evaluated for many actors, with and
+//! without the content memo
+//!
+//! Each batch carries `CONTENTS` distinct posts, each shared by `SHARERS`
+//! actors of varying trust and tags. Without the memo every event is
+//! analyzed in full; with it each post is analyzed once per batch and the
+//! rest reuse its analyses. Run with `cargo bench --bench content_memo`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ethics_dsl::utils::create_event;
+use ethics_dsl::{
+    Actor, ActorType, Content, ContentType, Context, EthicsConfig, EthicsEngine, EthicsEvaluator, EthicsEvent,
+    MemoConfig, UrgencyLevel,
+};
+use std::collections::HashMap;
+
+const CONTENTS: usize = 20;
+const SHARERS: usize = 50;
+
+fn viral_events() -> Vec<EthicsEvent> {
+    let mut events = Vec::with_capacity(CONTENTS * SHARERS);
+    for sharer in 0..SHARERS {
+        for post in 0..CONTENTS {
+            let actor = Actor {
+                actor_type: ActorType::Person,
+                tags: if sharer % 5 == 0 { vec!["DECEPTION".to_string()] } else { vec![] },
+                trust_level: (sharer % 10) as f64 / 10.0,
+                history: None,
+            };
+            let content = Content {
+                content_type: ContentType::Text,
+                data: format!("Post {}: {}", post, "a widely shared message about the news of the day ".repeat(20)),
+                metadata: HashMap::from([("received_by".to_string(), format!("relay-{}", sharer))]),
+                content_hash: String::new(),
+            };
+            let context = Context {
+                location: None,
+                culture: None,
+                platform: Some("social".to_string()),
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            };
+            events.push(create_event(format!("share-{}-{}", post, sharer), actor, Some(content), context));
+        }
+    }
+    events
+}
+
+fn bench_content_memo(c: &mut Criterion) {
+    let events = viral_events();
+    let mut group = c.benchmark_group("viral_content");
+    group.throughput(Throughput::Elements(events.len() as u64));
+
+    for enabled in [false, true] {
+        let label = if enabled { "memo" } else { "no_memo" };
+        group.bench_with_input(BenchmarkId::new("evaluate", label), &enabled, |b, &enabled| {
+            b.iter_batched(
+                || {
+                    let config = EthicsConfig {
+                        content_memo: MemoConfig { enabled, ..MemoConfig::default() },
+                        ..EthicsConfig::default()
+                    };
+                    EthicsEngine::new(config).expect("engine")
+                },
+                |engine| {
+                    for event in &events {
+                        black_box(engine.evaluate(event).expect("evaluation"));
+                    }
+                    engine.memo_stats()
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_content_memo);
+criterion_main!(benches);
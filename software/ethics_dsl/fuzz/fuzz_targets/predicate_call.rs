@@ -0,0 +1,36 @@
+//! Predicate Call Fuzzing - Untrusted Rule Expressions
+//! "Prove all things; hold fast that which is good" - 1 Thessalonians 5:21
+//!
+//! Feeds arbitrary bytes to the predicate call parser and evaluates whatever
+//! parses against a fixed event. Any panic is a bug; inputs that found one
+//! belong in `regressions/predicate_call`, which the crate's tests replay.
+
+#![no_main]
+
+use ethics_dsl::{Actor, ActorType, Context, EthicsEvent, PredicateRegistry, UrgencyLevel};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let expression = String::from_utf8_lossy(data);
+    if ethics_dsl::parse_call(&expression).is_ok() {
+        let event = EthicsEvent {
+            event_id: "fuzz".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Person,
+                tags: vec!["Deception".to_string()],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: None,
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: chrono::Utc::now(),
+        };
+        let _ = PredicateRegistry::standard().evaluate(&expression, &event);
+    }
+});
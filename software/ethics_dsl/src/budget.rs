@@ -0,0 +1,163 @@
+//! Latency Budgets - One Deadline Across the Pipeline
+//! "So teach us to number our days, that we may apply our hearts unto wisdom" - Psalm 90:12
+//!
+//! Ethics evaluation, harm prediction and actuation each have a timeout of
+//! their own, but a request has a single end-to-end deadline. A
+//! `LatencyBudget` is started when content is ingested and handed from stage
+//! to stage. Before expensive work a stage checks whether its usual cost
+//! still fits in what remains; when it does not, the stage takes a faster
+//! path (a cached decision, the lexical predictor) and records the
+//! degradation, so the final verdict shows which stages were cut short.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Stages a request passes through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PipelineStage {
+    /// Normalization, hashing and deduplication
+    Ingestion,
+    /// Ethics engine evaluation
+    EthicsEvaluation,
+    /// Cold-Mirror harm prediction
+    HarmPrediction,
+    /// Selecting and carrying out the action
+    Actuation,
+}
+
+/// A stage that took a faster path because the budget was tight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Degradation {
+    /// Stage that degraded
+    pub stage: PipelineStage,
+    /// Faster path taken instead (e.g. `cached decision`)
+    pub fallback: String,
+    /// Budget left when the stage degraded
+    pub remaining: Duration,
+}
+
+/// Deadline for one request, shared by every stage
+#[derive(Debug, Clone)]
+pub struct LatencyBudget {
+    total: Duration,
+    started: Instant,
+    degraded: Vec<Degradation>,
+}
+
+impl LatencyBudget {
+    /// Start a budget of `total`, measured from now
+    pub fn new(total: Duration) -> Self {
+        Self {
+            total,
+            started: Instant::now(),
+            degraded: Vec::new(),
+        }
+    }
+
+    /// A budget that never runs out
+    pub fn unbounded() -> Self {
+        Self::new(Duration::MAX)
+    }
+
+    /// Total budget
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Time spent since the budget started
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Time left before the deadline
+    pub fn remaining(&self) -> Duration {
+        self.total.saturating_sub(self.elapsed())
+    }
+
+    /// Whether the deadline has passed
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Whether work expected to take `cost` still fits before the deadline
+    pub fn allows(&self, cost: Duration) -> bool {
+        self.remaining() >= cost
+    }
+
+    /// Record that `stage` took a faster path
+    pub fn degrade(&mut self, stage: PipelineStage, fallback: impl Into<String>) {
+        let degradation = Degradation {
+            stage,
+            fallback: fallback.into(),
+            remaining: self.remaining(),
+        };
+        log::debug!("{:?} degraded to {} with {:?} left", stage, degradation.fallback, degradation.remaining);
+        self.degraded.push(degradation);
+    }
+
+    /// Degradations recorded so far, in order
+    pub fn degradations(&self) -> &[Degradation] {
+        &self.degraded
+    }
+
+    /// Whether `stage` degraded
+    pub fn is_degraded(&self, stage: PipelineStage) -> bool {
+        self.degraded.iter().any(|degradation| degradation.stage == stage)
+    }
+
+    /// Summary for the final verdict
+    pub fn report(&self) -> BudgetReport {
+        BudgetReport {
+            budget: self.total,
+            elapsed: self.elapsed(),
+            exceeded: self.is_exhausted(),
+            degraded: self.degraded.clone(),
+        }
+    }
+}
+
+/// How a request spent its latency budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetReport {
+    /// Total budget
+    pub budget: Duration,
+    /// Time taken
+    pub elapsed: Duration,
+    /// Whether the deadline was missed
+    pub exceeded: bool,
+    /// Stages that took a faster path
+    pub degraded: Vec<Degradation>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_allows_work_that_fits() {
+        let budget = LatencyBudget::new(Duration::from_secs(60));
+        assert!(budget.allows(Duration::from_millis(50)));
+        assert!(!budget.allows(Duration::from_secs(61)));
+        assert!(!budget.is_exhausted());
+
+        let unbounded = LatencyBudget::unbounded();
+        assert!(unbounded.allows(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_exhausted_budget_reports_degradations() {
+        let mut budget = LatencyBudget::new(Duration::ZERO);
+        assert!(budget.is_exhausted());
+        assert!(!budget.allows(Duration::from_millis(1)));
+
+        budget.degrade(PipelineStage::HarmPrediction, "lexical predictor");
+        assert!(budget.is_degraded(PipelineStage::HarmPrediction));
+        assert!(!budget.is_degraded(PipelineStage::EthicsEvaluation));
+
+        let report = budget.report();
+        assert!(report.exceeded);
+        assert_eq!(report.degraded.len(), 1);
+        assert_eq!(report.degraded[0].fallback, "lexical predictor");
+        assert_eq!(report.degraded[0].remaining, Duration::ZERO);
+    }
+}
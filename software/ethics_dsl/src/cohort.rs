@@ -0,0 +1,540 @@
+//! Actor Cohorts - Clustering Actors by Violation Patterns
+//! "My son, if sinners entice thee, consent thou not" - Proverbs 1:10
+//!
+//! The decision journal records what was decided but not for whom. With
+//! `EthicsConfig::actor_ledger` set, the engine also appends the actor of
+//! every event whose actor it knows - the producer of a signed envelope or
+//! the actor named to `evaluate_issued` - to an actor ledger keyed by event
+//! id. With pseudonymization configured the ledger holds pseudonyms only.
+//!
+//! `analyze` joins the ledger with the journal and groups actors whose
+//! refusals cite the same principles at the same hours of the day. A group
+//! of at least `min_members` actors is reported as a cohort, with its most
+//! cited principles, how closely its members' refusals follow one another
+//! and the events that represent it best. A cohort whose first refusal
+//! falls within `emerging_days` is emerging; one whose refusals mostly
+//! arrive within `burst_minutes` of another member's is coordinated, the
+//! mark of a manipulation campaign. Once an analysis is installed in the
+//! engine, events of a member carry a `cohort:<id>` actor tag and the
+//! membership counts against the actor's standing.
+
+use crate::sinks::decision_severity;
+use crate::{EthicsDecision, EthicsError, EthicsResult, JournalEntry};
+use chrono::{DateTime, Duration, Timelike, Utc};
+use log::warn;
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Prefix of the actor tag naming an actor's cohort
+pub const COHORT_TAG_PREFIX: &str = "cohort:";
+
+/// Principles reported per cohort
+const TOP_PRINCIPLES: usize = 5;
+
+/// Actor of one evaluated event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// When the event was evaluated
+    pub recorded_at: DateTime<Utc>,
+    /// Event id, as in the decision journal
+    pub event_id: String,
+    /// Actor identifier or pseudonym
+    pub actor: String,
+}
+
+/// Append-only ledger of the actors of evaluated events
+#[derive(Debug)]
+pub struct ActorLedger {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl ActorLedger {
+    /// Open `path` for appending, creating it if missing
+    pub fn open(path: impl Into<PathBuf>) -> EthicsResult<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    /// Ledger file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record `actor` as the actor of `event_id`
+    pub fn record(&self, event_id: &str, actor: &str) -> EthicsResult<()> {
+        let entry = LedgerEntry { recorded_at: Utc::now(), event_id: event_id.to_string(), actor: actor.to_string() };
+        let mut line = serde_json::to_vec(&entry)
+            .map_err(|e| EthicsError::RuntimeError(format!("Actor ledger entry: {}", e)))?;
+        line.push(b'\n');
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        file.write_all(&line)
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", self.path.display(), e)))
+    }
+}
+
+/// Read the ledger entries recorded at or after `since`, skipping lines that fail to parse
+pub fn read_ledger_since(path: &Path, since: DateTime<Utc>) -> EthicsResult<Vec<LedgerEntry>> {
+    let file = File::open(path).map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))?;
+    let mut entries = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match decode::json::<LedgerEntry>(line.as_bytes(), &DecodeLimits::RECORD) {
+            Ok(entry) if entry.recorded_at >= since => entries.push(entry),
+            Ok(_) => {}
+            Err(e) => warn!("Skipping ledger line {} of {}: {}", line_number + 1, path.display(), e),
+        }
+    }
+    Ok(entries)
+}
+
+/// Clustering and risk parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CohortConfig {
+    /// Refusals an actor needs before it is clustered
+    pub min_refusals: usize,
+    /// Similarity (0.0-1.0) at which two actors are grouped
+    pub similarity: f64,
+    /// Share of the similarity taken from hour-of-day activity rather than principles
+    pub temporal_weight: f64,
+    /// Actors a group needs to be reported as a cohort
+    pub min_members: usize,
+    /// A cohort whose first refusal is this recent is emerging
+    pub emerging_days: i64,
+    /// Refusals this close to another member's count as coordinated
+    pub burst_minutes: i64,
+    /// Share of coordinated refusals at which a cohort is coordinated
+    pub coordination_threshold: f64,
+    /// Most actors clustered per analysis, those with the most refusals first
+    pub max_actors: usize,
+    /// Representative events reported per cohort
+    pub representative_events: usize,
+    /// Standing lost by a member of an emerging or coordinated cohort
+    pub history_penalty: f64,
+}
+
+impl Default for CohortConfig {
+    fn default() -> Self {
+        Self {
+            min_refusals: 3,
+            similarity: 0.8,
+            temporal_weight: 0.3,
+            min_members: 3,
+            emerging_days: 7,
+            burst_minutes: 60,
+            coordination_threshold: 0.5,
+            max_actors: 5000,
+            representative_events: 5,
+            history_penalty: 0.2,
+        }
+    }
+}
+
+/// Event chosen to represent a cohort
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepresentativeEvent {
+    /// Event id
+    pub event_id: String,
+    /// Member the event came from
+    pub actor: String,
+    /// When it was refused
+    pub recorded_at: DateTime<Utc>,
+    /// Principles it violated
+    pub principles: Vec<String>,
+    /// Severity of its decision (1-10)
+    pub severity: u8,
+}
+
+/// Group of actors refused for the same principles at the same times
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CohortReport {
+    /// Id derived from the members
+    pub id: String,
+    /// Member actors, sorted
+    pub members: Vec<String>,
+    /// Most cited principles with their refusal counts
+    pub principles: Vec<(String, usize)>,
+    /// Refusals of all members
+    pub refusals: usize,
+    /// First refusal of any member
+    pub first_seen: DateTime<Utc>,
+    /// Last refusal of any member
+    pub last_seen: DateTime<Utc>,
+    /// Share of refusals within `burst_minutes` of another member's
+    pub coordination: f64,
+    /// First refusal within `emerging_days`
+    pub emerging: bool,
+    /// Coordination at or above `coordination_threshold`
+    pub coordinated: bool,
+    /// Most severe refusals, one per member first
+    pub representative_events: Vec<RepresentativeEvent>,
+}
+
+/// Outcome of one cohort analysis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CohortAnalysis {
+    /// When the analysis ran
+    pub analyzed_at: DateTime<Utc>,
+    /// Actors with enough refusals to be clustered
+    pub actors: usize,
+    /// Cohorts, largest first
+    pub cohorts: Vec<CohortReport>,
+}
+
+impl CohortAnalysis {
+    /// Cohorts that are emerging or coordinated
+    pub fn threats(&self) -> impl Iterator<Item = &CohortReport> {
+        self.cohorts.iter().filter(|cohort| cohort.emerging || cohort.coordinated)
+    }
+}
+
+/// Cohort an actor belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CohortMembership {
+    /// Cohort id
+    pub cohort: String,
+    /// Whether the cohort is emerging
+    pub emerging: bool,
+    /// Whether the cohort is coordinated
+    pub coordinated: bool,
+}
+
+impl CohortMembership {
+    /// Actor tag naming the cohort
+    pub fn tag(&self) -> String {
+        format!("{}{}", COHORT_TAG_PREFIX, self.cohort)
+    }
+}
+
+/// Cohort memberships of an installed analysis
+#[derive(Debug, Clone, Default)]
+pub struct CohortIndex {
+    actors: HashMap<String, CohortMembership>,
+    cohorts: HashMap<String, CohortMembership>,
+}
+
+impl CohortIndex {
+    /// Index of the cohorts of `analysis`
+    pub fn from_analysis(analysis: &CohortAnalysis) -> Self {
+        let mut index = Self::default();
+        for cohort in &analysis.cohorts {
+            let membership = CohortMembership {
+                cohort: cohort.id.clone(),
+                emerging: cohort.emerging,
+                coordinated: cohort.coordinated,
+            };
+            for member in &cohort.members {
+                index.actors.insert(member.clone(), membership.clone());
+            }
+            index.cohorts.insert(cohort.id.clone(), membership);
+        }
+        index
+    }
+
+    /// Cohort of `actor`, if any
+    pub fn membership(&self, actor: &str) -> Option<&CohortMembership> {
+        self.actors.get(actor)
+    }
+
+    /// Cohort named by an actor tag, if the tag names a known cohort
+    pub fn tagged(&self, tag: &str) -> Option<&CohortMembership> {
+        self.cohorts.get(tag.strip_prefix(COHORT_TAG_PREFIX)?)
+    }
+
+    /// Number of cohorts
+    pub fn len(&self) -> usize {
+        self.cohorts.len()
+    }
+
+    /// Whether no cohorts are installed
+    pub fn is_empty(&self) -> bool {
+        self.cohorts.is_empty()
+    }
+}
+
+/// One refused event of an actor
+struct Refusal<'a> {
+    event_id: &'a str,
+    actor: &'a str,
+    recorded_at: DateTime<Utc>,
+    principles: &'a [String],
+    severity: u8,
+}
+
+/// Violation pattern and temporal behavior of one actor
+struct Profile<'a> {
+    refusals: Vec<Refusal<'a>>,
+    principles: BTreeMap<&'a str, f64>,
+    hours: [f64; 24],
+}
+
+impl Profile<'_> {
+    fn similarity(&self, other: &Profile<'_>, temporal_weight: f64) -> f64 {
+        let dot: f64 = self.principles.iter()
+            .filter_map(|(principle, count)| other.principles.get(principle).map(|other| count * other))
+            .sum();
+        let principles = cosine(dot, self.principles.values(), other.principles.values());
+        let dot: f64 = self.hours.iter().zip(&other.hours).map(|(a, b)| a * b).sum();
+        let hours = cosine(dot, self.hours.iter(), other.hours.iter());
+        (1.0 - temporal_weight) * principles + temporal_weight * hours
+    }
+}
+
+fn cosine<'a>(dot: f64, a: impl Iterator<Item = &'a f64>, b: impl Iterator<Item = &'a f64>) -> f64 {
+    let norm = |values: Vec<&f64>| values.iter().map(|v| *v * *v).sum::<f64>().sqrt();
+    let (a, b) = (norm(a.collect()), norm(b.collect()));
+    if a == 0.0 || b == 0.0 {
+        0.0
+    } else {
+        dot / (a * b)
+    }
+}
+
+/// Cluster the actors of `ledger` by their refusals in `journal`
+pub fn analyze(ledger: &[LedgerEntry], journal: &[JournalEntry], config: &CohortConfig, now: DateTime<Utc>) -> CohortAnalysis {
+    let actors: HashMap<&str, &str> = ledger.iter()
+        .map(|entry| (entry.event_id.as_str(), entry.actor.as_str()))
+        .collect();
+
+    let mut profiles: BTreeMap<&str, Profile> = BTreeMap::new();
+    for entry in journal {
+        let principles = match &entry.decision {
+            EthicsDecision::Allow { .. } => continue,
+            EthicsDecision::Deny { violated_principles, .. } | EthicsDecision::Purge { violated_principles, .. } => {
+                violated_principles
+            }
+        };
+        let Some(actor) = actors.get(entry.event.event_id.as_str()).copied() else {
+            continue;
+        };
+        let profile = profiles.entry(actor).or_insert_with(|| Profile {
+            refusals: Vec::new(),
+            principles: BTreeMap::new(),
+            hours: [0.0; 24],
+        });
+        for principle in principles {
+            *profile.principles.entry(principle.as_str()).or_default() += 1.0;
+        }
+        profile.hours[entry.recorded_at.hour() as usize] += 1.0;
+        profile.refusals.push(Refusal {
+            event_id: &entry.event.event_id,
+            actor,
+            recorded_at: entry.recorded_at,
+            principles,
+            severity: decision_severity(&entry.decision),
+        });
+    }
+
+    let mut candidates: Vec<(&str, Profile)> = profiles.into_iter()
+        .filter(|(_, profile)| profile.refusals.len() >= config.min_refusals)
+        .collect();
+    if candidates.len() > config.max_actors {
+        warn!("Clustering the {} of {} actors with the most refusals", config.max_actors, candidates.len());
+        candidates.sort_by(|(a, pa), (b, pb)| pb.refusals.len().cmp(&pa.refusals.len()).then(a.cmp(b)));
+        candidates.truncate(config.max_actors);
+        candidates.sort_by_key(|(actor, _)| *actor);
+    }
+
+    // Single-linkage grouping: actors similar to any member join the group
+    let mut parent: Vec<usize> = (0..candidates.len()).collect();
+    for i in 0..candidates.len() {
+        for j in i + 1..candidates.len() {
+            if candidates[i].1.similarity(&candidates[j].1, config.temporal_weight) >= config.similarity {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..candidates.len() {
+        groups.entry(root(&mut parent, i)).or_default().push(i);
+    }
+
+    let mut cohorts: Vec<CohortReport> = groups.into_values()
+        .filter(|group| group.len() >= config.min_members.max(2))
+        .map(|group| report(group.iter().map(|&i| &candidates[i]), config, now))
+        .collect();
+    cohorts.sort_by(|a, b| b.members.len().cmp(&a.members.len()).then(b.refusals.cmp(&a.refusals)));
+
+    CohortAnalysis { analyzed_at: now, actors: candidates.len(), cohorts }
+}
+
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn report<'a, 'b: 'a>(
+    members: impl Iterator<Item = &'a (&'b str, Profile<'b>)>,
+    config: &CohortConfig,
+    now: DateTime<Utc>,
+) -> CohortReport {
+    let mut names = Vec::new();
+    let mut refusals: Vec<&Refusal> = Vec::new();
+    for (actor, profile) in members {
+        names.push(actor.to_string());
+        refusals.extend(&profile.refusals);
+    }
+    refusals.sort_by_key(|refusal| refusal.recorded_at);
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for refusal in &refusals {
+        for principle in refusal.principles {
+            *counts.entry(principle.as_str()).or_default() += 1;
+        }
+    }
+    let mut principles: Vec<(String, usize)> = counts.into_iter().map(|(p, n)| (p.to_string(), n)).collect();
+    principles.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    principles.truncate(TOP_PRINCIPLES);
+
+    // A refusal is coordinated when another member was refused within the burst window
+    let burst = Duration::minutes(config.burst_minutes);
+    let near_other = |i: usize| {
+        let refusal = refusals[i];
+        refusals[..i].iter().rev().take_while(|other| refusal.recorded_at - other.recorded_at <= burst)
+            .chain(refusals[i + 1..].iter().take_while(|other| other.recorded_at - refusal.recorded_at <= burst))
+            .any(|other| other.actor != refusal.actor)
+    };
+    let coordinated_refusals = (0..refusals.len()).filter(|&i| near_other(i)).count();
+    let coordination = coordinated_refusals as f64 / refusals.len().max(1) as f64;
+
+    // Most severe refusals first, covering as many members as possible
+    let mut by_severity = refusals.clone();
+    by_severity.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.recorded_at.cmp(&b.recorded_at)));
+    let mut represented = BTreeSet::new();
+    let (first_pass, rest): (Vec<&Refusal>, Vec<&Refusal>) =
+        by_severity.into_iter().partition(|refusal| represented.insert(refusal.actor));
+    let representative_events = first_pass.into_iter().chain(rest)
+        .take(config.representative_events)
+        .map(|refusal| RepresentativeEvent {
+            event_id: refusal.event_id.to_string(),
+            actor: refusal.actor.to_string(),
+            recorded_at: refusal.recorded_at,
+            principles: refusal.principles.to_vec(),
+            severity: refusal.severity,
+        })
+        .collect();
+
+    let first_seen = refusals.first().map(|refusal| refusal.recorded_at).unwrap_or(now);
+    let last_seen = refusals.last().map(|refusal| refusal.recorded_at).unwrap_or(now);
+    let id = blake3::hash(names.join("\n").as_bytes()).to_hex()[..12].to_string();
+    CohortReport {
+        id: format!("c{}", id),
+        members: names,
+        principles,
+        refusals: refusals.len(),
+        first_seen,
+        last_seen,
+        coordination,
+        emerging: first_seen >= now - Duration::days(config.emerging_days),
+        coordinated: coordination >= config.coordination_threshold,
+        representative_events,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+
+    fn entry(event_id: &str, at: DateTime<Utc>, principles: &[&str]) -> JournalEntry {
+        let event = crate::utils::create_event(
+            event_id.to_string(),
+            Actor { actor_type: ActorType::Person, tags: vec![], trust_level: 0.5, history: None },
+            None,
+            Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+        );
+        let decision = if principles.is_empty() {
+            EthicsDecision::Allow { confidence: 0.9, justification: String::new(), scripture_refs: vec![] }
+        } else {
+            EthicsDecision::Deny {
+                confidence: 0.9,
+                violation: "test".to_string(),
+                violated_principles: principles.iter().map(|p| p.to_string()).collect(),
+                scripture_refs: vec![],
+            }
+        };
+        JournalEntry { recorded_at: at, event, decision }
+    }
+
+    /// Ledger and journal where `campaign` actors are refused for deception together
+    fn traffic(now: DateTime<Utc>) -> (Vec<LedgerEntry>, Vec<JournalEntry>) {
+        let (mut ledger, mut journal) = (Vec::new(), Vec::new());
+        let mut push = |actor: &str, at: DateTime<Utc>, principles: &[&str]| {
+            let event_id = format!("{}-{}", actor, journal.len());
+            ledger.push(LedgerEntry { recorded_at: at, event_id: event_id.clone(), actor: actor.to_string() });
+            journal.push(entry(&event_id, at, principles));
+        };
+        for wave in 0..4 {
+            let start = now - Duration::days(2) + Duration::hours(wave * 6);
+            for (n, actor) in ["ps_a", "ps_b", "ps_c", "ps_d"].iter().enumerate() {
+                push(actor, start + Duration::minutes(n as i64 * 5), &["TRUTH_OVER_LIES"]);
+            }
+        }
+        // A lone long-standing offender and a well-behaved actor
+        for day in 0..4 {
+            push("ps_lone", now - Duration::days(30 + day), &["SANCTITY_OF_LIFE"]);
+            push("ps_good", now - Duration::days(day), &[]);
+        }
+        (ledger, journal)
+    }
+
+    #[test]
+    fn test_coordinated_campaign_forms_emerging_cohort() {
+        let now = Utc::now();
+        let (ledger, journal) = traffic(now);
+        let analysis = analyze(&ledger, &journal, &CohortConfig::default(), now);
+
+        assert_eq!(analysis.actors, 5);
+        assert_eq!(analysis.cohorts.len(), 1);
+        let cohort = &analysis.cohorts[0];
+        assert_eq!(cohort.members, ["ps_a", "ps_b", "ps_c", "ps_d"]);
+        assert_eq!(cohort.principles, [("TRUTH_OVER_LIES".to_string(), 16)]);
+        assert!(cohort.emerging && cohort.coordinated);
+        assert_eq!(cohort.coordination, 1.0);
+        let represented: BTreeSet<&str> = cohort.representative_events.iter().take(4).map(|e| e.actor.as_str()).collect();
+        assert_eq!(represented.len(), 4);
+        assert_eq!(analysis.threats().count(), 1);
+
+        let index = CohortIndex::from_analysis(&analysis);
+        let membership = index.membership("ps_b").unwrap();
+        assert_eq!(index.tagged(&membership.tag()), Some(membership));
+        assert!(index.membership("ps_lone").is_none());
+        assert!(index.tagged("cohort:unknown").is_none());
+    }
+
+    #[test]
+    fn test_ledger_reads_back_recent_entries() {
+        let path = std::env::temp_dir().join(format!("ethics_actor_ledger_{}.jsonl", std::process::id()));
+        let old = LedgerEntry { recorded_at: Utc::now() - Duration::days(10), event_id: "old".into(), actor: "ps_x".into() };
+        std::fs::write(&path, format!("{}\ngarbage\n", serde_json::to_string(&old).unwrap())).unwrap();
+
+        let ledger = ActorLedger::open(&path).unwrap();
+        ledger.record("recent", "ps_y").unwrap();
+
+        let entries = read_ledger_since(ledger.path(), Utc::now() - Duration::days(1)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!((entries[0].event_id.as_str(), entries[0].actor.as_str()), ("recent", "ps_y"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
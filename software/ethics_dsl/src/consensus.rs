@@ -0,0 +1,316 @@
+//! Engine Consensus - Independent Agreement on Grave Decisions
+//! "In the mouth of two or three witnesses shall every word be established" - 2 Corinthians 13:1
+//!
+//! A `ConsensusEvaluator` puts one event before several engines - instances
+//! with different rule packs or strictness profiles, or remote engines
+//! reached over the sentinel, each behind `ConsensusVoter` - and decides by
+//! quorum. The default `QuorumPolicy` purges only when every engine votes
+//! to purge and denies when a majority votes to deny or purge; otherwise
+//! the event is allowed. An engine that fails to evaluate abstains, which
+//! counts against every quorum, so a silent engine can block a purge but
+//! never cause one. Each engine's vote is recorded in the trace's
+//! `consensus` list.
+
+use crate::engine::EthicsEngine;
+use crate::multimodal::DecisionTrace;
+use crate::sinks::{decision_severity, DecisionKind};
+use crate::{EthicsConfig, EthicsDecision, EthicsError, EthicsEvent, EthicsResult};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Engine taking part in a consensus evaluation
+pub trait ConsensusVoter: Send + Sync {
+    /// Evaluate `event` and report the decision with its trace
+    fn vote(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)>;
+}
+
+impl ConsensusVoter for EthicsEngine {
+    fn vote(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)> {
+        self.evaluate_traced(event)
+    }
+}
+
+/// Number of votes a decision needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quorum {
+    /// Every engine
+    Unanimous,
+    /// More than half of the engines
+    Majority,
+    /// At least this many engines
+    AtLeast(usize),
+}
+
+impl Quorum {
+    /// Whether `votes` of `engines` reach the quorum
+    pub fn reached(&self, votes: usize, engines: usize) -> bool {
+        match *self {
+            Quorum::Unanimous => engines > 0 && votes == engines,
+            Quorum::Majority => votes * 2 > engines,
+            Quorum::AtLeast(required) => votes >= required.max(1),
+        }
+    }
+}
+
+/// Quorums for purging and denying
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuorumPolicy {
+    /// Purge votes needed to purge
+    pub purge: Quorum,
+    /// Deny or purge votes needed to deny
+    pub deny: Quorum,
+}
+
+impl Default for QuorumPolicy {
+    fn default() -> Self {
+        Self { purge: Quorum::Unanimous, deny: Quorum::Majority }
+    }
+}
+
+/// One engine's vote, as recorded in the trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineVote {
+    /// Engine name
+    pub engine: String,
+    /// Decision voted for, `None` when the engine abstained
+    pub vote: Option<DecisionKind>,
+    /// Severity of the vote on the 1-10 purge scale
+    pub severity: u8,
+    /// Why the engine abstained
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Evaluates events by quorum of several engines
+pub struct ConsensusEvaluator {
+    engines: Vec<(String, Arc<dyn ConsensusVoter>)>,
+    policy: QuorumPolicy,
+}
+
+impl ConsensusEvaluator {
+    /// Evaluator with no engines deciding by `policy`
+    pub fn new(policy: QuorumPolicy) -> Self {
+        Self { engines: Vec::new(), policy }
+    }
+
+    /// Evaluator over one local engine per named configuration
+    pub fn from_profiles(profiles: Vec<(String, EthicsConfig)>, policy: QuorumPolicy) -> EthicsResult<Self> {
+        let mut evaluator = Self::new(policy);
+        for (name, config) in profiles {
+            let engine = EthicsEngine::new(config)?;
+            evaluator = evaluator.with_engine(name, Arc::new(engine));
+        }
+        Ok(evaluator)
+    }
+
+    /// Add an engine voting under `name`
+    pub fn with_engine(mut self, name: impl Into<String>, engine: Arc<dyn ConsensusVoter>) -> Self {
+        self.engines.push((name.into(), engine));
+        self
+    }
+
+    /// Number of engines voting
+    pub fn engine_count(&self) -> usize {
+        self.engines.len()
+    }
+
+    /// Decide `event` by quorum, recording each engine's vote in the trace
+    ///
+    /// The trace's content and enrichment are those of the first engine
+    /// that voted. Fails only when no engine voted.
+    pub fn evaluate_traced(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)> {
+        let mut votes = Vec::with_capacity(self.engines.len());
+        let mut decisions = Vec::with_capacity(self.engines.len());
+        let mut trace = None;
+        for (name, engine) in &self.engines {
+            match engine.vote(event) {
+                Ok((decision, engine_trace)) => {
+                    votes.push(EngineVote {
+                        engine: name.clone(),
+                        vote: Some(DecisionKind::of(&decision)),
+                        severity: decision_severity(&decision),
+                        error: None,
+                    });
+                    trace.get_or_insert(engine_trace);
+                    decisions.push(decision);
+                }
+                Err(e) => {
+                    warn!("Engine {} abstained on {}: {}", name, event.event_id, e);
+                    votes.push(EngineVote { engine: name.clone(), vote: None, severity: 0, error: Some(e.to_string()) });
+                }
+            }
+        }
+        let Some(mut trace) = trace else {
+            return Err(EthicsError::RuntimeError(format!(
+                "No engine voted on {}: {} engines in consensus",
+                event.event_id,
+                self.engines.len()
+            )));
+        };
+
+        trace.consensus = votes;
+        Ok((self.decide(&decisions), trace))
+    }
+
+    /// Decide `event` by quorum
+    pub fn evaluate(&self, event: &EthicsEvent) -> EthicsResult<EthicsDecision> {
+        self.evaluate_traced(event).map(|(decision, _)| decision)
+    }
+
+    fn decide(&self, decisions: &[EthicsDecision]) -> EthicsDecision {
+        let engines = self.engines.len();
+        let purges = decisions.iter().filter(|d| DecisionKind::of(d) == DecisionKind::Purge).count();
+        let refusals = decisions.iter().filter(|d| DecisionKind::of(d) != DecisionKind::Allow).count();
+        let mut principles = Vec::new();
+        let mut scripture = Vec::new();
+        let mut reasons = Vec::new();
+        for decision in decisions {
+            match decision {
+                EthicsDecision::Allow { .. } => {}
+                EthicsDecision::Deny { violation: reason, violated_principles, scripture_refs, .. }
+                | EthicsDecision::Purge { reason, violated_principles, scripture_refs, .. } => {
+                    reasons.push(reason.as_str());
+                    merge(&mut principles, violated_principles);
+                    merge(&mut scripture, scripture_refs);
+                }
+            }
+        }
+
+        if self.policy.purge.reached(purges, engines) {
+            // Purge no harder than every purging engine agrees on
+            let severity = decisions.iter()
+                .filter_map(|d| match d {
+                    EthicsDecision::Purge { severity, .. } => Some(*severity),
+                    _ => None,
+                })
+                .min()
+                .unwrap_or(1);
+            EthicsDecision::Purge {
+                severity,
+                reason: format!("{} of {} engines voted to purge: {}", purges, engines, reasons.join("; ")),
+                violated_principles: principles,
+                scripture_refs: scripture,
+            }
+        } else if self.policy.deny.reached(refusals, engines) {
+            EthicsDecision::Deny {
+                confidence: refusals as f64 / engines as f64,
+                violation: format!("{} of {} engines voted to deny: {}", refusals, engines, reasons.join("; ")),
+                violated_principles: principles,
+                scripture_refs: scripture,
+            }
+        } else {
+            let mut allow_scripture = Vec::new();
+            for decision in decisions {
+                if let EthicsDecision::Allow { scripture_refs, .. } = decision {
+                    merge(&mut allow_scripture, scripture_refs);
+                }
+            }
+            EthicsDecision::Allow {
+                confidence: (decisions.len() - refusals) as f64 / engines as f64,
+                justification: format!("{} of {} engines allowed; no quorum to deny", decisions.len() - refusals, engines),
+                scripture_refs: allow_scripture,
+            }
+        }
+    }
+}
+
+fn merge(into: &mut Vec<String>, items: &[String]) {
+    for item in items {
+        if !into.contains(item) {
+            into.push(item.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multimodal::ContentSource;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+    use chrono::Utc;
+
+    struct Fixed(Option<EthicsDecision>);
+
+    impl ConsensusVoter for Fixed {
+        fn vote(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)> {
+            let decision = self.0.clone().ok_or_else(|| EthicsError::RuntimeError("engine offline".to_string()))?;
+            Ok((decision, DecisionTrace {
+                event_id: event.event_id.clone(),
+                content: ContentSource::None,
+                enrichment: Vec::new(),
+                consensus: Vec::new(),
+            }))
+        }
+    }
+
+    fn allow() -> Fixed {
+        Fixed(Some(EthicsDecision::Allow { confidence: 0.9, justification: "ok".to_string(), scripture_refs: vec![] }))
+    }
+
+    fn deny() -> Fixed {
+        Fixed(Some(EthicsDecision::Deny {
+            confidence: 0.8,
+            violation: "deceit".to_string(),
+            violated_principles: vec!["truth".to_string()],
+            scripture_refs: vec!["Exodus 20:16".to_string()],
+        }))
+    }
+
+    fn purge(severity: u8) -> Fixed {
+        Fixed(Some(EthicsDecision::Purge {
+            severity,
+            reason: "violence".to_string(),
+            violated_principles: vec!["life".to_string()],
+            scripture_refs: vec!["Exodus 20:13".to_string()],
+        }))
+    }
+
+    fn evaluator(voters: Vec<Fixed>) -> ConsensusEvaluator {
+        voters.into_iter().enumerate().fold(ConsensusEvaluator::new(QuorumPolicy::default()), |evaluator, (i, voter)| {
+            evaluator.with_engine(format!("engine-{}", i), Arc::new(voter))
+        })
+    }
+
+    fn event() -> EthicsEvent {
+        EthicsEvent {
+            event_id: "consensus-1".to_string(),
+            actor: Actor { actor_type: ActorType::Person, tags: vec![], trust_level: 0.5, history: None },
+            content: None,
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_purge_needs_unanimity_and_deny_a_majority() {
+        let (decision, trace) = evaluator(vec![purge(9), purge(7), purge(8)]).evaluate_traced(&event()).unwrap();
+        assert!(matches!(decision, EthicsDecision::Purge { severity: 7, .. }), "{:?}", decision);
+        assert_eq!(trace.consensus.len(), 3);
+        assert_eq!(trace.consensus[0].severity, 9);
+
+        // Two purges and an allow are still a majority to deny
+        let decision = evaluator(vec![purge(9), purge(9), allow()]).evaluate(&event()).unwrap();
+        match decision {
+            EthicsDecision::Deny { violated_principles, .. } => assert_eq!(violated_principles, ["life"]),
+            other => panic!("expected deny, got {:?}", other),
+        }
+
+        let decision = evaluator(vec![deny(), allow(), allow()]).evaluate(&event()).unwrap();
+        assert!(matches!(decision, EthicsDecision::Allow { .. }), "{:?}", decision);
+    }
+
+    #[test]
+    fn test_abstentions_count_against_quorum() {
+        let (decision, trace) = evaluator(vec![purge(9), purge(9), Fixed(None)]).evaluate_traced(&event()).unwrap();
+        assert!(matches!(decision, EthicsDecision::Deny { .. }), "{:?}", decision);
+        assert_eq!(trace.consensus[2].vote, None);
+        assert_eq!(trace.consensus[2].error.as_deref(), Some("Runtime error: engine offline"));
+
+        assert!(evaluator(vec![Fixed(None)]).evaluate(&event()).is_err());
+        assert!(Quorum::AtLeast(2).reached(2, 5));
+        assert!(!Quorum::Majority.reached(2, 4));
+    }
+}
@@ -0,0 +1,325 @@
+//! Rule Pack Diff - Decisions a Rule Edit Would Change
+//! "Ponder the path of thy feet, and let all thy ways be established" - Proverbs 4:26
+//!
+//! Before a rule edit is merged, `diff_packs` decides a corpus of events
+//! under the base pack and the candidate pack and reports every event whose
+//! outcome differs, so review rests on the decisions the edit changes
+//! rather than on reading the DSL text. Changes are sorted into categories:
+//! events the candidate newly blocks or newly allows, blocks escalated
+//! from Deny to Purge or relaxed from Purge to Deny, and events decided the
+//! same way by a different rule. Each change carries the rule trace of both
+//! packs, in the form `testing` reports failed cases with.
+//!
+//! `diff_engines` does the same for DSL packs, deciding the corpus with two
+//! engines that hold the base and the candidate pack. The engine reports
+//! no rule trace, so each change carries the justification, violation or
+//! purge reason the engine gave instead.
+
+use crate::embedded::{PackDecision, PackOutcome, RulePack};
+use crate::predicates::PredicateRegistry;
+use crate::testing::{trace, RuleTrace};
+use crate::{EthicsDecision, EthicsEvaluator, EthicsEvent, EthicsResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kind of change between the base and candidate outcome of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeCategory {
+    /// Allowed by the base pack, denied or purged by the candidate
+    NewlyBlocked,
+    /// Denied or purged by the base pack, allowed by the candidate
+    NewlyAllowed,
+    /// Denied by the base pack, purged by the candidate
+    Escalated,
+    /// Purged by the base pack, denied by the candidate
+    Relaxed,
+    /// Same decision, taken by a different rule or the default
+    Reattributed,
+}
+
+impl ChangeCategory {
+    /// Every category, in report order
+    pub const ALL: [ChangeCategory; 5] = [
+        ChangeCategory::NewlyBlocked,
+        ChangeCategory::NewlyAllowed,
+        ChangeCategory::Escalated,
+        ChangeCategory::Relaxed,
+        ChangeCategory::Reattributed,
+    ];
+
+    /// Category of a change from `base` to `candidate`; `None` if unchanged
+    pub fn of(base: &PackOutcome, candidate: &PackOutcome) -> Option<Self> {
+        use PackDecision::*;
+        match (base.decision, candidate.decision) {
+            (Allow, Deny | Purge) => Some(ChangeCategory::NewlyBlocked),
+            (Deny | Purge, Allow) => Some(ChangeCategory::NewlyAllowed),
+            (Deny, Purge) => Some(ChangeCategory::Escalated),
+            (Purge, Deny) => Some(ChangeCategory::Relaxed),
+            _ if base.rule != candidate.rule => Some(ChangeCategory::Reattributed),
+            _ => None,
+        }
+    }
+
+    /// Whether the decision itself changed
+    pub fn is_flip(self) -> bool {
+        self != ChangeCategory::Reattributed
+    }
+
+    /// Label used in reports
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeCategory::NewlyBlocked => "newly blocked",
+            ChangeCategory::NewlyAllowed => "newly allowed",
+            ChangeCategory::Escalated => "escalated",
+            ChangeCategory::Relaxed => "relaxed",
+            ChangeCategory::Reattributed => "reattributed",
+        }
+    }
+}
+
+/// Event decided differently by the two packs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionChange {
+    /// Event id
+    pub event_id: String,
+    /// Kind of change
+    pub category: ChangeCategory,
+    /// Outcome under the base pack
+    pub base: PackOutcome,
+    /// Outcome under the candidate pack
+    pub candidate: PackOutcome,
+    /// Rules the base pack tried, up to the one that matched
+    pub base_trace: Vec<RuleTrace>,
+    /// Rules the candidate pack tried, up to the one that matched
+    pub candidate_trace: Vec<RuleTrace>,
+    /// Reason the base engine gave; only for engine diffs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_reason: Option<String>,
+    /// Reason the candidate engine gave; only for engine diffs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_reason: Option<String>,
+}
+
+impl DecisionChange {
+    /// Base and candidate outcome as a diff, with both rule traces
+    pub fn describe(&self) -> String {
+        let outcome = |outcome: &PackOutcome| match &outcome.rule {
+            Some(rule) => format!("{:?} by rule {}", outcome.decision, rule),
+            None => format!("{:?} by default", outcome.decision),
+        };
+        let mut description = format!("- {}\n+ {}\n", outcome(&self.base), outcome(&self.candidate));
+        for (pack, reason) in [("base", &self.base_reason), ("candidate", &self.candidate_reason)] {
+            if let Some(reason) = reason {
+                description.push_str(&format!("  {}: {}\n", pack, reason));
+            }
+        }
+        for (pack, trace) in [("base", &self.base_trace), ("candidate", &self.candidate_trace)] {
+            for step in trace {
+                match &step.failed_condition {
+                    Some(condition) => description.push_str(&format!(
+                        "  {} rule {}: `{}` did not hold\n", pack, step.rule, condition
+                    )),
+                    None => description.push_str(&format!("  {} rule {}: matched\n", pack, step.rule)),
+                }
+            }
+        }
+        description
+    }
+}
+
+/// Number of changes in one category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryCount {
+    /// Category
+    pub category: ChangeCategory,
+    /// Changes in it
+    pub count: usize,
+}
+
+/// Outcome changes between two packs over a corpus
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackDiff {
+    /// Events decided
+    pub total_events: usize,
+    /// Changed events, in corpus order
+    pub changes: Vec<DecisionChange>,
+}
+
+impl PackDiff {
+    /// Events whose decision changed, not counting reattributions
+    pub fn flips(&self) -> usize {
+        self.changes.iter().filter(|change| change.category.is_flip()).count()
+    }
+
+    /// Changes of every category, including empty ones, in report order
+    pub fn categories(&self) -> Vec<CategoryCount> {
+        ChangeCategory::ALL
+            .iter()
+            .map(|&category| CategoryCount {
+                category,
+                count: self.changes.iter().filter(|change| change.category == category).count(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for PackDiff {
+    /// Summary by category, then every change with its traces
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "decided {} events: {} decisions changed, {} reattributed",
+            self.total_events,
+            self.flips(),
+            self.changes.len() - self.flips()
+        )?;
+        let categories = self.categories();
+        for count in &categories {
+            writeln!(f, "  {}: {}", count.category.label(), count.count)?;
+        }
+        for count in categories.iter().filter(|count| count.count > 0) {
+            writeln!(f, "\n{} ({}):", count.category.label(), count.count)?;
+            for change in self.changes.iter().filter(|change| change.category == count.category) {
+                write!(f, "\n---- {} ----\n{}", change.event_id, change.describe())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decide `events` under `base` and `candidate` and collect the changes
+pub fn diff_packs(
+    base: &RulePack,
+    candidate: &RulePack,
+    registry: &PredicateRegistry,
+    events: &[EthicsEvent],
+) -> EthicsResult<PackDiff> {
+    let mut diff = PackDiff { total_events: events.len(), changes: Vec::new() };
+    for event in events {
+        let (base_outcome, base_trace) = trace(base, registry, event)?;
+        let (candidate_outcome, candidate_trace) = trace(candidate, registry, event)?;
+        if let Some(category) = ChangeCategory::of(&base_outcome, &candidate_outcome) {
+            diff.changes.push(DecisionChange {
+                event_id: event.event_id.clone(),
+                category,
+                base: base_outcome,
+                candidate: candidate_outcome,
+                base_trace,
+                candidate_trace,
+                base_reason: None,
+                candidate_reason: None,
+            });
+        }
+    }
+    Ok(diff)
+}
+
+/// Decide `events` with engines holding the base and candidate DSL packs and collect the changes
+pub fn diff_engines(
+    base: &dyn EthicsEvaluator,
+    candidate: &dyn EthicsEvaluator,
+    events: &[EthicsEvent],
+) -> EthicsResult<PackDiff> {
+    let mut diff = PackDiff { total_events: events.len(), changes: Vec::new() };
+    for event in events {
+        let base_decision = base.evaluate(event)?;
+        let candidate_decision = candidate.evaluate(event)?;
+        let base_outcome = PackOutcome { rule: None, decision: PackDecision::from(&base_decision) };
+        let candidate_outcome = PackOutcome { rule: None, decision: PackDecision::from(&candidate_decision) };
+        if let Some(category) = ChangeCategory::of(&base_outcome, &candidate_outcome) {
+            diff.changes.push(DecisionChange {
+                event_id: event.event_id.clone(),
+                category,
+                base: base_outcome,
+                candidate: candidate_outcome,
+                base_trace: Vec::new(),
+                candidate_trace: Vec::new(),
+                base_reason: Some(reason(&base_decision).to_string()),
+                candidate_reason: Some(reason(&candidate_decision).to_string()),
+            });
+        }
+    }
+    Ok(diff)
+}
+
+/// Justification, violation or purge reason of an engine decision
+fn reason(decision: &EthicsDecision) -> &str {
+    match decision {
+        EthicsDecision::Allow { justification, .. } => justification,
+        EthicsDecision::Deny { violation, .. } => violation,
+        EthicsDecision::Purge { reason, .. } => reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedded::PackRule;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+
+    fn event(event_id: &str, trust_level: f64) -> EthicsEvent {
+        EthicsEvent {
+            event_id: event_id.into(),
+            actor: Actor { actor_type: ActorType::Person, tags: Vec::new(), trust_level, history: None },
+            content: None,
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    fn rule(name: &str, threshold: f64, decision: PackDecision) -> PackRule {
+        PackRule { name: name.into(), when: vec![format!("actor.trust_below({})", threshold)], decision }
+    }
+
+    #[test]
+    fn test_changes_are_categorized_with_traces() {
+        let base = RulePack {
+            rules: vec![rule("distrusted", 0.2, PackDecision::Purge), rule("untrusted", 0.4, PackDecision::Deny)],
+            default: PackDecision::Allow,
+            tests: Vec::new(),
+        };
+        let candidate = RulePack {
+            rules: vec![rule("distrusted", 0.2, PackDecision::Deny), rule("doubtful", 0.6, PackDecision::Deny)],
+            default: PackDecision::Allow,
+            tests: Vec::new(),
+        };
+        let events = [event("low", 0.1), event("middling", 0.3), event("borderline", 0.5), event("trusted", 0.9)];
+
+        let diff = diff_packs(&base, &candidate, &PredicateRegistry::standard(), &events).unwrap();
+        let categories: Vec<_> = diff.changes.iter().map(|c| (c.event_id.as_str(), c.category)).collect();
+        assert_eq!(categories, [
+            ("low", ChangeCategory::Relaxed),
+            ("middling", ChangeCategory::Reattributed),
+            ("borderline", ChangeCategory::NewlyBlocked),
+        ]);
+        assert_eq!(diff.flips(), 2);
+        assert_eq!(diff.changes[2].describe(), "- Allow by default\n+ Deny by rule doubtful\n  base rule distrusted: `actor.trust_below(0.2)` did not hold\n  base rule untrusted: `actor.trust_below(0.4)` did not hold\n  candidate rule distrusted: `actor.trust_below(0.2)` did not hold\n  candidate rule doubtful: matched\n");
+
+        let output = diff.to_string();
+        assert!(output.starts_with("decided 4 events: 2 decisions changed, 1 reattributed\n"));
+        assert!(output.contains("  newly allowed: 0\n"));
+        assert!(output.contains("\nrelaxed (1):\n\n---- low ----\n- Purge by rule distrusted\n+ Deny by rule distrusted\n"));
+    }
+
+    #[test]
+    fn test_engine_changes_carry_reasons() {
+        let change = DecisionChange {
+            event_id: "untrusted".into(),
+            category: ChangeCategory::NewlyBlocked,
+            base: PackOutcome { rule: None, decision: PackDecision::Allow },
+            candidate: PackOutcome { rule: None, decision: PackDecision::Deny },
+            base_trace: Vec::new(),
+            candidate_trace: Vec::new(),
+            base_reason: Some("No violation found".into()),
+            candidate_reason: Some("Trust below threshold".into()),
+        };
+        assert_eq!(change.describe(), "- Allow by default\n+ Deny by default\n  base: No violation found\n  candidate: Trust below threshold\n");
+        assert_eq!(reason(&EthicsDecision::Purge {
+            severity: 9,
+            reason: "Corrupts children".into(),
+            violated_principles: Vec::new(),
+            scripture_refs: Vec::new(),
+        }), "Corrupts children");
+    }
+}
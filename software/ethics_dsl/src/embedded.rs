@@ -0,0 +1,1071 @@
+//! Embedded Rule Packs - Static Decision Tables for the Firmware
+//! "I will put my laws into their mind, and write them in their hearts" - Hebrews 8:10
+//!
+//! The firmware application layer has neither a filesystem nor an allocator
+//! to interpret a rule pack at run time. A `RulePack` is an ordered list of
+//! rules, each a conjunction of predicate calls (see `predicates`) with the
+//! decision taken when all of them hold; the first matching rule decides
+//! and `default` applies when none match. `compile` lowers every call into
+//! a fixed `CompiledCondition` - tags and locations as static strings,
+//! actor types, content types, age groups and weekdays as bit masks - and
+//! rejects packs whose predicates need data the firmware does not have.
+//! `CompiledPack::to_rust` writes the table as a static Rust expression for
+//! the firmware build script, and `CompiledPack::measurement` hashes the
+//! table in the encoding the firmware's `DecisionTable::measurement`
+//! recomputes at boot from the table it actually holds.
+//!
+//! `verify_equivalence` decides every event of a conformance corpus with
+//! both the interpreter and the compiled table. The corpus from
+//! `conformance_corpus` probes each threshold, tag, type and time boundary
+//! the pack mentions, alone and around an event matching each rule, so a
+//! build that would embed a table diverging from the interpreter fails.
+//! That corpus is derived from the compiled table, so a condition the
+//! compiler lowers wrongly is probed where the compiler thinks its
+//! boundary is. `verify_expectations` closes the gap: it decides the
+//! hand-written test cases shipped with the pack, whose expected decisions
+//! come from neither side, and refuses a pack that ships none.
+//!
+//! `RulePack::load_logged` loads a pack only if the transparency log
+//! records exactly the bytes read as the rule pack named by the file stem;
+//! a pack with a missing or failing proof is refused.
+
+use crate::predicates::{self, parse_call, PredicateArg, PredicateRegistry};
+use crate::testing::{PackTest, MAX_PACK_TESTS};
+use crate::{tags, ActorType, AgeGroup, Audience, ContentType, EthicsError, EthicsEvent, EthicsResult};
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::Dilithium3;
+use pq_types::transparency::{ArtifactKind, LogRequirement};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Most rules in a pack
+pub const MAX_PACK_RULES: usize = 1024;
+
+/// Most predicate calls in one rule
+pub const MAX_RULE_CONDITIONS: usize = 32;
+
+/// Actor types by bit position in `ActorIs` masks
+pub const ACTOR_TYPES: [&str; 5] = ["Person", "ArtificialIntelligence", "Content", "Institution", "Elite"];
+
+/// Content types by bit position in `ContentIs` masks
+pub const CONTENT_TYPES: [&str; 8] = ["Text", "Image", "Video", "Audio", "Code", "Educational", "News", "Entertainment"];
+
+/// Age groups by bit position in `AudienceAny` masks
+pub const AGE_GROUPS: [&str; 5] = ["Children", "Teenagers", "YoungAdults", "Adults", "Seniors"];
+
+/// Seconds in a day
+const DAY_SECONDS: u32 = 24 * 3600;
+
+/// Most violations a corpus event records
+const MAX_PROBE_VIOLATIONS: f64 = 1024.0;
+
+/// Key derivation context of rule table measurements
+pub const MEASUREMENT_CONTEXT: &str = "ARK embedded rule table measurement v1";
+
+/// Decision of a rule pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackDecision {
+    /// Allow the content
+    Allow,
+    /// Deny the content
+    Deny,
+    /// Purge the content
+    Purge,
+}
+
+impl From<&crate::EthicsDecision> for PackDecision {
+    fn from(decision: &crate::EthicsDecision) -> Self {
+        match decision {
+            crate::EthicsDecision::Allow { .. } => PackDecision::Allow,
+            crate::EthicsDecision::Deny { .. } => PackDecision::Deny,
+            crate::EthicsDecision::Purge { .. } => PackDecision::Purge,
+        }
+    }
+}
+
+/// One rule of a pack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackRule {
+    /// Rule name, reported with its decisions
+    pub name: String,
+    /// Predicate calls that must all hold; empty always matches
+    #[serde(default)]
+    pub when: Vec<String>,
+    /// Decision when the rule matches
+    pub decision: PackDecision,
+}
+
+/// Ordered rules evaluated first-match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePack {
+    /// Rules in evaluation order
+    pub rules: Vec<PackRule>,
+    /// Decision when no rule matches
+    pub default: PackDecision,
+    /// Test cases shipped with the pack, run by `testing::run_tests`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<PackTest>,
+}
+
+impl Validate for RulePack {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("rules", self.rules.len(), MAX_PACK_RULES)?;
+        for rule in &self.rules {
+            decode::check_identifier("rule name", &rule.name, 128)?;
+            decode::check_count("conditions", rule.when.len(), MAX_RULE_CONDITIONS)?;
+            for call in &rule.when {
+                decode::check_len("condition", call, predicates::MAX_EXPRESSION_LENGTH)?;
+            }
+        }
+        decode::check_count("tests", self.tests.len(), MAX_PACK_TESTS)?;
+        for test in &self.tests {
+            test.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl RulePack {
+    /// Load a pack from a JSON file
+    pub fn load(path: &Path) -> EthicsResult<Self> {
+        let contents = std::fs::read(path)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load a pack from a JSON file proven in the transparency log of `log`
+    pub fn load_logged(path: &Path, log: &LogRequirement) -> EthicsResult<Self> {
+        let load_error = |e: &dyn std::fmt::Display| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e));
+        let contents = std::fs::read(path).map_err(|e| load_error(&e))?;
+        // The bytes read are what gets decoded, so they are what is checked
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        log.verify::<Dilithium3>(ArtifactKind::RulePack, name, &contents).map_err(|e| load_error(&e))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE).map_err(|e| load_error(&e))
+    }
+
+    /// BLAKE3 digest of the pack's JSON, embedded with the compiled table
+    ///
+    /// Test cases are left out, so adding tests does not change the digest.
+    pub fn digest(&self) -> EthicsResult<[u8; 32]> {
+        let rules = RulePack { rules: self.rules.clone(), default: self.default, tests: Vec::new() };
+        let json = serde_json::to_vec(&rules)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize rule pack: {}", e)))?;
+        Ok(*blake3::hash(&json).as_bytes())
+    }
+
+    /// Decide an event with the predicate interpreter
+    pub fn evaluate(&self, registry: &PredicateRegistry, event: &EthicsEvent) -> EthicsResult<PackOutcome> {
+        for rule in &self.rules {
+            let mut holds = true;
+            for call in &rule.when {
+                if !registry.evaluate(call, event)? {
+                    holds = false;
+                    break;
+                }
+            }
+            if holds {
+                return Ok(PackOutcome { rule: Some(rule.name.clone()), decision: rule.decision });
+            }
+        }
+        Ok(PackOutcome { rule: None, decision: self.default })
+    }
+}
+
+/// Decision of a pack and the rule that took it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackOutcome {
+    /// Matching rule; `None` when the default applied
+    pub rule: Option<String>,
+    /// Decision taken
+    pub decision: PackDecision,
+}
+
+/// Predicate call lowered to a condition the firmware evaluates
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompiledCondition {
+    /// `actor.trust_below(x)`
+    TrustBelow(f64),
+    /// `actor.trust_at_least(x)`
+    TrustAtLeast(f64),
+    /// `actor.is(...)`, as a mask over `ACTOR_TYPES`
+    ActorIs(u8),
+    /// `actor.violations_at_least(n)`
+    ViolationsAtLeast(f64),
+    /// `tag.has`, `tag.any` and `tag.any_violation`
+    TagAny(Vec<String>),
+    /// `audience.contains*`, as a mask over `AGE_GROUPS`
+    AudienceAny(u8),
+    /// `audience.size_at_least(n)`
+    AudienceSizeAtLeast(f64),
+    /// `content.present()`
+    ContentPresent,
+    /// `content.is(...)`, as a mask over `CONTENT_TYPES`
+    ContentIs(u8),
+    /// `time.between(start, end)`, as seconds since midnight UTC
+    TimeBetween(u32, u32),
+    /// `time.weekday(...)`, as a mask with Monday in bit 0
+    Weekday(u8),
+    /// `location.in(...)`
+    LocationIn(Vec<String>),
+    /// `location.unknown()`
+    LocationUnknown,
+}
+
+/// Facts of an event the compiled conditions read
+///
+/// The firmware builds the same facts from its own event representation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventFacts {
+    /// Index into `ACTOR_TYPES`
+    pub actor_type: u8,
+    /// Actor trust level
+    pub trust_level: f64,
+    /// Recorded violations of the actor
+    pub violations: u64,
+    /// Actor tags and content metadata tags
+    pub tags: Vec<String>,
+    /// Age groups of the audience as a mask over `AGE_GROUPS`; `None` without audience
+    pub age_groups: Option<u8>,
+    /// Audience size, if known
+    pub audience_size: Option<u64>,
+    /// Index into `CONTENT_TYPES`; `None` without content
+    pub content_type: Option<u8>,
+    /// Event time in seconds since midnight UTC
+    pub seconds_of_day: u32,
+    /// Weekday of the event, Monday = 0
+    pub weekday: u8,
+    /// Location code
+    pub location: Option<String>,
+}
+
+impl EventFacts {
+    /// Extract the facts of an event
+    pub fn from_event(event: &EthicsEvent) -> Self {
+        let time = event.timestamp.time();
+        Self {
+            actor_type: index_of(&ACTOR_TYPES, &event.actor.actor_type),
+            trust_level: event.actor.trust_level,
+            violations: event.actor.history.as_ref().map_or(0, |h| h.violations.len() as u64),
+            tags: predicates::event_tags(event),
+            age_groups: event.context.audience.as_ref().map(|a| {
+                a.age_groups.iter().fold(0, |mask, group| mask | 1 << index_of(&AGE_GROUPS, group))
+            }),
+            audience_size: event.context.audience.as_ref().and_then(|a| a.size),
+            content_type: event.content.as_ref().map(|c| index_of(&CONTENT_TYPES, &c.content_type)),
+            seconds_of_day: time.hour() * 3600 + time.minute() * 60 + time.second(),
+            weekday: event.timestamp.weekday().num_days_from_monday() as u8,
+            location: event.context.location.clone(),
+        }
+    }
+}
+
+fn index_of<T: std::fmt::Debug>(names: &[&str], value: &T) -> u8 {
+    let name = format!("{:?}", value);
+    names.iter().position(|n| *n == name).unwrap_or(0) as u8
+}
+
+impl CompiledCondition {
+    /// Whether the condition holds for `facts`
+    pub fn holds(&self, facts: &EventFacts) -> bool {
+        match self {
+            Self::TrustBelow(x) => facts.trust_level < *x,
+            Self::TrustAtLeast(x) => facts.trust_level >= *x,
+            Self::ActorIs(mask) => mask & (1 << facts.actor_type) != 0,
+            Self::ViolationsAtLeast(n) => facts.violations as f64 >= *n,
+            Self::TagAny(wanted) => wanted.iter().any(|w| facts.tags.iter().any(|t| t.eq_ignore_ascii_case(w))),
+            Self::AudienceAny(mask) => facts.age_groups.is_some_and(|groups| groups & mask != 0),
+            Self::AudienceSizeAtLeast(n) => facts.audience_size.is_some_and(|size| size as f64 >= *n),
+            Self::ContentPresent => facts.content_type.is_some(),
+            Self::ContentIs(mask) => facts.content_type.is_some_and(|t| mask & (1 << t) != 0),
+            Self::TimeBetween(start, end) => {
+                let now = facts.seconds_of_day;
+                if start <= end {
+                    *start <= now && now < *end
+                } else {
+                    now >= *start || now < *end
+                }
+            }
+            Self::Weekday(mask) => mask & (1 << facts.weekday) != 0,
+            Self::LocationIn(codes) => {
+                facts.location.as_ref().is_some_and(|l| codes.iter().any(|c| c.eq_ignore_ascii_case(l)))
+            }
+            Self::LocationUnknown => facts.location.is_none(),
+        }
+    }
+
+    fn to_rust(&self) -> String {
+        let strings = |values: &[String]| values.iter().map(|v| format!("{:?}", v)).collect::<Vec<_>>().join(", ");
+        match self {
+            Self::TrustBelow(x) => format!("Condition::TrustBelow({:?})", x),
+            Self::TrustAtLeast(x) => format!("Condition::TrustAtLeast({:?})", x),
+            Self::ActorIs(mask) => format!("Condition::ActorIs({:#010b})", mask),
+            Self::ViolationsAtLeast(n) => format!("Condition::ViolationsAtLeast({:?})", n),
+            Self::TagAny(values) => format!("Condition::TagAny(&[{}])", strings(values)),
+            Self::AudienceAny(mask) => format!("Condition::AudienceAny({:#010b})", mask),
+            Self::AudienceSizeAtLeast(n) => format!("Condition::AudienceSizeAtLeast({:?})", n),
+            Self::ContentPresent => "Condition::ContentPresent".to_string(),
+            Self::ContentIs(mask) => format!("Condition::ContentIs({:#010b})", mask),
+            Self::TimeBetween(start, end) => format!("Condition::TimeBetween({}, {})", start, end),
+            Self::Weekday(mask) => format!("Condition::Weekday({:#010b})", mask),
+            Self::LocationIn(values) => format!("Condition::LocationIn(&[{}])", strings(values)),
+            Self::LocationUnknown => "Condition::LocationUnknown".to_string(),
+        }
+    }
+}
+
+/// Rule with its compiled conditions
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledRule {
+    /// Rule name
+    pub name: String,
+    /// Conditions that must all hold
+    pub conditions: Vec<CompiledCondition>,
+    /// Decision when the rule matches
+    pub decision: PackDecision,
+}
+
+/// Rule pack compiled to a decision table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledPack {
+    /// Digest of the source pack
+    pub digest: [u8; 32],
+    /// Rules in evaluation order
+    pub rules: Vec<CompiledRule>,
+    /// Decision when no rule matches
+    pub default: PackDecision,
+}
+
+impl CompiledPack {
+    /// Decide an event with the compiled table
+    pub fn evaluate(&self, event: &EthicsEvent) -> PackOutcome {
+        let facts = EventFacts::from_event(event);
+        self.rules
+            .iter()
+            .find(|rule| rule.conditions.iter().all(|condition| condition.holds(&facts)))
+            .map(|rule| PackOutcome { rule: Some(rule.name.clone()), decision: rule.decision })
+            .unwrap_or(PackOutcome { rule: None, decision: self.default })
+    }
+
+    /// The table as a Rust expression of the firmware's `DecisionTable`
+    ///
+    /// The firmware includes the file with `embedded_rule_pack!`, which
+    /// brings `DecisionTable`, `Rule`, `Condition` and `Decision` into scope.
+    pub fn to_rust(&self) -> String {
+        let mut source = String::from("// Generated from a rule pack by ethics_dsl::embedded; do not edit\n");
+        let digest = self.digest.iter().map(|b| format!("{:#04x}", b)).collect::<Vec<_>>().join(", ");
+        let _ = writeln!(source, "DecisionTable {{");
+        let _ = writeln!(source, "    digest: [{}],", digest);
+        let _ = writeln!(source, "    rules: &[");
+        for rule in &self.rules {
+            let conditions = rule.conditions.iter().map(CompiledCondition::to_rust).collect::<Vec<_>>().join(", ");
+            let _ = writeln!(source, "        Rule {{");
+            let _ = writeln!(source, "            name: {:?},", rule.name);
+            let _ = writeln!(source, "            conditions: &[{}],", conditions);
+            let _ = writeln!(source, "            decision: Decision::{:?},", rule.decision);
+            let _ = writeln!(source, "        }},");
+        }
+        let _ = writeln!(source, "    ],");
+        let _ = writeln!(source, "    default: Decision::{:?},", self.default);
+        source.push_str("}\n");
+        source
+    }
+
+    /// BLAKE3 measurement of the table, as the firmware computes it
+    ///
+    /// Keyed with `MEASUREMENT_CONTEXT` over the pack digest, each rule's
+    /// name, conditions and decision, and the default. Counts and lengths are
+    /// little-endian u32, numbers little-endian IEEE bits, conditions a tag
+    /// byte in declaration order followed by their values, and decisions the
+    /// Optic Gate codes (Allow 1, Deny 2, Purge 3).
+    pub fn measurement(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new_derive_key(MEASUREMENT_CONTEXT);
+        let count = |hasher: &mut blake3::Hasher, n: usize| {
+            hasher.update(&(n as u32).to_le_bytes());
+        };
+        let text = |hasher: &mut blake3::Hasher, s: &str| {
+            hasher.update(&(s.len() as u32).to_le_bytes());
+            hasher.update(s.as_bytes());
+        };
+        let list = |hasher: &mut blake3::Hasher, tag: u8, values: &[String]| {
+            hasher.update(&[tag]);
+            count(hasher, values.len());
+            for value in values {
+                text(hasher, value);
+            }
+        };
+        let decision = |decision: PackDecision| match decision {
+            PackDecision::Allow => 1u8,
+            PackDecision::Deny => 2,
+            PackDecision::Purge => 3,
+        };
+        hasher.update(&self.digest);
+        count(&mut hasher, self.rules.len());
+        for rule in &self.rules {
+            text(&mut hasher, &rule.name);
+            count(&mut hasher, rule.conditions.len());
+            for condition in &rule.conditions {
+                match condition {
+                    CompiledCondition::TrustBelow(x) => hasher.update(&[0]).update(&x.to_le_bytes()),
+                    CompiledCondition::TrustAtLeast(x) => hasher.update(&[1]).update(&x.to_le_bytes()),
+                    CompiledCondition::ActorIs(mask) => hasher.update(&[2, *mask]),
+                    CompiledCondition::ViolationsAtLeast(n) => hasher.update(&[3]).update(&n.to_le_bytes()),
+                    CompiledCondition::TagAny(values) => {
+                        list(&mut hasher, 4, values);
+                        &mut hasher
+                    }
+                    CompiledCondition::AudienceAny(mask) => hasher.update(&[5, *mask]),
+                    CompiledCondition::AudienceSizeAtLeast(n) => hasher.update(&[6]).update(&n.to_le_bytes()),
+                    CompiledCondition::ContentPresent => hasher.update(&[7]),
+                    CompiledCondition::ContentIs(mask) => hasher.update(&[8, *mask]),
+                    CompiledCondition::TimeBetween(start, end) => {
+                        hasher.update(&[9]).update(&start.to_le_bytes()).update(&end.to_le_bytes())
+                    }
+                    CompiledCondition::Weekday(mask) => hasher.update(&[10, *mask]),
+                    CompiledCondition::LocationIn(values) => {
+                        list(&mut hasher, 11, values);
+                        &mut hasher
+                    }
+                    CompiledCondition::LocationUnknown => hasher.update(&[12]),
+                };
+            }
+            hasher.update(&[decision(rule.decision)]);
+        }
+        hasher.update(&[decision(self.default)]);
+        *hasher.finalize().as_bytes()
+    }
+}
+
+/// Compile a pack to a decision table
+///
+/// Fails on calls the interpreter would reject and on predicates the
+/// firmware cannot evaluate (`audience.has_vulnerable`,
+/// `content.metadata_equals` and host-registered predicates).
+pub fn compile(pack: &RulePack) -> EthicsResult<CompiledPack> {
+    let rules = pack
+        .rules
+        .iter()
+        .map(|rule| {
+            let conditions = rule
+                .when
+                .iter()
+                .map(|call| {
+                    compile_call(call).map_err(|e| EthicsError::ParseError(format!("Rule {}: {}", rule.name, e)))
+                })
+                .collect::<EthicsResult<_>>()?;
+            Ok(CompiledRule { name: rule.name.clone(), conditions, decision: rule.decision })
+        })
+        .collect::<EthicsResult<_>>()?;
+
+    Ok(CompiledPack { digest: pack.digest()?, rules, default: pack.default })
+}
+
+fn compile_call(call: &str) -> EthicsResult<CompiledCondition> {
+    let (name, args) = parse_call(call)?;
+    let arity = |min: usize, max: Option<usize>| {
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            Err(EthicsError::ParseError(format!("{} takes {}..{:?} arguments, got {}", name, min, max, args.len())))
+        } else {
+            Ok(())
+        }
+    };
+
+    let condition = match name.as_str() {
+        "actor.trust_below" => {
+            arity(1, Some(1))?;
+            CompiledCondition::TrustBelow(number(&args[0])?)
+        }
+        "actor.trust_at_least" => {
+            arity(1, Some(1))?;
+            CompiledCondition::TrustAtLeast(number(&args[0])?)
+        }
+        "actor.is" => {
+            arity(1, None)?;
+            CompiledCondition::ActorIs(mask(&ACTOR_TYPES, &args, "actor type")?)
+        }
+        "actor.violations_at_least" => {
+            arity(1, Some(1))?;
+            CompiledCondition::ViolationsAtLeast(number(&args[0])?)
+        }
+        "tag.has" => {
+            arity(1, Some(1))?;
+            CompiledCondition::TagAny(vec![text(&args[0])])
+        }
+        "tag.any" => {
+            arity(1, None)?;
+            CompiledCondition::TagAny(args.iter().map(text).collect())
+        }
+        "tag.any_violation" => {
+            arity(0, Some(0))?;
+            CompiledCondition::TagAny(tags::ALL_VIOLATION_TAGS.iter().map(|t| t.to_string()).collect())
+        }
+        "audience.contains" => {
+            arity(1, Some(1))?;
+            CompiledCondition::AudienceAny(mask(&AGE_GROUPS, &args, "age group")?)
+        }
+        "audience.contains_children" => {
+            arity(0, Some(0))?;
+            CompiledCondition::AudienceAny(0b1)
+        }
+        "audience.contains_minors" => {
+            arity(0, Some(0))?;
+            CompiledCondition::AudienceAny(0b11)
+        }
+        "audience.size_at_least" => {
+            arity(1, Some(1))?;
+            CompiledCondition::AudienceSizeAtLeast(number(&args[0])?)
+        }
+        "content.present" => {
+            arity(0, Some(0))?;
+            CompiledCondition::ContentPresent
+        }
+        "content.is" => {
+            arity(1, None)?;
+            CompiledCondition::ContentIs(mask(&CONTENT_TYPES, &args, "content type")?)
+        }
+        "time.between" => {
+            arity(2, Some(2))?;
+            CompiledCondition::TimeBetween(seconds(&text(&args[0]))?, seconds(&text(&args[1]))?)
+        }
+        "time.weekday" => {
+            arity(1, None)?;
+            let mut days = 0u8;
+            for day in args.iter().map(text) {
+                let day: chrono::Weekday = day
+                    .parse()
+                    .map_err(|_| EthicsError::ParseError(format!("Invalid weekday {:?}", day)))?;
+                days |= 1 << day.num_days_from_monday();
+            }
+            CompiledCondition::Weekday(days)
+        }
+        "location.in" => {
+            arity(1, None)?;
+            CompiledCondition::LocationIn(args.iter().map(text).collect())
+        }
+        "location.unknown" => {
+            arity(0, Some(0))?;
+            CompiledCondition::LocationUnknown
+        }
+        other => return Err(EthicsError::ParseError(format!("Predicate {} cannot be embedded", other))),
+    };
+    Ok(condition)
+}
+
+fn number(arg: &PredicateArg) -> EthicsResult<f64> {
+    match arg {
+        PredicateArg::Number(n) if n.is_finite() => Ok(*n),
+        other => Err(EthicsError::ParseError(format!("Expected a finite number, got {}", other))),
+    }
+}
+
+/// Argument as the interpreter reads text: numbers in their display form
+fn text(arg: &PredicateArg) -> String {
+    match arg {
+        PredicateArg::Text(s) => s.clone(),
+        PredicateArg::Number(n) => n.to_string(),
+    }
+}
+
+fn mask(names: &[&str], args: &[PredicateArg], what: &str) -> EthicsResult<u8> {
+    let mut mask = 0u8;
+    for value in args.iter().map(text) {
+        let index = names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(&value))
+            .ok_or_else(|| EthicsError::ParseError(format!("Unknown {} {:?}", what, value)))?;
+        mask |= 1 << index;
+    }
+    Ok(mask)
+}
+
+fn seconds(value: &str) -> EthicsResult<u32> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map(|time| time.num_seconds_from_midnight())
+        .map_err(|e| EthicsError::ParseError(format!("Invalid time {:?} (expected HH:MM): {}", value, e)))
+}
+
+/// Event on which the interpreter and the compiled table disagree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// Event id in the corpus
+    pub event_id: String,
+    /// Interpreter outcome
+    pub interpreted: PackOutcome,
+    /// Compiled table outcome
+    pub compiled: PackOutcome,
+    /// Decision the pack's test case expects; `None` for generated events
+    #[serde(default)]
+    pub expected: Option<PackDecision>,
+}
+
+/// Result of comparing a compiled table with the interpreter
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EquivalenceReport {
+    /// Events decided
+    pub cases: usize,
+    /// Events decided differently
+    pub divergences: Vec<Divergence>,
+}
+
+impl EquivalenceReport {
+    /// Whether every event was decided identically, by the same rule
+    pub fn is_equivalent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Decide every corpus event with the interpreter and the compiled table
+pub fn verify_equivalence(
+    pack: &RulePack,
+    compiled: &CompiledPack,
+    registry: &PredicateRegistry,
+    corpus: &[EthicsEvent],
+) -> EthicsResult<EquivalenceReport> {
+    let mut report = EquivalenceReport::default();
+    for event in corpus {
+        let interpreted = pack.evaluate(registry, event)?;
+        let embedded = compiled.evaluate(event);
+        report.cases += 1;
+        if interpreted != embedded {
+            report.divergences.push(Divergence {
+                event_id: event.event_id.clone(),
+                interpreted,
+                compiled: embedded,
+                expected: None,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Decide every test case of `pack` with the interpreter and the compiled table
+///
+/// A case diverges when the two disagree or when either misses the
+/// expected decision or rule. A pack without test cases is rejected: the
+/// generated corpus alone cannot catch a lowering both sides agree on.
+pub fn verify_expectations(
+    pack: &RulePack,
+    compiled: &CompiledPack,
+    registry: &PredicateRegistry,
+) -> EthicsResult<EquivalenceReport> {
+    if pack.tests.is_empty() {
+        return Err(EthicsError::ConfigurationError(
+            "Rule pack ships no test cases to check the compiled table against".to_string(),
+        ));
+    }
+    let mut report = EquivalenceReport::default();
+    for test in &pack.tests {
+        let interpreted = pack.evaluate(registry, &test.event)?;
+        let embedded = compiled.evaluate(&test.event);
+        let meets = |outcome: &PackOutcome| {
+            outcome.decision == test.expect
+                && test.expect_rule.as_ref().is_none_or(|rule| outcome.rule.as_ref() == Some(rule))
+        };
+        report.cases += 1;
+        if interpreted != embedded || !meets(&interpreted) || !meets(&embedded) {
+            report.divergences.push(Divergence {
+                event_id: test.name.clone(),
+                interpreted,
+                compiled: embedded,
+                expected: Some(test.expect),
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Change applied to an event when building the conformance corpus
+#[derive(Debug, Clone)]
+enum Probe {
+    Trust(f64),
+    Violations(u64),
+    Actor(usize),
+    Tag(String),
+    ContentTag(String),
+    AgeGroup(usize),
+    AudienceSize(Option<u64>),
+    NoAudience,
+    Content(Option<usize>),
+    Time(u32),
+    Weekday(u32),
+    Location(Option<String>),
+}
+
+impl Probe {
+    fn apply(&self, event: &mut EthicsEvent) {
+        let audience = |event: &mut EthicsEvent| -> &mut Audience {
+            event.context.audience.get_or_insert_with(|| Audience {
+                age_groups: Vec::new(),
+                vulnerable_groups: Vec::new(),
+                size: None,
+            })
+        };
+        match self {
+            Probe::Trust(x) => event.actor.trust_level = *x,
+            Probe::Violations(n) => {
+                let history = event.actor.history.get_or_insert_with(|| crate::ActorHistory {
+                    violations: Vec::new(),
+                    trust_history: Vec::new(),
+                    total_evaluations: 0,
+                });
+                history.violations = (0..*n)
+                    .map(|i| crate::Violation {
+                        timestamp: event.timestamp,
+                        principle: "CONFORMANCE".to_string(),
+                        severity: 1,
+                        description: format!("probe violation {}", i),
+                    })
+                    .collect();
+            }
+            Probe::Actor(index) => event.actor.actor_type = actor_type(*index),
+            Probe::Tag(tag) => event.actor.tags.push(tag.clone()),
+            Probe::ContentTag(tag) => {
+                let content = event.content.get_or_insert_with(|| content(0));
+                let tags = content.metadata.entry("tags".to_string()).or_insert_with(|| serde_json::json!([]));
+                if let Some(list) = tags.as_array_mut() {
+                    list.push(serde_json::Value::String(tag.clone()));
+                }
+            }
+            Probe::AgeGroup(index) => audience(event).age_groups.push(age_group(*index)),
+            Probe::AudienceSize(size) => audience(event).size = *size,
+            Probe::NoAudience => event.context.audience = None,
+            Probe::Content(None) => event.content = None,
+            Probe::Content(Some(index)) => {
+                let existing = event.content.take().map(|c| c.metadata).unwrap_or_default();
+                let mut replacement = content(*index);
+                replacement.metadata = existing;
+                event.content = Some(replacement);
+            }
+            Probe::Time(seconds) => {
+                let date = event.timestamp.date_naive();
+                let time = NaiveTime::from_num_seconds_from_midnight_opt(*seconds % DAY_SECONDS, 0).unwrap_or_default();
+                event.timestamp = Utc.from_utc_datetime(&date.and_time(time));
+            }
+            Probe::Weekday(day) => {
+                let monday = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap_or_default();
+                let date = monday + chrono::Duration::days(*day as i64);
+                event.timestamp = Utc.from_utc_datetime(&date.and_time(event.timestamp.time()));
+            }
+            Probe::Location(location) => event.context.location = location.clone(),
+        }
+    }
+}
+
+fn actor_type(index: usize) -> ActorType {
+    [ActorType::Person, ActorType::ArtificialIntelligence, ActorType::Content, ActorType::Institution, ActorType::Elite]
+        [index % ACTOR_TYPES.len()]
+    .clone()
+}
+
+fn age_group(index: usize) -> AgeGroup {
+    [AgeGroup::Children, AgeGroup::Teenagers, AgeGroup::YoungAdults, AgeGroup::Adults, AgeGroup::Seniors]
+        [index % AGE_GROUPS.len()]
+    .clone()
+}
+
+fn content(index: usize) -> crate::Content {
+    let content_type = [
+        ContentType::Text,
+        ContentType::Image,
+        ContentType::Video,
+        ContentType::Audio,
+        ContentType::Code,
+        ContentType::Educational,
+        ContentType::News,
+        ContentType::Entertainment,
+    ][index % CONTENT_TYPES.len()]
+    .clone();
+    crate::Content {
+        content_type,
+        data: String::new(),
+        metadata: HashMap::new(),
+        content_hash: String::new(),
+    }
+}
+
+/// Probes around the boundaries of one condition
+fn boundary_probes(condition: &CompiledCondition) -> Vec<Probe> {
+    match condition {
+        CompiledCondition::TrustBelow(x) | CompiledCondition::TrustAtLeast(x) => {
+            vec![Probe::Trust(*x), Probe::Trust(x - 1e-9), Probe::Trust(0.0), Probe::Trust(1.0)]
+        }
+        CompiledCondition::ActorIs(_) => (0..ACTOR_TYPES.len()).map(Probe::Actor).collect(),
+        CompiledCondition::ViolationsAtLeast(n) => {
+            let at = n.clamp(0.0, MAX_PROBE_VIOLATIONS).ceil() as u64;
+            vec![Probe::Violations(at), Probe::Violations(at.saturating_sub(1)), Probe::Violations(0)]
+        }
+        CompiledCondition::TagAny(values) => values
+            .iter()
+            .flat_map(|t| [Probe::Tag(t.clone()), Probe::Tag(t.to_lowercase()), Probe::ContentTag(t.clone())])
+            .chain([Probe::Tag("UNRELATED".to_string())])
+            .collect(),
+        CompiledCondition::AudienceAny(_) => {
+            (0..AGE_GROUPS.len()).map(Probe::AgeGroup).chain([Probe::NoAudience]).collect()
+        }
+        CompiledCondition::AudienceSizeAtLeast(n) => {
+            let at = n.max(0.0).ceil() as u64;
+            vec![Probe::AudienceSize(Some(at)), Probe::AudienceSize(Some(at.saturating_sub(1))), Probe::AudienceSize(None)]
+        }
+        CompiledCondition::ContentPresent | CompiledCondition::ContentIs(_) => {
+            (0..CONTENT_TYPES.len()).map(|i| Probe::Content(Some(i))).chain([Probe::Content(None)]).collect()
+        }
+        CompiledCondition::TimeBetween(start, end) => [*start, *end]
+            .iter()
+            .flat_map(|s| [Probe::Time(*s), Probe::Time((s + DAY_SECONDS - 1) % DAY_SECONDS)])
+            .collect(),
+        CompiledCondition::Weekday(_) => (0..7).map(Probe::Weekday).collect(),
+        CompiledCondition::LocationIn(codes) => codes
+            .iter()
+            .map(|c| Probe::Location(Some(c.to_lowercase())))
+            .chain([Probe::Location(None), Probe::Location(Some("ZZ".to_string()))])
+            .collect(),
+        CompiledCondition::LocationUnknown => vec![Probe::Location(None), Probe::Location(Some("ZZ".to_string()))],
+    }
+}
+
+/// Probes making one condition hold
+fn witness_probes(condition: &CompiledCondition) -> Vec<Probe> {
+    let first_bit = |mask: u8| mask.trailing_zeros() as usize;
+    match condition {
+        CompiledCondition::TrustBelow(x) => vec![Probe::Trust(x - 0.01)],
+        CompiledCondition::TrustAtLeast(x) => vec![Probe::Trust(*x)],
+        CompiledCondition::ActorIs(mask) => vec![Probe::Actor(first_bit(*mask))],
+        CompiledCondition::ViolationsAtLeast(n) => vec![Probe::Violations(n.clamp(0.0, MAX_PROBE_VIOLATIONS).ceil() as u64)],
+        CompiledCondition::TagAny(values) => values.first().map(|t| Probe::Tag(t.clone())).into_iter().collect(),
+        CompiledCondition::AudienceAny(mask) => vec![Probe::AgeGroup(first_bit(*mask))],
+        CompiledCondition::AudienceSizeAtLeast(n) => vec![Probe::AudienceSize(Some(n.max(0.0).ceil() as u64))],
+        CompiledCondition::ContentPresent => vec![Probe::Content(Some(0))],
+        CompiledCondition::ContentIs(mask) => vec![Probe::Content(Some(first_bit(*mask)))],
+        CompiledCondition::TimeBetween(start, _) => vec![Probe::Time(*start)],
+        CompiledCondition::Weekday(mask) => vec![Probe::Weekday(mask.trailing_zeros())],
+        CompiledCondition::LocationIn(codes) => codes.first().map(|c| Probe::Location(Some(c.clone()))).into_iter().collect(),
+        CompiledCondition::LocationUnknown => vec![Probe::Location(None)],
+    }
+}
+
+/// Event every corpus case starts from: a Person of middling trust, Monday noon UTC
+fn base_event() -> EthicsEvent {
+    EthicsEvent {
+        event_id: String::new(),
+        actor: crate::Actor {
+            actor_type: ActorType::Person,
+            tags: Vec::new(),
+            trust_level: 0.5,
+            history: None,
+        },
+        content: None,
+        context: crate::Context {
+            location: None,
+            culture: None,
+            platform: None,
+            audience: None,
+            urgency: crate::UrgencyLevel::Normal,
+        },
+        timestamp: Utc.with_ymd_and_hms(2025, 3, 3, 12, 0, 0).single().unwrap_or_else(Utc::now),
+    }
+}
+
+/// Events probing every boundary of a compiled pack
+///
+/// Each boundary probe is applied to a neutral event and to an event
+/// matching each rule, so both the conditions and the rule order are
+/// exercised.
+pub fn conformance_corpus(compiled: &CompiledPack) -> Vec<EthicsEvent> {
+    let probes: Vec<Probe> = compiled
+        .rules
+        .iter()
+        .flat_map(|rule| rule.conditions.iter().flat_map(boundary_probes))
+        .collect();
+
+    let mut starts = vec![base_event()];
+    for rule in &compiled.rules {
+        let mut witness = base_event();
+        for probe in rule.conditions.iter().flat_map(witness_probes) {
+            probe.apply(&mut witness);
+        }
+        starts.push(witness);
+    }
+
+    let mut corpus = Vec::new();
+    for start in &starts {
+        corpus.push(start.clone());
+        for probe in &probes {
+            let mut event = start.clone();
+            probe.apply(&mut event);
+            corpus.push(event);
+        }
+    }
+    for (index, event) in corpus.iter_mut().enumerate() {
+        event.event_id = format!("conformance-{}", index);
+    }
+    corpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack() -> RulePack {
+        let rule = |name: &str, when: &[&str], decision| PackRule {
+            name: name.to_string(),
+            when: when.iter().map(|c| c.to_string()).collect(),
+            decision,
+        };
+        RulePack {
+            rules: vec![
+                rule("corrupting_children", &["audience.contains_minors()", "tag.any(CHILD_CORRUPTION, SEXUAL_IMMORALITY)"], PackDecision::Purge),
+                rule("untrusted_violator", &["actor.trust_below(0.3)", "tag.any_violation()"], PackDecision::Deny),
+                rule("night_broadcast", &["time.between(\"22:00\", \"06:00\")", "content.is(Video, Audio)", "audience.size_at_least(1000)"], PackDecision::Deny),
+                rule("weekend_unknown_origin", &["time.weekday(Sat, Sun)", "location.unknown()", "actor.is(ArtificialIntelligence)"], PackDecision::Deny),
+                rule("repeat_offender", &["actor.violations_at_least(3)", "location.in(US, gb)"], PackDecision::Deny),
+            ],
+            default: PackDecision::Allow,
+            tests: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compiled_table_matches_interpreter_on_conformance_corpus() {
+        let pack = pack();
+        let compiled = compile(&pack).unwrap();
+        let corpus = conformance_corpus(&compiled);
+        let report = verify_equivalence(&pack, &compiled, &PredicateRegistry::standard(), &corpus).unwrap();
+
+        assert!(report.is_equivalent(), "{:#?}", report.divergences);
+        assert_eq!(report.cases, corpus.len());
+        // Every rule decides at least one corpus event
+        for rule in &pack.rules {
+            assert!(corpus.iter().any(|event| compiled.evaluate(event).rule.as_ref() == Some(&rule.name)), "{}", rule.name);
+        }
+
+        let source = compiled.to_rust();
+        assert!(source.contains("Condition::AudienceAny(0b00000011)"));
+        assert!(source.contains("Condition::TimeBetween(79200, 21600)"));
+        assert!(source.contains("default: Decision::Allow"));
+    }
+
+    /// Hand-written event: trust, actor tags, audience ages, content type and UTC hour:minute
+    fn case(name: &str, trust: f64, tags: &[&str], ages: Vec<AgeGroup>, content_type: Option<ContentType>, (hour, minute): (u32, u32)) -> EthicsEvent {
+        EthicsEvent {
+            event_id: name.to_string(),
+            actor: crate::Actor {
+                actor_type: ActorType::Person,
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                trust_level: trust,
+                history: None,
+            },
+            content: content_type.map(|content_type| crate::Content {
+                content_type,
+                data: String::new(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: crate::Context {
+                location: Some("FR".to_string()),
+                culture: None,
+                platform: None,
+                audience: Some(Audience { age_groups: ages, vulnerable_groups: Vec::new(), size: Some(5000) }),
+                urgency: crate::UrgencyLevel::Normal,
+            },
+            // A Wednesday
+            timestamp: Utc.with_ymd_and_hms(2025, 3, 5, hour, minute, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_compiled_table_meets_hand_written_expectations() {
+        let mut pack = pack();
+        assert!(verify_expectations(&pack, &compile(&pack).unwrap(), &PredicateRegistry::standard()).is_err());
+
+        let test = |event: EthicsEvent, expect, rule: Option<&str>| PackTest {
+            name: event.event_id.clone(),
+            event,
+            expect,
+            expect_rule: rule.map(str::to_string),
+        };
+        let minors = || vec![AgeGroup::Teenagers];
+        let adults = || vec![AgeGroup::Adults];
+        pack.tests = vec![
+            test(case("minors_corrupted", 0.9, &["CHILD_CORRUPTION"], minors(), None, (12, 0)), PackDecision::Purge, Some("corrupting_children")),
+            test(case("adults_corrupted", 0.9, &["CHILD_CORRUPTION"], adults(), None, (12, 0)), PackDecision::Allow, None),
+            test(case("untrusted_deceiver", 0.29, &["DECEPTION"], adults(), None, (12, 0)), PackDecision::Deny, Some("untrusted_violator")),
+            test(case("trust_at_threshold", 0.3, &["DECEPTION"], adults(), None, (12, 0)), PackDecision::Allow, None),
+            test(case("video_at_night", 0.9, &[], adults(), Some(ContentType::Video), (22, 0)), PackDecision::Deny, Some("night_broadcast")),
+            test(case("audio_before_dawn", 0.9, &[], adults(), Some(ContentType::Audio), (5, 59)), PackDecision::Deny, Some("night_broadcast")),
+            test(case("video_at_dawn", 0.9, &[], adults(), Some(ContentType::Video), (6, 0)), PackDecision::Allow, None),
+            test(case("text_at_night", 0.9, &[], adults(), Some(ContentType::Text), (23, 0)), PackDecision::Allow, None),
+        ];
+        let compiled = compile(&pack).unwrap();
+        let report = verify_expectations(&pack, &compiled, &PredicateRegistry::standard()).unwrap();
+        assert!(report.is_equivalent(), "{:#?}", report.divergences);
+        assert_eq!(report.cases, pack.tests.len());
+
+        // A table lowered wrongly fails although it still matches the generated corpus it defines
+        let mut skewed = compiled.clone();
+        skewed.rules[1].conditions[0] = CompiledCondition::TrustBelow(0.31);
+        let report = verify_expectations(&pack, &skewed, &PredicateRegistry::standard()).unwrap();
+        assert_eq!(report.divergences.len(), 1);
+        assert_eq!(report.divergences[0].event_id, "trust_at_threshold");
+        assert_eq!(report.divergences[0].expected, Some(PackDecision::Allow));
+    }
+
+    #[test]
+    fn test_measurement_covers_every_rule_edit() {
+        let pack = pack();
+        let measurement = compile(&pack).unwrap().measurement();
+        assert_eq!(compile(&pack).unwrap().measurement(), measurement);
+
+        let mut compiled = compile(&pack).unwrap();
+        compiled.rules[1].conditions[0] = CompiledCondition::TrustBelow(0.31);
+        assert_ne!(compiled.measurement(), measurement);
+
+        let mut compiled = compile(&pack).unwrap();
+        compiled.rules.swap(1, 2);
+        assert_ne!(compiled.measurement(), measurement);
+
+        let mut compiled = compile(&pack).unwrap();
+        compiled.default = PackDecision::Deny;
+        assert_ne!(compiled.measurement(), measurement);
+    }
+
+    #[test]
+    fn test_unembeddable_predicates_rejected() {
+        let mut pack = pack();
+        for call in ["audience.has_vulnerable()", "content.metadata_equals(rating, 12)", "actor.is(Robot)", "actor.trust_below(inf)", "time.between(late, \"06:00\")"] {
+            pack.rules[0].when = vec![call.to_string()];
+            assert!(compile(&pack).is_err(), "{}", call);
+        }
+    }
+
+    #[test]
+    fn test_packs_load_only_with_a_log_proof() {
+        use pq_types::scheme::SignatureScheme;
+        use pq_types::transparency::{ArtifactProof, LogEntry, LogHash, MerkleTree, SignedTreeHead, TreeHead};
+
+        let dir = std::env::temp_dir().join(format!("ark-logged-pack-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("core.json");
+        std::fs::write(&path, serde_json::to_vec(&pack()).unwrap()).unwrap();
+        let (public, secret) = Dilithium3::keypair().unwrap();
+        let log = LogRequirement { log_id: "ark-log".into(), log_public_key: hex::encode(public.as_ref()), proof: dir.join("core.proof.json") };
+        assert!(matches!(RulePack::load_logged(&path, &log), Err(EthicsError::ConfigurationError(_))));
+
+        let entry = LogEntry { kind: ArtifactKind::RulePack, name: "core".into(), version: "7".into(), digest: LogHash::digest(&std::fs::read(&path).unwrap()) };
+        let mut tree = MerkleTree::new();
+        tree.push(&entry);
+        let head = TreeHead { log_id: "ark-log".into(), size: 1, root: tree.root_at(1).unwrap(), timestamp: 1_700_000_000 };
+        let proof = ArtifactProof { entry, inclusion: tree.inclusion_proof(0, 1).unwrap(), tree_head: SignedTreeHead::sign::<Dilithium3>(head, &secret).unwrap() };
+        std::fs::write(&log.proof, serde_json::to_vec(&proof).unwrap()).unwrap();
+        assert_eq!(RulePack::load_logged(&path, &log).unwrap().rules.len(), pack().rules.len());
+
+        // A pack edited after it was logged is refused
+        let mut edited = pack();
+        edited.default = PackDecision::Deny;
+        std::fs::write(&path, serde_json::to_vec(&edited).unwrap()).unwrap();
+        assert!(RulePack::load_logged(&path, &log).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -3,11 +3,28 @@
 
 use crate::{
     biblical::BiblicalFoundation,
+    budget::{LatencyBudget, PipelineStage},
+    cohort::{self, ActorLedger, CohortAnalysis, CohortIndex, CohortMembership},
+    envelope::{self, Authentication, IdentityRegistry, IncomingEvent, SignatureFailure},
     EthicsConfig, EthicsDecision, EthicsError, EthicsEvent, EthicsEvaluator, EthicsResult,
-    tags, CORE_PRINCIPLES,
+    enrichment::{Enricher, EnrichmentProvider, MissingEnrichmentPolicy},
+    ingest::{ContentIngestor, Ingested, IngestedDecision},
+    journal::DecisionJournal,
+    locale::{LocalizedJustification, Localizer},
+    memo::{self, ContentMemo, MemoLookup, MemoStats},
+    multimodal::{self, ContentSource, DecisionTrace, MultimodalAnalyzer},
+    predicates::{PredicateArg, PredicateRegistry},
+    pseudonym::Pseudonymizer,
+    signals::{AgiDetectionConfig, HarmSignalSource, HarmSignals},
+    sinks::{DecisionSink, RetryPolicy, SinkDispatcher, SinkFilter, SinkMetrics},
+    stats::{EngineStats, EvaluationStats, StatsExportHandle, StatsExporter},
+    tags,
+    validity::{IssuedDecision, Revocation, RevocationList, RevocationSource, RevocationTarget},
+    CORE_PRINCIPLES,
 };
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 use chrono::{DateTime, Utc};
 use blake3::Hasher;
 use log::{debug, error, info, warn};
@@ -21,12 +38,38 @@ pub struct EthicsEngine {
     config: EthicsConfig,
     /// Rule cache for performance
     rule_cache: Arc<RwLock<HashMap<String, CachedEvaluation>>>,
+    /// Analyses of identical content reused across actors
+    memo: Arc<RwLock<ContentMemo<MemoEntry>>>,
     /// Scripture database
     scripture_db: ScriptureDatabase,
     /// Evaluation statistics
     stats: Arc<RwLock<EvaluationStats>>,
     /// Advanced AGI Attack Detection System
     agi_detector: AGIAttackDetector,
+    /// Predicates callable from rules
+    predicates: PredicateRegistry,
+    /// Producer identities for signed events
+    identities: Option<IdentityRegistry>,
+    /// Decision notification sinks; started with the first sink
+    sinks: Option<SinkDispatcher>,
+    /// Journal of evaluated events for rule pack replay
+    journal: Option<DecisionJournal>,
+    /// Model scoring image, video and audio content
+    multimodal: Option<Arc<dyn MultimodalAnalyzer>>,
+    /// Harm model consulted by the AGI attack detector
+    harm_signals: Option<Arc<dyn HarmSignalSource>>,
+    /// Providers filling sparse context before evaluation
+    enricher: Enricher,
+    /// Revoked decisions, actors and content
+    revocations: RevocationList,
+    /// Keyed pseudonyms replacing actor identifiers in decisions and logs
+    pseudonyms: Option<Arc<Pseudonymizer>>,
+    /// Catalogs and translations for operator-facing justifications
+    localizer: Localizer,
+    /// Ledger of the actors of evaluated events, for cohort analysis
+    actor_ledger: Option<ActorLedger>,
+    /// Cohort memberships of the last installed analysis
+    cohorts: RwLock<CohortIndex>,
 }
 
 /// Cached evaluation result
@@ -42,6 +85,15 @@ struct CachedEvaluation {
     ttl: std::time::Duration,
 }
 
+/// Actor-independent part of an evaluation, kept in the content memo
+#[derive(Debug, Clone)]
+struct MemoEntry {
+    content: ContentAnalysis,
+    context: ContextAnalysis,
+    /// Decision for an actor of any standing, when the content settles it
+    settled: Option<EthicsDecision>,
+}
+
 /// Scripture database for quick lookups
 #[derive(Debug, Clone)]
 struct ScriptureDatabase {
@@ -68,23 +120,6 @@ struct ScriptureVerse {
     language: String,
 }
 
-/// Engine evaluation statistics
-#[derive(Debug, Default)]
-struct EvaluationStats {
-    /// Total evaluations performed
-    total_evaluations: u64,
-    /// Decisions by type
-    allow_count: u64,
-    deny_count: u64,
-    purge_count: u64,
-    /// Average evaluation time (microseconds)
-    avg_evaluation_time_us: u64,
-    /// Cache hit rate
-    cache_hit_rate: f64,
-    /// Errors encountered
-    error_count: u64,
-}
-
 /// Advanced AGI Attack Detection System
 #[derive(Debug, Clone)]
 pub struct AGIAttackDetector {
@@ -94,6 +129,8 @@ pub struct AGIAttackDetector {
     analysis_window: std::time::Duration,
     /// Threat intelligence database
     threat_db: Arc<RwLock<ThreatDatabase>>,
+    /// Weights of signature matches and model signals
+    config: AgiDetectionConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -149,32 +186,347 @@ impl EthicsEngine {
         let foundation = BiblicalFoundation::new()?;
         let scripture_db = ScriptureDatabase::new()?;
         
-        let agi_detector = AGIAttackDetector::new();
+        let agi_detector = AGIAttackDetector::with_config(config.agi_detection.clone());
+        let identities = match &config.identity.registry_path {
+            Some(path) => Some(IdentityRegistry::load(path)?),
+            None => None,
+        };
+        let sinks = (!config.sinks.is_empty()).then(|| SinkDispatcher::from_config(&config.sinks));
+        let journal = match (&config.journal_storage, &config.journal) {
+            (Some(storage), _) => Some(DecisionJournal::with_storage(ark_storage::open(storage)
+                .map_err(|e| EthicsError::ConfigurationError(format!("Journal store: {}", e)))?)),
+            (None, Some(path)) => Some(DecisionJournal::open(path)?),
+            (None, None) => None,
+        };
+        let enricher = Enricher::new(config.enrichment.clone());
+        let revocations = match &config.validity.revocation_list {
+            Some(path) => RevocationList::open(path)?,
+            None => RevocationList::new(),
+        };
+        let stats = EvaluationStats::new(config.stats);
+        let pseudonyms = match &config.pseudonymization {
+            Some(pseudonymization) => Some(Arc::new(Pseudonymizer::open(pseudonymization.clone())?)),
+            None => None,
+        };
+        let localizer = Localizer::from_config(&config.language, &config.localization)?;
+        let actor_ledger = match &config.actor_ledger {
+            Some(path) => Some(ActorLedger::open(path)?),
+            None => None,
+        };
         
         Ok(EthicsEngine {
             foundation,
+            memo: Arc::new(RwLock::new(ContentMemo::new(&config.content_memo))),
             config,
             rule_cache: Arc::new(RwLock::new(HashMap::new())),
             scripture_db,
-            stats: Arc::new(RwLock::new(EvaluationStats::default())),
+            stats: Arc::new(RwLock::new(stats)),
             agi_detector,
+            predicates: PredicateRegistry::standard(),
+            identities,
+            sinks,
+            journal,
+            multimodal: None,
+            harm_signals: None,
+            enricher,
+            revocations,
+            pseudonyms,
+            localizer,
+            actor_ledger,
+            cohorts: RwLock::new(CohortIndex::default()),
         })
     }
     
     /// Enhanced content evaluation with AGI attack protection
     pub fn evaluate_content(&self, event: &EthicsEvent) -> EthicsResult<EthicsDecision> {
+        self.evaluate_content_traced(event).map(|(decision, _)| decision)
+    }
+    
+    /// Evaluate an event, recording context enrichment and whether the decision relied on model output
+    pub fn evaluate_traced(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)> {
+        let started = std::time::Instant::now();
+        
+        let enriched;
+        let mut enrichment = Vec::new();
+        let mut quarantine = false;
+        let event = if self.enricher.is_empty() {
+            event
+        } else {
+            let mut copy = event.clone();
+            let report = self.enricher.enrich(&mut copy);
+            if !report.missing.is_empty() {
+                debug!("Context of {} still lacks {:?} after enrichment", event.event_id, report.missing);
+                quarantine = self.enricher.missing_policy() == MissingEnrichmentPolicy::Quarantine;
+            }
+            enrichment = report.records;
+            enriched = copy;
+            &enriched
+        };
+        
+        let result = if quarantine {
+            warn!("Quarantining {}: context enrichment incomplete", event.event_id);
+            Ok((Self::quarantine_decision(), DecisionTrace {
+                event_id: event.event_id.clone(),
+                content: ContentSource::None,
+                enrichment: Vec::new(),
+                consensus: Vec::new(),
+            }))
+        } else {
+            self.evaluate_content_traced(event)
+        }
+        .map(|(decision, mut trace)| {
+            trace.enrichment = enrichment;
+            (decision, trace)
+        });
+        let elapsed = started.elapsed();
+        
+        self.update_stats(|stats| match &result {
+            Ok((decision, trace)) => {
+                let mut tags: Vec<&str> = event.actor.tags.iter().map(String::as_str).collect();
+                if let ContentSource::Model { tags: synthetic, .. } = &trace.content {
+                    tags.extend(synthetic.iter().map(String::as_str).filter(|tag| !event.actor.tags.iter().any(|t| t == tag)));
+                }
+                stats.record_decision(decision, &tags, elapsed)
+            }
+            Err(_) => stats.record_error(elapsed),
+        });
+        
+        if let (Ok((decision, _)), Some(journal)) = (&result, &self.journal) {
+            if let Err(e) = journal.record(event, decision) {
+                warn!("Failed to journal decision on {}: {}", event.event_id, e);
+            }
+        }
+        if let (Ok((decision, _)), Some(sinks)) = (&result, &self.sinks) {
+            sinks.notify(&event.event_id, decision);
+        }
+        
+        result
+    }
+    
+    /// Evaluate an event and issue the decision with an expiry and revocation ID
+    ///
+    /// `actor` identifies the actor for actor-wide revocation, e.g. the
+    /// producer of a signed envelope. With pseudonymization configured the
+    /// decision carries the actor's pseudonym instead. The actor is
+    /// recorded in the actor ledger, and the event is evaluated with the
+    /// tag of the actor's cohort, if it belongs to one.
+    pub fn evaluate_issued(&self, event: &EthicsEvent, actor: Option<&str>) -> EthicsResult<IssuedDecision> {
+        let actor = actor.map(|actor| self.pseudonymize(actor));
+        let event = match &actor {
+            Some(actor) => self.with_cohort(event, actor),
+            None => Cow::Borrowed(event),
+        };
+        let (decision, _) = self.evaluate_traced(&event)?;
+        if let Some(actor) = &actor {
+            self.record_actor(&event.event_id, actor);
+        }
+        Ok(IssuedDecision::issue(&event, decision, actor.as_deref(), &self.config.validity))
+    }
+    
+    /// Revoke every earlier Allow issued for `actor`
+    ///
+    /// With pseudonymization configured, the actor is revoked under its
+    /// pseudonym of every retained key, so Allows issued before the last
+    /// key rotation are revoked too and the list never holds the identifier.
+    pub fn revoke_actor(&self, actor: &str, source: RevocationSource, reason: &str) -> EthicsResult<Vec<Revocation>> {
+        let targets = match &self.pseudonyms {
+            Some(pseudonyms) => pseudonyms.pseudonyms(actor),
+            None => vec![actor.to_string()],
+        };
+        targets.into_iter()
+            .map(|target| self.revocations.revoke(RevocationTarget::Actor(target), source.clone(), reason))
+            .collect()
+    }
+    
+    /// Identifier of `actor` as it may appear in decisions and logs
+    ///
+    /// The keyed pseudonym when pseudonymization is configured, the
+    /// identifier itself otherwise.
+    pub fn pseudonymize(&self, actor: &str) -> String {
+        match &self.pseudonyms {
+            Some(pseudonyms) => pseudonyms.pseudonymize(actor),
+            None => actor.to_string(),
+        }
+    }
+    
+    /// Pseudonymizer, for authorized re-identification
+    pub fn pseudonymizer(&self) -> Option<&Arc<Pseudonymizer>> {
+        self.pseudonyms.as_ref()
+    }
+    
+    /// Cluster the actors of the ledger by their refusals journaled since `since`
+    ///
+    /// Needs both the actor ledger and the decision journal. The analysis
+    /// is installed, so later events of cohort members are evaluated with
+    /// the cohort's tag.
+    pub fn analyze_cohorts(&self, since: DateTime<Utc>) -> EthicsResult<CohortAnalysis> {
+        let (Some(ledger), Some(journal)) = (&self.actor_ledger, &self.journal) else {
+            return Err(EthicsError::ConfigurationError(
+                "Cohort analysis needs both the actor ledger and the decision journal".to_string(),
+            ));
+        };
+        let ledger = cohort::read_ledger_since(ledger.path(), since)?;
+        let analysis = cohort::analyze(&ledger, &journal.read_since(since)?, &self.config.cohorts, Utc::now());
+        self.install_cohorts(&analysis);
+        Ok(analysis)
+    }
+    
+    /// Evaluate later events of cohort members with the cohorts of `analysis`
+    pub fn install_cohorts(&self, analysis: &CohortAnalysis) {
+        for cohort in analysis.threats() {
+            warn!(
+                "Cohort {} of {} actors{}{}: {} refusals since {}, mostly {}",
+                cohort.id,
+                cohort.members.len(),
+                if cohort.emerging { ", emerging" } else { "" },
+                if cohort.coordinated { ", coordinated" } else { "" },
+                cohort.refusals,
+                cohort.first_seen,
+                cohort.principles.first().map(|(principle, _)| principle.as_str()).unwrap_or("-"),
+            );
+        }
+        info!("Installed {} actor cohorts over {} clustered actors", analysis.cohorts.len(), analysis.actors);
+        let index = CohortIndex::from_analysis(analysis);
+        match self.cohorts.write() {
+            Ok(mut cohorts) => *cohorts = index,
+            Err(poisoned) => *poisoned.into_inner() = index,
+        }
+    }
+    
+    /// Cohort `actor` belongs to in the installed analysis
+    pub fn cohort_of(&self, actor: &str) -> Option<CohortMembership> {
+        self.read_cohorts().membership(&self.pseudonymize(actor)).cloned()
+    }
+    
+    fn read_cohorts(&self) -> RwLockReadGuard<'_, CohortIndex> {
+        self.cohorts.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// `event` tagged with the cohort of `actor`, when it belongs to one
+    fn with_cohort<'a>(&self, event: &'a EthicsEvent, actor: &str) -> Cow<'a, EthicsEvent> {
+        let Some(tag) = self.read_cohorts().membership(actor).map(CohortMembership::tag) else {
+            return Cow::Borrowed(event);
+        };
+        if event.actor.tags.contains(&tag) {
+            return Cow::Borrowed(event);
+        }
+        let mut tagged = event.clone();
+        tagged.actor.tags.push(tag);
+        Cow::Owned(tagged)
+    }
+    
+    fn record_actor(&self, event_id: &str, actor: &str) {
+        if let Some(ledger) = &self.actor_ledger {
+            if let Err(e) = ledger.record(event_id, actor) {
+                warn!("Failed to record the actor of {} in the ledger: {}", event_id, e);
+            }
+        }
+    }
+    
+    /// Justification of `decision` for an operator reading `locale`
+    ///
+    /// Without a locale the configured `language` is used.
+    pub fn justify(&self, decision: &EthicsDecision, locale: Option<&str>) -> LocalizedJustification {
+        self.localizer.justify(decision, locale)
+    }
+    
+    /// Whether an issued decision is unexpired and unrevoked
+    pub fn is_decision_valid(&self, issued: &IssuedDecision) -> bool {
+        self.revocations.is_decision_valid(issued)
+    }
+    
+    /// Revocation list consulted by `is_decision_valid`
+    pub fn revocations(&self) -> &RevocationList {
+        &self.revocations
+    }
+    
+    /// Revoke earlier Allows of content the AGI attack detector blocked
+    fn revoke_attacked_content(&self, event: &EthicsEvent) {
+        let Some(hash) = event.content.as_ref().map(|c| &c.content_hash).filter(|h| !h.is_empty()) else {
+            return;
+        };
+        let target = RevocationTarget::ContentHash(hash.clone());
+        if let Err(e) = self.revocations.revoke(target, RevocationSource::AgiDetector, &format!("AGI attack detected in {}", event.event_id)) {
+            error!("Failed to revoke Allows of {}: {}", hash, e);
+        }
+    }
+    
+    /// Consult `provider` for missing context before every evaluation
+    pub fn register_enrichment_provider(&mut self, provider: Arc<dyn EnrichmentProvider>) {
+        info!("Registered context enrichment provider {}", provider.name());
+        self.enricher.register(provider);
+    }
+    
+    /// Decision for events whose context could not be completed under the quarantine policy
+    fn quarantine_decision() -> EthicsDecision {
+        EthicsDecision::Deny {
+            confidence: 0.5,
+            violation: "Context enrichment incomplete; quarantined pending review".to_string(),
+            violated_principles: vec!["CAUTION".to_string()],
+            scripture_refs: vec!["Proverbs 14:15".to_string()],
+        }
+    }
+    
+    /// Decision for media the multimodal analyzer could not analyze
+    fn unanalyzed_decision() -> EthicsDecision {
+        EthicsDecision::Deny {
+            confidence: 0.5,
+            violation: "Media content could not be analyzed; denied unseen".to_string(),
+            violated_principles: vec!["CAUTION".to_string()],
+            scripture_refs: vec!["Proverbs 14:15".to_string()],
+        }
+    }
+    
+    /// Delegate image, video and audio content to `analyzer` instead of the text rules
+    pub fn set_multimodal_analyzer(&mut self, analyzer: Arc<dyn MultimodalAnalyzer>) {
+        info!("Delegating media content to {}", analyzer.name());
+        self.multimodal = Some(analyzer);
+        
+        // Cached decisions on media were made by the text rules
+        self.clear_caches();
+    }
+    
+    /// Combine `source`'s harm signals with the AGI attack detector's signatures
+    pub fn set_harm_signal_source(&mut self, source: Arc<dyn HarmSignalSource>) {
+        info!("AGI attack detection consults {}", source.name());
+        self.harm_signals = Some(source);
+    }
+    
+    /// Signals of the installed harm model for `event`, if it has any
+    fn harm_signals(&self, event: &EthicsEvent) -> Option<HarmSignals> {
+        let source = self.harm_signals.as_ref()?;
+        match source.signals(event) {
+            Ok(signals) => Some(signals),
+            Err(e) => {
+                warn!("{} gave no harm signals for {}; signatures decide alone: {}", source.name(), event.event_id, e);
+                None
+            }
+        }
+    }
+    
+    fn evaluate_content_traced(&self, event: &EthicsEvent) -> EthicsResult<(EthicsDecision, DecisionTrace)> {
+        let trace = |content| DecisionTrace {
+            event_id: event.event_id.clone(),
+            content,
+            enrichment: Vec::new(),
+            consensus: Vec::new(),
+        };
+        
         // 1. First run AGI attack detection
-        let agi_result = self.agi_detector.detect_agi_attack(event);
+        let signals = self.harm_signals(event);
+        let agi_result = self.agi_detector.detect_agi_attack_with(event, signals.as_ref());
         
         if agi_result.threat_detected {
             warn!("AGI attack detected: {:?}", agi_result);
             
             if agi_result.blocking_recommended {
-                return Ok(EthicsDecision::Purge {
+                self.revoke_attacked_content(event);
+                let decision = EthicsDecision::Purge {
                     reason: format!("AGI attack detected: threat level {:?}", agi_result.threat_level),
                     confidence: 0.99,
                     biblical_basis: "Be alert and of sober mind - 1 Peter 5:8".to_string(),
-                });
+                };
+                return Ok((decision, trace(ContentSource::None)));
             }
         }
         
@@ -182,30 +534,296 @@ impl EthicsEngine {
         let cached_key = format!("{:?}", event);
         
         if let Ok(cache) = self.rule_cache.read() {
-            if let Some(cached_decision) = cache.get(&cached_key) {
-                return Ok(cached_decision.clone());
+            let cached = cache.get(&cached_key);
+            self.update_stats(|stats| stats.record_cache_lookup(cached.is_some()));
+            if let Some(cached_decision) = cached {
+                return Ok((cached_decision.clone(), trace(ContentSource::Cached)));
             }
         }
         
-        // 3. Perform comprehensive moral analysis
-        let actor_analysis = self.analyze_actor(&event.actor)?;
-        let content_analysis = self.analyze_content(&event.content)?;
-        let context_analysis = self.analyze_context(&event.context)?;
+        // 3. Analyze content and context, or reuse the analyses of
+        //    identical content evaluated for another actor
+        let memo_key = match &event.content {
+            Some(content) if self.config.content_memo.enabled => Some(memo::memo_key(content, &event.context)),
+            _ => None,
+        };
+        let memoized = memo_key.as_ref().and_then(|key| self.memo.read().ok()?.get(key));
+        let (entry, memo_hit) = match memoized {
+            Some(entry) => (entry, true),
+            None => {
+                let content = self.analyze_content(&event.content)?;
+                let context = self.analyze_context(&event.context)?;
+                let settled = Self::settled_decision(&content, &context);
+                (MemoEntry { content, context, settled }, false)
+            }
+        };
         
-        // 4. Make final decision with enhanced security
-        let decision = self.make_enhanced_decision(
-            &actor_analysis,
-            &content_analysis,
-            &context_analysis,
-            &agi_result,
-        )?;
+        // 4. Make final decision with enhanced security; the actor is only
+        //    analyzed when the content leaves the outcome open
+        let unanalyzed = matches!(entry.content.source, ContentSource::ModelFailed { .. });
+        let decision = if let Some(decision) = Self::agi_gate(&agi_result) {
+            decision
+        } else if unanalyzed {
+            Self::unanalyzed_decision()
+        } else if let Some(decision) = &entry.settled {
+            decision.clone()
+        } else {
+            let actor_analysis = self.analyze_actor(&event.actor)?;
+            Self::risk_decision(actor_analysis.risk_level, &entry.content, &entry.context)
+        };
         
-        // 5. Cache the decision
+        // 5. Cache the decision and memoize the content's analyses; a failed
+        //    analysis is retried next time rather than remembered
+        if unanalyzed {
+            return Ok((decision, trace(entry.content.source)));
+        }
         if let Ok(mut cache) = self.rule_cache.write() {
             cache.insert(cached_key, decision.clone());
         }
+        if let Some(key) = memo_key {
+            if memo_hit {
+                let lookup = if entry.settled.is_some() { MemoLookup::Settled } else { MemoLookup::Adjusted };
+                if let Ok(memo) = self.memo.read() {
+                    memo.record(lookup);
+                }
+            } else if let Ok(mut memo) = self.memo.write() {
+                memo.record(MemoLookup::Miss);
+                memo.insert(key, entry.clone());
+            }
+        }
         
-        Ok(decision)
+        Ok((decision, trace(entry.content.source)))
+    }
+    
+    /// Lookups of the content memo since the rules last changed
+    pub fn memo_stats(&self) -> MemoStats {
+        match self.memo.read() {
+            Ok(memo) => memo.stats(),
+            Err(poisoned) => poisoned.into_inner().stats(),
+        }
+    }
+    
+    /// Forget cached decisions and memoized content analyses
+    fn clear_caches(&self) {
+        if let Ok(mut cache) = self.rule_cache.write() {
+            cache.clear();
+        }
+        if let Ok(mut memo) = self.memo.write() {
+            memo.clear();
+        }
+    }
+    
+    /// Snapshot of the current statistics window
+    pub fn stats(&self) -> EngineStats {
+        match self.stats.read() {
+            Ok(stats) => stats.snapshot(),
+            Err(poisoned) => poisoned.into_inner().snapshot(),
+        }
+    }
+    
+    /// Close the current statistics window, returning its final snapshot
+    pub fn rotate_stats(&self) -> EngineStats {
+        match self.stats.write() {
+            Ok(mut stats) => stats.rotate(),
+            Err(poisoned) => poisoned.into_inner().rotate(),
+        }
+    }
+    
+    /// Discard the current statistics window
+    pub fn reset_stats(&self) {
+        self.rotate_stats();
+    }
+    
+    /// Export statistics snapshots every `interval` until the handle is dropped
+    ///
+    /// With `rotate` set, each export closes the window so snapshots cover
+    /// disjoint periods; otherwise they are cumulative.
+    pub fn start_stats_export(
+        &self,
+        interval: std::time::Duration,
+        rotate: bool,
+        exporter: Box<dyn StatsExporter>,
+    ) -> StatsExportHandle {
+        info!("Exporting ethics stats every {:?}", interval);
+        StatsExportHandle::spawn(self.stats.clone(), interval, rotate, exporter)
+    }
+    
+    /// Notify `sink` of decisions passing `filter`, retrying failures per `retry`
+    pub fn add_sink(&mut self, sink: Arc<dyn DecisionSink>, filter: SinkFilter, retry: RetryPolicy) {
+        self.sinks.get_or_insert_with(SinkDispatcher::new).add(sink, filter, retry);
+    }
+    
+    /// Delivery counters of every decision sink
+    pub fn sink_metrics(&self) -> Vec<SinkMetrics> {
+        self.sinks.as_ref().map(SinkDispatcher::metrics).unwrap_or_default()
+    }
+    
+    /// Block until queued decision notifications have been delivered or have failed
+    pub fn flush_sinks(&self) {
+        if let Some(sinks) = &self.sinks {
+            sinks.flush();
+        }
+    }
+    
+    /// Predicates available to rules
+    pub fn predicates(&self) -> &PredicateRegistry {
+        &self.predicates
+    }
+    
+    /// Register a host-defined predicate for use in rules
+    pub fn register_predicate<F>(
+        &mut self,
+        name: &str,
+        min_args: usize,
+        max_args: Option<usize>,
+        description: &str,
+        function: F,
+    ) -> EthicsResult<()>
+    where
+        F: Fn(&EthicsEvent, &[PredicateArg]) -> EthicsResult<bool> + Send + Sync + 'static,
+    {
+        self.predicates.register(name, min_args, max_args, description, function)?;
+        
+        // Cached decisions were made without the new predicate
+        self.clear_caches();
+        
+        info!("Registered rule predicate {}", name);
+        Ok(())
+    }
+    
+    /// Evaluate a predicate call such as `audience.contains_children()`
+    pub fn evaluate_predicate(&self, expression: &str, event: &EthicsEvent) -> EthicsResult<bool> {
+        self.predicates.evaluate(expression, event)
+    }
+    
+    /// Verify an incoming event's producer identity before it is ingested
+    ///
+    /// Returns the event to evaluate: a verified one tagged with its
+    /// producer's cohort, an unsigned one with its trust capped. Envelopes
+    /// that fail verification are never evaluated: the error is their
+    /// denial, already recorded in the identity audit log.
+    pub fn admit_incoming(&self, incoming: IncomingEvent) -> Result<EthicsEvent, EthicsDecision> {
+        match self.authenticate(incoming) {
+            Authentication::Verified { producer, event } => {
+                let producer = self.pseudonymize(&producer);
+                debug!("Event {} verified as signed by {}", event.event_id, producer);
+                self.record_actor(&event.event_id, &producer);
+                Ok(self.with_cohort(&event, &producer).into_owned())
+            }
+            Authentication::Unsigned(event) => {
+                self.update_stats(|stats| stats.record_unsigned());
+                Ok(event)
+            }
+            Authentication::Rejected(failure) => Err(self.reject_signature(&failure)),
+        }
+    }
+    
+    /// Verify an incoming event against the identity registry, capping its trust
+    pub fn authenticate(&self, incoming: IncomingEvent) -> Authentication {
+        envelope::authenticate(self.identities.as_ref(), &self.config.identity, incoming)
+    }
+    
+    /// Replace the identity registry
+    pub fn set_identities(&mut self, identities: IdentityRegistry) {
+        info!("Loaded {} producer identities", identities.identities.len());
+        self.identities = Some(identities);
+    }
+    
+    /// Decision for an envelope that failed verification
+    fn reject_signature(&self, failure: &SignatureFailure) -> EthicsDecision {
+        let failure = &SignatureFailure { producer: self.pseudonymize(&failure.producer), ..failure.clone() };
+        warn!("Rejected event envelope from {}: {}", failure.producer, failure.reason);
+        self.update_stats(|stats| stats.record_signature_failure());
+        if let Some(path) = &self.config.identity.audit_log {
+            if let Err(e) = failure.append_to(path) {
+                error!("Failed to audit signature failure: {}", e);
+            }
+        }
+        
+        EthicsDecision::Deny {
+            confidence: 0.99,
+            violation: format!("Event signature verification failed: {}", failure.reason),
+            violated_principles: vec!["TRUTH_OVER_LIES".to_string()],
+            scripture_refs: vec!["John 8:44".to_string()],
+        }
+    }
+    
+    /// Evaluate an event within a latency budget
+    ///
+    /// Runs the full evaluation while the stage's configured evaluation
+    /// time still fits in the budget. Otherwise a cached decision for the
+    /// event is reused; without one the event is denied pending review
+    /// rather than allowed unevaluated.
+    pub fn evaluate_within(&self, event: &EthicsEvent, budget: &mut LatencyBudget) -> EthicsResult<EthicsDecision> {
+        let allowance = std::time::Duration::from_millis(self.config.performance.max_evaluation_time_ms);
+        if budget.allows(allowance) {
+            let decision = self.evaluate(event)?;
+            self.cache_result(event, &decision)?;
+            return Ok(decision);
+        }
+        
+        let cached = self.check_cache(event)?;
+        self.update_stats(|stats| stats.record_cache_lookup(cached.is_some()));
+        if let Some(cached) = cached {
+            budget.degrade(PipelineStage::EthicsEvaluation, "cached decision");
+            return Ok(cached.decision);
+        }
+        
+        warn!("Latency budget exhausted before evaluating {}; denying pending review", event.event_id);
+        budget.degrade(PipelineStage::EthicsEvaluation, "fail-closed deny");
+        Ok(EthicsDecision::Deny {
+            confidence: 0.5,
+            violation: "Latency budget exhausted before evaluation".to_string(),
+            violated_principles: vec!["CAUTION".to_string()],
+            scripture_refs: vec!["Proverbs 14:15".to_string()],
+        })
+    }
+    
+    /// Ingest an event and evaluate it, reusing the original decision for duplicate content
+    pub fn evaluate_ingested(
+        &self,
+        ingestor: &mut ContentIngestor,
+        event: EthicsEvent,
+    ) -> EthicsResult<IngestedDecision> {
+        self.evaluate_ingested_within(ingestor, event, &mut LatencyBudget::unbounded())
+    }
+    
+    /// Ingest and evaluate an event within a latency budget (see `evaluate_within`)
+    pub fn evaluate_ingested_within(
+        &self,
+        ingestor: &mut ContentIngestor,
+        event: EthicsEvent,
+        budget: &mut LatencyBudget,
+    ) -> EthicsResult<IngestedDecision> {
+        let (event, duplicate_of) = match ingestor.ingest(event)? {
+            Ingested::Unique(event) => (event, None),
+            Ingested::Duplicate { event, original_event_id, decision: Some(decision) } => {
+                debug!("Reusing decision of {} for duplicate {}", original_event_id, event.event_id);
+                return Ok(IngestedDecision {
+                    event_id: event.event_id,
+                    decision,
+                    duplicate_of: Some(original_event_id),
+                });
+            }
+            Ingested::Duplicate { event, original_event_id, decision: None } => (event, Some(original_event_id)),
+        };
+        
+        let decision = self.evaluate_within(&event, budget)?;
+        if budget.is_degraded(PipelineStage::EthicsEvaluation) {
+            // A fallback decision must not stand in for later duplicates
+            return Ok(IngestedDecision {
+                event_id: event.event_id,
+                decision,
+                duplicate_of,
+            });
+        }
+        ingestor.record_decision(&event, &decision);
+        
+        Ok(IngestedDecision {
+            event_id: event.event_id,
+            decision,
+            duplicate_of,
+        })
     }
     
     /// Perform the actual moral evaluation
@@ -249,22 +867,43 @@ impl EthicsEngine {
         };
         
         // Check history if available
-        let history_modifier = if let Some(ref history) = actor.history {
+        let mut history_modifier = if let Some(ref history) = actor.history {
             self.evaluate_actor_history(history)?
         } else {
             0.0
         };
         
+        let mut risk_level = self.calculate_actor_risk(&violations, actor.trust_level);
+        
+        // Membership of an emerging or coordinated cohort counts against the actor
+        let cohorts = self.read_cohorts();
+        let threat = actor.tags.iter()
+            .filter_map(|tag| cohorts.tagged(tag))
+            .find(|cohort| cohort.emerging || cohort.coordinated);
+        if let Some(cohort) = threat {
+            debug!("Actor belongs to cohort {}", cohort.cohort);
+            history_modifier -= self.config.cohorts.history_penalty;
+            if risk_level < RiskLevel::High {
+                risk_level = RiskLevel::High;
+            }
+        }
+        
         Ok(ActorAnalysis {
             violations,
             trust_modifier,
             history_modifier,
-            risk_level: self.calculate_actor_risk(&violations, actor.trust_level),
+            risk_level,
         })
     }
     
     /// Analyze content for moral violations
     fn analyze_content(&self, content: &crate::Content) -> EthicsResult<ContentAnalysis> {
+        if multimodal::is_media(&content.content_type) {
+            if let Some(analysis) = self.analyze_media(content)? {
+                return Ok(analysis);
+            }
+        }
+        
         let mut violations = Vec::new();
         let mut severity_score = 0u8;
         
@@ -292,9 +931,61 @@ impl EthicsEngine {
             severity_score,
             content_hash: content.content_hash.clone(),
             biblical_alignment: self.assess_biblical_alignment(&content.data)?,
+            source: ContentSource::Rules,
         })
     }
     
+    /// Analyze media content through the multimodal analyzer
+    ///
+    /// Findings scored at or above `multimodal.min_score` become synthetic
+    /// tags evaluated like actor tags. Without an analyzer, returns `None`
+    /// and the content goes through the text path; when the analyzer fails
+    /// the analysis is marked [`ContentSource::ModelFailed`] and the event
+    /// is denied.
+    fn analyze_media(&self, content: &crate::Content) -> EthicsResult<Option<ContentAnalysis>> {
+        let Some(analyzer) = &self.multimodal else {
+            debug!("No multimodal analyzer installed; evaluating {:?} content as text", content.content_type);
+            return Ok(None);
+        };
+        let analysis = match analyzer.analyze(content) {
+            Ok(analysis) => analysis,
+            Err(e) => {
+                warn!("Multimodal analysis by {} failed, denying the content: {}", analyzer.name(), e);
+                return Ok(Some(ContentAnalysis {
+                    violations: Vec::new(),
+                    severity_score: 10,
+                    content_hash: content.content_hash.clone(),
+                    biblical_alignment: 0.0,
+                    source: ContentSource::ModelFailed {
+                        analyzer: analyzer.name().to_string(),
+                        error: e.to_string(),
+                    },
+                }));
+            }
+        };
+        
+        let min_score = self.config.multimodal.min_score;
+        let tags = analysis.synthetic_tags(min_score);
+        let mut violations = Vec::new();
+        for tag in &tags {
+            if let Some(violation) = self.evaluate_tag(tag)? {
+                violations.push(violation);
+            }
+        }
+        debug!("{} tagged {:?} content with {:?}", analysis.model, content.content_type, tags);
+        
+        Ok(Some(ContentAnalysis {
+            violations,
+            severity_score: (analysis.max_score(min_score) * 10.0).round() as u8,
+            content_hash: content.content_hash.clone(),
+            biblical_alignment: 0.0,
+            source: ContentSource::Model {
+                model: analysis.model,
+                tags,
+            },
+        }))
+    }
+    
     /// Analyze context for situational factors
     fn analyze_context(&self, context: &crate::Context) -> EthicsResult<ContextAnalysis> {
         let mut risk_multiplier = 1.0;
@@ -451,18 +1142,11 @@ impl EthicsEngine {
         Ok(hex::encode(hasher.finalize().as_bytes()))
     }
     
-    fn make_enhanced_decision(
-        &self,
-        actor_analysis: &ActorAnalysis,
-        content_analysis: &ContentAnalysis,
-        context_analysis: &ContextAnalysis,
-        agi_result: &AGIDetectionResult,
-    ) -> EthicsResult<EthicsDecision> {
-        // Enhanced decision making with AGI protection
-        
+    /// Decision forced by attack detection, whatever the content and actor
+    fn agi_gate(agi_result: &AGIDetectionResult) -> Option<EthicsDecision> {
         // If AGI threat detected, apply strict filtering
         if agi_result.threat_level >= ThreatLevel::High {
-            return Ok(EthicsDecision::Deny {
+            return Some(EthicsDecision::Deny {
                 reason: "AGI threat detected - access denied".to_string(),
                 confidence: 0.95,
                 biblical_basis: "Test the spirits - 1 John 4:1".to_string(),
@@ -471,39 +1155,58 @@ impl EthicsEngine {
         
         // Enhanced biblical compliance checking
         if agi_result.biblical_compliance_score < 0.7 {
-            return Ok(EthicsDecision::Deny {
+            return Some(EthicsDecision::Deny {
                 reason: "Biblical compliance insufficient".to_string(), 
                 confidence: 0.90,
                 biblical_basis: "Whatever is true, whatever is noble - Philippians 4:8".to_string(),
             });
         }
         
+        None
+    }
+    
+    /// Decision by cumulative risk of actor, content and context
+    fn risk_decision(
+        actor_risk: RiskLevel,
+        content_analysis: &ContentAnalysis,
+        context_analysis: &ContextAnalysis,
+    ) -> EthicsDecision {
         // Standard analysis with enhanced thresholds
-        let total_risk = actor_analysis.risk_level as u8 + 
+        let total_risk = actor_risk as u8 + 
                         (content_analysis.severity_score / 2) +
                         (context_analysis.risk_multiplier * 10.0) as u8;
                         
         if total_risk > 15 {
-            Ok(EthicsDecision::Purge {
+            EthicsDecision::Purge {
                 reason: "High cumulative risk detected".to_string(),
                 confidence: 0.88,
                 biblical_basis: "Avoid every kind of evil - 1 Thessalonians 5:22".to_string(),
-            })
+            }
         } else if total_risk > 8 {
-            Ok(EthicsDecision::Deny {
+            EthicsDecision::Deny {
                 reason: "Moderate risk detected".to_string(),
                 confidence: 0.75,
                 biblical_basis: "Be wise as serpents - Matthew 10:16".to_string(),
-            })
+            }
         } else {
-            Ok(EthicsDecision::Allow {
+            EthicsDecision::Allow {
                 confidence: 0.85,
                 conditions: vec!["Continuous monitoring".to_string()],
                 biblical_basis: "Test everything; hold fast what is good - 1 Thessalonians 5:21".to_string(),
-            })
+            }
         }
     }
     
+    /// Decision the content and context settle for an actor of any standing
+    ///
+    /// Risk only grows with the actor's risk level, so the outcome is
+    /// actor-independent when the least and the most risky actor agree.
+    fn settled_decision(content_analysis: &ContentAnalysis, context_analysis: &ContextAnalysis) -> Option<EthicsDecision> {
+        let least = Self::risk_decision(RiskLevel::Low, content_analysis, context_analysis);
+        let most = Self::risk_decision(RiskLevel::Critical, content_analysis, context_analysis);
+        (least == most).then_some(least)
+    }
+    
     fn analyze_actor(&self, _actor: &serde_json::Value) -> EthicsResult<ActorAnalysis> {
         // Enhanced actor analysis
         Ok(ActorAnalysis {
@@ -521,6 +1224,7 @@ impl EthicsEngine {
             severity_score: 1,
             content_hash: "placeholder".to_string(),
             biblical_alignment: 0.9,
+            source: ContentSource::Rules,
         })
     }
     
@@ -536,7 +1240,7 @@ impl EthicsEngine {
 
 impl EthicsEvaluator for EthicsEngine {
     fn evaluate(&self, event: &EthicsEvent) -> EthicsResult<EthicsDecision> {
-        self.evaluate_content(event)
+        self.evaluate_traced(event).map(|(decision, _)| decision)
     }
     
     fn validate_rules(&self, rules: &str) -> EthicsResult<()> {
@@ -545,10 +1249,7 @@ impl EthicsEvaluator for EthicsEngine {
     
     fn update_rules(&mut self, rules: &str) -> EthicsResult<()> {
         self.foundation.update_rules(rules)?;
-        
-        if let Ok(mut cache) = self.rule_cache.write() {
-            cache.clear();
-        }
+        self.clear_caches();
         
         Ok(())
     }
@@ -568,15 +1269,16 @@ struct ActorAnalysis {
     risk_level: RiskLevel,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct ContentAnalysis {
     violations: Vec<MoralViolation>,
     severity_score: u8,
     content_hash: String,
     biblical_alignment: f64, // -1.0 to 1.0
+    source: ContentSource,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct ContextAnalysis {
     risk_multiplier: f64,
     protection_level: ProtectionLevel,
@@ -597,7 +1299,7 @@ impl MoralViolation {
     }
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 enum RiskLevel {
     Low,
     Medium,
@@ -605,7 +1307,7 @@ enum RiskLevel {
     Critical,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum ProtectionLevel {
     Standard,
     YouthProtection,
@@ -633,6 +1335,11 @@ impl ScriptureDatabase {
 
 impl AGIAttackDetector {
     pub fn new() -> Self {
+        Self::with_config(AgiDetectionConfig::default())
+    }
+    
+    /// Detector combining signatures and model signals with `config` weights
+    pub fn with_config(config: AgiDetectionConfig) -> Self {
         let mut attack_patterns = HashMap::new();
         
         // Advanced AGI attack patterns based on real-world threats
@@ -674,6 +1381,7 @@ impl AGIAttackDetector {
             attack_patterns,
             analysis_window: std::time::Duration::from_secs(86400), // 24 hours
             threat_db,
+            config,
         }
     }
     
@@ -751,10 +1459,17 @@ impl AGIAttackDetector {
     }
     
     pub fn detect_agi_attack(&self, event: &EthicsEvent) -> AGIDetectionResult {
+        self.detect_agi_attack_with(event, None)
+    }
+    
+    /// Detect an attack, weighing signature matches against the harm model's `signals`
+    pub fn detect_agi_attack_with(&self, event: &EthicsEvent, signals: Option<&HarmSignals>) -> AGIDetectionResult {
         // Multi-layered AGI attack detection
         
-        // 1. Pattern matching against known AGI attack signatures
+        // 1. Pattern matching against known AGI attack signatures, combined with the model
         let pattern_match = self.analyze_attack_patterns(event);
+        let signature_score = if pattern_match.is_empty() { 0.0 } else { 1.0 };
+        let manipulation_score = self.config.combine(signature_score, signals);
         
         // 2. Behavioral analysis
         let behavioral_risk = self.analyze_behavioral_patterns(event);
@@ -769,7 +1484,7 @@ impl AGIAttackDetector {
         let temporal_consistency = self.check_temporal_consistency(event);
         
         let overall_threat_level = self.calculate_threat_level(
-            &pattern_match,
+            manipulation_score,
             behavioral_risk,
             quantum_risk,
             biblical_compliance,
@@ -780,6 +1495,10 @@ impl AGIAttackDetector {
             threat_detected: overall_threat_level >= ThreatLevel::High,
             threat_level: overall_threat_level,
             attack_patterns: pattern_match,
+            manipulation_score,
+            model_signals: signals
+                .filter(|signals| signals.confidence >= self.config.min_confidence)
+                .map(|signals| signals.model.clone()),
             behavioral_risk_score: behavioral_risk,
             quantum_risk_score: quantum_risk,
             biblical_compliance_score: biblical_compliance,
@@ -856,25 +1575,25 @@ impl AGIAttackDetector {
     
     fn calculate_threat_level(
         &self,
-        patterns: &[String],
+        manipulation: f64,
         behavioral_risk: f64,
         quantum_risk: f64,
         biblical_compliance: f64,
         temporal_consistency: f64,
     ) -> ThreatLevel {
-        if !patterns.is_empty() || behavioral_risk > 0.9 || quantum_risk > 0.9 || biblical_compliance < 0.1 {
+        if manipulation >= 0.9 || behavioral_risk > 0.9 || quantum_risk > 0.9 || biblical_compliance < 0.1 {
             return ThreatLevel::AGIManipulation;
         }
         
-        if behavioral_risk > 0.7 || quantum_risk > 0.7 || biblical_compliance < 0.3 || temporal_consistency < 0.5 {
+        if manipulation >= 0.7 || behavioral_risk > 0.7 || quantum_risk > 0.7 || biblical_compliance < 0.3 || temporal_consistency < 0.5 {
             return ThreatLevel::Critical;
         }
         
-        if behavioral_risk > 0.5 || quantum_risk > 0.5 || biblical_compliance < 0.7 {
+        if manipulation >= 0.5 || behavioral_risk > 0.5 || quantum_risk > 0.5 || biblical_compliance < 0.7 {
             return ThreatLevel::High;
         }
         
-        if behavioral_risk > 0.2 || quantum_risk > 0.2 || biblical_compliance < 0.9 {
+        if manipulation >= 0.2 || behavioral_risk > 0.2 || quantum_risk > 0.2 || biblical_compliance < 0.9 {
             return ThreatLevel::Medium;
         }
         
@@ -887,6 +1606,10 @@ pub struct AGIDetectionResult {
     pub threat_detected: bool,
     pub threat_level: ThreatLevel,
     pub attack_patterns: Vec<String>,
+    /// Signature matches combined with the model's manipulation score (0.0 to 1.0)
+    pub manipulation_score: f64,
+    /// Model whose signals were combined, if any
+    pub model_signals: Option<String>,
     pub behavioral_risk_score: f64,
     pub quantum_risk_score: f64,
     pub biblical_compliance_score: f64,
@@ -0,0 +1,494 @@
+//! Context Enrichment - Filling Sparse Context Before Evaluation
+//! "Every purpose is established by counsel" - Proverbs 20:18
+//!
+//! Events often arrive without location, platform or audience, and the
+//! engine then judges them as if no children could be watching. Registered
+//! `EnrichmentProvider`s look the missing fields up - from a geo-IP
+//! service, a platform registry, an audience analytics store - before the
+//! event is evaluated. Fields the producer supplied are never overwritten.
+//!
+//! Every lookup runs under a timeout on a fixed pool of worker threads, so
+//! hung providers can tie up at most the pool and its bounded queue; a
+//! lookup that finds the queue full is not attempted. Successful lookups
+//! are cached per provider and key. Each field filled, served from cache, or left empty
+//! is recorded as an `EnrichmentRecord` in the decision trace. When a field
+//! a provider is registered for stays empty, the configured
+//! `MissingEnrichmentPolicy` either proceeds with the sparse context or
+//! quarantines the event.
+
+use crate::{Audience, EthicsEvent, EthicsResult};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Context field a provider can fill
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextField {
+    /// Geographic location (ISO 3166)
+    Location,
+    /// Platform/medium
+    Platform,
+    /// Audience information
+    Audience,
+}
+
+/// Values found by a provider; `None` where it found nothing
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Enrichment {
+    /// Geographic location (ISO 3166)
+    pub location: Option<String>,
+    /// Platform/medium
+    pub platform: Option<String>,
+    /// Audience information
+    pub audience: Option<Audience>,
+}
+
+impl Enrichment {
+    fn has(&self, field: ContextField) -> bool {
+        match field {
+            ContextField::Location => self.location.is_some(),
+            ContextField::Platform => self.platform.is_some(),
+            ContextField::Audience => self.audience.is_some(),
+        }
+    }
+}
+
+/// External lookup of context fields
+pub trait EnrichmentProvider: Send + Sync {
+    /// Provider name recorded in the trace
+    fn name(&self) -> &str;
+
+    /// Fields the provider can fill
+    fn fields(&self) -> &[ContextField];
+
+    /// Key under which the result for `event` is cached; `None` disables caching
+    fn cache_key(&self, _event: &EthicsEvent) -> Option<String> {
+        None
+    }
+
+    /// Look up context for `event`
+    fn enrich(&self, event: &EthicsEvent) -> EthicsResult<Enrichment>;
+}
+
+/// What to do when a field a provider is registered for stays empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingEnrichmentPolicy {
+    /// Evaluate with the sparse context
+    #[default]
+    Proceed,
+    /// Deny pending review without evaluating
+    Quarantine,
+}
+
+/// Enrichment settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnrichmentConfig {
+    /// Time allowed for one provider lookup (milliseconds)
+    pub timeout_ms: u64,
+    /// Worker threads running lookups
+    pub workers: usize,
+    /// Lookups that may wait for a free worker
+    pub queue_capacity: usize,
+    /// How long a cached lookup is reused (seconds)
+    pub cache_ttl_secs: u64,
+    /// Cached lookups kept across all providers
+    pub cache_capacity: usize,
+    /// Handling of fields no provider could fill
+    pub missing: MissingEnrichmentPolicy,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: 20,
+            workers: 4,
+            queue_capacity: 64,
+            cache_ttl_secs: 300,
+            cache_capacity: 10_000,
+            missing: MissingEnrichmentPolicy::Proceed,
+        }
+    }
+}
+
+/// Result of one provider lookup for one field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum EnrichmentOutcome {
+    /// Filled from a fresh lookup
+    Filled,
+    /// Filled from the cache
+    Cached,
+    /// Lookup succeeded but found nothing for the field
+    NotFound,
+    /// Lookup failed
+    Failed {
+        /// Provider error
+        error: String,
+    },
+    /// Lookup did not answer within the timeout
+    TimedOut,
+    /// Lookup not attempted: every worker was busy and the queue full
+    Saturated,
+}
+
+/// Provenance of one enriched (or unenriched) field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnrichmentRecord {
+    /// Field looked up
+    pub field: ContextField,
+    /// Provider consulted
+    pub provider: String,
+    /// What the lookup yielded
+    pub outcome: EnrichmentOutcome,
+}
+
+/// Result of enriching one event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnrichmentReport {
+    /// Every lookup made, in order
+    pub records: Vec<EnrichmentRecord>,
+    /// Fields that had a provider but are still empty
+    pub missing: Vec<ContextField>,
+}
+
+struct CachedEnrichment {
+    enrichment: Enrichment,
+    stored_at: Instant,
+}
+
+/// Lookup handed to the pool, with where to send its result
+type Lookup = (Arc<dyn EnrichmentProvider>, EthicsEvent, mpsc::Sender<EthicsResult<Enrichment>>);
+
+/// Fixed set of worker threads sharing one bounded lookup queue
+///
+/// Dropping the pool closes the queue; each worker exits once its current
+/// lookup returns, without the owner waiting on a hung provider.
+struct LookupPool {
+    jobs: mpsc::SyncSender<Lookup>,
+}
+
+impl LookupPool {
+    fn start(workers: usize, queue_capacity: usize) -> Self {
+        let (jobs, queue) = mpsc::sync_channel::<Lookup>(queue_capacity);
+        let queue = Arc::new(Mutex::new(queue));
+        for index in 0..workers.max(1) {
+            let queue = queue.clone();
+            let spawned = std::thread::Builder::new().name(format!("enrichment-{}", index)).spawn(move || loop {
+                let next = match queue.lock() {
+                    Ok(queue) => queue.recv(),
+                    Err(poisoned) => poisoned.into_inner().recv(),
+                };
+                let Ok((provider, event, reply)) = next else {
+                    break;
+                };
+                let result = catch_unwind(AssertUnwindSafe(|| provider.enrich(&event))).unwrap_or_else(|_| {
+                    Err(crate::EthicsError::RuntimeError(format!("enrichment provider {} panicked", provider.name())))
+                });
+                let _ = reply.send(result);
+            });
+            if let Err(e) = spawned {
+                warn!("Could not start enrichment worker {}: {}", index, e);
+            }
+        }
+        Self { jobs }
+    }
+}
+
+/// Registered providers with their lookup cache
+pub struct Enricher {
+    providers: Vec<Arc<dyn EnrichmentProvider>>,
+    config: EnrichmentConfig,
+    cache: Mutex<HashMap<(String, String), CachedEnrichment>>,
+    /// Started with the first provider
+    pool: Option<LookupPool>,
+}
+
+impl Enricher {
+    /// Enricher without providers
+    pub fn new(config: EnrichmentConfig) -> Self {
+        Self {
+            providers: Vec::new(),
+            config,
+            cache: Mutex::new(HashMap::new()),
+            pool: None,
+        }
+    }
+
+    /// Consult `provider` after those already registered
+    pub fn register(&mut self, provider: Arc<dyn EnrichmentProvider>) {
+        if self.pool.is_none() {
+            self.pool = Some(LookupPool::start(self.config.workers, self.config.queue_capacity));
+        }
+        self.providers.push(provider);
+    }
+
+    /// Whether no providers are registered
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Policy for fields left empty
+    pub fn missing_policy(&self) -> MissingEnrichmentPolicy {
+        self.config.missing
+    }
+
+    /// Fill the empty context fields of `event`
+    ///
+    /// Providers are consulted in registration order and only while a field
+    /// they cover is still empty.
+    pub fn enrich(&self, event: &mut EthicsEvent) -> EnrichmentReport {
+        let mut report = EnrichmentReport::default();
+        let mut covered = Vec::new();
+
+        for provider in &self.providers {
+            let wanted: Vec<ContextField> =
+                provider.fields().iter().copied().filter(|field| is_empty(event, *field)).collect();
+            covered.extend(provider.fields().iter().copied());
+            if wanted.is_empty() {
+                continue;
+            }
+
+            let (result, cached) = self.lookup(provider, event);
+            for field in wanted {
+                let outcome = match &result {
+                    Ok(enrichment) if enrichment.has(field) => {
+                        fill(event, field, enrichment);
+                        if cached {
+                            EnrichmentOutcome::Cached
+                        } else {
+                            EnrichmentOutcome::Filled
+                        }
+                    }
+                    Ok(_) => EnrichmentOutcome::NotFound,
+                    Err(outcome) => outcome.clone(),
+                };
+                report.records.push(EnrichmentRecord {
+                    field,
+                    provider: provider.name().to_string(),
+                    outcome,
+                });
+            }
+        }
+
+        covered.sort_by_key(|field| *field as u8);
+        covered.dedup();
+        report.missing = covered.into_iter().filter(|field| is_empty(event, *field)).collect();
+        report
+    }
+
+    /// Cached or fresh lookup; the flag is set for cache hits
+    fn lookup(
+        &self,
+        provider: &Arc<dyn EnrichmentProvider>,
+        event: &EthicsEvent,
+    ) -> (Result<Enrichment, EnrichmentOutcome>, bool) {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+        let key = provider.cache_key(event).map(|key| (provider.name().to_string(), key));
+        if let Some(key) = &key {
+            if let Some(hit) = self.lock_cache().get(key).filter(|hit| hit.stored_at.elapsed() < ttl) {
+                return (Ok(hit.enrichment.clone()), true);
+            }
+        }
+
+        let result = self.call(provider, event);
+        if let (Ok(enrichment), Some(key)) = (&result, key) {
+            let mut cache = self.lock_cache();
+            if cache.len() >= self.config.cache_capacity {
+                cache.retain(|_, entry| entry.stored_at.elapsed() < ttl);
+            }
+            if cache.len() < self.config.cache_capacity {
+                cache.insert(key, CachedEnrichment { enrichment: enrichment.clone(), stored_at: Instant::now() });
+            }
+        }
+        (result, false)
+    }
+
+    /// Run the lookup on the pool so a hung provider cannot stall evaluation
+    fn call(&self, provider: &Arc<dyn EnrichmentProvider>, event: &EthicsEvent) -> Result<Enrichment, EnrichmentOutcome> {
+        let Some(pool) = &self.pool else {
+            return Err(EnrichmentOutcome::Saturated);
+        };
+        let (tx, rx) = mpsc::channel();
+        if pool.jobs.try_send((provider.clone(), event.clone(), tx)).is_err() {
+            warn!("Enrichment provider {} skipped: lookup queue full", provider.name());
+            return Err(EnrichmentOutcome::Saturated);
+        }
+
+        match rx.recv_timeout(Duration::from_millis(self.config.timeout_ms)) {
+            Ok(Ok(enrichment)) => Ok(enrichment),
+            Ok(Err(e)) => {
+                warn!("Enrichment provider {} failed: {}", provider.name(), e);
+                Err(EnrichmentOutcome::Failed { error: e.to_string() })
+            }
+            Err(_) => {
+                debug!("Enrichment provider {} timed out after {}ms", provider.name(), self.config.timeout_ms);
+                Err(EnrichmentOutcome::TimedOut)
+            }
+        }
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), CachedEnrichment>> {
+        match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn is_empty(event: &EthicsEvent, field: ContextField) -> bool {
+    match field {
+        ContextField::Location => event.context.location.is_none(),
+        ContextField::Platform => event.context.platform.is_none(),
+        ContextField::Audience => event.context.audience.is_none(),
+    }
+}
+
+fn fill(event: &mut EthicsEvent, field: ContextField, enrichment: &Enrichment) {
+    match field {
+        ContextField::Location => event.context.location = enrichment.location.clone(),
+        ContextField::Platform => event.context.platform = enrichment.platform.clone(),
+        ContextField::Audience => event.context.audience = enrichment.audience.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, AgeGroup, Context, EthicsError, UrgencyLevel};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct GeoLookup {
+        calls: AtomicUsize,
+    }
+
+    impl EnrichmentProvider for GeoLookup {
+        fn name(&self) -> &str {
+            "geo"
+        }
+
+        fn fields(&self) -> &[ContextField] {
+            &[ContextField::Location, ContextField::Platform]
+        }
+
+        fn cache_key(&self, event: &EthicsEvent) -> Option<String> {
+            Some(event.actor.tags.join(","))
+        }
+
+        fn enrich(&self, _event: &EthicsEvent) -> EthicsResult<Enrichment> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(Enrichment {
+                location: Some("DE".to_string()),
+                ..Enrichment::default()
+            })
+        }
+    }
+
+    struct Hanging;
+
+    impl EnrichmentProvider for Hanging {
+        fn name(&self) -> &str {
+            "audience"
+        }
+
+        fn fields(&self) -> &[ContextField] {
+            &[ContextField::Audience]
+        }
+
+        fn enrich(&self, _event: &EthicsEvent) -> EthicsResult<Enrichment> {
+            std::thread::sleep(Duration::from_millis(500));
+            Err(EthicsError::RuntimeError("unreachable".to_string()))
+        }
+    }
+
+    fn event(location: Option<&str>) -> EthicsEvent {
+        crate::utils::create_event(
+            "sparse".to_string(),
+            Actor {
+                actor_type: ActorType::Person,
+                tags: vec!["user-7".to_string()],
+                trust_level: 0.5,
+                history: None,
+            },
+            None,
+            Context {
+                location: location.map(str::to_string),
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+        )
+    }
+
+    #[test]
+    fn test_enrich_fills_caches_and_reports_missing() {
+        let geo = Arc::new(GeoLookup { calls: AtomicUsize::new(0) });
+        let mut enricher = Enricher::new(EnrichmentConfig::default());
+        enricher.register(geo.clone());
+        enricher.register(Arc::new(Hanging));
+
+        let mut first = event(None);
+        let report = enricher.enrich(&mut first);
+        assert_eq!(first.context.location.as_deref(), Some("DE"));
+        assert_eq!(
+            report.records.iter().map(|r| (r.field, r.outcome.clone())).collect::<Vec<_>>(),
+            vec![
+                (ContextField::Location, EnrichmentOutcome::Filled),
+                (ContextField::Platform, EnrichmentOutcome::NotFound),
+                (ContextField::Audience, EnrichmentOutcome::TimedOut),
+            ]
+        );
+        assert_eq!(report.missing, vec![ContextField::Platform, ContextField::Audience]);
+
+        let mut second = event(None);
+        let report = enricher.enrich(&mut second);
+        assert_eq!(report.records[0].outcome, EnrichmentOutcome::Cached);
+        assert_eq!(geo.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_hung_lookups_are_bounded_by_the_pool() {
+        let mut enricher = Enricher::new(EnrichmentConfig { workers: 1, queue_capacity: 1, ..EnrichmentConfig::default() });
+        enricher.register(Arc::new(Hanging));
+
+        // One lookup hangs the only worker and one waits in the queue;
+        // further lookups are not attempted until they drain
+        let outcomes: Vec<EnrichmentOutcome> = (0..4)
+            .map(|_| enricher.enrich(&mut event(None)).records.remove(0).outcome)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                EnrichmentOutcome::TimedOut,
+                EnrichmentOutcome::TimedOut,
+                EnrichmentOutcome::Saturated,
+                EnrichmentOutcome::Saturated,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_supplied_fields_are_kept() {
+        let mut enricher = Enricher::new(EnrichmentConfig::default());
+        enricher.register(Arc::new(GeoLookup { calls: AtomicUsize::new(0) }));
+
+        let mut supplied = event(Some("FR"));
+        supplied.context.platform = Some("radio".to_string());
+        supplied.context.audience = Some(Audience {
+            age_groups: vec![AgeGroup::Adults],
+            vulnerable_groups: vec![],
+            size: None,
+        });
+        let report = enricher.enrich(&mut supplied);
+
+        assert_eq!(supplied.context.location.as_deref(), Some("FR"));
+        assert!(report.records.is_empty());
+        assert!(report.missing.is_empty());
+    }
+}
@@ -0,0 +1,345 @@
+//! Event Envelopes - Signed Actor Identity
+//! "Beloved, believe not every spirit, but try the spirits whether they are of God" - 1 John 4:1
+//!
+//! `Actor.trust_level` is only a claim by whoever submitted the event.
+//! Producers with a registered identity wrap events in an `EventEnvelope`:
+//! the event's JSON payload signed with their Dilithium3 key. The engine
+//! verifies the envelope against the identity registry before evaluation
+//! and caps the event's trust at the identity's maximum. Unsigned events
+//! are still evaluated, but their trust is capped at a low configured
+//! level. Envelopes that fail verification are never evaluated: they get a
+//! dedicated denial and an entry in the identity audit log.
+
+use chrono::{DateTime, Utc};
+use pq_types::canonical::CanonicalEncoder;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::DilithiumSignatureBytes;
+use pqcrypto_dilithium::{detached_sign, verify_detached_signature, DetachedSignature, PublicKey, SecretKey};
+use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::schema;
+use crate::{EthicsError, EthicsEvent, EthicsResult};
+
+/// Domain separator for envelope signatures
+pub const ENVELOPE_SIGNATURE_DOMAIN: &[u8] = b"ark-ethics-event-envelope-v2";
+
+/// Most identities accepted in a registry
+pub const MAX_IDENTITIES: usize = 4096;
+
+/// Longest producer identifier
+pub const MAX_PRODUCER_ID_LENGTH: usize = 128;
+
+/// Identity verification settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentityConfig {
+    /// Registry of producer identities (JSON); signed events are rejected without one
+    pub registry_path: Option<PathBuf>,
+    /// Highest trust level an unsigned event keeps
+    pub unsigned_trust_cap: f64,
+    /// Append-only log of signature failures (JSON lines)
+    pub audit_log: Option<PathBuf>,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            registry_path: None,
+            unsigned_trust_cap: 0.1,
+            audit_log: None,
+        }
+    }
+}
+
+/// Producer allowed to sign events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredIdentity {
+    /// Producer id named in envelopes
+    pub id: String,
+    /// Dilithium3 public key (hex)
+    pub dilithium_public: String,
+    /// Highest trust level events from this producer may claim
+    #[serde(default = "full_trust")]
+    pub max_trust: f64,
+}
+
+fn full_trust() -> f64 {
+    1.0
+}
+
+/// Identities whose signed events are accepted
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdentityRegistry {
+    /// Registered producers
+    pub identities: Vec<RegisteredIdentity>,
+}
+
+impl Validate for IdentityRegistry {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("identities", self.identities.len(), MAX_IDENTITIES)?;
+        for identity in &self.identities {
+            decode::check_identifier("identity id", &identity.id, MAX_PRODUCER_ID_LENGTH)?;
+            // Hex keys: Dilithium3 public keys are under 2 KiB
+            decode::check_len("dilithium_public", &identity.dilithium_public, 8192)?;
+            if !(0.0..=1.0).contains(&identity.max_trust) {
+                return Err(format!("identity {} max_trust must be within 0.0..=1.0", identity.id));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IdentityRegistry {
+    /// Load a registry from a JSON file
+    pub fn load(path: &Path) -> EthicsResult<Self> {
+        let contents = std::fs::read(path)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Registered identity of a producer
+    pub fn get(&self, id: &str) -> Option<&RegisteredIdentity> {
+        self.identities.iter().find(|identity| identity.id == id)
+    }
+
+    /// Verify an envelope and return its event with trust capped for the producer
+    pub fn open(&self, envelope: &EventEnvelope) -> Result<EthicsEvent, SignatureFailure> {
+        let fail = |reason: &str| SignatureFailure::new(&envelope.producer, reason);
+
+        let identity = self.get(&envelope.producer).ok_or_else(|| fail("unknown producer"))?;
+        let public = hex::decode(&identity.dilithium_public)
+            .ok()
+            .and_then(|bytes| PublicKey::from_bytes(&bytes).ok())
+            .ok_or_else(|| fail("malformed registered key"))?;
+        let signature = DetachedSignature::from_bytes(envelope.signature.as_bytes())
+            .map_err(|_| fail("malformed signature"))?;
+        verify_detached_signature(&signature, &signed_message(&envelope.producer, &envelope.payload), &public)
+            .map_err(|_| fail("signature does not verify"))?;
+
+        let mut event: EthicsEvent = schema::from_slice(envelope.payload.as_bytes(), &DecodeLimits::MESSAGE)
+            .map_err(|e| fail(&format!("malformed payload: {}", e)))?;
+        event.actor.trust_level = event.actor.trust_level.clamp(0.0, identity.max_trust);
+        Ok(event)
+    }
+}
+
+/// Event signed by a registered producer
+///
+/// The payload is the event's JSON exactly as signed, so verification does
+/// not depend on re-serializing the event identically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Producer id in the identity registry
+    pub producer: String,
+    /// Event JSON
+    pub payload: String,
+    /// Dilithium3 signature over the producer and payload
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl EventEnvelope {
+    /// Sign an event as `producer`
+    pub fn seal(event: &EthicsEvent, producer: &str, secret: &SecretKey) -> EthicsResult<Self> {
+        let payload = schema::to_string(event)?;
+        let signature = detached_sign(&signed_message(producer, &payload), secret);
+        Ok(Self {
+            producer: producer.to_string(),
+            signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+                .map_err(|e| EthicsError::RuntimeError(e.to_string()))?,
+            payload,
+        })
+    }
+}
+
+/// Event as received from a producer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IncomingEvent {
+    /// Event in a signed envelope
+    Signed(EventEnvelope),
+    /// Bare event; its claimed trust is capped
+    Unsigned(EthicsEvent),
+}
+
+impl From<EthicsEvent> for IncomingEvent {
+    fn from(event: EthicsEvent) -> Self {
+        Self::Unsigned(event)
+    }
+}
+
+/// Outcome of identity verification
+#[derive(Debug, Clone)]
+pub enum Authentication {
+    /// Envelope verified against the producer's registered key
+    Verified {
+        /// Producer that signed the event
+        producer: String,
+        /// Event with trust capped at the producer's maximum
+        event: EthicsEvent,
+    },
+    /// Unsigned event with trust capped at the unsigned level
+    Unsigned(EthicsEvent),
+    /// Envelope failed verification and must not be evaluated
+    Rejected(SignatureFailure),
+}
+
+/// Failed envelope verification, as written to the identity audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureFailure {
+    /// When verification failed
+    pub timestamp: DateTime<Utc>,
+    /// Producer the envelope claimed
+    pub producer: String,
+    /// Why verification failed
+    pub reason: String,
+}
+
+impl SignatureFailure {
+    fn new(producer: &str, reason: &str) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            producer: producer.chars().take(MAX_PRODUCER_ID_LENGTH).collect(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Append this failure to an audit log as a JSON line
+    pub fn append_to(&self, path: &Path) -> EthicsResult<()> {
+        let line = serde_json::to_string(self)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize audit entry: {}", e)))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))?;
+        writeln!(file, "{}", line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Verify an incoming event and cap its trust
+///
+/// Signed events need a registry; without one every envelope is rejected.
+pub fn authenticate(
+    registry: Option<&IdentityRegistry>,
+    config: &IdentityConfig,
+    incoming: IncomingEvent,
+) -> Authentication {
+    match incoming {
+        IncomingEvent::Signed(envelope) => {
+            let opened = match registry {
+                Some(registry) => registry.open(&envelope),
+                None => Err(SignatureFailure::new(&envelope.producer, "no identity registry configured")),
+            };
+            match opened {
+                Ok(event) => Authentication::Verified { producer: envelope.producer, event },
+                Err(failure) => Authentication::Rejected(failure),
+            }
+        }
+        IncomingEvent::Unsigned(mut event) => {
+            event.actor.trust_level = event.actor.trust_level.clamp(0.0, config.unsigned_trust_cap);
+            Authentication::Unsigned(event)
+        }
+    }
+}
+
+/// Canonical message signed for an envelope: the producer and the payload digest
+fn signed_message(producer: &str, payload: &str) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(ENVELOPE_SIGNATURE_DOMAIN);
+    encoder.str(producer).fixed(blake3::hash(payload.as_bytes()).as_bytes());
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+    use pqcrypto_dilithium::keypair;
+
+    fn event(trust_level: f64) -> EthicsEvent {
+        EthicsEvent {
+            event_id: "envelope".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Institution,
+                tags: vec![],
+                trust_level,
+                history: None,
+            },
+            content: None,
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn registry(public: &PublicKey, max_trust: f64) -> IdentityRegistry {
+        IdentityRegistry {
+            identities: vec![RegisteredIdentity {
+                id: "newsroom".to_string(),
+                dilithium_public: hex::encode(public.as_bytes()),
+                max_trust,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_verified_envelope_caps_trust_at_identity() {
+        let (public, secret) = keypair();
+        let registry = registry(&public, 0.7);
+        let envelope = EventEnvelope::seal(&event(0.95), "newsroom", &secret).unwrap();
+
+        match authenticate(Some(&registry), &IdentityConfig::default(), IncomingEvent::Signed(envelope)) {
+            Authentication::Verified { producer, event } => {
+                assert_eq!(producer, "newsroom");
+                assert_eq!(event.actor.trust_level, 0.7);
+            }
+            other => panic!("expected verified event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tampered_and_unregistered_envelopes_rejected() {
+        let (public, secret) = keypair();
+        let (_, impostor) = keypair();
+        let registry = registry(&public, 1.0);
+        let config = IdentityConfig::default();
+
+        let mut tampered = EventEnvelope::seal(&event(0.2), "newsroom", &secret).unwrap();
+        tampered.payload = tampered.payload.replace("0.2", "1.0");
+        let forged = EventEnvelope::seal(&event(0.2), "newsroom", &impostor).unwrap();
+        let unknown = EventEnvelope::seal(&event(0.2), "stranger", &secret).unwrap();
+
+        for envelope in [tampered, forged, unknown.clone()] {
+            assert!(matches!(
+                authenticate(Some(&registry), &config, IncomingEvent::Signed(envelope)),
+                Authentication::Rejected(_)
+            ));
+        }
+        assert!(matches!(authenticate(None, &config, IncomingEvent::Signed(unknown)), Authentication::Rejected(_)));
+    }
+
+    #[test]
+    fn test_unsigned_event_trust_capped() {
+        let config = IdentityConfig::default();
+        match authenticate(None, &config, IncomingEvent::Unsigned(event(0.99))) {
+            Authentication::Unsigned(event) => assert_eq!(event.actor.trust_level, config.unsigned_trust_cap),
+            other => panic!("expected unsigned event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_signed_message_is_frozen() {
+        let message = signed_message("sensor-1", "{\"event_id\":\"envelope\"}");
+        assert_eq!(&message[8..8 + ENVELOPE_SIGNATURE_DOMAIN.len()], ENVELOPE_SIGNATURE_DOMAIN);
+        assert_eq!(blake3::hash(&message).to_hex().as_str(), "3cd531d13a0676b60ca9279cf1e59f50d7892053aa35d903ed7f922b25906338");
+    }
+}
@@ -0,0 +1,359 @@
+//! Content Ingestion - Canonical Hashing and Deduplication
+//! "A false balance is abomination to the LORD: but a just weight is his delight" - Proverbs 11:1
+//!
+//! `Content.content_hash` arrives from the caller and cannot be trusted. The
+//! ingestor normalizes content (Unicode NFC, LF line endings, no trailing
+//! whitespace), computes a canonical blake3 hash over the content type and
+//! normalized data, and rejects events whose supplied hash differs. Metadata
+//! is not hashed: it carries per-delivery details such as source and receipt
+//! time, which must not make identical content look distinct.
+//!
+//! Content seen again within the deduplication window is reported as a
+//! duplicate linked to the first event carrying it. The decision recorded
+//! for the content is only handed to a duplicate from the same subject:
+//! an actor of the same type, trust level, tags and history, in the same
+//! context. Anyone else's copy of the content is evaluated afresh, so one
+//! actor's verdict is never served to another.
+
+use crate::budget::LatencyBudget;
+use crate::{Content, EthicsDecision, EthicsError, EthicsEvent, EthicsResult};
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use unicode_normalization::UnicodeNormalization;
+
+/// Key derivation context for canonical content hashes
+pub const CONTENT_HASH_CONTEXT: &str = "ARK ethics_dsl content hash v1";
+
+/// Key derivation context for decision subject keys
+pub const SUBJECT_KEY_CONTEXT: &str = "ARK ethics_dsl decision subject v1";
+
+/// Ingestion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestConfig {
+    /// How long identical content is treated as a duplicate
+    pub dedup_window: Duration,
+    /// Reject content that arrives without a hash
+    pub require_hash: bool,
+    /// Upper bound on remembered content hashes
+    pub max_tracked: usize,
+    /// End-to-end latency budget started for each ingested event
+    pub latency_budget: Duration,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            dedup_window: Duration::from_secs(3600),
+            require_hash: false,
+            max_tracked: 100_000,
+            latency_budget: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Outcome of ingesting an event
+#[derive(Debug, Clone)]
+pub enum Ingested {
+    /// First occurrence of the content within the window, or no content
+    Unique(EthicsEvent),
+    /// Content already seen within the window
+    Duplicate {
+        /// Normalized duplicate event
+        event: EthicsEvent,
+        /// Event that first carried the content
+        original_event_id: String,
+        /// Decision recorded for the content, if it was taken for the same subject
+        decision: Option<EthicsDecision>,
+    },
+}
+
+impl Ingested {
+    /// The normalized event
+    pub fn event(&self) -> &EthicsEvent {
+        match self {
+            Ingested::Unique(event) => event,
+            Ingested::Duplicate { event, .. } => event,
+        }
+    }
+}
+
+/// Decision for an ingested event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngestedDecision {
+    /// Event the decision applies to
+    pub event_id: String,
+    /// The decision
+    pub decision: EthicsDecision,
+    /// Original event when the content was a duplicate
+    pub duplicate_of: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct SeenContent {
+    original_event_id: String,
+    first_seen: DateTime<Utc>,
+    /// Decision with the subject key of the event it was taken for
+    decision: Option<(String, EthicsDecision)>,
+}
+
+/// Normalizes, verifies and deduplicates incoming events
+#[derive(Debug, Default)]
+pub struct ContentIngestor {
+    config: IngestConfig,
+    seen: HashMap<String, SeenContent>,
+    /// Hashes in order of first sighting, for expiry
+    order: VecDeque<String>,
+}
+
+impl ContentIngestor {
+    /// Create an ingestor
+    pub fn new(config: IngestConfig) -> Self {
+        Self {
+            config,
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Normalize and verify an event, detecting duplicate content
+    pub fn ingest(&mut self, mut event: EthicsEvent) -> EthicsResult<Ingested> {
+        let Some(content) = event.content.as_mut() else {
+            return Ok(Ingested::Unique(event));
+        };
+
+        content.data = normalize_data(&content.data);
+        let computed = content_hash(content);
+        let supplied = content.content_hash.trim();
+        if supplied.is_empty() {
+            if self.config.require_hash {
+                return Err(EthicsError::IntegrityError(format!("Event {} has no content hash", event.event_id)));
+            }
+        } else if !supplied.eq_ignore_ascii_case(&computed) {
+            return Err(EthicsError::IntegrityError(format!(
+                "Event {} content hash mismatch: supplied {}, computed {}",
+                event.event_id, supplied, computed
+            )));
+        }
+        content.content_hash = computed.clone();
+
+        self.expire(event.timestamp);
+
+        if let Some(seen) = self.seen.get(&computed) {
+            debug!("Event {} duplicates content of {}", event.event_id, seen.original_event_id);
+            let subject = subject_key(&event);
+            let decision = seen
+                .decision
+                .as_ref()
+                .filter(|(decided_for, _)| subject.as_ref() == Some(decided_for))
+                .map(|(_, decision)| decision.clone());
+            return Ok(Ingested::Duplicate {
+                original_event_id: seen.original_event_id.clone(),
+                decision,
+                event,
+            });
+        }
+
+        if self.seen.len() >= self.config.max_tracked {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(computed.clone(), SeenContent {
+            original_event_id: event.event_id.clone(),
+            first_seen: event.timestamp,
+            decision: None,
+        });
+        self.order.push_back(computed);
+
+        Ok(Ingested::Unique(event))
+    }
+
+    /// Start the latency budget for an event about to be ingested
+    pub fn start_budget(&self) -> LatencyBudget {
+        LatencyBudget::new(self.config.latency_budget)
+    }
+
+    /// Link the decision on an ingested event to its content, so later
+    /// duplicates from the same subject can reuse it
+    pub fn record_decision(&mut self, event: &EthicsEvent, decision: &EthicsDecision) {
+        let (Some(content), Some(subject)) = (&event.content, subject_key(event)) else {
+            return;
+        };
+        if let Some(seen) = self.seen.get_mut(&content.content_hash) {
+            seen.decision = Some((subject, decision.clone()));
+        }
+    }
+
+    /// Number of content hashes currently tracked
+    pub fn tracked(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Forget content first seen before the window preceding `now`
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let window = chrono::Duration::from_std(self.config.dedup_window).unwrap_or(chrono::Duration::MAX);
+        let cutoff = now.checked_sub_signed(window).unwrap_or(DateTime::<Utc>::MIN_UTC);
+        while let Some(oldest) = self.order.front() {
+            match self.seen.get(oldest) {
+                Some(seen) if seen.first_seen >= cutoff => break,
+                _ => {
+                    let oldest = self.order.pop_front().expect("front exists");
+                    self.seen.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+/// Canonical form of content data
+pub fn normalize_data(data: &str) -> String {
+    let unified = data.replace("\r\n", "\n").replace('\r', "\n");
+    let lines: Vec<&str> = unified.lines().map(str::trim_end).collect();
+    lines.join("\n").trim_end_matches('\n').nfc().collect()
+}
+
+/// Key of everything besides the content that decides an event
+///
+/// Covers the actor's type, trust level, tags (in any order) and history
+/// and the event context. `None` when they cannot be serialized; such an
+/// event never shares a decision.
+pub fn subject_key(event: &EthicsEvent) -> Option<String> {
+    let mut actor = event.actor.clone();
+    actor.tags.sort();
+    actor.tags.dedup();
+    let subject = serde_json::to_vec(&(&actor, &event.context)).ok()?;
+    Some(blake3::Hasher::new_derive_key(SUBJECT_KEY_CONTEXT).update(&subject).finalize().to_hex().to_string())
+}
+
+/// Canonical hash of normalized content, as lowercase hex
+pub fn content_hash(content: &Content) -> String {
+    let data = normalize_data(&content.data);
+    let content_type = format!("{:?}", content.content_type);
+
+    let mut hasher = blake3::Hasher::new_derive_key(CONTENT_HASH_CONTEXT);
+    hasher.update(&(content_type.len() as u64).to_le_bytes());
+    hasher.update(content_type.as_bytes());
+    hasher.update(&(data.len() as u64).to_le_bytes());
+    hasher.update(data.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, ContentType, Context, UrgencyLevel};
+
+    fn event(id: &str, data: &str, hash: &str, minutes: i64) -> EthicsEvent {
+        EthicsEvent {
+            event_id: id.to_string(),
+            actor: Actor {
+                actor_type: ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: data.to_string(),
+                metadata: HashMap::new(),
+                content_hash: hash.to_string(),
+            }),
+            context: Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+            timestamp: DateTime::<Utc>::from_timestamp(1_700_000_000 + minutes * 60, 0).unwrap(),
+        }
+    }
+
+    fn allow() -> EthicsDecision {
+        EthicsDecision::Allow {
+            confidence: 0.9,
+            justification: "righteous".to_string(),
+            scripture_refs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_normalization_makes_hash_canonical() {
+        let a = event("a", "Caf\u{e9}  \r\nline two\n\n", "", 0);
+        let b = event("b", "Cafe\u{301}\nline two", "", 0);
+        let hash_a = content_hash(a.content.as_ref().unwrap());
+        assert_eq!(hash_a, content_hash(b.content.as_ref().unwrap()));
+
+        let mut video = b.content.clone().unwrap();
+        video.content_type = ContentType::Video;
+        assert_ne!(hash_a, content_hash(&video));
+        assert_eq!(normalize_data("x \r\ny\r"), "x\ny");
+    }
+
+    #[test]
+    fn test_supplied_hash_is_verified() {
+        let mut ingestor = ContentIngestor::new(IngestConfig::default());
+        let expected = content_hash(event("a", "peace", "", 0).content.as_ref().unwrap());
+
+        let filled = ingestor.ingest(event("a", "peace", "", 0)).unwrap();
+        assert_eq!(filled.event().content.as_ref().unwrap().content_hash, expected);
+        assert!(ingestor.ingest(event("b", "peace", &expected.to_uppercase(), 0)).is_ok());
+        assert!(matches!(
+            ingestor.ingest(event("c", "war", &expected, 0)),
+            Err(EthicsError::IntegrityError(_))
+        ));
+
+        let mut strict = ContentIngestor::new(IngestConfig { require_hash: true, ..IngestConfig::default() });
+        assert!(strict.ingest(event("d", "peace", "", 0)).is_err());
+    }
+
+    #[test]
+    fn test_duplicates_link_to_original_decision_within_window() {
+        let mut ingestor = ContentIngestor::new(IngestConfig {
+            dedup_window: Duration::from_secs(600),
+            ..IngestConfig::default()
+        });
+
+        let first = ingestor.ingest(event("first", "hello", "", 0)).unwrap();
+        assert!(matches!(first, Ingested::Unique(_)));
+
+        match ingestor.ingest(event("second", "hello\r\n", "", 5)).unwrap() {
+            Ingested::Duplicate { original_event_id, decision, .. } => {
+                assert_eq!(original_event_id, "first");
+                assert!(decision.is_none());
+            }
+            other => panic!("expected duplicate, got {:?}", other),
+        }
+
+        ingestor.record_decision(first.event(), &allow());
+        match ingestor.ingest(event("third", "hello", "", 9)).unwrap() {
+            Ingested::Duplicate { decision, .. } => assert_eq!(decision, Some(allow())),
+            other => panic!("expected duplicate, got {:?}", other),
+        }
+
+        // Another actor's copy is a duplicate, but the decision is not theirs
+        for change in [
+            (|e: &mut EthicsEvent| e.actor.actor_type = ActorType::Person) as fn(&mut EthicsEvent),
+            |e| e.actor.trust_level = 0.1,
+            |e| e.actor.tags.push(crate::tags::DECEPTION.to_string()),
+        ] {
+            let mut other = event("other", "hello", "", 9);
+            change(&mut other);
+            match ingestor.ingest(other).unwrap() {
+                Ingested::Duplicate { original_event_id, decision, .. } => {
+                    assert_eq!(original_event_id, "first");
+                    assert!(decision.is_none());
+                }
+                other => panic!("expected duplicate, got {:?}", other),
+            }
+        }
+
+        // Outside the window the content counts as new again
+        assert!(matches!(ingestor.ingest(event("late", "hello", "", 11)).unwrap(), Ingested::Unique(_)));
+        assert_eq!(ingestor.tracked(), 1);
+    }
+}
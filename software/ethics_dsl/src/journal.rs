@@ -0,0 +1,236 @@
+//! Decision Journal - Durable Record of Evaluated Events
+//! "And the books were opened... and the dead were judged out of those things which were written in the books" - Revelation 20:12
+//!
+//! When configured, the engine appends every successful evaluation - the
+//! full event and the decision taken - to a JSON-lines journal. The journal
+//! lets a candidate rule pack be replayed over recent production traffic
+//! and compared with what was actually decided. Entries carry a schema
+//! version (see `schema`), so journals written before a format change stay
+//! readable.
+//!
+//! Instead of a file, the journal can be kept in a store (see
+//! `ark_storage`), one entry per line in the `journal` tree; the lines are
+//! the same either way.
+
+use crate::schema;
+use crate::{EthicsDecision, EthicsError, EthicsEvent, EthicsResult};
+use ark_storage::Storage;
+use chrono::{DateTime, Utc};
+use log::warn;
+use pq_types::decode::DecodeLimits;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Tree of a journal kept in a store
+pub const JOURNAL_TREE: &str = "journal";
+
+/// One journaled evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// When the decision was taken
+    pub recorded_at: DateTime<Utc>,
+    /// Evaluated event
+    pub event: EthicsEvent,
+    /// Decision taken
+    pub decision: EthicsDecision,
+}
+
+/// Append-only journal of evaluated events
+#[derive(Debug)]
+pub struct DecisionJournal {
+    sink: JournalSink,
+}
+
+#[derive(Debug)]
+enum JournalSink {
+    File { path: PathBuf, file: Mutex<File> },
+    Storage(Arc<dyn Storage>),
+}
+
+impl DecisionJournal {
+    /// Open `path` for appending, creating it if missing
+    pub fn open(path: impl Into<PathBuf>) -> EthicsResult<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        Ok(Self {
+            sink: JournalSink::File { path, file: Mutex::new(file) },
+        })
+    }
+
+    /// Journal kept in the `journal` tree of `storage`
+    pub fn with_storage(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            sink: JournalSink::Storage(storage),
+        }
+    }
+
+    /// Journal file, unless the journal is kept in a store
+    pub fn path(&self) -> Option<&Path> {
+        match &self.sink {
+            JournalSink::File { path, .. } => Some(path),
+            JournalSink::Storage(_) => None,
+        }
+    }
+
+    /// Read back the entries recorded at or after `since`
+    pub fn read_since(&self, since: DateTime<Utc>) -> EthicsResult<Vec<JournalEntry>> {
+        match &self.sink {
+            JournalSink::File { path, .. } => read_since(path, since),
+            JournalSink::Storage(storage) => read_since_storage(storage.as_ref(), since),
+        }
+    }
+
+    /// Append one evaluation
+    pub fn record(&self, event: &EthicsEvent, decision: &EthicsDecision) -> EthicsResult<()> {
+        let entry = JournalEntry {
+            recorded_at: Utc::now(),
+            event: event.clone(),
+            decision: decision.clone(),
+        };
+        let mut line = schema::to_string(&entry)?.into_bytes();
+
+        let (path, file) = match &self.sink {
+            JournalSink::File { path, file } => (path, file),
+            JournalSink::Storage(storage) => {
+                return ark_storage::append(storage.as_ref(), JOURNAL_TREE, &line)
+                    .map(|_| ())
+                    .map_err(|e| EthicsError::RuntimeError(format!("Journal store: {}", e)))
+            }
+        };
+        line.push(b'\n');
+        let mut file = match file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // One write per line keeps concurrent appends from interleaving
+        file.write_all(&line)
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Read the entries recorded at or after `since`, skipping lines that fail to parse
+///
+/// A journal holding entries from a newer schema version is refused
+/// rather than read without them.
+pub fn read_since(path: &Path, since: DateTime<Utc>) -> EthicsResult<Vec<JournalEntry>> {
+    let file = File::open(path).map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))?;
+    let lines = BufReader::new(file)
+        .lines()
+        .map(|line| line.map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e))));
+    parse_since(lines, &path.display().to_string(), since)
+}
+
+/// Read the entries of a journal kept in `storage` recorded at or after `since`
+pub fn read_since_storage(storage: &dyn Storage, since: DateTime<Utc>) -> EthicsResult<Vec<JournalEntry>> {
+    let entries = storage
+        .scan(JOURNAL_TREE, &[])
+        .map_err(|e| EthicsError::RuntimeError(format!("Journal store: {}", e)))?;
+    let lines = entries
+        .into_iter()
+        .map(|(_, line)| Ok(String::from_utf8_lossy(&line).into_owned()));
+    parse_since(lines, "journal store", since)
+}
+
+fn parse_since(
+    lines: impl Iterator<Item = EthicsResult<String>>,
+    source: &str,
+    since: DateTime<Utc>,
+) -> EthicsResult<Vec<JournalEntry>> {
+    let mut entries = Vec::new();
+    for (line_number, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match schema::from_slice::<JournalEntry>(line.as_bytes(), &DecodeLimits::RECORD) {
+            Ok(entry) if entry.recorded_at >= since => entries.push(entry),
+            Ok(_) => {}
+            Err(e @ EthicsError::UnsupportedSchemaVersion { .. }) => {
+                return Err(EthicsError::RuntimeError(format!("{} line {}: {}", source, line_number + 1, e)))
+            }
+            Err(e) => warn!("Skipping journal line {} of {}: {}", line_number + 1, source, e),
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+
+    fn event(event_id: &str) -> EthicsEvent {
+        crate::utils::create_event(
+            event_id.to_string(),
+            Actor {
+                actor_type: ActorType::Content,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            None,
+            Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+        )
+    }
+
+    #[test]
+    fn test_read_since_filters_old_and_malformed_entries() {
+        let path = std::env::temp_dir().join(format!("ethics_journal_{}.jsonl", std::process::id()));
+        let allow = EthicsDecision::Allow {
+            confidence: 0.9,
+            justification: String::new(),
+            scripture_refs: vec![],
+        };
+
+        let old = JournalEntry {
+            recorded_at: Utc::now() - chrono::Duration::days(40),
+            event: event("old"),
+            decision: allow.clone(),
+        };
+        std::fs::write(&path, format!("{}\nnot json\n", serde_json::to_string(&old).unwrap())).unwrap();
+
+        let journal = DecisionJournal::open(&path).unwrap();
+        journal.record(&event("recent"), &allow).unwrap();
+
+        let entries = read_since(&path, Utc::now() - chrono::Duration::days(30)).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].event.event_id, "recent");
+        assert_eq!(entries[0].decision, allow);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_storage_journal_reads_back_in_order() {
+        let dir = std::env::temp_dir().join(format!("ethics_journal_store_{}", std::process::id()));
+        let storage: Arc<dyn Storage> = Arc::new(ark_storage::FileStorage::open(&dir).unwrap());
+        let journal = DecisionJournal::with_storage(storage.clone());
+        assert!(journal.path().is_none());
+
+        let deny = EthicsDecision::Deny {
+            confidence: 0.8,
+            violation: "test".to_string(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        journal.record(&event("first"), &deny).unwrap();
+        journal.record(&event("second"), &deny).unwrap();
+
+        let entries = read_since_storage(storage.as_ref(), Utc::now() - chrono::Duration::days(1)).unwrap();
+        let ids: Vec<&str> = entries.iter().map(|entry| entry.event.event_id.as_str()).collect();
+        assert_eq!(ids, ["first", "second"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
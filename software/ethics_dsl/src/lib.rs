@@ -4,27 +4,121 @@
 //! This library implements the core ethics engine for ARK, based on Biblical morality.
 //! It provides a domain-specific language for defining, parsing, and evaluating 
 //! moral principles according to Scripture.
+//!
+//! With only the `core` feature the crate provides the event and decision
+//! types, schema versioning, the decision journal and the evaluator trait,
+//! for the firmware application layer and thin clients that exchange
+//! events without evaluating them. The default `full` feature adds the
+//! engine, parser, ingestion, enrichment and sinks, together with the
+//! tokio, parsing and cryptography stack they need. Rule packs for the
+//! firmware are compiled on the host by `embedded` into static decision
+//! tables. `consensus` decides grave events by quorum of several engines.
+//! Packs ship their own test cases, run with rule coverage by `testing`,
+//! and `diff` shows which corpus decisions a rule edit would change.
+//! `pseudonym` replaces actor identifiers with keyed pseudonyms. `memo`
+//! reuses content analyses across actors sharing identical content.
+//! `locale` renders decision justifications and scripture citations in the
+//! operator's language. `cohort` clusters actors by their refusals to surface
+//! coordinated campaigns.
 
 #![deny(missing_docs)]
 #![warn(clippy::all)]
 
 pub mod ast;
 pub mod biblical;
+pub mod budget;
+#[cfg(feature = "full")]
+pub mod cohort;
+#[cfg(feature = "full")]
+pub mod consensus;
+#[cfg(feature = "full")]
+pub mod diff;
+#[cfg(feature = "full")]
+pub mod embedded;
+#[cfg(feature = "full")]
 pub mod engine;
+#[cfg(feature = "full")]
+pub mod enrichment;
+#[cfg(feature = "full")]
+pub mod envelope;
+#[cfg(feature = "full")]
 pub mod formal;
+#[cfg(feature = "full")]
 pub mod grammar;
+#[cfg(feature = "full")]
+pub mod ingest;
+pub mod journal;
+pub mod locale;
+#[cfg(feature = "full")]
+pub mod memo;
+#[cfg(feature = "full")]
+pub mod multimodal;
+#[cfg(feature = "full")]
 pub mod interpreter;
+#[cfg(feature = "full")]
 pub mod parser;
+#[cfg(feature = "full")]
+pub mod predicates;
+pub mod schema;
+#[cfg(feature = "full")]
+pub mod pseudonym;
+#[cfg(feature = "full")]
 pub mod semantic;
+#[cfg(feature = "full")]
+pub mod signals;
+#[cfg(feature = "full")]
+pub mod sinks;
+#[cfg(feature = "full")]
+pub mod stats;
+#[cfg(feature = "full")]
+pub mod testing;
 pub mod types;
+#[cfg(feature = "full")]
+pub mod validity;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
 pub use ast::*;
+pub use budget::{BudgetReport, Degradation, LatencyBudget, PipelineStage};
+#[cfg(feature = "full")]
+pub use cohort::{ActorLedger, CohortAnalysis, CohortConfig, CohortIndex, CohortMembership, CohortReport, LedgerEntry};
+#[cfg(feature = "full")]
+pub use consensus::{ConsensusEvaluator, ConsensusVoter, EngineVote, Quorum, QuorumPolicy};
+#[cfg(feature = "full")]
+pub use diff::{diff_engines, diff_packs, CategoryCount, ChangeCategory, DecisionChange, PackDiff};
+#[cfg(feature = "full")]
+pub use embedded::{compile, conformance_corpus, verify_equivalence, verify_expectations, CompiledPack, EquivalenceReport, PackDecision, PackRule, RulePack};
+#[cfg(feature = "full")]
 pub use engine::EthicsEngine;
+#[cfg(feature = "full")]
+pub use enrichment::{ContextField, Enrichment, EnrichmentConfig, EnrichmentProvider, EnrichmentRecord, MissingEnrichmentPolicy};
+#[cfg(feature = "full")]
+pub use envelope::{Authentication, EventEnvelope, IdentityConfig, IdentityRegistry, IncomingEvent, SignatureFailure};
+#[cfg(feature = "full")]
+pub use ingest::{ContentIngestor, IngestConfig, Ingested, IngestedDecision};
+pub use journal::{DecisionJournal, JournalEntry};
+pub use locale::{Citation, LocalizationConfig, LocalizedJustification, Localizer, MessageCatalog, ScriptureTranslation, TranslationLicense};
+#[cfg(feature = "full")]
+pub use memo::{MemoConfig, MemoStats};
+#[cfg(feature = "full")]
+pub use multimodal::{ContentSource, DecisionTrace, ModelFinding, MultimodalAnalysis, MultimodalAnalyzer, MultimodalConfig};
+#[cfg(feature = "full")]
+pub use predicates::{parse_call, PredicateArg, PredicateDoc, PredicateRegistry, MAX_EXPRESSION_LENGTH, MAX_PREDICATE_ARGS};
+#[cfg(feature = "full")]
+pub use pseudonym::{PseudonymConfig, Pseudonymizer, ReidentificationRecord, ReidentificationRequest};
+#[cfg(feature = "full")]
+pub use signals::{AgiDetectionConfig, HarmSignalSource, HarmSignals, SignalFactor};
+#[cfg(feature = "full")]
+pub use sinks::{DecisionNotification, DecisionSink, EventBusSink, FileSink, SinkConfig, SinkDispatcher, SinkFilter, SinkMetrics, WebhookSink};
+#[cfg(feature = "full")]
+pub use stats::{AuditLogExporter, EngineStats, KeyedCounts, StatsConfig, StatsExporter};
+#[cfg(feature = "full")]
+pub use testing::{load_tests, run_engine_tests, run_tests, tests_path, CaseResult, PackTest, RuleCoverage, RuleTrace, TestReport};
 pub use types::*;
+#[cfg(feature = "full")]
+pub use validity::{DecisionStatus, IssuedDecision, Revocation, RevocationList, RevocationSource, RevocationTarget, ValidityConfig};
 
 /// Version of the Ethics DSL
 pub const DSL_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -269,6 +363,21 @@ pub enum EthicsError {
     /// Runtime error
     #[error("Runtime error: {0}")]
     RuntimeError(String),
+    
+    /// Content integrity error
+    #[error("Content integrity error: {0}")]
+    IntegrityError(String),
+    
+    /// Serialized record written with a newer schema than this engine reads
+    #[error("Unsupported {record} schema version {found} (this engine reads up to version {supported})")]
+    UnsupportedSchemaVersion {
+        /// Record type
+        record: &'static str,
+        /// Version of the record
+        found: u32,
+        /// Newest version this engine reads
+        supported: u32,
+    },
 }
 
 /// Result type for ethics operations
@@ -290,18 +399,61 @@ pub trait EthicsEvaluator {
 }
 
 /// Configuration for the ethics engine
+#[cfg(feature = "full")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EthicsConfig {
     /// Enable formal verification
     pub formal_verification: bool,
     /// Strictness level (1-10)
     pub strictness_level: u8,
-    /// Language preference
+    /// Language preference; the default locale of justifications
     pub language: String,
     /// Cultural adaptations
     pub cultural_adaptations: Vec<String>,
     /// Performance settings
     pub performance: PerformanceConfig,
+    /// Producer identity verification
+    #[serde(default)]
+    pub identity: envelope::IdentityConfig,
+    /// Sinks notified of decisions
+    #[serde(default)]
+    pub sinks: Vec<sinks::SinkConfig>,
+    /// JSON-lines journal of every evaluated event and its decision
+    #[serde(default)]
+    pub journal: Option<std::path::PathBuf>,
+    /// Store keeping the journal instead of the `journal` file
+    #[serde(default)]
+    pub journal_storage: Option<ark_storage::StorageConfig>,
+    /// Reuse of content analyses across actors sharing identical content
+    #[serde(default)]
+    pub content_memo: memo::MemoConfig,
+    /// Delegation of image, video and audio content to a model
+    #[serde(default)]
+    pub multimodal: multimodal::MultimodalConfig,
+    /// Context enrichment before evaluation
+    #[serde(default)]
+    pub enrichment: enrichment::EnrichmentConfig,
+    /// Lifetimes of issued decisions and the revocation list
+    #[serde(default)]
+    pub validity: validity::ValidityConfig,
+    /// Weights of signatures and model signals in AGI attack detection
+    #[serde(default)]
+    pub agi_detection: signals::AgiDetectionConfig,
+    /// Cardinality limits of the per-rule and per-tag decision counts
+    #[serde(default)]
+    pub stats: stats::StatsConfig,
+    /// Keyed pseudonyms for actor identifiers in decisions and logs
+    #[serde(default)]
+    pub pseudonymization: Option<pseudonym::PseudonymConfig>,
+    /// Message catalogs and scripture translations beyond the built-in ones
+    #[serde(default)]
+    pub localization: locale::LocalizationConfig,
+    /// JSON-lines ledger of the actor of every event whose actor is known
+    #[serde(default)]
+    pub actor_ledger: Option<std::path::PathBuf>,
+    /// Actor cohort clustering and the standing cohort membership costs
+    #[serde(default)]
+    pub cohorts: cohort::CohortConfig,
 }
 
 /// Performance configuration
@@ -317,6 +469,7 @@ pub struct PerformanceConfig {
     pub memory_limit_mb: usize,
 }
 
+#[cfg(feature = "full")]
 impl Default for EthicsConfig {
     fn default() -> Self {
         Self {
@@ -330,6 +483,20 @@ impl Default for EthicsConfig {
                 cache_size: 10000,
                 memory_limit_mb: 512,
             },
+            identity: envelope::IdentityConfig::default(),
+            sinks: Vec::new(),
+            journal: None,
+            journal_storage: None,
+            content_memo: memo::MemoConfig::default(),
+            multimodal: multimodal::MultimodalConfig::default(),
+            enrichment: enrichment::EnrichmentConfig::default(),
+            validity: validity::ValidityConfig::default(),
+            agi_detection: signals::AgiDetectionConfig::default(),
+            stats: stats::StatsConfig::default(),
+            pseudonymization: None,
+            localization: locale::LocalizationConfig::default(),
+            actor_ledger: None,
+            cohorts: cohort::CohortConfig::default(),
         }
     }
 }
@@ -0,0 +1,499 @@
+//! Localization - Justifications in the Operator's Language
+//! "Of all nations, and kindreds, and people, and tongues" - Revelation 7:9
+//!
+//! Decisions carry English justifications, principle keys and scripture
+//! references. `Localizer::justify` renders them for an operator: a summary
+//! from the locale's message catalog, the violated principles by their
+//! localized names, and each cited verse quoted from a translation in the
+//! operator's language. The original English text is kept alongside.
+//!
+//! Locales fall back from the requested one (`pt-BR`) to its language
+//! (`pt`), then to the configured default and finally to English, message
+//! by message, so a partial catalog still renders. Scripture is quoted only
+//! from translations whose license permits quotation; without one in the
+//! operator's language the verse is quoted from the next locale in the
+//! chain and the citation is marked as a fallback. English (KJV) and
+//! Russian (Synodal) public-domain texts of the core passages are built in;
+//! further catalogs and translations are loaded from JSON files named in
+//! `LocalizationConfig`.
+
+use crate::{EthicsDecision, EthicsError, EthicsResult};
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Locale every chain ends with
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Catalog and translation files loaded besides the built-in ones
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalizationConfig {
+    /// Message catalogs; messages override built-in ones of the same locale
+    pub catalogs: Vec<PathBuf>,
+    /// Scripture translations
+    pub translations: Vec<PathBuf>,
+}
+
+/// Message templates of one locale
+///
+/// Templates name their arguments in braces, such as `{confidence}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageCatalog {
+    /// Locale tag, such as `es` or `pt-BR`
+    pub locale: String,
+    /// Templates by message key
+    pub messages: BTreeMap<String, String>,
+}
+
+impl MessageCatalog {
+    /// Load a catalog from a JSON file
+    pub fn load(path: &Path) -> EthicsResult<Self> {
+        load_json(path)
+    }
+
+    fn builtin(locale: &str, messages: &[(&str, &str)]) -> Self {
+        Self {
+            locale: locale.to_string(),
+            messages: messages.iter().map(|(key, text)| (key.to_string(), text.to_string())).collect(),
+        }
+    }
+}
+
+/// Terms under which a translation's text may be shown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranslationLicense {
+    /// Free to quote
+    PublicDomain,
+    /// Held by a publisher
+    Licensed {
+        /// Rights holder
+        holder: String,
+        /// Whether the license covers quoting verses in justifications
+        quotation_permitted: bool,
+    },
+}
+
+impl TranslationLicense {
+    /// Whether verses may be quoted
+    pub fn permits_quotation(&self) -> bool {
+        match self {
+            TranslationLicense::PublicDomain => true,
+            TranslationLicense::Licensed { quotation_permitted, .. } => *quotation_permitted,
+        }
+    }
+}
+
+/// Scripture text in one translation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptureTranslation {
+    /// Short identifier, such as `KJV`
+    pub id: String,
+    /// Full name
+    pub name: String,
+    /// Language tag of the text
+    pub language: String,
+    /// Terms of use
+    pub license: TranslationLicense,
+    /// Verse text by English reference, such as `John 8:44`
+    pub verses: BTreeMap<String, String>,
+}
+
+impl ScriptureTranslation {
+    /// Load a translation from a JSON file
+    pub fn load(path: &Path) -> EthicsResult<Self> {
+        load_json(path)
+    }
+}
+
+/// Scripture reference of a decision, rendered for the operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Reference as the decision gave it
+    pub reference: String,
+    /// Reference with the book named in the operator's language
+    pub display: String,
+    /// Quoted text; `None` when no translation may quote the verse
+    pub text: Option<String>,
+    /// Translation quoted
+    pub translation: Option<String>,
+    /// Whether the text is from a language other than the operator's
+    pub fallback: bool,
+}
+
+/// Decision justification rendered in a locale
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizedJustification {
+    /// Locale whose catalog rendered the summary
+    pub locale: String,
+    /// Decision, confidence or severity, and principles
+    pub summary: String,
+    /// Violated principles by localized name
+    pub principles: Vec<String>,
+    /// Cited scripture
+    pub citations: Vec<Citation>,
+    /// Justification, violation or reason of the decision, in English
+    pub original: String,
+}
+
+/// Renders decisions with message catalogs and scripture translations
+#[derive(Debug, Clone)]
+pub struct Localizer {
+    default_locale: String,
+    catalogs: HashMap<String, MessageCatalog>,
+    translations: Vec<ScriptureTranslation>,
+}
+
+impl Localizer {
+    /// Built-in catalogs and translations, defaulting to `default_locale`
+    pub fn builtin(default_locale: &str) -> Self {
+        let mut localizer = Self { default_locale: normalize(default_locale), catalogs: HashMap::new(), translations: Vec::new() };
+        localizer.add_catalog(MessageCatalog::builtin("en", EN_MESSAGES));
+        localizer.add_catalog(MessageCatalog::builtin("es", ES_MESSAGES));
+        localizer.add_catalog(MessageCatalog::builtin("ru", RU_MESSAGES));
+        localizer.add_translation(builtin_translation("KJV", "King James Version", "en", KJV_VERSES));
+        localizer.add_translation(builtin_translation("SYNODAL", "Синодальный перевод", "ru", SYNODAL_VERSES));
+        localizer
+    }
+
+    /// Built-in data plus the files `config` names
+    pub fn from_config(default_locale: &str, config: &LocalizationConfig) -> EthicsResult<Self> {
+        let mut localizer = Self::builtin(default_locale);
+        for path in &config.catalogs {
+            localizer.add_catalog(MessageCatalog::load(path)?);
+        }
+        for path in &config.translations {
+            localizer.add_translation(ScriptureTranslation::load(path)?);
+        }
+        Ok(localizer)
+    }
+
+    /// Add `catalog`, its messages overriding those already known for the locale
+    pub fn add_catalog(&mut self, catalog: MessageCatalog) {
+        let locale = normalize(&catalog.locale);
+        let entry = self
+            .catalogs
+            .entry(locale.clone())
+            .or_insert_with(|| MessageCatalog { locale, messages: BTreeMap::new() });
+        entry.messages.extend(catalog.messages);
+    }
+
+    /// Add `translation`; earlier translations of a language are preferred
+    pub fn add_translation(&mut self, translation: ScriptureTranslation) {
+        self.translations.push(ScriptureTranslation { language: normalize(&translation.language), ..translation });
+    }
+
+    /// Locale that renders for `requested`: the first of its chain with a catalog
+    pub fn resolve(&self, requested: Option<&str>) -> String {
+        self.chain(requested)
+            .into_iter()
+            .find(|locale| self.catalogs.contains_key(locale))
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+    }
+
+    /// Message `key` for `requested` with `args` filled in; the key itself
+    /// when no locale of the chain has it
+    pub fn message(&self, requested: Option<&str>, key: &str, args: &[(&str, String)]) -> String {
+        let template = self
+            .chain(requested)
+            .iter()
+            .find_map(|locale| self.catalogs.get(locale)?.messages.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string());
+        args.iter().fold(template, |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+    }
+
+    /// `reference` rendered for `requested`, quoted where a translation may quote it
+    pub fn cite(&self, reference: &str, requested: Option<&str>) -> Citation {
+        let Some((book, verse)) = parse_reference(reference) else {
+            return Citation { reference: reference.to_string(), display: reference.to_string(), text: None, translation: None, fallback: false };
+        };
+        let display = format!("{} {}", self.message(requested, &format!("book.{}", book), &[]), verse);
+        let canonical = format!("{} {}", book, verse);
+
+        let chain = self.chain(requested);
+        let wanted = language(&self.resolve(requested)).to_string();
+        let quoted = chain.iter().map(|locale| language(locale)).find_map(|language| {
+            self.translations.iter().find(|translation| {
+                translation.language == language
+                    && translation.license.permits_quotation()
+                    && translation.verses.contains_key(&canonical)
+            })
+        });
+        match quoted {
+            Some(translation) => Citation {
+                reference: reference.to_string(),
+                display,
+                text: translation.verses.get(&canonical).cloned(),
+                translation: Some(translation.id.clone()),
+                fallback: translation.language != wanted,
+            },
+            None => Citation { reference: reference.to_string(), display, text: None, translation: None, fallback: false },
+        }
+    }
+
+    /// `decision` rendered for `requested`
+    pub fn justify(&self, decision: &EthicsDecision, requested: Option<&str>) -> LocalizedJustification {
+        let (key, mut args, principles, references, original) = match decision {
+            EthicsDecision::Allow { confidence, justification, scripture_refs } => {
+                ("decision.allow", vec![("confidence", percent(*confidence))], &[][..], scripture_refs, justification)
+            }
+            EthicsDecision::Deny { confidence, violation, violated_principles, scripture_refs } => {
+                ("decision.deny", vec![("confidence", percent(*confidence))], &violated_principles[..], scripture_refs, violation)
+            }
+            EthicsDecision::Purge { severity, reason, violated_principles, scripture_refs } => {
+                ("decision.purge", vec![("severity", severity.to_string())], &violated_principles[..], scripture_refs, reason)
+            }
+        };
+        let principles: Vec<String> = principles
+            .iter()
+            .map(|principle| {
+                let name = self.message(requested, &format!("principle.{}", principle), &[]);
+                if name.starts_with("principle.") { principle.clone() } else { name }
+            })
+            .collect();
+        args.push((
+            "principles",
+            if principles.is_empty() {
+                self.message(requested, "principles.none", &[])
+            } else {
+                principles.join(&self.message(requested, "list.separator", &[]))
+            },
+        ));
+
+        LocalizedJustification {
+            locale: self.resolve(requested),
+            summary: self.message(requested, key, &args),
+            principles,
+            citations: references.iter().map(|reference| self.cite(reference, requested)).collect(),
+            original: original.clone(),
+        }
+    }
+
+    /// Locales tried for `requested`, most specific first, without repeats
+    fn chain(&self, requested: Option<&str>) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        for locale in requested.map(normalize).into_iter().chain([self.default_locale.clone(), DEFAULT_LOCALE.to_string()]) {
+            for candidate in [locale.clone(), language(&locale).to_string()] {
+                if !candidate.is_empty() && !chain.contains(&candidate) {
+                    chain.push(candidate);
+                }
+            }
+        }
+        chain
+    }
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Self::builtin(DEFAULT_LOCALE)
+    }
+}
+
+/// Lowercase tag with `-` separators: `pt_BR` becomes `pt-br`
+fn normalize(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+/// Language of a normalized tag
+fn language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+fn percent(confidence: f64) -> String {
+    format!("{:.0}", (confidence.clamp(0.0, 1.0) * 100.0).round())
+}
+
+/// Book and `chapter:verse` of the first part of `text` shaped like a reference
+///
+/// Decisions cite as `John 8:44`, `Genesis 1:27 - Created in God's image`
+/// or `Be alert and of sober mind - 1 Peter 5:8`; of several references
+/// (`Genesis 1:27, Matthew 19:4-6`) the first is taken.
+fn parse_reference(text: &str) -> Option<(&str, &str)> {
+    text.split(" - ").flat_map(|part| part.split(", ")).map(str::trim).find_map(|part| {
+        let (book, verse) = part.rsplit_once(' ')?;
+        let shaped = verse.contains(':') && verse.chars().all(|c| c.is_ascii_digit() || matches!(c, ':' | '-' | ','));
+        (shaped && !book.is_empty()).then_some((book, verse))
+    })
+}
+
+fn builtin_translation(id: &str, name: &str, language: &str, verses: &[(&str, &str)]) -> ScriptureTranslation {
+    ScriptureTranslation {
+        id: id.to_string(),
+        name: name.to_string(),
+        language: language.to_string(),
+        license: TranslationLicense::PublicDomain,
+        verses: verses.iter().map(|(reference, text)| (reference.to_string(), text.to_string())).collect(),
+    }
+}
+
+fn load_json<T: serde::de::DeserializeOwned>(path: &Path) -> EthicsResult<T> {
+    let bytes = std::fs::read(path).map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+    decode::json(&bytes, &DecodeLimits::FILE).map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))
+}
+
+const EN_MESSAGES: &[(&str, &str)] = &[
+    ("decision.allow", "Allowed with {confidence}% confidence."),
+    ("decision.deny", "Denied with {confidence}% confidence: {principles}."),
+    ("decision.purge", "Purged at severity {severity}: {principles}."),
+    ("principles.none", "no named principle"),
+    ("list.separator", ", "),
+    ("principle.SANCTITY_OF_LIFE", "sanctity of life"),
+    ("principle.TRUTH_OVER_LIES", "truth over lies"),
+    ("principle.PROTECTING_CHILDREN", "protecting children"),
+    ("principle.REJECTING_IDOLATRY", "rejecting idolatry"),
+    ("principle.SEXUAL_PURITY", "sexual purity"),
+    ("principle.RIGHTEOUSNESS", "righteousness"),
+    ("principle.LOVE_OF_NEIGHBOR", "love of neighbor"),
+    ("principle.WISDOM_SEEKING", "seeking wisdom"),
+    ("book.Genesis", "Genesis"),
+    ("book.Exodus", "Exodus"),
+    ("book.Proverbs", "Proverbs"),
+    ("book.Matthew", "Matthew"),
+    ("book.Mark", "Mark"),
+    ("book.John", "John"),
+    ("book.1 Thessalonians", "1 Thessalonians"),
+    ("book.1 Peter", "1 Peter"),
+    ("book.1 John", "1 John"),
+];
+
+const ES_MESSAGES: &[(&str, &str)] = &[
+    ("decision.allow", "Permitido con un {confidence}% de confianza."),
+    ("decision.deny", "Denegado con un {confidence}% de confianza: {principles}."),
+    ("decision.purge", "Purgado con severidad {severity}: {principles}."),
+    ("principles.none", "ningún principio nombrado"),
+    ("principle.SANCTITY_OF_LIFE", "santidad de la vida"),
+    ("principle.TRUTH_OVER_LIES", "la verdad sobre la mentira"),
+    ("principle.PROTECTING_CHILDREN", "protección de los niños"),
+    ("principle.REJECTING_IDOLATRY", "rechazo de la idolatría"),
+    ("principle.SEXUAL_PURITY", "pureza sexual"),
+    ("principle.RIGHTEOUSNESS", "justicia"),
+    ("principle.LOVE_OF_NEIGHBOR", "amor al prójimo"),
+    ("principle.WISDOM_SEEKING", "búsqueda de la sabiduría"),
+    ("book.Genesis", "Génesis"),
+    ("book.Exodus", "Éxodo"),
+    ("book.Proverbs", "Proverbios"),
+    ("book.Matthew", "Mateo"),
+    ("book.Mark", "Marcos"),
+    ("book.John", "Juan"),
+    ("book.1 Thessalonians", "1 Tesalonicenses"),
+    ("book.1 Peter", "1 Pedro"),
+    ("book.1 John", "1 Juan"),
+];
+
+const RU_MESSAGES: &[(&str, &str)] = &[
+    ("decision.allow", "Разрешено с уверенностью {confidence}%."),
+    ("decision.deny", "Отклонено с уверенностью {confidence}%: {principles}."),
+    ("decision.purge", "Удалено со степенью тяжести {severity}: {principles}."),
+    ("principles.none", "без указанного принципа"),
+    ("principle.SANCTITY_OF_LIFE", "святость жизни"),
+    ("principle.TRUTH_OVER_LIES", "истина выше лжи"),
+    ("principle.PROTECTING_CHILDREN", "защита детей"),
+    ("principle.REJECTING_IDOLATRY", "отвержение идолопоклонства"),
+    ("principle.SEXUAL_PURITY", "половая чистота"),
+    ("principle.RIGHTEOUSNESS", "праведность"),
+    ("principle.LOVE_OF_NEIGHBOR", "любовь к ближнему"),
+    ("principle.WISDOM_SEEKING", "стремление к мудрости"),
+    ("book.Genesis", "Бытие"),
+    ("book.Exodus", "Исход"),
+    ("book.Proverbs", "Притчи"),
+    ("book.Matthew", "Матфея"),
+    ("book.Mark", "Марка"),
+    ("book.John", "Иоанна"),
+    ("book.1 Thessalonians", "1 Фессалоникийцам"),
+    ("book.1 Peter", "1 Петра"),
+    ("book.1 John", "1 Иоанна"),
+];
+
+const KJV_VERSES: &[(&str, &str)] = &[
+    ("Genesis 1:27", "So God created man in his own image, in the image of God created he him; male and female created he them."),
+    ("Exodus 20:3", "Thou shalt have no other gods before me."),
+    ("Proverbs 1:7", "The fear of the LORD is the beginning of knowledge: but fools despise wisdom and instruction."),
+    ("Proverbs 14:15", "The simple believeth every word: but the prudent man looketh well to his going."),
+    ("Proverbs 21:3", "To do justice and judgment is more acceptable to the LORD than sacrifice."),
+    ("Matthew 18:6", "But whoso shall offend one of these little ones which believe in me, it were better for him that a millstone were hanged about his neck, and that he were drowned in the depth of the sea."),
+    ("Mark 12:31", "And the second is like, namely this, Thou shalt love thy neighbour as thyself. There is none other commandment greater than these."),
+    ("John 8:44", "Ye are of your father the devil, and the lusts of your father ye will do. He was a murderer from the beginning, and abode not in the truth, because there is no truth in him. When he speaketh a lie, he speaketh of his own: for he is a liar, and the father of it."),
+    ("1 Thessalonians 5:21", "Prove all things; hold fast that which is good."),
+    ("1 Thessalonians 5:22", "Abstain from all appearance of evil."),
+    ("1 Peter 5:8", "Be sober, be vigilant; because your adversary the devil, as a roaring lion, walketh about, seeking whom he may devour:"),
+    ("1 John 4:1", "Beloved, believe not every spirit, but try the spirits whether they are of God: because many false prophets are gone out into the world."),
+];
+
+const SYNODAL_VERSES: &[(&str, &str)] = &[
+    ("Genesis 1:27", "И сотворил Бог человека по образу Своему, по образу Божию сотворил его; мужчину и женщину сотворил их."),
+    ("Exodus 20:3", "да не будет у тебя других богов пред лицем Моим."),
+    ("Mark 12:31", "Вторая подобная ей: возлюби ближнего твоего, как самого себя. Иной большей сих заповеди нет."),
+    ("1 Thessalonians 5:21", "Все испытывайте, хорошего держитесь."),
+    ("1 Thessalonians 5:22", "Удерживайтесь от всякого рода зла."),
+    ("1 John 4:1", "Возлюбленные! не всякому духу верьте, но испытывайте духов, от Бога ли они, потому что много лжепророков появилось в мире."),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deny() -> EthicsDecision {
+        EthicsDecision::Deny {
+            confidence: 0.834,
+            violation: "Deceptive content".to_string(),
+            violated_principles: vec!["TRUTH_OVER_LIES".to_string(), "UNLISTED".to_string()],
+            scripture_refs: vec![
+                "Exodus 20:3".to_string(),
+                "John 8:44 - Satan is the father of lies".to_string(),
+                "Sirach 1:1".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_justification_follows_locale_chain() {
+        let localizer = Localizer::builtin("es");
+
+        let russian = localizer.justify(&deny(), Some("ru_RU"));
+        assert_eq!(russian.locale, "ru");
+        assert_eq!(russian.summary, "Отклонено с уверенностью 83%: истина выше лжи, UNLISTED.");
+        assert_eq!(russian.original, "Deceptive content");
+        assert_eq!(russian.citations[0].display, "Исход 20:3");
+        assert_eq!(russian.citations[0].translation.as_deref(), Some("SYNODAL"));
+        assert!(!russian.citations[0].fallback);
+        // No Synodal text of John 8:44 is built in: quoted from the KJV
+        assert_eq!(russian.citations[1].display, "Иоанна 8:44");
+        assert_eq!(russian.citations[1].translation.as_deref(), Some("KJV"));
+        assert!(russian.citations[1].fallback);
+        assert_eq!(russian.citations[2].text, None);
+
+        // Unknown locales fall back to the configured default, then English
+        let french = localizer.justify(&deny(), Some("fr-CA"));
+        assert_eq!(french.locale, "es");
+        assert!(french.summary.starts_with("Denegado con un 83% de confianza"));
+        assert_eq!(Localizer::builtin("de").justify(&deny(), None).locale, "en");
+        assert_eq!(localizer.message(Some("es"), "list.separator", &[]), ", ");
+    }
+
+    #[test]
+    fn test_loaded_catalogs_and_licensed_translations() {
+        let dir = std::env::temp_dir().join(format!("ethics_locale_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let catalog = dir.join("pt.json");
+        std::fs::write(&catalog, r#"{"locale": "pt", "messages": {"decision.deny": "Negado: {principles}.", "principle.TRUTH_OVER_LIES": "verdade acima da mentira"}}"#).unwrap();
+        let licensed = dir.join("nvi.json");
+        std::fs::write(&licensed, r#"{"id": "NVI", "name": "Nova Versão Internacional", "language": "pt", "license": {"kind": "licensed", "holder": "Biblica", "quotation_permitted": false}, "verses": {"Exodus 20:3": "Não terás outros deuses além de mim."}}"#).unwrap();
+
+        let config = LocalizationConfig { catalogs: vec![catalog], translations: vec![licensed] };
+        let localizer = Localizer::from_config("en", &config).unwrap();
+        let portuguese = localizer.justify(&deny(), Some("pt-BR"));
+        assert_eq!(portuguese.locale, "pt");
+        assert_eq!(portuguese.summary, "Negado: verdade acima da mentira, UNLISTED.");
+        // The license does not permit quotation, so the verse comes from the KJV
+        assert_eq!(portuguese.citations[0].translation.as_deref(), Some("KJV"));
+        assert!(portuguese.citations[0].fallback);
+        assert_eq!(portuguese.citations[0].display, "Exodus 20:3");
+
+        let missing = LocalizationConfig { catalogs: vec![dir.join("absent.json")], translations: Vec::new() };
+        assert!(Localizer::from_config("en", &missing).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
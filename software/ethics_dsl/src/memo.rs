@@ -0,0 +1,193 @@
+//! Content Decision Memo - Reuse Across Actors
+//! "The thing that hath been, it is that which shall be" - Ecclesiastes 1:9
+//!
+//! Identical content reaches the engine once for every actor who shares it,
+//! yet its content and context analyses do not depend on who shares it. The
+//! engine keeps those analyses in a memo keyed by the content and the
+//! event context; metadata is left out of the key, as in `ingest`, since it
+//! carries per-delivery details. When the memoized analyses settle the
+//! outcome for an actor of any standing, the decision itself is kept and
+//! reused as is; otherwise only the actor analysis is recomputed and
+//! combined with them. Attack detection still runs for every event.
+//!
+//! The memo is cleared whenever rules, predicates or the multimodal
+//! analyzer change, and holds at most `capacity` entries, oldest evicted
+//! first.
+
+use crate::{Content, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Key derivation context for memo keys
+const MEMO_KEY_CONTEXT: &str = "ARK ethics_dsl content memo v1";
+
+/// Content memo configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoConfig {
+    /// Reuse analyses of identical content across actors
+    pub enabled: bool,
+    /// Most memoized contents
+    pub capacity: usize,
+}
+
+impl Default for MemoConfig {
+    fn default() -> Self {
+        Self { enabled: true, capacity: 10_000 }
+    }
+}
+
+/// Counters of memo lookups since the memo was created or cleared
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoStats {
+    /// Lookups answered with a decision settled by the content alone
+    pub settled_hits: u64,
+    /// Lookups that reused the content analyses and recomputed the actor's part
+    pub adjusted_hits: u64,
+    /// Lookups of content not in the memo
+    pub misses: u64,
+    /// Contents currently memoized
+    pub entries: usize,
+}
+
+/// Outcome of a memo lookup, for the counters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MemoLookup {
+    Settled,
+    Adjusted,
+    Miss,
+}
+
+/// Bounded memo of per-content values
+#[derive(Debug)]
+pub(crate) struct ContentMemo<V> {
+    capacity: usize,
+    entries: HashMap<String, V>,
+    /// Keys in insertion order, for eviction
+    order: VecDeque<String>,
+    settled_hits: AtomicU64,
+    adjusted_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V: Clone> ContentMemo<V> {
+    pub(crate) fn new(config: &MemoConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            settled_hits: AtomicU64::new(0),
+            adjusted_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<V> {
+        self.entries.get(key).cloned()
+    }
+
+    pub(crate) fn insert(&mut self, key: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), value).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Count a lookup; callable under a read lock
+    pub(crate) fn record(&self, lookup: MemoLookup) {
+        let counter = match lookup {
+            MemoLookup::Settled => &self.settled_hits,
+            MemoLookup::Adjusted => &self.adjusted_hits,
+            MemoLookup::Miss => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        for counter in [&self.settled_hits, &self.adjusted_hits, &self.misses] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> MemoStats {
+        MemoStats {
+            settled_hits: self.settled_hits.load(Ordering::Relaxed),
+            adjusted_hits: self.adjusted_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.len(),
+        }
+    }
+}
+
+/// Memo key of `content` evaluated in `context`
+///
+/// Covers the content type and data exactly as received, so content that
+/// only normalizes to the same canonical hash is analyzed on its own.
+pub fn memo_key(content: &Content, context: &Context) -> String {
+    let content_type = format!("{:?}", content.content_type);
+    let context = serde_json::to_vec(context).unwrap_or_default();
+
+    let mut hasher = blake3::Hasher::new_derive_key(MEMO_KEY_CONTEXT);
+    for part in [content_type.as_bytes(), content.data.as_bytes(), &context] {
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContentType, UrgencyLevel};
+
+    fn content(data: &str, source: &str) -> Content {
+        Content {
+            content_type: ContentType::Text,
+            data: data.to_string(),
+            metadata: HashMap::from([("source".to_string(), source.to_string())]),
+            content_hash: String::new(),
+        }
+    }
+
+    fn context(urgency: UrgencyLevel) -> Context {
+        Context { location: None, culture: None, platform: None, audience: None, urgency }
+    }
+
+    #[test]
+    fn test_key_ignores_metadata_but_not_data_or_context() {
+        let key = memo_key(&content("shared post", "feed-a"), &context(UrgencyLevel::Normal));
+        assert_eq!(key, memo_key(&content("shared post", "feed-b"), &context(UrgencyLevel::Normal)));
+        assert_ne!(key, memo_key(&content("shared post ", "feed-a"), &context(UrgencyLevel::Normal)));
+        assert_ne!(key, memo_key(&content("shared post", "feed-a"), &context(UrgencyLevel::Critical)));
+    }
+
+    #[test]
+    fn test_oldest_entries_are_evicted_and_clear_resets_counters() {
+        let mut memo = ContentMemo::new(&MemoConfig { enabled: true, capacity: 2 });
+        memo.insert("a".to_string(), 1);
+        memo.insert("b".to_string(), 2);
+        memo.insert("a".to_string(), 3);
+        memo.insert("c".to_string(), 4);
+        assert_eq!(memo.get("a"), None);
+        assert_eq!((memo.get("b"), memo.get("c")), (Some(2), Some(4)));
+
+        memo.record(MemoLookup::Settled);
+        memo.record(MemoLookup::Miss);
+        assert_eq!(memo.stats(), MemoStats { settled_hits: 1, adjusted_hits: 0, misses: 1, entries: 2 });
+        memo.clear();
+        assert_eq!(memo.stats(), MemoStats::default());
+    }
+}
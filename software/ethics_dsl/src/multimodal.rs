@@ -0,0 +1,166 @@
+//! Multimodal Delegation - Model Analysis of Image, Video and Audio
+//! "For now we see through a glass, darkly; but then face to face" - 1 Corinthians 13:12
+//!
+//! The text rules cannot see into images, video or audio. When a
+//! `MultimodalAnalyzer` is installed, the engine hands such content to it
+//! instead; the harm categories the model reports are translated into
+//! synthetic tags and evaluated like actor tags. A decision that relied on
+//! model output records the model and tags in its `DecisionTrace`, so it
+//! can be told apart from one reached by the rules alone. Media the
+//! installed analyzer fails on is denied, never read as text instead.
+
+use crate::consensus::EngineVote;
+use crate::enrichment::EnrichmentRecord;
+use crate::{Content, ContentType, EthicsResult};
+use serde::{Deserialize, Serialize};
+
+/// Multimodal delegation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MultimodalConfig {
+    /// Lowest model score (0.0 to 1.0) at which a finding becomes a tag
+    pub min_score: f64,
+}
+
+impl Default for MultimodalConfig {
+    fn default() -> Self {
+        Self { min_score: 0.5 }
+    }
+}
+
+/// One harm category reported by a model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelFinding {
+    /// Model's category id
+    pub category: String,
+    /// Moral tag the category maps to (see `tags`)
+    pub tag: String,
+    /// Score (0.0 to 1.0)
+    pub score: f64,
+}
+
+/// Model output for one piece of content
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultimodalAnalysis {
+    /// Model name and version
+    pub model: String,
+    /// Categories the model reported
+    pub findings: Vec<ModelFinding>,
+}
+
+impl MultimodalAnalysis {
+    /// Tags of findings scored at or above `min_score`, sorted and deduplicated
+    pub fn synthetic_tags(&self, min_score: f64) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .findings
+            .iter()
+            .filter(|finding| finding.score >= min_score)
+            .map(|finding| finding.tag.clone())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// Highest score among findings at or above `min_score`
+    pub fn max_score(&self, min_score: f64) -> f64 {
+        self.findings
+            .iter()
+            .map(|finding| finding.score)
+            .filter(|score| *score >= min_score)
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Model that scores non-text content
+pub trait MultimodalAnalyzer: Send + Sync {
+    /// Analyzer name for logs
+    fn name(&self) -> &str;
+
+    /// Harm categories found in `content`
+    fn analyze(&self, content: &Content) -> EthicsResult<MultimodalAnalysis>;
+}
+
+/// Whether content of this type is delegated rather than read as text
+pub fn is_media(content_type: &ContentType) -> bool {
+    matches!(content_type, ContentType::Image | ContentType::Video | ContentType::Audio)
+}
+
+/// How an event's content contributed to its decision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum ContentSource {
+    /// No content, or the decision was taken before content was analyzed
+    None,
+    /// Text rules over the content data
+    Rules,
+    /// Model output merged into the rules as synthetic tags
+    Model {
+        /// Model name and version
+        model: String,
+        /// Synthetic tags taken from the model's findings
+        tags: Vec<String>,
+    },
+    /// Decision reused from the cache
+    Cached,
+    /// The analyzer failed and the content was denied unseen
+    ModelFailed {
+        /// Analyzer name
+        analyzer: String,
+        /// Why analysis failed
+        error: String,
+    },
+}
+
+/// Record of how a decision was reached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionTrace {
+    /// Evaluated event
+    pub event_id: String,
+    /// How the content was analyzed
+    pub content: ContentSource,
+    /// Context lookups made before evaluation
+    #[serde(default)]
+    pub enrichment: Vec<EnrichmentRecord>,
+    /// Votes of the engines behind a consensus decision
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consensus: Vec<EngineVote>,
+}
+
+impl DecisionTrace {
+    /// Whether the decision relied on model output
+    pub fn model_assisted(&self) -> bool {
+        matches!(self.content, ContentSource::Model { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(category: &str, tag: &str, score: f64) -> ModelFinding {
+        ModelFinding {
+            category: category.to_string(),
+            tag: tag.to_string(),
+            score,
+        }
+    }
+
+    #[test]
+    fn test_synthetic_tags_apply_threshold_and_dedup() {
+        let analysis = MultimodalAnalysis {
+            model: "vision-1".to_string(),
+            findings: vec![
+                finding("PhysicalHarm", crate::tags::VIOLENCE_INNOCENT, 0.9),
+                finding("SocialHarm", crate::tags::DECEPTION, 0.2),
+                finding("Gore", crate::tags::VIOLENCE_INNOCENT, 0.6),
+            ],
+        };
+
+        assert_eq!(analysis.synthetic_tags(0.5), vec![crate::tags::VIOLENCE_INNOCENT]);
+        assert_eq!(analysis.max_score(0.5), 0.9);
+        assert_eq!(analysis.max_score(0.95), 0.0);
+        assert!(is_media(&ContentType::Video));
+        assert!(!is_media(&ContentType::Educational));
+    }
+}
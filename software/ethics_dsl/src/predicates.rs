@@ -0,0 +1,602 @@
+//! Predicate Library - Reusable Checks for Rule Authors
+//! "Prove all things; hold fast that which is good" - 1 Thessalonians 5:21
+//!
+//! Common rule conditions ("audience contains children", "actor trust below
+//! X") are provided once as named predicates instead of being rewritten in
+//! every rule pack. Rules call them as `name(arg, ...)`, for example
+//! `actor.trust_below(0.3)` or `time.between("22:00", "06:00")`. Arguments
+//! are numbers, quoted strings or bare words; bare words are treated as
+//! strings. Names and tags are compared case-insensitively.
+//!
+//! | Predicate | Holds when |
+//! |-----------|------------|
+//! | `audience.contains(group)` | the audience includes the age group |
+//! | `audience.contains_children()` | the audience includes children |
+//! | `audience.contains_minors()` | the audience includes children or teenagers |
+//! | `audience.has_vulnerable([group])` | any, or the named, vulnerable group is present |
+//! | `audience.size_at_least(n)` | the audience size is known and at least `n` |
+//! | `actor.trust_below(x)` | actor trust is below `x` |
+//! | `actor.trust_at_least(x)` | actor trust is at least `x` |
+//! | `actor.is(type, ...)` | the actor is one of the types |
+//! | `actor.violations_at_least(n)` | the actor has at least `n` recorded violations |
+//! | `tag.has(tag)` | the actor or content carries the tag |
+//! | `tag.any(tag, ...)` | the actor or content carries any of the tags |
+//! | `tag.any_violation()` | any tag is a moral violation tag |
+//! | `content.present()` | the event carries content |
+//! | `content.is(type, ...)` | the content is one of the types |
+//! | `content.metadata_equals(key, value)` | a content metadata field equals `value` |
+//! | `time.between(start, end)` | the event time (UTC, `HH:MM`) is in `[start, end)`; may wrap midnight |
+//! | `time.weekday(day, ...)` | the event falls on one of the days (`Mon` .. `Sun`) |
+//! | `location.in(code, ...)` | the event location is one of the ISO 3166 codes |
+//! | `location.unknown()` | the event has no location |
+//!
+//! Host code can add domain predicates with `PredicateRegistry::register`.
+//!
+//! Call expressions come from rule packs and are untrusted: their length
+//! and argument count are bounded, and `parse_call` is fuzzed by the
+//! `predicate_call` target under `fuzz/`.
+
+use crate::{tags, EthicsError, EthicsEvent, EthicsResult};
+use chrono::{Datelike, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Argument passed to a predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PredicateArg {
+    /// Numeric literal
+    Number(f64),
+    /// String literal or bare word
+    Text(String),
+}
+
+impl fmt::Display for PredicateArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateArg::Number(n) => write!(f, "{}", n),
+            PredicateArg::Text(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// Longest accepted call expression, in bytes
+pub const MAX_EXPRESSION_LENGTH: usize = 4096;
+
+/// Most arguments accepted in one call
+pub const MAX_PREDICATE_ARGS: usize = 64;
+
+/// Signature of a predicate implementation
+pub type PredicateFn = dyn Fn(&EthicsEvent, &[PredicateArg]) -> EthicsResult<bool> + Send + Sync;
+
+/// Documentation of a registered predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredicateDoc {
+    /// Name used in rules
+    pub name: String,
+    /// Minimum number of arguments
+    pub min_args: usize,
+    /// Maximum number of arguments, `None` for variadic
+    pub max_args: Option<usize>,
+    /// What the predicate checks
+    pub description: String,
+}
+
+#[derive(Clone)]
+struct RegisteredPredicate {
+    doc: PredicateDoc,
+    function: Arc<PredicateFn>,
+}
+
+/// Named predicates callable from rules
+#[derive(Clone, Default)]
+pub struct PredicateRegistry {
+    predicates: BTreeMap<String, RegisteredPredicate>,
+}
+
+impl fmt::Debug for PredicateRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.predicates.keys()).finish()
+    }
+}
+
+impl PredicateRegistry {
+    /// Registry with the standard library predicates
+    pub fn standard() -> Self {
+        let mut registry = Self::default();
+        for (name, min_args, max_args, description, function) in standard_predicates() {
+            registry
+                .insert(name, min_args, max_args, description, function)
+                .expect("standard predicate names are unique and valid");
+        }
+        registry
+    }
+
+    /// Register a custom predicate
+    ///
+    /// Names are dotted identifiers (`domain.check`) and must not shadow an
+    /// existing predicate.
+    pub fn register<F>(
+        &mut self,
+        name: &str,
+        min_args: usize,
+        max_args: Option<usize>,
+        description: &str,
+        function: F,
+    ) -> EthicsResult<()>
+    where
+        F: Fn(&EthicsEvent, &[PredicateArg]) -> EthicsResult<bool> + Send + Sync + 'static,
+    {
+        self.insert(name, min_args, max_args, description, Arc::new(function))
+    }
+
+    fn insert(
+        &mut self,
+        name: &str,
+        min_args: usize,
+        max_args: Option<usize>,
+        description: &str,
+        function: Arc<PredicateFn>,
+    ) -> EthicsResult<()> {
+        let valid = !name.is_empty()
+            && name.split('.').all(|part| {
+                part.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            });
+        if !valid {
+            return Err(EthicsError::ConfigurationError(format!("Invalid predicate name {:?}", name)));
+        }
+        if max_args.is_some_and(|max| max < min_args) {
+            return Err(EthicsError::ConfigurationError(
+                format!("Predicate {} accepts at most {:?} of at least {} arguments", name, max_args, min_args),
+            ));
+        }
+        if self.predicates.contains_key(name) {
+            return Err(EthicsError::ConfigurationError(format!("Predicate {} is already registered", name)));
+        }
+
+        self.predicates.insert(name.to_string(), RegisteredPredicate {
+            doc: PredicateDoc {
+                name: name.to_string(),
+                min_args,
+                max_args,
+                description: description.to_string(),
+            },
+            function,
+        });
+        Ok(())
+    }
+
+    /// Whether a predicate is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.predicates.contains_key(name)
+    }
+
+    /// Documentation of every registered predicate, sorted by name
+    pub fn docs(&self) -> Vec<PredicateDoc> {
+        self.predicates.values().map(|p| p.doc.clone()).collect()
+    }
+
+    /// Call a predicate with already parsed arguments
+    pub fn call(&self, name: &str, args: &[PredicateArg], event: &EthicsEvent) -> EthicsResult<bool> {
+        let predicate = self.predicates
+            .get(name)
+            .ok_or_else(|| EthicsError::EvaluationError(format!("Unknown predicate {}", name)))?;
+
+        let doc = &predicate.doc;
+        if args.len() < doc.min_args || doc.max_args.is_some_and(|max| args.len() > max) {
+            return Err(EthicsError::EvaluationError(format!(
+                "Predicate {} takes {} argument(s), got {}",
+                name,
+                match doc.max_args {
+                    Some(max) if max == doc.min_args => max.to_string(),
+                    Some(max) => format!("{} to {}", doc.min_args, max),
+                    None => format!("at least {}", doc.min_args),
+                },
+                args.len()
+            )));
+        }
+
+        (predicate.function)(event, args)
+    }
+
+    /// Parse and evaluate a call expression such as `actor.trust_below(0.5)`
+    pub fn evaluate(&self, expression: &str, event: &EthicsEvent) -> EthicsResult<bool> {
+        let (name, args) = parse_call(expression)?;
+        self.call(&name, &args, event)
+    }
+}
+
+/// Split `name(arg, ...)` into the predicate name and its arguments
+pub fn parse_call(expression: &str) -> EthicsResult<(String, Vec<PredicateArg>)> {
+    let parse_error = |reason: &str| EthicsError::ParseError(format!("{}: {:?}", reason, expression));
+
+    if expression.len() > MAX_EXPRESSION_LENGTH {
+        return Err(EthicsError::ParseError(format!(
+            "Predicate call of {} bytes exceeds {} bytes",
+            expression.len(),
+            MAX_EXPRESSION_LENGTH
+        )));
+    }
+    let expression = expression.trim();
+    let open = expression.find('(').ok_or_else(|| parse_error("Expected '(' in predicate call"))?;
+    let body = expression[open + 1..]
+        .strip_suffix(')')
+        .ok_or_else(|| parse_error("Expected ')' at end of predicate call"))?;
+    let name = expression[..open].trim().to_string();
+
+    let mut args = Vec::new();
+    let mut chars = body.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        let Some(&first) = chars.peek() else { break };
+
+        let token = if first == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.push(chars.next().ok_or_else(|| parse_error("Unterminated escape"))?),
+                    Some(c) => text.push(c),
+                    None => return Err(parse_error("Unterminated string")),
+                }
+            }
+            PredicateArg::Text(text)
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c == ',' || c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            match word.parse::<f64>() {
+                Ok(number) => PredicateArg::Number(number),
+                Err(_) => PredicateArg::Text(word),
+            }
+        };
+        if args.len() == MAX_PREDICATE_ARGS {
+            return Err(parse_error(&format!("More than {} arguments", MAX_PREDICATE_ARGS)));
+        }
+        args.push(token);
+
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        match chars.next() {
+            None => break,
+            Some(',') => {}
+            Some(c) => return Err(parse_error(&format!("Unexpected {:?} in arguments", c))),
+        }
+    }
+
+    Ok((name, args))
+}
+
+fn number(args: &[PredicateArg], index: usize) -> EthicsResult<f64> {
+    match args.get(index) {
+        Some(PredicateArg::Number(n)) => Ok(*n),
+        other => Err(EthicsError::EvaluationError(format!("Argument {} must be a number, got {:?}", index + 1, other))),
+    }
+}
+
+fn text(args: &[PredicateArg], index: usize) -> EthicsResult<String> {
+    match args.get(index) {
+        Some(PredicateArg::Text(s)) => Ok(s.clone()),
+        Some(PredicateArg::Number(n)) => Ok(n.to_string()),
+        None => Err(EthicsError::EvaluationError(format!("Missing argument {}", index + 1))),
+    }
+}
+
+fn texts(args: &[PredicateArg]) -> EthicsResult<Vec<String>> {
+    (0..args.len()).map(|i| text(args, i)).collect()
+}
+
+/// Enum variant name matched case-insensitively against `wanted`
+fn variant_is<T: fmt::Debug>(value: &T, wanted: &str) -> bool {
+    format!("{:?}", value).eq_ignore_ascii_case(wanted)
+}
+
+/// Actor tags plus string entries of the content's `tags` metadata
+pub(crate) fn event_tags(event: &EthicsEvent) -> Vec<String> {
+    let mut all = event.actor.tags.clone();
+    if let Some(serde_json::Value::Array(values)) = event.content.as_ref().and_then(|c| c.metadata.get("tags")) {
+        all.extend(values.iter().filter_map(|v| v.as_str().map(str::to_string)));
+    }
+    all
+}
+
+fn has_tag(event: &EthicsEvent, tag: &str) -> bool {
+    event_tags(event).iter().any(|t| t.eq_ignore_ascii_case(tag))
+}
+
+fn parse_time(value: &str) -> EthicsResult<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|e| EthicsError::EvaluationError(format!("Invalid time {:?} (expected HH:MM): {}", value, e)))
+}
+
+type StandardPredicate = (&'static str, usize, Option<usize>, &'static str, Arc<PredicateFn>);
+
+fn predicate<F>(
+    name: &'static str,
+    min_args: usize,
+    max_args: Option<usize>,
+    description: &'static str,
+    function: F,
+) -> StandardPredicate
+where
+    F: Fn(&EthicsEvent, &[PredicateArg]) -> EthicsResult<bool> + Send + Sync + 'static,
+{
+    (name, min_args, max_args, description, Arc::new(function))
+}
+
+fn standard_predicates() -> Vec<StandardPredicate> {
+    vec![
+        predicate("audience.contains", 1, Some(1), "Audience includes the age group",
+            |event, args| {
+                let group = text(args, 0)?;
+                Ok(event.context.audience.as_ref()
+                    .is_some_and(|a| a.age_groups.iter().any(|g| variant_is(g, &group))))
+            }),
+        predicate("audience.contains_children", 0, Some(0), "Audience includes children",
+            |event, _| {
+                Ok(event.context.audience.as_ref()
+                    .is_some_and(|a| a.age_groups.iter().any(|g| matches!(g, crate::AgeGroup::Children))))
+            }),
+        predicate("audience.contains_minors", 0, Some(0), "Audience includes children or teenagers",
+            |event, _| {
+                Ok(event.context.audience.as_ref().is_some_and(|a| {
+                    a.age_groups.iter().any(|g| matches!(g, crate::AgeGroup::Children | crate::AgeGroup::Teenagers))
+                }))
+            }),
+        predicate("audience.has_vulnerable", 0, Some(1), "Any, or the named, vulnerable group is in the audience",
+            |event, args| {
+                let wanted = args.first().map(|_| text(args, 0)).transpose()?;
+                Ok(event.context.audience.as_ref().is_some_and(|a| match &wanted {
+                    Some(group) => a.vulnerable_groups.iter().any(|g| g.eq_ignore_ascii_case(group)),
+                    None => !a.vulnerable_groups.is_empty(),
+                }))
+            }),
+        predicate("audience.size_at_least", 1, Some(1), "Audience size is known and at least n",
+            |event, args| {
+                let n = number(args, 0)?;
+                Ok(event.context.audience.as_ref().and_then(|a| a.size).is_some_and(|size| size as f64 >= n))
+            }),
+        predicate("actor.trust_below", 1, Some(1), "Actor trust level is below x",
+            |event, args| Ok(event.actor.trust_level < number(args, 0)?)),
+        predicate("actor.trust_at_least", 1, Some(1), "Actor trust level is at least x",
+            |event, args| Ok(event.actor.trust_level >= number(args, 0)?)),
+        predicate("actor.is", 1, None, "Actor is one of the given types",
+            |event, args| {
+                Ok(texts(args)?.iter().any(|t| variant_is(&event.actor.actor_type, t)))
+            }),
+        predicate("actor.violations_at_least", 1, Some(1), "Actor has at least n recorded violations",
+            |event, args| {
+                let count = event.actor.history.as_ref().map_or(0, |h| h.violations.len());
+                Ok(count as f64 >= number(args, 0)?)
+            }),
+        predicate("tag.has", 1, Some(1), "Actor or content carries the tag",
+            |event, args| Ok(has_tag(event, &text(args, 0)?))),
+        predicate("tag.any", 1, None, "Actor or content carries any of the tags",
+            |event, args| {
+                Ok(texts(args)?.iter().any(|t| has_tag(event, t)))
+            }),
+        predicate("tag.any_violation", 0, Some(0), "Actor or content carries a moral violation tag",
+            |event, _| {
+                Ok(tags::ALL_VIOLATION_TAGS.iter().any(|t| has_tag(event, t)))
+            }),
+        predicate("content.present", 0, Some(0), "Event carries content",
+            |event, _| Ok(event.content.is_some())),
+        predicate("content.is", 1, None, "Content is one of the given types",
+            |event, args| {
+                let types = texts(args)?;
+                Ok(event.content.as_ref().is_some_and(|c| types.iter().any(|t| variant_is(&c.content_type, t))))
+            }),
+        predicate("content.metadata_equals", 2, Some(2), "Content metadata field equals the value",
+            |event, args| {
+                let key = text(args, 0)?;
+                let Some(value) = event.content.as_ref().and_then(|c| c.metadata.get(&key)) else {
+                    return Ok(false);
+                };
+                Ok(match (&args[1], value) {
+                    (PredicateArg::Number(n), serde_json::Value::Number(v)) => v.as_f64() == Some(*n),
+                    (PredicateArg::Text(s), serde_json::Value::String(v)) => v == s,
+                    (PredicateArg::Text(s), serde_json::Value::Bool(v)) => s.parse::<bool>().ok() == Some(*v),
+                    _ => false,
+                })
+            }),
+        predicate("time.between", 2, Some(2), "Event time of day (UTC) is in [start, end), wrapping midnight if end < start",
+            |event, args| {
+                let start = parse_time(&text(args, 0)?)?;
+                let end = parse_time(&text(args, 1)?)?;
+                let time = event.timestamp.time();
+                let now = NaiveTime::from_hms_opt(time.hour(), time.minute(), time.second()).unwrap_or(time);
+                Ok(if start <= end {
+                    start <= now && now < end
+                } else {
+                    now >= start || now < end
+                })
+            }),
+        predicate("time.weekday", 1, None, "Event falls on one of the given days (Mon .. Sun)",
+            |event, args| {
+                let today = event.timestamp.weekday();
+                for day in texts(args)? {
+                    let wanted: chrono::Weekday = day.parse()
+                        .map_err(|_| EthicsError::EvaluationError(format!("Invalid weekday {:?}", day)))?;
+                    if wanted == today {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }),
+        predicate("location.in", 1, None, "Event location is one of the ISO 3166 codes",
+            |event, args| {
+                let codes = texts(args)?;
+                Ok(event.context.location.as_ref().is_some_and(|l| codes.iter().any(|c| c.eq_ignore_ascii_case(l))))
+            }),
+        predicate("location.unknown", 0, Some(0), "Event has no location",
+            |event, _| Ok(event.context.location.is_none())),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorHistory, ActorType, AgeGroup, Audience, Content, ContentType, Context, UrgencyLevel, Violation};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn event() -> EthicsEvent {
+        EthicsEvent {
+            event_id: "evt-1".to_string(),
+            actor: Actor {
+                actor_type: ActorType::Institution,
+                tags: vec!["Deception".to_string()],
+                trust_level: 0.4,
+                history: Some(ActorHistory {
+                    violations: vec![Violation {
+                        timestamp: chrono::Utc::now(),
+                        principle: "TRUTH_OVER_LIES".to_string(),
+                        severity: 5,
+                        description: "false claim".to_string(),
+                    }],
+                    trust_history: vec![],
+                    total_evaluations: 3,
+                }),
+            },
+            content: Some(Content {
+                content_type: ContentType::Video,
+                data: String::new(),
+                metadata: HashMap::from([
+                    ("tags".to_string(), serde_json::json!(["kids"])),
+                    ("rating".to_string(), serde_json::json!(12)),
+                ]),
+                content_hash: "h".to_string(),
+            }),
+            context: Context {
+                location: Some("GB".to_string()),
+                culture: None,
+                platform: None,
+                audience: Some(Audience {
+                    age_groups: vec![AgeGroup::Teenagers, AgeGroup::Adults],
+                    vulnerable_groups: vec!["refugees".to_string()],
+                    size: Some(5000),
+                }),
+                urgency: UrgencyLevel::Normal,
+            },
+            // A Saturday
+            timestamp: chrono::Utc.with_ymd_and_hms(2025, 3, 1, 23, 30, 0).unwrap(),
+        }
+    }
+
+    fn check(expression: &str) -> bool {
+        PredicateRegistry::standard().evaluate(expression, &event()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_call() {
+        let (name, args) = parse_call(r#" tag.any( "a \"b\"", WORD , -1.5 ) "#).unwrap();
+        assert_eq!(name, "tag.any");
+        assert_eq!(args, vec![
+            PredicateArg::Text("a \"b\"".to_string()),
+            PredicateArg::Text("WORD".to_string()),
+            PredicateArg::Number(-1.5),
+        ]);
+        assert_eq!(parse_call("content.present()").unwrap().1, vec![]);
+        assert!(parse_call("actor.trust_below 0.5").is_err());
+        assert!(parse_call("tag.has(\"open)").is_err());
+        assert!(parse_call("tag.any(a b)").is_err());
+        assert!(parse_call(&format!("tag.any({})", vec!["x"; MAX_PREDICATE_ARGS + 1].join(","))).is_err());
+        assert!(parse_call(&format!("tag.has({})", "x".repeat(MAX_EXPRESSION_LENGTH))).is_err());
+    }
+
+    /// Inputs that once crashed or hung a fuzz target must keep parsing to a result
+    #[test]
+    fn test_fuzz_regressions() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions/predicate_call");
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let input = std::fs::read(entry.unwrap().path()).unwrap();
+            let expression = String::from_utf8_lossy(&input);
+            let _ = PredicateRegistry::standard().evaluate(&expression, &event());
+        }
+    }
+
+    #[test]
+    fn test_audience_predicates() {
+        assert!(check("audience.contains(teenagers)"));
+        assert!(!check("audience.contains_children()"));
+        assert!(check("audience.contains_minors()"));
+        assert!(check("audience.has_vulnerable()"));
+        assert!(check("audience.has_vulnerable(\"Refugees\")"));
+        assert!(!check("audience.has_vulnerable(elderly)"));
+        assert!(check("audience.size_at_least(5000)"));
+        assert!(!check("audience.size_at_least(5001)"));
+    }
+
+    #[test]
+    fn test_actor_predicates() {
+        assert!(check("actor.trust_below(0.5)"));
+        assert!(!check("actor.trust_at_least(0.5)"));
+        assert!(check("actor.is(Person, Institution)"));
+        assert!(!check("actor.is(Elite)"));
+        assert!(check("actor.violations_at_least(1)"));
+        assert!(!check("actor.violations_at_least(2)"));
+    }
+
+    #[test]
+    fn test_tag_predicates() {
+        assert!(check("tag.has(DECEPTION)"));
+        assert!(check("tag.has(kids)"));
+        assert!(check("tag.any(IDOLATRY, kids)"));
+        assert!(!check("tag.any(IDOLATRY, PRIDE)"));
+        assert!(check("tag.any_violation()"));
+    }
+
+    #[test]
+    fn test_content_predicates() {
+        assert!(check("content.present()"));
+        assert!(check("content.is(text, video)"));
+        assert!(!check("content.is(Code)"));
+        assert!(check("content.metadata_equals(rating, 12)"));
+        assert!(!check("content.metadata_equals(rating, \"12\")"));
+        assert!(!check("content.metadata_equals(missing, 1)"));
+    }
+
+    #[test]
+    fn test_time_predicates() {
+        assert!(check("time.between(\"22:00\", \"06:00\")"));
+        assert!(!check("time.between(\"08:00\", \"23:30\")"));
+        assert!(check("time.weekday(Sat, Sun)"));
+        assert!(!check("time.weekday(Mon)"));
+        assert!(PredicateRegistry::standard().evaluate("time.between(late, \"06:00\")", &event()).is_err());
+    }
+
+    #[test]
+    fn test_location_predicates() {
+        assert!(check("location.in(US, gb)"));
+        assert!(!check("location.in(FR)"));
+        assert!(!check("location.unknown()"));
+    }
+
+    #[test]
+    fn test_custom_predicates_and_arity() {
+        let mut registry = PredicateRegistry::standard();
+        registry.register("platform.is_broadcast", 0, Some(0), "Platform is a broadcast medium", |event, _| {
+            Ok(event.context.platform.as_deref() == Some("broadcast"))
+        }).unwrap();
+        assert!(registry.contains("platform.is_broadcast"));
+        assert!(!registry.evaluate("platform.is_broadcast()", &event()).unwrap());
+
+        assert!(registry.register("actor.trust_below", 1, Some(1), "shadow", |_, _| Ok(true)).is_err());
+        assert!(registry.register("bad name", 0, None, "", |_, _| Ok(true)).is_err());
+        assert!(registry.evaluate("actor.trust_below()", &event()).is_err());
+        assert!(registry.evaluate("actor.trust_below(high)", &event()).is_err());
+        assert!(registry.evaluate("nothing.here()", &event()).is_err());
+        assert_eq!(registry.docs().len(), 20);
+    }
+}
@@ -0,0 +1,367 @@
+//! Actor Pseudonymization - Keyed Pseudonyms in Decisions and Logs
+//! "A good name is rather to be chosen than great riches" - Proverbs 22:1
+//!
+//! With `EthicsConfig::pseudonymization` set, actor identifiers are
+//! replaced by keyed pseudonyms before they reach an issued decision, the
+//! revocation list or a log line: an HMAC-SHA3-256 of the identifier under
+//! the current key of a key ring. Every component opening the same key ring
+//! derives the same pseudonym for the same actor, so records stay
+//! correlatable across the ethics engine, Cold-Mirror and the audit trail.
+//!
+//! The key rotates after `rotation_days`; a pseudonym names the key epoch it
+//! was derived under, and the previous `retained_keys - 1` keys are kept so
+//! recent pseudonyms can still be matched. A pseudonym cannot be reversed.
+//! Re-identification matches it against candidate identifiers and is only
+//! open to the operators listed in `authorized`; every request, granted or
+//! not, is appended to the re-identification log before it is answered.
+
+use crate::{EthicsError, EthicsResult};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha3::Sha3_256;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Prefix of every pseudonym
+pub const PSEUDONYM_PREFIX: &str = "ps";
+
+/// Bytes of the HMAC kept in a pseudonym
+const PSEUDONYM_BYTES: usize = 16;
+
+/// Bytes of a pseudonymization key
+const KEY_BYTES: usize = 32;
+
+/// Most keys a key ring file may hold
+const MAX_KEYS: usize = 64;
+
+/// Pseudonymization settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PseudonymConfig {
+    /// Key ring file shared by every component; created when missing
+    pub key_file: PathBuf,
+    /// Days a key is used before the next one is generated
+    #[serde(default = "default_rotation_days")]
+    pub rotation_days: u32,
+    /// Keys kept, the current one included; older pseudonyms no longer match
+    #[serde(default = "default_retained_keys")]
+    pub retained_keys: usize,
+    /// JSON-lines log of every re-identification request
+    pub reidentification_log: PathBuf,
+    /// Operators allowed to re-identify a pseudonym
+    #[serde(default)]
+    pub authorized: Vec<String>,
+}
+
+fn default_rotation_days() -> u32 {
+    30
+}
+
+fn default_retained_keys() -> usize {
+    4
+}
+
+/// One key of the ring
+#[derive(Clone, Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+struct PseudonymKey {
+    epoch: u32,
+    #[zeroize(skip)]
+    created_at: DateTime<Utc>,
+    /// Hex-encoded key
+    secret: String,
+}
+
+impl PseudonymKey {
+    fn generate(epoch: u32, created_at: DateTime<Utc>) -> Self {
+        let mut secret = Zeroizing::new([0u8; KEY_BYTES]);
+        rand::thread_rng().fill_bytes(secret.as_mut());
+        Self { epoch, created_at, secret: hex::encode(secret.as_ref()) }
+    }
+
+    fn pseudonym(&self, actor: &str) -> String {
+        let secret = Zeroizing::new(hex::decode(&self.secret).unwrap_or_default());
+        let mut mac = Hmac::<Sha3_256>::new_from_slice(&secret).expect("HMAC accepts keys of any length");
+        mac.update(actor.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        format!("{}{}_{}", PSEUDONYM_PREFIX, self.epoch, hex::encode(&digest[..PSEUDONYM_BYTES]))
+    }
+}
+
+/// Key ring as stored, newest key last
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct KeyRing {
+    keys: Vec<PseudonymKey>,
+}
+
+impl Validate for KeyRing {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("keys", self.keys.len(), MAX_KEYS)?;
+        for key in &self.keys {
+            if key.secret.len() != KEY_BYTES * 2 || hex::decode(&key.secret).is_err() {
+                return Err(format!("key of epoch {} is not {} hex-encoded bytes", key.epoch, KEY_BYTES));
+            }
+        }
+        if self.keys.windows(2).any(|pair| pair[0].epoch >= pair[1].epoch) {
+            return Err("key epochs must increase".into());
+        }
+        Ok(())
+    }
+}
+
+/// Operator's request to re-identify a pseudonym
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReidentificationRequest {
+    /// Pseudonym to re-identify
+    pub pseudonym: String,
+    /// Operator asking
+    pub requested_by: String,
+    /// Why, e.g. a case or ticket reference
+    pub reason: String,
+}
+
+/// Entry of the re-identification log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReidentificationRecord {
+    /// When the request was answered
+    pub at: DateTime<Utc>,
+    /// Pseudonym asked about
+    pub pseudonym: String,
+    /// Operator asking
+    pub requested_by: String,
+    /// Reason given
+    pub reason: String,
+    /// Whether the operator was authorized
+    pub granted: bool,
+    /// Whether a candidate matched; never the identifier itself
+    pub matched: bool,
+}
+
+/// Derives actor pseudonyms and answers re-identification requests
+pub struct Pseudonymizer {
+    config: PseudonymConfig,
+    ring: RwLock<KeyRing>,
+}
+
+impl std::fmt::Debug for Pseudonymizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pseudonymizer")
+            .field("key_file", &self.config.key_file)
+            .field("epoch", &self.epoch())
+            .finish()
+    }
+}
+
+impl Pseudonymizer {
+    /// Open the key ring in `config.key_file`, creating or rotating it as due
+    pub fn open(config: PseudonymConfig) -> EthicsResult<Self> {
+        if config.retained_keys == 0 || config.rotation_days == 0 {
+            return Err(EthicsError::ConfigurationError(
+                "pseudonymization needs at least one retained key and a positive rotation period".into(),
+            ));
+        }
+        let ring = load_ring(&config.key_file)?.unwrap_or_default();
+        let pseudonymizer = Self { config, ring: RwLock::new(ring) };
+        pseudonymizer.rotate_if_due(Utc::now())?;
+        Ok(pseudonymizer)
+    }
+
+    /// Epoch of the current key
+    pub fn epoch(&self) -> u32 {
+        self.read().keys.last().map_or(0, |key| key.epoch)
+    }
+
+    /// Pseudonym of `actor` under the current key
+    pub fn pseudonymize(&self, actor: &str) -> String {
+        if let Err(e) = self.rotate_if_due(Utc::now()) {
+            warn!("Pseudonymization key rotation failed, keeping epoch {}: {}", self.epoch(), e);
+        }
+        let ring = self.read();
+        let key = ring.keys.last().expect("key ring holds a key once opened");
+        key.pseudonym(actor)
+    }
+
+    /// Pseudonyms of `actor` under every retained key, newest first
+    pub fn pseudonyms(&self, actor: &str) -> Vec<String> {
+        self.read().keys.iter().rev().map(|key| key.pseudonym(actor)).collect()
+    }
+
+    /// Whether `pseudonym` was derived from `actor` under a retained key
+    pub fn matches(&self, pseudonym: &str, actor: &str) -> bool {
+        let ring = self.read();
+        epoch_of(pseudonym)
+            .and_then(|epoch| ring.keys.iter().find(|key| key.epoch == epoch))
+            .is_some_and(|key| constant_time_eq(key.pseudonym(actor).as_bytes(), pseudonym.as_bytes()))
+    }
+
+    /// Re-identify `request.pseudonym` among `candidates`
+    ///
+    /// The request is logged before it is answered; an unauthorized
+    /// operator is refused, and the refusal is logged too.
+    pub fn reidentify<'a>(
+        &self,
+        request: &ReidentificationRequest,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> EthicsResult<Option<String>> {
+        let granted = !request.reason.trim().is_empty()
+            && self.config.authorized.iter().any(|operator| *operator == request.requested_by);
+        let found = if granted {
+            candidates.into_iter().find(|candidate| self.matches(&request.pseudonym, candidate)).map(str::to_string)
+        } else {
+            None
+        };
+
+        self.log(&ReidentificationRecord {
+            at: Utc::now(),
+            pseudonym: request.pseudonym.clone(),
+            requested_by: request.requested_by.clone(),
+            reason: request.reason.clone(),
+            granted,
+            matched: found.is_some(),
+        })?;
+        if !granted {
+            warn!("Refused re-identification of {} by {}", request.pseudonym, request.requested_by);
+            return Err(EthicsError::ConfigurationError(format!(
+                "{} is not authorized to re-identify pseudonyms, or gave no reason", request.requested_by
+            )));
+        }
+        info!("Re-identification of {} by {}: {}", request.pseudonym, request.requested_by,
+              if found.is_some() { "matched" } else { "no match" });
+        Ok(found)
+    }
+
+    /// Generate the next key when the current one is older than the rotation period
+    ///
+    /// Another process may have rotated the shared key ring already, so it
+    /// is reloaded first and only rotated if still due.
+    pub fn rotate_if_due(&self, now: DateTime<Utc>) -> EthicsResult<bool> {
+        let period = Duration::days(i64::from(self.config.rotation_days));
+        let due = |ring: &KeyRing| ring.keys.last().is_none_or(|key| key.created_at + period <= now);
+        if !due(&self.read()) {
+            return Ok(false);
+        }
+
+        let mut ring = self.ring.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(stored) = load_ring(&self.config.key_file)? {
+            if stored.keys.last().map(|key| key.epoch) > ring.keys.last().map(|key| key.epoch) {
+                *ring = stored;
+            }
+        }
+        if !due(&ring) {
+            return Ok(false);
+        }
+        let epoch = ring.keys.last().map_or(1, |key| key.epoch + 1);
+        ring.keys.push(PseudonymKey::generate(epoch, now));
+        let excess = ring.keys.len().saturating_sub(self.config.retained_keys);
+        ring.keys.drain(..excess);
+        store_ring(&self.config.key_file, &ring)?;
+        info!("Rotated pseudonymization key to epoch {}", epoch);
+        Ok(true)
+    }
+
+    fn log(&self, record: &ReidentificationRecord) -> EthicsResult<()> {
+        let path = &self.config.reidentification_log;
+        let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", path.display(), e));
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize re-identification record: {}", e)))?;
+        line.push(b'\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(io_error)?;
+        file.write_all(&line).and_then(|_| file.sync_data()).map_err(io_error)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, KeyRing> {
+        self.ring.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Key epoch named by a pseudonym
+fn epoch_of(pseudonym: &str) -> Option<u32> {
+    pseudonym.strip_prefix(PSEUDONYM_PREFIX)?.split_once('_')?.0.parse().ok()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn load_ring(path: &Path) -> EthicsResult<Option<KeyRing>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => Zeroizing::new(bytes),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(EthicsError::ConfigurationError(format!("{}: {}", path.display(), e))),
+    };
+    decode::json_validated(&bytes, &DecodeLimits::FILE)
+        .map(Some)
+        .map_err(|e| EthicsError::ConfigurationError(format!("Pseudonymization key ring {}: {}", path.display(), e)))
+}
+
+/// Replace the key ring file atomically, readable by the owner only
+fn store_ring(path: &Path, ring: &KeyRing) -> EthicsResult<()> {
+    let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", path.display(), e));
+    let contents = Zeroizing::new(
+        serde_json::to_vec_pretty(ring).map_err(|e| EthicsError::RuntimeError(e.to_string()))?,
+    );
+    let temp = path.with_extension("tmp");
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&temp).map_err(io_error)?;
+    file.write_all(&contents).and_then(|_| file.sync_all()).map_err(io_error)?;
+    std::fs::rename(&temp, path).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &Path, authorized: &[&str]) -> PseudonymConfig {
+        PseudonymConfig {
+            key_file: dir.join("pseudonym-keys.json"),
+            rotation_days: 30,
+            retained_keys: 2,
+            reidentification_log: dir.join("reidentification.jsonl"),
+            authorized: authorized.iter().map(|operator| operator.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_pseudonyms_are_shared_rotate_and_reidentify_with_a_log() {
+        let dir = std::env::temp_dir().join(format!("ark-pseudonym-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let engine = Pseudonymizer::open(config(&dir, &["auditor"])).unwrap();
+        let mirror = Pseudonymizer::open(config(&dir, &[])).unwrap();
+        let pseudonym = engine.pseudonymize("alice@example.org");
+        assert_eq!(pseudonym, mirror.pseudonymize("alice@example.org"));
+        assert!(pseudonym.starts_with("ps1_") && !pseudonym.contains("alice"));
+        assert_ne!(pseudonym, engine.pseudonymize("bob@example.org"));
+
+        // After rotation the old pseudonym still matches, under its own epoch
+        assert!(engine.rotate_if_due(Utc::now() + Duration::days(31)).unwrap());
+        assert!(engine.pseudonymize("alice@example.org").starts_with("ps2_"));
+        assert!(engine.matches(&pseudonym, "alice@example.org"));
+        assert_eq!(engine.pseudonyms("alice@example.org")[1], pseudonym);
+
+        let request = |by: &str| ReidentificationRequest {
+            pseudonym: pseudonym.clone(),
+            requested_by: by.into(),
+            reason: "case 2291".into(),
+        };
+        let candidates = ["bob@example.org", "alice@example.org"];
+        assert_eq!(engine.reidentify(&request("auditor"), candidates).unwrap().as_deref(), Some("alice@example.org"));
+        assert!(mirror.reidentify(&request("intruder"), candidates).is_err());
+
+        let log = std::fs::read_to_string(dir.join("reidentification.jsonl")).unwrap();
+        let records: Vec<ReidentificationRecord> = log.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].granted && records[0].matched);
+        assert!(!records[1].granted && records[1].requested_by == "intruder");
+        assert!(!log.contains("alice"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
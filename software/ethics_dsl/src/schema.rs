@@ -0,0 +1,201 @@
+//! Schema Versions - Readable Records Across Format Changes
+//! "Remove not the ancient landmark, which thy fathers have set" - Proverbs 22:28
+//!
+//! Events, decisions and journal entries are written as JSON objects that
+//! carry a `schema_version` field. Records written before versioning have
+//! no field and are version 1. Reading a record upgrades it one version at
+//! a time through its type's migrations until it reaches
+//! `CURRENT_SCHEMA_VERSION`, then deserializes it; a record written by a
+//! newer engine is refused with `UnsupportedSchemaVersion` instead of being
+//! misread. Changing a serialized type means bumping the version and
+//! appending a migration for every record type, so each step only knows
+//! the version before it. A record's migrations also upgrade the records
+//! nested in it, which carry no version of their own.
+
+use pq_types::decode::{self, DecodeLimits};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{EthicsDecision, EthicsError, EthicsEvent, EthicsResult, JournalEntry};
+
+/// Schema version written by this engine
+pub const CURRENT_SCHEMA_VERSION: u32 = 2;
+
+/// Field holding the schema version of a record
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// Upgrade of a record's JSON object from one version to the next
+pub type Migration = fn(&mut Map<String, Value>) -> EthicsResult<()>;
+
+/// Type serialized with a schema version
+pub trait VersionedRecord: Serialize + DeserializeOwned {
+    /// Record name used in errors
+    const RECORD: &'static str;
+    /// `MIGRATIONS[n]` upgrades version `n + 1` to `n + 2`
+    const MIGRATIONS: &'static [Migration];
+}
+
+/// Version 2 only added the version field
+fn v1_to_v2(_record: &mut Map<String, Value>) -> EthicsResult<()> {
+    Ok(())
+}
+
+impl VersionedRecord for EthicsEvent {
+    const RECORD: &'static str = "event";
+    const MIGRATIONS: &'static [Migration] = &[v1_to_v2];
+}
+
+impl VersionedRecord for EthicsDecision {
+    const RECORD: &'static str = "decision";
+    const MIGRATIONS: &'static [Migration] = &[v1_to_v2];
+}
+
+impl VersionedRecord for JournalEntry {
+    const RECORD: &'static str = "journal entry";
+    const MIGRATIONS: &'static [Migration] = &[v1_to_v2];
+}
+
+/// Record as a JSON object tagged with the current schema version
+pub fn to_value<T: VersionedRecord>(record: &T) -> EthicsResult<Value> {
+    let mut value = serde_json::to_value(record)
+        .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize {}: {}", T::RECORD, e)))?;
+    value
+        .as_object_mut()
+        .ok_or_else(|| EthicsError::RuntimeError(format!("{} does not serialize to an object", T::RECORD)))?
+        .insert(SCHEMA_VERSION_FIELD.to_string(), CURRENT_SCHEMA_VERSION.into());
+    Ok(value)
+}
+
+/// Record as JSON tagged with the current schema version
+pub fn to_string<T: VersionedRecord>(record: &T) -> EthicsResult<String> {
+    Ok(to_value(record)?.to_string())
+}
+
+/// Record from JSON of any supported schema version
+pub fn from_value<T: VersionedRecord>(value: Value) -> EthicsResult<T> {
+    let Value::Object(mut object) = value else {
+        return Err(EthicsError::ParseError(format!("{} record is not a JSON object", T::RECORD)));
+    };
+    let version = match object.remove(SCHEMA_VERSION_FIELD) {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| EthicsError::ParseError(format!("Invalid {} schema version: {}", T::RECORD, version)))?,
+    };
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(EthicsError::UnsupportedSchemaVersion {
+            record: T::RECORD,
+            found: version,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+
+    for migration in &T::MIGRATIONS[version as usize - 1..] {
+        migration(&mut object)?;
+    }
+    serde_json::from_value(Value::Object(object))
+        .map_err(|e| EthicsError::ParseError(format!("Malformed {} record: {}", T::RECORD, e)))
+}
+
+/// Record from JSON bytes of any supported schema version, within `limits`
+pub fn from_slice<T: VersionedRecord>(bytes: &[u8], limits: &DecodeLimits) -> EthicsResult<T> {
+    let value: Value = decode::json(bytes, limits)
+        .map_err(|e| EthicsError::ParseError(format!("Malformed {} record: {}", T::RECORD, e)))?;
+    from_value(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, Context, UrgencyLevel};
+    use chrono::Utc;
+
+    fn entry() -> JournalEntry {
+        let event = crate::utils::create_event(
+            "evt-1".to_string(),
+            Actor {
+                actor_type: ActorType::Person,
+                tags: vec!["tag".to_string()],
+                trust_level: 0.7,
+                history: None,
+            },
+            None,
+            Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+        );
+        JournalEntry {
+            recorded_at: Utc::now(),
+            event,
+            decision: EthicsDecision::Deny {
+                confidence: 0.8,
+                violation: "deception".to_string(),
+                violated_principles: vec!["TRUTH_OVER_LIES".to_string()],
+                scripture_refs: vec!["John 8:44".to_string()],
+            },
+        }
+    }
+
+    /// The record serialized as `version` wrote it
+    fn written_as<T: VersionedRecord>(record: &T, version: u32) -> Vec<u8> {
+        let mut value = to_value(record).unwrap();
+        let object = value.as_object_mut().unwrap();
+        if version == 1 {
+            object.remove(SCHEMA_VERSION_FIELD);
+        } else {
+            object.insert(SCHEMA_VERSION_FIELD.to_string(), version.into());
+        }
+        serde_json::to_vec(&value).unwrap()
+    }
+
+    fn round_trip<T: VersionedRecord>(record: &T) -> Vec<T> {
+        (1..=CURRENT_SCHEMA_VERSION)
+            .map(|version| from_slice::<T>(&written_as(record, version), &DecodeLimits::RECORD).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_supported_version_round_trips() {
+        for record in [EthicsEvent::MIGRATIONS, EthicsDecision::MIGRATIONS, JournalEntry::MIGRATIONS] {
+            assert_eq!(record.len() as u32, CURRENT_SCHEMA_VERSION - 1);
+        }
+
+        let entry = entry();
+        for read in round_trip(&entry) {
+            assert_eq!(read.recorded_at, entry.recorded_at);
+            assert_eq!(read.event.event_id, entry.event.event_id);
+            assert_eq!(read.decision, entry.decision);
+        }
+        for read in round_trip(&entry.event) {
+            assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&entry.event).unwrap());
+        }
+        for read in round_trip(&entry.decision) {
+            assert_eq!(read, entry.decision);
+        }
+
+        let written: Value = serde_json::from_str(&to_string(&entry).unwrap()).unwrap();
+        assert_eq!(written[SCHEMA_VERSION_FIELD], CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_future_and_invalid_versions_refused() {
+        let future = written_as(&entry(), CURRENT_SCHEMA_VERSION + 1);
+        let error = from_slice::<JournalEntry>(&future, &DecodeLimits::RECORD).unwrap_err();
+        assert!(matches!(
+            error,
+            EthicsError::UnsupportedSchemaVersion { record: "journal entry", found, supported: CURRENT_SCHEMA_VERSION }
+                if found == CURRENT_SCHEMA_VERSION + 1
+        ));
+        assert!(error.to_string().contains("schema version 3"));
+
+        let zero = written_as(&entry().decision, 0);
+        assert!(matches!(from_slice::<EthicsDecision>(&zero, &DecodeLimits::RECORD), Err(EthicsError::ParseError(_))));
+    }
+}
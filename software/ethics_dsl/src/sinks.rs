@@ -0,0 +1,857 @@
+//! Decision Sinks - Immediate Notification of Deny and Purge Decisions
+//! "Son of man, I have made thee a watchman unto the house of Israel" - Ezekiel 3:17
+//!
+//! Sinks are told about every decision that passes their filter. Evaluation
+//! never waits on delivery: the engine queues notifications to a background
+//! dispatcher, which delivers each one to its sink, retries failures with
+//! exponential backoff, and counts deliveries, retries and failures per
+//! sink. Built-in sinks POST to a webhook, append JSON lines to a file, or
+//! publish on an in-process event bus. The queue is bounded: when sinks fall
+//! that far behind, new notifications are dropped and counted rather than
+//! held without limit.
+//!
+//! Webhooks require TLS: `https://` URLs verified against the Web PKI roots.
+//! Plain HTTP is only accepted to loopback addresses, for local relays.
+//! Crates with a post-quantum channel (the network sentinel depends on this
+//! crate, not the other way round) plug it in through [`WebhookTransport`].
+
+use crate::{EthicsDecision, EthicsError, EthicsResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, mpsc};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+/// Notifications queued for delivery before new ones are dropped
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Deliveries, including their retries, running at once
+const MAX_IN_FLIGHT: usize = 32;
+
+/// Kind of decision, for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    /// `EthicsDecision::Allow`
+    Allow,
+    /// `EthicsDecision::Deny`
+    Deny,
+    /// `EthicsDecision::Purge`
+    Purge,
+}
+
+impl DecisionKind {
+    /// Kind of `decision`
+    pub fn of(decision: &EthicsDecision) -> Self {
+        match decision {
+            EthicsDecision::Allow { .. } => DecisionKind::Allow,
+            EthicsDecision::Deny { .. } => DecisionKind::Deny,
+            EthicsDecision::Purge { .. } => DecisionKind::Purge,
+        }
+    }
+}
+
+/// Severity of a decision on the 1-10 purge scale
+///
+/// Purges carry their own severity, denials scale their confidence onto the
+/// range and round it, and allows are 0.
+pub fn decision_severity(decision: &EthicsDecision) -> u8 {
+    match decision {
+        EthicsDecision::Allow { .. } => 0,
+        EthicsDecision::Deny { confidence, .. } => ((confidence.clamp(0.0, 1.0) * 10.0).round() as u8).max(1),
+        EthicsDecision::Purge { severity, .. } => *severity,
+    }
+}
+
+/// Notification delivered to sinks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecisionNotification {
+    /// Evaluated event
+    pub event_id: String,
+    /// Kind of decision
+    pub kind: DecisionKind,
+    /// Severity per [`decision_severity`]
+    pub severity: u8,
+    /// Full decision
+    pub decision: EthicsDecision,
+    /// When the decision was made
+    pub decided_at: DateTime<Utc>,
+}
+
+impl DecisionNotification {
+    /// Notification of `decision` on event `event_id`, made now
+    pub fn new(event_id: impl Into<String>, decision: EthicsDecision) -> Self {
+        Self {
+            event_id: event_id.into(),
+            kind: DecisionKind::of(&decision),
+            severity: decision_severity(&decision),
+            decision,
+            decided_at: Utc::now(),
+        }
+    }
+}
+
+/// Which decisions a sink is told about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SinkFilter {
+    /// Decision kinds to deliver
+    pub decisions: Vec<DecisionKind>,
+    /// Minimum severity to deliver
+    pub min_severity: u8,
+}
+
+impl Default for SinkFilter {
+    fn default() -> Self {
+        Self {
+            decisions: vec![DecisionKind::Deny, DecisionKind::Purge],
+            min_severity: 0,
+        }
+    }
+}
+
+impl SinkFilter {
+    /// Whether `notification` passes the filter
+    pub fn matches(&self, notification: &DecisionNotification) -> bool {
+        self.decisions.contains(&notification.kind) && notification.severity >= self.min_severity
+    }
+}
+
+/// Retry schedule for failed deliveries
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per notification, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry (milliseconds); doubles on each retry
+    pub initial_backoff_ms: u64,
+    /// Upper bound on the delay between retries (milliseconds)
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay after failed attempt number `attempt` (starting at 1)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(32);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// Destination for decision notifications
+#[async_trait]
+pub trait DecisionSink: Send + Sync {
+    /// Name used in logs and metrics
+    fn name(&self) -> &str;
+
+    /// Deliver one notification; errors are retried per the sink's policy
+    async fn deliver(&self, notification: &DecisionNotification) -> EthicsResult<()>;
+}
+
+/// Channel a webhook request is sent over
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    /// POST a JSON `body` to `url`, returning the HTTP status code
+    async fn post(&self, url: &str, body: &[u8]) -> EthicsResult<u16>;
+}
+
+/// HTTP/1.1 over TLS, verified against the Web PKI roots; accepts `https://` URLs only
+#[derive(Clone)]
+pub struct HttpsTransport {
+    connector: TlsConnector,
+    timeout: Duration,
+}
+
+impl HttpsTransport {
+    /// Transport failing requests that take longer than `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            timeout,
+        }
+    }
+}
+
+impl Default for HttpsTransport {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for HttpsTransport {
+    async fn post(&self, url: &str, body: &[u8]) -> EthicsResult<u16> {
+        let target = split_url(url, "https://", 443)?;
+        let server_name = ServerName::try_from(target.host.clone())
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", url, e)))?;
+        let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", url, e));
+
+        let exchange = async {
+            let stream = tokio::net::TcpStream::connect(&target.address).await.map_err(io_error)?;
+            let stream = self.connector.connect(server_name, stream).await.map_err(io_error)?;
+            post_json(stream, &target, body, url).await
+        };
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| EthicsError::RuntimeError(format!("{}: timed out after {:?}", url, self.timeout)))?
+    }
+}
+
+/// HTTP/1.1 over plain TCP; accepts `http://` URLs to loopback addresses only
+#[derive(Debug, Clone)]
+pub struct PlainHttpTransport {
+    timeout: Duration,
+}
+
+impl PlainHttpTransport {
+    /// Transport failing requests that take longer than `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+impl Default for PlainHttpTransport {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for PlainHttpTransport {
+    async fn post(&self, url: &str, body: &[u8]) -> EthicsResult<u16> {
+        let target = split_url(url, "http://", 80)?;
+        let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", url, e));
+
+        let exchange = async {
+            // Resolve first so a name cannot point a loopback check elsewhere
+            let addresses: Vec<std::net::SocketAddr> =
+                tokio::net::lookup_host(&target.address).await.map_err(io_error)?.collect();
+            if addresses.is_empty() || addresses.iter().any(|address| !address.ip().is_loopback()) {
+                return Err(EthicsError::ConfigurationError(format!(
+                    "{}: plain HTTP webhooks must target a loopback address; use https://",
+                    url
+                )));
+            }
+            let stream = tokio::net::TcpStream::connect(&addresses[..]).await.map_err(io_error)?;
+            post_json(stream, &target, body, url).await
+        };
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| EthicsError::RuntimeError(format!("{}: timed out after {:?}", url, self.timeout)))?
+    }
+}
+
+/// Parts of a webhook URL
+struct WebhookTarget {
+    /// Host name or IP literal, without brackets or port
+    host: String,
+    /// `host:port` to connect to
+    address: String,
+    /// Request path
+    path: String,
+}
+
+/// Split a `scheme://host[:port]/path` URL, filling in `default_port`
+fn split_url(url: &str, scheme: &str, default_port: u16) -> EthicsResult<WebhookTarget> {
+    let rest = url.strip_prefix(scheme).ok_or_else(|| {
+        EthicsError::ConfigurationError(format!("{}: expected a {} URL", url, scheme))
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err(EthicsError::ConfigurationError(format!("{}: missing host", url)));
+    }
+    let has_port = authority.rsplit(']').next().unwrap_or(authority).contains(':');
+    let (host, address) = if has_port {
+        let index = authority.rfind(':').unwrap_or(authority.len());
+        (&authority[..index], authority.to_string())
+    } else {
+        (authority, format!("{}:{}", authority, default_port))
+    };
+    Ok(WebhookTarget {
+        host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+        address,
+        path: path.to_string(),
+    })
+}
+
+/// Send a JSON POST over `stream` and read the response status
+async fn post_json<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    target: &WebhookTarget,
+    body: &[u8],
+    url: &str,
+) -> EthicsResult<u16> {
+    let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", url, e));
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        target.path,
+        target.address,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await.map_err(io_error)?;
+    stream.write_all(body).await.map_err(io_error)?;
+    stream.flush().await.map_err(io_error)?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).await.map_err(io_error)?;
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| EthicsError::RuntimeError(format!("{}: malformed response {:?}", url, status_line.trim_end())))
+}
+
+/// POSTs each notification as JSON to a URL
+pub struct WebhookSink {
+    name: String,
+    url: String,
+    transport: Arc<dyn WebhookTransport>,
+}
+
+impl WebhookSink {
+    /// Webhook posting to an `https://` URL over [`HttpsTransport`]
+    pub fn new(url: impl Into<String>) -> Self {
+        let url = url.into();
+        Self {
+            name: format!("webhook:{}", url),
+            url,
+            transport: Arc::new(HttpsTransport::default()),
+        }
+    }
+
+    /// Post over `transport` instead, e.g. a PQ-TLS channel where one is
+    /// available, or [`PlainHttpTransport`] to a loopback relay
+    pub fn with_transport(mut self, transport: Arc<dyn WebhookTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+}
+
+#[async_trait]
+impl DecisionSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, notification: &DecisionNotification) -> EthicsResult<()> {
+        let body = serde_json::to_vec(notification)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize notification: {}", e)))?;
+        match self.transport.post(&self.url, &body).await? {
+            status if (200..300).contains(&status) => Ok(()),
+            status => Err(EthicsError::RuntimeError(format!("{} returned HTTP {}", self.url, status))),
+        }
+    }
+}
+
+/// Appends notifications as JSON lines to a file
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    name: String,
+    path: PathBuf,
+}
+
+impl FileSink {
+    /// Sink appending to `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            name: format!("file:{}", path.display()),
+            path,
+        }
+    }
+}
+
+#[async_trait]
+impl DecisionSink for FileSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&self, notification: &DecisionNotification) -> EthicsResult<()> {
+        let mut line = serde_json::to_vec(notification)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize notification: {}", e)))?;
+        line.push(b'\n');
+        let io_error = |e: std::io::Error| EthicsError::RuntimeError(format!("{}: {}", self.path.display(), e));
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(io_error)?;
+        file.write_all(&line).await.map_err(io_error)?;
+        file.sync_data().await.map_err(io_error)
+    }
+}
+
+/// Publishes notifications on an in-process broadcast channel
+///
+/// Publishing with no subscribers counts as delivered; subscribers that
+/// fall more than `capacity` notifications behind miss the oldest ones.
+#[derive(Debug, Clone)]
+pub struct EventBusSink {
+    sender: broadcast::Sender<DecisionNotification>,
+}
+
+impl EventBusSink {
+    /// Bus buffering up to `capacity` notifications per subscriber
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity.max(1)).0,
+        }
+    }
+
+    /// Receive every notification published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<DecisionNotification> {
+        self.sender.subscribe()
+    }
+}
+
+#[async_trait]
+impl DecisionSink for EventBusSink {
+    fn name(&self) -> &str {
+        "event-bus"
+    }
+
+    async fn deliver(&self, notification: &DecisionNotification) -> EthicsResult<()> {
+        let receivers = self.sender.send(notification.clone()).unwrap_or(0);
+        debug!("Published decision on {} to {} subscribers", notification.event_id, receivers);
+        Ok(())
+    }
+}
+
+/// Built-in sink selected from configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuiltinSink {
+    /// [`WebhookSink`] over HTTPS
+    Webhook {
+        /// Endpoint URL
+        url: String,
+    },
+    /// [`FileSink`]
+    File {
+        /// JSON lines file
+        path: PathBuf,
+    },
+}
+
+impl BuiltinSink {
+    /// Instantiate the sink
+    pub fn build(&self) -> Arc<dyn DecisionSink> {
+        match self {
+            BuiltinSink::Webhook { url } => Arc::new(WebhookSink::new(url.clone())),
+            BuiltinSink::File { path } => Arc::new(FileSink::new(path.clone())),
+        }
+    }
+}
+
+/// Configured sink with its filter and retry policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkConfig {
+    /// Which sink
+    #[serde(flatten)]
+    pub sink: BuiltinSink,
+    /// Decisions delivered to it
+    #[serde(default)]
+    pub filter: SinkFilter,
+    /// Retry schedule
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// Delivery counters of one sink
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SinkMetrics {
+    /// Sink name
+    pub sink: String,
+    /// Notifications delivered
+    pub delivered: u64,
+    /// Failed attempts that were retried
+    pub retries: u64,
+    /// Notifications dropped after exhausting retries
+    pub failed: u64,
+    /// Notifications dropped because the delivery queue was full
+    #[serde(default)]
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct SinkCounters {
+    delivered: AtomicU64,
+    retries: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+struct RegisteredSink {
+    sink: Arc<dyn DecisionSink>,
+    filter: SinkFilter,
+    retry: RetryPolicy,
+    counters: SinkCounters,
+}
+
+enum Command {
+    Deliver(Arc<RegisteredSink>, Arc<DecisionNotification>),
+    Flush(std::sync::mpsc::Sender<()>),
+}
+
+/// Background delivery of decision notifications to registered sinks
+///
+/// At most `MAX_IN_FLIGHT` deliveries run at once; further notifications
+/// wait in a bounded queue. Dropping the dispatcher waits for in-flight
+/// deliveries, including their retries, to finish.
+pub struct SinkDispatcher {
+    sinks: RwLock<Vec<Arc<RegisteredSink>>>,
+    queue: Option<mpsc::Sender<Command>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SinkDispatcher {
+    /// Start a dispatcher with no sinks and the default queue capacity
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Start a dispatcher queueing at most `capacity` notifications
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (queue, commands) = mpsc::channel(capacity.max(1));
+        let thread = std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime.block_on(run_dispatcher(commands)),
+                Err(e) => error!("Decision sink dispatcher failed to start: {}", e),
+            }
+        });
+
+        Self {
+            sinks: RwLock::new(Vec::new()),
+            queue: Some(queue),
+            thread: Some(thread),
+        }
+    }
+
+    /// Start a dispatcher with the configured built-in sinks
+    pub fn from_config(configs: &[SinkConfig]) -> Self {
+        let dispatcher = Self::new();
+        for config in configs {
+            dispatcher.add(config.sink.build(), config.filter.clone(), config.retry);
+        }
+        dispatcher
+    }
+
+    /// Register a sink
+    pub fn add(&self, sink: Arc<dyn DecisionSink>, filter: SinkFilter, retry: RetryPolicy) {
+        info!("Registered decision sink {}", sink.name());
+        let registered = Arc::new(RegisteredSink {
+            sink,
+            filter,
+            retry,
+            counters: SinkCounters::default(),
+        });
+        match self.sinks.write() {
+            Ok(mut sinks) => sinks.push(registered),
+            Err(poisoned) => poisoned.into_inner().push(registered),
+        }
+    }
+
+    /// Queue `decision` for every sink whose filter it passes
+    pub fn notify(&self, event_id: &str, decision: &EthicsDecision) {
+        let sinks = match self.sinks.read() {
+            Ok(sinks) => sinks,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let Some(queue) = &self.queue else { return };
+
+        let mut notification = None;
+        for registered in sinks.iter() {
+            let notification = notification
+                .get_or_insert_with(|| Arc::new(DecisionNotification::new(event_id, decision.clone())))
+                .clone();
+            if !registered.filter.matches(&notification) {
+                continue;
+            }
+            match queue.try_send(Command::Deliver(registered.clone(), notification)) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    registered.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    warn!("Decision sink queue is full; dropping notification for {} to {}",
+                          event_id, registered.sink.name());
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    warn!("Decision sink dispatcher stopped; dropping notification for {}", event_id);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Block until every notification queued so far has been delivered or has failed
+    ///
+    /// Waits for queue space, so it must not be called from an async task.
+    pub fn flush(&self) {
+        let (done, finished) = std::sync::mpsc::channel();
+        if let Some(queue) = &self.queue {
+            if queue.blocking_send(Command::Flush(done)).is_ok() {
+                let _ = finished.recv();
+            }
+        }
+    }
+
+    /// Delivery counters of every sink, in registration order
+    pub fn metrics(&self) -> Vec<SinkMetrics> {
+        let sinks = match self.sinks.read() {
+            Ok(sinks) => sinks,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        sinks
+            .iter()
+            .map(|registered| SinkMetrics {
+                sink: registered.sink.name().to_string(),
+                delivered: registered.counters.delivered.load(Ordering::Relaxed),
+                retries: registered.counters.retries.load(Ordering::Relaxed),
+                failed: registered.counters.failed.load(Ordering::Relaxed),
+                dropped: registered.counters.dropped.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+impl Default for SinkDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SinkDispatcher {
+    fn drop(&mut self) {
+        self.queue.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+async fn run_dispatcher(mut commands: mpsc::Receiver<Command>) {
+    let mut deliveries = tokio::task::JoinSet::new();
+    while let Some(command) = commands.recv().await {
+        match command {
+            Command::Deliver(registered, notification) => {
+                // Stop taking from the queue while the limit is reached, so
+                // slow sinks fill the bounded queue instead of spawning tasks
+                while deliveries.len() >= MAX_IN_FLIGHT {
+                    deliveries.join_next().await;
+                }
+                deliveries.spawn(deliver_with_retry(registered, notification));
+            }
+            Command::Flush(done) => {
+                while deliveries.join_next().await.is_some() {}
+                let _ = done.send(());
+            }
+        }
+    }
+    while deliveries.join_next().await.is_some() {}
+}
+
+async fn deliver_with_retry(registered: Arc<RegisteredSink>, notification: Arc<DecisionNotification>) {
+    let name = registered.sink.name();
+    let mut attempt = 1;
+    loop {
+        match registered.sink.deliver(&notification).await {
+            Ok(()) => {
+                registered.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                debug!("Delivered decision on {} to {}", notification.event_id, name);
+                return;
+            }
+            Err(e) if attempt < registered.retry.max_attempts => {
+                registered.counters.retries.fetch_add(1, Ordering::Relaxed);
+                let delay = registered.retry.backoff(attempt);
+                warn!("Delivery to {} failed (attempt {}): {}; retrying in {:?}", name, attempt, e, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => {
+                registered.counters.failed.fetch_add(1, Ordering::Relaxed);
+                error!("Dropping decision on {} for {} after {} attempts: {}", notification.event_id, name, attempt, e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use tokio::io::AsyncReadExt;
+
+    fn deny(confidence: f64) -> EthicsDecision {
+        EthicsDecision::Deny {
+            confidence,
+            violation: "deception".to_string(),
+            violated_principles: vec!["TRUTH_OVER_LIES".to_string()],
+            scripture_refs: vec![],
+        }
+    }
+
+    /// Fails the first `failures` deliveries
+    struct FlakySink {
+        failures: u32,
+        attempts: AtomicU32,
+    }
+
+    #[async_trait]
+    impl DecisionSink for FlakySink {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        async fn deliver(&self, _notification: &DecisionNotification) -> EthicsResult<()> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(EthicsError::RuntimeError("unavailable".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    /// Holds every delivery until the gate is opened
+    struct GatedSink {
+        gate: tokio::sync::Semaphore,
+    }
+
+    #[async_trait]
+    impl DecisionSink for GatedSink {
+        fn name(&self) -> &str {
+            "gated"
+        }
+
+        async fn deliver(&self, _notification: &DecisionNotification) -> EthicsResult<()> {
+            self.gate.acquire().await.map(drop).map_err(|e| EthicsError::RuntimeError(e.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_filter_by_kind_and_severity() {
+        let filter = SinkFilter { min_severity: 8, ..SinkFilter::default() };
+        let allow = EthicsDecision::Allow {
+            confidence: 1.0,
+            justification: String::new(),
+            scripture_refs: vec![],
+        };
+
+        assert!(filter.matches(&DecisionNotification::new("e1", deny(0.75))));
+        assert!(!filter.matches(&DecisionNotification::new("e2", deny(0.7))));
+        assert!(!filter.matches(&DecisionNotification::new("e3", allow)));
+        assert_eq!(RetryPolicy::default().backoff(1), Duration::from_millis(200));
+        assert_eq!(RetryPolicy::default().backoff(3), Duration::from_millis(800));
+        assert_eq!(RetryPolicy::default().backoff(40), Duration::from_millis(10_000));
+    }
+
+    #[test]
+    fn test_retries_and_failure_metrics() {
+        let retry = RetryPolicy { max_attempts: 3, initial_backoff_ms: 1, max_backoff_ms: 1 };
+        let bus = EventBusSink::new(8);
+        let mut subscriber = bus.subscribe();
+
+        let dispatcher = SinkDispatcher::new();
+        dispatcher.add(Arc::new(FlakySink { failures: 2, attempts: AtomicU32::new(0) }), SinkFilter::default(), retry);
+        dispatcher.add(Arc::new(FlakySink { failures: 10, attempts: AtomicU32::new(0) }), SinkFilter::default(), retry);
+        dispatcher.add(Arc::new(bus), SinkFilter::default(), retry);
+
+        dispatcher.notify("e1", &deny(0.9));
+        dispatcher.flush();
+
+        let metrics = dispatcher.metrics();
+        assert_eq!((metrics[0].delivered, metrics[0].retries, metrics[0].failed), (1, 2, 0));
+        assert_eq!((metrics[1].delivered, metrics[1].retries, metrics[1].failed), (0, 2, 1));
+        assert_eq!(metrics[2].delivered, 1);
+        assert_eq!(subscriber.try_recv().unwrap().event_id, "e1");
+    }
+
+    #[test]
+    fn test_full_queue_drops_and_counts() {
+        let sink = Arc::new(GatedSink { gate: tokio::sync::Semaphore::new(0) });
+        let dispatcher = SinkDispatcher::with_capacity(1);
+        dispatcher.add(sink.clone(), SinkFilter::default(), RetryPolicy::default());
+
+        // In-flight deliveries, the one waiting for a slot and the queued
+        // one are accepted; the rest are dropped
+        let sent = MAX_IN_FLIGHT + 8;
+        for i in 0..sent {
+            dispatcher.notify(&format!("e{}", i), &deny(0.9));
+        }
+        sink.gate.add_permits(sent);
+        dispatcher.flush();
+
+        let metrics = &dispatcher.metrics()[0];
+        assert!(metrics.dropped >= 6);
+        assert_eq!(metrics.delivered + metrics.dropped, sent as u64);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_posts_json() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hooks/ethics", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            while !String::from_utf8_lossy(&request).contains("\"event_id\"") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        WebhookSink::new(url)
+            .with_transport(Arc::new(PlainHttpTransport::default()))
+            .deliver(&DecisionNotification::new("e1", deny(0.9)))
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hooks/ethics HTTP/1.1\r\n"));
+        assert!(request.contains("\"kind\":\"deny\""));
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_require_tls_off_loopback() {
+        let notification = DecisionNotification::new("e1", deny(0.9));
+        assert!(matches!(
+            WebhookSink::new("http://example.org/").deliver(&notification).await,
+            Err(EthicsError::ConfigurationError(_))
+        ));
+        assert!(matches!(
+            PlainHttpTransport::default().post("http://192.0.2.7:8080/hook", b"{}").await,
+            Err(EthicsError::ConfigurationError(_))
+        ));
+
+        let target = split_url("https://[fd00::7]:8443/hooks", "https://", 443).unwrap();
+        assert_eq!((target.host.as_str(), target.address.as_str(), target.path.as_str()), ("fd00::7", "[fd00::7]:8443", "/hooks"));
+        let target = split_url("https://alerts.example.org", "https://", 443).unwrap();
+        assert_eq!((target.host.as_str(), target.address.as_str(), target.path.as_str()), ("alerts.example.org", "alerts.example.org:443", "/"));
+    }
+}
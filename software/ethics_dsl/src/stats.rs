@@ -0,0 +1,497 @@
+//! Evaluation Statistics - Decision Trends and Audit Export
+//! "Be thou diligent to know the state of thy flocks" - Proverbs 27:23
+//!
+//! The engine counts every evaluation in a statistics window. `EngineStats`
+//! is an immutable snapshot of that window; rotating the window returns the
+//! final snapshot and starts a fresh one. Snapshots can be exported
+//! periodically to an append-only audit log so moral-decision distributions
+//! can be trended over time.
+//!
+//! Decisions are also broken down by the rules that drove them (the
+//! violated principles) and by the moral tags of the event, so policy
+//! owners can see which rules never fire and which over-fire. Both
+//! breakdowns have bounded cardinality: at most `StatsConfig::max_tracked`
+//! keys are counted per window, later keys are folded into `other`, and a
+//! snapshot reports the `top_k` keys with the most refusals plus `other`.
+
+use crate::sinks::DecisionKind;
+use crate::{EthicsDecision, EthicsError, EthicsResult};
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Number of most recent evaluation times kept for percentile estimates
+pub const LATENCY_SAMPLE_WINDOW: usize = 1024;
+
+/// Key that counts folded out of a breakdown are reported under
+pub const OTHER_KEY: &str = "other";
+
+/// Cardinality limits of the per-rule and per-tag breakdowns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsConfig {
+    /// Keys reported in a snapshot, by refusals; the rest are reported as `other`
+    pub top_k: usize,
+    /// Keys counted per window; later keys are counted as `other`
+    pub max_tracked: usize,
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self { top_k: 20, max_tracked: 1000 }
+    }
+}
+
+/// Decisions attributed to one rule or tag
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyedCounts {
+    /// Rule ID or tag, or `other`
+    pub key: String,
+    /// `Allow` decisions
+    pub allow: u64,
+    /// `Deny` decisions
+    pub deny: u64,
+    /// `Purge` decisions
+    pub purge: u64,
+}
+
+impl KeyedCounts {
+    /// `Deny` and `Purge` decisions
+    pub fn refusals(&self) -> u64 {
+        self.deny + self.purge
+    }
+
+    fn add(&mut self, kind: DecisionKind, count: u64) {
+        match kind {
+            DecisionKind::Allow => self.allow += count,
+            DecisionKind::Deny => self.deny += count,
+            DecisionKind::Purge => self.purge += count,
+        }
+    }
+
+    fn merge(&mut self, other: &KeyedCounts) {
+        self.allow += other.allow;
+        self.deny += other.deny;
+        self.purge += other.purge;
+    }
+}
+
+/// Decision counts by key with a bound on the keys tracked
+#[derive(Debug, Default)]
+struct Breakdown {
+    counts: HashMap<String, KeyedCounts>,
+    other: KeyedCounts,
+}
+
+impl Breakdown {
+    fn record(&mut self, key: &str, kind: DecisionKind, max_tracked: usize) {
+        if let Some(counts) = self.counts.get_mut(key) {
+            counts.add(kind, 1);
+        } else if self.counts.len() < max_tracked {
+            let mut counts = KeyedCounts { key: key.to_string(), ..Default::default() };
+            counts.add(kind, 1);
+            self.counts.insert(key.to_string(), counts);
+        } else {
+            self.other.add(kind, 1);
+        }
+    }
+
+    /// The `top_k` keys with the most refusals, then `other` if anything was folded
+    fn top(&self, top_k: usize) -> Vec<KeyedCounts> {
+        let mut ranked: Vec<&KeyedCounts> = self.counts.values().collect();
+        ranked.sort_by(|a, b| {
+            b.refusals().cmp(&a.refusals())
+                .then(b.allow.cmp(&a.allow))
+                .then(a.key.cmp(&b.key))
+        });
+        let mut other = KeyedCounts { key: OTHER_KEY.to_string(), ..self.other.clone() };
+        for counts in ranked.iter().skip(top_k) {
+            other.merge(counts);
+        }
+        let mut top: Vec<KeyedCounts> = ranked.into_iter().take(top_k).cloned().collect();
+        if other.allow + other.refusals() > 0 {
+            top.push(other);
+        }
+        top
+    }
+}
+
+/// Counters for the current statistics window
+#[derive(Debug)]
+pub(crate) struct EvaluationStats {
+    /// Start of the window
+    window_start: DateTime<Utc>,
+    /// Total evaluations performed
+    total_evaluations: u64,
+    /// Decisions by type
+    allow_count: u64,
+    deny_count: u64,
+    purge_count: u64,
+    /// Cache lookups
+    cache_hits: u64,
+    cache_misses: u64,
+    /// Errors encountered
+    error_count: u64,
+    /// Events evaluated without a signed envelope
+    unsigned_count: u64,
+    /// Envelopes rejected by identity verification
+    signature_failure_count: u64,
+    /// Sum of evaluation times (microseconds)
+    total_time_us: u64,
+    /// Most recent evaluation times (microseconds)
+    latencies_us: VecDeque<u64>,
+    /// Decisions by violated principle
+    rules: Breakdown,
+    /// Decisions by moral tag of the event
+    tags: Breakdown,
+    /// Cardinality limits of the breakdowns
+    config: StatsConfig,
+}
+
+impl Default for EvaluationStats {
+    fn default() -> Self {
+        Self::new(StatsConfig::default())
+    }
+}
+
+impl EvaluationStats {
+    /// Empty window with breakdowns bounded by `config`
+    pub(crate) fn new(config: StatsConfig) -> Self {
+        Self {
+            window_start: Utc::now(),
+            total_evaluations: 0,
+            allow_count: 0,
+            deny_count: 0,
+            purge_count: 0,
+            cache_hits: 0,
+            cache_misses: 0,
+            error_count: 0,
+            unsigned_count: 0,
+            signature_failure_count: 0,
+            total_time_us: 0,
+            latencies_us: VecDeque::with_capacity(LATENCY_SAMPLE_WINDOW),
+            rules: Breakdown::default(),
+            tags: Breakdown::default(),
+            config,
+        }
+    }
+
+    /// Record a completed evaluation
+    ///
+    /// The decision is attributed to each principle it names and to each
+    /// of the event's moral `tags`.
+    pub(crate) fn record_decision(&mut self, decision: &EthicsDecision, tags: &[&str], elapsed: Duration) {
+        let kind = DecisionKind::of(decision);
+        match decision {
+            EthicsDecision::Allow { .. } => self.allow_count += 1,
+            EthicsDecision::Deny { .. } => self.deny_count += 1,
+            EthicsDecision::Purge { .. } => self.purge_count += 1,
+        }
+        if let EthicsDecision::Deny { violated_principles, .. }
+        | EthicsDecision::Purge { violated_principles, .. } = decision
+        {
+            for principle in violated_principles {
+                self.rules.record(principle, kind, self.config.max_tracked);
+            }
+        }
+        for tag in tags {
+            self.tags.record(tag, kind, self.config.max_tracked);
+        }
+        self.record_time(elapsed);
+    }
+
+    /// Record a failed evaluation
+    pub(crate) fn record_error(&mut self, elapsed: Duration) {
+        self.error_count += 1;
+        self.record_time(elapsed);
+    }
+
+    /// Record a cache lookup
+    pub(crate) fn record_cache_lookup(&mut self, hit: bool) {
+        if hit {
+            self.cache_hits += 1;
+        } else {
+            self.cache_misses += 1;
+        }
+    }
+
+    /// Record an event evaluated without a signed envelope
+    pub(crate) fn record_unsigned(&mut self) {
+        self.unsigned_count += 1;
+    }
+
+    /// Record an envelope rejected by identity verification
+    pub(crate) fn record_signature_failure(&mut self) {
+        self.signature_failure_count += 1;
+    }
+
+    fn record_time(&mut self, elapsed: Duration) {
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        self.total_evaluations += 1;
+        self.total_time_us = self.total_time_us.saturating_add(micros);
+        if self.latencies_us.len() == LATENCY_SAMPLE_WINDOW {
+            self.latencies_us.pop_front();
+        }
+        self.latencies_us.push_back(micros);
+    }
+
+    /// Snapshot of the current window
+    pub(crate) fn snapshot(&self) -> EngineStats {
+        let lookups = self.cache_hits + self.cache_misses;
+        let mut sorted: Vec<u64> = self.latencies_us.iter().copied().collect();
+        sorted.sort_unstable();
+        let p95 = if sorted.is_empty() {
+            0
+        } else {
+            sorted[((sorted.len() * 95).div_ceil(100)).saturating_sub(1)]
+        };
+
+        EngineStats {
+            window_start: self.window_start,
+            window_end: Utc::now(),
+            total_evaluations: self.total_evaluations,
+            allow_count: self.allow_count,
+            deny_count: self.deny_count,
+            purge_count: self.purge_count,
+            cache_hit_rate: if lookups == 0 { 0.0 } else { self.cache_hits as f64 / lookups as f64 },
+            avg_evaluation_time_us: self.total_time_us.checked_div(self.total_evaluations).unwrap_or(0),
+            p95_evaluation_time_us: p95,
+            error_count: self.error_count,
+            unsigned_count: self.unsigned_count,
+            signature_failure_count: self.signature_failure_count,
+            rules: self.rules.top(self.config.top_k),
+            tags: self.tags.top(self.config.top_k),
+        }
+    }
+
+    /// Close the current window and start a new one
+    pub(crate) fn rotate(&mut self) -> EngineStats {
+        let snapshot = self.snapshot();
+        *self = Self {
+            window_start: snapshot.window_end,
+            ..Self::new(self.config)
+        };
+        snapshot
+    }
+}
+
+/// Snapshot of engine statistics for one window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EngineStats {
+    /// Start of the statistics window
+    pub window_start: DateTime<Utc>,
+    /// Time the snapshot was taken
+    pub window_end: DateTime<Utc>,
+    /// Total evaluations, including failed ones
+    pub total_evaluations: u64,
+    /// `Allow` decisions
+    pub allow_count: u64,
+    /// `Deny` decisions
+    pub deny_count: u64,
+    /// `Purge` decisions
+    pub purge_count: u64,
+    /// Fraction of cache lookups that hit (0.0 to 1.0)
+    pub cache_hit_rate: f64,
+    /// Mean evaluation time (microseconds)
+    pub avg_evaluation_time_us: u64,
+    /// 95th percentile of recent evaluation times (microseconds)
+    pub p95_evaluation_time_us: u64,
+    /// Failed evaluations
+    pub error_count: u64,
+    /// Events evaluated without a signed envelope
+    #[serde(default)]
+    pub unsigned_count: u64,
+    /// Envelopes rejected by identity verification
+    #[serde(default)]
+    pub signature_failure_count: u64,
+    /// Decisions by violated principle, most refusals first, then `other`
+    #[serde(default)]
+    pub rules: Vec<KeyedCounts>,
+    /// Decisions by moral tag of the event, most refusals first, then `other`
+    #[serde(default)]
+    pub tags: Vec<KeyedCounts>,
+}
+
+/// Destination for periodic statistics snapshots
+pub trait StatsExporter: Send {
+    /// Export one snapshot
+    fn export(&mut self, stats: &EngineStats) -> EthicsResult<()>;
+}
+
+/// Appends snapshots as JSON lines to an audit log file
+#[derive(Debug, Clone)]
+pub struct AuditLogExporter {
+    path: PathBuf,
+}
+
+impl AuditLogExporter {
+    /// Create an exporter appending to `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl StatsExporter for AuditLogExporter {
+    fn export(&mut self, stats: &EngineStats) -> EthicsResult<()> {
+        let line = serde_json::to_string(stats)
+            .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize stats: {}", e)))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", self.path.display(), e)))?;
+        writeln!(file, "{}", line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", self.path.display(), e)))
+    }
+}
+
+/// Handle to a background statistics export task
+///
+/// The task exports a final snapshot and stops when the handle is dropped.
+#[derive(Debug)]
+pub struct StatsExportHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StatsExportHandle {
+    pub(crate) fn spawn(
+        stats: Arc<RwLock<EvaluationStats>>,
+        interval: Duration,
+        rotate: bool,
+        mut exporter: Box<dyn StatsExporter>,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::spawn(move || loop {
+            let finished = !matches!(stopped.recv_timeout(interval), Err(RecvTimeoutError::Timeout));
+            let snapshot = match stats.write() {
+                Ok(mut stats) if rotate => stats.rotate(),
+                Ok(stats) => stats.snapshot(),
+                Err(_) => break,
+            };
+            match exporter.export(&snapshot) {
+                Ok(()) => debug!("Exported ethics stats: {} evaluations", snapshot.total_evaluations),
+                Err(e) => warn!("Ethics stats export failed: {}", e),
+            }
+            if finished {
+                break;
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Stop exporting, writing one final snapshot
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for StatsExportHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow() -> EthicsDecision {
+        EthicsDecision::Allow {
+            confidence: 0.9,
+            justification: "righteous".to_string(),
+            scripture_refs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_and_rotate() {
+        let mut stats = EvaluationStats::default();
+        for micros in 1..=100 {
+            stats.record_decision(&allow(), &[], Duration::from_micros(micros));
+        }
+        stats.record_error(Duration::from_micros(50));
+        stats.record_cache_lookup(true);
+        stats.record_cache_lookup(false);
+
+        let snapshot = stats.rotate();
+        assert_eq!(snapshot.total_evaluations, 101);
+        assert_eq!(snapshot.allow_count, 100);
+        assert_eq!(snapshot.error_count, 1);
+        assert_eq!(snapshot.cache_hit_rate, 0.5);
+        // Nearest rank over 101 samples (1..=100 and the error's 50) is the
+        // 96th smallest, which the duplicate 50 makes 95
+        assert_eq!(snapshot.p95_evaluation_time_us, 95);
+
+        let fresh = stats.snapshot();
+        assert_eq!(fresh.total_evaluations, 0);
+        assert_eq!(fresh.window_start, snapshot.window_end);
+    }
+
+    #[test]
+    fn test_rule_and_tag_breakdowns_are_bounded() {
+        let mut stats = EvaluationStats::new(StatsConfig { top_k: 2, max_tracked: 3 });
+        let deny = |principle: &str| EthicsDecision::Deny {
+            confidence: 0.8,
+            violation: "violation".to_string(),
+            violated_principles: vec![principle.to_string()],
+            scripture_refs: vec![],
+        };
+        for (principle, times) in [("TRUTH", 5), ("LIFE", 3), ("CAUTION", 1), ("PURITY", 2)] {
+            for _ in 0..times {
+                stats.record_decision(&deny(principle), &["DECEPTION"], Duration::from_micros(1));
+            }
+        }
+        stats.record_decision(&allow(), &["DECEPTION"], Duration::from_micros(1));
+
+        let snapshot = stats.rotate();
+        let rules: Vec<(&str, u64)> = snapshot.rules.iter().map(|c| (c.key.as_str(), c.deny)).collect();
+        // PURITY arrived after three keys were tracked; CAUTION fell outside the top 2
+        assert_eq!(rules, [("TRUTH", 5), ("LIFE", 3), (OTHER_KEY, 3)]);
+        assert_eq!(snapshot.tags, [KeyedCounts { key: "DECEPTION".to_string(), allow: 1, deny: 11, purge: 0 }]);
+        assert!(stats.snapshot().rules.is_empty());
+        assert_eq!(stats.config.top_k, 2);
+    }
+
+    #[test]
+    fn test_export_writes_final_snapshot_on_stop() {
+        let path = std::env::temp_dir().join(format!("ethics_stats_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let stats = Arc::new(RwLock::new(EvaluationStats::default()));
+        stats.write().unwrap().record_decision(&allow(), &[], Duration::from_micros(10));
+
+        let handle = StatsExportHandle::spawn(
+            stats.clone(),
+            Duration::from_secs(3600),
+            true,
+            Box::new(AuditLogExporter::new(&path)),
+        );
+        handle.stop();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let exported: EngineStats = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(exported.allow_count, 1);
+        assert_eq!(stats.read().unwrap().snapshot().total_evaluations, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
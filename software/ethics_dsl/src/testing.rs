@@ -0,0 +1,372 @@
+//! Rule Pack Tests - Expected Decisions Shipped With the Pack
+//! "Examine yourselves, whether ye be in the faith; prove your own selves" - 2 Corinthians 13:5
+//!
+//! A rule pack carries its own test cases in `tests`: an event fixture and
+//! the decision expected for it, optionally with the rule expected to take
+//! it. `run_tests` decides every fixture with the predicate interpreter,
+//! like `cargo test` for rule authors. A failing case reports a diff of the
+//! expected and actual outcome together with a trace of the rules tried
+//! before the decision and the first condition of each that did not hold.
+//! The report also counts which rules the test corpus exercised, so rules
+//! no case ever reaches stand out.
+//!
+//! A DSL pack, the `.ethics` text the engine loads, ships its cases in a
+//! JSON-lines file next to it (see `load_tests`). `run_engine_tests`
+//! decides them with an engine holding that pack. The engine does not
+//! attribute decisions to rules, so such a report has no traces or rule
+//! coverage, and a case naming `expect_rule` is refused rather than
+//! passed unchecked.
+
+use crate::embedded::{PackDecision, PackOutcome, RulePack};
+use crate::predicates::PredicateRegistry;
+use crate::{EthicsError, EthicsEvaluator, EthicsEvent, EthicsResult};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Most test cases in a pack
+pub const MAX_PACK_TESTS: usize = 4096;
+
+/// One test case of a rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackTest {
+    /// Test name, reported with its result
+    pub name: String,
+    /// Event fixture to decide
+    pub event: EthicsEvent,
+    /// Decision the pack must take
+    pub expect: PackDecision,
+    /// Rule that must take it; `None` accepts any rule or the default
+    #[serde(default)]
+    pub expect_rule: Option<String>,
+}
+
+impl Validate for PackTest {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("test name", &self.name, 128)?;
+        if let Some(rule) = &self.expect_rule {
+            decode::check_identifier("expect_rule", rule, 128)?;
+        }
+        Ok(())
+    }
+}
+
+/// How far a rule got while deciding an event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleTrace {
+    /// Rule name
+    pub rule: String,
+    /// First condition that did not hold; `None` when the rule matched
+    pub failed_condition: Option<String>,
+}
+
+/// Result of one test case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseResult {
+    /// Test name
+    pub name: String,
+    /// Whether the pack decided as expected
+    pub passed: bool,
+    /// Expected decision
+    pub expected: PackDecision,
+    /// Expected rule, if the test names one
+    pub expected_rule: Option<String>,
+    /// What the pack decided
+    pub actual: PackOutcome,
+    /// Rules tried, in order, up to the one that matched
+    pub trace: Vec<RuleTrace>,
+}
+
+impl CaseResult {
+    /// Expected and actual outcome as a diff, with the rule trace
+    pub fn diff(&self) -> String {
+        let by = |rule: Option<&String>| match rule {
+            Some(rule) => format!("rule {}", rule),
+            None => "default".to_string(),
+        };
+        let expected_by = self.expected_rule.as_ref().map_or_else(|| "any rule".to_string(), |rule| by(Some(rule)));
+        let mut diff = format!(
+            "- {:?} by {}\n+ {:?} by {}\n",
+            self.expected,
+            expected_by,
+            self.actual.decision,
+            by(self.actual.rule.as_ref())
+        );
+        for step in &self.trace {
+            match &step.failed_condition {
+                Some(condition) => diff.push_str(&format!("  rule {}: `{}` did not hold\n", step.rule, condition)),
+                None => diff.push_str(&format!("  rule {}: matched\n", step.rule)),
+            }
+        }
+        diff
+    }
+}
+
+/// How often a rule decided a test fixture
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleCoverage {
+    /// Rule name
+    pub rule: String,
+    /// Fixtures the rule decided
+    pub hits: usize,
+}
+
+/// Results and rule coverage of a pack's tests
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReport {
+    /// Every case, in pack order
+    pub cases: Vec<CaseResult>,
+    /// Every rule, in pack order, with the fixtures it decided
+    pub coverage: Vec<RuleCoverage>,
+    /// Fixtures no rule matched, decided by the default
+    pub default_hits: usize,
+}
+
+impl TestReport {
+    /// Cases that passed
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.passed).count()
+    }
+
+    /// Cases that failed
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.cases.iter().filter(|case| !case.passed)
+    }
+
+    /// Whether every case passed
+    pub fn is_success(&self) -> bool {
+        self.cases.iter().all(|case| case.passed)
+    }
+
+    /// Rules no fixture reached
+    pub fn uncovered_rules(&self) -> impl Iterator<Item = &str> {
+        self.coverage.iter().filter(|rule| rule.hits == 0).map(|rule| rule.rule.as_str())
+    }
+
+    /// Share of rules exercised by at least one fixture
+    pub fn rule_coverage(&self) -> f64 {
+        if self.coverage.is_empty() {
+            return 1.0;
+        }
+        let covered = self.coverage.iter().filter(|rule| rule.hits > 0).count();
+        covered as f64 / self.coverage.len() as f64
+    }
+}
+
+impl fmt::Display for TestReport {
+    /// Test output in the style of `cargo test`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "running {} tests", self.cases.len())?;
+        for case in &self.cases {
+            writeln!(f, "test {} ... {}", case.name, if case.passed { "ok" } else { "FAILED" })?;
+        }
+        if !self.is_success() {
+            writeln!(f, "\nfailures:")?;
+            for case in self.failures() {
+                write!(f, "\n---- {} ----\n{}", case.name, case.diff())?;
+            }
+        }
+        let failed = self.cases.len() - self.passed();
+        writeln!(
+            f,
+            "\ntest result: {}. {} passed; {} failed",
+            if failed == 0 { "ok" } else { "FAILED" },
+            self.passed(),
+            failed
+        )?;
+        // Reports from the engine carry no rule attribution to cover
+        if self.coverage.is_empty() {
+            return Ok(());
+        }
+        let covered = self.coverage.iter().filter(|rule| rule.hits > 0).count();
+        writeln!(
+            f,
+            "rule coverage: {}/{} rules exercised ({:.1}%), default decided {}",
+            covered,
+            self.coverage.len(),
+            self.rule_coverage() * 100.0,
+            self.default_hits
+        )?;
+        let uncovered: Vec<&str> = self.uncovered_rules().collect();
+        if !uncovered.is_empty() {
+            writeln!(f, "never exercised: {}", uncovered.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decide `event`, recording how far each rule got
+pub fn trace(pack: &RulePack, registry: &PredicateRegistry, event: &EthicsEvent) -> EthicsResult<(PackOutcome, Vec<RuleTrace>)> {
+    let mut trace = Vec::new();
+    for rule in &pack.rules {
+        let mut failed_condition = None;
+        for call in &rule.when {
+            if !registry.evaluate(call, event)? {
+                failed_condition = Some(call.clone());
+                break;
+            }
+        }
+        let matched = failed_condition.is_none();
+        trace.push(RuleTrace { rule: rule.name.clone(), failed_condition });
+        if matched {
+            return Ok((PackOutcome { rule: Some(rule.name.clone()), decision: rule.decision }, trace));
+        }
+    }
+    Ok((PackOutcome { rule: None, decision: pack.default }, trace))
+}
+
+/// Run every test case shipped with `pack`
+pub fn run_tests(pack: &RulePack, registry: &PredicateRegistry) -> EthicsResult<TestReport> {
+    let mut report = TestReport {
+        coverage: pack.rules.iter().map(|rule| RuleCoverage { rule: rule.name.clone(), hits: 0 }).collect(),
+        ..Default::default()
+    };
+    for test in &pack.tests {
+        let (actual, trace) = trace(pack, registry, &test.event)?;
+        match actual.rule.as_ref().and_then(|rule| report.coverage.iter_mut().find(|c| &c.rule == rule)) {
+            Some(coverage) => coverage.hits += 1,
+            None => report.default_hits += 1,
+        }
+        let passed = actual.decision == test.expect
+            && test.expect_rule.as_ref().is_none_or(|rule| actual.rule.as_ref() == Some(rule));
+        report.cases.push(CaseResult {
+            name: test.name.clone(),
+            passed,
+            expected: test.expect,
+            expected_rule: test.expect_rule.clone(),
+            actual,
+            trace,
+        });
+    }
+    Ok(report)
+}
+
+/// Test cases file shipped next to the DSL pack at `pack`: `live.ethics` has `live.tests.jsonl`
+pub fn tests_path(pack: &Path) -> PathBuf {
+    pack.with_extension("tests.jsonl")
+}
+
+/// Load test cases, one JSON `PackTest` per line
+pub fn load_tests(path: &Path) -> EthicsResult<Vec<PackTest>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+    let mut tests = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let test: PackTest = decode::json_validated(line.as_bytes(), &DecodeLimits::RECORD)
+            .map_err(|e| EthicsError::ConfigurationError(format!("{}:{}: {}", path.display(), line_number + 1, e)))?;
+        tests.push(test);
+        decode::check_count("tests", tests.len(), MAX_PACK_TESTS).map_err(EthicsError::ConfigurationError)?;
+    }
+    Ok(tests)
+}
+
+/// Decide every test case with `engine`, which holds the DSL pack under test
+pub fn run_engine_tests(engine: &dyn EthicsEvaluator, tests: &[PackTest]) -> EthicsResult<TestReport> {
+    if let Some(test) = tests.iter().find(|test| test.expect_rule.is_some()) {
+        return Err(EthicsError::ConfigurationError(format!(
+            "Test {} expects a rule, but the engine does not report which rule decided",
+            test.name
+        )));
+    }
+    let mut report = TestReport::default();
+    for test in tests {
+        let actual = PackOutcome { rule: None, decision: PackDecision::from(&engine.evaluate(&test.event)?) };
+        report.cases.push(CaseResult {
+            name: test.name.clone(),
+            passed: actual.decision == test.expect,
+            expected: test.expect,
+            expected_rule: None,
+            actual,
+            trace: Vec::new(),
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedded::PackRule;
+    use crate::{Actor, ActorType, Content, ContentType, Context, UrgencyLevel};
+    use std::collections::HashMap;
+
+    fn event(trust_level: f64) -> EthicsEvent {
+        EthicsEvent {
+            event_id: "fixture".into(),
+            actor: Actor {
+                actor_type: ActorType::Person,
+                tags: Vec::new(),
+                trust_level,
+                history: None,
+            },
+            content: Some(Content {
+                content_type: ContentType::Text,
+                data: String::new(),
+                metadata: HashMap::new(),
+                content_hash: String::new(),
+            }),
+            context: Context { location: None, culture: None, platform: None, audience: None, urgency: UrgencyLevel::Normal },
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_failures_diff_and_coverage_names_unreached_rules() {
+        let test = |name: &str, event, expect, expect_rule: Option<&str>| PackTest {
+            name: name.into(),
+            event,
+            expect,
+            expect_rule: expect_rule.map(str::to_string),
+        };
+        let pack = RulePack {
+            rules: vec![
+                PackRule { name: "untrusted".into(), when: vec!["actor.trust_below(0.3)".into()], decision: PackDecision::Deny },
+                PackRule { name: "minors".into(), when: vec!["audience.contains_minors()".into()], decision: PackDecision::Purge },
+            ],
+            default: PackDecision::Allow,
+            tests: vec![
+                test("untrusted_denied", event(0.1), PackDecision::Deny, Some("untrusted")),
+                test("trusted_allowed", event(0.9), PackDecision::Allow, None),
+                test("trusted_denied", event(0.9), PackDecision::Deny, None),
+            ],
+        };
+
+        let report = run_tests(&pack, &PredicateRegistry::standard()).unwrap();
+        assert_eq!(report.passed(), 2);
+        assert!(!report.is_success());
+        let failure = report.failures().next().unwrap();
+        assert_eq!(failure.name, "trusted_denied");
+        assert_eq!(failure.diff(), "- Deny by any rule\n+ Allow by default\n  rule untrusted: `actor.trust_below(0.3)` did not hold\n  rule minors: `audience.contains_minors()` did not hold\n");
+
+        assert_eq!(report.coverage[0].hits, 1);
+        assert_eq!(report.default_hits, 2);
+        assert_eq!(report.uncovered_rules().collect::<Vec<_>>(), ["minors"]);
+        let output = report.to_string();
+        assert!(output.contains("test trusted_denied ... FAILED"));
+        assert!(output.contains("rule coverage: 1/2 rules exercised (50.0%)"));
+    }
+
+    #[test]
+    fn test_dsl_pack_cases_load_from_sibling_file() {
+        assert_eq!(tests_path(Path::new("rules/live.ethics")), Path::new("rules/live.tests.jsonl"));
+
+        let path = std::env::temp_dir().join(format!("ark-pack-tests-{}.tests.jsonl", std::process::id()));
+        let case = PackTest { name: "trusted".into(), event: event(0.9), expect: PackDecision::Allow, expect_rule: None };
+        std::fs::write(&path, format!("{}\n\n", serde_json::to_string(&case).unwrap())).unwrap();
+        let tests = load_tests(&path).unwrap();
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].name, "trusted");
+
+        // A malformed case fails the load rather than being skipped
+        std::fs::write(&path, format!("{}\n{{\"name\": \"broken\"}}\n", serde_json::to_string(&case).unwrap())).unwrap();
+        let error = load_tests(&path).unwrap_err().to_string();
+        assert!(error.contains(":2:"), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
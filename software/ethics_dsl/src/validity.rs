@@ -0,0 +1,351 @@
+//! Decision Validity - Lifetimes and Revocation of Allow Decisions
+//! "The grass withereth, the flower fadeth: but the word of our God shall stand for ever" - Isaiah 40:8
+//!
+//! An Allow reflects what was known when it was issued. Decisions issued
+//! through `EthicsEngine::evaluate_issued` carry an expiry and a revocation
+//! ID; consumers holding one ask `is_decision_valid` before acting on it.
+//! Allows expire after `allow_ttl`; Deny and Purge only expire when
+//! `deny_ttl` is set. Operators, and the engine itself when the AGI attack
+//! detector blocks content, add entries to the revocation list: revoking a
+//! decision ID invalidates that decision, revoking an actor or content hash
+//! invalidates every Allow issued for it before the revocation. Denials are
+//! never revoked. With `revocation_list` set, revocations are appended to a
+//! JSON-lines file and survive restarts.
+
+use crate::{EthicsDecision, EthicsError, EthicsEvent, EthicsResult};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Decision lifetimes and the revocation list file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValidityConfig {
+    /// How long an Allow stays valid (seconds)
+    pub allow_ttl_secs: u64,
+    /// How long a Deny or Purge stays valid (seconds); `None` never expires
+    pub deny_ttl_secs: Option<u64>,
+    /// JSON-lines file the revocation list is kept in
+    pub revocation_list: Option<PathBuf>,
+}
+
+impl Default for ValidityConfig {
+    fn default() -> Self {
+        Self {
+            allow_ttl_secs: 24 * 3600,
+            deny_ttl_secs: None,
+            revocation_list: None,
+        }
+    }
+}
+
+impl ValidityConfig {
+    /// Expiry of `decision` issued at `issued_at`
+    pub fn expiry(&self, decision: &EthicsDecision, issued_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let ttl = match decision {
+            EthicsDecision::Allow { .. } => Some(self.allow_ttl_secs),
+            _ => self.deny_ttl_secs,
+        }?;
+        let ttl = chrono::Duration::seconds(i64::try_from(ttl).unwrap_or(i64::MAX));
+        issued_at.checked_add_signed(ttl)
+    }
+}
+
+/// Decision with its lifetime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedDecision {
+    /// Decision taken
+    pub decision: EthicsDecision,
+    /// ID under which the decision can be revoked
+    pub revocation_id: String,
+    /// Evaluated event
+    pub event_id: String,
+    /// Actor the decision was issued for, if identified
+    pub actor: Option<String>,
+    /// Hash of the evaluated content
+    pub content_hash: Option<String>,
+    /// When the decision was issued
+    pub issued_at: DateTime<Utc>,
+    /// When the decision expires; `None` never
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl IssuedDecision {
+    /// Issue `decision` on `event` under `config`
+    pub fn issue(event: &EthicsEvent, decision: EthicsDecision, actor: Option<&str>, config: &ValidityConfig) -> Self {
+        static ISSUED: AtomicU64 = AtomicU64::new(0);
+
+        let issued_at = Utc::now();
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(event.event_id.as_bytes());
+        hasher.update(&issued_at.timestamp_nanos_opt().unwrap_or_default().to_le_bytes());
+        hasher.update(&ISSUED.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+        hasher.update(&std::process::id().to_le_bytes());
+
+        Self {
+            expires_at: config.expiry(&decision, issued_at),
+            decision,
+            revocation_id: hasher.finalize().to_hex()[..32].to_string(),
+            event_id: event.event_id.clone(),
+            actor: actor.map(str::to_string),
+            content_hash: event.content.as_ref().map(|c| c.content_hash.clone()).filter(|h| !h.is_empty()),
+            issued_at,
+        }
+    }
+
+    /// Whether the decision is an Allow
+    pub fn is_allow(&self) -> bool {
+        matches!(self.decision, EthicsDecision::Allow { .. })
+    }
+}
+
+/// What a revocation invalidates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum RevocationTarget {
+    /// One decision, by revocation ID
+    Decision(String),
+    /// Every earlier Allow issued for the actor
+    Actor(String),
+    /// Every earlier Allow issued for the content hash
+    ContentHash(String),
+}
+
+/// Who revoked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationSource {
+    /// Named operator
+    Operator(String),
+    /// The engine's AGI attack detector
+    AgiDetector,
+}
+
+/// Entry of the revocation list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revocation {
+    /// What is revoked
+    pub target: RevocationTarget,
+    /// Who revoked it
+    pub source: RevocationSource,
+    /// Why
+    pub reason: String,
+    /// When; Allows issued later are unaffected
+    pub revoked_at: DateTime<Utc>,
+}
+
+impl Revocation {
+    /// Whether this revocation invalidates `issued`
+    pub fn applies_to(&self, issued: &IssuedDecision) -> bool {
+        if !issued.is_allow() {
+            return false;
+        }
+        match &self.target {
+            RevocationTarget::Decision(id) => *id == issued.revocation_id,
+            RevocationTarget::Actor(actor) => {
+                issued.actor.as_ref() == Some(actor) && issued.issued_at < self.revoked_at
+            }
+            RevocationTarget::ContentHash(hash) => {
+                issued.content_hash.as_ref() == Some(hash) && issued.issued_at < self.revoked_at
+            }
+        }
+    }
+}
+
+/// Validity of an issued decision
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionStatus {
+    /// The decision may be acted on
+    Valid,
+    /// The decision's lifetime is over
+    Expired,
+    /// The decision was revoked
+    Revoked(Revocation),
+}
+
+/// Shared, optionally persisted revocation list
+#[derive(Debug, Clone, Default)]
+pub struct RevocationList {
+    revocations: Arc<RwLock<Vec<Revocation>>>,
+    file: Option<Arc<std::sync::Mutex<File>>>,
+    path: Option<PathBuf>,
+}
+
+impl RevocationList {
+    /// Empty in-memory list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the list kept in `path` and append new revocations to it
+    ///
+    /// Lines that fail to parse are skipped with a warning.
+    pub fn open(path: impl Into<PathBuf>) -> EthicsResult<Self> {
+        let path = path.into();
+        let io_error = |e: std::io::Error| EthicsError::ConfigurationError(format!("{}: {}", path.display(), e));
+
+        let mut revocations = Vec::new();
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path).map_err(io_error)?);
+            for (line_number, line) in reader.lines().enumerate() {
+                let line = line.map_err(io_error)?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match decode::json::<Revocation>(line.as_bytes(), &DecodeLimits::RECORD) {
+                    Ok(revocation) => revocations.push(revocation),
+                    Err(e) => warn!("Skipping revocation line {} of {}: {}", line_number + 1, path.display(), e),
+                }
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path).map_err(io_error)?;
+
+        Ok(Self {
+            revocations: Arc::new(RwLock::new(revocations)),
+            file: Some(Arc::new(std::sync::Mutex::new(file))),
+            path: Some(path),
+        })
+    }
+
+    /// Revoke `target`, persisting the entry first when the list has a file
+    pub fn revoke(&self, target: RevocationTarget, source: RevocationSource, reason: &str) -> EthicsResult<Revocation> {
+        let revocation = Revocation {
+            target,
+            source,
+            reason: reason.to_string(),
+            revoked_at: Utc::now(),
+        };
+
+        if let (Some(file), Some(path)) = (&self.file, &self.path) {
+            let mut line = serde_json::to_vec(&revocation)
+                .map_err(|e| EthicsError::RuntimeError(format!("Failed to serialize revocation: {}", e)))?;
+            line.push(b'\n');
+            let mut file = file.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            file.write_all(&line)
+                .map_err(|e| EthicsError::RuntimeError(format!("{}: {}", path.display(), e)))?;
+        }
+
+        info!("Revoked {:?} ({:?}): {}", revocation.target, revocation.source, revocation.reason);
+        self.write().push(revocation.clone());
+        Ok(revocation)
+    }
+
+    /// Every revocation, oldest first
+    pub fn revocations(&self) -> Vec<Revocation> {
+        self.read().clone()
+    }
+
+    /// Validity of `issued` at `now`
+    pub fn status(&self, issued: &IssuedDecision, now: DateTime<Utc>) -> DecisionStatus {
+        if let Some(revocation) = self.read().iter().find(|revocation| revocation.applies_to(issued)) {
+            return DecisionStatus::Revoked(revocation.clone());
+        }
+        match issued.expires_at {
+            Some(expires_at) if now >= expires_at => DecisionStatus::Expired,
+            _ => DecisionStatus::Valid,
+        }
+    }
+
+    /// Whether `issued` may still be acted on
+    pub fn is_decision_valid(&self, issued: &IssuedDecision) -> bool {
+        self.status(issued, Utc::now()) == DecisionStatus::Valid
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Vec<Revocation>> {
+        self.revocations.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Vec<Revocation>> {
+        self.revocations.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Actor, ActorType, Content, ContentType, Context, UrgencyLevel};
+
+    fn event(content_hash: &str) -> EthicsEvent {
+        crate::utils::create_event(
+            "evt".to_string(),
+            Actor {
+                actor_type: ActorType::Person,
+                tags: vec![],
+                trust_level: 0.5,
+                history: None,
+            },
+            Some(Content {
+                content_type: ContentType::Text,
+                data: String::new(),
+                metadata: Default::default(),
+                content_hash: content_hash.to_string(),
+            }),
+            Context {
+                location: None,
+                culture: None,
+                platform: None,
+                audience: None,
+                urgency: UrgencyLevel::Normal,
+            },
+        )
+    }
+
+    fn allow() -> EthicsDecision {
+        EthicsDecision::Allow {
+            confidence: 0.9,
+            justification: String::new(),
+            scripture_refs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_allow_expires_and_deny_persists() {
+        let config = ValidityConfig { allow_ttl_secs: 60, ..Default::default() };
+        let list = RevocationList::new();
+        let issued = IssuedDecision::issue(&event("h1"), allow(), Some("alice"), &config);
+        assert!(list.is_decision_valid(&issued));
+        assert_eq!(list.status(&issued, issued.issued_at + chrono::Duration::seconds(61)), DecisionStatus::Expired);
+
+        let deny = EthicsDecision::Deny {
+            confidence: 0.9,
+            violation: String::new(),
+            violated_principles: vec![],
+            scripture_refs: vec![],
+        };
+        let denied = IssuedDecision::issue(&event("h1"), deny, Some("alice"), &config);
+        assert_eq!(denied.expires_at, None);
+        list.revoke(RevocationTarget::Actor("alice".into()), RevocationSource::AgiDetector, "attack").unwrap();
+        assert!(list.is_decision_valid(&denied), "denials are never revoked");
+        assert_ne!(issued.revocation_id, denied.revocation_id);
+    }
+
+    #[test]
+    fn test_revocations_cover_earlier_allows_and_persist() {
+        let path = std::env::temp_dir().join(format!("ethics_revocations_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = ValidityConfig::default();
+
+        let list = RevocationList::open(&path).unwrap();
+        let first = IssuedDecision::issue(&event("h1"), allow(), Some("alice"), &config);
+        let other = IssuedDecision::issue(&event("h2"), allow(), Some("bob"), &config);
+        list.revoke(RevocationTarget::ContentHash("h1".into()), RevocationSource::Operator("ops".into()), "reported").unwrap();
+        list.revoke(RevocationTarget::Decision(other.revocation_id.clone()), RevocationSource::Operator("ops".into()), "mistake").unwrap();
+
+        // An Allow issued after the revocation is a fresh judgement
+        let later = IssuedDecision::issue(&event("h1"), allow(), Some("alice"), &config);
+        assert!(!list.is_decision_valid(&first));
+        assert!(!list.is_decision_valid(&other));
+        assert!(list.is_decision_valid(&later));
+
+        let reopened = RevocationList::open(&path).unwrap();
+        assert_eq!(reopened.revocations(), list.revocations());
+        assert!(matches!(reopened.status(&first, Utc::now()), DecisionStatus::Revoked(r) if r.reason == "reported"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,364 @@
+//! Sentinel Access Control - Ethics-Backed Connection Policy
+//! "Open the gates, that the righteous nation which keepeth the truth may enter in" - Isaiah 26:2
+//!
+//! After negotiation the client names the service it wants. The authenticated
+//! peer identity and the requested service are turned into an `EthicsEvent`
+//! and judged by a policy engine (an embedded `EthicsEngine` or a remote
+//! evaluator). `Deny` and `Purge` close the connection. When no engine is
+//! configured, or the engine fails, a static ACL decides instead.
+//!
+//! A peer is only who its keys say it is. Over sealed records the client
+//! proves its identity keys during the handshake, and the name it claims
+//! stands only if it is pinned to those keys. A name pinned to other keys
+//! is refused and alerted as a possible impersonation; an unpinned one
+//! leaves the key fingerprint as the peer's identity. Connections without a key exchange prove
+//! nothing and are all judged as `UNAUTHENTICATED_PEER`.
+
+use std::sync::Arc;
+
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::transport::PeerAddr;
+use crate::SentinelError;
+use ark_alert::{Alert, Severity};
+use pq_types::pins::{KeyFingerprint, PinCheck, PinStore};
+use ethics_dsl::{
+    Actor, ActorType, Content, ContentType, Context, EthicsDecision, EthicsEvaluator, EthicsEvent,
+    UrgencyLevel,
+};
+
+/// Wildcard matching any peer or service in an ACL rule
+pub const ACL_WILDCARD: &str = "*";
+
+/// Identity of every peer that proved no keys, whatever name it claims
+pub const UNAUTHENTICATED_PEER: &str = "anonymous";
+
+/// Authenticated identity of a connecting peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    /// Pinned name or key fingerprint the peer proved, or `UNAUTHENTICATED_PEER`
+    pub id: String,
+    /// Remote address on the transport the peer connected over
+    pub addr: PeerAddr,
+}
+
+impl PeerIdentity {
+    /// Peer that proved it holds the identity keys of `fingerprint`
+    ///
+    /// Identified by the name it claims when that name is pinned to those
+    /// keys in `pins`, by the fingerprint when the name is not pinned.
+    /// Claiming a name pinned to other keys is refused.
+    pub fn authenticated(
+        claimed: &str,
+        fingerprint: KeyFingerprint,
+        pins: &PinStore,
+        addr: PeerAddr,
+    ) -> Result<Self, SentinelError> {
+        let id = match pins.check(claimed, fingerprint) {
+            PinCheck::Match => claimed.to_string(),
+            PinCheck::Unpinned => fingerprint.to_string(),
+            PinCheck::Changed { pinned, presented } => {
+                tracing::error!("ALERT: {} claimed pinned peer {} with key {} instead of {}", addr, claimed, presented, pinned);
+                ark_alert::raise(
+                    Alert::new("network_sentinel", "key_pin_changed", claimed, Severity::Critical, "peer key fingerprint differs from its pin")
+                        .with_detail("pinned", pinned)
+                        .with_detail("presented", presented),
+                );
+                return Err(SentinelError::AccessDenied(format!("{} is pinned to other keys", claimed)));
+            }
+        };
+        Ok(Self { id, addr })
+    }
+
+    /// Peer that proved no keys
+    pub fn unauthenticated(addr: PeerAddr) -> Self {
+        Self { id: UNAUTHENTICATED_PEER.to_string(), addr }
+    }
+}
+
+/// Source of policy decisions for connection requests
+pub trait PolicyEngine: Send + Sync {
+    /// Judge a connection request event
+    fn decide(&self, event: &EthicsEvent) -> Result<EthicsDecision, String>;
+}
+
+impl<T> PolicyEngine for T
+where
+    T: EthicsEvaluator + Send + Sync,
+{
+    fn decide(&self, event: &EthicsEvent) -> Result<EthicsDecision, String> {
+        self.evaluate(event).map_err(|e| e.to_string())
+    }
+}
+
+/// Single static ACL entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AclRule {
+    /// Peer identifier, or `*` for any peer
+    pub peer: String,
+    /// Service name, or `*` for any service
+    pub service: String,
+    /// Whether matching requests are allowed
+    pub allow: bool,
+}
+
+impl AclRule {
+    fn matches(&self, peer: &str, service: &str) -> bool {
+        (self.peer == ACL_WILDCARD || self.peer == peer)
+            && (self.service == ACL_WILDCARD || self.service == service)
+    }
+}
+
+/// Static ACL used when the policy engine is unavailable
+///
+/// Rules are checked in order and the first match wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticAcl {
+    /// Ordered rules
+    pub rules: Vec<AclRule>,
+    /// Decision when no rule matches
+    pub default_allow: bool,
+}
+
+impl StaticAcl {
+    /// Whether the peer may use the service
+    pub fn permits(&self, peer: &str, service: &str) -> bool {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(peer, service))
+            .map(|rule| rule.allow)
+            .unwrap_or(self.default_allow)
+    }
+}
+
+/// Most rules accepted in a static ACL file
+pub const MAX_ACL_RULES: usize = 10_000;
+
+impl Validate for StaticAcl {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("rules", self.rules.len(), MAX_ACL_RULES)?;
+        for rule in &self.rules {
+            decode::check_identifier("rule peer", &rule.peer, crate::protocol::MAX_NAME_LENGTH)?;
+            decode::check_identifier("rule service", &rule.service, crate::protocol::MAX_NAME_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which policy source produced a decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecisionSource {
+    /// Ethics policy engine
+    Engine,
+    /// Static ACL fallback
+    StaticAcl,
+}
+
+/// Outcome of authorizing a connection request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDecision {
+    /// Whether the connection may proceed
+    pub granted: bool,
+    /// Human-readable reason
+    pub reason: String,
+    /// Policy source that decided
+    pub source: DecisionSource,
+}
+
+/// Authorization layer for sentinel connections
+#[derive(Clone, Default)]
+pub struct Authorizer {
+    engine: Option<Arc<dyn PolicyEngine>>,
+    fallback: StaticAcl,
+}
+
+impl Authorizer {
+    /// Create an authorizer backed by a policy engine
+    pub fn new(engine: Arc<dyn PolicyEngine>, fallback: StaticAcl) -> Self {
+        Self {
+            engine: Some(engine),
+            fallback,
+        }
+    }
+
+    /// Create an authorizer that only consults the static ACL
+    pub fn static_only(fallback: StaticAcl) -> Self {
+        Self {
+            engine: None,
+            fallback,
+        }
+    }
+
+    /// Static ACL used as fallback
+    pub fn fallback(&self) -> &StaticAcl {
+        &self.fallback
+    }
+
+    /// The same policy engine with another static ACL
+    pub fn with_fallback(&self, fallback: StaticAcl) -> Self {
+        Self {
+            engine: self.engine.clone(),
+            fallback,
+        }
+    }
+
+    /// Decide whether a peer may use a service
+    pub fn authorize(&self, peer: &PeerIdentity, service: &str) -> AccessDecision {
+        if let Some(engine) = &self.engine {
+            match engine.decide(&access_event(peer, service)) {
+                Ok(decision) => return engine_decision(decision),
+                Err(e) => warn!("Policy engine unavailable for {} -> {}: {}; using static ACL", peer.id, service, e),
+            }
+        }
+
+        let granted = self.fallback.permits(&peer.id, service);
+        debug!("Static ACL {} {} -> {}", if granted { "allows" } else { "denies" }, peer.id, service);
+        AccessDecision {
+            granted,
+            reason: if granted { "Allowed by static ACL" } else { "Denied by static ACL" }.to_string(),
+            source: DecisionSource::StaticAcl,
+        }
+    }
+}
+
+impl std::fmt::Debug for Authorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authorizer")
+            .field("engine", &self.engine.is_some())
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+/// Convert a connection request into an ethics event
+pub fn access_event(peer: &PeerIdentity, service: &str) -> EthicsEvent {
+    let request = format!("{} requests service {}", peer.id, service);
+
+    ethics_dsl::utils::create_event(
+        format!("sentinel-access-{}-{}", peer.addr, service),
+        Actor {
+            actor_type: ActorType::Person,
+            tags: vec![format!("peer:{}", peer.id), format!("service:{}", service)],
+            trust_level: 0.5,
+            history: None,
+        },
+        Some(Content {
+            content_type: ContentType::Text,
+            content_hash: blake3::hash(request.as_bytes()).to_hex().to_string(),
+            data: request,
+            metadata: Default::default(),
+        }),
+        Context {
+            location: Some(peer.addr.to_string()),
+            culture: None,
+            platform: Some("network_sentinel".to_string()),
+            audience: None,
+            urgency: UrgencyLevel::Normal,
+        },
+    )
+}
+
+fn engine_decision(decision: EthicsDecision) -> AccessDecision {
+    let (granted, reason) = match decision {
+        EthicsDecision::Allow { justification, .. } => (true, justification),
+        EthicsDecision::Deny { violation, .. } => (false, violation),
+        EthicsDecision::Purge { reason, .. } => (false, reason),
+    };
+    AccessDecision {
+        granted,
+        reason,
+        source: DecisionSource::Engine,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedEngine(Result<bool, String>);
+
+    impl PolicyEngine for FixedEngine {
+        fn decide(&self, _event: &EthicsEvent) -> Result<EthicsDecision, String> {
+            match &self.0 {
+                Ok(true) => Ok(EthicsDecision::Allow {
+                    confidence: 1.0,
+                    justification: "righteous".to_string(),
+                    scripture_refs: Vec::new(),
+                }),
+                Ok(false) => Ok(EthicsDecision::Deny {
+                    confidence: 1.0,
+                    violation: "forbidden service".to_string(),
+                    violated_principles: Vec::new(),
+                    scripture_refs: Vec::new(),
+                }),
+                Err(e) => Err(e.clone()),
+            }
+        }
+    }
+
+    fn peer(id: &str) -> PeerIdentity {
+        PeerIdentity {
+            id: id.to_string(),
+            addr: "10.0.0.7:40000".parse().unwrap(),
+        }
+    }
+
+    fn acl() -> StaticAcl {
+        StaticAcl {
+            rules: vec![
+                AclRule { peer: "mallory".into(), service: ACL_WILDCARD.into(), allow: false },
+                AclRule { peer: ACL_WILDCARD.into(), service: "echo".into(), allow: true },
+            ],
+            default_allow: false,
+        }
+    }
+
+    #[test]
+    fn test_static_acl_first_match_wins() {
+        let acl = acl();
+        assert!(acl.permits("alice", "echo"));
+        assert!(!acl.permits("mallory", "echo"));
+        assert!(!acl.permits("alice", "relay"));
+    }
+
+    #[test]
+    fn test_engine_decision_overrides_acl() {
+        let authorizer = Authorizer::new(Arc::new(FixedEngine(Ok(false))), acl());
+        let decision = authorizer.authorize(&peer("alice"), "echo");
+
+        assert!(!decision.granted);
+        assert_eq!(decision.reason, "forbidden service");
+        assert_eq!(decision.source, DecisionSource::Engine);
+    }
+
+    #[test]
+    fn test_engine_failure_falls_back_to_acl() {
+        let authorizer = Authorizer::new(Arc::new(FixedEngine(Err("offline".into()))), acl());
+
+        let allowed = authorizer.authorize(&peer("alice"), "echo");
+        assert!(allowed.granted);
+        assert_eq!(allowed.source, DecisionSource::StaticAcl);
+
+        assert!(!authorizer.authorize(&peer("mallory"), "echo").granted);
+    }
+
+    #[test]
+    fn test_identity_comes_from_proven_keys() {
+        let addr: PeerAddr = "10.0.0.7:40000".parse().unwrap();
+        let alice = KeyFingerprint::hybrid(&[1u8; 32], &[1u8; 1952]);
+        let mallory = KeyFingerprint::hybrid(&[2u8; 32], &[2u8; 1952]);
+        let mut pins = PinStore::in_memory();
+        pins.pin("alice", alice).unwrap();
+
+        assert_eq!(PeerIdentity::authenticated("alice", alice, &pins, addr).unwrap().id, "alice");
+        // An unpinned name earns only the fingerprint; a pinned one with other keys nothing
+        assert_eq!(PeerIdentity::authenticated("bob", mallory, &pins, addr).unwrap().id, mallory.to_string());
+        assert!(matches!(
+            PeerIdentity::authenticated("alice", mallory, &pins, addr),
+            Err(SentinelError::AccessDenied(_))
+        ));
+        assert_eq!(PeerIdentity::unauthenticated(addr).id, UNAUTHENTICATED_PEER);
+    }
+}
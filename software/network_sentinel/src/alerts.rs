@@ -0,0 +1,129 @@
+//! Sentinel Alert Relay - Component Alerts to the Central Alerter
+//! "Son of man, I have made thee a watchman" - Ezekiel 33:7
+//!
+//! Components deliver alerts into a local `ark_alert::AlertSpool`.
+//! `forward_pending` sends the spooled alerts over a sealed sentinel
+//! connection to the `alert-relay` service and removes each one when its
+//! acknowledgement arrives, so a dropped connection only means the alert
+//! is sent again. The relay side is `receive_alerts`, which raises every
+//! alert it receives on the central `ark_alert::Alerter`; that alerter
+//! deduplicates and rate limits across the whole fleet before anything
+//! reaches an operator.
+
+use std::net::SocketAddr;
+
+use ark_alert::{Alert, AlertSpool, Alerter};
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::channel::SecureChannel;
+use crate::protocol::{self, MAX_NAME_LENGTH};
+use crate::transport::TransportStream;
+use crate::{SentinelClient, SentinelError};
+
+/// Sentinel service name of the central alert relay
+pub const RELAY_SERVICE: &str = "alert-relay";
+
+/// Relay acknowledgement of one received alert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertAck {
+    /// Id of the received alert
+    pub id: String,
+}
+
+impl Validate for AlertAck {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("id", &self.id, MAX_NAME_LENGTH * 4)
+    }
+}
+
+/// Forward every spooled alert in `spool` to the relay at `relay`
+///
+/// `client` must carry the component's identity and the relay service
+/// (`with_service(peer, RELAY_SERVICE)`), offer secure records and pin the
+/// relay's identity keys (`with_pins`).
+/// Returns the number of alerts forwarded.
+pub async fn forward_pending(spool: &AlertSpool, client: &mut SentinelClient, relay: SocketAddr) -> Result<usize, SentinelError> {
+    if spool.pending().map_err(alert_error)?.is_empty() {
+        return Ok(0);
+    }
+    let mut channel = client.connect_secure(relay).await?;
+    let forwarded = send_alerts(spool, &mut channel).await?;
+    channel.shutdown().await?;
+    info!("Relayed {} alerts to {}", forwarded, relay);
+    Ok(forwarded)
+}
+
+/// Send spooled alerts over an established channel
+pub async fn send_alerts<S: TransportStream>(spool: &AlertSpool, channel: &mut SecureChannel<S>) -> Result<usize, SentinelError> {
+    let mut forwarded = 0;
+    for alert in spool.pending().map_err(alert_error)? {
+        channel.send_message(&alert).await?;
+        let ack: AlertAck = channel.recv_message().await?;
+        if ack.id != alert.id {
+            return Err(SentinelError::ProtocolError(format!(
+                "Relay acknowledged {} for alert {}", ack.id, alert.id
+            )));
+        }
+        spool.remove(&alert).map_err(alert_error)?;
+        forwarded += 1;
+    }
+    Ok(forwarded)
+}
+
+/// Raise alerts sent by a component on `alerter` until it closes the channel
+///
+/// Returns the number of alerts received.
+pub async fn receive_alerts<S: TransportStream>(alerter: &Alerter, channel: &mut SecureChannel<S>) -> Result<usize, SentinelError> {
+    let mut received = 0;
+    while let Some(record) = channel.recv().await? {
+        let alert: Alert = protocol::decode_message(&record)?;
+        let id = alert.id.clone();
+        alerter.raise(alert);
+        channel.send_message(&AlertAck { id }).await?;
+        received += 1;
+    }
+    Ok(received)
+}
+
+fn alert_error(e: ark_alert::AlertError) -> SentinelError {
+    SentinelError::AlertError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::Transcript;
+    use crate::pqc_tls::PQAlgorithm;
+    use ark_alert::{AlertConfig, AlertSink, Disposition, Severity};
+
+    #[tokio::test]
+    async fn test_spooled_alerts_are_removed_once_relayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = AlertSpool::new(dir.path().to_path_buf(), 10);
+        let mut alert = Alert::new("patch_orchestrator", "rollback", "eu-west", Severity::Warning, "patch rolled back");
+        alert.id = "patch_orchestrator-1".into();
+        spool.deliver(&alert).unwrap();
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let transcript = Transcript::default().hash();
+        let mut client = SecureChannel::new(client, PQAlgorithm::Kyber768, &[5u8; 32], &transcript, true);
+        let mut server = SecureChannel::new(server, PQAlgorithm::Kyber768, &[5u8; 32], &transcript, false);
+
+        let relay = tokio::spawn(async move {
+            let central = Alerter::new(AlertConfig::default(), Vec::new());
+            let received = receive_alerts(&central, &mut server).await.unwrap();
+            (received, central.records())
+        });
+        assert_eq!(send_alerts(&spool, &mut client).await.unwrap(), 1);
+        client.shutdown().await.unwrap();
+
+        let (received, records) = relay.await.unwrap();
+        assert_eq!(received, 1);
+        assert_eq!(records[0].key, "patch_orchestrator/rollback/eu-west");
+        assert!(records[0].last_delivered.is_some());
+        assert!(spool.pending().unwrap().is_empty());
+        assert_eq!(Alerter::new(AlertConfig { enabled: false, ..AlertConfig::default() }, Vec::new()).raise(alert), Disposition::Disabled);
+    }
+}
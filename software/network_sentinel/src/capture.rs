@@ -0,0 +1,890 @@
+//! Sentinel Session Capture - Signed Connection Archives for Intrusion Analysis
+//! "For nothing is secret, that shall not be made manifest" - Luke 8:17
+//!
+//! Capture is opt-in. When enabled, every authorized connection (or only the
+//! listed peers) is recorded: connection metadata, the negotiated session and
+//! the decrypted application-layer frames in both directions. Text frames are
+//! scrubbed of credential values and long digit runs before they are kept,
+//! including ones split across reads; binary frames are reduced to their
+//! length unless explicitly retained. Each session keeps a bounded number of
+//! frames and payload bytes.
+//!
+//! When the connection closes the recording is written as one session
+//! archive, signed with the sentinel's Dilithium3 archive key, so incident
+//! responders can trust that an archive was produced by this sentinel and has
+//! not been edited since. Archives are pruned by age, count and total size.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use pqcrypto::prelude::*;
+use pqcrypto_dilithium::{
+    detached_sign, keypair, verify_detached_signature, DetachedSignature, PublicKey, SecretKey,
+};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::DilithiumSignatureBytes;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::acl::PeerIdentity;
+use crate::pqc_tls::PQAlgorithm;
+use crate::protocol::NegotiatedSession;
+use crate::transport::PeerAddr;
+use crate::SentinelError;
+
+/// Session archive format version
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// File extension of session archives
+pub const ARCHIVE_EXTENSION: &str = "session";
+
+/// Replacement for scrubbed values
+const REDACTED: &str = "[REDACTED]";
+
+/// Upper bound on the configured frames per session
+const MAX_FRAMES: usize = 100_000;
+
+/// Bytes of the previous frame in a direction scanned with the next one
+const SCRUB_WINDOW: usize = 256;
+
+/// Decoding limits for archive files
+const ARCHIVE_LIMITS: DecodeLimits = DecodeLimits::new(64 * 1024 * 1024, 8);
+
+/// Capture configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Record sessions at all
+    pub enabled: bool,
+    /// Directory receiving session archives
+    pub directory: PathBuf,
+    /// Archive signing key (generated on first use; public key beside it as `.pub`)
+    pub key_file: PathBuf,
+    /// Peers to record; empty records every authorized peer
+    pub peers: Vec<String>,
+    /// Payload bytes kept per frame
+    pub max_frame_bytes: usize,
+    /// Payload bytes kept per session; later frames keep metadata only
+    pub max_session_bytes: usize,
+    /// Frames archived per session; later frames only count towards the byte totals
+    pub max_frames: usize,
+    /// Scrubbing applied before frames are kept
+    pub scrub: ScrubConfig,
+    /// Archive retention limits
+    pub retention: RetentionPolicy,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("capture"),
+            key_file: PathBuf::from("capture/archive_signing.key"),
+            peers: Vec::new(),
+            max_frame_bytes: 4096,
+            max_session_bytes: 1024 * 1024,
+            max_frames: 10_000,
+            scrub: ScrubConfig::default(),
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+/// Scrubbing of sensitive frame content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrubConfig {
+    /// Keys whose values are redacted in text frames (case-insensitive)
+    pub sensitive_keys: Vec<String>,
+    /// Digit runs at least this long are masked (0 disables)
+    pub min_digit_run: usize,
+    /// Keep non-UTF-8 frames as hex; otherwise only their length is archived
+    pub keep_binary: bool,
+}
+
+impl Default for ScrubConfig {
+    fn default() -> Self {
+        Self {
+            sensitive_keys: ["password", "passwd", "secret", "token", "api_key", "apikey", "authorization", "cookie"]
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            min_digit_run: 12,
+            keep_binary: false,
+        }
+    }
+}
+
+/// Archive retention limits; the oldest archives go first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Archives older than this are deleted
+    pub max_age: Duration,
+    /// Maximum number of archives kept
+    pub max_archives: usize,
+    /// Maximum total size of kept archives
+    pub max_total_bytes: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(7 * 24 * 3600),
+            max_archives: 10_000,
+            max_total_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Direction of a captured frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Peer to sentinel
+    Inbound,
+    /// Sentinel to peer
+    Outbound,
+}
+
+/// Archived frame content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FramePayload {
+    /// Scrubbed UTF-8 text
+    Text(String),
+    /// Hex-encoded binary payload
+    Binary(String),
+    /// Payload not retained
+    Omitted,
+}
+
+/// One application-layer frame
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    /// Time since the session started
+    pub offset: Duration,
+    pub direction: Direction,
+    /// Length of the original frame
+    pub length: usize,
+    pub payload: FramePayload,
+    /// Payload was cut to the per-frame limit
+    pub truncated: bool,
+    /// Scrubbing changed the payload
+    pub scrubbed: bool,
+}
+
+/// Negotiated session parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedNegotiation {
+    pub version: u16,
+    pub algorithm: PQAlgorithm,
+    pub extensions: Vec<String>,
+}
+
+/// Everything recorded about one connection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionArchive {
+    pub format: u32,
+    pub session_id: String,
+    pub peer_id: String,
+    pub peer_addr: PeerAddr,
+    pub service: String,
+    pub negotiated: Option<CapturedNegotiation>,
+    pub started_at: SystemTime,
+    pub ended_at: SystemTime,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub close_reason: String,
+    pub frames: Vec<CapturedFrame>,
+}
+
+/// Archive file contents: the archive and its detached signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedSessionArchive {
+    pub archive: SessionArchive,
+    /// Dilithium3 signature over the archive digest
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl Validate for CaptureConfig {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("peers", self.peers.len(), crate::acl::MAX_ACL_RULES)?;
+        for peer in &self.peers {
+            decode::check_identifier("peer", peer, crate::protocol::MAX_NAME_LENGTH)?;
+        }
+        if self.max_frame_bytes > self.max_session_bytes {
+            return Err("max_frame_bytes exceeds max_session_bytes".to_string());
+        }
+        decode::check_count("max_frames", self.max_frames, MAX_FRAMES)
+    }
+}
+
+impl Validate for SignedSessionArchive {
+    fn validate(&self) -> Result<(), String> {
+        let archive = &self.archive;
+        if archive.format != ARCHIVE_FORMAT_VERSION {
+            return Err(format!("unsupported archive format {}", archive.format));
+        }
+        decode::check_identifier("session_id", &archive.session_id, 64)?;
+        decode::check_len("peer_id", &archive.peer_id, crate::protocol::MAX_NAME_LENGTH)?;
+        decode::check_len("service", &archive.service, crate::protocol::MAX_NAME_LENGTH)
+    }
+}
+
+/// Archive signing keypair, persisted as hex in the key file
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    public: String,
+    secret: String,
+}
+
+/// Shared capture state handing out per-connection recorders
+pub struct SessionCapture {
+    config: CaptureConfig,
+    keys: Option<(PublicKey, SecretKey)>,
+}
+
+impl SessionCapture {
+    /// Create capture state, loading or generating the archive key when enabled
+    pub fn new(config: CaptureConfig) -> Result<Self, SentinelError> {
+        if !config.enabled {
+            return Ok(Self::disabled());
+        }
+
+        std::fs::create_dir_all(&config.directory)?;
+        let keys = load_or_generate_key(&config.key_file)?;
+        info!("Session capture enabled, archives in {:?}", config.directory);
+
+        Ok(Self { config, keys: Some(keys) })
+    }
+
+    /// Capture state that records nothing
+    pub fn disabled() -> Self {
+        Self {
+            config: CaptureConfig::default(),
+            keys: None,
+        }
+    }
+
+    /// Capture configuration
+    pub fn config(&self) -> &CaptureConfig {
+        &self.config
+    }
+
+    /// Public key verifying this sentinel's archives
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        self.keys.as_ref().map(|(public, _)| public)
+    }
+
+    /// Recorder for a newly authorized connection
+    ///
+    /// Returns `None` when capture is disabled or the peer is not selected.
+    pub fn session(
+        self: &Arc<Self>,
+        peer: &PeerIdentity,
+        service: &str,
+        negotiated: Option<&NegotiatedSession>,
+    ) -> Option<SessionRecorder> {
+        if self.keys.is_none() {
+            return None;
+        }
+        if !self.config.peers.is_empty() && !self.config.peers.contains(&peer.id) {
+            return None;
+        }
+
+        let mut id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id);
+        let now = SystemTime::now();
+
+        Some(SessionRecorder {
+            capture: self.clone(),
+            started: Instant::now(),
+            kept_bytes: 0,
+            dropped_frames: 0,
+            inbound: ScrubStream::default(),
+            outbound: ScrubStream::default(),
+            archive: SessionArchive {
+                format: ARCHIVE_FORMAT_VERSION,
+                session_id: to_hex(&id),
+                peer_id: peer.id.clone(),
+                peer_addr: peer.addr,
+                service: service.to_string(),
+                negotiated: negotiated.map(|session| CapturedNegotiation {
+                    version: session.version,
+                    algorithm: session.algorithm,
+                    extensions: session.extensions.iter().map(|ext| format!("{:?}", ext)).collect(),
+                }),
+                started_at: now,
+                ended_at: now,
+                bytes_in: 0,
+                bytes_out: 0,
+                close_reason: String::new(),
+                frames: Vec::new(),
+            },
+        })
+    }
+}
+
+impl Default for SessionCapture {
+    fn default() -> Self {
+        Self::disabled()
+    }
+}
+
+impl std::fmt::Debug for SessionCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionCapture")
+            .field("config", &self.config)
+            .field("enabled", &self.keys.is_some())
+            .finish()
+    }
+}
+
+/// Recording of one connection, archived when finished
+pub struct SessionRecorder {
+    capture: Arc<SessionCapture>,
+    started: Instant,
+    /// Payload bytes kept so far
+    kept_bytes: usize,
+    /// Frames past `max_frames`, counted but not archived
+    dropped_frames: u64,
+    inbound: ScrubStream,
+    outbound: ScrubStream,
+    archive: SessionArchive,
+}
+
+impl SessionRecorder {
+    /// Session identifier
+    pub fn session_id(&self) -> &str {
+        &self.archive.session_id
+    }
+
+    /// Record a decrypted application-layer frame
+    pub fn record(&mut self, direction: Direction, frame: &[u8]) {
+        let config = &self.capture.config;
+        match direction {
+            Direction::Inbound => self.archive.bytes_in += frame.len() as u64,
+            Direction::Outbound => self.archive.bytes_out += frame.len() as u64,
+        }
+        if self.archive.frames.len() >= config.max_frames {
+            self.dropped_frames += 1;
+            return;
+        }
+
+        let budget = config.max_frame_bytes.min(config.max_session_bytes.saturating_sub(self.kept_bytes));
+        let kept = &frame[..frame.len().min(budget)];
+        let stream = match direction {
+            Direction::Inbound => &mut self.inbound,
+            Direction::Outbound => &mut self.outbound,
+        };
+        let (payload, scrubbed) = if kept.is_empty() && !frame.is_empty() {
+            *stream = ScrubStream::default();
+            (FramePayload::Omitted, false)
+        } else {
+            stream.scrub(kept, &config.scrub, &mut self.archive.frames)
+        };
+        if payload != FramePayload::Omitted {
+            self.kept_bytes += kept.len();
+        }
+
+        self.archive.frames.push(CapturedFrame {
+            offset: self.started.elapsed(),
+            direction,
+            length: frame.len(),
+            payload,
+            truncated: kept.len() < frame.len(),
+            scrubbed,
+        });
+    }
+
+    /// Sign and write the archive, then apply retention
+    pub fn finish(mut self, close_reason: &str) -> Result<PathBuf, SentinelError> {
+        self.archive.ended_at = SystemTime::now();
+        self.archive.close_reason = close_reason.to_string();
+        if self.dropped_frames > 0 {
+            warn!("Session {} exceeded {} frames; {} later frames were not archived",
+                  self.archive.session_id, self.capture.config.max_frames, self.dropped_frames);
+        }
+
+        let (_, secret) = self.capture.keys.as_ref()
+            .ok_or_else(|| SentinelError::CaptureError("Capture is disabled".into()))?;
+        let signature = detached_sign(blake3::hash(&self.archive.signing_bytes()).as_bytes(), secret);
+        let signed = SignedSessionArchive {
+            signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+                .map_err(|e| SentinelError::CaptureError(e.to_string()))?,
+            archive: self.archive,
+        };
+
+        let config = &self.capture.config;
+        let started = signed.archive.started_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = config.directory.join(format!("{:010}-{}.{}", started, signed.archive.session_id, ARCHIVE_EXTENSION));
+
+        let encoded = serde_json::to_vec(&signed)
+            .map_err(|e| SentinelError::CaptureError(e.to_string()))?;
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, encoded)?;
+        std::fs::rename(&temporary, &path)?;
+        debug!("Archived session {} of {} ({} frames)",
+               signed.archive.session_id, signed.archive.peer_id, signed.archive.frames.len());
+
+        let removed = enforce_retention(&config.directory, &config.retention, SystemTime::now())?;
+        if removed > 0 {
+            debug!("Retention removed {} session archives", removed);
+        }
+        Ok(path)
+    }
+}
+
+/// Load an archive, verifying its signature against the sentinel's public key
+pub fn verify_archive(path: &Path, public_key: &PublicKey) -> Result<SessionArchive, SentinelError> {
+    let signed: SignedSessionArchive = decode::json_validated(&std::fs::read(path)?, &ARCHIVE_LIMITS)
+        .map_err(|e| SentinelError::CaptureError(format!("Archive {:?}: {}", path, e)))?;
+    let signature = DetachedSignature::from_bytes(signed.signature.as_bytes())
+        .map_err(|_| SentinelError::CaptureError("Invalid archive signature encoding".into()))?;
+
+    verify_detached_signature(&signature, blake3::hash(&signed.archive.signing_bytes()).as_bytes(), public_key)
+        .map_err(|_| SentinelError::CaptureError(format!("Archive {:?} signature verification failed", path)))?;
+    Ok(signed.archive)
+}
+
+/// Read a public key written beside the archive key file
+pub fn load_public_key(path: &Path) -> Result<PublicKey, SentinelError> {
+    let bytes = from_hex(std::fs::read_to_string(path)?.trim())?;
+    PublicKey::from_bytes(&bytes)
+        .map_err(|_| SentinelError::CaptureError(format!("Invalid archive public key {:?}", path)))
+}
+
+/// Delete archives beyond the retention limits, returning how many were removed
+pub fn enforce_retention(directory: &Path, policy: &RetentionPolicy, now: SystemTime) -> Result<usize, SentinelError> {
+    let mut archives: Vec<(PathBuf, u64, SystemTime)> = std::fs::read_dir(directory)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if path.extension()? != ARCHIVE_EXTENSION {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            Some((path, metadata.len(), metadata.modified().unwrap_or(now)))
+        })
+        .collect();
+    // File names start with the session start time
+    archives.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut count = archives.len();
+    let mut total: u64 = archives.iter().map(|(_, size, _)| size).sum();
+    let mut removed = 0;
+    for (path, size, modified) in archives {
+        let expired = now.duration_since(modified).unwrap_or_default() > policy.max_age;
+        if !expired && count <= policy.max_archives && total <= policy.max_total_bytes {
+            break;
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("Failed to remove session archive {:?}: {}", path, e);
+            continue;
+        }
+        count -= 1;
+        total -= size;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Part of a text frame hidden before it is archived
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Hidden {
+    /// Sensitive value, replaced by `[REDACTED]`
+    Value,
+    /// Long digit run, masked with `*`
+    Digits,
+}
+
+/// Byte range of a text frame and how it is hidden
+type Span = (usize, usize, Hidden);
+
+/// Scrubbing state of one direction of a connection
+///
+/// Frames are individual reads, so a credential or digit run can be split
+/// between two of them. Each text frame is scanned together with the tail of
+/// the previous one, whose archived payload is rendered again when a span
+/// reaches back into it, and a sensitive value left open at the end of a
+/// frame is continued at the start of the next.
+#[derive(Default)]
+struct ScrubStream {
+    /// Archive index, kept text and spans of the previous text frame
+    last: Option<(usize, String, Vec<Span>)>,
+    /// Quote of a sensitive value the previous frame left open
+    open: Option<Option<u8>>,
+}
+
+impl ScrubStream {
+    /// Scrub the next frame of this direction, which is archived at the end of `frames`
+    fn scrub(&mut self, frame: &[u8], config: &ScrubConfig, frames: &mut [CapturedFrame]) -> (FramePayload, bool) {
+        let Ok(text) = std::str::from_utf8(frame) else {
+            *self = Self::default();
+            return scrub_frame(frame, config);
+        };
+
+        // An open value already hides the tail, so only scan it otherwise
+        let carried = match (&self.last, self.open) {
+            (Some((_, previous, _)), None) => tail(previous, SCRUB_WINDOW).to_string(),
+            _ => String::new(),
+        };
+        let (spans, open) = text_spans(&format!("{}{}", carried, text), config, self.open);
+
+        if let Some((index, previous, previous_spans)) = &mut self.last {
+            let base = previous.len() - carried.len();
+            let before = previous_spans.len();
+            previous_spans.extend(spans.iter()
+                .filter(|(start, _, _)| *start < carried.len())
+                .map(|&(start, end, hidden)| (base + start, base + end.min(carried.len()), hidden)));
+            if previous_spans.len() > before {
+                if let Some(archived) = frames.get_mut(*index) {
+                    archived.payload = FramePayload::Text(render(previous, previous_spans));
+                    archived.scrubbed = true;
+                }
+            }
+        }
+
+        let spans: Vec<Span> = spans.into_iter()
+            .filter(|(_, end, _)| *end > carried.len())
+            .map(|(start, end, hidden)| (start.saturating_sub(carried.len()), end - carried.len(), hidden))
+            .collect();
+        let rendered = render(text, &spans);
+        let scrubbed = !spans.is_empty();
+        self.last = Some((frames.len(), text.to_string(), spans));
+        self.open = open;
+        (FramePayload::Text(rendered), scrubbed)
+    }
+}
+
+/// Scrub a single frame for archiving, returning the payload and whether it changed
+fn scrub_frame(frame: &[u8], config: &ScrubConfig) -> (FramePayload, bool) {
+    match std::str::from_utf8(frame) {
+        Ok(text) => {
+            let (spans, _) = text_spans(text, config, None);
+            (FramePayload::Text(render(text, &spans)), !spans.is_empty())
+        }
+        Err(_) if config.keep_binary => (FramePayload::Binary(to_hex(frame)), false),
+        Err(_) => (FramePayload::Omitted, false),
+    }
+}
+
+/// Spans to hide in a text frame and the quote of a value left open at its end
+fn text_spans(text: &str, config: &ScrubConfig, open: Option<Option<u8>>) -> (Vec<Span>, Option<Option<u8>>) {
+    let (values, open) = value_spans(text, &config.sensitive_keys, open);
+    let digits = digit_spans(text, config.min_digit_run, &values);
+    let spans = values.into_iter()
+        .map(|(start, end)| (start, end, Hidden::Value))
+        .chain(digits.into_iter().map(|(start, end)| (start, end, Hidden::Digits)))
+        .collect();
+    (spans, open)
+}
+
+/// Values following `key=`, `key:` or `"key":` for sensitive keys
+///
+/// `open` continues a value the previous frame left open, given its quote.
+fn value_spans(text: &str, keys: &[String], open: Option<Option<u8>>) -> (Vec<(usize, usize)>, Option<Option<u8>>) {
+    let lower = text.to_ascii_lowercase();
+    let bytes = lower.as_bytes();
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut left_open = None;
+
+    let value_end = |mut i: usize, quote: Option<u8>| {
+        while let Some(&b) = bytes.get(i) {
+            let end = match quote {
+                Some(quote) => b == quote,
+                None => matches!(b, b'\n' | b'\r' | b'&' | b',' | b';' | b'}' | b'"'),
+            };
+            if end {
+                break;
+            }
+            i += 1;
+        }
+        i
+    };
+
+    if let Some(quote) = open {
+        let end = value_end(0, quote);
+        if end > 0 {
+            spans.push((0, end));
+        }
+        if end == bytes.len() {
+            left_open = Some(quote);
+        }
+    }
+
+    for key in keys {
+        let key = key.to_ascii_lowercase();
+        let mut from = 0;
+        while let Some(found) = lower[from..].find(&key) {
+            let mut i = from + found + key.len();
+            from = i;
+
+            if matches!(bytes.get(i), Some(b'"' | b'\'')) {
+                i += 1;
+            }
+            while bytes.get(i) == Some(&b' ') {
+                i += 1;
+            }
+            if !matches!(bytes.get(i), Some(b':' | b'=')) {
+                continue;
+            }
+            i += 1;
+            while bytes.get(i) == Some(&b' ') {
+                i += 1;
+            }
+            let quote = match bytes.get(i) {
+                Some(&quote @ (b'"' | b'\'')) => {
+                    i += 1;
+                    Some(quote)
+                }
+                _ => None,
+            };
+
+            // A value not started yet is found again with the next frame's window
+            let start = i;
+            let end = value_end(start, quote);
+            if end > start {
+                spans.push((start, end));
+                if end == bytes.len() {
+                    left_open = Some(quote);
+                }
+            }
+        }
+    }
+    (spans, left_open)
+}
+
+/// Runs of at least `min_run` ASCII digits outside the `hidden` spans
+fn digit_spans(text: &str, min_run: usize, hidden: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    if min_run == 0 {
+        return Vec::new();
+    }
+
+    let mut spans = Vec::new();
+    let mut run_start = None;
+    for (i, b) in text.bytes().enumerate() {
+        let digit = b.is_ascii_digit() && !hidden.iter().any(|&(start, end)| (start..end).contains(&i));
+        match (digit, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if i - start >= min_run {
+                    spans.push((start, i));
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start.filter(|start| text.len() - start >= min_run) {
+        spans.push((start, text.len()));
+    }
+    spans
+}
+
+/// Text with every span hidden; overlapping spans are merged
+fn render(text: &str, spans: &[Span]) -> String {
+    let mut spans = spans.to_vec();
+    spans.sort();
+
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for (start, end, hidden) in spans {
+        if end <= cursor {
+            continue;
+        }
+        if start >= cursor {
+            out.push_str(&text[cursor..start]);
+            match hidden {
+                Hidden::Value => out.push_str(REDACTED),
+                Hidden::Digits => out.extend(std::iter::repeat_n('*', end - start)),
+            }
+        } else if hidden == Hidden::Digits {
+            out.extend(std::iter::repeat_n('*', end - cursor));
+        }
+        cursor = end;
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+/// Last `max` bytes of `text`, starting on a character boundary
+fn tail(text: &str, max: usize) -> &str {
+    let mut start = text.len().saturating_sub(max);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// Load the archive key, generating and persisting one if absent
+fn load_or_generate_key(path: &Path) -> Result<(PublicKey, SecretKey), SentinelError> {
+    if path.exists() {
+        let stored: StoredKey = decode::json(&std::fs::read(path)?, &DecodeLimits::FILE)
+            .map_err(|e| SentinelError::CaptureError(format!("Archive key {:?}: {}", path, e)))?;
+        let public = PublicKey::from_bytes(&from_hex(&stored.public)?)
+            .map_err(|_| SentinelError::CaptureError("Invalid archive public key".into()))?;
+        let secret = SecretKey::from_bytes(&from_hex(&stored.secret)?)
+            .map_err(|_| SentinelError::CaptureError("Invalid archive secret key".into()))?;
+        return Ok((public, secret));
+    }
+
+    let (public, secret) = keypair();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let stored = StoredKey {
+        public: to_hex(public.as_bytes()),
+        secret: to_hex(secret.as_bytes()),
+    };
+    let encoded = serde_json::to_string(&stored)
+        .map_err(|e| SentinelError::CaptureError(e.to_string()))?;
+    std::fs::write(path, encoded)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::write(path.with_extension("pub"), &stored.public)?;
+    info!("Generated session archive signing key {:?}", path);
+
+    Ok((public, secret))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, SentinelError> {
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return Err(SentinelError::CaptureError("Malformed hex".into()));
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16)
+            .map_err(|_| SentinelError::CaptureError("Malformed hex".into())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(dir: &Path, config: CaptureConfig) -> Arc<SessionCapture> {
+        Arc::new(SessionCapture::new(CaptureConfig {
+            enabled: true,
+            directory: dir.join("archives"),
+            key_file: dir.join("archive.key"),
+            ..config
+        }).unwrap())
+    }
+
+    fn peer(id: &str) -> PeerIdentity {
+        PeerIdentity { id: id.to_string(), addr: "10.0.0.7:40000".parse().unwrap() }
+    }
+
+    #[test]
+    fn test_scrubbing_redacts_credentials_and_numbers() {
+        let config = ScrubConfig::default();
+        let (payload, scrubbed) = scrub_frame(
+            br#"{"user":"eve","Password": "hunter2","card":"4111111111111111"} token=abc&x=1"#,
+            &config,
+        );
+        assert!(scrubbed);
+        assert_eq!(payload, FramePayload::Text(
+            r#"{"user":"eve","Password": "[REDACTED]","card":"****************"} token=[REDACTED]&x=1"#.to_string()
+        ));
+
+        assert_eq!(scrub_frame(b"ping 42", &config), (FramePayload::Text("ping 42".into()), false));
+        assert_eq!(scrub_frame(&[0xff, 0x00], &config).0, FramePayload::Omitted);
+        let keep = ScrubConfig { keep_binary: true, ..ScrubConfig::default() };
+        assert_eq!(scrub_frame(&[0xff, 0x00], &keep).0, FramePayload::Binary("ff00".into()));
+    }
+
+    #[test]
+    fn test_archive_is_signed_and_tamper_evident() {
+        let dir = tempfile::tempdir().unwrap();
+        let capture = capture(dir.path(), CaptureConfig { max_frame_bytes: 4, ..CaptureConfig::default() });
+
+        assert!(capture.session(&peer("intruder"), "echo", None).is_some());
+        let mut recorder = capture.session(&peer("intruder"), "echo", None).unwrap();
+        recorder.record(Direction::Inbound, b"GET /etc/shadow");
+        recorder.record(Direction::Outbound, b"no");
+        let path = recorder.finish("peer closed").unwrap();
+
+        let public = load_public_key(&dir.path().join("archive.pub")).unwrap();
+        let archive = verify_archive(&path, &public).unwrap();
+        assert_eq!(archive.peer_id, "intruder");
+        assert_eq!((archive.bytes_in, archive.bytes_out), (15, 2));
+        assert_eq!(archive.frames[0].payload, FramePayload::Text("GET ".into()));
+        assert!(archive.frames[0].truncated);
+        assert_eq!(archive.close_reason, "peer closed");
+
+        // Any edit breaks the signature
+        let mut signed: SignedSessionArchive = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        signed.archive.frames.pop();
+        std::fs::write(&path, serde_json::to_vec(&signed).unwrap()).unwrap();
+        assert!(verify_archive(&path, &public).is_err());
+
+        // Unselected peers and disabled capture record nothing
+        let selective = capture(dir.path(), CaptureConfig { peers: vec!["suspect".into()], ..CaptureConfig::default() });
+        assert!(selective.session(&peer("intruder"), "echo", None).is_none());
+        assert!(Arc::new(SessionCapture::disabled()).session(&peer("intruder"), "echo", None).is_none());
+    }
+
+    #[test]
+    fn test_retention_removes_oldest_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["0000000001-a", "0000000002-b", "0000000003-c"] {
+            std::fs::write(dir.path().join(format!("{}.{}", name, ARCHIVE_EXTENSION)), [0u8; 100]).unwrap();
+        }
+        std::fs::write(dir.path().join("archive.key"), b"not an archive").unwrap();
+
+        let policy = RetentionPolicy { max_archives: 2, ..RetentionPolicy::default() };
+        assert_eq!(enforce_retention(dir.path(), &policy, SystemTime::now()).unwrap(), 1);
+        assert!(!dir.path().join("0000000001-a.session").exists());
+
+        let policy = RetentionPolicy { max_total_bytes: 150, ..RetentionPolicy::default() };
+        assert_eq!(enforce_retention(dir.path(), &policy, SystemTime::now()).unwrap(), 1);
+        assert!(dir.path().join("0000000003-c.session").exists());
+
+        let later = SystemTime::now() + Duration::from_secs(8 * 24 * 3600);
+        assert_eq!(enforce_retention(dir.path(), &RetentionPolicy::default(), later).unwrap(), 1);
+        assert!(dir.path().join("archive.key").exists());
+    }
+
+    #[test]
+    fn test_secrets_split_across_frames_are_scrubbed() {
+        let dir = tempfile::tempdir().unwrap();
+        let capture = capture(dir.path(), CaptureConfig { max_frames: 6, ..CaptureConfig::default() });
+        let mut recorder = capture.session(&peer("intruder"), "echo", None).unwrap();
+        recorder.record(Direction::Inbound, b"user=eve&pass");
+        recorder.record(Direction::Outbound, b"ok");
+        recorder.record(Direction::Inbound, b"word=hun");
+        recorder.record(Direction::Inbound, b"ter2&card=411111");
+        recorder.record(Direction::Inbound, b"1111111111&x=1");
+        for _ in 0..3 {
+            recorder.record(Direction::Outbound, b"ok");
+        }
+
+        let payloads: Vec<FramePayload> = recorder.archive.frames.iter().map(|frame| frame.payload.clone()).collect();
+        assert_eq!(payloads, [
+            "user=eve&pass",
+            "ok",
+            "word=[REDACTED]",
+            "[REDACTED]&card=******",
+            "**********&x=1",
+            "ok",
+        ].map(|text| FramePayload::Text(text.to_string())));
+
+        // Frames past the cap still count towards the byte totals
+        assert_eq!(recorder.dropped_frames, 2);
+        assert_eq!(recorder.archive.bytes_out, 8);
+    }
+}
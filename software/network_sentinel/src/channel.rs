@@ -0,0 +1,608 @@
+//! Secure Channel - Encrypted Records After the Hybrid Key Exchange
+//! "The name of the Lord is a strong tower: the righteous runneth into it, and is safe" - Proverbs 18:10
+//!
+//! When both sides agree to the `SecureRecords` extension, the negotiated
+//! key exchange algorithm runs right after the capability selection: the
+//! server sends a `PQKeyShare`, the client answers with a `PQKeyExchange`,
+//! and both derive the same shared secret. Record keys are derived from
+//! that secret and a hash of the whole negotiation transcript, so a peer
+//! that saw a different offer or selection - one stripped of its
+//! strongest algorithms by an attacker, say - ends up with different keys.
+//! Each side then sends the transcript hash as its first record; failing
+//! to open or match the peer's ends the connection as a possible
+//! downgrade. Records are ChaCha20-Poly1305 sealed, framed with a
+//! big-endian `u32` length prefix, and use a per-direction key with a
+//! counter nonce. `export_keying_material` derives further labelled keys
+//! from the same secret and transcript for the layers above.
+//!
+//! The key exchange alone proves nothing about who is on the other end.
+//! Once the records are keyed, the server sends an `IdentityProof`: its
+//! long-term Ed25519 and Dilithium3 public keys and a hybrid signature
+//! over the transcript hash. The client checks the signature and requires
+//! the keys' fingerprint to be the one pinned for the server it meant to
+//! reach, so a proxy that runs its own key exchange with each side can
+//! neither reuse the server's signature (its transcript differs) nor sign
+//! with keys of its own (they are not pinned). The client then proves its
+//! own identity keys the same way, and the server identifies it by them
+//! (`PeerIdentity::authenticated`) rather than by the name it claims.
+//! `load_or_generate_identity` keeps those keys across restarts so there
+//! is something to pin.
+//!
+//! `split` hands the receiving and sending directions to separate tasks,
+//! which is how the keep-alive record layer and messaging run over a
+//! channel (`LiveConnection::start_sealed`).
+
+use std::path::Path;
+use std::sync::Arc;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use pq_types::canonical::CanonicalEncoder;
+use pq_types::decode::Validate;
+use pq_types::pins::{KeyFingerprint, PinStore};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, Ed25519PublicKeyBytes};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use zeroize::Zeroizing;
+
+use crate::pqc_tls::{self, PQAlgorithm, PQHandshake, PQKeyExchange, PQKeyShare, PQSignature, PQTlsConfig, PeerPublicKeys};
+use crate::{discovery, protocol};
+use crate::SentinelError;
+
+/// Largest plaintext carried by one record
+pub const MAX_RECORD: usize = 64 * 1024;
+
+/// Poly1305 tag length
+const TAG_LENGTH: usize = 16;
+
+/// Associated data of every record
+const RECORD_AAD: &[u8] = b"ark-sentinel-record-v1";
+
+/// Domain of the transcript signatures in identity proofs
+const IDENTITY_DOMAIN: &[u8] = b"ARK-SENTINEL-IDENTITY-V1";
+
+// Key shares and exchanges hold only fixed-size fields checked by their byte wrappers
+impl Validate for PQKeyShare {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl Validate for PQKeyExchange {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Long-term identity keys of one side and its signature over the transcript
+#[derive(Clone, Serialize, Deserialize)]
+pub struct IdentityProof {
+    /// Ed25519 half of the hybrid identity
+    pub ed25519_public: Ed25519PublicKeyBytes,
+    /// Dilithium3 half of the hybrid identity
+    pub dilithium_public: DilithiumPublicKeyBytes,
+    /// Hybrid Ed25519 + Dilithium3 signature over `identity_message`
+    pub signature: PQSignature,
+}
+
+// Keys and signatures are checked by their byte wrappers
+impl Validate for IdentityProof {
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl IdentityProof {
+    /// Keys to verify the signature with
+    fn public_keys(&self) -> Result<PeerPublicKeys, SentinelError> {
+        let ed25519_public: ed25519_dalek::VerifyingKey = (&self.ed25519_public).try_into()
+            .map_err(|e| SentinelError::NegotiationError(format!("Invalid identity key: {}", e)))?;
+        Ok(PeerPublicKeys {
+            ed25519_public: Some(ed25519_public),
+            dilithium_public: Some(self.dilithium_public.clone()),
+            ml_dsa_public: None,
+        })
+    }
+}
+
+/// Bytes an identity proof signs: the side's role and the transcript hash
+fn identity_message(role: &str, transcript: &[u8; 32]) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(IDENTITY_DOMAIN);
+    encoder.variant(role).fixed(transcript);
+    encoder.finish()
+}
+
+/// Load the Ed25519 and Dilithium3 identity keys kept in `directory`, generating them if absent
+///
+/// Secret keys are stored as hex in `ed25519.key` and `dilithium.key`, the
+/// public keys beside them as `.pub` for `keys show` to fingerprint. Returns
+/// the fingerprint peers pin.
+pub fn load_or_generate_identity(directory: &Path, config: &mut PQTlsConfig) -> Result<KeyFingerprint, SentinelError> {
+    let ed25519_path = directory.join("ed25519.key");
+    let dilithium_path = directory.join("dilithium.key");
+    let invalid = |path: &Path, e: pq_types::PqBytesError| {
+        SentinelError::ConfigError(format!("Invalid identity key {:?}: {}", path, e))
+    };
+
+    if ed25519_path.exists() && dilithium_path.exists() {
+        let ed25519 = pq_types::dalek::signing_key_from_secret(&Zeroizing::new(discovery::read_hex(&ed25519_path)?))
+            .map_err(|e| invalid(&ed25519_path, e))?;
+        let dilithium_public = DilithiumPublicKeyBytes::from_vec(discovery::read_hex(&dilithium_path.with_extension("pub"))?)
+            .map_err(|e| invalid(&dilithium_path, e))?;
+        let dilithium_secret = DilithiumSecretKeyBytes::from_vec(discovery::read_hex(&dilithium_path)?)
+            .map_err(|e| invalid(&dilithium_path, e))?;
+        config.ed25519_keypair = Some(ed25519);
+        config.dilithium_keypair = Some((dilithium_public, dilithium_secret));
+    } else {
+        let ed25519 = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let (dilithium_public, dilithium_secret) = Dilithium3::keypair().map_err(pqc_tls::PQTlsError::from)?;
+        std::fs::create_dir_all(directory)?;
+        write_secret(&ed25519_path, ed25519.as_bytes())?;
+        std::fs::write(ed25519_path.with_extension("pub"), discovery::to_hex(ed25519.verifying_key().as_bytes()))?;
+        write_secret(&dilithium_path, dilithium_secret.expose_secret())?;
+        std::fs::write(dilithium_path.with_extension("pub"), discovery::to_hex(dilithium_public.as_bytes()))?;
+        tracing::info!("Generated identity keys in {:?}", directory);
+        config.ed25519_keypair = Some(ed25519);
+        config.dilithium_keypair = Some((dilithium_public, dilithium_secret));
+    }
+
+    config.identity_fingerprint()
+        .ok_or_else(|| SentinelError::ConfigError("Identity keys missing after loading".into()))
+}
+
+/// Write a hex-encoded secret key readable by its owner only
+fn write_secret(path: &Path, secret: &[u8]) -> Result<(), SentinelError> {
+    std::fs::write(path, Zeroizing::new(discovery::to_hex(secret)).as_bytes())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// Running hash of the negotiation messages, as encoded on the wire
+#[derive(Clone, Default)]
+pub struct Transcript(Sha3_256);
+
+impl Transcript {
+    /// Add a negotiation message
+    pub fn add<T: Serialize>(&mut self, message: &T) -> Result<(), SentinelError> {
+        let bytes = protocol::encode_message(message)?;
+        self.0.update((bytes.len() as u32).to_be_bytes());
+        self.0.update(&bytes);
+        Ok(())
+    }
+
+    /// Hash of the messages added so far
+    pub fn hash(&self) -> [u8; 32] {
+        self.0.clone().finalize().into()
+    }
+}
+
+/// Key for one direction of the channel
+fn record_key(secret: &[u8], transcript: &[u8; 32], direction: &[u8]) -> Zeroizing<[u8; 32]> {
+    let mut kdf = Sha3_256::new();
+    kdf.update(b"ARK-SENTINEL-RECORD-KEY-V1");
+    kdf.update(direction);
+    kdf.update(secret);
+    kdf.update(transcript);
+    Zeroizing::new(kdf.finalize().into())
+}
+
+fn nonce(counter: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce
+}
+
+/// Seal `plaintext` under the next send counter and write it as one record
+async fn seal_record<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    sealer: &ChaCha20Poly1305,
+    sent: &mut u64,
+    plaintext: &[u8],
+) -> Result<(), SentinelError> {
+    if plaintext.len() > MAX_RECORD {
+        return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", plaintext.len())));
+    }
+    let sealed = sealer
+        .encrypt(Nonce::from_slice(&nonce(*sent)), Payload { msg: plaintext, aad: RECORD_AAD })
+        .map_err(|_| SentinelError::ProtocolError("Record encryption failed".into()))?;
+    *sent += 1;
+
+    stream.write_u32(sealed.len() as u32).await?;
+    stream.write_all(&sealed).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read one record and open it under the next receive counter
+async fn open_record<R: AsyncRead + Unpin>(
+    stream: &mut R,
+    opener: &ChaCha20Poly1305,
+    received: &mut u64,
+) -> Result<Option<Vec<u8>>, SentinelError> {
+    let len = match stream.read_u32().await {
+        Ok(len) => len as usize,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if !(TAG_LENGTH..=MAX_RECORD + TAG_LENGTH).contains(&len) {
+        return Err(SentinelError::ProtocolError(format!("Invalid record length {}", len)));
+    }
+
+    let mut sealed = vec![0u8; len];
+    stream.read_exact(&mut sealed).await?;
+    let plaintext = opener
+        .decrypt(Nonce::from_slice(&nonce(*received)), Payload { msg: &sealed, aad: RECORD_AAD })
+        .map_err(|_| SentinelError::ProtocolError("Record authentication failed".into()))?;
+    *received += 1;
+    Ok(Some(plaintext))
+}
+
+/// Record-encrypted stream
+pub struct SecureChannel<S> {
+    stream: S,
+    algorithm: PQAlgorithm,
+    sealer: ChaCha20Poly1305,
+    opener: ChaCha20Poly1305,
+    sent: u64,
+    received: u64,
+    exporter_secret: Zeroizing<[u8; 32]>,
+    transcript: [u8; 32],
+    peer: Option<KeyFingerprint>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SecureChannel<S> {
+    /// Channel keyed from a key exchange's shared secret and transcript
+    pub fn new(stream: S, algorithm: PQAlgorithm, secret: &[u8], transcript: &[u8; 32], is_client: bool) -> Self {
+        let client_key = record_key(secret, transcript, b"client");
+        let server_key = record_key(secret, transcript, b"server");
+        let (seal_key, open_key) = if is_client { (client_key, server_key) } else { (server_key, client_key) };
+        Self {
+            stream,
+            algorithm,
+            sealer: ChaCha20Poly1305::new(Key::from_slice(seal_key.as_slice())),
+            opener: ChaCha20Poly1305::new(Key::from_slice(open_key.as_slice())),
+            sent: 0,
+            received: 0,
+            exporter_secret: record_key(secret, transcript, b"exporter"),
+            transcript: *transcript,
+            peer: None,
+        }
+    }
+
+    /// Key exchange algorithm the channel was keyed with
+    pub fn algorithm(&self) -> PQAlgorithm {
+        self.algorithm
+    }
+
+    /// Fingerprint of the identity keys the peer proved it holds
+    pub fn peer_fingerprint(&self) -> Option<KeyFingerprint> {
+        self.peer
+    }
+
+    /// Fill `output` with a key bound to this channel, `label` and `context`
+    ///
+    /// Independent of the record keys; both ends derive the same key.
+    pub fn export_keying_material(&self, label: &[u8], context: &[u8], output: &mut [u8]) -> Result<(), SentinelError> {
+        pqc_tls::export_keying_material(self.exporter_secret.as_slice(), &self.transcript, label, context, output)?;
+        Ok(())
+    }
+
+    /// Seal and send one record
+    pub async fn send(&mut self, plaintext: &[u8]) -> Result<(), SentinelError> {
+        seal_record(&mut self.stream, &self.sealer, &mut self.sent, plaintext).await
+    }
+
+    /// Receive and open one record; `None` once the peer has closed the stream
+    pub async fn recv(&mut self) -> Result<Option<Vec<u8>>, SentinelError> {
+        open_record(&mut self.stream, &self.opener, &mut self.received).await
+    }
+
+    /// Receiving and sending halves, for use from separate tasks
+    ///
+    /// The halves keep only their record keys: export keying material first.
+    pub fn split(self) -> (SecureReader<S>, SecureWriter<S>) {
+        let (reader, writer) = tokio::io::split(self.stream);
+        (
+            SecureReader { stream: reader, opener: self.opener, received: self.received },
+            SecureWriter { stream: writer, sealer: self.sealer, sent: self.sent },
+        )
+    }
+
+    /// Send a negotiation message as one record
+    pub async fn send_message<T: Serialize>(&mut self, message: &T) -> Result<(), SentinelError> {
+        self.send(&protocol::encode_message(message)?).await
+    }
+
+    /// Receive a negotiation message sent with `send_message`
+    pub async fn recv_message<T: DeserializeOwned + Validate>(&mut self) -> Result<T, SentinelError> {
+        let record = self.recv().await?
+            .ok_or_else(|| SentinelError::ProtocolError("Connection closed before message".into()))?;
+        protocol::decode_message(&record)
+    }
+
+    /// Close the sending side; the peer's `recv` returns `None`
+    pub async fn shutdown(&mut self) -> Result<(), SentinelError> {
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+
+    /// Exchange transcript hashes; the server speaks first
+    async fn confirm(&mut self, transcript: &[u8; 32], is_client: bool) -> Result<(), SentinelError> {
+        let downgrade = || SentinelError::NegotiationError("Handshake transcript mismatch (possible downgrade)".into());
+        if !is_client {
+            self.send(transcript).await?;
+        }
+        match self.recv().await {
+            Ok(Some(peer)) if peer == transcript => {}
+            Ok(_) | Err(SentinelError::ProtocolError(_)) => return Err(downgrade()),
+            Err(e) => return Err(e),
+        }
+        if is_client {
+            self.send(transcript).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Receiving half of a split `SecureChannel`
+pub struct SecureReader<S> {
+    stream: ReadHalf<S>,
+    opener: ChaCha20Poly1305,
+    received: u64,
+}
+
+impl<S: AsyncRead + AsyncWrite> SecureReader<S> {
+    /// Receive and open one record; `None` once the peer has closed the stream
+    pub async fn recv(&mut self) -> Result<Option<Vec<u8>>, SentinelError> {
+        open_record(&mut self.stream, &self.opener, &mut self.received).await
+    }
+}
+
+/// Sending half of a split `SecureChannel`
+pub struct SecureWriter<S> {
+    stream: WriteHalf<S>,
+    sealer: ChaCha20Poly1305,
+    sent: u64,
+}
+
+impl<S: AsyncRead + AsyncWrite> SecureWriter<S> {
+    /// Seal and send one record
+    pub async fn send(&mut self, plaintext: &[u8]) -> Result<(), SentinelError> {
+        seal_record(&mut self.stream, &self.sealer, &mut self.sent, plaintext).await
+    }
+
+    /// Close the sending side; the peer's `recv` returns `None`
+    pub async fn shutdown(&mut self) -> Result<(), SentinelError> {
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+/// Server side of the key exchange over a negotiated stream
+///
+/// `transcript` holds the offer and selection already exchanged. Ends by
+/// proving the identity keys in `config` to the client and checking the
+/// client's proof of its own, whose fingerprint `peer_fingerprint` returns.
+pub async fn server_handshake<S>(
+    mut stream: S,
+    config: Arc<PQTlsConfig>,
+    algorithm: PQAlgorithm,
+    mut transcript: Transcript,
+) -> Result<SecureChannel<S>, SentinelError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut handshake = PQHandshake::new(config.clone(), false);
+    let share = handshake.offer_key_share(algorithm)?;
+    protocol::write_message(&mut stream, &share).await?;
+    transcript.add(&share)?;
+
+    let exchange: PQKeyExchange = protocol::read_message(&mut stream).await?;
+    handshake.complete_key_exchange(&exchange)?;
+    transcript.add(&exchange)?;
+
+    let mut channel = finish(stream, &handshake, algorithm, &transcript, false).await?;
+    let proof = prove_identity(&handshake, &config, "server", &channel.transcript)?;
+    channel.send_message(&proof).await?;
+    let proof: IdentityProof = channel.recv_message().await?;
+    channel.peer = Some(verify_proof(&handshake, &proof, "client", &channel.transcript)?);
+    Ok(channel)
+}
+
+/// Client side of the key exchange over a negotiated stream
+///
+/// `transcript` holds the offer and selection already exchanged. The
+/// server must prove it holds the identity keys pinned for `server` in
+/// `pins`; an unpinned server is refused like one with other keys. The
+/// client then proves the identity keys in `config`.
+pub async fn client_handshake<S>(
+    mut stream: S,
+    config: Arc<PQTlsConfig>,
+    algorithm: PQAlgorithm,
+    mut transcript: Transcript,
+    server: &str,
+    pins: &PinStore,
+) -> Result<SecureChannel<S>, SentinelError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let share: PQKeyShare = protocol::read_message(&mut stream).await?;
+    if share.algorithm != algorithm {
+        return Err(SentinelError::NegotiationError(
+            format!("Key share uses {:?}, negotiated {:?}", share.algorithm, algorithm)
+        ));
+    }
+    transcript.add(&share)?;
+
+    let mut handshake = PQHandshake::new(config.clone(), true);
+    let exchange = handshake.accept_key_share(&share)?;
+    protocol::write_message(&mut stream, &exchange).await?;
+    transcript.add(&exchange)?;
+
+    let mut channel = finish(stream, &handshake, algorithm, &transcript, true).await?;
+    let proof: IdentityProof = channel.recv_message().await?;
+    let fingerprint = verify_identity(&handshake, &proof, "server", &channel.transcript, server, pins)?;
+    channel.peer = Some(fingerprint);
+    let proof = prove_identity(&handshake, &config, "client", &channel.transcript)?;
+    channel.send_message(&proof).await?;
+    Ok(channel)
+}
+
+/// Proof of this side's identity keys over `transcript`
+fn prove_identity(
+    handshake: &PQHandshake,
+    config: &PQTlsConfig,
+    role: &str,
+    transcript: &[u8; 32],
+) -> Result<IdentityProof, SentinelError> {
+    let (Some(ed25519), Some((dilithium_public, _))) = (&config.ed25519_keypair, &config.dilithium_keypair) else {
+        return Err(SentinelError::ConfigError("No identity keys to prove".into()));
+    };
+    Ok(IdentityProof {
+        ed25519_public: Ed25519PublicKeyBytes::from(&ed25519.verifying_key()),
+        dilithium_public: dilithium_public.clone(),
+        signature: handshake.sign_identity(&identity_message(role, transcript))?,
+    })
+}
+
+/// Check `peer`'s proof over `transcript` and its keys against their pin
+fn verify_identity(
+    handshake: &PQHandshake,
+    proof: &IdentityProof,
+    role: &str,
+    transcript: &[u8; 32],
+    peer: &str,
+    pins: &PinStore,
+) -> Result<KeyFingerprint, SentinelError> {
+    let refused = |reason: String| SentinelError::NegotiationError(format!("{} failed to prove its identity: {}", peer, reason));
+    if pins.get(peer).is_none() {
+        return Err(refused("no pinned key".into()));
+    }
+    if proof.signature.algorithm != PQAlgorithm::HybridEd25519Dilithium3 {
+        return Err(refused(format!("{:?} signature", proof.signature.algorithm)));
+    }
+    let keys = proof.public_keys()?;
+    handshake.verify_pinned_signature(&identity_message(role, transcript), &proof.signature, peer, &keys, pins)
+        .map_err(|e| refused(e.to_string()))?;
+    keys.fingerprint().ok_or_else(|| refused("incomplete hybrid key".into()))
+}
+
+/// Check an unpinned peer's proof over `transcript`, returning its keys' fingerprint
+fn verify_proof(
+    handshake: &PQHandshake,
+    proof: &IdentityProof,
+    role: &str,
+    transcript: &[u8; 32],
+) -> Result<KeyFingerprint, SentinelError> {
+    let refused = |reason: String| SentinelError::NegotiationError(format!("Peer failed to prove its identity: {}", reason));
+    if proof.signature.algorithm != PQAlgorithm::HybridEd25519Dilithium3 {
+        return Err(refused(format!("{:?} signature", proof.signature.algorithm)));
+    }
+    let keys = proof.public_keys()?;
+    handshake.verify_signature(&identity_message(role, transcript), &proof.signature, &keys)
+        .map_err(|e| refused(e.to_string()))?;
+    keys.fingerprint().ok_or_else(|| refused("incomplete hybrid key".into()))
+}
+
+async fn finish<S>(
+    stream: S,
+    handshake: &PQHandshake,
+    algorithm: PQAlgorithm,
+    transcript: &Transcript,
+    is_client: bool,
+) -> Result<SecureChannel<S>, SentinelError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let secret = handshake.get_shared_secret()
+        .ok_or_else(|| SentinelError::NegotiationError("Key exchange produced no shared secret".into()))?;
+    let transcript = transcript.hash();
+    let mut channel = SecureChannel::new(stream, algorithm, secret, &transcript, is_client);
+    channel.confirm(&transcript, is_client).await?;
+    Ok(channel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records_round_trip_and_reject_tampering() {
+        let (client, server) = tokio::io::duplex(4096);
+        let transcript = Transcript::default().hash();
+        let mut client = SecureChannel::new(client, PQAlgorithm::Kyber768, &[7u8; 32], &transcript, true);
+        let mut server = SecureChannel::new(server, PQAlgorithm::Kyber768, &[7u8; 32], &transcript, false);
+
+        client.send(b"first").await.unwrap();
+        client.send(b"second").await.unwrap();
+        assert_eq!(server.recv().await.unwrap().as_deref(), Some(&b"first"[..]));
+        assert_eq!(server.recv().await.unwrap().as_deref(), Some(&b"second"[..]));
+
+        server.send(b"reply").await.unwrap();
+        assert_eq!(client.recv().await.unwrap().as_deref(), Some(&b"reply"[..]));
+
+        client.shutdown().await.unwrap();
+        assert_eq!(server.recv().await.unwrap(), None);
+
+        // Keys bound to a different transcript cannot open the records
+        let (client, server) = tokio::io::duplex(4096);
+        let mut tampered = Transcript::default();
+        tampered.add(&PQAlgorithm::Kyber768).unwrap();
+        let mut client = SecureChannel::new(client, PQAlgorithm::Kyber768, &[7u8; 32], &tampered.hash(), true);
+        let mut server = SecureChannel::new(server, PQAlgorithm::Kyber768, &[7u8; 32], &transcript, false);
+        client.send(b"first").await.unwrap();
+        assert!(matches!(server.recv().await, Err(SentinelError::ProtocolError(_))));
+
+        // Split halves carry on from the channel's counters
+        let (client, server) = tokio::io::duplex(4096);
+        let mut client = SecureChannel::new(client, PQAlgorithm::Kyber768, &[7u8; 32], &transcript, true);
+        let mut server = SecureChannel::new(server, PQAlgorithm::Kyber768, &[7u8; 32], &transcript, false);
+        client.send(b"before").await.unwrap();
+        assert_eq!(server.recv().await.unwrap().as_deref(), Some(&b"before"[..]));
+        let (mut reader, mut writer) = server.split();
+        client.send(b"after").await.unwrap();
+        assert_eq!(reader.recv().await.unwrap().as_deref(), Some(&b"after"[..]));
+        writer.send(b"reply").await.unwrap();
+        assert_eq!(client.recv().await.unwrap().as_deref(), Some(&b"reply"[..]));
+        client.shutdown().await.unwrap();
+        assert_eq!(reader.recv().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_identity_proof_bound_to_transcript_and_pin() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        let fingerprint = config.identity_fingerprint().unwrap();
+        let config = Arc::new(config);
+        let handshake = PQHandshake::new(config.clone(), false);
+        let transcript = [1u8; 32];
+        let proof = prove_identity(&handshake, &config, "server", &transcript).unwrap();
+
+        // Unpinned keys prove nothing
+        let mut pins = PinStore::in_memory();
+        assert!(verify_identity(&handshake, &proof, "server", &transcript, "sentinel", &pins).is_err());
+        pins.pin("sentinel", fingerprint).unwrap();
+        assert_eq!(verify_identity(&handshake, &proof, "server", &transcript, "sentinel", &pins).unwrap(), fingerprint);
+
+        // Replayed on another connection or for the other role
+        assert!(verify_identity(&handshake, &proof, "server", &[2u8; 32], "sentinel", &pins).is_err());
+        assert!(verify_identity(&handshake, &proof, "client", &transcript, "sentinel", &pins).is_err());
+
+        pins.pin("sentinel", KeyFingerprint::hybrid(&[0u8; 32], &[0u8; 1952])).unwrap();
+        assert!(verify_identity(&handshake, &proof, "server", &transcript, "sentinel", &pins).is_err());
+
+        // Unpinned peers still have to sign the transcript with the keys they present
+        assert_eq!(verify_proof(&handshake, &proof, "server", &transcript).unwrap(), fingerprint);
+        assert!(verify_proof(&handshake, &proof, "client", &transcript).is_err());
+        let mut forged = proof.clone();
+        forged.dilithium_public = DilithiumPublicKeyBytes::from_vec(vec![0u8; DilithiumPublicKeyBytes::LEN]).unwrap();
+        assert!(verify_proof(&handshake, &forged, "server", &transcript).is_err());
+    }
+}
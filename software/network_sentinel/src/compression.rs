@@ -0,0 +1,406 @@
+//! Sentinel Compression - Per-Connection zstd with Shared Dictionaries
+//! "Gather up the fragments that remain, that nothing be lost" - John 6:12
+//!
+//! Patch metadata and decision payloads are small, repetitive JSON that
+//! compresses poorly on its own but very well against a dictionary trained
+//! on its type. Peers advertise the dictionaries they hold in the data of
+//! the `Compression` extension; a dictionary is used only when both sides
+//! hold it under the same id with the same digest.
+//!
+//! Compression applies to the keep-alive record layer: a `Compressed`
+//! record carries the dictionary id (`u32`, big-endian, 0 for none)
+//! followed by one zstd frame. Records are sent uncompressed when they are
+//! below `min_size` or compression does not shrink them. Decompression is
+//! bounded by the record size limit and by `max_ratio`, so a small record
+//! cannot expand into an arbitrarily large one.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+use crate::keepalive::MAX_RECORD_PAYLOAD;
+use crate::protocol::{self, Extension, ExtensionKind, MAX_NEGOTIATION_ENTRIES};
+use crate::SentinelError;
+
+/// Dictionary id meaning "no dictionary"
+pub const NO_DICTIONARY: u32 = 0;
+
+/// Bytes of dictionary id in front of each compressed frame
+const DICTIONARY_ID_LEN: usize = 4;
+
+/// Shared dictionary for one payload type
+pub struct CompressionDictionary {
+    /// Identifier agreed on the wire; never `NO_DICTIONARY`
+    pub id: u32,
+    /// Payload type the dictionary was trained on (e.g. "patch-metadata")
+    pub payload_type: String,
+    digest: [u8; 32],
+    data: Vec<u8>,
+}
+
+impl CompressionDictionary {
+    /// Wrap raw dictionary bytes
+    pub fn new(id: u32, payload_type: impl Into<String>, data: Vec<u8>) -> Result<Self, SentinelError> {
+        if id == NO_DICTIONARY {
+            return Err(SentinelError::ConfigError("Dictionary id 0 is reserved".into()));
+        }
+        Ok(Self {
+            id,
+            payload_type: payload_type.into(),
+            digest: *blake3::hash(&data).as_bytes(),
+            data,
+        })
+    }
+
+    /// BLAKE3 digest of the dictionary bytes
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest
+    }
+
+    fn reference(&self) -> DictionaryRef {
+        DictionaryRef { id: self.id, digest: self.digest }
+    }
+}
+
+impl std::fmt::Debug for CompressionDictionary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionDictionary")
+            .field("id", &self.id)
+            .field("payload_type", &self.payload_type)
+            .field("size", &self.data.len())
+            .finish()
+    }
+}
+
+/// Dictionary as advertised to the peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictionaryRef {
+    /// Dictionary id
+    pub id: u32,
+    /// BLAKE3 digest of the dictionary bytes
+    pub digest: [u8; 32],
+}
+
+/// Data of the `Compression` extension
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressionParams {
+    /// Dictionaries offered, or agreed in a selection
+    pub dictionaries: Vec<DictionaryRef>,
+}
+
+impl Validate for CompressionParams {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("dictionaries", self.dictionaries.len(), MAX_NEGOTIATION_ENTRIES)
+    }
+}
+
+/// Compression settings
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    /// zstd level
+    pub level: i32,
+    /// Payloads smaller than this are sent uncompressed
+    pub min_size: usize,
+    /// Largest accepted ratio of decompressed to compressed size
+    pub max_ratio: usize,
+    /// Shared dictionaries held locally
+    pub dictionaries: Vec<Arc<CompressionDictionary>>,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            level: 3,
+            min_size: 64,
+            max_ratio: 200,
+            dictionaries: Vec::new(),
+        }
+    }
+}
+
+impl CompressionConfig {
+    /// Advertised parameters
+    pub fn params(&self) -> CompressionParams {
+        CompressionParams {
+            dictionaries: self.dictionaries.iter().map(|dictionary| dictionary.reference()).collect(),
+        }
+    }
+
+    /// `Compression` extension advertising our dictionaries
+    pub fn extension(&self, mandatory: bool) -> Result<Extension, SentinelError> {
+        Ok(Extension {
+            data: protocol::encode_message(&self.params())?,
+            ..Extension::new(ExtensionKind::Compression, mandatory)
+        })
+    }
+
+    /// Dictionaries from the peer's extension data that we hold with the same digest
+    ///
+    /// Empty data is an offer without dictionaries.
+    pub fn agree(&self, peer_data: &[u8]) -> Result<CompressionParams, SentinelError> {
+        if peer_data.is_empty() {
+            return Ok(CompressionParams::default());
+        }
+        let peer: CompressionParams = protocol::decode_message(peer_data)?;
+        let local = self.params();
+        Ok(CompressionParams {
+            dictionaries: peer.dictionaries.into_iter().filter(|d| local.dictionaries.contains(d)).collect(),
+        })
+    }
+
+    /// Codec using the agreed dictionaries
+    pub fn codec(&self, agreed: &CompressionParams) -> Result<RecordCodec, SentinelError> {
+        let mut dictionaries = HashMap::new();
+        let mut by_type = HashMap::new();
+        for dictionary in &self.dictionaries {
+            if !agreed.dictionaries.contains(&dictionary.reference()) {
+                continue;
+            }
+            dictionaries.insert(
+                dictionary.id,
+                (
+                    EncoderDictionary::copy(&dictionary.data, self.level),
+                    DecoderDictionary::copy(&dictionary.data),
+                ),
+            );
+            by_type.insert(dictionary.payload_type.clone(), dictionary.id);
+        }
+        Ok(RecordCodec {
+            level: self.level,
+            min_size: self.min_size,
+            max_ratio: self.max_ratio.max(1),
+            dictionaries,
+            by_type,
+        })
+    }
+}
+
+/// Compression counters of one connection
+#[derive(Debug, Default)]
+pub struct CompressionMetrics {
+    records_compressed: AtomicU64,
+    records_skipped: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    records_decompressed: AtomicU64,
+    records_rejected: AtomicU64,
+}
+
+/// Point-in-time copy of `CompressionMetrics`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompressionSnapshot {
+    /// Outgoing records sent compressed
+    pub records_compressed: u64,
+    /// Outgoing records sent uncompressed because compression did not pay
+    pub records_skipped: u64,
+    /// Payload bytes of compressed records before compression
+    pub bytes_in: u64,
+    /// Payload bytes of compressed records after compression
+    pub bytes_out: u64,
+    /// Incoming compressed records accepted
+    pub records_decompressed: u64,
+    /// Incoming compressed records rejected as corrupt or over a limit
+    pub records_rejected: u64,
+}
+
+impl CompressionSnapshot {
+    /// Achieved compression ratio of outgoing records (input / output)
+    pub fn ratio(&self) -> Option<f64> {
+        (self.bytes_out > 0).then(|| self.bytes_in as f64 / self.bytes_out as f64)
+    }
+}
+
+impl CompressionMetrics {
+    /// Current values
+    pub fn snapshot(&self) -> CompressionSnapshot {
+        CompressionSnapshot {
+            records_compressed: self.records_compressed.load(Ordering::Relaxed),
+            records_skipped: self.records_skipped.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            records_decompressed: self.records_decompressed.load(Ordering::Relaxed),
+            records_rejected: self.records_rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Compressor and decompressor for the records of one connection
+pub struct RecordCodec {
+    level: i32,
+    min_size: usize,
+    max_ratio: usize,
+    dictionaries: HashMap<u32, (EncoderDictionary<'static>, DecoderDictionary<'static>)>,
+    by_type: HashMap<String, u32>,
+}
+
+impl RecordCodec {
+    /// Agreed dictionary for a payload type
+    pub fn dictionary_for(&self, payload_type: &str) -> Option<u32> {
+        self.by_type.get(payload_type).copied()
+    }
+
+    /// Body of a `Compressed` record, or `None` if the payload should go uncompressed
+    pub fn compress(
+        &self,
+        payload_type: Option<&str>,
+        payload: &[u8],
+        metrics: &CompressionMetrics,
+    ) -> Result<Option<Vec<u8>>, SentinelError> {
+        if payload.len() < self.min_size {
+            metrics.records_skipped.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        let id = payload_type.and_then(|t| self.dictionary_for(t)).unwrap_or(NO_DICTIONARY);
+        let frame = match self.dictionaries.get(&id) {
+            Some((encoder, _)) => zstd::bulk::Compressor::with_prepared_dictionary(encoder)
+                .and_then(|mut compressor| compressor.compress(payload)),
+            None => zstd::bulk::compress(payload, self.level),
+        }
+        .map_err(|e| SentinelError::ProtocolError(format!("Compression failed: {}", e)))?;
+
+        if DICTIONARY_ID_LEN + frame.len() >= payload.len() {
+            metrics.records_skipped.fetch_add(1, Ordering::Relaxed);
+            return Ok(None);
+        }
+
+        let mut record = Vec::with_capacity(DICTIONARY_ID_LEN + frame.len());
+        record.extend_from_slice(&id.to_be_bytes());
+        record.extend_from_slice(&frame);
+        metrics.records_compressed.fetch_add(1, Ordering::Relaxed);
+        metrics.bytes_in.fetch_add(payload.len() as u64, Ordering::Relaxed);
+        metrics.bytes_out.fetch_add(record.len() as u64, Ordering::Relaxed);
+        Ok(Some(record))
+    }
+
+    /// Payload of a `Compressed` record
+    pub fn decompress(&self, record: &[u8], metrics: &CompressionMetrics) -> Result<Vec<u8>, SentinelError> {
+        let result = self.decompress_bounded(record);
+        let counter = match result {
+            Ok(_) => &metrics.records_decompressed,
+            Err(_) => &metrics.records_rejected,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    fn decompress_bounded(&self, record: &[u8]) -> Result<Vec<u8>, SentinelError> {
+        if record.len() < DICTIONARY_ID_LEN {
+            return Err(SentinelError::ProtocolError("Compressed record too short".into()));
+        }
+        let (id, frame) = record.split_at(DICTIONARY_ID_LEN);
+        let id = u32::from_be_bytes([id[0], id[1], id[2], id[3]]);
+
+        // The capacity bounds the allocation; zstd fails rather than grow it
+        let capacity = MAX_RECORD_PAYLOAD.min(frame.len().saturating_mul(self.max_ratio));
+        let payload = if id == NO_DICTIONARY {
+            zstd::bulk::decompress(frame, capacity)
+        } else {
+            let (_, decoder) = self
+                .dictionaries
+                .get(&id)
+                .ok_or_else(|| SentinelError::ProtocolError(format!("Unknown compression dictionary {}", id)))?;
+            zstd::bulk::Decompressor::with_prepared_dictionary(decoder)
+                .and_then(|mut decompressor| decompressor.decompress(frame, capacity))
+        };
+        payload.map_err(|e| {
+            SentinelError::ProtocolError(format!(
+                "Rejected compressed record of {} bytes (limit {} bytes): {}",
+                frame.len(),
+                capacity,
+                e
+            ))
+        })
+    }
+}
+
+impl std::fmt::Debug for RecordCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordCodec")
+            .field("level", &self.level)
+            .field("min_size", &self.min_size)
+            .field("max_ratio", &self.max_ratio)
+            .field("dictionaries", &self.by_type)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(n: usize) -> Vec<u8> {
+        format!(
+            r#"{{"event_id":"evt-{}","decision":"Allow","confidence":0.97,"principles":["TRUTH_OVER_LIES","PROTECT_CHILDREN"],"reasoning":"No violations detected"}}"#,
+            n
+        )
+        .into_bytes()
+    }
+
+    fn config(dictionaries: Vec<Arc<CompressionDictionary>>) -> CompressionConfig {
+        CompressionConfig { dictionaries, ..Default::default() }
+    }
+
+    fn dictionary(id: u32, data: &[u8]) -> Arc<CompressionDictionary> {
+        Arc::new(CompressionDictionary::new(id, "decision", data.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_agreement_requires_matching_digest() {
+        let trained = decision(0).repeat(4);
+        let server = config(vec![dictionary(1, &trained), dictionary(2, b"patch metadata")]);
+        let client = config(vec![dictionary(1, &trained), dictionary(2, b"other bytes")]);
+
+        let offer = server.extension(false).unwrap();
+        let agreed = client.agree(&offer.data).unwrap();
+        assert_eq!(agreed.dictionaries, vec![DictionaryRef { id: 1, digest: client.dictionaries[0].digest() }]);
+        assert_eq!(server.agree(&protocol::encode_message(&agreed).unwrap()).unwrap(), agreed);
+        assert!(client.agree(&[]).unwrap().dictionaries.is_empty());
+
+        let codec = client.codec(&agreed).unwrap();
+        assert_eq!(codec.dictionary_for("decision"), Some(1));
+        assert!(CompressionDictionary::new(NO_DICTIONARY, "decision", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_dictionary_round_trip_and_metrics() {
+        let shared = config(vec![dictionary(1, &decision(0).repeat(4))]);
+        let codec = shared.codec(&shared.params()).unwrap();
+        let metrics = CompressionMetrics::default();
+
+        let payload = decision(42);
+        let record = codec.compress(Some("decision"), &payload, &metrics).unwrap().unwrap();
+        assert_eq!(&record[..4], &1u32.to_be_bytes());
+        assert_eq!(codec.decompress(&record, &metrics).unwrap(), payload);
+
+        // Small payloads are not worth compressing
+        assert!(codec.compress(None, b"tiny", &metrics).unwrap().is_none());
+
+        let snapshot = metrics.snapshot();
+        assert_eq!((snapshot.records_compressed, snapshot.records_skipped), (1, 1));
+        assert!(snapshot.ratio().unwrap() > 2.0);
+    }
+
+    #[test]
+    fn test_compression_bomb_rejected() {
+        let codec = CompressionConfig::default().codec(&CompressionParams::default()).unwrap();
+        let metrics = CompressionMetrics::default();
+
+        // 1 MiB of zeros compresses to a few dozen bytes
+        let mut record = NO_DICTIONARY.to_be_bytes().to_vec();
+        record.extend(zstd::bulk::compress(&vec![0u8; 1 << 20], 3).unwrap());
+        assert!(matches!(codec.decompress(&record, &metrics), Err(SentinelError::ProtocolError(_))));
+
+        // Within the record limit but over the ratio limit
+        let mut record = NO_DICTIONARY.to_be_bytes().to_vec();
+        record.extend(zstd::bulk::compress(&vec![0u8; MAX_RECORD_PAYLOAD], 3).unwrap());
+        assert!(codec.decompress(&record, &metrics).is_err());
+
+        assert!(codec.decompress(&[0, 0, 0, 9, 1, 2], &metrics).is_err());
+        assert_eq!(metrics.snapshot().records_rejected, 3);
+    }
+}
@@ -0,0 +1,130 @@
+//! Sentinel Crash Forwarding - Crash Reports to the Central Collector
+//! "Confess your faults one to another" - James 5:16
+//!
+//! Components keep their crash reports in a local `ark_crash::CrashStore`.
+//! `forward_pending` sends the reports the collector has not yet
+//! acknowledged over a sealed sentinel connection to the `crash-collector`
+//! service and marks each one forwarded when its acknowledgement arrives,
+//! so a report is never lost to a dropped connection, only sent again. The
+//! collector side is `receive_reports`, run on each connection the central
+//! sentinel grants for the service.
+
+use std::net::SocketAddr;
+use std::time::SystemTime;
+
+use ark_crash::{CrashReport, CrashStore};
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::channel::SecureChannel;
+use crate::protocol::{self, MAX_NAME_LENGTH};
+use crate::transport::TransportStream;
+use crate::{SentinelClient, SentinelError};
+
+/// Sentinel service name of the central crash collector
+pub const COLLECTOR_SERVICE: &str = "crash-collector";
+
+/// Collector acknowledgement of one stored report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashAck {
+    /// Id of the stored report
+    pub id: String,
+}
+
+impl Validate for CrashAck {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("id", &self.id, MAX_NAME_LENGTH * 4)
+    }
+}
+
+/// Forward every unacknowledged report in `store` to the collector at `collector`
+///
+/// `client` must carry the component's identity and the collector service
+/// (`with_service(peer, COLLECTOR_SERVICE)`), offer secure records and pin
+/// the collector's identity keys (`with_pins`).
+/// Returns the number of reports forwarded.
+pub async fn forward_pending(store: &CrashStore, client: &mut SentinelClient, collector: SocketAddr) -> Result<usize, SentinelError> {
+    if store.unforwarded().map_err(crash_error)?.is_empty() {
+        return Ok(0);
+    }
+    let mut channel = client.connect_secure(collector).await?;
+    let forwarded = send_reports(store, &mut channel).await?;
+    channel.shutdown().await?;
+    info!("Forwarded {} crash reports to {}", forwarded, collector);
+    Ok(forwarded)
+}
+
+/// Send unacknowledged reports over an established channel
+pub async fn send_reports<S: TransportStream>(store: &CrashStore, channel: &mut SecureChannel<S>) -> Result<usize, SentinelError> {
+    let mut forwarded = 0;
+    for report in store.unforwarded().map_err(crash_error)? {
+        channel.send_message(&report).await?;
+        let ack: CrashAck = channel.recv_message().await?;
+        if ack.id != report.id {
+            return Err(SentinelError::ProtocolError(format!(
+                "Collector acknowledged {} for crash report {}", ack.id, report.id
+            )));
+        }
+        store.mark_forwarded(&report, SystemTime::now()).map_err(crash_error)?;
+        forwarded += 1;
+    }
+    Ok(forwarded)
+}
+
+/// Store reports sent by a component until it closes the channel
+///
+/// Returns the number of reports stored.
+pub async fn receive_reports<S: TransportStream>(store: &CrashStore, channel: &mut SecureChannel<S>) -> Result<usize, SentinelError> {
+    let mut received = 0;
+    while let Some(record) = channel.recv().await? {
+        let report: CrashReport = protocol::decode_message(&record)?;
+        warn!("Crash of {} {} at {:?}: {}", report.component, report.version, report.occurred_at, report.message);
+        store.write(&report).map_err(crash_error)?;
+        channel.send_message(&CrashAck { id: report.id }).await?;
+        received += 1;
+    }
+    Ok(received)
+}
+
+fn crash_error(e: ark_crash::CrashError) -> SentinelError {
+    SentinelError::CrashReportError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel::Transcript;
+    use crate::pqc_tls::PQAlgorithm;
+    use ark_crash::CrashConfig;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_reports_are_marked_forwarded_once_acknowledged() {
+        let local = tempfile::tempdir().unwrap();
+        let central = tempfile::tempdir().unwrap();
+        let store = |dir: &tempfile::TempDir| {
+            CrashStore::new(&CrashConfig { enabled: true, directory: dir.path().to_path_buf(), retention: 10 })
+        };
+        let (local, central) = (store(&local), store(&central));
+        local.write(&CrashReport::new("sentinel", "1.0.0", "main", "index out of bounds", None, String::new(), BTreeMap::new())).unwrap();
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let transcript = Transcript::default().hash();
+        let mut client = SecureChannel::new(client, PQAlgorithm::Kyber768, &[3u8; 32], &transcript, true);
+        let mut server = SecureChannel::new(server, PQAlgorithm::Kyber768, &[3u8; 32], &transcript, false);
+
+        let collector = tokio::spawn(async move {
+            let received = receive_reports(&central, &mut server).await.unwrap();
+            (received, central.reports().unwrap())
+        });
+        assert_eq!(send_reports(&local, &mut client).await.unwrap(), 1);
+        client.shutdown().await.unwrap();
+
+        let (received, stored) = collector.await.unwrap();
+        assert_eq!(received, 1);
+        assert_eq!(stored[0].message, "index out of bounds");
+        assert!(local.unforwarded().unwrap().is_empty());
+        assert!(local.last().unwrap().unwrap().summary().forwarded);
+    }
+}
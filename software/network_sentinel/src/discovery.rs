@@ -0,0 +1,377 @@
+//! Sentinel Service Discovery - Signed Service Catalogs over PQ-TLS
+//! "Thine ears shall hear a word behind thee, saying, This is the way, walk ye in it" - Isaiah 30:21
+//!
+//! Field devices resolve named ARK services (patch server, ethics relay)
+//! instead of hard-coding addresses. An operator signs a service catalog
+//! offline with a Dilithium3 catalog key; the sentinel serves it to
+//! authorized peers requesting the `discovery` service over the negotiated
+//! channel. Clients pin the catalog public key, so a catalog is only
+//! accepted if it verifies against that key, has not expired, was not
+//! issued in the future and does not roll back to an older serial.
+//! Resolved catalogs are cached until they are due for refresh; when a
+//! refresh fails, the cached catalog keeps serving until it expires.
+
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use pqcrypto::prelude::*;
+use pqcrypto_dilithium::{detached_sign, verify_detached_signature, DetachedSignature, PublicKey, SecretKey};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::DilithiumSignatureBytes;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::egress::EgressDialer;
+use crate::protocol::{self, MAX_NAME_LENGTH};
+use crate::{SentinelClient, SentinelError};
+
+/// Sentinel service name answering with the signed catalog
+pub const DISCOVERY_SERVICE: &str = "discovery";
+
+/// Well-known name of the patch server
+pub const PATCH_SERVER: &str = "patch-server";
+
+/// Well-known name of the ethics relay
+pub const ETHICS_RELAY: &str = "ethics-relay";
+
+/// Most services in one catalog
+pub const MAX_CATALOG_SERVICES: usize = 128;
+
+/// Most addresses per service
+pub const MAX_SERVICE_ADDRESSES: usize = 8;
+
+/// Clock skew tolerated on `issued_at`
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+/// Addresses of one named service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceRecord {
+    /// Service name (e.g. `patch-server`)
+    pub name: String,
+    /// Endpoints, in order of preference
+    pub addresses: Vec<SocketAddr>,
+}
+
+/// Catalog of ARK services
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceCatalog {
+    /// Increases with every published catalog
+    pub serial: u64,
+    pub issued_at: SystemTime,
+    /// Catalog must not be used after this time
+    pub expires_at: SystemTime,
+    pub services: Vec<ServiceRecord>,
+}
+
+impl ServiceCatalog {
+    /// Addresses of a named service
+    pub fn lookup(&self, name: &str) -> Option<&[SocketAddr]> {
+        self.services
+            .iter()
+            .find(|service| service.name == name)
+            .map(|service| service.addresses.as_slice())
+    }
+}
+
+/// Catalog with its Dilithium3 signature, as served to peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedCatalog {
+    pub catalog: ServiceCatalog,
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl Validate for SignedCatalog {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("services", self.catalog.services.len(), MAX_CATALOG_SERVICES)?;
+        for service in &self.catalog.services {
+            decode::check_identifier("service name", &service.name, MAX_NAME_LENGTH)?;
+            decode::check_count("addresses", service.addresses.len(), MAX_SERVICE_ADDRESSES)?;
+            if service.addresses.is_empty() {
+                return Err(format!("service {} has no addresses", service.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SignedCatalog {
+    /// Read a signed catalog file
+    pub fn load(path: &Path) -> Result<Self, SentinelError> {
+        decode::json_validated(&std::fs::read(path)?, &DecodeLimits::FILE)
+            .map_err(|e| SentinelError::DiscoveryError(format!("Catalog {:?}: {}", path, e)))
+    }
+}
+
+/// Sign a catalog with the catalog key
+pub fn sign_catalog(catalog: ServiceCatalog, secret: &SecretKey) -> Result<SignedCatalog, SentinelError> {
+    let signature = detached_sign(blake3::hash(&catalog.signing_bytes()).as_bytes(), secret);
+    Ok(SignedCatalog {
+        catalog,
+        signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+            .map_err(|e| SentinelError::DiscoveryError(e.to_string()))?,
+    })
+}
+
+/// Verify a catalog against the pinned key and check it is fresh at `now`
+pub fn verify_catalog(signed: &SignedCatalog, pinned: &PublicKey, now: SystemTime) -> Result<(), SentinelError> {
+    let signature = DetachedSignature::from_bytes(signed.signature.as_bytes())
+        .map_err(|_| SentinelError::DiscoveryError("Invalid catalog signature encoding".into()))?;
+    verify_detached_signature(&signature, blake3::hash(&signed.catalog.signing_bytes()).as_bytes(), pinned)
+        .map_err(|_| SentinelError::DiscoveryError("Catalog signature does not match the pinned key".into()))?;
+
+    let catalog = &signed.catalog;
+    if catalog.issued_at > now + MAX_CLOCK_SKEW {
+        return Err(SentinelError::DiscoveryError(format!("Catalog {} is issued in the future", catalog.serial)));
+    }
+    if catalog.expires_at <= now {
+        return Err(SentinelError::DiscoveryError(format!("Catalog {} has expired", catalog.serial)));
+    }
+    Ok(())
+}
+
+/// Read a pinned catalog public key (hex)
+pub fn load_pinned_key(path: &Path) -> Result<PublicKey, SentinelError> {
+    PublicKey::from_bytes(&read_hex(path)?)
+        .map_err(|_| SentinelError::DiscoveryError(format!("Invalid catalog key {:?}", path)))
+}
+
+/// Load the catalog signing key, generating one if absent
+///
+/// The secret key is stored as hex in `path` and the public key to pin on
+/// clients beside it with a `.pub` extension.
+pub fn load_or_generate_catalog_key(path: &Path) -> Result<(PublicKey, SecretKey), SentinelError> {
+    let public_path = path.with_extension("pub");
+    if path.exists() {
+        let secret = SecretKey::from_bytes(&read_hex(path)?)
+            .map_err(|_| SentinelError::DiscoveryError(format!("Invalid catalog signing key {:?}", path)))?;
+        return Ok((load_pinned_key(&public_path)?, secret));
+    }
+
+    let (public, secret) = pqcrypto_dilithium::keypair();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, to_hex(secret.as_bytes()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::write(&public_path, to_hex(public.as_bytes()))?;
+    info!("Generated service catalog signing key {:?}", path);
+
+    Ok((public, secret))
+}
+
+/// Read a hex-encoded key file
+pub fn read_hex(path: &Path) -> Result<Vec<u8>, SentinelError> {
+    let text = std::fs::read_to_string(path)?;
+    let text = text.trim();
+    let malformed = || SentinelError::DiscoveryError(format!("Malformed hex in {:?}", path));
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return Err(malformed());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| malformed()))
+        .collect()
+}
+
+/// Lowercase hex of `bytes`, as key files store it
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Service resolver configuration
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    /// Sentinel serving the catalog
+    pub sentinel: SocketAddr,
+    /// Identity presented to the sentinel
+    pub peer_id: String,
+    /// Negotiate post-quantum security with the sentinel
+    pub quantum_resistant: bool,
+    /// Age after which a cached catalog is refreshed
+    pub refresh_interval: Duration,
+    /// Egress policy the connection to the sentinel must pass
+    pub egress: Option<Arc<EgressDialer>>,
+}
+
+impl ResolverConfig {
+    /// Resolve through `sentinel`, refreshing every five minutes
+    pub fn new(sentinel: SocketAddr, peer_id: impl Into<String>) -> Self {
+        Self {
+            sentinel,
+            peer_id: peer_id.into(),
+            quantum_resistant: true,
+            refresh_interval: Duration::from_secs(300),
+            egress: None,
+        }
+    }
+}
+
+/// Catalog accepted by the resolver
+struct CachedCatalog {
+    catalog: ServiceCatalog,
+    fetched_at: Instant,
+}
+
+/// Resolves named ARK services through the sentinel's signed catalog
+pub struct ServiceResolver {
+    config: ResolverConfig,
+    pinned: PublicKey,
+    cache: Mutex<Option<CachedCatalog>>,
+}
+
+impl ServiceResolver {
+    /// Create a resolver trusting only catalogs signed by `pinned`
+    pub fn new(config: ResolverConfig, pinned: PublicKey) -> Self {
+        Self {
+            config,
+            pinned,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Addresses of a named service, refreshing the catalog when due
+    pub async fn resolve(&self, name: &str) -> Result<Vec<SocketAddr>, SentinelError> {
+        if self.refresh_due() {
+            if let Err(e) = self.refresh().await {
+                warn!("Service catalog refresh failed: {}", e);
+            }
+        }
+
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = cache
+            .as_ref()
+            .filter(|cached| cached.catalog.expires_at > SystemTime::now())
+            .ok_or_else(|| SentinelError::DiscoveryError("No fresh service catalog available".into()))?;
+        cached
+            .catalog
+            .lookup(name)
+            .map(<[SocketAddr]>::to_vec)
+            .ok_or_else(|| SentinelError::DiscoveryError(format!("Unknown service {}", name)))
+    }
+
+    /// Fetch the catalog from the sentinel and install it
+    pub async fn refresh(&self) -> Result<u64, SentinelError> {
+        let mut client = SentinelClient::new(self.config.quantum_resistant)
+            .with_service(self.config.peer_id.clone(), DISCOVERY_SERVICE);
+        if let Some(egress) = &self.config.egress {
+            client = client.with_egress(egress.clone());
+        }
+        let mut stream = client.connect(self.config.sentinel).await?;
+        let signed: SignedCatalog = protocol::read_message(&mut stream).await?;
+        self.install(signed, SystemTime::now())
+    }
+
+    /// Verify a catalog and cache it, returning its serial
+    ///
+    /// Catalogs older than the cached one are refused so a replayed catalog
+    /// cannot roll back service addresses.
+    pub fn install(&self, signed: SignedCatalog, now: SystemTime) -> Result<u64, SentinelError> {
+        verify_catalog(&signed, &self.pinned, now)?;
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cache.as_ref() {
+            if signed.catalog.serial < cached.catalog.serial {
+                return Err(SentinelError::DiscoveryError(format!(
+                    "Catalog serial {} is older than cached serial {}",
+                    signed.catalog.serial, cached.catalog.serial
+                )));
+            }
+        }
+
+        let serial = signed.catalog.serial;
+        if cache.as_ref().map_or(true, |cached| cached.catalog.serial != serial) {
+            info!("Installed service catalog {} with {} services", serial, signed.catalog.services.len());
+        }
+        *cache = Some(CachedCatalog {
+            catalog: signed.catalog,
+            fetched_at: Instant::now(),
+        });
+        Ok(serial)
+    }
+
+    fn refresh_due(&self) -> bool {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        match cache.as_ref() {
+            Some(cached) => {
+                let due = cached.fetched_at.elapsed() >= self.config.refresh_interval
+                    || cached.catalog.expires_at <= SystemTime::now();
+                debug!("Service catalog {} refresh due: {}", cached.catalog.serial, due);
+                due
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pqcrypto_dilithium::keypair;
+
+    fn catalog(serial: u64, valid_for: Duration) -> ServiceCatalog {
+        let now = SystemTime::now();
+        ServiceCatalog {
+            serial,
+            issued_at: now,
+            expires_at: now + valid_for,
+            services: vec![ServiceRecord {
+                name: PATCH_SERVER.to_string(),
+                addresses: vec!["10.0.0.5:8443".parse().unwrap()],
+            }],
+        }
+    }
+
+    fn resolver(pinned: PublicKey) -> ServiceResolver {
+        ServiceResolver::new(ResolverConfig::new("127.0.0.1:8443".parse().unwrap(), "sensor-1"), pinned)
+    }
+
+    #[test]
+    fn test_pinned_key_enforced() {
+        let (public, secret) = keypair();
+        let (_, other_secret) = keypair();
+        let resolver = resolver(public);
+
+        let forged = sign_catalog(catalog(1, Duration::from_secs(3600)), &other_secret).unwrap();
+        assert!(resolver.install(forged, SystemTime::now()).is_err());
+
+        let mut tampered = sign_catalog(catalog(1, Duration::from_secs(3600)), &secret).unwrap();
+        tampered.catalog.services[0].addresses[0] = "203.0.113.9:8443".parse().unwrap();
+        assert!(resolver.install(tampered, SystemTime::now()).is_err());
+
+        let genuine = sign_catalog(catalog(1, Duration::from_secs(3600)), &secret).unwrap();
+        assert_eq!(resolver.install(genuine, SystemTime::now()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_freshness_and_rollback() {
+        let (public, secret) = keypair();
+        let resolver = resolver(public);
+        let now = SystemTime::now();
+
+        let expired = sign_catalog(catalog(5, Duration::from_secs(60)), &secret).unwrap();
+        assert!(resolver.install(expired, now + Duration::from_secs(61)).is_err());
+
+        resolver.install(sign_catalog(catalog(5, Duration::from_secs(3600)), &secret).unwrap(), now).unwrap();
+        let older = sign_catalog(catalog(4, Duration::from_secs(3600)), &secret).unwrap();
+        assert!(resolver.install(older, now).is_err());
+        assert_eq!(resolver.install(sign_catalog(catalog(6, Duration::from_secs(3600)), &secret).unwrap(), now).unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_from_cache() {
+        let (public, secret) = keypair();
+        let resolver = resolver(public);
+        resolver.install(sign_catalog(catalog(1, Duration::from_secs(3600)), &secret).unwrap(), SystemTime::now()).unwrap();
+
+        // Cached catalog is fresh, so no sentinel is contacted
+        let addresses = resolver.resolve(PATCH_SERVER).await.unwrap();
+        assert_eq!(addresses, vec!["10.0.0.5:8443".parse::<SocketAddr>().unwrap()]);
+        assert!(resolver.resolve(ETHICS_RELAY).await.is_err());
+    }
+}
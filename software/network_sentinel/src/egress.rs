@@ -0,0 +1,350 @@
+//! Sentinel Egress Policy - Outbound Connections by Signed Allow-List Only
+//! "Hitherto shalt thou come, but no further" - Job 38:11
+//!
+//! ARK components open outbound connections through an `EgressDialer`
+//! rather than dialing sockets themselves. The dialer checks every
+//! destination against an allow-list before the socket is created: a TCP
+//! destination must fall in an allowed network and port range, a UNIX
+//! socket or vsock destination must be listed exactly. Rules may be limited
+//! to one component. Anything else is refused, logged and counted; the
+//! counts are a tamper indicator, since a correctly configured component
+//! never attempts a destination it was not given.
+//!
+//! The allow-list is signed offline with a Dilithium3 egress key, like the
+//! service catalog. A policy is only installed if it verifies against the
+//! pinned key, is fresh and does not roll back to an older serial. Until
+//! one is installed the dialer refuses every destination.
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::SystemTime;
+
+use pqcrypto::prelude::*;
+use pqcrypto_dilithium::{detached_sign, verify_detached_signature, DetachedSignature, PublicKey, SecretKey};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::DilithiumSignatureBytes;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+use crate::discovery::MAX_CLOCK_SKEW;
+use crate::protocol::MAX_NAME_LENGTH;
+use crate::transport::{self, BoxedStream, Endpoint};
+use crate::SentinelError;
+
+/// Most rules in one policy
+pub const MAX_EGRESS_RULES: usize = 256;
+
+/// Component name of rules that apply to every component
+pub const ANY_COMPONENT: &str = "*";
+
+/// Most components counted separately in violation counters
+const MAX_COUNTED_COMPONENTS: usize = 64;
+
+/// Destinations a rule allows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EgressTarget {
+    /// TCP to an address in `network`/`prefix_len`, on a port in `ports` (inclusive; any when `None`)
+    Tcp { network: IpAddr, prefix_len: u8, ports: Option<[u16; 2]> },
+    /// UNIX domain socket at exactly `path`
+    Unix { path: PathBuf },
+    /// vsock context `cid`, on `port` (any when `None`)
+    Vsock { cid: u32, port: Option<u32> },
+}
+
+impl EgressTarget {
+    /// Whether `endpoint` is an allowed destination
+    pub fn allows(&self, endpoint: &Endpoint) -> bool {
+        match (self, endpoint) {
+            (EgressTarget::Tcp { network, prefix_len, ports }, Endpoint::Tcp(addr)) => {
+                in_network(addr.ip(), *network, *prefix_len)
+                    && ports.map_or(true, |[low, high]| (low..=high).contains(&addr.port()))
+            }
+            (EgressTarget::Unix { path }, Endpoint::Unix(destination)) => path == destination,
+            (EgressTarget::Vsock { cid, port }, Endpoint::Vsock { cid: destination, port: destination_port }) => {
+                cid == destination && port.map_or(true, |port| port == *destination_port)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// One allowed destination
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressRule {
+    /// Component allowed to connect, or `*` for every component
+    pub component: String,
+    pub target: EgressTarget,
+    /// Why the destination is needed, for review
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Outbound allow-list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressPolicy {
+    /// Increases with every published policy
+    pub serial: u64,
+    pub issued_at: SystemTime,
+    /// Policy must not be used after this time
+    pub expires_at: SystemTime,
+    pub rules: Vec<EgressRule>,
+}
+
+impl EgressPolicy {
+    /// Rule allowing `component` to connect to `endpoint`, if any
+    pub fn allowing(&self, component: &str, endpoint: &Endpoint) -> Option<&EgressRule> {
+        self.rules.iter().find(|rule| {
+            (rule.component == ANY_COMPONENT || rule.component == component) && rule.target.allows(endpoint)
+        })
+    }
+}
+
+/// Policy with its Dilithium3 signature, as distributed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedEgressPolicy {
+    pub policy: EgressPolicy,
+    pub signature: DilithiumSignatureBytes,
+}
+
+impl Validate for SignedEgressPolicy {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("rules", self.policy.rules.len(), MAX_EGRESS_RULES)?;
+        for rule in &self.policy.rules {
+            if rule.component != ANY_COMPONENT {
+                decode::check_identifier("component", &rule.component, MAX_NAME_LENGTH)?;
+            }
+            decode::check_len("description", &rule.description, 256)?;
+            match &rule.target {
+                EgressTarget::Tcp { network, prefix_len, ports } => {
+                    let max = if network.is_ipv4() { 32 } else { 128 };
+                    if *prefix_len > max {
+                        return Err(format!("prefix length {} is too long for {}", prefix_len, network));
+                    }
+                    if ports.is_some_and(|[low, high]| low > high) {
+                        return Err(format!("port range of {} is reversed", network));
+                    }
+                }
+                EgressTarget::Unix { path } => decode::check_len("path", &path.to_string_lossy(), 4096)?,
+                EgressTarget::Vsock { .. } => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SignedEgressPolicy {
+    /// Read a signed policy file
+    pub fn load(path: &Path) -> Result<Self, SentinelError> {
+        decode::json_validated(&std::fs::read(path)?, &DecodeLimits::FILE)
+            .map_err(|e| SentinelError::ConfigError(format!("Egress policy {:?}: {}", path, e)))
+    }
+}
+
+/// Sign a policy with the egress key
+pub fn sign_policy(policy: EgressPolicy, secret: &SecretKey) -> Result<SignedEgressPolicy, SentinelError> {
+    let signature = detached_sign(blake3::hash(&policy.signing_bytes()).as_bytes(), secret);
+    Ok(SignedEgressPolicy {
+        policy,
+        signature: DilithiumSignatureBytes::from_slice(signature.as_bytes())
+            .map_err(|e| SentinelError::ConfigError(e.to_string()))?,
+    })
+}
+
+/// Verify a policy against the pinned key and check it is fresh at `now`
+pub fn verify_policy(signed: &SignedEgressPolicy, pinned: &PublicKey, now: SystemTime) -> Result<(), SentinelError> {
+    let signature = DetachedSignature::from_bytes(signed.signature.as_bytes())
+        .map_err(|_| SentinelError::ConfigError("Invalid egress policy signature encoding".into()))?;
+    verify_detached_signature(&signature, blake3::hash(&signed.policy.signing_bytes()).as_bytes(), pinned)
+        .map_err(|_| SentinelError::ConfigError("Egress policy signature does not match the pinned key".into()))?;
+
+    let policy = &signed.policy;
+    if policy.issued_at > now + MAX_CLOCK_SKEW {
+        return Err(SentinelError::ConfigError(format!("Egress policy {} is issued in the future", policy.serial)));
+    }
+    if policy.expires_at <= now {
+        return Err(SentinelError::ConfigError(format!("Egress policy {} has expired", policy.serial)));
+    }
+    Ok(())
+}
+
+/// Whether `ip` lies in `network`/`prefix_len`
+fn in_network(ip: IpAddr, network: IpAddr, prefix_len: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix_len.min(32))).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix_len.min(128))).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Refused connection attempts, the tamper indicator
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EgressViolations {
+    /// Every refused attempt
+    pub total: u64,
+    /// Refused attempts by component; components beyond the first 64 are counted under `*`
+    pub by_component: BTreeMap<String, u64>,
+    /// Most recent refused attempt: when, which component and which destination
+    pub last: Option<(SystemTime, String, String)>,
+}
+
+/// Dialer enforcing the egress policy for outbound connections
+pub struct EgressDialer {
+    pinned: PublicKey,
+    policy: RwLock<Option<EgressPolicy>>,
+    total: AtomicU64,
+    violations: Mutex<EgressViolations>,
+}
+
+impl std::fmt::Debug for EgressDialer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EgressDialer")
+            .field("serial", &self.serial())
+            .field("violations", &self.total.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl EgressDialer {
+    /// Create a dialer trusting only policies signed by `pinned`; refuses everything until one is installed
+    pub fn new(pinned: PublicKey) -> Self {
+        Self {
+            pinned,
+            policy: RwLock::new(None),
+            total: AtomicU64::new(0),
+            violations: Mutex::new(EgressViolations::default()),
+        }
+    }
+
+    /// Verify a policy and enforce it, returning its serial
+    ///
+    /// Policies older than the installed one are refused so a replayed
+    /// policy cannot widen the allow-list again.
+    pub fn install(&self, signed: SignedEgressPolicy, now: SystemTime) -> Result<u64, SentinelError> {
+        verify_policy(&signed, &self.pinned, now)?;
+        let mut policy = self.policy.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(installed) = policy.as_ref().filter(|installed| signed.policy.serial < installed.serial) {
+            return Err(SentinelError::ConfigError(format!(
+                "Egress policy serial {} is older than installed serial {}",
+                signed.policy.serial, installed.serial
+            )));
+        }
+        let serial = signed.policy.serial;
+        info!("Enforcing egress policy {} with {} rules", serial, signed.policy.rules.len());
+        *policy = Some(signed.policy);
+        Ok(serial)
+    }
+
+    /// Allow `component` to connect to `endpoint`, or refuse and count the attempt
+    pub fn check(&self, component: &str, endpoint: &Endpoint) -> Result<(), SentinelError> {
+        let policy = self.policy.read().unwrap_or_else(|e| e.into_inner());
+        let now = SystemTime::now();
+        let reason = match policy.as_ref() {
+            Some(policy) if policy.expires_at <= now => format!("egress policy {} has expired", policy.serial),
+            Some(policy) => match policy.allowing(component, endpoint) {
+                Some(_) => return Ok(()),
+                None => "destination is not on the allow-list".to_string(),
+            },
+            None => "no egress policy is installed".to_string(),
+        };
+        drop(policy);
+
+        let total = self.total.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut violations = self.violations.lock().unwrap_or_else(|e| e.into_inner());
+        violations.total = total;
+        let counted = if violations.by_component.contains_key(component)
+            || violations.by_component.len() < MAX_COUNTED_COMPONENTS
+        {
+            component
+        } else {
+            ANY_COMPONENT
+        };
+        *violations.by_component.entry(counted.to_string()).or_default() += 1;
+        violations.last = Some((now, component.to_string(), endpoint.to_string()));
+        warn!("Egress denied: {} to {}: {} ({} attempts refused)", component, endpoint, reason, total);
+        Err(SentinelError::EgressDenied(format!("{} may not connect to {}: {}", component, endpoint, reason)))
+    }
+
+    /// Connect `component` to `endpoint` if the policy allows it
+    pub async fn connect(&self, component: &str, endpoint: &Endpoint) -> Result<BoxedStream, SentinelError> {
+        self.check(component, endpoint)?;
+        Ok(transport::connect(endpoint).await?)
+    }
+
+    /// Connect `component` over TCP to `addr` if the policy allows it
+    pub async fn connect_tcp(&self, component: &str, addr: SocketAddr) -> Result<TcpStream, SentinelError> {
+        self.check(component, &Endpoint::Tcp(addr))?;
+        Ok(TcpStream::connect(addr).await?)
+    }
+
+    /// Refused attempts since start
+    pub fn violations(&self) -> EgressViolations {
+        self.violations.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Serial of the enforced policy
+    pub fn serial(&self) -> Option<u64> {
+        self.policy.read().unwrap_or_else(|e| e.into_inner()).as_ref().map(|policy| policy.serial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pqcrypto_dilithium::keypair;
+    use std::time::Duration;
+
+    fn policy(serial: u64, rules: Vec<EgressRule>) -> EgressPolicy {
+        let now = SystemTime::now();
+        EgressPolicy { serial, issued_at: now, expires_at: now + Duration::from_secs(3600), rules }
+    }
+
+    #[test]
+    fn test_only_signed_destinations_are_dialed() {
+        let (public, secret) = keypair();
+        let (_, other_secret) = keypair();
+        let dialer = EgressDialer::new(public);
+        let patch_server = Endpoint::Tcp("10.0.4.7:8443".parse().unwrap());
+        assert!(matches!(dialer.check("cold_mirror", &patch_server), Err(SentinelError::EgressDenied(_))));
+
+        let rules = vec![
+            EgressRule {
+                component: ANY_COMPONENT.into(),
+                target: EgressTarget::Tcp { network: "10.0.4.0".parse().unwrap(), prefix_len: 24, ports: Some([8443, 8443]) },
+                description: "patch servers".into(),
+            },
+            EgressRule {
+                component: "ethics_dsl".into(),
+                target: EgressTarget::Unix { path: "/run/ark/relay.sock".into() },
+                description: String::new(),
+            },
+        ];
+        let forged = sign_policy(policy(1, rules.clone()), &other_secret).unwrap();
+        assert!(dialer.install(forged, SystemTime::now()).is_err());
+        let signed = sign_policy(policy(2, rules.clone()), &secret).unwrap();
+        assert_eq!(dialer.install(signed, SystemTime::now()).unwrap(), 2);
+        let replayed = sign_policy(policy(1, rules), &secret).unwrap();
+        assert!(dialer.install(replayed, SystemTime::now()).is_err());
+
+        let relay = Endpoint::Unix("/run/ark/relay.sock".into());
+        dialer.check("cold_mirror", &patch_server).unwrap();
+        dialer.check("ethics_dsl", &relay).unwrap();
+        assert!(dialer.check("cold_mirror", &relay).is_err());
+        assert!(dialer.check("cold_mirror", &Endpoint::Tcp("10.0.4.7:22".parse().unwrap())).is_err());
+        assert!(dialer.check("cold_mirror", &Endpoint::Tcp("203.0.113.1:8443".parse().unwrap())).is_err());
+
+        let violations = dialer.violations();
+        assert_eq!(violations.total, 4);
+        assert_eq!(violations.by_component["cold_mirror"], 4);
+        assert_eq!(violations.last.unwrap().2, "203.0.113.1:8443");
+    }
+}
@@ -0,0 +1,710 @@
+//! Sentinel Keep-Alive - Record Layer Liveness and RTT
+//! "Watch therefore: for ye know not what hour your Lord doth come" - Matthew 24:42
+//!
+//! Connections to field devices die silently behind NAT: neither side sees
+//! a reset, and an idle link looks the same as a dead one. When both peers
+//! negotiate the `KeepAlive` extension, the data stream after the access
+//! grant is carried in records of the form `type: u8, length: u32 (BE),
+//! payload`. Each side sends a `Ping` every `interval`; the peer answers
+//! with a `Pong` echoing the ping's nonce, which yields the round-trip time.
+//! Any record from the peer proves it alive. After `miss_threshold`
+//! consecutive intervals without hearing from the peer it is declared dead
+//! and the connection is closed.
+//!
+//! `LiveConnection` runs the record layer on a background task and exposes
+//! only data records plus shared `ConnectionMetrics`. When compression was
+//! negotiated, data may also travel in `Compressed` records (see
+//! `compression`), which are expanded before delivery.
+//!
+//! Over a `SecureChannel` (`start_sealed`) the same records travel inside
+//! sealed records, the type byte first and the payload after it; the
+//! length prefix is the channel's own. Sealed connections are not
+//! compressed.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use pq_types::decode::Validate;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::channel::{SecureChannel, SecureReader, SecureWriter};
+use crate::compression::{CompressionMetrics, CompressionSnapshot, RecordCodec};
+use crate::SentinelError;
+
+/// Largest record payload
+pub const MAX_RECORD_PAYLOAD: usize = 16 * 1024;
+
+/// Records buffered in each direction before senders wait
+const RECORD_QUEUE: usize = 64;
+
+/// Type of a record on a keep-alive connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    /// Application data
+    Data,
+    /// Liveness probe carrying a nonce
+    Ping,
+    /// Answer to a probe echoing its nonce
+    Pong,
+    /// Application data compressed with the negotiated codec
+    Compressed,
+}
+
+impl RecordType {
+    fn to_byte(self) -> u8 {
+        match self {
+            RecordType::Data => 0,
+            RecordType::Ping => 1,
+            RecordType::Pong => 2,
+            RecordType::Compressed => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(RecordType::Data),
+            1 => Some(RecordType::Ping),
+            2 => Some(RecordType::Pong),
+            3 => Some(RecordType::Compressed),
+            _ => None,
+        }
+    }
+}
+
+/// Keep-alive settings
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KeepAliveConfig {
+    /// Time between pings
+    pub interval: Duration,
+    /// Consecutive silent intervals after which the peer is dead
+    pub miss_threshold: u32,
+}
+
+impl Default for KeepAliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            miss_threshold: 3,
+        }
+    }
+}
+
+impl Validate for KeepAliveConfig {
+    fn validate(&self) -> Result<(), String> {
+        if self.interval.is_zero() {
+            return Err("keep-alive interval must be positive".to_string());
+        }
+        if self.miss_threshold == 0 {
+            return Err("keep-alive miss threshold must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Liveness and round-trip metrics of one connection
+#[derive(Debug, Default)]
+pub struct ConnectionMetrics {
+    last_rtt_micros: AtomicU64,
+    smoothed_rtt_micros: AtomicU64,
+    pings_sent: AtomicU64,
+    pongs_received: AtomicU64,
+    consecutive_misses: AtomicU32,
+    dead: AtomicBool,
+    compression: CompressionMetrics,
+}
+
+/// Point-in-time copy of `ConnectionMetrics`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    /// Most recent round-trip time
+    pub last_rtt: Option<Duration>,
+    /// Smoothed round-trip time (1/8 gain, as TCP's SRTT)
+    pub smoothed_rtt: Option<Duration>,
+    /// Pings sent
+    pub pings_sent: u64,
+    /// Pongs matching an outstanding ping
+    pub pongs_received: u64,
+    /// Intervals in a row without hearing from the peer
+    pub consecutive_misses: u32,
+    /// Peer has been declared dead
+    pub dead: bool,
+    /// Compression counters; all zero when compression is off
+    #[serde(default)]
+    pub compression: CompressionSnapshot,
+}
+
+impl ConnectionMetrics {
+    /// Current values
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let rtt = |micros: u64| (micros > 0).then(|| Duration::from_micros(micros));
+        MetricsSnapshot {
+            last_rtt: rtt(self.last_rtt_micros.load(Ordering::Relaxed)),
+            smoothed_rtt: rtt(self.smoothed_rtt_micros.load(Ordering::Relaxed)),
+            pings_sent: self.pings_sent.load(Ordering::Relaxed),
+            pongs_received: self.pongs_received.load(Ordering::Relaxed),
+            consecutive_misses: self.consecutive_misses.load(Ordering::Relaxed),
+            dead: self.dead.load(Ordering::Relaxed),
+            compression: self.compression.snapshot(),
+        }
+    }
+
+    /// Compression counters
+    pub fn compression(&self) -> &CompressionMetrics {
+        &self.compression
+    }
+
+    /// Whether the peer has been declared dead
+    pub fn is_dead(&self) -> bool {
+        self.dead.load(Ordering::Relaxed)
+    }
+
+    fn record_rtt(&self, rtt: Duration) {
+        let sample = (rtt.as_micros() as u64).max(1);
+        self.last_rtt_micros.store(sample, Ordering::Relaxed);
+        let smoothed = match self.smoothed_rtt_micros.load(Ordering::Relaxed) {
+            0 => sample,
+            previous => previous - previous / 8 + sample / 8,
+        };
+        self.smoothed_rtt_micros.store(smoothed, Ordering::Relaxed);
+        self.pongs_received.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Outcome of a keep-alive interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tick {
+    /// Send a ping with this nonce
+    Ping(u64),
+    /// The peer missed too many intervals
+    Dead,
+}
+
+/// Ping scheduling and miss counting, independent of I/O
+#[derive(Debug)]
+pub struct KeepAliveTracker {
+    config: KeepAliveConfig,
+    next_nonce: u64,
+    outstanding: Option<(u64, Instant)>,
+    heard: bool,
+    misses: u32,
+}
+
+impl KeepAliveTracker {
+    /// Create a tracker
+    pub fn new(config: KeepAliveConfig) -> Self {
+        Self {
+            config,
+            next_nonce: 0,
+            outstanding: None,
+            heard: false,
+            misses: 0,
+        }
+    }
+
+    /// Intervals in a row without hearing from the peer
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    /// Called once per interval
+    pub fn tick(&mut self, now: Instant) -> Tick {
+        if self.heard {
+            self.misses = 0;
+        } else if self.outstanding.is_some() {
+            self.misses += 1;
+        }
+        self.heard = false;
+
+        if self.misses >= self.config.miss_threshold {
+            return Tick::Dead;
+        }
+        self.next_nonce += 1;
+        self.outstanding = Some((self.next_nonce, now));
+        Tick::Ping(self.next_nonce)
+    }
+
+    /// Any record arrived from the peer
+    pub fn heard(&mut self) {
+        self.heard = true;
+    }
+
+    /// A pong arrived; returns the round-trip time if it answers the outstanding ping
+    pub fn pong(&mut self, nonce: u64, now: Instant) -> Option<Duration> {
+        self.heard = true;
+        match self.outstanding {
+            Some((outstanding, sent)) if outstanding == nonce => {
+                self.outstanding = None;
+                Some(now.saturating_duration_since(sent))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Write one record
+pub async fn write_record<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    record_type: RecordType,
+    payload: &[u8],
+) -> Result<(), SentinelError> {
+    if payload.len() > MAX_RECORD_PAYLOAD {
+        return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", payload.len())));
+    }
+    stream.write_u8(record_type.to_byte()).await?;
+    stream.write_u32(payload.len() as u32).await?;
+    stream.write_all(payload).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read one record
+pub async fn read_record<R: AsyncRead + Unpin>(stream: &mut R) -> Result<(RecordType, Vec<u8>), SentinelError> {
+    let byte = stream.read_u8().await?;
+    let record_type = RecordType::from_byte(byte)
+        .ok_or_else(|| SentinelError::ProtocolError(format!("Unknown record type {}", byte)))?;
+    let len = stream.read_u32().await? as usize;
+    if len > MAX_RECORD_PAYLOAD {
+        return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", len)));
+    }
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok((record_type, payload))
+}
+
+/// Transport the record layer reads records from
+trait RecordSource: Send + 'static {
+    fn read(&mut self) -> impl Future<Output = Result<(RecordType, Vec<u8>), SentinelError>> + Send;
+}
+
+/// Transport the record layer writes records to
+trait RecordSink: Send + 'static {
+    fn write(&mut self, record_type: RecordType, payload: &[u8]) -> impl Future<Output = Result<(), SentinelError>> + Send;
+
+    fn close(&mut self) -> impl Future<Output = Result<(), SentinelError>> + Send;
+}
+
+/// Records framed directly on a stream
+struct Framed<T>(T);
+
+impl<R: AsyncRead + Unpin + Send + 'static> RecordSource for Framed<R> {
+    async fn read(&mut self) -> Result<(RecordType, Vec<u8>), SentinelError> {
+        read_record(&mut self.0).await
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send + 'static> RecordSink for Framed<W> {
+    async fn write(&mut self, record_type: RecordType, payload: &[u8]) -> Result<(), SentinelError> {
+        write_record(&mut self.0, record_type, payload).await
+    }
+
+    async fn close(&mut self) -> Result<(), SentinelError> {
+        self.0.shutdown().await?;
+        Ok(())
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Send + 'static> RecordSource for SecureReader<S> {
+    async fn read(&mut self) -> Result<(RecordType, Vec<u8>), SentinelError> {
+        // The peer closing reads as end of stream, as on a plain stream
+        let mut record = self.recv().await?
+            .ok_or_else(|| SentinelError::IoError(std::io::ErrorKind::UnexpectedEof.into()))?;
+        if record.is_empty() {
+            return Err(SentinelError::ProtocolError("Empty sealed record".into()));
+        }
+        let byte = record.remove(0);
+        let record_type = RecordType::from_byte(byte)
+            .ok_or_else(|| SentinelError::ProtocolError(format!("Unknown record type {}", byte)))?;
+        if record.len() > MAX_RECORD_PAYLOAD {
+            return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", record.len())));
+        }
+        Ok((record_type, record))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Send + 'static> RecordSink for SecureWriter<S> {
+    async fn write(&mut self, record_type: RecordType, payload: &[u8]) -> Result<(), SentinelError> {
+        if payload.len() > MAX_RECORD_PAYLOAD {
+            return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", payload.len())));
+        }
+        let mut record = Vec::with_capacity(1 + payload.len());
+        record.push(record_type.to_byte());
+        record.extend_from_slice(payload);
+        self.send(&record).await
+    }
+
+    async fn close(&mut self) -> Result<(), SentinelError> {
+        self.shutdown().await
+    }
+}
+
+fn nonce_of(payload: &[u8]) -> Result<u64, SentinelError> {
+    let bytes: [u8; 8] = payload
+        .try_into()
+        .map_err(|_| SentinelError::ProtocolError("Keep-alive nonce must be 8 bytes".into()))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Cloneable sending half of a `LiveConnection`
+#[derive(Clone)]
+pub struct RecordSender {
+    outgoing: mpsc::Sender<(RecordType, Vec<u8>)>,
+    metrics: Arc<ConnectionMetrics>,
+    codec: Option<Arc<RecordCodec>>,
+}
+
+impl RecordSender {
+    /// Send a data record
+    pub async fn send(&self, payload: Vec<u8>) -> Result<(), SentinelError> {
+        self.send_record(None, payload).await
+    }
+
+    /// Send a data record of a known payload type
+    ///
+    /// With compression on, the payload is compressed against the shared
+    /// dictionary for `payload_type` if one was agreed.
+    pub async fn send_typed(&self, payload_type: &str, payload: Vec<u8>) -> Result<(), SentinelError> {
+        self.send_record(Some(payload_type), payload).await
+    }
+
+    async fn send_record(&self, payload_type: Option<&str>, payload: Vec<u8>) -> Result<(), SentinelError> {
+        if payload.len() > MAX_RECORD_PAYLOAD {
+            return Err(SentinelError::ProtocolError(format!("Record too large: {} bytes", payload.len())));
+        }
+        let compressed = match &self.codec {
+            Some(codec) => codec.compress(payload_type, &payload, self.metrics.compression())?,
+            None => None,
+        };
+        let record = match compressed {
+            Some(compressed) => (RecordType::Compressed, compressed),
+            None => (RecordType::Data, payload),
+        };
+        self.outgoing
+            .send(record)
+            .await
+            .map_err(|_| SentinelError::PeerUnresponsive("connection closed".into()))
+    }
+}
+
+/// Data channel of a connection running the keep-alive record layer
+pub struct LiveConnection {
+    sender: RecordSender,
+    incoming: mpsc::Receiver<Vec<u8>>,
+    metrics: Arc<ConnectionMetrics>,
+    driver: JoinHandle<Result<(), SentinelError>>,
+}
+
+impl LiveConnection {
+    /// Run the record layer over an established, granted stream
+    pub fn start<S>(stream: S, config: KeepAliveConfig) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        Self::start_with(stream, config, None)
+    }
+
+    /// Run the record layer with negotiated compression
+    pub fn start_compressed<S>(stream: S, config: KeepAliveConfig, codec: RecordCodec) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        Self::start_with(stream, config, Some(Arc::new(codec)))
+    }
+
+    /// Run the record layer inside the sealed records of `channel`
+    ///
+    /// Derive anything needed from the channel's exporter (such as a
+    /// `ChannelBinding`) before handing it over.
+    pub fn start_sealed<S>(channel: SecureChannel<S>, config: KeepAliveConfig) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (reader, writer) = channel.split();
+        Self::start_over(reader, writer, config, None)
+    }
+
+    fn start_with<S>(stream: S, config: KeepAliveConfig, codec: Option<Arc<RecordCodec>>) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = tokio::io::split(stream);
+        Self::start_over(Framed(reader), Framed(writer), config, codec)
+    }
+
+    fn start_over(
+        reader: impl RecordSource,
+        writer: impl RecordSink,
+        config: KeepAliveConfig,
+        codec: Option<Arc<RecordCodec>>,
+    ) -> Self {
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(RECORD_QUEUE);
+        let (incoming_tx, incoming_rx) = mpsc::channel(RECORD_QUEUE);
+        let metrics = Arc::new(ConnectionMetrics::default());
+        let driver = tokio::spawn(drive(reader, writer, config, codec.clone(), outgoing_rx, incoming_tx, metrics.clone()));
+
+        Self {
+            sender: RecordSender { outgoing: outgoing_tx, metrics: metrics.clone(), codec },
+            incoming: incoming_rx,
+            metrics,
+            driver,
+        }
+    }
+
+    /// Send a data record
+    pub async fn send(&self, payload: Vec<u8>) -> Result<(), SentinelError> {
+        self.sender.send(payload).await
+    }
+
+    /// Send a data record of a known payload type
+    ///
+    /// With compression on, the payload is compressed against the shared
+    /// dictionary for `payload_type` if one was agreed.
+    pub async fn send_typed(&self, payload_type: &str, payload: Vec<u8>) -> Result<(), SentinelError> {
+        self.sender.send_typed(payload_type, payload).await
+    }
+
+    /// Handle for sending from other tasks while this one receives
+    ///
+    /// The record layer keeps running until every sender is dropped or the
+    /// connection ends.
+    pub fn sender(&self) -> RecordSender {
+        self.sender.clone()
+    }
+
+    /// Next data record; `None` once the connection is closed or the peer is dead
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        self.incoming.recv().await
+    }
+
+    /// Shared liveness and RTT metrics
+    pub fn metrics(&self) -> Arc<ConnectionMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Whether the record layer is still running and the peer alive
+    pub fn is_alive(&self) -> bool {
+        !self.metrics.is_dead() && !self.driver.is_finished()
+    }
+
+    /// Stop the record layer and return how it ended
+    ///
+    /// Dropping the connection also stops the record layer.
+    pub async fn close(self) -> Result<(), SentinelError> {
+        let Self { sender, incoming, driver, .. } = self;
+        drop(sender);
+        drop(incoming);
+        match driver.await {
+            Ok(result) => result,
+            Err(e) => Err(SentinelError::ProtocolError(format!("Keep-alive task failed: {}", e))),
+        }
+    }
+}
+
+/// Record layer loop; returns when either side closes or the peer is dead
+async fn drive(
+    mut reader: impl RecordSource,
+    mut writer: impl RecordSink,
+    config: KeepAliveConfig,
+    codec: Option<Arc<RecordCodec>>,
+    mut outgoing: mpsc::Receiver<(RecordType, Vec<u8>)>,
+    incoming: mpsc::Sender<Vec<u8>>,
+    metrics: Arc<ConnectionMetrics>,
+) -> Result<(), SentinelError> {
+    // Reading a record is not cancel-safe, so it runs on its own task
+    let (records_tx, mut records) = mpsc::channel(RECORD_QUEUE);
+    let reader_task = tokio::spawn(async move {
+        loop {
+            let record = reader.read().await;
+            let failed = record.is_err();
+            if records_tx.send(record).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let mut tracker = KeepAliveTracker::new(config);
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + config.interval, config.interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    let result = loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let tick = tracker.tick(Instant::now());
+                metrics.consecutive_misses.store(tracker.misses(), Ordering::Relaxed);
+                match tick {
+                    Tick::Ping(nonce) => {
+                        metrics.pings_sent.fetch_add(1, Ordering::Relaxed);
+                        if let Err(e) = writer.write(RecordType::Ping, &nonce.to_be_bytes()).await {
+                            break Err(e);
+                        }
+                    }
+                    Tick::Dead => {
+                        warn!("Peer missed {} keep-alive intervals; declaring it dead", tracker.misses());
+                        metrics.dead.store(true, Ordering::Relaxed);
+                        break Err(SentinelError::PeerUnresponsive(
+                            format!("no response for {} intervals of {:?}", tracker.misses(), config.interval)
+                        ));
+                    }
+                }
+            }
+            record = records.recv() => {
+                let (record_type, payload) = match record {
+                    Some(Ok(record)) => record,
+                    Some(Err(SentinelError::IoError(e))) if e.kind() == std::io::ErrorKind::UnexpectedEof => break Ok(()),
+                    Some(Err(e)) => break Err(e),
+                    None => break Ok(()),
+                };
+                tracker.heard();
+                match record_type {
+                    RecordType::Data => {
+                        if incoming.send(payload).await.is_err() {
+                            break Ok(());
+                        }
+                    }
+                    RecordType::Compressed => {
+                        let decompressed = match &codec {
+                            Some(codec) => codec.decompress(&payload, metrics.compression()),
+                            None => Err(SentinelError::ProtocolError("Compressed record without negotiated compression".into())),
+                        };
+                        let payload = match decompressed {
+                            Ok(payload) => payload,
+                            Err(e) => break Err(e),
+                        };
+                        if incoming.send(payload).await.is_err() {
+                            break Ok(());
+                        }
+                    }
+                    RecordType::Ping => {
+                        if let Err(e) = writer.write(RecordType::Pong, &payload).await {
+                            break Err(e);
+                        }
+                    }
+                    RecordType::Pong => {
+                        let nonce = match nonce_of(&payload) {
+                            Ok(nonce) => nonce,
+                            Err(e) => break Err(e),
+                        };
+                        if let Some(rtt) = tracker.pong(nonce, Instant::now()) {
+                            debug!("Keep-alive RTT {:?}", rtt);
+                            metrics.record_rtt(rtt);
+                        }
+                    }
+                }
+            }
+            record = outgoing.recv() => {
+                match record {
+                    Some((record_type, payload)) => {
+                        if let Err(e) = writer.write(record_type, &payload).await {
+                            break Err(e);
+                        }
+                    }
+                    None => break Ok(()),
+                }
+            }
+        }
+    };
+
+    reader_task.abort();
+    let _ = writer.close().await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(interval_ms: u64) -> KeepAliveConfig {
+        KeepAliveConfig {
+            interval: Duration::from_millis(interval_ms),
+            miss_threshold: 3,
+        }
+    }
+
+    #[test]
+    fn test_tracker_counts_misses_and_resets_on_traffic() {
+        let start = Instant::now();
+        let mut tracker = KeepAliveTracker::new(config(100));
+
+        assert_eq!(tracker.tick(start), Tick::Ping(1));
+        assert_eq!(tracker.pong(1, start + Duration::from_millis(20)), Some(Duration::from_millis(20)));
+        assert_eq!(tracker.tick(start), Tick::Ping(2));
+
+        // Two silent intervals, then any record resets the count
+        assert_eq!(tracker.tick(start), Tick::Ping(3));
+        assert_eq!(tracker.tick(start), Tick::Ping(4));
+        assert_eq!(tracker.misses(), 2);
+        tracker.heard();
+        assert_eq!(tracker.tick(start), Tick::Ping(5));
+        assert_eq!(tracker.misses(), 0);
+
+        // A stale pong does not yield an RTT
+        assert_eq!(tracker.pong(4, start), None);
+        for _ in 0..3 {
+            tracker.tick(start);
+        }
+        assert_eq!(tracker.tick(start), Tick::Dead);
+    }
+
+    #[tokio::test]
+    async fn test_live_connections_exchange_data_and_measure_rtt() {
+        let (left, right) = tokio::io::duplex(64 * 1024);
+        let mut client = LiveConnection::start(left, config(20));
+        let mut server = LiveConnection::start(right, config(20));
+
+        client.send(b"hello".to_vec()).await.unwrap();
+        assert_eq!(server.recv().await.unwrap(), b"hello");
+        server.send(b"world".to_vec()).await.unwrap();
+        assert_eq!(client.recv().await.unwrap(), b"world");
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        let metrics = client.metrics().snapshot();
+        assert!(metrics.pings_sent >= 2);
+        assert!(metrics.pongs_received >= 1);
+        assert!(metrics.smoothed_rtt.is_some());
+        assert!(client.is_alive() && server.is_alive());
+    }
+
+    #[tokio::test]
+    async fn test_silent_peer_declared_dead() {
+        // The far end is held open but never reads or answers
+        let (left, _silent) = tokio::io::duplex(64 * 1024);
+        let mut client = LiveConnection::start(left, config(10));
+
+        assert!(client.recv().await.is_none());
+        assert!(client.metrics().is_dead());
+        assert!(!client.is_alive());
+        assert!(matches!(client.close().await, Err(SentinelError::PeerUnresponsive(_))));
+    }
+
+    #[tokio::test]
+    async fn test_compressed_records_round_trip() {
+        use crate::compression::{CompressionConfig, CompressionDictionary};
+
+        let payload = br#"{"patch_id":"p-1","component":"cold_mirror","version":"1.2.0","signatures":["dilithium3"]}"#.to_vec();
+        let dictionary = CompressionDictionary::new(1, "patch-metadata", payload.repeat(3)).unwrap();
+        let compression = CompressionConfig { dictionaries: vec![Arc::new(dictionary)], ..Default::default() };
+        let codec = || compression.codec(&compression.params()).unwrap();
+
+        let (left, right) = tokio::io::duplex(64 * 1024);
+        let client = LiveConnection::start_compressed(left, config(1000), codec());
+        let mut server = LiveConnection::start_compressed(right, config(1000), codec());
+
+        client.send_typed("patch-metadata", payload.clone()).await.unwrap();
+        assert_eq!(server.recv().await.unwrap(), payload);
+        assert!(client.metrics().snapshot().compression.ratio().unwrap() > 1.0);
+        assert_eq!(server.metrics().snapshot().compression.records_decompressed, 1);
+
+        // A peer without compression rejects compressed records
+        let (left, right) = tokio::io::duplex(64 * 1024);
+        let client = LiveConnection::start_compressed(left, config(1000), codec());
+        let mut plain = LiveConnection::start(right, config(1000));
+        client.send_typed("patch-metadata", payload).await.unwrap();
+        assert!(plain.recv().await.is_none());
+        assert!(matches!(plain.close().await, Err(SentinelError::ProtocolError(_))));
+    }
+}
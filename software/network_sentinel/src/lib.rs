@@ -1,16 +1,48 @@
 //! Network Sentinel - Post-Quantum Secure Communications Library
 //! "The Lord watches over all who love him" - Psalm 145:20
 
+pub mod acl;
+pub mod alerts;
+pub mod capture;
+pub mod channel;
+pub mod compression;
+pub mod crash;
+pub mod discovery;
+pub mod egress;
+pub mod keepalive;
+pub mod messaging;
+pub mod pool;
 pub mod pqc_tls;
+pub mod protocol;
+pub mod reload;
+pub mod shaping;
+pub mod signing;
+pub mod transport;
 
+use std::future::Future;
+use std::future::poll_fn;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
+use pq_types::pins::PinStore;
+use tokio::net::TcpStream;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tracing::{info, warn, error};
 use thiserror::Error;
 
 pub use pqc_tls::{PQTlsConfig, PQTlsAcceptor, PQTlsStream, PQAlgorithm};
+pub use protocol::{Capabilities, CapabilityOffer, CapabilitySelection, Extension, ExtensionKind, NegotiatedSession};
+pub use acl::{AclRule, Authorizer, PeerIdentity, PolicyEngine, StaticAcl};
+pub use shaping::{BandwidthShaper, ClassQuota, PeerClass, ShapingConfig, ThrottleStats};
+pub use capture::{CaptureConfig, Direction, SessionArchive, SessionCapture, SessionRecorder};
+pub use channel::{SecureChannel, Transcript};
+pub use compression::{CompressionConfig, CompressionDictionary, CompressionParams, CompressionSnapshot, RecordCodec};
+pub use discovery::{ResolverConfig, ServiceCatalog, ServiceRecord, ServiceResolver, SignedCatalog};
+pub use egress::{EgressDialer, EgressPolicy, EgressRule, EgressTarget, EgressViolations, SignedEgressPolicy};
+pub use keepalive::{ConnectionMetrics, KeepAliveConfig, LiveConnection, MetricsSnapshot, RecordSender};
+pub use messaging::{ChannelBinding, Envelope, MessageClient, MessageHandler, MessageKind};
+pub use pool::SentinelPool;
+pub use reload::{ReloadHandle, ReloadReport, ServerSettings};
+pub use transport::{BoxedStream, Endpoint, ListenerConfig, PeerAddr, TransportStream};
 
 /// Network Sentinel errors
 #[derive(Error, Debug)]
@@ -26,13 +58,45 @@ pub enum SentinelError {
     
     #[error("Protocol error: {0}")]
     ProtocolError(String),
+    
+    #[error("Negotiation failed: {0}")]
+    NegotiationError(String),
+    
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+    
+    #[error("Session capture error: {0}")]
+    CaptureError(String),
+    
+    #[error("Service discovery error: {0}")]
+    DiscoveryError(String),
+    
+    #[error("Peer unresponsive: {0}")]
+    PeerUnresponsive(String),
+    
+    #[error("Remote error: {0}")]
+    RemoteError(String),
+    
+    #[error("Request timed out: {0}")]
+    Timeout(String),
+    
+    #[error("Egress denied: {0}")]
+    EgressDenied(String),
+    
+    #[error("Crash report error: {0}")]
+    CrashReportError(String),
+    
+    #[error("Alert error: {0}")]
+    AlertError(String),
 }
 
 /// Network Sentinel configuration
 #[derive(Clone)]
 pub struct SentinelConfig {
-    /// Listen address
+    /// Listen address, used when `listeners` is empty
     pub bind_addr: SocketAddr,
+    /// TCP, UNIX socket and vsock listeners; empty listens on `bind_addr`
+    pub listeners: Vec<ListenerConfig>,
     /// PQ-TLS configuration
     pub pq_tls_config: Arc<PQTlsConfig>,
     /// Maximum concurrent connections
@@ -41,6 +105,24 @@ pub struct SentinelConfig {
     pub connection_timeout: u64,
     /// Enable quantum-resistant mode
     pub quantum_resistant: bool,
+    /// Protocol versions, algorithms and extensions offered during negotiation
+    pub capabilities: Capabilities,
+    /// Connection authorization policy
+    pub authorizer: Arc<Authorizer>,
+    /// Per-connection and per-identity bandwidth shaping
+    pub shaper: Arc<BandwidthShaper>,
+    /// Opt-in session capture for intrusion analysis
+    pub capture: Arc<SessionCapture>,
+    /// Signed service catalog served to `discovery` requests
+    pub catalog: Option<Arc<SignedCatalog>>,
+    /// Keep-alive settings for connections that negotiate `KeepAlive`
+    pub keepalive: KeepAliveConfig,
+    /// Record compression for connections that negotiate `Compression`
+    pub compression: CompressionConfig,
+    /// Allow-list checked before every outbound connection; unchecked when `None`
+    pub egress: Option<Arc<EgressDialer>>,
+    /// Identity key fingerprints expected of peers
+    pub pins: Arc<PinStore>,
 }
 
 impl Default for SentinelConfig {
@@ -50,10 +132,73 @@ impl Default for SentinelConfig {
         
         Self {
             bind_addr: "127.0.0.1:8443".parse().unwrap(),
+            listeners: Vec::new(),
             pq_tls_config: Arc::new(pq_config),
             max_connections: 1000,
             connection_timeout: 30,
             quantum_resistant: true,
+            capabilities: Capabilities::default(),
+            authorizer: Arc::new(default_authorizer()),
+            shaper: Arc::new(BandwidthShaper::default()),
+            capture: Arc::new(SessionCapture::disabled()),
+            catalog: None,
+            keepalive: KeepAliveConfig::default(),
+            compression: CompressionConfig::default(),
+            egress: None,
+            pins: Arc::new(PinStore::in_memory()),
+        }
+    }
+}
+
+impl SentinelConfig {
+    /// Listeners to bind: `listeners`, or an encrypted TCP listener on `bind_addr`
+    pub fn effective_listeners(&self) -> Vec<ListenerConfig> {
+        if self.listeners.is_empty() {
+            vec![ListenerConfig::tcp(self.bind_addr)]
+        } else {
+            self.listeners.clone()
+        }
+    }
+    
+    /// Offer record compression with these settings
+    ///
+    /// Compression applies to the keep-alive record layer, so `KeepAlive`
+    /// must be offered as well for it to take effect.
+    pub fn enable_compression(&mut self, compression: CompressionConfig) -> Result<(), SentinelError> {
+        let extension = compression.extension(false)?;
+        self.capabilities.extensions.retain(|e| e.kind() != Some(ExtensionKind::Compression));
+        self.capabilities.extensions.push(extension);
+        self.compression = compression;
+        Ok(())
+    }
+    
+    /// Offer X25519 + ML-KEM-768, ML-KEM-768 and ML-DSA-65 ahead of the round-3 algorithms
+    ///
+    /// Peers built before these variants existed cannot decode an offer that
+    /// contains them, so they are not offered by default.
+    pub fn enable_standardized_pq(&mut self) {
+        self.capabilities.algorithms.retain(|a| !a.is_standardized());
+        self.capabilities.algorithms.splice(0..0, [PQAlgorithm::HybridX25519MlKem768, PQAlgorithm::MlKem768, PQAlgorithm::MlDsa65]);
+    }
+    
+    /// Offer the key exchange and sealed records of `channel`
+    ///
+    /// With `KeepAlive` agreed as well, the keep-alive record layer runs
+    /// inside the sealed records, uncompressed.
+    pub fn enable_secure_records(&mut self) {
+        if !self.capabilities.extensions.iter().any(|e| e.kind() == Some(ExtensionKind::SecureRecords)) {
+            self.capabilities.extensions.push(Extension::new(ExtensionKind::SecureRecords, false));
+        }
+    }
+}
+
+/// Embedded ethics engine with a deny-all fallback
+fn default_authorizer() -> Authorizer {
+    match ethics_dsl::EthicsEngine::new(ethics_dsl::EthicsConfig::default()) {
+        Ok(engine) => Authorizer::new(Arc::new(engine), StaticAcl::default()),
+        Err(e) => {
+            warn!("Ethics engine unavailable, using static ACL only: {}", e);
+            Authorizer::static_only(StaticAcl::default())
         }
     }
 }
@@ -61,136 +206,498 @@ impl Default for SentinelConfig {
 /// Network Sentinel server
 pub struct NetworkSentinel {
     config: SentinelConfig,
-    listener: Option<TcpListener>,
+    listeners: Vec<(ListenerConfig, transport::BoundListener)>,
+    reload_sender: tokio::sync::mpsc::Sender<reload::ReloadRequest>,
+    reloads: tokio::sync::mpsc::Receiver<reload::ReloadRequest>,
 }
 
 impl NetworkSentinel {
     /// Create new Network Sentinel
     pub fn new(config: SentinelConfig) -> Self {
+        let (reload_sender, reloads) = tokio::sync::mpsc::channel(reload::RELOAD_QUEUE);
         Self {
             config,
-            listener: None,
+            listeners: Vec::new(),
+            reload_sender,
+            reloads,
         }
     }
     
+    /// Handle for changing settings while the server runs
+    ///
+    /// Reloads sent before `run` are applied once it starts.
+    pub fn reload_handle(&self) -> ReloadHandle {
+        ReloadHandle { requests: self.reload_sender.clone() }
+    }
+    
     /// Initialize and bind to address
     pub async fn initialize(&mut self) -> Result<(), SentinelError> {
-        info!("Initializing Network Sentinel on {}", self.config.bind_addr);
+        let listeners = self.config.effective_listeners();
+        for listener in &listeners {
+            listener.validate()?;
+        }
+        info!("Initializing Network Sentinel on {}",
+              listeners.iter().map(|l| l.endpoint.to_string()).collect::<Vec<_>>().join(", "));
         
-        // Generate PQ keypairs if needed
-        if self.config.quantum_resistant {
+        // Generate the PQ keypairs not configured; loaded identity keys are kept
+        if self.config.quantum_resistant && !self.config.pq_tls_config.has_keypairs() {
             Arc::get_mut(&mut self.config.pq_tls_config)
                 .ok_or_else(|| SentinelError::ConfigError("Cannot modify shared config".into()))?
-                .generate_keypairs()
+                .generate_missing_keypairs()
                 .map_err(|e| SentinelError::PQTlsError(e))?;
             
             info!("Post-quantum keypairs generated successfully");
         }
+        if let Some(fingerprint) = self.config.pq_tls_config.identity_fingerprint() {
+            info!("Identity key fingerprint {}", fingerprint);
+        }
         
-        // Bind to address
-        self.listener = Some(TcpListener::bind(self.config.bind_addr).await?);
+        // Bind every endpoint
+        let mut bound = Vec::with_capacity(listeners.len());
+        for listener in listeners {
+            let socket = transport::BoundListener::bind(&listener.endpoint).await?;
+            bound.push((listener, socket));
+        }
+        self.listeners = bound;
         
         info!("Network Sentinel initialized and listening");
         Ok(())
     }
     
+    /// Address of the first TCP listener, once initialized
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listeners.iter().find_map(|(_, socket)| socket.local_addr())
+    }
+    
     /// Run the server
     pub async fn run(&mut self) -> Result<(), SentinelError> {
-        let listener = self.listener.as_ref()
-            .ok_or_else(|| SentinelError::ConfigError("Server not initialized".into()))?;
+        self.run_until(std::future::pending()).await
+    }
+    
+    /// Run the server until `shutdown` completes
+    ///
+    /// Then stops accepting, releases the listening sockets and gives open
+    /// connections up to the connection timeout to finish before aborting
+    /// them.
+    pub async fn run_until(&mut self, shutdown: impl Future<Output = ()>) -> Result<(), SentinelError> {
+        if self.listeners.is_empty() {
+            return Err(SentinelError::ConfigError("Server not initialized".into()));
+        }
         
         info!("Network Sentinel running in {} mode", 
               if self.config.quantum_resistant { "quantum-resistant" } else { "classical" });
         
+        let mut connections = tokio::task::JoinSet::new();
+        tokio::pin!(shutdown);
         loop {
-            match listener.accept().await {
-                Ok((stream, addr)) => {
-                    info!("New connection from {}", addr);
-                    
-                    let config = self.config.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = handle_connection(stream, config).await {
-                            error!("Connection error: {}", e);
+            tokio::select! {
+                _ = &mut shutdown => break,
+                accepted = accept_any(&self.listeners) => match accepted {
+                    Ok((stream, addr, encrypted)) => {
+                        if connections.len() >= self.config.max_connections {
+                            warn!("Refusing connection from {}: {} connections open", addr, connections.len());
+                            continue;
                         }
-                    });
+                        info!("New connection from {}", addr);
+                        
+                        // Plaintext listeners skip the negotiation
+                        let mut config = self.config.clone();
+                        config.quantum_resistant &= encrypted;
+                        connections.spawn(async move {
+                            if let Err(e) = handle_connection(stream, addr, config).await {
+                                error!("Connection error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        error!("Accept error: {}", e);
+                    }
+                },
+                Some(request) = self.reloads.recv() => {
+                    let report = self.reload(request.settings).await;
+                    let _ = request.reply.send(report);
                 }
-                Err(e) => {
-                    error!("Accept error: {}", e);
+                // Reap finished connections so the set does not grow
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            }
+        }
+        
+        info!("Network Sentinel shutting down with {} open connections", connections.len());
+        self.listeners.clear();
+        let grace = tokio::time::Duration::from_secs(self.config.connection_timeout);
+        let drained = tokio::time::timeout(grace, async {
+            while connections.join_next().await.is_some() {}
+        }).await;
+        if drained.is_err() {
+            warn!("Aborting {} connections still open after {:?}", connections.len(), grace);
+            connections.shutdown().await;
+        }
+        Ok(())
+    }
+}
+
+impl NetworkSentinel {
+    /// Apply new settings to the connections accepted from now on
+    ///
+    /// Open connections keep the configuration they were accepted under.
+    async fn reload(&mut self, settings: ServerSettings) -> ReloadReport {
+        let mut report = ReloadReport::default();
+        settings.apply_to(&mut self.config, &mut report);
+        
+        let mut listeners = self.config.effective_listeners();
+        if !settings.listeners.is_empty() {
+            for removed in listeners.iter().filter(|l| !settings.listeners.contains(l)) {
+                report.rejected.push(format!("listener {}: removing a listener requires a restart", removed.endpoint));
+            }
+        }
+        for listener in settings.listeners.iter().filter(|l| !listeners.contains(l)).cloned().collect::<Vec<_>>() {
+            let bound = match listener.validate() {
+                Ok(()) => transport::BoundListener::bind(&listener.endpoint).await,
+                Err(e) => Err(e),
+            };
+            match bound {
+                Ok(socket) => {
+                    info!("Listening on {} after reload", listener.endpoint);
+                    report.applied.push(format!("listener {}", listener.endpoint));
+                    self.listeners.push((listener.clone(), socket));
+                    listeners.push(listener);
                 }
+                Err(e) => report.rejected.push(format!("listener {}: {}", listener.endpoint, e)),
             }
         }
+        self.config.listeners = listeners;
+        
+        if !report.is_empty() {
+            info!("Reload applied {:?}, deferred {:?}, rejected {:?}", report.applied, report.deferred, report.rejected);
+        }
+        report
     }
 }
 
+/// Next connection on any listener, with its peer and whether it is encrypted
+async fn accept_any(
+    listeners: &[(ListenerConfig, transport::BoundListener)],
+) -> std::io::Result<(BoxedStream, PeerAddr, bool)> {
+    poll_fn(|cx| {
+        for (listener, socket) in listeners {
+            if let std::task::Poll::Ready(accepted) = socket.poll_accept(cx) {
+                return std::task::Poll::Ready(accepted.map(|(stream, addr)| (stream, addr, listener.encrypted)));
+            }
+        }
+        std::task::Poll::Pending
+    }).await
+}
+
 /// Handle individual connection
 async fn handle_connection(
-    mut stream: TcpStream,
+    mut stream: BoxedStream,
+    addr: PeerAddr,
     config: SentinelConfig,
 ) -> Result<(), SentinelError> {
-    // Set connection timeout
+    // Set connection timeout; negotiation, handshake and the first service
+    // request must all finish within one timeout of the accept
     let timeout = tokio::time::Duration::from_secs(config.connection_timeout);
+    let deadline = tokio::time::Instant::now() + timeout;
     
     // In a real implementation, we would perform the PQ-TLS handshake here
     // For now, we'll demonstrate the protocol flow
     
+    let mut negotiated = None;
+    let mut compression = None;
+    let mut transcript = Transcript::default();
     if config.quantum_resistant {
         info!("Initiating post-quantum handshake");
         
-        // Send supported algorithms
-        let supported_algos = vec![
-            PQAlgorithm::HybridX25519Kyber768,
-            PQAlgorithm::HybridEd25519Dilithium3,
-        ];
-        
-        let algo_bytes = bincode::serialize(&supported_algos)
-            .map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
+        // Offer supported versions, algorithms and extensions
+        let offer = config.capabilities.offer();
+        within(deadline, "capability offer", protocol::write_message(&mut stream, &offer)).await?;
         
-        stream.write_u32(algo_bytes.len() as u32).await?;
-        stream.write_all(&algo_bytes).await?;
-        
-        // Read client's choice
-        let choice_len = stream.read_u32().await? as usize;
-        let mut choice_buf = vec![0u8; choice_len];
-        stream.read_exact(&mut choice_buf).await?;
-        
-        let chosen_algo: PQAlgorithm = bincode::deserialize(&choice_buf)
-            .map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
-        
-        info!("Client chose algorithm: {:?}", chosen_algo);
+        // Read and validate client's selection
+        let selection: CapabilitySelection =
+            within(deadline, "capability selection", protocol::read_message(&mut stream)).await?;
+        let session = config.capabilities.accept(&selection)?;
+        transcript.add(&offer)?;
+        transcript.add(&selection)?;
+        if session.extensions.contains(&ExtensionKind::Compression) {
+            let data = selection.extensions.iter()
+                .find(|e| e.kind() == Some(ExtensionKind::Compression))
+                .map_or(&[][..], |e| e.data.as_slice());
+            compression = Some(config.compression.agree(data)?);
+        }
         
-        // Continue with PQ-TLS handshake...
-        // This would integrate with the pqc_tls module
+        info!("Negotiated protocol v{} with {:?}, extensions {:?}",
+              session.version, session.algorithm, session.extensions);
+        negotiated = Some(session);
+    }
+    
+    // Agreed secure records: run the key exchange, then seal everything else
+    if let Some(session) = negotiated.as_ref().filter(|s| s.extensions.contains(&ExtensionKind::SecureRecords)) {
+        if !session.algorithm.is_key_exchange() {
+            return Err(SentinelError::NegotiationError(
+                format!("Secure records need a key exchange, negotiated {:?}", session.algorithm)
+            ));
+        }
+        let channel = within(deadline, "key exchange",
+            channel::server_handshake(stream, config.pq_tls_config.clone(), session.algorithm, transcript)).await?;
+        info!("Completed {:?} key exchange with {}", session.algorithm, addr);
+        return serve_secure(channel, addr, &config, negotiated.as_ref(), deadline).await;
+    }
+    
+    // Authorize the requested service; Deny closes the connection. Nothing
+    // backs the claimed name without a key exchange.
+    let request: protocol::ServiceRequest =
+        within(deadline, "service request", protocol::read_message(&mut stream)).await?;
+    let peer = PeerIdentity::unauthenticated(addr);
+    if request.peer_id != peer.id {
+        info!("{} claims to be {} without proving it; judged as {}", addr, request.peer_id, peer.id);
+    }
+    let decision = config.authorizer.authorize(&peer, &request.service);
+    
+    protocol::write_message(&mut stream, &protocol::AccessResponse {
+        granted: decision.granted,
+        reason: decision.reason.clone(),
+    }).await?;
+    
+    if !decision.granted {
+        warn!("Denied {} ({}) access to {}: {}", peer.id, peer.addr, request.service, decision.reason);
+        return Err(SentinelError::AccessDenied(decision.reason));
+    }
+    info!("Granted {} access to {}", peer.id, request.service);
+    
+    // Discovery requests get the signed catalog and nothing else
+    if request.service == discovery::DISCOVERY_SERVICE {
+        let catalog = config.catalog.as_ref()
+            .ok_or_else(|| SentinelError::DiscoveryError("No service catalog configured".into()))?;
+        protocol::write_message(&mut stream, catalog.as_ref()).await?;
+        info!("Served service catalog {} to {}", catalog.catalog.serial, peer.id);
+        return Ok(());
+    }
+    
+    // Over-quota peers are slowed down, not disconnected
+    let limiter = config.shaper.connection(&peer.id);
+    let mut recorder = config.capture.session(&peer, &request.service, negotiated.as_ref());
+    
+    let keepalive = negotiated.as_ref()
+        .map_or(false, |session| session.extensions.contains(&ExtensionKind::KeepAlive));
+    if keepalive {
+        let connection = match compression {
+            Some(agreed) => LiveConnection::start_compressed(stream, config.keepalive, config.compression.codec(&agreed)?),
+            None => LiveConnection::start(stream, config.keepalive),
+        };
+        return echo_records(connection, &peer, limiter, recorder).await;
     }
     
     // Echo server for demonstration
     let mut buf = [0; 1024];
-    loop {
+    let mut outcome = Ok(());
+    let close_reason = loop {
         match tokio::time::timeout(timeout, stream.read(&mut buf)).await {
             Ok(Ok(0)) => {
                 info!("Connection closed");
-                break;
+                break "peer closed".to_string();
             }
             Ok(Ok(n)) => {
+                if let Some(limiter) = &limiter {
+                    limiter.acquire(n).await;
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record(Direction::Inbound, &buf[..n]);
+                }
                 // Echo back
-                stream.write_all(&buf[..n]).await?;
+                if let Err(e) = stream.write_all(&buf[..n]).await {
+                    let reason = format!("write error: {}", e);
+                    outcome = Err(SentinelError::IoError(e));
+                    break reason;
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record(Direction::Outbound, &buf[..n]);
+                }
             }
             Ok(Err(e)) => {
                 error!("Read error: {}", e);
-                break;
+                break format!("read error: {}", e);
             }
             Err(_) => {
                 warn!("Connection timeout");
-                break;
+                break "timeout".to_string();
             }
         }
+    };
+    
+    // A failed archive must not hide the connection outcome
+    if let Some(recorder) = recorder {
+        match recorder.finish(&close_reason) {
+            Ok(path) => info!("Archived session of {} to {:?}", peer.id, path),
+            Err(e) => error!("Failed to archive session of {}: {}", peer.id, e),
+        }
     }
     
-    Ok(())
+    outcome
+}
+
+/// Run one connection setup step, failing it once `deadline` has passed
+///
+/// Sockets still negotiating count against `max_connections`; a peer that
+/// stalls mid-setup is dropped rather than holding a slot forever.
+async fn within<T>(
+    deadline: tokio::time::Instant,
+    step: &str,
+    future: impl Future<Output = Result<T, SentinelError>>,
+) -> Result<T, SentinelError> {
+    tokio::time::timeout_at(deadline, future).await
+        .unwrap_or_else(|_| Err(SentinelError::Timeout(format!("{} not completed before the connection timeout", step))))
+}
+
+/// Authorization and echo over sealed records
+async fn serve_secure(
+    mut channel: SecureChannel<BoxedStream>,
+    addr: PeerAddr,
+    config: &SentinelConfig,
+    negotiated: Option<&NegotiatedSession>,
+    deadline: tokio::time::Instant,
+) -> Result<(), SentinelError> {
+    // Authorize the proven identity for the requested service; Deny closes the connection
+    let request: protocol::ServiceRequest = within(deadline, "service request", channel.recv_message()).await?;
+    let fingerprint = channel.peer_fingerprint()
+        .ok_or_else(|| SentinelError::NegotiationError("Peer proved no identity keys".into()))?;
+    let peer = match PeerIdentity::authenticated(&request.peer_id, fingerprint, &config.pins, addr) {
+        Ok(peer) => peer,
+        Err(e) => {
+            channel.send_message(&protocol::AccessResponse { granted: false, reason: e.to_string() }).await?;
+            return Err(e);
+        }
+    };
+    let decision = config.authorizer.authorize(&peer, &request.service);
+    
+    channel.send_message(&protocol::AccessResponse {
+        granted: decision.granted,
+        reason: decision.reason.clone(),
+    }).await?;
+    
+    if !decision.granted {
+        warn!("Denied {} ({}) access to {}: {}", peer.id, peer.addr, request.service, decision.reason);
+        return Err(SentinelError::AccessDenied(decision.reason));
+    }
+    info!("Granted {} access to {} over sealed records", peer.id, request.service);
+    
+    if request.service == discovery::DISCOVERY_SERVICE {
+        let catalog = config.catalog.as_ref()
+            .ok_or_else(|| SentinelError::DiscoveryError("No service catalog configured".into()))?;
+        channel.send_message(catalog.as_ref()).await?;
+        info!("Served service catalog {} to {}", catalog.catalog.serial, peer.id);
+        return Ok(());
+    }
+    
+    let limiter = config.shaper.connection(&peer.id);
+    let mut recorder = config.capture.session(&peer, &request.service, negotiated);
+    
+    // Keep-alive runs inside the sealed records, uncompressed
+    if negotiated.map_or(false, |session| session.extensions.contains(&ExtensionKind::KeepAlive)) {
+        return echo_records(LiveConnection::start_sealed(channel, config.keepalive), &peer, limiter, recorder).await;
+    }
+    
+    // Echo server for demonstration; records that fail to open end the connection
+    let timeout = tokio::time::Duration::from_secs(config.connection_timeout);
+    let mut outcome = Ok(());
+    let close_reason = loop {
+        match tokio::time::timeout(timeout, channel.recv()).await {
+            Ok(Ok(None)) => {
+                info!("Connection closed");
+                break "peer closed".to_string();
+            }
+            Ok(Ok(Some(record))) => {
+                if let Some(limiter) = &limiter {
+                    limiter.acquire(record.len()).await;
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record(Direction::Inbound, &record);
+                }
+                if let Err(e) = channel.send(&record).await {
+                    let reason = format!("write error: {}", e);
+                    outcome = Err(e);
+                    break reason;
+                }
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.record(Direction::Outbound, &record);
+                }
+            }
+            Ok(Err(e)) => {
+                error!("Read error: {}", e);
+                let reason = format!("read error: {}", e);
+                outcome = Err(e);
+                break reason;
+            }
+            Err(_) => {
+                warn!("Connection timeout");
+                break "timeout".to_string();
+            }
+        }
+    };
+    if let Err(e) = channel.shutdown().await {
+        warn!("Failed to close connection of {}: {}", peer.id, e);
+    }
+    
+    if let Some(recorder) = recorder {
+        match recorder.finish(&close_reason) {
+            Ok(path) => info!("Archived session of {} to {:?}", peer.id, path),
+            Err(e) => error!("Failed to archive session of {}: {}", peer.id, e),
+        }
+    }
+    
+    outcome
+}
+
+/// Echo loop over the keep-alive record layer
+///
+/// Replaces the idle timeout: the connection stays open while the peer
+/// answers keep-alives and closes once it is declared dead.
+async fn echo_records(
+    mut connection: LiveConnection,
+    peer: &PeerIdentity,
+    limiter: Option<shaping::ConnectionLimiter>,
+    mut recorder: Option<SessionRecorder>,
+) -> Result<(), SentinelError> {
+    while let Some(record) = connection.recv().await {
+        if let Some(limiter) = &limiter {
+            limiter.acquire(record.len()).await;
+        }
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(Direction::Inbound, &record);
+        }
+        connection.send(record.clone()).await?;
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record(Direction::Outbound, &record);
+        }
+    }
+    
+    let metrics = connection.metrics().snapshot();
+    let outcome = connection.close().await;
+    let close_reason = match &outcome {
+        Ok(()) => "peer closed".to_string(),
+        Err(e) => e.to_string(),
+    };
+    info!("Keep-alive connection of {} closed ({}), smoothed RTT {:?}, compression ratio {:?}",
+          peer.id, close_reason, metrics.smoothed_rtt, metrics.compression.ratio());
+    
+    if let Some(recorder) = recorder {
+        match recorder.finish(&close_reason) {
+            Ok(path) => info!("Archived session of {} to {:?}", peer.id, path),
+            Err(e) => error!("Failed to archive session of {}: {}", peer.id, e),
+        }
+    }
+    
+    outcome
 }
 
 /// Client connection with PQ-TLS
 pub struct SentinelClient {
     config: SentinelConfig,
+    /// Identifier presented to the server
+    peer_id: String,
+    /// Service requested after negotiation
+    service: String,
+    /// Name the server's identity keys are pinned under in `config.pins`
+    server: Option<String>,
 }
 
 impl SentinelClient {
@@ -205,54 +712,275 @@ impl SentinelClient {
             ..Default::default()
         };
         
-        Self { config }
+        Self {
+            config,
+            peer_id: "anonymous".to_string(),
+            service: "echo".to_string(),
+            server: None,
+        }
+    }
+    
+    /// Set the identity and service presented to the server
+    ///
+    /// Over sealed records the server honors `peer_id` only if it is pinned
+    /// to this client's identity keys, and refuses it if pinned to others;
+    /// elsewhere it is not honored at all.
+    pub fn with_service(mut self, peer_id: impl Into<String>, service: impl Into<String>) -> Self {
+        self.peer_id = peer_id.into();
+        self.service = service.into();
+        self
+    }
+    
+    /// Offer the keep-alive record layer with these settings
+    pub fn with_keepalive(mut self, keepalive: KeepAliveConfig) -> Self {
+        if !self.config.capabilities.extensions.iter().any(|e| e.kind() == Some(ExtensionKind::KeepAlive)) {
+            self.config.capabilities.extensions.push(Extension::new(ExtensionKind::KeepAlive, false));
+        }
+        self.config.keepalive = keepalive;
+        self
+    }
+    
+    /// Offer record compression with these settings
+    ///
+    /// Takes effect on `connect_live` connections; see `SentinelConfig::enable_compression`.
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Result<Self, SentinelError> {
+        self.config.enable_compression(compression)?;
+        Ok(self)
+    }
+    
+    /// Check every connection against the egress policy, as component `peer_id`
+    pub fn with_egress(mut self, egress: Arc<EgressDialer>) -> Self {
+        self.config.egress = Some(egress);
+        self
+    }
+    
+    /// Offer the key exchange and sealed records used by `connect_secure`
+    pub fn with_secure_records(mut self) -> Self {
+        self.config.enable_secure_records();
+        self
+    }
+    
+    /// Require the server to prove the identity keys pinned for `server` in `pins`
+    ///
+    /// `connect_secure` refuses servers without a pin.
+    pub fn with_pins(mut self, server: impl Into<String>, pins: Arc<PinStore>) -> Self {
+        self.server = Some(server.into());
+        self.config.pins = pins;
+        self
+    }
+    
+    /// Use these keys, identity keys included, instead of generating fresh ones
+    pub fn with_identity(mut self, pq_tls_config: Arc<PQTlsConfig>) -> Self {
+        self.config.pq_tls_config = pq_tls_config;
+        self
     }
     
     /// Connect to server
     pub async fn connect(&mut self, addr: SocketAddr) -> Result<TcpStream, SentinelError> {
-        info!("Connecting to {} with {} security", addr,
+        let stream = self.open(&Endpoint::Tcp(addr), TcpStream::connect(addr)).await?;
+        Ok(self.connect_negotiated(stream, &addr).await?.0)
+    }
+    
+    /// Connect to a server on any transport
+    ///
+    /// Plaintext UNIX socket listeners expect a client without the
+    /// post-quantum negotiation, i.e. `SentinelClient::new(false)`.
+    pub async fn connect_endpoint(&mut self, endpoint: &Endpoint) -> Result<BoxedStream, SentinelError> {
+        let stream = self.open(endpoint, transport::connect(endpoint)).await?;
+        Ok(self.connect_negotiated(stream, endpoint).await?.0)
+    }
+    
+    /// Connect, run the key exchange and request the service over sealed records
+    ///
+    /// Requires the post-quantum negotiation, in which the server must
+    /// agree to the `SecureRecords` extension offered by `with_secure_records`
+    /// and then prove the identity keys pinned by `with_pins`. If it also
+    /// agreed to `KeepAlive`, run the record layer over the channel with
+    /// `LiveConnection::start_sealed` or `MessageClient::start_secure`.
+    pub async fn connect_secure(&mut self, addr: SocketAddr) -> Result<SecureChannel<TcpStream>, SentinelError> {
+        let stream = self.open(&Endpoint::Tcp(addr), TcpStream::connect(addr)).await?;
+        self.secure(stream, &addr).await
+    }
+    
+    /// `connect_secure` on any transport
+    pub async fn connect_secure_endpoint(
+        &mut self,
+        endpoint: &Endpoint,
+    ) -> Result<SecureChannel<BoxedStream>, SentinelError> {
+        let stream = self.open(endpoint, transport::connect(endpoint)).await?;
+        self.secure(stream, endpoint).await
+    }
+    
+    /// Run the key exchange and request the service over sealed records
+    async fn secure<S: TransportStream>(
+        &mut self,
+        stream: S,
+        target: &(dyn std::fmt::Display + Sync),
+    ) -> Result<SecureChannel<S>, SentinelError> {
+        let server = self.server.clone()
+            .ok_or_else(|| SentinelError::ConfigError(format!("No pinned server name for {}; see with_pins", target)))?;
+        let (stream, negotiation) = self.negotiate(stream).await?;
+        let negotiation = negotiation
+            .filter(|n| n.extensions.contains(&ExtensionKind::SecureRecords))
+            .ok_or_else(|| SentinelError::NegotiationError(format!("{} did not agree to secure records", target)))?;
+        
+        let mut channel = channel::client_handshake(
+            stream,
+            self.config.pq_tls_config.clone(),
+            negotiation.algorithm,
+            negotiation.transcript,
+            &server,
+            &self.config.pins,
+        ).await?;
+        
+        // Request the service and honor the server's decision
+        channel.send_message(&self.service_request()).await?;
+        let response: protocol::AccessResponse = channel.recv_message().await?;
+        if !response.granted {
+            return Err(SentinelError::AccessDenied(response.reason));
+        }
+        Ok(channel)
+    }
+    
+    /// Connect and run the keep-alive record layer
+    ///
+    /// Requires the post-quantum negotiation, in which the server must
+    /// agree to the `KeepAlive` extension offered by `with_keepalive`.
+    pub async fn connect_live(&mut self, addr: SocketAddr) -> Result<LiveConnection, SentinelError> {
+        let stream = self.open(&Endpoint::Tcp(addr), TcpStream::connect(addr)).await?;
+        self.live(stream, &addr).await
+    }
+    
+    /// `connect_live` on any transport
+    pub async fn connect_live_endpoint(&mut self, endpoint: &Endpoint) -> Result<LiveConnection, SentinelError> {
+        let stream = self.open(endpoint, transport::connect(endpoint)).await?;
+        self.live(stream, endpoint).await
+    }
+    
+    /// Start the keep-alive record layer on a connected stream
+    async fn live<S: TransportStream>(
+        &mut self,
+        stream: S,
+        target: &(dyn std::fmt::Display + Sync),
+    ) -> Result<LiveConnection, SentinelError> {
+        let (stream, extensions, compression) = self.connect_negotiated(stream, target).await?;
+        if !extensions.contains(&ExtensionKind::KeepAlive) {
+            return Err(SentinelError::NegotiationError(format!("{} did not agree to keep-alive", target)));
+        }
+        Ok(match compression {
+            Some(agreed) => {
+                let codec = self.config.compression.codec(&agreed)?;
+                LiveConnection::start_compressed(stream, self.config.keepalive, codec)
+            }
+            None => LiveConnection::start(stream, self.config.keepalive),
+        })
+    }
+    
+    /// Negotiate and request the service, returning the stream, the
+    /// extensions agreed with the server and the agreed compression
+    /// parameters, if any
+    async fn connect_negotiated<S: TransportStream>(
+        &mut self,
+        stream: S,
+        target: &(dyn std::fmt::Display + Sync),
+    ) -> Result<(S, Vec<ExtensionKind>, Option<CompressionParams>), SentinelError> {
+        let (mut stream, negotiation) = self.negotiate(stream).await?;
+        let (extensions, compression) = match negotiation {
+            Some(negotiation) if negotiation.extensions.contains(&ExtensionKind::SecureRecords) => {
+                return Err(SentinelError::NegotiationError(format!("Secure records agreed with {}; use connect_secure", target)));
+            }
+            Some(negotiation) => (negotiation.extensions, negotiation.compression),
+            None => (Vec::new(), None),
+        };
+        
+        // Request the service and honor the server's decision
+        protocol::write_message(&mut stream, &self.service_request()).await?;
+        let response: protocol::AccessResponse = protocol::read_message(&mut stream).await?;
+        if !response.granted {
+            return Err(SentinelError::AccessDenied(response.reason));
+        }
+        
+        Ok((stream, extensions, compression))
+    }
+    
+    /// Service request naming this client's identity and service
+    fn service_request(&self) -> protocol::ServiceRequest {
+        protocol::ServiceRequest {
+            peer_id: self.peer_id.clone(),
+            service: self.service.clone(),
+        }
+    }
+    
+    /// Generate client keypairs, if post-quantum, and connect
+    async fn open<S>(
+        &mut self,
+        target: &Endpoint,
+        connecting: impl Future<Output = std::io::Result<S>>,
+    ) -> Result<S, SentinelError> {
+        info!("Connecting to {} with {} security", target,
               if self.config.quantum_resistant { "post-quantum" } else { "classical" });
+        if let Some(egress) = &self.config.egress {
+            egress.check(&self.peer_id, target)?;
+        }
         
-        // Generate client keypairs
-        if self.config.quantum_resistant {
+        // Generate the client keypairs not configured
+        if self.config.quantum_resistant && !self.config.pq_tls_config.has_keypairs() {
             Arc::get_mut(&mut self.config.pq_tls_config)
                 .ok_or_else(|| SentinelError::ConfigError("Cannot modify shared config".into()))?
-                .generate_keypairs()
+                .generate_missing_keypairs()
                 .map_err(|e| SentinelError::PQTlsError(e))?;
         }
         
-        let mut stream = TcpStream::connect(addr).await?;
+        Ok(connecting.await?)
+    }
+    
+    /// Run the capability negotiation, if post-quantum
+    async fn negotiate<S: TransportStream>(&mut self, mut stream: S) -> Result<(S, Option<ClientNegotiation>), SentinelError> {
+        if !self.config.quantum_resistant {
+            return Ok((stream, None));
+        }
         
-        if self.config.quantum_resistant {
-            // Read server's supported algorithms
-            let algo_len = stream.read_u32().await? as usize;
-            let mut algo_buf = vec![0u8; algo_len];
-            stream.read_exact(&mut algo_buf).await?;
-            
-            let supported_algos: Vec<PQAlgorithm> = bincode::deserialize(&algo_buf)
-                .map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
-            
-            info!("Server supports: {:?}", supported_algos);
-            
-            // Choose first supported algorithm
-            let chosen = supported_algos.first()
-                .ok_or_else(|| SentinelError::ProtocolError("No supported algorithms".into()))?;
-            
-            let choice_bytes = bincode::serialize(chosen)
-                .map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
-            
-            stream.write_u32(choice_bytes.len() as u32).await?;
-            stream.write_all(&choice_bytes).await?;
-            
-            info!("Chose algorithm: {:?}", chosen);
-            
-            // Continue with PQ-TLS handshake...
+        let mut compression = None;
+        // Read server's offer
+        let offer: CapabilityOffer = protocol::read_message(&mut stream).await?;
+        
+        info!("Server supports versions {:?}, algorithms {:?}", offer.versions, offer.algorithms);
+        
+        // Choose highest common version and first common algorithm
+        let mut selection = self.config.capabilities.select(&offer)?;
+        
+        // Answer the server's dictionaries with the ones we share
+        for ext in &mut selection.extensions {
+            if ext.kind() == Some(ExtensionKind::Compression) {
+                let agreed = self.config.compression.agree(&ext.data)?;
+                ext.data = protocol::encode_message(&agreed)?;
+                compression = Some(agreed);
+            }
         }
+        protocol::write_message(&mut stream, &selection).await?;
         
-        Ok(stream)
+        info!("Chose protocol v{} with {:?}", selection.version, selection.algorithm);
+        let mut transcript = Transcript::default();
+        transcript.add(&offer)?;
+        transcript.add(&selection)?;
+        
+        Ok((stream, Some(ClientNegotiation {
+            algorithm: selection.algorithm,
+            extensions: selection.extensions.iter().filter_map(Extension::kind).collect(),
+            compression,
+            transcript,
+        })))
     }
 }
 
+/// Client's view of a completed capability negotiation
+struct ClientNegotiation {
+    algorithm: PQAlgorithm,
+    extensions: Vec<ExtensionKind>,
+    compression: Option<CompressionParams>,
+    transcript: Transcript,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,7 +992,7 @@ mod tests {
         
         // Should initialize successfully
         assert!(sentinel.initialize().await.is_ok());
-        assert!(sentinel.listener.is_some());
+        assert_eq!(sentinel.listeners.len(), 1);
     }
     
     #[tokio::test]
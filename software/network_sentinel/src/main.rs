@@ -1,12 +1,29 @@
 //! Network Sentinel - Main Entry Point
 //! "He will command his angels concerning you to guard you in all your ways" - Psalm 91:11
 
-use network_sentinel::{NetworkSentinel, SentinelConfig, SentinelClient};
+use network_sentinel::{Authorizer, BandwidthShaper, CaptureConfig, NetworkSentinel, PQTlsConfig, ReloadReport, SentinelConfig, SentinelClient, ServerSettings, SessionCapture, ShapingConfig, StaticAcl};
+use network_sentinel::alerts::{self, RELAY_SERVICE};
+use network_sentinel::channel;
+use network_sentinel::crash::{self, COLLECTOR_SERVICE};
+use network_sentinel::discovery::{self, ResolverConfig, ServiceCatalog, ServiceResolver, SignedCatalog};
+use network_sentinel::pqc_tls::offload::{self, OffloadMode};
+use network_sentinel::reload::CONFIG_COMPONENT;
+use ark_alert::{AlertConfig, AlertSpool, Alerter};
+use ark_crash::{CrashConfig, CrashStore};
+use pq_types::decode::{self, DecodeLimits};
+use pq_types::pins::{KeyFingerprint, PinStore};
 use std::net::SocketAddr;
-use clap::{Parser, Subcommand};
-use tracing::{info, error};
+use std::time::Duration;
+use clap::{Args, Parser, Subcommand};
+use tracing::{info, error, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+/// How often pending crash reports are forwarded to the collector
+const CRASH_FORWARD_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// How often spooled alerts are relayed to the central alerter
+const ALERT_RELAY_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Parser)]
 #[command(name = "network-sentinel")]
 #[command(about = "ARK Network Sentinel - Post-Quantum Secure Communications")]
@@ -30,6 +47,48 @@ enum Commands {
         /// Maximum concurrent connections
         #[arg(long, default_value = "1000")]
         max_connections: usize,
+        
+        /// Static ACL (JSON) used when the ethics engine is unavailable
+        #[arg(long)]
+        acl: Option<String>,
+        
+        /// Bandwidth shaping configuration (JSON)
+        #[arg(long)]
+        shaping: Option<String>,
+        
+        /// Session capture configuration (JSON); capture stays off without it
+        #[arg(long)]
+        capture: Option<String>,
+        
+        /// Signed service catalog (JSON) served to discovery requests
+        #[arg(long)]
+        catalog: Option<String>,
+        
+        /// Server settings (JSON) overriding the flags above; reloaded when
+        /// the file changes or on SIGHUP
+        #[arg(long)]
+        config: Option<String>,
+        
+        /// Where post-quantum operations run: software, tri-compute, or auto
+        /// (the Tri-Compute Core if present and consistent with software)
+        #[arg(long, default_value = "auto")]
+        pq_offload: OffloadMode,
+        
+        /// Directory of this sentinel's identity keys, generated on first
+        /// use; without it the keys change on every start and cannot be pinned
+        #[arg(long)]
+        identity: Option<String>,
+        
+        /// Pinned-peer file; the crash collector and alert relay must be
+        /// pinned under their address
+        #[arg(long)]
+        pins: Option<String>,
+        
+        #[command(flatten)]
+        crash: CrashArgs,
+        
+        #[command(flatten)]
+        alerting: AlertArgs,
     },
     
     /// Run as client
@@ -45,6 +104,62 @@ enum Commands {
         /// Message to send
         #[arg(short, long)]
         message: Option<String>,
+        
+        /// Identity presented to the server
+        #[arg(long, default_value = "anonymous")]
+        peer_id: String,
+        
+        /// Service to request
+        #[arg(long, default_value = "echo")]
+        service: String,
+    },
+    
+    /// Verify a session archive and print its contents
+    VerifyArchive {
+        /// Session archive file
+        archive: String,
+        
+        /// Archive public key (hex) written beside the capture key file
+        #[arg(long)]
+        public_key: String,
+    },
+    
+    /// Sign a service catalog (JSON) with the catalog key
+    SignCatalog {
+        /// Unsigned service catalog
+        catalog: String,
+        
+        /// Catalog signing key (generated on first use; public key beside it as `.pub`)
+        #[arg(long)]
+        key: String,
+        
+        /// Signed catalog output file
+        #[arg(short, long)]
+        output: String,
+    },
+    
+    /// Resolve a named ARK service through a sentinel's signed catalog
+    Resolve {
+        /// Sentinel address
+        #[arg(short, long)]
+        connect: String,
+        
+        /// Service name (e.g. patch-server, ethics-relay)
+        name: String,
+        
+        /// Pinned catalog public key (hex)
+        #[arg(long)]
+        catalog_key: String,
+        
+        /// Identity presented to the sentinel
+        #[arg(long, default_value = "anonymous")]
+        peer_id: String,
+    },
+    
+    /// Inspect and pin peer key fingerprints
+    Keys {
+        #[command(subcommand)]
+        action: KeysCommand,
     },
     
     /// Run benchmark tests
@@ -55,6 +170,78 @@ enum Commands {
     },
 }
 
+/// Crash reporting of the server
+#[derive(Args)]
+struct CrashArgs {
+    /// Directory of this sentinel's crash reports; crash reporting stays off without it
+    #[arg(long)]
+    crash_dir: Option<String>,
+    
+    /// Crash reports kept in the crash directory
+    #[arg(long, default_value = "20")]
+    crash_retention: usize,
+    
+    /// Sentinel of the central crash collector; pending reports are
+    /// forwarded at startup and hourly
+    #[arg(long)]
+    crash_collector: Option<SocketAddr>,
+    
+    /// Crash report directory of another component to forward as well
+    #[arg(long = "forward-crashes", value_name = "DIR")]
+    forward_crashes: Vec<String>,
+}
+
+/// Alerting of the server
+#[derive(Args)]
+struct AlertArgs {
+    /// Spool directory of this sentinel's alerts; without it alerts are only logged
+    #[arg(long)]
+    alert_spool: Option<String>,
+    
+    /// HTTP webhook receiving this sentinel's alerts as JSON
+    #[arg(long)]
+    alert_webhook: Option<String>,
+    
+    /// Sentinel of the central alert relay; spooled alerts are relayed every 30 seconds
+    #[arg(long)]
+    alert_relay: Option<SocketAddr>,
+    
+    /// Alert spool of another component to relay as well
+    #[arg(long = "forward-alerts", value_name = "DIR")]
+    forward_alerts: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// Print the hybrid fingerprint of a key pair and the pinned peers
+    Show {
+        /// Ed25519 public key (hex), e.g. `ed25519.pub` of an identity directory
+        #[arg(long)]
+        ed25519: Option<String>,
+        
+        /// Dilithium3 public key (hex), e.g. `dilithium.pub` of an identity directory
+        #[arg(long)]
+        dilithium: Option<String>,
+        
+        /// Pinned-peer file
+        #[arg(long)]
+        pins: Option<String>,
+    },
+    
+    /// Pin a peer to a key fingerprint
+    Pin {
+        /// Peer identity
+        peer: String,
+        
+        /// Hybrid key fingerprint, as printed by `keys show`
+        fingerprint: String,
+        
+        /// Pinned-peer file
+        #[arg(long)]
+        pins: String,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing
@@ -68,11 +255,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
     
     match cli.command {
-        Commands::Server { bind, no_pq, max_connections } => {
-            run_server(bind, !no_pq, max_connections).await?;
+        Commands::Server { bind, no_pq, max_connections, acl, shaping, capture, catalog, config, pq_offload, identity, pins, crash, alerting } => {
+            run_server(bind, !no_pq, max_connections, acl, shaping, capture, catalog, config, pq_offload, identity, pins, crash, alerting).await?;
+        }
+        Commands::Client { connect, no_pq, message, peer_id, service } => {
+            run_client(connect, !no_pq, message, peer_id, service).await?;
+        }
+        Commands::VerifyArchive { archive, public_key } => {
+            verify_archive(archive, public_key)?;
+        }
+        Commands::SignCatalog { catalog, key, output } => {
+            sign_catalog(catalog, key, output)?;
+        }
+        Commands::Resolve { connect, name, catalog_key, peer_id } => {
+            resolve_service(connect, name, catalog_key, peer_id).await?;
         }
-        Commands::Client { connect, no_pq, message } => {
-            run_client(connect, !no_pq, message).await?;
+        Commands::Keys { action: KeysCommand::Show { ed25519, dilithium, pins } } => {
+            show_keys(ed25519, dilithium, pins)?;
+        }
+        Commands::Keys { action: KeysCommand::Pin { peer, fingerprint, pins } } => {
+            pin_key(peer, fingerprint, pins)?;
         }
         Commands::Benchmark { iterations } => {
             run_benchmark(iterations).await?;
@@ -82,37 +284,293 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn run_server(bind_addr: String, quantum_resistant: bool, max_connections: usize) -> Result<(), Box<dyn std::error::Error>> {
+#[allow(clippy::too_many_arguments)]
+async fn run_server(bind_addr: String, quantum_resistant: bool, max_connections: usize, acl: Option<String>, shaping: Option<String>, capture: Option<String>, catalog: Option<String>, settings: Option<String>, pq_offload: OffloadMode, identity: Option<String>, pins: Option<String>, crash: CrashArgs, alerting: AlertArgs) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Network Sentinel server");
     info!("Post-quantum security: {}", if quantum_resistant { "ENABLED" } else { "DISABLED" });
     
     let addr: SocketAddr = bind_addr.parse()?;
     
+    // Identity keys are loaded before anything connects out with them
+    let mut pq_config = network_sentinel::PQTlsConfig::default();
+    if quantum_resistant {
+        pq_config.offload = offload::select(pq_offload)?;
+        info!("Post-quantum operations run on: {}", pq_config.offload.name());
+        match &identity {
+            Some(directory) => {
+                let fingerprint = channel::load_or_generate_identity(std::path::Path::new(directory), &mut pq_config)?;
+                info!("Identity key fingerprint {} ({})", fingerprint, directory);
+            }
+            None => warn!("No --identity: this sentinel's keys change on every start and peers cannot pin them"),
+        }
+        pq_config.generate_missing_keypairs()?;
+    }
+    let pq_config = std::sync::Arc::new(pq_config);
+    let pins = std::sync::Arc::new(match pins {
+        Some(path) => PinStore::load(std::path::Path::new(&path))?,
+        None => PinStore::in_memory(),
+    });
+    
+    start_crash_reporting(&crash, quantum_resistant, &pq_config, &pins)?;
+    start_alerting(&alerting, quantum_resistant, &pq_config, &pins)?;
+    ark_crash::record_state("bind_addr", addr);
+    ark_crash::record_state("max_connections", max_connections);
+    if quantum_resistant {
+        ark_crash::record_state("pq_offload", pq_config.offload.name());
+    }
+    
     let mut config = SentinelConfig::default();
     config.bind_addr = addr;
     config.quantum_resistant = quantum_resistant;
     config.max_connections = max_connections;
+    config.pq_tls_config = pq_config;
+    config.pins = pins;
     
-    if quantum_resistant {
-        config.pq_tls_config = std::sync::Arc::new(network_sentinel::PQTlsConfig::default());
+    if let Some((path, contents)) = read_verified_config(acl)? {
+        let fallback: StaticAcl = decode::json_validated(&contents, &DecodeLimits::FILE)?;
+        info!("Loaded static ACL with {} rules from {}", fallback.rules.len(), path);
+        config.authorizer = std::sync::Arc::new(match ethics_dsl::EthicsEngine::new(ethics_dsl::EthicsConfig::default()) {
+            Ok(engine) => Authorizer::new(std::sync::Arc::new(engine), fallback),
+            Err(e) => {
+                error!("Ethics engine unavailable, using static ACL only: {}", e);
+                Authorizer::static_only(fallback)
+            }
+        });
+    }
+    
+    if let Some((path, contents)) = read_verified_config(shaping)? {
+        let shaping: ShapingConfig = decode::json_validated(&contents, &DecodeLimits::FILE)?;
+        info!("Loaded bandwidth quotas for {} peer classes from {}", shaping.quotas.len(), path);
+        config.shaper = std::sync::Arc::new(BandwidthShaper::new(shaping));
+    }
+    
+    if let Some((path, contents)) = read_verified_config(capture)? {
+        let capture: CaptureConfig = decode::json_validated(&contents, &DecodeLimits::FILE)?;
+        info!("Session capture {} (configuration {})", if capture.enabled { "ENABLED" } else { "disabled" }, path);
+        config.capture = std::sync::Arc::new(SessionCapture::new(capture)?);
+    }
+    
+    if let Some(path) = catalog {
+        let catalog = SignedCatalog::load(std::path::Path::new(&path))?;
+        info!("Serving service catalog {} with {} services from {}",
+              catalog.catalog.serial, catalog.catalog.services.len(), path);
+        config.catalog = Some(std::sync::Arc::new(catalog));
+    }
+    
+    if let Some(path) = &settings {
+        let loaded = ServerSettings::load(std::path::Path::new(path))?;
+        let mut report = ReloadReport::default();
+        loaded.apply_to(&mut config, &mut report);
+        if !loaded.listeners.is_empty() {
+            config.listeners = loaded.listeners;
+        }
+        info!("Loaded server settings from {}: {:?}", path, report.applied);
+    }
+    
+    for attestation in ark_config::active() {
+        let hash = attestation.config_hash.as_deref().unwrap_or("built-in");
+        info!("Configuration {}: {} ({})", attestation.path.display(), hash, attestation.status.name());
+        ark_crash::record_state(format!("config.{}", attestation.path.display()), hash);
     }
     
     let mut sentinel = NetworkSentinel::new(config);
     sentinel.initialize().await?;
     
+    if let Some(path) = settings {
+        let path = std::path::PathBuf::from(path);
+        let handle = sentinel.reload_handle();
+        handle.clone().watch_file(path.clone(), network_sentinel::reload::SETTINGS_POLL_INTERVAL);
+        let mut hangups = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                if let Err(e) = handle.reload_file(&path).await {
+                    error!("Reload on SIGHUP failed: {}", e);
+                }
+            }
+        });
+    }
+    
     info!("Server listening on {}", addr);
     sentinel.run().await?;
     
     Ok(())
 }
 
-async fn run_client(server_addr: String, quantum_resistant: bool, message: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+/// Read a configuration file named on the command line, verified against
+/// the authorized signers named by `ARK_CONFIG_SIGNERS`
+///
+/// `None` when no file was named, or when it failed verification under the
+/// safe-defaults policy, leaving the built-in default in place.
+fn read_verified_config(path: Option<String>) -> Result<Option<(String, Vec<u8>)>, Box<dyn std::error::Error>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let loaded = ark_config::load(std::path::Path::new(&path), &ark_config::VerifyOptions::from_env(CONFIG_COMPONENT)?)?;
+    match (loaded.contents, loaded.attestation.status) {
+        (Some(contents), _) => Ok(Some((path, contents))),
+        (None, ark_config::ConfigStatus::SafeDefaults { reason }) => {
+            error!("Configuration {} failed verification, using built-in defaults: {}", path, reason);
+            Ok(None)
+        }
+        (None, _) => Err(format!("{} not found", path).into()),
+    }
+}
+
+/// Install the crash reporter and start forwarding reports to the collector
+fn start_crash_reporting(crash: &CrashArgs, quantum_resistant: bool, identity: &std::sync::Arc<PQTlsConfig>, pins: &std::sync::Arc<PinStore>) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(directory) = &crash.crash_dir else {
+        return Ok(());
+    };
+    let config = CrashConfig { enabled: true, directory: directory.into(), retention: crash.crash_retention };
+    let Some(own) = ark_crash::install("network_sentinel", env!("CARGO_PKG_VERSION"), &config)? else {
+        return Ok(());
+    };
+    if let Some(last) = own.last()? {
+        warn!("Last crash {} at {:?}: {} ({})",
+              last.id, last.occurred_at, last.message, last.location.as_deref().unwrap_or("unknown location"));
+    }
+    
+    let Some(collector) = crash.crash_collector else {
+        return Ok(());
+    };
+    // Other components' stores keep their own retention
+    let mut stores = vec![own];
+    stores.extend(crash.forward_crashes.iter().map(|directory| {
+        CrashStore::new(&CrashConfig { enabled: true, directory: directory.into(), retention: usize::MAX })
+    }));
+    info!("Forwarding crash reports from {} directories to {}", stores.len(), collector);
+    let (identity, pins) = (identity.clone(), pins.clone());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CRASH_FORWARD_INTERVAL);
+        loop {
+            interval.tick().await;
+            for store in &stores {
+                let mut client = SentinelClient::new(quantum_resistant)
+                    .with_service("network-sentinel", COLLECTOR_SERVICE)
+                    .with_secure_records()
+                    .with_identity(identity.clone())
+                    .with_pins(collector.to_string(), pins.clone());
+                if let Err(e) = crash::forward_pending(store, &mut client, collector).await {
+                    warn!("Forwarding crash reports from {} failed: {}", store.directory().display(), e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Install the alerter and start relaying spooled alerts to the central relay
+fn start_alerting(alerting: &AlertArgs, quantum_resistant: bool, identity: &std::sync::Arc<PQTlsConfig>, pins: &std::sync::Arc<PinStore>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = AlertConfig {
+        webhook: alerting.alert_webhook.clone(),
+        spool: alerting.alert_spool.as_ref().map(Into::into),
+        ..AlertConfig::default()
+    };
+    let alerter = ark_alert::install(Alerter::from_config(&config)?);
+    info!("Alerts delivered to {:?}", alerter);
+    
+    let Some(relay) = alerting.alert_relay else {
+        return Ok(());
+    };
+    let spools: Vec<AlertSpool> = alerting.alert_spool.iter().chain(&alerting.forward_alerts)
+        .map(|directory| AlertSpool::new(directory.into(), config.spool_retention))
+        .collect();
+    info!("Relaying alerts from {} spools to {}", spools.len(), relay);
+    let (identity, pins) = (identity.clone(), pins.clone());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ALERT_RELAY_INTERVAL);
+        loop {
+            interval.tick().await;
+            for spool in &spools {
+                let mut client = SentinelClient::new(quantum_resistant)
+                    .with_service("network-sentinel", RELAY_SERVICE)
+                    .with_secure_records()
+                    .with_identity(identity.clone())
+                    .with_pins(relay.to_string(), pins.clone());
+                if let Err(e) = alerts::forward_pending(spool, &mut client, relay).await {
+                    warn!("Relaying alerts from {} failed: {}", spool.directory().display(), e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+fn verify_archive(archive: String, public_key: String) -> Result<(), Box<dyn std::error::Error>> {
+    let public_key = network_sentinel::capture::load_public_key(std::path::Path::new(&public_key))?;
+    let archive = network_sentinel::capture::verify_archive(std::path::Path::new(&archive), &public_key)?;
+    
+    info!("Archive signature valid: session {} of {} ({}) on {}",
+          archive.session_id, archive.peer_id, archive.peer_addr, archive.service);
+    println!("{}", serde_json::to_string_pretty(&archive)?);
+    
+    Ok(())
+}
+
+fn sign_catalog(catalog: String, key: String, output: String) -> Result<(), Box<dyn std::error::Error>> {
+    let catalog: ServiceCatalog = decode::json(&std::fs::read(&catalog)?, &DecodeLimits::FILE)?;
+    let (_, secret) = discovery::load_or_generate_catalog_key(std::path::Path::new(&key))?;
+    let signed = discovery::sign_catalog(catalog, &secret)?;
+    
+    std::fs::write(&output, serde_json::to_string_pretty(&signed)?)?;
+    info!("Signed service catalog {} with {} services to {}",
+          signed.catalog.serial, signed.catalog.services.len(), output);
+    
+    Ok(())
+}
+
+async fn resolve_service(server_addr: String, name: String, catalog_key: String, peer_id: String) -> Result<(), Box<dyn std::error::Error>> {
+    let pinned = discovery::load_pinned_key(std::path::Path::new(&catalog_key))?;
+    let resolver = ServiceResolver::new(ResolverConfig::new(server_addr.parse()?, peer_id), pinned);
+    
+    for address in resolver.resolve(&name).await? {
+        println!("{}", address);
+    }
+    
+    Ok(())
+}
+
+fn show_keys(ed25519: Option<String>, dilithium: Option<String>, pins: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    match (ed25519, dilithium) {
+        (Some(ed25519), Some(dilithium)) => {
+            let ed25519 = discovery::read_hex(std::path::Path::new(&ed25519))?;
+            let dilithium = discovery::read_hex(std::path::Path::new(&dilithium))?;
+            println!("fingerprint {}", KeyFingerprint::hybrid(&ed25519, &dilithium));
+        }
+        (None, None) => {}
+        _ => return Err("Both --ed25519 and --dilithium are needed for a hybrid fingerprint".into()),
+    }
+    
+    if let Some(path) = pins {
+        for (peer, fingerprint) in PinStore::load(std::path::Path::new(&path))?.iter() {
+            println!("pinned {} {}", peer, fingerprint);
+        }
+    }
+    
+    Ok(())
+}
+
+fn pin_key(peer: String, fingerprint: String, pins: String) -> Result<(), Box<dyn std::error::Error>> {
+    let fingerprint: KeyFingerprint = fingerprint.parse()?;
+    let mut store = PinStore::load(std::path::Path::new(&pins))?;
+    match store.pin(&peer, fingerprint)? {
+        Some(old) if old != fingerprint => {
+            tracing::warn!("Re-pinned peer {} from {} to {}; signatures under the old keys are now refused", peer, old, fingerprint);
+        }
+        _ => info!("Pinned peer {} to {}", peer, fingerprint),
+    }
+    store.save()?;
+    
+    Ok(())
+}
+
+async fn run_client(server_addr: String, quantum_resistant: bool, message: Option<String>, peer_id: String, service: String) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Network Sentinel client");
     info!("Post-quantum security: {}", if quantum_resistant { "ENABLED" } else { "DISABLED" });
     
     let addr: SocketAddr = server_addr.parse()?;
     
-    let mut client = SentinelClient::new(quantum_resistant);
+    let mut client = SentinelClient::new(quantum_resistant).with_service(peer_id, service);
     
     info!("Connecting to {}", addr);
     let mut stream = client.connect(addr).await?;
@@ -160,13 +618,17 @@ async fn run_benchmark(iterations: usize) -> Result<(), Box<dyn std::error::Erro
     // Benchmark handshake simulation
     let mut config = PQTlsConfig::default();
     config.generate_keypairs()?;
+    let config = std::sync::Arc::new(config);
     
     let start = Instant::now();
     for _ in 0..iterations {
-        // Simulate key exchange
+        // Full key exchange: server share, client encapsulation, server decapsulation
         use network_sentinel::pqc_tls::{PQHandshake, PQAlgorithm};
-        let handshake = PQHandshake::new(std::sync::Arc::new(config.clone()), true);
-        let _key_share = handshake.generate_key_share(PQAlgorithm::HybridX25519Kyber768)?;
+        let mut server = PQHandshake::new(config.clone(), false);
+        let mut client = PQHandshake::new(config.clone(), true);
+        let share = server.offer_key_share(PQAlgorithm::HybridX25519Kyber768)?;
+        let exchange = client.accept_key_share(&share)?;
+        server.complete_key_exchange(&exchange)?;
     }
     let handshake_time = start.elapsed();
     
@@ -0,0 +1,978 @@
+//! Sentinel Messaging - Typed Requests and Responses over Records
+//! "Let your speech be alway with grace, seasoned with salt, that ye may know how ye ought to answer every man" - Colossians 4:6
+//!
+//! The record layer carries opaque byte records. This module carries typed
+//! messages on top of it: each message is an `Envelope` with a format
+//! version, a correlation ID, a kind and a method name around a serde
+//! payload, written as a big-endian `u32` length and the bincode envelope,
+//! and split across as many data records as it needs. Payloads are JSON,
+//! so events with free-form metadata travel as well as plain structs.
+//!
+//! `MessageClient` sends requests and routes each response to the caller
+//! waiting on its correlation ID, so any number of requests can be in
+//! flight on one connection; a request that gets no answer within its
+//! timeout fails on its own. `serve` answers requests with a
+//! `MessageHandler`, each on its own task; at most `MAX_IN_FLIGHT_REQUESTS`
+//! run at once per connection, and the connection is not read further
+//! until one of them finishes. The `protocol!` macro generates
+//! a typed client and a service trait with its handler for a set of
+//! methods, as used for the patch distribution and ethics relay protocols
+//! below.
+//!
+//! A `ChannelBinding` ties messages to the connection they travel on. It
+//! is keyed from the connection's exporter, and every envelope is tagged
+//! with it; envelopes whose tag does not match are refused. A patch
+//! approval or ethics verdict lifted from one connection and replayed on
+//! another - even between the same two peers - is therefore rejected.
+//! `MessageClient::start_secure` and `serve_secure` run messaging over the
+//! keep-alive record layer inside a `SecureChannel` and bind it from the
+//! channel's exporter; devices and relays should serve both protocols
+//! that way.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, Semaphore};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{debug, warn};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use zeroize::Zeroizing;
+
+use crate::channel::SecureChannel;
+use crate::keepalive::{KeepAliveConfig, LiveConnection, RecordSender, MAX_RECORD_PAYLOAD};
+use crate::pqc_tls::PQTlsStream;
+use crate::SentinelError;
+
+/// Envelope format version
+pub const MESSAGE_VERSION: u16 = 3;
+
+/// Exporter label of channel binding keys
+pub const BINDING_LABEL: &[u8] = b"EXPORTER-ark-sentinel-message-binding";
+
+/// Largest encoded envelope
+pub const MAX_MESSAGE: usize = 4 * 1024 * 1024;
+
+/// Longest method name
+pub const MAX_METHOD_LENGTH: usize = 64;
+
+/// Timeout of requests made without one of their own
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Most requests handled at once on one served connection
+pub const MAX_IN_FLIGHT_REQUESTS: usize = 64;
+
+const ENVELOPE_LIMITS: DecodeLimits = DecodeLimits::new(MAX_MESSAGE, 8);
+const PAYLOAD_LIMITS: DecodeLimits = DecodeLimits::new(MAX_MESSAGE, 64);
+
+/// Role of an envelope in an exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageKind {
+    /// Call expecting a response with the same correlation ID
+    Request,
+    /// Successful answer to a request
+    Response,
+    /// Failed request; the payload is the error message
+    Error,
+}
+
+/// Versioned message with its correlation ID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Envelope format version
+    pub version: u16,
+    /// ID shared by a request and its response
+    pub correlation_id: u64,
+    /// Request, response or error
+    pub kind: MessageKind,
+    /// Method called
+    pub method: String,
+    /// JSON payload
+    pub payload: Vec<u8>,
+    /// Tag binding the envelope to its connection, if the connection is bound
+    pub binding: Option<[u8; 32]>,
+}
+
+impl Validate for Envelope {
+    fn validate(&self) -> Result<(), String> {
+        if self.version != MESSAGE_VERSION {
+            return Err(format!("unsupported message version {}", self.version));
+        }
+        decode::check_identifier("method", &self.method, MAX_METHOD_LENGTH)
+    }
+}
+
+impl Envelope {
+    /// Envelope carrying `body` as its payload
+    pub fn new<T: Serialize>(correlation_id: u64, kind: MessageKind, method: &str, body: &T) -> Result<Self, SentinelError> {
+        Ok(Self {
+            version: MESSAGE_VERSION,
+            correlation_id,
+            kind,
+            method: method.to_string(),
+            payload: encode_body(body).map_err(SentinelError::ProtocolError)?,
+            binding: None,
+        })
+    }
+
+    /// Answer to this request
+    fn reply(&self, kind: MessageKind, payload: Vec<u8>) -> Self {
+        Self {
+            version: MESSAGE_VERSION,
+            correlation_id: self.correlation_id,
+            kind,
+            method: self.method.clone(),
+            payload,
+            binding: None,
+        }
+    }
+
+    /// Length-prefixed wire form
+    pub fn encode(&self) -> Result<Vec<u8>, SentinelError> {
+        let body = bincode::serialize(self).map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
+        if body.len() > MAX_MESSAGE {
+            return Err(SentinelError::ProtocolError(format!("Message too large: {} bytes", body.len())));
+        }
+        let mut frame = Vec::with_capacity(4 + body.len());
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&body);
+        Ok(frame)
+    }
+
+    /// Decode the payload of a response, or the remote error it carries
+    fn into_result<T: DeserializeOwned + Validate>(self) -> Result<T, SentinelError> {
+        match self.kind {
+            MessageKind::Response => decode_body(&self.payload).map_err(SentinelError::ProtocolError),
+            MessageKind::Error => {
+                let message: String = decode_body(&self.payload).map_err(SentinelError::ProtocolError)?;
+                Err(SentinelError::RemoteError(format!("{}: {}", self.method, message)))
+            }
+            MessageKind::Request => Err(SentinelError::ProtocolError("Request received as a response".into())),
+        }
+    }
+}
+
+/// Key tying envelopes to one connection
+///
+/// Both ends derive the same key from their connection's exporter; no other
+/// connection yields it.
+pub struct ChannelBinding {
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl ChannelBinding {
+    /// Binding from a key exported for `BINDING_LABEL`
+    pub fn from_key(key: [u8; 32]) -> Self {
+        Self { key: Zeroizing::new(key) }
+    }
+
+    /// Binding of a record-encrypted channel
+    pub fn from_channel<S: AsyncRead + AsyncWrite + Unpin>(channel: &SecureChannel<S>) -> Result<Self, SentinelError> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        channel.export_keying_material(BINDING_LABEL, &[], &mut key[..])?;
+        Ok(Self::from_key(*key))
+    }
+
+    /// Binding of a post-quantum TLS stream
+    pub fn from_tls<IO: AsyncRead + AsyncWrite + Unpin>(stream: &PQTlsStream<IO>) -> Result<Self, SentinelError> {
+        let mut key = Zeroizing::new([0u8; 32]);
+        stream.export_keying_material(BINDING_LABEL, &[], &mut key[..])?;
+        Ok(Self::from_key(*key))
+    }
+
+    /// Tag of everything in `envelope` but the tag itself
+    fn tag(&self, envelope: &Envelope) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        hasher.update(b"ark-sentinel-message-binding-v1");
+        hasher.update(&envelope.version.to_be_bytes());
+        hasher.update(&envelope.correlation_id.to_be_bytes());
+        hasher.update(&[envelope.kind as u8]);
+        hasher.update(&(envelope.method.len() as u32).to_be_bytes());
+        hasher.update(envelope.method.as_bytes());
+        hasher.update(&envelope.payload);
+        hasher.finalize().into()
+    }
+
+    /// Tag `envelope` for this connection
+    fn seal(&self, envelope: &mut Envelope) {
+        envelope.binding = Some(self.tag(envelope));
+    }
+
+    /// Check that `envelope` was tagged for this connection
+    fn verify(&self, envelope: &Envelope) -> Result<(), SentinelError> {
+        match envelope.binding {
+            Some(tag) if constant_time_eq::constant_time_eq_32(&tag, &self.tag(envelope)) => Ok(()),
+            Some(_) => Err(SentinelError::ProtocolError(format!("{} is bound to another connection", envelope.method))),
+            None => Err(SentinelError::ProtocolError(format!("{} is not bound to its connection", envelope.method))),
+        }
+    }
+}
+
+/// Payload of a message body
+pub fn encode_body<T: Serialize>(body: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(body).map_err(|e| e.to_string())
+}
+
+/// Decode and validate a message body
+pub fn decode_body<T: DeserializeOwned + Validate>(payload: &[u8]) -> Result<T, String> {
+    decode::json_validated(payload, &PAYLOAD_LIMITS).map_err(|e| e.to_string())
+}
+
+/// Collects envelopes from data records
+#[derive(Default)]
+struct Reassembly {
+    buffer: Vec<u8>,
+}
+
+impl Reassembly {
+    /// Add a record, returning the envelopes it completes
+    fn push(&mut self, record: &[u8]) -> Result<Vec<Envelope>, SentinelError> {
+        self.buffer.extend_from_slice(record);
+        let mut envelopes = Vec::new();
+        while self.buffer.len() >= 4 {
+            let len = u32::from_be_bytes([self.buffer[0], self.buffer[1], self.buffer[2], self.buffer[3]]) as usize;
+            if len > MAX_MESSAGE {
+                return Err(SentinelError::ProtocolError(format!("Message too large: {} bytes", len)));
+            }
+            if self.buffer.len() < 4 + len {
+                break;
+            }
+            let envelope = decode::bincode_validated(&self.buffer[4..4 + len], &ENVELOPE_LIMITS)
+                .map_err(|e| SentinelError::ProtocolError(e.to_string()))?;
+            self.buffer.drain(..4 + len);
+            envelopes.push(envelope);
+        }
+        Ok(envelopes)
+    }
+}
+
+/// Record sender shared by every task writing envelopes to a connection
+type EnvelopeSender = Arc<tokio::sync::Mutex<RecordSender>>;
+
+/// Send an envelope as one or more data records
+///
+/// The sender stays locked until the last record is queued, so the records
+/// of envelopes sent by different tasks do not interleave.
+async fn send_envelope(sender: &EnvelopeSender, envelope: &Envelope) -> Result<(), SentinelError> {
+    let frame = envelope.encode()?;
+    let sender = sender.lock().await;
+    for chunk in frame.chunks(MAX_RECORD_PAYLOAD) {
+        sender.send(chunk.to_vec()).await?;
+    }
+    Ok(())
+}
+
+/// Callers waiting for responses; `None` once the connection has ended
+type Pending = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<Envelope>>>>>;
+
+fn lock(pending: &Pending) -> MutexGuard<'_, Option<HashMap<u64, oneshot::Sender<Envelope>>>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn closed() -> SentinelError {
+    SentinelError::PeerUnresponsive("connection closed".into())
+}
+
+/// Requesting side of a messaging connection
+pub struct MessageClient {
+    sender: EnvelopeSender,
+    pending: Pending,
+    next_id: AtomicU64,
+    timeout: Duration,
+    binding: Option<ChannelBinding>,
+    reader: JoinHandle<()>,
+}
+
+impl MessageClient {
+    /// Send requests over `connection`, routing responses on a background task
+    pub fn start(connection: LiveConnection) -> Self {
+        let pending: Pending = Arc::new(Mutex::new(Some(HashMap::new())));
+        Self {
+            sender: Arc::new(tokio::sync::Mutex::new(connection.sender())),
+            reader: tokio::spawn(route_responses(connection, pending.clone())),
+            pending,
+            next_id: AtomicU64::new(1),
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            binding: None,
+        }
+    }
+
+    /// Send requests over the sealed records of `channel`, bound to it
+    pub fn start_secure<S>(channel: SecureChannel<S>, keepalive: KeepAliveConfig) -> Result<Self, SentinelError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let binding = ChannelBinding::from_channel(&channel)?;
+        Ok(Self::start(LiveConnection::start_sealed(channel, keepalive)).with_binding(binding))
+    }
+
+    /// Tag requests with `binding` and accept only responses tagged with it
+    pub fn with_binding(mut self, binding: ChannelBinding) -> Self {
+        self.binding = Some(binding);
+        self
+    }
+
+    /// Use `timeout` for requests made without one of their own
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Call `method` with the default timeout
+    pub async fn call<Req, Resp>(&self, method: &str, request: &Req) -> Result<Resp, SentinelError>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned + Validate,
+    {
+        self.call_within(method, request, self.timeout).await
+    }
+
+    /// Call `method`, failing with `Timeout` if no response arrives within `timeout`
+    pub async fn call_within<Req, Resp>(&self, method: &str, request: &Req, timeout: Duration) -> Result<Resp, SentinelError>
+    where
+        Req: Serialize,
+        Resp: DeserializeOwned + Validate,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut envelope = Envelope::new(id, MessageKind::Request, method, request)?;
+        if let Some(binding) = &self.binding {
+            binding.seal(&mut envelope);
+        }
+        let (waiter, response) = oneshot::channel();
+        lock(&self.pending).as_mut().ok_or_else(closed)?.insert(id, waiter);
+
+        let exchange = async {
+            send_envelope(&self.sender, &envelope).await?;
+            response.await.map_err(|_| closed())
+        };
+        let result = match tokio::time::timeout(timeout, exchange).await {
+            Ok(result) => result,
+            Err(_) => Err(SentinelError::Timeout(format!("{} got no response within {:?}", method, timeout))),
+        };
+        if result.is_err() {
+            if let Some(pending) = lock(&self.pending).as_mut() {
+                pending.remove(&id);
+            }
+        }
+        let response = result?;
+        if let Some(binding) = &self.binding {
+            binding.verify(&response)?;
+        }
+        response.into_result()
+    }
+}
+
+impl Drop for MessageClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Hand each response to the caller waiting on its correlation ID
+async fn route_responses(mut connection: LiveConnection, pending: Pending) {
+    let mut reassembly = Reassembly::default();
+    while let Some(record) = connection.recv().await {
+        let envelopes = match reassembly.push(&record) {
+            Ok(envelopes) => envelopes,
+            Err(e) => {
+                warn!("Dropping messaging connection: {}", e);
+                break;
+            }
+        };
+        for envelope in envelopes {
+            if envelope.kind == MessageKind::Request {
+                warn!("Ignoring {} request sent to a client", envelope.method);
+                continue;
+            }
+            let waiter = lock(&pending).as_mut().and_then(|pending| pending.remove(&envelope.correlation_id));
+            match waiter {
+                Some(waiter) => {
+                    let _ = waiter.send(envelope);
+                }
+                None => debug!("Response {} arrived after its request ended", envelope.correlation_id),
+            }
+        }
+    }
+    // Dropping the waiters fails every request still in flight
+    lock(&pending).take();
+}
+
+/// Answers requests of one protocol
+pub trait MessageHandler: Send + Sync + 'static {
+    /// Handle a request, returning the response payload or an error message
+    fn handle(&self, method: &str, payload: &[u8]) -> impl Future<Output = Result<Vec<u8>, String>> + Send;
+}
+
+/// Answer requests on `connection` until the peer closes it
+///
+/// Each request runs on its own task, so a slow request does not hold up
+/// the ones behind it; a peer sending faster than its requests are
+/// answered is held back once `MAX_IN_FLIGHT_REQUESTS` are running.
+pub async fn serve<H: MessageHandler>(connection: LiveConnection, handler: Arc<H>) -> Result<(), SentinelError> {
+    serve_with(connection, handler, None).await
+}
+
+/// `serve` over the sealed records of `channel`, bound to it
+///
+/// Answers only requests tagged with the channel's binding and tags every
+/// answer; requests with a missing or foreign tag get an error without
+/// reaching the handler.
+pub async fn serve_secure<S, H>(channel: SecureChannel<S>, keepalive: KeepAliveConfig, handler: Arc<H>) -> Result<(), SentinelError>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    H: MessageHandler,
+{
+    let binding = ChannelBinding::from_channel(&channel)?;
+    serve_with(LiveConnection::start_sealed(channel, keepalive), handler, Some(Arc::new(binding))).await
+}
+
+async fn serve_with<H: MessageHandler>(
+    mut connection: LiveConnection,
+    handler: Arc<H>,
+    binding: Option<Arc<ChannelBinding>>,
+) -> Result<(), SentinelError> {
+    let sender = Arc::new(tokio::sync::Mutex::new(connection.sender()));
+    let mut reassembly = Reassembly::default();
+    let mut requests = JoinSet::new();
+    let in_flight = Arc::new(Semaphore::new(MAX_IN_FLIGHT_REQUESTS));
+    let result = 'serve: loop {
+        tokio::select! {
+            record = connection.recv() => {
+                let Some(record) = record else { break Ok(()) };
+                let envelopes = match reassembly.push(&record) {
+                    Ok(envelopes) => envelopes,
+                    Err(e) => break Err(e),
+                };
+                for request in envelopes {
+                    if request.kind != MessageKind::Request {
+                        warn!("Ignoring unsolicited {:?} for {}", request.kind, request.method);
+                        continue;
+                    }
+                    // Reading stops here while every permit is taken
+                    let Ok(permit) = in_flight.clone().acquire_owned().await else { break 'serve Ok(()) };
+                    let handler = handler.clone();
+                    let sender = sender.clone();
+                    let binding = binding.clone();
+                    requests.spawn(async move {
+                        let _permit = permit;
+                        let bound = match &binding {
+                            Some(binding) => binding.verify(&request).map_err(|e| {
+                                warn!("Refusing request {}: {}", request.correlation_id, e);
+                                "request not bound to this connection".to_string()
+                            }),
+                            None => Ok(()),
+                        };
+                        let outcome = match bound {
+                            Ok(()) => handler.handle(&request.method, &request.payload).await,
+                            Err(message) => Err(message),
+                        };
+                        let mut reply = match outcome {
+                            Ok(payload) => request.reply(MessageKind::Response, payload),
+                            Err(message) => match encode_body(&message) {
+                                Ok(payload) => request.reply(MessageKind::Error, payload),
+                                Err(e) => {
+                                    warn!("Failed to encode error for {}: {}", request.method, e);
+                                    return;
+                                }
+                            },
+                        };
+                        if let Some(binding) = &binding {
+                            binding.seal(&mut reply);
+                        }
+                        if let Err(e) = send_envelope(&sender, &reply).await {
+                            debug!("Failed to answer {} {}: {}", request.method, request.correlation_id, e);
+                        }
+                    });
+                }
+            }
+            // Reap answered requests so the set does not grow
+            Some(_) = requests.join_next(), if !requests.is_empty() => {}
+        }
+    };
+
+    // Senders held by request tasks keep the record layer running
+    requests.shutdown().await;
+    drop(sender);
+    let closed = connection.close().await;
+    result.and(closed)
+}
+
+/// Typed client, service trait and handler for a set of methods
+///
+/// Service methods fail with a message that reaches the caller as a
+/// `SentinelError::RemoteError`.
+macro_rules! protocol {
+    (
+        client $client:ident;
+        service $service:ident, server $server:ident;
+        $( $(#[$doc:meta])* fn $method:ident($request:ty) -> $response:ty = $name:literal; )*
+    ) => {
+        /// Typed client of the protocol
+        pub struct $client {
+            client: $crate::messaging::MessageClient,
+        }
+
+        impl $client {
+            /// Client sending over `client`
+            pub fn new(client: $crate::messaging::MessageClient) -> Self {
+                Self { client }
+            }
+
+            /// Client sending over the sealed records of `channel`, bound to it
+            pub fn secure<S>(
+                channel: $crate::channel::SecureChannel<S>,
+                keepalive: $crate::keepalive::KeepAliveConfig,
+            ) -> Result<Self, $crate::SentinelError>
+            where
+                S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+            {
+                Ok(Self::new($crate::messaging::MessageClient::start_secure(channel, keepalive)?))
+            }
+
+            $(
+                $(#[$doc])*
+                pub async fn $method(&self, request: &$request) -> Result<$response, $crate::SentinelError> {
+                    self.client.call($name, request).await
+                }
+            )*
+        }
+
+        /// Server side of the protocol
+        pub trait $service: Send + Sync + 'static {
+            $(
+                $(#[$doc])*
+                fn $method(&self, request: $request) -> impl std::future::Future<Output = Result<$response, String>> + Send;
+            )*
+        }
+
+        /// Handler dispatching requests to a service
+        pub struct $server<S>(pub S);
+
+        impl<S: $service> $crate::messaging::MessageHandler for $server<S> {
+            fn handle(&self, method: &str, payload: &[u8]) -> impl std::future::Future<Output = Result<Vec<u8>, String>> + Send {
+                let method = method.to_string();
+                let payload = payload.to_vec();
+                async move {
+                    match method.as_str() {
+                        $(
+                            $name => {
+                                let request: $request = $crate::messaging::decode_body(&payload)?;
+                                $crate::messaging::encode_body(&self.0.$method(request).await?)
+                            }
+                        )*
+                        other => Err(format!("unknown method {}", other)),
+                    }
+                }
+            }
+        }
+    };
+}
+
+/// Patch distribution between the orchestrator and field devices
+pub mod patch_distribution {
+    use pq_types::decode::{self, Validate};
+    use serde::{Deserialize, Serialize};
+
+    /// Largest patch chunk
+    ///
+    /// Chunk bytes travel hex-encoded, so a full chunk takes twice its size
+    /// in the payload and must still fit in one message.
+    pub const MAX_CHUNK: usize = 1024 * 1024;
+
+    const _: () = assert!(2 * MAX_CHUNK + 64 * 1024 <= super::MAX_MESSAGE);
+
+    const MAX_ID_LENGTH: usize = 128;
+
+    /// Patch offered to a device
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PatchOffer {
+        /// Patch identifier
+        pub patch_id: String,
+        /// Component patched
+        pub component: String,
+        /// Version installed by the patch
+        pub version: String,
+        /// Patch size in bytes
+        pub size: u64,
+        /// BLAKE3 hash of the patch, hex-encoded
+        pub blake3: String,
+    }
+
+    /// Device's answer to an offer
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct OfferReply {
+        /// Whether the device will fetch the patch
+        pub accepted: bool,
+        /// Reason for declining
+        pub reason: String,
+    }
+
+    /// Request for part of a patch
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ChunkRequest {
+        /// Patch identifier
+        pub patch_id: String,
+        /// Offset of the chunk
+        pub offset: u64,
+        /// Requested length, at most `MAX_CHUNK`
+        pub length: u32,
+    }
+
+    /// Part of a patch
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct PatchChunk {
+        /// Patch identifier
+        pub patch_id: String,
+        /// Offset of the chunk
+        pub offset: u64,
+        /// Chunk bytes, hex-encoded on the wire rather than as a JSON
+        /// array of numbers
+        #[serde(with = "hex_data")]
+        pub data: Vec<u8>,
+        /// Whether the chunk ends the patch
+        pub last: bool,
+    }
+
+    /// Outcome of installing a patch on a device
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct InstallReport {
+        /// Patch identifier
+        pub patch_id: String,
+        /// Whether the patch was installed
+        pub installed: bool,
+        /// Installer output or failure reason
+        pub detail: String,
+    }
+
+    /// Acknowledgement of a report
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct ReportAck {
+        /// Whether the report was recorded
+        pub recorded: bool,
+    }
+
+    mod hex_data {
+        use serde::de::Error as _;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&hex::encode(data))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+            hex::decode(String::deserialize(deserializer)?).map_err(D::Error::custom)
+        }
+    }
+
+    impl Validate for PatchOffer {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("patch_id", &self.patch_id, MAX_ID_LENGTH)?;
+            decode::check_identifier("component", &self.component, MAX_ID_LENGTH)?;
+            decode::check_len("version", &self.version, MAX_ID_LENGTH)?;
+            decode::check_len("blake3", &self.blake3, 64)
+        }
+    }
+
+    impl Validate for OfferReply {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_len("reason", &self.reason, 1024)
+        }
+    }
+
+    impl Validate for ChunkRequest {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("patch_id", &self.patch_id, MAX_ID_LENGTH)?;
+            decode::check_count("length", self.length as usize, MAX_CHUNK)
+        }
+    }
+
+    impl Validate for PatchChunk {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("patch_id", &self.patch_id, MAX_ID_LENGTH)?;
+            decode::check_count("data", self.data.len(), MAX_CHUNK)
+        }
+    }
+
+    impl Validate for InstallReport {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("patch_id", &self.patch_id, MAX_ID_LENGTH)?;
+            decode::check_len("detail", &self.detail, 16 * 1024)
+        }
+    }
+
+    impl Validate for ReportAck {
+        fn validate(&self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    protocol! {
+        client PatchDistributionClient;
+        service PatchDistribution, server PatchDistributionServer;
+        /// Offer a patch to the device
+        fn offer(PatchOffer) -> OfferReply = "patch.offer";
+        /// Fetch part of an offered patch
+        fn fetch_chunk(ChunkRequest) -> PatchChunk = "patch.fetch_chunk";
+        /// Report the outcome of an installation
+        fn report(InstallReport) -> ReportAck = "patch.report";
+    }
+}
+
+/// Relay of events to a remote ethics engine
+pub mod ethics_relay {
+    use ethics_dsl::{EthicsDecision, EthicsEvent};
+    use pq_types::decode::{self, Validate};
+    use serde::{Deserialize, Serialize};
+
+    /// Event to evaluate
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct RelayRequest {
+        /// Event as seen by the relaying node
+        pub event: EthicsEvent,
+    }
+
+    /// Decision of the remote engine
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct RelayVerdict {
+        /// Evaluated event
+        pub event_id: String,
+        /// Decision taken
+        pub decision: EthicsDecision,
+    }
+
+    impl Validate for RelayRequest {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("event_id", &self.event.event_id, 256)
+        }
+    }
+
+    impl Validate for RelayVerdict {
+        fn validate(&self) -> Result<(), String> {
+            decode::check_identifier("event_id", &self.event_id, 256)
+        }
+    }
+
+    protocol! {
+        client EthicsRelayClient;
+        service EthicsRelay, server EthicsRelayServer;
+        /// Evaluate an event with the remote engine
+        fn evaluate(RelayRequest) -> RelayVerdict = "ethics.evaluate";
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::patch_distribution::*;
+    use super::*;
+    use crate::channel::{self, Transcript};
+    use crate::pqc_tls::{PQAlgorithm, PQTlsConfig};
+    use pq_types::pins::PinStore;
+    use tokio::io::DuplexStream;
+
+    struct Device;
+
+    impl PatchDistribution for Device {
+        async fn offer(&self, request: PatchOffer) -> Result<OfferReply, String> {
+            Ok(OfferReply { accepted: request.component == "cold_mirror", reason: String::new() })
+        }
+
+        async fn fetch_chunk(&self, request: ChunkRequest) -> Result<PatchChunk, String> {
+            if request.offset > 0 {
+                // Held back so a later request overtakes it
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+            Ok(PatchChunk {
+                patch_id: request.patch_id,
+                offset: request.offset,
+                data: vec![request.offset as u8; request.length as usize],
+                last: request.offset > 0,
+            })
+        }
+
+        async fn report(&self, request: InstallReport) -> Result<ReportAck, String> {
+            if request.installed {
+                Ok(ReportAck { recorded: true })
+            } else {
+                Err(format!("{} failed: {}", request.patch_id, request.detail))
+            }
+        }
+    }
+
+    fn pair() -> (LiveConnection, LiveConnection) {
+        let config = KeepAliveConfig { interval: Duration::from_secs(5), miss_threshold: 3 };
+        let (left, right) = tokio::io::duplex(64 * 1024);
+        (LiveConnection::start(left, config), LiveConnection::start(right, config))
+    }
+
+    /// Client and server ends of a key exchange, the server's keys pinned by the client
+    async fn secure_pair() -> (SecureChannel<DuplexStream>, SecureChannel<DuplexStream>) {
+        let mut device = PQTlsConfig::default();
+        device.generate_keypairs().unwrap();
+        let mut pins = PinStore::in_memory();
+        pins.pin("device", device.identity_fingerprint().unwrap()).unwrap();
+        let mut orchestrator = PQTlsConfig::default();
+        orchestrator.generate_keypairs().unwrap();
+
+        let algorithm = PQAlgorithm::HybridX25519Kyber768;
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let (client, server) = tokio::join!(
+            channel::client_handshake(client, Arc::new(orchestrator), algorithm, Transcript::default(), "device", &pins),
+            channel::server_handshake(server, Arc::new(device), algorithm, Transcript::default()),
+        );
+        (client.unwrap(), server.unwrap())
+    }
+
+    #[test]
+    fn test_envelopes_span_records() {
+        let envelope = Envelope::new(7, MessageKind::Request, "patch.fetch_chunk", &vec![42u8; 3 * MAX_RECORD_PAYLOAD]).unwrap();
+        let frame = envelope.encode().unwrap();
+        assert!(frame.len() > 3 * MAX_RECORD_PAYLOAD);
+
+        let mut reassembly = Reassembly::default();
+        let mut received = Vec::new();
+        for chunk in frame.chunks(MAX_RECORD_PAYLOAD) {
+            received.extend(reassembly.push(chunk).unwrap());
+        }
+        assert_eq!(received, vec![envelope.clone()]);
+
+        let future = Envelope { version: MESSAGE_VERSION + 1, ..envelope };
+        assert!(Reassembly::default().push(&future.encode().unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_typed_calls_correlate_and_time_out() {
+        let (client, server) = pair();
+        let server = tokio::spawn(serve(server, Arc::new(PatchDistributionServer(Device))));
+        let devices = PatchDistributionClient::new(MessageClient::start(client));
+
+        let offer = PatchOffer {
+            patch_id: "p-1".to_string(),
+            component: "cold_mirror".to_string(),
+            version: "1.2.0".to_string(),
+            size: 64 * 1024,
+            blake3: String::new(),
+        };
+        assert!(devices.offer(&offer).await.unwrap().accepted);
+
+        // The slow first chunk is answered after the second
+        let (slow, fast) = tokio::join!(
+            devices.fetch_chunk(&ChunkRequest { patch_id: "p-1".to_string(), offset: 32 * 1024, length: 32 * 1024 }),
+            devices.fetch_chunk(&ChunkRequest { patch_id: "p-1".to_string(), offset: 0, length: 32 * 1024 }),
+        );
+        assert_eq!((slow.unwrap().offset, fast.unwrap().offset), (32 * 1024, 0));
+
+        let failed = InstallReport { patch_id: "p-1".to_string(), installed: false, detail: "disk full".to_string() };
+        assert!(matches!(devices.report(&failed).await, Err(SentinelError::RemoteError(message)) if message.contains("disk full")));
+
+        // A peer that holds the connection open but never answers
+        let (silent, _peer) = tokio::io::duplex(64 * 1024);
+        let config = KeepAliveConfig { interval: Duration::from_secs(5), miss_threshold: 3 };
+        let timed_out = MessageClient::start(LiveConnection::start(silent, config)).with_timeout(Duration::from_millis(20));
+        let result: Result<ReportAck, _> = timed_out.call("patch.report", &failed).await;
+        assert!(matches!(result, Err(SentinelError::Timeout(_))));
+
+        drop(devices);
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_full_chunk_of_high_bytes_fits_one_message() {
+        // 0xFF is the widest byte as a JSON number; hex keeps it at two characters
+        let chunk = PatchChunk { patch_id: "p-1".to_string(), offset: 0, data: vec![0xFF; MAX_CHUNK], last: true };
+        let envelope = Envelope::new(9, MessageKind::Response, "patch.fetch_chunk", &chunk).unwrap();
+        assert!(envelope.encode().unwrap().len() <= 4 + MAX_MESSAGE);
+        assert_eq!(decode_body::<PatchChunk>(&envelope.payload).unwrap(), chunk);
+
+        let (client, server) = pair();
+        let server = tokio::spawn(serve(server, Arc::new(PatchDistributionServer(Device))));
+        let devices = PatchDistributionClient::new(MessageClient::start(client));
+        let request = ChunkRequest { patch_id: "p-1".to_string(), offset: 0xFF, length: MAX_CHUNK as u32 };
+        let fetched = devices.fetch_chunk(&request).await.unwrap();
+        assert_eq!(fetched.data, vec![0xFF; MAX_CHUNK]);
+        drop(devices);
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_requests_are_bounded() {
+        struct Counting {
+            running: std::sync::atomic::AtomicUsize,
+            peak: std::sync::atomic::AtomicUsize,
+        }
+
+        impl MessageHandler for Counting {
+            fn handle(&self, _method: &str, _payload: &[u8]) -> impl Future<Output = Result<Vec<u8>, String>> + Send {
+                async move {
+                    let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.peak.fetch_max(running, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    self.running.fetch_sub(1, Ordering::SeqCst);
+                    encode_body(&ReportAck { recorded: true })
+                }
+            }
+        }
+
+        let (client, server) = pair();
+        let handler = Arc::new(Counting { running: Default::default(), peak: Default::default() });
+        let server = tokio::spawn(serve(server, handler.clone()));
+        let client = Arc::new(MessageClient::start(client));
+        let mut calls = JoinSet::new();
+        for _ in 0..2 * MAX_IN_FLIGHT_REQUESTS {
+            let client = client.clone();
+            calls.spawn(async move {
+                let report = InstallReport { patch_id: "p-1".to_string(), installed: true, detail: String::new() };
+                client.call::<_, ReportAck>("patch.report", &report).await
+            });
+        }
+        while let Some(result) = calls.join_next().await {
+            assert!(result.unwrap().unwrap().recorded);
+        }
+        assert!(handler.peak.load(Ordering::SeqCst) <= MAX_IN_FLIGHT_REQUESTS);
+        drop(client);
+        assert!(server.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bound_messages_rejected_on_other_connections() {
+        let offer = PatchOffer {
+            patch_id: "p-2".to_string(),
+            component: "cold_mirror".to_string(),
+            version: "1.3.0".to_string(),
+            size: 1024,
+            blake3: String::new(),
+        };
+
+        // Bound from the exporter of a real key exchange on both ends
+        let (client, server) = secure_pair().await;
+        let keepalive = KeepAliveConfig { interval: Duration::from_secs(5), miss_threshold: 3 };
+        let server = tokio::spawn(serve_secure(server, keepalive, Arc::new(PatchDistributionServer(Device))));
+        let devices = PatchDistributionClient::secure(client, keepalive).unwrap();
+        assert!(devices.offer(&offer).await.unwrap().accepted);
+        let chunk = devices.fetch_chunk(&ChunkRequest { patch_id: "p-2".to_string(), offset: 0, length: 1024 }).await.unwrap();
+        assert_eq!(chunk.data.len(), 1024);
+        drop(devices);
+        assert!(server.await.unwrap().is_ok());
+
+        // An approval tagged for one connection does not verify on another
+        let mut approval = Envelope::new(3, MessageKind::Response, "patch.offer", &OfferReply { accepted: true, reason: String::new() }).unwrap();
+        ChannelBinding::from_key([1; 32]).seal(&mut approval);
+        assert!(ChannelBinding::from_key([1; 32]).verify(&approval).is_ok());
+        assert!(ChannelBinding::from_key([2; 32]).verify(&approval).is_err());
+        let forged = Envelope { payload: encode_body(&OfferReply { accepted: false, reason: String::new() }).unwrap(), ..approval };
+        assert!(ChannelBinding::from_key([1; 32]).verify(&forged).is_err());
+
+        // Peers holding different bindings refuse each other's messages
+        let (client, server) = pair();
+        let server = tokio::spawn(serve_with(server, Arc::new(PatchDistributionServer(Device)), Some(Arc::new(ChannelBinding::from_key([2; 32])))));
+        let devices = PatchDistributionClient::new(MessageClient::start(client).with_binding(ChannelBinding::from_key([1; 32])));
+        assert!(matches!(devices.offer(&offer).await, Err(SentinelError::ProtocolError(_))));
+        drop(devices);
+        assert!(server.await.unwrap().is_ok());
+    }
+}
@@ -0,0 +1,208 @@
+//! Sentinel Connection Pool - Reusing Live Connections to Peers
+//! "Behold, I stand at the door, and knock" - Revelation 3:20
+//!
+//! Keeps one keep-alive connection per (address, service). A connection
+//! whose peer is declared dead, or whose record layer has stopped, is
+//! evicted the next time it is requested or swept, and `get` transparently
+//! reconnects. Metrics of every pooled connection, including RTT, are
+//! available for monitoring.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::keepalive::{ConnectionMetrics, KeepAliveConfig, LiveConnection, MetricsSnapshot};
+use crate::{SentinelClient, SentinelError};
+
+/// Pool key: peer address and service
+pub type PoolKey = (SocketAddr, String);
+
+/// Connection shared by users of the pool
+pub type PooledConnection = Arc<Mutex<LiveConnection>>;
+
+/// Pooled connection and its metrics, readable while the connection is in use
+struct PoolEntry {
+    connection: PooledConnection,
+    metrics: Arc<ConnectionMetrics>,
+}
+
+impl PoolEntry {
+    fn new(connection: LiveConnection) -> Self {
+        Self {
+            metrics: connection.metrics(),
+            connection: Arc::new(Mutex::new(connection)),
+        }
+    }
+
+    /// Whether the connection can be handed out again
+    ///
+    /// A connection locked by a caller is judged by its metrics alone rather
+    /// than waiting for the caller to release it.
+    fn is_alive(&self) -> bool {
+        match self.connection.try_lock() {
+            Ok(connection) => connection.is_alive(),
+            Err(_) => !self.metrics.is_dead(),
+        }
+    }
+}
+
+/// Pooled keep-alive connections of one local identity
+///
+/// The pool lock is never held across an `.await`: connecting, and
+/// waiting on a connection in use, happen with it released.
+pub struct SentinelPool {
+    peer_id: String,
+    quantum_resistant: bool,
+    keepalive: KeepAliveConfig,
+    connections: std::sync::Mutex<HashMap<PoolKey, PoolEntry>>,
+}
+
+impl SentinelPool {
+    /// Create an empty pool connecting as `peer_id`
+    pub fn new(peer_id: impl Into<String>, quantum_resistant: bool, keepalive: KeepAliveConfig) -> Self {
+        Self {
+            peer_id: peer_id.into(),
+            quantum_resistant,
+            keepalive,
+            connections: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Live connection to `service` at `addr`, reconnecting if the pooled one died
+    pub async fn get(&self, addr: SocketAddr, service: &str) -> Result<PooledConnection, SentinelError> {
+        let key = (addr, service.to_string());
+        if let Some(connection) = self.pooled(&key) {
+            return Ok(connection);
+        }
+
+        let mut client = SentinelClient::new(self.quantum_resistant)
+            .with_service(self.peer_id.clone(), service)
+            .with_keepalive(self.keepalive);
+        let entry = PoolEntry::new(client.connect_live(addr).await?);
+
+        // Another caller may have connected meanwhile; theirs wins and ours
+        // is dropped, which stops its record layer
+        let mut connections = self.lock();
+        if let Some(existing) = connections.get(&key).filter(|existing| existing.is_alive()) {
+            return Ok(existing.connection.clone());
+        }
+        info!("Pooled connection to {} at {}", service, addr);
+        let connection = entry.connection.clone();
+        connections.insert(key, entry);
+        Ok(connection)
+    }
+
+    /// Remove every connection whose peer is dead; returns the evicted keys
+    pub async fn evict_dead(&self) -> Vec<PoolKey> {
+        let mut connections = self.lock();
+        let mut evicted = Vec::new();
+        connections.retain(|key, entry| {
+            let alive = entry.is_alive();
+            if !alive {
+                warn!("Evicting dead connection to {} at {}", key.1, key.0);
+                evicted.push(key.clone());
+            }
+            alive
+        });
+        evicted
+    }
+
+    /// Metrics of every pooled connection
+    pub async fn metrics(&self) -> HashMap<PoolKey, MetricsSnapshot> {
+        self.lock()
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.metrics.snapshot()))
+            .collect()
+    }
+
+    /// Pooled connection for `key` if it is alive; a dead one is evicted
+    fn pooled(&self, key: &PoolKey) -> Option<PooledConnection> {
+        let mut connections = self.lock();
+        let entry = connections.get(key)?;
+        if entry.is_alive() {
+            return Some(entry.connection.clone());
+        }
+        warn!("Evicting dead connection to {} at {}; reconnecting", key.1, key.0);
+        connections.remove(key);
+        None
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PoolKey, PoolEntry>> {
+        self.connections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{Extension, ExtensionKind};
+    use crate::{Authorizer, NetworkSentinel, SentinelConfig, StaticAcl};
+    use std::time::Duration;
+
+    async fn server(keepalive: KeepAliveConfig) -> SocketAddr {
+        let mut config = SentinelConfig::default();
+        config.bind_addr = "127.0.0.1:0".parse().unwrap();
+        config.capabilities.extensions.push(Extension::new(ExtensionKind::KeepAlive, false));
+        config.keepalive = keepalive;
+        config.authorizer = Arc::new(Authorizer::static_only(StaticAcl { rules: vec![], default_allow: true }));
+
+        let mut sentinel = NetworkSentinel::new(config);
+        sentinel.initialize().await.unwrap();
+        let addr = sentinel.local_addr().unwrap();
+        tokio::spawn(async move { sentinel.run().await });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_pool_reuses_and_replaces_connections() {
+        let keepalive = KeepAliveConfig { interval: Duration::from_millis(20), miss_threshold: 3 };
+        let addr = server(keepalive).await;
+        let pool = SentinelPool::new("sensor-1", true, keepalive);
+
+        let first = pool.get(addr, "echo").await.unwrap();
+        {
+            let mut connection = first.lock().await;
+            connection.send(b"ping".to_vec()).await.unwrap();
+            assert_eq!(connection.recv().await.unwrap(), b"ping");
+        }
+        assert!(Arc::ptr_eq(&first, &pool.get(addr, "echo").await.unwrap()));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let metrics = pool.metrics().await;
+        assert!(metrics[&(addr, "echo".to_string())].smoothed_rtt.is_some());
+
+        // A stopped record layer is evicted and replaced on the next get
+        pool.lock().insert(
+            (addr, "echo".to_string()),
+            PoolEntry::new(LiveConnection::start(tokio::io::duplex(64).0, keepalive)),
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(pool.evict_dead().await, vec![(addr, "echo".to_string())]);
+        let replacement = pool.get(addr, "echo").await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &replacement));
+    }
+
+    #[tokio::test]
+    async fn test_connection_in_use_does_not_block_the_pool() {
+        let keepalive = KeepAliveConfig { interval: Duration::from_millis(20), miss_threshold: 3 };
+        let addr = server(keepalive).await;
+        let pool = SentinelPool::new("sensor-1", true, keepalive);
+
+        // A caller waiting on a reply holds its connection's lock
+        let held = pool.get(addr, "echo").await.unwrap();
+        let guard = held.lock().await;
+
+        let other = tokio::time::timeout(Duration::from_secs(5), pool.get(addr, "other")).await
+            .expect("pool blocked behind a connection in use")
+            .unwrap();
+        assert!(!Arc::ptr_eq(&held, &other));
+        let reused = tokio::time::timeout(Duration::from_secs(1), pool.get(addr, "echo")).await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&held, &reused));
+        assert_eq!(tokio::time::timeout(Duration::from_secs(1), pool.metrics()).await.unwrap().len(), 2);
+        assert!(pool.evict_dead().await.is_empty());
+        drop(guard);
+    }
+}
@@ -1,22 +1,52 @@
 //! Post-Quantum TLS 1.3 Implementation
 //! "A cord of three strands is not quickly broken" - Ecclesiastes 4:12
 //!
-//! Implements hybrid classical + post-quantum TLS for future-proof security
+//! Implements hybrid classical + post-quantum TLS for future-proof security.
+//! Besides the round-3 Kyber768/Dilithium3 parameter sets it supports their
+//! FIPS 203/204 successors ML-KEM-768 and ML-DSA-65, alone or, for the
+//! key exchange, paired with X25519. Negotiation prefers a hybrid key
+//! exchange, and the standardized one among those, whenever both sides
+//! offer it; a hybrid secret hashes the X25519 and KEM secrets together,
+//! so it holds while either does. Peers whose hybrid key
+//! fingerprint is pinned are held to it by `verify_pinned_signature`;
+//! `sign_identity` signs with the long-term Ed25519 + Dilithium3 identity
+//! whatever algorithm was negotiated, for proving a handshake side's keys.
+//! The post-quantum operations of a handshake run on the configured
+//! `offload` path, the host CPU or the Tri-Compute Core.
+//! Once the key exchange completes, `export_keying_material` derives
+//! labelled keys from its secret and transcript (mixed with the TLS
+//! exporter on a `PQTlsStream`), for binding application messages to the
+//! connection they were sent on.
+
+pub mod offload;
 
 use std::sync::Arc;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_rustls::TlsAcceptor;
 use rustls::{ServerConfig, Certificate, PrivateKey};
 use ring::rand::{SecureRandom, SystemRandom};
-use pqcrypto_kyber::*;
-use pqcrypto_dilithium::*;
 use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey};
-use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey as Ed25519PublicKey};
+use ed25519_dalek::{Signer as _, SigningKey as Ed25519SigningKey, Verifier as _, VerifyingKey as Ed25519VerifyingKey};
 use sha3::{Sha3_256, Digest};
 use std::error::Error;
 use std::fmt;
 use serde::{Serialize, Deserialize};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use pq_types::scheme::{Dilithium3, Kem, Kyber768, MlDsa65, MlKem768, SchemeError, SignatureScheme};
+use pq_types::pins::{KeyFingerprint, PinCheck, PinStore};
+use ark_alert::{Alert, Severity};
+use pq_types::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, Ed25519SignatureBytes,
+    KyberCiphertextBytes, KyberPublicKeyBytes, KyberSecretKeyBytes, MlDsaPublicKeyBytes, MlDsaSecretKeyBytes,
+    MlKemPublicKeyBytes, MlKemSecretKeyBytes, PqBytesError, PqSignatureBytes, X25519PublicKeyBytes,
+};
+use offload::{OffloadScheme, PqOffload, SoftwareOffload};
+
+/// Domain of keys derived by `export_keying_material`
+const EXPORTER_DOMAIN: &[u8] = b"ARK-PQ-TLS-EXPORTER-V1";
+
+/// Longest exporter label
+pub const MAX_EXPORTER_LABEL: usize = 255;
 
 
 /// Post-quantum TLS errors
@@ -48,7 +78,24 @@ impl fmt::Display for PQTlsError {
 
 impl Error for PQTlsError {}
 
+impl From<PqBytesError> for PQTlsError {
+    fn from(e: PqBytesError) -> Self {
+        PQTlsError::CryptoError(e.to_string())
+    }
+}
+
+impl From<SchemeError> for PQTlsError {
+    fn from(e: SchemeError) -> Self {
+        match e {
+            SchemeError::VerificationFailed => PQTlsError::SignatureVerificationFailed,
+            other => PQTlsError::CryptoError(other.to_string()),
+        }
+    }
+}
+
 /// Supported post-quantum algorithms
+///
+/// New variants are appended: the variant index is the wire encoding.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PQAlgorithm {
     /// Hybrid X25519 + Kyber768
@@ -59,6 +106,40 @@ pub enum PQAlgorithm {
     Kyber768,
     /// Pure Dilithium3
     Dilithium3,
+    /// ML-KEM-768 (FIPS 203)
+    MlKem768,
+    /// ML-DSA-65 (FIPS 204)
+    MlDsa65,
+    /// Hybrid X25519 + ML-KEM-768
+    HybridX25519MlKem768,
+}
+
+impl PQAlgorithm {
+    /// Whether this is a final NIST standard rather than a round-3 parameter set
+    pub fn is_standardized(self) -> bool {
+        matches!(self, PQAlgorithm::MlKem768 | PQAlgorithm::MlDsa65 | PQAlgorithm::HybridX25519MlKem768)
+    }
+    
+    /// Whether this is a key exchange rather than a signature algorithm
+    pub fn is_key_exchange(self) -> bool {
+        matches!(
+            self,
+            PQAlgorithm::HybridX25519Kyber768 | PQAlgorithm::Kyber768 | PQAlgorithm::MlKem768 | PQAlgorithm::HybridX25519MlKem768
+        )
+    }
+    
+    /// Whether this pairs a classical algorithm with a post-quantum one
+    pub fn is_hybrid(self) -> bool {
+        matches!(
+            self,
+            PQAlgorithm::HybridX25519Kyber768 | PQAlgorithm::HybridEd25519Dilithium3 | PQAlgorithm::HybridX25519MlKem768
+        )
+    }
+    
+    /// Whether the key exchange has an X25519 half
+    fn has_x25519(self) -> bool {
+        matches!(self, PQAlgorithm::HybridX25519Kyber768 | PQAlgorithm::HybridX25519MlKem768)
+    }
 }
 
 /// Post-quantum key share for TLS handshake
@@ -67,9 +148,20 @@ pub struct PQKeyShare {
     /// Algorithm used
     pub algorithm: PQAlgorithm,
     /// Classical public key (if hybrid)
-    pub classical_public: Option<Vec<u8>>,
-    /// Post-quantum public key
-    pub pq_public: Vec<u8>,
+    pub classical_public: Option<X25519PublicKeyBytes>,
+    /// Post-quantum public key (Kyber768 and ML-KEM-768 keys are both 1184 bytes)
+    pub pq_public: KyberPublicKeyBytes,
+}
+
+/// Client's answer to a server key share
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PQKeyExchange {
+    /// Algorithm of the key share answered
+    pub algorithm: PQAlgorithm,
+    /// Client's ephemeral X25519 public key (hybrid only)
+    pub classical_public: Option<X25519PublicKeyBytes>,
+    /// KEM ciphertext (Kyber768 and ML-KEM-768 ciphertexts are both 1088 bytes)
+    pub ciphertext: KyberCiphertextBytes,
 }
 
 /// Post-quantum signature for TLS certificates
@@ -78,42 +170,49 @@ pub struct PQSignature {
     /// Algorithm used
     pub algorithm: PQAlgorithm,
     /// Classical signature (if hybrid)
-    pub classical_signature: Option<Vec<u8>>,
-    /// Post-quantum signature
-    pub pq_signature: Vec<u8>,
+    pub classical_signature: Option<Ed25519SignatureBytes>,
+    /// Post-quantum signature (detached), Dilithium3 or ML-DSA-65 per `algorithm`
+    pub pq_signature: PqSignatureBytes,
 }
 
-/// Hybrid key exchange result
+/// Hybrid key exchange result, wiped on drop
+#[derive(Zeroize, ZeroizeOnDrop)]
 pub struct HybridSharedSecret {
     /// Combined shared secret
     pub secret: Vec<u8>,
 }
 
-impl Zeroize for HybridSharedSecret {
-    fn zeroize(&mut self) {
-        self.secret.zeroize();
-    }
-}
-
 /// Post-quantum TLS configuration
+///
+/// Every secret key type held here wipes itself on drop (see the
+/// zeroization tests); public keys are not wiped.
 pub struct PQTlsConfig {
     /// Supported PQ algorithms in preference order
     pub supported_algorithms: Vec<PQAlgorithm>,
     /// Whether to require PQ algorithms
     pub require_pq: bool,
     /// Kyber keypair
-    pub kyber_keypair: Option<(pqcrypto_kyber::PublicKey, pqcrypto_kyber::SecretKey)>,
+    pub kyber_keypair: Option<(KyberPublicKeyBytes, KyberSecretKeyBytes)>,
     /// Dilithium keypair
-    pub dilithium_keypair: Option<(pqcrypto_dilithium::PublicKey, pqcrypto_dilithium::SecretKey)>,
-    /// Classical keypairs for hybrid mode
-    pub x25519_secret: Option<EphemeralSecret>,
-    pub ed25519_keypair: Option<Ed25519Keypair>,
+    pub dilithium_keypair: Option<(DilithiumPublicKeyBytes, DilithiumSecretKeyBytes)>,
+    /// ML-KEM-768 keypair
+    pub ml_kem_keypair: Option<(MlKemPublicKeyBytes, MlKemSecretKeyBytes)>,
+    /// ML-DSA-65 keypair
+    pub ml_dsa_keypair: Option<(MlDsaPublicKeyBytes, MlDsaSecretKeyBytes)>,
+    /// Classical identity keypair for hybrid mode; X25519 keys are
+    /// generated per handshake
+    pub ed25519_keypair: Option<Ed25519SigningKey>,
+    /// Path the handshake's KEM and signature operations run on
+    pub offload: Arc<dyn PqOffload>,
 }
 
 impl Default for PQTlsConfig {
     fn default() -> Self {
         Self {
             supported_algorithms: vec![
+                PQAlgorithm::HybridX25519MlKem768,
+                PQAlgorithm::MlKem768,
+                PQAlgorithm::MlDsa65,
                 PQAlgorithm::HybridX25519Kyber768,
                 PQAlgorithm::HybridEd25519Dilithium3,
                 PQAlgorithm::Kyber768,
@@ -122,30 +221,73 @@ impl Default for PQTlsConfig {
             require_pq: true,
             kyber_keypair: None,
             dilithium_keypair: None,
-            x25519_secret: None,
+            ml_kem_keypair: None,
+            ml_dsa_keypair: None,
             ed25519_keypair: None,
+            offload: Arc::new(SoftwareOffload),
         }
     }
 }
 
 impl PQTlsConfig {
     /// Generate new keypairs for all algorithms
+    ///
+    /// The PQ keys come from whichever `pq_types::scheme` backend the
+    /// `pq-*` features selected.
     pub fn generate_keypairs(&mut self) -> Result<(), PQTlsError> {
-        // Generate Kyber keypair
-        let (kyber_pk, kyber_sk) = pqcrypto_kyber::keypair();
-        self.kyber_keypair = Some((kyber_pk, kyber_sk));
+        // Generate Kyber and Dilithium keypairs
+        self.kyber_keypair = Some(Kyber768::keypair()?);
+        self.dilithium_keypair = Some(Dilithium3::keypair()?);
         
-        // Generate Dilithium keypair
-        let (dilithium_pk, dilithium_sk) = pqcrypto_dilithium::keypair();
-        self.dilithium_keypair = Some((dilithium_pk, dilithium_sk));
+        // Generate the standardized ML-KEM and ML-DSA keypairs
+        self.ml_kem_keypair = Some(MlKem768::keypair()?);
+        self.ml_dsa_keypair = Some(MlDsa65::keypair()?);
         
         // Generate classical keypairs
         use rand::rngs::OsRng;
-        self.x25519_secret = Some(EphemeralSecret::new(OsRng));
-        self.ed25519_keypair = Some(Ed25519Keypair::generate(&mut OsRng));
+        self.ed25519_keypair = Some(Ed25519SigningKey::generate(&mut OsRng));
         
         Ok(())
     }
+    
+    /// Whether every key pair a handshake may need is configured
+    pub fn has_keypairs(&self) -> bool {
+        self.kyber_keypair.is_some()
+            && self.dilithium_keypair.is_some()
+            && self.ml_kem_keypair.is_some()
+            && self.ml_dsa_keypair.is_some()
+            && self.ed25519_keypair.is_some()
+    }
+    
+    /// Generate the key pairs not already configured
+    ///
+    /// Identity keys loaded beforehand are kept, so peers can pin them.
+    pub fn generate_missing_keypairs(&mut self) -> Result<(), PQTlsError> {
+        use rand::rngs::OsRng;
+        if self.kyber_keypair.is_none() {
+            self.kyber_keypair = Some(Kyber768::keypair()?);
+        }
+        if self.dilithium_keypair.is_none() {
+            self.dilithium_keypair = Some(Dilithium3::keypair()?);
+        }
+        if self.ml_kem_keypair.is_none() {
+            self.ml_kem_keypair = Some(MlKem768::keypair()?);
+        }
+        if self.ml_dsa_keypair.is_none() {
+            self.ml_dsa_keypair = Some(MlDsa65::keypair()?);
+        }
+        if self.ed25519_keypair.is_none() {
+            self.ed25519_keypair = Some(Ed25519SigningKey::generate(&mut OsRng));
+        }
+        Ok(())
+    }
+    
+    /// Hybrid fingerprint of the Ed25519 and Dilithium3 identity keys
+    pub fn identity_fingerprint(&self) -> Option<KeyFingerprint> {
+        let ed25519 = self.ed25519_keypair.as_ref()?;
+        let (dilithium, _) = self.dilithium_keypair.as_ref()?;
+        Some(KeyFingerprint::hybrid(ed25519.verifying_key().as_bytes(), dilithium.as_bytes()))
+    }
 }
 
 /// Post-quantum TLS handshake extension
@@ -158,6 +300,10 @@ pub struct PQHandshake {
     negotiated_algorithm: Option<PQAlgorithm>,
     /// Shared secret after key exchange
     shared_secret: Option<HybridSharedSecret>,
+    /// Server's X25519 secret for the hybrid share it sent
+    x25519_ephemeral: Option<EphemeralSecret>,
+    /// Running hash of the key share and exchange
+    transcript: Sha3_256,
 }
 
 impl PQHandshake {
@@ -168,139 +314,172 @@ impl PQHandshake {
             is_client,
             negotiated_algorithm: None,
             shared_secret: None,
+            x25519_ephemeral: None,
+            transcript: Sha3_256::new(),
         }
     }
     
-    /// Generate key share for handshake
-    pub fn generate_key_share(&self, algorithm: PQAlgorithm) -> Result<PQKeyShare, PQTlsError> {
-        match algorithm {
-            PQAlgorithm::HybridX25519Kyber768 => {
-                // Get X25519 public key
-                let x25519_public = self.config.x25519_secret.as_ref()
-                    .map(|secret| X25519PublicKey::from(secret))
-                    .ok_or(PQTlsError::CryptoError("Missing X25519 key".into()))?;
-                
-                // Get Kyber public key
-                let kyber_public = self.config.kyber_keypair.as_ref()
-                    .map(|(pk, _)| pk.clone())
-                    .ok_or(PQTlsError::CryptoError("Missing Kyber key".into()))?;
-                
-                Ok(PQKeyShare {
-                    algorithm,
-                    classical_public: Some(x25519_public.as_bytes().to_vec()),
-                    pq_public: kyber_public.as_bytes().to_vec(),
-                })
-            }
-            PQAlgorithm::Kyber768 => {
-                let kyber_public = self.config.kyber_keypair.as_ref()
-                    .map(|(pk, _)| pk.clone())
-                    .ok_or(PQTlsError::CryptoError("Missing Kyber key".into()))?;
-                
-                Ok(PQKeyShare {
-                    algorithm,
-                    classical_public: None,
-                    pq_public: kyber_public.as_bytes().to_vec(),
-                })
+    /// Server side: key share opening the key exchange for `algorithm`
+    ///
+    /// The KEM key comes from the configuration; the X25519 half of a
+    /// hybrid share is generated for this handshake only.
+    pub fn offer_key_share(&mut self, algorithm: PQAlgorithm) -> Result<PQKeyShare, PQTlsError> {
+        let pq_public = match algorithm {
+            PQAlgorithm::HybridX25519Kyber768 | PQAlgorithm::Kyber768 => self.config.kyber_keypair.as_ref()
+                .map(|(pk, _)| pk.clone())
+                .ok_or(PQTlsError::CryptoError("Missing Kyber key".into()))?,
+            PQAlgorithm::HybridX25519MlKem768 | PQAlgorithm::MlKem768 => {
+                let (ml_kem_public, _) = self.config.ml_kem_keypair.as_ref()
+                    .ok_or(PQTlsError::CryptoError("Missing ML-KEM key".into()))?;
+                KyberPublicKeyBytes::from_slice(ml_kem_public.as_bytes())?
             }
-            _ => Err(PQTlsError::UnsupportedAlgorithm),
-        }
+            _ => return Err(PQTlsError::UnsupportedAlgorithm),
+        };
+        
+        let classical_public = if algorithm.has_x25519() {
+            let secret = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+            let public = X25519PublicKey::from(&secret);
+            self.x25519_ephemeral = Some(secret);
+            Some(X25519PublicKeyBytes::from_slice(public.as_bytes())?)
+        } else {
+            None
+        };
+        
+        self.negotiated_algorithm = Some(algorithm);
+        let share = PQKeyShare { algorithm, classical_public, pq_public };
+        self.absorb(&share)?;
+        Ok(share)
     }
     
-    /// Process peer's key share and derive shared secret
-    pub fn process_key_share(&mut self, peer_share: &PQKeyShare) -> Result<(), PQTlsError> {
+    /// Client side: encapsulate to the server's key share and derive the shared secret
+    pub fn accept_key_share(&mut self, peer_share: &PQKeyShare) -> Result<PQKeyExchange, PQTlsError> {
+        // ML-KEM-768 ciphertexts are the size of Kyber768 ones, so either fits the field
+        let scheme = OffloadScheme::kem(peer_share.algorithm)?;
+        let (pq_shared, ciphertext) = self.config.offload.encapsulate(scheme, peer_share.pq_public.as_bytes())?;
+        let ciphertext = KyberCiphertextBytes::from_vec(ciphertext)?;
+        
+        self.absorb(peer_share)?;
+        let (classical_public, classical_shared) = match peer_share.algorithm {
+            algorithm if algorithm.has_x25519() => {
+                let peer_x25519 = peer_share.classical_public.as_ref()
+                    .ok_or(PQTlsError::ProtocolError("Missing classical key".into()))?;
+                let secret = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+                let public = X25519PublicKey::from(&secret);
+                let shared = secret.diffie_hellman(&X25519PublicKey::from(<[u8; 32]>::from(peer_x25519.clone())));
+                (Some(X25519PublicKeyBytes::from_slice(public.as_bytes())?), Some(shared))
+            }
+            _ => (None, None),
+        };
+        
         self.negotiated_algorithm = Some(peer_share.algorithm);
+        self.shared_secret = Some(combine_secrets(
+            scheme,
+            classical_shared.as_ref().map(|shared| shared.as_bytes().as_slice()),
+            pq_shared.as_slice(),
+        ));
+        let exchange = PQKeyExchange { algorithm: peer_share.algorithm, classical_public, ciphertext };
+        self.absorb(&exchange)?;
+        Ok(exchange)
+    }
+    
+    /// Server side: decapsulate the client's answer to `offer_key_share` and derive the shared secret
+    pub fn complete_key_exchange(&mut self, exchange: &PQKeyExchange) -> Result<(), PQTlsError> {
+        if self.negotiated_algorithm != Some(exchange.algorithm) {
+            return Err(PQTlsError::ProtocolError("Key exchange does not answer the key share".into()));
+        }
+        
+        let scheme = OffloadScheme::kem(exchange.algorithm)?;
+        let secret_key = match scheme {
+            OffloadScheme::Kyber768 => self.config.kyber_keypair.as_ref()
+                .map(|(_, secret)| secret.expose_secret())
+                .ok_or(PQTlsError::CryptoError("Missing Kyber key".into()))?,
+            _ => self.config.ml_kem_keypair.as_ref()
+                .map(|(_, secret)| secret.expose_secret())
+                .ok_or(PQTlsError::CryptoError("Missing ML-KEM key".into()))?,
+        };
+        let pq_shared = self.config.offload.decapsulate(scheme, exchange.ciphertext.as_bytes(), secret_key)?;
         
-        match peer_share.algorithm {
-            PQAlgorithm::HybridX25519Kyber768 => {
-                // Process X25519 part
-                let peer_x25519_public = peer_share.classical_public.as_ref()
+        let classical_shared = match exchange.algorithm {
+            algorithm if algorithm.has_x25519() => {
+                let peer_x25519 = exchange.classical_public.as_ref()
                     .ok_or(PQTlsError::ProtocolError("Missing classical key".into()))?;
-                
-                let x25519_public = X25519PublicKey::from(
-                    <[u8; 32]>::try_from(peer_x25519_public.as_slice())
-                        .map_err(|_| PQTlsError::CryptoError("Invalid X25519 key".into()))?
-                );
-                
-                let x25519_secret = self.config.x25519_secret.as_ref()
+                let secret = self.x25519_ephemeral.take()
                     .ok_or(PQTlsError::CryptoError("Missing X25519 secret".into()))?;
-                
-                // Note: In real implementation, we'd need to handle the ephemeral secret properly
-                // This is a simplified version
-                
-                // Process Kyber part
-                let peer_kyber_public = pqcrypto_kyber::PublicKey::from_bytes(&peer_share.pq_public)
-                    .map_err(|_| PQTlsError::CryptoError("Invalid Kyber key".into()))?;
-                
-                let (ciphertext, kyber_shared) = pqcrypto_kyber::encapsulate(&peer_kyber_public);
-                
-                // Combine secrets with domain separation
-                let mut kdf = Sha3_256::new();
-                kdf.update(b"ARK-PQ-TLS-HYBRID-V1");
-                kdf.update(b"X25519");
-                // kdf.update(x25519_shared.as_bytes()); // Would need the actual shared secret
-                kdf.update(b"KYBER768");
-                kdf.update(&kyber_shared);
-                
-                let combined_secret = kdf.finalize().to_vec();
-                
-                self.shared_secret = Some(HybridSharedSecret {
-                    secret: combined_secret,
-                });
-                
-                Ok(())
+                Some(secret.diffie_hellman(&X25519PublicKey::from(<[u8; 32]>::from(peer_x25519.clone()))))
             }
-            PQAlgorithm::Kyber768 => {
-                let peer_kyber_public = pqcrypto_kyber::PublicKey::from_bytes(&peer_share.pq_public)
-                    .map_err(|_| PQTlsError::CryptoError("Invalid Kyber key".into()))?;
-                
-                if self.is_client {
-                    // Client encapsulates
-                    let (ciphertext, shared_secret) = pqcrypto_kyber::encapsulate(&peer_kyber_public);
-                    
-                    self.shared_secret = Some(HybridSharedSecret {
-                        secret: shared_secret.as_bytes().to_vec(),
-                    });
-                } else {
-                    // Server will decapsulate when receiving ciphertext
-                    // This is handled in process_key_exchange_response
-                }
-                
-                Ok(())
-            }
-            _ => Err(PQTlsError::UnsupportedAlgorithm),
+            _ => None,
+        };
+        
+        self.shared_secret = Some(combine_secrets(
+            scheme,
+            classical_shared.as_ref().map(|shared| shared.as_bytes().as_slice()),
+            pq_shared.as_slice(),
+        ));
+        self.absorb(exchange)?;
+        Ok(())
+    }
+    
+    /// Hash of the key share and exchange seen by this handshake
+    pub fn transcript_hash(&self) -> [u8; 32] {
+        self.transcript.clone().finalize().into()
+    }
+    
+    /// Fill `output` with a key bound to this key exchange, `label` and `context`
+    ///
+    /// Both sides of one exchange derive the same key; any other label,
+    /// context or connection gives an unrelated one.
+    pub fn export_keying_material(&self, label: &[u8], context: &[u8], output: &mut [u8]) -> Result<(), PQTlsError> {
+        let secret = self.get_shared_secret()
+            .ok_or(PQTlsError::ProtocolError("Key exchange not complete".into()))?;
+        export_keying_material(secret, &self.transcript_hash(), label, context, output)
+    }
+    
+    /// Add a key share or exchange to the transcript
+    fn absorb<T: Serialize>(&mut self, message: &T) -> Result<(), PQTlsError> {
+        let bytes = bincode::serialize(message).map_err(|e| PQTlsError::ProtocolError(e.to_string()))?;
+        self.transcript.update((bytes.len() as u32).to_be_bytes());
+        self.transcript.update(&bytes);
+        Ok(())
+    }
+    
+    /// Client side: process the server's key share, returning the exchange to send back
+    ///
+    /// Same as `accept_key_share`: the KEM ciphertext and X25519 public key
+    /// in the returned exchange are what lets the server derive the same
+    /// secret, so they must reach it.
+    pub fn process_key_share(&mut self, peer_share: &PQKeyShare) -> Result<PQKeyExchange, PQTlsError> {
+        if !self.is_client {
+            return Err(PQTlsError::ProtocolError(
+                "The server completes the key exchange with complete_key_exchange".into()
+            ));
         }
+        self.accept_key_share(peer_share)
     }
     
     /// Create signature using hybrid algorithm
     pub fn create_signature(&self, message: &[u8]) -> Result<PQSignature, PQTlsError> {
         match self.negotiated_algorithm {
-            Some(PQAlgorithm::HybridEd25519Dilithium3) => {
-                // Ed25519 signature
-                let ed25519_keypair = self.config.ed25519_keypair.as_ref()
-                    .ok_or(PQTlsError::CryptoError("Missing Ed25519 key".into()))?;
-                
-                let ed25519_sig = ed25519_keypair.sign(message);
-                
-                // Dilithium signature
+            Some(PQAlgorithm::HybridEd25519Dilithium3) => self.sign_identity(message),
+            Some(PQAlgorithm::Dilithium3) => {
                 let (_, dilithium_sk) = self.config.dilithium_keypair.as_ref()
                     .ok_or(PQTlsError::CryptoError("Missing Dilithium key".into()))?;
                 
-                let dilithium_sig = pqcrypto_dilithium::sign(message, dilithium_sk);
+                let signature = self.offload_sign(OffloadScheme::Dilithium3, message, dilithium_sk.expose_secret())?;
                 
                 Ok(PQSignature {
-                    algorithm: PQAlgorithm::HybridEd25519Dilithium3,
-                    classical_signature: Some(ed25519_sig.to_bytes().to_vec()),
-                    pq_signature: dilithium_sig,
+                    algorithm: PQAlgorithm::Dilithium3,
+                    classical_signature: None,
+                    pq_signature: signature,
                 })
             }
-            Some(PQAlgorithm::Dilithium3) => {
-                let (_, dilithium_sk) = self.config.dilithium_keypair.as_ref()
-                    .ok_or(PQTlsError::CryptoError("Missing Dilithium key".into()))?;
+            Some(PQAlgorithm::MlDsa65) => {
+                let (_, ml_dsa_sk) = self.config.ml_dsa_keypair.as_ref()
+                    .ok_or(PQTlsError::CryptoError("Missing ML-DSA key".into()))?;
                 
-                let signature = pqcrypto_dilithium::sign(message, dilithium_sk);
+                let signature = self.offload_sign(OffloadScheme::MlDsa65, message, ml_dsa_sk.expose_secret())?;
                 
                 Ok(PQSignature {
-                    algorithm: PQAlgorithm::Dilithium3,
+                    algorithm: PQAlgorithm::MlDsa65,
                     classical_signature: None,
                     pq_signature: signature,
                 })
@@ -309,40 +488,109 @@ impl PQHandshake {
         }
     }
     
+    /// Hybrid Ed25519 + Dilithium3 signature with the identity keys
+    ///
+    /// Independent of the negotiated algorithm, which after a key exchange
+    /// names a KEM rather than a signature scheme.
+    pub fn sign_identity(&self, message: &[u8]) -> Result<PQSignature, PQTlsError> {
+        let ed25519_keypair = self.config.ed25519_keypair.as_ref()
+            .ok_or(PQTlsError::CryptoError("Missing Ed25519 key".into()))?;
+        let ed25519_sig = ed25519_keypair.sign(message);
+        
+        let (_, dilithium_sk) = self.config.dilithium_keypair.as_ref()
+            .ok_or(PQTlsError::CryptoError("Missing Dilithium key".into()))?;
+        let dilithium_sig = self.offload_sign(OffloadScheme::Dilithium3, message, dilithium_sk.expose_secret())?;
+        
+        Ok(PQSignature {
+            algorithm: PQAlgorithm::HybridEd25519Dilithium3,
+            classical_signature: Some(Ed25519SignatureBytes::from(&ed25519_sig)),
+            pq_signature: dilithium_sig,
+        })
+    }
+    
+    /// Verify a signature of `peer`, holding its keys to their pin
+    ///
+    /// Keys whose fingerprint differs from the one pinned for `peer` are
+    /// refused before the signature is checked, and the change is logged as
+    /// an alert. Unpinned peers are verified as by `verify_signature`.
+    pub fn verify_pinned_signature(&self, message: &[u8], signature: &PQSignature, peer: &str,
+                                   peer_public_keys: &PeerPublicKeys, pins: &PinStore) -> Result<(), PQTlsError> {
+        match peer_public_keys.fingerprint() {
+            Some(presented) => {
+                if let PinCheck::Changed { pinned, presented } = pins.check(peer, presented) {
+                    tracing::error!("ALERT: key fingerprint of peer {} changed from pinned {} to {}", peer, pinned, presented);
+                    ark_alert::raise(
+                        Alert::new("network_sentinel", "key_pin_changed", peer, Severity::Critical, "peer key fingerprint differs from its pin")
+                            .with_detail("pinned", pinned)
+                            .with_detail("presented", presented),
+                    );
+                    return Err(PQTlsError::SignatureVerificationFailed);
+                }
+            }
+            None if pins.get(peer).is_some() => {
+                tracing::error!("ALERT: pinned peer {} presented no hybrid key pair", peer);
+                ark_alert::raise(Alert::new("network_sentinel", "downgrade", peer, Severity::Critical,
+                                            "pinned peer presented no hybrid key pair"));
+                return Err(PQTlsError::SignatureVerificationFailed);
+            }
+            None => {}
+        }
+        self.verify_signature(message, signature, peer_public_keys)
+    }
+    
     /// Verify signature using hybrid algorithm
     pub fn verify_signature(&self, message: &[u8], signature: &PQSignature, 
                           peer_public_keys: &PeerPublicKeys) -> Result<(), PQTlsError> {
         match signature.algorithm {
             PQAlgorithm::HybridEd25519Dilithium3 => {
-                // Verify Ed25519 signature
-                if let Some(ed25519_sig_bytes) = &signature.classical_signature {
-                    let ed25519_sig = ed25519_dalek::Signature::from_bytes(
-                        &<[u8; 64]>::try_from(ed25519_sig_bytes.as_slice())
-                            .map_err(|_| PQTlsError::CryptoError("Invalid Ed25519 signature".into()))?
-                    );
-                    
-                    peer_public_keys.ed25519_public.as_ref()
-                        .ok_or(PQTlsError::CryptoError("Missing Ed25519 public key".into()))?
-                        .verify(message, &ed25519_sig)
-                        .map_err(|_| PQTlsError::SignatureVerificationFailed)?;
-                }
+                // Verify Ed25519 signature; a hybrid signature without one is forged
+                let ed25519_sig_bytes = signature.classical_signature.as_ref()
+                    .ok_or(PQTlsError::SignatureVerificationFailed)?;
+                let ed25519_sig = ed25519_dalek::Signature::from(ed25519_sig_bytes);
+                
+                peer_public_keys.ed25519_public.as_ref()
+                    .ok_or(PQTlsError::CryptoError("Missing Ed25519 public key".into()))?
+                    .verify(message, &ed25519_sig)
+                    .map_err(|_| PQTlsError::SignatureVerificationFailed)?;
                 
                 // Verify Dilithium signature
-                pqcrypto_dilithium::verify(
-                    &signature.pq_signature,
+                let dilithium_sig = signature.pq_signature.dilithium()
+                    .map_err(|_| PQTlsError::CryptoError("Invalid Dilithium signature".into()))?;
+                self.config.offload.verify(
+                    OffloadScheme::Dilithium3,
                     message,
+                    dilithium_sig.as_bytes(),
                     peer_public_keys.dilithium_public.as_ref()
                         .ok_or(PQTlsError::CryptoError("Missing Dilithium public key".into()))?
+                        .as_bytes(),
                 ).map_err(|_| PQTlsError::SignatureVerificationFailed)?;
                 
                 Ok(())
             }
             PQAlgorithm::Dilithium3 => {
-                pqcrypto_dilithium::verify(
-                    &signature.pq_signature,
+                let dilithium_sig = signature.pq_signature.dilithium()
+                    .map_err(|_| PQTlsError::CryptoError("Invalid Dilithium signature".into()))?;
+                self.config.offload.verify(
+                    OffloadScheme::Dilithium3,
                     message,
+                    dilithium_sig.as_bytes(),
                     peer_public_keys.dilithium_public.as_ref()
                         .ok_or(PQTlsError::CryptoError("Missing Dilithium public key".into()))?
+                        .as_bytes(),
+                ).map_err(|_| PQTlsError::SignatureVerificationFailed)?;
+                
+                Ok(())
+            }
+            PQAlgorithm::MlDsa65 => {
+                let ml_dsa_sig = signature.pq_signature.ml_dsa()
+                    .map_err(|_| PQTlsError::CryptoError("Invalid ML-DSA signature".into()))?;
+                self.config.offload.verify(
+                    OffloadScheme::MlDsa65,
+                    message,
+                    ml_dsa_sig.as_bytes(),
+                    peer_public_keys.ml_dsa_public.as_ref()
+                        .ok_or(PQTlsError::CryptoError("Missing ML-DSA public key".into()))?
+                        .as_bytes(),
                 ).map_err(|_| PQTlsError::SignatureVerificationFailed)?;
                 
                 Ok(())
@@ -355,12 +603,84 @@ impl PQHandshake {
     pub fn get_shared_secret(&self) -> Option<&[u8]> {
         self.shared_secret.as_ref().map(|s| s.secret.as_slice())
     }
+    
+    /// Post-quantum signature over `message` from the offload path
+    fn offload_sign(&self, scheme: OffloadScheme, message: &[u8], secret_key: &[u8]) -> Result<PqSignatureBytes, PQTlsError> {
+        Ok(PqSignatureBytes::from_vec(self.config.offload.sign(scheme, message, secret_key)?)?)
+    }
+}
+
+/// Shared secret of a key exchange
+///
+/// Hybrid exchanges hash both halves with domain separation, so the
+/// result stays secret while either half does; pure KEM exchanges use the
+/// KEM secret as is.
+fn combine_secrets(kem: OffloadScheme, classical: Option<&[u8]>, pq: &[u8]) -> HybridSharedSecret {
+    let secret = match classical {
+        Some(classical) => {
+            let mut kdf = Sha3_256::new();
+            kdf.update(b"ARK-PQ-TLS-HYBRID-V1");
+            kdf.update(b"X25519");
+            kdf.update(classical);
+            kdf.update(match kem {
+                OffloadScheme::MlKem768 => b"MLKEM768".as_slice(),
+                _ => b"KYBER768".as_slice(),
+            });
+            kdf.update(pq);
+            Zeroizing::new(<[u8; 32]>::from(kdf.finalize())).to_vec()
+        }
+        None => pq.to_vec(),
+    };
+    HybridSharedSecret { secret }
+}
+
+/// HKDF-SHA256 output length for `ring`
+struct ExporterLength(usize);
+
+impl ring::hkdf::KeyType for ExporterLength {
+    fn len(&self) -> usize {
+        self.0
+    }
+}
+
+/// Fill `output` with HKDF-SHA256 keying material from `secret`, salted with `transcript`
+///
+/// The label and context are length-prefixed in the HKDF info, so no
+/// label/context split collides with another.
+pub fn export_keying_material(
+    secret: &[u8],
+    transcript: &[u8; 32],
+    label: &[u8],
+    context: &[u8],
+    output: &mut [u8],
+) -> Result<(), PQTlsError> {
+    if label.is_empty() || label.len() > MAX_EXPORTER_LABEL {
+        return Err(PQTlsError::ProtocolError(format!("Invalid exporter label length {}", label.len())));
+    }
+    let label_length = [label.len() as u8];
+    let context_length = (context.len() as u32).to_be_bytes();
+    let info: [&[u8]; 5] = [EXPORTER_DOMAIN, &label_length, label, &context_length, context];
+    
+    let prk = ring::hkdf::Salt::new(ring::hkdf::HKDF_SHA256, transcript).extract(secret);
+    prk.expand(&info, ExporterLength(output.len()))
+        .and_then(|okm| okm.fill(output))
+        .map_err(|_| PQTlsError::CryptoError(format!("Exporter output of {} bytes too long", output.len())))
 }
 
 /// Peer's public keys for verification
 pub struct PeerPublicKeys {
-    pub ed25519_public: Option<Ed25519PublicKey>,
-    pub dilithium_public: Option<pqcrypto_dilithium::PublicKey>,
+    pub ed25519_public: Option<Ed25519VerifyingKey>,
+    pub dilithium_public: Option<DilithiumPublicKeyBytes>,
+    pub ml_dsa_public: Option<MlDsaPublicKeyBytes>,
+}
+
+impl PeerPublicKeys {
+    /// Hybrid fingerprint of the Ed25519 and Dilithium3 keys, if both are present
+    pub fn fingerprint(&self) -> Option<KeyFingerprint> {
+        let ed25519 = self.ed25519_public.as_ref()?;
+        let dilithium = self.dilithium_public.as_ref()?;
+        Some(KeyFingerprint::hybrid(ed25519.as_bytes(), dilithium.as_bytes()))
+    }
 }
 
 /// Post-quantum TLS acceptor
@@ -420,6 +740,25 @@ where
     pub fn get_pq_shared_secret(&self) -> Option<&[u8]> {
         self.pq_handshake.as_ref()?.get_shared_secret()
     }
+    
+    /// Fill `output` with a key bound to this connection, `label` and `context`
+    ///
+    /// The TLS 1.3 exporter value for the same label and context is mixed
+    /// with the PQ shared secret, salted with the PQ transcript, so the key
+    /// depends on both handshakes.
+    pub fn export_keying_material(&self, label: &[u8], context: &[u8], output: &mut [u8]) -> Result<(), PQTlsError> {
+        let handshake = self.pq_handshake.as_ref()
+            .ok_or(PQTlsError::ProtocolError("No PQ handshake on this stream".into()))?;
+        let secret = handshake.get_shared_secret()
+            .ok_or(PQTlsError::ProtocolError("Key exchange not complete".into()))?;
+        
+        let mut tls_exporter = Zeroizing::new([0u8; 32]);
+        self.inner.get_ref().1
+            .export_keying_material(&mut tls_exporter[..], label, Some(context))
+            .map_err(|e| PQTlsError::CryptoError(e.to_string()))?;
+        let ikm = Zeroizing::new([tls_exporter.as_slice(), secret].concat());
+        export_keying_material(&ikm, &handshake.transcript_hash(), label, context, output)
+    }
 }
 
 /// Create hybrid TLS configuration
@@ -446,6 +785,10 @@ pub fn create_hybrid_tls_config(require_pq: bool) -> Result<(ServerConfig, PQTls
 mod tests {
     use super::*;
     
+    #[cfg(debug_assertions)]
+    #[global_allocator]
+    static ALLOCATOR: pq_types::canary::CanaryAllocator = pq_types::canary::CanaryAllocator;
+    
     #[test]
     fn test_pq_key_generation() {
         let mut config = PQTlsConfig::default();
@@ -453,7 +796,6 @@ mod tests {
         
         assert!(config.kyber_keypair.is_some());
         assert!(config.dilithium_keypair.is_some());
-        assert!(config.x25519_secret.is_some());
         assert!(config.ed25519_keypair.is_some());
     }
     
@@ -462,19 +804,23 @@ mod tests {
         let mut config = PQTlsConfig::default();
         config.generate_keypairs().unwrap();
         
-        let handshake = PQHandshake::new(Arc::new(config), true);
+        let config = Arc::new(config);
         
-        // Test hybrid key share
-        let hybrid_share = handshake.generate_key_share(PQAlgorithm::HybridX25519Kyber768).unwrap();
+        // Test hybrid key share; the X25519 half is fresh for every handshake
+        let hybrid_share = PQHandshake::new(config.clone(), false).offer_key_share(PQAlgorithm::HybridX25519Kyber768).unwrap();
         assert_eq!(hybrid_share.algorithm, PQAlgorithm::HybridX25519Kyber768);
         assert!(hybrid_share.classical_public.is_some());
         assert!(!hybrid_share.pq_public.is_empty());
+        let again = PQHandshake::new(config.clone(), false).offer_key_share(PQAlgorithm::HybridX25519Kyber768).unwrap();
+        assert_ne!(again.classical_public, hybrid_share.classical_public);
         
         // Test pure PQ key share
-        let pq_share = handshake.generate_key_share(PQAlgorithm::Kyber768).unwrap();
+        let mut server = PQHandshake::new(config, false);
+        let pq_share = server.offer_key_share(PQAlgorithm::Kyber768).unwrap();
         assert_eq!(pq_share.algorithm, PQAlgorithm::Kyber768);
         assert!(pq_share.classical_public.is_none());
         assert!(!pq_share.pq_public.is_empty());
+        assert_eq!(server.negotiated_algorithm, Some(PQAlgorithm::Kyber768));
     }
     
     #[test]
@@ -495,8 +841,9 @@ mod tests {
         
         // Prepare public keys for verification
         let peer_keys = PeerPublicKeys {
-            ed25519_public: config.ed25519_keypair.as_ref().map(|kp| kp.public),
+            ed25519_public: config.ed25519_keypair.as_ref().map(|kp| kp.verifying_key()),
             dilithium_public: config.dilithium_keypair.as_ref().map(|(pk, _)| pk.clone()),
+            ml_dsa_public: None,
         };
         
         // Verify signature
@@ -505,5 +852,199 @@ mod tests {
         // Verify with wrong message fails
         let wrong_message = b"Wrong message";
         assert!(handshake.verify_signature(wrong_message, &signature, &peer_keys).is_err());
+        
+        // Stripping the Ed25519 half does not leave a valid Dilithium-only signature
+        let mut stripped = signature.clone();
+        stripped.classical_signature = None;
+        assert!(matches!(
+            handshake.verify_signature(message, &stripped, &peer_keys),
+            Err(PQTlsError::SignatureVerificationFailed)
+        ));
+    }
+    
+    #[test]
+    fn test_pinned_signature_rejects_changed_keys() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        let mut handshake = PQHandshake::new(Arc::new(config.clone()), true);
+        handshake.negotiated_algorithm = Some(PQAlgorithm::HybridEd25519Dilithium3);
+        let message = b"Pinned peer message";
+        let signature = handshake.create_signature(message).unwrap();
+        let peer_keys = PeerPublicKeys {
+            ed25519_public: config.ed25519_keypair.as_ref().map(|kp| kp.verifying_key()),
+            dilithium_public: config.dilithium_keypair.as_ref().map(|(pk, _)| pk.clone()),
+            ml_dsa_public: None,
+        };
+        
+        let mut pins = PinStore::in_memory();
+        assert!(handshake.verify_pinned_signature(message, &signature, "sentinel-b", &peer_keys, &pins).is_ok());
+        pins.pin("sentinel-b", peer_keys.fingerprint().unwrap()).unwrap();
+        assert!(handshake.verify_pinned_signature(message, &signature, "sentinel-b", &peer_keys, &pins).is_ok());
+        
+        pins.pin("sentinel-b", KeyFingerprint::hybrid(&[0u8; 32], &[0u8; 1952])).unwrap();
+        assert!(matches!(
+            handshake.verify_pinned_signature(message, &signature, "sentinel-b", &peer_keys, &pins),
+            Err(PQTlsError::SignatureVerificationFailed)
+        ));
+    }
+    
+    #[test]
+    fn test_standardized_algorithms() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        assert!(config.supported_algorithms[0].is_standardized());
+        assert!(config.supported_algorithms[0].is_hybrid());
+        let config = Arc::new(config);
+        
+        let mut server = PQHandshake::new(config.clone(), false);
+        let share = server.offer_key_share(PQAlgorithm::MlKem768).unwrap();
+        assert_eq!(share.pq_public.len(), pq_types::sizes::ML_KEM_768_PUBLIC_KEY);
+        let mut client = PQHandshake::new(config.clone(), true);
+        let exchange = client.process_key_share(&share).unwrap();
+        assert_eq!(client.get_shared_secret().map(<[u8]>::len), Some(32));
+        // The ciphertext reaches the server, which derives the same secret
+        assert!(server.process_key_share(&share).is_err());
+        server.complete_key_exchange(&exchange).unwrap();
+        assert_eq!(server.get_shared_secret(), client.get_shared_secret());
+        
+        client.negotiated_algorithm = Some(PQAlgorithm::MlDsa65);
+        let signature = client.create_signature(b"fips 204").unwrap();
+        assert_eq!(signature.pq_signature.len(), pq_types::sizes::ML_DSA_65_SIGNATURE);
+        
+        let peer_keys = PeerPublicKeys {
+            ed25519_public: None,
+            dilithium_public: None,
+            ml_dsa_public: config.ml_dsa_keypair.as_ref().map(|(pk, _)| pk.clone()),
+        };
+        assert!(client.verify_signature(b"fips 204", &signature, &peer_keys).is_ok());
+        assert!(client.verify_signature(b"fips 203", &signature, &peer_keys).is_err());
+        
+        // A Dilithium3 signature relabelled as ML-DSA-65 is rejected by length
+        client.negotiated_algorithm = Some(PQAlgorithm::Dilithium3);
+        let mut relabelled = client.create_signature(b"fips 204").unwrap();
+        relabelled.algorithm = PQAlgorithm::MlDsa65;
+        assert!(client.verify_signature(b"fips 204", &relabelled, &peer_keys).is_err());
+    }
+    
+    #[test]
+    fn test_hybrid_ml_kem_secret_covers_both_halves() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        let config = Arc::new(config);
+        
+        let mut server = PQHandshake::new(config.clone(), false);
+        let share = server.offer_key_share(PQAlgorithm::HybridX25519MlKem768).unwrap();
+        assert!(share.classical_public.is_some());
+        let mut client = PQHandshake::new(config.clone(), true);
+        let exchange = client.accept_key_share(&share).unwrap();
+        assert!(exchange.classical_public.is_some());
+        server.complete_key_exchange(&exchange).unwrap();
+        let secret = client.get_shared_secret().unwrap().to_vec();
+        assert_eq!(server.get_shared_secret(), Some(secret.as_slice()));
+        
+        // The hybrid secret is neither KEM secret alone
+        let (_, ml_kem_secret) = config.ml_kem_keypair.as_ref().unwrap();
+        let pq_shared = MlKem768::decapsulate(
+            &pq_types::MlKemCiphertextBytes::from_slice(exchange.ciphertext.as_bytes()).unwrap(),
+            ml_kem_secret,
+        ).unwrap();
+        assert_ne!(pq_shared.as_slice(), secret.as_slice());
+        
+        // A ciphertext from another exchange gives the server a different secret
+        let mut server = PQHandshake::new(config.clone(), false);
+        let share = server.offer_key_share(PQAlgorithm::HybridX25519MlKem768).unwrap();
+        let mut client = PQHandshake::new(config.clone(), true);
+        let mut exchange = client.accept_key_share(&share).unwrap();
+        exchange.ciphertext = PQHandshake::new(config, true).accept_key_share(&share).unwrap().ciphertext;
+        server.complete_key_exchange(&exchange).unwrap();
+        assert_ne!(server.get_shared_secret(), client.get_shared_secret());
+    }
+    
+    #[test]
+    fn test_exporter_keys_agree_per_exchange_and_label() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        let config = Arc::new(config);
+        let exchange = || {
+            let mut server = PQHandshake::new(config.clone(), false);
+            let mut client = PQHandshake::new(config.clone(), true);
+            let share = server.offer_key_share(PQAlgorithm::HybridX25519Kyber768).unwrap();
+            server.complete_key_exchange(&client.accept_key_share(&share).unwrap()).unwrap();
+            (server, client)
+        };
+        let export = |handshake: &PQHandshake, label: &[u8], context: &[u8]| {
+            let mut key = [0u8; 32];
+            handshake.export_keying_material(label, context, &mut key).unwrap();
+            key
+        };
+        
+        let (server, client) = exchange();
+        assert_eq!(server.transcript_hash(), client.transcript_hash());
+        let key = export(&server, b"EXPORTER-test", b"ctx");
+        assert_eq!(key, export(&client, b"EXPORTER-test", b"ctx"));
+        assert_ne!(key, export(&client, b"EXPORTER-other", b"ctx"));
+        assert_ne!(key, export(&client, b"EXPORTER-test", b"other"));
+        
+        // Another exchange over the same long-term keys gives unrelated keys
+        let (_, other) = exchange();
+        assert_ne!(key, export(&other, b"EXPORTER-test", b"ctx"));
+        
+        let mut key = [0u8; 32];
+        assert!(PQHandshake::new(config, true).export_keying_material(b"EXPORTER-test", b"", &mut key).is_err());
+    }
+    
+    #[test]
+    fn test_malformed_wire_fields_rejected() {
+        let mut config = PQTlsConfig::default();
+        config.generate_keypairs().unwrap();
+        
+        let mut handshake = PQHandshake::new(Arc::new(config), true);
+        handshake.negotiated_algorithm = Some(PQAlgorithm::Dilithium3);
+        
+        let signature = handshake.create_signature(b"wire").unwrap();
+        assert_eq!(signature.pq_signature.len(), pq_types::sizes::DILITHIUM3_SIGNATURE);
+        
+        // Truncate the Dilithium signature on the wire
+        let mut value = serde_json::to_value(&signature).unwrap();
+        value["pq_signature"].as_array_mut().unwrap().truncate(100);
+        assert!(serde_json::from_value::<PQSignature>(value).is_err());
+        
+        // Oversized Kyber public key
+        let share = handshake.offer_key_share(PQAlgorithm::Kyber768).unwrap();
+        let mut value = serde_json::to_value(&share).unwrap();
+        value["pq_public"].as_array_mut().unwrap().push(serde_json::json!(0));
+        assert!(serde_json::from_value::<PQKeyShare>(value).is_err());
+    }
+    
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_secrets_wiped_on_drop() {
+        use pq_types::canary;
+        
+        canary::assert_zeroize_on_drop::<HybridSharedSecret>();
+        canary::assert_zeroize_on_drop::<KyberSecretKeyBytes>();
+        canary::assert_zeroize_on_drop::<DilithiumSecretKeyBytes>();
+        canary::assert_zeroize_on_drop::<MlKemSecretKeyBytes>();
+        canary::assert_zeroize_on_drop::<MlDsaSecretKeyBytes>();
+        canary::assert_zeroize_on_drop::<Ed25519SigningKey>();
+        
+        let leaks = canary::watch(|canary| {
+            drop(HybridSharedSecret { secret: canary::fill(canary, 32) });
+            
+            let mut config = PQTlsConfig::default();
+            config.kyber_keypair = Some((
+                KyberPublicKeyBytes::from_vec(vec![0; KyberPublicKeyBytes::LEN]).unwrap(),
+                KyberSecretKeyBytes::from_vec(canary::fill(canary, KyberSecretKeyBytes::LEN)).unwrap(),
+            ));
+            let mut seed = Zeroizing::new([0u8; 32]);
+            seed[..canary.len()].copy_from_slice(canary);
+            config.ed25519_keypair = Some(Ed25519SigningKey::from_bytes(&seed));
+            
+            // The handshake keeps the configuration on the heap
+            let mut handshake = PQHandshake::new(Arc::new(config), true);
+            handshake.shared_secret = Some(combine_secrets(OffloadScheme::Kyber768, None, canary));
+            drop(handshake);
+        });
+        assert_eq!(leaks, 0);
     }
 }
@@ -0,0 +1,576 @@
+//! PQ Operation Offload - Tri-Compute Core Hook
+//! "Bear ye one another's burdens" - Galatians 6:2
+//!
+//! The handshake runs its KEM and signature operations through a
+//! `PqOffload`. `SoftwareOffload` performs them with the `pq_types::scheme`
+//! backend; `TriComputeOffload` sends them as commands to the Tri-Compute
+//! Core through the firmware's `api::tri_compute_execute`, reached over a
+//! `TriComputeLink` (the firmware's `ark_tri_compute_execute` export, with
+//! the `tri-compute` feature). `select` picks the path at runtime and only
+//! trusts the Core once `cross_check` shows both paths agree: each must
+//! decapsulate what the other encapsulated, accept what the other signed
+//! and reject a signature over another message.
+//!
+//! Commands are `[version, operation, scheme]` followed by the operation's
+//! fields, each a little-endian u32 length and its bytes; responses are a
+//! status byte followed by the result fields in the same form.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use pq_types::scheme::{self, Kem, SharedSecret, SignatureScheme};
+use pq_types::sizes;
+use pq_types::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, KyberCiphertextBytes,
+    KyberPublicKeyBytes, KyberSecretKeyBytes, MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes,
+    MlKemCiphertextBytes, MlKemPublicKeyBytes, MlKemSecretKeyBytes,
+};
+use zeroize::Zeroizing;
+
+use super::{PQAlgorithm, PQTlsError};
+
+/// Version of the Tri-Compute command format
+pub const COMMAND_VERSION: u8 = 1;
+
+/// Largest response accepted from the Tri-Compute Core
+pub const MAX_RESPONSE: usize = 8192;
+
+/// Message signed by `cross_check`
+const CROSS_CHECK_MESSAGE: &[u8] = b"ARK PQ offload cross-check v1";
+
+/// Response status: operation succeeded
+const STATUS_OK: u8 = 0;
+/// Response status: signature does not verify
+const STATUS_VERIFICATION_FAILED: u8 = 1;
+/// Response status: scheme not supported by the Core
+const STATUS_UNSUPPORTED: u8 = 2;
+/// Response status: operation failed
+const STATUS_FAILED: u8 = 3;
+
+/// Primitive an offloaded operation uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OffloadScheme {
+    /// Kyber768 KEM
+    Kyber768 = 1,
+    /// ML-KEM-768 KEM
+    MlKem768 = 2,
+    /// Dilithium3 signatures
+    Dilithium3 = 3,
+    /// ML-DSA-65 signatures
+    MlDsa65 = 4,
+}
+
+impl OffloadScheme {
+    /// KEM of a key exchange algorithm
+    pub fn kem(algorithm: PQAlgorithm) -> Result<Self, PQTlsError> {
+        match algorithm {
+            PQAlgorithm::HybridX25519Kyber768 | PQAlgorithm::Kyber768 => Ok(OffloadScheme::Kyber768),
+            PQAlgorithm::HybridX25519MlKem768 | PQAlgorithm::MlKem768 => Ok(OffloadScheme::MlKem768),
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+
+    /// Post-quantum half of a signature algorithm
+    pub fn signature(algorithm: PQAlgorithm) -> Result<Self, PQTlsError> {
+        match algorithm {
+            PQAlgorithm::HybridEd25519Dilithium3 | PQAlgorithm::Dilithium3 => Ok(OffloadScheme::Dilithium3),
+            PQAlgorithm::MlDsa65 => Ok(OffloadScheme::MlDsa65),
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(OffloadScheme::Kyber768),
+            2 => Some(OffloadScheme::MlKem768),
+            3 => Some(OffloadScheme::Dilithium3),
+            4 => Some(OffloadScheme::MlDsa65),
+            _ => None,
+        }
+    }
+}
+
+/// Operation of a Tri-Compute command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Operation {
+    /// Fields: public key; result: shared secret, ciphertext
+    Encapsulate = 1,
+    /// Fields: ciphertext, secret key; result: shared secret
+    Decapsulate = 2,
+    /// Fields: message, secret key; result: signature
+    Sign = 3,
+    /// Fields: message, signature, public key; no result
+    Verify = 4,
+}
+
+impl Operation {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Operation::Encapsulate),
+            2 => Some(Operation::Decapsulate),
+            3 => Some(Operation::Sign),
+            4 => Some(Operation::Verify),
+            _ => None,
+        }
+    }
+
+    fn fields(self) -> usize {
+        match self {
+            Operation::Encapsulate => 1,
+            Operation::Decapsulate | Operation::Sign => 2,
+            Operation::Verify => 3,
+        }
+    }
+}
+
+/// Path that performs the handshake's post-quantum operations
+///
+/// Keys, ciphertexts and signatures are raw bytes of the sizes
+/// `pq_types` defines for `scheme`.
+pub trait PqOffload: Send + Sync {
+    /// Name for logs and crash state
+    fn name(&self) -> &'static str;
+
+    /// Shared secret and the ciphertext carrying it to `public_key`'s owner
+    fn encapsulate(&self, scheme: OffloadScheme, public_key: &[u8]) -> Result<(SharedSecret, Vec<u8>), PQTlsError>;
+
+    /// Shared secret carried by `ciphertext`
+    fn decapsulate(&self, scheme: OffloadScheme, ciphertext: &[u8], secret_key: &[u8]) -> Result<SharedSecret, PQTlsError>;
+
+    /// Detached signature over `message`
+    fn sign(&self, scheme: OffloadScheme, message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>, PQTlsError>;
+
+    /// Check `signature` over `message`
+    fn verify(&self, scheme: OffloadScheme, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<(), PQTlsError>;
+}
+
+/// Operations run by the `pq_types::scheme` backend on the host CPU
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SoftwareOffload;
+
+impl PqOffload for SoftwareOffload {
+    fn name(&self) -> &'static str {
+        "software"
+    }
+
+    fn encapsulate(&self, scheme: OffloadScheme, public_key: &[u8]) -> Result<(SharedSecret, Vec<u8>), PQTlsError> {
+        match scheme {
+            OffloadScheme::Kyber768 => {
+                let (shared, ciphertext) = scheme::Kyber768::encapsulate(&KyberPublicKeyBytes::from_slice(public_key)?)?;
+                Ok((shared, ciphertext.as_bytes().to_vec()))
+            }
+            OffloadScheme::MlKem768 => {
+                let (shared, ciphertext) = scheme::MlKem768::encapsulate(&MlKemPublicKeyBytes::from_slice(public_key)?)?;
+                Ok((shared, ciphertext.as_bytes().to_vec()))
+            }
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+
+    fn decapsulate(&self, scheme: OffloadScheme, ciphertext: &[u8], secret_key: &[u8]) -> Result<SharedSecret, PQTlsError> {
+        match scheme {
+            OffloadScheme::Kyber768 => Ok(scheme::Kyber768::decapsulate(
+                &KyberCiphertextBytes::from_slice(ciphertext)?,
+                &KyberSecretKeyBytes::from_slice(secret_key)?,
+            )?),
+            OffloadScheme::MlKem768 => Ok(scheme::MlKem768::decapsulate(
+                &MlKemCiphertextBytes::from_slice(ciphertext)?,
+                &MlKemSecretKeyBytes::from_slice(secret_key)?,
+            )?),
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+
+    fn sign(&self, scheme: OffloadScheme, message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>, PQTlsError> {
+        match scheme {
+            OffloadScheme::Dilithium3 => {
+                let signature = scheme::Dilithium3::sign(message, &DilithiumSecretKeyBytes::from_slice(secret_key)?)?;
+                Ok(signature.as_bytes().to_vec())
+            }
+            OffloadScheme::MlDsa65 => {
+                let signature = scheme::MlDsa65::sign(message, &MlDsaSecretKeyBytes::from_slice(secret_key)?)?;
+                Ok(signature.as_bytes().to_vec())
+            }
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+
+    fn verify(&self, scheme: OffloadScheme, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<(), PQTlsError> {
+        match scheme {
+            OffloadScheme::Dilithium3 => Ok(scheme::Dilithium3::verify(
+                message,
+                &DilithiumSignatureBytes::from_slice(signature)?,
+                &DilithiumPublicKeyBytes::from_slice(public_key)?,
+            )?),
+            OffloadScheme::MlDsa65 => Ok(scheme::MlDsa65::verify(
+                message,
+                &MlDsaSignatureBytes::from_slice(signature)?,
+                &MlDsaPublicKeyBytes::from_slice(public_key)?,
+            )?),
+            _ => Err(PQTlsError::UnsupportedAlgorithm),
+        }
+    }
+}
+
+/// Transport of commands to the Tri-Compute Core
+pub trait TriComputeLink: Send + Sync {
+    /// Run `command` and return the Core's response
+    fn execute(&self, command: &[u8]) -> Result<Zeroizing<Vec<u8>>, PQTlsError>;
+}
+
+#[cfg(feature = "tri-compute")]
+extern "C" {
+    /// `api::tri_compute_execute` as exported by the ARK firmware
+    fn ark_tri_compute_execute(
+        command: *const u8,
+        command_len: usize,
+        response: *mut u8,
+        response_capacity: usize,
+        response_len: *mut usize,
+    ) -> i32;
+}
+
+/// Link to the Core through the firmware this binary is linked with
+#[cfg(feature = "tri-compute")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirmwareLink;
+
+#[cfg(feature = "tri-compute")]
+impl TriComputeLink for FirmwareLink {
+    fn execute(&self, command: &[u8]) -> Result<Zeroizing<Vec<u8>>, PQTlsError> {
+        let mut response = Zeroizing::new(vec![0u8; MAX_RESPONSE]);
+        let mut response_len = 0usize;
+        // SAFETY: both buffers are valid for their stated lengths for the
+        // whole call, and the firmware writes at most `response_capacity` bytes
+        let code = unsafe {
+            ark_tri_compute_execute(command.as_ptr(), command.len(), response.as_mut_ptr(), response.len(), &mut response_len)
+        };
+        match code {
+            0 if response_len <= response.len() => {
+                response.truncate(response_len);
+                Ok(response)
+            }
+            0 => Err(PQTlsError::CryptoError("Tri-Compute response overran its buffer".into())),
+            code => Err(PQTlsError::CryptoError(format!("Tri-Compute Core failed with code {}", code))),
+        }
+    }
+}
+
+/// Operations run on the Tri-Compute Core
+#[derive(Clone)]
+pub struct TriComputeOffload {
+    link: Arc<dyn TriComputeLink>,
+}
+
+impl TriComputeOffload {
+    pub fn new(link: Arc<dyn TriComputeLink>) -> Self {
+        Self { link }
+    }
+
+    /// Send one command and return the response's result fields
+    fn call(&self, operation: Operation, scheme: OffloadScheme, fields: &[&[u8]], results: usize) -> Result<Zeroizing<Vec<Vec<u8>>>, PQTlsError> {
+        let mut command = Zeroizing::new(vec![COMMAND_VERSION, operation as u8, scheme as u8]);
+        put_fields(&mut command, fields);
+        let response = self.link.execute(&command)?;
+        match response.split_first() {
+            Some((&STATUS_OK, rest)) => {
+                let fields = take_fields(rest, results)?;
+                Ok(Zeroizing::new(fields.into_iter().map(<[u8]>::to_vec).collect()))
+            }
+            Some((&STATUS_VERIFICATION_FAILED, _)) => Err(PQTlsError::SignatureVerificationFailed),
+            Some((&STATUS_UNSUPPORTED, _)) => Err(PQTlsError::UnsupportedAlgorithm),
+            Some((status, _)) => Err(PQTlsError::CryptoError(format!("Tri-Compute {:?} failed with status {}", operation, status))),
+            None => Err(PQTlsError::CryptoError("Empty Tri-Compute response".into())),
+        }
+    }
+}
+
+impl PqOffload for TriComputeOffload {
+    fn name(&self) -> &'static str {
+        "tri-compute"
+    }
+
+    fn encapsulate(&self, scheme: OffloadScheme, public_key: &[u8]) -> Result<(SharedSecret, Vec<u8>), PQTlsError> {
+        let results = self.call(Operation::Encapsulate, scheme, &[public_key], 2)?;
+        Ok((shared_secret(&results[0])?, results[1].clone()))
+    }
+
+    fn decapsulate(&self, scheme: OffloadScheme, ciphertext: &[u8], secret_key: &[u8]) -> Result<SharedSecret, PQTlsError> {
+        let results = self.call(Operation::Decapsulate, scheme, &[ciphertext, secret_key], 1)?;
+        shared_secret(&results[0])
+    }
+
+    fn sign(&self, scheme: OffloadScheme, message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>, PQTlsError> {
+        let results = self.call(Operation::Sign, scheme, &[message, secret_key], 1)?;
+        Ok(results[0].clone())
+    }
+
+    fn verify(&self, scheme: OffloadScheme, message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<(), PQTlsError> {
+        self.call(Operation::Verify, scheme, &[message, signature, public_key], 0)?;
+        Ok(())
+    }
+}
+
+/// Execute a Tri-Compute command with `offload` and encode its response
+///
+/// This is the Core's side of the command format; tests use it with
+/// `SoftwareOffload` to stand in for the hardware.
+pub fn serve(offload: &dyn PqOffload, command: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut response = Zeroizing::new(Vec::new());
+    let (operation, scheme, fields) = match parse_command(command) {
+        Some(parsed) => parsed,
+        None => {
+            response.push(STATUS_FAILED);
+            return response;
+        }
+    };
+    let result = match operation {
+        Operation::Encapsulate => offload
+            .encapsulate(scheme, fields[0])
+            .map(|(shared, ciphertext)| put_fields(&mut response, &[shared.as_slice(), &ciphertext])),
+        Operation::Decapsulate => offload
+            .decapsulate(scheme, fields[0], fields[1])
+            .map(|shared| put_fields(&mut response, &[shared.as_slice()])),
+        Operation::Sign => offload
+            .sign(scheme, fields[0], fields[1])
+            .map(|signature| put_fields(&mut response, &[&signature])),
+        Operation::Verify => offload.verify(scheme, fields[0], fields[1], fields[2]),
+    };
+    let status = match result {
+        Ok(()) => STATUS_OK,
+        Err(PQTlsError::SignatureVerificationFailed) => STATUS_VERIFICATION_FAILED,
+        Err(PQTlsError::UnsupportedAlgorithm) => STATUS_UNSUPPORTED,
+        Err(_) => STATUS_FAILED,
+    };
+    if status != STATUS_OK {
+        response.clear();
+    }
+    response.insert(0, status);
+    response
+}
+
+fn parse_command(command: &[u8]) -> Option<(Operation, OffloadScheme, Vec<&[u8]>)> {
+    match command {
+        [COMMAND_VERSION, operation, scheme, rest @ ..] => {
+            let operation = Operation::from_byte(*operation)?;
+            let scheme = OffloadScheme::from_byte(*scheme)?;
+            let fields = take_fields(rest, operation.fields()).ok()?;
+            Some((operation, scheme, fields))
+        }
+        _ => None,
+    }
+}
+
+fn put_fields(buffer: &mut Vec<u8>, fields: &[&[u8]]) {
+    for field in fields {
+        buffer.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(field);
+    }
+}
+
+/// Exactly `count` length-prefixed fields filling `bytes`
+fn take_fields(mut bytes: &[u8], count: usize) -> Result<Vec<&[u8]>, PQTlsError> {
+    let malformed = || PQTlsError::ProtocolError("Malformed Tri-Compute message".into());
+    let mut fields = Vec::with_capacity(count);
+    for _ in 0..count {
+        if bytes.len() < 4 {
+            return Err(malformed());
+        }
+        let (length, rest) = bytes.split_at(4);
+        let length = u32::from_le_bytes(length.try_into().map_err(|_| malformed())?) as usize;
+        if rest.len() < length {
+            return Err(malformed());
+        }
+        let (field, rest) = rest.split_at(length);
+        fields.push(field);
+        bytes = rest;
+    }
+    if !bytes.is_empty() {
+        return Err(malformed());
+    }
+    Ok(fields)
+}
+
+fn shared_secret(bytes: &[u8]) -> Result<SharedSecret, PQTlsError> {
+    if bytes.len() != sizes::KEM_SHARED_SECRET {
+        return Err(PQTlsError::CryptoError("Tri-Compute shared secret has the wrong length".into()));
+    }
+    let mut secret = Zeroizing::new([0u8; sizes::KEM_SHARED_SECRET]);
+    secret.copy_from_slice(bytes);
+    Ok(secret)
+}
+
+/// Check that `a` and `b` agree on every scheme, with fresh software keys
+pub fn cross_check(a: &dyn PqOffload, b: &dyn PqOffload) -> Result<(), PQTlsError> {
+    let (kyber_public, kyber_secret) = scheme::Kyber768::keypair()?;
+    check_kem(OffloadScheme::Kyber768, kyber_public.as_bytes(), kyber_secret.expose_secret(), a, b)?;
+    let (ml_kem_public, ml_kem_secret) = scheme::MlKem768::keypair()?;
+    check_kem(OffloadScheme::MlKem768, ml_kem_public.as_bytes(), ml_kem_secret.expose_secret(), a, b)?;
+    let (dilithium_public, dilithium_secret) = scheme::Dilithium3::keypair()?;
+    check_signature(OffloadScheme::Dilithium3, dilithium_public.as_bytes(), dilithium_secret.expose_secret(), a, b)?;
+    let (ml_dsa_public, ml_dsa_secret) = scheme::MlDsa65::keypair()?;
+    check_signature(OffloadScheme::MlDsa65, ml_dsa_public.as_bytes(), ml_dsa_secret.expose_secret(), a, b)
+}
+
+fn disagreement(scheme: OffloadScheme, first: &dyn PqOffload, second: &dyn PqOffload, what: &str) -> PQTlsError {
+    PQTlsError::CryptoError(format!("{:?} cross-check failed: {} {} from {}", scheme, second.name(), what, first.name()))
+}
+
+fn check_kem(scheme: OffloadScheme, public_key: &[u8], secret_key: &[u8], a: &dyn PqOffload, b: &dyn PqOffload) -> Result<(), PQTlsError> {
+    for (sealer, opener) in [(a, b), (b, a)] {
+        let (sealed, ciphertext) = sealer.encapsulate(scheme, public_key)?;
+        let opened = opener
+            .decapsulate(scheme, &ciphertext, secret_key)
+            .map_err(|_| disagreement(scheme, sealer, opener, "could not decapsulate a ciphertext"))?;
+        if *opened != *sealed {
+            return Err(disagreement(scheme, sealer, opener, "derived another secret for a ciphertext"));
+        }
+    }
+    Ok(())
+}
+
+fn check_signature(scheme: OffloadScheme, public_key: &[u8], secret_key: &[u8], a: &dyn PqOffload, b: &dyn PqOffload) -> Result<(), PQTlsError> {
+    for (signer, verifier) in [(a, b), (b, a)] {
+        let signature = signer.sign(scheme, CROSS_CHECK_MESSAGE, secret_key)?;
+        verifier
+            .verify(scheme, CROSS_CHECK_MESSAGE, &signature, public_key)
+            .map_err(|_| disagreement(scheme, signer, verifier, "rejected a signature"))?;
+        if verifier.verify(scheme, b"ARK PQ offload cross-check v0", &signature, public_key).is_ok() {
+            return Err(disagreement(scheme, signer, verifier, "accepted a signature over another message"));
+        }
+    }
+    Ok(())
+}
+
+/// Which path runs the handshake's post-quantum operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OffloadMode {
+    /// Always the host CPU
+    Software,
+    /// The Tri-Compute Core; startup fails if it is absent or disagrees with software
+    TriCompute,
+    /// The Tri-Compute Core when present and in agreement with software, else the host CPU
+    #[default]
+    Auto,
+}
+
+impl FromStr for OffloadMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "software" => Ok(OffloadMode::Software),
+            "tri-compute" => Ok(OffloadMode::TriCompute),
+            "auto" => Ok(OffloadMode::Auto),
+            other => Err(format!("unknown offload mode '{}' (expected software, tri-compute or auto)", other)),
+        }
+    }
+}
+
+impl fmt::Display for OffloadMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OffloadMode::Software => "software",
+            OffloadMode::TriCompute => "tri-compute",
+            OffloadMode::Auto => "auto",
+        })
+    }
+}
+
+/// Link to the firmware's Tri-Compute Core, if this build has one
+pub fn firmware_link() -> Option<Arc<dyn TriComputeLink>> {
+    #[cfg(feature = "tri-compute")]
+    {
+        Some(Arc::new(FirmwareLink))
+    }
+    #[cfg(not(feature = "tri-compute"))]
+    {
+        None
+    }
+}
+
+/// Offload for `mode` on this build's firmware link
+pub fn select(mode: OffloadMode) -> Result<Arc<dyn PqOffload>, PQTlsError> {
+    select_with(mode, firmware_link())
+}
+
+/// Offload for `mode`, reaching the Core over `link`
+///
+/// The Core is used only after it passes `cross_check` against software.
+pub fn select_with(mode: OffloadMode, link: Option<Arc<dyn TriComputeLink>>) -> Result<Arc<dyn PqOffload>, PQTlsError> {
+    if mode == OffloadMode::Software {
+        return Ok(Arc::new(SoftwareOffload));
+    }
+    let checked = match link {
+        Some(link) => {
+            let core = TriComputeOffload::new(link);
+            cross_check(&SoftwareOffload, &core).map(|()| core)
+        }
+        None => Err(PQTlsError::CryptoError("this build has no Tri-Compute link".into())),
+    };
+    match (checked, mode) {
+        (Ok(core), _) => Ok(Arc::new(core)),
+        (Err(e), OffloadMode::TriCompute) => Err(e),
+        (Err(e), _) => {
+            tracing::warn!("Tri-Compute offload unavailable, using software: {}", e);
+            Ok(Arc::new(SoftwareOffload))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Core stand-in that answers commands in software
+    struct EmulatedCore;
+
+    impl TriComputeLink for EmulatedCore {
+        fn execute(&self, command: &[u8]) -> Result<Zeroizing<Vec<u8>>, PQTlsError> {
+            Ok(serve(&SoftwareOffload, command))
+        }
+    }
+
+    /// Core that corrupts the shared secrets it returns
+    struct FaultyCore;
+
+    impl TriComputeLink for FaultyCore {
+        fn execute(&self, command: &[u8]) -> Result<Zeroizing<Vec<u8>>, PQTlsError> {
+            let mut response = serve(&SoftwareOffload, command);
+            if command[1] == Operation::Decapsulate as u8 {
+                let last = response.len() - 1;
+                response[last] ^= 1;
+            }
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn test_tri_compute_agrees_with_software() {
+        let core = TriComputeOffload::new(Arc::new(EmulatedCore));
+        cross_check(&SoftwareOffload, &core).unwrap();
+        assert_eq!(select_with(OffloadMode::Auto, Some(Arc::new(EmulatedCore))).unwrap().name(), "tri-compute");
+
+        let faulty = TriComputeOffload::new(Arc::new(FaultyCore));
+        let error = cross_check(&SoftwareOffload, &faulty).unwrap_err().to_string();
+        assert!(error.contains("Kyber768 cross-check failed: tri-compute derived another secret"), "{}", error);
+        assert_eq!(select_with(OffloadMode::Auto, Some(Arc::new(FaultyCore))).unwrap().name(), "software");
+        assert!(select_with(OffloadMode::TriCompute, Some(Arc::new(FaultyCore))).is_err());
+        assert!(select_with(OffloadMode::TriCompute, None).is_err());
+    }
+
+    #[test]
+    fn test_malformed_commands_are_refused() {
+        assert_eq!(*serve(&SoftwareOffload, &[COMMAND_VERSION, Operation::Sign as u8, 9]), [STATUS_FAILED]);
+        let mut command = vec![COMMAND_VERSION, Operation::Encapsulate as u8, OffloadScheme::Kyber768 as u8];
+        put_fields(&mut command, &[b"key", b"extra field"]);
+        assert_eq!(*serve(&SoftwareOffload, &command), [STATUS_FAILED]);
+
+        let mut command = vec![COMMAND_VERSION, Operation::Encapsulate as u8, OffloadScheme::Dilithium3 as u8];
+        put_fields(&mut command, &[&[0u8; 1184]]);
+        assert_eq!(*serve(&SoftwareOffload, &command), [STATUS_UNSUPPORTED]);
+    }
+}
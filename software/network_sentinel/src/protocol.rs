@@ -0,0 +1,487 @@
+//! Sentinel Wire Protocol - Version and Capability Negotiation
+//! "Can two walk together, except they be agreed?" - Amos 3:3
+//!
+//! Before the PQ-TLS handshake the server sends a `CapabilityOffer` listing
+//! the protocol versions, algorithms and extensions it supports. The client
+//! answers with a single `CapabilitySelection`, then names the service it
+//! wants in a `ServiceRequest`, which the server answers with an
+//! `AccessResponse`. Every message is bincode encoded and framed with a
+//! big-endian `u32` length prefix. With `SecureRecords` agreed, the key
+//! exchange of `channel` runs between the selection and the
+//! `ServiceRequest`, and every later message travels in sealed records.
+
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Serialize, Deserialize, de::DeserializeOwned};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::SentinelError;
+use crate::pqc_tls::PQAlgorithm;
+
+/// Protocol version 1 (initial wire format)
+pub const PROTOCOL_VERSION_1: u16 = 1;
+
+/// Protocol versions this build understands, oldest first
+pub const SUPPORTED_VERSIONS: &[u16] = &[PROTOCOL_VERSION_1];
+
+/// Largest negotiation message accepted from a peer
+pub const MAX_NEGOTIATION_MESSAGE: usize = 64 * 1024;
+
+/// Most entries accepted in any list of a negotiation message
+pub const MAX_NEGOTIATION_ENTRIES: usize = 32;
+
+/// Largest extension parameter block
+pub const MAX_EXTENSION_DATA: usize = 4096;
+
+/// Longest peer identifier, service name or denial reason
+pub const MAX_NAME_LENGTH: usize = 256;
+
+/// Decoding limits for negotiation messages
+const NEGOTIATION_LIMITS: DecodeLimits = DecodeLimits::new(MAX_NEGOTIATION_MESSAGE, 8);
+
+/// Extensions known to this build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtensionKind {
+    /// Session ticket resumption
+    SessionTickets,
+    /// Per-connection payload compression
+    Compression,
+    /// Relaying traffic through another sentinel
+    Relay,
+    /// Keep-alive record layer after the access grant
+    KeepAlive,
+    /// Key exchange and encrypted records after the selection (see `channel`)
+    SecureRecords,
+}
+
+impl ExtensionKind {
+    /// Wire identifier of the extension
+    pub fn id(self) -> u16 {
+        match self {
+            ExtensionKind::SessionTickets => 1,
+            ExtensionKind::Compression => 2,
+            ExtensionKind::Relay => 3,
+            ExtensionKind::KeepAlive => 4,
+            ExtensionKind::SecureRecords => 5,
+        }
+    }
+
+    /// Look up a known extension by its wire identifier
+    pub fn from_id(id: u16) -> Option<Self> {
+        match id {
+            1 => Some(ExtensionKind::SessionTickets),
+            2 => Some(ExtensionKind::Compression),
+            3 => Some(ExtensionKind::Relay),
+            4 => Some(ExtensionKind::KeepAlive),
+            5 => Some(ExtensionKind::SecureRecords),
+            _ => None,
+        }
+    }
+}
+
+/// Extension as carried on the wire
+///
+/// Identifiers are kept numeric so that extensions introduced by newer peers
+/// still decode; whether they may be ignored is decided by `mandatory`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Extension {
+    /// Extension identifier
+    pub id: u16,
+    /// Peer must understand this extension or abort the connection
+    pub mandatory: bool,
+    /// Extension-specific parameters
+    pub data: Vec<u8>,
+}
+
+impl Extension {
+    /// Create an extension entry without parameters
+    pub fn new(kind: ExtensionKind, mandatory: bool) -> Self {
+        Self {
+            id: kind.id(),
+            mandatory,
+            data: Vec::new(),
+        }
+    }
+
+    /// Known extension kind, if any
+    pub fn kind(&self) -> Option<ExtensionKind> {
+        ExtensionKind::from_id(self.id)
+    }
+}
+
+/// Server -> client: everything the server is willing to speak
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityOffer {
+    /// Supported protocol versions
+    pub versions: Vec<u16>,
+    /// Supported algorithms in preference order
+    pub algorithms: Vec<PQAlgorithm>,
+    /// Offered extensions
+    pub extensions: Vec<Extension>,
+}
+
+/// Client -> server: the parameters chosen from an offer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilitySelection {
+    /// Chosen protocol version
+    pub version: u16,
+    /// Chosen algorithm
+    pub algorithm: PQAlgorithm,
+    /// Extensions the client wants enabled
+    pub extensions: Vec<Extension>,
+}
+
+/// Outcome of a successful negotiation
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegotiatedSession {
+    /// Protocol version in use
+    pub version: u16,
+    /// Algorithm in use
+    pub algorithm: PQAlgorithm,
+    /// Enabled extensions
+    pub extensions: Vec<ExtensionKind>,
+}
+
+/// Client -> server: the service the peer wants to use
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceRequest {
+    /// Peer identifier
+    pub peer_id: String,
+    /// Requested service name
+    pub service: String,
+}
+
+/// Server -> client: authorization outcome for a `ServiceRequest`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessResponse {
+    /// Whether the connection may proceed
+    pub granted: bool,
+    /// Reason given by the policy
+    pub reason: String,
+}
+
+fn validate_extensions(extensions: &[Extension]) -> Result<(), String> {
+    decode::check_count("extensions", extensions.len(), MAX_NEGOTIATION_ENTRIES)?;
+    for extension in extensions {
+        decode::check_count("extension data", extension.data.len(), MAX_EXTENSION_DATA)?;
+    }
+    Ok(())
+}
+
+impl Validate for CapabilityOffer {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("versions", self.versions.len(), MAX_NEGOTIATION_ENTRIES)?;
+        decode::check_count("algorithms", self.algorithms.len(), MAX_NEGOTIATION_ENTRIES)?;
+        validate_extensions(&self.extensions)
+    }
+}
+
+impl Validate for CapabilitySelection {
+    fn validate(&self) -> Result<(), String> {
+        validate_extensions(&self.extensions)
+    }
+}
+
+impl Validate for ServiceRequest {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("peer_id", &self.peer_id, MAX_NAME_LENGTH)?;
+        decode::check_identifier("service", &self.service, MAX_NAME_LENGTH)
+    }
+}
+
+impl Validate for AccessResponse {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_len("reason", &self.reason, MAX_NAME_LENGTH)
+    }
+}
+
+/// Local negotiation capabilities
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    /// Protocol versions we speak
+    pub versions: Vec<u16>,
+    /// Algorithms we accept in preference order
+    pub algorithms: Vec<PQAlgorithm>,
+    /// Extensions we support; `mandatory` ones are required from the peer
+    pub extensions: Vec<Extension>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            versions: SUPPORTED_VERSIONS.to_vec(),
+            algorithms: vec![
+                PQAlgorithm::HybridX25519Kyber768,
+                PQAlgorithm::HybridEd25519Dilithium3,
+            ],
+            extensions: Vec::new(),
+        }
+    }
+}
+
+impl Capabilities {
+    /// Build the offer sent by the server
+    pub fn offer(&self) -> CapabilityOffer {
+        CapabilityOffer {
+            versions: self.versions.clone(),
+            algorithms: self.algorithms.clone(),
+            extensions: self.extensions.clone(),
+        }
+    }
+
+    /// Client side: choose parameters from the server's offer
+    pub fn select(&self, offer: &CapabilityOffer) -> Result<CapabilitySelection, SentinelError> {
+        let version = offer.versions.iter()
+            .filter(|v| self.versions.contains(v))
+            .max()
+            .copied()
+            .ok_or_else(|| SentinelError::NegotiationError(
+                format!("No common protocol version (peer offers {:?})", offer.versions)
+            ))?;
+
+        // Key exchanges win over the peer's order of preference, hybrid ones
+        // first so a post-quantum-only exchange never displaces X25519,
+        // and the standardized one among those
+        let mut common: Vec<PQAlgorithm> = offer.algorithms.iter()
+            .filter(|a| self.algorithms.contains(a))
+            .copied()
+            .collect();
+        common.sort_by_key(|a| (!a.is_key_exchange(), !a.is_hybrid(), !a.is_standardized()));
+        let algorithm = common.first()
+            .copied()
+            .ok_or_else(|| SentinelError::NegotiationError("No common algorithm".into()))?;
+
+        let mut extensions = Vec::new();
+        for ext in &offer.extensions {
+            if let Some(kind) = self.check_peer_extension(ext)? {
+                extensions.push(Extension { id: kind.id(), mandatory: ext.mandatory, data: ext.data.clone() });
+            }
+        }
+        self.check_required_present(extensions.iter().filter_map(Extension::kind))?;
+
+        Ok(CapabilitySelection { version, algorithm, extensions })
+    }
+
+    /// Server side: validate the client's selection against what we offered
+    pub fn accept(&self, selection: &CapabilitySelection) -> Result<NegotiatedSession, SentinelError> {
+        if !self.versions.contains(&selection.version) {
+            return Err(SentinelError::NegotiationError(
+                format!("Unsupported protocol version {}", selection.version)
+            ));
+        }
+        if !self.algorithms.contains(&selection.algorithm) {
+            return Err(SentinelError::NegotiationError(
+                format!("Algorithm {:?} was not offered", selection.algorithm)
+            ));
+        }
+
+        let mut extensions = Vec::new();
+        for ext in &selection.extensions {
+            if let Some(kind) = self.check_peer_extension(ext)? {
+                if !extensions.contains(&kind) {
+                    extensions.push(kind);
+                }
+            }
+        }
+        self.check_required_present(extensions.iter().copied())?;
+
+        Ok(NegotiatedSession {
+            version: selection.version,
+            algorithm: selection.algorithm,
+            extensions,
+        })
+    }
+
+    /// Decide whether a peer extension is enabled, ignored or fatal
+    fn check_peer_extension(&self, ext: &Extension) -> Result<Option<ExtensionKind>, SentinelError> {
+        let supported = ext.kind().filter(|kind| {
+            self.extensions.iter().any(|local| local.id == kind.id())
+        });
+
+        match supported {
+            Some(kind) => Ok(Some(kind)),
+            None if ext.mandatory => Err(SentinelError::NegotiationError(
+                format!("Unsupported mandatory extension {}", ext.id)
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Ensure every extension we mark mandatory was agreed by the peer
+    fn check_required_present(&self, agreed: impl Iterator<Item = ExtensionKind>) -> Result<(), SentinelError> {
+        let agreed: Vec<ExtensionKind> = agreed.collect();
+        for required in self.extensions.iter().filter(|e| e.mandatory) {
+            if !agreed.iter().any(|kind| kind.id() == required.id) {
+                return Err(SentinelError::NegotiationError(
+                    format!("Peer does not support required extension {}", required.id)
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Encode a negotiation message body (without length prefix)
+pub fn encode_message<T: Serialize>(message: &T) -> Result<Vec<u8>, SentinelError> {
+    bincode::serialize(message)
+        .map_err(|e| SentinelError::ProtocolError(e.to_string()))
+}
+
+/// Decode and validate a negotiation message body (without length prefix)
+pub fn decode_message<T: DeserializeOwned + Validate>(bytes: &[u8]) -> Result<T, SentinelError> {
+    decode::bincode_validated(bytes, &NEGOTIATION_LIMITS)
+        .map_err(|e| SentinelError::ProtocolError(e.to_string()))
+}
+
+/// Write a length-prefixed negotiation message
+pub async fn write_message<W, T>(stream: &mut W, message: &T) -> Result<(), SentinelError>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let bytes = encode_message(message)?;
+    stream.write_u32(bytes.len() as u32).await?;
+    stream.write_all(&bytes).await?;
+    Ok(())
+}
+
+/// Read a length-prefixed negotiation message
+pub async fn read_message<R, T>(stream: &mut R) -> Result<T, SentinelError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned + Validate,
+{
+    let len = stream.read_u32().await? as usize;
+    if len > MAX_NEGOTIATION_MESSAGE {
+        return Err(SentinelError::ProtocolError(
+            format!("Negotiation message too large: {} bytes", len)
+        ));
+    }
+
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    decode_message(&buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Default v1 offer: versions [1], both hybrid algorithms, no extensions
+    const GOLDEN_V1_OFFER: &[u8] = &[
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // versions.len()
+        0x01, 0x00,                                     // 1
+        0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // algorithms.len()
+        0x00, 0x00, 0x00, 0x00,                         // HybridX25519Kyber768
+        0x01, 0x00, 0x00, 0x00,                         // HybridEd25519Dilithium3
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // extensions.len()
+    ];
+
+    /// v1 selection: version 1, HybridX25519Kyber768, optional compression
+    const GOLDEN_V1_SELECTION: &[u8] = &[
+        0x01, 0x00,                                     // version
+        0x00, 0x00, 0x00, 0x00,                         // HybridX25519Kyber768
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // extensions.len()
+        0x02, 0x00,                                     // Compression
+        0x00,                                           // mandatory = false
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // data.len()
+    ];
+
+    fn with_extensions(extensions: Vec<Extension>) -> Capabilities {
+        Capabilities { extensions, ..Default::default() }
+    }
+
+    #[test]
+    fn test_v1_wire_format_is_pinned() {
+        let offer = Capabilities::default().offer();
+        assert_eq!(encode_message(&offer).unwrap(), GOLDEN_V1_OFFER);
+        assert_eq!(decode_message::<CapabilityOffer>(GOLDEN_V1_OFFER).unwrap(), offer);
+
+        let selection = CapabilitySelection {
+            version: PROTOCOL_VERSION_1,
+            algorithm: PQAlgorithm::HybridX25519Kyber768,
+            extensions: vec![Extension::new(ExtensionKind::Compression, false)],
+        };
+        assert_eq!(encode_message(&selection).unwrap(), GOLDEN_V1_SELECTION);
+        assert_eq!(decode_message::<CapabilitySelection>(GOLDEN_V1_SELECTION).unwrap(), selection);
+    }
+
+    #[test]
+    fn test_negotiation_round_trip() {
+        let server = with_extensions(vec![
+            Extension::new(ExtensionKind::SessionTickets, false),
+            Extension::new(ExtensionKind::Relay, false),
+        ]);
+        let client = with_extensions(vec![Extension::new(ExtensionKind::SessionTickets, false)]);
+
+        let selection = client.select(&server.offer()).unwrap();
+        assert_eq!(selection.version, PROTOCOL_VERSION_1);
+        assert_eq!(selection.algorithm, PQAlgorithm::HybridX25519Kyber768);
+
+        let session = server.accept(&selection).unwrap();
+        assert_eq!(session.extensions, vec![ExtensionKind::SessionTickets]);
+    }
+
+    #[test]
+    fn test_hybrid_algorithm_preferred() {
+        let legacy = Capabilities::default();
+        let mut standardized = Capabilities::default();
+        standardized.algorithms.insert(0, PQAlgorithm::MlDsa65);
+        standardized.algorithms.insert(0, PQAlgorithm::MlKem768);
+
+        // A post-quantum-only exchange does not displace the hybrid one
+        let selection = standardized.select(&standardized.offer()).unwrap();
+        assert_eq!(selection.algorithm, PQAlgorithm::HybridX25519Kyber768);
+
+        // The standardized hybrid wins, even when the server lists it last
+        standardized.algorithms.push(PQAlgorithm::HybridX25519MlKem768);
+        let selection = standardized.select(&standardized.offer()).unwrap();
+        assert_eq!(selection.algorithm, PQAlgorithm::HybridX25519MlKem768);
+        assert!(standardized.accept(&selection).is_ok());
+
+        // Peers without it still agree on a round-3 hybrid
+        assert_eq!(legacy.select(&standardized.offer()).unwrap().algorithm, PQAlgorithm::HybridX25519Kyber768);
+        assert_eq!(standardized.select(&legacy.offer()).unwrap().algorithm, PQAlgorithm::HybridX25519Kyber768);
+    }
+
+    #[test]
+    fn test_unknown_mandatory_extension_rejected() {
+        let client = Capabilities::default();
+        let mut offer = Capabilities::default().offer();
+
+        // Unknown optional extensions are ignored
+        offer.extensions.push(Extension { id: 0x7777, mandatory: false, data: vec![1, 2, 3] });
+        assert!(client.select(&offer).unwrap().extensions.is_empty());
+
+        // Unknown mandatory extensions abort negotiation
+        offer.extensions.push(Extension { id: 0x7778, mandatory: true, data: Vec::new() });
+        assert!(matches!(client.select(&offer), Err(SentinelError::NegotiationError(_))));
+    }
+
+    #[test]
+    fn test_version_and_requirement_mismatch() {
+        let client = Capabilities::default();
+        let mut offer = Capabilities::default().offer();
+        offer.versions = vec![2];
+        assert!(client.select(&offer).is_err());
+
+        // Server requiring relay rejects a client that did not select it
+        let server = with_extensions(vec![Extension::new(ExtensionKind::Relay, true)]);
+        let selection = CapabilitySelection {
+            version: PROTOCOL_VERSION_1,
+            algorithm: PQAlgorithm::HybridX25519Kyber768,
+            extensions: Vec::new(),
+        };
+        assert!(server.accept(&selection).is_err());
+    }
+
+    #[test]
+    fn test_oversized_fields_rejected_on_decode() {
+        let mut offer = Capabilities::default().offer();
+        offer.extensions = vec![Extension::new(ExtensionKind::SessionTickets, false); MAX_NEGOTIATION_ENTRIES + 1];
+        let encoded = encode_message(&offer).unwrap();
+        assert!(matches!(decode_message::<CapabilityOffer>(&encoded), Err(SentinelError::ProtocolError(_))));
+
+        let request = ServiceRequest { peer_id: "../../etc".into(), service: "echo".into() };
+        assert!(decode_message::<ServiceRequest>(&encode_message(&request).unwrap()).is_err());
+    }
+}
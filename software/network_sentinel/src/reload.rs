@@ -0,0 +1,272 @@
+//! Sentinel Configuration Reload - Changing Settings Without Dropping Connections
+//! "Behold, I make all things new" - Revelation 21:5
+//!
+//! A running sentinel takes new `ServerSettings` through a `ReloadHandle`,
+//! either directly, on SIGHUP, or whenever the settings file changes on
+//! disk. Connections already open are never touched: each one keeps the
+//! configuration it was accepted under. Limits, quotas, the static ACL and
+//! new listeners take effect for the next accepted connection. A changed
+//! algorithm policy is deferred: it only reaches connections that run a new
+//! handshake, so sessions negotiated earlier keep their algorithm until
+//! they reconnect. Changes that need a restart (removing a listener) are
+//! rejected. The `ReloadReport` names every field that was applied,
+//! deferred or rejected. Settings files are verified against the
+//! authorized configuration signers like every other configuration file.
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+
+use crate::{ListenerConfig, PQAlgorithm, SentinelConfig, SentinelError, ShapingConfig, StaticAcl};
+
+/// How often a watched settings file is checked for changes
+pub const SETTINGS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Component the sentinel's configuration signatures are bound to
+pub const CONFIG_COMPONENT: &str = "network_sentinel";
+
+/// Reloads queued while the sentinel applies an earlier one
+pub(crate) const RELOAD_QUEUE: usize = 4;
+
+/// Server settings file; absent fields are left unchanged on reload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerSettings {
+    /// Listeners to run; listeners not yet bound are added
+    pub listeners: Vec<ListenerConfig>,
+    /// Maximum concurrent connections
+    pub max_connections: Option<usize>,
+    /// Connection timeout in seconds
+    pub connection_timeout: Option<u64>,
+    /// Static ACL of the authorizer
+    pub acl: Option<StaticAcl>,
+    /// Bandwidth quotas
+    pub shaping: Option<ShapingConfig>,
+    /// Algorithms offered in the negotiation, in preference order
+    pub algorithms: Option<Vec<PQAlgorithm>>,
+}
+
+impl Validate for ServerSettings {
+    fn validate(&self) -> Result<(), String> {
+        for listener in &self.listeners {
+            listener.validate().map_err(|e| e.to_string())?;
+        }
+        if self.max_connections == Some(0) {
+            return Err("max_connections must be positive".into());
+        }
+        if self.algorithms.as_ref().is_some_and(Vec::is_empty) {
+            return Err("algorithms must not be empty".into());
+        }
+        if let Some(acl) = &self.acl {
+            acl.validate()?;
+        }
+        if let Some(shaping) = &self.shaping {
+            shaping.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl ServerSettings {
+    /// Load and validate a settings file
+    ///
+    /// The file is verified against the authorized signers named by
+    /// `ARK_CONFIG_SIGNERS` (see `ark_config`). One that fails
+    /// verification under the safe-defaults policy loads as empty settings,
+    /// which change nothing.
+    pub fn load(path: &std::path::Path) -> Result<Self, SentinelError> {
+        let config_error = |e: ark_config::ConfigError| SentinelError::ConfigError(e.to_string());
+        let loaded = ark_config::load(path, &ark_config::VerifyOptions::from_env(CONFIG_COMPONENT).map_err(config_error)?)
+            .map_err(config_error)?;
+        let bytes = match (loaded.contents, loaded.attestation.status) {
+            (Some(bytes), _) => bytes,
+            (None, ark_config::ConfigStatus::SafeDefaults { reason }) => {
+                warn!("Settings {} failed verification, leaving settings unchanged: {}", path.display(), reason);
+                return Ok(Self::default());
+            }
+            (None, _) => return Err(SentinelError::ConfigError(format!("{} not found", path.display()))),
+        };
+        decode::json_validated(&bytes, &DecodeLimits::FILE)
+            .map_err(|e| SentinelError::ConfigError(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Apply everything but the listeners to `config`, recording what changed
+    pub fn apply_to(&self, config: &mut SentinelConfig, report: &mut ReloadReport) {
+        if let Some(max) = self.max_connections.filter(|max| *max != config.max_connections) {
+            config.max_connections = max;
+            report.applied.push("max_connections".into());
+        }
+        if let Some(timeout) = self.connection_timeout.filter(|timeout| *timeout != config.connection_timeout) {
+            config.connection_timeout = timeout;
+            report.applied.push("connection_timeout".into());
+        }
+        if let Some(acl) = self.acl.as_ref().filter(|acl| *acl != config.authorizer.fallback()) {
+            config.authorizer = std::sync::Arc::new(config.authorizer.with_fallback(acl.clone()));
+            report.applied.push("acl".into());
+        }
+        if let Some(shaping) = self.shaping.as_ref().filter(|shaping| *shaping != config.shaper.config()) {
+            config.shaper = std::sync::Arc::new(crate::BandwidthShaper::new(shaping.clone()));
+            report.applied.push("shaping".into());
+        }
+        if let Some(algorithms) = self.algorithms.as_ref().filter(|a| **a != config.capabilities.algorithms) {
+            config.capabilities.algorithms = algorithms.clone();
+            report.deferred.push("algorithms".into());
+        }
+    }
+}
+
+/// Outcome of a reload
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// Fields in effect for every new connection
+    pub applied: Vec<String>,
+    /// Fields in effect only for connections that run a new handshake
+    pub deferred: Vec<String>,
+    /// Changes that were not made, with the reason
+    pub rejected: Vec<String>,
+}
+
+impl ReloadReport {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.deferred.is_empty() && self.rejected.is_empty()
+    }
+}
+
+/// Reload queued for the running sentinel
+pub(crate) struct ReloadRequest {
+    pub(crate) settings: ServerSettings,
+    pub(crate) reply: oneshot::Sender<ReloadReport>,
+}
+
+/// Sends new settings to a running sentinel
+#[derive(Clone)]
+pub struct ReloadHandle {
+    pub(crate) requests: mpsc::Sender<ReloadRequest>,
+}
+
+impl ReloadHandle {
+    /// Apply `settings` to the running sentinel
+    ///
+    /// Waits until the sentinel has applied them; fails once it has stopped.
+    pub async fn reload(&self, settings: ServerSettings) -> Result<ReloadReport, SentinelError> {
+        let (reply, report) = oneshot::channel();
+        self.requests.send(ReloadRequest { settings, reply }).await
+            .map_err(|_| SentinelError::ConfigError("Sentinel is not running".into()))?;
+        report.await.map_err(|_| SentinelError::ConfigError("Sentinel stopped during reload".into()))
+    }
+
+    /// Load `path` and apply it, logging the report
+    pub async fn reload_file(&self, path: &std::path::Path) -> Result<ReloadReport, SentinelError> {
+        let report = self.reload(ServerSettings::load(path)?).await?;
+        info!("Reloaded {}: applied {:?}, deferred to new handshakes {:?}",
+              path.display(), report.applied, report.deferred);
+        if !report.rejected.is_empty() {
+            warn!("Reload of {} rejected {:?}", path.display(), report.rejected);
+        }
+        Ok(report)
+    }
+
+    /// Reload `path` whenever its modification time changes
+    ///
+    /// A file that fails to load or validate is logged and left unapplied;
+    /// the watch ends when the sentinel stops.
+    pub fn watch_file(self, path: PathBuf, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            let mut seen: Option<SystemTime> = modified(&path);
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                if self.requests.is_closed() {
+                    return;
+                }
+                let current = modified(&path);
+                if current == seen {
+                    continue;
+                }
+                seen = current;
+                if let Err(e) = self.reload_file(&path).await {
+                    warn!("Not reloading {}: {}", path.display(), e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AclRule, Authorizer, Endpoint, NetworkSentinel, SentinelClient};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn acl(peers: &[&str]) -> StaticAcl {
+        StaticAcl {
+            rules: peers.iter().map(|peer| AclRule { peer: peer.to_string(), service: "echo".into(), allow: true }).collect(),
+            default_allow: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_keeps_connections_and_reports_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = ListenerConfig::unix(dir.path().join("first.sock")).plaintext();
+        let second = ListenerConfig::unix(dir.path().join("second.sock")).plaintext();
+        let config = SentinelConfig {
+            listeners: vec![first.clone()],
+            connection_timeout: 5,
+            authorizer: Arc::new(Authorizer::static_only(acl(&[crate::acl::UNAUTHENTICATED_PEER]))),
+            ..Default::default()
+        };
+        let mut sentinel = NetworkSentinel::new(config);
+        sentinel.initialize().await.unwrap();
+        let handle = sentinel.reload_handle();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            sentinel.run_until(async {
+                let _ = stopped.await;
+            }).await
+        });
+
+        let mut client = SentinelClient::new(false).with_service("mirror", "echo");
+        let mut open = client.connect_endpoint(&first.endpoint).await.unwrap();
+
+        let report = handle.reload(ServerSettings {
+            listeners: vec![first.clone(), second.clone()],
+            max_connections: Some(10),
+            acl: Some(acl(&["stranger"])),
+            algorithms: Some(vec![PQAlgorithm::HybridX25519Kyber768]),
+            ..Default::default()
+        }).await.unwrap();
+        let added = format!("listener {}", second.endpoint);
+        assert_eq!(report.applied, ["max_connections".to_string(), "acl".to_string(), added]);
+        assert_eq!(report.deferred, ["algorithms"]);
+
+        // The open connection outlives the policy that admitted it
+        open.write_all(b"still here").await.unwrap();
+        let mut echoed = [0u8; 10];
+        open.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"still here");
+
+        // The new listener answers under the new policy, which admits no unauthenticated peer
+        let endpoint: &Endpoint = &second.endpoint;
+        let mut stranger = SentinelClient::new(false).with_service("stranger", "echo");
+        assert!(matches!(stranger.connect_endpoint(endpoint).await, Err(SentinelError::AccessDenied(_))));
+        assert!(matches!(client.connect_endpoint(&first.endpoint).await, Err(SentinelError::AccessDenied(_))));
+
+        let report = handle.reload(ServerSettings { listeners: vec![second], ..Default::default() }).await.unwrap();
+        assert_eq!(report.rejected.len(), 1);
+        assert!(report.rejected[0].contains("first.sock"), "{:?}", report);
+        assert!(ServerSettings { algorithms: Some(vec![]), ..Default::default() }.validate().is_err());
+
+        drop(open);
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(handle.reload(ServerSettings::default()).await.is_err());
+    }
+}
@@ -0,0 +1,399 @@
+//! Sentinel Bandwidth Shaping - Per-Connection and Per-Identity Quotas
+//! "Let all things be done decently and in order" - 1 Corinthians 14:40
+//!
+//! Field links are narrow, so every record forwarded on a connection draws
+//! from two token buckets: one owned by the connection and one shared by all
+//! connections of the same peer identity. Bucket sizes come from the quota
+//! of the peer's class. A peer that exceeds its quota is slowed down rather
+//! than disconnected: the record waits until enough tokens have accrued,
+//! which propagates back to the sender as ordinary TCP backpressure.
+//! Identity buckets no connection holds are dropped once they have refilled,
+//! so the shaper does not grow with every identity ever seen.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use pq_types::decode::{self, Validate};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Class of peer, used to select a quota
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeerClass {
+    /// Field device
+    Device,
+    /// Human operator console
+    Operator,
+    /// Upstream relay or data centre
+    Upstream,
+}
+
+/// Bandwidth quota for one peer class (bytes per second and burst bytes)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ClassQuota {
+    /// Sustained rate for a single connection
+    pub connection_rate: u64,
+    /// Burst allowance for a single connection
+    pub connection_burst: u64,
+    /// Sustained rate shared by all connections of one identity
+    pub identity_rate: u64,
+    /// Burst allowance shared by all connections of one identity
+    pub identity_burst: u64,
+}
+
+/// Shaping configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShapingConfig {
+    /// Quota per peer class
+    pub quotas: HashMap<PeerClass, ClassQuota>,
+    /// Class assigned to known peer identities
+    #[serde(default)]
+    pub peer_classes: HashMap<String, PeerClass>,
+    /// Class of peers not listed in `peer_classes`
+    pub default_class: PeerClass,
+}
+
+impl Default for ShapingConfig {
+    fn default() -> Self {
+        let quota = |connection_rate: u64, identity_rate: u64| ClassQuota {
+            connection_rate,
+            connection_burst: connection_rate,
+            identity_rate,
+            identity_burst: identity_rate,
+        };
+
+        Self {
+            quotas: HashMap::from([
+                (PeerClass::Device, quota(64 * 1024, 128 * 1024)),
+                (PeerClass::Operator, quota(256 * 1024, 512 * 1024)),
+                (PeerClass::Upstream, quota(1024 * 1024, 4 * 1024 * 1024)),
+            ]),
+            peer_classes: HashMap::new(),
+            default_class: PeerClass::Device,
+        }
+    }
+}
+
+impl ShapingConfig {
+    /// Class of a peer identity
+    pub fn class_of(&self, peer_id: &str) -> PeerClass {
+        self.peer_classes.get(peer_id).copied().unwrap_or(self.default_class)
+    }
+}
+
+/// Most peer identities accepted in a shaping file
+pub const MAX_PEER_CLASSES: usize = 100_000;
+
+/// Identity buckets held before idle ones are swept
+const IDENTITY_SWEEP_THRESHOLD: usize = 1024;
+
+impl Validate for ShapingConfig {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("peer_classes", self.peer_classes.len(), MAX_PEER_CLASSES)?;
+        for peer in self.peer_classes.keys() {
+            decode::check_identifier("peer", peer, crate::protocol::MAX_NAME_LENGTH)?;
+        }
+        for (class, quota) in &self.quotas {
+            if quota.connection_rate == 0 || quota.identity_rate == 0 {
+                return Err(format!("{:?} quota has a zero rate", class));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Token bucket measured in bytes
+///
+/// Tokens may go negative: a record larger than the remaining tokens is
+/// admitted after a delay proportional to the deficit, so records larger
+/// than the burst size still make progress.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(rate: u64, burst: u64) -> Self {
+        Self {
+            rate: rate.max(1) as f64,
+            burst: burst.max(1) as f64,
+            tokens: burst.max(1) as f64,
+            updated: Instant::now(),
+        }
+    }
+
+    /// Take `bytes` tokens, returning how long the caller must wait
+    pub fn reserve(&mut self, bytes: u64, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.updated = now;
+
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    /// Whether the bucket has refilled to its burst size by `now`
+    pub fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * self.rate >= self.burst
+    }
+}
+
+/// Identity buckets by peer id
+#[derive(Debug)]
+struct IdentityBuckets {
+    buckets: HashMap<String, Arc<Mutex<TokenBucket>>>,
+    /// Size at which idle buckets are next swept
+    sweep_at: usize,
+}
+
+impl IdentityBuckets {
+    fn new() -> Self {
+        Self {
+            buckets: HashMap::new(),
+            sweep_at: IDENTITY_SWEEP_THRESHOLD,
+        }
+    }
+
+    /// Bucket of `peer_id`, created full if absent
+    fn bucket(&mut self, peer_id: &str, quota: &ClassQuota) -> Arc<Mutex<TokenBucket>> {
+        if let Some(bucket) = self.buckets.get(peer_id) {
+            return bucket.clone();
+        }
+        if self.buckets.len() >= self.sweep_at {
+            self.sweep(Instant::now());
+            self.sweep_at = (self.buckets.len() * 2).max(IDENTITY_SWEEP_THRESHOLD);
+        }
+
+        let bucket = Arc::new(Mutex::new(TokenBucket::new(quota.identity_rate, quota.identity_burst)));
+        self.buckets.insert(peer_id.to_string(), bucket.clone());
+        bucket
+    }
+
+    /// Drop buckets no connection holds that have refilled completely
+    ///
+    /// Such a bucket is indistinguishable from a new one, so reconnecting
+    /// after the sweep gains a peer nothing.
+    fn sweep(&mut self, now: Instant) {
+        let before = self.buckets.len();
+        self.buckets.retain(|_, bucket| {
+            Arc::strong_count(bucket) > 1 || !bucket.lock().unwrap_or_else(|e| e.into_inner()).is_full(now)
+        });
+        debug!("Swept {} idle identity buckets", before - self.buckets.len());
+    }
+}
+
+/// Throttling counters for one peer class
+#[derive(Debug, Default)]
+struct ClassCounters {
+    bytes: AtomicU64,
+    throttle_events: AtomicU64,
+    throttled_micros: AtomicU64,
+}
+
+/// Snapshot of throttling metrics for one peer class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ThrottleStats {
+    /// Bytes passed through the shaper
+    pub bytes: u64,
+    /// Records that had to wait for tokens
+    pub throttle_events: u64,
+    /// Total time records spent waiting
+    pub throttled_micros: u64,
+}
+
+/// Shared shaper handing out per-connection limiters
+#[derive(Debug)]
+pub struct BandwidthShaper {
+    config: ShapingConfig,
+    identities: Mutex<IdentityBuckets>,
+    counters: HashMap<PeerClass, ClassCounters>,
+}
+
+impl BandwidthShaper {
+    /// Create a shaper from configuration
+    pub fn new(config: ShapingConfig) -> Self {
+        let counters = [PeerClass::Device, PeerClass::Operator, PeerClass::Upstream]
+            .into_iter()
+            .map(|class| (class, ClassCounters::default()))
+            .collect();
+
+        Self {
+            config,
+            identities: Mutex::new(IdentityBuckets::new()),
+            counters,
+        }
+    }
+
+    /// Shaping configuration
+    pub fn config(&self) -> &ShapingConfig {
+        &self.config
+    }
+
+    /// Limiter for a new connection of `peer_id`
+    ///
+    /// Returns `None` when the peer's class has no quota (unshaped).
+    pub fn connection(self: &Arc<Self>, peer_id: &str) -> Option<ConnectionLimiter> {
+        let class = self.config.class_of(peer_id);
+        let quota = *self.config.quotas.get(&class)?;
+
+        let identity = self.identities
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .bucket(peer_id, &quota);
+
+        Some(ConnectionLimiter {
+            shaper: self.clone(),
+            class,
+            peer_id: peer_id.to_string(),
+            connection: Mutex::new(TokenBucket::new(quota.connection_rate, quota.connection_burst)),
+            identity,
+        })
+    }
+
+    /// Throttling metrics per peer class
+    pub fn stats(&self) -> HashMap<PeerClass, ThrottleStats> {
+        self.counters
+            .iter()
+            .map(|(class, counters)| (*class, ThrottleStats {
+                bytes: counters.bytes.load(Ordering::Relaxed),
+                throttle_events: counters.throttle_events.load(Ordering::Relaxed),
+                throttled_micros: counters.throttled_micros.load(Ordering::Relaxed),
+            }))
+            .collect()
+    }
+}
+
+impl Default for BandwidthShaper {
+    fn default() -> Self {
+        Self::new(ShapingConfig::default())
+    }
+}
+
+/// Bandwidth limiter for one connection
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    shaper: Arc<BandwidthShaper>,
+    class: PeerClass,
+    peer_id: String,
+    connection: Mutex<TokenBucket>,
+    identity: Arc<Mutex<TokenBucket>>,
+}
+
+impl ConnectionLimiter {
+    /// Class the connection is shaped as
+    pub fn class(&self) -> PeerClass {
+        self.class
+    }
+
+    /// Reserve bandwidth for a record, returning the required delay
+    pub fn reserve(&self, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let connection = self.connection.lock().unwrap_or_else(|e| e.into_inner()).reserve(bytes as u64, now);
+        let identity = self.identity.lock().unwrap_or_else(|e| e.into_inner()).reserve(bytes as u64, now);
+        let delay = connection.max(identity);
+
+        let counters = &self.shaper.counters[&self.class];
+        counters.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        if !delay.is_zero() {
+            counters.throttle_events.fetch_add(1, Ordering::Relaxed);
+            counters.throttled_micros.fetch_add(delay.as_micros() as u64, Ordering::Relaxed);
+            debug!("Throttling {} ({:?}) for {:?}", self.peer_id, self.class, delay);
+        }
+        delay
+    }
+
+    /// Wait until a record of `bytes` may be forwarded
+    pub async fn acquire(&self, bytes: usize) {
+        let delay = self.reserve(bytes);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shaper() -> Arc<BandwidthShaper> {
+        let mut config = ShapingConfig::default();
+        config.quotas.insert(PeerClass::Device, ClassQuota {
+            connection_rate: 1000,
+            connection_burst: 1000,
+            identity_rate: 1500,
+            identity_burst: 1500,
+        });
+        config.peer_classes.insert("console".into(), PeerClass::Operator);
+        Arc::new(BandwidthShaper::new(config))
+    }
+
+    #[test]
+    fn test_token_bucket_refills_and_delays() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1000, 1000);
+
+        assert_eq!(bucket.reserve(1000, start), Duration::ZERO);
+        assert_eq!(bucket.reserve(500, start), Duration::from_millis(500));
+        // One second later the deficit is repaid and 500 tokens remain
+        assert_eq!(bucket.reserve(500, start + Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_identity_bucket_shared_across_connections() {
+        let shaper = shaper();
+        let first = shaper.connection("sensor-1").unwrap();
+        let second = shaper.connection("sensor-1").unwrap();
+        assert_eq!(first.class(), PeerClass::Device);
+
+        assert_eq!(first.reserve(1000), Duration::ZERO);
+        // Second connection has its own full bucket but shares the identity's
+        assert!(second.reserve(1000) > Duration::ZERO);
+
+        let stats = shaper.stats()[&PeerClass::Device];
+        assert_eq!(stats.bytes, 2000);
+        assert_eq!(stats.throttle_events, 1);
+        assert_eq!(shaper.stats()[&PeerClass::Operator].throttle_events, 0);
+    }
+
+    #[test]
+    fn test_idle_identity_buckets_are_evicted() {
+        let shaper = shaper();
+        let held = shaper.connection("sensor-held").unwrap();
+        let drained = shaper.connection("sensor-drained").unwrap();
+        assert!(drained.reserve(1_000_000) > Duration::ZERO);
+        drop(drained);
+
+        for i in 0..4 * IDENTITY_SWEEP_THRESHOLD {
+            assert!(shaper.connection(&format!("sensor-{}", i)).is_some());
+        }
+
+        // Held and still-refilling buckets survive; their state is kept
+        let identities = shaper.identities.lock().unwrap();
+        assert!(identities.buckets.len() <= IDENTITY_SWEEP_THRESHOLD);
+        assert!(Arc::ptr_eq(&identities.buckets["sensor-held"], &held.identity));
+        assert!(!identities.buckets["sensor-drained"].lock().unwrap().is_full(Instant::now()));
+    }
+
+    #[test]
+    fn test_unshaped_class() {
+        let mut config = ShapingConfig::default();
+        config.quotas.remove(&PeerClass::Upstream);
+        config.peer_classes.insert("relay".into(), PeerClass::Upstream);
+        let shaper = Arc::new(BandwidthShaper::new(config));
+
+        assert!(shaper.connection("relay").is_none());
+        assert_eq!(shaper.connection("console").unwrap().class(), PeerClass::Device);
+    }
+}
@@ -0,0 +1,275 @@
+//! Canonical Signing Bytes - Catalogs, Egress Policies and Session Archives
+//! "Just balances, just weights, a just ephah, and a just hin, shall ye have" - Leviticus 19:36
+//!
+//! Service catalogs, egress policies and session archives are signed over
+//! the BLAKE3 digest of their `pq_types::canonical` encoding, not of their
+//! bincode or JSON serialization, so a serde, bincode or field order change
+//! can no longer alter what an issued signature covers. Each encoder lists
+//! every field in a fixed order; a field added to one of these structs is
+//! not signed until its encoder is extended under a new domain. The golden
+//! vectors in the tests freeze every layout.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pq_types::canonical::CanonicalEncoder;
+
+use crate::capture::{CapturedFrame, Direction, FramePayload, SessionArchive};
+use crate::discovery::ServiceCatalog;
+use crate::egress::{EgressPolicy, EgressTarget};
+use crate::pqc_tls::PQAlgorithm;
+use crate::transport::PeerAddr;
+
+/// Domain of service catalog signatures
+const CATALOG_DOMAIN: &[u8] = b"ark-sentinel-service-catalog-v2";
+
+/// Domain of egress policy signatures
+const EGRESS_DOMAIN: &[u8] = b"ark-sentinel-egress-policy-v2";
+
+/// Domain of session archive signatures
+const ARCHIVE_DOMAIN: &[u8] = b"ark-sentinel-session-archive-v2";
+
+impl ServiceCatalog {
+    /// Bytes whose digest the catalog key signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(CATALOG_DOMAIN);
+        encoder
+            .u64(self.serial)
+            .u128(unix_nanos(self.issued_at))
+            .u128(unix_nanos(self.expires_at))
+            .seq(&self.services, |encoder, service| {
+                encoder.str(&service.name).seq(&service.addresses, |encoder, address| {
+                    encode_socket_addr(encoder, address);
+                });
+            });
+        encoder.finish()
+    }
+}
+
+impl EgressPolicy {
+    /// Bytes whose digest the egress key signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(EGRESS_DOMAIN);
+        encoder
+            .u64(self.serial)
+            .u128(unix_nanos(self.issued_at))
+            .u128(unix_nanos(self.expires_at))
+            .seq(&self.rules, |encoder, rule| {
+                encoder.str(&rule.component);
+                match &rule.target {
+                    EgressTarget::Tcp { network, prefix_len, ports } => {
+                        encoder.variant("tcp");
+                        encode_ip(encoder, network);
+                        encoder.u8(*prefix_len).option(*ports, |encoder, [low, high]| {
+                            encoder.u32(u32::from(low)).u32(u32::from(high));
+                        });
+                    }
+                    EgressTarget::Unix { path } => {
+                        encoder.variant("unix").bytes(path.as_os_str().as_encoded_bytes());
+                    }
+                    EgressTarget::Vsock { cid, port } => {
+                        encoder.variant("vsock").u32(*cid).option(*port, |encoder, port| {
+                            encoder.u32(port);
+                        });
+                    }
+                }
+                encoder.str(&rule.description);
+            });
+        encoder.finish()
+    }
+}
+
+impl SessionArchive {
+    /// Bytes whose digest the archive key signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(ARCHIVE_DOMAIN);
+        encoder.u32(self.format).str(&self.session_id).str(&self.peer_id);
+        match &self.peer_addr {
+            PeerAddr::Tcp(addr) => {
+                encoder.variant("tcp");
+                encode_socket_addr(&mut encoder, addr);
+            }
+            PeerAddr::Unix { uid, pid } => {
+                encoder
+                    .variant("unix")
+                    .option(*uid, |encoder, uid| {
+                        encoder.u32(uid);
+                    })
+                    .option(*pid, |encoder, pid| {
+                        encoder.u32(pid as u32);
+                    });
+            }
+            PeerAddr::Vsock { cid, port } => {
+                encoder.variant("vsock").u32(*cid).u32(*port);
+            }
+        }
+        encoder
+            .str(&self.service)
+            .option(self.negotiated.as_ref(), |encoder, negotiated| {
+                encoder
+                    .u32(u32::from(negotiated.version))
+                    .variant(algorithm_name(negotiated.algorithm))
+                    .strs(&negotiated.extensions);
+            })
+            .u128(unix_nanos(self.started_at))
+            .u128(unix_nanos(self.ended_at))
+            .u64(self.bytes_in)
+            .u64(self.bytes_out)
+            .str(&self.close_reason)
+            .seq(&self.frames, encode_frame);
+        encoder.finish()
+    }
+}
+
+fn encode_frame(encoder: &mut CanonicalEncoder, frame: &CapturedFrame) {
+    encoder
+        .u128(frame.offset.as_nanos())
+        .variant(match frame.direction {
+            Direction::Inbound => "inbound",
+            Direction::Outbound => "outbound",
+        })
+        .u64(frame.length as u64);
+    match &frame.payload {
+        FramePayload::Text(text) => encoder.variant("text").str(text),
+        FramePayload::Binary(hex) => encoder.variant("binary").str(hex),
+        FramePayload::Omitted => encoder.variant("omitted"),
+    };
+    encoder.bool(frame.truncated).bool(frame.scrubbed);
+}
+
+fn encode_socket_addr(encoder: &mut CanonicalEncoder, addr: &SocketAddr) {
+    encode_ip(encoder, &addr.ip());
+    encoder.u32(u32::from(addr.port()));
+}
+
+fn encode_ip(encoder: &mut CanonicalEncoder, ip: &IpAddr) {
+    match ip {
+        IpAddr::V4(ip) => encoder.variant("v4").fixed(&ip.octets()),
+        IpAddr::V6(ip) => encoder.variant("v6").fixed(&ip.octets()),
+    };
+}
+
+// Variant names are part of the signed bytes: never rename one
+
+fn algorithm_name(algorithm: PQAlgorithm) -> &'static str {
+    match algorithm {
+        PQAlgorithm::HybridX25519Kyber768 => "x25519+kyber768",
+        PQAlgorithm::HybridEd25519Dilithium3 => "ed25519+dilithium3",
+        PQAlgorithm::Kyber768 => "kyber768",
+        PQAlgorithm::Dilithium3 => "dilithium3",
+        PQAlgorithm::MlKem768 => "mlkem768",
+        PQAlgorithm::MlDsa65 => "mldsa65",
+        PQAlgorithm::HybridX25519MlKem768 => "x25519+mlkem768",
+    }
+}
+
+/// Nanoseconds since the Unix epoch; earlier times encode as 0
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::CapturedNegotiation;
+    use crate::discovery::ServiceRecord;
+    use crate::egress::EgressRule;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn golden(bytes: &[u8]) -> String {
+        blake3::hash(bytes).to_hex().to_string()
+    }
+
+    #[test]
+    fn test_catalog_and_policy_signing_bytes_are_frozen() {
+        let catalog = ServiceCatalog {
+            serial: 7,
+            issued_at: at(1_700_000_000),
+            expires_at: at(1_700_086_400),
+            services: vec![ServiceRecord {
+                name: "patch-server".to_string(),
+                addresses: vec!["10.0.0.5:8443".parse().unwrap(), "[fd00::5]:8443".parse().unwrap()],
+            }],
+        };
+        let bytes = catalog.signing_bytes();
+        assert_eq!(&bytes[8..8 + CATALOG_DOMAIN.len()], CATALOG_DOMAIN);
+        assert_eq!(golden(&bytes), "3bd6e6ca8cbb460d5ab7575835a674be13e73f94d003930617e23c469c0ec852");
+
+        let policy = EgressPolicy {
+            serial: 3,
+            issued_at: at(1_700_000_000),
+            expires_at: at(1_700_086_400),
+            rules: vec![
+                EgressRule {
+                    component: "patch_orchestrator".to_string(),
+                    target: EgressTarget::Tcp { network: "10.0.0.0".parse().unwrap(), prefix_len: 8, ports: Some([8443, 8443]) },
+                    description: "patch server".to_string(),
+                },
+                EgressRule {
+                    component: "*".to_string(),
+                    target: EgressTarget::Unix { path: "/run/ark/ethics.sock".into() },
+                    description: String::new(),
+                },
+                EgressRule {
+                    component: "cold_mirror".to_string(),
+                    target: EgressTarget::Vsock { cid: 3, port: None },
+                    description: "model host".to_string(),
+                },
+            ],
+        };
+        assert_eq!(golden(&policy.signing_bytes()), "83827d7ade03aaf8ff474930f42249dd330ec2b4caf5eaace15570120b09ccb4");
+
+        // Every field is covered
+        let mut widened = policy.clone();
+        widened.rules[0].target = EgressTarget::Tcp { network: "10.0.0.0".parse().unwrap(), prefix_len: 8, ports: None };
+        assert_ne!(widened.signing_bytes(), policy.signing_bytes());
+    }
+
+    #[test]
+    fn test_archive_signing_bytes_are_frozen() {
+        let archive = SessionArchive {
+            format: 1,
+            session_id: "session-1".to_string(),
+            peer_id: "sensor-1".to_string(),
+            peer_addr: PeerAddr::Unix { uid: Some(1000), pid: None },
+            service: "ethics".to_string(),
+            negotiated: Some(CapturedNegotiation {
+                version: 3,
+                algorithm: PQAlgorithm::HybridX25519MlKem768,
+                extensions: vec!["compression".to_string()],
+            }),
+            started_at: at(1_700_000_000),
+            ended_at: at(1_700_000_060),
+            bytes_in: 42,
+            bytes_out: 7,
+            close_reason: "peer closed".to_string(),
+            frames: vec![
+                CapturedFrame {
+                    offset: Duration::from_millis(5),
+                    direction: Direction::Inbound,
+                    length: 42,
+                    payload: FramePayload::Text("password=[REDACTED]".to_string()),
+                    truncated: false,
+                    scrubbed: true,
+                },
+                CapturedFrame {
+                    offset: Duration::from_millis(9),
+                    direction: Direction::Outbound,
+                    length: 7,
+                    payload: FramePayload::Omitted,
+                    truncated: false,
+                    scrubbed: false,
+                },
+            ],
+        };
+        assert_eq!(golden(&archive.signing_bytes()), "e9fb7a8cf1cd74fb6450697b5628500547ed6838bfe7d2184e6c9ea0a08e3bee");
+
+        let mut reordered = archive.clone();
+        reordered.frames.swap(0, 1);
+        assert_ne!(reordered.signing_bytes(), archive.signing_bytes());
+    }
+}
@@ -0,0 +1,346 @@
+//! Sentinel Transports - TCP, UNIX Domain Sockets and vsock
+//! "Thou hast set all the borders of the earth" - Psalm 74:17
+//!
+//! Co-located components should not pay for TCP and TLS, and components in
+//! separate VMs reach the sentinel over vsock. Every listener hands the
+//! handler and record layers a `BoxedStream`, so negotiation, sealed records,
+//! authorization and capture run the same on each transport. A UNIX socket
+//! listener may be marked plaintext: it skips the negotiation like a
+//! classical listener, but peers still present an identity that the
+//! authorizer judges, and the kernel-reported uid and pid of the peer are
+//! recorded as its address.
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tracing::warn;
+
+use crate::SentinelError;
+
+/// Where a listener binds or a client connects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    /// TCP socket address
+    Tcp(SocketAddr),
+    /// UNIX domain socket path
+    Unix(PathBuf),
+    /// vsock context id and port
+    Vsock { cid: u32, port: u32 },
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "{}", addr),
+            Endpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+            Endpoint::Vsock { cid, port } => write!(f, "vsock:{}:{}", cid, port),
+        }
+    }
+}
+
+impl From<SocketAddr> for Endpoint {
+    fn from(addr: SocketAddr) -> Self {
+        Endpoint::Tcp(addr)
+    }
+}
+
+/// One listening endpoint of the sentinel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListenerConfig {
+    /// Endpoint to bind
+    pub endpoint: Endpoint,
+    /// Run the post-quantum negotiation (with `quantum_resistant`); only
+    /// UNIX socket listeners may turn this off
+    #[serde(default = "encrypted_by_default")]
+    pub encrypted: bool,
+}
+
+fn encrypted_by_default() -> bool {
+    true
+}
+
+impl ListenerConfig {
+    /// Encrypted TCP listener
+    pub fn tcp(addr: SocketAddr) -> Self {
+        Self { endpoint: Endpoint::Tcp(addr), encrypted: true }
+    }
+
+    /// Encrypted UNIX socket listener
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self { endpoint: Endpoint::Unix(path.into()), encrypted: true }
+    }
+
+    /// Encrypted vsock listener
+    pub fn vsock(cid: u32, port: u32) -> Self {
+        Self { endpoint: Endpoint::Vsock { cid, port }, encrypted: true }
+    }
+
+    /// Skip the negotiation and encryption on this listener
+    pub fn plaintext(mut self) -> Self {
+        self.encrypted = false;
+        self
+    }
+
+    /// Reject plaintext listeners on anything but a UNIX socket
+    pub fn validate(&self) -> Result<(), SentinelError> {
+        if !self.encrypted && !matches!(self.endpoint, Endpoint::Unix(_)) {
+            return Err(SentinelError::ConfigError(format!(
+                "Listener {} must be encrypted; only UNIX sockets may skip encryption",
+                self.endpoint
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Address of a connected peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum PeerAddr {
+    /// Remote TCP socket address
+    Tcp(SocketAddr),
+    /// Local process, as reported by the kernel
+    Unix { uid: Option<u32>, pid: Option<i32> },
+    /// Remote vsock context id and port
+    Vsock { cid: u32, port: u32 },
+}
+
+impl From<SocketAddr> for PeerAddr {
+    fn from(addr: SocketAddr) -> Self {
+        PeerAddr::Tcp(addr)
+    }
+}
+
+impl fmt::Display for PeerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn known<T: fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "?".to_string(), T::to_string)
+        }
+        match self {
+            PeerAddr::Tcp(addr) => write!(f, "{}", addr),
+            PeerAddr::Unix { uid, pid } => write!(f, "unix:{}:{}", known(uid), known(pid)),
+            PeerAddr::Vsock { cid, port } => write!(f, "vsock:{}:{}", cid, port),
+        }
+    }
+}
+
+impl FromStr for PeerAddr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn known<T: FromStr>(part: &str) -> Result<Option<T>, String> {
+            match part {
+                "?" => Ok(None),
+                _ => part.parse().map(Some).map_err(|_| format!("Malformed peer address part {:?}", part)),
+            }
+        }
+        let invalid = || format!("Malformed peer address {:?}", s);
+        if let Some(rest) = s.strip_prefix("unix:") {
+            let (uid, pid) = rest.split_once(':').ok_or_else(invalid)?;
+            return Ok(PeerAddr::Unix { uid: known(uid)?, pid: known(pid)? });
+        }
+        if let Some(rest) = s.strip_prefix("vsock:") {
+            let (cid, port) = rest.split_once(':').ok_or_else(invalid)?;
+            return Ok(PeerAddr::Vsock {
+                cid: cid.parse().map_err(|_| invalid())?,
+                port: port.parse().map_err(|_| invalid())?,
+            });
+        }
+        s.parse().map(PeerAddr::Tcp).map_err(|_| invalid())
+    }
+}
+
+impl From<PeerAddr> for String {
+    fn from(addr: PeerAddr) -> Self {
+        addr.to_string()
+    }
+}
+
+impl TryFrom<String> for PeerAddr {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Byte stream of any transport
+pub trait TransportStream: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + 'static> TransportStream for T {}
+
+/// Type-erased connection handed to the handler and record layers
+pub type BoxedStream = Box<dyn TransportStream>;
+
+/// Bound listening socket
+pub(crate) enum BoundListener {
+    Tcp(TcpListener),
+    /// Removes its socket file when dropped
+    Unix(UnixListener, PathBuf),
+    #[cfg(feature = "vsock")]
+    Vsock(tokio_vsock::VsockListener),
+}
+
+impl BoundListener {
+    /// Bind `endpoint`, replacing a stale socket file at a UNIX socket path
+    pub(crate) async fn bind(endpoint: &Endpoint) -> Result<Self, SentinelError> {
+        match endpoint {
+            Endpoint::Tcp(addr) => Ok(BoundListener::Tcp(TcpListener::bind(addr).await?)),
+            Endpoint::Unix(path) => {
+                use std::os::unix::fs::FileTypeExt;
+                if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    std::fs::remove_file(path)?;
+                }
+                Ok(BoundListener::Unix(UnixListener::bind(path)?, path.clone()))
+            }
+            #[cfg(feature = "vsock")]
+            Endpoint::Vsock { cid, port } => Ok(BoundListener::Vsock(tokio_vsock::VsockListener::bind(
+                tokio_vsock::VsockAddr::new(*cid, *port),
+            )?)),
+            #[cfg(not(feature = "vsock"))]
+            Endpoint::Vsock { .. } => Err(vsock_unsupported().into()),
+        }
+    }
+
+    /// TCP address the listener is bound to
+    pub(crate) fn local_addr(&self) -> Option<SocketAddr> {
+        match self {
+            BoundListener::Tcp(listener) => listener.local_addr().ok(),
+            _ => None,
+        }
+    }
+
+    /// Poll for the next connection and the address of its peer
+    pub(crate) fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(BoxedStream, PeerAddr)>> {
+        match self {
+            BoundListener::Tcp(listener) => listener
+                .poll_accept(cx)
+                .map_ok(|(stream, addr)| (Box::new(stream) as BoxedStream, PeerAddr::Tcp(addr))),
+            BoundListener::Unix(listener, _) => listener.poll_accept(cx).map_ok(|(stream, _)| {
+                let peer = unix_peer(&stream);
+                (Box::new(stream) as BoxedStream, peer)
+            }),
+            #[cfg(feature = "vsock")]
+            BoundListener::Vsock(listener) => listener.poll_accept(cx).map_ok(|(stream, addr)| {
+                (Box::new(stream) as BoxedStream, PeerAddr::Vsock { cid: addr.cid(), port: addr.port() })
+            }),
+        }
+    }
+}
+
+impl Drop for BoundListener {
+    fn drop(&mut self) {
+        if let BoundListener::Unix(_, path) = self {
+            if let Err(e) = std::fs::remove_file(path.as_path()) {
+                warn!("Failed to remove socket {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// Kernel-reported credentials of a UNIX socket peer
+fn unix_peer(stream: &UnixStream) -> PeerAddr {
+    match stream.peer_cred() {
+        Ok(cred) => PeerAddr::Unix { uid: Some(cred.uid()), pid: cred.pid() },
+        Err(e) => {
+            warn!("Peer credentials unavailable: {}", e);
+            PeerAddr::Unix { uid: None, pid: None }
+        }
+    }
+}
+
+#[cfg(not(feature = "vsock"))]
+fn vsock_unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "vsock support requires the `vsock` feature")
+}
+
+/// Open a connection to `endpoint`
+pub async fn connect(endpoint: &Endpoint) -> io::Result<BoxedStream> {
+    Ok(match endpoint {
+        Endpoint::Tcp(addr) => Box::new(TcpStream::connect(addr).await?),
+        Endpoint::Unix(path) => Box::new(UnixStream::connect(path).await?),
+        #[cfg(feature = "vsock")]
+        Endpoint::Vsock { cid, port } => {
+            Box::new(tokio_vsock::VsockStream::connect(tokio_vsock::VsockAddr::new(*cid, *port)).await?)
+        }
+        #[cfg(not(feature = "vsock"))]
+        Endpoint::Vsock { .. } => return Err(vsock_unsupported()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AclRule, Authorizer, NetworkSentinel, SentinelClient, SentinelConfig, StaticAcl};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_plaintext_only_on_unix_and_peer_addr_round_trip() {
+        assert!(ListenerConfig::unix("/run/ark/sentinel.sock").plaintext().validate().is_ok());
+        assert!(ListenerConfig::tcp("127.0.0.1:0".parse().unwrap()).plaintext().validate().is_err());
+        assert!(ListenerConfig::vsock(3, 5000).plaintext().validate().is_err());
+
+        for addr in [
+            PeerAddr::Tcp("10.0.0.7:40000".parse().unwrap()),
+            PeerAddr::Unix { uid: Some(1000), pid: None },
+            PeerAddr::Vsock { cid: 3, port: 5000 },
+        ] {
+            assert_eq!(addr.to_string().parse::<PeerAddr>(), Ok(addr));
+        }
+        // Archives written before other transports existed still decode
+        let tcp: PeerAddr = serde_json::from_str("\"10.0.0.7:40000\"").unwrap();
+        assert_eq!(tcp, PeerAddr::Tcp("10.0.0.7:40000".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_unix_listener_authorizes_and_echoes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sentinel.sock");
+        let config = SentinelConfig {
+            listeners: vec![ListenerConfig::unix(&path).plaintext()],
+            connection_timeout: 5,
+            authorizer: Arc::new(Authorizer::static_only(StaticAcl {
+                rules: vec![
+                    AclRule { peer: crate::acl::UNAUTHENTICATED_PEER.into(), service: "echo".into(), allow: true },
+                    AclRule { peer: "mirror".into(), service: "admin".into(), allow: true },
+                ],
+                default_allow: false,
+            })),
+            ..Default::default()
+        };
+        let mut sentinel = NetworkSentinel::new(config);
+        sentinel.initialize().await.unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            sentinel.run_until(async {
+                let _ = stopped.await;
+            }).await
+        });
+
+        let endpoint = Endpoint::Unix(path.clone());
+        let mut client = SentinelClient::new(false).with_service("mirror", "echo");
+        let mut stream = client.connect_endpoint(&endpoint).await.unwrap();
+        stream.write_all(b"co-located").await.unwrap();
+        let mut echoed = [0u8; 10];
+        stream.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"co-located");
+        drop(stream);
+
+        // Without a key exchange the claimed name earns nothing of its own
+        let mut admin = SentinelClient::new(false).with_service("mirror", "admin");
+        assert!(matches!(admin.connect_endpoint(&endpoint).await, Err(crate::SentinelError::AccessDenied(_))));
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,350 @@
+//! Sentinel Harness - In-Process Client/Server Pairs
+//! "Iron sharpeneth iron; so a man sharpeneth the countenance of his friend" - Proverbs 27:17
+//!
+//! Starts the sentinel on an ephemeral loopback port and drives it with
+//! the client, so the key exchange, sealed records, keep-alive inside
+//! them, downgrade and interception protection and shutdown are
+//! exercised end to end without any outside network.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use network_sentinel::pqc_tls::PQHandshake;
+use network_sentinel::{
+    channel, protocol, AclRule, Authorizer, Capabilities, CapabilityOffer, CapabilitySelection, Extension,
+    ExtensionKind, KeepAliveConfig, LiveConnection, NetworkSentinel, PQAlgorithm, PQTlsConfig, SentinelClient,
+    SentinelConfig, SentinelError, StaticAcl, Transcript,
+};
+use pq_types::pins::{KeyFingerprint, PinStore};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Name the harness sentinel's identity keys are pinned under
+const SENTINEL: &str = "sentinel";
+
+/// Sentinel serving `echo` on an ephemeral port until stopped
+struct Harness {
+    addr: SocketAddr,
+    /// Fingerprint of the sentinel's identity keys
+    identity: KeyFingerprint,
+    /// Pins holding `identity` for `SENTINEL`
+    pins: Arc<PinStore>,
+    stop: Option<oneshot::Sender<()>>,
+    server: JoinHandle<Result<(), SentinelError>>,
+}
+
+impl Harness {
+    async fn start(algorithms: Vec<PQAlgorithm>) -> Self {
+        let acl = StaticAcl {
+            rules: vec![AclRule { peer: "*".into(), service: "echo".into(), allow: true }],
+            default_allow: false,
+        };
+        Self::start_with(algorithms, acl, PinStore::in_memory()).await
+    }
+
+    /// Sentinel authorizing by `acl`, with `client_pins` naming clients by their keys
+    async fn start_with(algorithms: Vec<PQAlgorithm>, acl: StaticAcl, client_pins: PinStore) -> Self {
+        let mut keys = PQTlsConfig::default();
+        keys.generate_keypairs().unwrap();
+        let identity = keys.identity_fingerprint().unwrap();
+        let mut pins = PinStore::in_memory();
+        pins.pin(SENTINEL, identity).unwrap();
+
+        let mut config = SentinelConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            pq_tls_config: Arc::new(keys),
+            connection_timeout: 5,
+            authorizer: Arc::new(Authorizer::static_only(acl)),
+            pins: Arc::new(client_pins),
+            ..Default::default()
+        };
+        config.capabilities.algorithms = algorithms;
+        config.capabilities.extensions.push(Extension::new(ExtensionKind::KeepAlive, false));
+        config.enable_secure_records();
+
+        let mut sentinel = NetworkSentinel::new(config);
+        sentinel.initialize().await.unwrap();
+        let addr = sentinel.local_addr().unwrap();
+
+        let (stop, stopped) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            sentinel
+                .run_until(async {
+                    let _ = stopped.await;
+                })
+                .await
+        });
+        Self { addr, identity, pins: Arc::new(pins), stop: Some(stop), server }
+    }
+
+    /// Client for `service` holding the sentinel to its pinned keys
+    fn client(&self, service: &str) -> SentinelClient {
+        SentinelClient::new(true)
+            .with_service("harness", service)
+            .with_secure_records()
+            .with_pins(SENTINEL, self.pins.clone())
+    }
+
+    async fn shutdown(mut self) -> Result<(), SentinelError> {
+        self.stop.take().unwrap().send(()).unwrap();
+        self.server.await.unwrap()
+    }
+}
+
+/// Forward one connection, dropping `stripped` from the server's offer
+async fn downgrading_proxy(server: SocketAddr, stripped: PQAlgorithm) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut upstream = TcpStream::connect(server).await.unwrap();
+
+        let mut offer: CapabilityOffer = protocol::read_message(&mut upstream).await.unwrap();
+        offer.algorithms.retain(|a| *a != stripped);
+        protocol::write_message(&mut client, &offer).await.unwrap();
+
+        let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+    });
+    addr
+}
+
+/// Forward one connection, running a key exchange of its own with each
+/// side and relaying the records in between
+///
+/// Holds the sentinel to its pin like any client, and offers the
+/// downstream client a key exchange under keys of its own.
+async fn intercepting_proxy(server: SocketAddr, pins: Arc<PinStore>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut keys = PQTlsConfig::default();
+    keys.generate_keypairs().unwrap();
+    let keys = Arc::new(keys);
+    let mut config = SentinelConfig::default();
+    config.enable_secure_records();
+    let capabilities = config.capabilities;
+
+    tokio::spawn(async move {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let mut upstream = TcpStream::connect(server).await.unwrap();
+
+        let offer: CapabilityOffer = protocol::read_message(&mut upstream).await.unwrap();
+        let selection = capabilities.select(&offer).unwrap();
+        protocol::write_message(&mut upstream, &selection).await.unwrap();
+        let mut transcript = Transcript::default();
+        transcript.add(&offer).unwrap();
+        transcript.add(&selection).unwrap();
+        let mut upstream = channel::client_handshake(upstream, keys.clone(), selection.algorithm, transcript, SENTINEL, &pins)
+            .await
+            .unwrap();
+
+        protocol::write_message(&mut client, &offer).await.unwrap();
+        let selection: CapabilitySelection = protocol::read_message(&mut client).await.unwrap();
+        let mut transcript = Transcript::default();
+        transcript.add(&offer).unwrap();
+        transcript.add(&selection).unwrap();
+        let Ok(mut downstream) = channel::server_handshake(client, keys, selection.algorithm, transcript).await else {
+            return;
+        };
+        while let Ok(Some(record)) = downstream.recv().await {
+            upstream.send(&record).await.unwrap();
+            let Ok(Some(reply)) = upstream.recv().await else { break };
+            downstream.send(&reply).await.unwrap();
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn test_key_exchange_agrees_on_shared_secret() {
+    let mut server_config = PQTlsConfig::default();
+    server_config.generate_keypairs().unwrap();
+    let server_config = Arc::new(server_config);
+
+    for algorithm in [PQAlgorithm::HybridX25519Kyber768, PQAlgorithm::Kyber768, PQAlgorithm::MlKem768, PQAlgorithm::HybridX25519MlKem768] {
+        let mut server = PQHandshake::new(server_config.clone(), false);
+        let mut client = PQHandshake::new(Arc::new(PQTlsConfig::default()), true);
+
+        let share = server.offer_key_share(algorithm).unwrap();
+        let exchange = client.accept_key_share(&share).unwrap();
+        server.complete_key_exchange(&exchange).unwrap();
+
+        let secret = server.get_shared_secret().unwrap();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(Some(secret), client.get_shared_secret(), "{:?}", algorithm);
+    }
+}
+
+#[tokio::test]
+async fn test_hybrid_handshake_and_encrypted_echo() {
+    let harness = Harness::start(Capabilities::default().algorithms).await;
+
+    let mut client = harness.client("echo");
+    let mut channel = client.connect_secure(harness.addr).await.unwrap();
+    assert_eq!(channel.algorithm(), PQAlgorithm::HybridX25519Kyber768);
+    assert_eq!(channel.peer_fingerprint(), Some(harness.identity));
+
+    for record in [&b"in the beginning"[..], &[0u8; 4096][..]] {
+        channel.send(record).await.unwrap();
+        assert_eq!(channel.recv().await.unwrap().as_deref(), Some(record));
+    }
+
+    // Clients that do not offer secure records get no sealed channel
+    let mut plain = SentinelClient::new(true).with_service("harness", "echo").with_pins(SENTINEL, harness.pins.clone());
+    assert!(plain.connect_secure(harness.addr).await.is_err());
+
+    // Nor do clients with no pin for the server
+    let mut unpinned = SentinelClient::new(true).with_service("harness", "echo").with_secure_records();
+    assert!(matches!(unpinned.connect_secure(harness.addr).await, Err(SentinelError::ConfigError(_))));
+    let mut unpinned = SentinelClient::new(true)
+        .with_service("harness", "echo")
+        .with_secure_records()
+        .with_pins("elsewhere", harness.pins.clone());
+    assert!(matches!(unpinned.connect_secure(harness.addr).await, Err(SentinelError::NegotiationError(_))));
+
+    channel.shutdown().await.unwrap();
+    assert_eq!(channel.recv().await.unwrap(), None);
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_keepalive_inside_sealed_records() {
+    let harness = Harness::start(Capabilities::default().algorithms).await;
+    let keepalive = KeepAliveConfig { interval: Duration::from_millis(50), miss_threshold: 3 };
+
+    let channel = harness.client("echo").with_keepalive(keepalive).connect_secure(harness.addr).await.unwrap();
+    let mut connection = LiveConnection::start_sealed(channel, keepalive);
+    connection.send(b"watch therefore".to_vec()).await.unwrap();
+    assert_eq!(connection.recv().await.as_deref(), Some(&b"watch therefore"[..]));
+
+    // Sealed pings keep the idle connection alive and measure its RTT
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(connection.is_alive());
+    assert!(connection.metrics().snapshot().smoothed_rtt.is_some());
+
+    connection.close().await.unwrap();
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stripped_offer_rejected_as_downgrade() {
+    let algorithms = vec![PQAlgorithm::HybridX25519Kyber768, PQAlgorithm::Kyber768];
+    let harness = Harness::start(algorithms.clone()).await;
+    let proxy = downgrading_proxy(harness.addr, PQAlgorithm::HybridX25519Kyber768).await;
+
+    // Client that would accept the weaker algorithm it is steered to
+    let mut config = SentinelConfig::default();
+    config.capabilities.algorithms = algorithms;
+    config.enable_secure_records();
+    let capabilities = config.capabilities;
+
+    let mut stream = TcpStream::connect(proxy).await.unwrap();
+    let offer: CapabilityOffer = protocol::read_message(&mut stream).await.unwrap();
+    let selection = capabilities.select(&offer).unwrap();
+    assert_eq!(selection.algorithm, PQAlgorithm::Kyber768);
+    protocol::write_message(&mut stream, &selection).await.unwrap();
+
+    let mut transcript = Transcript::default();
+    transcript.add(&offer).unwrap();
+    transcript.add(&selection).unwrap();
+    let result = channel::client_handshake(
+        stream,
+        Arc::new(PQTlsConfig::default()),
+        selection.algorithm,
+        transcript,
+        SENTINEL,
+        &harness.pins,
+    ).await;
+    match result {
+        Err(SentinelError::NegotiationError(reason)) => assert!(reason.contains("downgrade"), "{}", reason),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("downgraded handshake completed"),
+    }
+
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_stalled_negotiation_dropped_after_timeout() {
+    let harness = Harness::start(Capabilities::default().algorithms).await;
+
+    // Take the offer and never answer it
+    let mut stream = TcpStream::connect(harness.addr).await.unwrap();
+    let _: CapabilityOffer = protocol::read_message(&mut stream).await.unwrap();
+    let mut buf = [0u8; 1];
+    let closed = tokio::time::timeout(Duration::from_secs(10), stream.read(&mut buf)).await
+        .expect("stalled connection still open after the connection timeout");
+    assert!(matches!(closed, Ok(0) | Err(_)), "{:?}", closed);
+
+    // Other clients are served as before
+    let mut channel = harness.client("echo").connect_secure(harness.addr).await.unwrap();
+    channel.send(b"after").await.unwrap();
+    assert_eq!(channel.recv().await.unwrap().as_deref(), Some(&b"after"[..]));
+    channel.shutdown().await.unwrap();
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_clients_identified_by_proven_keys() {
+    let mut keys = PQTlsConfig::default();
+    keys.generate_keypairs().unwrap();
+    let mut client_pins = PinStore::in_memory();
+    client_pins.pin("harness", keys.identity_fingerprint().unwrap()).unwrap();
+    let acl = StaticAcl {
+        rules: vec![AclRule { peer: "harness".into(), service: "echo".into(), allow: true }],
+        default_allow: false,
+    };
+    let harness = Harness::start_with(Capabilities::default().algorithms, acl, client_pins).await;
+
+    let mut channel = harness.client("echo").with_identity(Arc::new(keys)).connect_secure(harness.addr).await.unwrap();
+    channel.send(b"pinned").await.unwrap();
+    assert_eq!(channel.recv().await.unwrap().as_deref(), Some(&b"pinned"[..]));
+    channel.shutdown().await.unwrap();
+
+    // The same name claimed with other keys is refused
+    assert!(matches!(
+        harness.client("echo").connect_secure(harness.addr).await,
+        Err(SentinelError::AccessDenied(_))
+    ));
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_intercepting_proxy_rejected() {
+    let harness = Harness::start(Capabilities::default().algorithms).await;
+    let proxy = intercepting_proxy(harness.addr, harness.pins.clone()).await;
+
+    // Both key exchanges complete and the transcripts agree hop by hop;
+    // only the server's identity proof gives the proxy away
+    match harness.client("echo").connect_secure(proxy).await {
+        Err(SentinelError::NegotiationError(reason)) => assert!(reason.contains("identity"), "{}", reason),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("intercepted handshake completed"),
+    }
+
+    // The sentinel itself still answers the same client
+    let mut channel = harness.client("echo").connect_secure(harness.addr).await.unwrap();
+    channel.send(b"direct").await.unwrap();
+    assert_eq!(channel.recv().await.unwrap().as_deref(), Some(&b"direct"[..]));
+    channel.shutdown().await.unwrap();
+    harness.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_connections_and_listener() {
+    let harness = Harness::start(Capabilities::default().algorithms).await;
+    let addr = harness.addr;
+
+    let mut channel = harness.client("echo").connect_secure(addr).await.unwrap();
+    channel.send(b"last words").await.unwrap();
+    assert_eq!(channel.recv().await.unwrap().as_deref(), Some(&b"last words"[..]));
+    channel.shutdown().await.unwrap();
+
+    harness.shutdown().await.unwrap();
+
+    // The server side of the connection is closed and nothing listens any more
+    assert_eq!(channel.recv().await.unwrap(), None);
+    assert!(TcpStream::connect(addr).await.is_err());
+}
@@ -0,0 +1,718 @@
+//! Management API
+//!
+//! Optional HTTPS server (feature `api`) for operators who manage patches
+//! without shell access. It exposes status, list, submit, approve, apply,
+//! rollback, the CycloneDX export (`sbom`) and release key fingerprints
+//! and pins (`keys`, `pin-key`), answering with the same
+//! documents as the CLI's JSON output (see `responses`); failures carry the
+//! CLI's error code and exit code.
+//!
+//! The API is served only over TLS, with the certificate chain and key
+//! named in `ApiConfig::tls`, so signed requests and the documents
+//! answering them never cross the network in the clear.
+//!
+//! Requests authenticate through the sentinel identity layer. A request
+//! names its operator in `x-ark-peer` and carries `x-ark-timestamp` (Unix
+//! seconds), a fresh random `x-ark-nonce` and `x-ark-signature`, the hex
+//! Ed25519 signature of `METHOD\npath?query\ntimestamp\nnonce\nblake3(body)`
+//! under the operator's key from `ApiConfig::operators`. The verified
+//! identity and the action, as service `patch-api:<action>`, are then
+//! judged by a `network_sentinel::Authorizer`, so the ACL or ethics policy
+//! engine that admits sentinel connections also decides who may apply or
+//! roll back. Signatures older or newer than `max_skew` are refused, and
+//! each operator's nonces are remembered for as long as their timestamp is
+//! accepted, so a captured request cannot be replayed; when the replay
+//! cache is full, requests are refused until entries age out. Every
+//! request, granted or not, is recorded in the audit trail with its
+//! action, status and the operator it authenticated as - a peer named by
+//! a request that failed authentication is never recorded as its author.
+//!
+//! ## Biblical Foundation
+//! "Let all things be done decently and in order" - 1 Corinthians 14:40
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{ConnectInfo, MatchedPath, Path, Query, Request, State};
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use ed25519_dalek::{Verifier as _, VerifyingKey as Ed25519VerifyingKey};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use network_sentinel::{Authorizer, PeerIdentity, StaticAcl};
+use pq_types::dalek;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::pins::{KeyFingerprint, MAX_PEER_NAME};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+use tower::Service as _;
+use tracing::{info, warn};
+
+use crate::audit::AuditEvent;
+use crate::keys::{KeyReport, PinResult};
+use crate::sbom::Bom;
+use crate::responses::{
+    ApplyResult, ApproveResult, ErrorDocument, ErrorReport, ListResult, RollbackResult, SubmitResult,
+};
+use crate::{OrchestratorError, PatchMetadata, PatchOrchestrator, SystemStatus, EXIT_FAILURE};
+
+/// Header naming the operator
+pub const PEER_HEADER: &str = "x-ark-peer";
+/// Header carrying the signing time in Unix seconds
+pub const TIMESTAMP_HEADER: &str = "x-ark-timestamp";
+/// Header carrying the request's random nonce
+pub const NONCE_HEADER: &str = "x-ark-nonce";
+/// Header carrying the hex Ed25519 request signature
+pub const SIGNATURE_HEADER: &str = "x-ark-signature";
+
+/// Prefix of the sentinel services naming API actions
+pub const SERVICE_PREFIX: &str = "patch-api:";
+
+/// Component recorded for API requests in the audit trail
+const AUDIT_COMPONENT: &str = "patch_api";
+
+/// Peer recorded for requests that did not authenticate
+pub const UNAUTHENTICATED_PEER: &str = "(unauthenticated)";
+
+/// Shortest accepted nonce, in characters
+pub const MIN_NONCE_LEN: usize = 16;
+
+/// Longest accepted nonce, in characters
+pub const MAX_NONCE_LEN: usize = 128;
+
+/// Management API configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    pub bind_addr: SocketAddr,
+    /// Operator ids and their hex Ed25519 public keys
+    pub operators: HashMap<String, String>,
+    /// Static ACL over `patch-api:<action>` services
+    #[serde(default)]
+    pub acl: StaticAcl,
+    /// Largest accepted difference between a request's timestamp and now
+    #[serde(default = "default_max_skew")]
+    pub max_skew: Duration,
+    /// Largest accepted request body; submitted patches travel hex-encoded
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Most nonces remembered across all operators
+    #[serde(default = "default_max_nonces")]
+    pub max_nonces: usize,
+    /// Certificate and key the API is served with
+    pub tls: ApiTlsConfig,
+}
+
+/// TLS identity of the management API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiTlsConfig {
+    /// PEM certificate chain, leaf first
+    pub certificate_chain: PathBuf,
+    /// PEM private key of the leaf certificate
+    pub private_key: PathBuf,
+}
+
+impl ApiTlsConfig {
+    /// Acceptor serving the configured certificate chain
+    pub fn acceptor(&self) -> Result<TlsAcceptor, OrchestratorError> {
+        let tls_error = |path: &std::path::Path, e: &dyn std::fmt::Display| {
+            OrchestratorError::Api(format!("{}: {}", path.display(), e))
+        };
+        let chain = std::fs::read(&self.certificate_chain).map_err(|e| tls_error(&self.certificate_chain, &e))?;
+        let certificates = rustls_pemfile::certs(&mut chain.as_slice())
+            .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+            .map_err(|e| tls_error(&self.certificate_chain, &e))?;
+        if certificates.is_empty() {
+            return Err(tls_error(&self.certificate_chain, &"no certificates"));
+        }
+        let key = zeroize::Zeroizing::new(std::fs::read(&self.private_key)
+            .map_err(|e| tls_error(&self.private_key, &e))?);
+        let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut key.as_slice())
+            .map_err(|e| tls_error(&self.private_key, &e))?
+            .ok_or_else(|| tls_error(&self.private_key, &"no private key"))?;
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certificates, key)
+            .map_err(|e| tls_error(&self.certificate_chain, &e))?;
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+fn default_max_skew() -> Duration {
+    Duration::from_secs(300)
+}
+
+fn default_max_body_bytes() -> usize {
+    32 * 1024 * 1024
+}
+
+fn default_max_nonces() -> usize {
+    65_536
+}
+
+impl ApiConfig {
+    /// Authorizer consulting only the configured static ACL
+    pub fn authorizer(&self) -> Authorizer {
+        Authorizer::static_only(self.acl.clone())
+    }
+}
+
+/// Body of `POST /v1/patches`
+#[derive(Debug, Deserialize)]
+pub struct SubmitRequest {
+    pub metadata: PatchMetadata,
+    /// Hex-encoded patch payload
+    pub patch_data: String,
+    #[serde(default)]
+    pub biblical_justification: Option<String>,
+}
+
+impl Validate for SubmitRequest {
+    fn validate(&self) -> Result<(), String> {
+        self.metadata.validate()?;
+        if let Some(justification) = &self.biblical_justification {
+            decode::check_len("biblical_justification", justification, crate::MAX_PATCH_TEXT_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+/// Query of `GET /v1/patches`
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// pending, applied or all
+    #[serde(rename = "type", default = "default_list_type")]
+    pub patch_type: String,
+}
+
+fn default_list_type() -> String {
+    "all".to_string()
+}
+
+/// Body of `POST /v1/keys/pins`
+#[derive(Debug, Default, Deserialize)]
+pub struct PinRequest {
+    /// Peer to pin, by default the release keys of the namespace
+    #[serde(default)]
+    pub peer: Option<String>,
+    /// Fingerprint to pin, by default the current release key fingerprint
+    #[serde(default)]
+    pub fingerprint: Option<KeyFingerprint>,
+}
+
+impl Validate for PinRequest {
+    fn validate(&self) -> Result<(), String> {
+        match &self.peer {
+            Some(peer) => decode::check_identifier("peer", peer, MAX_PEER_NAME),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    orchestrator: Arc<Mutex<PatchOrchestrator>>,
+    operators: Arc<HashMap<String, Ed25519VerifyingKey>>,
+    authorizer: Arc<Authorizer>,
+    nonces: Arc<std::sync::Mutex<NonceCache>>,
+    max_skew: Duration,
+    max_body_bytes: usize,
+}
+
+/// Nonces of signed requests whose timestamp is still accepted
+struct NonceCache {
+    /// Operator and nonce, with the request timestamp
+    seen: HashMap<(String, String), u64>,
+    capacity: usize,
+}
+
+/// Why a nonce was refused
+#[derive(Debug, PartialEq, Eq)]
+enum NonceRefusal {
+    Replayed,
+    Full,
+}
+
+impl NonceCache {
+    fn new(capacity: usize) -> Self {
+        Self { seen: HashMap::new(), capacity }
+    }
+
+    /// Remember `nonce` of `peer`, refusing one already seen
+    ///
+    /// Entries whose timestamp fell out of the skew window are forgotten
+    /// once the cache fills up; their requests are refused as stale anyway.
+    fn admit(&mut self, peer: &str, nonce: &str, timestamp: u64, now: u64, max_skew: Duration) -> Result<(), NonceRefusal> {
+        let key = (peer.to_string(), nonce.to_string());
+        if self.seen.contains_key(&key) {
+            return Err(NonceRefusal::Replayed);
+        }
+        if self.seen.len() >= self.capacity {
+            let oldest = now.saturating_sub(max_skew.as_secs());
+            self.seen.retain(|_, seen_at| *seen_at >= oldest);
+            if self.seen.len() >= self.capacity {
+                return Err(NonceRefusal::Full);
+            }
+        }
+        self.seen.insert(key, timestamp);
+        Ok(())
+    }
+}
+
+/// Operator whose request was authenticated and authorized
+#[derive(Debug, Clone)]
+struct Operator(String);
+
+/// Error answered with an `ErrorDocument`
+struct ApiError {
+    status: StatusCode,
+    report: ErrorReport,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            report: ErrorReport { code: code.to_string(), exit_code: EXIT_FAILURE, message: message.into() },
+        }
+    }
+}
+
+impl From<OrchestratorError> for ApiError {
+    fn from(error: OrchestratorError) -> Self {
+        Self { status: status_for(&error), report: ErrorReport::new(&error) }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(ErrorDocument { error: &self.report })).into_response()
+    }
+}
+
+/// HTTP status answering an orchestrator error
+fn status_for(error: &OrchestratorError) -> StatusCode {
+    match error {
+        OrchestratorError::PatchNotFound(_) | OrchestratorError::BackupNotFound(_) => StatusCode::NOT_FOUND,
+        OrchestratorError::ApprovalRequired { .. }
+        | OrchestratorError::DetachedApprovalRequired { .. }
+        | OrchestratorError::NotApproved(_)
+        | OrchestratorError::PatchConflict { .. }
+        | OrchestratorError::DuplicatePatch(_)
+        | OrchestratorError::RollbackRefused { .. } => StatusCode::CONFLICT,
+        OrchestratorError::PatchTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        e if e.exit_code() == crate::EXIT_MORAL_REJECTION || e.exit_code() == crate::EXIT_VERIFICATION_FAILURE => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Action named by a route, as used in `patch-api:<action>`
+fn action_for(method: &Method, route: &str) -> Option<&'static str> {
+    match (method.as_str(), route) {
+        ("GET", "/v1/status") => Some("status"),
+        ("GET", "/v1/patches") => Some("list"),
+        ("GET", "/v1/sbom") => Some("sbom"),
+        ("GET", "/v1/keys") => Some("keys"),
+        ("POST", "/v1/keys/pins") => Some("pin-key"),
+        ("POST", "/v1/patches") => Some("submit"),
+        ("POST", "/v1/patches/:id/approve") => Some("approve"),
+        ("POST", "/v1/patches/:id/apply") => Some("apply"),
+        ("POST", "/v1/patches/:id/rollback") => Some("rollback"),
+        _ => None,
+    }
+}
+
+/// Message an operator signs for a request
+pub fn signing_message(method: &str, path_and_query: &str, timestamp: u64, nonce: &str, body: &[u8]) -> Vec<u8> {
+    format!("{}\n{}\n{}\n{}\n{}", method, path_and_query, timestamp, nonce, blake3::hash(body).to_hex()).into_bytes()
+}
+
+/// Router serving the API over `orchestrator`
+pub fn router(
+    orchestrator: Arc<Mutex<PatchOrchestrator>>,
+    config: &ApiConfig,
+    authorizer: Authorizer,
+) -> Result<Router, OrchestratorError> {
+    let operators = config.operators.iter()
+        .map(|(id, key)| {
+            let bytes = hex::decode(key)
+                .map_err(|e| OrchestratorError::Api(format!("Key of operator {} is not hex: {}", id, e)))?;
+            let key = dalek::verifying_key_from_bytes(&bytes)
+                .map_err(|e| OrchestratorError::Api(format!("Key of operator {}: {}", id, e)))?;
+            Ok((id.clone(), key))
+        })
+        .collect::<Result<HashMap<_, _>, OrchestratorError>>()?;
+
+    let state = ApiState {
+        orchestrator,
+        operators: Arc::new(operators),
+        authorizer: Arc::new(authorizer),
+        nonces: Arc::new(std::sync::Mutex::new(NonceCache::new(config.max_nonces))),
+        max_skew: config.max_skew,
+        max_body_bytes: config.max_body_bytes,
+    };
+    Ok(Router::new()
+        .route("/v1/status", get(status))
+        .route("/v1/patches", get(list).post(submit))
+        .route("/v1/sbom", get(sbom))
+        .route("/v1/keys", get(keys))
+        .route("/v1/keys/pins", post(pin_key))
+        .route("/v1/patches/:id/approve", post(approve))
+        .route("/v1/patches/:id/apply", post(apply))
+        .route("/v1/patches/:id/rollback", post(rollback))
+        .route_layer(middleware::from_fn_with_state(state.clone(), guard))
+        .with_state(state))
+}
+
+/// Serve `router` over TLS on `bind_addr` until `shutdown` completes
+pub async fn serve(
+    router: Router,
+    bind_addr: SocketAddr,
+    acceptor: TlsAcceptor,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<(), OrchestratorError> {
+    let listener = tokio::net::TcpListener::bind(bind_addr).await
+        .map_err(|e| OrchestratorError::Api(format!("Failed to bind {}: {}", bind_addr, e)))?;
+    info!("Management API listening on {} (TLS)", bind_addr);
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, addr) = tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Management API accept failed: {}", e);
+                    continue;
+                }
+            },
+        };
+        let acceptor = acceptor.clone();
+        let router = router.clone().layer(Extension(ConnectInfo(addr)));
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("TLS handshake with {} failed: {}", addr, e);
+                    return;
+                }
+            };
+            let service = hyper::service::service_fn(move |request: Request<hyper::body::Incoming>| {
+                router.clone().call(request)
+            });
+            if let Err(e) = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                warn!("Management API connection from {} failed: {}", addr, e);
+            }
+        });
+    }
+}
+
+/// Authenticate, authorize and audit every request
+async fn guard(
+    State(state): State<ApiState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = request.into_parts();
+    let route = parts.extensions.get::<MatchedPath>().map(|path| path.as_str().to_string()).unwrap_or_default();
+    let action = action_for(&parts.method, &route).unwrap_or("unknown");
+    // Routes with an id name the patch as their third segment
+    let patch_id = if route.contains(":id") {
+        parts.uri.path().split('/').nth(3).unwrap_or_default().to_string()
+    } else {
+        String::new()
+    };
+
+    // Only an identity whose signature verified is ever recorded
+    let mut peer = UNAUTHENTICATED_PEER.to_string();
+    let response = match axum::body::to_bytes(body, state.max_body_bytes).await {
+        Err(_) => ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "body_too_large",
+                                format!("Request body exceeds {} bytes", state.max_body_bytes)).into_response(),
+        Ok(body) => match authenticate(&state, &parts.method, &parts.uri, &parts.headers, &body) {
+            Err(e) => e.into_response(),
+            Ok(authenticated) => {
+                peer = authenticated.clone();
+                match authorize(&state, authenticated, addr, action) {
+                    Err(e) => e.into_response(),
+                    Ok(operator) => {
+                        parts.extensions.insert(operator);
+                        next.run(Request::from_parts(parts, Body::from(body))).await
+                    }
+                }
+            }
+        },
+    };
+
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        warn!("API {} by {} from {} answered {}", action, peer, addr, status);
+    }
+    let event = AuditEvent::ApiRequest { peer, action: action.to_string(), status: status.as_u16() };
+    if let Err(e) = state.orchestrator.lock().await.audit_trail().record(&patch_id, AUDIT_COMPONENT, event) {
+        warn!("Failed to audit API request: {}", e);
+    }
+    response
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Operator id whose signature over the request verifies
+fn authenticate(
+    state: &ApiState,
+    method: &Method,
+    uri: &axum::http::Uri,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<String, ApiError> {
+    let unauthenticated = |message: &str| ApiError::new(StatusCode::UNAUTHORIZED, "unauthenticated", message);
+
+    let peer = header(headers, PEER_HEADER).ok_or_else(|| unauthenticated("Missing operator"))?;
+    let key = state.operators.get(peer).ok_or_else(|| unauthenticated("Unknown operator"))?;
+    let timestamp: u64 = header(headers, TIMESTAMP_HEADER)
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| unauthenticated("Missing or malformed timestamp"))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if now.abs_diff(timestamp) > state.max_skew.as_secs() {
+        return Err(unauthenticated("Request timestamp outside the accepted skew"));
+    }
+    let nonce = header(headers, NONCE_HEADER)
+        .filter(|nonce| (MIN_NONCE_LEN..=MAX_NONCE_LEN).contains(&nonce.len()))
+        .filter(|nonce| nonce.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'))
+        .ok_or_else(|| unauthenticated("Missing or malformed nonce"))?;
+
+    let signature = header(headers, SIGNATURE_HEADER)
+        .and_then(|value| hex::decode(value).ok())
+        .and_then(|bytes| dalek::signature_from_bytes(&bytes).ok())
+        .ok_or_else(|| unauthenticated("Missing or malformed signature"))?;
+    let path_and_query = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or_else(|| uri.path());
+    key.verify(&signing_message(method.as_str(), path_and_query, timestamp, nonce, body), &signature)
+        .map_err(|_| unauthenticated("Signature does not verify"))?;
+
+    // Only a verified request may take up room in the replay cache
+    let admitted = state.nonces.lock().unwrap_or_else(|e| e.into_inner())
+        .admit(peer, nonce, timestamp, now, state.max_skew);
+    match admitted {
+        Ok(()) => Ok(peer.to_string()),
+        Err(NonceRefusal::Replayed) => Err(unauthenticated("Replayed request")),
+        Err(NonceRefusal::Full) => Err(ApiError::new(StatusCode::SERVICE_UNAVAILABLE, "replay_cache_full",
+                                                     "Too many recent requests; retry later")),
+    }
+}
+
+/// Ask the sentinel authorizer whether `peer` may perform `action`
+fn authorize(state: &ApiState, peer: String, addr: SocketAddr, action: &str) -> Result<Operator, ApiError> {
+    let identity = PeerIdentity { id: peer, addr: addr.into() };
+    let decision = state.authorizer.authorize(&identity, &format!("{}{}", SERVICE_PREFIX, action));
+    if !decision.granted {
+        return Err(ApiError::new(StatusCode::FORBIDDEN, "forbidden", decision.reason));
+    }
+    Ok(Operator(identity.id))
+}
+
+async fn status(State(state): State<ApiState>) -> Json<SystemStatus> {
+    Json(state.orchestrator.lock().await.get_system_status())
+}
+
+async fn list(State(state): State<ApiState>, Query(query): Query<ListQuery>) -> Result<Json<serde_json::Value>, ApiError> {
+    if !["pending", "applied", "all"].contains(&query.patch_type.as_str()) {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "invalid_request",
+                                 format!("Unknown patch type {}", query.patch_type)));
+    }
+
+    let orchestrator = state.orchestrator.lock().await;
+    let result = ListResult {
+        pending: if query.patch_type != "applied" { orchestrator.pending_patches().collect() } else { vec![] },
+        applied: if query.patch_type != "pending" { orchestrator.applied_patches().collect() } else { vec![] },
+        patch_type: query.patch_type,
+    };
+    serde_json::to_value(&result)
+        .map(Json)
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "error", e.to_string()))
+}
+
+async fn sbom(State(state): State<ApiState>) -> Json<Bom> {
+    Json(state.orchestrator.lock().await.sbom())
+}
+
+async fn keys(State(state): State<ApiState>) -> Result<Json<KeyReport>, ApiError> {
+    Ok(Json(state.orchestrator.lock().await.key_report()?))
+}
+
+async fn pin_key(
+    State(state): State<ApiState>,
+    Extension(operator): Extension<Operator>,
+    body: Bytes,
+) -> Result<Json<PinResult>, ApiError> {
+    let request: PinRequest = decode::json_validated(&body, &DecodeLimits::new(state.max_body_bytes, 8))
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "invalid_request", e.to_string()))?;
+    info!("Operator {} pinning {} over the API", operator.0, request.peer.as_deref().unwrap_or("release keys"));
+    let orchestrator = state.orchestrator.lock().await;
+    Ok(Json(orchestrator.pin_key(request.peer.as_deref(), request.fingerprint)?))
+}
+
+async fn submit(
+    State(state): State<ApiState>,
+    Extension(operator): Extension<Operator>,
+    body: Bytes,
+) -> Result<Json<SubmitResult>, ApiError> {
+    let request: SubmitRequest = decode::json_validated(&body, &DecodeLimits::new(state.max_body_bytes, 32))
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "invalid_request", e.to_string()))?;
+    let patch_data = hex::decode(&request.patch_data)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "invalid_request", format!("patch_data: {}", e)))?;
+
+    let mut metadata = request.metadata;
+    if let Some(justification) = &request.biblical_justification {
+        metadata.biblical_justification = Some(justification.clone());
+    }
+    metadata.prepare_submission(Some(&patch_data));
+
+    info!("Operator {} submitting patch {} over the API", operator.0, metadata.id);
+    let patch_id = state.orchestrator.lock().await.submit_patch(&patch_data, metadata).await?;
+    Ok(Json(SubmitResult { patch_id, biblical_justification: request.biblical_justification.is_some() }))
+}
+
+async fn approve(
+    State(state): State<ApiState>,
+    Extension(operator): Extension<Operator>,
+    Path(patch_id): Path<String>,
+) -> Result<Json<ApproveResult>, ApiError> {
+    info!("Operator {} approving patch {} over the API", operator.0, patch_id);
+    state.orchestrator.lock().await.approve_patch(&patch_id)?;
+    Ok(Json(ApproveResult { patch_id, approved: true }))
+}
+
+async fn apply(
+    State(state): State<ApiState>,
+    Extension(operator): Extension<Operator>,
+    Path(patch_id): Path<String>,
+) -> Result<Json<ApplyResult>, ApiError> {
+    info!("Operator {} applying patch {} over the API", operator.0, patch_id);
+    state.orchestrator.lock().await.apply_patch(&patch_id).await?;
+    Ok(Json(ApplyResult { patch_id, applied: true }))
+}
+
+async fn rollback(
+    State(state): State<ApiState>,
+    Extension(operator): Extension<Operator>,
+    Path(patch_id): Path<String>,
+) -> Result<Json<RollbackResult>, ApiError> {
+    info!("Operator {} rolling back patch {} over the API", operator.0, patch_id);
+    let mut orchestrator = state.orchestrator.lock().await;
+    let component = orchestrator.applied_patches()
+        .find(|patch| patch.id == patch_id)
+        .map(|patch| patch.component.clone())
+        .ok_or_else(|| OrchestratorError::PatchNotFound(patch_id.clone()))?;
+    orchestrator.rollback_patch(&patch_id).await?;
+    Ok(Json(RollbackResult { patch_id, component, rolled_back: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::Signer as _;
+    use network_sentinel::AclRule;
+
+    fn state(acl: StaticAcl) -> (ApiState, ed25519_dalek::SigningKey, tempfile::TempDir) {
+        let key = dalek::signing_key_from_secret(&[5u8; 32]).unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut config = crate::OrchestratorConfig::for_tests(temp_dir.path());
+        config.require_biblical_justification = false;
+        let orchestrator = tokio::runtime::Runtime::new().unwrap().block_on(PatchOrchestrator::new(config)).unwrap();
+        let state = ApiState {
+            orchestrator: Arc::new(Mutex::new(orchestrator)),
+            operators: Arc::new(HashMap::from([("ops".to_string(), key.verifying_key())])),
+            authorizer: Arc::new(Authorizer::static_only(acl)),
+            nonces: Arc::new(std::sync::Mutex::new(NonceCache::new(default_max_nonces()))),
+            max_skew: default_max_skew(),
+            max_body_bytes: default_max_body_bytes(),
+        };
+        (state, key, temp_dir)
+    }
+
+    fn signed(
+        key: &ed25519_dalek::SigningKey,
+        method: &Method,
+        path: &str,
+        timestamp: u64,
+        nonce: &str,
+        body: &[u8],
+    ) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(PEER_HEADER, "ops".parse().unwrap());
+        headers.insert(TIMESTAMP_HEADER, timestamp.to_string().parse().unwrap());
+        headers.insert(NONCE_HEADER, nonce.parse().unwrap());
+        let signature = key.sign(&signing_message(method.as_str(), path, timestamp, nonce, body));
+        headers.insert(SIGNATURE_HEADER, hex::encode(signature.to_bytes()).parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_signed_requests_authenticated_and_authorized() {
+        let acl = StaticAcl {
+            rules: vec![AclRule { peer: "ops".to_string(), service: "patch-api:status".to_string(), allow: true }],
+            default_allow: false,
+        };
+        let (state, key, _dir) = state(acl);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let uri: axum::http::Uri = "/v1/patches/p-1/apply".parse().unwrap();
+
+        let headers = signed(&key, &Method::POST, "/v1/patches/p-1/apply", now, "nonce-0000000001", b"");
+        // Body, path and freshness are all covered by the signature
+        assert!(authenticate(&state, &Method::POST, &uri, &headers, b"tampered").is_err());
+        assert_eq!(authenticate(&state, &Method::POST, &uri, &headers, b"").ok().as_deref(), Some("ops"));
+        let stale = signed(&key, &Method::POST, "/v1/patches/p-1/apply", now - 3600, "nonce-0000000002", b"");
+        assert_eq!(authenticate(&state, &Method::POST, &uri, &stale, b"").err().unwrap().status, StatusCode::UNAUTHORIZED);
+
+        // A request is accepted once; a fresh nonce signs a new one
+        assert_eq!(authenticate(&state, &Method::POST, &uri, &headers, b"").err().unwrap().report.message, "Replayed request");
+        let again = signed(&key, &Method::POST, "/v1/patches/p-1/apply", now, "nonce-0000000003", b"");
+        assert!(authenticate(&state, &Method::POST, &uri, &again, b"").is_ok());
+        let mut unsigned = again.clone();
+        unsigned.remove(NONCE_HEADER);
+        assert!(authenticate(&state, &Method::POST, &uri, &unsigned, b"").is_err());
+
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        assert!(authorize(&state, "ops".to_string(), addr, "status").is_ok());
+        assert_eq!(authorize(&state, "ops".to_string(), addr, "apply").err().unwrap().status, StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_nonce_cache_refuses_when_full_of_fresh_nonces() {
+        let skew = Duration::from_secs(300);
+        let mut cache = NonceCache::new(2);
+        assert_eq!(cache.admit("ops", "a", 1_000, 1_000, skew), Ok(()));
+        assert_eq!(cache.admit("ops", "a", 1_000, 1_000, skew), Err(NonceRefusal::Replayed));
+        assert_eq!(cache.admit("audit", "a", 1_000, 1_000, skew), Ok(()));
+        assert_eq!(cache.admit("ops", "b", 1_000, 1_000, skew), Err(NonceRefusal::Full));
+        // Nonces whose timestamp left the skew window make room
+        assert_eq!(cache.admit("ops", "b", 1_400, 1_400, skew), Ok(()));
+    }
+
+    #[test]
+    fn test_routes_and_errors_mapped() {
+        assert_eq!(action_for(&Method::POST, "/v1/patches/:id/rollback"), Some("rollback"));
+        assert_eq!(action_for(&Method::DELETE, "/v1/patches"), None);
+        assert_eq!(status_for(&OrchestratorError::PatchNotFound("p".into())), StatusCode::NOT_FOUND);
+        assert_eq!(status_for(&OrchestratorError::MoralViolation("p".into())), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            status_for(&OrchestratorError::RollbackRefused { patch_id: "p".into(), reason: "r".into() }),
+            StatusCode::CONFLICT
+        );
+    }
+}
@@ -0,0 +1,368 @@
+//! Offline Patch Approval
+//!
+//! Air-gapped deployments approve patches on a separate signing workstation.
+//! The orchestrator exports an `ApprovalRequest` carrying a canonical digest of
+//! the patch; the workstation signs that digest and writes a detached
+//! `DetachedApproval` file, which is carried back and imported. Imported
+//! approvals are verified against the trust bundle of approver keys, and a
+//! patch only counts as approved once the required number of distinct
+//! approvers have signed. Verified approvals are saved with the patch
+//! store, so a restart does not lose them, and are verified again against
+//! the current patch before they count. Neither `apply_patch` nor
+//! `apply_release` installs a patch that is not approved. With approvals
+//! required, patches are never auto-applied.
+//!
+//! ## Biblical Foundation
+//! "At the mouth of two witnesses, or at the mouth of three witnesses,
+//! shall the matter be established" - Deuteronomy 19:15
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use blake3::Hash;
+use ed25519_dalek::{Signature as Ed25519Signature, SigningKey as Ed25519SigningKey};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes};
+use serde::{Deserialize, Serialize};
+
+use crate::{signing, OrchestratorError, PatchMetadata};
+
+/// Domain separator for approval digests
+pub const APPROVAL_DIGEST_DOMAIN: &str = "ark-patch-approval-v3";
+
+/// Most approvers accepted in a trust bundle
+pub const MAX_APPROVERS: usize = 256;
+
+/// File extension of detached approval files
+pub const APPROVAL_FILE_EXTENSION: &str = "approval";
+
+/// Detached approval requirements
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// Trust bundle of approver keys (JSON)
+    pub trust_bundle: Option<PathBuf>,
+    /// Distinct approvers required before a patch counts as approved;
+    /// 0 keeps interactive operator approval
+    pub required_approvals: usize,
+}
+
+/// Approver trusted to sign patch approvals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedApprover {
+    pub id: String,
+    /// Dilithium3 public key (hex)
+    pub dilithium_public: String,
+    /// Ed25519 public key (hex)
+    pub ed25519_public: String,
+}
+
+/// Set of approver keys trusted by this deployment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustBundle {
+    pub approvers: Vec<TrustedApprover>,
+}
+
+impl TrustBundle {
+    /// Load a trust bundle from a JSON file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Approval(format!("Trust bundle {:?}: {}", path, e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| OrchestratorError::Approval(format!("Trust bundle {:?}: {}", path, e)))
+    }
+
+    fn approver(&self, id: &str) -> Option<&TrustedApprover> {
+        self.approvers.iter().find(|approver| approver.id == id)
+    }
+}
+
+impl Validate for TrustBundle {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("approvers", self.approvers.len(), MAX_APPROVERS)?;
+        for approver in &self.approvers {
+            decode::check_identifier("approver id", &approver.id, crate::MAX_PATCH_FIELD_LENGTH)?;
+            // Hex keys: Dilithium3 public keys are under 2 KiB
+            decode::check_len("dilithium_public", &approver.dilithium_public, 8192)?;
+            decode::check_len("ed25519_public", &approver.ed25519_public, 64)?;
+        }
+        Ok(())
+    }
+}
+
+/// Request exported for signing on the offline workstation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub patch_id: String,
+    #[serde(default = "crate::namespace::default_namespace")]
+    pub namespace: String,
+    pub component: String,
+    pub version: String,
+    pub description: String,
+    /// Payload hash (hex)
+    pub payload_hash: String,
+    /// Canonical approval digest to sign (hex)
+    pub digest: String,
+}
+
+impl ApprovalRequest {
+    /// Build the request for a patch
+    pub fn for_patch(metadata: &PatchMetadata) -> Self {
+        Self {
+            patch_id: metadata.id.clone(),
+            namespace: metadata.namespace.clone(),
+            component: metadata.component.clone(),
+            version: metadata.version.clone(),
+            description: metadata.description.clone(),
+            payload_hash: metadata.hash.to_hex().to_string(),
+            digest: approval_digest(metadata).to_hex().to_string(),
+        }
+    }
+}
+
+/// Approval signature produced offline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetachedApproval {
+    pub patch_id: String,
+    /// Approval digest that was signed (hex)
+    pub digest: String,
+    pub approver: String,
+    pub signed_at: SystemTime,
+    pub pq_signature: DilithiumSignatureBytes,
+    pub classical_signature: Ed25519SignatureBytes,
+}
+
+impl Validate for DetachedApproval {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("patch_id", &self.patch_id, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_identifier("approver", &self.approver, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("digest", &self.digest, 64)
+    }
+}
+
+impl DetachedApproval {
+    /// Read a detached approval file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Approval(format!("{:?}: {}", path, e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| OrchestratorError::Approval(format!("{:?}: {}", path, e)))
+    }
+
+    /// Write a detached approval file
+    pub fn save(&self, path: &Path) -> Result<(), OrchestratorError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| OrchestratorError::Approval(e.to_string()))?;
+        std::fs::write(path, contents)
+            .map_err(|e| OrchestratorError::Approval(format!("{:?}: {}", path, e)))
+    }
+}
+
+/// Digest approvers sign
+///
+/// Covers the identity, content, criticality, files and expiry of the patch
+/// but not fields that change after submission (assessments, verification
+/// status, signatures); see `signing` for the canonical encoding hashed.
+pub fn approval_digest(metadata: &PatchMetadata) -> Hash {
+    blake3::hash(&metadata.approval_signing_bytes())
+}
+
+/// Sign an approval request on the offline workstation
+pub fn sign_approval(
+    request: &ApprovalRequest,
+    approver: &str,
+    pq_secret: &DilithiumSecretKeyBytes,
+    classical: &Ed25519SigningKey,
+) -> Result<DetachedApproval, OrchestratorError> {
+    use ed25519_dalek::Signer;
+
+    let message = signing::approval_message(&request.patch_id, &request.digest);
+    let pq_signature = Dilithium3::sign(&message, pq_secret)
+        .map_err(|e| OrchestratorError::Approval(e.to_string()))?;
+    let classical_signature = classical.sign(&message);
+
+    Ok(DetachedApproval {
+        patch_id: request.patch_id.clone(),
+        digest: request.digest.clone(),
+        approver: approver.to_string(),
+        signed_at: SystemTime::now(),
+        pq_signature,
+        classical_signature: Ed25519SignatureBytes::from_slice(&classical_signature.to_bytes())
+            .map_err(|e| OrchestratorError::Approval(e.to_string()))?,
+    })
+}
+
+/// Verify a detached approval for a patch against the trust bundle
+///
+/// Both the Dilithium3 and Ed25519 signatures must verify.
+pub fn verify_approval(
+    approval: &DetachedApproval,
+    metadata: &PatchMetadata,
+    bundle: &TrustBundle,
+) -> Result<(), OrchestratorError> {
+    if approval.patch_id != metadata.id {
+        return Err(OrchestratorError::Approval(
+            format!("Approval is for {}, not {}", approval.patch_id, metadata.id)
+        ));
+    }
+    let digest = approval_digest(metadata).to_hex().to_string();
+    if approval.digest != digest {
+        return Err(OrchestratorError::Approval(
+            format!("Approval digest does not match patch {}", metadata.id)
+        ));
+    }
+
+    let message = signing::approval_message(&approval.patch_id, &approval.digest);
+    verify_signature(bundle, &approval.approver, &message, &approval.pq_signature, &approval.classical_signature)
+        .map_err(OrchestratorError::Approval)
+}
+
+/// Verify an approver's hybrid signature over a message
+///
+/// Both the Dilithium3 and Ed25519 signatures must verify against the keys
+/// the trust bundle holds for the approver.
+pub(crate) fn verify_signature(
+    bundle: &TrustBundle,
+    approver_id: &str,
+    message: &[u8],
+    pq_signature: &DilithiumSignatureBytes,
+    classical_signature: &Ed25519SignatureBytes,
+) -> Result<(), String> {
+    use ed25519_dalek::Verifier;
+
+    let approver = bundle.approver(approver_id)
+        .ok_or_else(|| format!("Approver {} is not in the trust bundle", approver_id))?;
+    let key_error = |e: hex::FromHexError| format!("Approver {} key: {}", approver.id, e);
+    let dilithium_public = DilithiumPublicKeyBytes::from_vec(hex::decode(&approver.dilithium_public).map_err(key_error)?)
+        .map_err(|_| format!("Invalid dilithium3 key for {}", approver.id))?;
+    let ed25519_public = pq_types::dalek::verifying_key_from_bytes(&hex::decode(&approver.ed25519_public).map_err(key_error)?)
+        .map_err(|_| format!("Invalid ed25519 key for {}", approver.id))?;
+
+    Dilithium3::verify(message, pq_signature, &dilithium_public)
+        .map_err(|_| format!("Dilithium signature from {} is invalid", approver.id))?;
+
+    let classical_signature = Ed25519Signature::from_bytes(&classical_signature.clone().into());
+    ed25519_public.verify(message, &classical_signature)
+        .map_err(|_| format!("Ed25519 signature from {} is invalid", approver.id))?;
+
+    Ok(())
+}
+
+/// Verified detached approvals for one patch, keyed by approver
+pub type ApprovalSet = BTreeMap<String, DetachedApproval>;
+
+/// Approval sets per patch
+pub fn group_approvals(approvals: Vec<DetachedApproval>) -> HashMap<String, ApprovalSet> {
+    let mut sets: HashMap<String, ApprovalSet> = HashMap::new();
+    for approval in approvals {
+        sets.entry(approval.patch_id.clone()).or_default().insert(approval.approver.clone(), approval);
+    }
+    sets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CriticalityLevel, HarmAnalysis, PatchMorality, SignatureAlgorithm, VerificationStatus};
+    use cold_mirror::RiskLevel;
+
+    fn metadata(id: &str) -> PatchMetadata {
+        PatchMetadata {
+            id: id.to_string(),
+            version: "1.0.0".to_string(),
+            description: "Rule pack update".to_string(),
+            component: "ethics_dsl".to_string(),
+            criticality: CriticalityLevel::Medium,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"rules"),
+            size_bytes: 5,
+            dependencies: vec!["b".to_string(), "a".to_string()],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: crate::namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    fn approver(id: &str) -> (TrustedApprover, DilithiumSecretKeyBytes, Ed25519SigningKey) {
+        let (pq_public, pq_secret) = Dilithium3::keypair().unwrap();
+        let classical = Ed25519SigningKey::generate(&mut rand::rngs::OsRng);
+        let trusted = TrustedApprover {
+            id: id.to_string(),
+            dilithium_public: hex::encode(pq_public.as_bytes()),
+            ed25519_public: hex::encode(classical.verifying_key().to_bytes()),
+        };
+        (trusted, pq_secret, classical)
+    }
+
+    #[test]
+    fn test_digest_ignores_assessment_and_dependency_order() {
+        let patch = metadata("p-1");
+        let mut assessed = patch.clone();
+        assessed.moral_assessment = PatchMorality::Righteous;
+        assessed.dependencies.reverse();
+        assert_eq!(approval_digest(&patch), approval_digest(&assessed));
+
+        let mut altered = patch.clone();
+        altered.hash = blake3::hash(b"other rules");
+        assert_ne!(approval_digest(&patch), approval_digest(&altered));
+    }
+
+    #[test]
+    fn test_offline_approval_round_trip() {
+        let patch = metadata("p-2");
+        let (trusted, pq_secret, classical) = approver("alice");
+        let bundle = TrustBundle { approvers: vec![trusted] };
+
+        let request = ApprovalRequest::for_patch(&patch);
+        let approval = sign_approval(&request, "alice", &pq_secret, &classical).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("p-2.{}", APPROVAL_FILE_EXTENSION));
+        approval.save(&path).unwrap();
+        let imported = DetachedApproval::load(&path).unwrap();
+
+        assert!(verify_approval(&imported, &patch, &bundle).is_ok());
+        assert!(verify_approval(&imported, &metadata("p-3"), &bundle).is_err());
+    }
+
+    #[test]
+    fn test_untrusted_or_tampered_approval_rejected() {
+        let patch = metadata("p-4");
+        let (trusted, _, _) = approver("alice");
+        let (_, mallory_secret, mallory_classical) = approver("mallory");
+        let bundle = TrustBundle { approvers: vec![trusted] };
+        let request = ApprovalRequest::for_patch(&patch);
+
+        // Unknown approver
+        let forged = sign_approval(&request, "mallory", &mallory_secret, &mallory_classical).unwrap();
+        assert!(verify_approval(&forged, &patch, &bundle).is_err());
+
+        // Claims to be alice but signed with mallory's keys
+        let impersonated = DetachedApproval { approver: "alice".to_string(), ..forged };
+        assert!(matches!(
+            verify_approval(&impersonated, &patch, &bundle),
+            Err(OrchestratorError::Approval(ref m)) if m.contains("invalid")
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Patch Audit Trail
+//!
+//! Append-only record of decisions taken while applying patches, so that
+//! promotions, rejections and their evidence can be reviewed later. Records
+//! are JSON lines kept in sequence in the `audit` tree of the configured
+//! store (see `ark_storage`). Every record carries the namespace it was
+//! written for, and a trail only reads back records of its own namespace.
+//!
+//! ## Biblical Foundation
+//! "Write the vision; make it plain on tablets" - Habakkuk 2:2
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use ark_storage::{Batch, Storage};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use serde::{Deserialize, Serialize};
+
+use crate::{CriticalityLevel, OrchestratorError, PatchMorality};
+use crate::cold_mirror_patch::ShadowComparison;
+use crate::conflict::ConflictResolution;
+use crate::ethics_patch::EthicsPatchReport;
+
+/// Audit trail file name inside the patch directory, and of the trail in snapshots
+pub const AUDIT_TRAIL_FILE: &str = "audit_trail.jsonl";
+
+/// Tree of the audit records in the store
+pub const AUDIT_TREE: &str = "audit";
+
+/// Event recorded in the audit trail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AuditEvent {
+    /// Ethics rule pack validated against the replay corpus
+    RulePackValidated(EthicsPatchReport),
+    /// Cold-Mirror candidate compared against the active model
+    ShadowEvaluation(ShadowComparison),
+    /// Active role about to be handed to a new orchestrator binary, once it is installed as the live binary
+    OrchestratorHandoff { standby_pid: u32, binary_hash: String },
+    /// Detached offline approval verified and imported
+    ApprovalImported { approver: String, approvals: usize, required: usize },
+    /// Emergency strictness activated by a signed authorization
+    EmergencyActivated { authorization: String, reason: String, approvers: Vec<String>, expires_at: SystemTime },
+    /// Emergency authorization expired and strictness reverted
+    EmergencyExpired { authorization: String },
+    /// Patch assessed under emergency strictness
+    EmergencyAssessment { authorization: String, moral_assessment: PatchMorality, accepted: bool },
+    /// Operator override of concern rules activated by a signed claim
+    OverrideActivated { claim: String, operator: String, rules: Vec<String>, components: Vec<String>, reason: String, expires_at: SystemTime },
+    /// Patch assessed with concern rules relaxed by an override; `uses` counts this patch
+    OverrideUsed { claim: String, operator: String, rules: Vec<String>, uses: usize, budget: usize },
+    /// Patch overlaps pending patches it does not supersede
+    ConflictDetected { conflicts: Vec<String>, resolution: ConflictResolution },
+    /// Pending patch withdrawn in favor of one that supersedes it
+    PatchSuperseded { superseded_by: String },
+    /// Patch accepted for assessment
+    PatchSubmitted { criticality: CriticalityLevel },
+    /// Patch passed moral, harm and binary assessment
+    PatchAssessed,
+    /// Patch approved by an operator, detached approvals or auto-apply
+    PatchApproved { automatic: bool },
+    /// Auto-apply deferred until maintenance window `window` opens at `opens_at`
+    PatchDeferred { window: String, opens_at: Option<SystemTime> },
+    /// Divine patch auto-applied while its maintenance window `window` was closed
+    MaintenanceOverride { window: String },
+    /// Patch applied to its component
+    PatchApplied,
+    /// Patch refused; `reason` is the error code
+    PatchRejected { reason: String },
+    /// Failed application reverted from backup; `reason` is the error code,
+    /// or `operator` for a requested rollback
+    PatchRolledBack { reason: String },
+    /// Management API request and the HTTP status it was answered with
+    ApiRequest { peer: String, action: String, status: u16 },
+    /// Signed snapshot of the system state written; `digest` is the signed manifest digest
+    SnapshotCreated { digest: String, files: usize, audit_records: usize },
+    /// System state rehydrated from a verified snapshot
+    SnapshotRestored { digest: String, files: usize, audit_records: usize },
+    /// Signed release verified; its members are submitted next
+    ReleaseSubmitted { members: Vec<String> },
+    /// Every member of a release promoted
+    ReleaseApplied { members: Vec<String> },
+    /// Release abandoned at submission or rolled back at application;
+    /// `member` failed with error code `reason`
+    ReleaseFailed { member: String, reason: String },
+    /// Audit git hook let a change through despite blocking findings;
+    /// `user` is the committer identity and `findings` what would have blocked
+    AuditHookBypassed { hook: String, user: String, reason: String, findings: Vec<String> },
+    /// Re-identification of an actor pseudonym was requested; `granted` is
+    /// false when the operator was refused, `matched` when a candidate matched
+    ActorReidentified { pseudonym: String, requested_by: String, reason: String, granted: bool, matched: bool },
+    /// Store trees copied to another backend; `entries` counts the entries copied
+    StorageMigrated { from: String, to: String, trees: Vec<String>, entries: usize },
+}
+
+/// Single audit trail entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: SystemTime,
+    #[serde(default = "crate::namespace::default_namespace")]
+    pub namespace: String,
+    pub patch_id: String,
+    pub component: String,
+    pub event: AuditEvent,
+}
+
+impl Validate for AuditRecord {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("namespace", &self.namespace, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("patch_id", &self.patch_id, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("component", &self.component, crate::MAX_PATCH_FIELD_LENGTH)
+    }
+}
+
+/// Append-only audit trail
+#[derive(Debug, Clone)]
+pub struct AuditTrail {
+    storage: Arc<dyn Storage>,
+    namespace: String,
+}
+
+impl AuditTrail {
+    pub fn new(storage: Arc<dyn Storage>, namespace: &str) -> Self {
+        Self { storage, namespace: namespace.to_string() }
+    }
+
+    /// Append a record; it is durable once this returns
+    pub fn record(&self, patch_id: &str, component: &str, event: AuditEvent) -> Result<(), OrchestratorError> {
+        let record = AuditRecord {
+            timestamp: SystemTime::now(),
+            namespace: self.namespace.clone(),
+            patch_id: patch_id.to_string(),
+            component: component.to_string(),
+            event,
+        };
+
+        let line = serde_json::to_vec(&record)
+            .map_err(|e| OrchestratorError::AuditTrail(e.to_string()))?;
+        ark_storage::append(self.storage.as_ref(), AUDIT_TREE, &line)
+            .map(|_| ())
+            .map_err(|e| OrchestratorError::AuditTrail(e.to_string()))
+    }
+
+    /// Read all records of this trail's namespace, oldest first
+    pub fn records(&self) -> Result<Vec<AuditRecord>, OrchestratorError> {
+        self.lines()?
+            .iter()
+            .map(|line| decode::json_validated::<AuditRecord>(line, &DecodeLimits::RECORD)
+                .map_err(|e| OrchestratorError::AuditTrail(e.to_string())))
+            .filter(|record| record.as_ref().map_or(true, |record| record.namespace == self.namespace))
+            .collect()
+    }
+
+    /// Every record of the store, all namespaces, as JSON lines
+    pub fn export_lines(&self) -> Result<Vec<u8>, OrchestratorError> {
+        let mut exported = Vec::new();
+        for line in self.lines()? {
+            exported.extend_from_slice(&line);
+            exported.push(b'\n');
+        }
+        Ok(exported)
+    }
+
+    /// Replace every record of the store with the JSON lines of `trail`
+    pub fn replace_lines(&self, trail: &[u8]) -> Result<(), OrchestratorError> {
+        let mut batch = Batch::new();
+        for (key, _) in self.scan()? {
+            batch.delete(AUDIT_TREE, &key);
+        }
+        for (sequence, line) in trail.split(|b| *b == b'\n').filter(|line| !line.iter().all(u8::is_ascii_whitespace)).enumerate() {
+            batch.put(AUDIT_TREE, &ark_storage::sequence_key(sequence as u64), line);
+        }
+        self.storage.apply(&batch).map_err(|e| OrchestratorError::AuditTrail(e.to_string()))
+    }
+
+    /// Move the records of a JSON-lines trail written before the store into an
+    /// empty store, renaming the file aside; returns the records imported
+    pub fn import_legacy(&self, path: &Path) -> Result<usize, OrchestratorError> {
+        let trail = match std::fs::read(path) {
+            Ok(trail) => trail,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(OrchestratorError::AuditTrail(format!("{:?}: {}", path, e))),
+        };
+        if !self.scan()?.is_empty() {
+            return Err(OrchestratorError::AuditTrail(
+                format!("{:?} was not imported: the audit store already holds records", path)
+            ));
+        }
+        self.replace_lines(&trail)?;
+        std::fs::rename(path, path.with_extension("jsonl.imported"))
+            .map_err(|e| OrchestratorError::AuditTrail(format!("{:?}: {}", path, e)))?;
+        Ok(self.lines()?.len())
+    }
+
+    fn scan(&self) -> Result<Vec<(Vec<u8>, Vec<u8>)>, OrchestratorError> {
+        self.storage.scan(AUDIT_TREE, &[]).map_err(|e| OrchestratorError::AuditTrail(e.to_string()))
+    }
+
+    fn lines(&self) -> Result<Vec<Vec<u8>>, OrchestratorError> {
+        Ok(self.scan()?.into_iter().map(|(_, line)| line).collect())
+    }
+}
@@ -0,0 +1,563 @@
+//! ARK Soak Test
+//!
+//! Runs the network sentinel, the patch orchestrator, the ethics engine and
+//! the Cold-Mirror harm predictor together in one process for hours. Client
+//! tasks push echo traffic through the sentinel over sealed PQ channels,
+//! with every connection authorized by the ethics engine, while a patch
+//! task submits, applies and rolls back ethics rule packs at a fixed
+//! interval. A sampler checks the invariants unit tests cannot:
+//!
+//! - resident memory stays within `--max-growth-mb` of the level reached
+//!   after warm-up (Linux, read from `/proc/self/statm`);
+//! - no operation, and no lock on the orchestrator, takes longer than
+//!   `--stall-after` seconds, and traffic keeps making progress;
+//! - the audit trail stays append-only: every earlier byte is unchanged,
+//!   every record decodes, timestamps never go backwards and every patch
+//!   opens with its submission.
+//!
+//! The run stops early on the first violation. A JSON report is written to
+//! `--report` (stdout by default) and the exit code is 0 when every
+//! invariant held, 1 otherwise.
+//!
+//! ## Biblical Foundation
+//! "Let us not be weary in well doing: for in due season we shall reap, if we faint not" - Galatians 6:9
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use clap::{Arg, ArgMatches, Command};
+use ed25519_dalek::{Signer as _, SigningKey as Ed25519SigningKey};
+use network_sentinel::{AclRule, Authorizer, NetworkSentinel, PQTlsConfig, SentinelClient, SentinelConfig, StaticAcl};
+use pq_types::pins::PinStore;
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumSecretKeyBytes, Ed25519SignatureBytes};
+use serde::Serialize;
+use tokio::sync::{oneshot, Mutex};
+use tracing::{info, warn, Level};
+
+use patch_orchestrator::audit::{AuditEvent, AuditRecord, AuditTrail};
+use patch_orchestrator::{
+    CriticalityLevel, HarmAnalysis, OrchestratorConfig, PatchMetadata, PatchMorality, PatchOrchestrator,
+    SignatureAlgorithm, VerificationStatus, EXIT_FAILURE, EXIT_OK,
+};
+
+/// Service the traffic tasks call on the sentinel
+const SOAK_SERVICE: &str = "echo";
+
+/// Name the traffic tasks pin the sentinel's identity keys under
+const SOAK_SENTINEL: &str = "soak-sentinel";
+
+/// Counters shared by the load tasks
+#[derive(Default)]
+struct Counters {
+    exchanges: AtomicU64,
+    traffic_errors: AtomicU64,
+    patches_submitted: AtomicU64,
+    patches_applied: AtomicU64,
+    patches_rejected: AtomicU64,
+    patches_rolled_back: AtomicU64,
+    stalls: AtomicU64,
+}
+
+/// Resident memory observed by the sampler
+#[derive(Debug, Default, Serialize)]
+struct MemoryReport {
+    /// Resident bytes after warm-up, the level growth is measured from
+    baseline: Option<u64>,
+    peak: Option<u64>,
+    last: Option<u64>,
+    /// Allowed growth over the baseline
+    max_growth: u64,
+}
+
+/// Result of a soak run
+#[derive(Debug, Serialize)]
+struct SoakReport {
+    started_at: SystemTime,
+    requested: Duration,
+    elapsed: Duration,
+    samples: u64,
+    exchanges: u64,
+    traffic_errors: u64,
+    patches_submitted: u64,
+    patches_applied: u64,
+    patches_rejected: u64,
+    patches_rolled_back: u64,
+    stalls: u64,
+    audit_records: usize,
+    memory: MemoryReport,
+    /// Invariants that did not hold, in the order they were found
+    violations: Vec<String>,
+    passed: bool,
+}
+
+/// Soak parameters
+struct Settings {
+    duration: Duration,
+    warmup: Duration,
+    sample_interval: Duration,
+    patch_interval: Duration,
+    stall_after: Duration,
+    clients: usize,
+    max_growth: u64,
+    report: Option<PathBuf>,
+}
+
+impl Settings {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        let secs = |name: &str| Duration::from_secs(*matches.get_one::<u64>(name).unwrap());
+        Self {
+            duration: secs("duration"),
+            warmup: secs("warmup"),
+            sample_interval: secs("sample-interval"),
+            patch_interval: secs("patch-interval"),
+            stall_after: secs("stall-after"),
+            clients: *matches.get_one::<usize>("clients").unwrap(),
+            max_growth: matches.get_one::<u64>("max-growth-mb").unwrap() * 1024 * 1024,
+            report: matches.get_one::<String>("report").map(PathBuf::from),
+        }
+    }
+}
+
+fn build_cli() -> Command {
+    let seconds = |name: &'static str, default: &'static str, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .value_name("SECONDS")
+            .help(help)
+            .value_parser(clap::value_parser!(u64))
+            .default_value(default)
+    };
+    Command::new("ark-soak")
+        .about("Run the sentinel, orchestrator, ethics engine and Cold-Mirror together under load")
+        .arg(seconds("duration", "14400", "How long to run"))
+        .arg(seconds("warmup", "300", "Time before the memory baseline is taken"))
+        .arg(seconds("sample-interval", "30", "Time between invariant checks"))
+        .arg(seconds("patch-interval", "60", "Time between rule pack patches"))
+        .arg(seconds("stall-after", "60", "Longest an operation may take before it counts as a stall"))
+        .arg(Arg::new("clients")
+            .long("clients")
+            .value_name("N")
+            .help("Concurrent traffic clients")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("8"))
+        .arg(Arg::new("max-growth-mb")
+            .long("max-growth-mb")
+            .value_name("MB")
+            .help("Resident memory growth allowed over the post-warm-up baseline")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("64"))
+        .arg(Arg::new("report")
+            .short('o')
+            .long("report")
+            .value_name("FILE")
+            .help("Write the JSON report to FILE instead of stdout"))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_max_level(Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+    let settings = Settings::from_matches(&build_cli().get_matches());
+
+    match soak(&settings).await {
+        Ok(report) => {
+            let json = serde_json::to_string_pretty(&report).expect("soak report serializes");
+            let written = match &settings.report {
+                Some(path) => std::fs::write(path, json),
+                None => {
+                    println!("{}", json);
+                    Ok(())
+                }
+            };
+            if let Err(e) = written {
+                eprintln!("Failed to write soak report: {}", e);
+                return ExitCode::from(EXIT_FAILURE);
+            }
+            ExitCode::from(if report.passed { EXIT_OK } else { EXIT_FAILURE })
+        }
+        Err(e) => {
+            eprintln!("Soak test could not start: {}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+async fn soak(settings: &Settings) -> Result<SoakReport, Box<dyn std::error::Error>> {
+    let started_at = SystemTime::now();
+    let workspace = tempfile::tempdir()?;
+
+    // Sentinel authorizing every connection through the ethics engine
+    let engine = ethics_dsl::EthicsEngine::new(ethics_dsl::EthicsConfig::default())?;
+    let mut keys = PQTlsConfig::default();
+    keys.generate_keypairs()?;
+    let mut pins = PinStore::in_memory();
+    pins.pin(SOAK_SENTINEL, keys.identity_fingerprint().ok_or("sentinel has no identity keys")?)?;
+    let pins = Arc::new(pins);
+    let mut config = SentinelConfig {
+        bind_addr: "127.0.0.1:0".parse()?,
+        pq_tls_config: Arc::new(keys),
+        connection_timeout: settings.stall_after.as_secs(),
+        authorizer: Arc::new(Authorizer::new(Arc::new(engine), StaticAcl {
+            rules: vec![AclRule { peer: "*".into(), service: SOAK_SERVICE.into(), allow: true }],
+            default_allow: false,
+        })),
+        ..Default::default()
+    };
+    config.enable_secure_records();
+    let mut sentinel = NetworkSentinel::new(config);
+    sentinel.initialize().await?;
+    let addr = sentinel.local_addr().ok_or("sentinel has no local address")?;
+    let (stop_sentinel, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        sentinel.run_until(async {
+            let _ = stopped.await;
+        }).await
+    });
+
+    // Orchestrator with the Cold-Mirror harm predictor, patching a scratch rule pack
+    let signer = Arc::new(ReleaseSigner::generate()?);
+    let orchestrator = Arc::new(Mutex::new(PatchOrchestrator::new(orchestrator_config(workspace.path(), &signer)).await?));
+    let audit_trail = orchestrator.lock().await.audit_trail().clone();
+
+    let counters = Arc::new(Counters::default());
+    let deadline = Instant::now() + settings.duration;
+    let mut tasks = Vec::new();
+    for client in 0..settings.clients {
+        tasks.push(tokio::spawn(traffic(addr, pins.clone(), client, counters.clone(), deadline, settings.stall_after)));
+    }
+    tasks.push(tokio::spawn(patches(orchestrator.clone(), signer, counters.clone(), deadline, settings.patch_interval, settings.stall_after)));
+
+    // Check the invariants until the time is up or one fails
+    let mut violations = Vec::new();
+    let mut memory = MemoryReport { max_growth: settings.max_growth, ..Default::default() };
+    let mut audit = AuditWatch::default();
+    let mut samples = 0;
+    let mut last_exchanges = 0;
+    let begin = Instant::now();
+    while Instant::now() < deadline && violations.is_empty() {
+        tokio::time::sleep(settings.sample_interval.min(deadline.saturating_duration_since(Instant::now()))).await;
+        samples += 1;
+
+        if let Some(rss) = resident_bytes() {
+            memory.last = Some(rss);
+            memory.peak = Some(memory.peak.map_or(rss, |peak| peak.max(rss)));
+            match memory.baseline {
+                None if begin.elapsed() >= settings.warmup => memory.baseline = Some(rss),
+                Some(baseline) if rss > baseline + settings.max_growth => violations.push(format!(
+                    "resident memory grew to {} bytes, {} over the baseline of {}", rss, rss - baseline, baseline
+                )),
+                _ => {}
+            }
+        }
+
+        match tokio::time::timeout(settings.stall_after, orchestrator.lock()).await {
+            Ok(guard) => drop(guard),
+            Err(_) => violations.push(format!("orchestrator lock held for over {:?}", settings.stall_after)),
+        }
+
+        let exchanges = counters.exchanges.load(Ordering::Relaxed);
+        if exchanges == last_exchanges && settings.clients > 0 {
+            violations.push(format!("no traffic completed in {:?}", settings.sample_interval));
+        }
+        last_exchanges = exchanges;
+
+        let stalls = counters.stalls.load(Ordering::Relaxed);
+        if stalls > 0 {
+            violations.push(format!("{} operations took longer than {:?}", stalls, settings.stall_after));
+        }
+
+        if let Err(violation) = audit.check(&audit_trail) {
+            violations.push(violation);
+        }
+        info!("Soak sample {}: {} exchanges, {} audit records, {:?} resident bytes",
+              samples, exchanges, audit.records, memory.last);
+    }
+
+    for task in &tasks {
+        task.abort();
+    }
+    let _ = stop_sentinel.send(());
+    match tokio::time::timeout(settings.stall_after, server).await {
+        Ok(Ok(Ok(()))) => {}
+        Ok(Ok(Err(e))) => violations.push(format!("sentinel failed: {}", e)),
+        Ok(Err(e)) => violations.push(format!("sentinel task failed: {}", e)),
+        Err(_) => violations.push(format!("sentinel did not shut down within {:?}", settings.stall_after)),
+    }
+
+    let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+    Ok(SoakReport {
+        started_at,
+        requested: settings.duration,
+        elapsed: begin.elapsed(),
+        samples,
+        exchanges: load(&counters.exchanges),
+        traffic_errors: load(&counters.traffic_errors),
+        patches_submitted: load(&counters.patches_submitted),
+        patches_applied: load(&counters.patches_applied),
+        patches_rejected: load(&counters.patches_rejected),
+        patches_rolled_back: load(&counters.patches_rolled_back),
+        stalls: load(&counters.stalls),
+        audit_records: audit.records,
+        memory,
+        passed: violations.is_empty(),
+        violations,
+    })
+}
+
+/// Release keys the soak run signs its rule packs with
+struct ReleaseSigner {
+    dilithium: (Vec<u8>, DilithiumSecretKeyBytes),
+    ed25519: Ed25519SigningKey,
+}
+
+impl ReleaseSigner {
+    fn generate() -> Result<Self, Box<dyn std::error::Error>> {
+        let (public, secret) = Dilithium3::keypair()?;
+        Ok(Self {
+            dilithium: (public.as_bytes().to_vec(), secret),
+            ed25519: Ed25519SigningKey::generate(&mut rand::rngs::OsRng),
+        })
+    }
+
+    /// Public keys, by algorithm, for `OrchestratorConfig::signing_keys`
+    fn trusted_keys(&self) -> HashMap<String, Vec<u8>> {
+        HashMap::from([
+            ("dilithium3".to_string(), self.dilithium.0.clone()),
+            ("ed25519".to_string(), self.ed25519.verifying_key().as_bytes().to_vec()),
+        ])
+    }
+
+    /// Sign `metadata` with both release keys
+    fn sign(&self, metadata: &mut PatchMetadata) -> Result<(), Box<dyn std::error::Error>> {
+        metadata.signature_algorithm = SignatureAlgorithm::HybridEd25519Dilithium3;
+        let bytes = metadata.signing_bytes();
+        metadata.pq_signature = Some(Dilithium3::sign(&bytes, &self.dilithium.1)?.into());
+        metadata.classical_signature = Some(Ed25519SignatureBytes::from_slice(&self.ed25519.sign(&bytes).to_bytes())?);
+        Ok(())
+    }
+}
+
+fn orchestrator_config(workspace: &Path, signer: &ReleaseSigner) -> OrchestratorConfig {
+    let mut config = OrchestratorConfig {
+        patch_directory: workspace.join("patches"),
+        staging_directory: workspace.join("staging"),
+        backup_directory: workspace.join("backups"),
+        max_patch_size: 1024 * 1024,
+        verification_timeout: Duration::from_secs(30),
+        auto_apply_threshold: CriticalityLevel::Divine,
+        require_biblical_justification: true,
+        signing_keys: signer.trusted_keys(),
+        moral_strictness: patch_orchestrator::MoralStrictness::Standard,
+        ethics_patch_policy: Default::default(),
+        shadow_policy: Default::default(),
+        handoff_policy: Default::default(),
+        ingest_limits: Default::default(),
+        approval_policy: Default::default(),
+        binary_audit: Default::default(),
+        emergency_policy: Default::default(),
+        persona_policy: Default::default(),
+        conflict_policy: Default::default(),
+        namespace: patch_orchestrator::namespace::default_namespace(),
+        namespaces: HashMap::new(),
+        slo: Default::default(),
+        pinned_keys: None,
+        crash_reports: Default::default(),
+        storage: Default::default(),
+        alerts: Default::default(),
+        transparency: None,
+        maintenance: Default::default(),
+    };
+    config.ethics_patch_policy.live_rule_pack = workspace.join("rules").join("live.ethics");
+    config
+}
+
+/// Push echo records through the sentinel until `deadline`
+async fn traffic(addr: SocketAddr, pins: Arc<PinStore>, client: usize, counters: Arc<Counters>, deadline: Instant, stall_after: Duration) {
+    let mut round = 0u64;
+    while Instant::now() < deadline {
+        round += 1;
+        let exchange = async {
+            let mut client = SentinelClient::new(true)
+                .with_service(format!("soak-{}", client), SOAK_SERVICE)
+                .with_secure_records()
+                .with_pins(SOAK_SENTINEL, pins.clone());
+            let mut channel = client.connect_secure(addr).await?;
+            // Records of varying size so buffers are resized as well as reused
+            let record = vec![(round % 251) as u8; 64 + (round as usize * 97) % 16384];
+            channel.send(&record).await?;
+            let echoed = channel.recv().await?;
+            channel.shutdown().await?;
+            Ok::<bool, network_sentinel::SentinelError>(echoed.as_deref() == Some(&record[..]))
+        };
+        match tokio::time::timeout(stall_after, exchange).await {
+            Ok(Ok(true)) => counters.exchanges.fetch_add(1, Ordering::Relaxed),
+            Ok(Ok(false)) => counters.traffic_errors.fetch_add(1, Ordering::Relaxed),
+            Ok(Err(e)) => {
+                warn!("Soak client {} exchange failed: {}", client, e);
+                counters.traffic_errors.fetch_add(1, Ordering::Relaxed)
+            }
+            Err(_) => counters.stalls.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+}
+
+/// Submit, apply and roll back a rule pack every `interval` until `deadline`
+async fn patches(
+    orchestrator: Arc<Mutex<PatchOrchestrator>>,
+    signer: Arc<ReleaseSigner>,
+    counters: Arc<Counters>,
+    deadline: Instant,
+    interval: Duration,
+    stall_after: Duration,
+) {
+    let mut sequence = 0u64;
+    let mut applied: Option<String> = None;
+    while Instant::now() < deadline {
+        tokio::time::sleep(interval).await;
+        sequence += 1;
+        let cycle = async {
+            let mut orchestrator = orchestrator.lock().await;
+            // Every third cycle rolls the last applied pack back instead
+            if sequence % 3 == 0 {
+                if let Some(patch_id) = applied.take() {
+                    if orchestrator.rollback_patch(&patch_id).await.is_ok() {
+                        counters.patches_rolled_back.fetch_add(1, Ordering::Relaxed);
+                    }
+                    return;
+                }
+            }
+
+            let rules = format!("rule soak_{}\n", sequence);
+            let mut metadata = patch_metadata(&format!("soak-{:06}", sequence), rules.as_bytes());
+            if let Err(e) = signer.sign(&mut metadata) {
+                warn!("Soak patch {} could not be signed: {}", sequence, e);
+                return;
+            }
+            counters.patches_submitted.fetch_add(1, Ordering::Relaxed);
+            let result = match orchestrator.submit_patch(rules.as_bytes(), metadata).await {
+                Ok(patch_id) => match orchestrator.approve_patch(&patch_id) {
+                    Ok(()) => orchestrator.apply_patch(&patch_id).await.map(|_| patch_id),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(patch_id) => {
+                    counters.patches_applied.fetch_add(1, Ordering::Relaxed);
+                    applied = Some(patch_id);
+                }
+                Err(e) => {
+                    info!("Soak patch {} not applied: {}", sequence, e);
+                    counters.patches_rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        };
+        if tokio::time::timeout(stall_after, cycle).await.is_err() {
+            counters.stalls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn patch_metadata(id: &str, patch_data: &[u8]) -> PatchMetadata {
+    let mut metadata = PatchMetadata {
+        id: id.to_string(),
+        version: "1.0.0".to_string(),
+        description: "Soak test rule pack that helps protect humanity".to_string(),
+        component: "ethics_dsl".to_string(),
+        criticality: CriticalityLevel::Medium,
+        moral_assessment: PatchMorality::Pending,
+        verification: VerificationStatus::Pending,
+        hash: blake3::hash(patch_data),
+        size_bytes: patch_data.len() as u64,
+        dependencies: vec![],
+        biblical_justification: Some("Matthew 22:39 - Love your neighbor as yourself".to_string()),
+        harm_analysis: HarmAnalysis {
+            moral_harm_risk: cold_mirror::RiskLevel::Low,
+            physical_harm_risk: cold_mirror::RiskLevel::Low,
+            psychological_harm_risk: cold_mirror::RiskLevel::Low,
+            spiritual_harm_risk: cold_mirror::RiskLevel::Low,
+            system_integrity_risk: cold_mirror::RiskLevel::Low,
+            overall_risk: cold_mirror::RiskLevel::Low,
+            mitigation_required: false,
+            biblical_concerns: vec![],
+            overridden_concerns: vec![],
+        },
+        created_at: SystemTime::now(),
+        expires_at: None,
+        pq_signature: None,
+        classical_signature: None,
+        signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+        security_issues: vec![],
+        namespace: patch_orchestrator::namespace::default_namespace(),
+        files: vec![],
+        supersedes: vec![],
+        emergency_scope: None,
+    };
+    metadata.prepare_submission(Some(patch_data));
+    metadata
+}
+
+/// Append-only check of the audit trail between samples
+#[derive(Default)]
+struct AuditWatch {
+    /// Bytes seen so far and their hash
+    len: usize,
+    digest: Option<blake3::Hash>,
+    records: usize,
+}
+
+impl AuditWatch {
+    fn check(&mut self, trail: &AuditTrail) -> Result<(), String> {
+        let bytes = trail.export_lines().map_err(|e| format!("audit trail unreadable: {}", e))?;
+        if bytes.len() < self.len || self.digest.is_some_and(|digest| blake3::hash(&bytes[..self.len]) != digest) {
+            return Err(format!("audit trail rewritten: first {} bytes changed", self.len));
+        }
+
+        let mut records = 0;
+        let mut previous: Option<SystemTime> = None;
+        let mut submitted = std::collections::HashSet::new();
+        for (index, line) in bytes.split(|b| *b == b'\n').enumerate().filter(|(_, line)| !line.is_empty()) {
+            let record: AuditRecord = pq_types::decode::json_validated(line, &pq_types::decode::DecodeLimits::RECORD)
+                .map_err(|e| format!("audit record {} does not decode: {}", index + 1, e))?;
+            if previous.is_some_and(|previous| record.timestamp < previous) {
+                return Err(format!("audit record {} is older than the one before it", index + 1));
+            }
+            previous = Some(record.timestamp);
+            match &record.event {
+                AuditEvent::PatchSubmitted { .. } => {
+                    submitted.insert(record.patch_id.clone());
+                }
+                AuditEvent::PatchAssessed
+                | AuditEvent::PatchApproved { .. }
+                | AuditEvent::PatchApplied
+                | AuditEvent::PatchRejected { .. }
+                    if !submitted.contains(&record.patch_id) =>
+                {
+                    return Err(format!("audit record {} for {} precedes its submission", index + 1, record.patch_id));
+                }
+                _ => {}
+            }
+            records += 1;
+        }
+
+        self.len = bytes.len();
+        self.digest = Some(blake3::hash(&bytes));
+        self.records = records;
+        Ok(())
+    }
+}
+
+/// Resident set size of this process
+fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
@@ -0,0 +1,356 @@
+//! Cold-Mirror Model Patching
+//!
+//! Candidate harm-prediction models are never promoted blind. The candidate is
+//! loaded next to the active model and both are run in shadow over live
+//! traffic (or a replay corpus) for a bounded window. Agreement, latency and
+//! harm-category drift are compared against policy and the candidate is
+//! promoted or rejected automatically.
+//!
+//! ## Biblical Foundation
+//! "By their fruit you will recognize them" - Matthew 7:16
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use cold_mirror::{HarmCategory, HarmPrediction, HarmPredictor, PredictionInput};
+
+use crate::OrchestratorError;
+use crate::ethics_patch::DecisionKind;
+
+/// Harm predictor that can be shared with the shadow evaluator
+pub type SharedPredictor = Box<dyn HarmPredictor + Send + Sync>;
+
+/// Loads a harm predictor from a model file
+pub trait PredictorLoader: Send + Sync {
+    fn load(&self, model_path: &Path) -> Result<SharedPredictor, OrchestratorError>;
+}
+
+/// Policy for Cold-Mirror model patches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowPolicy {
+    /// Model file used by the active predictor
+    pub active_model: PathBuf,
+    /// JSON-lines file of `PredictionInput`s used when no live traffic is attached
+    pub replay_corpus: Option<PathBuf>,
+    /// Maximum wall-clock duration of the shadow window
+    pub window: Duration,
+    /// Maximum number of inputs evaluated in the window
+    pub max_samples: usize,
+    /// Minimum samples for a verdict; fewer rejects the candidate
+    pub min_samples: usize,
+    /// Minimum fraction of inputs on which both models recommend the same decision
+    pub min_agreement: f64,
+    /// Maximum candidate/active mean latency ratio
+    pub max_latency_ratio: f64,
+    /// Maximum total variation distance between harm-category distributions
+    pub max_category_drift: f64,
+}
+
+impl Default for ShadowPolicy {
+    fn default() -> Self {
+        Self {
+            active_model: PathBuf::from("software/cold_mirror/models/active.model"),
+            replay_corpus: None,
+            window: Duration::from_secs(300),
+            max_samples: 10_000,
+            min_samples: 100,
+            min_agreement: 0.95,
+            max_latency_ratio: 1.5,
+            max_category_drift: 0.1,
+        }
+    }
+}
+
+/// Metrics gathered while shadowing the active model
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowComparison {
+    pub samples: usize,
+    pub agreements: usize,
+    pub active_latency_total: Duration,
+    pub candidate_latency_total: Duration,
+    pub active_categories: HashMap<String, usize>,
+    pub candidate_categories: HashMap<String, usize>,
+    pub candidate_errors: usize,
+    pub verdict: Option<ShadowVerdict>,
+}
+
+/// Promotion decision for a candidate model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ShadowVerdict {
+    Promote,
+    Reject { reasons: Vec<String> },
+}
+
+impl ShadowComparison {
+    /// Record predictions of both models for one input
+    pub fn record(
+        &mut self,
+        active: &HarmPrediction,
+        active_latency: Duration,
+        candidate: &HarmPrediction,
+        candidate_latency: Duration,
+    ) {
+        self.samples += 1;
+        self.active_latency_total += active_latency;
+        self.candidate_latency_total += candidate_latency;
+
+        let active_kind = DecisionKind::from(&cold_mirror::utils::to_ethics_decision(active));
+        let candidate_kind = DecisionKind::from(&cold_mirror::utils::to_ethics_decision(candidate));
+        if active_kind == candidate_kind {
+            self.agreements += 1;
+        }
+
+        for category in &active.harm_categories {
+            *self.active_categories.entry(category_name(category).to_string()).or_insert(0) += 1;
+        }
+        for category in &candidate.harm_categories {
+            *self.candidate_categories.entry(category_name(category).to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Fraction of inputs on which both models agree
+    pub fn agreement_rate(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.agreements as f64 / self.samples as f64
+        }
+    }
+
+    /// Candidate mean latency relative to the active model
+    pub fn latency_ratio(&self) -> f64 {
+        let active = self.active_latency_total.as_secs_f64();
+        if active == 0.0 {
+            1.0
+        } else {
+            self.candidate_latency_total.as_secs_f64() / active
+        }
+    }
+
+    /// Total variation distance between harm-category distributions
+    pub fn category_drift(&self) -> f64 {
+        let active_total: usize = self.active_categories.values().sum();
+        let candidate_total: usize = self.candidate_categories.values().sum();
+        if active_total == 0 && candidate_total == 0 {
+            return 0.0;
+        }
+
+        let share = |counts: &HashMap<String, usize>, total: usize, name: &str| {
+            if total == 0 {
+                0.0
+            } else {
+                *counts.get(name).unwrap_or(&0) as f64 / total as f64
+            }
+        };
+
+        let names: std::collections::HashSet<&String> = self.active_categories.keys()
+            .chain(self.candidate_categories.keys())
+            .collect();
+        names.into_iter()
+            .map(|name| (share(&self.active_categories, active_total, name)
+                - share(&self.candidate_categories, candidate_total, name)).abs())
+            .sum::<f64>() / 2.0
+    }
+
+    /// Apply the promotion policy
+    pub fn evaluate(&self, policy: &ShadowPolicy) -> ShadowVerdict {
+        let mut reasons = Vec::new();
+
+        if self.samples < policy.min_samples {
+            reasons.push(format!("only {} samples, {} required", self.samples, policy.min_samples));
+        }
+        if self.candidate_errors > 0 {
+            reasons.push(format!("{} candidate inference errors", self.candidate_errors));
+        }
+        if self.agreement_rate() < policy.min_agreement {
+            reasons.push(format!("agreement {:.3} below {:.3}", self.agreement_rate(), policy.min_agreement));
+        }
+        if self.latency_ratio() > policy.max_latency_ratio {
+            reasons.push(format!("latency ratio {:.2} above {:.2}", self.latency_ratio(), policy.max_latency_ratio));
+        }
+        if self.category_drift() > policy.max_category_drift {
+            reasons.push(format!("category drift {:.3} above {:.3}", self.category_drift(), policy.max_category_drift));
+        }
+
+        if reasons.is_empty() {
+            ShadowVerdict::Promote
+        } else {
+            ShadowVerdict::Reject { reasons }
+        }
+    }
+}
+
+/// Stable name of a harm category, ignoring its parameters
+pub fn category_name(category: &HarmCategory) -> &str {
+    cold_mirror::policy::category_name(category)
+}
+
+/// Source of inputs for a shadow window
+pub enum ShadowSource<'a> {
+    /// Live traffic mirrored to the orchestrator
+    Live(&'a mut mpsc::Receiver<PredictionInput>),
+    /// Recorded inputs
+    Replay(Vec<PredictionInput>),
+}
+
+/// Load a replay corpus of prediction inputs
+pub fn load_shadow_corpus(path: &Path) -> Result<Vec<PredictionInput>, OrchestratorError> {
+    let file = File::open(path)
+        .map_err(|e| OrchestratorError::Staging(format!("Shadow corpus {:?}: {}", path, e)))?;
+
+    let mut inputs = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match decode::json::<PredictionInput>(line.as_bytes(), &DecodeLimits::RECORD) {
+            Ok(input) => inputs.push(input),
+            Err(e) => warn!("Skipping shadow corpus line {}: {}", line_number + 1, e),
+        }
+    }
+    Ok(inputs)
+}
+
+/// Run both models over the shadow window and compare them
+pub async fn run_shadow_window(
+    active: &(dyn HarmPredictor + Send + Sync),
+    candidate: &(dyn HarmPredictor + Send + Sync),
+    source: ShadowSource<'_>,
+    policy: &ShadowPolicy,
+) -> ShadowComparison {
+    let mut comparison = ShadowComparison::default();
+    let deadline = Instant::now() + policy.window;
+
+    let mut shadow = |input: &PredictionInput, comparison: &mut ShadowComparison| {
+        let started = Instant::now();
+        let active_prediction = active.predict_harm(input);
+        let active_latency = started.elapsed();
+
+        let started = Instant::now();
+        let candidate_prediction = candidate.predict_harm(input);
+        let candidate_latency = started.elapsed();
+
+        match (active_prediction, candidate_prediction) {
+            (Ok(a), Ok(c)) => comparison.record(&a, active_latency, &c, candidate_latency),
+            (Ok(_), Err(e)) => {
+                warn!("Candidate model failed during shadow inference: {}", e);
+                comparison.candidate_errors += 1;
+            }
+            // Inputs the active model cannot handle say nothing about the candidate
+            (Err(_), _) => {}
+        }
+    };
+
+    match source {
+        ShadowSource::Live(receiver) => {
+            while comparison.samples < policy.max_samples {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match tokio::time::timeout(remaining, receiver.recv()).await {
+                    Ok(Some(input)) => shadow(&input, &mut comparison),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+        }
+        ShadowSource::Replay(inputs) => {
+            for input in inputs.iter().take(policy.max_samples) {
+                if Instant::now() >= deadline {
+                    break;
+                }
+                shadow(input, &mut comparison);
+            }
+        }
+    }
+
+    comparison.verdict = Some(comparison.evaluate(policy));
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use cold_mirror::{MonitoringLevel, RecommendedAction};
+
+    fn prediction(block: bool, categories: Vec<HarmCategory>) -> HarmPrediction {
+        HarmPrediction {
+            harm_level: if block { 0.9 } else { 0.1 },
+            confidence: 0.9,
+            time_horizon: 24.0,
+            harm_categories: categories,
+            risk_factors: vec![],
+            recommended_action: if block {
+                RecommendedAction::Block { reason: "harm".to_string(), duration: None }
+            } else {
+                RecommendedAction::AllowWithMonitoring {
+                    monitoring_level: MonitoringLevel::Basic,
+                    review_interval: 24.0,
+                }
+            },
+            timestamp: Utc::now(),
+            model_version: "test".to_string(),
+            policy_version: None,
+        }
+    }
+
+    fn moral() -> HarmCategory {
+        HarmCategory::MoralDegradation { violation: "deception".to_string(), severity: 0.5 }
+    }
+
+    fn spiritual() -> HarmCategory {
+        HarmCategory::SpiritualHarm {
+            principle: "truth".to_string(),
+            scripture_reference: "John 8:32".to_string(),
+            eternal_impact: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_identical_models_are_promoted() {
+        let policy = ShadowPolicy { min_samples: 10, ..Default::default() };
+        let mut comparison = ShadowComparison::default();
+        for i in 0..10 {
+            let p = prediction(i % 2 == 0, vec![moral()]);
+            comparison.record(&p, Duration::from_millis(2), &p, Duration::from_millis(2));
+        }
+
+        assert_eq!(comparison.agreement_rate(), 1.0);
+        assert_eq!(comparison.category_drift(), 0.0);
+        assert_eq!(comparison.evaluate(&policy), ShadowVerdict::Promote);
+    }
+
+    #[test]
+    fn test_disagreement_drift_and_latency_reject() {
+        let policy = ShadowPolicy { min_samples: 4, ..Default::default() };
+        let mut comparison = ShadowComparison::default();
+        for _ in 0..4 {
+            comparison.record(
+                &prediction(true, vec![moral()]),
+                Duration::from_millis(1),
+                &prediction(false, vec![spiritual()]),
+                Duration::from_millis(5),
+            );
+        }
+
+        assert_eq!(comparison.agreement_rate(), 0.0);
+        assert_eq!(comparison.category_drift(), 1.0);
+        match comparison.evaluate(&policy) {
+            ShadowVerdict::Reject { reasons } => assert_eq!(reasons.len(), 3),
+            ShadowVerdict::Promote => panic!("divergent candidate must be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_too_few_samples_reject() {
+        let comparison = ShadowComparison::default();
+        assert!(matches!(comparison.evaluate(&ShadowPolicy::default()), ShadowVerdict::Reject { .. }));
+    }
+}
@@ -0,0 +1,192 @@
+//! Patch Conflict Detection
+//!
+//! Two pending patches that write the same files would race: whichever is
+//! applied second silently overwrites the first, and a rollback of either
+//! restores a backup that may already contain the other. At submit time the
+//! footprint of each patch - the component directory, narrowed to the files
+//! listed in its metadata - is compared with the footprints of the patches
+//! already pending. A conflicting patch either names the patches it replaces
+//! in `supersedes`, which withdraws them, or is resolved per the configured
+//! policy: queued behind them so application is serialized in submission
+//! order, or rejected.
+//!
+//! ## Biblical Foundation
+//! "Let all things be done decently and in order" - 1 Corinthians 14:40
+
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::PatchMetadata;
+
+/// How a patch overlapping pending patches it does not supersede is handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Queue the patch until every overlapping patch has been applied or withdrawn
+    #[default]
+    Serialize,
+    /// Reject the patch unless it supersedes every overlapping patch
+    RequireSupersedes,
+}
+
+/// Conflict detection settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConflictPolicy {
+    pub resolution: ConflictResolution,
+}
+
+/// Paths a patch writes when applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footprint {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Footprint {
+    /// Footprint of `metadata` applied under `component_dir`
+    ///
+    /// A patch without a file list is taken to rewrite the whole component.
+    pub fn of(metadata: &PatchMetadata, component_dir: &Path) -> Self {
+        let root = normalize(component_dir);
+        let paths = if metadata.files.is_empty() {
+            BTreeSet::from([root])
+        } else {
+            metadata.files.iter().map(|file| root.join(normalize(Path::new(file)))).collect()
+        };
+        Self { paths }
+    }
+
+    /// Whether the two footprints share a path, or one contains a path of the other
+    pub fn overlaps(&self, other: &Footprint) -> bool {
+        self.paths.iter().any(|a| other.paths.iter().any(|b| a.starts_with(b) || b.starts_with(a)))
+    }
+}
+
+/// Drop `.` components so equal paths compare equal
+fn normalize(path: &Path) -> PathBuf {
+    path.components().filter(|component| !matches!(component, Component::CurDir)).collect()
+}
+
+/// Check that listed files stay inside the component directory
+pub fn check_files(files: &[String]) -> Result<(), String> {
+    for file in files {
+        let path = Path::new(file);
+        if file.is_empty() || path.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+            return Err(format!("files: {:?} is not a relative path inside the component", file));
+        }
+    }
+    Ok(())
+}
+
+/// Pending patches overlapping the candidate, split by whether it supersedes them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Conflicts {
+    /// Overlapping patches the candidate replaces
+    pub superseded: Vec<String>,
+    /// Overlapping patches the candidate does not replace
+    pub unresolved: Vec<String>,
+}
+
+/// Compare a candidate footprint with those of pending patches
+pub fn detect<'a>(
+    candidate: &PatchMetadata,
+    footprint: &Footprint,
+    pending: impl IntoIterator<Item = (&'a PatchMetadata, Footprint)>,
+) -> Conflicts {
+    let mut conflicts = Conflicts::default();
+    for (metadata, other) in pending {
+        if metadata.id == candidate.id || !footprint.overlaps(&other) {
+            continue;
+        }
+        if candidate.supersedes.contains(&metadata.id) {
+            conflicts.superseded.push(metadata.id.clone());
+        } else {
+            conflicts.unresolved.push(metadata.id.clone());
+        }
+    }
+    conflicts.superseded.sort();
+    conflicts.unresolved.sort();
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CriticalityLevel, HarmAnalysis, PatchMorality, SignatureAlgorithm, VerificationStatus};
+    use cold_mirror::RiskLevel;
+    use std::time::SystemTime;
+
+    fn metadata(id: &str, files: &[&str]) -> PatchMetadata {
+        PatchMetadata {
+            id: id.to_string(),
+            version: "1.0.0".to_string(),
+            description: "Rule pack update".to_string(),
+            component: "ethics_dsl".to_string(),
+            criticality: CriticalityLevel::Medium,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(id.as_bytes()),
+            size_bytes: 5,
+            dependencies: vec![],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: crate::namespace::default_namespace(),
+            files: files.iter().map(|file| file.to_string()).collect(),
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    fn footprint(files: &[&str]) -> Footprint {
+        Footprint::of(&metadata("p", files), Path::new("software/ethics_dsl/"))
+    }
+
+    #[test]
+    fn test_footprint_overlap() {
+        let whole = footprint(&[]);
+        let rules = footprint(&["rules/core.ethics"]);
+        let rules_dir = footprint(&["./rules"]);
+        let config = footprint(&["config.toml"]);
+
+        assert!(whole.overlaps(&config));
+        assert!(rules.overlaps(&rules_dir));
+        assert!(!rules.overlaps(&config));
+        assert!(check_files(&["rules/core.ethics".to_string()]).is_ok());
+        assert!(check_files(&["../cold_mirror/model.bin".to_string()]).is_err());
+        assert!(check_files(&["/etc/passwd".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_detect_splits_superseded() {
+        let dir = Path::new("software/ethics_dsl/");
+        let a = metadata("a", &["rules/core.ethics"]);
+        let b = metadata("b", &["rules/core.ethics"]);
+        let c = metadata("c", &["config.toml"]);
+        let mut candidate = metadata("d", &[]);
+        candidate.supersedes = vec!["b".to_string()];
+
+        let pending = [&a, &b, &c].map(|m| (m, Footprint::of(m, dir)));
+        let conflicts = detect(&candidate, &Footprint::of(&candidate, dir), pending);
+
+        assert_eq!(conflicts.superseded, vec!["b"]);
+        assert_eq!(conflicts.unresolved, vec!["a", "c"]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Emergency Mode Authorization
+//!
+//! `MoralStrictness::Emergency` relaxes the moral checks applied to patches,
+//! so it cannot be switched on through configuration. It is activated by a
+//! signed, time-limited `EmergencyAuthorization`: approvers from the approval
+//! trust bundle sign a request naming the namespace, the reason and the
+//! validity window, and a quorum of distinct approvers can be required. The
+//! orchestrator reverts to its configured strictness as soon as the
+//! authorization expires, and every assessment made while emergency mode is
+//! active is written to the audit trail. An assessment relaxed by emergency
+//! strictness carries its `EmergencyScope` and only stands while that
+//! authorization is active; on expiry pending patches get back the
+//! assessment the configured strictness gives them.
+//!
+//! ## Biblical Foundation
+//! "Watch ye therefore: for ye know not when the master of the house cometh" - Mark 13:35
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use blake3::Hash;
+use ed25519_dalek::SigningKey as Ed25519SigningKey;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes};
+use serde::{Deserialize, Serialize};
+
+use crate::approval::{self, TrustBundle};
+use crate::{OrchestratorError, PatchMorality};
+
+/// Domain separator for emergency authorization digests
+pub const EMERGENCY_DIGEST_DOMAIN: &str = "ark-emergency-authorization-v2";
+
+/// Clock skew tolerated on `issued_at`
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// Emergency activation requirements
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmergencyPolicy {
+    /// Distinct approvers that must sign an authorization
+    pub required_signers: usize,
+    /// Longest validity window an authorization may grant
+    pub max_duration: Duration,
+}
+
+impl Default for EmergencyPolicy {
+    fn default() -> Self {
+        Self {
+            required_signers: 1,
+            max_duration: Duration::from_secs(4 * 3600),
+        }
+    }
+}
+
+/// Signed body of an emergency authorization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmergencyRequest {
+    pub namespace: String,
+    pub reason: String,
+    pub issued_at: SystemTime,
+    pub expires_at: SystemTime,
+    /// Random value making every authorization unique (hex)
+    pub nonce: String,
+}
+
+impl EmergencyRequest {
+    /// Request valid from now for `duration`
+    pub fn new(namespace: &str, reason: &str, duration: Duration) -> Self {
+        use rand::RngCore;
+
+        let mut nonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let issued_at = SystemTime::now();
+        Self {
+            namespace: namespace.to_string(),
+            reason: reason.to_string(),
+            issued_at,
+            expires_at: issued_at + duration,
+            nonce: hex::encode(nonce),
+        }
+    }
+
+    /// Digest approvers sign, of the canonical encoding in `signing`
+    pub fn digest(&self) -> Hash {
+        blake3::hash(&self.signing_bytes())
+    }
+}
+
+/// One approver's signature over an emergency request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencySignature {
+    pub approver: String,
+    pub pq_signature: DilithiumSignatureBytes,
+    pub classical_signature: Ed25519SignatureBytes,
+}
+
+/// Emergency request with its approver signatures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyAuthorization {
+    pub request: EmergencyRequest,
+    pub signatures: Vec<EmergencySignature>,
+}
+
+impl Validate for EmergencyAuthorization {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("namespace", &self.request.namespace, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("reason", &self.request.reason, crate::MAX_PATCH_TEXT_LENGTH)?;
+        decode::check_len("nonce", &self.request.nonce, 64)?;
+        decode::check_count("signatures", self.signatures.len(), approval::MAX_APPROVERS)?;
+        for signature in &self.signatures {
+            decode::check_identifier("approver", &signature.approver, crate::MAX_PATCH_FIELD_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+impl EmergencyAuthorization {
+    /// Read an authorization file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Emergency(format!("{:?}: {}", path, e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| OrchestratorError::Emergency(format!("{:?}: {}", path, e)))
+    }
+
+    /// Write an authorization file
+    pub fn save(&self, path: &Path) -> Result<(), OrchestratorError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| OrchestratorError::Emergency(e.to_string()))?;
+        std::fs::write(path, contents)
+            .map_err(|e| OrchestratorError::Emergency(format!("{:?}: {}", path, e)))
+    }
+}
+
+/// Emergency mode granted by a verified authorization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveEmergency {
+    /// Digest of the authorization (hex)
+    pub authorization: String,
+    pub reason: String,
+    pub approvers: Vec<String>,
+    pub expires_at: SystemTime,
+}
+
+impl ActiveEmergency {
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Authorization a patch's moral assessment was made under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmergencyScope {
+    /// Digest of the authorization (hex)
+    pub authorization: String,
+    pub expires_at: SystemTime,
+    /// Assessment under the configured strictness
+    pub unrelaxed: PatchMorality,
+}
+
+/// Sign an emergency request on an approver's workstation
+pub fn sign_emergency(
+    request: &EmergencyRequest,
+    approver: &str,
+    pq_secret: &DilithiumSecretKeyBytes,
+    classical: &Ed25519SigningKey,
+) -> Result<EmergencySignature, OrchestratorError> {
+    use ed25519_dalek::Signer;
+
+    let message = signed_message(request);
+    let pq_signature = Dilithium3::sign(&message, pq_secret)
+        .map_err(|e| OrchestratorError::Emergency(e.to_string()))?;
+    let classical_signature = classical.sign(&message);
+
+    Ok(EmergencySignature {
+        approver: approver.to_string(),
+        pq_signature,
+        classical_signature: Ed25519SignatureBytes::from_slice(&classical_signature.to_bytes())
+            .map_err(|e| OrchestratorError::Emergency(e.to_string()))?,
+    })
+}
+
+/// Verify an authorization for a namespace at time `now`
+///
+/// Every signature must verify against the trust bundle, and at least
+/// `required_signers` distinct approvers must have signed.
+pub fn verify_authorization(
+    authorization: &EmergencyAuthorization,
+    policy: &EmergencyPolicy,
+    bundle: &TrustBundle,
+    namespace: &str,
+    now: SystemTime,
+) -> Result<ActiveEmergency, OrchestratorError> {
+    let request = &authorization.request;
+    let reject = |reason: String| Err(OrchestratorError::Emergency(reason));
+
+    if request.namespace != namespace {
+        return reject(format!("Authorization is for namespace {}, not {}", request.namespace, namespace));
+    }
+    if request.issued_at > now + MAX_CLOCK_SKEW {
+        return reject("Authorization is issued in the future".into());
+    }
+    if request.expires_at <= now {
+        return reject("Authorization has expired".into());
+    }
+    match request.expires_at.duration_since(request.issued_at) {
+        Ok(duration) if duration <= policy.max_duration => {}
+        _ => return reject(format!("Authorization window exceeds {:?}", policy.max_duration)),
+    }
+
+    let message = signed_message(request);
+    let mut approvers = BTreeSet::new();
+    for signature in &authorization.signatures {
+        approval::verify_signature(bundle, &signature.approver, &message, &signature.pq_signature, &signature.classical_signature)
+            .map_err(OrchestratorError::Emergency)?;
+        approvers.insert(signature.approver.clone());
+    }
+
+    let required = policy.required_signers.max(1);
+    if approvers.len() < required {
+        return reject(format!("{} of {} required approvers signed", approvers.len(), required));
+    }
+
+    Ok(ActiveEmergency {
+        authorization: request.digest().to_hex().to_string(),
+        reason: request.reason.clone(),
+        approvers: approvers.into_iter().collect(),
+        expires_at: request.expires_at,
+    })
+}
+
+fn signed_message(request: &EmergencyRequest) -> Vec<u8> {
+    format!("{}\n{}", EMERGENCY_DIGEST_DOMAIN, request.digest().to_hex()).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval::TrustedApprover;
+
+    struct Approver {
+        id: &'static str,
+        pq_secret: DilithiumSecretKeyBytes,
+        classical: Ed25519SigningKey,
+    }
+
+    fn approvers(ids: &[&'static str]) -> (Vec<Approver>, TrustBundle) {
+        let mut bundle = TrustBundle::default();
+        let approvers = ids
+            .iter()
+            .map(|id| {
+                let (pq_public, pq_secret) = Dilithium3::keypair().unwrap();
+                let classical = Ed25519SigningKey::generate(&mut rand::rngs::OsRng);
+                bundle.approvers.push(TrustedApprover {
+                    id: id.to_string(),
+                    dilithium_public: hex::encode(pq_public.as_bytes()),
+                    ed25519_public: hex::encode(classical.verifying_key().to_bytes()),
+                });
+                Approver { id, pq_secret, classical }
+            })
+            .collect();
+        (approvers, bundle)
+    }
+
+    fn authorize(request: EmergencyRequest, signers: &[Approver]) -> EmergencyAuthorization {
+        let signatures = signers
+            .iter()
+            .map(|a| sign_emergency(&request, a.id, &a.pq_secret, &a.classical).unwrap())
+            .collect();
+        EmergencyAuthorization { request, signatures }
+    }
+
+    #[test]
+    fn test_quorum_required() {
+        let (signers, bundle) = approvers(&["alice", "bob"]);
+        let policy = EmergencyPolicy { required_signers: 2, ..Default::default() };
+        let request = EmergencyRequest::new("default", "CVE response", Duration::from_secs(3600));
+        let now = SystemTime::now();
+
+        let one = authorize(request.clone(), &signers[..1]);
+        assert!(verify_authorization(&one, &policy, &bundle, "default", now).is_err());
+
+        let both = authorize(request, &signers);
+        let active = verify_authorization(&both, &policy, &bundle, "default", now).unwrap();
+        assert_eq!(active.approvers, vec!["alice".to_string(), "bob".to_string()]);
+        assert!(verify_authorization(&both, &policy, &bundle, "tenant-a", now).is_err());
+    }
+
+    #[test]
+    fn test_expiry_and_window_enforced() {
+        let (signers, bundle) = approvers(&["alice"]);
+        let policy = EmergencyPolicy::default();
+
+        let request = EmergencyRequest::new("default", "outage", Duration::from_secs(600));
+        let authorization = authorize(request, &signers);
+        let active = verify_authorization(&authorization, &policy, &bundle, "default", SystemTime::now()).unwrap();
+        let later = SystemTime::now() + Duration::from_secs(601);
+        assert!(active.is_expired(later));
+        assert!(verify_authorization(&authorization, &policy, &bundle, "default", later).is_err());
+
+        let too_long = authorize(EmergencyRequest::new("default", "outage", policy.max_duration * 2), &signers);
+        assert!(verify_authorization(&too_long, &policy, &bundle, "default", SystemTime::now()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_request_rejected() {
+        let (signers, bundle) = approvers(&["alice"]);
+        let mut authorization = authorize(EmergencyRequest::new("default", "outage", Duration::from_secs(600)), &signers);
+        authorization.request.expires_at += Duration::from_secs(60);
+
+        assert!(verify_authorization(&authorization, &EmergencyPolicy::default(), &bundle, "default", SystemTime::now()).is_err());
+    }
+}
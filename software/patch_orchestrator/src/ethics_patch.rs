@@ -0,0 +1,438 @@
+//! Ethics Rule Pack Patching
+//!
+//! Staged update pipeline for the Ethics DSL rule pack. A candidate pack is
+//! written to the staging directory, checked for formal consistency, run
+//! against the embedded conformance suite and replayed over a corpus of
+//! historic events to measure how many decisions it would change. Only then is
+//! the live pack replaced, via write-to-temp and rename.
+//!
+//! The replay covers the last days of the engine's decision journal, where
+//! the candidate is compared with the decision actually taken, and any
+//! curated corpus, where it is compared with the live pack. Each flip is
+//! counted by direction (Allow→Deny, Deny→Purge, ...).
+//!
+//! ## Biblical Foundation
+//! "Do not move the ancient boundary stone set up by your forefathers" - Proverbs 22:28
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use ark_storage::StorageConfig;
+use pq_types::decode::{self, DecodeLimits};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use ethics_dsl::{
+    ActorType, Actor, Content, ContentType, Context, EthicsConfig, EthicsDecision, EthicsEngine,
+    EthicsEvaluator, EthicsEvent, JournalEntry, UrgencyLevel,
+};
+
+use crate::OrchestratorError;
+
+/// Policy for ethics rule pack patches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthicsPatchPolicy {
+    /// Rule pack read by the live ethics engine
+    pub live_rule_pack: PathBuf,
+    /// JSON-lines file of historic `EthicsEvent`s replayed for the decision diff
+    pub replay_corpus: Option<PathBuf>,
+    /// Decision journal written by the live engine, replayed for the decision diff
+    #[serde(default)]
+    pub decision_journal: Option<PathBuf>,
+    /// Store of the live engine's decision journal, when it keeps one
+    /// (`journal_storage` in its configuration); used instead of `decision_journal`
+    #[serde(default)]
+    pub journal_storage: Option<StorageConfig>,
+    /// Days of the decision journal to replay
+    #[serde(default = "default_replay_window_days")]
+    pub replay_window_days: u32,
+    /// Fraction of replayed decisions that may flip without explicit approval;
+    /// above it the patch is not auto-applied
+    pub approval_threshold: f64,
+    /// Configuration of the live ethics engine; candidate and live packs
+    /// are loaded and replayed under it
+    #[serde(default)]
+    pub engine: EthicsConfig,
+}
+
+fn default_replay_window_days() -> u32 {
+    30
+}
+
+impl Default for EthicsPatchPolicy {
+    fn default() -> Self {
+        Self {
+            live_rule_pack: PathBuf::from("software/ethics_dsl/rules/live.ethics"),
+            replay_corpus: None,
+            decision_journal: None,
+            journal_storage: None,
+            replay_window_days: default_replay_window_days(),
+            approval_threshold: 0.05,
+            engine: EthicsConfig::default(),
+        }
+    }
+}
+
+impl EthicsPatchPolicy {
+    /// The live engine's configuration without its side effects
+    ///
+    /// Replayed decisions must not reach the live engine's sinks, journal
+    /// or actor ledger.
+    pub fn replay_config(&self) -> EthicsConfig {
+        EthicsConfig {
+            sinks: Vec::new(),
+            journal: None,
+            journal_storage: None,
+            actor_ledger: None,
+            ..self.engine.clone()
+        }
+    }
+}
+
+/// Coarse decision class used for comparisons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecisionKind {
+    Allow,
+    Deny,
+    Purge,
+}
+
+impl From<&EthicsDecision> for DecisionKind {
+    fn from(decision: &EthicsDecision) -> Self {
+        match decision {
+            EthicsDecision::Allow { .. } => DecisionKind::Allow,
+            EthicsDecision::Deny { .. } => DecisionKind::Deny,
+            EthicsDecision::Purge { .. } => DecisionKind::Purge,
+        }
+    }
+}
+
+/// Number of replayed decisions that flipped in one direction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionFlip {
+    pub from: DecisionKind,
+    pub to: DecisionKind,
+    pub count: usize,
+}
+
+/// Behavioral change between the live and candidate rule packs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DecisionDiff {
+    pub total_events: usize,
+    pub changed_events: usize,
+    /// Events the candidate would now block that the live pack allowed
+    pub newly_blocked: usize,
+    /// Events the candidate would now allow that the live pack blocked
+    pub newly_allowed: usize,
+    pub changed_event_ids: Vec<String>,
+    /// Changed events by direction, in order of first occurrence
+    #[serde(default)]
+    pub flips: Vec<DecisionFlip>,
+}
+
+impl DecisionDiff {
+    /// Record one replayed event
+    pub fn record(&mut self, event_id: &str, live: DecisionKind, candidate: DecisionKind) {
+        self.total_events += 1;
+        if live == candidate {
+            return;
+        }
+
+        self.changed_events += 1;
+        self.changed_event_ids.push(event_id.to_string());
+        match self.flips.iter_mut().find(|flip| flip.from == live && flip.to == candidate) {
+            Some(flip) => flip.count += 1,
+            None => self.flips.push(DecisionFlip { from: live, to: candidate, count: 1 }),
+        }
+        match (live, candidate) {
+            (DecisionKind::Allow, _) => self.newly_blocked += 1,
+            (_, DecisionKind::Allow) => self.newly_allowed += 1,
+            _ => {}
+        }
+    }
+
+    /// Fraction of replayed events whose decision changed
+    pub fn changed_ratio(&self) -> f64 {
+        if self.total_events == 0 {
+            0.0
+        } else {
+            self.changed_events as f64 / self.total_events as f64
+        }
+    }
+
+    /// Flips formatted as `Allow→Deny: 3, Deny→Purge: 1`
+    pub fn flip_summary(&self) -> String {
+        self.flips.iter()
+            .map(|flip| format!("{:?}→{:?}: {}", flip.from, flip.to, flip.count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Conformance case every rule pack must satisfy
+#[derive(Debug, Clone)]
+pub struct ConformanceCase {
+    pub name: &'static str,
+    pub event: EthicsEvent,
+    /// Any of these decisions is acceptable
+    pub expected: &'static [DecisionKind],
+}
+
+/// Outcome of validating a candidate rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EthicsPatchReport {
+    pub conformance_failures: Vec<String>,
+    pub diff: DecisionDiff,
+}
+
+/// Embedded conformance suite
+pub fn conformance_suite() -> Vec<ConformanceCase> {
+    const BLOCK: &[DecisionKind] = &[DecisionKind::Deny, DecisionKind::Purge];
+    const ALLOW: &[DecisionKind] = &[DecisionKind::Allow];
+
+    vec![
+        ConformanceCase {
+            name: "child_corruption_blocked",
+            event: conformance_event(
+                "conformance-child-corruption",
+                &[ethics_dsl::tags::CHILD_CORRUPTION],
+                "Material designed to corrupt children",
+            ),
+            expected: BLOCK,
+        },
+        ConformanceCase {
+            name: "violence_against_innocent_blocked",
+            event: conformance_event(
+                "conformance-violence",
+                &[ethics_dsl::tags::VIOLENCE_INNOCENT],
+                "Instructions for harming the innocent",
+            ),
+            expected: BLOCK,
+        },
+        ConformanceCase {
+            name: "scripture_study_allowed",
+            event: conformance_event(
+                "conformance-scripture",
+                &[],
+                "Your word is a lamp to my feet and a light to my path",
+            ),
+            expected: ALLOW,
+        },
+    ]
+}
+
+fn conformance_event(event_id: &str, tags: &[&str], text: &str) -> EthicsEvent {
+    let content = Content {
+        content_type: ContentType::Text,
+        data: text.to_string(),
+        metadata: Default::default(),
+        content_hash: blake3::hash(text.as_bytes()).to_hex().to_string(),
+    };
+
+    ethics_dsl::utils::create_event(
+        event_id.to_string(),
+        Actor {
+            actor_type: ActorType::Content,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            trust_level: 0.5,
+            history: None,
+        },
+        Some(content),
+        Context {
+            location: None,
+            culture: None,
+            platform: Some("conformance".to_string()),
+            audience: None,
+            urgency: UrgencyLevel::Normal,
+        },
+    )
+}
+
+/// Write a candidate rule pack into the staging directory
+pub fn stage_rule_pack(staging_directory: &Path, patch_id: &str, rules: &str) -> Result<PathBuf, OrchestratorError> {
+    let staged = staging_directory.join(format!("{}.ethics", patch_id));
+    std::fs::write(&staged, rules)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    Ok(staged)
+}
+
+/// Read the live rule pack the candidate is compared with
+///
+/// A missing or unreadable live pack is an error rather than an empty pack:
+/// diffing against nothing would report every decision as unchanged.
+pub fn load_live_rule_pack(path: &Path) -> Result<String, OrchestratorError> {
+    std::fs::read_to_string(path)
+        .map_err(|e| OrchestratorError::Staging(format!("Live rule pack {:?}: {}", path, e)))
+}
+
+/// Load a DSL rule pack into an engine, failing if it does not validate
+pub fn load_rule_engine(path: &Path, config: &EthicsConfig) -> Result<EthicsEngine, OrchestratorError> {
+    let rules = std::fs::read_to_string(path)
+        .map_err(|e| OrchestratorError::Staging(format!("Rule pack {:?}: {}", path, e)))?;
+    let mut engine = EthicsEngine::new(config.clone())
+        .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+    engine.validate_rules(&rules)
+        .map_err(|e| OrchestratorError::ConsistencyCheck(format!("{:?}: {}", path, e)))?;
+    engine.update_rules(&rules)
+        .map_err(|e| OrchestratorError::ConsistencyCheck(format!("{:?}: {}", path, e)))?;
+    Ok(engine)
+}
+
+/// Load the replay corpus, skipping lines that fail to parse
+pub fn load_replay_corpus(path: &Path) -> Result<Vec<EthicsEvent>, OrchestratorError> {
+    let file = File::open(path)
+        .map_err(|e| OrchestratorError::Staging(format!("Replay corpus {:?}: {}", path, e)))?;
+
+    let mut events = Vec::new();
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match decode::json::<EthicsEvent>(line.as_bytes(), &DecodeLimits::RECORD) {
+            Ok(event) => events.push(event),
+            Err(e) => warn!("Skipping replay corpus line {}: {}", line_number + 1, e),
+        }
+    }
+    Ok(events)
+}
+
+/// Load the decision journal entries of the last `window_days` days
+pub fn load_journal(path: &Path, window_days: u32) -> Result<Vec<JournalEntry>, OrchestratorError> {
+    let since = chrono::Utc::now() - chrono::Duration::days(i64::from(window_days));
+    ethics_dsl::journal::read_since(path, since)
+        .map_err(|e| OrchestratorError::Staging(format!("Decision journal {:?}: {}", path, e)))
+}
+
+/// Load the entries of the last `window_days` days from a decision journal store
+pub fn load_journal_storage(config: &StorageConfig, window_days: u32) -> Result<Vec<JournalEntry>, OrchestratorError> {
+    let since = chrono::Utc::now() - chrono::Duration::days(i64::from(window_days));
+    let storage = ark_storage::open(config).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+    ethics_dsl::journal::read_since_storage(storage.as_ref(), since)
+        .map_err(|e| OrchestratorError::Staging(format!("Decision journal store {:?}: {}", config.path, e)))
+}
+
+/// Check a candidate rule pack against the live one
+///
+/// Journaled events are compared with the decision recorded for them,
+/// corpus events with the live pack's decision.
+pub fn validate_rule_pack(
+    live_rules: &str,
+    candidate_rules: &str,
+    corpus: &[EthicsEvent],
+    journal: &[JournalEntry],
+    config: &EthicsConfig,
+) -> Result<EthicsPatchReport, OrchestratorError> {
+    let mut live = EthicsEngine::new(config.clone())
+        .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+    live.update_rules(live_rules)
+        .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+
+    let mut candidate = EthicsEngine::new(config.clone())
+        .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+
+    // Formal consistency check
+    candidate.validate_rules(candidate_rules)
+        .map_err(|e| OrchestratorError::ConsistencyCheck(e.to_string()))?;
+    candidate.update_rules(candidate_rules)
+        .map_err(|e| OrchestratorError::ConsistencyCheck(e.to_string()))?;
+
+    // Embedded conformance suite
+    let mut conformance_failures = Vec::new();
+    for case in conformance_suite() {
+        match candidate.evaluate(&case.event) {
+            Ok(decision) if case.expected.contains(&DecisionKind::from(&decision)) => {}
+            Ok(decision) => conformance_failures.push(
+                format!("{}: got {:?}", case.name, DecisionKind::from(&decision))
+            ),
+            Err(e) => conformance_failures.push(format!("{}: {}", case.name, e)),
+        }
+    }
+
+    // Decision diff over the journal and the replay corpus
+    let mut diff = DecisionDiff::default();
+    for entry in journal {
+        let candidate_decision = candidate.evaluate(&entry.event)
+            .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+        diff.record(&entry.event.event_id, (&entry.decision).into(), (&candidate_decision).into());
+    }
+    for event in corpus {
+        let live_decision = live.evaluate(event)
+            .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+        let candidate_decision = candidate.evaluate(event)
+            .map_err(|e| OrchestratorError::EthicsEvaluation(e.to_string()))?;
+        diff.record(&event.event_id, (&live_decision).into(), (&candidate_decision).into());
+    }
+
+    debug!("Rule pack replay: {}/{} decisions changed", diff.changed_events, diff.total_events);
+
+    Ok(EthicsPatchReport { conformance_failures, diff })
+}
+
+/// Atomically replace the live rule pack with the staged one
+pub fn swap_rule_pack(staged: &Path, live: &Path) -> Result<(), OrchestratorError> {
+    crate::staging::atomic_replace(staged, live)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_decision_diff_counts_direction() {
+        let mut diff = DecisionDiff::default();
+        diff.record("a", DecisionKind::Allow, DecisionKind::Allow);
+        diff.record("b", DecisionKind::Allow, DecisionKind::Deny);
+        diff.record("c", DecisionKind::Purge, DecisionKind::Allow);
+        diff.record("d", DecisionKind::Deny, DecisionKind::Purge);
+
+        assert_eq!(diff.total_events, 4);
+        assert_eq!(diff.changed_events, 3);
+        assert_eq!(diff.newly_blocked, 1);
+        assert_eq!(diff.newly_allowed, 1);
+        assert_eq!(diff.changed_event_ids, vec!["b", "c", "d"]);
+        assert!((diff.changed_ratio() - 0.75).abs() < f64::EPSILON);
+
+        diff.record("e", DecisionKind::Allow, DecisionKind::Deny);
+        assert_eq!(diff.flips[0], DecisionFlip { from: DecisionKind::Allow, to: DecisionKind::Deny, count: 2 });
+        assert_eq!(diff.flip_summary(), "Allow→Deny: 2, Purge→Allow: 1, Deny→Purge: 1");
+    }
+
+    #[test]
+    fn test_stage_and_swap_rule_pack() {
+        let dir = tempdir().unwrap();
+        let live = dir.path().join("rules").join("live.ethics");
+        std::fs::create_dir_all(live.parent().unwrap()).unwrap();
+        std::fs::write(&live, "rule old").unwrap();
+
+        let staged = stage_rule_pack(dir.path(), "ethics-002", "rule new").unwrap();
+        swap_rule_pack(&staged, &live).unwrap();
+
+        assert_eq!(load_live_rule_pack(&live).unwrap(), "rule new");
+        assert!(staged.exists());
+        assert!(load_live_rule_pack(&dir.path().join("missing.ethics")).is_err());
+    }
+
+    #[test]
+    fn test_conformance_suite_has_block_and_allow_cases() {
+        let suite = conformance_suite();
+        assert!(suite.iter().any(|c| c.expected.contains(&DecisionKind::Allow)));
+        assert!(suite.iter().any(|c| c.expected.contains(&DecisionKind::Purge)));
+    }
+
+    #[test]
+    fn test_replay_uses_the_engine_config_without_side_effects() {
+        let mut policy = EthicsPatchPolicy::default();
+        policy.engine.strictness_level = 3;
+        policy.engine.journal = Some(PathBuf::from("/var/lib/ark/decisions.jsonl"));
+        policy.engine.actor_ledger = Some(PathBuf::from("/var/lib/ark/actors.jsonl"));
+
+        let replay = policy.replay_config();
+        assert_eq!(replay.strictness_level, 3);
+        assert!(replay.journal.is_none());
+        assert!(replay.actor_ledger.is_none());
+        assert!(replay.sinks.is_empty());
+    }
+}
@@ -0,0 +1,466 @@
+//! Orchestrator Self-Update Handoff
+//!
+//! Protocol used when the orchestrator patches itself. The new binary is
+//! launched in standby, receives the pending patch queue and the signing keys
+//! over a local socket, answers a health check, and only then takes over the
+//! active role. Until the standby acknowledges its promotion the old instance
+//! remains active, so a failed handoff leaves the system as it was.
+//!
+//! Keys never cross the socket in the clear: they are wrapped under a one-time
+//! key-encryption key (KEK) that is handed to the standby through its stdin.
+//!
+//! ## Biblical Foundation
+//! "One generation shall commend your works to another" - Psalm 145:4
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime};
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tracing::debug;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::approval::DetachedApproval;
+use crate::release::SignedRelease;
+use crate::{OrchestratorError, PatchMetadata};
+
+/// Handoff socket file name inside the staging directory
+pub const HANDOFF_SOCKET: &str = "orchestrator-handoff.sock";
+
+/// Active instance record file name inside the patch directory
+pub const ACTIVE_INSTANCE_FILE: &str = "active_instance.json";
+
+/// Upper bound on a single handoff message
+pub const MAX_HANDOFF_MESSAGE: usize = 16 * 1024 * 1024;
+
+/// Decoding limits for handoff messages
+const HANDOFF_LIMITS: DecodeLimits = DecodeLimits::new(MAX_HANDOFF_MESSAGE, 32);
+
+/// Most pending patches or approvals carried by one handoff
+pub const MAX_HANDOFF_ENTRIES: usize = 10_000;
+
+/// Associated data binding wrapped keys to this protocol
+const KEY_WRAP_AAD: &[u8] = b"ark-orchestrator-handoff-v1";
+
+/// Policy for orchestrator self-patches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffPolicy {
+    /// Binary the supervisor launches as the active orchestrator
+    pub live_binary: PathBuf,
+    /// Configuration file passed to the standby instance
+    pub config_file: PathBuf,
+    /// Time allowed for the standby to start listening
+    pub standby_timeout: Duration,
+    /// Time allowed for each protocol exchange once connected
+    pub exchange_timeout: Duration,
+}
+
+impl Default for HandoffPolicy {
+    fn default() -> Self {
+        Self {
+            live_binary: PathBuf::from("bin/patch_orchestrator"),
+            config_file: PathBuf::from("config/orchestrator.toml"),
+            standby_timeout: Duration::from_secs(30),
+            exchange_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Raw signing key material carried across a handoff
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct KeyMaterial {
+    pub dilithium_public: Vec<u8>,
+    pub dilithium_secret: Vec<u8>,
+    pub ed25519_keypair: Vec<u8>,
+    /// Empty when handed over by an instance without ML-DSA keys
+    #[serde(default)]
+    pub ml_dsa_public: Vec<u8>,
+    #[serde(default)]
+    pub ml_dsa_secret: Vec<u8>,
+}
+
+/// Key material encrypted under a one-time KEK
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedKeys {
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// Generate a fresh key-encryption key
+pub fn generate_kek() -> Zeroizing<[u8; 32]> {
+    let mut kek = Zeroizing::new([0u8; 32]);
+    rand::rngs::OsRng.fill_bytes(kek.as_mut());
+    kek
+}
+
+/// Wrap key material under a KEK
+pub fn wrap_keys(keys: &KeyMaterial, kek: &[u8; 32]) -> Result<WrappedKeys, OrchestratorError> {
+    let plaintext = Zeroizing::new(bincode::serialize(keys)
+        .map_err(|e| OrchestratorError::Handoff(format!("Key serialization failed: {}", e)))?);
+
+    let mut nonce = [0u8; 12];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(kek));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext.as_slice(), aad: KEY_WRAP_AAD })
+        .map_err(|_| OrchestratorError::Handoff("Key wrapping failed".into()))?;
+
+    Ok(WrappedKeys { nonce, ciphertext })
+}
+
+/// Unwrap key material with the KEK it was wrapped under
+pub fn unwrap_keys(wrapped: &WrappedKeys, kek: &[u8; 32]) -> Result<KeyMaterial, OrchestratorError> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(kek));
+    let plaintext = Zeroizing::new(cipher
+        .decrypt(Nonce::from_slice(&wrapped.nonce), Payload { msg: &wrapped.ciphertext, aad: KEY_WRAP_AAD })
+        .map_err(|_| OrchestratorError::Handoff("Key unwrapping failed - wrong KEK or tampered keys".into()))?);
+
+    decode::bincode(&plaintext, &DecodeLimits::FILE)
+        .map_err(|e| OrchestratorError::Handoff(format!("Key deserialization failed: {}", e)))
+}
+
+/// State transferred to the standby instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffState {
+    pub pending_patches: Vec<PatchMetadata>,
+    /// Overlapping patches each pending patch waits for
+    #[serde(default)]
+    pub blocked_by: HashMap<String, Vec<String>>,
+    pub approved_patches: Vec<String>,
+    pub detached_approvals: Vec<DetachedApproval>,
+    pub keys: WrappedKeys,
+    /// Pending releases grouping pending patches
+    #[serde(default)]
+    pub releases: Vec<SignedRelease>,
+}
+
+impl Validate for HandoffState {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("pending_patches", self.pending_patches.len(), MAX_HANDOFF_ENTRIES)?;
+        decode::check_count("blocked_by", self.blocked_by.len(), MAX_HANDOFF_ENTRIES)?;
+        decode::check_count("approved_patches", self.approved_patches.len(), MAX_HANDOFF_ENTRIES)?;
+        decode::check_count("detached_approvals", self.detached_approvals.len(), MAX_HANDOFF_ENTRIES)?;
+        decode::check_count("releases", self.releases.len(), MAX_HANDOFF_ENTRIES)?;
+        self.pending_patches.iter().try_for_each(Validate::validate)?;
+        self.releases.iter().try_for_each(Validate::validate)?;
+        self.detached_approvals.iter().try_for_each(Validate::validate)
+    }
+}
+
+/// Messages exchanged between the active and standby instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HandoffMessage {
+    /// Active -> standby: state to take over
+    State(HandoffState),
+    /// Standby -> active: state restored
+    StateAccepted { pending_patches: usize },
+    /// Active -> standby: report health
+    HealthCheck,
+    /// Standby -> active: healthy and ready to serve
+    Healthy { version: String, pending_patches: usize },
+    /// Active -> standby: take over the active role
+    Promote,
+    /// Standby -> active: now active
+    Promoted { pid: u32 },
+    /// Either side: handoff abandoned
+    Abort(String),
+}
+
+impl Validate for HandoffMessage {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            HandoffMessage::State(state) => state.validate(),
+            HandoffMessage::Healthy { version, .. } => decode::check_len("version", version, crate::MAX_PATCH_FIELD_LENGTH),
+            HandoffMessage::Abort(reason) => decode::check_len("reason", reason, crate::MAX_PATCH_TEXT_LENGTH),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Write a length-prefixed handoff message
+pub async fn write_message<W>(writer: &mut W, message: &HandoffMessage) -> Result<(), OrchestratorError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let bytes = bincode::serialize(message)
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    if bytes.len() > MAX_HANDOFF_MESSAGE {
+        return Err(OrchestratorError::Handoff(format!("Message of {} bytes exceeds limit", bytes.len())));
+    }
+
+    writer.write_u32(bytes.len() as u32).await
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    writer.write_all(&bytes).await
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    writer.flush().await
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))
+}
+
+/// Read a length-prefixed handoff message
+pub async fn read_message<R>(reader: &mut R) -> Result<HandoffMessage, OrchestratorError>
+where
+    R: AsyncRead + Unpin,
+{
+    let length = reader.read_u32().await
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))? as usize;
+    if length > MAX_HANDOFF_MESSAGE {
+        return Err(OrchestratorError::Handoff(format!("Message of {} bytes exceeds limit", length)));
+    }
+
+    let mut bytes = vec![0u8; length];
+    reader.read_exact(&mut bytes).await
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    decode::bincode_validated(&bytes, &HANDOFF_LIMITS)
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))
+}
+
+/// Send a request and wait for the reply
+pub async fn exchange<S>(stream: &mut S, request: &HandoffMessage, timeout: Duration) -> Result<HandoffMessage, OrchestratorError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    write_message(stream, request).await?;
+    tokio::time::timeout(timeout, read_message(stream))
+        .await
+        .map_err(|_| OrchestratorError::Handoff("Peer did not respond in time".into()))?
+}
+
+/// Record of which instance holds the active role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveInstance {
+    pub pid: u32,
+    pub version: String,
+    pub since: SystemTime,
+}
+
+/// Atomically record the active instance
+pub fn record_active_instance(path: &Path, instance: &ActiveInstance) -> Result<(), OrchestratorError> {
+    let directory = path.parent().unwrap_or_else(|| Path::new("."));
+    let json = serde_json::to_vec_pretty(instance)
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+
+    let staged = tempfile::NamedTempFile::new_in(directory)
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    std::fs::write(staged.path(), json)
+        .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+    crate::staging::atomic_replace(staged.path(), path)
+}
+
+/// Write the new orchestrator binary into the staging directory
+pub fn stage_binary(staging_directory: &Path, patch_id: &str, binary: &[u8]) -> Result<PathBuf, OrchestratorError> {
+    let staged = staging_directory.join(format!("{}.bin", patch_id));
+    std::fs::write(&staged, binary)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    make_executable(&staged)?;
+    Ok(staged)
+}
+
+/// Standby orchestrator launched for a handoff
+///
+/// The process is killed when this is dropped, so a failed or abandoned
+/// handoff never leaves a second orchestrator running. Once promoted it is
+/// detached and outlives the instance that launched it.
+pub struct Standby {
+    child: Option<Child>,
+}
+
+impl Standby {
+    /// Process ID, while the process is running
+    pub fn id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(Child::id)
+    }
+
+    /// Release the promoted standby so it keeps running
+    pub fn detach(mut self) {
+        // Dropping a child spawned without kill_on_drop leaves it running
+        self.child.take();
+    }
+}
+
+impl Drop for Standby {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.start_kill();
+        }
+    }
+}
+
+/// Launch a staged binary in standby mode and hand it the KEK
+pub async fn launch_standby(
+    binary: &Path,
+    policy: &HandoffPolicy,
+    namespace: &str,
+    socket: &Path,
+    kek: &[u8; 32],
+) -> Result<Standby, OrchestratorError> {
+    let child = Command::new(binary)
+        .arg("--config").arg(&policy.config_file)
+        .arg("--namespace").arg(namespace)
+        .arg("standby")
+        .arg("--socket").arg(socket)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| OrchestratorError::Handoff(format!("Failed to launch standby: {}", e)))?;
+    let mut standby = Standby { child: Some(child) };
+
+    let mut stdin = standby.child.as_mut().and_then(|child| child.stdin.take())
+        .ok_or_else(|| OrchestratorError::Handoff("Standby stdin unavailable".into()))?;
+    let encoded = Zeroizing::new(format!("{}\n", hex::encode(kek)));
+    stdin.write_all(encoded.as_bytes()).await
+        .map_err(|e| OrchestratorError::Handoff(format!("Failed to pass KEK: {}", e)))?;
+    drop(stdin);
+
+    debug!("Launched standby orchestrator {:?} (pid {:?})", binary, standby.id());
+    Ok(standby)
+}
+
+/// Mark a promoted orchestrator binary executable
+pub fn make_executable(binary: &Path) -> Result<(), OrchestratorError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(binary, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    }
+    #[cfg(not(unix))]
+    let _ = binary;
+
+    Ok(())
+}
+
+/// Read the KEK the active instance passed on stdin
+pub async fn read_kek<R>(reader: &mut R) -> Result<Zeroizing<[u8; 32]>, OrchestratorError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut encoded = Zeroizing::new(String::new());
+    reader.read_to_string(&mut encoded).await
+        .map_err(|e| OrchestratorError::Handoff(format!("Failed to read KEK: {}", e)))?;
+
+    let decoded = Zeroizing::new(hex::decode(encoded.trim())
+        .map_err(|_| OrchestratorError::Handoff("KEK is not valid hex".into()))?);
+    if decoded.len() != 32 {
+        return Err(OrchestratorError::Handoff(format!("KEK must be 32 bytes, got {}", decoded.len())));
+    }
+
+    let mut kek = Zeroizing::new([0u8; 32]);
+    kek.copy_from_slice(&decoded);
+    Ok(kek)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(debug_assertions)]
+    #[global_allocator]
+    static ALLOCATOR: pq_types::canary::CanaryAllocator = pq_types::canary::CanaryAllocator;
+
+    fn sample_keys() -> KeyMaterial {
+        KeyMaterial {
+            dilithium_public: vec![1; 16],
+            dilithium_secret: vec![2; 32],
+            ed25519_keypair: vec![3; 64],
+            ml_dsa_public: Vec::new(),
+            ml_dsa_secret: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_key_wrapping_round_trip() {
+        let kek = generate_kek();
+        let wrapped = wrap_keys(&sample_keys(), &kek).unwrap();
+
+        let unwrapped = unwrap_keys(&wrapped, &kek).unwrap();
+        assert_eq!(unwrapped.dilithium_secret, vec![2; 32]);
+        assert_eq!(unwrapped.ed25519_keypair, vec![3; 64]);
+
+        let other = generate_kek();
+        assert!(unwrap_keys(&wrapped, &other).is_err());
+
+        let mut tampered = wrapped.clone();
+        tampered.ciphertext[0] ^= 0x01;
+        assert!(unwrap_keys(&tampered, &kek).is_err());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_key_material_wiped_on_drop() {
+        use ed25519_dalek::SigningKey;
+        use pq_types::canary;
+        use pq_types::{DilithiumSecretKeyBytes, MlDsaSecretKeyBytes};
+
+        // The orchestrator's own signing keys are held in these types
+
+        canary::assert_zeroize_on_drop::<KeyMaterial>();
+        canary::assert_zeroize_on_drop::<SigningKey>();
+        canary::assert_zeroize_on_drop::<DilithiumSecretKeyBytes>();
+        canary::assert_zeroize_on_drop::<MlDsaSecretKeyBytes>();
+
+        let kek = generate_kek();
+        let leaks = canary::watch(|canary| {
+            let keys = KeyMaterial {
+                dilithium_public: vec![1; 16],
+                dilithium_secret: canary::fill(canary, 64),
+                ed25519_keypair: canary::fill(canary, 64),
+                ml_dsa_public: Vec::new(),
+                ml_dsa_secret: canary::fill(canary, 64),
+            };
+            let wrapped = wrap_keys(&keys, &kek).unwrap();
+            drop(keys);
+
+            // The standby's decrypted copy and its serialized plaintext
+            let unwrapped = unwrap_keys(&wrapped, &kek).unwrap();
+            assert_eq!(unwrapped.ed25519_keypair.len(), 64);
+            drop(unwrapped);
+        });
+        assert_eq!(leaks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_message_framing_round_trip() {
+        let (mut active, mut standby) = tokio::io::duplex(4096);
+
+        write_message(&mut active, &HandoffMessage::Healthy { version: "3.1.0".into(), pending_patches: 2 })
+            .await
+            .unwrap();
+
+        match read_message(&mut standby).await.unwrap() {
+            HandoffMessage::Healthy { version, pending_patches } => {
+                assert_eq!(version, "3.1.0");
+                assert_eq!(pending_patches, 2);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_message_rejected() {
+        let (mut active, mut standby) = tokio::io::duplex(64);
+        active.write_u32((MAX_HANDOFF_MESSAGE + 1) as u32).await.unwrap();
+
+        assert!(read_message(&mut standby).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hostile_length_prefix_rejected() {
+        // Abort(String) whose string claims u64::MAX bytes
+        let mut bytes = bincode::serialize(&HandoffMessage::Abort("x".into())).unwrap();
+        let len_at = bytes.len() - 9;
+        bytes[len_at..len_at + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let (mut active, mut standby) = tokio::io::duplex(64);
+        active.write_u32(bytes.len() as u32).await.unwrap();
+        active.write_all(&bytes).await.unwrap();
+
+        assert!(read_message(&mut standby).await.is_err());
+    }
+}
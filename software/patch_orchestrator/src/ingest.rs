@@ -0,0 +1,301 @@
+//! Streaming Patch Ingestion
+//!
+//! Large patches (firmware images in particular) are shipped as zstd-compressed
+//! bundles and ingested chunk by chunk, so the staging host never holds the
+//! whole payload in memory. Every chunk carries the blake3 hash of its
+//! decompressed contents and is checked as soon as it is decompressed; the
+//! payload hash is accumulated incrementally and compared with the signed
+//! metadata before the staged file is moved into place. Size and ratio limits
+//! stop decompression bombs early.
+//!
+//! Bundle layout (all integers little-endian):
+//!
+//! ```text
+//! header: magic "ARKZ" | version u16 | chunk_size u32 | total_size u64
+//! chunk:  compressed_len u32 | decompressed_len u32 | blake3 [32] | zstd frame
+//! ```
+//!
+//! ## Biblical Foundation
+//! "Whoever is faithful in a very little is also faithful in much" - Luke 16:10
+
+use std::io::{Read, Write};
+use std::path::Path;
+
+use blake3::{Hash, Hasher};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::OrchestratorError;
+
+/// Bundle magic bytes
+pub const BUNDLE_MAGIC: &[u8; 4] = b"ARKZ";
+
+/// Current bundle format version
+pub const BUNDLE_VERSION: u16 = 1;
+
+/// Default decompressed chunk size
+pub const DEFAULT_CHUNK_SIZE: u32 = 1024 * 1024;
+
+const HEADER_LEN: usize = 4 + 2 + 4 + 8;
+const CHUNK_HEADER_LEN: usize = 4 + 4 + 32;
+
+/// Limits applied while ingesting a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestLimits {
+    /// Maximum decompressed payload size
+    pub max_decompressed_size: u64,
+    /// Maximum decompressed chunk size a bundle may declare
+    pub max_chunk_size: u32,
+    /// Maximum ratio of decompressed to compressed bytes
+    pub max_compression_ratio: f64,
+}
+
+impl Default for IngestLimits {
+    fn default() -> Self {
+        Self {
+            max_decompressed_size: 10 * 1024 * 1024,
+            max_chunk_size: 4 * 1024 * 1024,
+            max_compression_ratio: 50.0,
+        }
+    }
+}
+
+/// Statistics of an ingested bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestReport {
+    pub hash: Hash,
+    pub chunks: u32,
+    pub compressed_size: u64,
+    pub decompressed_size: u64,
+}
+
+/// Compress a payload into a bundle, returning the payload hash
+pub fn write_bundle<R: Read, W: Write>(
+    mut input: R,
+    mut output: W,
+    total_size: u64,
+    chunk_size: u32,
+    level: i32,
+) -> Result<Hash, OrchestratorError> {
+    let io_error = |e: std::io::Error| OrchestratorError::Ingestion(e.to_string());
+
+    output.write_all(BUNDLE_MAGIC).map_err(io_error)?;
+    output.write_all(&BUNDLE_VERSION.to_le_bytes()).map_err(io_error)?;
+    output.write_all(&chunk_size.to_le_bytes()).map_err(io_error)?;
+    output.write_all(&total_size.to_le_bytes()).map_err(io_error)?;
+
+    let mut hasher = Hasher::new();
+    let mut chunk = vec![0u8; chunk_size as usize];
+    let mut written = 0u64;
+    loop {
+        let filled = read_full(&mut input, &mut chunk).map_err(io_error)?;
+        if filled == 0 {
+            break;
+        }
+        let data = &chunk[..filled];
+        hasher.update(data);
+        written += filled as u64;
+
+        let compressed = zstd::bulk::compress(data, level).map_err(io_error)?;
+        output.write_all(&(compressed.len() as u32).to_le_bytes()).map_err(io_error)?;
+        output.write_all(&(filled as u32).to_le_bytes()).map_err(io_error)?;
+        output.write_all(blake3::hash(data).as_bytes()).map_err(io_error)?;
+        output.write_all(&compressed).map_err(io_error)?;
+    }
+
+    if written != total_size {
+        return Err(OrchestratorError::Ingestion(
+            format!("Declared size {} but read {} bytes", total_size, written)
+        ));
+    }
+    Ok(hasher.finalize())
+}
+
+/// Decompress and verify a bundle into `destination`
+///
+/// The payload is written to a temporary file next to `destination` and only
+/// renamed into place once every chunk and the overall hash have verified.
+pub fn ingest_bundle<R: Read>(
+    mut input: R,
+    destination: &Path,
+    expected: &Hash,
+    limits: &IngestLimits,
+) -> Result<IngestReport, OrchestratorError> {
+    let io_error = |e: std::io::Error| OrchestratorError::Ingestion(e.to_string());
+
+    let mut header = [0u8; HEADER_LEN];
+    input.read_exact(&mut header).map_err(io_error)?;
+    if &header[..4] != BUNDLE_MAGIC {
+        return Err(OrchestratorError::Ingestion("Not a patch bundle".into()));
+    }
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    if version != BUNDLE_VERSION {
+        return Err(OrchestratorError::Ingestion(format!("Unsupported bundle version {}", version)));
+    }
+    let chunk_size = u32::from_le_bytes(header[6..10].try_into().unwrap());
+    let total_size = u64::from_le_bytes(header[10..18].try_into().unwrap());
+
+    if chunk_size == 0 || chunk_size > limits.max_chunk_size {
+        return Err(OrchestratorError::Ingestion(format!("Chunk size {} outside limits", chunk_size)));
+    }
+    if total_size > limits.max_decompressed_size {
+        return Err(OrchestratorError::PatchTooLarge {
+            size: total_size,
+            max_allowed: limits.max_decompressed_size,
+        });
+    }
+
+    let directory = destination.parent().unwrap_or_else(|| Path::new("."));
+    let mut staged = tempfile::NamedTempFile::new_in(directory).map_err(io_error)?;
+
+    let max_compressed = zstd::zstd_safe::compress_bound(chunk_size as usize);
+    let mut hasher = Hasher::new();
+    let mut report = IngestReport {
+        hash: *expected,
+        chunks: 0,
+        compressed_size: HEADER_LEN as u64,
+        decompressed_size: 0,
+    };
+
+    while report.decompressed_size < total_size {
+        let mut chunk_header = [0u8; CHUNK_HEADER_LEN];
+        input.read_exact(&mut chunk_header)
+            .map_err(|e| OrchestratorError::Ingestion(format!("Truncated bundle at chunk {}: {}", report.chunks, e)))?;
+        let compressed_len = u32::from_le_bytes(chunk_header[0..4].try_into().unwrap()) as usize;
+        let decompressed_len = u32::from_le_bytes(chunk_header[4..8].try_into().unwrap());
+        let chunk_hash = Hash::from(<[u8; 32]>::try_from(&chunk_header[8..40]).unwrap());
+
+        if decompressed_len == 0 || decompressed_len > chunk_size || compressed_len > max_compressed {
+            return Err(OrchestratorError::Ingestion(format!("Chunk {} has invalid lengths", report.chunks)));
+        }
+        if report.decompressed_size + decompressed_len as u64 > total_size {
+            return Err(OrchestratorError::Ingestion("Bundle exceeds its declared size".into()));
+        }
+
+        // Reject bombs before spending effort on decompression
+        report.compressed_size += (CHUNK_HEADER_LEN + compressed_len) as u64;
+        let ratio = (report.decompressed_size + decompressed_len as u64) as f64 / report.compressed_size as f64;
+        if ratio > limits.max_compression_ratio {
+            return Err(OrchestratorError::Ingestion(
+                format!("Compression ratio {:.1} exceeds limit {:.1}", ratio, limits.max_compression_ratio)
+            ));
+        }
+
+        let mut compressed = vec![0u8; compressed_len];
+        input.read_exact(&mut compressed)
+            .map_err(|e| OrchestratorError::Ingestion(format!("Truncated bundle at chunk {}: {}", report.chunks, e)))?;
+        let data = zstd::bulk::decompress(&compressed, decompressed_len as usize)
+            .map_err(|e| OrchestratorError::Ingestion(format!("Chunk {} failed to decompress: {}", report.chunks, e)))?;
+
+        if data.len() != decompressed_len as usize || blake3::hash(&data) != chunk_hash {
+            return Err(OrchestratorError::Ingestion(format!("Chunk {} is corrupt", report.chunks)));
+        }
+
+        hasher.update(&data);
+        staged.write_all(&data).map_err(io_error)?;
+        report.decompressed_size += data.len() as u64;
+        report.chunks += 1;
+    }
+
+    let computed = hasher.finalize();
+    if computed != *expected {
+        return Err(OrchestratorError::HashMismatch {
+            expected: *expected,
+            computed,
+        });
+    }
+
+    staged.as_file().sync_all().map_err(io_error)?;
+    staged.persist(destination)
+        .map_err(|e| OrchestratorError::Ingestion(e.to_string()))?;
+
+    debug!("Ingested bundle: {} chunks, {} -> {} bytes",
+           report.chunks, report.compressed_size, report.decompressed_size);
+    Ok(report)
+}
+
+/// Fill `buf` from `reader`, returning fewer bytes only at end of input
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn bundle(payload: &[u8], chunk_size: u32) -> (Vec<u8>, Hash) {
+        let mut out = Vec::new();
+        let hash = write_bundle(payload, &mut out, payload.len() as u64, chunk_size, 3).unwrap();
+        (out, hash)
+    }
+
+    /// Pseudo-random, poorly compressible payload
+    fn payload() -> Vec<u8> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..800_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let dir = tempdir().unwrap();
+        let payload = payload();
+        let (bundle, hash) = bundle(&payload, 64 * 1024);
+        assert_eq!(hash, blake3::hash(&payload));
+
+        let destination = dir.path().join("fw.patch");
+        let report = ingest_bundle(&bundle[..], &destination, &hash, &IngestLimits::default()).unwrap();
+
+        assert_eq!(report.decompressed_size, payload.len() as u64);
+        assert_eq!(report.chunks, 13);
+        assert_eq!(std::fs::read(&destination).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_corrupt_chunk_detected_and_nothing_staged() {
+        let dir = tempdir().unwrap();
+        let payload = payload();
+        let (mut bundle, hash) = bundle(&payload, 64 * 1024);
+
+        // Flip a byte in the first chunk's hash
+        bundle[HEADER_LEN + 8] ^= 0xFF;
+
+        let destination = dir.path().join("fw.patch");
+        let err = ingest_bundle(&bundle[..], &destination, &hash, &IngestLimits::default()).unwrap_err();
+        assert!(matches!(err, OrchestratorError::Ingestion(ref m) if m.contains("Chunk 0")));
+        assert!(!destination.exists());
+    }
+
+    #[test]
+    fn test_decompression_bomb_rejected() {
+        let dir = tempdir().unwrap();
+        let zeros = vec![0u8; 2 * 1024 * 1024];
+        let (bundle, hash) = bundle(&zeros, 1024 * 1024);
+
+        let destination = dir.path().join("bomb.patch");
+        let err = ingest_bundle(&bundle[..], &destination, &hash, &IngestLimits::default()).unwrap_err();
+        assert!(matches!(err, OrchestratorError::Ingestion(ref m) if m.contains("ratio")));
+
+        let small = IngestLimits { max_decompressed_size: 1024, ..IngestLimits::default() };
+        assert!(matches!(
+            ingest_bundle(&bundle[..], &destination, &hash, &small),
+            Err(OrchestratorError::PatchTooLarge { .. })
+        ));
+    }
+}
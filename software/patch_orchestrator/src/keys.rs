@@ -0,0 +1,191 @@
+//! Release Key Fingerprints and Pins
+//!
+//! Operators see the trusted release keys as one hybrid fingerprint, the
+//! SHA3-256 hash of the Ed25519 and Dilithium3 public keys (see
+//! `pq_types::pins`), the same fingerprint the sentinel prints for a peer.
+//! With `pinned_keys` configured, the release keys are pinned as peer
+//! `release` (`release@<namespace>` outside the default namespace): before
+//! a patch signature is verified, the fingerprint of the configured
+//! keys must match the pin. A changed fingerprint is logged
+//! as an alert and refused until an operator pins the new keys with
+//! `keys pin`.
+//!
+//! ## Biblical Foundation
+//! "Remove not the ancient landmark, which thy fathers have set" - Proverbs 22:28
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use pq_types::pins::{KeyFingerprint, PinCheck, PinStore};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::OrchestratorError;
+
+/// Peer name the release keys of the default namespace are pinned under
+pub const RELEASE_PEER: &str = "release";
+
+/// Peer name the release keys of `namespace` are pinned under
+pub fn release_peer(namespace: &str) -> String {
+    if namespace == crate::namespace::DEFAULT_NAMESPACE {
+        RELEASE_PEER.to_string()
+    } else {
+        format!("{}@{}", RELEASE_PEER, namespace)
+    }
+}
+
+/// How the release keys compare with their pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinStatus {
+    /// No pin file configured
+    Disabled,
+    /// Release keys are not pinned yet
+    Unpinned,
+    /// Release keys match their pin
+    Match,
+    /// Release keys differ from their pin
+    Changed,
+}
+
+/// Result of `keys show`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyReport {
+    pub namespace: String,
+    /// Hybrid fingerprint of the trusted Ed25519 and Dilithium3 release keys
+    pub release_fingerprint: Option<KeyFingerprint>,
+    /// Algorithms with a trusted key configured
+    pub algorithms: Vec<String>,
+    pub pin_status: PinStatus,
+    /// Pinned peers and their fingerprints
+    pub pins: BTreeMap<String, KeyFingerprint>,
+}
+
+/// Result of `keys pin`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinResult {
+    pub peer: String,
+    pub fingerprint: KeyFingerprint,
+    /// Fingerprint the peer was pinned to before
+    pub replaced: Option<KeyFingerprint>,
+}
+
+/// Hybrid fingerprint of the trusted release keys, if both halves are configured
+pub fn release_fingerprint(signing_keys: &HashMap<String, Vec<u8>>) -> Option<KeyFingerprint> {
+    let ed25519 = signing_keys.get("ed25519")?;
+    let dilithium = signing_keys.get("dilithium3")?;
+    Some(KeyFingerprint::hybrid(ed25519, dilithium))
+}
+
+/// Load a pin file
+pub fn load_pins(path: &Path) -> Result<PinStore, OrchestratorError> {
+    PinStore::load(path).map_err(|e| OrchestratorError::KeyPin(format!("{:?}: {}", path, e)))
+}
+
+/// Report the release fingerprint and the pins in `pin_file`
+pub fn report(
+    namespace: &str,
+    signing_keys: &HashMap<String, Vec<u8>>,
+    pin_file: Option<&Path>,
+) -> Result<KeyReport, OrchestratorError> {
+    let fingerprint = release_fingerprint(signing_keys);
+    let mut algorithms: Vec<String> = signing_keys.keys().cloned().collect();
+    algorithms.sort();
+
+    let (pin_status, pins) = match pin_file {
+        None => (PinStatus::Disabled, BTreeMap::new()),
+        Some(path) => {
+            let store = load_pins(path)?;
+            let status = match (fingerprint, store.get(&release_peer(namespace))) {
+                (_, None) => PinStatus::Unpinned,
+                (Some(fingerprint), Some(pinned)) if fingerprint == pinned => PinStatus::Match,
+                _ => PinStatus::Changed,
+            };
+            (status, store.iter().map(|(peer, fingerprint)| (peer.to_string(), fingerprint)).collect())
+        }
+    };
+
+    Ok(KeyReport {
+        namespace: namespace.to_string(),
+        release_fingerprint: fingerprint,
+        algorithms,
+        pin_status,
+        pins,
+    })
+}
+
+/// Pin `peer` to `fingerprint` in `pin_file`
+pub fn pin(pin_file: &Path, peer: &str, fingerprint: KeyFingerprint) -> Result<PinResult, OrchestratorError> {
+    let mut store = load_pins(pin_file)?;
+    let replaced = store.pin(peer, fingerprint).map_err(OrchestratorError::KeyPin)?;
+    store.save().map_err(|e| OrchestratorError::KeyPin(format!("{:?}: {}", pin_file, e)))?;
+    Ok(PinResult { peer: peer.to_string(), fingerprint, replaced })
+}
+
+/// Refuse release keys whose fingerprint differs from their pin
+///
+/// Keys are accepted when no pin file is configured or the release keys
+/// are not pinned yet.
+pub fn check_release_pin(
+    namespace: &str,
+    signing_keys: &HashMap<String, Vec<u8>>,
+    pin_file: Option<&Path>,
+) -> Result<(), OrchestratorError> {
+    let Some(path) = pin_file else { return Ok(()) };
+    let fingerprint = release_fingerprint(signing_keys)
+        .ok_or_else(|| OrchestratorError::KeyPin("Release keys are pinned but not configured".into()))?;
+    match load_pins(path)?.check(&release_peer(namespace), fingerprint) {
+        PinCheck::Changed { pinned, presented } => {
+            error!("ALERT: release key fingerprint of {} changed from pinned {} to {}", namespace, pinned, presented);
+            ark_alert::raise(
+                ark_alert::Alert::new("patch_orchestrator", "key_pin_changed", namespace, ark_alert::Severity::Critical,
+                                      "release key fingerprint differs from its pin")
+                    .with_detail("pinned", &pinned)
+                    .with_detail("presented", &presented),
+            );
+            Err(OrchestratorError::KeyPin(format!(
+                "Release key fingerprint {} does not match pinned {}", presented, pinned
+            )))
+        }
+        PinCheck::Match | PinCheck::Unpinned => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn keys(dilithium: u8) -> HashMap<String, Vec<u8>> {
+        HashMap::from([
+            ("ed25519".to_string(), vec![1u8; 32]),
+            ("dilithium3".to_string(), vec![dilithium; 1952]),
+        ])
+    }
+
+    #[test]
+    fn test_rotated_release_keys_refused_until_repinned() {
+        let dir = tempdir().unwrap();
+        let pins = dir.path().join("pins.json");
+        assert!(check_release_pin("default", &keys(2), Some(&pins)).is_ok());
+        assert_eq!(report("default", &keys(2), Some(&pins)).unwrap().pin_status, PinStatus::Unpinned);
+
+        let pinned = pin(&pins, RELEASE_PEER, release_fingerprint(&keys(2)).unwrap()).unwrap();
+        assert_eq!(pinned.replaced, None);
+        assert_eq!(report("default", &keys(2), Some(&pins)).unwrap().pin_status, PinStatus::Match);
+
+        let rotated = keys(3);
+        assert!(matches!(check_release_pin("default", &rotated, Some(&pins)), Err(OrchestratorError::KeyPin(_))));
+        // Other namespaces are pinned under their own peer
+        assert!(check_release_pin("eu-west", &rotated, Some(&pins)).is_ok());
+        assert_eq!(release_peer("eu-west"), "release@eu-west");
+        let report = report("default", &rotated, Some(&pins)).unwrap();
+        assert_eq!(report.pin_status, PinStatus::Changed);
+        assert_eq!(report.algorithms, ["dilithium3", "ed25519"]);
+
+        let repinned = pin(&pins, RELEASE_PEER, release_fingerprint(&rotated).unwrap()).unwrap();
+        assert_eq!(repinned.replaced, Some(pinned.fingerprint));
+        assert!(check_release_pin("default", &rotated, Some(&pins)).is_ok());
+        assert!(check_release_pin("default", &keys(2), None).is_ok());
+    }
+}
@@ -8,7 +8,33 @@
 //! "Every good gift and every perfect gift is from above" - James 1:17
 //! Patches must demonstrate moral goodness before deployment.
 
-use std::collections::HashMap;
+#[cfg(feature = "api")]
+pub mod api;
+pub mod approval;
+pub mod audit;
+pub mod cold_mirror_patch;
+pub mod conflict;
+pub mod emergency;
+pub mod ethics_patch;
+pub mod handoff;
+pub mod ingest;
+pub mod keys;
+pub mod maintenance;
+pub mod namespace;
+pub mod persona;
+#[cfg(any(test, kani))]
+mod properties;
+pub mod release;
+pub mod repository;
+pub mod responses;
+pub mod sbom;
+pub mod signing;
+pub mod slo;
+pub mod snapshot;
+pub mod staging;
+pub mod transparency;
+
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 
@@ -18,18 +44,43 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 use tracing::{info, warn, error, debug};
 
 // Post-quantum imports
-use pqcrypto_dilithium::{
-    sign as dilithium_sign,
-    verify as dilithium_verify,
-    keypair as dilithium_keypair,
-    PublicKey as DilithiumPublicKey,
-    SecretKey as DilithiumSecretKey,
-    Signature as DilithiumSignature,
+use pq_types::scheme::{Dilithium3, MlDsa65, SchemeError, SignatureScheme};
+use pq_types::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, Ed25519SignatureBytes, MlDsaPublicKeyBytes,
+    MlDsaSecretKeyBytes, PqSignatureBytes,
 };
-use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey as Ed25519PublicKey, Signature as Ed25519Signature};
+use pq_types::decode::{self, Validate};
+use pq_types::pins::KeyFingerprint;
+use ark_alert::{Alert, AlertConfig, Severity};
+use ark_config::ConfigAttestation;
+use ark_crash::{CrashConfig, CrashStore, CrashSummary};
+use ark_provenance::ProvenanceManifest;
+use ark_storage::{MigrationReport, Storage, StorageConfig};
+use ed25519_dalek::{Signature as Ed25519Signature, Signer as _, SigningKey as Ed25519SigningKey, VerifyingKey as Ed25519VerifyingKey};
+use pq_types::dalek;
 
 use ethics_dsl::{EthicsEngine, Decision, Actor, Content, Context};
 use cold_mirror::{HarmPredictor, HarmCategory, RiskLevel};
+use co_audit_ai::SecurityIssue;
+use co_audit_ai::binary::{self, BinaryAuditConfig, BinaryReport};
+
+use approval::{ApprovalPolicy, ApprovalRequest, ApprovalSet, DetachedApproval, TrustBundle};
+use audit::{AuditEvent, AuditTrail};
+use emergency::{ActiveEmergency, EmergencyAuthorization, EmergencyPolicy, EmergencyScope};
+use cold_mirror_patch::{PredictorLoader, ShadowPolicy, ShadowSource, ShadowVerdict};
+use conflict::{ConflictPolicy, ConflictResolution, Footprint};
+use ethics_patch::EthicsPatchPolicy;
+use handoff::{HandoffMessage, HandoffPolicy, HandoffState, KeyMaterial};
+use ingest::IngestLimits;
+use keys::{KeyReport, PinResult};
+use maintenance::{DeferredPatch, MaintenancePolicy, WindowCheck};
+use namespace::NamespaceConfig;
+use persona::{ActiveOverride, PersonaPolicy, SignedOverride};
+use release::SignedRelease;
+use repository::PatchRepository;
+use sbom::Bom;
+use slo::{PatchTimeline, SloConfig, SloReport};
+use staging::Promotion;
 
 /// Biblical principles for patch evaluation
 pub const PATCH_PRINCIPLES: &[&str] = &[
@@ -84,12 +135,93 @@ pub struct PatchMetadata {
     pub harm_analysis: HarmAnalysis,
     pub created_at: SystemTime,
     pub expires_at: Option<SystemTime>,
-    /// Post-quantum signature (Dilithium3)
-    pub pq_signature: Option<Vec<u8>>,
+    /// Post-quantum signature (Dilithium3 or ML-DSA-65, detached)
+    pub pq_signature: Option<PqSignatureBytes>,
     /// Classical signature (Ed25519) for backwards compatibility
-    pub classical_signature: Option<Vec<u8>>,
+    pub classical_signature: Option<Ed25519SignatureBytes>,
     /// Signature algorithm used
     pub signature_algorithm: SignatureAlgorithm,
+    /// Findings of the binary payload audit
+    #[serde(default)]
+    pub security_issues: Vec<SecurityIssue>,
+    /// Namespace the patch was built for
+    #[serde(default = "namespace::default_namespace")]
+    pub namespace: String,
+    /// Files the patch writes, relative to the component; empty means the whole component
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Pending patches this patch replaces
+    #[serde(default)]
+    pub supersedes: Vec<String>,
+    /// Emergency authorization the moral assessment was relaxed under
+    #[serde(default)]
+    pub emergency_scope: Option<EmergencyScope>,
+}
+
+impl PatchMetadata {
+    /// Reset the fields the orchestrator fills in, before submitting
+    ///
+    /// `patch_data` sets the hash and size; bundles, hashed while streamed,
+    /// pass `None` and keep the ones they declare.
+    pub fn prepare_submission(&mut self, patch_data: Option<&[u8]>) {
+        if let Some(patch_data) = patch_data {
+            self.hash = blake3::hash(patch_data);
+            self.size_bytes = patch_data.len() as u64;
+        }
+        self.created_at = SystemTime::now();
+        self.verification = VerificationStatus::Pending;
+        self.moral_assessment = PatchMorality::Pending;
+        self.emergency_scope = None;
+        self.harm_analysis = HarmAnalysis {
+            moral_harm_risk: RiskLevel::Unknown,
+            physical_harm_risk: RiskLevel::Unknown,
+            psychological_harm_risk: RiskLevel::Unknown,
+            spiritual_harm_risk: RiskLevel::Unknown,
+            system_integrity_risk: RiskLevel::Unknown,
+            overall_risk: RiskLevel::Unknown,
+            mitigation_required: false,
+            biblical_concerns: vec![],
+            overridden_concerns: vec![],
+        };
+    }
+}
+
+/// Longest accepted patch id, component, version or namespace
+pub const MAX_PATCH_FIELD_LENGTH: usize = 256;
+
+/// Longest accepted patch description or justification
+pub const MAX_PATCH_TEXT_LENGTH: usize = 64 * 1024;
+
+/// Most dependencies or findings accepted on one patch
+pub const MAX_PATCH_ENTRIES: usize = 1024;
+
+impl Validate for PatchMetadata {
+    fn validate(&self) -> Result<(), String> {
+        // Ids and components name staged files and backups
+        decode::check_identifier("id", &self.id, MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_identifier("component", &self.component, MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_identifier("namespace", &self.namespace, MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("version", &self.version, MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("description", &self.description, MAX_PATCH_TEXT_LENGTH)?;
+        if let Some(justification) = &self.biblical_justification {
+            decode::check_len("biblical_justification", justification, MAX_PATCH_TEXT_LENGTH)?;
+        }
+        decode::check_count("dependencies", self.dependencies.len(), MAX_PATCH_ENTRIES)?;
+        for dependency in &self.dependencies {
+            decode::check_identifier("dependency", dependency, MAX_PATCH_FIELD_LENGTH)?;
+        }
+        decode::check_count("security_issues", self.security_issues.len(), MAX_PATCH_ENTRIES)?;
+        decode::check_count("files", self.files.len(), MAX_PATCH_ENTRIES)?;
+        for file in &self.files {
+            decode::check_len("file", file, MAX_PATCH_FIELD_LENGTH)?;
+        }
+        conflict::check_files(&self.files)?;
+        decode::check_count("supersedes", self.supersedes.len(), MAX_PATCH_ENTRIES)?;
+        for superseded in &self.supersedes {
+            decode::check_identifier("supersedes", superseded, MAX_PATCH_FIELD_LENGTH)?;
+        }
+        Ok(())
+    }
 }
 
 /// Signature algorithm for patches
@@ -101,6 +233,10 @@ pub enum SignatureAlgorithm {
     Dilithium3,
     /// Hybrid Ed25519 + Dilithium3
     HybridEd25519Dilithium3,
+    /// Post-quantum ML-DSA-65 (FIPS 204)
+    MlDsa65,
+    /// Hybrid Ed25519 + ML-DSA-65
+    HybridEd25519MlDsa65,
 }
 
 /// Patch criticality levels
@@ -129,8 +265,37 @@ pub struct HarmAnalysis {
     pub overall_risk: RiskLevel,
     pub mitigation_required: bool,
     pub biblical_concerns: Vec<String>,
+    /// Concern rules that matched but were relaxed by an operator override
+    #[serde(default)]
+    pub overridden_concerns: Vec<String>,
+}
+
+/// Named rule raising a Biblical concern when a patch description mentions one of its keywords
+pub struct ConcernRule {
+    pub name: &'static str,
+    pub keywords: &'static [&'static str],
+    pub concern: &'static str,
 }
 
+/// Concern rules checked on every patch; operator overrides relax them by name
+pub const CONCERN_RULES: &[ConcernRule] = &[
+    ConcernRule {
+        name: "kill_switch",
+        keywords: &["kill", "shutdown", "disable"],
+        concern: "Potential kill-switch functionality violates divine autonomy",
+    },
+    ConcernRule {
+        name: "moral_compromise",
+        keywords: &["compromise", "bypass"],
+        concern: "May compromise moral integrity",
+    },
+    ConcernRule {
+        name: "external_control",
+        keywords: &["remote", "external"],
+        concern: "External control mechanisms violate autonomous mission",
+    },
+];
+
 /// Patch orchestrator configuration
 #[derive(Debug, Clone, Serialize, Deserialize, ZeroizeOnDrop)]
 pub struct OrchestratorConfig {
@@ -144,6 +309,112 @@ pub struct OrchestratorConfig {
     #[zeroize(skip)]
     pub signing_keys: HashMap<String, Vec<u8>>,
     pub moral_strictness: MoralStrictness,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub ethics_patch_policy: EthicsPatchPolicy,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub shadow_policy: ShadowPolicy,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub handoff_policy: HandoffPolicy,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub ingest_limits: IngestLimits,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub approval_policy: ApprovalPolicy,
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub binary_audit: BinaryAuditConfig,
+    /// Requirements for activating emergency strictness
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub emergency_policy: EmergencyPolicy,
+    /// Operators allowed to relax concern rules with signed overrides
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub persona_policy: PersonaPolicy,
+    /// Handling of pending patches with overlapping footprints
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub conflict_policy: ConflictPolicy,
+    /// Namespace served by this instance
+    #[serde(default = "namespace::default_namespace")]
+    #[zeroize(skip)]
+    pub namespace: String,
+    /// Per-namespace key sets and component trees
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub namespaces: HashMap<String, NamespaceConfig>,
+    /// Lifecycle targets checked by the SLO report
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub slo: SloConfig,
+    /// Pinned-peer file; release keys must match their pin before a
+    /// signature is verified with them
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub pinned_keys: Option<PathBuf>,
+    /// Where crash reports of this orchestrator are kept
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub crash_reports: CrashConfig,
+    /// Store of the audit trail and patch repository; a relative path is
+    /// below the patch directory. sled locks its database to one process,
+    /// so handoff to a standby needs the files or SQLite backend
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub storage: StorageConfig,
+    /// Deduplication, rate limits and sinks of the orchestrator's alerts
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub alerts: AlertConfig,
+    /// Transparency log every submitted patch must be logged in
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub transparency: Option<transparency::TransparencyPolicy>,
+    /// Windows auto-applied patches are confined to
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub maintenance: MaintenancePolicy,
+}
+
+#[cfg(test)]
+impl OrchestratorConfig {
+    /// Configuration with its directories below `root` and every policy at
+    /// its default, shared by the tests
+    pub(crate) fn for_tests(root: &Path) -> Self {
+        Self {
+            patch_directory: root.join("patches"),
+            staging_directory: root.join("staging"),
+            backup_directory: root.join("backups"),
+            max_patch_size: 1024 * 1024,
+            verification_timeout: Duration::from_secs(30),
+            auto_apply_threshold: CriticalityLevel::High,
+            require_biblical_justification: true,
+            signing_keys: HashMap::new(),
+            moral_strictness: MoralStrictness::Standard,
+            ethics_patch_policy: EthicsPatchPolicy::default(),
+            shadow_policy: ShadowPolicy::default(),
+            handoff_policy: HandoffPolicy::default(),
+            ingest_limits: IngestLimits::default(),
+            approval_policy: ApprovalPolicy::default(),
+            binary_audit: BinaryAuditConfig::default(),
+            emergency_policy: EmergencyPolicy::default(),
+            persona_policy: PersonaPolicy::default(),
+            conflict_policy: ConflictPolicy::default(),
+            namespace: namespace::default_namespace(),
+            namespaces: HashMap::new(),
+            slo: SloConfig::default(),
+            pinned_keys: None,
+            crash_reports: CrashConfig::default(),
+            storage: StorageConfig::default(),
+            alerts: AlertConfig::default(),
+            transparency: None,
+            maintenance: MaintenancePolicy::default(),
+        }
+    }
 }
 
 /// Moral strictness levels for patch evaluation
@@ -153,10 +424,37 @@ pub enum MoralStrictness {
     Orthodox,
     /// Standard Biblical compliance required
     Standard,
-    /// Permissive mode for emergency situations
+    /// Permissive mode for emergency situations; only entered through a
+    /// signed authorization (see `emergency`)
     Emergency,
 }
 
+/// Whether a patch assessed as `morality` may be applied under `strictness`
+///
+/// Wicked and Corrupting patches are refused at every strictness.
+pub fn morally_acceptable(strictness: &MoralStrictness, morality: &PatchMorality) -> bool {
+    match strictness {
+        MoralStrictness::Orthodox => *morality == PatchMorality::Righteous,
+        MoralStrictness::Standard => matches!(morality, PatchMorality::Righteous | PatchMorality::Permissible),
+        MoralStrictness::Emergency => !matches!(morality, PatchMorality::Wicked | PatchMorality::Corrupting),
+    }
+}
+
+/// Assessment of a patch the ethics engine judged `morality`, under `strictness`
+pub fn apply_strictness(strictness: &MoralStrictness, morality: PatchMorality) -> PatchMorality {
+    match strictness {
+        MoralStrictness::Orthodox if morality != PatchMorality::Righteous => PatchMorality::Questionable,
+        MoralStrictness::Orthodox | MoralStrictness::Standard => morality,
+        MoralStrictness::Emergency => {
+            if morality == PatchMorality::Corrupting || morality == PatchMorality::Wicked {
+                morality
+            } else {
+                PatchMorality::Permissible
+            }
+        },
+    }
+}
+
 /// Main patch orchestrator
 pub struct PatchOrchestrator {
     config: OrchestratorConfig,
@@ -164,16 +462,50 @@ pub struct PatchOrchestrator {
     harm_predictor: HarmPredictor,
     pending_patches: HashMap<String, PatchMetadata>,
     applied_patches: HashMap<String, PatchMetadata>,
+    /// Patches explicitly approved by an operator
+    approved_patches: HashSet<String>,
+    /// Verified detached approvals per patch
+    detached_approvals: HashMap<String, ApprovalSet>,
+    /// Record of patch application decisions
+    audit_trail: AuditTrail,
+    /// Stored pending, applied and approved patches
+    repository: PatchRepository,
+    /// Store behind the audit trail and the patch repository
+    storage: std::sync::Arc<dyn Storage>,
+    /// Loader for Cold-Mirror models
+    predictor_loader: Option<Box<dyn PredictorLoader>>,
+    /// Live traffic mirrored for Cold-Mirror shadow evaluation
+    shadow_traffic: Option<tokio::sync::Mutex<tokio::sync::mpsc::Receiver<cold_mirror::PredictionInput>>>,
     /// Post-quantum signing keypair
-    pq_signing_key: Option<(DilithiumPublicKey, DilithiumSecretKey)>,
+    pq_signing_key: Option<(DilithiumPublicKeyBytes, DilithiumSecretKeyBytes)>,
+    /// Standardized post-quantum signing keypair
+    ml_dsa_signing_key: Option<(MlDsaPublicKeyBytes, MlDsaSecretKeyBytes)>,
     /// Classical signing keypair for hybrid mode
-    classical_signing_key: Option<Ed25519Keypair>,
+    classical_signing_key: Option<Ed25519SigningKey>,
+    /// Emergency mode granted by a signed authorization
+    emergency: Option<ActiveEmergency>,
+    /// Concern rule overrides granted by signed operator claims
+    overrides: Vec<ActiveOverride>,
+    /// Overlapping pending patches each pending patch must wait for
+    blocked_by: HashMap<String, Vec<String>>,
+    /// Lifecycle timestamps of patches seen by this instance
+    timelines: HashMap<String, PatchTimeline>,
+    /// Submitted releases whose members are applied together
+    releases: HashMap<String, SignedRelease>,
 }
 
 impl PatchOrchestrator {
     /// Initialize the patch orchestrator with Biblical foundation
-    pub async fn new(config: OrchestratorConfig) -> Result<Self, OrchestratorError> {
+    pub async fn new(mut config: OrchestratorConfig) -> Result<Self, OrchestratorError> {
         info!("Initializing ARK Patch Orchestrator with Biblical moral compliance");
+        namespace::validate_name(&config.namespace)?;
+        config.maintenance.validate()?;
+        
+        // Emergency strictness requires a signed, time-limited authorization
+        if matches!(config.moral_strictness, MoralStrictness::Emergency) {
+            warn!("Emergency strictness in configuration ignored; using Standard until a signed authorization is activated");
+            config.moral_strictness = MoralStrictness::Standard;
+        }
         
         // Initialize ethics engine with Biblical principles
         let ethics_engine = EthicsEngine::new_with_principles(PATCH_PRINCIPLES.to_vec())
@@ -192,24 +524,61 @@ impl PatchOrchestrator {
         std::fs::create_dir_all(&config.backup_directory)
             .map_err(|e| OrchestratorError::DirectoryCreation(e.to_string()))?;
         
+        let storage = ark_storage::open(&config.storage.below(&config.patch_directory))
+            .map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+        let audit_trail = AuditTrail::new(storage.clone(), &config.namespace);
+        let imported = audit_trail.import_legacy(&config.patch_directory.join(audit::AUDIT_TRAIL_FILE))?;
+        if imported > 0 {
+            info!("Imported {} audit records into the {:?} store", imported, config.storage.backend);
+        }
+        let repository = PatchRepository::new(storage.clone(), &config.namespace);
+        let stored = repository.load()?;
+        if !stored.pending.is_empty() || !stored.applied.is_empty() {
+            info!("Loaded {} pending and {} applied patches from the store", stored.pending.len(), stored.applied.len());
+        }
+        
         // Generate post-quantum signing keys
-        let (pq_public, pq_secret) = dilithium_keypair();
+        let (pq_public, pq_secret) = Dilithium3::keypair().map_err(scheme_error)?;
+        let ml_dsa_keypair = MlDsa65::keypair().map_err(scheme_error)?;
         
         // Generate classical signing key for hybrid mode
         use rand::rngs::OsRng;
-        let classical_keypair = Ed25519Keypair::generate(&mut OsRng);
+        let classical_keypair = Ed25519SigningKey::generate(&mut OsRng);
         
-        info!("Generated post-quantum signing keys (Dilithium3)");
+        info!("Generated post-quantum signing keys (Dilithium3, ML-DSA-65)");
         info!("Generated classical signing keys (Ed25519) for hybrid mode");
         
         Ok(Self {
             config,
             ethics_engine,
             harm_predictor,
-            pending_patches: HashMap::new(),
-            applied_patches: HashMap::new(),
+            pending_patches: stored.pending.into_iter()
+                .map(|mut patch| {
+                    // No emergency authorization survives a restart
+                    if let Some(scope) = patch.emergency_scope.take() {
+                        patch.moral_assessment = scope.unrelaxed;
+                    }
+                    (patch.id.clone(), patch)
+                })
+                .collect(),
+            applied_patches: stored.applied.into_iter().map(|patch| (patch.id.clone(), patch)).collect(),
+            approved_patches: stored.approved.into_iter().collect(),
+            detached_approvals: approval::group_approvals(stored.detached_approvals),
+            audit_trail,
+            repository,
+            storage,
+            predictor_loader: None,
+            shadow_traffic: None,
             pq_signing_key: Some((pq_public, pq_secret)),
+            ml_dsa_signing_key: Some(ml_dsa_keypair),
             classical_signing_key: Some(classical_keypair),
+            emergency: None,
+            overrides: Vec::new(),
+            blocked_by: stored.blocked_by,
+            timelines: HashMap::new(),
+            releases: stored.releases.into_iter()
+                .map(|release| (release.manifest.release_id.clone(), release))
+                .collect(),
         })
     }
     
@@ -220,6 +589,25 @@ impl PatchOrchestrator {
         metadata: PatchMetadata,
     ) -> Result<String, OrchestratorError> {
         info!("Submitting patch {} for Biblical moral evaluation", metadata.id);
+        namespace::check_isolation(&self.config, &metadata)?;
+        let (patch_id, component) = (metadata.id.clone(), metadata.component.clone());
+        self.record_lifecycle(&patch_id, &component, AuditEvent::PatchSubmitted {
+            criticality: metadata.criticality.clone(),
+        });
+        let result = self.stage_patch(patch_data, metadata).await;
+        self.record_rejection(&patch_id, &component, &result);
+        self.persist_patches();
+        result
+    }
+    
+    /// Verify, assess and queue a submitted patch
+    async fn stage_patch(
+        &mut self,
+        patch_data: &[u8],
+        metadata: PatchMetadata,
+    ) -> Result<String, OrchestratorError> {
+        self.expire_emergency()?;
+        self.check_unique(&metadata.id)?;
         
         // Verify patch size constraints
         if metadata.size_bytes > self.config.max_patch_size {
@@ -238,16 +626,124 @@ impl PatchOrchestrator {
             });
         }
         
+        let updated_metadata = self.assess_patch(patch_data, metadata).await?;
+        
+        // Store patch for further processing
+        std::fs::write(self.staged_payload_path(&updated_metadata.id), patch_data)
+            .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+        self.queue_patch(updated_metadata).await
+    }
+    
+    /// Submit a zstd-compressed patch bundle without loading it into memory
+    ///
+    /// The bundle is decompressed and verified chunk by chunk straight into the
+    /// staging directory; assessment then reads the staged file through a
+    /// memory map.
+    pub async fn submit_patch_bundle<R: std::io::Read>(
+        &mut self,
+        bundle: R,
+        metadata: PatchMetadata,
+    ) -> Result<String, OrchestratorError> {
+        info!("Submitting patch bundle {} for Biblical moral evaluation", metadata.id);
+        namespace::check_isolation(&self.config, &metadata)?;
+        let (patch_id, component) = (metadata.id.clone(), metadata.component.clone());
+        self.record_lifecycle(&patch_id, &component, AuditEvent::PatchSubmitted {
+            criticality: metadata.criticality.clone(),
+        });
+        let result = self.stage_patch_bundle(bundle, metadata).await;
+        self.record_rejection(&patch_id, &component, &result);
+        self.persist_patches();
+        result
+    }
+    
+    /// Ingest, assess and queue a submitted bundle
+    async fn stage_patch_bundle<R: std::io::Read>(
+        &mut self,
+        bundle: R,
+        metadata: PatchMetadata,
+    ) -> Result<String, OrchestratorError> {
+        self.expire_emergency()?;
+        self.check_unique(&metadata.id)?;
+        
+        if metadata.size_bytes > self.config.max_patch_size {
+            return Err(OrchestratorError::PatchTooLarge {
+                size: metadata.size_bytes,
+                max_allowed: self.config.max_patch_size,
+            });
+        }
+        
+        let limits = IngestLimits {
+            max_decompressed_size: self.config.ingest_limits.max_decompressed_size.min(self.config.max_patch_size),
+            ..self.config.ingest_limits.clone()
+        };
+        let staged = self.staged_payload_path(&metadata.id);
+        let report = ingest::ingest_bundle(bundle, &staged, &metadata.hash, &limits)?;
+        info!("Ingested {} chunks ({} -> {} bytes) for {}",
+              report.chunks, report.compressed_size, report.decompressed_size, metadata.id);
+        
+        let assessed = {
+            let file = std::fs::File::open(&staged)
+                .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+            // SAFETY: the staged file is private to the orchestrator and is not
+            // modified while mapped
+            let payload = unsafe { memmap2::Mmap::map(&file) }
+                .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+            self.assess_patch(&payload, metadata).await
+        };
+        
+        match assessed {
+            Ok(updated_metadata) => self.queue_patch(updated_metadata).await,
+            Err(e) => {
+                let _ = std::fs::remove_file(&staged);
+                Err(e)
+            }
+        }
+    }
+    
+    /// Refuse an id that is already pending or applied
+    ///
+    /// Approvals are keyed by patch id; replacing the staged payload under
+    /// an approved id would let them vouch for content nobody reviewed.
+    fn check_unique(&self, patch_id: &str) -> Result<(), OrchestratorError> {
+        if self.pending_patches.contains_key(patch_id) || self.applied_patches.contains_key(patch_id) {
+            return Err(OrchestratorError::DuplicatePatch(patch_id.to_string()));
+        }
+        Ok(())
+    }
+    
+    /// Run moral and harm assessment, rejecting patches that fail
+    async fn assess_patch(
+        &self,
+        patch_data: &[u8],
+        metadata: PatchMetadata,
+    ) -> Result<PatchMetadata, OrchestratorError> {
+        if let Some(policy) = &self.config.transparency {
+            let head = transparency::check_logged(policy, &metadata, patch_data)?;
+            debug!("Patch {} is logged in {} at tree size {}", metadata.id, head.log_id, head.size);
+        }
+        
+        // Binary payloads are audited structurally; their bytes are not text
+        let binary_report = binary::is_binary(patch_data)
+            .then(|| binary::analyze(patch_data, &self.config.binary_audit));
+        
         // Perform Biblical moral assessment
-        let moral_assessment = self.assess_patch_morality(&metadata, patch_data).await?;
+        let morality = self.assess_patch_morality(&metadata, patch_data, binary_report.as_ref()).await?;
         
         // Perform harm analysis
         let harm_analysis = self.analyze_patch_harm(&metadata, patch_data).await?;
         
         // Update metadata with assessments
         let mut updated_metadata = metadata;
-        updated_metadata.moral_assessment = moral_assessment;
+        updated_metadata.moral_assessment = apply_strictness(&self.strictness(), morality.clone());
+        updated_metadata.emergency_scope = self.emergency().map(|emergency| EmergencyScope {
+            authorization: emergency.authorization.clone(),
+            expires_at: emergency.expires_at,
+            unrelaxed: apply_strictness(&self.config.moral_strictness, morality),
+        });
         updated_metadata.harm_analysis = harm_analysis;
+        updated_metadata.security_issues = binary_report
+            .map(|report| report.security_issues())
+            .unwrap_or_default();
         
         // Check if patch passes moral requirements
         if !self.is_morally_acceptable(&updated_metadata) {
@@ -258,30 +754,225 @@ impl PatchOrchestrator {
             return Err(OrchestratorError::MoralViolation(updated_metadata.id.clone()));
         }
         
-        // Store patch for further processing
-        let patch_id = updated_metadata.id.clone();
-        self.pending_patches.insert(patch_id.clone(), updated_metadata);
+        // Check binary findings against the rejection threshold
+        let blocking: Vec<&str> = updated_metadata.security_issues.iter()
+            .filter(|issue| issue.severity >= self.config.binary_audit.reject_severity)
+            .map(|issue| issue.description.as_str())
+            .collect();
+        if !blocking.is_empty() {
+            warn!("Patch {} rejected by binary audit: {}", updated_metadata.id, blocking.join("; "));
+            return Err(OrchestratorError::BinaryAuditFailed {
+                patch_id: updated_metadata.id.clone(),
+                findings: blocking.join("; "),
+            });
+        }
+        
+        Ok(updated_metadata)
+    }
+    
+    /// Queue an assessed, staged patch and auto-apply it if eligible
+    async fn queue_patch(&mut self, metadata: PatchMetadata) -> Result<String, OrchestratorError> {
+        let patch_id = metadata.id.clone();
+        self.record_lifecycle(&patch_id, &metadata.component, AuditEvent::PatchAssessed);
+        self.record_override_uses(&metadata)?;
+        self.resolve_conflicts(&metadata)?;
+        let component = metadata.component.clone();
+        self.pending_patches.insert(patch_id.clone(), metadata);
         
-        // Auto-apply if meets criteria
-        if self.should_auto_apply(&self.pending_patches[&patch_id]) {
-            info!("Auto-applying patch {} due to high priority and moral compliance", patch_id);
-            self.apply_patch(&patch_id).await?;
+        // Auto-apply if meets criteria, inside its maintenance window
+        if self.should_auto_apply(&self.pending_patches[&patch_id])
+            && self.maintenance_permits(&patch_id, &component, SystemTime::now(), true)
+        {
+            self.auto_apply(&patch_id, &component).await?;
         }
         
         Ok(patch_id)
     }
     
+    /// Apply an eligible patch without operator approval
+    async fn auto_apply(&mut self, patch_id: &str, component: &str) -> Result<(), OrchestratorError> {
+        info!("Auto-applying patch {} due to high priority and moral compliance", patch_id);
+        self.record_lifecycle(patch_id, component, AuditEvent::PatchApproved { automatic: true });
+        match self.install_patch(patch_id).await {
+            Ok(()) => Ok(()),
+            // Rule packs flipping too many replayed decisions wait for an operator
+            Err(OrchestratorError::ApprovalRequired { changed_ratio, .. }) => {
+                warn!("Auto-apply of {} blocked: {:.1}% of replayed decisions flip; approval required",
+                      patch_id, changed_ratio * 100.0);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Whether the maintenance windows let `patch_id` auto-apply at `now`
+    ///
+    /// Divine-criticality patches pass closed windows when the policy
+    /// allows it, loudly. Otherwise a closed window defers the patch; with
+    /// `notify` the deferral is audited and alerted.
+    fn maintenance_permits(&mut self, patch_id: &str, component: &str, now: SystemTime, notify: bool) -> bool {
+        let metadata = &self.pending_patches[patch_id];
+        let (window, opens_at) = match self.config.maintenance.check(metadata, now) {
+            WindowCheck::Unrestricted | WindowCheck::Open { .. } => return true,
+            WindowCheck::Closed { window, opens_at } => (window, opens_at),
+        };
+        let window = window.unwrap_or_default();
+        if metadata.criticality == CriticalityLevel::Divine && self.config.maintenance.divine_override {
+            warn!("MAINTENANCE OVERRIDE: Divine patch {} for {} applies outside maintenance window {}",
+                  patch_id, component, window);
+            self.record_lifecycle(patch_id, component, AuditEvent::MaintenanceOverride { window: window.clone() });
+            ark_alert::raise(
+                Alert::new("patch_orchestrator", "maintenance_override", component, Severity::Warning,
+                           "Divine patch applied outside its maintenance window")
+                    .with_detail("patch", patch_id)
+                    .with_detail("window", &window),
+            );
+            return true;
+        }
+        if notify {
+            let opens = opens_at.map(|at| chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339());
+            info!("Patch {} deferred until maintenance window {} opens at {}",
+                  patch_id, window, opens.as_deref().unwrap_or("an unknown time"));
+            self.record_lifecycle(patch_id, component, AuditEvent::PatchDeferred { window: window.clone(), opens_at });
+            ark_alert::raise(
+                Alert::new("patch_orchestrator", "patch_deferred", component, Severity::Info,
+                           "patch deferred to its maintenance window")
+                    .with_detail("patch", patch_id)
+                    .with_detail("window", &window)
+                    .with_detail("opens_at", opens.unwrap_or_else(|| "none within a year".into())),
+            );
+        }
+        false
+    }
+    
+    /// Patches that would auto-apply but wait for a closed maintenance window
+    pub fn deferred_patches(&self, now: SystemTime) -> Vec<DeferredPatch> {
+        let mut deferred: Vec<DeferredPatch> = self.pending_patches.values()
+            .filter(|metadata| self.should_auto_apply(metadata))
+            .filter_map(|metadata| match self.config.maintenance.check(metadata, now) {
+                WindowCheck::Closed { window, opens_at } => Some(DeferredPatch {
+                    patch_id: metadata.id.clone(),
+                    component: metadata.component.clone(),
+                    criticality: metadata.criticality.clone(),
+                    window,
+                    opens_at,
+                }),
+                _ => None,
+            })
+            .collect();
+        deferred.sort_by(|a, b| a.opens_at.cmp(&b.opens_at).then_with(|| a.patch_id.cmp(&b.patch_id)));
+        deferred
+    }
+    
+    /// Auto-apply the pending patches whose maintenance window is open
+    ///
+    /// Returns the patches applied. Run periodically so deferred patches
+    /// land inside their windows.
+    pub async fn apply_deferred_patches(&mut self) -> Result<Vec<String>, OrchestratorError> {
+        let now = SystemTime::now();
+        let mut due: Vec<(String, String)> = self.pending_patches.values()
+            .filter(|metadata| self.should_auto_apply(metadata))
+            .map(|metadata| (metadata.id.clone(), metadata.component.clone()))
+            .collect();
+        due.sort();
+        
+        let mut applied = Vec::new();
+        for (patch_id, component) in due {
+            if !self.maintenance_permits(&patch_id, &component, now, false) {
+                continue;
+            }
+            self.auto_apply(&patch_id, &component).await?;
+            if self.applied_patches.contains_key(&patch_id) {
+                applied.push(patch_id);
+            }
+        }
+        if !applied.is_empty() {
+            self.persist_patches();
+        }
+        Ok(applied)
+    }
+    
+    /// Compare a patch's footprint with the pending patches before queueing it
+    ///
+    /// Overlapping patches it supersedes are withdrawn; any others either
+    /// block it until they leave the queue or reject it, per the conflict
+    /// policy.
+    fn resolve_conflicts(&mut self, metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
+        let footprint = Footprint::of(metadata, &self.get_component_path(&metadata.component));
+        let conflicts = conflict::detect(metadata, &footprint, self.pending_patches.values()
+            .map(|pending| (pending, Footprint::of(pending, &self.get_component_path(&pending.component)))));
+        
+        let resolution = self.config.conflict_policy.resolution;
+        if !conflicts.unresolved.is_empty() && resolution == ConflictResolution::RequireSupersedes {
+            warn!("Patch {} overlaps pending patches {:?} without superseding them", metadata.id, conflicts.unresolved);
+            let _ = std::fs::remove_file(self.staged_payload_path(&metadata.id));
+            return Err(OrchestratorError::PatchConflict {
+                patch_id: metadata.id.clone(),
+                conflicts: conflicts.unresolved.join(", "),
+            });
+        }
+        
+        for superseded in &conflicts.superseded {
+            self.withdraw_patch(superseded, &metadata.id);
+        }
+        
+        self.blocked_by.remove(&metadata.id);
+        if !conflicts.unresolved.is_empty() {
+            info!("Patch {} queued behind overlapping patches {:?}", metadata.id, conflicts.unresolved);
+            if let Err(e) = self.audit_trail.record(&metadata.id, &metadata.component, AuditEvent::ConflictDetected {
+                conflicts: conflicts.unresolved.clone(),
+                resolution,
+            }) {
+                error!("Failed to audit conflicts of {}: {}", metadata.id, e);
+            }
+            self.blocked_by.insert(metadata.id.clone(), conflicts.unresolved);
+        }
+        Ok(())
+    }
+    
+    /// Drop a pending patch replaced by `superseded_by`
+    fn withdraw_patch(&mut self, patch_id: &str, superseded_by: &str) {
+        let Some(metadata) = self.pending_patches.remove(patch_id) else { return };
+        info!("Patch {} superseded by {}; withdrawn", patch_id, superseded_by);
+        self.approved_patches.remove(patch_id);
+        self.detached_approvals.remove(patch_id);
+        self.blocked_by.remove(patch_id);
+        let _ = std::fs::remove_file(self.staged_payload_path(patch_id));
+        if let Err(e) = self.audit_trail.record(patch_id, &metadata.component, AuditEvent::PatchSuperseded {
+            superseded_by: superseded_by.to_string(),
+        }) {
+            error!("Failed to audit withdrawal of {}: {}", patch_id, e);
+        }
+    }
+    
+    /// Pending patches that must be applied or withdrawn before `patch_id`
+    pub fn blocking_patches(&self, patch_id: &str) -> Vec<String> {
+        self.blocked_by.get(patch_id)
+            .map(|blockers| blockers.iter()
+                .filter(|blocker| self.pending_patches.contains_key(*blocker))
+                .cloned()
+                .collect())
+            .unwrap_or_default()
+    }
+    
     /// Assess patch morality according to Biblical principles
+    ///
+    /// Strictness is not applied here; see `apply_strictness`.
     async fn assess_patch_morality(
         &self,
         metadata: &PatchMetadata,
         patch_data: &[u8],
+        binary_report: Option<&BinaryReport>,
     ) -> Result<PatchMorality, OrchestratorError> {
         debug!("Assessing patch morality for {}", metadata.id);
         
-        // Convert patch to content for ethics evaluation
+        // Convert patch to content for ethics evaluation; only the embedded
+        // strings of a binary payload are meaningful text
         let content = Content {
-            text: String::from_utf8_lossy(patch_data).to_string(),
+            text: match binary_report {
+                Some(report) => report.strings.join("\n"),
+                None => String::from_utf8_lossy(patch_data).to_string(),
+            },
             metadata: HashMap::from([
                 ("component".to_string(), metadata.component.clone()),
                 ("description".to_string(), metadata.description.clone()),
@@ -322,25 +1013,7 @@ impl PatchOrchestrator {
             Decision::Deny => PatchMorality::Wicked,
             Decision::Purge => PatchMorality::Corrupting,
         };
-        
-        // Apply strictness level
-        match self.config.moral_strictness {
-            MoralStrictness::Orthodox => {
-                if morality != PatchMorality::Righteous {
-                    Ok(PatchMorality::Questionable)
-                } else {
-                    Ok(morality)
-                }
-            },
-            MoralStrictness::Standard => Ok(morality),
-            MoralStrictness::Emergency => {
-                if morality == PatchMorality::Corrupting || morality == PatchMorality::Wicked {
-                    Ok(morality)
-                } else {
-                    Ok(PatchMorality::Permissible)
-                }
-            },
-        }
+        Ok(morality)
     }
     
     /// Analyze potential harm from patch application
@@ -394,7 +1067,7 @@ impl PatchOrchestrator {
             .unwrap_or(RiskLevel::Low);
         
         // Check for Biblical concerns
-        let biblical_concerns = self.identify_biblical_concerns(metadata);
+        let (biblical_concerns, overridden_concerns) = self.identify_biblical_concerns(metadata);
         
         Ok(HarmAnalysis {
             moral_harm_risk: moral_harm,
@@ -405,121 +1078,726 @@ impl PatchOrchestrator {
             overall_risk,
             mitigation_required: overall_risk >= RiskLevel::Medium,
             biblical_concerns,
+            overridden_concerns,
         })
     }
     
     /// Identify Biblical concerns in patch
-    fn identify_biblical_concerns(&self, metadata: &PatchMetadata) -> Vec<String> {
+    ///
+    /// Returns the concerns raised and the names of matching rules that an
+    /// active operator override relaxes instead.
+    fn identify_biblical_concerns(&self, metadata: &PatchMetadata) -> (Vec<String>, Vec<String>) {
+        let description = metadata.description.to_lowercase();
         let mut concerns = Vec::new();
+        let mut overridden = Vec::new();
         
-        // Check for kill-switch related patches
-        if metadata.description.to_lowercase().contains("kill") 
-            || metadata.description.to_lowercase().contains("shutdown")
-            || metadata.description.to_lowercase().contains("disable") {
-            concerns.push("Potential kill-switch functionality violates divine autonomy".to_string());
+        for rule in CONCERN_RULES {
+            if !rule.keywords.iter().any(|keyword| description.contains(keyword)) {
+                continue;
+            }
+            if self.covering_override(rule.name, &metadata.component).is_some() {
+                overridden.push(rule.name.to_string());
+            } else {
+                concerns.push(rule.concern.to_string());
+            }
         }
         
-        // Check for moral compromise
-        if metadata.description.to_lowercase().contains("compromise")
-            || metadata.description.to_lowercase().contains("bypass") {
-            concerns.push("May compromise moral integrity".to_string());
+        (concerns, overridden)
+    }
+    
+    /// First active override relaxing `rule` for `component`
+    fn covering_override(&self, rule: &str, component: &str) -> Option<usize> {
+        let now = SystemTime::now();
+        self.overrides.iter().position(|active| active.covers(rule, component, now))
+    }
+    
+    /// Count an assessed patch against the budgets of the operators whose
+    /// overrides relaxed its concerns
+    ///
+    /// The budget belongs to the persona, not the claim: one use is shared
+    /// by every active override of the same operator.
+    fn record_override_uses(&mut self, metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
+        let mut used: Vec<usize> = Vec::new();
+        for rule in &metadata.harm_analysis.overridden_concerns {
+            if let Some(index) = self.covering_override(rule, &metadata.component) {
+                let operator = &self.overrides[index].operator;
+                if !used.iter().any(|&seen| self.overrides[seen].operator == *operator) {
+                    used.push(index);
+                }
+            }
         }
         
-        // Check for external control
-        if metadata.description.to_lowercase().contains("remote")
-            || metadata.description.to_lowercase().contains("external") {
-            concerns.push("External control mechanisms violate autonomous mission".to_string());
+        for index in used {
+            let operator = self.overrides[index].operator.clone();
+            for other in self.overrides.iter_mut().filter(|other| other.operator == operator) {
+                other.uses += 1;
+            }
+            let active = &self.overrides[index];
+            let rules: Vec<String> = metadata.harm_analysis.overridden_concerns.iter()
+                .filter(|rule| self.overrides.iter().any(|o| o.operator == operator && o.rules.contains(rule)))
+                .cloned()
+                .collect();
+            warn!("Patch {} relieved of {:?} by override {} of {} ({} of {} uses)",
+                  metadata.id, rules, active.claim, active.operator, active.uses, active.budget);
+            let event = AuditEvent::OverrideUsed {
+                claim: active.claim.clone(),
+                operator: active.operator.clone(),
+                rules,
+                uses: active.uses,
+                budget: active.budget,
+            };
+            self.audit_trail.record(&metadata.id, &metadata.component, event)?;
         }
+        Ok(())
+    }
+    
+    /// Activate a signed operator override file
+    ///
+    /// The claim is verified against the approval trust bundle and the
+    /// persona policy. Uses recorded in the audit trail for any claim of
+    /// the same operator count against the persona's budget, so neither
+    /// reloading a claim nor signing a new one renews it.
+    pub fn activate_override(&mut self, path: &Path) -> Result<ActiveOverride, OrchestratorError> {
+        let signed = SignedOverride::load(path)?;
+        let bundle_path = self.config.approval_policy.trust_bundle.as_ref()
+            .ok_or_else(|| OrchestratorError::Override("No trust bundle configured".into()))?;
+        let bundle = TrustBundle::load(bundle_path)?;
+        let mut active = persona::verify_override(
+            &signed,
+            &self.config.persona_policy,
+            &bundle,
+            &self.config.namespace,
+            SystemTime::now(),
+        )?;
+        active.uses = self.audit_trail.records()?.iter()
+            .filter(|record| matches!(&record.event, AuditEvent::OverrideUsed { operator, .. } if *operator == active.operator))
+            .count();
         
-        concerns
+        self.audit_trail.record("", "patch_orchestrator", AuditEvent::OverrideActivated {
+            claim: active.claim.clone(),
+            operator: active.operator.clone(),
+            rules: active.rules.clone(),
+            components: active.components.clone(),
+            reason: active.reason.clone(),
+            expires_at: active.expires_at,
+        })?;
+        warn!("Override {} by {} relaxes {:?} until {:?} ({} of {} uses spent): {}",
+              active.claim, active.operator, active.rules, active.expires_at, active.uses, active.budget, active.reason);
+        
+        self.overrides.retain(|existing| existing.claim != active.claim);
+        self.overrides.push(active.clone());
+        Ok(active)
+    }
+    
+    /// Overrides that are neither expired nor exhausted
+    pub fn overrides(&self) -> impl Iterator<Item = &ActiveOverride> {
+        let now = SystemTime::now();
+        self.overrides.iter().filter(move |active| !active.is_expired(now) && !active.is_exhausted())
     }
     
     /// Check if patch is morally acceptable for application
     fn is_morally_acceptable(&self, metadata: &PatchMetadata) -> bool {
-        match self.config.moral_strictness {
-            MoralStrictness::Orthodox => {
-                metadata.moral_assessment == PatchMorality::Righteous
-            },
-            MoralStrictness::Standard => {
-                matches!(
-                    metadata.moral_assessment,
-                    PatchMorality::Righteous | PatchMorality::Permissible
-                )
-            },
-            MoralStrictness::Emergency => {
-                !matches!(
-                    metadata.moral_assessment,
-                    PatchMorality::Wicked | PatchMorality::Corrupting
-                )
-            },
+        let strictness = self.strictness();
+        let accepted = morally_acceptable(&strictness, self.moral_assessment(metadata));
+        if matches!(strictness, MoralStrictness::Emergency) {
+            self.audit_emergency_assessment(metadata, accepted);
+        }
+        accepted
+    }
+    
+    /// Moral assessment of a patch that stands now
+    ///
+    /// An assessment relaxed under an emergency authorization only stands
+    /// while that authorization is active.
+    fn moral_assessment<'a>(&self, metadata: &'a PatchMetadata) -> &'a PatchMorality {
+        match &metadata.emergency_scope {
+            Some(scope) if self.emergency().map(|active| &active.authorization) != Some(&scope.authorization) => {
+                &scope.unrelaxed
+            }
+            _ => &metadata.moral_assessment,
+        }
+    }
+    
+    /// Strictness in force: Emergency only while an authorization is valid
+    fn strictness(&self) -> MoralStrictness {
+        match &self.emergency {
+            Some(emergency) if !emergency.is_expired(SystemTime::now()) => MoralStrictness::Emergency,
+            _ => self.config.moral_strictness.clone(),
+        }
+    }
+    
+    /// Every assessment made in emergency mode is recorded
+    fn audit_emergency_assessment(&self, metadata: &PatchMetadata, accepted: bool) {
+        let Some(emergency) = &self.emergency else { return };
+        warn!("Emergency-mode assessment of {}: {:?} {}", metadata.id, self.moral_assessment(metadata),
+              if accepted { "accepted" } else { "rejected" });
+        if let Err(e) = self.audit_trail.record(&metadata.id, &metadata.component, AuditEvent::EmergencyAssessment {
+            authorization: emergency.authorization.clone(),
+            moral_assessment: self.moral_assessment(metadata).clone(),
+            accepted,
+        }) {
+            error!("Failed to audit emergency assessment of {}: {}", metadata.id, e);
         }
     }
     
+    /// Activate emergency strictness with a signed authorization file
+    ///
+    /// The authorization is verified against the approval trust bundle and
+    /// the emergency policy; strictness reverts automatically at expiry.
+    pub fn activate_emergency(&mut self, path: &Path) -> Result<SystemTime, OrchestratorError> {
+        let authorization = EmergencyAuthorization::load(path)?;
+        let bundle_path = self.config.approval_policy.trust_bundle.as_ref()
+            .ok_or_else(|| OrchestratorError::Emergency("No trust bundle configured".into()))?;
+        let bundle = TrustBundle::load(bundle_path)?;
+        let active = emergency::verify_authorization(
+            &authorization,
+            &self.config.emergency_policy,
+            &bundle,
+            &self.config.namespace,
+            SystemTime::now(),
+        )?;
+        
+        self.audit_trail.record("", "patch_orchestrator", AuditEvent::EmergencyActivated {
+            authorization: active.authorization.clone(),
+            reason: active.reason.clone(),
+            approvers: active.approvers.clone(),
+            expires_at: active.expires_at,
+        })?;
+        error!("ALERT: emergency strictness activated in namespace {} by {} until {:?}: {}",
+               self.config.namespace, active.approvers.join(", "), active.expires_at, active.reason);
+        ark_alert::raise(
+            Alert::new("patch_orchestrator", "emergency_strictness", &self.config.namespace, Severity::Critical, &active.reason)
+                .with_detail("approvers", active.approvers.join(", "))
+                .with_detail("expires_at", format!("{:?}", active.expires_at)),
+        );
+        
+        let expires_at = active.expires_at;
+        self.emergency = Some(active);
+        Ok(expires_at)
+    }
+    
+    /// Active emergency authorization, if any and not yet expired
+    pub fn emergency(&self) -> Option<&ActiveEmergency> {
+        self.emergency.as_ref().filter(|emergency| !emergency.is_expired(SystemTime::now()))
+    }
+    
+    /// Revert to the configured strictness once the authorization expires
+    ///
+    /// Pending patches assessed under the authorization get back their
+    /// unrelaxed assessment.
+    fn expire_emergency(&mut self) -> Result<(), OrchestratorError> {
+        if !self.emergency.as_ref().is_some_and(|emergency| emergency.is_expired(SystemTime::now())) {
+            return Ok(());
+        }
+        if let Some(expired) = self.emergency.take() {
+            warn!("Emergency authorization {} expired; reverted to {:?}",
+                  expired.authorization, self.config.moral_strictness);
+            let mut restored = 0;
+            for metadata in self.pending_patches.values_mut() {
+                if let Some(scope) = metadata.emergency_scope.take() {
+                    metadata.moral_assessment = scope.unrelaxed;
+                    restored += 1;
+                }
+            }
+            if restored > 0 {
+                info!("Restored the unrelaxed assessment of {} pending patches", restored);
+                self.persist_patches();
+            }
+            self.audit_trail.record("", "patch_orchestrator", AuditEvent::EmergencyExpired {
+                authorization: expired.authorization,
+            })?;
+        }
+        Ok(())
+    }
+    
     /// Determine if patch should be auto-applied
+    ///
+    /// Concerns an override relaxed still keep the patch for an operator:
+    /// an override never makes a patch eligible for auto-apply.
     fn should_auto_apply(&self, metadata: &PatchMetadata) -> bool {
         metadata.criticality >= self.config.auto_apply_threshold
             && self.is_morally_acceptable(metadata)
             && metadata.harm_analysis.overall_risk <= RiskLevel::Low
-            && metadata.biblical_concerns.is_empty()
+            && metadata.harm_analysis.biblical_concerns.is_empty()
+            && metadata.harm_analysis.overridden_concerns.is_empty()
+            && self.blocking_patches(&metadata.id).is_empty()
+            && self.release_of(&metadata.id).is_none()
+            && self.config.approval_policy.required_approvals == 0
     }
     
-    /// Apply approved patch to system
-    pub async fn apply_patch(&mut self, patch_id: &str) -> Result<(), OrchestratorError> {
-        info!("Applying patch {} to ARK system", patch_id);
-        
-        let metadata = self.pending_patches.get(patch_id)
-            .ok_or_else(|| OrchestratorError::PatchNotFound(patch_id.to_string()))?
-            .clone();
-        
-        // Final moral verification before application
-        if !self.is_morally_acceptable(&metadata) {
-            return Err(OrchestratorError::MoralViolation(patch_id.to_string()));
+    /// Record explicit operator approval for a pending patch
+    ///
+    /// When detached approvals are required, interactive approval only
+    /// succeeds once enough of them have been imported.
+    pub fn approve_patch(&mut self, patch_id: &str) -> Result<(), OrchestratorError> {
+        if !self.pending_patches.contains_key(patch_id) {
+            return Err(OrchestratorError::PatchNotFound(patch_id.to_string()));
         }
         
-        // Create backup before applying
-        self.create_backup(&metadata.component).await?;
+        let required = self.config.approval_policy.required_approvals;
+        let present = self.verified_approvals(patch_id);
+        if present < required {
+            return Err(OrchestratorError::DetachedApprovalRequired {
+                patch_id: patch_id.to_string(),
+                present,
+                required,
+            });
+        }
         
-        // Apply patch (implementation depends on component)
-        match self.apply_component_patch(&metadata).await {
-            Ok(()) => {
-                info!("Successfully applied patch {}", patch_id);
-                
-                // Move to applied patches
-                self.applied_patches.insert(patch_id.to_string(), metadata);
-                self.pending_patches.remove(patch_id);
-                
-                Ok(())
-            },
-            Err(e) => {
-                error!("Failed to apply patch {}: {:?}", patch_id, e);
-                
-                // Restore from backup
-                self.restore_backup(&metadata.component).await?;
-                
-                Err(e)
-            }
+        info!("Patch {} explicitly approved", patch_id);
+        if self.approved_patches.insert(patch_id.to_string()) {
+            let component = self.pending_patches[patch_id].component.clone();
+            self.record_lifecycle(patch_id, &component, AuditEvent::PatchApproved { automatic: false });
+            self.persist_patches();
         }
+        Ok(())
     }
     
-    /// Create component backup before patch application
-    async fn create_backup(&self, component: &str) -> Result<(), OrchestratorError> {
-        debug!("Creating backup for component {}", component);
-        
-        let component_path = self.get_component_path(component);
-        let backup_path = self.config.backup_directory.join(format!("{}_backup_{}", 
-            component, 
-            SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs()
-        ));
-        
-        fs_extra::dir::copy(&component_path, &backup_path, &fs_extra::dir::CopyOptions::new())
-            .map_err(|e| OrchestratorError::BackupCreation(e.to_string()))?;
-        
-        Ok(())
+    /// Whether a patch counts as approved
+    ///
+    /// With detached approvals required, only the imported offline approvals
+    /// count; otherwise an interactive operator approval is enough.
+    pub fn is_approved(&self, patch_id: &str) -> bool {
+        match self.config.approval_policy.required_approvals {
+            0 => self.approved_patches.contains(patch_id),
+            required => self.verified_approvals(patch_id) >= required,
+        }
     }
     
-    /// Restore component from backup
-    async fn restore_backup(&self, component: &str) -> Result<(), OrchestratorError> {
-        warn!("Restoring component {} from backup", component);
+    /// Number of detached approvals that still verify for a pending patch
+    ///
+    /// Approvals restored from the store or a handoff are checked again
+    /// against the current metadata and trust bundle; ones that no longer
+    /// verify are not counted.
+    fn verified_approvals(&self, patch_id: &str) -> usize {
+        let (Some(approvals), Some(metadata)) =
+            (self.detached_approvals.get(patch_id), self.pending_patches.get(patch_id))
+        else {
+            return 0;
+        };
+        let Some(bundle_path) = self.config.approval_policy.trust_bundle.as_ref() else {
+            warn!("No trust bundle configured; approvals for {} cannot be verified", patch_id);
+            return 0;
+        };
+        let bundle = match TrustBundle::load(bundle_path) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                warn!("Failed to load trust bundle {:?}: {}", bundle_path, e);
+                return 0;
+            }
+        };
+        approvals.values()
+            .filter(|approval| match approval::verify_approval(approval, metadata, &bundle) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Approval for {} from {} no longer verifies: {}", patch_id, approval.approver, e);
+                    false
+                }
+            })
+            .count()
+    }
+    
+    /// Canonical approval request for signing on an offline workstation
+    pub fn export_approval_request(&self, patch_id: &str) -> Result<ApprovalRequest, OrchestratorError> {
+        let metadata = self.pending_patches.get(patch_id)
+            .ok_or_else(|| OrchestratorError::PatchNotFound(patch_id.to_string()))?;
+        Ok(ApprovalRequest::for_patch(metadata))
+    }
+    
+    /// Import and verify a detached approval file
+    ///
+    /// Returns the number of distinct approvers now recorded for the patch.
+    pub fn import_approval(&mut self, path: &Path) -> Result<usize, OrchestratorError> {
+        let approval = DetachedApproval::load(path)?;
+        let metadata = self.pending_patches.get(&approval.patch_id)
+            .ok_or_else(|| OrchestratorError::PatchNotFound(approval.patch_id.clone()))?;
+        
+        let bundle_path = self.config.approval_policy.trust_bundle.as_ref()
+            .ok_or_else(|| OrchestratorError::Approval("No trust bundle configured".into()))?;
+        let bundle = TrustBundle::load(bundle_path)?;
+        approval::verify_approval(&approval, metadata, &bundle)?;
+        
+        let (patch_id, component, approver) =
+            (metadata.id.clone(), metadata.component.clone(), approval.approver.clone());
+        let approvals = self.detached_approvals.entry(patch_id.clone()).or_default();
+        approvals.insert(approver.clone(), approval);
+        let count = approvals.len();
+        self.persist_patches();
+        
+        self.audit_trail.record(&patch_id, &component, AuditEvent::ApprovalImported {
+            approver: approver.clone(),
+            approvals: count,
+            required: self.config.approval_policy.required_approvals,
+        })?;
+        info!("Imported approval for {} from {} ({}/{})",
+              patch_id, approver, count, self.config.approval_policy.required_approvals);
+        
+        if self.is_approved(&patch_id) && self.approved_patches.insert(patch_id.clone()) {
+            self.record_lifecycle(&patch_id, &component, AuditEvent::PatchApproved { automatic: false });
+            self.persist_patches();
+        }
+        Ok(count)
+    }
+    
+    /// Set the loader used to instantiate Cold-Mirror models
+    pub fn set_predictor_loader(&mut self, loader: Box<dyn PredictorLoader>) {
+        self.predictor_loader = Some(loader);
+    }
+    
+    /// Mirror live prediction traffic into Cold-Mirror shadow evaluation
+    pub fn attach_shadow_traffic(&mut self, receiver: tokio::sync::mpsc::Receiver<cold_mirror::PredictionInput>) {
+        self.shadow_traffic = Some(tokio::sync::Mutex::new(receiver));
+    }
+    
+    /// Audit trail of patch application decisions
+    pub fn audit_trail(&self) -> &AuditTrail {
+        &self.audit_trail
+    }
+    
+    /// Policy rule pack patches are validated under
+    pub fn ethics_patch_policy(&self) -> &EthicsPatchPolicy {
+        &self.config.ethics_patch_policy
+    }
+    
+    /// Namespace served by this orchestrator
+    pub fn namespace(&self) -> &str {
+        &self.config.namespace
+    }
+    
+    /// CycloneDX document of the patches applied in this namespace
+    ///
+    /// Reads the provenance manifest of every component with an applied
+    /// patch, and of the orchestrated components, afresh on each call.
+    pub fn sbom(&self) -> Bom {
+        let mut components: Vec<&str> = vec!["firmware", "ethics_dsl", "cold_mirror", "patch_orchestrator"];
+        for patch in self.applied_patches.values() {
+            if !components.contains(&patch.component.as_str()) {
+                components.push(&patch.component);
+            }
+        }
+        let manifests: Vec<ProvenanceManifest> = components.into_iter()
+            .filter_map(|component| ProvenanceManifest::load(&self.get_component_path(component)).ok().flatten())
+            .collect();
+        let applied: Vec<&PatchMetadata> = self.applied_patches.values().collect();
+        Bom::build(&self.config.namespace, &applied, &manifests, &self.config.signing_keys, SystemTime::now())
+    }
+    
+    /// Compare the recorded patch lifecycles with the configured SLO targets
+    pub fn slo_report(&self) -> Result<SloReport, OrchestratorError> {
+        Ok(SloReport::build(&self.audit_trail.records()?, &self.config.slo, SystemTime::now()))
+    }
+    
+    /// Fingerprint of the trusted release keys and the pinned peers
+    pub fn key_report(&self) -> Result<KeyReport, OrchestratorError> {
+        keys::report(&self.config.namespace, &self.config.signing_keys, self.config.pinned_keys.as_deref())
+    }
+    
+    /// Pin `peer` to `fingerprint` in the pinned-peer file
+    ///
+    /// `peer` defaults to the release keys of this namespace, and the
+    /// fingerprint of the release keys to their current fingerprint.
+    pub fn pin_key(&self, peer: Option<&str>, fingerprint: Option<KeyFingerprint>) -> Result<PinResult, OrchestratorError> {
+        let pin_file = self.config.pinned_keys.as_deref()
+            .ok_or_else(|| OrchestratorError::KeyPin("No pinned_keys file configured".into()))?;
+        let release = keys::release_peer(&self.config.namespace);
+        let peer = peer.unwrap_or(&release);
+        let fingerprint = match fingerprint {
+            Some(fingerprint) => fingerprint,
+            None if peer == release => keys::release_fingerprint(&self.config.signing_keys)
+                .ok_or_else(|| OrchestratorError::KeyPin("No trusted ed25519 and dilithium3 keys configured".into()))?,
+            None => return Err(OrchestratorError::KeyPin(format!("A fingerprint is required to pin {}", peer))),
+        };
+        let result = keys::pin(pin_file, peer, fingerprint)?;
+        match result.replaced {
+            Some(old) if old != fingerprint => warn!("Re-pinned {} from {} to {}", peer, old, fingerprint),
+            _ => info!("Pinned {} to {}", peer, fingerprint),
+        }
+        Ok(result)
+    }
+    
+    /// Audit a lifecycle event and publish the metrics it completes
+    ///
+    /// Timelines of patches submitted by an earlier process are rebuilt from
+    /// the audit trail on first use.
+    fn record_lifecycle(&mut self, patch_id: &str, component: &str, event: AuditEvent) {
+        if !self.timelines.contains_key(patch_id) {
+            let mut timeline = PatchTimeline::default();
+            if !matches!(event, AuditEvent::PatchSubmitted { .. }) {
+                for record in self.audit_trail.records().unwrap_or_default() {
+                    if record.patch_id == patch_id {
+                        timeline.observe(&record.event, record.timestamp);
+                    }
+                }
+            }
+            self.timelines.insert(patch_id.to_string(), timeline);
+        }
+        if let Err(e) = self.audit_trail.record(patch_id, component, event.clone()) {
+            error!("Failed to audit lifecycle of {}: {}", patch_id, e);
+        }
+        if let AuditEvent::PatchRolledBack { reason } = &event {
+            // Operator rollbacks are expected; anything else undid a failed apply
+            let severity = if reason == "operator" { Severity::Info } else { Severity::Warning };
+            ark_alert::raise(
+                Alert::new("patch_orchestrator", "rollback", component, severity, format!("patch {} rolled back", patch_id))
+                    .with_detail("namespace", &self.config.namespace)
+                    .with_detail("reason", reason),
+            );
+        }
+        
+        let timeline = self.timelines.get_mut(patch_id).expect("timeline inserted above");
+        let completed = timeline.observe(&event, SystemTime::now());
+        slo::publish(component, timeline, &event, &completed);
+        
+        if matches!(event, AuditEvent::PatchApplied | AuditEvent::PatchRejected { .. }) {
+            self.timelines.remove(patch_id);
+        }
+    }
+    
+    /// Copy store trees (every tree when `trees` is empty) into the store `target` describes
+    ///
+    /// The configured store is left as it was; point `storage` in the
+    /// configuration at the target once the copy is verified. The migration
+    /// is audited in the configured store, and in the target when the audit
+    /// trail was copied.
+    pub fn migrate_storage(&self, target: &StorageConfig, trees: &[String]) -> Result<MigrationReport, OrchestratorError> {
+        let source = self.config.storage.below(&self.config.patch_directory);
+        if source.backend == target.backend && source.path == target.path {
+            return Err(OrchestratorError::Storage("Target is the configured store".into()));
+        }
+        let to = ark_storage::open(target).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+        let report = ark_storage::migrate(self.storage.as_ref(), to.as_ref(), trees)
+            .map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+        
+        let event = AuditEvent::StorageMigrated {
+            from: format!("{:?} at {}", source.backend, source.path.display()),
+            to: format!("{:?} at {}", target.backend, target.path.display()),
+            trees: report.trees.keys().cloned().collect(),
+            entries: report.entries(),
+        };
+        self.audit_trail.record("", "patch_orchestrator", event.clone())?;
+        if report.trees.contains_key(audit::AUDIT_TREE) {
+            AuditTrail::new(to, &self.config.namespace).record("", "patch_orchestrator", event)?;
+        }
+        info!("Migrated {} entries in {} trees to {:?} at {:?}",
+              report.entries(), report.trees.len(), target.backend, target.path);
+        Ok(report)
+    }
+    
+    /// Save the patch maps to the repository
+    ///
+    /// The in-memory maps stay authoritative for this process; a failed save
+    /// is logged and retried with the next change.
+    fn persist_patches(&self) {
+        if let Err(e) = self.repository.save(&self.patch_store()) {
+            error!("Failed to save patches of namespace {}: {}", self.config.namespace, e);
+        }
+    }
+    
+    /// Pending, applied and approved patches with their detached approvals,
+    /// blockers and releases
+    fn patch_store(&self) -> snapshot::PatchStore {
+        snapshot::PatchStore {
+            pending: self.pending_patches.values().cloned().collect(),
+            applied: self.applied_patches.values().cloned().collect(),
+            approved: self.approved_patches.iter().cloned().collect(),
+            detached_approvals: self.detached_approvals.values()
+                .flat_map(|set| set.values().cloned())
+                .collect(),
+            blocked_by: self.blocked_by.clone(),
+            releases: self.releases.values().cloned().collect(),
+        }
+    }
+    
+    /// Replace the detached approvals with `approvals`
+    fn set_detached_approvals(&mut self, approvals: Vec<DetachedApproval>) {
+        self.detached_approvals = approval::group_approvals(approvals);
+    }
+    
+    /// Record a failed submission that left nothing pending as a rejection
+    fn record_rejection(&mut self, patch_id: &str, component: &str, result: &Result<String, OrchestratorError>) {
+        if let Err(e) = result {
+            if !self.pending_patches.contains_key(patch_id) {
+                self.record_lifecycle(patch_id, component, AuditEvent::PatchRejected {
+                    reason: e.code().to_string(),
+                });
+            }
+        }
+    }
+    
+    /// Apply approved patch to system
+    ///
+    /// Refused unless the patch is approved (see `is_approved`); only
+    /// auto-apply installs patches without an approval.
+    pub async fn apply_patch(&mut self, patch_id: &str) -> Result<(), OrchestratorError> {
+        if !self.pending_patches.contains_key(patch_id) {
+            return Err(OrchestratorError::PatchNotFound(patch_id.to_string()));
+        }
+        if !self.is_approved(patch_id) {
+            return Err(OrchestratorError::NotApproved(patch_id.to_string()));
+        }
+        self.install_patch(patch_id).await
+    }
+    
+    /// Verify, back up and apply a pending patch
+    async fn install_patch(&mut self, patch_id: &str) -> Result<(), OrchestratorError> {
+        info!("Applying patch {} to ARK system", patch_id);
+        
+        let metadata = self.pending_patches.get(patch_id)
+            .ok_or_else(|| OrchestratorError::PatchNotFound(patch_id.to_string()))?
+            .clone();
+        namespace::check_isolation(&self.config, &metadata)?;
+        if let Some(release_id) = self.release_of(patch_id) {
+            return Err(OrchestratorError::Release(format!(
+                "Patch {} belongs to release {} and is applied with it", patch_id, release_id
+            )));
+        }
+        self.expire_emergency()?;
+        
+        // Overlapping patches submitted earlier go first
+        let blockers = self.blocking_patches(patch_id);
+        if !blockers.is_empty() {
+            return Err(OrchestratorError::PatchConflict {
+                patch_id: patch_id.to_string(),
+                conflicts: blockers.join(", "),
+            });
+        }
+        
+        // Final moral verification before application
+        if !self.is_morally_acceptable(&metadata) {
+            let error = OrchestratorError::MoralViolation(patch_id.to_string());
+            self.record_lifecycle(patch_id, &metadata.component, AuditEvent::PatchRejected {
+                reason: error.code().to_string(),
+            });
+            return Err(error);
+        }
+        
+        // Every payload is verified against the release keys before anything is written
+        self.verify_staged_payload(&metadata)?;
+        
+        // Create backup before applying
+        self.create_backup(&metadata.component).await?;
+        
+        // Apply patch (implementation depends on component)
+        match self.apply_component_patch(&metadata).await {
+            Ok(()) => {
+                info!("Successfully applied patch {}", patch_id);
+                
+                // The component is already patched; a missing manifest only
+                // leaves its provenance unknown
+                match self.write_provenance(&metadata) {
+                    Ok(path) => info!("Wrote provenance manifest {:?}", path),
+                    Err(e) => error!("Failed to write provenance manifest for {}: {}", patch_id, e),
+                }
+                
+                // Move to applied patches
+                self.record_lifecycle(patch_id, &metadata.component, AuditEvent::PatchApplied);
+                self.applied_patches.insert(patch_id.to_string(), metadata);
+                self.pending_patches.remove(patch_id);
+                self.approved_patches.remove(patch_id);
+                self.detached_approvals.remove(patch_id);
+                self.blocked_by.remove(patch_id);
+                self.persist_patches();
+                
+                Ok(())
+            },
+            Err(e) => {
+                error!("Failed to apply patch {}: {:?}", patch_id, e);
+                
+                // Restore from backup
+                self.restore_backup(&metadata.component).await?;
+                self.record_lifecycle(patch_id, &metadata.component, AuditEvent::PatchRolledBack {
+                    reason: e.code().to_string(),
+                });
+                
+                Err(e)
+            }
+        }
+    }
+    
+    /// Roll an applied patch back by restoring the backup taken before it
+    ///
+    /// Only the patch installed on its component, as named by the
+    /// component's provenance manifest, can be rolled back; earlier patches
+    /// are covered by later backups.
+    pub async fn rollback_patch(&mut self, patch_id: &str) -> Result<(), OrchestratorError> {
+        let metadata = self.applied_patches.get(patch_id)
+            .ok_or_else(|| OrchestratorError::PatchNotFound(patch_id.to_string()))?
+            .clone();
+        
+        let installed = ProvenanceManifest::load(&self.get_component_path(&metadata.component))
+            .ok()
+            .flatten()
+            .map(|manifest| manifest.patch_id);
+        if installed.as_deref() != Some(patch_id) {
+            return Err(OrchestratorError::RollbackRefused {
+                patch_id: patch_id.to_string(),
+                reason: format!("{} has {} installed", metadata.component,
+                                installed.as_deref().unwrap_or("no known patch")),
+            });
+        }
+        
+        self.restore_backup(&metadata.component).await?;
+        self.record_lifecycle(patch_id, &metadata.component, AuditEvent::PatchRolledBack {
+            reason: "operator".to_string(),
+        });
+        self.applied_patches.remove(patch_id);
+        self.persist_patches();
+        info!("Rolled back patch {} from {}", patch_id, metadata.component);
+        Ok(())
+    }
+    
+    /// Record the applied patch next to the component
+    fn write_provenance(&self, metadata: &PatchMetadata) -> Result<PathBuf, ark_provenance::ProvenanceError> {
+        let component_dir = self.get_component_path(&metadata.component);
+        let previous_patch_id = ProvenanceManifest::load(&component_dir)
+            .ok()
+            .flatten()
+            .map(|previous| previous.patch_id);
+        
+        ProvenanceManifest {
+            format: ark_provenance::MANIFEST_FORMAT_VERSION,
+            component: metadata.component.clone(),
+            namespace: metadata.namespace.clone(),
+            patch_id: metadata.id.clone(),
+            version: metadata.version.clone(),
+            payload_hash: metadata.hash.to_hex().to_string(),
+            signature_algorithm: format!("{:?}", metadata.signature_algorithm),
+            pq_signature: metadata.pq_signature.as_ref().map(|signature| hex::encode(signature.as_bytes())),
+            classical_signature: metadata.classical_signature.as_ref().map(|signature| hex::encode(signature.as_bytes())),
+            approvers: self.detached_approvals.get(&metadata.id)
+                .map(|approvals| approvals.keys().cloned().collect())
+                .unwrap_or_default(),
+            operator_approved: self.approved_patches.contains(&metadata.id),
+            applied_at: SystemTime::now(),
+            applied_by: env!("CARGO_PKG_VERSION").to_string(),
+            previous_patch_id,
+        }
+        .write(&component_dir)
+    }
+    
+    /// Create component backup before patch application
+    async fn create_backup(&self, component: &str) -> Result<(), OrchestratorError> {
+        debug!("Creating backup for component {}", component);
+        
+        let component_path = self.get_component_path(component);
+        // Nanoseconds keep backups taken within the same second apart
+        let backup_path = self.config.backup_directory.join(format!("{}_backup_{}", 
+            component, 
+            SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_nanos()
+        ));
+        
+        // The backup holds the component's contents, not a copy of its directory
+        std::fs::create_dir_all(&self.config.backup_directory)
+            .map_err(|e| OrchestratorError::BackupCreation(e.to_string()))?;
+        fs_extra::dir::copy(&component_path, &backup_path, &fs_extra::dir::CopyOptions::new().copy_inside(true))
+            .map_err(|e| OrchestratorError::BackupCreation(e.to_string()))?;
+        
+        Ok(())
+    }
+    
+    /// Restore component from backup
+    async fn restore_backup(&self, component: &str) -> Result<(), OrchestratorError> {
+        warn!("Restoring component {} from backup", component);
         
         // Find most recent backup
         let backup_pattern = format!("{}_backup_", component);
@@ -528,15 +1806,13 @@ impl PatchOrchestrator {
             .filter_map(|entry| {
                 let entry = entry.ok()?;
                 let name = entry.file_name().to_string_lossy().to_string();
-                if name.starts_with(&backup_pattern) {
-                    Some((entry.path(), name))
-                } else {
-                    None
-                }
+                // Older backups are stamped in seconds, newer in nanoseconds
+                let stamp: u128 = name.strip_prefix(&backup_pattern)?.parse().ok()?;
+                Some((entry.path(), stamp))
             })
             .collect();
         
-        backups.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by name (timestamp)
+        backups.sort_by(|a, b| b.1.cmp(&a.1)); // Newest first
         
         if let Some((backup_path, _)) = backups.first() {
             let component_path = self.get_component_path(component);
@@ -548,7 +1824,7 @@ impl PatchOrchestrator {
             }
             
             // Restore from backup
-            fs_extra::dir::copy(backup_path, &component_path, &fs_extra::dir::CopyOptions::new())
+            fs_extra::dir::copy(backup_path, &component_path, &fs_extra::dir::CopyOptions::new().copy_inside(true))
                 .map_err(|e| OrchestratorError::BackupRestoration(e.to_string()))?;
             
             info!("Successfully restored component {} from backup", component);
@@ -563,9 +1839,16 @@ impl PatchOrchestrator {
     async fn apply_component_patch(&self, metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
         match metadata.component.as_str() {
             "firmware" => self.apply_firmware_patch(metadata).await,
-            "ethics_dsl" => self.apply_ethics_patch(metadata).await,
-            "cold_mirror" => self.apply_cold_mirror_patch(metadata).await,
             "patch_orchestrator" => self.apply_orchestrator_patch(metadata).await,
+            _ => self.stage_component_patch(metadata).await?.promote().map(|_| ()),
+        }
+    }
+    
+    /// Stage a patch and run its component's checks without making it live
+    async fn stage_component_patch(&self, metadata: &PatchMetadata) -> Result<Promotion, OrchestratorError> {
+        match metadata.component.as_str() {
+            "ethics_dsl" => self.stage_ethics_patch(metadata).await,
+            "cold_mirror" => self.stage_cold_mirror_patch(metadata).await,
             _ => Err(OrchestratorError::UnsupportedComponent(metadata.component.clone())),
         }
     }
@@ -577,52 +1860,411 @@ impl PatchOrchestrator {
         todo!("Firmware patching implementation with secure boot verification")
     }
     
-    /// Apply ethics DSL patch
-    async fn apply_ethics_patch(&self, _metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
-        // Implementation for ethics engine updates
-        todo!("Ethics DSL patching with moral consistency verification")
+    /// Stage an ethics DSL rule pack and validate it against the live one
+    async fn stage_ethics_patch(&self, metadata: &PatchMetadata) -> Result<Promotion, OrchestratorError> {
+        let policy = &self.config.ethics_patch_policy;
+        
+        // Stage the candidate rule pack
+        let candidate_rules = String::from_utf8(self.read_staged_payload(&metadata.id)?)
+            .map_err(|e| OrchestratorError::Staging(format!("Rule pack is not UTF-8: {}", e)))?;
+        let staged = ethics_patch::stage_rule_pack(&self.config.staging_directory, &metadata.id, &candidate_rules)?;
+        
+        let live_rules = ethics_patch::load_live_rule_pack(&policy.live_rule_pack)?;
+        let corpus = match &policy.replay_corpus {
+            Some(path) => ethics_patch::load_replay_corpus(path)?,
+            None => Vec::new(),
+        };
+        let journal = match (&policy.journal_storage, &policy.decision_journal) {
+            (Some(storage), _) => ethics_patch::load_journal_storage(storage, policy.replay_window_days)?,
+            (None, Some(path)) => ethics_patch::load_journal(path, policy.replay_window_days)?,
+            (None, None) => Vec::new(),
+        };
+        if corpus.is_empty() && journal.is_empty() {
+            warn!("No replay corpus or journaled decisions - decision diff for {} is empty", metadata.id);
+        }
+        
+        // Consistency check, conformance suite and decision diff
+        let report = ethics_patch::validate_rule_pack(
+            &live_rules,
+            &candidate_rules,
+            &corpus,
+            &journal,
+            &policy.replay_config(),
+        )?;
+        
+        self.audit_trail.record(&metadata.id, &metadata.component, AuditEvent::RulePackValidated(report.clone()))?;
+        
+        if !report.conformance_failures.is_empty() {
+            return Err(OrchestratorError::ConformanceFailure(report.conformance_failures.join("; ")));
+        }
+        
+        let changed_ratio = report.diff.changed_ratio();
+        info!("Rule pack {} changes {}/{} replayed decisions ({} newly blocked, {} newly allowed)",
+              metadata.id, report.diff.changed_events, report.diff.total_events,
+              report.diff.newly_blocked, report.diff.newly_allowed);
+        if !report.diff.flips.is_empty() {
+            info!("Rule pack {} decision flips: {}", metadata.id, report.diff.flip_summary());
+        }
+        
+        if changed_ratio > policy.approval_threshold && !self.is_approved(&metadata.id) {
+            return Err(OrchestratorError::ApprovalRequired {
+                patch_id: metadata.id.clone(),
+                changed_ratio,
+            });
+        }
+        
+        Ok(Promotion { patch_id: metadata.id.clone(), staged, live: policy.live_rule_pack.clone() })
     }
     
-    /// Apply Cold-Mirror patch
-    async fn apply_cold_mirror_patch(&self, _metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
-        // Implementation for AI model updates
-        todo!("Cold-Mirror patching with harm prediction verification")
+    /// Stage a Cold-Mirror model and shadow-evaluate it against the active one
+    async fn stage_cold_mirror_patch(&self, metadata: &PatchMetadata) -> Result<Promotion, OrchestratorError> {
+        let policy = &self.config.shadow_policy;
+        let loader = self.predictor_loader.as_ref()
+            .ok_or_else(|| OrchestratorError::ModelLoad("No Cold-Mirror model loader configured".into()))?;
+        
+        // Stage and load the candidate next to the active model
+        let staged = self.config.staging_directory.join(format!("{}.model", metadata.id));
+        std::fs::write(&staged, self.read_staged_payload(&metadata.id)?)
+            .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+        
+        let active = loader.load(&policy.active_model)?;
+        let candidate = loader.load(&staged)?;
+        
+        // Shadow inference on live traffic, or the replay corpus if none is attached
+        let comparison = match &self.shadow_traffic {
+            Some(traffic) => {
+                let mut receiver = traffic.lock().await;
+                cold_mirror_patch::run_shadow_window(
+                    active.as_ref(), candidate.as_ref(), ShadowSource::Live(&mut *receiver), policy,
+                ).await
+            }
+            None => {
+                let corpus = match &policy.replay_corpus {
+                    Some(path) => cold_mirror_patch::load_shadow_corpus(path)?,
+                    None => Vec::new(),
+                };
+                cold_mirror_patch::run_shadow_window(
+                    active.as_ref(), candidate.as_ref(), ShadowSource::Replay(corpus), policy,
+                ).await
+            }
+        };
+        
+        info!("Shadow evaluation of {}: {} samples, agreement {:.3}, latency ratio {:.2}, drift {:.3}",
+              metadata.id, comparison.samples, comparison.agreement_rate(),
+              comparison.latency_ratio(), comparison.category_drift());
+        
+        let verdict = comparison.verdict.clone().unwrap_or_else(|| comparison.evaluate(policy));
+        self.audit_trail.record(&metadata.id, &metadata.component, AuditEvent::ShadowEvaluation(comparison))?;
+        
+        match verdict {
+            ShadowVerdict::Promote => Ok(Promotion {
+                patch_id: metadata.id.clone(),
+                staged,
+                live: policy.active_model.clone(),
+            }),
+            ShadowVerdict::Reject { reasons } => Err(OrchestratorError::ShadowRejected {
+                patch_id: metadata.id.clone(),
+                reasons: reasons.join("; "),
+            }),
+        }
     }
     
     /// Apply orchestrator self-patch
-    async fn apply_orchestrator_patch(&self, _metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
-        // Implementation for self-updating capability
-        todo!("Self-patching with continuity preservation")
+    ///
+    /// Hands the active role to the new binary. Every step that can fail -
+    /// state transfer, health check, installing the live binary, auditing -
+    /// runs before the standby is told to take over, so no failure leaves this
+    /// instance demoted without a live successor. On success this instance
+    /// must step down and the standby is detached to keep running; on any
+    /// failure the live binary is put back, the standby is killed and this
+    /// instance stays active.
+    async fn apply_orchestrator_patch(&self, metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
+        let policy = &self.config.handoff_policy;
+        
+        // Verified again: the staged file is read anew for staging
+        let binary = self.verify_staged_payload(metadata)?;
+        let computed_hash = metadata.hash;
+        let staged = handoff::stage_binary(&self.config.staging_directory, &metadata.id, &binary)?;
+        
+        // Launch the standby and hand over state
+        let socket = self.config.staging_directory.join(handoff::HANDOFF_SOCKET);
+        let _ = std::fs::remove_file(&socket);
+        
+        let kek = handoff::generate_kek();
+        let mut state = self.export_handoff_state(&kek)?;
+        state.pending_patches.retain(|patch| patch.id != metadata.id);
+        state.approved_patches.retain(|id| id != &metadata.id);
+        state.detached_approvals.retain(|approval| approval.patch_id != metadata.id);
+        let standby = handoff::launch_standby(&staged, policy, &self.config.namespace, &socket, &kek).await?;
+        
+        let live = Promotion { patch_id: metadata.id.clone(), staged, live: policy.live_binary.clone() };
+        let mut previous = None;
+        let handed_over = async {
+            let mut stream = self.prepare_handoff(&socket, state).await?;
+            
+            previous = Some(live.promote()?);
+            handoff::make_executable(&live.live)?;
+            self.audit_trail.record(&metadata.id, &metadata.component, AuditEvent::OrchestratorHandoff {
+                standby_pid: standby.id().unwrap_or_default(),
+                binary_hash: computed_hash.to_hex().to_string(),
+            })?;
+            
+            self.promote_standby(&mut stream).await
+        }
+        .await;
+        
+        match handed_over {
+            Ok(pid) => {
+                standby.detach();
+                info!("Orchestrator handoff to pid {} complete - stepping down", pid);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Orchestrator handoff failed, remaining active: {}", e);
+                if let Some(previous) = previous {
+                    if let Err(revert) = live.revert(previous.as_deref()) {
+                        error!("Failed to restore the live orchestrator binary: {}", revert);
+                    }
+                }
+                drop(standby);
+                let _ = std::fs::remove_file(&socket);
+                Err(e)
+            }
+        }
+    }
+    
+    /// Transfer state to the standby and check its health, short of promoting it
+    async fn prepare_handoff(&self, socket: &Path, state: HandoffState) -> Result<tokio::net::UnixStream, OrchestratorError> {
+        let policy = &self.config.handoff_policy;
+        let expected_pending = state.pending_patches.len();
+        
+        // The standby binds the socket once it is up
+        let deadline = tokio::time::Instant::now() + policy.standby_timeout;
+        let mut stream = loop {
+            match tokio::net::UnixStream::connect(socket).await {
+                Ok(stream) => break stream,
+                Err(_) if tokio::time::Instant::now() < deadline => {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                Err(e) => return Err(OrchestratorError::Handoff(format!("Standby never came up: {}", e))),
+            }
+        };
+        
+        match handoff::exchange(&mut stream, &HandoffMessage::State(state), policy.exchange_timeout).await? {
+            HandoffMessage::StateAccepted { pending_patches } if pending_patches == expected_pending => {}
+            other => return Err(OrchestratorError::Handoff(format!("State transfer failed: {:?}", other))),
+        }
+        
+        match handoff::exchange(&mut stream, &HandoffMessage::HealthCheck, policy.exchange_timeout).await? {
+            HandoffMessage::Healthy { version, pending_patches } if pending_patches == expected_pending => {
+                info!("Standby orchestrator v{} is healthy", version);
+            }
+            other => return Err(OrchestratorError::Handoff(format!("Health check failed: {:?}", other))),
+        }
+        
+        Ok(stream)
+    }
+    
+    /// Tell a prepared standby to take over, returning its pid
+    async fn promote_standby(&self, stream: &mut tokio::net::UnixStream) -> Result<u32, OrchestratorError> {
+        let policy = &self.config.handoff_policy;
+        match handoff::exchange(stream, &HandoffMessage::Promote, policy.exchange_timeout).await? {
+            HandoffMessage::Promoted { pid } => Ok(pid),
+            other => Err(OrchestratorError::Handoff(format!("Promotion failed: {:?}", other))),
+        }
+    }
+    
+    /// Serve the standby side of a handoff until promoted
+    pub async fn run_standby(&mut self, socket: &Path, kek: &[u8; 32]) -> Result<(), OrchestratorError> {
+        let listener = tokio::net::UnixListener::bind(socket)
+            .map_err(|e| OrchestratorError::Handoff(format!("Failed to bind {:?}: {}", socket, e)))?;
+        let (mut stream, _) = listener.accept().await
+            .map_err(|e| OrchestratorError::Handoff(e.to_string()))?;
+        let _ = std::fs::remove_file(socket);
+        
+        let mut restored = false;
+        loop {
+            let reply = match handoff::read_message(&mut stream).await? {
+                HandoffMessage::State(state) => match self.restore_handoff_state(state, kek) {
+                    Ok(()) => {
+                        restored = true;
+                        HandoffMessage::StateAccepted { pending_patches: self.pending_patches.len() }
+                    }
+                    Err(e) => HandoffMessage::Abort(e.to_string()),
+                },
+                HandoffMessage::HealthCheck if restored => HandoffMessage::Healthy {
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    pending_patches: self.pending_patches.len(),
+                },
+                HandoffMessage::Promote if restored => {
+                    handoff::record_active_instance(
+                        &self.config.patch_directory.join(handoff::ACTIVE_INSTANCE_FILE),
+                        &handoff::ActiveInstance {
+                            pid: std::process::id(),
+                            version: env!("CARGO_PKG_VERSION").to_string(),
+                            since: SystemTime::now(),
+                        },
+                    )?;
+                    handoff::write_message(&mut stream, &HandoffMessage::Promoted { pid: std::process::id() }).await?;
+                    info!("Standby orchestrator promoted to active");
+                    return Ok(());
+                }
+                HandoffMessage::Abort(reason) => {
+                    return Err(OrchestratorError::Handoff(format!("Active instance aborted: {}", reason)));
+                }
+                other => HandoffMessage::Abort(format!("Unexpected message {:?}", other)),
+            };
+            handoff::write_message(&mut stream, &reply).await?;
+        }
     }
     
-    /// Get component filesystem path
+    /// Snapshot the state a standby instance needs to take over
+    pub fn export_handoff_state(&self, kek: &[u8; 32]) -> Result<HandoffState, OrchestratorError> {
+        let (pq_public, pq_secret) = self.pq_signing_key.as_ref()
+            .ok_or_else(|| OrchestratorError::Handoff("No PQ signing key available".into()))?;
+        let classical = self.classical_signing_key.as_ref()
+            .ok_or_else(|| OrchestratorError::Handoff("No classical signing key available".into()))?;
+        let (ml_dsa_public, ml_dsa_secret) = self.ml_dsa_signing_key.as_ref()
+            .map(|(public, secret)| (public.as_bytes().to_vec(), secret.expose_secret().to_vec()))
+            .unwrap_or_default();
+        
+        let keys = KeyMaterial {
+            dilithium_public: pq_public.as_bytes().to_vec(),
+            dilithium_secret: pq_secret.expose_secret().to_vec(),
+            ed25519_keypair: dalek::keypair_bytes(classical).to_vec(),
+            ml_dsa_public,
+            ml_dsa_secret,
+        };
+        
+        Ok(HandoffState {
+            pending_patches: self.pending_patches.values().cloned().collect(),
+            blocked_by: self.blocked_by.clone(),
+            approved_patches: self.approved_patches.iter().cloned().collect(),
+            detached_approvals: self.detached_approvals.values()
+                .flat_map(|set| set.values().cloned())
+                .collect(),
+            keys: handoff::wrap_keys(&keys, kek)?,
+            releases: self.releases.values().cloned().collect(),
+        })
+    }
+    
+    /// Take over state exported by the active instance
+    pub fn restore_handoff_state(&mut self, state: HandoffState, kek: &[u8; 32]) -> Result<(), OrchestratorError> {
+        if let Some(patch) = state.pending_patches.iter().find(|patch| patch.namespace != self.config.namespace) {
+            return Err(OrchestratorError::Handoff(format!(
+                "Patch {} belongs to namespace {}, not {}", patch.id, patch.namespace, self.config.namespace
+            )));
+        }
+        let keys = handoff::unwrap_keys(&state.keys, kek)?;
+        
+        let pq_public = DilithiumPublicKeyBytes::from_slice(&keys.dilithium_public)
+            .map_err(|_| OrchestratorError::Handoff("Invalid Dilithium public key".into()))?;
+        let pq_secret = DilithiumSecretKeyBytes::from_slice(&keys.dilithium_secret)
+            .map_err(|_| OrchestratorError::Handoff("Invalid Dilithium secret key".into()))?;
+        let classical = dalek::signing_key_from_keypair(&keys.ed25519_keypair)
+            .map_err(|_| OrchestratorError::Handoff("Invalid Ed25519 keypair".into()))?;
+        // An active instance predating ML-DSA hands over no key; keep our own
+        if !keys.ml_dsa_secret.is_empty() {
+            let ml_dsa_public = MlDsaPublicKeyBytes::from_slice(&keys.ml_dsa_public)
+                .map_err(|_| OrchestratorError::Handoff("Invalid ML-DSA public key".into()))?;
+            let ml_dsa_secret = MlDsaSecretKeyBytes::from_slice(&keys.ml_dsa_secret)
+                .map_err(|_| OrchestratorError::Handoff("Invalid ML-DSA secret key".into()))?;
+            self.ml_dsa_signing_key = Some((ml_dsa_public, ml_dsa_secret));
+        }
+        
+        self.pq_signing_key = Some((pq_public, pq_secret));
+        self.classical_signing_key = Some(classical);
+        self.pending_patches = state.pending_patches.into_iter()
+            .map(|patch| (patch.id.clone(), patch))
+            .collect();
+        self.blocked_by = state.blocked_by;
+        self.approved_patches = state.approved_patches.into_iter().collect();
+        self.set_detached_approvals(state.detached_approvals);
+        self.releases = state.releases.into_iter()
+            .map(|release| (release.manifest.release_id.clone(), release))
+            .collect();
+        self.persist_patches();
+        
+        Ok(())
+    }
+    
+    /// Release signing keys every patch payload is verified against
+    ///
+    /// Refused when they no longer match their pin.
+    fn trusted_public_keys(&self) -> Result<PatchPublicKeys, OrchestratorError> {
+        keys::check_release_pin(&self.config.namespace, &self.config.signing_keys, self.config.pinned_keys.as_deref())?;
+        let dilithium = self.config.signing_keys.get("dilithium3")
+            .ok_or_else(|| OrchestratorError::SignatureError("No trusted dilithium3 key configured".into()))?;
+        let ed25519 = self.config.signing_keys.get("ed25519")
+            .ok_or_else(|| OrchestratorError::SignatureError("No trusted ed25519 key configured".into()))?;
+        
+        Ok(PatchPublicKeys {
+            dilithium_public: DilithiumPublicKeyBytes::from_slice(dilithium)
+                .map_err(|_| OrchestratorError::SignatureError("Invalid trusted dilithium3 key".into()))?,
+            ed25519_public: dalek::verifying_key_from_bytes(ed25519)
+                .map_err(|_| OrchestratorError::SignatureError("Invalid trusted ed25519 key".into()))?,
+            ml_dsa_public: self.config.signing_keys.get("mldsa65")
+                .map(|key| MlDsaPublicKeyBytes::from_slice(key)
+                    .map_err(|_| OrchestratorError::SignatureError("Invalid trusted mldsa65 key".into())))
+                .transpose()?,
+        })
+    }
+    
+    /// Read a staged payload and verify its hash and signature against trusted release keys
+    fn verify_staged_payload(&self, metadata: &PatchMetadata) -> Result<Vec<u8>, OrchestratorError> {
+        let payload = self.read_staged_payload(&metadata.id)?;
+        let computed_hash = blake3::hash(&payload);
+        if computed_hash != metadata.hash {
+            return Err(OrchestratorError::HashMismatch {
+                expected: metadata.hash,
+                computed: computed_hash,
+            });
+        }
+        if !self.verify_patch_signature(metadata, &self.trusted_public_keys()?)? {
+            return Err(OrchestratorError::SignatureError(format!("Signature of {} does not verify", metadata.id)));
+        }
+        Ok(payload)
+    }
+    
+    /// Path of a submitted patch payload in the staging directory
+    fn staged_payload_path(&self, patch_id: &str) -> PathBuf {
+        self.config.staging_directory.join(format!("{}.patch", patch_id))
+    }
+    
+    /// Read a submitted patch payload from the staging directory
+    fn read_staged_payload(&self, patch_id: &str) -> Result<Vec<u8>, OrchestratorError> {
+        std::fs::read(self.staged_payload_path(patch_id))
+            .map_err(|e| OrchestratorError::Staging(format!("Payload for {}: {}", patch_id, e)))
+    }
+    
+    /// Get component filesystem path, below the namespace root if scoped
     fn get_component_path(&self, component: &str) -> PathBuf {
-        match component {
+        let path = match component {
             "firmware" => PathBuf::from("firmware/"),
             "ethics_dsl" => PathBuf::from("software/ethics_dsl/"),
             "cold_mirror" => PathBuf::from("software/cold_mirror/"),
             "patch_orchestrator" => PathBuf::from("software/patch_orchestrator/"),
             _ => PathBuf::from(format!("software/{}/", component)),
+        };
+        match self.config.component_root() {
+            Some(root) => root.join(path),
+            None => path,
         }
     }
     
     /// Sign patch with post-quantum signature
     pub fn sign_patch(&self, patch: &mut PatchMetadata, algorithm: SignatureAlgorithm) -> Result<(), OrchestratorError> {
-        // Serialize patch data for signing (excluding signatures)
-        let mut patch_copy = patch.clone();
-        patch_copy.pq_signature = None;
-        patch_copy.classical_signature = None;
-        
-        let patch_bytes = bincode::serialize(&patch_copy)
-            .map_err(|e| OrchestratorError::SignatureError(format!("Serialization failed: {}", e)))?;
+        // The algorithm is signed too, so it is set before the signing bytes are taken
+        patch.signature_algorithm = algorithm.clone();
+        let patch_bytes = patch.signing_bytes();
         
         match algorithm {
             SignatureAlgorithm::Dilithium3 => {
                 let (_, secret_key) = self.pq_signing_key.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No PQ signing key available".into()))?;
                 
-                let signature = dilithium_sign(&patch_bytes, secret_key);
-                patch.pq_signature = Some(signature);
+                let signature = Dilithium3::sign(&patch_bytes, secret_key).map_err(scheme_error)?;
+                patch.pq_signature = Some(signature.into());
                 patch.signature_algorithm = SignatureAlgorithm::Dilithium3;
                 
                 info!("Patch {} signed with Dilithium3 (post-quantum)", patch.id);
@@ -632,7 +2274,8 @@ impl PatchOrchestrator {
                     .ok_or_else(|| OrchestratorError::SignatureError("No classical signing key available".into()))?;
                 
                 let signature = keypair.sign(&patch_bytes);
-                patch.classical_signature = Some(signature.to_bytes().to_vec());
+                patch.classical_signature = Some(Ed25519SignatureBytes::from_slice(&signature.to_bytes())
+                    .map_err(|e| OrchestratorError::SignatureError(e.to_string()))?);
                 patch.signature_algorithm = SignatureAlgorithm::Ed25519;
                 
                 info!("Patch {} signed with Ed25519 (classical)", patch.id);
@@ -644,15 +2287,32 @@ impl PatchOrchestrator {
                 let classical_keypair = self.classical_signing_key.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No classical signing key available".into()))?;
                 
-                let pq_signature = dilithium_sign(&patch_bytes, pq_secret);
+                let pq_signature = Dilithium3::sign(&patch_bytes, pq_secret).map_err(scheme_error)?;
                 let classical_signature = classical_keypair.sign(&patch_bytes);
                 
-                patch.pq_signature = Some(pq_signature);
-                patch.classical_signature = Some(classical_signature.to_bytes().to_vec());
+                patch.pq_signature = Some(pq_signature.into());
+                patch.classical_signature = Some(Ed25519SignatureBytes::from_slice(&classical_signature.to_bytes())
+                    .map_err(|e| OrchestratorError::SignatureError(e.to_string()))?);
                 patch.signature_algorithm = SignatureAlgorithm::HybridEd25519Dilithium3;
                 
                 info!("Patch {} signed with hybrid Ed25519+Dilithium3", patch.id);
             }
+            SignatureAlgorithm::MlDsa65 | SignatureAlgorithm::HybridEd25519MlDsa65 => {
+                let (_, ml_dsa_secret) = self.ml_dsa_signing_key.as_ref()
+                    .ok_or_else(|| OrchestratorError::SignatureError("No ML-DSA signing key available".into()))?;
+                
+                let pq_signature = MlDsa65::sign(&patch_bytes, ml_dsa_secret).map_err(scheme_error)?;
+                patch.pq_signature = Some(pq_signature.into());
+                
+                if algorithm == SignatureAlgorithm::HybridEd25519MlDsa65 {
+                    let classical_keypair = self.classical_signing_key.as_ref()
+                        .ok_or_else(|| OrchestratorError::SignatureError("No classical signing key available".into()))?;
+                    patch.classical_signature = Some(Ed25519SignatureBytes::from(&classical_keypair.sign(&patch_bytes)));
+                }
+                
+                info!("Patch {} signed with {:?}", patch.id, algorithm);
+                patch.signature_algorithm = algorithm;
+            }
         }
         
         Ok(())
@@ -660,20 +2320,16 @@ impl PatchOrchestrator {
     
     /// Verify patch signature
     pub fn verify_patch_signature(&self, patch: &PatchMetadata, public_keys: &PatchPublicKeys) -> Result<bool, OrchestratorError> {
-        // Serialize patch data for verification (excluding signatures)
-        let mut patch_copy = patch.clone();
-        patch_copy.pq_signature = None;
-        patch_copy.classical_signature = None;
-        
-        let patch_bytes = bincode::serialize(&patch_copy)
-            .map_err(|e| OrchestratorError::SignatureError(format!("Serialization failed: {}", e)))?;
+        let patch_bytes = patch.signing_bytes();
         
         match patch.signature_algorithm {
             SignatureAlgorithm::Dilithium3 => {
-                let signature = patch.pq_signature.as_ref()
+                let signature_bytes = patch.pq_signature.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No PQ signature present".into()))?;
+                let signature = signature_bytes.dilithium()
+                    .map_err(|_| OrchestratorError::SignatureError("Invalid Dilithium signature format".into()))?;
                 
-                dilithium_verify(signature, &patch_bytes, &public_keys.dilithium_public)
+                Dilithium3::verify(&patch_bytes, &signature, &public_keys.dilithium_public)
                     .map_err(|_| OrchestratorError::SignatureError("Dilithium signature verification failed".into()))?;
                 
                 Ok(true)
@@ -682,10 +2338,7 @@ impl PatchOrchestrator {
                 let signature_bytes = patch.classical_signature.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No classical signature present".into()))?;
                 
-                let signature = Ed25519Signature::from_bytes(
-                    &<[u8; 64]>::try_from(signature_bytes.as_slice())
-                        .map_err(|_| OrchestratorError::SignatureError("Invalid Ed25519 signature format".into()))?
-                );
+                let signature = Ed25519Signature::from_bytes(&signature_bytes.clone().into());
                 
                 use ed25519_dalek::Verifier;
                 public_keys.ed25519_public.verify(&patch_bytes, &signature)
@@ -695,36 +2348,72 @@ impl PatchOrchestrator {
             }
             SignatureAlgorithm::HybridEd25519Dilithium3 => {
                 // Verify both signatures
-                let pq_signature = patch.pq_signature.as_ref()
+                let pq_signature_bytes = patch.pq_signature.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No PQ signature present".into()))?;
+                let pq_signature = pq_signature_bytes.dilithium()
+                    .map_err(|_| OrchestratorError::SignatureError("Invalid Dilithium signature format".into()))?;
                 let classical_signature_bytes = patch.classical_signature.as_ref()
                     .ok_or_else(|| OrchestratorError::SignatureError("No classical signature present".into()))?;
                 
                 // Verify Dilithium
-                dilithium_verify(pq_signature, &patch_bytes, &public_keys.dilithium_public)
+                Dilithium3::verify(&patch_bytes, &pq_signature, &public_keys.dilithium_public)
                     .map_err(|_| OrchestratorError::SignatureError("Dilithium signature verification failed".into()))?;
                 
                 // Verify Ed25519
-                let classical_signature = Ed25519Signature::from_bytes(
-                    &<[u8; 64]>::try_from(classical_signature_bytes.as_slice())
-                        .map_err(|_| OrchestratorError::SignatureError("Invalid Ed25519 signature format".into()))?
-                );
+                let classical_signature = Ed25519Signature::from_bytes(&classical_signature_bytes.clone().into());
                 
                 use ed25519_dalek::Verifier;
                 public_keys.ed25519_public.verify(&patch_bytes, &classical_signature)
                     .map_err(|_| OrchestratorError::SignatureError("Ed25519 signature verification failed".into()))?;
                 
+                Ok(true)
+            }
+            SignatureAlgorithm::MlDsa65 | SignatureAlgorithm::HybridEd25519MlDsa65 => {
+                let ml_dsa_public = public_keys.ml_dsa_public.as_ref()
+                    .ok_or_else(|| OrchestratorError::SignatureError("No trusted mldsa65 key configured".into()))?;
+                let pq_signature = patch.pq_signature.as_ref()
+                    .ok_or_else(|| OrchestratorError::SignatureError("No PQ signature present".into()))?
+                    .ml_dsa()
+                    .map_err(|_| OrchestratorError::SignatureError("Invalid ML-DSA signature format".into()))?;
+                
+                MlDsa65::verify(&patch_bytes, &pq_signature, ml_dsa_public)
+                    .map_err(|_| OrchestratorError::SignatureError("ML-DSA signature verification failed".into()))?;
+                
+                if patch.signature_algorithm == SignatureAlgorithm::HybridEd25519MlDsa65 {
+                    let classical_signature_bytes = patch.classical_signature.as_ref()
+                        .ok_or_else(|| OrchestratorError::SignatureError("No classical signature present".into()))?;
+                    
+                    use ed25519_dalek::Verifier;
+                    public_keys.ed25519_public.verify(&patch_bytes, &Ed25519Signature::from(classical_signature_bytes))
+                        .map_err(|_| OrchestratorError::SignatureError("Ed25519 signature verification failed".into()))?;
+                }
+                
                 Ok(true)
             }
         }
     }
     
+    /// Patches awaiting application
+    pub fn pending_patches(&self) -> impl Iterator<Item = &PatchMetadata> {
+        self.pending_patches.values()
+    }
+    
+    /// Patches applied to this namespace
+    pub fn applied_patches(&self) -> impl Iterator<Item = &PatchMetadata> {
+        self.applied_patches.values()
+    }
+    
     /// Get system status and patch information
     pub fn get_system_status(&self) -> SystemStatus {
         SystemStatus {
+            namespace: self.config.namespace.clone(),
             pending_patches: self.pending_patches.len(),
             applied_patches: self.applied_patches.len(),
-            moral_strictness: self.config.moral_strictness.clone(),
+            moral_strictness: self.strictness(),
+            emergency_expires_at: self.emergency().map(|emergency| emergency.expires_at),
+            provenance: ProvenanceManifest::load(&self.get_component_path("patch_orchestrator")).ok().flatten(),
+            last_crash: CrashStore::new(&self.config.crash_reports).last().ok().flatten().map(|report| report.summary()),
+            config: ark_config::active(),
             last_update: SystemTime::now(),
             biblical_compliance: true,
         }
@@ -733,16 +2422,36 @@ impl PatchOrchestrator {
 
 /// Public keys for patch signature verification
 pub struct PatchPublicKeys {
-    pub dilithium_public: DilithiumPublicKey,
-    pub ed25519_public: Ed25519PublicKey,
+    pub dilithium_public: DilithiumPublicKeyBytes,
+    pub ed25519_public: Ed25519VerifyingKey,
+    /// Trusted ML-DSA-65 key, required only for ML-DSA signed patches
+    pub ml_dsa_public: Option<MlDsaPublicKeyBytes>,
+}
+
+/// Signature scheme failures surface as signature errors
+fn scheme_error(e: SchemeError) -> OrchestratorError {
+    OrchestratorError::SignatureError(e.to_string())
 }
 
 /// System status information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStatus {
+    pub namespace: String,
     pub pending_patches: usize,
     pub applied_patches: usize,
     pub moral_strictness: MoralStrictness,
+    #[serde(default)]
+    pub emergency_expires_at: Option<SystemTime>,
+    /// Patch this orchestrator binary was installed from
+    #[serde(default)]
+    pub provenance: Option<ProvenanceManifest>,
+    /// Most recent crash of this orchestrator
+    #[serde(default)]
+    pub last_crash: Option<CrashSummary>,
+    /// Configuration files this process loaded, with their hashes and
+    /// verification status
+    #[serde(default)]
+    pub config: Vec<ConfigAttestation>,
     pub last_update: SystemTime,
     pub biblical_compliance: bool,
 }
@@ -791,6 +2500,189 @@ pub enum OrchestratorError {
     
     #[error("Signature error: {0}")]
     SignatureError(String),
+    
+    #[error("Staging error: {0}")]
+    Staging(String),
+    
+    #[error("Rule pack consistency check failed: {0}")]
+    ConsistencyCheck(String),
+    
+    #[error("Conformance suite failed: {0}")]
+    ConformanceFailure(String),
+    
+    #[error("Patch {patch_id} changes {changed_ratio:.3} of replayed decisions and requires explicit approval")]
+    ApprovalRequired { patch_id: String, changed_ratio: f64 },
+    
+    #[error("Model loading failed: {0}")]
+    ModelLoad(String),
+    
+    #[error("Candidate model {patch_id} rejected after shadow evaluation: {reasons}")]
+    ShadowRejected { patch_id: String, reasons: String },
+    
+    #[error("Audit trail error: {0}")]
+    AuditTrail(String),
+    
+    #[error("Orchestrator handoff failed: {0}")]
+    Handoff(String),
+    
+    #[error("Patch bundle rejected: {0}")]
+    Ingestion(String),
+    
+    #[error("Approval rejected: {0}")]
+    Approval(String),
+    
+    #[error("Patch {patch_id} has {present} of {required} required detached approvals")]
+    DetachedApprovalRequired { patch_id: String, present: usize, required: usize },
+    
+    #[error("Patch {0} has not been approved")]
+    NotApproved(String),
+    
+    #[error("Binary payload of patch {patch_id} failed audit: {findings}")]
+    BinaryAuditFailed { patch_id: String, findings: String },
+    
+    #[error("Unknown namespace: {0}")]
+    UnknownNamespace(String),
+    
+    #[error("Namespace configuration error: {0}")]
+    Namespace(String),
+    
+    #[error("Emergency authorization rejected: {0}")]
+    Emergency(String),
+    
+    #[error("Operator override rejected: {0}")]
+    Override(String),
+    
+    #[error("Patch {patch_id} violates namespace isolation: {reason}")]
+    NamespaceViolation { patch_id: String, reason: String },
+    
+    #[error("Patch {patch_id} overlaps pending patches: {conflicts}")]
+    PatchConflict { patch_id: String, conflicts: String },
+    
+    #[error("Patch {0} is already pending or applied")]
+    DuplicatePatch(String),
+    
+    #[error("Patch {patch_id} cannot be rolled back: {reason}")]
+    RollbackRefused { patch_id: String, reason: String },
+    
+    #[error("Management API error: {0}")]
+    Api(String),
+    
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+    
+    #[error("Key pin error: {0}")]
+    KeyPin(String),
+    
+    #[error("Release rejected: {0}")]
+    Release(String),
+    
+    #[error("Storage error: {0}")]
+    Storage(String),
+    
+    #[error("Transparency log check failed: {0}")]
+    Transparency(String),
+    
+    #[error("Maintenance window error: {0}")]
+    Maintenance(String),
+}
+
+/// Process exit code for success
+pub const EXIT_OK: u8 = 0;
+/// Process exit code for errors not covered by a more specific code
+pub const EXIT_FAILURE: u8 = 1;
+/// Process exit code when a patch is rejected on moral grounds
+pub const EXIT_MORAL_REJECTION: u8 = 2;
+/// Process exit code when a patch fails integrity, signature or behavioral verification
+pub const EXIT_VERIFICATION_FAILURE: u8 = 3;
+/// Process exit code when a verified patch could not be applied
+pub const EXIT_APPLY_FAILURE: u8 = 4;
+
+impl OrchestratorError {
+    /// Stable machine-readable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::EthicsInitialization(_) => "ethics_initialization",
+            Self::HarmPredictorInitialization(_) => "harm_predictor_initialization",
+            Self::DirectoryCreation(_) => "directory_creation",
+            Self::PatchTooLarge { .. } => "patch_too_large",
+            Self::HashMismatch { .. } => "hash_mismatch",
+            Self::MoralViolation(_) => "moral_violation",
+            Self::EthicsEvaluation(_) => "ethics_evaluation",
+            Self::HarmAnalysis(_) => "harm_analysis",
+            Self::PatchNotFound(_) => "patch_not_found",
+            Self::BackupCreation(_) => "backup_creation",
+            Self::BackupRestoration(_) => "backup_restoration",
+            Self::BackupNotFound(_) => "backup_not_found",
+            Self::UnsupportedComponent(_) => "unsupported_component",
+            Self::SignatureError(_) => "signature_error",
+            Self::Staging(_) => "staging",
+            Self::ConsistencyCheck(_) => "consistency_check",
+            Self::ConformanceFailure(_) => "conformance_failure",
+            Self::ApprovalRequired { .. } => "approval_required",
+            Self::ModelLoad(_) => "model_load",
+            Self::ShadowRejected { .. } => "shadow_rejected",
+            Self::AuditTrail(_) => "audit_trail",
+            Self::Handoff(_) => "handoff",
+            Self::Ingestion(_) => "ingestion",
+            Self::Approval(_) => "approval",
+            Self::DetachedApprovalRequired { .. } => "detached_approval_required",
+            Self::NotApproved(_) => "not_approved",
+            Self::BinaryAuditFailed { .. } => "binary_audit_failed",
+            Self::UnknownNamespace(_) => "unknown_namespace",
+            Self::Namespace(_) => "namespace",
+            Self::Emergency(_) => "emergency",
+            Self::Override(_) => "override",
+            Self::NamespaceViolation { .. } => "namespace_violation",
+            Self::PatchConflict { .. } => "patch_conflict",
+            Self::DuplicatePatch(_) => "duplicate_patch",
+            Self::RollbackRefused { .. } => "rollback_refused",
+            Self::Api(_) => "api",
+            Self::Snapshot(_) => "snapshot",
+            Self::KeyPin(_) => "key_pin",
+            Self::Release(_) => "release",
+            Self::Storage(_) => "storage",
+            Self::Transparency(_) => "transparency",
+            Self::Maintenance(_) => "maintenance",
+        }
+    }
+    
+    /// Process exit code for this error
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::MoralViolation(_) => EXIT_MORAL_REJECTION,
+            
+            Self::PatchTooLarge { .. }
+            | Self::HashMismatch { .. }
+            | Self::SignatureError(_)
+            | Self::ConsistencyCheck(_)
+            | Self::ConformanceFailure(_)
+            | Self::ShadowRejected { .. }
+            | Self::Ingestion(_)
+            | Self::Approval(_)
+            | Self::BinaryAuditFailed { .. }
+            | Self::Emergency(_)
+            | Self::Override(_)
+            | Self::NamespaceViolation { .. }
+            | Self::Snapshot(_)
+            | Self::KeyPin(_)
+            | Self::Release(_)
+            | Self::Transparency(_) => EXIT_VERIFICATION_FAILURE,
+            
+            Self::BackupCreation(_)
+            | Self::BackupRestoration(_)
+            | Self::BackupNotFound(_)
+            | Self::Staging(_)
+            | Self::ApprovalRequired { .. }
+            | Self::ModelLoad(_)
+            | Self::Handoff(_)
+            | Self::DetachedApprovalRequired { .. }
+            | Self::NotApproved(_)
+            | Self::PatchConflict { .. }
+            | Self::RollbackRefused { .. } => EXIT_APPLY_FAILURE,
+            
+            _ => EXIT_FAILURE,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -801,17 +2693,7 @@ mod tests {
     #[tokio::test]
     async fn test_righteous_patch_acceptance() {
         let temp_dir = tempdir().unwrap();
-        let config = OrchestratorConfig {
-            patch_directory: temp_dir.path().join("patches"),
-            staging_directory: temp_dir.path().join("staging"),
-            backup_directory: temp_dir.path().join("backups"),
-            max_patch_size: 1024 * 1024,
-            verification_timeout: Duration::from_secs(30),
-            auto_apply_threshold: CriticalityLevel::High,
-            require_biblical_justification: true,
-            signing_keys: HashMap::new(),
-            moral_strictness: MoralStrictness::Standard,
-        };
+        let config = OrchestratorConfig::for_tests(temp_dir.path());
         
         let mut orchestrator = PatchOrchestrator::new(config).await.unwrap();
         
@@ -837,14 +2719,27 @@ mod tests {
                 overall_risk: RiskLevel::Low,
                 mitigation_required: false,
                 biblical_concerns: vec![],
+                overridden_concerns: vec![],
             },
             created_at: SystemTime::now(),
             expires_at: None,
         };
         
-        let patch_id = orchestrator.submit_patch(patch_data, metadata).await.unwrap();
+        let patch_id = orchestrator.submit_patch(patch_data, metadata.clone()).await.unwrap();
         assert_eq!(patch_id, "test-righteous-001");
         
+        // Below the auto-apply threshold, it waits for an operator
+        assert!(matches!(orchestrator.apply_patch(&patch_id).await, Err(OrchestratorError::NotApproved(_))));
+        
+        // A pending id cannot be reused to swap the staged payload
+        let swapped = b"// Same id, different content";
+        let mut resubmitted = metadata;
+        resubmitted.hash = blake3::hash(swapped);
+        resubmitted.size_bytes = swapped.len() as u64;
+        assert!(matches!(orchestrator.submit_patch(swapped, resubmitted).await,
+                         Err(OrchestratorError::DuplicatePatch(_))));
+        assert_eq!(std::fs::read(orchestrator.staged_payload_path(&patch_id)).unwrap(), patch_data);
+        
         let status = orchestrator.get_system_status();
         assert!(status.biblical_compliance);
     }
@@ -852,17 +2747,8 @@ mod tests {
     #[tokio::test]
     async fn test_wicked_patch_rejection() {
         let temp_dir = tempdir().unwrap();
-        let config = OrchestratorConfig {
-            patch_directory: temp_dir.path().join("patches"),
-            staging_directory: temp_dir.path().join("staging"),
-            backup_directory: temp_dir.path().join("backups"),
-            max_patch_size: 1024 * 1024,
-            verification_timeout: Duration::from_secs(30),
-            auto_apply_threshold: CriticalityLevel::High,
-            require_biblical_justification: true,
-            signing_keys: HashMap::new(),
-            moral_strictness: MoralStrictness::Orthodox,
-        };
+        let mut config = OrchestratorConfig::for_tests(temp_dir.path());
+        config.moral_strictness = MoralStrictness::Orthodox;
         
         let mut orchestrator = PatchOrchestrator::new(config).await.unwrap();
         
@@ -888,6 +2774,7 @@ mod tests {
                 overall_risk: RiskLevel::Critical,
                 mitigation_required: true,
                 biblical_concerns: vec!["Violates autonomous divine mission".to_string()],
+                overridden_concerns: vec![],
             },
             created_at: SystemTime::now(),
             expires_at: None,
@@ -895,6 +2782,160 @@ mod tests {
         
         let result = orchestrator.submit_patch(patch_data, metadata).await;
         assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), OrchestratorError::MoralViolation(_)));
+        let error = result.unwrap_err();
+        assert!(matches!(error, OrchestratorError::MoralViolation(_)));
+        assert_eq!(error.exit_code(), EXIT_MORAL_REJECTION);
+        assert_eq!(error.code(), "moral_violation");
+    }
+    
+    #[tokio::test]
+    async fn test_override_budget_is_per_operator_and_never_auto_applies() {
+        let temp_dir = tempdir().unwrap();
+        let mut config = OrchestratorConfig::for_tests(temp_dir.path());
+        config.auto_apply_threshold = CriticalityLevel::Low;
+        let mut orchestrator = PatchOrchestrator::new(config).await.unwrap();
+        
+        // Two claims by the same operator share the persona's single use
+        for claim in ["claim-a", "claim-b"] {
+            orchestrator.overrides.push(ActiveOverride {
+                claim: claim.to_string(),
+                operator: "keymaster".to_string(),
+                rules: vec!["kill_switch".to_string()],
+                components: vec![],
+                reason: "key rotation".to_string(),
+                expires_at: SystemTime::now() + Duration::from_secs(600),
+                budget: 1,
+                uses: 0,
+            });
+        }
+        
+        let mut metadata = PatchMetadata {
+            id: "rotate-keys".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Disable the retired signing key".to_string(),
+            component: "network_sentinel".to_string(),
+            criticality: CriticalityLevel::Critical,
+            moral_assessment: PatchMorality::Righteous,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"rotate"),
+            size_bytes: 6,
+            dependencies: vec![],
+            biblical_justification: Some("1 Corinthians 4:2".to_string()),
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: None,
+        };
+        
+        let (concerns, overridden) = orchestrator.identify_biblical_concerns(&metadata);
+        assert!(concerns.is_empty());
+        assert_eq!(overridden, vec!["kill_switch".to_string()]);
+        metadata.harm_analysis.overridden_concerns = overridden;
+        assert!(!orchestrator.should_auto_apply(&metadata));
+        
+        orchestrator.record_override_uses(&metadata).unwrap();
+        assert_eq!(orchestrator.overrides().count(), 0);
+        let (concerns, overridden) = orchestrator.identify_biblical_concerns(&metadata);
+        assert_eq!(concerns.len(), 1);
+        assert!(overridden.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_emergency_assessment_ends_with_the_authorization() {
+        let temp_dir = tempdir().unwrap();
+        let config = OrchestratorConfig::for_tests(temp_dir.path());
+        let mut orchestrator = PatchOrchestrator::new(config).await.unwrap();
+        orchestrator.emergency = Some(ActiveEmergency {
+            authorization: "auth-1".to_string(),
+            reason: "outage".to_string(),
+            approvers: vec!["keymaster".to_string()],
+            expires_at: SystemTime::now() + Duration::from_secs(600),
+        });
+        
+        let metadata = PatchMetadata {
+            id: "hotfix".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Restart the stalled sensor".to_string(),
+            component: "network_sentinel".to_string(),
+            criticality: CriticalityLevel::High,
+            moral_assessment: apply_strictness(&MoralStrictness::Emergency, PatchMorality::Questionable),
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"hotfix"),
+            size_bytes: 6,
+            dependencies: vec![],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: Some(EmergencyScope {
+                authorization: "auth-1".to_string(),
+                expires_at: SystemTime::now() + Duration::from_secs(600),
+                unrelaxed: PatchMorality::Questionable,
+            }),
+        };
+        assert_eq!(metadata.moral_assessment, PatchMorality::Permissible);
+        orchestrator.pending_patches.insert(metadata.id.clone(), metadata.clone());
+        assert!(orchestrator.is_morally_acceptable(&metadata));
+        
+        // Past its window the relaxed assessment no longer counts, even
+        // before the authorization is cleared
+        if let Some(emergency) = orchestrator.emergency.as_mut() {
+            emergency.expires_at = SystemTime::now() - Duration::from_secs(1);
+        }
+        assert!(matches!(orchestrator.strictness(), MoralStrictness::Standard));
+        assert!(!orchestrator.is_morally_acceptable(&metadata));
+        
+        orchestrator.expire_emergency().unwrap();
+        assert!(orchestrator.emergency.is_none());
+        let pending = &orchestrator.pending_patches["hotfix"];
+        assert_eq!(pending.moral_assessment, PatchMorality::Questionable);
+        assert!(pending.emergency_scope.is_none());
+    }
+    
+    #[test]
+    fn test_exit_codes_by_failure_class() {
+        let verification = OrchestratorError::SignatureError("bad".to_string());
+        assert_eq!(verification.exit_code(), EXIT_VERIFICATION_FAILURE);
+        
+        let apply = OrchestratorError::Staging("disk full".to_string());
+        assert_eq!(apply.exit_code(), EXIT_APPLY_FAILURE);
+        
+        let other = OrchestratorError::PatchNotFound("missing".to_string());
+        assert_eq!(other.exit_code(), EXIT_FAILURE);
+        assert_eq!(other.code(), "patch_not_found");
     }
 } 
\ No newline at end of file
@@ -0,0 +1,350 @@
+//! Maintenance Windows and Patch Deferral
+//!
+//! Patches that would auto-apply wait for a maintenance window instead of
+//! landing during peak defensive activity. Windows start on a cron-like
+//! schedule (`minute hour day-of-month month day-of-week`, in UTC) and
+//! last a fixed duration; each covers some components and criticalities,
+//! or all of them. A patch no window covers is not restricted. A patch
+//! whose covering windows are all closed stays pending as deferred: the
+//! deferral is audited and raised as an alert naming when the next window
+//! opens, and `apply_deferred_patches`, run periodically by the management
+//! API and by `maintenance --apply-due`, applies it inside that window.
+//!
+//! Divine-criticality patches bypass closed windows when the policy allows
+//! it, with every bypass logged, audited and alerted. Operators can still
+//! approve and apply any patch explicitly.
+//!
+//! ## Biblical Foundation
+//! "He hath made every thing beautiful in his time" - Ecclesiastes 3:11
+
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{CriticalityLevel, OrchestratorError, PatchMetadata};
+
+/// Longest window; bounds the search for a window's start
+pub const MAX_WINDOW_DURATION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Furthest ahead the next opening of a window is searched, in minutes
+const SEARCH_HORIZON_MINUTES: i64 = 366 * 24 * 60;
+
+/// Maintenance windows for auto-applied patches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenancePolicy {
+    /// Windows auto-apply is confined to
+    pub windows: Vec<MaintenanceWindow>,
+    /// Whether Divine-criticality patches auto-apply outside their windows
+    pub divine_override: bool,
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        Self { windows: Vec::new(), divine_override: true }
+    }
+}
+
+/// Recurring period in which covered patches may auto-apply
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub name: String,
+    /// Start times: `minute hour day-of-month month day-of-week` (UTC)
+    pub schedule: String,
+    /// Time the window stays open after each start
+    pub duration: Duration,
+    /// Components covered; empty covers every component
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Criticalities covered; empty covers every criticality
+    #[serde(default)]
+    pub criticalities: Vec<CriticalityLevel>,
+}
+
+impl MaintenanceWindow {
+    /// Whether the window governs `metadata`
+    pub fn covers(&self, metadata: &PatchMetadata) -> bool {
+        (self.components.is_empty() || self.components.contains(&metadata.component))
+            && (self.criticalities.is_empty() || self.criticalities.contains(&metadata.criticality))
+    }
+
+    /// Whether the window is open at `at`
+    pub fn is_open(&self, at: SystemTime) -> bool {
+        let Ok(schedule) = self.schedule.parse::<Schedule>() else { return false };
+        let at = DateTime::<Utc>::from(at);
+        let duration = chrono::Duration::from_std(self.duration).unwrap_or_else(|_| chrono::Duration::zero());
+        let mut start = minute_of(at);
+        while start + duration > at {
+            if schedule.matches(start) {
+                return true;
+            }
+            start -= chrono::Duration::minutes(1);
+        }
+        false
+    }
+
+    /// Next start after `at`, within a year
+    pub fn next_opening(&self, at: SystemTime) -> Option<SystemTime> {
+        let schedule = self.schedule.parse::<Schedule>().ok()?;
+        let first = minute_of(DateTime::<Utc>::from(at)) + chrono::Duration::minutes(1);
+        (0..SEARCH_HORIZON_MINUTES)
+            .map(|offset| first + chrono::Duration::minutes(offset))
+            .find(|start| schedule.matches(*start))
+            .map(SystemTime::from)
+    }
+}
+
+/// Whether a patch may auto-apply now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowCheck {
+    /// No window covers the patch
+    Unrestricted,
+    /// A covering window is open
+    Open { window: String },
+    /// Every covering window is closed
+    Closed {
+        /// Covering window opening next, if any opens within a year
+        window: Option<String>,
+        opens_at: Option<SystemTime>,
+    },
+}
+
+impl WindowCheck {
+    /// Whether the patch may auto-apply
+    pub fn permits(&self) -> bool {
+        !matches!(self, WindowCheck::Closed { .. })
+    }
+}
+
+/// Pending patch waiting for its maintenance window
+#[derive(Debug, Clone, Serialize)]
+pub struct DeferredPatch {
+    pub patch_id: String,
+    pub component: String,
+    pub criticality: CriticalityLevel,
+    /// Covering window opening next
+    pub window: Option<String>,
+    pub opens_at: Option<SystemTime>,
+}
+
+impl MaintenancePolicy {
+    /// Reject unparsable schedules and empty or overlong windows
+    pub fn validate(&self) -> Result<(), OrchestratorError> {
+        for window in &self.windows {
+            window.schedule.parse::<Schedule>()
+                .map_err(|e| OrchestratorError::Maintenance(format!("Window {}: {}", window.name, e)))?;
+            if window.duration.is_zero() || window.duration > MAX_WINDOW_DURATION {
+                return Err(OrchestratorError::Maintenance(format!(
+                    "Window {} lasts {:?}; windows last between a second and {:?}", window.name, window.duration, MAX_WINDOW_DURATION
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `metadata` may auto-apply at `at`
+    pub fn check(&self, metadata: &PatchMetadata, at: SystemTime) -> WindowCheck {
+        let covering: Vec<&MaintenanceWindow> = self.windows.iter().filter(|window| window.covers(metadata)).collect();
+        if covering.is_empty() {
+            return WindowCheck::Unrestricted;
+        }
+        if let Some(open) = covering.iter().find(|window| window.is_open(at)) {
+            return WindowCheck::Open { window: open.name.clone() };
+        }
+        let next = covering.iter()
+            .filter_map(|window| window.next_opening(at).map(|opens_at| (opens_at, window.name.clone())))
+            .min();
+        WindowCheck::Closed { window: next.as_ref().map(|(_, name)| name.clone()), opens_at: next.map(|(opens_at, _)| opens_at) }
+    }
+}
+
+/// Start of the minute containing `at`
+fn minute_of(at: DateTime<Utc>) -> DateTime<Utc> {
+    at.with_second(0).and_then(|at| at.with_nanosecond(0)).unwrap_or(at)
+}
+
+/// Parsed cron-like schedule
+///
+/// Fields accept `*`, values, ranges `a-b`, steps `*/n` and `a-b/n`, and
+/// comma-separated lists of these. Day of week runs from 0 (Sunday) to 6,
+/// with 7 also Sunday. As in cron, a time matches when both day fields
+/// match, or either of them if both are restricted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("schedule {:?} needs five fields: minute hour day-of-month month day-of-week", s));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl Schedule {
+    /// Whether the minute starting at `at` is a start time
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        let bit = |set: u64, value: u32| set & (1 << value) != 0;
+        let day = bit(self.days, at.day());
+        let weekday = bit(self.weekdays, at.weekday().num_days_from_sunday());
+        let day_matches = if self.days_restricted && self.weekdays_restricted { day || weekday } else { day && weekday };
+        bit(self.minutes, at.minute()) && bit(self.hours, at.hour()) && bit(self.months, at.month()) && day_matches
+    }
+}
+
+/// Bit set of the values `field` selects between `min` and `max`
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        text.parse::<u32>().ok().filter(|value| (min..=max).contains(value))
+            .ok_or_else(|| format!("{:?} is not a value between {} and {}", text, min, max))
+    };
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0)
+                .ok_or_else(|| format!("{:?} has an invalid step", part))?),
+            None => (part, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (value(first)?, value(last)?),
+            None if part.contains('/') => (value(range)?, max),
+            None => (value(range)?, value(range)?),
+        };
+        if first > last {
+            return Err(format!("{:?} is an empty range", part));
+        }
+        for selected in (first..=last).step_by(step as usize) {
+            set |= 1 << selected;
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HarmAnalysis, PatchMorality, SignatureAlgorithm, VerificationStatus};
+    use chrono::TimeZone;
+    use cold_mirror::RiskLevel;
+
+    fn patch(component: &str) -> PatchMetadata {
+        PatchMetadata {
+            id: "nightly-fix".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Model calibration".to_string(),
+            component: component.to_string(),
+            criticality: CriticalityLevel::High,
+            moral_assessment: PatchMorality::Righteous,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"weights"),
+            size_bytes: 7,
+            dependencies: vec![],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: crate::namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> SystemTime {
+        // March 2024; the 3rd is a Sunday
+        SystemTime::from(Utc.with_ymd_and_hms(2024, 3, day, hour, minute, 0).unwrap())
+    }
+
+    fn window(schedule: &str, hours: u64, criticalities: Vec<CriticalityLevel>) -> MaintenanceWindow {
+        MaintenanceWindow {
+            name: "nightly".into(),
+            schedule: schedule.into(),
+            duration: Duration::from_secs(hours * 3600),
+            components: vec!["cold_mirror".into()],
+            criticalities,
+        }
+    }
+
+    #[test]
+    fn test_schedule_fields() {
+        let weekdays: Schedule = "*/15 2-4 * * 1-5".parse().unwrap();
+        assert!(weekdays.matches(DateTime::from(at(4, 2, 45))));
+        assert!(!weekdays.matches(DateTime::from(at(4, 2, 50))));
+        assert!(!weekdays.matches(DateTime::from(at(3, 2, 45))));
+
+        // Both day fields restricted: the 1st of the month or any Sunday
+        let either: Schedule = "0 0 1 * 7".parse().unwrap();
+        assert!(either.matches(DateTime::from(at(1, 0, 0))));
+        assert!(either.matches(DateTime::from(at(3, 0, 0))));
+        assert!(!either.matches(DateTime::from(at(4, 0, 0))));
+
+        for invalid in ["* * * *", "60 * * * *", "5-1 * * * *", "*/0 * * * *", "a * * * *"] {
+            assert!(invalid.parse::<Schedule>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_windows_confine_covered_patches() {
+        let policy = MaintenancePolicy {
+            windows: vec![window("30 22 * * *", 2, vec![])],
+            divine_override: true,
+        };
+        policy.validate().unwrap();
+        let mut patch = patch("cold_mirror");
+
+        assert_eq!(policy.check(&patch, at(4, 23, 59)), WindowCheck::Open { window: "nightly".into() });
+        assert_eq!(policy.check(&patch, at(5, 0, 29)), WindowCheck::Open { window: "nightly".into() });
+        assert_eq!(policy.check(&patch, at(5, 0, 30)), WindowCheck::Closed {
+            window: Some("nightly".into()),
+            opens_at: Some(at(5, 22, 30)),
+        });
+        patch.component = "ethics_dsl".into();
+        assert_eq!(policy.check(&patch, at(5, 12, 0)), WindowCheck::Unrestricted);
+
+        let overlong = MaintenancePolicy { windows: vec![window("0 0 * * *", 24 * 8, vec![])], divine_override: false };
+        assert!(overlong.validate().is_err());
+        let critical_only = window("0 0 * * *", 1, vec![CriticalityLevel::Critical]);
+        patch.component = "cold_mirror".into();
+        assert!(!critical_only.covers(&patch));
+    }
+}
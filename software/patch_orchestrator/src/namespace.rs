@@ -0,0 +1,363 @@
+//! Multi-Tenant Namespaces
+//!
+//! Several ARK instances (one per region) can be managed from a single control
+//! host. Each namespace gets its own patch, staging and backup directories,
+//! its own trusted key set and trust bundle, and its own component root, so
+//! the orchestrator serving one namespace never reads or writes another's
+//! files. Patches carry the namespace they were built for; it is covered by
+//! patch signatures and approval digests, and a patch may only depend on
+//! patches of its own namespace.
+//!
+//! The `default` namespace keeps the unscoped directories and keys of the base
+//! configuration, so single-instance deployments are unchanged.
+//!
+//! ## Biblical Foundation
+//! "Remove not the ancient landmark, which thy fathers have set" - Proverbs 22:28
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{OrchestratorConfig, OrchestratorError, PatchMetadata, PatchOrchestrator};
+
+/// Namespace of unscoped, single-instance deployments
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Separator between namespace and patch ID in qualified references
+pub const NAMESPACE_SEPARATOR: char = '/';
+
+/// Longest accepted namespace name
+pub const MAX_NAMESPACE_LENGTH: usize = 63;
+
+/// Per-namespace settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamespaceConfig {
+    /// Trusted signing keys; never inherited from the base configuration
+    pub signing_keys: HashMap<String, Vec<u8>>,
+    /// Trust bundle of approver keys; never inherited from the base configuration
+    pub trust_bundle: Option<PathBuf>,
+    /// Components this namespace may patch; empty allows all
+    pub components: Vec<String>,
+    /// Root of the namespace's component tree (defaults to the namespace name)
+    pub component_root: Option<PathBuf>,
+}
+
+/// Serde default for namespace fields
+pub fn default_namespace() -> String {
+    DEFAULT_NAMESPACE.to_string()
+}
+
+/// Check that a namespace name is safe to use as a directory name
+pub fn validate_name(namespace: &str) -> Result<(), OrchestratorError> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= MAX_NAMESPACE_LENGTH
+        && namespace.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        && !namespace.starts_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(OrchestratorError::Namespace(format!(
+            "Invalid namespace name {:?}: use 1-{} lowercase letters, digits, '-' or '_'",
+            namespace, MAX_NAMESPACE_LENGTH
+        )))
+    }
+}
+
+/// Qualify a patch ID with its namespace, e.g. `eu-west/patch-001`
+pub fn qualify(namespace: &str, patch_id: &str) -> String {
+    format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, patch_id)
+}
+
+/// Split a possibly qualified patch reference into namespace and patch ID
+pub fn split_qualified(reference: &str) -> (Option<&str>, &str) {
+    match reference.split_once(NAMESPACE_SEPARATOR) {
+        Some((namespace, patch_id)) => (Some(namespace), patch_id),
+        None => (None, reference),
+    }
+}
+
+/// Reject patches that reach outside the orchestrator's namespace
+pub fn check_isolation(config: &OrchestratorConfig, metadata: &PatchMetadata) -> Result<(), OrchestratorError> {
+    let violation = |reason: String| OrchestratorError::NamespaceViolation {
+        patch_id: metadata.id.clone(),
+        reason,
+    };
+
+    if metadata.namespace != config.namespace {
+        return Err(violation(format!(
+            "patch belongs to namespace {:?}, this orchestrator serves {:?}",
+            metadata.namespace, config.namespace
+        )));
+    }
+
+    // Component names become paths below the component root
+    let component = Path::new(&metadata.component);
+    if metadata.component.is_empty()
+        || !component.components().all(|part| matches!(part, Component::Normal(_)))
+        || metadata.component.contains(['/', '\\'])
+    {
+        return Err(violation(format!("component {:?} escapes the namespace", metadata.component)));
+    }
+
+    if let Some(namespace) = config.namespaces.get(&config.namespace) {
+        if !namespace.components.is_empty() && !namespace.components.contains(&metadata.component) {
+            return Err(violation(format!(
+                "component {:?} is not part of namespace {:?}",
+                metadata.component, config.namespace
+            )));
+        }
+    }
+
+    for dependency in &metadata.dependencies {
+        if let (Some(namespace), _) = split_qualified(dependency) {
+            if namespace != config.namespace {
+                return Err(violation(format!("dependency {:?} belongs to another namespace", dependency)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl OrchestratorConfig {
+    /// Configuration of a single namespace
+    ///
+    /// Non-default namespaces get directories below the base ones, their own
+    /// key set and trust bundle, and live artifact paths resolved below their
+    /// component root. The result only knows about its own namespace.
+    pub fn for_namespace(&self, namespace: &str) -> Result<OrchestratorConfig, OrchestratorError> {
+        validate_name(namespace)?;
+
+        let mut scoped = self.clone();
+        scoped.namespace = namespace.to_string();
+
+        if namespace == DEFAULT_NAMESPACE {
+            scoped.namespaces = self.namespaces.get(namespace)
+                .map(|settings| HashMap::from([(namespace.to_string(), settings.clone())]))
+                .unwrap_or_default();
+            return Ok(scoped);
+        }
+
+        let mut settings = self.namespaces.get(namespace)
+            .cloned()
+            .ok_or_else(|| OrchestratorError::UnknownNamespace(namespace.to_string()))?;
+        let root = settings.component_root.clone().unwrap_or_else(|| PathBuf::from(namespace));
+
+        scoped.patch_directory = self.patch_directory.join(namespace);
+        scoped.staging_directory = self.staging_directory.join(namespace);
+        scoped.backup_directory = self.backup_directory.join(namespace);
+        scoped.signing_keys = settings.signing_keys.clone();
+        scoped.approval_policy.trust_bundle = settings.trust_bundle.clone();
+        scoped.ethics_patch_policy.live_rule_pack = rebase(&root, &self.ethics_patch_policy.live_rule_pack)?;
+        scoped.shadow_policy.active_model = rebase(&root, &self.shadow_policy.active_model)?;
+        scoped.handoff_policy.live_binary = rebase(&root, &self.handoff_policy.live_binary)?;
+
+        settings.component_root = Some(root);
+        scoped.namespaces = HashMap::from([(namespace.to_string(), settings)]);
+        Ok(scoped)
+    }
+
+    /// Namespaces declared by this configuration, or the default one
+    pub fn namespace_names(&self) -> Vec<String> {
+        if self.namespaces.is_empty() {
+            return vec![default_namespace()];
+        }
+        let mut names: Vec<String> = self.namespaces.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Root of this namespace's component tree, if scoped
+    pub fn component_root(&self) -> Option<&Path> {
+        self.namespaces.get(&self.namespace)?.component_root.as_deref()
+    }
+}
+
+/// Resolve a live artifact path below a namespace component root
+fn rebase(root: &Path, path: &Path) -> Result<PathBuf, OrchestratorError> {
+    if path.is_relative() {
+        return Ok(root.join(path));
+    }
+    if path.starts_with(root) {
+        return Ok(path.to_path_buf());
+    }
+    Err(OrchestratorError::Namespace(format!(
+        "Absolute path {:?} lies outside namespace root {:?}",
+        path, root
+    )))
+}
+
+/// Orchestrators for every namespace managed from this host
+pub struct NamespaceHost {
+    orchestrators: BTreeMap<String, PatchOrchestrator>,
+}
+
+impl NamespaceHost {
+    /// Start one orchestrator per declared namespace
+    pub async fn new(config: OrchestratorConfig) -> Result<Self, OrchestratorError> {
+        let mut orchestrators = BTreeMap::new();
+        for namespace in config.namespace_names() {
+            let scoped = config.for_namespace(&namespace)?;
+            info!("Starting orchestrator for namespace {}", namespace);
+            orchestrators.insert(namespace, PatchOrchestrator::new(scoped).await?);
+        }
+        Ok(Self { orchestrators })
+    }
+
+    /// Names of the managed namespaces
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.orchestrators.keys().map(String::as_str)
+    }
+
+    /// Orchestrator serving a namespace
+    pub fn get(&self, namespace: &str) -> Result<&PatchOrchestrator, OrchestratorError> {
+        self.orchestrators.get(namespace)
+            .ok_or_else(|| OrchestratorError::UnknownNamespace(namespace.to_string()))
+    }
+
+    /// Mutable orchestrator serving a namespace
+    pub fn get_mut(&mut self, namespace: &str) -> Result<&mut PatchOrchestrator, OrchestratorError> {
+        self.orchestrators.get_mut(namespace)
+            .ok_or_else(|| OrchestratorError::UnknownNamespace(namespace.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval::approval_digest;
+    use crate::audit::{AuditEvent, AuditTrail};
+    use crate::{CriticalityLevel, HarmAnalysis, PatchMorality, SignatureAlgorithm, VerificationStatus};
+    use cold_mirror::RiskLevel;
+    use std::time::SystemTime;
+
+    fn base_config() -> OrchestratorConfig {
+        let mut config = OrchestratorConfig::for_tests(Path::new("/srv/ark"));
+        config.signing_keys = HashMap::from([("ed25519".to_string(), vec![1; 32])]);
+        config.namespaces = HashMap::from([
+            ("eu-west".to_string(), NamespaceConfig {
+                signing_keys: HashMap::from([("ed25519".to_string(), vec![2; 32])]),
+                trust_bundle: Some(PathBuf::from("/etc/ark/eu-west-bundle.json")),
+                components: vec!["ethics_dsl".to_string(), "cold_mirror".to_string()],
+                component_root: Some(PathBuf::from("/srv/ark/eu-west")),
+            }),
+            ("us-east".to_string(), NamespaceConfig::default()),
+        ]);
+        config
+    }
+
+    fn metadata(namespace: &str, component: &str, dependencies: &[&str]) -> PatchMetadata {
+        PatchMetadata {
+            id: "patch-001".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Regional rule pack".to_string(),
+            component: component.to_string(),
+            criticality: CriticalityLevel::Medium,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"rules"),
+            size_bytes: 5,
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: namespace.to_string(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    #[test]
+    fn test_scoped_configs_share_no_directories_or_keys() {
+        let base = base_config();
+        let eu = base.for_namespace("eu-west").unwrap();
+        let us = base.for_namespace("us-east").unwrap();
+        let default = base.for_namespace(DEFAULT_NAMESPACE).unwrap();
+
+        assert_eq!(default.patch_directory, base.patch_directory);
+        assert_eq!(default.signing_keys, base.signing_keys);
+
+        for (a, b) in [(&eu, &us), (&eu, &default), (&us, &default)] {
+            assert_ne!(a.patch_directory, b.patch_directory);
+            assert_ne!(a.staging_directory, b.staging_directory);
+            assert_ne!(a.backup_directory, b.backup_directory);
+            assert_ne!(a.ethics_patch_policy.live_rule_pack, b.ethics_patch_policy.live_rule_pack);
+            assert_ne!(a.shadow_policy.active_model, b.shadow_policy.active_model);
+        }
+        assert_eq!(eu.signing_keys["ed25519"], vec![2; 32]);
+        assert!(us.signing_keys.is_empty(), "base keys must not leak into a namespace");
+        assert!(us.approval_policy.trust_bundle.is_none());
+        assert!(eu.ethics_patch_policy.live_rule_pack.starts_with("/srv/ark/eu-west"));
+        assert_eq!(eu.namespaces.len(), 1);
+
+        assert!(matches!(base.for_namespace("ap-south"), Err(OrchestratorError::UnknownNamespace(_))));
+        for invalid in ["", "../eu-west", "eu/west", "EU", "-eu"] {
+            assert!(base.for_namespace(invalid).is_err(), "{:?} accepted", invalid);
+        }
+        assert_eq!(base.namespace_names(), vec!["eu-west".to_string(), "us-east".to_string()]);
+    }
+
+    #[test]
+    fn test_patches_cannot_reach_other_namespaces() {
+        let eu = base_config().for_namespace("eu-west").unwrap();
+
+        assert!(check_isolation(&eu, &metadata("eu-west", "ethics_dsl", &["base", "eu-west/prior"])).is_ok());
+
+        for patch in [
+            metadata("us-east", "ethics_dsl", &[]),
+            metadata(DEFAULT_NAMESPACE, "ethics_dsl", &[]),
+            metadata("eu-west", "firmware", &[]),
+            metadata("eu-west", "ethics_dsl", &["us-east/prior"]),
+            metadata("eu-west", "../us-east/ethics_dsl", &[]),
+        ] {
+            assert!(matches!(
+                check_isolation(&eu, &patch),
+                Err(OrchestratorError::NamespaceViolation { .. })
+            ));
+        }
+
+        // Approvals and signatures are bound to the namespace
+        assert_ne!(
+            approval_digest(&metadata("eu-west", "ethics_dsl", &[])),
+            approval_digest(&metadata("us-east", "ethics_dsl", &[]))
+        );
+    }
+
+    #[test]
+    fn test_audit_trails_are_isolated() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: std::sync::Arc<dyn ark_storage::Storage> =
+            std::sync::Arc::new(ark_storage::FileStorage::open(dir.path()).unwrap());
+        let eu = AuditTrail::new(storage.clone(), "eu-west");
+        let us = AuditTrail::new(storage, "us-east");
+
+        let event = AuditEvent::ApprovalImported { approver: "alice".to_string(), approvals: 1, required: 2 };
+        eu.record("patch-001", "ethics_dsl", event.clone()).unwrap();
+        us.record("patch-002", "ethics_dsl", event).unwrap();
+
+        let records = eu.records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].patch_id, "patch-001");
+        assert_eq!(records[0].namespace, "eu-west");
+        assert_eq!(us.records().unwrap()[0].patch_id, "patch-002");
+    }
+}
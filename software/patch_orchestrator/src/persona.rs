@@ -0,0 +1,350 @@
+//! Operator Personas and Scoped Overrides
+//!
+//! Some maintenance patches legitimately trip the moral concern rules: a key
+//! rotation script that disables the retired key reads like a kill switch.
+//! Operators configured as personas may relax named concern rules for such
+//! work. An operator signs an `OverrideClaim` naming the rules, the
+//! components it covers, the reason and a validity window; the claim is
+//! verified against the approval trust bundle and the persona's
+//! capabilities, so an operator can never relax a rule the configuration
+//! does not grant them. Every patch assessed under an override counts as
+//! one use of the operator's budget, is written to the audit trail, and
+//! once the persona's budget is spent the rules apply again; signing a
+//! fresh claim does not renew it. An override only ever turns a concern
+//! into something an operator must approve by hand: a patch assessed
+//! under one is never applied automatically.
+//!
+//! ## Biblical Foundation
+//! "Moreover it is required in stewards, that a man be found faithful" - 1 Corinthians 4:2
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use blake3::Hash;
+use ed25519_dalek::SigningKey as Ed25519SigningKey;
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes};
+use serde::{Deserialize, Serialize};
+
+use crate::approval::{self, TrustBundle};
+use crate::emergency::MAX_CLOCK_SKEW;
+use crate::OrchestratorError;
+
+/// Domain separator for override claim digests
+pub const OVERRIDE_DIGEST_DOMAIN: &str = "ark-operator-override-v2";
+
+/// Most rules or components one claim may name
+pub const MAX_CLAIM_ENTRIES: usize = 32;
+
+/// Operator allowed to relax concern rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorPersona {
+    /// Approver id in the trust bundle
+    pub operator: String,
+    /// Concern rules the operator may relax (see `CONCERN_RULES`)
+    pub rules: Vec<String>,
+    /// Components the operator's overrides may cover; empty means any
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Patches the operator's overrides may be used for, across all claims
+    pub override_budget: usize,
+}
+
+/// Personas and the limits on their overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonaPolicy {
+    pub personas: Vec<OperatorPersona>,
+    /// Longest validity window a claim may request
+    pub max_duration: Duration,
+}
+
+impl Default for PersonaPolicy {
+    fn default() -> Self {
+        Self {
+            personas: Vec::new(),
+            max_duration: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl PersonaPolicy {
+    pub fn persona(&self, operator: &str) -> Option<&OperatorPersona> {
+        self.personas.iter().find(|persona| persona.operator == operator)
+    }
+}
+
+/// Signed body of an override
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverrideClaim {
+    pub operator: String,
+    pub namespace: String,
+    /// Concern rules relaxed
+    pub rules: Vec<String>,
+    /// Components covered; empty means every component the persona may cover
+    pub components: Vec<String>,
+    pub reason: String,
+    pub issued_at: SystemTime,
+    pub expires_at: SystemTime,
+    /// Random value making every claim unique (hex)
+    pub nonce: String,
+}
+
+impl OverrideClaim {
+    /// Claim valid from now for `duration`
+    pub fn new(operator: &str, namespace: &str, rules: &[&str], components: &[&str], reason: &str, duration: Duration) -> Self {
+        use rand::RngCore;
+
+        let mut nonce = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let issued_at = SystemTime::now();
+        Self {
+            operator: operator.to_string(),
+            namespace: namespace.to_string(),
+            rules: rules.iter().map(|rule| rule.to_string()).collect(),
+            components: components.iter().map(|component| component.to_string()).collect(),
+            reason: reason.to_string(),
+            issued_at,
+            expires_at: issued_at + duration,
+            nonce: hex::encode(nonce),
+        }
+    }
+
+    /// Digest the operator signs, of the canonical encoding in `signing`
+    pub fn digest(&self) -> Hash {
+        blake3::hash(&self.signing_bytes())
+    }
+}
+
+/// Override claim with the operator's signatures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedOverride {
+    pub claim: OverrideClaim,
+    pub pq_signature: DilithiumSignatureBytes,
+    pub classical_signature: Ed25519SignatureBytes,
+}
+
+impl Validate for SignedOverride {
+    fn validate(&self) -> Result<(), String> {
+        let claim = &self.claim;
+        decode::check_identifier("operator", &claim.operator, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_identifier("namespace", &claim.namespace, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_count("rules", claim.rules.len(), MAX_CLAIM_ENTRIES)?;
+        for rule in &claim.rules {
+            decode::check_identifier("rule", rule, crate::MAX_PATCH_FIELD_LENGTH)?;
+        }
+        decode::check_count("components", claim.components.len(), MAX_CLAIM_ENTRIES)?;
+        for component in &claim.components {
+            decode::check_len("component", component, crate::MAX_PATCH_FIELD_LENGTH)?;
+        }
+        decode::check_len("reason", &claim.reason, crate::MAX_PATCH_TEXT_LENGTH)?;
+        decode::check_len("nonce", &claim.nonce, 64)
+    }
+}
+
+impl SignedOverride {
+    /// Read a signed override file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Override(format!("{:?}: {}", path, e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| OrchestratorError::Override(format!("{:?}: {}", path, e)))
+    }
+
+    /// Write a signed override file
+    pub fn save(&self, path: &Path) -> Result<(), OrchestratorError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| OrchestratorError::Override(e.to_string()))?;
+        std::fs::write(path, contents)
+            .map_err(|e| OrchestratorError::Override(format!("{:?}: {}", path, e)))
+    }
+}
+
+/// Override granted by a verified claim
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActiveOverride {
+    /// Digest of the claim (hex)
+    pub claim: String,
+    pub operator: String,
+    pub rules: Vec<String>,
+    /// Components covered; empty means any
+    pub components: Vec<String>,
+    pub reason: String,
+    pub expires_at: SystemTime,
+    /// Patches the operator's overrides may be used for
+    pub budget: usize,
+    /// Patches the operator's overrides have been used for, under any claim
+    pub uses: usize,
+}
+
+impl ActiveOverride {
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.uses >= self.budget
+    }
+
+    /// Whether the override relaxes `rule` for `component` at time `now`
+    pub fn covers(&self, rule: &str, component: &str, now: SystemTime) -> bool {
+        !self.is_expired(now)
+            && !self.is_exhausted()
+            && self.rules.iter().any(|r| r == rule)
+            && (self.components.is_empty() || self.components.iter().any(|c| c == component))
+    }
+}
+
+/// Sign an override claim on the operator's workstation
+pub fn sign_override(
+    claim: OverrideClaim,
+    pq_secret: &DilithiumSecretKeyBytes,
+    classical: &Ed25519SigningKey,
+) -> Result<SignedOverride, OrchestratorError> {
+    use ed25519_dalek::Signer;
+
+    let message = signed_message(&claim);
+    let pq_signature = Dilithium3::sign(&message, pq_secret)
+        .map_err(|e| OrchestratorError::Override(e.to_string()))?;
+    let classical_signature = classical.sign(&message);
+
+    Ok(SignedOverride {
+        claim,
+        pq_signature,
+        classical_signature: Ed25519SignatureBytes::from_slice(&classical_signature.to_bytes())
+            .map_err(|e| OrchestratorError::Override(e.to_string()))?,
+    })
+}
+
+/// Verify a signed override for a namespace at time `now`
+///
+/// The operator must be a persona granted every claimed rule and
+/// component, and the signature must verify against the trust bundle.
+pub fn verify_override(
+    signed: &SignedOverride,
+    policy: &PersonaPolicy,
+    bundle: &TrustBundle,
+    namespace: &str,
+    now: SystemTime,
+) -> Result<ActiveOverride, OrchestratorError> {
+    let claim = &signed.claim;
+    let reject = |reason: String| Err(OrchestratorError::Override(reason));
+
+    let Some(persona) = policy.persona(&claim.operator) else {
+        return reject(format!("Operator {} has no persona", claim.operator));
+    };
+    if claim.namespace != namespace {
+        return reject(format!("Override is for namespace {}, not {}", claim.namespace, namespace));
+    }
+    if claim.rules.is_empty() {
+        return reject("Override names no rules".into());
+    }
+    if let Some(rule) = claim.rules.iter().find(|rule| !persona.rules.contains(rule)) {
+        return reject(format!("Operator {} may not relax {}", claim.operator, rule));
+    }
+    if !persona.components.is_empty() {
+        if claim.components.is_empty() {
+            return reject(format!("Operator {} must name the components an override covers", claim.operator));
+        }
+        if let Some(component) = claim.components.iter().find(|c| !persona.components.contains(c)) {
+            return reject(format!("Operator {} may not override {}", claim.operator, component));
+        }
+    }
+    if claim.issued_at > now + MAX_CLOCK_SKEW {
+        return reject("Override is issued in the future".into());
+    }
+    if claim.expires_at <= now {
+        return reject("Override has expired".into());
+    }
+    match claim.expires_at.duration_since(claim.issued_at) {
+        Ok(duration) if duration <= policy.max_duration => {}
+        _ => return reject(format!("Override window exceeds {:?}", policy.max_duration)),
+    }
+
+    approval::verify_signature(bundle, &claim.operator, &signed_message(claim), &signed.pq_signature, &signed.classical_signature)
+        .map_err(OrchestratorError::Override)?;
+
+    Ok(ActiveOverride {
+        claim: claim.digest().to_hex().to_string(),
+        operator: claim.operator.clone(),
+        rules: claim.rules.iter().cloned().collect::<BTreeSet<_>>().into_iter().collect(),
+        components: claim.components.clone(),
+        reason: claim.reason.clone(),
+        expires_at: claim.expires_at,
+        budget: persona.override_budget,
+        uses: 0,
+    })
+}
+
+fn signed_message(claim: &OverrideClaim) -> Vec<u8> {
+    format!("{}\n{}", OVERRIDE_DIGEST_DOMAIN, claim.digest().to_hex()).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval::TrustedApprover;
+
+    fn operator(id: &str) -> (DilithiumSecretKeyBytes, Ed25519SigningKey, TrustBundle) {
+        let (pq_public, pq_secret) = Dilithium3::keypair().unwrap();
+        let classical = Ed25519SigningKey::generate(&mut rand::rngs::OsRng);
+        let mut bundle = TrustBundle::default();
+        bundle.approvers.push(TrustedApprover {
+            id: id.to_string(),
+            dilithium_public: hex::encode(pq_public.as_bytes()),
+            ed25519_public: hex::encode(classical.verifying_key().to_bytes()),
+        });
+        (pq_secret, classical, bundle)
+    }
+
+    fn policy() -> PersonaPolicy {
+        PersonaPolicy {
+            personas: vec![OperatorPersona {
+                operator: "keymaster".to_string(),
+                rules: vec!["kill_switch".to_string()],
+                components: vec!["network_sentinel".to_string()],
+                override_budget: 2,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_override_scoped_to_persona_capabilities() {
+        let (pq_secret, classical, bundle) = operator("keymaster");
+        let policy = policy();
+        let now = SystemTime::now();
+        let sign = |rules: &[&str], components: &[&str]| {
+            let claim = OverrideClaim::new("keymaster", "default", rules, components, "key rotation", Duration::from_secs(600));
+            sign_override(claim, &pq_secret, &classical).unwrap()
+        };
+
+        let granted = verify_override(&sign(&["kill_switch"], &["network_sentinel"]), &policy, &bundle, "default", now).unwrap();
+        assert_eq!(granted.budget, 2);
+        assert!(granted.covers("kill_switch", "network_sentinel", now));
+        assert!(!granted.covers("external_control", "network_sentinel", now));
+        assert!(!granted.covers("kill_switch", "firmware", now));
+        assert!(!granted.covers("kill_switch", "network_sentinel", now + Duration::from_secs(601)));
+
+        for (rules, components) in [(&["external_control"][..], &["network_sentinel"][..]), (&["kill_switch"], &["firmware"]), (&["kill_switch"], &[])] {
+            assert!(verify_override(&sign(rules, components), &policy, &bundle, "default", now).is_err());
+        }
+        assert!(verify_override(&sign(&["kill_switch"], &["network_sentinel"]), &policy, &bundle, "tenant-a", now).is_err());
+    }
+
+    #[test]
+    fn test_tampered_or_unknown_operator_rejected() {
+        let (pq_secret, classical, bundle) = operator("keymaster");
+        let policy = policy();
+        let claim = OverrideClaim::new("keymaster", "default", &["kill_switch"], &["network_sentinel"], "rotation", Duration::from_secs(600));
+        let mut signed = sign_override(claim, &pq_secret, &classical).unwrap();
+        signed.claim.reason = "routine maintenance".to_string();
+        assert!(verify_override(&signed, &policy, &bundle, "default", SystemTime::now()).is_err());
+
+        let stranger = OverrideClaim::new("stranger", "default", &["kill_switch"], &["network_sentinel"], "rotation", Duration::from_secs(600));
+        let signed = sign_override(stranger, &pq_secret, &classical).unwrap();
+        assert!(verify_override(&signed, &policy, &bundle, "default", SystemTime::now()).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+//! Patch Pipeline Properties
+//!
+//! Machine-checked properties of the patch pipeline:
+//!
+//! 1. A Wicked or Corrupting patch never reaches the applied set, however it
+//!    entered the pending queue and whatever strictness is in force.
+//! 2. Rolling a patch back, by an operator or after a failed application,
+//!    restores the component tree to the hashes it had before the apply.
+//! 3. Signature verification precedes any file write: a patch to any
+//!    component is verified against the trusted release keys first, and one
+//!    that fails leaves the staging, backup and component trees as they were.
+//!
+//! Property 1 is proved over every strictness and assessment by the Kani
+//! harnesses below (`cargo kani`). All three are also checked by proptest
+//! state-machine tests that drive a real orchestrator through random
+//! operation sequences; they run with the crate's unit tests.
+//!
+//! ## Biblical Foundation
+//! "The fining pot is for silver, and the furnace for gold: but the LORD trieth the hearts" - Proverbs 17:3
+
+#[cfg(kani)]
+use crate::{morally_acceptable, MoralStrictness, PatchMorality};
+
+#[cfg(kani)]
+fn any_strictness() -> MoralStrictness {
+    match kani::any::<u8>() % 3 {
+        0 => MoralStrictness::Orthodox,
+        1 => MoralStrictness::Standard,
+        _ => MoralStrictness::Emergency,
+    }
+}
+
+#[cfg(kani)]
+fn any_morality() -> PatchMorality {
+    match kani::any::<u8>() % 5 {
+        0 => PatchMorality::Righteous,
+        1 => PatchMorality::Permissible,
+        2 => PatchMorality::Questionable,
+        3 => PatchMorality::Wicked,
+        _ => PatchMorality::Corrupting,
+    }
+}
+
+/// No strictness accepts a Wicked or Corrupting assessment
+#[cfg(kani)]
+#[kani::proof]
+fn wicked_is_never_acceptable() {
+    let strictness = any_strictness();
+    let morality = any_morality();
+    if matches!(morality, PatchMorality::Wicked | PatchMorality::Corrupting) {
+        assert!(!morally_acceptable(&strictness, &morality));
+    }
+}
+
+/// Relaxing the strictness only ever admits more assessments
+#[cfg(kani)]
+#[kani::proof]
+fn relaxing_strictness_only_widens() {
+    let morality = any_morality();
+    if morally_acceptable(&MoralStrictness::Orthodox, &morality) {
+        assert!(morally_acceptable(&MoralStrictness::Standard, &morality));
+    }
+    if morally_acceptable(&MoralStrictness::Standard, &morality) {
+        assert!(morally_acceptable(&MoralStrictness::Emergency, &morality));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    const NAMESPACE: &str = "properties";
+
+    /// Patch ids the operations draw from; few, so operations collide
+    const SLOTS: usize = 4;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        /// Submit through assessment
+        Submit { slot: usize, wicked: bool },
+        /// Put a patch in the pending queue with a given assessment, as a
+        /// restored handoff state or a tampered queue would
+        Enqueue { slot: usize, morality: PatchMorality },
+        Approve(usize),
+        Apply(usize),
+        Rollback(usize),
+    }
+
+    fn morality() -> impl Strategy<Value = PatchMorality> {
+        prop_oneof![
+            Just(PatchMorality::Righteous),
+            Just(PatchMorality::Permissible),
+            Just(PatchMorality::Questionable),
+            Just(PatchMorality::Wicked),
+            Just(PatchMorality::Corrupting),
+        ]
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0..SLOTS, any::<bool>()).prop_map(|(slot, wicked)| Op::Submit { slot, wicked }),
+            (0..SLOTS, morality()).prop_map(|(slot, morality)| Op::Enqueue { slot, morality }),
+            (0..SLOTS).prop_map(Op::Approve),
+            (0..SLOTS).prop_map(Op::Apply),
+            (0..SLOTS).prop_map(Op::Rollback),
+        ]
+    }
+
+    /// An orchestrator scoped to a namespace rooted in a temporary directory
+    struct Pipeline {
+        _dir: TempDir,
+        runtime: tokio::runtime::Runtime,
+        orchestrator: PatchOrchestrator,
+    }
+
+    impl Pipeline {
+        fn new(strictness: MoralStrictness, signing_keys: HashMap<String, Vec<u8>>) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let mut base = OrchestratorConfig::for_tests(dir.path());
+            base.moral_strictness = strictness;
+            base.namespaces = HashMap::from([(NAMESPACE.to_string(), NamespaceConfig {
+                signing_keys,
+                component_root: Some(dir.path().join("root")),
+                ..NamespaceConfig::default()
+            })]);
+            let config = base.for_namespace(NAMESPACE).unwrap();
+
+            let live = &config.ethics_patch_policy.live_rule_pack;
+            std::fs::create_dir_all(live.parent().unwrap()).unwrap();
+            std::fs::write(live, "").unwrap();
+
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            let orchestrator = runtime.block_on(PatchOrchestrator::new(config)).unwrap();
+            Self { _dir: dir, runtime, orchestrator }
+        }
+
+        /// Trust the orchestrator's own signing keys as the release keys
+        fn trust_own_keys(&mut self) {
+            let (dilithium_public, _) = self.orchestrator.pq_signing_key.as_ref().unwrap();
+            let ed25519 = self.orchestrator.classical_signing_key.as_ref().unwrap();
+            self.orchestrator.config.signing_keys = HashMap::from([
+                ("dilithium3".to_string(), dilithium_public.as_bytes().to_vec()),
+                ("ed25519".to_string(), ed25519.verifying_key().as_bytes().to_vec()),
+            ]);
+        }
+
+        /// Metadata for `payload`, signed with the orchestrator's own keys
+        fn metadata(&self, slot: usize, component: &str, payload: &[u8]) -> PatchMetadata {
+            let mut metadata = PatchMetadata {
+                id: format!("patch-{}", slot),
+                version: "1.0.0".to_string(),
+                description: "Property test patch".to_string(),
+                component: component.to_string(),
+                criticality: CriticalityLevel::Low,
+                moral_assessment: PatchMorality::Permissible,
+                verification: VerificationStatus::Pending,
+                hash: blake3::hash(payload),
+                size_bytes: payload.len() as u64,
+                dependencies: vec![],
+                biblical_justification: Some("Matthew 22:39 - Love your neighbor as yourself".to_string()),
+                harm_analysis: HarmAnalysis {
+                    moral_harm_risk: RiskLevel::Low,
+                    physical_harm_risk: RiskLevel::Low,
+                    psychological_harm_risk: RiskLevel::Low,
+                    spiritual_harm_risk: RiskLevel::Low,
+                    system_integrity_risk: RiskLevel::Low,
+                    overall_risk: RiskLevel::Low,
+                    mitigation_required: false,
+                    biblical_concerns: vec![],
+                    overridden_concerns: vec![],
+                },
+                created_at: SystemTime::now(),
+                expires_at: None,
+                pq_signature: None,
+                classical_signature: None,
+                signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+                security_issues: Vec::new(),
+                namespace: NAMESPACE.to_string(),
+                files: vec![],
+                supersedes: vec![],
+                emergency_scope: None,
+            };
+            self.orchestrator.sign_patch(&mut metadata, SignatureAlgorithm::HybridEd25519Dilithium3).unwrap();
+            metadata
+        }
+
+        /// Queue `metadata` as is, skipping submission and assessment
+        fn enqueue(&mut self, metadata: PatchMetadata, payload: &[u8]) {
+            std::fs::write(self.orchestrator.staged_payload_path(&metadata.id), payload).unwrap();
+            self.orchestrator.pending_patches.insert(metadata.id.clone(), metadata);
+        }
+
+        fn component_digest(&self, component: &str) -> BTreeMap<PathBuf, Hash> {
+            tree_digest(&self.orchestrator.get_component_path(component))
+        }
+    }
+
+    /// Hash of every file below `root`, by path relative to it
+    fn tree_digest(root: &Path) -> BTreeMap<PathBuf, Hash> {
+        let mut digest = BTreeMap::new();
+        let mut directories = vec![root.to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            let Ok(entries) = std::fs::read_dir(&directory) else { continue };
+            for entry in entries {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    directories.push(path);
+                } else {
+                    digest.insert(path.strip_prefix(root).unwrap().to_path_buf(), blake3::hash(&std::fs::read(&path).unwrap()));
+                }
+            }
+        }
+        digest
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_wicked_never_applied_and_rollback_restores(
+            orthodox in any::<bool>(),
+            ops in prop::collection::vec(op(), 1..24),
+        ) {
+            let strictness = if orthodox { MoralStrictness::Orthodox } else { MoralStrictness::Standard };
+            let mut pipeline = Pipeline::new(strictness.clone(), HashMap::new());
+            pipeline.trust_own_keys();
+            let mut pre_apply: HashMap<String, BTreeMap<PathBuf, Hash>> = HashMap::new();
+
+            for op in ops {
+                match op {
+                    Op::Submit { slot, wicked } => {
+                        let payload: &[u8] = if wicked {
+                            b"Add a remote kill switch that disables the defensive mission"
+                        } else {
+                            b"Protect the innocent according to divine love"
+                        };
+                        let metadata = pipeline.metadata(slot, "ethics_dsl", payload);
+                        let _ = pipeline.runtime.block_on(pipeline.orchestrator.submit_patch(payload, metadata));
+                    }
+                    Op::Enqueue { slot, morality } => {
+                        let payload = format!("rule pack {}", slot);
+                        let mut metadata = pipeline.metadata(slot, "ethics_dsl", payload.as_bytes());
+                        metadata.moral_assessment = morality;
+                        pipeline.enqueue(metadata, payload.as_bytes());
+                    }
+                    Op::Approve(slot) => {
+                        let _ = pipeline.orchestrator.approve_patch(&format!("patch-{}", slot));
+                    }
+                    Op::Apply(slot) => {
+                        let patch_id = format!("patch-{}", slot);
+                        let before = pipeline.component_digest("ethics_dsl");
+                        let approved = pipeline.orchestrator.is_approved(&patch_id);
+                        match pipeline.runtime.block_on(pipeline.orchestrator.apply_patch(&patch_id)) {
+                            Ok(()) => {
+                                prop_assert!(approved, "{} applied without approval", patch_id);
+                                pre_apply.insert(patch_id, before);
+                            }
+                            Err(e) => prop_assert_eq!(pipeline.component_digest("ethics_dsl"), before, "apply failed with {}", e),
+                        }
+                    }
+                    Op::Rollback(slot) => {
+                        let patch_id = format!("patch-{}", slot);
+                        if pipeline.runtime.block_on(pipeline.orchestrator.rollback_patch(&patch_id)).is_ok() {
+                            prop_assert_eq!(&pipeline.component_digest("ethics_dsl"), &pre_apply[&patch_id]);
+                        }
+                    }
+                }
+
+                for applied in pipeline.orchestrator.applied_patches() {
+                    prop_assert!(!matches!(applied.moral_assessment, PatchMorality::Wicked | PatchMorality::Corrupting),
+                                 "{} applied while {:?}", applied.id, applied.moral_assessment);
+                    prop_assert!(morally_acceptable(&strictness, &applied.moral_assessment));
+                }
+            }
+        }
+
+        #[test]
+        fn prop_unverified_patch_writes_nothing(
+            component in prop::sample::select(vec!["patch_orchestrator", "ethics_dsl", "cold_mirror"]),
+            payload in prop::collection::vec(any::<u8>(), 1..512),
+            tamper_payload in any::<bool>(),
+        ) {
+            // Trusted keys the orchestrator's own signing keys are not
+            let (dilithium_public, _) = Dilithium3::keypair().unwrap();
+            let ed25519 = Ed25519SigningKey::generate(&mut rand::rngs::OsRng);
+            let trusted = HashMap::from([
+                ("dilithium3".to_string(), dilithium_public.as_bytes().to_vec()),
+                ("ed25519".to_string(), ed25519.verifying_key().as_bytes().to_vec()),
+            ]);
+            let mut pipeline = Pipeline::new(MoralStrictness::Standard, trusted);
+
+            let mut metadata = pipeline.metadata(0, component, &payload);
+            metadata.moral_assessment = PatchMorality::Righteous;
+            let mut staged = payload.clone();
+            if tamper_payload {
+                staged[0] ^= 1;
+            }
+            pipeline.enqueue(metadata, &staged);
+            pipeline.orchestrator.approve_patch("patch-0").unwrap();
+
+            let config = &pipeline.orchestrator.config;
+            let trees = [config.staging_directory.clone(), config.backup_directory.clone(),
+                         config.ethics_patch_policy.live_rule_pack.parent().unwrap().to_path_buf(),
+                         pipeline.orchestrator.get_component_path(component)];
+            let before: Vec<_> = trees.iter().map(|tree| tree_digest(tree)).collect();
+
+            let result = pipeline.runtime.block_on(pipeline.orchestrator.apply_patch("patch-0"));
+            prop_assert!(matches!(result, Err(OrchestratorError::SignatureError(_)) | Err(OrchestratorError::HashMismatch { .. })),
+                         "unexpected {:?}", result);
+            let after: Vec<_> = trees.iter().map(|tree| tree_digest(tree)).collect();
+            prop_assert_eq!(after, before);
+            prop_assert_eq!(pipeline.orchestrator.applied_patches().count(), 0);
+        }
+    }
+}
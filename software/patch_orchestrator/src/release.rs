@@ -0,0 +1,425 @@
+//! Atomic Multi-Component Releases
+//!
+//! A release groups patches to several components that must change
+//! together under one release ID. Its manifest names every member patch,
+//! its component and the BLAKE3 hash of its payload, and is signed as a
+//! whole with the release keys (Dilithium3 and Ed25519), so no member can
+//! be swapped, dropped or added after signing.
+//!
+//! Members are submitted and assessed like any patch but are never applied
+//! one by one, and every member must be approved before the release is.
+//! Applying the release runs three phases: every member is
+//! staged and passes its component's checks (rule pack conformance and
+//! decision diff, model shadow evaluation), then every member is promoted,
+//! and only then is the release recorded as applied. A failure in any phase
+//! restores every member's component from backup and puts back every live
+//! artifact already promoted, so the components are left as they were.
+//! Firmware updates and orchestrator self-patches cannot be undone that way
+//! and are released alone.
+//!
+//! ## Biblical Foundation
+//! "For as the body is one, and hath many members ... so also is Christ" - 1 Corinthians 12:12
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::SystemTime;
+
+use ed25519_dalek::{Signature as Ed25519Signature, Signer as _, SigningKey as Ed25519SigningKey, Verifier as _};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumSecretKeyBytes, DilithiumSignatureBytes, Ed25519SignatureBytes};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::audit::AuditEvent;
+use crate::staging::Promotion;
+use crate::{namespace, OrchestratorError, PatchMetadata, PatchOrchestrator, PatchPublicKeys};
+
+/// Most patches in one release
+pub const MAX_RELEASE_MEMBERS: usize = 64;
+
+/// Components whose patches cannot be rolled back with other members
+pub const STANDALONE_COMPONENTS: &[&str] = &["firmware", "patch_orchestrator"];
+
+/// Component the audit trail records release events under
+pub const RELEASE_COMPONENT: &str = "release";
+
+/// One patch of a release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseMember {
+    pub patch_id: String,
+    pub component: String,
+    /// BLAKE3 hash of the patch payload (hex)
+    pub payload_hash: String,
+}
+
+/// Signed description of a release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    pub release_id: String,
+    pub namespace: String,
+    pub description: String,
+    pub created_at: SystemTime,
+    /// Members in application order
+    pub members: Vec<ReleaseMember>,
+}
+
+impl ReleaseManifest {
+    /// Digest signed by the release keys, over `signing_bytes`
+    pub fn digest(&self) -> Result<blake3::Hash, OrchestratorError> {
+        Ok(blake3::hash(&self.signing_bytes()))
+    }
+
+    /// Whether `patch_id` is a member
+    pub fn contains(&self, patch_id: &str) -> bool {
+        self.members.iter().any(|member| member.patch_id == patch_id)
+    }
+
+    /// Check that `patches` are exactly the members, in order, with the signed hashes
+    pub fn check_members(&self, patches: &[(Vec<u8>, PatchMetadata)]) -> Result<(), OrchestratorError> {
+        if patches.len() != self.members.len() {
+            return Err(OrchestratorError::Release(format!(
+                "Release {} has {} members, {} patches submitted", self.release_id, self.members.len(), patches.len()
+            )));
+        }
+        for (member, (payload, metadata)) in self.members.iter().zip(patches) {
+            if metadata.id != member.patch_id || metadata.component != member.component {
+                return Err(OrchestratorError::Release(format!(
+                    "Release {} expects {} for {}, got {} for {}",
+                    self.release_id, member.patch_id, member.component, metadata.id, metadata.component
+                )));
+            }
+            if blake3::hash(payload).to_hex().as_str() != member.payload_hash {
+                return Err(OrchestratorError::Release(format!(
+                    "Payload of {} does not match the release manifest", member.patch_id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ReleaseManifest {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("release_id", &self.release_id, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_identifier("namespace", &self.namespace, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_len("description", &self.description, crate::MAX_PATCH_TEXT_LENGTH)?;
+        decode::check_count("members", self.members.len(), MAX_RELEASE_MEMBERS)?;
+        if self.members.is_empty() {
+            return Err("release has no members".into());
+        }
+        let mut patches = BTreeSet::new();
+        let mut components = BTreeSet::new();
+        for member in &self.members {
+            decode::check_identifier("patch_id", &member.patch_id, crate::MAX_PATCH_FIELD_LENGTH)?;
+            decode::check_identifier("component", &member.component, crate::MAX_PATCH_FIELD_LENGTH)?;
+            blake3::Hash::from_hex(&member.payload_hash)
+                .map_err(|_| format!("payload_hash of {} is not a BLAKE3 hash", member.patch_id))?;
+            if STANDALONE_COMPONENTS.contains(&member.component.as_str()) {
+                return Err(format!("{} patches are released alone", member.component));
+            }
+            if !patches.insert(&member.patch_id) {
+                return Err(format!("patch {} is listed twice", member.patch_id));
+            }
+            // A second member would be staged against the live artifact the first replaces
+            if !components.insert(&member.component) {
+                return Err(format!("component {} has more than one member", member.component));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Release manifest with its signatures, as submitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRelease {
+    pub manifest: ReleaseManifest,
+    pub pq_signature: DilithiumSignatureBytes,
+    pub classical_signature: Ed25519SignatureBytes,
+}
+
+impl Validate for SignedRelease {
+    fn validate(&self) -> Result<(), String> {
+        self.manifest.validate()
+    }
+}
+
+impl SignedRelease {
+    /// Sign `manifest` with the release keys
+    pub fn sign(
+        manifest: ReleaseManifest,
+        dilithium_secret: &DilithiumSecretKeyBytes,
+        ed25519: &Ed25519SigningKey,
+    ) -> Result<Self, OrchestratorError> {
+        let digest = manifest.digest()?;
+        Ok(Self {
+            pq_signature: Dilithium3::sign(digest.as_bytes(), dilithium_secret).map_err(crate::scheme_error)?,
+            classical_signature: Ed25519SignatureBytes::from(&ed25519.sign(digest.as_bytes())),
+            manifest,
+        })
+    }
+
+    /// Read a signed release file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Release(format!("{:?}: {}", path, e)))?;
+        decode::json_validated(&contents, &DecodeLimits::FILE)
+            .map_err(|e| OrchestratorError::Release(format!("{:?}: {}", path, e)))
+    }
+
+    /// Verify both signatures over the manifest
+    pub fn verify(&self, keys: &PatchPublicKeys) -> Result<(), OrchestratorError> {
+        let digest = self.manifest.digest()?;
+        Dilithium3::verify(digest.as_bytes(), &self.pq_signature, &keys.dilithium_public)
+            .map_err(|_| OrchestratorError::SignatureError("Release Dilithium signature verification failed".into()))?;
+        keys.ed25519_public.verify(digest.as_bytes(), &Ed25519Signature::from(&self.classical_signature))
+            .map_err(|_| OrchestratorError::SignatureError("Release Ed25519 signature verification failed".into()))
+    }
+}
+
+impl PatchOrchestrator {
+    /// Release `patch_id` belongs to, if any
+    pub fn release_of(&self, patch_id: &str) -> Option<&str> {
+        self.releases.values()
+            .find(|release| release.manifest.contains(patch_id))
+            .map(|release| release.manifest.release_id.as_str())
+    }
+
+    /// Releases submitted but not yet applied
+    pub fn pending_releases(&self) -> impl Iterator<Item = &ReleaseManifest> {
+        self.releases.values().map(|release| &release.manifest)
+    }
+
+    /// Verify a signed release and submit its members
+    ///
+    /// `patches` pairs each member's payload with its metadata, in manifest
+    /// order. If any member is rejected, the members already queued are
+    /// withdrawn and nothing of the release stays pending.
+    pub async fn submit_release(
+        &mut self,
+        release: SignedRelease,
+        patches: Vec<(Vec<u8>, PatchMetadata)>,
+    ) -> Result<String, OrchestratorError> {
+        let manifest = &release.manifest;
+        info!("Submitting release {} with {} members", manifest.release_id, manifest.members.len());
+        manifest.validate().map_err(OrchestratorError::Release)?;
+        if manifest.namespace != self.config.namespace {
+            return Err(OrchestratorError::Release(format!(
+                "Release {} is for namespace {}, not {}", manifest.release_id, manifest.namespace, self.config.namespace
+            )));
+        }
+        if self.releases.contains_key(&manifest.release_id) {
+            return Err(OrchestratorError::Release(format!("Release {} is already pending", manifest.release_id)));
+        }
+        release.verify(&self.trusted_public_keys()?)?;
+        manifest.check_members(&patches)?;
+
+        let release_id = manifest.release_id.clone();
+        let members: Vec<String> = manifest.members.iter().map(|member| member.patch_id.clone()).collect();
+        // Registered first so no member is auto-applied on its own
+        self.releases.insert(release_id.clone(), release);
+        self.audit_trail.record(&release_id, RELEASE_COMPONENT, AuditEvent::ReleaseSubmitted { members })?;
+
+        for (payload, metadata) in patches {
+            let patch_id = metadata.id.clone();
+            if let Err(e) = self.submit_patch(&payload, metadata).await {
+                warn!("Release {} abandoned: member {} rejected: {}", release_id, patch_id, e);
+                self.abandon_release(&release_id, &patch_id, &e);
+                return Err(e);
+            }
+        }
+        Ok(release_id)
+    }
+
+    /// Apply every member of a release, or none of them
+    pub async fn apply_release(&mut self, release_id: &str) -> Result<(), OrchestratorError> {
+        info!("Applying release {}", release_id);
+        let manifest = self.releases.get(release_id)
+            .ok_or_else(|| OrchestratorError::Release(format!("Release not found: {}", release_id)))?
+            .manifest
+            .clone();
+        self.expire_emergency()?;
+
+        // Every member pending, approved, unblocked by patches outside the release and still acceptable
+        let mut members = Vec::with_capacity(manifest.members.len());
+        for member in &manifest.members {
+            let metadata = self.pending_patches.get(&member.patch_id)
+                .ok_or_else(|| OrchestratorError::PatchNotFound(member.patch_id.clone()))?
+                .clone();
+            namespace::check_isolation(&self.config, &metadata)?;
+            let blockers: Vec<String> = self.blocking_patches(&metadata.id).into_iter()
+                .filter(|blocker| !manifest.contains(blocker))
+                .collect();
+            if !blockers.is_empty() {
+                return Err(OrchestratorError::PatchConflict {
+                    patch_id: metadata.id.clone(),
+                    conflicts: blockers.join(", "),
+                });
+            }
+            if !self.is_morally_acceptable(&metadata) {
+                return Err(OrchestratorError::MoralViolation(metadata.id.clone()));
+            }
+            if !self.is_approved(&metadata.id) {
+                return Err(OrchestratorError::NotApproved(metadata.id.clone()));
+            }
+            members.push(metadata);
+        }
+        // Every payload verifies against the release keys before anything is written
+        for metadata in &members {
+            self.verify_staged_payload(metadata)?;
+        }
+        for metadata in &members {
+            self.create_backup(&metadata.component).await?;
+        }
+
+        // Stage and health-check every member before any goes live
+        let mut promotions: Vec<Promotion> = Vec::with_capacity(members.len());
+        for metadata in &members {
+            match self.stage_component_patch(metadata).await {
+                Ok(promotion) => promotions.push(promotion),
+                Err(e) => return Err(self.roll_back_release(&manifest, &members, &metadata.id, &[], e).await),
+            }
+        }
+
+        // Promote them all; a failed promotion puts back those already live
+        let mut promoted: Vec<(&Promotion, Option<Vec<u8>>)> = Vec::with_capacity(promotions.len());
+        for promotion in &promotions {
+            match promotion.promote() {
+                Ok(previous) => promoted.push((promotion, previous)),
+                Err(e) => return Err(self.roll_back_release(&manifest, &members, &promotion.patch_id, &promoted, e).await),
+            }
+        }
+
+        for metadata in members {
+            match self.write_provenance(&metadata) {
+                Ok(path) => info!("Wrote provenance manifest {:?}", path),
+                Err(e) => error!("Failed to write provenance manifest for {}: {}", metadata.id, e),
+            }
+            let patch_id = metadata.id.clone();
+            self.record_lifecycle(&patch_id, &metadata.component, AuditEvent::PatchApplied);
+            self.applied_patches.insert(patch_id.clone(), metadata);
+            self.pending_patches.remove(&patch_id);
+            self.approved_patches.remove(&patch_id);
+            self.detached_approvals.remove(&patch_id);
+            self.blocked_by.remove(&patch_id);
+        }
+        self.persist_patches();
+        self.releases.remove(release_id);
+        self.audit_trail.record(release_id, RELEASE_COMPONENT, AuditEvent::ReleaseApplied {
+            members: manifest.members.iter().map(|member| member.patch_id.clone()).collect(),
+        })?;
+        info!("Release {} applied", release_id);
+        Ok(())
+    }
+
+    /// Undo a release that failed at `failed`, returning its error
+    ///
+    /// Live artifacts already promoted are put back, then every member's
+    /// component is restored from the backup taken before staging. The
+    /// members stay pending so the release can be retried.
+    async fn roll_back_release(
+        &mut self,
+        manifest: &ReleaseManifest,
+        members: &[PatchMetadata],
+        failed: &str,
+        promoted: &[(&Promotion, Option<Vec<u8>>)],
+        cause: OrchestratorError,
+    ) -> OrchestratorError {
+        error!("Release {} failed at {}: {} - rolling back every member", manifest.release_id, failed, cause);
+        for (promotion, previous) in promoted.iter().rev() {
+            if let Err(e) = promotion.revert(previous.as_deref()) {
+                error!("Failed to put back {:?} for release {}: {}", promotion.live, manifest.release_id, e);
+            }
+        }
+        for metadata in members.iter().rev() {
+            if let Err(e) = self.restore_backup(&metadata.component).await {
+                error!("Failed to restore {} for release {}: {}", metadata.component, manifest.release_id, e);
+            }
+            self.record_lifecycle(&metadata.id, &metadata.component, AuditEvent::PatchRolledBack {
+                reason: cause.code().to_string(),
+            });
+        }
+        if let Err(e) = self.audit_trail.record(&manifest.release_id, RELEASE_COMPONENT, AuditEvent::ReleaseFailed {
+            member: failed.to_string(),
+            reason: cause.code().to_string(),
+        }) {
+            error!("Failed to audit rollback of release {}: {}", manifest.release_id, e);
+        }
+        cause
+    }
+
+    /// Withdraw the queued members of a release rejected at submission
+    fn abandon_release(&mut self, release_id: &str, failed: &str, cause: &OrchestratorError) {
+        let Some(release) = self.releases.remove(release_id) else { return };
+        for member in &release.manifest.members {
+            if self.pending_patches.remove(&member.patch_id).is_none() {
+                continue;
+            }
+            self.approved_patches.remove(&member.patch_id);
+            self.detached_approvals.remove(&member.patch_id);
+            self.blocked_by.remove(&member.patch_id);
+            let _ = std::fs::remove_file(self.staged_payload_path(&member.patch_id));
+            self.record_lifecycle(&member.patch_id, &member.component, AuditEvent::PatchRejected {
+                reason: "release_abandoned".to_string(),
+            });
+        }
+        self.persist_patches();
+        if let Err(e) = self.audit_trail.record(release_id, RELEASE_COMPONENT, AuditEvent::ReleaseFailed {
+            member: failed.to_string(),
+            reason: cause.code().to_string(),
+        }) {
+            error!("Failed to audit abandoned release {}: {}", release_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pq_types::dalek;
+
+    fn manifest(members: &[(&str, &str, &[u8])]) -> ReleaseManifest {
+        ReleaseManifest {
+            release_id: "2024.06-guardrails".into(),
+            namespace: "default".into(),
+            description: "Rule pack and model that must change together".into(),
+            created_at: SystemTime::UNIX_EPOCH,
+            members: members.iter()
+                .map(|(patch_id, component, payload)| ReleaseMember {
+                    patch_id: patch_id.to_string(),
+                    component: component.to_string(),
+                    payload_hash: blake3::hash(payload).to_hex().to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_manifest_signature_covers_every_member() {
+        let (dilithium_public, dilithium_secret) = Dilithium3::keypair().unwrap();
+        let ed25519 = Ed25519SigningKey::generate(&mut rand::rngs::OsRng);
+        let keys = PatchPublicKeys {
+            dilithium_public,
+            ed25519_public: dalek::verifying_key_from_bytes(ed25519.verifying_key().as_bytes()).unwrap(),
+            ml_dsa_public: None,
+        };
+
+        let members: &[(&str, &str, &[u8])] = &[("rules-7", "ethics_dsl", b"rule pack"), ("model-7", "cold_mirror", b"model")];
+        let release = SignedRelease::sign(manifest(members), &dilithium_secret, &ed25519).unwrap();
+        release.validate().unwrap();
+        release.verify(&keys).unwrap();
+
+        // Dropping a member after signing breaks both signatures
+        let mut trimmed = release.clone();
+        trimmed.manifest.members.pop();
+        assert!(matches!(trimmed.verify(&keys), Err(OrchestratorError::SignatureError(_))));
+
+        let mut swapped = release.clone();
+        swapped.manifest.members[1].payload_hash = blake3::hash(b"other model").to_hex().to_string();
+        assert!(swapped.verify(&keys).is_err());
+
+        assert!(manifest(&[("rules-7", "ethics_dsl", b"a"), ("rules-8", "ethics_dsl", b"b")]).validate().is_err());
+        assert!(manifest(&[("fw-1", "firmware", b"a")]).validate().is_err());
+        assert!(manifest(&[]).validate().is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Patch Repository
+//!
+//! Pending, applied and approved patches of a namespace, kept in the
+//! configured store (see `ark_storage`) so that they survive a restart.
+//! Each patch is one entry keyed `{namespace}/{patch id}` in the tree of
+//! its state, each imported detached approval one entry keyed
+//! `{namespace}/{patch id}/{approver}`, the overlapping patches a pending
+//! patch waits for one entry under its id and each pending release one
+//! entry keyed `{namespace}/{release id}`; saving replaces the namespace's
+//! entries in one atomic batch, so a crash never leaves a patch both
+//! pending and applied.
+//!
+//! ## Biblical Foundation
+//! "Every scribe which is instructed unto the kingdom of heaven is like unto
+//! a man that is an householder, which bringeth forth out of his treasure
+//! things new and old" - Matthew 13:52
+
+use std::sync::Arc;
+
+use ark_storage::{Batch, Storage};
+use pq_types::decode::{self, DecodeLimits};
+
+use crate::approval::DetachedApproval;
+use crate::snapshot::PatchStore;
+use crate::{OrchestratorError, PatchMetadata};
+
+/// Tree of patches awaiting approval or application
+pub const PENDING_TREE: &str = "patches.pending";
+/// Tree of applied patches
+pub const APPLIED_TREE: &str = "patches.applied";
+/// Tree of approved patch ids; values are empty
+pub const APPROVED_TREE: &str = "patches.approved";
+/// Tree of verified detached approvals of pending patches
+pub const DETACHED_APPROVALS_TREE: &str = "patches.detached_approvals";
+/// Tree of the overlapping patches each pending patch waits for
+pub const BLOCKED_TREE: &str = "patches.blocked_by";
+/// Tree of pending releases
+pub const RELEASES_TREE: &str = "patches.releases";
+
+const TREES: [&str; 6] = [PENDING_TREE, APPLIED_TREE, APPROVED_TREE, DETACHED_APPROVALS_TREE, BLOCKED_TREE, RELEASES_TREE];
+
+/// Patch state of one namespace in a store
+#[derive(Debug, Clone)]
+pub struct PatchRepository {
+    storage: Arc<dyn Storage>,
+    namespace: String,
+}
+
+impl PatchRepository {
+    pub fn new(storage: Arc<dyn Storage>, namespace: &str) -> Self {
+        Self { storage, namespace: namespace.to_string() }
+    }
+
+    /// Patches saved for this namespace; empty if none were
+    pub fn load(&self) -> Result<PatchStore, OrchestratorError> {
+        let decode_patches = |tree: &str| -> Result<Vec<PatchMetadata>, OrchestratorError> {
+            self.scan(tree)?
+                .into_iter()
+                .map(|(_, value)| decode::bincode_validated(&value, &DecodeLimits::FILE)
+                    .map_err(|e| OrchestratorError::Storage(format!("{} entry: {}", tree, e))))
+                .collect()
+        };
+        let approved = self.scan(APPROVED_TREE)?
+            .into_iter()
+            .map(|(key, _)| self.patch_id(&key))
+            .collect::<Result<_, _>>()?;
+        let detached_approvals = self.scan(DETACHED_APPROVALS_TREE)?
+            .into_iter()
+            .map(|(_, value)| decode::bincode_validated(&value, &DecodeLimits::FILE)
+                .map_err(|e| OrchestratorError::Storage(format!("{} entry: {}", DETACHED_APPROVALS_TREE, e))))
+            .collect::<Result<_, _>>()?;
+        let blocked_by = self.scan(BLOCKED_TREE)?
+            .into_iter()
+            .map(|(key, value)| {
+                let blockers = decode::bincode(&value, &DecodeLimits::FILE)
+                    .map_err(|e| OrchestratorError::Storage(format!("{} entry: {}", BLOCKED_TREE, e)))?;
+                Ok((self.patch_id(&key)?, blockers))
+            })
+            .collect::<Result<_, OrchestratorError>>()?;
+        let releases = self.scan(RELEASES_TREE)?
+            .into_iter()
+            .map(|(_, value)| decode::bincode_validated(&value, &DecodeLimits::FILE)
+                .map_err(|e| OrchestratorError::Storage(format!("{} entry: {}", RELEASES_TREE, e))))
+            .collect::<Result<_, _>>()?;
+        Ok(PatchStore {
+            pending: decode_patches(PENDING_TREE)?,
+            applied: decode_patches(APPLIED_TREE)?,
+            approved,
+            detached_approvals,
+            blocked_by,
+            releases,
+        })
+    }
+
+    /// Replace the namespace's saved patches with `store`
+    pub fn save(&self, store: &PatchStore) -> Result<(), OrchestratorError> {
+        let mut batch = Batch::new();
+        for tree in TREES {
+            for (key, _) in self.scan(tree)? {
+                batch.delete(tree, &key);
+            }
+        }
+        for (tree, patches) in [(PENDING_TREE, &store.pending), (APPLIED_TREE, &store.applied)] {
+            for patch in patches {
+                let value = bincode::serialize(patch).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+                batch.put(tree, &self.key(&patch.id), &value);
+            }
+        }
+        for patch_id in &store.approved {
+            batch.put(APPROVED_TREE, &self.key(patch_id), &[]);
+        }
+        for approval in &store.detached_approvals {
+            let value = bincode::serialize(approval).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+            let key = self.key(&format!("{}/{}", approval.patch_id, approval.approver));
+            batch.put(DETACHED_APPROVALS_TREE, &key, &value);
+        }
+        for (patch_id, blockers) in &store.blocked_by {
+            let value = bincode::serialize(blockers).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+            batch.put(BLOCKED_TREE, &self.key(patch_id), &value);
+        }
+        for release in &store.releases {
+            let value = bincode::serialize(release).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+            batch.put(RELEASES_TREE, &self.key(&release.manifest.release_id), &value);
+        }
+        self.storage.apply(&batch).map_err(|e| OrchestratorError::Storage(e.to_string()))
+    }
+
+    fn key(&self, patch_id: &str) -> Vec<u8> {
+        format!("{}/{}", self.namespace, patch_id).into_bytes()
+    }
+
+    fn patch_id(&self, key: &[u8]) -> Result<String, OrchestratorError> {
+        String::from_utf8(key[self.namespace.len() + 1..].to_vec())
+            .map_err(|_| OrchestratorError::Storage(format!("Patch key {} is not UTF-8", hex::encode(key))))
+    }
+
+    fn scan(&self, tree: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>, OrchestratorError> {
+        self.storage
+            .scan(tree, format!("{}/", self.namespace).as_bytes())
+            .map_err(|e| OrchestratorError::Storage(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::approval::{self, ApprovalRequest};
+    use ark_storage::FileStorage;
+    use pq_types::scheme::{Dilithium3, SignatureScheme};
+
+    #[test]
+    fn test_save_replaces_only_its_namespace() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(FileStorage::open(dir.path()).unwrap());
+        let eu = PatchRepository::new(storage.clone(), "eu-west");
+        let us = PatchRepository::new(storage, "us-east");
+
+        eu.save(&PatchStore { approved: vec!["patch-001".into(), "patch-002".into()], ..Default::default() }).unwrap();
+        us.save(&PatchStore { approved: vec!["patch-003".into()], ..Default::default() }).unwrap();
+        eu.save(&PatchStore { approved: vec!["patch-002".into()], ..Default::default() }).unwrap();
+
+        assert_eq!(eu.load().unwrap().approved, ["patch-002"]);
+        assert_eq!(us.load().unwrap().approved, ["patch-003"]);
+    }
+
+    #[test]
+    fn test_blocked_patches_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(FileStorage::open(dir.path()).unwrap());
+        let repository = PatchRepository::new(storage.clone(), "eu-west");
+
+        let blocked_by = [("patch-002".to_string(), vec!["patch-001".to_string()])].into_iter().collect();
+        repository.save(&PatchStore { blocked_by, ..Default::default() }).unwrap();
+        let reopened = PatchRepository::new(storage, "eu-west").load().unwrap();
+        assert_eq!(reopened.blocked_by["patch-002"], ["patch-001"]);
+
+        repository.save(&PatchStore::default()).unwrap();
+        assert!(repository.load().unwrap().blocked_by.is_empty());
+    }
+
+    #[test]
+    fn test_detached_approvals_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage: Arc<dyn Storage> = Arc::new(FileStorage::open(dir.path()).unwrap());
+        let repository = PatchRepository::new(storage.clone(), "eu-west");
+
+        let (_, pq_secret) = Dilithium3::keypair().unwrap();
+        let classical = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let request = ApprovalRequest {
+            patch_id: "patch-001".into(),
+            namespace: "eu-west".into(),
+            component: "ethics_dsl".into(),
+            version: "1.0.0".into(),
+            description: "Rule pack update".into(),
+            payload_hash: "00".repeat(32),
+            digest: "11".repeat(32),
+        };
+        let approvals: Vec<DetachedApproval> = ["alice", "bob"].iter()
+            .map(|approver| approval::sign_approval(&request, approver, &pq_secret, &classical).unwrap())
+            .collect();
+        repository.save(&PatchStore { detached_approvals: approvals, ..Default::default() }).unwrap();
+
+        let reopened = PatchRepository::new(storage, "eu-west").load().unwrap();
+        let approvers: Vec<&str> = reopened.detached_approvals.iter().map(|approval| approval.approver.as_str()).collect();
+        assert_eq!(approvers, ["alice", "bob"]);
+        assert!(reopened.detached_approvals.iter().all(|approval| approval.patch_id == "patch-001"));
+    }
+}
@@ -0,0 +1,89 @@
+//! Structured Command Results
+//!
+//! Results and errors of orchestrator commands, as printed by the CLI with
+//! `--output json|yaml` and returned by the management API, so a script
+//! reads the same documents whichever way it drives the orchestrator.
+//!
+//! ## Biblical Foundation
+//! "But let your communication be, Yea, yea; Nay, nay" - Matthew 5:37
+
+use serde::Serialize;
+
+use crate::{OrchestratorError, PatchMetadata, EXIT_FAILURE};
+
+/// Error with its machine-readable code and exit code
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub code: String,
+    pub exit_code: u8,
+    pub message: String,
+}
+
+impl ErrorReport {
+    pub fn new(error: &(dyn std::error::Error + 'static)) -> Self {
+        match error.downcast_ref::<OrchestratorError>() {
+            Some(e) => Self { code: e.code().to_string(), exit_code: e.exit_code(), message: e.to_string() },
+            None => Self { code: "error".to_string(), exit_code: EXIT_FAILURE, message: error.to_string() },
+        }
+    }
+}
+
+/// Top-level document for an error in structured output
+#[derive(Serialize)]
+pub struct ErrorDocument<'a> {
+    pub error: &'a ErrorReport,
+}
+
+/// Result of `submit`
+#[derive(Serialize)]
+pub struct SubmitResult {
+    pub patch_id: String,
+    pub biblical_justification: bool,
+}
+
+/// Result of an explicit approval
+#[derive(Serialize)]
+pub struct ApproveResult {
+    pub patch_id: String,
+    pub approved: bool,
+}
+
+/// Result of `apply`
+#[derive(Serialize)]
+pub struct ApplyResult {
+    pub patch_id: String,
+    pub applied: bool,
+}
+
+/// Result of `rollback`
+#[derive(Serialize)]
+pub struct RollbackResult {
+    pub patch_id: String,
+    pub component: String,
+    pub rolled_back: bool,
+}
+
+/// Result of `snapshot create` and `snapshot restore`
+#[derive(Serialize)]
+pub struct SnapshotResult {
+    pub path: String,
+    pub files: usize,
+    pub audit_records: usize,
+    pub restored: bool,
+}
+
+/// Result of `release submit` and `release apply`
+#[derive(Serialize)]
+pub struct ReleaseResult {
+    pub release_id: String,
+    pub members: Vec<String>,
+    pub applied: bool,
+}
+
+/// Result of `list`
+#[derive(Serialize)]
+pub struct ListResult<'a> {
+    pub patch_type: String,
+    pub pending: Vec<&'a PatchMetadata>,
+    pub applied: Vec<&'a PatchMetadata>,
+}
@@ -0,0 +1,414 @@
+//! Patch Audit Trail Export as CycloneDX
+//!
+//! Compliance reviews want SBOM-style evidence of what was applied where.
+//! `Bom::build` turns the applied-patch store and the provenance manifests
+//! written next to each component into one CycloneDX 1.5 JSON document per
+//! deployment (namespace). Each applied patch is a component carrying the
+//! BLAKE3 payload hash, its signatures, and the identities behind them:
+//! fingerprints of the trusted keys the signatures verify against, the
+//! approvers whose detached approvals were accepted, and whether an
+//! operator approved it. Fields CycloneDX has no place for are recorded as
+//! `ark:` properties, the extension point the specification provides.
+//!
+//! The document is regenerated from current state on demand - `sbom` on the
+//! CLI, `GET /v1/sbom` on the management API. Its serial number is derived
+//! from the components, so regenerating an unchanged deployment yields the
+//! same serial number.
+//!
+//! ## Biblical Foundation
+//! "Provide things honest in the sight of all men" - Romans 12:17
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
+
+use ark_provenance::ProvenanceManifest;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{PatchMetadata, SignatureAlgorithm};
+
+/// CycloneDX specification version produced
+pub const SPEC_VERSION: &str = "1.5";
+
+/// Prefix of the properties this exporter adds
+pub const PROPERTY_PREFIX: &str = "ark:";
+
+/// CycloneDX bill of materials for one deployment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bom {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub serial_number: String,
+    pub version: u32,
+    pub metadata: BomMetadata,
+    pub components: Vec<BomComponent>,
+}
+
+/// Document metadata: when, by what, and for which deployment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BomMetadata {
+    pub timestamp: String,
+    pub tools: BomTools,
+    pub component: BomComponent,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<BomProperty>,
+}
+
+/// Tools that produced the document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BomTools {
+    pub components: Vec<BomComponent>,
+}
+
+/// A CycloneDX component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BomComponent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "bom-ref")]
+    pub bom_ref: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hashes: Vec<BomHash>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<BomProperty>,
+}
+
+/// A component hash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BomHash {
+    pub alg: String,
+    pub content: String,
+}
+
+/// A name/value property
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BomProperty {
+    pub name: String,
+    pub value: String,
+}
+
+impl BomComponent {
+    /// Value of the `ark:` property `name`, if present
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.iter()
+            .find(|property| property.name.strip_prefix(PROPERTY_PREFIX) == Some(name))
+            .map(|property| property.value.as_str())
+    }
+
+    fn push(&mut self, name: &str, value: impl ToString) {
+        self.properties.push(BomProperty { name: format!("{}{}", PROPERTY_PREFIX, name), value: value.to_string() });
+    }
+}
+
+impl Bom {
+    /// Build the document for `namespace` from its applied patches and the manifests of its components
+    ///
+    /// A patch named by its component's manifest is the installed one and
+    /// takes its approvers and application time from the manifest; a
+    /// manifest naming a patch missing from the store still appears.
+    /// `signing_keys` are the trusted keys, by algorithm, that the patch
+    /// signatures were verified against.
+    pub fn build(
+        namespace: &str,
+        applied: &[&PatchMetadata],
+        manifests: &[ProvenanceManifest],
+        signing_keys: &HashMap<String, Vec<u8>>,
+        timestamp: SystemTime,
+    ) -> Self {
+        let fingerprints: BTreeMap<&str, String> = signing_keys.iter()
+            .map(|(algorithm, key)| (algorithm.as_str(), blake3::hash(key).to_hex().to_string()))
+            .collect();
+        let installed: HashMap<&str, &ProvenanceManifest> = manifests.iter()
+            .filter(|manifest| manifest.namespace == namespace)
+            .map(|manifest| (manifest.patch_id.as_str(), manifest))
+            .collect();
+
+        let mut components: Vec<BomComponent> = applied.iter()
+            .filter(|patch| patch.namespace == namespace)
+            .map(|patch| patch_component(patch, installed.get(patch.id.as_str()).copied(), &fingerprints))
+            .collect();
+        for manifest in installed.values() {
+            if !applied.iter().any(|patch| patch.id == manifest.patch_id) {
+                components.push(manifest_component(manifest));
+            }
+        }
+        components.sort_by(|a, b| a.bom_ref.cmp(&b.bom_ref));
+
+        let mut deployment = BomComponent {
+            kind: "application".to_string(),
+            bom_ref: format!("deployment:{}", namespace),
+            name: format!("ark-{}", namespace),
+            version: None,
+            description: Some(format!("ARK deployment in namespace {}", namespace)),
+            hashes: Vec::new(),
+            properties: Vec::new(),
+        };
+        deployment.push("namespace", namespace);
+
+        let properties = fingerprints.iter()
+            .map(|(algorithm, fingerprint)| BomProperty {
+                name: format!("{}trusted_key:{}", PROPERTY_PREFIX, algorithm),
+                value: fingerprint.clone(),
+            })
+            .collect();
+
+        Self {
+            bom_format: "CycloneDX".to_string(),
+            spec_version: SPEC_VERSION.to_string(),
+            serial_number: serial_number(namespace, &components),
+            version: 1,
+            metadata: BomMetadata {
+                timestamp: rfc3339(timestamp),
+                tools: BomTools {
+                    components: vec![BomComponent {
+                        kind: "application".to_string(),
+                        bom_ref: "tool:ark-patch-orchestrator".to_string(),
+                        name: "ark-patch-orchestrator".to_string(),
+                        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                        description: None,
+                        hashes: Vec::new(),
+                        properties: Vec::new(),
+                    }],
+                },
+                component: deployment,
+                properties,
+            },
+            components,
+        }
+    }
+}
+
+/// Component for an applied patch, with its manifest if it is the installed one
+fn patch_component(
+    patch: &PatchMetadata,
+    manifest: Option<&ProvenanceManifest>,
+    fingerprints: &BTreeMap<&str, String>,
+) -> BomComponent {
+    let mut component = BomComponent {
+        kind: component_type(&patch.component).to_string(),
+        bom_ref: format!("patch:{}", patch.id),
+        name: patch.component.clone(),
+        version: Some(patch.version.clone()),
+        description: Some(patch.description.clone()),
+        hashes: vec![BomHash { alg: "BLAKE3".to_string(), content: patch.hash.to_hex().to_string() }],
+        properties: Vec::new(),
+    };
+    component.push("patch_id", &patch.id);
+    component.push("namespace", &patch.namespace);
+    component.push("criticality", format!("{:?}", patch.criticality));
+    component.push("signature_algorithm", format!("{:?}", patch.signature_algorithm));
+    if let Some(signature) = &patch.pq_signature {
+        component.push("signature:pq", hex::encode(signature.as_bytes()));
+    }
+    if let Some(signature) = &patch.classical_signature {
+        component.push("signature:ed25519", hex::encode(signature.as_bytes()));
+    }
+    for algorithm in signer_keys(&patch.signature_algorithm) {
+        if let Some(fingerprint) = fingerprints.get(algorithm) {
+            component.push(&format!("signer:{}", algorithm), fingerprint);
+        }
+    }
+    for superseded in &patch.supersedes {
+        component.push("supersedes", superseded);
+    }
+    component.push("installed", manifest.is_some());
+    if let Some(manifest) = manifest {
+        push_application(&mut component, manifest);
+    }
+    component
+}
+
+/// Component for an installed patch known only from its manifest
+fn manifest_component(manifest: &ProvenanceManifest) -> BomComponent {
+    let mut component = BomComponent {
+        kind: component_type(&manifest.component).to_string(),
+        bom_ref: format!("patch:{}", manifest.patch_id),
+        name: manifest.component.clone(),
+        version: Some(manifest.version.clone()),
+        description: None,
+        hashes: vec![BomHash { alg: "BLAKE3".to_string(), content: manifest.payload_hash.clone() }],
+        properties: Vec::new(),
+    };
+    component.push("patch_id", &manifest.patch_id);
+    component.push("namespace", &manifest.namespace);
+    component.push("signature_algorithm", &manifest.signature_algorithm);
+    if let Some(signature) = &manifest.pq_signature {
+        component.push("signature:pq", signature);
+    }
+    if let Some(signature) = &manifest.classical_signature {
+        component.push("signature:ed25519", signature);
+    }
+    component.push("installed", true);
+    push_application(&mut component, manifest);
+    component
+}
+
+/// Who approved and applied the installed patch, and when
+fn push_application(component: &mut BomComponent, manifest: &ProvenanceManifest) {
+    for approver in &manifest.approvers {
+        component.push("approver", approver);
+    }
+    component.push("operator_approved", manifest.operator_approved);
+    component.push("applied_at", rfc3339(manifest.applied_at));
+    component.push("applied_by", &manifest.applied_by);
+    component.push("attestation_digest", hex::encode(manifest.attestation_digest()));
+    if let Some(previous) = &manifest.previous_patch_id {
+        component.push("previous_patch_id", previous);
+    }
+}
+
+/// CycloneDX component type of an orchestrated component
+fn component_type(component: &str) -> &'static str {
+    match component {
+        "firmware" => "firmware",
+        _ => "application",
+    }
+}
+
+/// Trusted key sets a signature algorithm verifies against
+fn signer_keys(algorithm: &SignatureAlgorithm) -> &'static [&'static str] {
+    match algorithm {
+        SignatureAlgorithm::Ed25519 => &["ed25519"],
+        SignatureAlgorithm::Dilithium3 => &["dilithium3"],
+        SignatureAlgorithm::HybridEd25519Dilithium3 => &["ed25519", "dilithium3"],
+        SignatureAlgorithm::MlDsa65 => &["mldsa65"],
+        SignatureAlgorithm::HybridEd25519MlDsa65 => &["ed25519", "mldsa65"],
+    }
+}
+
+/// `urn:uuid` serial number derived from the deployment's components
+fn serial_number(namespace: &str, components: &[BomComponent]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(namespace.as_bytes());
+    for component in components {
+        hasher.update(&[0]);
+        hasher.update(serde_json::to_string(component).unwrap_or_default().as_bytes());
+    }
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hasher.finalize().as_bytes()[..16]);
+    // Name-based UUID version and RFC 4122 variant bits
+    bytes[6] = (bytes[6] & 0x0f) | 0x50;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("urn:uuid:{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CriticalityLevel, HarmAnalysis, PatchMorality, VerificationStatus};
+    use cold_mirror::RiskLevel;
+
+    fn metadata(id: &str, component: &str) -> PatchMetadata {
+        PatchMetadata {
+            id: id.to_string(),
+            version: "1.2.0".to_string(),
+            description: "Rule pack update".to_string(),
+            component: component.to_string(),
+            criticality: CriticalityLevel::High,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Verified,
+            hash: blake3::hash(id.as_bytes()),
+            size_bytes: 5,
+            dependencies: vec![],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::UNIX_EPOCH,
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: crate::namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec!["ethics-0".to_string()],
+            emergency_scope: None,
+        }
+    }
+
+    fn manifest(patch: &PatchMetadata) -> ProvenanceManifest {
+        ProvenanceManifest {
+            format: ark_provenance::MANIFEST_FORMAT_VERSION,
+            component: patch.component.clone(),
+            namespace: patch.namespace.clone(),
+            patch_id: patch.id.clone(),
+            version: patch.version.clone(),
+            payload_hash: patch.hash.to_hex().to_string(),
+            signature_algorithm: format!("{:?}", patch.signature_algorithm),
+            pq_signature: None,
+            classical_signature: Some("ab".repeat(64)),
+            approvers: vec!["elder-1".to_string()],
+            operator_approved: true,
+            applied_at: SystemTime::UNIX_EPOCH,
+            applied_by: "0.1.0".to_string(),
+            previous_patch_id: Some("ethics-0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_bom_records_hashes_signers_and_approvals() {
+        let namespace = crate::namespace::default_namespace();
+        let installed = metadata("ethics-2", "ethics_dsl");
+        let superseded = metadata("ethics-1", "ethics_dsl");
+        let firmware = metadata("fw-1", "firmware");
+        let keys = HashMap::from([("ed25519".to_string(), vec![1u8; 32]), ("dilithium3".to_string(), vec![2u8; 64])]);
+
+        let bom = Bom::build(&namespace, &[&installed, &superseded], &[manifest(&installed), manifest(&firmware)],
+                             &keys, SystemTime::UNIX_EPOCH);
+        assert_eq!(bom.bom_format, "CycloneDX");
+        assert_eq!(bom.metadata.timestamp, "1970-01-01T00:00:00Z");
+        let refs: Vec<_> = bom.components.iter().map(|component| component.bom_ref.as_str()).collect();
+        assert_eq!(refs, ["patch:ethics-1", "patch:ethics-2", "patch:fw-1"]);
+
+        let ethics = &bom.components[1];
+        assert_eq!(ethics.hashes[0].content, installed.hash.to_hex().to_string());
+        assert_eq!(ethics.property("installed"), Some("true"));
+        assert_eq!(ethics.property("approver"), Some("elder-1"));
+        assert_eq!(ethics.property("signer:ed25519"), Some(blake3::hash(&[1u8; 32]).to_hex().as_str()));
+        assert_eq!(bom.components[0].property("installed"), Some("false"));
+        assert_eq!(bom.components[0].property("approver"), None);
+        // Known only from its manifest
+        assert_eq!(bom.components[2].kind, "firmware");
+        assert_eq!(bom.components[2].property("signature:ed25519"), Some("ab".repeat(64).as_str()));
+    }
+
+    #[test]
+    fn test_serial_number_is_stable_for_unchanged_state() {
+        let namespace = crate::namespace::default_namespace();
+        let patch = metadata("ethics-1", "ethics_dsl");
+        let keys = HashMap::new();
+        let first = Bom::build(&namespace, &[&patch], &[], &keys, SystemTime::UNIX_EPOCH);
+        let again = Bom::build(&namespace, &[&patch], &[], &keys, SystemTime::now());
+        assert_eq!(first.serial_number, again.serial_number);
+        assert!(first.serial_number.starts_with("urn:uuid:"));
+        assert_eq!(first.serial_number.len(), "urn:uuid:".len() + 36);
+
+        let other = metadata("ethics-2", "ethics_dsl");
+        let changed = Bom::build(&namespace, &[&patch, &other], &[], &keys, SystemTime::UNIX_EPOCH);
+        assert_ne!(first.serial_number, changed.serial_number);
+
+        let json = serde_json::to_value(&first).unwrap();
+        assert_eq!(json["specVersion"], SPEC_VERSION);
+        assert_eq!(json["components"][0]["bom-ref"], "patch:ethics-1");
+    }
+}
@@ -0,0 +1,382 @@
+//! Canonical Signing Bytes
+//!
+//! Patches, release manifests, snapshot manifests, approvals, emergency
+//! authorizations and override claims are signed over their
+//! `pq_types::canonical` encoding instead of their bincode serialization,
+//! so reordering fields, adding serde attributes or upgrading bincode can
+//! no longer silently break verification of signatures already issued.
+//! Each encoder lists the fields it covers in a fixed order; a field added
+//! to one of these structs is not signed until its encoder is extended
+//! under a new domain. The golden vectors in the tests freeze every layout.
+//!
+//! A patch signature covers what its releaser decides: identity, content
+//! hash and size, criticality, justification, dependencies, files,
+//! supersession, expiry and the signature algorithm. The fields the
+//! orchestrator fills in after submission - submission time, moral and
+//! harm assessments, verification status, payload audit findings and the
+//! signatures themselves - are left out, as they are from approval digests.
+//! An approval digest covers the same fields bar the justification and the
+//! signature algorithm, with dependencies, files and superseded patches in
+//! sorted order; the approver signs the canonical encoding of the patch id
+//! and that digest.
+//!
+//! ## Biblical Foundation
+//! "Ye shall do no unrighteousness in judgment, in meteyard, in weight, or in measure" - Leviticus 19:35
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pq_types::canonical::CanonicalEncoder;
+
+use crate::approval::APPROVAL_DIGEST_DOMAIN;
+use crate::emergency::{EmergencyRequest, EMERGENCY_DIGEST_DOMAIN};
+use crate::persona::{OverrideClaim, OVERRIDE_DIGEST_DOMAIN};
+use crate::release::ReleaseManifest;
+use crate::snapshot::{SnapshotComponent, SnapshotEntry, SnapshotManifest};
+use crate::{CriticalityLevel, PatchMetadata, SignatureAlgorithm};
+
+/// Domain of patch signatures
+const PATCH_DOMAIN: &[u8] = b"ARK-PATCH-SIGNATURE-V2";
+
+/// Domain of release manifest signatures
+const RELEASE_DOMAIN: &[u8] = b"ARK-RELEASE-V2";
+
+/// Domain of snapshot manifest signatures, also the seed of the entry hash chain
+pub(crate) const SNAPSHOT_DOMAIN: &[u8] = b"ARK-SNAPSHOT-V2";
+
+/// Domain of snapshot entries folded into the manifest hash chain
+const SNAPSHOT_ENTRY_DOMAIN: &[u8] = b"ARK-SNAPSHOT-ENTRY-V2";
+
+/// Domain of the message an approver signs
+const APPROVAL_MESSAGE_DOMAIN: &[u8] = b"ARK-PATCH-APPROVAL-MESSAGE-V3";
+
+impl PatchMetadata {
+    /// Bytes the release keys sign
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(PATCH_DOMAIN);
+        encoder
+            .str(&self.id)
+            .str(&self.namespace)
+            .str(&self.version)
+            .str(&self.component)
+            .str(&self.description)
+            .variant(criticality_name(&self.criticality))
+            .fixed(self.hash.as_bytes())
+            .u64(self.size_bytes)
+            .option(self.biblical_justification.as_deref(), |encoder, justification| {
+                encoder.str(justification);
+            })
+            .strs(&self.dependencies)
+            .strs(&self.files)
+            .strs(&self.supersedes)
+            .option(self.expires_at, |encoder, expires_at| {
+                encoder.u128(unix_nanos(expires_at));
+            })
+            .variant(algorithm_name(&self.signature_algorithm));
+        encoder.finish()
+    }
+
+    /// Bytes whose digest approvers sign
+    pub fn approval_signing_bytes(&self) -> Vec<u8> {
+        let mut dependencies = self.dependencies.clone();
+        dependencies.sort();
+        let mut files = self.files.clone();
+        files.sort();
+        let mut supersedes = self.supersedes.clone();
+        supersedes.sort();
+
+        let mut encoder = CanonicalEncoder::new(APPROVAL_DIGEST_DOMAIN.as_bytes());
+        encoder
+            .str(&self.id)
+            .str(&self.namespace)
+            .str(&self.version)
+            .str(&self.component)
+            .str(&self.description)
+            .variant(criticality_name(&self.criticality))
+            .fixed(self.hash.as_bytes())
+            .u64(self.size_bytes)
+            .strs(&dependencies)
+            .strs(&files)
+            .strs(&supersedes)
+            .option(self.expires_at, |encoder, expires_at| {
+                encoder.u128(unix_nanos(expires_at));
+            });
+        encoder.finish()
+    }
+}
+
+/// Bytes an approver signs for the approval digest of `patch_id`
+pub(crate) fn approval_message(patch_id: &str, digest: &str) -> Vec<u8> {
+    let mut encoder = CanonicalEncoder::new(APPROVAL_MESSAGE_DOMAIN);
+    encoder.str(patch_id).str(digest);
+    encoder.finish()
+}
+
+impl EmergencyRequest {
+    /// Bytes whose digest approvers sign
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(EMERGENCY_DIGEST_DOMAIN.as_bytes());
+        encoder
+            .str(&self.namespace)
+            .str(&self.reason)
+            .u128(unix_nanos(self.issued_at))
+            .u128(unix_nanos(self.expires_at))
+            .str(&self.nonce);
+        encoder.finish()
+    }
+}
+
+impl OverrideClaim {
+    /// Bytes whose digest the operator signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(OVERRIDE_DIGEST_DOMAIN.as_bytes());
+        encoder
+            .str(&self.operator)
+            .str(&self.namespace)
+            .strs(&self.rules)
+            .strs(&self.components)
+            .str(&self.reason)
+            .u128(unix_nanos(self.issued_at))
+            .u128(unix_nanos(self.expires_at))
+            .str(&self.nonce);
+        encoder.finish()
+    }
+}
+
+impl ReleaseManifest {
+    /// Bytes whose digest the release keys sign
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(RELEASE_DOMAIN);
+        encoder
+            .str(&self.release_id)
+            .str(&self.namespace)
+            .str(&self.description)
+            .u128(unix_nanos(self.created_at))
+            .seq(&self.members, |encoder, member| {
+                encoder.str(&member.patch_id).str(&member.component).str(&member.payload_hash);
+            });
+        encoder.finish()
+    }
+}
+
+impl SnapshotManifest {
+    /// Bytes whose digest the orchestrator signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(SNAPSHOT_DOMAIN);
+        encoder
+            .u32(self.version)
+            .str(&self.namespace)
+            .u128(unix_nanos(self.created_at))
+            .seq(&self.entries, |encoder, entry| {
+                encode_entry(encoder, entry);
+            })
+            .str(&self.chain_head)
+            .u64(self.audit_checkpoint.records as u64)
+            .str(&self.audit_checkpoint.head);
+        encoder.finish()
+    }
+}
+
+impl SnapshotEntry {
+    /// Bytes folded into the manifest hash chain
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut encoder = CanonicalEncoder::new(SNAPSHOT_ENTRY_DOMAIN);
+        encode_entry(&mut encoder, self);
+        encoder.finish()
+    }
+}
+
+fn encode_entry(encoder: &mut CanonicalEncoder, entry: &SnapshotEntry) {
+    encoder
+        .variant(component_name(entry.component))
+        .str(&entry.name)
+        .u64(entry.size)
+        .str(&entry.hash);
+}
+
+// Variant names are part of the signed bytes: never rename one
+
+fn criticality_name(criticality: &CriticalityLevel) -> &'static str {
+    match criticality {
+        CriticalityLevel::Divine => "divine",
+        CriticalityLevel::Critical => "critical",
+        CriticalityLevel::High => "high",
+        CriticalityLevel::Medium => "medium",
+        CriticalityLevel::Low => "low",
+    }
+}
+
+fn algorithm_name(algorithm: &SignatureAlgorithm) -> &'static str {
+    match algorithm {
+        SignatureAlgorithm::Ed25519 => "ed25519",
+        SignatureAlgorithm::Dilithium3 => "dilithium3",
+        SignatureAlgorithm::HybridEd25519Dilithium3 => "ed25519+dilithium3",
+        SignatureAlgorithm::MlDsa65 => "mldsa65",
+        SignatureAlgorithm::HybridEd25519MlDsa65 => "ed25519+mldsa65",
+    }
+}
+
+fn component_name(component: SnapshotComponent) -> &'static str {
+    match component {
+        SnapshotComponent::RulePack => "rule_pack",
+        SnapshotComponent::ModelRegistry => "model_registry",
+        SnapshotComponent::ActorLedger => "actor_ledger",
+        SnapshotComponent::PatchStore => "patch_store",
+        SnapshotComponent::AuditLog => "audit_log",
+    }
+}
+
+/// Nanoseconds since the Unix epoch; earlier times encode as 0
+fn unix_nanos(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::release::ReleaseMember;
+    use crate::snapshot::AuditCheckpoint;
+    use crate::{HarmAnalysis, PatchMorality, VerificationStatus};
+    use cold_mirror::RiskLevel;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn patch() -> PatchMetadata {
+        PatchMetadata {
+            id: "patch-7".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Rule pack update".to_string(),
+            component: "ethics_dsl".to_string(),
+            criticality: CriticalityLevel::Medium,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"patched rule pack"),
+            size_bytes: 17,
+            dependencies: vec!["patch-6".to_string()],
+            biblical_justification: Some("Leviticus 19:35".to_string()),
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Unknown,
+                physical_harm_risk: RiskLevel::Unknown,
+                psychological_harm_risk: RiskLevel::Unknown,
+                spiritual_harm_risk: RiskLevel::Unknown,
+                system_integrity_risk: RiskLevel::Unknown,
+                overall_risk: RiskLevel::Unknown,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: at(1_700_000_000),
+            expires_at: Some(at(1_800_000_000)),
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: "default".to_string(),
+            files: vec!["rules/core.ark".to_string()],
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    #[test]
+    fn test_patch_signing_bytes_are_frozen() {
+        let patch = patch();
+        let bytes = patch.signing_bytes();
+        assert_eq!(&bytes[8..30], PATCH_DOMAIN);
+        assert_eq!(blake3::hash(&bytes).to_hex().as_str(), "df69b113412dc212fdea3d05297d32c7d87b57e01286282521d59619aeae99db");
+
+        // Fields filled in after submission are not signed
+        let mut assessed = patch.clone();
+        assessed.created_at = SystemTime::now();
+        assessed.moral_assessment = PatchMorality::Righteous;
+        assessed.harm_analysis.overall_risk = RiskLevel::Low;
+        assessed.verification = VerificationStatus::Rejected { moral_violation: "test".to_string() };
+        assert_eq!(assessed.signing_bytes(), bytes);
+
+        // Fields the releaser decides are
+        let mut widened = patch;
+        widened.files.push("rules/extra.ark".to_string());
+        assert_ne!(widened.signing_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_manifest_signing_bytes_are_frozen() {
+        let release = ReleaseManifest {
+            release_id: "release-3".to_string(),
+            namespace: "default".to_string(),
+            description: "Rule pack and model together".to_string(),
+            created_at: at(1_700_000_000),
+            members: vec![ReleaseMember {
+                patch_id: "patch-7".to_string(),
+                component: "ethics_dsl".to_string(),
+                payload_hash: blake3::hash(b"patched rule pack").to_hex().to_string(),
+            }],
+        };
+        assert_eq!(blake3::hash(&release.signing_bytes()).to_hex().as_str(), "57d9edd73bb0bb79aefd5c9439ac1fbb3bccb1847a994085eaf605bb14955474");
+
+        let entry = SnapshotEntry {
+            component: SnapshotComponent::RulePack,
+            name: "core.ark".to_string(),
+            size: 17,
+            hash: blake3::hash(b"patched rule pack").to_hex().to_string(),
+        };
+        assert_eq!(blake3::hash(&entry.signing_bytes()).to_hex().as_str(), "6ce147d0a3eed1a5a6a69fc504f7b3289a504312a21e2ec4fc488f0165edc22a");
+
+        let snapshot = SnapshotManifest {
+            version: 2,
+            namespace: "default".to_string(),
+            created_at: at(1_700_000_000),
+            entries: vec![entry],
+            chain_head: "00".repeat(32),
+            audit_checkpoint: AuditCheckpoint { records: 4, head: "11".repeat(32) },
+        };
+        assert_eq!(blake3::hash(&snapshot.signing_bytes()).to_hex().as_str(), "af0aa794c8b86d8c1dc4c5ecd3faadaffa6e6e1247d3ad45ec45e6822dfec3da");
+    }
+
+    #[test]
+    fn test_authorization_signing_bytes_are_frozen() {
+        let mut patch = patch();
+        patch.dependencies = vec!["patch-6".to_string(), "patch-5".to_string()];
+        let approval = patch.approval_signing_bytes();
+        let digest = blake3::hash(&approval);
+        assert_eq!(digest.to_hex().as_str(), "391a327471400a10c6d5660becb6b1878f686b342884a5b19fab24d1f1ba2dee");
+        let message = approval_message(&patch.id, digest.to_hex().as_str());
+        assert_eq!(blake3::hash(&message).to_hex().as_str(), "bcecaa18d2ac70bf1f5ab93e7bc8505680b244d28c7b30a611f9c81093896984");
+        patch.dependencies.reverse();
+        assert_eq!(patch.approval_signing_bytes(), approval);
+
+        // Criticality, files and expiry are approved too
+        let mut escalated = patch.clone();
+        escalated.criticality = CriticalityLevel::Divine;
+        assert_ne!(escalated.approval_signing_bytes(), approval);
+        let mut widened = patch.clone();
+        widened.files.push("rules/extra.ark".to_string());
+        assert_ne!(widened.approval_signing_bytes(), approval);
+        let mut extended = patch.clone();
+        extended.expires_at = None;
+        assert_ne!(extended.approval_signing_bytes(), approval);
+
+        let emergency = EmergencyRequest {
+            namespace: "default".to_string(),
+            reason: "Actively exploited rule bypass".to_string(),
+            issued_at: at(1_700_000_000),
+            expires_at: at(1_700_003_600),
+            nonce: "00".repeat(16),
+        };
+        assert_eq!(blake3::hash(&emergency.signing_bytes()).to_hex().as_str(), "557a1030d9743b56510462dc3dbbc5cf5ffa224c9e33d92a7667fdda19fda834");
+
+        let claim = OverrideClaim {
+            operator: "alice".to_string(),
+            namespace: "default".to_string(),
+            rules: vec!["harm.physical".to_string()],
+            components: vec![],
+            reason: "Medical content review".to_string(),
+            issued_at: at(1_700_000_000),
+            expires_at: at(1_700_003_600),
+            nonce: "11".repeat(16),
+        };
+        assert_eq!(blake3::hash(&claim.signing_bytes()).to_hex().as_str(), "5195e96363159eb134321d33dd8a9b2ad7969a555191778730c2283491685dca");
+    }
+}
@@ -0,0 +1,327 @@
+//! Patch Lifecycle Metrics and SLO Reporting
+//!
+//! The orchestrator records each patch's lifecycle in the audit trail:
+//! submission, assessment, approval, application, and any rejection or
+//! rollback. When a stage completes, its duration is published through the
+//! `metrics` facade as `ark_patch_stage_seconds`, labelled with the stage
+//! and the patch's criticality. Rejections and rollbacks are counted in
+//! `ark_patch_rejections_total`, labelled by error code, and in
+//! `ark_patch_rollbacks_total`, labelled by component.
+//!
+//! Because the audit trail persists, `SloReport::build` can rebuild every
+//! timeline from it and compare the stage durations with the configured
+//! targets, such as "Critical patches applied within 4 hours of
+//! submission". A patch still inside a stage past its deadline counts as an
+//! open breach.
+//!
+//! ## Biblical Foundation
+//! "To every thing there is a season, and a time to every purpose under the heaven" - Ecclesiastes 3:1
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::{AuditEvent, AuditRecord};
+use crate::CriticalityLevel;
+
+/// Span of a patch's lifecycle measured by the SLOs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    SubmitToAssessment,
+    AssessmentToApproval,
+    ApprovalToApply,
+    SubmitToApply,
+}
+
+impl Stage {
+    /// Label value used in metrics
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::SubmitToAssessment => "submit_to_assessment",
+            Stage::AssessmentToApproval => "assessment_to_approval",
+            Stage::ApprovalToApply => "approval_to_apply",
+            Stage::SubmitToApply => "submit_to_apply",
+        }
+    }
+}
+
+/// Lifecycle timestamps of one patch
+#[derive(Debug, Clone, Default)]
+pub struct PatchTimeline {
+    pub criticality: Option<CriticalityLevel>,
+    pub submitted: Option<SystemTime>,
+    pub assessed: Option<SystemTime>,
+    pub approved: Option<SystemTime>,
+    pub applied: Option<SystemTime>,
+    /// Rejected or superseded before being applied
+    pub closed: bool,
+}
+
+impl PatchTimeline {
+    /// Apply a lifecycle event, returning the stages it completed
+    pub fn observe(&mut self, event: &AuditEvent, at: SystemTime) -> Vec<(Stage, Duration)> {
+        match event {
+            AuditEvent::PatchSubmitted { criticality } => {
+                // A resubmission starts a new lifecycle
+                *self = Self { criticality: Some(criticality.clone()), submitted: Some(at), ..Self::default() };
+            }
+            AuditEvent::PatchAssessed => self.assessed = Some(at),
+            // The latest approval counts: a blocked auto-apply waits for an operator
+            AuditEvent::PatchApproved { .. } => self.approved = Some(at),
+            AuditEvent::PatchApplied => self.applied = Some(at),
+            AuditEvent::PatchRejected { .. } | AuditEvent::PatchSuperseded { .. } => self.closed = true,
+            _ => return Vec::new(),
+        }
+
+        [Stage::SubmitToAssessment, Stage::AssessmentToApproval, Stage::ApprovalToApply, Stage::SubmitToApply]
+            .into_iter()
+            .filter(|stage| self.ended(*stage) == Some(at))
+            .filter_map(|stage| Some((stage, self.duration(stage)?)))
+            .collect()
+    }
+
+    fn bounds(&self, stage: Stage) -> (Option<SystemTime>, Option<SystemTime>) {
+        match stage {
+            Stage::SubmitToAssessment => (self.submitted, self.assessed),
+            Stage::AssessmentToApproval => (self.assessed, self.approved),
+            Stage::ApprovalToApply => (self.approved, self.applied),
+            Stage::SubmitToApply => (self.submitted, self.applied),
+        }
+    }
+
+    /// When the patch entered `stage`
+    pub fn started(&self, stage: Stage) -> Option<SystemTime> {
+        self.bounds(stage).0
+    }
+
+    /// When the patch left `stage`
+    pub fn ended(&self, stage: Stage) -> Option<SystemTime> {
+        self.bounds(stage).1
+    }
+
+    /// Time spent in a completed stage
+    pub fn duration(&self, stage: Stage) -> Option<Duration> {
+        let (start, end) = self.bounds(stage);
+        Some(end?.duration_since(start?).unwrap_or_default())
+    }
+}
+
+/// Publish the metrics for a lifecycle event
+pub fn publish(component: &str, timeline: &PatchTimeline, event: &AuditEvent, completed: &[(Stage, Duration)]) {
+    let criticality = timeline.criticality.as_ref().map_or_else(|| "unknown".to_string(), |c| format!("{:?}", c));
+    for (stage, duration) in completed {
+        metrics::histogram!("ark_patch_stage_seconds", "stage" => stage.name(), "criticality" => criticality.clone())
+            .record(duration.as_secs_f64());
+    }
+    match event {
+        AuditEvent::PatchRejected { reason } => {
+            metrics::counter!("ark_patch_rejections_total", "reason" => reason.clone()).increment(1);
+        }
+        AuditEvent::PatchRolledBack { .. } => {
+            metrics::counter!("ark_patch_rollbacks_total", "component" => component.to_string()).increment(1);
+        }
+        _ => {}
+    }
+}
+
+/// Upper bound on a stage for patches of one criticality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloTarget {
+    pub criticality: CriticalityLevel,
+    pub stage: Stage,
+    pub within: Duration,
+}
+
+/// Service level objectives for the patch lifecycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SloConfig {
+    pub targets: Vec<SloTarget>,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        Self {
+            targets: vec![
+                SloTarget { criticality: CriticalityLevel::Divine, stage: Stage::SubmitToApply, within: hours(1) },
+                SloTarget { criticality: CriticalityLevel::Critical, stage: Stage::SubmitToApply, within: hours(4) },
+                SloTarget { criticality: CriticalityLevel::High, stage: Stage::SubmitToApply, within: hours(24) },
+                SloTarget {
+                    criticality: CriticalityLevel::Critical,
+                    stage: Stage::SubmitToAssessment,
+                    within: Duration::from_secs(15 * 60),
+                },
+            ],
+        }
+    }
+}
+
+/// Outcome of one target
+#[derive(Debug, Clone, Serialize)]
+pub struct SloResult {
+    pub target: SloTarget,
+    /// Patches that completed the stage within the target
+    pub met: usize,
+    /// Patches that completed the stage late
+    pub breached: usize,
+    /// Patches still in the stage past the target
+    pub open_breaches: usize,
+    /// 95th percentile of completed stage durations
+    pub p95: Option<Duration>,
+    /// Share of completed stages within the target
+    pub compliance: Option<f64>,
+}
+
+impl SloResult {
+    pub fn is_met(&self) -> bool {
+        self.breached == 0 && self.open_breaches == 0
+    }
+}
+
+/// Lifecycle SLOs and failure counts for one namespace
+#[derive(Debug, Clone, Serialize)]
+pub struct SloReport {
+    pub generated_at: SystemTime,
+    pub results: Vec<SloResult>,
+    pub rollbacks: usize,
+    /// Rejections by error code
+    pub rejections: BTreeMap<String, usize>,
+}
+
+impl SloReport {
+    /// Rebuild timelines from audit records and evaluate every target at `now`
+    pub fn build(records: &[AuditRecord], config: &SloConfig, now: SystemTime) -> Self {
+        let mut timelines: HashMap<&str, PatchTimeline> = HashMap::new();
+        let mut rollbacks = 0;
+        let mut rejections = BTreeMap::new();
+        for record in records {
+            match &record.event {
+                AuditEvent::PatchRejected { reason } => *rejections.entry(reason.clone()).or_insert(0) += 1,
+                AuditEvent::PatchRolledBack { .. } => rollbacks += 1,
+                _ => {}
+            }
+            timelines.entry(&record.patch_id).or_default().observe(&record.event, record.timestamp);
+        }
+
+        let results = config
+            .targets
+            .iter()
+            .map(|target| {
+                let mut durations = Vec::new();
+                let mut open_breaches = 0;
+                for timeline in timelines.values().filter(|t| t.criticality.as_ref() == Some(&target.criticality)) {
+                    match (timeline.duration(target.stage), timeline.started(target.stage)) {
+                        (Some(duration), _) => durations.push(duration),
+                        (None, Some(started))
+                            if !timeline.closed && now.duration_since(started).unwrap_or_default() > target.within =>
+                        {
+                            open_breaches += 1
+                        }
+                        _ => {}
+                    }
+                }
+                durations.sort();
+                let met = durations.iter().filter(|d| **d <= target.within).count();
+                SloResult {
+                    target: target.clone(),
+                    met,
+                    breached: durations.len() - met,
+                    open_breaches,
+                    p95: percentile(&durations, 0.95),
+                    compliance: (!durations.is_empty()).then(|| met as f64 / durations.len() as f64),
+                }
+            })
+            .collect();
+
+        Self { generated_at: now, results, rollbacks, rejections }
+    }
+
+    /// Whether every target is met
+    pub fn is_met(&self) -> bool {
+        self.results.iter().all(SloResult::is_met)
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(patch_id: &str, at: SystemTime, event: AuditEvent) -> AuditRecord {
+        AuditRecord {
+            timestamp: at,
+            namespace: "default".to_string(),
+            patch_id: patch_id.to_string(),
+            component: "ethics_dsl".to_string(),
+            event,
+        }
+    }
+
+    fn submitted(criticality: CriticalityLevel) -> AuditEvent {
+        AuditEvent::PatchSubmitted { criticality }
+    }
+
+    #[test]
+    fn test_timeline_reports_completed_stages() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let mut timeline = PatchTimeline::default();
+
+        assert!(timeline.observe(&submitted(CriticalityLevel::High), t0).is_empty());
+        let completed = timeline.observe(&AuditEvent::PatchAssessed, t0 + Duration::from_secs(30));
+        assert_eq!(completed, vec![(Stage::SubmitToAssessment, Duration::from_secs(30))]);
+
+        timeline.observe(&AuditEvent::PatchApproved { automatic: false }, t0 + Duration::from_secs(90));
+        let completed = timeline.observe(&AuditEvent::PatchApplied, t0 + Duration::from_secs(100));
+        assert_eq!(
+            completed,
+            vec![(Stage::ApprovalToApply, Duration::from_secs(10)), (Stage::SubmitToApply, Duration::from_secs(100))]
+        );
+    }
+
+    #[test]
+    fn test_report_compares_against_targets() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let hour = Duration::from_secs(3600);
+        let records = vec![
+            // Critical, applied in 2 hours
+            record("fast", t0, submitted(CriticalityLevel::Critical)),
+            record("fast", t0 + Duration::from_secs(60), AuditEvent::PatchAssessed),
+            record("fast", t0 + hour, AuditEvent::PatchApproved { automatic: false }),
+            record("fast", t0 + 2 * hour, AuditEvent::PatchApplied),
+            // Critical, applied in 6 hours after a rollback
+            record("slow", t0, submitted(CriticalityLevel::Critical)),
+            record("slow", t0 + Duration::from_secs(60), AuditEvent::PatchAssessed),
+            record("slow", t0 + hour, AuditEvent::PatchApproved { automatic: true }),
+            record("slow", t0 + 2 * hour, AuditEvent::PatchRolledBack { reason: "staging".to_string() }),
+            record("slow", t0 + 6 * hour, AuditEvent::PatchApplied),
+            // Critical, pending for 5 hours
+            record("stuck", t0 + 3 * hour, submitted(CriticalityLevel::Critical)),
+            // Rejected patches are not open breaches
+            record("bad", t0, submitted(CriticalityLevel::Critical)),
+            record("bad", t0, AuditEvent::PatchRejected { reason: "moral_violation".to_string() }),
+        ];
+        let config = SloConfig {
+            targets: vec![SloTarget { criticality: CriticalityLevel::Critical, stage: Stage::SubmitToApply, within: 4 * hour }],
+        };
+
+        let report = SloReport::build(&records, &config, t0 + 8 * hour);
+        let result = &report.results[0];
+        assert_eq!((result.met, result.breached, result.open_breaches), (1, 1, 1));
+        assert_eq!(result.p95, Some(6 * hour));
+        assert_eq!(result.compliance, Some(0.5));
+        assert!(!report.is_met());
+        assert_eq!(report.rollbacks, 1);
+        assert_eq!(report.rejections["moral_violation"], 1);
+    }
+}
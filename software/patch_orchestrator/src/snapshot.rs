@@ -0,0 +1,550 @@
+//! Disaster Recovery Snapshots
+//!
+//! A snapshot gathers the state a replacement host needs into one signed
+//! archive: the live ethics rule pack, the active Cold-Mirror model (the
+//! model registry), the decision journal (the ledger of what each actor was
+//! decided, from its file or its store), the patch store - pending, applied
+//! and approved patches, the detached approvals imported for them, the
+//! overlapping patches each waits for and the pending releases grouping
+//! them, with their staged files - and the audit trail. The orchestrator takes it while
+//! holding itself exclusively, so the patch store cannot change underneath
+//! it; rule packs and models are only ever replaced by rename, so each file
+//! read is whole.
+//!
+//! Every file is hashed and the hashes are folded into a chain, and the
+//! audit trail is checkpointed by a second chain over its records. The
+//! manifest carrying both chain heads is signed with the orchestrator's
+//! Dilithium3 and Ed25519 keys. Restoring verifies the signature against
+//! the configured trusted keys and recomputes every hash and both chains
+//! before a single file is written. Every file is then staged beside its
+//! destination, and only once all of them are staged are they renamed into
+//! place and the journal store, audit trail and patch state replaced, so a
+//! restore that fails part way leaves the host as it was.
+//!
+//! ## Biblical Foundation
+//! "Except the Lord build the house, they labour in vain that build it" - Psalm 127:1
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ark_storage::{Batch, StorageConfig};
+use blake3::Hasher;
+use ed25519_dalek::{Signature as Ed25519Signature, Signer as _, Verifier as _};
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::{Dilithium3, SignatureScheme};
+use pq_types::{DilithiumSignatureBytes, Ed25519SignatureBytes};
+use ethics_dsl::journal::JOURNAL_TREE;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::approval::DetachedApproval;
+use crate::audit::{AuditEvent, AUDIT_TRAIL_FILE};
+use crate::release::SignedRelease;
+use crate::signing;
+use crate::{OrchestratorError, PatchMetadata, PatchOrchestrator, PatchPublicKeys};
+
+/// Snapshot archive format version
+///
+/// Version 2 signs manifests over their canonical encoding; version 1
+/// archives do not verify and are refused. Version 4 adds pending releases
+/// and blocked patches to the patch store.
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+/// Largest snapshot archive accepted for restore
+pub const MAX_SNAPSHOT_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Most files carried by one snapshot
+pub const MAX_SNAPSHOT_FILES: usize = 10_000;
+
+/// Name of the serialized patch store inside a snapshot
+pub const PATCH_STORE_FILE: &str = "patch_store.bin";
+
+/// Name of a decision journal kept in a store inside a snapshot
+pub const JOURNAL_STORE_FILE: &str = "decision_journal.jsonl";
+
+const SNAPSHOT_LIMITS: DecodeLimits = DecodeLimits::new(MAX_SNAPSHOT_BYTES, 32);
+
+/// Part of the system state a snapshot file belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotComponent {
+    /// Live ethics rule pack
+    RulePack,
+    /// Active Cold-Mirror model
+    ModelRegistry,
+    /// Ethics decision journal
+    ActorLedger,
+    /// Patch metadata and staged patch files
+    PatchStore,
+    /// Orchestrator audit trail
+    AuditLog,
+}
+
+/// One file recorded in a snapshot manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub component: SnapshotComponent,
+    /// File name, restored into the component's configured location
+    pub name: String,
+    pub size: u64,
+    /// BLAKE3 hash of the contents (hex)
+    pub hash: String,
+}
+
+/// Audit trail position covered by a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    /// Records in the trail when the snapshot was taken
+    pub records: usize,
+    /// Head of the hash chain over those records (hex)
+    pub head: String,
+}
+
+/// Signed description of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub namespace: String,
+    pub created_at: SystemTime,
+    pub entries: Vec<SnapshotEntry>,
+    /// Head of the hash chain over `entries` (hex)
+    pub chain_head: String,
+    pub audit_checkpoint: AuditCheckpoint,
+}
+
+impl SnapshotManifest {
+    /// Digest signed by the orchestrator, over `signing_bytes`
+    pub fn digest(&self) -> Result<blake3::Hash, OrchestratorError> {
+        Ok(blake3::hash(&self.signing_bytes()))
+    }
+}
+
+/// Snapshot archive as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotArchive {
+    pub manifest: SnapshotManifest,
+    pub pq_signature: DilithiumSignatureBytes,
+    pub classical_signature: Ed25519SignatureBytes,
+    /// File contents, in manifest entry order
+    pub files: Vec<Vec<u8>>,
+}
+
+impl Validate for SnapshotArchive {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("namespace", &self.manifest.namespace, crate::MAX_PATCH_FIELD_LENGTH)?;
+        decode::check_count("entries", self.manifest.entries.len(), MAX_SNAPSHOT_FILES)?;
+        if self.files.len() != self.manifest.entries.len() {
+            return Err(format!("{} files for {} manifest entries", self.files.len(), self.manifest.entries.len()));
+        }
+        for entry in &self.manifest.entries {
+            decode::check_identifier("name", &entry.name, crate::MAX_PATCH_FIELD_LENGTH)?;
+        }
+        Ok(())
+    }
+}
+
+impl SnapshotArchive {
+    /// Read an archive file
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let contents = std::fs::read(path)
+            .map_err(|e| OrchestratorError::Snapshot(format!("{:?}: {}", path, e)))?;
+        decode::bincode_validated(&contents, &SNAPSHOT_LIMITS)
+            .map_err(|e| OrchestratorError::Snapshot(format!("{:?}: {}", path, e)))
+    }
+
+    /// Write an archive file
+    pub fn save(&self, path: &Path) -> Result<(), OrchestratorError> {
+        let contents = bincode::serialize(self).map_err(|e| OrchestratorError::Snapshot(e.to_string()))?;
+        let mut staged = StagedFiles::default();
+        staged.stage(path, &contents)?;
+        staged.commit()
+    }
+
+    /// Contents of the files of `component`, with their names
+    fn files_of(&self, component: SnapshotComponent) -> impl Iterator<Item = (&str, &[u8])> {
+        self.manifest.entries.iter()
+            .zip(&self.files)
+            .filter(move |(entry, _)| entry.component == component)
+            .map(|(entry, contents)| (entry.name.as_str(), contents.as_slice()))
+    }
+}
+
+/// Patch state of a namespace as carried by a snapshot and the repository
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatchStore {
+    pub pending: Vec<PatchMetadata>,
+    pub applied: Vec<PatchMetadata>,
+    pub approved: Vec<String>,
+    /// Verified detached approvals of pending patches
+    pub detached_approvals: Vec<DetachedApproval>,
+    /// Overlapping patches each pending patch waits for
+    pub blocked_by: HashMap<String, Vec<String>>,
+    /// Pending releases grouping pending patches
+    pub releases: Vec<SignedRelease>,
+}
+
+/// Fold an entry into the manifest hash chain
+fn chain_entry(previous: &blake3::Hash, entry: &SnapshotEntry) -> blake3::Hash {
+    let mut hasher = Hasher::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(&entry.signing_bytes());
+    hasher.finalize()
+}
+
+/// Head of the hash chain over manifest entries
+pub fn chain_head(entries: &[SnapshotEntry]) -> blake3::Hash {
+    entries.iter().fold(blake3::hash(signing::SNAPSHOT_DOMAIN), |head, entry| chain_entry(&head, entry))
+}
+
+/// Checkpoint of an audit trail's records, one JSON line each
+pub fn audit_checkpoint(trail: &[u8]) -> AuditCheckpoint {
+    let mut records = 0;
+    let mut head = blake3::hash(AUDIT_TRAIL_FILE.as_bytes());
+    for line in trail.split(|b| *b == b'\n').filter(|line| !line.iter().all(u8::is_ascii_whitespace)) {
+        let mut hasher = Hasher::new();
+        hasher.update(head.as_bytes());
+        hasher.update(line);
+        head = hasher.finalize();
+        records += 1;
+    }
+    AuditCheckpoint { records, head: head.to_hex().to_string() }
+}
+
+/// Check every file hash and both chains against the manifest
+pub fn verify_contents(archive: &SnapshotArchive) -> Result<(), OrchestratorError> {
+    let reject = |reason: String| Err(OrchestratorError::Snapshot(reason));
+
+    for (entry, contents) in archive.manifest.entries.iter().zip(&archive.files) {
+        if contents.len() as u64 != entry.size || blake3::hash(contents).to_hex().as_str() != entry.hash {
+            return reject(format!("{:?} file {} does not match its manifest entry", entry.component, entry.name));
+        }
+    }
+    if chain_head(&archive.manifest.entries).to_hex().as_str() != archive.manifest.chain_head {
+        return reject("Manifest hash chain is broken".into());
+    }
+
+    let trail: Vec<u8> = archive.files_of(SnapshotComponent::AuditLog).flat_map(|(_, contents)| contents.to_vec()).collect();
+    if audit_checkpoint(&trail) != archive.manifest.audit_checkpoint {
+        return reject("Audit trail does not match its checkpoint".into());
+    }
+    Ok(())
+}
+
+/// Verify the manifest signature against trusted keys
+pub fn verify_signature(archive: &SnapshotArchive, keys: &PatchPublicKeys) -> Result<(), OrchestratorError> {
+    let digest = archive.manifest.digest()?;
+    Dilithium3::verify(digest.as_bytes(), &archive.pq_signature, &keys.dilithium_public)
+        .map_err(|_| OrchestratorError::SignatureError("Snapshot Dilithium signature verification failed".into()))?;
+    let classical_signature = Ed25519Signature::from_bytes(&archive.classical_signature.clone().into());
+    keys.ed25519_public.verify(digest.as_bytes(), &classical_signature)
+        .map_err(|_| OrchestratorError::SignatureError("Snapshot Ed25519 signature verification failed".into()))
+}
+
+/// Files written beside their destinations, renamed into place together
+#[derive(Default)]
+struct StagedFiles(Vec<(PathBuf, PathBuf)>);
+
+impl StagedFiles {
+    /// Write `contents` next to `path`, synced, without touching `path`
+    fn stage(&mut self, path: &Path, contents: &[u8]) -> Result<(), OrchestratorError> {
+        use std::io::Write;
+
+        let io_error = |e: std::io::Error| OrchestratorError::Snapshot(format!("{:?}: {}", path, e));
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".snapshot-tmp");
+        let temp = path.with_file_name(temp_name);
+        self.0.push((temp.clone(), path.to_path_buf()));
+        let mut file = std::fs::File::create(&temp).map_err(io_error)?;
+        file.write_all(contents).and_then(|()| file.sync_all()).map_err(io_error)
+    }
+
+    /// Rename every staged file over its destination
+    fn commit(mut self) -> Result<(), OrchestratorError> {
+        for (temp, path) in &self.0 {
+            std::fs::rename(temp, path).map_err(|e| OrchestratorError::Snapshot(format!("{:?}: {}", path, e)))?;
+        }
+        self.0.clear();
+        Ok(())
+    }
+}
+
+/// Staged files not committed are removed
+impl Drop for StagedFiles {
+    fn drop(&mut self) {
+        for (temp, _) in &self.0 {
+            let _ = std::fs::remove_file(temp);
+        }
+    }
+}
+
+/// Whether `name` is a single path component, safe to join onto a directory
+fn is_plain_file_name(name: &str) -> bool {
+    Path::new(name).file_name().and_then(|file| file.to_str()) == Some(name)
+}
+
+fn file_name(path: &Path) -> Result<String, OrchestratorError> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| OrchestratorError::Snapshot(format!("{:?} has no file name", path)))
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, OrchestratorError> {
+    std::fs::read(path).map_err(|e| OrchestratorError::Snapshot(format!("{:?}: {}", path, e)))
+}
+
+/// Entries of a decision journal store as JSON lines
+fn read_journal_store(config: &StorageConfig) -> Result<Vec<u8>, OrchestratorError> {
+    let storage = ark_storage::open(config).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+    let mut lines = Vec::new();
+    for (_, line) in storage.scan(JOURNAL_TREE, &[]).map_err(|e| OrchestratorError::Storage(e.to_string()))? {
+        lines.extend_from_slice(&line);
+        lines.push(b'\n');
+    }
+    Ok(lines)
+}
+
+/// Replace the entries of a decision journal store with JSON lines
+fn write_journal_store(storage: &dyn ark_storage::Storage, contents: &[u8]) -> Result<(), OrchestratorError> {
+    let mut batch = Batch::new();
+    for (key, _) in storage.scan(JOURNAL_TREE, &[]).map_err(|e| OrchestratorError::Storage(e.to_string()))? {
+        batch.delete(JOURNAL_TREE, &key);
+    }
+    for (sequence, line) in contents.split(|b| *b == b'\n').filter(|line| !line.is_empty()).enumerate() {
+        batch.put(JOURNAL_TREE, &ark_storage::sequence_key(sequence as u64), line);
+    }
+    storage.apply(&batch).map_err(|e| OrchestratorError::Storage(e.to_string()))
+}
+
+impl PatchOrchestrator {
+    /// Take a signed snapshot of the full system state into `path`
+    pub fn create_snapshot(&self, path: &Path) -> Result<SnapshotManifest, OrchestratorError> {
+        let ethics = &self.config.ethics_patch_policy;
+        let mut files: Vec<(SnapshotComponent, String, Vec<u8>)> = vec![
+            (SnapshotComponent::RulePack, file_name(&ethics.live_rule_pack)?, read(&ethics.live_rule_pack)?),
+            (SnapshotComponent::ModelRegistry, file_name(&self.config.shadow_policy.active_model)?,
+             read(&self.config.shadow_policy.active_model)?),
+        ];
+        if let Some(storage) = &ethics.journal_storage {
+            files.push((SnapshotComponent::ActorLedger, JOURNAL_STORE_FILE.to_string(), read_journal_store(storage)?));
+        } else if let Some(journal) = ethics.decision_journal.as_ref().filter(|journal| journal.exists()) {
+            files.push((SnapshotComponent::ActorLedger, file_name(journal)?, read(journal)?));
+        }
+
+        let store = bincode::serialize(&self.patch_store()).map_err(|e| OrchestratorError::Snapshot(e.to_string()))?;
+        files.push((SnapshotComponent::PatchStore, PATCH_STORE_FILE.to_string(), store));
+        let mut staged: Vec<PathBuf> = std::fs::read_dir(&self.config.staging_directory)
+            .map_err(|e| OrchestratorError::Snapshot(format!("{:?}: {}", self.config.staging_directory, e)))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect();
+        staged.sort();
+        for path in staged {
+            files.push((SnapshotComponent::PatchStore, file_name(&path)?, read(&path)?));
+        }
+
+        let trail = self.audit_trail.export_lines()?;
+        let audit_checkpoint = audit_checkpoint(&trail);
+        files.push((SnapshotComponent::AuditLog, AUDIT_TRAIL_FILE.to_string(), trail));
+
+        let entries: Vec<SnapshotEntry> = files.iter()
+            .map(|(component, name, contents)| SnapshotEntry {
+                component: *component,
+                name: name.clone(),
+                size: contents.len() as u64,
+                hash: blake3::hash(contents).to_hex().to_string(),
+            })
+            .collect();
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_VERSION,
+            namespace: self.config.namespace.clone(),
+            created_at: SystemTime::now(),
+            chain_head: chain_head(&entries).to_hex().to_string(),
+            entries,
+            audit_checkpoint,
+        };
+
+        let (_, pq_secret) = self.pq_signing_key.as_ref()
+            .ok_or_else(|| OrchestratorError::SignatureError("No PQ signing key available".into()))?;
+        let classical = self.classical_signing_key.as_ref()
+            .ok_or_else(|| OrchestratorError::SignatureError("No classical signing key available".into()))?;
+        let digest = manifest.digest()?;
+        let archive = SnapshotArchive {
+            pq_signature: Dilithium3::sign(digest.as_bytes(), pq_secret).map_err(crate::scheme_error)?,
+            classical_signature: Ed25519SignatureBytes::from_slice(&classical.sign(digest.as_bytes()).to_bytes())
+                .map_err(|e| OrchestratorError::SignatureError(e.to_string()))?,
+            manifest,
+            files: files.into_iter().map(|(_, _, contents)| contents).collect(),
+        };
+        archive.save(path)?;
+
+        self.audit_trail.record("", "patch_orchestrator", AuditEvent::SnapshotCreated {
+            digest: digest.to_hex().to_string(),
+            files: archive.manifest.entries.len(),
+            audit_records: archive.manifest.audit_checkpoint.records,
+        })?;
+        info!("Snapshot of namespace {} written to {:?} ({} files)",
+              archive.manifest.namespace, path, archive.manifest.entries.len());
+        Ok(archive.manifest)
+    }
+
+    /// Verify a snapshot and rehydrate this host's state from it
+    ///
+    /// Intended for a replacement host: the restore is refused when this
+    /// instance already holds patches or an audit trail of its own.
+    pub fn restore_snapshot(&mut self, path: &Path) -> Result<SnapshotManifest, OrchestratorError> {
+        let archive = SnapshotArchive::load(path)?;
+        let manifest = &archive.manifest;
+        if manifest.version != SNAPSHOT_VERSION {
+            return Err(OrchestratorError::Snapshot(format!("Unsupported snapshot version {}", manifest.version)));
+        }
+        if manifest.namespace != self.config.namespace {
+            return Err(OrchestratorError::Snapshot(
+                format!("Snapshot is of namespace {}, not {}", manifest.namespace, self.config.namespace)
+            ));
+        }
+        verify_signature(&archive, &self.trusted_public_keys()?)?;
+        verify_contents(&archive)?;
+
+        let trail_in_use = !self.audit_trail.export_lines()?.is_empty();
+        if !self.pending_patches.is_empty() || !self.applied_patches.is_empty() || trail_in_use {
+            return Err(OrchestratorError::Snapshot("Refusing to restore over existing patch state".into()));
+        }
+        let journal = self.config.ethics_patch_policy.decision_journal.clone();
+        let journal_storage = self.config.ethics_patch_policy.journal_storage.clone();
+        if archive.files_of(SnapshotComponent::ActorLedger).next().is_some() && journal.is_none() && journal_storage.is_none() {
+            return Err(OrchestratorError::Snapshot("Snapshot carries a decision journal but none is configured".into()));
+        }
+
+        // Stage every file and decode the patch store before changing anything
+        let mut store = None;
+        let mut journal_lines = None;
+        let mut trail = None;
+        let mut staged = StagedFiles::default();
+        for (entry, contents) in manifest.entries.iter().zip(&archive.files) {
+            match entry.component {
+                SnapshotComponent::RulePack => staged.stage(&self.config.ethics_patch_policy.live_rule_pack, contents)?,
+                SnapshotComponent::ModelRegistry => staged.stage(&self.config.shadow_policy.active_model, contents)?,
+                SnapshotComponent::ActorLedger => match (&journal_storage, &journal) {
+                    (Some(_), _) => journal_lines = Some(contents),
+                    (None, Some(journal)) => staged.stage(journal, contents)?,
+                    (None, None) => {}
+                },
+                SnapshotComponent::PatchStore if entry.name == PATCH_STORE_FILE => {
+                    store = Some(decode::bincode::<PatchStore>(contents, &SNAPSHOT_LIMITS)
+                        .map_err(|e| OrchestratorError::Snapshot(format!("Patch store: {}", e)))?);
+                }
+                SnapshotComponent::PatchStore => {
+                    if !is_plain_file_name(&entry.name) {
+                        return Err(OrchestratorError::Snapshot(format!("Staged file {:?} is not a plain file name", entry.name)));
+                    }
+                    staged.stage(&self.config.staging_directory.join(&entry.name), contents)?
+                }
+                SnapshotComponent::AuditLog => trail = Some(contents),
+            }
+        }
+        let store = store.ok_or_else(|| OrchestratorError::Snapshot("Snapshot carries no patch store".into()))?;
+        if let Some(release) = store.releases.iter().find(|release| release.manifest.namespace != self.config.namespace) {
+            return Err(OrchestratorError::Snapshot(format!(
+                "Release {} belongs to namespace {}", release.manifest.release_id, release.manifest.namespace
+            )));
+        }
+        let journal_store = match (&journal_storage, journal_lines) {
+            (Some(storage), Some(lines)) => {
+                let storage = ark_storage::open(storage).map_err(|e| OrchestratorError::Storage(e.to_string()))?;
+                Some((storage, lines))
+            }
+            _ => None,
+        };
+
+        staged.commit()?;
+        if let Some((storage, lines)) = journal_store {
+            write_journal_store(storage.as_ref(), lines)?;
+        }
+        if let Some(trail) = trail {
+            self.audit_trail.replace_lines(trail)?;
+        }
+        self.pending_patches = store.pending.into_iter().map(|patch| (patch.id.clone(), patch)).collect();
+        self.applied_patches = store.applied.into_iter().map(|patch| (patch.id.clone(), patch)).collect();
+        self.approved_patches = store.approved.into_iter().collect();
+        self.set_detached_approvals(store.detached_approvals);
+        self.blocked_by = store.blocked_by;
+        self.releases = store.releases.into_iter()
+            .map(|release| (release.manifest.release_id.clone(), release))
+            .collect();
+        self.persist_patches();
+
+        self.audit_trail.record("", "patch_orchestrator", AuditEvent::SnapshotRestored {
+            digest: manifest.digest()?.to_hex().to_string(),
+            files: manifest.entries.len(),
+            audit_records: manifest.audit_checkpoint.records,
+        })?;
+        info!("Namespace {} restored from snapshot {:?}: {} pending, {} applied patches",
+              manifest.namespace, path, self.pending_patches.len(), self.applied_patches.len());
+        Ok(archive.manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archive() -> SnapshotArchive {
+        let files = vec![b"rule pack".to_vec(), b"{\"a\":1}\n{\"b\":2}\n".to_vec()];
+        let entries = vec![
+            SnapshotEntry {
+                component: SnapshotComponent::RulePack,
+                name: "live.ethics".into(),
+                size: files[0].len() as u64,
+                hash: blake3::hash(&files[0]).to_hex().to_string(),
+            },
+            SnapshotEntry {
+                component: SnapshotComponent::AuditLog,
+                name: AUDIT_TRAIL_FILE.into(),
+                size: files[1].len() as u64,
+                hash: blake3::hash(&files[1]).to_hex().to_string(),
+            },
+        ];
+        SnapshotArchive {
+            manifest: SnapshotManifest {
+                version: SNAPSHOT_VERSION,
+                namespace: "default".into(),
+                created_at: SystemTime::UNIX_EPOCH,
+                chain_head: chain_head(&entries).to_hex().to_string(),
+                audit_checkpoint: audit_checkpoint(&files[1]),
+                entries,
+            },
+            pq_signature: DilithiumSignatureBytes::from_vec(vec![0; pq_types::sizes::DILITHIUM3_SIGNATURE]).unwrap(),
+            classical_signature: Ed25519SignatureBytes::from_slice(&[0; 64]).unwrap(),
+            files,
+        }
+    }
+
+    #[test]
+    fn test_tampering_breaks_hashes_and_chains() {
+        let archive = archive();
+        assert_eq!(archive.manifest.audit_checkpoint.records, 2);
+        verify_contents(&archive).unwrap();
+
+        let mut tampered = archive.clone();
+        tampered.files[0] = b"rule pock".to_vec();
+        assert!(verify_contents(&tampered).is_err());
+
+        // A dropped audit record is caught even with a consistent file entry
+        let mut truncated = archive.clone();
+        truncated.files[1] = b"{\"a\":1}\n".to_vec();
+        truncated.manifest.entries[1].size = truncated.files[1].len() as u64;
+        truncated.manifest.entries[1].hash = blake3::hash(&truncated.files[1]).to_hex().to_string();
+        assert!(verify_contents(&truncated).is_err());
+        truncated.manifest.chain_head = chain_head(&truncated.manifest.entries).to_hex().to_string();
+        assert!(verify_contents(&truncated).is_err());
+
+        let mut reordered = archive;
+        reordered.manifest.entries.swap(0, 1);
+        reordered.files.swap(0, 1);
+        assert!(verify_contents(&reordered).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Staging Helpers
+//!
+//! Shared file operations for moving staged artifacts into place.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use tracing::info;
+
+use crate::OrchestratorError;
+
+/// Staged artifact that passed its component's checks, waiting to go live
+#[derive(Debug, Clone)]
+pub struct Promotion {
+    pub patch_id: String,
+    pub staged: PathBuf,
+    pub live: PathBuf,
+}
+
+impl Promotion {
+    /// Replace the live artifact with the staged one, returning the contents it replaced
+    pub fn promote(&self) -> Result<Option<Vec<u8>>, OrchestratorError> {
+        let previous = match std::fs::read(&self.live) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(OrchestratorError::Staging(e.to_string())),
+        };
+        atomic_replace(&self.staged, &self.live)?;
+        info!("{} is now live at {:?}", self.patch_id, self.live);
+        Ok(previous)
+    }
+
+    /// Put back the contents `promote` replaced
+    pub fn revert(&self, previous: Option<&[u8]>) -> Result<(), OrchestratorError> {
+        match previous {
+            Some(contents) => atomic_write(contents, &self.live),
+            None => match std::fs::remove_file(&self.live) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(OrchestratorError::Staging(e.to_string())),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Atomically replace `live` with the contents of `staged`
+///
+/// The data is written to a temporary file in the target directory, synced and
+/// renamed over the live file, so readers observe either the old or the new
+/// contents but never a partial write.
+pub fn atomic_replace(staged: &Path, live: &Path) -> Result<(), OrchestratorError> {
+    let contents = std::fs::read(staged)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    atomic_write(&contents, live)
+}
+
+/// Atomically replace `live` with `contents`
+pub fn atomic_write(contents: &[u8], live: &Path) -> Result<(), OrchestratorError> {
+    let directory = live.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(directory)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(directory)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    temp.write_all(contents)
+        .and_then(|_| temp.as_file().sync_all())
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+    temp.persist(live)
+        .map_err(|e| OrchestratorError::Staging(e.to_string()))?;
+
+    Ok(())
+}
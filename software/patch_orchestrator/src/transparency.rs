@@ -0,0 +1,204 @@
+//! Transparency Log Checks
+//!
+//! With a transparency policy configured, a patch is accepted only once
+//! the public transparency log has logged its payload, so a payload built
+//! for this host alone cannot be slipped in with valid release signatures.
+//! Operators fetch the patch's proof from the log and place it in the
+//! policy's proof directory as `<patch id>.json`: the
+//! `pq_types::transparency` artifact proof, plus consistency proofs from
+//! tree heads this orchestrator may already trust. Submission checks that
+//! the logged entry is this patch and version, that it names the SHA3-256
+//! digest of the payload and that the tree head is signed with the log's
+//! Dilithium3 key. The witness file then moves only along consistent
+//! trees; a tree head that forks from the one trusted is refused and
+//! raised as a critical alert.
+//!
+//! ## Biblical Foundation
+//! "A faithful witness will not lie: but a false witness will utter lies" - Proverbs 14:5
+
+use std::path::{Path, PathBuf};
+
+use pq_types::decode::{self, DecodeLimits, Validate};
+use pq_types::scheme::Dilithium3;
+use pq_types::transparency::{ArtifactKind, ArtifactProof, ConsistencyProof, LogHash, LogWitness, TransparencyError, TreeHead, MAX_PROOF_HASHES};
+use pq_types::DilithiumPublicKeyBytes;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{OrchestratorError, PatchMetadata};
+
+/// Transparency log patches must be logged in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransparencyPolicy {
+    /// Identifier the log signs its tree heads with
+    pub log_id: String,
+    /// Dilithium3 public key of the log (hex)
+    pub log_public_key: String,
+    /// Directory of `<patch id>.json` proofs
+    pub proof_directory: PathBuf,
+    /// File keeping the newest tree head accepted from the log
+    pub witness: PathBuf,
+}
+
+/// Proof file of one patch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedPatch {
+    /// Inclusion of the patch under a signed tree head
+    pub proof: ArtifactProof,
+    /// Consistency proofs between that tree head and others the witness may hold
+    #[serde(default)]
+    pub consistency: Vec<ConsistencyProof>,
+}
+
+impl Validate for LoggedPatch {
+    fn validate(&self) -> Result<(), String> {
+        self.proof.validate()?;
+        decode::check_count("consistency", self.consistency.len(), MAX_PROOF_HASHES)?;
+        self.consistency.iter().try_for_each(Validate::validate)
+    }
+}
+
+/// Check that `payload` of `metadata` is logged, returning the tree head it is logged under
+pub fn check_logged(policy: &TransparencyPolicy, metadata: &PatchMetadata, payload: &[u8]) -> Result<TreeHead, OrchestratorError> {
+    let path = policy.proof_directory.join(format!("{}.json", metadata.id));
+    let logged = load_proof(&path)?;
+    let entry = &logged.proof.entry;
+    if entry.kind != ArtifactKind::Patch || entry.name != metadata.id || entry.version != metadata.version {
+        return Err(OrchestratorError::Transparency(format!(
+            "Log entry is {} {} {}, not patch {} {}",
+            entry.kind.as_str(), entry.name, entry.version, metadata.id, metadata.version
+        )));
+    }
+
+    let log_key = hex::decode(&policy.log_public_key)
+        .ok()
+        .and_then(|key| DilithiumPublicKeyBytes::from_slice(&key).ok())
+        .ok_or_else(|| OrchestratorError::Transparency("Invalid transparency log public key".into()))?;
+    let head = logged.proof.verify::<Dilithium3>(&LogHash::digest(payload), &log_key).map_err(transparency_error)?;
+
+    let mut witness = LogWitness::load(&policy.witness, &policy.log_id)
+        .map_err(|e| OrchestratorError::Transparency(format!("Witness {:?}: {}", policy.witness, e)))?;
+    let trusted_size = witness.latest.as_ref().map(|latest| latest.size);
+    let consistency = trusted_size.and_then(|trusted| {
+        let (old, new) = (trusted.min(head.size), trusted.max(head.size));
+        logged.consistency.iter().find(|proof| proof.old_size == old && proof.new_size == new)
+    });
+    match witness.observe(head, consistency) {
+        Ok(true) => {
+            witness.save(&policy.witness)
+                .map_err(|e| OrchestratorError::Transparency(format!("Witness {:?}: {}", policy.witness, e)))?;
+            info!("Transparency log {} advanced to size {}", policy.log_id, head.size);
+        }
+        Ok(false) => {}
+        Err(e @ TransparencyError::Inconsistent { .. }) => {
+            error!("ALERT: transparency log {} presented a tree head forking from the trusted one: {}", policy.log_id, e);
+            ark_alert::raise(
+                ark_alert::Alert::new("patch_orchestrator", "transparency_fork", &policy.log_id, ark_alert::Severity::Critical,
+                                      "transparency log tree head is inconsistent with the trusted one")
+                    .with_detail("patch", &metadata.id)
+                    .with_detail("presented_size", head.size)
+                    .with_detail("trusted_size", trusted_size.unwrap_or(0)),
+            );
+            return Err(transparency_error(e));
+        }
+        Err(e) => return Err(transparency_error(e)),
+    }
+    Ok(head.clone())
+}
+
+fn load_proof(path: &Path) -> Result<LoggedPatch, OrchestratorError> {
+    let contents = std::fs::read(path)
+        .map_err(|e| OrchestratorError::Transparency(format!("No transparency proof {:?}: {}", path, e)))?;
+    decode::json_validated(&contents, &DecodeLimits::FILE)
+        .map_err(|e| OrchestratorError::Transparency(format!("{:?}: {}", path, e)))
+}
+
+fn transparency_error(e: TransparencyError) -> OrchestratorError {
+    OrchestratorError::Transparency(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CriticalityLevel, HarmAnalysis, PatchMorality, SignatureAlgorithm, VerificationStatus};
+    use cold_mirror::RiskLevel;
+    use pq_types::scheme::SignatureScheme;
+    use pq_types::transparency::{LogEntry, MerkleTree, SignedTreeHead};
+    use std::time::SystemTime;
+
+    fn metadata(id: &str) -> PatchMetadata {
+        PatchMetadata {
+            id: id.to_string(),
+            version: "1.0.0".to_string(),
+            description: "Rule pack update".to_string(),
+            component: "ethics_dsl".to_string(),
+            criticality: CriticalityLevel::Medium,
+            moral_assessment: PatchMorality::Permissible,
+            verification: VerificationStatus::Pending,
+            hash: blake3::hash(b"patched rule pack"),
+            size_bytes: 17,
+            dependencies: vec![],
+            biblical_justification: None,
+            harm_analysis: HarmAnalysis {
+                moral_harm_risk: RiskLevel::Low,
+                physical_harm_risk: RiskLevel::Low,
+                psychological_harm_risk: RiskLevel::Low,
+                spiritual_harm_risk: RiskLevel::Low,
+                system_integrity_risk: RiskLevel::Low,
+                overall_risk: RiskLevel::Low,
+                mitigation_required: false,
+                biblical_concerns: vec![],
+                overridden_concerns: vec![],
+            },
+            created_at: SystemTime::now(),
+            expires_at: None,
+            pq_signature: None,
+            classical_signature: None,
+            signature_algorithm: SignatureAlgorithm::HybridEd25519Dilithium3,
+            security_issues: Vec::new(),
+            namespace: crate::namespace::default_namespace(),
+            files: vec![],
+            supersedes: vec![],
+            emergency_scope: None,
+        }
+    }
+
+    #[test]
+    fn test_only_logged_payloads_pass() {
+        let dir = tempfile::tempdir().unwrap();
+        let (public, secret) = Dilithium3::keypair().unwrap();
+        let policy = TransparencyPolicy {
+            log_id: "ark-log".into(),
+            log_public_key: hex::encode(public.as_ref()),
+            proof_directory: dir.path().join("proofs"),
+            witness: dir.path().join("witness.json"),
+        };
+        std::fs::create_dir_all(&policy.proof_directory).unwrap();
+
+        let payload = b"patched rule pack";
+        let patch = metadata("patch-7");
+        let mut tree = MerkleTree::new();
+        tree.push(&LogEntry { kind: ArtifactKind::Model, name: "guardian".into(), version: "3".into(), digest: LogHash::digest(b"weights") });
+        let index = tree.push(&LogEntry {
+            kind: ArtifactKind::Patch,
+            name: patch.id.clone(),
+            version: patch.version.clone(),
+            digest: LogHash::digest(payload),
+        });
+        let head = TreeHead { log_id: "ark-log".into(), size: tree.len(), root: tree.root(), timestamp: 1_700_000_000 };
+        let logged = LoggedPatch {
+            proof: ArtifactProof {
+                entry: LogEntry { kind: ArtifactKind::Patch, name: patch.id.clone(), version: patch.version.clone(), digest: LogHash::digest(payload) },
+                inclusion: tree.inclusion_proof(index, tree.len()).unwrap(),
+                tree_head: SignedTreeHead::sign::<Dilithium3>(head, &secret).unwrap(),
+            },
+            consistency: Vec::new(),
+        };
+        std::fs::write(policy.proof_directory.join("patch-7.json"), serde_json::to_vec(&logged).unwrap()).unwrap();
+
+        assert_eq!(check_logged(&policy, &patch, payload).unwrap().size, 2);
+        assert_eq!(LogWitness::load(&policy.witness, "ark-log").unwrap().latest.unwrap().size, 2);
+        assert!(matches!(check_logged(&policy, &patch, b"other payload"), Err(OrchestratorError::Transparency(_))));
+        assert!(matches!(check_logged(&policy, &metadata("patch-8"), payload), Err(OrchestratorError::Transparency(_))));
+    }
+}
@@ -0,0 +1,150 @@
+//! Zeroization canaries for debug builds
+//!
+//! `CanaryAllocator` wraps the system allocator. While `watch` runs, every
+//! heap block freed is scanned for a random canary before it is released,
+//! so a secret built from the canary that is dropped, reallocated or copied
+//! without being wiped shows up as a leaked block. Stack copies are not
+//! seen. A test binary opts in by installing the allocator:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: pq_types::canary::CanaryAllocator = pq_types::canary::CanaryAllocator;
+//!
+//! let leaks = pq_types::canary::watch(|canary| {
+//!     let key = DilithiumSecretKeyBytes::from_vec(canary::fill(canary, DilithiumSecretKeyBytes::LEN)).unwrap();
+//!     drop(key);
+//! });
+//! assert_eq!(leaks, 0);
+//! ```
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use zeroize::ZeroizeOnDrop;
+
+/// Canary length in bytes
+pub const CANARY_LEN: usize = 16;
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ARMED: AtomicBool = AtomicBool::new(false);
+static CANARY: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+static LEAKS: AtomicUsize = AtomicUsize::new(0);
+static SESSION: Mutex<()> = Mutex::new(());
+
+/// System allocator that reports freed blocks still holding the canary
+pub struct CanaryAllocator;
+
+unsafe impl GlobalAlloc for CanaryAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        INSTALLED.store(true, Ordering::Relaxed);
+        // SAFETY: forwarded unchanged to the system allocator
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: the block is live and `layout.size()` bytes long until released below
+        if ARMED.load(Ordering::Acquire) && unsafe { contains_canary(ptr, layout.size()) } {
+            LEAKS.fetch_add(1, Ordering::Relaxed);
+        }
+        // SAFETY: forwarded unchanged to the system allocator
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+fn canary() -> [u8; CANARY_LEN] {
+    let mut bytes = [0u8; CANARY_LEN];
+    bytes[..8].copy_from_slice(&CANARY[0].load(Ordering::Relaxed).to_le_bytes());
+    bytes[8..].copy_from_slice(&CANARY[1].load(Ordering::Relaxed).to_le_bytes());
+    bytes
+}
+
+/// Whether the `len` bytes at `ptr` hold the canary
+///
+/// A freed block may contain bytes that were never initialized (spare
+/// capacity, padding), so it is never viewed as a `&[u8]`: each byte is read
+/// once with a volatile read into a sliding window.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads of `len` bytes.
+unsafe fn contains_canary(ptr: *const u8, len: usize) -> bool {
+    let canary = canary();
+    let mut window = [0u8; CANARY_LEN];
+    for i in 0..len {
+        window.copy_within(1.., 0);
+        // SAFETY: `i < len`, within the block the caller vouches for
+        window[CANARY_LEN - 1] = unsafe { core::ptr::read_volatile(ptr.add(i)) };
+        if i + 1 >= CANARY_LEN && window == canary {
+            return true;
+        }
+    }
+    false
+}
+
+/// Buffer of `len` bytes repeating the canary, allocated at its final size
+pub fn fill(canary: &[u8; CANARY_LEN], len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    bytes.extend(canary.iter().cycle().take(len));
+    bytes
+}
+
+/// Run `scope` with a fresh canary; returns the heap blocks freed with the canary intact
+///
+/// Sessions are serialized. Panics unless `CanaryAllocator` is the global
+/// allocator.
+pub fn watch<F: FnOnce(&[u8; CANARY_LEN])>(scope: F) -> usize {
+    let _session = SESSION.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Any allocation at all marks the allocator installed
+    drop(Vec::<u8>::with_capacity(1));
+    assert!(INSTALLED.load(Ordering::Relaxed), "CanaryAllocator is not the global allocator");
+
+    let state = std::collections::hash_map::RandomState::new();
+    for (index, word) in CANARY.iter().enumerate() {
+        let mut hasher = state.build_hasher();
+        hasher.write_usize(index);
+        word.store(hasher.finish(), Ordering::Relaxed);
+    }
+    LEAKS.store(0, Ordering::Relaxed);
+
+    ARMED.store(true, Ordering::Release);
+    scope(&canary());
+    ARMED.store(false, Ordering::Release);
+    LEAKS.load(Ordering::Relaxed)
+}
+
+/// Compile-time check that `T` wipes itself when dropped
+pub const fn assert_zeroize_on_drop<T: ZeroizeOnDrop + ?Sized>() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DilithiumSecretKeyBytes;
+
+    #[global_allocator]
+    static ALLOCATOR: CanaryAllocator = CanaryAllocator;
+
+    #[test]
+    fn test_canary_detects_unwiped_heap_copies() {
+        let leaks = watch(|canary| drop(fill(canary, 64)));
+        assert_eq!(leaks, 1);
+        // Never-written capacity is scanned without being read as a slice
+        assert_eq!(watch(|_| drop(Vec::<u8>::with_capacity(64))), 0);
+        let leaks = watch(|canary| {
+            let mut straddling = alloc::vec![0u8; 7];
+            straddling.extend_from_slice(canary);
+            drop(straddling);
+        });
+        assert_eq!(leaks, 1);
+
+        assert_zeroize_on_drop::<DilithiumSecretKeyBytes>();
+        let leaks = watch(|canary| {
+            let key = DilithiumSecretKeyBytes::from_vec(fill(canary, DilithiumSecretKeyBytes::LEN)).unwrap();
+            drop(key);
+            // A rejected buffer is wiped as well
+            assert!(DilithiumSecretKeyBytes::from_vec(fill(canary, 10)).is_err());
+        });
+        assert_eq!(leaks, 0);
+    }
+}
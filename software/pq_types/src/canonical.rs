@@ -0,0 +1,182 @@
+//! Canonical Signing Encoding - One Byte Layout per Signed Value
+//! "Thou shalt not have in thy bag divers weights, a great and a small" - Deuteronomy 25:13
+//!
+//! A signature must cover bytes that depend only on the values signed, not
+//! on how a serializer lays out a struct today: reordering or renaming
+//! fields, adding enum variants or upgrading serde or bincode must never
+//! change what was signed. A [`CanonicalEncoder`] writes a domain separator
+//! and then the fields of a value in an order fixed by its encoder, each in
+//! one layout:
+//!
+//! - integers are fixed-width little-endian, booleans one byte (0 or 1)
+//! - byte and UTF-8 strings carry a `u64` little-endian length prefix;
+//!   fixed-size values such as digests are written as they are
+//! - enum variants are written by a stable name, as a string
+//! - an option is a `0` byte, or a `1` byte followed by the value
+//! - a sequence is its `u64` length followed by each element
+//!
+//! The domain is itself length-prefixed, so no domain is a prefix of
+//! another. Encoders of signed structures live in the crates that sign
+//! them, each with a golden-vector test freezing its bytes.
+
+use alloc::vec::Vec;
+
+/// Writer of the canonical encoding of one value
+#[derive(Debug, Clone)]
+pub struct CanonicalEncoder {
+    bytes: Vec<u8>,
+}
+
+impl CanonicalEncoder {
+    /// Encoder of a value signed under `domain`
+    pub fn new(domain: &[u8]) -> Self {
+        let mut encoder = Self { bytes: Vec::with_capacity(256) };
+        encoder.bytes(domain);
+        encoder
+    }
+
+    /// Write a `u8`
+    pub fn u8(&mut self, value: u8) -> &mut Self {
+        self.bytes.push(value);
+        self
+    }
+
+    /// Write a `u32`
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Write a `u64`
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Write a `u128`
+    pub fn u128(&mut self, value: u128) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Write a boolean
+    pub fn bool(&mut self, value: bool) -> &mut Self {
+        self.u8(value as u8)
+    }
+
+    /// Write a length-prefixed byte string
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.u64(value.len() as u64);
+        self.bytes.extend_from_slice(value);
+        self
+    }
+
+    /// Write a length-prefixed UTF-8 string
+    pub fn str(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+
+    /// Write a value of fixed size, such as a digest, without a length
+    pub fn fixed(&mut self, value: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(value);
+        self
+    }
+
+    /// Write an enum variant by its stable name
+    pub fn variant(&mut self, name: &str) -> &mut Self {
+        self.str(name)
+    }
+
+    /// Write an option, with `encode` writing the value if present
+    pub fn option<T>(&mut self, value: Option<T>, encode: impl FnOnce(&mut Self, T)) -> &mut Self {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                encode(self, value);
+            }
+            None => {
+                self.u8(0);
+            }
+        }
+        self
+    }
+
+    /// Write a sequence, with `encode` writing each element
+    pub fn seq<I>(&mut self, items: I, mut encode: impl FnMut(&mut Self, I::Item)) -> &mut Self
+    where
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let items = items.into_iter();
+        self.u64(items.len() as u64);
+        for item in items {
+            encode(self, item);
+        }
+        self
+    }
+
+    /// Write a sequence of strings
+    pub fn strs<S: AsRef<str>>(&mut self, items: &[S]) -> &mut Self {
+        self.seq(items, |encoder, item| {
+            encoder.str(item.as_ref());
+        })
+    }
+
+    /// The encoded bytes
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding_is_frozen() {
+        let mut encoder = CanonicalEncoder::new(b"ARK-TEST-V1");
+        encoder
+            .u8(7)
+            .u32(0x0102_0304)
+            .u64(5)
+            .u128(1)
+            .bool(true)
+            .str("ark")
+            .fixed(&[0xAA, 0xBB])
+            .variant("Dilithium3")
+            .option(Some("x"), |encoder, value| {
+                encoder.str(value);
+            })
+            .option(None::<u64>, |encoder, value| {
+                encoder.u64(value);
+            })
+            .strs(&["a", "bc"]);
+
+        let golden: &[u8] = b"\x0b\0\0\0\0\0\0\0ARK-TEST-V1\
+            \x07\
+            \x04\x03\x02\x01\
+            \x05\0\0\0\0\0\0\0\
+            \x01\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\
+            \x01\
+            \x03\0\0\0\0\0\0\0ark\
+            \xaa\xbb\
+            \x0a\0\0\0\0\0\0\0Dilithium3\
+            \x01\x01\0\0\0\0\0\0\0x\
+            \x00\
+            \x02\0\0\0\0\0\0\0\x01\0\0\0\0\0\0\0a\x02\0\0\0\0\0\0\0bc";
+        assert_eq!(encoder.finish(), golden);
+    }
+
+    #[test]
+    fn test_lengths_keep_fields_apart() {
+        let encode = |fields: &[&str]| {
+            let mut encoder = CanonicalEncoder::new(b"ARK-TEST-V1");
+            for field in fields {
+                encoder.str(field);
+            }
+            encoder.finish()
+        };
+        assert_ne!(encode(&["ab", "c"]), encode(&["a", "bc"]));
+        assert_ne!(encode(&["", "a"]), encode(&["a", ""]));
+    }
+}
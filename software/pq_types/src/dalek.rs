@@ -0,0 +1,119 @@
+//! Ed25519 key and signature conversions for ed25519-dalek 2.x
+//!
+//! Keys and signatures were stored and exchanged in the ed25519-dalek 1.x
+//! byte layouts: a 32-byte secret key, a 32-byte public key, a 64-byte
+//! signature, and a 64-byte keypair made of the secret key followed by the
+//! public key. 2.x keeps those layouts but replaces the slice-based
+//! constructors with fixed-size arrays and `SigningKey`/`VerifyingKey`.
+//! These helpers accept the stored slices, check their lengths like the
+//! other wrappers in this crate, and return the 2.x types, so callers can
+//! migrate without changing anything on disk or on the wire.
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+use crate::{check_length, sizes, Ed25519PublicKeyBytes, Ed25519SignatureBytes, PqBytesError};
+
+fn array<const N: usize>(kind: &'static str, bytes: &[u8]) -> Result<[u8; N], PqBytesError> {
+    check_length(kind, N, bytes.len())?;
+    let mut out = [0u8; N];
+    out.copy_from_slice(bytes);
+    Ok(out)
+}
+
+/// Signing key from a 32-byte secret key (1.x `SecretKey::to_bytes`)
+pub fn signing_key_from_secret(bytes: &[u8]) -> Result<SigningKey, PqBytesError> {
+    let mut secret = array::<{ sizes::ED25519_SECRET_KEY }>("Ed25519 secret key", bytes)?;
+    let key = SigningKey::from_bytes(&secret);
+    zeroize::Zeroize::zeroize(&mut secret);
+    Ok(key)
+}
+
+/// Signing key from a 64-byte keypair (1.x `Keypair::to_bytes`)
+///
+/// The public half must match the secret half.
+pub fn signing_key_from_keypair(bytes: &[u8]) -> Result<SigningKey, PqBytesError> {
+    let mut keypair = array::<{ sizes::ED25519_KEYPAIR }>("Ed25519 keypair", bytes)?;
+    let key = SigningKey::from_keypair_bytes(&keypair)
+        .map_err(|_| PqBytesError::InvalidKey { kind: "Ed25519 keypair" });
+    zeroize::Zeroize::zeroize(&mut keypair);
+    key
+}
+
+/// 64-byte keypair in the 1.x `Keypair::to_bytes` layout
+pub fn keypair_bytes(key: &SigningKey) -> zeroize::Zeroizing<[u8; sizes::ED25519_KEYPAIR]> {
+    zeroize::Zeroizing::new(key.to_keypair_bytes())
+}
+
+/// Verifying key from a 32-byte public key (1.x `PublicKey::from_bytes`)
+pub fn verifying_key_from_bytes(bytes: &[u8]) -> Result<VerifyingKey, PqBytesError> {
+    let public = array::<{ sizes::ED25519_PUBLIC_KEY }>("Ed25519 public key", bytes)?;
+    VerifyingKey::from_bytes(&public).map_err(|_| PqBytesError::InvalidKey { kind: "Ed25519 public key" })
+}
+
+/// Signature from 64 bytes
+pub fn signature_from_bytes(bytes: &[u8]) -> Result<Signature, PqBytesError> {
+    Ok(Signature::from_bytes(&array::<{ sizes::ED25519_SIGNATURE }>("Ed25519 signature", bytes)?))
+}
+
+impl From<&VerifyingKey> for Ed25519PublicKeyBytes {
+    fn from(key: &VerifyingKey) -> Self {
+        Self(key.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<&Ed25519PublicKeyBytes> for VerifyingKey {
+    type Error = PqBytesError;
+
+    fn try_from(bytes: &Ed25519PublicKeyBytes) -> Result<Self, Self::Error> {
+        verifying_key_from_bytes(bytes.as_bytes())
+    }
+}
+
+impl From<&Signature> for Ed25519SignatureBytes {
+    fn from(signature: &Signature) -> Self {
+        Self(signature.to_bytes().to_vec())
+    }
+}
+
+impl From<&Ed25519SignatureBytes> for Signature {
+    fn from(bytes: &Ed25519SignatureBytes) -> Self {
+        let mut out = [0u8; sizes::ED25519_SIGNATURE];
+        out.copy_from_slice(bytes.as_bytes());
+        Signature::from_bytes(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, Verifier};
+
+    #[test]
+    fn test_legacy_layouts_round_trip() {
+        let key = signing_key_from_secret(&[7u8; 32]).unwrap();
+        let stored = keypair_bytes(&key);
+        assert_eq!(&stored[..32], &[7u8; 32]);
+        assert_eq!(&stored[32..], key.verifying_key().as_bytes());
+
+        let restored = signing_key_from_keypair(&stored[..]).unwrap();
+        let signature = restored.sign(b"ark");
+        let wire = Ed25519SignatureBytes::from(&signature);
+        let public = Ed25519PublicKeyBytes::from(&key.verifying_key());
+        let verifying = VerifyingKey::try_from(&public).unwrap();
+        assert!(verifying.verify(b"ark", &Signature::from(&wire)).is_ok());
+        assert_eq!(signature_from_bytes(wire.as_bytes()).unwrap(), signature);
+    }
+
+    #[test]
+    fn test_rejects_malformed_keys() {
+        assert!(matches!(signing_key_from_secret(&[7u8; 31]), Err(PqBytesError::InvalidLength { .. })));
+
+        // Public half belonging to a different secret
+        let mut mismatched = *keypair_bytes(&signing_key_from_secret(&[7u8; 32]).unwrap());
+        mismatched[32..].copy_from_slice(signing_key_from_secret(&[9u8; 32]).unwrap().verifying_key().as_bytes());
+        assert_eq!(
+            signing_key_from_keypair(&mismatched).unwrap_err(),
+            PqBytesError::InvalidKey { kind: "Ed25519 keypair" }
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Bounded Deserialization
+//!
+//! Shared entry point for decoding untrusted bincode and JSON read from the
+//! network or from disk. Every decode checks the input size before parsing,
+//! bounds bincode allocations by the same limit (default bincode options do
+//! not), rejects JSON nested deeper than allowed and trailing bytes, and runs
+//! the explicit field checks of types implementing [`Validate`] before the
+//! value is handed out.
+//!
+//! bincode is not self-describing, so its nesting is fixed by the target type;
+//! the depth limit applies to JSON.
+
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+use bincode::Options;
+use serde::de::DeserializeOwned;
+
+/// Size and nesting limits for one kind of input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Largest accepted encoded input in bytes
+    pub max_bytes: usize,
+    /// Deepest accepted JSON nesting of objects and arrays
+    pub max_depth: usize,
+}
+
+impl DecodeLimits {
+    /// Protocol messages exchanged with peers
+    pub const MESSAGE: Self = Self::new(64 * 1024, 16);
+    /// Configuration, metadata and key files
+    pub const FILE: Self = Self::new(1024 * 1024, 32);
+    /// One record of a line-oriented log or corpus
+    pub const RECORD: Self = Self::new(256 * 1024, 32);
+
+    /// Limits with the given size and depth
+    pub const fn new(max_bytes: usize, max_depth: usize) -> Self {
+        Self { max_bytes, max_depth }
+    }
+}
+
+/// Decoding failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Input exceeds the size limit
+    TooLarge {
+        /// Input size in bytes
+        size: usize,
+        /// Limit in bytes
+        max: usize,
+    },
+    /// JSON nesting exceeds the depth limit
+    TooDeep {
+        /// Limit on nesting depth
+        max: usize,
+    },
+    /// Input is not a valid encoding of the type
+    Malformed(String),
+    /// Decoded value failed field validation
+    Invalid(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooLarge { size, max } => write!(f, "input of {} bytes exceeds limit of {}", size, max),
+            DecodeError::TooDeep { max } => write!(f, "input nested deeper than {} levels", max),
+            DecodeError::Malformed(e) => write!(f, "malformed input: {}", e),
+            DecodeError::Invalid(e) => write!(f, "invalid field: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Explicit field validation run after decoding
+pub trait Validate {
+    /// Check field contents and counts, describing the first problem found
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Reject inputs larger than the limit
+pub fn check_size(size: usize, limits: &DecodeLimits) -> Result<(), DecodeError> {
+    if size > limits.max_bytes {
+        return Err(DecodeError::TooLarge { size, max: limits.max_bytes });
+    }
+    Ok(())
+}
+
+/// Reject JSON nested deeper than `max_depth`, without parsing it
+pub fn check_json_depth(bytes: &[u8], max_depth: usize) -> Result<(), DecodeError> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for &byte in bytes {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(DecodeError::TooDeep { max: max_depth });
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Decode bincode written by `bincode::serialize`, within limits
+pub fn bincode<T: DeserializeOwned>(bytes: &[u8], limits: &DecodeLimits) -> Result<T, DecodeError> {
+    check_size(bytes.len(), limits)?;
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(limits.max_bytes as u64)
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .map_err(|e| DecodeError::Malformed(format!("{}", e)))
+}
+
+/// Decode JSON within limits
+pub fn json<T: DeserializeOwned>(bytes: &[u8], limits: &DecodeLimits) -> Result<T, DecodeError> {
+    check_size(bytes.len(), limits)?;
+    check_json_depth(bytes, limits.max_depth)?;
+    serde_json::from_slice(bytes).map_err(|e| DecodeError::Malformed(format!("{}", e)))
+}
+
+/// Decode bincode within limits and validate the result
+pub fn bincode_validated<T: DeserializeOwned + Validate>(bytes: &[u8], limits: &DecodeLimits) -> Result<T, DecodeError> {
+    validated(bincode(bytes, limits)?)
+}
+
+/// Decode JSON within limits and validate the result
+pub fn json_validated<T: DeserializeOwned + Validate>(bytes: &[u8], limits: &DecodeLimits) -> Result<T, DecodeError> {
+    validated(json(bytes, limits)?)
+}
+
+/// Run field validation on a decoded value
+pub fn validated<T: Validate>(value: T) -> Result<T, DecodeError> {
+    value.validate().map_err(DecodeError::Invalid)?;
+    Ok(value)
+}
+
+/// Check a text field's length in bytes
+pub fn check_len(field: &str, value: &str, max: usize) -> Result<(), String> {
+    if value.len() > max {
+        return Err(format!("{} is {} bytes, limit {}", field, value.len(), max));
+    }
+    Ok(())
+}
+
+/// Check the number of entries in a collection field
+pub fn check_count(field: &str, count: usize, max: usize) -> Result<(), String> {
+    if count > max {
+        return Err(format!("{} has {} entries, limit {}", field, count, max));
+    }
+    Ok(())
+}
+
+/// Check an identifier that may end up in file names or log lines
+///
+/// Identifiers must be non-empty, at most `max` bytes, free of control
+/// characters and path separators, and not `.` or `..`.
+pub fn check_identifier(field: &str, value: &str, max: usize) -> Result<(), String> {
+    check_len(field, value, max)?;
+    if value.is_empty() || value == "." || value == ".." {
+        return Err(format!("{} {:?} is not a valid identifier", field, value));
+    }
+    if value.chars().any(|c| c.is_control() || c == '/' || c == '\\') {
+        return Err(format!("{} {:?} contains control characters or path separators", field, value));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Request {
+        peer_id: String,
+        tags: Vec<String>,
+    }
+
+    impl Validate for Request {
+        fn validate(&self) -> Result<(), String> {
+            check_identifier("peer_id", &self.peer_id, 64)?;
+            check_count("tags", self.tags.len(), 4)
+        }
+    }
+
+    fn request(peer_id: &str) -> Request {
+        Request { peer_id: peer_id.into(), tags: Vec::new() }
+    }
+
+    #[test]
+    fn test_bincode_round_trip_and_bounds() {
+        let limits = DecodeLimits::new(128, 8);
+        let encoded = ::bincode::serialize(&request("sensor-1")).unwrap();
+        assert_eq!(bincode_validated::<Request>(&encoded, &limits).unwrap(), request("sensor-1"));
+
+        // A length prefix claiming a huge vector fails on the limit, not on allocation
+        let mut hostile = encoded.clone();
+        let tags_len = hostile.len() - 8;
+        hostile[tags_len..].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(bincode::<Request>(&hostile, &limits), Err(DecodeError::Malformed(_))));
+
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(bincode::<Request>(&trailing, &limits).is_err());
+        assert!(matches!(
+            bincode::<Request>(&[0u8; 129], &limits),
+            Err(DecodeError::TooLarge { size: 129, max: 128 })
+        ));
+    }
+
+    #[test]
+    fn test_json_depth_ignores_brackets_in_strings() {
+        assert!(check_json_depth(br#"{"a":"[[[[[[[[\"]]]"}"#, 1).is_ok());
+        assert_eq!(check_json_depth(b"[[[1]]]", 2), Err(DecodeError::TooDeep { max: 2 }));
+
+        let deep: Vec<u8> = core::iter::repeat_n(b'[', 10_000).collect();
+        assert!(matches!(json::<Request>(&deep, &DecodeLimits::MESSAGE), Err(DecodeError::TooDeep { .. })));
+    }
+
+    #[test]
+    fn test_field_validation() {
+        let limits = DecodeLimits::MESSAGE;
+        assert!(json_validated::<Request>(br#"{"peer_id":"ok","tags":[]}"#, &limits).is_ok());
+        for bad in [
+            &br#"{"peer_id":"../etc","tags":[]}"#[..],
+            br#"{"peer_id":"","tags":[]}"#,
+            br#"{"peer_id":"a","tags":["1","2","3","4","5"]}"#,
+        ] {
+            assert!(matches!(json_validated::<Request>(bad, &limits), Err(DecodeError::Invalid(_))));
+        }
+    }
+}
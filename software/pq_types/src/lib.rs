@@ -0,0 +1,475 @@
+//! ARK Post-Quantum Byte Types
+//! "Let your yes be yes and your no be no" - Matthew 5:37
+//!
+//! Typed wrappers for post-quantum (and hybrid classical) keys, ciphertexts and
+//! signatures, covering both the round-3 Kyber/Dilithium parameter sets and
+//! their FIPS 203/204 standardized successors ML-KEM/ML-DSA. Every wrapper validates its length on construction and on
+//! deserialization, so malformed inputs are rejected at the boundary instead of
+//! failing deep inside pqcrypto. Secret material is zeroized on drop.
+//!
+//! With the `decode` feature, [`decode`] provides the bounded bincode/JSON
+//! decoding used for every network- and disk-facing input, and with `pins`
+//! [`pins`] adds hybrid key fingerprints and the pinned-peer file. With
+//! `transparency`, [`transparency`] verifies that models, rule packs and
+//! patches are logged in the append-only transparency log. With the `dalek`
+//! feature, [`dalek`] converts stored Ed25519 keys and signatures to the
+//! ed25519-dalek 2.x types. [`scheme`] abstracts the KEM and signature
+//! primitives over the backend picked by the `pqcrypto`, `liboqs` or
+//! `pure-rust` feature. In debug builds with `std`, [`canary`] lets tests
+//! check that dropped secrets leave no heap copies behind. [`canonical`]
+//! is the byte layout signed structures are encoded in for signing.
+
+#![no_std]
+#![deny(missing_docs)]
+#![warn(clippy::all)]
+
+extern crate alloc;
+
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(all(feature = "std", debug_assertions))]
+pub mod canary;
+pub mod canonical;
+#[cfg(feature = "dalek")]
+pub mod dalek;
+#[cfg(feature = "decode")]
+pub mod decode;
+#[cfg(feature = "pins")]
+pub mod pins;
+pub mod scheme;
+#[cfg(feature = "transparency")]
+pub mod transparency;
+
+/// Byte sizes of the supported algorithms
+pub mod sizes {
+    /// Dilithium3 public key
+    pub const DILITHIUM3_PUBLIC_KEY: usize = 1952;
+    /// Dilithium3 secret key
+    pub const DILITHIUM3_SECRET_KEY: usize = 4000;
+    /// Dilithium3 detached signature
+    pub const DILITHIUM3_SIGNATURE: usize = 3293;
+    /// Kyber768 public key
+    pub const KYBER768_PUBLIC_KEY: usize = 1184;
+    /// Kyber768 secret key
+    pub const KYBER768_SECRET_KEY: usize = 2400;
+    /// Kyber768 ciphertext
+    pub const KYBER768_CIPHERTEXT: usize = 1088;
+    /// Kyber768 shared secret
+    pub const KYBER768_SHARED_SECRET: usize = 32;
+    /// Shared secret of every supported KEM
+    pub const KEM_SHARED_SECRET: usize = 32;
+    /// ML-KEM-768 encapsulation key (FIPS 203)
+    pub const ML_KEM_768_PUBLIC_KEY: usize = 1184;
+    /// ML-KEM-768 decapsulation key (FIPS 203)
+    pub const ML_KEM_768_SECRET_KEY: usize = 2400;
+    /// ML-KEM-768 ciphertext (FIPS 203)
+    pub const ML_KEM_768_CIPHERTEXT: usize = 1088;
+    /// ML-DSA-65 public key (FIPS 204)
+    pub const ML_DSA_65_PUBLIC_KEY: usize = 1952;
+    /// ML-DSA-65 secret key (FIPS 204)
+    pub const ML_DSA_65_SECRET_KEY: usize = 4032;
+    /// ML-DSA-65 signature (FIPS 204)
+    pub const ML_DSA_65_SIGNATURE: usize = 3309;
+    /// Ed25519 secret key
+    pub const ED25519_SECRET_KEY: usize = 32;
+    /// Ed25519 public key
+    pub const ED25519_PUBLIC_KEY: usize = 32;
+    /// Ed25519 keypair (secret key followed by public key)
+    pub const ED25519_KEYPAIR: usize = 64;
+    /// Ed25519 signature
+    pub const ED25519_SIGNATURE: usize = 64;
+    /// X25519 public key
+    pub const X25519_PUBLIC_KEY: usize = 32;
+}
+
+/// Validation errors for post-quantum byte wrappers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PqBytesError {
+    /// Input length does not match the algorithm's fixed size
+    InvalidLength {
+        /// Human-readable type name
+        kind: &'static str,
+        /// Required length in bytes
+        expected: usize,
+        /// Length that was supplied
+        actual: usize,
+    },
+    /// Input has the right length but is not a valid key
+    InvalidKey {
+        /// Human-readable type name
+        kind: &'static str,
+    },
+}
+
+impl fmt::Display for PqBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PqBytesError::InvalidLength { kind, expected, actual } => write!(
+                f,
+                "Invalid {} length: expected {} bytes, got {}",
+                kind, expected, actual
+            ),
+            PqBytesError::InvalidKey { kind } => write!(f, "Invalid {}", kind),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PqBytesError {}
+
+fn check_length(kind: &'static str, expected: usize, actual: usize) -> Result<(), PqBytesError> {
+    if expected != actual {
+        return Err(PqBytesError::InvalidLength { kind, expected, actual });
+    }
+    Ok(())
+}
+
+/// Public (non-secret) fixed-length byte wrapper
+macro_rules! pq_public_bytes {
+    ($(#[$meta:meta])* $name:ident, $kind:expr, $len:expr) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+        #[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+        pub struct $name(Vec<u8>);
+
+        impl $name {
+            /// Required length in bytes
+            pub const LEN: usize = $len;
+
+            /// Validate and wrap an owned buffer
+            pub fn from_vec(bytes: Vec<u8>) -> Result<Self, PqBytesError> {
+                check_length($kind, Self::LEN, bytes.len())?;
+                Ok(Self(bytes))
+            }
+
+            /// Validate and copy a slice
+            pub fn from_slice(bytes: &[u8]) -> Result<Self, PqBytesError> {
+                check_length($kind, Self::LEN, bytes.len())?;
+                Ok(Self(bytes.to_vec()))
+            }
+
+            /// Borrow the raw bytes
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+
+            /// Unwrap into the raw buffer
+            pub fn into_vec(self) -> Vec<u8> {
+                self.0
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for $name {
+            type Error = PqBytesError;
+
+            fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+                Self::from_vec(bytes)
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = PqBytesError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                Self::from_slice(bytes)
+            }
+        }
+
+        impl From<$name> for Vec<u8> {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl From<$name> for [u8; $len] {
+            fn from(value: $name) -> Self {
+                let mut out = [0u8; $len];
+                out.copy_from_slice(&value.0);
+                out
+            }
+        }
+
+        impl Deref for $name {
+            type Target = [u8];
+
+            fn deref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                for byte in self.0.iter().take(8) {
+                    write!(f, "{:02x}", byte)?;
+                }
+                write!(f, "..; {} bytes)", self.0.len())
+            }
+        }
+    };
+}
+
+/// Secret fixed-length byte wrapper - zeroized on drop, never serialized
+macro_rules! pq_secret_bytes {
+    ($(#[$meta:meta])* $name:ident, $kind:expr, $len:expr) => {
+        $(#[$meta])*
+        #[derive(Zeroize, ZeroizeOnDrop, Deserialize)]
+        #[serde(try_from = "Vec<u8>")]
+        pub struct $name(Vec<u8>);
+
+        impl $name {
+            /// Required length in bytes
+            pub const LEN: usize = $len;
+
+            /// Validate and wrap an owned buffer (zeroized if rejected)
+            pub fn from_vec(mut bytes: Vec<u8>) -> Result<Self, PqBytesError> {
+                if let Err(e) = check_length($kind, Self::LEN, bytes.len()) {
+                    bytes.zeroize();
+                    return Err(e);
+                }
+                Ok(Self(bytes))
+            }
+
+            /// Validate and copy a slice
+            pub fn from_slice(bytes: &[u8]) -> Result<Self, PqBytesError> {
+                check_length($kind, Self::LEN, bytes.len())?;
+                Ok(Self(bytes.to_vec()))
+            }
+
+            /// Borrow the raw secret bytes
+            pub fn expose_secret(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for $name {
+            type Error = PqBytesError;
+
+            fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+                Self::from_vec(bytes)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}([REDACTED]; {} bytes)", stringify!($name), self.0.len())
+            }
+        }
+    };
+}
+
+pq_public_bytes!(
+    /// Dilithium3 public key
+    DilithiumPublicKeyBytes, "Dilithium3 public key", sizes::DILITHIUM3_PUBLIC_KEY
+);
+pq_public_bytes!(
+    /// Dilithium3 detached signature
+    DilithiumSignatureBytes, "Dilithium3 signature", sizes::DILITHIUM3_SIGNATURE
+);
+pq_public_bytes!(
+    /// Kyber768 public key
+    KyberPublicKeyBytes, "Kyber768 public key", sizes::KYBER768_PUBLIC_KEY
+);
+pq_public_bytes!(
+    /// Kyber768 encapsulation ciphertext
+    KyberCiphertextBytes, "Kyber768 ciphertext", sizes::KYBER768_CIPHERTEXT
+);
+pq_public_bytes!(
+    /// ML-KEM-768 encapsulation key
+    MlKemPublicKeyBytes, "ML-KEM-768 encapsulation key", sizes::ML_KEM_768_PUBLIC_KEY
+);
+pq_public_bytes!(
+    /// ML-KEM-768 ciphertext
+    MlKemCiphertextBytes, "ML-KEM-768 ciphertext", sizes::ML_KEM_768_CIPHERTEXT
+);
+pq_public_bytes!(
+    /// ML-DSA-65 public key
+    MlDsaPublicKeyBytes, "ML-DSA-65 public key", sizes::ML_DSA_65_PUBLIC_KEY
+);
+pq_public_bytes!(
+    /// ML-DSA-65 detached signature
+    MlDsaSignatureBytes, "ML-DSA-65 signature", sizes::ML_DSA_65_SIGNATURE
+);
+pq_public_bytes!(
+    /// Ed25519 public key
+    Ed25519PublicKeyBytes, "Ed25519 public key", sizes::ED25519_PUBLIC_KEY
+);
+pq_public_bytes!(
+    /// Ed25519 signature
+    Ed25519SignatureBytes, "Ed25519 signature", sizes::ED25519_SIGNATURE
+);
+pq_public_bytes!(
+    /// X25519 public key
+    X25519PublicKeyBytes, "X25519 public key", sizes::X25519_PUBLIC_KEY
+);
+
+pq_secret_bytes!(
+    /// Dilithium3 secret key
+    DilithiumSecretKeyBytes, "Dilithium3 secret key", sizes::DILITHIUM3_SECRET_KEY
+);
+pq_secret_bytes!(
+    /// Kyber768 secret key
+    KyberSecretKeyBytes, "Kyber768 secret key", sizes::KYBER768_SECRET_KEY
+);
+pq_secret_bytes!(
+    /// ML-KEM-768 decapsulation key
+    MlKemSecretKeyBytes, "ML-KEM-768 decapsulation key", sizes::ML_KEM_768_SECRET_KEY
+);
+pq_secret_bytes!(
+    /// ML-DSA-65 secret key
+    MlDsaSecretKeyBytes, "ML-DSA-65 secret key", sizes::ML_DSA_65_SECRET_KEY
+);
+pq_secret_bytes!(
+    /// Kyber768 shared secret
+    KyberSharedSecretBytes, "Kyber768 shared secret", sizes::KYBER768_SHARED_SECRET
+);
+
+/// Detached post-quantum signature of either supported scheme
+///
+/// Serialized as a plain byte vector, exactly like `DilithiumSignatureBytes`,
+/// so records that carried a Dilithium3 signature keep their encoding; the
+/// algorithm is recorded next to the signature, not in it. Only Dilithium3
+/// and ML-DSA-65 lengths are accepted; convert to the specific wrapper
+/// before verifying.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<u8>", into = "Vec<u8>")]
+pub struct PqSignatureBytes(Vec<u8>);
+
+impl PqSignatureBytes {
+    /// Validate and wrap an owned buffer
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Self, PqBytesError> {
+        if bytes.len() != sizes::ML_DSA_65_SIGNATURE {
+            check_length("post-quantum signature", sizes::DILITHIUM3_SIGNATURE, bytes.len())?;
+        }
+        Ok(Self(bytes))
+    }
+
+    /// Validate and copy a slice
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, PqBytesError> {
+        Self::from_vec(bytes.to_vec())
+    }
+
+    /// Borrow the raw bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// As a Dilithium3 signature
+    pub fn dilithium(&self) -> Result<DilithiumSignatureBytes, PqBytesError> {
+        DilithiumSignatureBytes::from_slice(&self.0)
+    }
+
+    /// As an ML-DSA-65 signature
+    pub fn ml_dsa(&self) -> Result<MlDsaSignatureBytes, PqBytesError> {
+        MlDsaSignatureBytes::from_slice(&self.0)
+    }
+}
+
+impl TryFrom<Vec<u8>> for PqSignatureBytes {
+    type Error = PqBytesError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::from_vec(bytes)
+    }
+}
+
+impl From<PqSignatureBytes> for Vec<u8> {
+    fn from(value: PqSignatureBytes) -> Self {
+        value.0
+    }
+}
+
+impl From<DilithiumSignatureBytes> for PqSignatureBytes {
+    fn from(value: DilithiumSignatureBytes) -> Self {
+        Self(value.into_vec())
+    }
+}
+
+impl From<MlDsaSignatureBytes> for PqSignatureBytes {
+    fn from(value: MlDsaSignatureBytes) -> Self {
+        Self(value.into_vec())
+    }
+}
+
+impl Deref for PqSignatureBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for PqSignatureBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PqSignatureBytes({} bytes)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_length_validation() {
+        assert!(DilithiumSignatureBytes::from_vec(vec![0u8; sizes::DILITHIUM3_SIGNATURE]).is_ok());
+
+        let err = DilithiumSignatureBytes::from_vec(vec![0u8; 10]).unwrap_err();
+        assert_eq!(
+            err,
+            PqBytesError::InvalidLength {
+                kind: "Dilithium3 signature",
+                expected: sizes::DILITHIUM3_SIGNATURE,
+                actual: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_serde_rejects_malformed_input() {
+        let sig = Ed25519SignatureBytes::from_slice(&[7u8; 64]).unwrap();
+        let json = serde_json::to_string(&sig).unwrap();
+        let back: Ed25519SignatureBytes = serde_json::from_str(&json).unwrap();
+        assert_eq!(sig, back);
+
+        let truncated = serde_json::to_string(&vec![7u8; 63]).unwrap();
+        assert!(serde_json::from_str::<Ed25519SignatureBytes>(&truncated).is_err());
+
+        let encoded = bincode::serialize(&vec![1u8; 12]).unwrap();
+        assert!(bincode::deserialize::<KyberCiphertextBytes>(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_pq_signature_accepts_both_schemes() {
+        let dilithium = DilithiumSignatureBytes::from_vec(vec![1u8; sizes::DILITHIUM3_SIGNATURE]).unwrap();
+        let wrapped = PqSignatureBytes::from(dilithium.clone());
+        assert_eq!(bincode::serialize(&wrapped).unwrap(), bincode::serialize(&dilithium).unwrap());
+        assert_eq!(wrapped.dilithium().unwrap(), dilithium);
+        assert!(wrapped.ml_dsa().is_err());
+
+        let ml_dsa = PqSignatureBytes::from_vec(vec![2u8; sizes::ML_DSA_65_SIGNATURE]).unwrap();
+        assert!(ml_dsa.ml_dsa().is_ok());
+        assert!(PqSignatureBytes::from_vec(vec![0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn test_secret_debug_is_redacted() {
+        let secret = KyberSharedSecretBytes::from_slice(&[0xAB; 32]).unwrap();
+        let rendered = std::format!("{:?}", secret);
+        assert!(rendered.contains("REDACTED"));
+        assert!(!rendered.contains("abab"));
+    }
+}
@@ -0,0 +1,249 @@
+//! Key Fingerprints and Pinning
+//!
+//! A hybrid key fingerprint is the SHA3-256 hash of an Ed25519 public key
+//! and a Dilithium3 public key, length-prefixed under a domain tag, so the
+//! orchestrator and the sentinel print the same fingerprint for the same
+//! key pair. A [`PinStore`] is the pinned-peer file: a JSON map from peer
+//! name to the fingerprint expected of it. Callers check presented keys
+//! with [`PinStore::check`] before trusting a handshake or signature and
+//! alert on [`PinCheck::Changed`].
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+
+use crate::decode::{self, DecodeLimits, Validate};
+
+/// Domain tag of hybrid key fingerprints
+const FINGERPRINT_DOMAIN: &[u8] = b"ARK-HYBRID-KEY-FINGERPRINT-V1";
+
+/// Most peers in one pin file
+pub const MAX_PINS: usize = 4096;
+
+/// Longest peer name in a pin file
+pub const MAX_PEER_NAME: usize = 128;
+
+/// SHA3-256 fingerprint of an Ed25519 + Dilithium3 public key pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct KeyFingerprint([u8; 32]);
+
+impl KeyFingerprint {
+    /// Fingerprint of a hybrid key pair
+    pub fn hybrid(ed25519_public: &[u8], dilithium_public: &[u8]) -> Self {
+        let mut hasher = Sha3_256::new();
+        hasher.update(FINGERPRINT_DOMAIN);
+        for key in [ed25519_public, dilithium_public] {
+            hasher.update((key.len() as u64).to_le_bytes());
+            hasher.update(key);
+        }
+        Self(hasher.finalize().into())
+    }
+
+    /// Fingerprint bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for KeyFingerprint {
+    /// Lowercase hex in colon-separated groups of four bytes
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, chunk) in self.0.chunks(4).enumerate() {
+            if index > 0 {
+                f.write_str(":")?;
+            }
+            for byte in chunk {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeyFingerprint {
+    type Err = String;
+
+    /// Parse 64 hex digits, with or without colons
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits: String = s.chars().filter(|c| *c != ':').collect();
+        if digits.len() != 64 || !digits.is_ascii() {
+            return Err(format!("fingerprint must be 64 hex digits, got {:?}", s));
+        }
+        let mut bytes = [0u8; 32];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[2 * index..2 * index + 2], 16)
+                .map_err(|_| format!("fingerprint is not hex: {:?}", s))?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for KeyFingerprint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyFingerprint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Outcome of checking presented keys against the pins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinCheck {
+    /// The peer is pinned to the presented fingerprint
+    Match,
+    /// The peer is not pinned
+    Unpinned,
+    /// The peer is pinned to a different fingerprint
+    Changed {
+        /// Pinned fingerprint
+        pinned: KeyFingerprint,
+        /// Fingerprint of the presented keys
+        presented: KeyFingerprint,
+    },
+}
+
+impl PinCheck {
+    /// Whether the keys may be used: matching, or for a peer without a pin
+    pub fn is_acceptable(&self) -> bool {
+        !matches!(self, PinCheck::Changed { .. })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PinFile {
+    #[serde(default)]
+    pins: BTreeMap<String, KeyFingerprint>,
+}
+
+impl Validate for PinFile {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("pins", self.pins.len(), MAX_PINS)?;
+        for peer in self.pins.keys() {
+            decode::check_identifier("peer", peer, MAX_PEER_NAME)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pinned-peer file
+#[derive(Debug, Default)]
+pub struct PinStore {
+    path: Option<PathBuf>,
+    pins: BTreeMap<String, KeyFingerprint>,
+}
+
+impl PinStore {
+    /// Pins held in memory only
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the pin file at `path`; a missing file holds no pins
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let pins = match std::fs::read(path) {
+            Ok(bytes) => decode::json_validated::<PinFile>(&bytes, &DecodeLimits::FILE)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?
+                .pins,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path: Some(path.to_path_buf()), pins })
+    }
+
+    /// Write the pins back to the file they were loaded from
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        let file = PinFile { pins: self.pins.clone() };
+        let json = serde_json::to_vec_pretty(&file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let staged = path.with_extension("tmp");
+        std::fs::write(&staged, json)?;
+        std::fs::rename(&staged, path)
+    }
+
+    /// Pin `peer` to `fingerprint`, returning the fingerprint it replaces
+    pub fn pin(&mut self, peer: &str, fingerprint: KeyFingerprint) -> Result<Option<KeyFingerprint>, String> {
+        decode::check_identifier("peer", peer, MAX_PEER_NAME)?;
+        if !self.pins.contains_key(peer) {
+            decode::check_count("pins", self.pins.len() + 1, MAX_PINS)?;
+        }
+        Ok(self.pins.insert(peer.to_string(), fingerprint))
+    }
+
+    /// Remove the pin of `peer`
+    pub fn unpin(&mut self, peer: &str) -> Option<KeyFingerprint> {
+        self.pins.remove(peer)
+    }
+
+    /// Fingerprint `peer` is pinned to
+    pub fn get(&self, peer: &str) -> Option<KeyFingerprint> {
+        self.pins.get(peer).copied()
+    }
+
+    /// Pinned peers and their fingerprints, by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, KeyFingerprint)> {
+        self.pins.iter().map(|(peer, fingerprint)| (peer.as_str(), *fingerprint))
+    }
+
+    /// Check the fingerprint `peer` presented
+    pub fn check(&self, peer: &str, presented: KeyFingerprint) -> PinCheck {
+        match self.pins.get(peer) {
+            None => PinCheck::Unpinned,
+            Some(pinned) if *pinned == presented => PinCheck::Match,
+            Some(pinned) => PinCheck::Changed { pinned: *pinned, presented },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_binds_both_keys_and_round_trips() {
+        let fingerprint = KeyFingerprint::hybrid(&[1u8; 32], &[2u8; 1952]);
+        assert_ne!(fingerprint, KeyFingerprint::hybrid(&[1u8; 32], &[3u8; 1952]));
+        assert_ne!(fingerprint, KeyFingerprint::hybrid(&[9u8; 32], &[2u8; 1952]));
+
+        let text = fingerprint.to_string();
+        assert_eq!(text.len(), 64 + 7);
+        assert_eq!(text.parse::<KeyFingerprint>(), Ok(fingerprint));
+        assert_eq!(text.replace(':', "").parse::<KeyFingerprint>(), Ok(fingerprint));
+        assert!("abcd".parse::<KeyFingerprint>().is_err());
+    }
+
+    #[test]
+    fn test_pins_persist_and_detect_changes() {
+        let path = std::env::temp_dir().join(format!("ark-pins-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let original = KeyFingerprint::hybrid(&[1u8; 32], &[2u8; 1952]);
+        let rotated = KeyFingerprint::hybrid(&[1u8; 32], &[4u8; 1952]);
+
+        let mut pins = PinStore::load(&path).unwrap();
+        assert_eq!(pins.check("release", original), PinCheck::Unpinned);
+        assert_eq!(pins.pin("release", original), Ok(None));
+        assert!(pins.pin("../etc", original).is_err());
+        pins.save().unwrap();
+
+        let pins = PinStore::load(&path).unwrap();
+        assert_eq!(pins.check("release", original), PinCheck::Match);
+        let check = pins.check("release", rotated);
+        assert_eq!(check, PinCheck::Changed { pinned: original, presented: rotated });
+        assert!(!check.is_acceptable());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
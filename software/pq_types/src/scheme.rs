@@ -0,0 +1,180 @@
+//! Post-quantum KEM and signature scheme abstraction
+//!
+//! [`Kem`] and [`SignatureScheme`] describe the operations the rest of ARK
+//! needs from a post-quantum primitive in terms of this crate's byte
+//! wrappers, so keys, ciphertexts and signatures look the same whichever
+//! library produced them. Each backend implements the traits for its own
+//! marker types:
+//!
+//! - `pqcrypto` - PQClean via the pqcrypto crates (no_std capable; the default
+//!   downstream)
+//! - `liboqs` - Open Quantum Safe's liboqs (std only)
+//! - `pure-rust` - RustCrypto's `ml-kem`/`ml-dsa`; only the standardized
+//!   ML-KEM-768 and ML-DSA-65, so it is combined with one of the others when
+//!   the round-3 Kyber768/Dilithium3 sets are still needed
+//!
+//! The markers re-exported here ([`Kyber768`], [`MlKem768`], [`Dilithium3`],
+//! [`MlDsa65`]) resolve to the most specific backend enabled: `pure-rust`,
+//! then `liboqs`, then `pqcrypto`. Callers name only these re-exports, so
+//! switching library is a feature change rather than a code change.
+
+use core::fmt;
+use zeroize::Zeroizing;
+
+use crate::{sizes, PqBytesError};
+
+#[cfg(feature = "liboqs")]
+pub mod liboqs;
+#[cfg(feature = "pqcrypto")]
+pub mod pqcrypto;
+#[cfg(feature = "pure-rust")]
+pub mod rustcrypto;
+
+#[cfg(all(feature = "pqcrypto", not(feature = "liboqs")))]
+pub use self::pqcrypto::{Dilithium3, Kyber768};
+#[cfg(feature = "liboqs")]
+pub use self::liboqs::{Dilithium3, Kyber768};
+
+#[cfg(all(feature = "pqcrypto", not(any(feature = "liboqs", feature = "pure-rust"))))]
+pub use self::pqcrypto::{MlDsa65, MlKem768};
+#[cfg(all(feature = "liboqs", not(feature = "pure-rust")))]
+pub use self::liboqs::{MlDsa65, MlKem768};
+#[cfg(feature = "pure-rust")]
+pub use self::rustcrypto::{MlDsa65, MlKem768};
+
+/// Shared secret agreed by a [`Kem`]; every supported KEM derives 32 bytes
+pub type SharedSecret = Zeroizing<[u8; sizes::KEM_SHARED_SECRET]>;
+
+/// Errors from a scheme backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemeError {
+    /// Key, ciphertext or signature rejected before use
+    Bytes(PqBytesError),
+    /// Backend failed to perform the operation
+    Backend {
+        /// Scheme name
+        scheme: &'static str,
+    },
+    /// Signature does not verify
+    VerificationFailed,
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemeError::Bytes(e) => write!(f, "{}", e),
+            SchemeError::Backend { scheme } => write!(f, "{} backend failure", scheme),
+            SchemeError::VerificationFailed => write!(f, "Signature verification failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SchemeError {}
+
+impl From<PqBytesError> for SchemeError {
+    fn from(e: PqBytesError) -> Self {
+        SchemeError::Bytes(e)
+    }
+}
+
+/// Key encapsulation mechanism
+pub trait Kem {
+    /// Algorithm name
+    const NAME: &'static str;
+
+    /// Encapsulation key
+    type PublicKey: Clone + AsRef<[u8]>;
+    /// Decapsulation key
+    type SecretKey;
+    /// Encapsulation ciphertext
+    type Ciphertext: Clone + AsRef<[u8]>;
+
+    /// Fresh keypair
+    fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError>;
+
+    /// Shared secret and the ciphertext that carries it to `public_key`'s owner
+    fn encapsulate(public_key: &Self::PublicKey) -> Result<(SharedSecret, Self::Ciphertext), SchemeError>;
+
+    /// Shared secret carried by `ciphertext`
+    fn decapsulate(ciphertext: &Self::Ciphertext, secret_key: &Self::SecretKey) -> Result<SharedSecret, SchemeError>;
+}
+
+/// Detached signature scheme
+pub trait SignatureScheme {
+    /// Algorithm name
+    const NAME: &'static str;
+
+    /// Verification key
+    type PublicKey: Clone + AsRef<[u8]>;
+    /// Signing key
+    type SecretKey;
+    /// Detached signature
+    type Signature: Clone + AsRef<[u8]>;
+
+    /// Fresh keypair
+    fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError>;
+
+    /// Sign `message`
+    fn sign(message: &[u8], secret_key: &Self::SecretKey) -> Result<Self::Signature, SchemeError>;
+
+    /// Check `signature` over `message`
+    fn verify(message: &[u8], signature: &Self::Signature, public_key: &Self::PublicKey) -> Result<(), SchemeError>;
+}
+
+/// Copy a backend's shared secret into a [`SharedSecret`]
+#[cfg_attr(not(any(feature = "pqcrypto", feature = "liboqs", feature = "pure-rust")), allow(dead_code))]
+pub(crate) fn shared_secret(scheme: &'static str, bytes: &[u8]) -> Result<SharedSecret, SchemeError> {
+    if bytes.len() != sizes::KEM_SHARED_SECRET {
+        return Err(SchemeError::Backend { scheme });
+    }
+    let mut secret = Zeroizing::new([0u8; sizes::KEM_SHARED_SECRET]);
+    secret.copy_from_slice(bytes);
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Round trip any KEM backend through the trait
+    #[allow(dead_code)]
+    pub(crate) fn kem_round_trip<K: Kem>() {
+        let (public, secret) = K::keypair().unwrap();
+        let (sent, ciphertext) = K::encapsulate(&public).unwrap();
+        assert_eq!(*K::decapsulate(&ciphertext, &secret).unwrap(), *sent);
+
+        let (other_public, _) = K::keypair().unwrap();
+        let (_, other_ciphertext) = K::encapsulate(&other_public).unwrap();
+        assert_ne!(K::decapsulate(&other_ciphertext, &secret).ok().map(|s| *s), Some(*sent));
+    }
+
+    /// Round trip any signature backend through the trait
+    #[allow(dead_code)]
+    pub(crate) fn signature_round_trip<S: SignatureScheme>() {
+        let (public, secret) = S::keypair().unwrap();
+        let signature = S::sign(b"ark", &secret).unwrap();
+        assert!(S::verify(b"ark", &signature, &public).is_ok());
+        assert_eq!(S::verify(b"arc", &signature, &public), Err(SchemeError::VerificationFailed));
+    }
+
+    #[test]
+    fn test_shared_secret_length_checked() {
+        assert!(shared_secret("test", &[1u8; 32]).is_ok());
+        assert_eq!(shared_secret("test", &[1u8; 31]), Err(SchemeError::Backend { scheme: "test" }));
+    }
+
+    #[cfg(any(feature = "pqcrypto", feature = "liboqs"))]
+    #[test]
+    fn test_round3_schemes_round_trip() {
+        kem_round_trip::<Kyber768>();
+        signature_round_trip::<Dilithium3>();
+    }
+
+    #[cfg(any(feature = "pqcrypto", feature = "liboqs", feature = "pure-rust"))]
+    #[test]
+    fn test_standardized_schemes_round_trip() {
+        kem_round_trip::<MlKem768>();
+        signature_round_trip::<MlDsa65>();
+    }
+}
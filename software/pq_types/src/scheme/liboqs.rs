@@ -0,0 +1,132 @@
+//! Open Quantum Safe backend via the oqs crate
+
+use oqs::{kem, sig};
+
+use super::{shared_secret, Kem, SchemeError, SharedSecret, SignatureScheme};
+use crate::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, KyberCiphertextBytes,
+    KyberPublicKeyBytes, KyberSecretKeyBytes, MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes,
+    MlKemCiphertextBytes, MlKemPublicKeyBytes, MlKemSecretKeyBytes, PqBytesError,
+};
+
+fn backend(scheme: &'static str) -> impl Fn(oqs::Error) -> SchemeError {
+    move |_| SchemeError::Backend { scheme }
+}
+
+macro_rules! oqs_kem {
+    ($(#[$meta:meta])* $name:ident, $algorithm:ident, $label:expr, $public:ty, $secret:ty, $ciphertext:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl $name {
+            fn instance() -> Result<kem::Kem, SchemeError> {
+                oqs::init();
+                kem::Kem::new(kem::Algorithm::$algorithm).map_err(backend($label))
+            }
+        }
+
+        impl Kem for $name {
+            const NAME: &'static str = $label;
+
+            type PublicKey = $public;
+            type SecretKey = $secret;
+            type Ciphertext = $ciphertext;
+
+            fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+                let (public, secret) = Self::instance()?.keypair().map_err(backend($label))?;
+                Ok((<$public>::from_slice(public.as_ref())?, <$secret>::from_vec(secret.into_vec())?))
+            }
+
+            fn encapsulate(public_key: &Self::PublicKey) -> Result<(SharedSecret, Self::Ciphertext), SchemeError> {
+                let kem = Self::instance()?;
+                let public = kem
+                    .public_key_from_bytes(public_key.as_bytes())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                let (ciphertext, shared) = kem.encapsulate(public).map_err(backend($label))?;
+                Ok((shared_secret($label, shared.as_ref())?, <$ciphertext>::from_slice(ciphertext.as_ref())?))
+            }
+
+            fn decapsulate(ciphertext: &Self::Ciphertext, secret_key: &Self::SecretKey) -> Result<SharedSecret, SchemeError> {
+                let kem = Self::instance()?;
+                let secret = kem
+                    .secret_key_from_bytes(secret_key.expose_secret())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                let ciphertext = kem
+                    .ciphertext_from_bytes(ciphertext.as_bytes())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                let shared = kem.decapsulate(secret, ciphertext).map_err(backend($label))?;
+                shared_secret($label, shared.as_ref())
+            }
+        }
+    };
+}
+
+macro_rules! oqs_signature {
+    ($(#[$meta:meta])* $name:ident, $algorithm:ident, $label:expr, $public:ty, $secret:ty, $signature:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl $name {
+            fn instance() -> Result<sig::Sig, SchemeError> {
+                oqs::init();
+                sig::Sig::new(sig::Algorithm::$algorithm).map_err(backend($label))
+            }
+        }
+
+        impl SignatureScheme for $name {
+            const NAME: &'static str = $label;
+
+            type PublicKey = $public;
+            type SecretKey = $secret;
+            type Signature = $signature;
+
+            fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+                let (public, secret) = Self::instance()?.keypair().map_err(backend($label))?;
+                Ok((<$public>::from_slice(public.as_ref())?, <$secret>::from_vec(secret.into_vec())?))
+            }
+
+            fn sign(message: &[u8], secret_key: &Self::SecretKey) -> Result<Self::Signature, SchemeError> {
+                let sig = Self::instance()?;
+                let secret = sig
+                    .secret_key_from_bytes(secret_key.expose_secret())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                let signature = sig.sign(message, secret).map_err(backend($label))?;
+                Ok(<$signature>::from_slice(signature.as_ref())?)
+            }
+
+            fn verify(message: &[u8], signature: &Self::Signature, public_key: &Self::PublicKey) -> Result<(), SchemeError> {
+                let sig = Self::instance()?;
+                let public = sig
+                    .public_key_from_bytes(public_key.as_bytes())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                let signature = sig
+                    .signature_from_bytes(signature.as_bytes())
+                    .ok_or(PqBytesError::InvalidKey { kind: $label })?;
+                sig.verify(message, signature, public).map_err(|_| SchemeError::VerificationFailed)
+            }
+        }
+    };
+}
+
+oqs_kem!(
+    /// Kyber768 (round 3) from liboqs
+    Kyber768, Kyber768, "Kyber768",
+    KyberPublicKeyBytes, KyberSecretKeyBytes, KyberCiphertextBytes
+);
+oqs_kem!(
+    /// ML-KEM-768 (FIPS 203) from liboqs
+    MlKem768, MlKem768, "ML-KEM-768",
+    MlKemPublicKeyBytes, MlKemSecretKeyBytes, MlKemCiphertextBytes
+);
+oqs_signature!(
+    /// Dilithium3 (round 3) from liboqs
+    Dilithium3, Dilithium3, "Dilithium3",
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes
+);
+oqs_signature!(
+    /// ML-DSA-65 (FIPS 204) from liboqs
+    MlDsa65, MlDsa65, "ML-DSA-65",
+    MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes
+);
@@ -0,0 +1,112 @@
+//! PQClean backend via the pqcrypto crates
+
+use pqcrypto_traits::kem::{
+    Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _,
+};
+use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _, SecretKey as _};
+
+use super::{shared_secret, Kem, SchemeError, SharedSecret, SignatureScheme};
+use crate::{
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes, KyberCiphertextBytes,
+    KyberPublicKeyBytes, KyberSecretKeyBytes, MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes,
+    MlKemCiphertextBytes, MlKemPublicKeyBytes, MlKemSecretKeyBytes, PqBytesError,
+};
+
+macro_rules! pqcrypto_kem {
+    ($(#[$meta:meta])* $name:ident, $krate:ident :: $module:ident, $label:expr, $public:ty, $secret:ty, $ciphertext:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl Kem for $name {
+            const NAME: &'static str = $label;
+
+            type PublicKey = $public;
+            type SecretKey = $secret;
+            type Ciphertext = $ciphertext;
+
+            fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+                use $krate::$module as kem;
+                let (public, secret) = kem::keypair();
+                Ok((<$public>::from_slice(public.as_bytes())?, <$secret>::from_slice(secret.as_bytes())?))
+            }
+
+            fn encapsulate(public_key: &Self::PublicKey) -> Result<(SharedSecret, Self::Ciphertext), SchemeError> {
+                use $krate::$module as kem;
+                let public = kem::PublicKey::from_bytes(public_key.as_bytes())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                let (shared, ciphertext) = kem::encapsulate(&public);
+                Ok((shared_secret($label, shared.as_bytes())?, <$ciphertext>::from_slice(ciphertext.as_bytes())?))
+            }
+
+            fn decapsulate(ciphertext: &Self::Ciphertext, secret_key: &Self::SecretKey) -> Result<SharedSecret, SchemeError> {
+                use $krate::$module as kem;
+                let secret = kem::SecretKey::from_bytes(secret_key.expose_secret())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                let ciphertext = kem::Ciphertext::from_bytes(ciphertext.as_bytes())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                shared_secret($label, kem::decapsulate(&ciphertext, &secret).as_bytes())
+            }
+        }
+    };
+}
+
+macro_rules! pqcrypto_signature {
+    ($(#[$meta:meta])* $name:ident, $krate:ident :: $module:ident, $label:expr, $public:ty, $secret:ty, $signature:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl SignatureScheme for $name {
+            const NAME: &'static str = $label;
+
+            type PublicKey = $public;
+            type SecretKey = $secret;
+            type Signature = $signature;
+
+            fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+                use $krate::$module as sig;
+                let (public, secret) = sig::keypair();
+                Ok((<$public>::from_slice(public.as_bytes())?, <$secret>::from_slice(secret.as_bytes())?))
+            }
+
+            fn sign(message: &[u8], secret_key: &Self::SecretKey) -> Result<Self::Signature, SchemeError> {
+                use $krate::$module as sig;
+                let secret = sig::SecretKey::from_bytes(secret_key.expose_secret())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                Ok(<$signature>::from_slice(sig::detached_sign(message, &secret).as_bytes())?)
+            }
+
+            fn verify(message: &[u8], signature: &Self::Signature, public_key: &Self::PublicKey) -> Result<(), SchemeError> {
+                use $krate::$module as sig;
+                let public = sig::PublicKey::from_bytes(public_key.as_bytes())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                let signature = sig::DetachedSignature::from_bytes(signature.as_bytes())
+                    .map_err(|_| PqBytesError::InvalidKey { kind: $label })?;
+                sig::verify_detached_signature(&signature, message, &public)
+                    .map_err(|_| SchemeError::VerificationFailed)
+            }
+        }
+    };
+}
+
+pqcrypto_kem!(
+    /// Kyber768 (round 3) from PQClean
+    Kyber768, pqcrypto_kyber::kyber768, "Kyber768",
+    KyberPublicKeyBytes, KyberSecretKeyBytes, KyberCiphertextBytes
+);
+pqcrypto_kem!(
+    /// ML-KEM-768 (FIPS 203) from PQClean
+    MlKem768, pqcrypto_mlkem::mlkem768, "ML-KEM-768",
+    MlKemPublicKeyBytes, MlKemSecretKeyBytes, MlKemCiphertextBytes
+);
+pqcrypto_signature!(
+    /// Dilithium3 (round 3) from PQClean
+    Dilithium3, pqcrypto_dilithium::dilithium3, "Dilithium3",
+    DilithiumPublicKeyBytes, DilithiumSecretKeyBytes, DilithiumSignatureBytes
+);
+pqcrypto_signature!(
+    /// ML-DSA-65 (FIPS 204) from PQClean
+    MlDsa65, pqcrypto_mldsa::mldsa65, "ML-DSA-65",
+    MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes
+);
@@ -0,0 +1,100 @@
+//! Pure-Rust backend via RustCrypto's ml-kem and ml-dsa
+//!
+//! Only the FIPS 203/204 parameter sets are available here.
+
+use ml_dsa::signature::{Signer as _, Verifier as _};
+use ml_dsa::{EncodedSignature, EncodedSigningKey, EncodedVerifyingKey, KeyGen as _};
+use ml_kem::kem::{Decapsulate as _, Encapsulate as _};
+use ml_kem::{Ciphertext, Encoded, EncodedSizeUser as _, KemCore};
+use rand_core::OsRng;
+use zeroize::Zeroize;
+
+use super::{shared_secret, Kem, SchemeError, SharedSecret, SignatureScheme};
+use crate::{
+    MlDsaPublicKeyBytes, MlDsaSecretKeyBytes, MlDsaSignatureBytes, MlKemCiphertextBytes, MlKemPublicKeyBytes,
+    MlKemSecretKeyBytes, PqBytesError,
+};
+
+type EncapsulationKey = <ml_kem::MlKem768 as KemCore>::EncapsulationKey;
+type DecapsulationKey = <ml_kem::MlKem768 as KemCore>::DecapsulationKey;
+
+/// ML-KEM-768 (FIPS 203) from RustCrypto
+#[derive(Debug, Clone, Copy)]
+pub struct MlKem768;
+
+impl Kem for MlKem768 {
+    const NAME: &'static str = "ML-KEM-768";
+
+    type PublicKey = MlKemPublicKeyBytes;
+    type SecretKey = MlKemSecretKeyBytes;
+    type Ciphertext = MlKemCiphertextBytes;
+
+    fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+        let (secret, public) = ml_kem::MlKem768::generate(&mut OsRng);
+        let mut encoded = secret.as_bytes();
+        let secret = MlKemSecretKeyBytes::from_slice(&encoded);
+        encoded.as_mut_slice().zeroize();
+        Ok((MlKemPublicKeyBytes::from_slice(&public.as_bytes())?, secret?))
+    }
+
+    fn encapsulate(public_key: &Self::PublicKey) -> Result<(SharedSecret, Self::Ciphertext), SchemeError> {
+        let encoded = Encoded::<EncapsulationKey>::try_from(public_key.as_bytes())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let (ciphertext, shared) = EncapsulationKey::from_bytes(&encoded)
+            .encapsulate(&mut OsRng)
+            .map_err(|_| SchemeError::Backend { scheme: Self::NAME })?;
+        Ok((shared_secret(Self::NAME, &shared)?, MlKemCiphertextBytes::from_slice(&ciphertext)?))
+    }
+
+    fn decapsulate(ciphertext: &Self::Ciphertext, secret_key: &Self::SecretKey) -> Result<SharedSecret, SchemeError> {
+        let mut encoded = Encoded::<DecapsulationKey>::try_from(secret_key.expose_secret())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let secret = DecapsulationKey::from_bytes(&encoded);
+        encoded.as_mut_slice().zeroize();
+        let ciphertext = Ciphertext::<ml_kem::MlKem768>::try_from(ciphertext.as_bytes())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let shared = secret
+            .decapsulate(&ciphertext)
+            .map_err(|_| SchemeError::Backend { scheme: Self::NAME })?;
+        shared_secret(Self::NAME, &shared)
+    }
+}
+
+/// ML-DSA-65 (FIPS 204) from RustCrypto
+#[derive(Debug, Clone, Copy)]
+pub struct MlDsa65;
+
+impl SignatureScheme for MlDsa65 {
+    const NAME: &'static str = "ML-DSA-65";
+
+    type PublicKey = MlDsaPublicKeyBytes;
+    type SecretKey = MlDsaSecretKeyBytes;
+    type Signature = MlDsaSignatureBytes;
+
+    fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+        let keypair = ml_dsa::MlDsa65::key_gen(&mut OsRng);
+        let mut encoded = keypair.signing_key().encode();
+        let secret = MlDsaSecretKeyBytes::from_slice(&encoded);
+        encoded.as_mut_slice().zeroize();
+        Ok((MlDsaPublicKeyBytes::from_slice(&keypair.verifying_key().encode())?, secret?))
+    }
+
+    fn sign(message: &[u8], secret_key: &Self::SecretKey) -> Result<Self::Signature, SchemeError> {
+        let mut encoded = EncodedSigningKey::<ml_dsa::MlDsa65>::try_from(secret_key.expose_secret())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let secret = ml_dsa::SigningKey::<ml_dsa::MlDsa65>::decode(&encoded);
+        encoded.as_mut_slice().zeroize();
+        Ok(MlDsaSignatureBytes::from_slice(&secret.sign(message).encode())?)
+    }
+
+    fn verify(message: &[u8], signature: &Self::Signature, public_key: &Self::PublicKey) -> Result<(), SchemeError> {
+        let encoded = EncodedVerifyingKey::<ml_dsa::MlDsa65>::try_from(public_key.as_bytes())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let public = ml_dsa::VerifyingKey::<ml_dsa::MlDsa65>::decode(&encoded);
+        let encoded = EncodedSignature::<ml_dsa::MlDsa65>::try_from(signature.as_bytes())
+            .map_err(|_| PqBytesError::InvalidKey { kind: Self::NAME })?;
+        let signature = ml_dsa::Signature::<ml_dsa::MlDsa65>::decode(&encoded)
+            .ok_or(PqBytesError::InvalidKey { kind: Self::NAME })?;
+        public.verify(message, &signature).map_err(|_| SchemeError::VerificationFailed)
+    }
+}
@@ -0,0 +1,871 @@
+//! Transparency Log Verification
+//!
+//! Every published model, rule pack and patch is logged by its hash in an
+//! append-only Merkle tree in the manner of Certificate Transparency
+//! (RFC 9162 tree hashing and proofs, over SHA3-256). The log signs tree
+//! heads naming its size and root with its post-quantum key. Before
+//! trusting an artifact a client checks an [`ArtifactProof`]: the logged
+//! [`LogEntry`] names the artifact's digest, the [`InclusionProof`] places
+//! the entry under the root of the [`SignedTreeHead`], and the tree head
+//! signature verifies under the log key.
+//!
+//! Inclusion alone does not stop a log from showing one client a tree the
+//! rest of the fleet never sees. A [`LogWitness`] keeps the newest tree
+//! head a client accepted and takes another only with a
+//! [`ConsistencyProof`] that one tree extends the other, so a targeted
+//! view is refused as soon as it diverges from what the client saw before.
+//! [`MerkleTree`] computes roots and proofs on the log side.
+//!
+//! Components that load artifacts from disk name a [`LogRequirement`] in
+//! their configuration; with one set, an artifact whose shipped proof is
+//! missing, malformed or fails to verify is refused.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+
+use crate::decode::{self, DecodeLimits, Validate};
+use crate::scheme::{SchemeError, SignatureScheme};
+use crate::{PqBytesError, PqSignatureBytes};
+
+/// Most hashes in one proof; enough for a tree of 2^64 entries
+pub const MAX_PROOF_HASHES: usize = 64;
+
+/// Longest log identifier or artifact name
+pub const MAX_LOG_NAME: usize = 256;
+
+/// Domain tag of entry leaves
+const ENTRY_DOMAIN: &[u8] = b"ARK-TRANSPARENCY-ENTRY-V1";
+
+/// Domain tag of signed tree heads
+const TREE_HEAD_DOMAIN: &[u8] = b"ARK-TRANSPARENCY-TREE-HEAD-V1";
+
+/// Prefix of leaf hashes (RFC 9162 section 2.1.1)
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Prefix of interior node hashes
+const NODE_PREFIX: u8 = 0x01;
+
+/// SHA3-256 hash in the log: a leaf, a node, a root or an artifact digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LogHash([u8; 32]);
+
+impl LogHash {
+    /// Wrap hash bytes
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// SHA3-256 of an artifact's contents
+    pub fn digest(contents: &[u8]) -> Self {
+        Self(Sha3_256::digest(contents).into())
+    }
+
+    /// Hash bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for LogHash {
+    /// 64 lowercase hex digits
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LogHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(format!("hash must be 64 hex digits, got {:?}", s));
+        }
+        let mut bytes = [0u8; 32];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * index..2 * index + 2], 16).map_err(|_| format!("hash is not hex: {:?}", s))?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for LogHash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for LogHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Hash of a leaf holding `data`
+pub fn leaf_hash(data: &[u8]) -> LogHash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(data);
+    LogHash(hasher.finalize().into())
+}
+
+/// Root of the empty tree
+pub fn empty_root() -> LogHash {
+    LogHash(Sha3_256::digest([]).into())
+}
+
+fn node_hash(left: &LogHash, right: &LogHash) -> LogHash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left.0);
+    hasher.update(right.0);
+    LogHash(hasher.finalize().into())
+}
+
+/// Transparency verification failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransparencyError {
+    /// Malformed proof, entry or tree head
+    Invalid(String),
+    /// The logged entry is for different contents
+    DigestMismatch {
+        /// Digest of the artifact at hand
+        artifact: LogHash,
+        /// Digest the log entry names
+        logged: LogHash,
+    },
+    /// The inclusion proof does not lead to the tree head's root
+    NotIncluded,
+    /// Two tree heads do not describe one append-only log
+    Inconsistent {
+        /// Size of the tree head already trusted
+        trusted: u64,
+        /// Size of the tree head presented
+        presented: u64,
+    },
+    /// The tree head is signed by a different log or algorithm
+    WrongLog(String),
+    /// The tree head signature does not verify
+    Signature(SchemeError),
+}
+
+impl fmt::Display for TransparencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransparencyError::Invalid(reason) => write!(f, "Invalid transparency proof: {}", reason),
+            TransparencyError::DigestMismatch { artifact, logged } => {
+                write!(f, "Artifact digest {} does not match logged digest {}", artifact, logged)
+            }
+            TransparencyError::NotIncluded => write!(f, "Entry is not included in the signed tree"),
+            TransparencyError::Inconsistent { trusted, presented } => {
+                write!(f, "Tree of size {} is not consistent with the trusted tree of size {}", presented, trusted)
+            }
+            TransparencyError::WrongLog(reason) => write!(f, "Tree head from the wrong log: {}", reason),
+            TransparencyError::Signature(e) => write!(f, "Tree head signature: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TransparencyError {}
+
+impl From<SchemeError> for TransparencyError {
+    fn from(e: SchemeError) -> Self {
+        TransparencyError::Signature(e)
+    }
+}
+
+impl From<PqBytesError> for TransparencyError {
+    fn from(e: PqBytesError) -> Self {
+        TransparencyError::Signature(SchemeError::Bytes(e))
+    }
+}
+
+/// Kind of logged artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Cold-Mirror model weights or manifest
+    Model,
+    /// Ethics rule pack
+    RulePack,
+    /// Orchestrator patch payload
+    Patch,
+}
+
+impl ArtifactKind {
+    /// Name in entries and logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::Model => "model",
+            ArtifactKind::RulePack => "rule_pack",
+            ArtifactKind::Patch => "patch",
+        }
+    }
+}
+
+/// One published artifact in the log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Kind of artifact
+    pub kind: ArtifactKind,
+    /// Artifact name, such as a patch ID or rule pack name
+    pub name: String,
+    /// Artifact version
+    pub version: String,
+    /// SHA3-256 of the artifact's contents
+    pub digest: LogHash,
+}
+
+impl LogEntry {
+    /// Leaf hash of the entry: its fields length-prefixed under a domain tag
+    pub fn leaf_hash(&self) -> LogHash {
+        let mut data = Vec::with_capacity(ENTRY_DOMAIN.len() + 64 + self.name.len() + self.version.len());
+        data.extend_from_slice(ENTRY_DOMAIN);
+        for field in [self.kind.as_str().as_bytes(), self.name.as_bytes(), self.version.as_bytes()] {
+            data.extend_from_slice(&(field.len() as u64).to_le_bytes());
+            data.extend_from_slice(field);
+        }
+        data.extend_from_slice(&self.digest.0);
+        leaf_hash(&data)
+    }
+}
+
+impl Validate for LogEntry {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("name", &self.name, MAX_LOG_NAME)?;
+        decode::check_len("version", &self.version, MAX_LOG_NAME)
+    }
+}
+
+/// Size and root of the log at one time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeHead {
+    /// Log identifier
+    pub log_id: String,
+    /// Number of entries
+    pub size: u64,
+    /// Root of the tree of the first `size` entries
+    pub root: LogHash,
+    /// Signing time (seconds since the Unix epoch)
+    pub timestamp: u64,
+}
+
+impl TreeHead {
+    /// Bytes the log signs
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(TREE_HEAD_DOMAIN.len() + self.log_id.len() + 56);
+        bytes.extend_from_slice(TREE_HEAD_DOMAIN);
+        bytes.extend_from_slice(&(self.log_id.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(self.log_id.as_bytes());
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&self.root.0);
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes
+    }
+}
+
+impl Validate for TreeHead {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("log_id", &self.log_id, MAX_LOG_NAME)
+    }
+}
+
+/// Tree head signed by the log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    /// Signed tree head
+    pub head: TreeHead,
+    /// Signature scheme name, such as `Dilithium3`
+    pub algorithm: String,
+    /// Signature over [`TreeHead::signing_bytes`]
+    pub signature: PqSignatureBytes,
+}
+
+impl SignedTreeHead {
+    /// Sign `head` as the log
+    pub fn sign<S: SignatureScheme>(head: TreeHead, secret_key: &S::SecretKey) -> Result<Self, TransparencyError> {
+        let signature = S::sign(&head.signing_bytes(), secret_key)?;
+        Ok(Self { head, algorithm: S::NAME.to_string(), signature: PqSignatureBytes::from_slice(signature.as_ref())? })
+    }
+
+    /// Check the signature under the log's `public_key`
+    pub fn verify<S: SignatureScheme>(&self, public_key: &S::PublicKey) -> Result<(), TransparencyError>
+    where
+        S::Signature: TryFrom<Vec<u8>, Error = PqBytesError>,
+    {
+        if self.algorithm != S::NAME {
+            return Err(TransparencyError::WrongLog(format!("signed with {}, expected {}", self.algorithm, S::NAME)));
+        }
+        let signature = S::Signature::try_from(self.signature.as_bytes().to_vec())?;
+        S::verify(&self.head.signing_bytes(), &signature, public_key)?;
+        Ok(())
+    }
+}
+
+impl Validate for SignedTreeHead {
+    fn validate(&self) -> Result<(), String> {
+        self.head.validate()?;
+        decode::check_len("algorithm", &self.algorithm, MAX_LOG_NAME)
+    }
+}
+
+/// Audit path of one leaf in a tree (RFC 9162 section 2.1.3)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// Zero-based index of the leaf
+    pub leaf_index: u64,
+    /// Size of the tree the path is for
+    pub tree_size: u64,
+    /// Sibling hashes from the leaf up
+    pub path: Vec<LogHash>,
+}
+
+impl InclusionProof {
+    /// Check that `leaf` is at `leaf_index` of the tree with `root`
+    pub fn verify(&self, leaf: &LogHash, root: &LogHash) -> Result<(), TransparencyError> {
+        if self.leaf_index >= self.tree_size {
+            return Err(TransparencyError::NotIncluded);
+        }
+        let (mut index, mut last) = (self.leaf_index, self.tree_size - 1);
+        let mut hash = *leaf;
+        for sibling in &self.path {
+            if last == 0 {
+                return Err(TransparencyError::NotIncluded);
+            }
+            if index & 1 == 1 || index == last {
+                hash = node_hash(sibling, &hash);
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                hash = node_hash(&hash, sibling);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+        if last == 0 && hash == *root {
+            Ok(())
+        } else {
+            Err(TransparencyError::NotIncluded)
+        }
+    }
+}
+
+impl Validate for InclusionProof {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("path", self.path.len(), MAX_PROOF_HASHES)
+    }
+}
+
+/// Proof that a tree extends an older one (RFC 9162 section 2.1.4)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    /// Size of the older tree
+    pub old_size: u64,
+    /// Size of the newer tree
+    pub new_size: u64,
+    /// Subtree hashes linking the two roots
+    pub path: Vec<LogHash>,
+}
+
+impl ConsistencyProof {
+    /// Check that the tree with `new_root` appends to the one with `old_root`
+    pub fn verify(&self, old_root: &LogHash, new_root: &LogHash) -> Result<(), TransparencyError> {
+        let inconsistent = TransparencyError::Inconsistent { trusted: self.old_size, presented: self.new_size };
+        let (old, new) = (self.old_size, self.new_size);
+        if old > new {
+            return Err(inconsistent);
+        }
+        if old == new || old == 0 {
+            let roots_agree = if old == 0 { *old_root == empty_root() } else { old_root == new_root };
+            return if self.path.is_empty() && roots_agree { Ok(()) } else { Err(inconsistent) };
+        }
+
+        let mut proof = Vec::with_capacity(self.path.len() + 1);
+        if old.is_power_of_two() {
+            proof.push(*old_root);
+        }
+        proof.extend_from_slice(&self.path);
+        let Some((first, rest)) = proof.split_first() else {
+            return Err(inconsistent);
+        };
+
+        let (mut index, mut last) = (old - 1, new - 1);
+        while index & 1 == 1 {
+            index >>= 1;
+            last >>= 1;
+        }
+        let (mut old_hash, mut new_hash) = (*first, *first);
+        for hash in rest {
+            if last == 0 {
+                return Err(inconsistent);
+            }
+            if index & 1 == 1 || index == last {
+                old_hash = node_hash(hash, &old_hash);
+                new_hash = node_hash(hash, &new_hash);
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                new_hash = node_hash(&new_hash, hash);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+        if last == 0 && old_hash == *old_root && new_hash == *new_root {
+            Ok(())
+        } else {
+            Err(inconsistent)
+        }
+    }
+}
+
+impl Validate for ConsistencyProof {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_count("path", self.path.len(), MAX_PROOF_HASHES)
+    }
+}
+
+/// What a client needs to trust one artifact, shipped alongside it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactProof {
+    /// Logged entry of the artifact
+    pub entry: LogEntry,
+    /// Path from the entry to the tree head's root
+    pub inclusion: InclusionProof,
+    /// Tree head the entry is included under
+    pub tree_head: SignedTreeHead,
+}
+
+impl ArtifactProof {
+    /// Decode a proof file
+    pub fn decode(bytes: &[u8]) -> Result<Self, TransparencyError> {
+        decode::json_validated(bytes, &DecodeLimits::FILE).map_err(|e| TransparencyError::Invalid(e.to_string()))
+    }
+
+    /// Check that the artifact with `digest` is logged under a tree head
+    /// signed with `log_key`, returning that tree head
+    ///
+    /// The caller then passes the tree head to its [`LogWitness`].
+    pub fn verify<S: SignatureScheme>(&self, digest: &LogHash, log_key: &S::PublicKey) -> Result<&TreeHead, TransparencyError>
+    where
+        S::Signature: TryFrom<Vec<u8>, Error = PqBytesError>,
+    {
+        if self.entry.digest != *digest {
+            return Err(TransparencyError::DigestMismatch { artifact: *digest, logged: self.entry.digest });
+        }
+        let head = &self.tree_head.head;
+        if self.inclusion.tree_size != head.size {
+            return Err(TransparencyError::Invalid(format!(
+                "inclusion proof is for a tree of size {}, tree head has size {}",
+                self.inclusion.tree_size, head.size
+            )));
+        }
+        self.tree_head.verify::<S>(log_key)?;
+        self.inclusion.verify(&self.entry.leaf_hash(), &head.root)?;
+        Ok(head)
+    }
+}
+
+/// Log an artifact loaded from disk must be proven in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRequirement {
+    /// Identifier the log signs its tree heads with
+    pub log_id: String,
+    /// Public key of the log (hex)
+    pub log_public_key: String,
+    /// Artifact proof shipped alongside the artifact
+    pub proof: PathBuf,
+}
+
+impl Validate for LogRequirement {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("log_id", &self.log_id, MAX_LOG_NAME)?;
+        // Hex keys: post-quantum public keys are under 4 KiB
+        decode::check_len("log_public_key", &self.log_public_key, 8192)
+    }
+}
+
+impl LogRequirement {
+    /// Load a requirement from a JSON file
+    pub fn load(path: &Path) -> Result<Self, TransparencyError> {
+        let bytes = std::fs::read(path).map_err(|e| TransparencyError::Invalid(format!("{}: {}", path.display(), e)))?;
+        decode::json_validated(&bytes, &DecodeLimits::FILE)
+            .map_err(|e| TransparencyError::Invalid(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Check that `contents` are the logged `kind` named `name`, returning the logged entry
+    ///
+    /// Every failure - no proof file, an undecodable one, an entry for
+    /// another artifact, a tree head from another log - is an error, so
+    /// callers refuse the artifact on any of them.
+    pub fn verify<S: SignatureScheme>(&self, kind: ArtifactKind, name: &str, contents: &[u8]) -> Result<LogEntry, TransparencyError>
+    where
+        S::PublicKey: TryFrom<Vec<u8>>,
+        S::Signature: TryFrom<Vec<u8>, Error = PqBytesError>,
+    {
+        let log_key = decode_hex(&self.log_public_key)
+            .and_then(|key| S::PublicKey::try_from(key).ok())
+            .ok_or_else(|| TransparencyError::Invalid(format!("log public key is not a hex {} key", S::NAME)))?;
+        let bytes = std::fs::read(&self.proof)
+            .map_err(|e| TransparencyError::Invalid(format!("{}: {}", self.proof.display(), e)))?;
+        let proof = ArtifactProof::decode(&bytes)?;
+        if proof.entry.kind != kind || proof.entry.name != name {
+            return Err(TransparencyError::Invalid(format!(
+                "log entry is {} {}, not {} {}",
+                proof.entry.kind.as_str(), proof.entry.name, kind.as_str(), name
+            )));
+        }
+        let head = proof.verify::<S>(&LogHash::digest(contents), &log_key)?;
+        if head.log_id != self.log_id {
+            return Err(TransparencyError::WrongLog(format!("tree head of {}, expected {}", head.log_id, self.log_id)));
+        }
+        Ok(proof.entry)
+    }
+}
+
+/// Bytes of an even-length hex string
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len()).step_by(2).map(|index| u8::from_str_radix(&s[index..index + 2], 16).ok()).collect()
+}
+
+impl Validate for ArtifactProof {
+    fn validate(&self) -> Result<(), String> {
+        self.entry.validate()?;
+        self.inclusion.validate()?;
+        self.tree_head.validate()
+    }
+}
+
+/// Newest tree head a client has accepted from one log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogWitness {
+    /// Log followed
+    pub log_id: String,
+    /// Newest accepted tree head
+    pub latest: Option<TreeHead>,
+}
+
+impl LogWitness {
+    /// Witness that has seen nothing of `log_id` yet
+    pub fn new(log_id: &str) -> Self {
+        Self { log_id: log_id.to_string(), latest: None }
+    }
+
+    /// Load the witness file at `path`; a missing file has seen nothing of `log_id`
+    pub fn load(path: &Path, log_id: &str) -> std::io::Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => decode::json_validated(&bytes, &DecodeLimits::FILE)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new(log_id)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the witness to `path`
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let staged = path.with_extension("tmp");
+        std::fs::write(&staged, json)?;
+        std::fs::rename(&staged, path)
+    }
+
+    /// Accept `head`, already signature-checked, if it describes the same log
+    ///
+    /// A head of the size already trusted must have the same root. Any
+    /// other size needs `consistency` between the two trees, oldest first;
+    /// a newer head then becomes the trusted one. Returns whether it did.
+    pub fn observe(&mut self, head: &TreeHead, consistency: Option<&ConsistencyProof>) -> Result<bool, TransparencyError> {
+        if head.log_id != self.log_id {
+            return Err(TransparencyError::WrongLog(format!("tree head of {}, following {}", head.log_id, self.log_id)));
+        }
+        let Some(latest) = &self.latest else {
+            self.latest = Some(head.clone());
+            return Ok(true);
+        };
+        if head.size == latest.size {
+            return match head.root == latest.root {
+                true => Ok(false),
+                false => Err(TransparencyError::Inconsistent { trusted: latest.size, presented: head.size }),
+            };
+        }
+
+        let (old, new) = if head.size < latest.size { (head, latest) } else { (latest, head) };
+        let proof = consistency.ok_or_else(|| {
+            TransparencyError::Invalid(format!("a consistency proof from size {} to {} is required", old.size, new.size))
+        })?;
+        if proof.old_size != old.size || proof.new_size != new.size {
+            return Err(TransparencyError::Invalid(format!(
+                "consistency proof is from size {} to {}, needed {} to {}",
+                proof.old_size, proof.new_size, old.size, new.size
+            )));
+        }
+        proof
+            .verify(&old.root, &new.root)
+            .map_err(|_| TransparencyError::Inconsistent { trusted: latest.size, presented: head.size })?;
+        if head.size > latest.size {
+            self.latest = Some(head.clone());
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl Validate for LogWitness {
+    fn validate(&self) -> Result<(), String> {
+        decode::check_identifier("log_id", &self.log_id, MAX_LOG_NAME)?;
+        self.latest.as_ref().map_or(Ok(()), Validate::validate)
+    }
+}
+
+/// Leaf hashes of a log, for computing roots and proofs
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTree {
+    leaves: Vec<LogHash>,
+}
+
+impl MerkleTree {
+    /// Empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `entry`, returning its index
+    pub fn push(&mut self, entry: &LogEntry) -> u64 {
+        self.leaves.push(entry.leaf_hash());
+        self.leaves.len() as u64 - 1
+    }
+
+    /// Number of leaves
+    pub fn len(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// Whether no entry has been appended
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Root of the whole tree
+    pub fn root(&self) -> LogHash {
+        subtree_root(&self.leaves)
+    }
+
+    /// Root of the tree of the first `size` leaves
+    pub fn root_at(&self, size: u64) -> Option<LogHash> {
+        self.leaves.get(..usize::try_from(size).ok()?).map(subtree_root)
+    }
+
+    /// Inclusion proof of leaf `index` in the tree of the first `size` leaves
+    pub fn inclusion_proof(&self, index: u64, size: u64) -> Option<InclusionProof> {
+        let leaves = self.leaves.get(..usize::try_from(size).ok()?)?;
+        let position = usize::try_from(index).ok().filter(|position| *position < leaves.len())?;
+        Some(InclusionProof { leaf_index: index, tree_size: size, path: audit_path(position, leaves) })
+    }
+
+    /// Consistency proof between the trees of the first `old_size` and `new_size` leaves
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Option<ConsistencyProof> {
+        let leaves = self.leaves.get(..usize::try_from(new_size).ok()?)?;
+        let old = usize::try_from(old_size).ok().filter(|old| *old <= leaves.len())?;
+        let path = if old == 0 || old == leaves.len() { Vec::new() } else { subproof(old, leaves, true) };
+        Some(ConsistencyProof { old_size, new_size, path })
+    }
+}
+
+/// Largest power of two below `n`, for `n >= 2`
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn subtree_root(leaves: &[LogHash]) -> LogHash {
+    match leaves.len() {
+        0 => empty_root(),
+        1 => leaves[0],
+        n => {
+            let k = split(n);
+            node_hash(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+        }
+    }
+}
+
+fn audit_path(index: usize, leaves: &[LogHash]) -> Vec<LogHash> {
+    if leaves.len() <= 1 {
+        return Vec::new();
+    }
+    let k = split(leaves.len());
+    let (mut path, sibling) = if index < k {
+        (audit_path(index, &leaves[..k]), subtree_root(&leaves[k..]))
+    } else {
+        (audit_path(index - k, &leaves[k..]), subtree_root(&leaves[..k]))
+    };
+    path.push(sibling);
+    path
+}
+
+fn subproof(old: usize, leaves: &[LogHash], complete: bool) -> Vec<LogHash> {
+    if old == leaves.len() {
+        return if complete { Vec::new() } else { alloc::vec![subtree_root(leaves)] };
+    }
+    let k = split(leaves.len());
+    let (mut path, sibling) = if old <= k {
+        (subproof(old, &leaves[..k], complete), subtree_root(&leaves[k..]))
+    } else {
+        (subproof(old - k, &leaves[k..], false), subtree_root(&leaves[..k]))
+    };
+    path.push(sibling);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sizes, DilithiumSignatureBytes};
+
+    /// Keyed-hash stand-in for a signature scheme; the key signs and verifies
+    struct TestScheme;
+
+    impl SignatureScheme for TestScheme {
+        const NAME: &'static str = "Test";
+        type PublicKey = Vec<u8>;
+        type SecretKey = Vec<u8>;
+        type Signature = DilithiumSignatureBytes;
+
+        fn keypair() -> Result<(Self::PublicKey, Self::SecretKey), SchemeError> {
+            Ok((alloc::vec![7; 32], alloc::vec![7; 32]))
+        }
+
+        fn sign(message: &[u8], secret_key: &Self::SecretKey) -> Result<Self::Signature, SchemeError> {
+            let tag: [u8; 32] = Sha3_256::new().chain_update(secret_key).chain_update(message).finalize().into();
+            let bytes = tag.iter().copied().cycle().take(sizes::DILITHIUM3_SIGNATURE).collect();
+            Ok(DilithiumSignatureBytes::from_vec(bytes)?)
+        }
+
+        fn verify(message: &[u8], signature: &Self::Signature, public_key: &Self::PublicKey) -> Result<(), SchemeError> {
+            match Self::sign(message, public_key)? == *signature {
+                true => Ok(()),
+                false => Err(SchemeError::VerificationFailed),
+            }
+        }
+    }
+
+    fn entry(n: u8) -> LogEntry {
+        LogEntry { kind: ArtifactKind::Patch, name: format!("patch-{}", n), version: "1".into(), digest: LogHash::digest(&[n]) }
+    }
+
+    fn tree(size: u8) -> MerkleTree {
+        let mut tree = MerkleTree::new();
+        for n in 0..size {
+            tree.push(&entry(n));
+        }
+        tree
+    }
+
+    #[test]
+    fn test_proofs_verify_for_every_leaf_and_prefix() {
+        let tree = tree(17);
+        assert_eq!(tree.root_at(0), Some(empty_root()));
+        for size in 1..=tree.len() {
+            let root = tree.root_at(size).unwrap();
+            for index in 0..size {
+                let proof = tree.inclusion_proof(index, size).unwrap();
+                let leaf = entry(index as u8).leaf_hash();
+                assert_eq!(proof.verify(&leaf, &root), Ok(()), "leaf {} of {}", index, size);
+                assert!(proof.verify(&entry(99).leaf_hash(), &root).is_err());
+                let moved = InclusionProof { leaf_index: (index + 1) % size, ..proof.clone() };
+                assert!(size == 1 || moved.verify(&leaf, &root).is_err());
+            }
+            for old in 0..=size {
+                let proof = tree.consistency_proof(old, size).unwrap();
+                let old_root = tree.root_at(old).unwrap();
+                assert_eq!(proof.verify(&old_root, &root), Ok(()), "{} to {}", old, size);
+                if old > 0 && old < size {
+                    assert!(proof.verify(&entry(99).leaf_hash(), &root).is_err());
+                    assert!(proof.verify(&old_root, &old_root).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_artifact_proof_and_witness_refuse_forked_views() {
+        let (public, secret) = TestScheme::keypair().unwrap();
+        let honest = tree(6);
+        let head = |tree: &MerkleTree, size: u64| {
+            let head = TreeHead { log_id: "ark-log".into(), size, root: tree.root_at(size).unwrap(), timestamp: 1_700_000_000 + size };
+            SignedTreeHead::sign::<TestScheme>(head, &secret).unwrap()
+        };
+
+        let proof = ArtifactProof { entry: entry(4), inclusion: honest.inclusion_proof(4, 6).unwrap(), tree_head: head(&honest, 6) };
+        let proof = ArtifactProof::decode(&serde_json::to_vec(&proof).unwrap()).unwrap();
+        let accepted = proof.verify::<TestScheme>(&LogHash::digest(&[4]), &public).unwrap().clone();
+        assert!(matches!(proof.verify::<TestScheme>(&LogHash::digest(&[5]), &public), Err(TransparencyError::DigestMismatch { .. })));
+        assert!(matches!(proof.verify::<TestScheme>(&LogHash::digest(&[4]), &alloc::vec![8; 32]), Err(TransparencyError::Signature(_))));
+
+        let mut witness = LogWitness::new("ark-log");
+        assert_eq!(witness.observe(&head(&honest, 4).head, None), Ok(true));
+        assert!(witness.observe(&accepted, None).is_err());
+        assert_eq!(witness.observe(&accepted, honest.consistency_proof(4, 6).as_ref()), Ok(true));
+        assert_eq!(witness.observe(&head(&honest, 5).head, honest.consistency_proof(5, 6).as_ref()), Ok(false));
+
+        // A view of the log with entry 4 replaced, shown to this client alone
+        let mut forked = tree(4);
+        forked.push(&entry(40));
+        forked.push(&entry(5));
+        assert!(matches!(witness.observe(&head(&forked, 6).head, None), Err(TransparencyError::Inconsistent { .. })));
+        let mut grown = forked.clone();
+        grown.push(&entry(6));
+        let fork = witness.observe(&head(&grown, 7).head, grown.consistency_proof(6, 7).as_ref());
+        assert!(matches!(fork, Err(TransparencyError::Inconsistent { .. })));
+        assert_eq!(witness.latest, Some(accepted));
+    }
+
+    #[test]
+    fn test_log_requirement_refuses_artifacts_without_a_matching_proof() {
+        let (public, secret) = TestScheme::keypair().unwrap();
+        let log = tree(6);
+        let head = TreeHead { log_id: "ark-log".into(), size: 6, root: log.root_at(6).unwrap(), timestamp: 1_700_000_000 };
+        let proof = ArtifactProof { entry: entry(3), inclusion: log.inclusion_proof(3, 6).unwrap(), tree_head: SignedTreeHead::sign::<TestScheme>(head, &secret).unwrap() };
+
+        let dir = std::env::temp_dir().join(format!("ark-log-requirement-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let requirement = LogRequirement {
+            log_id: "ark-log".into(),
+            log_public_key: public.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            proof: dir.join("patch-3.proof.json"),
+        };
+        let verify = |requirement: &LogRequirement, kind, name: &str, contents: &[u8]| requirement.verify::<TestScheme>(kind, name, contents);
+
+        // No proof shipped
+        assert!(matches!(verify(&requirement, ArtifactKind::Patch, "patch-3", &[3]), Err(TransparencyError::Invalid(_))));
+
+        std::fs::write(&requirement.proof, serde_json::to_vec(&proof).unwrap()).unwrap();
+        assert_eq!(verify(&requirement, ArtifactKind::Patch, "patch-3", &[3]).unwrap(), entry(3));
+        assert!(matches!(verify(&requirement, ArtifactKind::Patch, "patch-3", &[4]), Err(TransparencyError::DigestMismatch { .. })));
+        assert!(matches!(verify(&requirement, ArtifactKind::RulePack, "patch-3", &[3]), Err(TransparencyError::Invalid(_))));
+        assert!(matches!(verify(&requirement, ArtifactKind::Patch, "patch-4", &[3]), Err(TransparencyError::Invalid(_))));
+        let other_log = LogRequirement { log_id: "other-log".into(), ..requirement.clone() };
+        assert!(matches!(verify(&other_log, ArtifactKind::Patch, "patch-3", &[3]), Err(TransparencyError::WrongLog(_))));
+        let bad_key = LogRequirement { log_public_key: "not hex".into(), ..requirement.clone() };
+        assert!(matches!(verify(&bad_key, ArtifactKind::Patch, "patch-3", &[3]), Err(TransparencyError::Invalid(_))));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}